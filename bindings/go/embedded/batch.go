@@ -0,0 +1,82 @@
+package kstone
+
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+import "encoding/json"
+
+// BatchGet reads several items in one call. The items are in key order,
+// nil where an item is missing. Unlike TransactGet the reads don't
+// share a snapshot.
+func (db *Database) BatchGet(keys ...Key) ([]Item, error) {
+	return db.getMany(keys, func(h *C.struct_KsDatabase, keys *C.char, out **C.char) C.enum_KsError {
+		return C.ks_database_batch_get(h, keys, out)
+	})
+}
+
+type batchWriteJSON struct {
+	Put    *batchPutJSON `json:"put,omitempty"`
+	Delete *keyJSON      `json:"delete,omitempty"`
+}
+
+type batchPutJSON struct {
+	keyJSON
+	Item Item `json:"item"`
+}
+
+// BatchWrite is a set of puts and deletes applied in one call, built by
+// chaining:
+//
+//	n, err := db.BatchWrite(kstone.NewBatchWrite().
+//		Put("user#1", kstone.Item{"name": "Ada"}).
+//		Delete("user#2"))
+//
+// Unlike a transaction, batch operations take no conditions. Only
+// WithSortKey applies to them.
+type BatchWrite struct {
+	writes []batchWriteJSON
+}
+
+// NewBatchWrite starts an empty batch.
+func NewBatchWrite() *BatchWrite {
+	return &BatchWrite{}
+}
+
+// Put stores item under pk.
+func (b *BatchWrite) Put(pk string, item Item, opts ...WriteOption) *BatchWrite {
+	o := applyOptions(opts)
+	b.writes = append(b.writes, batchWriteJSON{Put: &batchPutJSON{keyJSON{PK: pk, SK: o.sk}, item}})
+	return b
+}
+
+// Delete removes the item under pk.
+func (b *BatchWrite) Delete(pk string, opts ...WriteOption) *BatchWrite {
+	o := applyOptions(opts)
+	b.writes = append(b.writes, batchWriteJSON{Delete: &keyJSON{PK: pk, SK: o.sk}})
+	return b
+}
+
+// Len returns the number of operations.
+func (b *BatchWrite) Len() int {
+	return len(b.writes)
+}
+
+// BatchWrite applies b and returns the number of operations applied.
+func (db *Database) BatchWrite(b *BatchWrite) (int, error) {
+	writes := b.writes
+	if writes == nil {
+		writes = []batchWriteJSON{}
+	}
+	data, err := json.Marshal(writes)
+	if err != nil {
+		return 0, &Error{Code: CodeInvalidArgument, Message: err.Error()}
+	}
+	cWrites := C.CString(string(data))
+	defer free(cWrites)
+	var processed C.uintptr_t
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError { return C.ks_database_batch_write(h, cWrites, &processed) })
+	})
+	return int(processed), err
+}
@@ -0,0 +1,83 @@
+package kstone
+
+// #include "keystone.h"
+import "C"
+
+import (
+	"errors"
+	"fmt"
+)
+
+// Code identifies the kind of failure, mirroring the C FFI's KsError.
+type Code int
+
+const (
+	CodeNullPointer            = Code(C.KS_ERROR_NULL_POINTER)
+	CodeInvalidUTF8            = Code(C.KS_ERROR_INVALID_UTF8)
+	CodeInvalidArgument        = Code(C.KS_ERROR_INVALID_ARGUMENT)
+	CodeNotFound               = Code(C.KS_ERROR_NOT_FOUND)
+	CodeIO                     = Code(C.KS_ERROR_IO)
+	CodeInternal               = Code(C.KS_ERROR_INTERNAL)
+	CodeConditionalCheckFailed = Code(C.KS_ERROR_CONDITIONAL_CHECK_FAILED)
+	CodeTransactionCanceled    = Code(C.KS_ERROR_TRANSACTION_CANCELED)
+)
+
+func (c Code) String() string {
+	switch c {
+	case CodeNullPointer:
+		return "null pointer"
+	case CodeInvalidUTF8:
+		return "invalid UTF-8"
+	case CodeInvalidArgument:
+		return "invalid argument"
+	case CodeNotFound:
+		return "not found"
+	case CodeIO:
+		return "I/O error"
+	case CodeInternal:
+		return "internal error"
+	case CodeConditionalCheckFailed:
+		return "conditional check failed"
+	case CodeTransactionCanceled:
+		return "transaction canceled"
+	}
+	return fmt.Sprintf("error %d", int(c))
+}
+
+// Error is returned by every failing call. Compare it against the
+// sentinel values with errors.Is, or read Code with errors.As:
+//
+//	if errors.Is(err, kstone.ErrConditionalCheckFailed) { ... }
+type Error struct {
+	Code    Code
+	Message string
+}
+
+func (e *Error) Error() string {
+	if e.Message == "" {
+		return "kstone: " + e.Code.String()
+	}
+	return "kstone: " + e.Message
+}
+
+// Is reports whether target is an *Error with the same code, so the
+// sentinels match errors carrying any message.
+func (e *Error) Is(target error) bool {
+	t, ok := target.(*Error)
+	return ok && t.Code == e.Code
+}
+
+// Sentinel errors, one per Code.
+var (
+	ErrInvalidArgument        = &Error{Code: CodeInvalidArgument}
+	ErrNotFound               = &Error{Code: CodeNotFound}
+	ErrIO                     = &Error{Code: CodeIO}
+	ErrInternal               = &Error{Code: CodeInternal}
+	ErrConditionalCheckFailed = &Error{Code: CodeConditionalCheckFailed}
+	ErrTransactionCanceled    = &Error{Code: CodeTransactionCanceled}
+	ErrInvalidUTF8            = &Error{Code: CodeInvalidUTF8}
+	ErrNullPointer            = &Error{Code: CodeNullPointer}
+)
+
+// ErrClosed is returned by calls on a closed Database.
+var ErrClosed = errors.New("kstone: database is closed")
@@ -0,0 +1,3 @@
+module github.com/keystone-db/keystonedb/bindings/go/embedded
+
+go 1.21
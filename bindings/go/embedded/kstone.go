@@ -0,0 +1,222 @@
+// Package kstone is the embedded KeystoneDB binding for Go.
+//
+// It runs the database in-process through the C FFI (the kstone-ffi
+// crate), so the shared library and its header must be on the cgo paths:
+//
+//	cargo build --release -p kstone-ffi
+//	export CGO_LDFLAGS="-L$(pwd)/target/release"
+//	export CGO_CFLAGS="-I$(pwd)/c-ffi/include"
+//
+// Items are Item maps in the JSON shape of the FFI: strings, numbers,
+// booleans, nil, slices and nested maps. Numbers come back as
+// json.Number so integers keep their precision.
+//
+// A Database is safe for concurrent use by multiple goroutines.
+package kstone
+
+// #cgo LDFLAGS: -lkstone_ffi
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+import (
+	"bytes"
+	"encoding/json"
+	"runtime"
+	"sync"
+	"unsafe"
+)
+
+// Item is one database item: attribute names to values.
+type Item map[string]any
+
+// Key identifies an item. An empty SK means the item has no sort key.
+type Key struct {
+	PK string
+	SK string
+}
+
+// Database is an open KeystoneDB database.
+type Database struct {
+	mu     sync.RWMutex
+	handle *C.struct_KsDatabase
+}
+
+// call runs an FFI call and turns its status into an error. The
+// message lives in thread-local storage on the Rust side, so the
+// goroutine stays on one OS thread until it has been read.
+func call(f func() C.enum_KsError) error {
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	status := f()
+	if status == C.KS_ERROR_OK {
+		return nil
+	}
+	return &Error{Code: Code(status), Message: C.GoString(C.ks_last_error())}
+}
+
+// cString converts s for C; free it with C.free. An empty s gives NULL
+// when nullIfEmpty is set, for optional arguments such as sort keys.
+func cString(s string, nullIfEmpty bool) *C.char {
+	if s == "" && nullIfEmpty {
+		return nil
+	}
+	return C.CString(s)
+}
+
+func free(s *C.char) {
+	if s != nil {
+		C.free(unsafe.Pointer(s))
+	}
+}
+
+// toJSON encodes v for the FFI; nil (or an empty map) gives NULL.
+func toJSON(v any) (*C.char, error) {
+	if v == nil {
+		return nil, nil
+	}
+	if m, ok := v.(Values); ok && len(m) == 0 {
+		return nil, nil
+	}
+	data, err := json.Marshal(v)
+	if err != nil {
+		return nil, &Error{Code: CodeInvalidArgument, Message: err.Error()}
+	}
+	return C.CString(string(data)), nil
+}
+
+// fromJSON decodes a string returned by the FFI into v and frees it.
+func fromJSON(s *C.char, v any) error {
+	defer C.ks_string_free(s)
+	decoder := json.NewDecoder(bytes.NewReader([]byte(C.GoString(s))))
+	decoder.UseNumber()
+	return decoder.Decode(v)
+}
+
+// takeItem converts an item returned by the FFI and frees it.
+func takeItem(item *C.struct_KsItem) (Item, error) {
+	defer C.ks_item_free(item)
+	var out *C.char
+	if err := call(func() C.enum_KsError { return C.ks_item_to_json(item, &out) }); err != nil {
+		return nil, err
+	}
+	var result Item
+	return result, fromJSON(out, &result)
+}
+
+func open(f func(**C.struct_KsDatabase) C.enum_KsError) (*Database, error) {
+	var handle *C.struct_KsDatabase
+	if err := call(func() C.enum_KsError { return f(&handle) }); err != nil {
+		return nil, err
+	}
+	return &Database{handle: handle}, nil
+}
+
+// Create creates a new database at path; it fails if one exists.
+func Create(path string) (*Database, error) {
+	cPath := C.CString(path)
+	defer free(cPath)
+	return open(func(out **C.struct_KsDatabase) C.enum_KsError { return C.ks_database_create(cPath, out) })
+}
+
+// Open opens the existing database at path.
+func Open(path string) (*Database, error) {
+	cPath := C.CString(path)
+	defer free(cPath)
+	return open(func(out **C.struct_KsDatabase) C.enum_KsError { return C.ks_database_open(cPath, out) })
+}
+
+// CreateInMemory creates a database that lives only in memory.
+func CreateInMemory() (*Database, error) {
+	return open(func(out **C.struct_KsDatabase) C.enum_KsError { return C.ks_database_create_in_memory(out) })
+}
+
+// with runs f with the open handle, holding a read lock so Close waits
+// for calls in flight.
+func (db *Database) with(f func(*C.struct_KsDatabase) error) error {
+	db.mu.RLock()
+	defer db.mu.RUnlock()
+	if db.handle == nil {
+		return ErrClosed
+	}
+	return f(db.handle)
+}
+
+// Close flushes and closes the database. Closing twice is harmless.
+func (db *Database) Close() error {
+	db.mu.Lock()
+	defer db.mu.Unlock()
+	if db.handle == nil {
+		return nil
+	}
+	err := call(func() C.enum_KsError { return C.ks_database_flush(db.handle) })
+	C.ks_database_close(db.handle)
+	db.handle = nil
+	return err
+}
+
+// Flush writes buffered changes to disk.
+func (db *Database) Flush() error {
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError { return C.ks_database_flush(h) })
+	})
+}
+
+// Put stores an item with one string attribute.
+func (db *Database) Put(pk, attrName, value string) error {
+	return db.PutWithSK(pk, "", attrName, value)
+}
+
+// PutWithSK is Put for an item with a sort key.
+func (db *Database) PutWithSK(pk, sk, attrName, value string) error {
+	cPK, cSK, cName, cValue := cString(pk, false), cString(sk, true), C.CString(attrName), C.CString(value)
+	defer free(cPK)
+	defer free(cSK)
+	defer free(cName)
+	defer free(cValue)
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError { return C.ks_database_put_string(h, cPK, cSK, cName, cValue) })
+	})
+}
+
+// Get returns the item stored under pk, or ErrNotFound.
+func (db *Database) Get(pk string) (Item, error) {
+	return db.GetWithSK(pk, "")
+}
+
+// GetWithSK is Get for an item with a sort key.
+func (db *Database) GetWithSK(pk, sk string) (Item, error) {
+	cPK, cSK := cString(pk, false), cString(sk, true)
+	defer free(cPK)
+	defer free(cSK)
+	var result Item
+	err := db.with(func(h *C.struct_KsDatabase) error {
+		var item *C.struct_KsItem
+		if err := call(func() C.enum_KsError { return C.ks_database_get(h, cPK, cSK, &item) }); err != nil {
+			return err
+		}
+		var err error
+		result, err = takeItem(item)
+		return err
+	})
+	if e, ok := err.(*Error); ok && e.Code == CodeNotFound {
+		return nil, ErrNotFound
+	}
+	return result, err
+}
+
+// Delete removes the item stored under pk; deleting a missing item
+// succeeds.
+func (db *Database) Delete(pk string) error {
+	return db.DeleteWithSK(pk, "")
+}
+
+// DeleteWithSK is Delete for an item with a sort key.
+func (db *Database) DeleteWithSK(pk, sk string) error {
+	cPK, cSK := cString(pk, false), cString(sk, true)
+	defer free(cPK)
+	defer free(cSK)
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError { return C.ks_database_delete(h, cPK, cSK) })
+	})
+}
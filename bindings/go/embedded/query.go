@@ -0,0 +1,347 @@
+package kstone
+
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+// Page is one page of a query or scan.
+type Page struct {
+	Items []Item
+	// ScannedCount is the number of items examined, including those the
+	// filter dropped.
+	ScannedCount int
+	// LastKey is set when more items may follow: pass it to StartAfter to
+	// fetch the next page. A page may carry a LastKey even when nothing
+	// follows; the next page is then empty and has none.
+	LastKey *Key
+	// Warnings from the engine, such as a scan too large for one call.
+	Warnings []string
+}
+
+type pageJSON struct {
+	Items        []Item `json:"items"`
+	ScannedCount int    `json:"scanned_count"`
+	LastKey      *struct {
+		PK string  `json:"pk"`
+		SK *string `json:"sk"`
+	} `json:"last_key"`
+	Warnings []string `json:"warnings"`
+}
+
+func (p pageJSON) page() *Page {
+	page := &Page{Items: p.Items, ScannedCount: p.ScannedCount, Warnings: p.Warnings}
+	if p.LastKey != nil {
+		page.LastKey = &Key{PK: p.LastKey.PK}
+		if p.LastKey.SK != nil {
+			page.LastKey.SK = *p.LastKey.SK
+		}
+	}
+	return page
+}
+
+// takePage converts a result returned by the FFI and frees it.
+func takePage(result *C.struct_KsResult) (*Page, error) {
+	defer C.ks_result_free(result)
+	var out *C.char
+	if err := call(func() C.enum_KsError { return C.ks_result_to_json(result, &out) }); err != nil {
+		return nil, err
+	}
+	var page pageJSON
+	if err := fromJSON(out, &page); err != nil {
+		return nil, err
+	}
+	return page.page(), nil
+}
+
+type filter struct {
+	expression string
+	values     Values
+}
+
+func (f *filter) set(expression string, values []Values) {
+	f.expression = expression
+	f.values = Values{}
+	for _, v := range values {
+		for k, value := range v {
+			f.values[k] = value
+		}
+	}
+}
+
+type skCondition struct {
+	op        string
+	low, high string
+}
+
+// Query reads one partition, built by chaining:
+//
+//	q := kstone.NewQuery("org#acme").SKBeginsWith("user#").Limit(100)
+//	for {
+//		page, err := db.Query(q)
+//		...
+//		if page.LastKey == nil {
+//			break
+//		}
+//		q.StartAfter(page.LastKey)
+//	}
+//
+// A Query holds only options, so it can be reused for page after page.
+type Query struct {
+	pk         string
+	sk         *skCondition
+	forward    bool
+	limit      int
+	index      string
+	filter     filter
+	startAfter *Key
+}
+
+// NewQuery starts a query of the partition pk.
+func NewQuery(pk string) *Query {
+	return &Query{pk: pk, forward: true}
+}
+
+func (q *Query) condition(op, low, high string) *Query {
+	q.sk = &skCondition{op: op, low: low, high: high}
+	return q
+}
+
+// SKEq keeps the item whose sort key equals sk. Each SK method replaces
+// the previous sort key condition.
+func (q *Query) SKEq(sk string) *Query { return q.condition("eq", sk, "") }
+
+// SKLt keeps items whose sort key sorts before sk.
+func (q *Query) SKLt(sk string) *Query { return q.condition("lt", sk, "") }
+
+// SKLte keeps items whose sort key sorts before or at sk.
+func (q *Query) SKLte(sk string) *Query { return q.condition("lte", sk, "") }
+
+// SKGt keeps items whose sort key sorts after sk.
+func (q *Query) SKGt(sk string) *Query { return q.condition("gt", sk, "") }
+
+// SKGte keeps items whose sort key sorts at or after sk.
+func (q *Query) SKGte(sk string) *Query { return q.condition("gte", sk, "") }
+
+// SKBetween keeps items whose sort key lies between low and high, inclusive.
+func (q *Query) SKBetween(low, high string) *Query { return q.condition("between", low, high) }
+
+// SKBeginsWith keeps items whose sort key starts with prefix.
+func (q *Query) SKBeginsWith(prefix string) *Query { return q.condition("begins_with", prefix, "") }
+
+// Forward sets the sort key order: ascending (the default) or descending.
+func (q *Query) Forward(forward bool) *Query {
+	q.forward = forward
+	return q
+}
+
+// Limit evaluates at most limit items per page; items the filter drops
+// count too, so a page can be short while more items follow.
+func (q *Query) Limit(limit int) *Query {
+	q.limit = limit
+	return q
+}
+
+// Index queries a secondary index instead of the table.
+func (q *Query) Index(name string) *Query {
+	q.index = name
+	return q
+}
+
+// Filter keeps only items matching expression, such as "age >= :min".
+func (q *Query) Filter(expression string, values ...Values) *Query {
+	q.filter.set(expression, values)
+	return q
+}
+
+// StartAfter continues after key, a page's LastKey; nil starts over.
+func (q *Query) StartAfter(key *Key) *Query {
+	q.startAfter = key
+	return q
+}
+
+// build creates the C query; the caller frees it with ks_query_free.
+func (q *Query) build(args *cArgs) (*C.struct_KsQuery, error) {
+	query := C.ks_query_new(args.add(cString(q.pk, false)))
+	err := call(func() C.enum_KsError {
+		status := C.ks_query_forward(query, C.bool(q.forward))
+		if status == C.KS_ERROR_OK && q.sk != nil {
+			low, high := args.add(C.CString(q.sk.low)), args.add(C.CString(q.sk.high))
+			switch q.sk.op {
+			case "eq":
+				status = C.ks_query_sk_eq(query, low)
+			case "lt":
+				status = C.ks_query_sk_lt(query, low)
+			case "lte":
+				status = C.ks_query_sk_lte(query, low)
+			case "gt":
+				status = C.ks_query_sk_gt(query, low)
+			case "gte":
+				status = C.ks_query_sk_gte(query, low)
+			case "between":
+				status = C.ks_query_sk_between(query, low, high)
+			case "begins_with":
+				status = C.ks_query_sk_begins_with(query, low)
+			}
+		}
+		if status == C.KS_ERROR_OK && q.limit > 0 {
+			status = C.ks_query_limit(query, C.uintptr_t(q.limit))
+		}
+		if status == C.KS_ERROR_OK && q.index != "" {
+			status = C.ks_query_index(query, args.add(C.CString(q.index)))
+		}
+		if status == C.KS_ERROR_OK && q.startAfter != nil {
+			status = C.ks_query_start_after(query, args.add(C.CString(q.startAfter.PK)),
+				args.add(cString(q.startAfter.SK, true)))
+		}
+		return status
+	})
+	if err == nil && q.filter.expression != "" {
+		err = applyFilter(args, q.filter, func(expression, values *C.char) C.enum_KsError {
+			return C.ks_query_filter(query, expression, values)
+		})
+	}
+	if err != nil {
+		C.ks_query_free(query)
+		return nil, err
+	}
+	return query, nil
+}
+
+func applyFilter(args *cArgs, f filter, set func(expression, values *C.char) C.enum_KsError) error {
+	values, err := toJSON(f.values)
+	if err != nil {
+		return err
+	}
+	expression := args.add(C.CString(f.expression))
+	args.add(values)
+	return call(func() C.enum_KsError { return set(expression, values) })
+}
+
+// Query fetches one page of q.
+func (db *Database) Query(q *Query) (*Page, error) {
+	var args cArgs
+	defer args.free()
+	query, err := q.build(&args)
+	if err != nil {
+		return nil, err
+	}
+	defer C.ks_query_free(query)
+	var page *Page
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		var result *C.struct_KsResult
+		if err := call(func() C.enum_KsError { return C.ks_database_query(h, query, &result) }); err != nil {
+			return err
+		}
+		var err error
+		page, err = takePage(result)
+		return err
+	})
+	return page, err
+}
+
+// Scan reads the whole table, built by chaining:
+//
+//	s := kstone.NewScan().PKPrefix("user#").Limit(100)
+//
+// It pages like Query.
+type Scan struct {
+	limit      int
+	pkPrefix   string
+	segment    int
+	total      int
+	filter     filter
+	startAfter *Key
+}
+
+// NewScan starts a scan of every item.
+func NewScan() *Scan {
+	return &Scan{}
+}
+
+// Limit evaluates at most limit items per page; items the filter drops
+// count too, so a page can be short while more items follow.
+func (s *Scan) Limit(limit int) *Scan {
+	s.limit = limit
+	return s
+}
+
+// PKPrefix keeps items whose partition key starts with prefix.
+func (s *Scan) PKPrefix(prefix string) *Scan {
+	s.pkPrefix = prefix
+	return s
+}
+
+// Segment scans one of total disjoint parts, for parallel workers.
+func (s *Scan) Segment(segment, total int) *Scan {
+	s.segment, s.total = segment, total
+	return s
+}
+
+// Filter keeps only items matching expression, such as "age >= :min".
+func (s *Scan) Filter(expression string, values ...Values) *Scan {
+	s.filter.set(expression, values)
+	return s
+}
+
+// StartAfter continues after key, a page's LastKey; nil starts over.
+func (s *Scan) StartAfter(key *Key) *Scan {
+	s.startAfter = key
+	return s
+}
+
+// build creates the C scan; the caller frees it with ks_scan_free.
+func (s *Scan) build(args *cArgs) (*C.struct_KsScan, error) {
+	if s.total > 0 && (s.segment < 0 || s.segment >= s.total) {
+		return nil, &Error{Code: CodeInvalidArgument, Message: "segment out of range"}
+	}
+	scan := C.ks_scan_new()
+	err := call(func() C.enum_KsError {
+		status := C.enum_KsError(C.KS_ERROR_OK)
+		if s.limit > 0 {
+			status = C.ks_scan_limit(scan, C.uintptr_t(s.limit))
+		}
+		if status == C.KS_ERROR_OK && s.pkPrefix != "" {
+			status = C.ks_scan_pk_prefix(scan, args.add(C.CString(s.pkPrefix)))
+		}
+		if status == C.KS_ERROR_OK && s.total > 0 {
+			status = C.ks_scan_segment(scan, C.uintptr_t(s.segment), C.uintptr_t(s.total))
+		}
+		if status == C.KS_ERROR_OK && s.startAfter != nil {
+			status = C.ks_scan_start_after(scan, args.add(C.CString(s.startAfter.PK)),
+				args.add(cString(s.startAfter.SK, true)))
+		}
+		return status
+	})
+	if err == nil && s.filter.expression != "" {
+		err = applyFilter(args, s.filter, func(expression, values *C.char) C.enum_KsError {
+			return C.ks_scan_filter(scan, expression, values)
+		})
+	}
+	if err != nil {
+		C.ks_scan_free(scan)
+		return nil, err
+	}
+	return scan, nil
+}
+
+// Scan fetches one page of s.
+func (db *Database) Scan(s *Scan) (*Page, error) {
+	var args cArgs
+	defer args.free()
+	scan, err := s.build(&args)
+	if err != nil {
+		return nil, err
+	}
+	defer C.ks_scan_free(scan)
+	var page *Page
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		var result *C.struct_KsResult
+		if err := call(func() C.enum_KsError { return C.ks_database_scan(h, scan, &result) }); err != nil {
+			return err
+		}
+		var err error
+		page, err = takePage(result)
+		return err
+	})
+	return page, err
+}
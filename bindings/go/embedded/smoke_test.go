@@ -0,0 +1,179 @@
+package kstone
+
+import (
+	"encoding/json"
+	"errors"
+	"path/filepath"
+	"testing"
+)
+
+func memoryDB(t *testing.T) *Database {
+	t.Helper()
+	db, err := CreateInMemory()
+	if err != nil {
+		t.Fatal(err)
+	}
+	t.Cleanup(func() { db.Close() })
+	return db
+}
+
+func TestPutGetDeleteAndReopen(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "smoke.keystone")
+	db, err := Create(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if err := db.Put("user#alice", "email", "alice@example.com"); err != nil {
+		t.Fatal(err)
+	}
+	if err := db.PutWithSK("org#acme", "user#alice", "role", "admin"); err != nil {
+		t.Fatal(err)
+	}
+	if err := db.Close(); err != nil {
+		t.Fatal(err)
+	}
+	if err := db.Put("user#bob", "email", "bob@example.com"); !errors.Is(err, ErrClosed) {
+		t.Fatalf("put after close: %v", err)
+	}
+
+	db, err = Open(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	defer db.Close()
+	item, err := db.Get("user#alice")
+	if err != nil || item["email"] != "alice@example.com" {
+		t.Fatalf("get: %v %v", item, err)
+	}
+	item, err = db.GetWithSK("org#acme", "user#alice")
+	if err != nil || item["role"] != "admin" {
+		t.Fatalf("get with sk: %v %v", item, err)
+	}
+	if err := db.Delete("user#alice"); err != nil {
+		t.Fatal(err)
+	}
+	if _, err := db.Get("user#alice"); err != ErrNotFound {
+		t.Fatalf("get deleted: %v", err)
+	}
+}
+
+func TestQueryAndScanPages(t *testing.T) {
+	db := memoryDB(t)
+	for i, sk := range []string{"user#1", "user#2", "user#3", "team#1"} {
+		if err := db.PutItem("org#acme", Item{"n": i}, WithSortKey(sk)); err != nil {
+			t.Fatal(err)
+		}
+	}
+
+	q := NewQuery("org#acme").SKBeginsWith("user#").Limit(2)
+	var seen []string
+	for {
+		page, err := db.Query(q)
+		if err != nil {
+			t.Fatal(err)
+		}
+		for _, item := range page.Items {
+			seen = append(seen, string(item["n"].(json.Number)))
+		}
+		if page.LastKey == nil {
+			break
+		}
+		q.StartAfter(page.LastKey)
+	}
+	if len(seen) != 3 || seen[0] != "0" || seen[2] != "2" {
+		t.Fatalf("query pages: %v", seen)
+	}
+
+	page, err := db.Query(NewQuery("org#acme").Filter("n >= :min", Values{":min": 2}))
+	if err != nil || len(page.Items) != 2 || page.ScannedCount != 4 {
+		t.Fatalf("query filter: %+v %v", page, err)
+	}
+
+	page, err = db.Scan(NewScan().Filter("#n < :max", Values{":max": 1, "#n": "n"}))
+	if err != nil || len(page.Items) != 1 {
+		t.Fatalf("scan filter: %+v %v", page, err)
+	}
+	if _, err := db.Scan(NewScan().Segment(2, 2)); !errors.Is(err, ErrInvalidArgument) {
+		t.Fatalf("bad segment: %v", err)
+	}
+}
+
+func TestConditionalWritesAndUpdate(t *testing.T) {
+	db := memoryDB(t)
+	if err := db.PutItem("user#bob", Item{"name": "Bob"}, WithCondition("attribute_not_exists(name)")); err != nil {
+		t.Fatal(err)
+	}
+	err := db.PutItem("user#bob", Item{"name": "Robert"}, WithCondition("attribute_not_exists(name)"))
+	var kerr *Error
+	if !errors.Is(err, ErrConditionalCheckFailed) || !errors.As(err, &kerr) || kerr.Message == "" {
+		t.Fatalf("conditional put: %v", err)
+	}
+
+	item, err := db.Update("user#bob", "SET visits = :one", WithValues(Values{":one": 1}))
+	if err != nil || item["visits"] != json.Number("1") {
+		t.Fatalf("update: %v %v", item, err)
+	}
+	if err := db.DeleteItem("user#bob", WithCondition("visits > :n"), WithValues(Values{":n": 5})); !errors.Is(err, ErrConditionalCheckFailed) {
+		t.Fatalf("conditional delete: %v", err)
+	}
+	if _, err := db.Update("user#bob", "SET ="); !errors.Is(err, ErrInvalidArgument) {
+		t.Fatalf("bad expression: %v", err)
+	}
+}
+
+func TestTransactionsAndBatches(t *testing.T) {
+	db := memoryDB(t)
+	n, err := db.BatchWrite(NewBatchWrite().
+		Put("account#a", Item{"balance": 100}).
+		Put("account#b", Item{"balance": 0}).
+		Put("org#acme", Item{"name": "Acme"}, WithSortKey("meta")).
+		Delete("account#c"))
+	if err != nil || n != 4 {
+		t.Fatalf("batch write: %d %v", n, err)
+	}
+
+	items, err := db.BatchGet(Key{PK: "org#acme", SK: "meta"}, Key{PK: "account#c"})
+	if err != nil || len(items) != 2 || items[0]["name"] != "Acme" || items[1] != nil {
+		t.Fatalf("batch get: %v %v", items, err)
+	}
+
+	transfer := func(amount int) error {
+		return db.TransactWrite(NewTransactWrite().
+			Update("account#a", "SET balance = balance - :amount",
+				WithCondition("balance >= :amount"), WithValues(Values{":amount": amount})).
+			Update("account#b", "SET balance = balance + :amount"))
+	}
+	if err := transfer(500); !errors.Is(err, ErrTransactionCanceled) {
+		t.Fatalf("overdraft: %v", err)
+	}
+	if err := transfer(30); err != nil {
+		t.Fatal(err)
+	}
+	items, err = db.TransactGet(Key{PK: "account#a"}, Key{PK: "account#b"})
+	if err != nil || items[0]["balance"] != json.Number("70") || items[1]["balance"] != json.Number("30") {
+		t.Fatalf("transact get: %v %v", items, err)
+	}
+}
+
+func TestExecuteStatement(t *testing.T) {
+	db := memoryDB(t)
+	res, err := db.ExecuteStatement("INSERT INTO items VALUE {'pk': ?, 'name': ?}", "stmt#1", "Ada")
+	if err != nil || res.Kind != StatementSuccess || res.Affected != 1 {
+		t.Fatalf("insert: %+v %v", res, err)
+	}
+	res, err = db.ExecuteStatement("SELECT * FROM items WHERE pk = 'stmt#1'")
+	if err != nil || res.Kind != StatementSelect || len(res.Items) != 1 || res.Items[0]["name"] != "Ada" {
+		t.Fatalf("select: %+v %v", res, err)
+	}
+	res, err = db.ExecuteStatementNamed("UPDATE items SET name = :name WHERE pk = 'stmt#1'", map[string]any{"name": "Grace"})
+	if err != nil || res.Kind != StatementItem || res.Item["name"] != "Grace" {
+		t.Fatalf("update: %+v %v", res, err)
+	}
+	res, err = db.ExecuteStatement("EXPLAIN SELECT * FROM items WHERE pk = 'stmt#1'")
+	if err != nil || res.Kind != StatementPlan || res.Plan == nil {
+		t.Fatalf("explain: %+v %v", res, err)
+	}
+	if _, err := db.ExecuteStatement("SELEC"); !errors.Is(err, ErrInvalidArgument) {
+		t.Fatalf("bad statement: %v", err)
+	}
+}
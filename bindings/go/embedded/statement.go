@@ -0,0 +1,87 @@
+package kstone
+
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+import "encoding/json"
+
+// StatementKind says which fields of a StatementResult are set.
+type StatementKind string
+
+const (
+	// StatementSelect: Items, ScannedCount and LastKey
+	StatementSelect StatementKind = "select"
+	// StatementItem (UPDATE): Item, the item after the update
+	StatementItem StatementKind = "item"
+	// StatementSuccess (INSERT, DELETE, INSERT ... SELECT): Success and
+	// Affected
+	StatementSuccess StatementKind = "success"
+	// StatementPlan (EXPLAIN): Plan
+	StatementPlan StatementKind = "plan"
+)
+
+// StatementResult is the outcome of a PartiQL statement.
+type StatementResult struct {
+	Kind         StatementKind  `json:"kind"`
+	Items        []Item         `json:"-"`
+	ScannedCount int            `json:"-"`
+	LastKey      *Key           `json:"-"`
+	Warnings     []string       `json:"-"`
+	Item         Item           `json:"item"`
+	Success      bool           `json:"success"`
+	Affected     int            `json:"affected"`
+	Plan         map[string]any `json:"plan"`
+}
+
+// ExecuteStatement runs a PartiQL statement. params bind its ?
+// placeholders in order:
+//
+//	res, err := db.ExecuteStatement("SELECT * FROM items WHERE pk = ?", "user#alice")
+func (db *Database) ExecuteStatement(sql string, params ...any) (*StatementResult, error) {
+	if params == nil {
+		params = []any{}
+	}
+	return db.executeStatement(sql, params)
+}
+
+// ExecuteStatementNamed runs a PartiQL statement whose :name
+// placeholders are bound by params (keys without the colon).
+func (db *Database) ExecuteStatementNamed(sql string, params map[string]any) (*StatementResult, error) {
+	if params == nil {
+		params = map[string]any{}
+	}
+	return db.executeStatement(sql, params)
+}
+
+func (db *Database) executeStatement(sql string, params any) (*StatementResult, error) {
+	data, err := json.Marshal(params)
+	if err != nil {
+		return nil, &Error{Code: CodeInvalidArgument, Message: err.Error()}
+	}
+	cSQL, cParams := C.CString(sql), C.CString(string(data))
+	defer free(cSQL)
+	defer free(cParams)
+	var result *StatementResult
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		var out *C.char
+		err := call(func() C.enum_KsError { return C.ks_database_execute_statement_json(h, cSQL, cParams, &out) })
+		if err != nil {
+			return err
+		}
+		var raw struct {
+			StatementResult
+			pageJSON
+		}
+		if err := fromJSON(out, &raw); err != nil {
+			return err
+		}
+		result = &raw.StatementResult
+		if raw.Kind == StatementSelect {
+			page := raw.pageJSON.page()
+			result.Items, result.ScannedCount, result.LastKey, result.Warnings = page.Items, page.ScannedCount, page.LastKey, page.Warnings
+		}
+		return nil
+	})
+	return result, err
+}
@@ -0,0 +1,165 @@
+package kstone
+
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+import "encoding/json"
+
+type transactOp struct {
+	kind       string
+	pk, sk     string
+	item       Item
+	expression string
+	condition  string
+}
+
+// TransactWrite is a set of writes applied all or nothing, built by
+// chaining:
+//
+//	tx := kstone.NewTransactWrite().
+//		Update("account#a", "SET balance = balance - :amount",
+//			kstone.WithCondition("balance >= :amount"), kstone.WithValues(kstone.Values{":amount": 50})).
+//		Update("account#b", "SET balance = balance + :amount")
+//	err := db.TransactWrite(tx) // errors.Is(err, kstone.ErrTransactionCanceled) if rolled back
+//
+// Placeholders are shared by every operation, so WithValues on any of
+// them binds for all.
+type TransactWrite struct {
+	ops    []transactOp
+	values Values
+}
+
+// NewTransactWrite starts an empty transaction.
+func NewTransactWrite() *TransactWrite {
+	return &TransactWrite{values: Values{}}
+}
+
+func (tx *TransactWrite) add(op transactOp, opts []WriteOption) *TransactWrite {
+	o := applyOptions(opts)
+	op.sk, op.condition = o.sk, o.condition
+	if tx.values == nil {
+		tx.values = Values{}
+	}
+	for k, v := range o.values {
+		tx.values[k] = v
+	}
+	tx.ops = append(tx.ops, op)
+	return tx
+}
+
+// Put stores item under pk.
+func (tx *TransactWrite) Put(pk string, item Item, opts ...WriteOption) *TransactWrite {
+	return tx.add(transactOp{kind: "put", pk: pk, item: item}, opts)
+}
+
+// Update applies an update expression to the item under pk.
+func (tx *TransactWrite) Update(pk, expression string, opts ...WriteOption) *TransactWrite {
+	return tx.add(transactOp{kind: "update", pk: pk, expression: expression}, opts)
+}
+
+// Delete removes the item under pk.
+func (tx *TransactWrite) Delete(pk string, opts ...WriteOption) *TransactWrite {
+	return tx.add(transactOp{kind: "delete", pk: pk}, opts)
+}
+
+// ConditionCheck cancels the transaction unless condition holds for the
+// item under pk, without writing it.
+func (tx *TransactWrite) ConditionCheck(pk, condition string, opts ...WriteOption) *TransactWrite {
+	return tx.add(transactOp{kind: "condition_check", pk: pk}, append(opts, WithCondition(condition)))
+}
+
+// Len returns the number of operations.
+func (tx *TransactWrite) Len() int {
+	return len(tx.ops)
+}
+
+// TransactWrite applies every operation of tx or none of them.
+func (db *Database) TransactWrite(tx *TransactWrite) error {
+	var args cArgs
+	defer args.free()
+	transaction := C.ks_transact_write_new()
+	defer C.ks_transact_write_free(transaction)
+	values, err := toJSON(tx.values)
+	if err != nil {
+		return err
+	}
+	args.add(values)
+	for _, op := range tx.ops {
+		var itemJSON *C.char
+		if op.kind == "put" {
+			if itemJSON, err = toJSON(op.item); err != nil {
+				return err
+			}
+			args.add(itemJSON)
+		}
+		pk, sk, condition := args.add(C.CString(op.pk)), args.add(cString(op.sk, true)), args.add(cString(op.condition, true))
+		expression := args.add(cString(op.expression, true))
+		err := call(func() C.enum_KsError {
+			switch op.kind {
+			case "put":
+				return C.ks_transact_write_put(transaction, pk, sk, itemJSON, condition)
+			case "update":
+				return C.ks_transact_write_update(transaction, pk, sk, expression, condition)
+			case "delete":
+				return C.ks_transact_write_delete(transaction, pk, sk, condition)
+			default:
+				return C.ks_transact_write_condition_check(transaction, pk, sk, condition)
+			}
+		})
+		if err != nil {
+			return err
+		}
+	}
+	if values != nil {
+		if err := call(func() C.enum_KsError { return C.ks_transact_write_values(transaction, values) }); err != nil {
+			return err
+		}
+	}
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError { return C.ks_database_transact_write(h, transaction) })
+	})
+}
+
+type keyJSON struct {
+	PK string `json:"pk"`
+	SK string `json:"sk,omitempty"`
+}
+
+func keysJSON(keys []Key) (*C.char, error) {
+	out := make([]keyJSON, len(keys))
+	for i, key := range keys {
+		out[i] = keyJSON{PK: key.PK, SK: key.SK}
+	}
+	data, err := json.Marshal(out)
+	if err != nil {
+		return nil, &Error{Code: CodeInvalidArgument, Message: err.Error()}
+	}
+	return C.CString(string(data)), nil
+}
+
+// getMany runs a multi-key read returning a JSON array aligned with keys.
+func (db *Database) getMany(keys []Key, get func(*C.struct_KsDatabase, *C.char, **C.char) C.enum_KsError) ([]Item, error) {
+	cKeys, err := keysJSON(keys)
+	if err != nil {
+		return nil, err
+	}
+	defer free(cKeys)
+	var items []Item
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		var out *C.char
+		if err := call(func() C.enum_KsError { return get(h, cKeys, &out) }); err != nil {
+			return err
+		}
+		return fromJSON(out, &items)
+	})
+	return items, err
+}
+
+// TransactGet reads keys from one consistent snapshot. The items are in
+// key order, nil where an item is missing.
+func (db *Database) TransactGet(keys ...Key) ([]Item, error) {
+	return db.getMany(keys, func(h *C.struct_KsDatabase, keys *C.char, out **C.char) C.enum_KsError {
+		return C.ks_database_transact_get(h, keys, out)
+	})
+}
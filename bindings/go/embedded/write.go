@@ -0,0 +1,141 @@
+package kstone
+
+// #include <stdlib.h>
+// #include "keystone.h"
+import "C"
+
+// Values binds expression placeholders: keys starting with ":" are
+// values, keys starting with "#" are attribute names.
+//
+//	kstone.Values{":min": 18, "#status": "status"}
+type Values map[string]any
+
+type writeOptions struct {
+	sk        string
+	condition string
+	values    Values
+}
+
+// WriteOption configures PutItem, DeleteItem, Update and the operations
+// of a transaction or batch.
+type WriteOption func(*writeOptions)
+
+// WithSortKey addresses the item with this sort key.
+func WithSortKey(sk string) WriteOption {
+	return func(o *writeOptions) { o.sk = sk }
+}
+
+// WithCondition only writes if the condition expression holds for the
+// stored item; otherwise the call fails with ErrConditionalCheckFailed.
+func WithCondition(expression string) WriteOption {
+	return func(o *writeOptions) { o.condition = expression }
+}
+
+// WithValues binds the placeholders of the condition and update
+// expressions. Repeated options add to the bindings.
+func WithValues(values Values) WriteOption {
+	return func(o *writeOptions) {
+		if o.values == nil {
+			o.values = Values{}
+		}
+		for k, v := range values {
+			o.values[k] = v
+		}
+	}
+}
+
+func applyOptions(opts []WriteOption) writeOptions {
+	var o writeOptions
+	for _, opt := range opts {
+		opt(&o)
+	}
+	return o
+}
+
+// cArgs holds C strings to free together.
+type cArgs []*C.char
+
+func (a *cArgs) add(s *C.char) *C.char {
+	*a = append(*a, s)
+	return s
+}
+
+func (a cArgs) free() {
+	for _, s := range a {
+		free(s)
+	}
+}
+
+// PutItem stores item under pk, replacing any existing item.
+func (db *Database) PutItem(pk string, item Item, opts ...WriteOption) error {
+	o := applyOptions(opts)
+	var args cArgs
+	defer args.free()
+	itemJSON, err := toJSON(item)
+	if err != nil {
+		return err
+	}
+	args.add(itemJSON)
+	values, err := toJSON(o.values)
+	if err != nil {
+		return err
+	}
+	cPK, cSK, cValues := args.add(cString(pk, false)), args.add(cString(o.sk, true)), args.add(values)
+	cCondition := args.add(cString(o.condition, true))
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError {
+			if cCondition == nil {
+				return C.ks_database_put(h, cPK, cSK, itemJSON)
+			}
+			return C.ks_database_put_conditional(h, cPK, cSK, itemJSON, cCondition, cValues)
+		})
+	})
+}
+
+// DeleteItem removes the item under pk.
+func (db *Database) DeleteItem(pk string, opts ...WriteOption) error {
+	o := applyOptions(opts)
+	var args cArgs
+	defer args.free()
+	values, err := toJSON(o.values)
+	if err != nil {
+		return err
+	}
+	cPK, cSK, cValues := args.add(cString(pk, false)), args.add(cString(o.sk, true)), args.add(values)
+	cCondition := args.add(cString(o.condition, true))
+	return db.with(func(h *C.struct_KsDatabase) error {
+		return call(func() C.enum_KsError {
+			if cCondition == nil {
+				return C.ks_database_delete(h, cPK, cSK)
+			}
+			return C.ks_database_delete_conditional(h, cPK, cSK, cCondition, cValues)
+		})
+	})
+}
+
+// Update applies an update expression such as "SET visits = visits + :one"
+// and returns the item as it is afterwards.
+func (db *Database) Update(pk, expression string, opts ...WriteOption) (Item, error) {
+	o := applyOptions(opts)
+	var args cArgs
+	defer args.free()
+	values, err := toJSON(o.values)
+	if err != nil {
+		return nil, err
+	}
+	cPK, cSK, cValues := args.add(cString(pk, false)), args.add(cString(o.sk, true)), args.add(values)
+	cExpression, cCondition := args.add(C.CString(expression)), args.add(cString(o.condition, true))
+	var result Item
+	err = db.with(func(h *C.struct_KsDatabase) error {
+		var item *C.struct_KsItem
+		err := call(func() C.enum_KsError {
+			return C.ks_database_update(h, cPK, cSK, cExpression, cCondition, cValues, &item)
+		})
+		if err != nil {
+			return err
+		}
+		result, err = takeItem(item)
+		return err
+	})
+	return result, err
+}
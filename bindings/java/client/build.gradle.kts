@@ -0,0 +1,93 @@
+import com.google.protobuf.gradle.id
+
+plugins {
+    `java-library`
+    id("com.google.protobuf") version "0.9.4"
+}
+
+group = "io.keystonedb"
+version = "0.1.0"
+
+val grpcVersion = "1.62.2"
+val protobufVersion = "3.25.3"
+
+java {
+    toolchain {
+        languageVersion.set(JavaLanguageVersion.of(11))
+    }
+    withSourcesJar()
+}
+
+repositories {
+    mavenCentral()
+}
+
+dependencies {
+    api("io.grpc:grpc-api:$grpcVersion")
+    implementation("io.grpc:grpc-protobuf:$grpcVersion")
+    implementation("io.grpc:grpc-stub:$grpcVersion")
+    implementation("com.google.protobuf:protobuf-java:$protobufVersion")
+    runtimeOnly("io.grpc:grpc-netty-shaded:$grpcVersion")
+    compileOnly("org.apache.tomcat:annotations-api:6.0.53")
+
+    testImplementation("io.grpc:grpc-inprocess:$grpcVersion")
+    testImplementation("org.junit.jupiter:junit-jupiter:5.10.2")
+    testRuntimeOnly("org.junit.platform:junit-platform-launcher")
+}
+
+// Generate the messages and service stub from the proto shared with the server
+sourceSets {
+    main {
+        proto {
+            srcDir("../../../kstone-proto/proto")
+        }
+    }
+}
+
+protobuf {
+    protoc {
+        artifact = "com.google.protobuf:protoc:$protobufVersion"
+    }
+    plugins {
+        id("grpc") {
+            artifact = "io.grpc:protoc-gen-grpc-java:$grpcVersion"
+        }
+    }
+    generateProtoTasks {
+        all().forEach { task ->
+            task.plugins {
+                id("grpc")
+            }
+        }
+    }
+}
+
+// The JNI library KeystoneDatabase loads, linked against the C FFI library that
+// `cargo build --release -p kstone-ffi` builds into the workspace target directory
+val repoRoot = projectDir.resolve("../../..").normalize()
+val ffiLibraryDir = repoRoot.resolve("target/release")
+val jniLibrary = layout.buildDirectory.file("jni/" + System.mapLibraryName("keystone_jni"))
+
+val buildJni by tasks.registering(Exec::class) {
+    description = "Builds the JNI library for KeystoneDatabase"
+    val javaHome = File(System.getProperty("java.home"))
+    val platform = if (System.getProperty("os.name").startsWith("Mac")) "darwin" else "linux"
+    val output = jniLibrary.get().asFile
+    inputs.file("src/main/c/keystone_jni.c")
+    inputs.file(repoRoot.resolve("c-ffi/include/keystone.h"))
+    outputs.file(output)
+    doFirst { output.parentFile.mkdirs() }
+    commandLine(
+        "cc", "-shared", "-fPIC", "-O2",
+        "-I$javaHome/include", "-I$javaHome/include/$platform", "-I${repoRoot.resolve("c-ffi/include")}",
+        "src/main/c/keystone_jni.c",
+        "-L$ffiLibraryDir", "-lkstone_ffi", "-Wl,-rpath,$ffiLibraryDir",
+        "-o", output.path,
+    )
+}
+
+tasks.test {
+    useJUnitPlatform()
+    dependsOn(buildJni)
+    systemProperty("keystonedb.jni.library", jniLibrary.get().asFile.path)
+}
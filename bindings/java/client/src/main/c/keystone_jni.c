@@ -0,0 +1,439 @@
+/*
+ * JNI glue between io.keystonedb.client.KeystoneDatabase and the C FFI
+ * (c-ffi/include/keystone.h).
+ *
+ * Strings arrive as UTF-8 byte arrays (null meaning absent) and JSON results
+ * go back the same way, so no text passes through JNI's modified UTF-8. A
+ * failing FFI call throws the exception KeystoneDatabase.error builds from
+ * its KsError code and ks_last_error().
+ *
+ * Build (Linux; use the darwin include directory and .dylib on macOS):
+ *
+ *   cc -shared -fPIC -I"$JAVA_HOME/include" -I"$JAVA_HOME/include/linux" \
+ *      -I../../../c-ffi/include src/main/c/keystone_jni.c \
+ *      -L../../../target/release -lkstone_ffi -o build/jni/libkeystone_jni.so
+ */
+
+#include <jni.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "keystone.h"
+
+#define MAX_ARGS 16
+
+/* C copies of the byte array arguments of one call, freed together */
+typedef struct {
+    char *strings[MAX_ARGS];
+    int count;
+    int failed;
+} Args;
+
+static void throw_error(JNIEnv *env, int code, const char *message) {
+    jclass database = (*env)->FindClass(env, "io/keystonedb/client/KeystoneDatabase");
+    if (database == NULL) {
+        return;
+    }
+    jmethodID error = (*env)->GetStaticMethodID(env, database, "error", "(I[B)Lio/keystonedb/client/KeystoneException;");
+    if (error == NULL) {
+        return;
+    }
+    jbyteArray text = NULL;
+    if (message != NULL) {
+        jsize length = (jsize)strlen(message);
+        text = (*env)->NewByteArray(env, length);
+        if (text == NULL) {
+            return;
+        }
+        (*env)->SetByteArrayRegion(env, text, 0, length, (const jbyte *)message);
+    }
+    jthrowable exception = (jthrowable)(*env)->CallStaticObjectMethod(env, database, error, (jint)code, text);
+    if (exception != NULL && !(*env)->ExceptionCheck(env)) {
+        (*env)->Throw(env, exception);
+    }
+}
+
+/* Whether the call succeeded; throws if it didn't */
+static int ok(JNIEnv *env, KsError status) {
+    if (status == KS_ERROR_OK) {
+        return 1;
+    }
+    throw_error(env, status, ks_last_error());
+    return 0;
+}
+
+/* A NUL-terminated copy of bytes, owned by args; NULL for a null array */
+static char *arg(JNIEnv *env, Args *args, jbyteArray bytes) {
+    if (bytes == NULL || args->failed) {
+        return NULL;
+    }
+    jsize length = (*env)->GetArrayLength(env, bytes);
+    char *copy = malloc((size_t)length + 1);
+    if (copy == NULL || args->count == MAX_ARGS) {
+        free(copy);
+        args->failed = 1;
+        throw_error(env, KS_ERROR_INTERNAL, "Out of memory copying arguments");
+        return NULL;
+    }
+    (*env)->GetByteArrayRegion(env, bytes, 0, length, (jbyte *)copy);
+    copy[length] = '\0';
+    args->strings[args->count++] = copy;
+    return copy;
+}
+
+static void free_args(Args *args) {
+    for (int i = 0; i < args->count; i++) {
+        free(args->strings[i]);
+    }
+    args->count = 0;
+}
+
+/* A byte array holding text returned by the FFI, which is freed */
+static jbyteArray take_string(JNIEnv *env, char *text) {
+    jsize length = (jsize)strlen(text);
+    jbyteArray bytes = (*env)->NewByteArray(env, length);
+    if (bytes != NULL) {
+        (*env)->SetByteArrayRegion(env, bytes, 0, length, (const jbyte *)text);
+    }
+    ks_string_free(text);
+    return bytes;
+}
+
+static jbyteArray take_item(JNIEnv *env, KsItem *item) {
+    char *json = NULL;
+    KsError status = ks_item_to_json(item, &json);
+    ks_item_free(item);
+    return ok(env, status) ? take_string(env, json) : NULL;
+}
+
+static jbyteArray take_result(JNIEnv *env, KsResult *result) {
+    char *json = NULL;
+    KsError status = ks_result_to_json(result, &json);
+    ks_result_free(result);
+    return ok(env, status) ? take_string(env, json) : NULL;
+}
+
+static KsDatabase *db_of(jlong db) {
+    return (KsDatabase *)(intptr_t)db;
+}
+
+JNIEXPORT jlong JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeOpen(
+    JNIEnv *env, jclass cls, jint mode, jbyteArray path) {
+    (void)cls;
+    Args args = {0};
+    char *c_path = arg(env, &args, path);
+    KsDatabase *db = NULL;
+    KsError status;
+    switch (mode) {
+    case 0:
+        status = ks_database_create(c_path, &db);
+        break;
+    case 1:
+        status = ks_database_open(c_path, &db);
+        break;
+    default:
+        status = ks_database_create_in_memory(&db);
+        break;
+    }
+    free_args(&args);
+    return !args.failed && ok(env, status) ? (jlong)(intptr_t)db : 0;
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeClose(JNIEnv *env, jclass cls, jlong db) {
+    (void)cls;
+    KsError status = ks_database_flush(db_of(db));
+    ks_database_close(db_of(db));
+    ok(env, status);
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeFlush(JNIEnv *env, jclass cls, jlong db) {
+    (void)cls;
+    ok(env, ks_database_flush(db_of(db)));
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativePut(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray pk, jbyteArray sk, jbyteArray item,
+    jbyteArray condition, jbyteArray values) {
+    (void)cls;
+    Args args = {0};
+    char *c_pk = arg(env, &args, pk), *c_sk = arg(env, &args, sk), *c_item = arg(env, &args, item);
+    char *c_condition = arg(env, &args, condition), *c_values = arg(env, &args, values);
+    if (!args.failed) {
+        ok(env, c_condition == NULL ? ks_database_put(db_of(db), c_pk, c_sk, c_item)
+                                    : ks_database_put_conditional(db_of(db), c_pk, c_sk, c_item, c_condition, c_values));
+    }
+    free_args(&args);
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeGet(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray pk, jbyteArray sk) {
+    (void)cls;
+    Args args = {0};
+    char *c_pk = arg(env, &args, pk), *c_sk = arg(env, &args, sk);
+    jbyteArray json = NULL;
+    if (!args.failed) {
+        KsItem *item = NULL;
+        KsError status = ks_database_get(db_of(db), c_pk, c_sk, &item);
+        /* A missing item is not an error here: Java gets null */
+        if (status != KS_ERROR_NOT_FOUND && ok(env, status)) {
+            json = take_item(env, item);
+        }
+    }
+    free_args(&args);
+    return json;
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeDelete(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray pk, jbyteArray sk, jbyteArray condition, jbyteArray values) {
+    (void)cls;
+    Args args = {0};
+    char *c_pk = arg(env, &args, pk), *c_sk = arg(env, &args, sk);
+    char *c_condition = arg(env, &args, condition), *c_values = arg(env, &args, values);
+    if (!args.failed) {
+        ok(env, c_condition == NULL ? ks_database_delete(db_of(db), c_pk, c_sk)
+                                    : ks_database_delete_conditional(db_of(db), c_pk, c_sk, c_condition, c_values));
+    }
+    free_args(&args);
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeUpdate(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray pk, jbyteArray sk, jbyteArray expression,
+    jbyteArray condition, jbyteArray values) {
+    (void)cls;
+    Args args = {0};
+    char *c_pk = arg(env, &args, pk), *c_sk = arg(env, &args, sk), *c_expression = arg(env, &args, expression);
+    char *c_condition = arg(env, &args, condition), *c_values = arg(env, &args, values);
+    jbyteArray json = NULL;
+    if (!args.failed) {
+        KsItem *item = NULL;
+        if (ok(env, ks_database_update(db_of(db), c_pk, c_sk, c_expression, c_condition, c_values, &item))) {
+            json = take_item(env, item);
+        }
+    }
+    free_args(&args);
+    return json;
+}
+
+/* Sets the sort key condition whose SortKeyCondition.Operator ordinal is operator */
+static KsError query_sk_condition(KsQuery *query, jint operator, const char *low, const char *high) {
+    switch (operator) {
+    case 0:
+        return ks_query_sk_eq(query, low);
+    case 1:
+        return ks_query_sk_lt(query, low);
+    case 2:
+        return ks_query_sk_lte(query, low);
+    case 3:
+        return ks_query_sk_gt(query, low);
+    case 4:
+        return ks_query_sk_gte(query, low);
+    case 5:
+        return ks_query_sk_between(query, low, high);
+    case 6:
+        return ks_query_sk_begins_with(query, low);
+    default:
+        return KS_ERROR_OK;
+    }
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeQuery(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray pk, jint operator, jbyteArray low, jbyteArray high,
+    jboolean forward, jint limit, jbyteArray index, jbyteArray filter, jbyteArray values,
+    jbyteArray start_pk, jbyteArray start_sk) {
+    (void)cls;
+    Args args = {0};
+    char *c_pk = arg(env, &args, pk), *c_low = arg(env, &args, low), *c_high = arg(env, &args, high);
+    char *c_index = arg(env, &args, index), *c_filter = arg(env, &args, filter), *c_values = arg(env, &args, values);
+    char *c_start_pk = arg(env, &args, start_pk), *c_start_sk = arg(env, &args, start_sk);
+    jbyteArray json = NULL;
+    KsQuery *query = args.failed ? NULL : ks_query_new(c_pk);
+    if (query != NULL) {
+        KsError status = ks_query_forward(query, forward == JNI_TRUE);
+        if (status == KS_ERROR_OK) {
+            status = query_sk_condition(query, operator, c_low, c_high);
+        }
+        if (status == KS_ERROR_OK && limit > 0) {
+            status = ks_query_limit(query, (uintptr_t)limit);
+        }
+        if (status == KS_ERROR_OK && c_index != NULL) {
+            status = ks_query_index(query, c_index);
+        }
+        if (status == KS_ERROR_OK && c_filter != NULL) {
+            status = ks_query_filter(query, c_filter, c_values);
+        }
+        if (status == KS_ERROR_OK && c_start_pk != NULL) {
+            status = ks_query_start_after(query, c_start_pk, c_start_sk);
+        }
+        KsResult *result = NULL;
+        if (status == KS_ERROR_OK) {
+            status = ks_database_query(db_of(db), query, &result);
+        }
+        if (ok(env, status)) {
+            json = take_result(env, result);
+        }
+        ks_query_free(query);
+    }
+    free_args(&args);
+    return json;
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeScan(
+    JNIEnv *env, jclass cls, jlong db, jint limit, jbyteArray pk_prefix, jint segment, jint total_segments,
+    jbyteArray filter, jbyteArray values, jbyteArray start_pk, jbyteArray start_sk) {
+    (void)cls;
+    Args args = {0};
+    char *c_prefix = arg(env, &args, pk_prefix), *c_filter = arg(env, &args, filter);
+    char *c_values = arg(env, &args, values);
+    char *c_start_pk = arg(env, &args, start_pk), *c_start_sk = arg(env, &args, start_sk);
+    jbyteArray json = NULL;
+    if (!args.failed) {
+        KsScan *scan = ks_scan_new();
+        KsError status = KS_ERROR_OK;
+        if (limit > 0) {
+            status = ks_scan_limit(scan, (uintptr_t)limit);
+        }
+        if (status == KS_ERROR_OK && c_prefix != NULL) {
+            status = ks_scan_pk_prefix(scan, c_prefix);
+        }
+        if (status == KS_ERROR_OK && total_segments > 0) {
+            status = segment < 0 ? KS_ERROR_INVALID_ARGUMENT
+                                 : ks_scan_segment(scan, (uintptr_t)segment, (uintptr_t)total_segments);
+        }
+        if (status == KS_ERROR_OK && c_filter != NULL) {
+            status = ks_scan_filter(scan, c_filter, c_values);
+        }
+        if (status == KS_ERROR_OK && c_start_pk != NULL) {
+            status = ks_scan_start_after(scan, c_start_pk, c_start_sk);
+        }
+        KsResult *result = NULL;
+        if (status == KS_ERROR_OK) {
+            status = ks_database_scan(db_of(db), scan, &result);
+        }
+        if (ok(env, status)) {
+            json = take_result(env, result);
+        }
+        ks_scan_free(scan);
+    }
+    free_args(&args);
+    return json;
+}
+
+typedef KsError (*KeysCall)(const KsDatabase *db, const char *keys_json, char **out_json);
+
+static jbyteArray get_many(JNIEnv *env, jlong db, jbyteArray keys, KeysCall call) {
+    Args args = {0};
+    char *c_keys = arg(env, &args, keys);
+    jbyteArray json = NULL;
+    if (!args.failed) {
+        char *out = NULL;
+        if (ok(env, call(db_of(db), c_keys, &out))) {
+            json = take_string(env, out);
+        }
+    }
+    free_args(&args);
+    return json;
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeBatchGet(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray keys) {
+    (void)cls;
+    return get_many(env, db, keys, ks_database_batch_get);
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeTransactGet(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray keys) {
+    (void)cls;
+    return get_many(env, db, keys, ks_database_transact_get);
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeBatchWrite(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray writes) {
+    (void)cls;
+    Args args = {0};
+    char *c_writes = arg(env, &args, writes);
+    if (!args.failed) {
+        ok(env, ks_database_batch_write(db_of(db), c_writes, NULL));
+    }
+    free_args(&args);
+}
+
+/* Adds write i of a transaction; kind is a TransactWriteItemsRequest.Write.Kind ordinal */
+static KsError transact_add(JNIEnv *env, KsTransactWrite *tx, jint kind, jobjectArray pks, jobjectArray sks,
+                            jobjectArray payloads, jobjectArray conditions, jsize i) {
+    Args args = {0};
+    char *pk = arg(env, &args, (jbyteArray)(*env)->GetObjectArrayElement(env, pks, i));
+    char *sk = arg(env, &args, (jbyteArray)(*env)->GetObjectArrayElement(env, sks, i));
+    char *payload = arg(env, &args, (jbyteArray)(*env)->GetObjectArrayElement(env, payloads, i));
+    char *condition = arg(env, &args, (jbyteArray)(*env)->GetObjectArrayElement(env, conditions, i));
+    KsError status = KS_ERROR_INTERNAL;
+    if (!args.failed) {
+        switch (kind) {
+        case 0:
+            status = ks_transact_write_put(tx, pk, sk, payload, condition);
+            break;
+        case 1:
+            status = ks_transact_write_update(tx, pk, sk, payload, condition);
+            break;
+        case 2:
+            status = ks_transact_write_delete(tx, pk, sk, condition);
+            break;
+        default:
+            status = ks_transact_write_condition_check(tx, pk, sk, condition);
+            break;
+        }
+    }
+    free_args(&args);
+    return status;
+}
+
+JNIEXPORT void JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeTransactWrite(
+    JNIEnv *env, jclass cls, jlong db, jintArray kinds, jobjectArray pks, jobjectArray sks,
+    jobjectArray payloads, jobjectArray conditions, jbyteArray values) {
+    (void)cls;
+    Args args = {0};
+    char *c_values = arg(env, &args, values);
+    if (args.failed) {
+        return;
+    }
+    KsTransactWrite *tx = ks_transact_write_new();
+    jsize count = (*env)->GetArrayLength(env, kinds);
+    jint *kind = (*env)->GetIntArrayElements(env, kinds, NULL);
+    KsError status = kind == NULL ? KS_ERROR_INTERNAL : KS_ERROR_OK;
+    for (jsize i = 0; status == KS_ERROR_OK && i < count; i++) {
+        status = transact_add(env, tx, kind[i], pks, sks, payloads, conditions, i);
+        if ((*env)->ExceptionCheck(env)) {
+            break;
+        }
+    }
+    if (kind != NULL) {
+        (*env)->ReleaseIntArrayElements(env, kinds, kind, JNI_ABORT);
+    }
+    if (!(*env)->ExceptionCheck(env)) {
+        if (status == KS_ERROR_OK && c_values != NULL) {
+            status = ks_transact_write_values(tx, c_values);
+        }
+        if (status == KS_ERROR_OK) {
+            status = ks_database_transact_write(db_of(db), tx);
+        }
+        ok(env, status);
+    }
+    ks_transact_write_free(tx);
+    free_args(&args);
+}
+
+JNIEXPORT jbyteArray JNICALL Java_io_keystonedb_client_KeystoneDatabase_nativeExecuteStatement(
+    JNIEnv *env, jclass cls, jlong db, jbyteArray sql, jbyteArray params) {
+    (void)cls;
+    Args args = {0};
+    char *c_sql = arg(env, &args, sql), *c_params = arg(env, &args, params);
+    jbyteArray json = NULL;
+    if (!args.failed) {
+        char *out = NULL;
+        if (ok(env, ks_database_execute_statement_json(db_of(db), c_sql, c_params, &out))) {
+            json = take_string(env, out);
+        }
+    }
+    free_args(&args);
+    return json;
+}
@@ -0,0 +1,172 @@
+package io.keystonedb.client;
+
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.Objects;
+
+/**
+ * An attribute value, one of the KeystoneDB value types.
+ *
+ * <p>Modeled on the DynamoDB SDK's {@code AttributeValue}: build values with the
+ * {@code from*} factories and read them with the accessor for their {@link #type()}.
+ * Numbers are kept as strings so no precision is lost.
+ */
+public final class AttributeValue {
+    /** The type of an attribute value */
+    public enum Type {
+        S, N, B, BOOL, NUL, L, M, VECTOR, TIMESTAMP
+    }
+
+    private final Type type;
+    private final Object value;
+
+    private AttributeValue(Type type, Object value) {
+        this.type = type;
+        this.value = value;
+    }
+
+    public static AttributeValue fromS(String value) {
+        return new AttributeValue(Type.S, Objects.requireNonNull(value, "value"));
+    }
+
+    public static AttributeValue fromN(String value) {
+        return new AttributeValue(Type.N, Objects.requireNonNull(value, "value"));
+    }
+
+    public static AttributeValue fromN(long value) {
+        return fromN(Long.toString(value));
+    }
+
+    public static AttributeValue fromN(double value) {
+        return fromN(Double.toString(value));
+    }
+
+    public static AttributeValue fromB(byte[] value) {
+        return new AttributeValue(Type.B, Objects.requireNonNull(value, "value").clone());
+    }
+
+    public static AttributeValue fromBool(boolean value) {
+        return new AttributeValue(Type.BOOL, value);
+    }
+
+    public static AttributeValue nul() {
+        return new AttributeValue(Type.NUL, null);
+    }
+
+    public static AttributeValue fromL(List<AttributeValue> value) {
+        return new AttributeValue(Type.L, List.copyOf(value));
+    }
+
+    public static AttributeValue fromM(Map<String, AttributeValue> value) {
+        return new AttributeValue(Type.M, Collections.unmodifiableMap(new LinkedHashMap<>(value)));
+    }
+
+    public static AttributeValue fromVector(float[] value) {
+        return new AttributeValue(Type.VECTOR, Objects.requireNonNull(value, "value").clone());
+    }
+
+    /** A timestamp in milliseconds since the Unix epoch */
+    public static AttributeValue fromTimestamp(long epochMillis) {
+        return new AttributeValue(Type.TIMESTAMP, epochMillis);
+    }
+
+    public Type type() {
+        return type;
+    }
+
+    public String s() {
+        return (String) expect(Type.S);
+    }
+
+    public String n() {
+        return (String) expect(Type.N);
+    }
+
+    public byte[] b() {
+        return ((byte[]) expect(Type.B)).clone();
+    }
+
+    public boolean bool() {
+        return (Boolean) expect(Type.BOOL);
+    }
+
+    public boolean isNul() {
+        return type == Type.NUL;
+    }
+
+    @SuppressWarnings("unchecked")
+    public List<AttributeValue> l() {
+        return (List<AttributeValue>) expect(Type.L);
+    }
+
+    @SuppressWarnings("unchecked")
+    public Map<String, AttributeValue> m() {
+        return (Map<String, AttributeValue>) expect(Type.M);
+    }
+
+    public float[] vector() {
+        return ((float[]) expect(Type.VECTOR)).clone();
+    }
+
+    public long timestamp() {
+        return (Long) expect(Type.TIMESTAMP);
+    }
+
+    private Object expect(Type expected) {
+        if (type != expected) {
+            throw new IllegalStateException("Attribute value is " + type + ", not " + expected);
+        }
+        return value;
+    }
+
+    @Override
+    public boolean equals(Object other) {
+        if (this == other) {
+            return true;
+        }
+        if (!(other instanceof AttributeValue)) {
+            return false;
+        }
+        AttributeValue that = (AttributeValue) other;
+        if (type != that.type) {
+            return false;
+        }
+        switch (type) {
+            case B:
+                return Arrays.equals((byte[]) value, (byte[]) that.value);
+            case VECTOR:
+                return Arrays.equals((float[]) value, (float[]) that.value);
+            default:
+                return Objects.equals(value, that.value);
+        }
+    }
+
+    @Override
+    public int hashCode() {
+        switch (type) {
+            case B:
+                return 31 * type.hashCode() + Arrays.hashCode((byte[]) value);
+            case VECTOR:
+                return 31 * type.hashCode() + Arrays.hashCode((float[]) value);
+            default:
+                return 31 * type.hashCode() + Objects.hashCode(value);
+        }
+    }
+
+    @Override
+    public String toString() {
+        switch (type) {
+            case B:
+                return "B(" + ((byte[]) value).length + " bytes)";
+            case VECTOR:
+                return "VECTOR" + Arrays.toString((float[]) value);
+            case NUL:
+                return "NUL";
+            default:
+                return type + "(" + value + ")";
+        }
+    }
+}
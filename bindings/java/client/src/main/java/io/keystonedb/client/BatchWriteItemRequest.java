@@ -0,0 +1,66 @@
+package io.keystonedb.client;
+
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Map;
+
+/** Put and delete several items in one call */
+public final class BatchWriteItemRequest {
+    /** One put or delete of a batch */
+    public static final class Write {
+        private final ItemKey key;
+        private final Map<String, AttributeValue> item;
+
+        private Write(ItemKey key, Map<String, AttributeValue> item) {
+            this.key = key;
+            this.item = item;
+        }
+
+        public ItemKey key() {
+            return key;
+        }
+
+        /** The item to put, or null for a delete */
+        public Map<String, AttributeValue> item() {
+            return item;
+        }
+
+        public boolean isDelete() {
+            return item == null;
+        }
+    }
+
+    private final List<Write> writes;
+
+    private BatchWriteItemRequest(Builder builder) {
+        this.writes = List.copyOf(builder.writes);
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    public List<Write> writes() {
+        return writes;
+    }
+
+    public static final class Builder {
+        private final List<Write> writes = new ArrayList<>();
+
+        private Builder() {}
+
+        public Builder put(ItemKey key, Map<String, AttributeValue> item) {
+            writes.add(new Write(key, Map.copyOf(item)));
+            return this;
+        }
+
+        public Builder delete(ItemKey key) {
+            writes.add(new Write(key, null));
+            return this;
+        }
+
+        public BatchWriteItemRequest build() {
+            return new BatchWriteItemRequest(this);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+package io.keystonedb.client;
+
+/** A condition expression evaluated to false */
+public class ConditionalCheckFailedException extends KeystoneException {
+    public ConditionalCheckFailedException(String errorCode, String message, Throwable cause) {
+        super(errorCode, message, cause);
+    }
+}
@@ -0,0 +1,48 @@
+package io.keystonedb.client;
+
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+
+/** The result of a PartiQL statement run by {@link KeystoneClient#executeStatement} */
+public final class ExecuteStatementResponse {
+    /** The kind of statement that ran */
+    public enum Kind {
+        SELECT, INSERT, UPDATE, DELETE
+    }
+
+    private final Kind kind;
+    private final List<Map<String, AttributeValue>> items;
+    private final ItemKey lastEvaluatedKey;
+    private final Map<String, AttributeValue> item;
+
+    ExecuteStatementResponse(
+        Kind kind,
+        List<Map<String, AttributeValue>> items,
+        ItemKey lastEvaluatedKey,
+        Map<String, AttributeValue> item
+    ) {
+        this.kind = kind;
+        this.items = List.copyOf(items);
+        this.lastEvaluatedKey = lastEvaluatedKey;
+        this.item = item == null ? null : Map.copyOf(item);
+    }
+
+    public Kind kind() {
+        return kind;
+    }
+
+    /** Items a SELECT returned; empty for other statements */
+    public List<Map<String, AttributeValue>> items() {
+        return items;
+    }
+
+    public Optional<ItemKey> lastEvaluatedKey() {
+        return Optional.ofNullable(lastEvaluatedKey);
+    }
+
+    /** The updated item of an UPDATE */
+    public Optional<Map<String, AttributeValue>> item() {
+        return Optional.ofNullable(item);
+    }
+}
@@ -0,0 +1,47 @@
+package io.keystonedb.client;
+
+/** Read the item with a key */
+public final class GetItemRequest {
+    private final ItemKey key;
+
+    private GetItemRequest(Builder builder) {
+        this.key = Keys.require(builder.partitionKey, builder.sortKey);
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    public ItemKey key() {
+        return key;
+    }
+
+    public static final class Builder {
+        private byte[] partitionKey;
+        private byte[] sortKey;
+
+        private Builder() {}
+
+        public Builder partitionKey(byte[] partitionKey) {
+            this.partitionKey = partitionKey;
+            return this;
+        }
+
+        public Builder partitionKey(String partitionKey) {
+            return partitionKey(ItemKey.utf8(partitionKey));
+        }
+
+        public Builder sortKey(byte[] sortKey) {
+            this.sortKey = sortKey;
+            return this;
+        }
+
+        public Builder sortKey(String sortKey) {
+            return sortKey(ItemKey.utf8(sortKey));
+        }
+
+        public GetItemRequest build() {
+            return new GetItemRequest(this);
+        }
+    }
+}
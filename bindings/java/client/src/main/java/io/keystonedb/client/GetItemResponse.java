@@ -0,0 +1,22 @@
+package io.keystonedb.client;
+
+import java.util.Map;
+import java.util.Optional;
+
+/** The item read by {@link KeystoneClient#getItem} */
+public final class GetItemResponse {
+    private final Map<String, AttributeValue> item;
+
+    GetItemResponse(Map<String, AttributeValue> item) {
+        this.item = item == null ? null : Map.copyOf(item);
+    }
+
+    /** The item, or empty if no item has the key */
+    public Optional<Map<String, AttributeValue>> item() {
+        return Optional.ofNullable(item);
+    }
+
+    public boolean hasItem() {
+        return item != null;
+    }
+}
@@ -0,0 +1,66 @@
+package io.keystonedb.client;
+
+import java.nio.charset.StandardCharsets;
+import java.util.Arrays;
+import java.util.Objects;
+import java.util.Optional;
+
+/** The key of an item: a partition key and an optional sort key */
+public final class ItemKey {
+    private final byte[] partitionKey;
+    private final byte[] sortKey;
+
+    private ItemKey(byte[] partitionKey, byte[] sortKey) {
+        this.partitionKey = Objects.requireNonNull(partitionKey, "partitionKey").clone();
+        this.sortKey = sortKey == null ? null : sortKey.clone();
+    }
+
+    public static ItemKey of(byte[] partitionKey) {
+        return new ItemKey(partitionKey, null);
+    }
+
+    public static ItemKey of(byte[] partitionKey, byte[] sortKey) {
+        return new ItemKey(partitionKey, sortKey);
+    }
+
+    /** Keys given as strings are UTF-8 encoded */
+    public static ItemKey of(String partitionKey) {
+        return of(utf8(partitionKey));
+    }
+
+    public static ItemKey of(String partitionKey, String sortKey) {
+        return of(utf8(partitionKey), utf8(sortKey));
+    }
+
+    public byte[] partitionKey() {
+        return partitionKey.clone();
+    }
+
+    public Optional<byte[]> sortKey() {
+        return Optional.ofNullable(sortKey).map(byte[]::clone);
+    }
+
+    static byte[] utf8(String key) {
+        return Objects.requireNonNull(key, "key").getBytes(StandardCharsets.UTF_8);
+    }
+
+    @Override
+    public boolean equals(Object other) {
+        if (!(other instanceof ItemKey)) {
+            return false;
+        }
+        ItemKey that = (ItemKey) other;
+        return Arrays.equals(partitionKey, that.partitionKey) && Arrays.equals(sortKey, that.sortKey);
+    }
+
+    @Override
+    public int hashCode() {
+        return 31 * Arrays.hashCode(partitionKey) + Arrays.hashCode(sortKey);
+    }
+
+    @Override
+    public String toString() {
+        String pk = new String(partitionKey, StandardCharsets.UTF_8);
+        return sortKey == null ? "ItemKey(" + pk + ")" : "ItemKey(" + pk + ", " + new String(sortKey, StandardCharsets.UTF_8) + ")";
+    }
+}
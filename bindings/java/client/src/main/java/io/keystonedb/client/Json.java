@@ -0,0 +1,315 @@
+package io.keystonedb.client;
+
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.Base64;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+
+/**
+ * The JSON the C FFI exchanges items in, for {@link KeystoneDatabase}.
+ *
+ * <p>Plain JSON, not DynamoDB's typed JSON: objects are items and {@code M},
+ * arrays {@code L}, numbers {@code N}. Binary values are written as base64
+ * strings, vectors as arrays of numbers and timestamps as millisecond numbers;
+ * they read back as {@code S}, {@code L} and {@code N}.
+ */
+final class Json {
+    /** A number, kept as its JSON text so no precision is lost */
+    static final class Num {
+        final String text;
+
+        Num(String text) {
+            this.text = text;
+        }
+    }
+
+    private Json() {}
+
+    /** Encode maps, lists, strings, booleans, numbers, {@link AttributeValue}s and null */
+    static String write(Object value) {
+        StringBuilder out = new StringBuilder();
+        write(out, value);
+        return out.toString();
+    }
+
+    static byte[] utf8(Object value) {
+        return write(value).getBytes(StandardCharsets.UTF_8);
+    }
+
+    private static void write(StringBuilder out, Object value) {
+        if (value == null) {
+            out.append("null");
+        } else if (value instanceof AttributeValue) {
+            write(out, plain((AttributeValue) value));
+        } else if (value instanceof String) {
+            string(out, (String) value);
+        } else if (value instanceof Num) {
+            out.append(((Num) value).text);
+        } else if (value instanceof Number || value instanceof Boolean) {
+            out.append(value);
+        } else if (value instanceof Map) {
+            out.append('{');
+            boolean first = true;
+            for (Map.Entry<?, ?> entry : ((Map<?, ?>) value).entrySet()) {
+                if (!first) {
+                    out.append(',');
+                }
+                first = false;
+                string(out, (String) entry.getKey());
+                out.append(':');
+                write(out, entry.getValue());
+            }
+            out.append('}');
+        } else if (value instanceof List) {
+            out.append('[');
+            boolean first = true;
+            for (Object element : (List<?>) value) {
+                if (!first) {
+                    out.append(',');
+                }
+                first = false;
+                write(out, element);
+            }
+            out.append(']');
+        } else {
+            throw new IllegalArgumentException("Can't write " + value.getClass() + " as JSON");
+        }
+    }
+
+    /** An attribute value as the plain value {@link #write} encodes */
+    private static Object plain(AttributeValue value) {
+        switch (value.type()) {
+            case S:
+                return value.s();
+            case N:
+                return new Num(value.n());
+            case B:
+                return Base64.getEncoder().encodeToString(value.b());
+            case BOOL:
+                return value.bool();
+            case NUL:
+                return null;
+            case L:
+                return value.l();
+            case M:
+                return value.m();
+            case VECTOR: {
+                List<Object> numbers = new ArrayList<>();
+                for (float f : value.vector()) {
+                    numbers.add(new Num(Float.toString(f)));
+                }
+                return numbers;
+            }
+            case TIMESTAMP:
+                return value.timestamp();
+            default:
+                throw new IllegalStateException("Unknown type " + value.type());
+        }
+    }
+
+    private static void string(StringBuilder out, String s) {
+        out.append('"');
+        for (int i = 0; i < s.length(); i++) {
+            char c = s.charAt(i);
+            switch (c) {
+                case '"':
+                    out.append("\\\"");
+                    break;
+                case '\\':
+                    out.append("\\\\");
+                    break;
+                case '\n':
+                    out.append("\\n");
+                    break;
+                case '\r':
+                    out.append("\\r");
+                    break;
+                case '\t':
+                    out.append("\\t");
+                    break;
+                default:
+                    if (c < 0x20) {
+                        out.append(String.format("\\u%04x", (int) c));
+                    } else {
+                        out.append(c);
+                    }
+            }
+        }
+        out.append('"');
+    }
+
+    /** Decode into maps, lists, strings, booleans, {@link Num}s and null */
+    static Object read(byte[] json) {
+        Reader reader = new Reader(new String(json, StandardCharsets.UTF_8));
+        Object value = reader.value();
+        reader.skipWhitespace();
+        if (reader.pos != reader.text.length()) {
+            throw reader.error("Trailing characters");
+        }
+        return value;
+    }
+
+    /** An item from a decoded JSON object */
+    @SuppressWarnings("unchecked")
+    static Map<String, AttributeValue> item(Object json) {
+        Map<String, AttributeValue> item = new LinkedHashMap<>();
+        ((Map<String, Object>) json).forEach((name, value) -> item.put(name, attributeValue(value)));
+        return item;
+    }
+
+    @SuppressWarnings("unchecked")
+    static AttributeValue attributeValue(Object json) {
+        if (json == null) {
+            return AttributeValue.nul();
+        } else if (json instanceof String) {
+            return AttributeValue.fromS((String) json);
+        } else if (json instanceof Num) {
+            return AttributeValue.fromN(((Num) json).text);
+        } else if (json instanceof Boolean) {
+            return AttributeValue.fromBool((Boolean) json);
+        } else if (json instanceof List) {
+            List<AttributeValue> values = new ArrayList<>();
+            for (Object element : (List<Object>) json) {
+                values.add(attributeValue(element));
+            }
+            return AttributeValue.fromL(values);
+        } else {
+            return AttributeValue.fromM(item(json));
+        }
+    }
+
+    private static final class Reader {
+        final String text;
+        int pos;
+
+        Reader(String text) {
+            this.text = text;
+        }
+
+        IllegalArgumentException error(String message) {
+            return new IllegalArgumentException(message + " at offset " + pos + " of JSON");
+        }
+
+        void skipWhitespace() {
+            while (pos < text.length() && Character.isWhitespace(text.charAt(pos))) {
+                pos++;
+            }
+        }
+
+        void expect(char c) {
+            skipWhitespace();
+            if (pos >= text.length() || text.charAt(pos) != c) {
+                throw error("Expected '" + c + "'");
+            }
+            pos++;
+        }
+
+        boolean consume(char c) {
+            skipWhitespace();
+            if (pos < text.length() && text.charAt(pos) == c) {
+                pos++;
+                return true;
+            }
+            return false;
+        }
+
+        Object value() {
+            skipWhitespace();
+            if (pos >= text.length()) {
+                throw error("Unexpected end");
+            }
+            char c = text.charAt(pos);
+            if (c == '{') {
+                pos++;
+                Map<String, Object> object = new LinkedHashMap<>();
+                if (consume('}')) {
+                    return object;
+                }
+                do {
+                    skipWhitespace();
+                    String name = string();
+                    expect(':');
+                    object.put(name, value());
+                } while (consume(','));
+                expect('}');
+                return object;
+            } else if (c == '[') {
+                pos++;
+                List<Object> array = new ArrayList<>();
+                if (consume(']')) {
+                    return array;
+                }
+                do {
+                    array.add(value());
+                } while (consume(','));
+                expect(']');
+                return array;
+            } else if (c == '"') {
+                return string();
+            } else if (text.startsWith("true", pos)) {
+                pos += 4;
+                return Boolean.TRUE;
+            } else if (text.startsWith("false", pos)) {
+                pos += 5;
+                return Boolean.FALSE;
+            } else if (text.startsWith("null", pos)) {
+                pos += 4;
+                return null;
+            } else if (c == '-' || (c >= '0' && c <= '9')) {
+                int start = pos;
+                while (pos < text.length() && "+-.eE0123456789".indexOf(text.charAt(pos)) >= 0) {
+                    pos++;
+                }
+                return new Num(text.substring(start, pos));
+            }
+            throw error("Unexpected character '" + c + "'");
+        }
+
+        String string() {
+            if (pos >= text.length() || text.charAt(pos) != '"') {
+                throw error("Expected a string");
+            }
+            pos++;
+            StringBuilder out = new StringBuilder();
+            while (true) {
+                if (pos >= text.length()) {
+                    throw error("Unterminated string");
+                }
+                char c = text.charAt(pos++);
+                if (c == '"') {
+                    return out.toString();
+                }
+                if (c != '\\') {
+                    out.append(c);
+                    continue;
+                }
+                char escape = text.charAt(pos++);
+                switch (escape) {
+                    case 'b':
+                        out.append('\b');
+                        break;
+                    case 'f':
+                        out.append('\f');
+                        break;
+                    case 'n':
+                        out.append('\n');
+                        break;
+                    case 'r':
+                        out.append('\r');
+                        break;
+                    case 't':
+                        out.append('\t');
+                        break;
+                    case 'u':
+                        out.append((char) Integer.parseInt(text.substring(pos, pos + 4), 16));
+                        pos += 4;
+                        break;
+                    default:
+                        out.append(escape);
+                }
+            }
+        }
+    }
+}
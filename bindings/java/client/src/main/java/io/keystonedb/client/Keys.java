@@ -0,0 +1,13 @@
+package io.keystonedb.client;
+
+/** Key checks shared by the request builders */
+final class Keys {
+    private Keys() {}
+
+    static ItemKey require(byte[] partitionKey, byte[] sortKey) {
+        if (partitionKey == null) {
+            throw new IllegalStateException("A partition key is required");
+        }
+        return sortKey == null ? ItemKey.of(partitionKey) : ItemKey.of(partitionKey, sortKey);
+    }
+}
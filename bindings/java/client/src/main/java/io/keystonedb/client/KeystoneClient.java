@@ -0,0 +1,348 @@
+package io.keystonedb.client;
+
+import com.google.protobuf.ByteString;
+import io.grpc.ManagedChannel;
+import io.grpc.ManagedChannelBuilder;
+import io.grpc.Metadata;
+import io.grpc.StatusRuntimeException;
+import io.grpc.stub.MetadataUtils;
+import io.keystonedb.proto.BatchGetRequest;
+import io.keystonedb.proto.BatchGetResponse;
+import io.keystonedb.proto.BatchWriteRequest;
+import io.keystonedb.proto.BatchWriteResponse;
+import io.keystonedb.proto.DeleteKey;
+import io.keystonedb.proto.DeleteRequest;
+import io.keystonedb.proto.DeleteResponse;
+import io.keystonedb.proto.ExecuteStatementRequest;
+import io.keystonedb.proto.GetRequest;
+import io.keystonedb.proto.GetResponse;
+import io.keystonedb.proto.KeystoneDBGrpc;
+import io.keystonedb.proto.PutItem;
+import io.keystonedb.proto.PutRequest;
+import io.keystonedb.proto.PutResponse;
+import io.keystonedb.proto.SelectResult;
+import io.keystonedb.proto.UpdateRequest;
+import io.keystonedb.proto.UpdateResponse;
+import io.keystonedb.proto.WriteRequest;
+import java.time.Duration;
+import java.util.ArrayList;
+import java.util.Collection;
+import java.util.Iterator;
+import java.util.List;
+import java.util.Map;
+import java.util.Objects;
+import java.util.concurrent.TimeUnit;
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+/**
+ * Client for a KeystoneDB server, with an interface modeled on the DynamoDB SDK.
+ *
+ * <pre>{@code
+ * try (KeystoneClient client = KeystoneClient.builder("localhost:50051").build()) {
+ *     client.putItem(PutItemRequest.builder()
+ *         .partitionKey("user#alice")
+ *         .attribute("name", AttributeValue.fromS("Alice"))
+ *         .build());
+ *     GetItemResponse response = client.getItem(GetItemRequest.builder().partitionKey("user#alice").build());
+ * }
+ * }</pre>
+ *
+ * <p>The client keeps a pool of channels and sends each call on the next one in
+ * turn. Calls failing with {@code UNAVAILABLE} or {@code RESOURCE_EXHAUSTED} are
+ * retried as the {@link RetryPolicy} allows; other failures are thrown as
+ * {@link KeystoneException}. The client is thread-safe.
+ */
+public final class KeystoneClient implements AutoCloseable {
+    /** Metadata keys the server reads; see kstone-server/src/auth.rs and registry.rs */
+    static final Metadata.Key<String> AUTHORIZATION_HEADER =
+        Metadata.Key.of("authorization", Metadata.ASCII_STRING_MARSHALLER);
+    static final Metadata.Key<String> DATABASE_HEADER =
+        Metadata.Key.of("kstone-database", Metadata.ASCII_STRING_MARSHALLER);
+
+    private final List<ManagedChannel> channels;
+    private final List<KeystoneDBGrpc.KeystoneDBBlockingStub> stubs;
+    private final AtomicInteger next = new AtomicInteger();
+    private final RetryPolicy retryPolicy;
+    private final Duration timeout;
+
+    private KeystoneClient(Builder builder) {
+        Metadata headers = new Metadata();
+        if (builder.token != null) {
+            headers.put(AUTHORIZATION_HEADER, "Bearer " + builder.token);
+        }
+        if (builder.database != null) {
+            headers.put(DATABASE_HEADER, builder.database);
+        }
+        this.channels = new ArrayList<>(builder.poolSize);
+        this.stubs = new ArrayList<>(builder.poolSize);
+        for (int i = 0; i < builder.poolSize; i++) {
+            ManagedChannel channel = builder.channelBuilder.get().build();
+            channels.add(channel);
+            stubs.add(KeystoneDBGrpc.newBlockingStub(channel)
+                .withInterceptors(MetadataUtils.newAttachHeadersInterceptor(headers)));
+        }
+        this.retryPolicy = builder.retryPolicy;
+        this.timeout = builder.timeout;
+    }
+
+    /** A client for the server at {@code target}, e.g. {@code "localhost:50051"}, over plaintext */
+    public static Builder builder(String target) {
+        Objects.requireNonNull(target, "target");
+        return new Builder(() -> ManagedChannelBuilder.forTarget(target).usePlaintext());
+    }
+
+    /** A client whose channels come from {@code channelBuilder}, e.g. to configure TLS */
+    public static Builder builder(Supplier<ManagedChannelBuilder<?>> channelBuilder) {
+        return new Builder(Objects.requireNonNull(channelBuilder, "channelBuilder"));
+    }
+
+    public void putItem(PutItemRequest request) {
+        ItemKey key = request.key();
+        PutRequest.Builder proto = PutRequest.newBuilder()
+            .setPartitionKey(ByteString.copyFrom(key.partitionKey()))
+            .setItem(Protos.item(request.item()))
+            .putAllExpressionValues(Protos.values(request.expressionAttributeValues()));
+        key.sortKey().ifPresent(sk -> proto.setSortKey(ByteString.copyFrom(sk)));
+        request.conditionExpression().ifPresent(proto::setConditionExpression);
+        PutResponse response = call(stub -> stub.put(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+    }
+
+    public GetItemResponse getItem(GetItemRequest request) {
+        ItemKey key = request.key();
+        GetRequest.Builder proto = GetRequest.newBuilder().setPartitionKey(ByteString.copyFrom(key.partitionKey()));
+        key.sortKey().ifPresent(sk -> proto.setSortKey(ByteString.copyFrom(sk)));
+        GetResponse response = call(stub -> stub.get(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+        return new GetItemResponse(response.hasItem() ? Protos.item(response.getItem()) : null);
+    }
+
+    public void deleteItem(DeleteItemRequest request) {
+        ItemKey key = request.key();
+        DeleteRequest.Builder proto = DeleteRequest.newBuilder()
+            .setPartitionKey(ByteString.copyFrom(key.partitionKey()))
+            .putAllExpressionValues(Protos.values(request.expressionAttributeValues()));
+        key.sortKey().ifPresent(sk -> proto.setSortKey(ByteString.copyFrom(sk)));
+        request.conditionExpression().ifPresent(proto::setConditionExpression);
+        DeleteResponse response = call(stub -> stub.delete(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+    }
+
+    public UpdateItemResponse updateItem(UpdateItemRequest request) {
+        ItemKey key = request.key();
+        UpdateRequest.Builder proto = UpdateRequest.newBuilder()
+            .setPartitionKey(ByteString.copyFrom(key.partitionKey()))
+            .setUpdateExpression(request.updateExpression())
+            .putAllExpressionValues(Protos.values(request.expressionAttributeValues()));
+        key.sortKey().ifPresent(sk -> proto.setSortKey(ByteString.copyFrom(sk)));
+        request.conditionExpression().ifPresent(proto::setConditionExpression);
+        UpdateResponse response = call(stub -> stub.update(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+        return new UpdateItemResponse(Protos.item(response.getItem()));
+    }
+
+    /** One page of the items in a partition */
+    public QueryResponse query(QueryRequest request) {
+        io.keystonedb.proto.QueryRequest.Builder proto = io.keystonedb.proto.QueryRequest.newBuilder()
+            .setPartitionKey(ByteString.copyFrom(request.partitionKey()))
+            .putAllExpressionValues(Protos.values(request.expressionAttributeValues()))
+            .setScanForward(request.scanIndexForward());
+        request.sortKeyCondition().ifPresent(c -> proto.setSortKeyCondition(Protos.sortKeyCondition(c)));
+        request.filterExpression().ifPresent(proto::setFilterExpression);
+        request.indexName().ifPresent(proto::setIndexName);
+        request.limit().ifPresent(proto::setLimit);
+        request.exclusiveStartKey().ifPresent(k -> proto.setExclusiveStartKey(Protos.lastKey(k)));
+        io.keystonedb.proto.QueryResponse response = call(stub -> stub.query(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+        return new QueryResponse(
+            Protos.items(response.getItemsList()),
+            response.getCount(),
+            response.getScannedCount(),
+            response.hasLastEvaluatedKey() ? Protos.itemKey(response.getLastEvaluatedKey()) : null);
+    }
+
+    /** Every page of a query, each fetched when the iteration reaches it */
+    public Iterable<QueryResponse> queryPaginator(QueryRequest request) {
+        return () -> new Paginator<>(request, this::query, QueryResponse::lastEvaluatedKey,
+            (r, key) -> r.toBuilder().exclusiveStartKey(key).build());
+    }
+
+    /** One page of the items in the table; the pages the server streams back are collected into one */
+    public ScanResponse scan(ScanRequest request) {
+        io.keystonedb.proto.ScanRequest.Builder proto = io.keystonedb.proto.ScanRequest.newBuilder()
+            .putAllExpressionValues(Protos.values(request.expressionAttributeValues()));
+        request.filterExpression().ifPresent(proto::setFilterExpression);
+        request.indexName().ifPresent(proto::setIndexName);
+        request.limit().ifPresent(proto::setLimit);
+        request.exclusiveStartKey().ifPresent(k -> proto.setExclusiveStartKey(Protos.lastKey(k)));
+        request.segment().ifPresent(proto::setSegment);
+        request.totalSegments().ifPresent(proto::setTotalSegments);
+        return call(stub -> {
+            List<Map<String, AttributeValue>> items = new ArrayList<>();
+            int count = 0;
+            int scannedCount = 0;
+            ItemKey lastKey = null;
+            Iterator<io.keystonedb.proto.ScanResponse> pages = stub.scan(proto.build());
+            while (pages.hasNext()) {
+                io.keystonedb.proto.ScanResponse page = pages.next();
+                Protos.check(page.hasError(), page.getError());
+                items.addAll(Protos.items(page.getItemsList()));
+                count += page.getCount();
+                scannedCount += page.getScannedCount();
+                lastKey = page.hasLastEvaluatedKey() ? Protos.itemKey(page.getLastEvaluatedKey()) : null;
+            }
+            return new ScanResponse(items, count, scannedCount, lastKey);
+        });
+    }
+
+    /** Every page of a scan, each fetched when the iteration reaches it */
+    public Iterable<ScanResponse> scanPaginator(ScanRequest request) {
+        return () -> new Paginator<>(request, this::scan, ScanResponse::lastEvaluatedKey,
+            (r, key) -> r.toBuilder().exclusiveStartKey(key).build());
+    }
+
+    /** The items that exist among {@code keys} */
+    public List<Map<String, AttributeValue>> batchGetItem(Collection<ItemKey> keys) {
+        BatchGetRequest.Builder proto = BatchGetRequest.newBuilder();
+        for (ItemKey key : keys) {
+            proto.addKeys(Protos.key(key));
+        }
+        BatchGetResponse response = call(stub -> stub.batchGet(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+        return Protos.items(response.getItemsList());
+    }
+
+    public void batchWriteItem(BatchWriteItemRequest request) {
+        BatchWriteRequest.Builder proto = BatchWriteRequest.newBuilder();
+        for (BatchWriteItemRequest.Write write : request.writes()) {
+            ItemKey key = write.key();
+            ByteString pk = ByteString.copyFrom(key.partitionKey());
+            if (write.isDelete()) {
+                DeleteKey.Builder delete = DeleteKey.newBuilder().setPartitionKey(pk);
+                key.sortKey().ifPresent(sk -> delete.setSortKey(ByteString.copyFrom(sk)));
+                proto.addWrites(WriteRequest.newBuilder().setDelete(delete));
+            } else {
+                PutItem.Builder put = PutItem.newBuilder().setPartitionKey(pk).setItem(Protos.item(write.item()));
+                key.sortKey().ifPresent(sk -> put.setSortKey(ByteString.copyFrom(sk)));
+                proto.addWrites(WriteRequest.newBuilder().setPut(put));
+            }
+        }
+        BatchWriteResponse response = call(stub -> stub.batchWrite(proto.build()));
+        Protos.check(response.hasError(), response.getError());
+    }
+
+    /** Run a PartiQL statement */
+    public ExecuteStatementResponse executeStatement(String statement) {
+        ExecuteStatementRequest proto = ExecuteStatementRequest.newBuilder().setStatement(statement).build();
+        io.keystonedb.proto.ExecuteStatementResponse response = call(stub -> stub.executeStatement(proto));
+        Protos.check(response.hasError(), response.getError());
+        switch (response.getResponseCase()) {
+            case SELECT: {
+                SelectResult select = response.getSelect();
+                ItemKey lastKey = select.hasLastKey() ? Protos.itemKey(select.getLastKey()) : null;
+                return new ExecuteStatementResponse(
+                    ExecuteStatementResponse.Kind.SELECT, Protos.items(select.getItemsList()), lastKey, null);
+            }
+            case INSERT:
+                return new ExecuteStatementResponse(ExecuteStatementResponse.Kind.INSERT, List.of(), null, null);
+            case UPDATE:
+                return new ExecuteStatementResponse(
+                    ExecuteStatementResponse.Kind.UPDATE, List.of(), null, Protos.item(response.getUpdate().getItem()));
+            case DELETE:
+                return new ExecuteStatementResponse(ExecuteStatementResponse.Kind.DELETE, List.of(), null, null);
+            default:
+                throw new KeystoneException("UNKNOWN", "Server returned no statement result", null);
+        }
+    }
+
+    /** Shut down every channel, waiting up to five seconds for calls in flight */
+    @Override
+    public void close() {
+        channels.forEach(ManagedChannel::shutdown);
+        try {
+            for (ManagedChannel channel : channels) {
+                if (!channel.awaitTermination(5, TimeUnit.SECONDS)) {
+                    channel.shutdownNow();
+                }
+            }
+        } catch (InterruptedException e) {
+            channels.forEach(ManagedChannel::shutdownNow);
+            Thread.currentThread().interrupt();
+        }
+    }
+
+    /** Send a call, retrying it on the next channel as the retry policy allows */
+    private <T> T call(Function<KeystoneDBGrpc.KeystoneDBBlockingStub, T> call) {
+        for (int attempt = 0; ; attempt++) {
+            KeystoneDBGrpc.KeystoneDBBlockingStub stub = stubs.get(Math.floorMod(next.getAndIncrement(), stubs.size()));
+            if (timeout != null) {
+                stub = stub.withDeadlineAfter(timeout.toMillis(), TimeUnit.MILLISECONDS);
+            }
+            try {
+                return call.apply(stub);
+            } catch (StatusRuntimeException e) {
+                KeystoneException error = Protos.exception(e);
+                if (!error.isRetryable() || attempt >= retryPolicy.maxAttempts()) {
+                    throw error;
+                }
+            }
+            try {
+                Thread.sleep(retryPolicy.backoff(attempt).toMillis());
+            } catch (InterruptedException e) {
+                Thread.currentThread().interrupt();
+                throw new KeystoneException("CANCELLED", "Interrupted while waiting to retry", e);
+            }
+        }
+    }
+
+    public static final class Builder {
+        private final Supplier<ManagedChannelBuilder<?>> channelBuilder;
+        private int poolSize = 4;
+        private RetryPolicy retryPolicy = RetryPolicy.standard();
+        private Duration timeout;
+        private String token;
+        private String database;
+
+        private Builder(Supplier<ManagedChannelBuilder<?>> channelBuilder) {
+            this.channelBuilder = channelBuilder;
+        }
+
+        /** Number of channels (connections) calls are spread over */
+        public Builder poolSize(int poolSize) {
+            if (poolSize < 1) {
+                throw new IllegalArgumentException("poolSize must be at least 1");
+            }
+            this.poolSize = poolSize;
+            return this;
+        }
+
+        public Builder retryPolicy(RetryPolicy retryPolicy) {
+            this.retryPolicy = Objects.requireNonNull(retryPolicy, "retryPolicy");
+            return this;
+        }
+
+        /** Limit on each attempt of a call; unset to wait as long as the server takes */
+        public Builder timeout(Duration timeout) {
+            this.timeout = timeout;
+            return this;
+        }
+
+        /** Bearer token for servers that require authentication */
+        public Builder token(String token) {
+            this.token = token;
+            return this;
+        }
+
+        /** Database to address on a server hosting several */
+        public Builder database(String database) {
+            this.database = database;
+            return this;
+        }
+
+        public KeystoneClient build() {
+            return new KeystoneClient(this);
+        }
+    }
+}
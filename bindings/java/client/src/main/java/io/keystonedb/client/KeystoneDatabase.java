@@ -0,0 +1,425 @@
+package io.keystonedb.client;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.Collection;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Locale;
+import java.util.Map;
+import java.util.Objects;
+import java.util.concurrent.locks.ReadWriteLock;
+import java.util.concurrent.locks.ReentrantReadWriteLock;
+import java.util.function.LongFunction;
+
+/**
+ * An embedded KeystoneDB database, running in-process through JNI on top of the
+ * C FFI library ({@code kstone-ffi}).
+ *
+ * <p>It takes the same requests and returns the same responses as
+ * {@link KeystoneClient}, so code can move between an embedded database and a
+ * server:
+ *
+ * <pre>{@code
+ * try (KeystoneDatabase db = KeystoneDatabase.open(Path.of("app.keystone"))) {
+ *     db.putItem(PutItemRequest.builder()
+ *         .partitionKey("user#alice")
+ *         .attribute("name", AttributeValue.fromS("Alice"))
+ *         .build());
+ *     GetItemResponse response = db.getItem(GetItemRequest.builder().partitionKey("user#alice").build());
+ * }
+ * }</pre>
+ *
+ * <p>The JNI library {@code keystone_jni} (built from {@code src/main/c}) is
+ * loaded from {@code java.library.path}, or from the file named by the
+ * {@code keystonedb.jni.library} system property. Keys must be UTF-8 text
+ * without NUL characters, and values cross the FFI as JSON, so binary,
+ * vector and timestamp attributes read back as {@code S}, {@code L} and
+ * {@code N} (see {@code kstone_api::json}). Failures throw
+ * {@link KeystoneException} with the closest gRPC status name as
+ * {@link KeystoneException#errorCode()}. The database is thread-safe.
+ */
+public final class KeystoneDatabase implements AutoCloseable {
+    static {
+        String library = System.getProperty("keystonedb.jni.library");
+        if (library != null) {
+            System.load(library);
+        } else {
+            System.loadLibrary("keystone_jni");
+        }
+    }
+
+    private static final int CREATE = 0;
+    private static final int OPEN = 1;
+    private static final int IN_MEMORY = 2;
+
+    /** {@code KsError} codes of the C FFI; see c-ffi/src/error.rs */
+    private static final int NULL_POINTER = 1;
+    private static final int INVALID_UTF8 = 2;
+    private static final int INVALID_ARGUMENT = 3;
+    private static final int NOT_FOUND = 4;
+    private static final int CONDITIONAL_CHECK_FAILED = 7;
+    private static final int TRANSACTION_CANCELED = 8;
+
+    private final ReadWriteLock lock = new ReentrantReadWriteLock();
+    private long handle;
+
+    private KeystoneDatabase(long handle) {
+        this.handle = handle;
+    }
+
+    /** Create a new database at {@code path}; fails if one exists */
+    public static KeystoneDatabase create(Path path) {
+        return new KeystoneDatabase(nativeOpen(CREATE, utf8(path.toString())));
+    }
+
+    /** Open the existing database at {@code path} */
+    public static KeystoneDatabase open(Path path) {
+        return new KeystoneDatabase(nativeOpen(OPEN, utf8(path.toString())));
+    }
+
+    /** A database that lives only in memory */
+    public static KeystoneDatabase createInMemory() {
+        return new KeystoneDatabase(nativeOpen(IN_MEMORY, null));
+    }
+
+    public void putItem(PutItemRequest request) {
+        ItemKey key = request.key();
+        byte[] item = Json.utf8(request.item());
+        byte[] condition = request.conditionExpression().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] values = values(request.expressionAttributeValues());
+        with(db -> {
+            nativePut(db, key.partitionKey(), sortKey(key), item, condition, values);
+            return null;
+        });
+    }
+
+    public GetItemResponse getItem(GetItemRequest request) {
+        ItemKey key = request.key();
+        byte[] json = with(db -> nativeGet(db, key.partitionKey(), sortKey(key)));
+        return new GetItemResponse(json == null ? null : Json.item(Json.read(json)));
+    }
+
+    public void deleteItem(DeleteItemRequest request) {
+        ItemKey key = request.key();
+        byte[] condition = request.conditionExpression().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] values = values(request.expressionAttributeValues());
+        with(db -> {
+            nativeDelete(db, key.partitionKey(), sortKey(key), condition, values);
+            return null;
+        });
+    }
+
+    public UpdateItemResponse updateItem(UpdateItemRequest request) {
+        ItemKey key = request.key();
+        byte[] expression = utf8(request.updateExpression());
+        byte[] condition = request.conditionExpression().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] values = values(request.expressionAttributeValues());
+        byte[] json = with(db -> nativeUpdate(db, key.partitionKey(), sortKey(key), expression, condition, values));
+        return new UpdateItemResponse(Json.item(Json.read(json)));
+    }
+
+    /** One page of the items in a partition */
+    public QueryResponse query(QueryRequest request) {
+        int operator = request.sortKeyCondition().map(c -> c.operator().ordinal()).orElse(-1);
+        byte[] low = request.sortKeyCondition().map(c -> keyBytes(c.value())).orElse(null);
+        byte[] high = request.sortKeyCondition().flatMap(SortKeyCondition::upper).map(KeystoneDatabase::keyBytes).orElse(null);
+        byte[] index = request.indexName().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] filter = request.filterExpression().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] values = values(request.expressionAttributeValues());
+        ItemKey start = request.exclusiveStartKey().orElse(null);
+        byte[] json = with(db -> nativeQuery(db, request.partitionKey(), operator, low, high, request.scanIndexForward(),
+            request.limit().orElse(0), index, filter, values,
+            start == null ? null : start.partitionKey(), start == null ? null : sortKey(start)));
+        Page page = new Page(json);
+        return new QueryResponse(page.items, page.items.size(), page.scannedCount, page.lastKey);
+    }
+
+    /** Every page of a query, each fetched when the iteration reaches it */
+    public Iterable<QueryResponse> queryPaginator(QueryRequest request) {
+        return () -> new Paginator<>(request, this::query, QueryResponse::lastEvaluatedKey,
+            (r, key) -> r.toBuilder().exclusiveStartKey(key).build());
+    }
+
+    /** One page of the items in the table */
+    public ScanResponse scan(ScanRequest request) {
+        if (request.indexName().isPresent()) {
+            throw new ValidationException("INVALID_ARGUMENT", "The embedded database can't scan an index", null);
+        }
+        byte[] filter = request.filterExpression().map(KeystoneDatabase::utf8).orElse(null);
+        byte[] values = values(request.expressionAttributeValues());
+        ItemKey start = request.exclusiveStartKey().orElse(null);
+        byte[] json = with(db -> nativeScan(db, request.limit().orElse(0), null,
+            request.segment().orElse(0), request.totalSegments().orElse(0), filter, values,
+            start == null ? null : start.partitionKey(), start == null ? null : sortKey(start)));
+        Page page = new Page(json);
+        return new ScanResponse(page.items, page.items.size(), page.scannedCount, page.lastKey);
+    }
+
+    /** Every page of a scan, each fetched when the iteration reaches it */
+    public Iterable<ScanResponse> scanPaginator(ScanRequest request) {
+        return () -> new Paginator<>(request, this::scan, ScanResponse::lastEvaluatedKey,
+            (r, key) -> r.toBuilder().exclusiveStartKey(key).build());
+    }
+
+    /** The items that exist among {@code keys} */
+    public List<Map<String, AttributeValue>> batchGetItem(Collection<ItemKey> keys) {
+        byte[] json = keysJson(keys);
+        List<Map<String, AttributeValue>> items = new ArrayList<>();
+        for (Object item : (List<?>) Json.read(with(db -> nativeBatchGet(db, json)))) {
+            if (item != null) {
+                items.add(Json.item(item));
+            }
+        }
+        return items;
+    }
+
+    public void batchWriteItem(BatchWriteItemRequest request) {
+        List<Object> writes = new ArrayList<>();
+        for (BatchWriteItemRequest.Write write : request.writes()) {
+            Map<String, Object> op = keyJson(write.key());
+            if (!write.isDelete()) {
+                op.put("item", write.item());
+            }
+            writes.add(Map.of(write.isDelete() ? "delete" : "put", op));
+        }
+        byte[] json = Json.utf8(writes);
+        with(db -> {
+            nativeBatchWrite(db, json);
+            return null;
+        });
+    }
+
+    /** Apply every write of {@code request}, or none of them */
+    public void transactWriteItems(TransactWriteItemsRequest request) {
+        List<TransactWriteItemsRequest.Write> writes = request.writes();
+        int[] kinds = new int[writes.size()];
+        byte[][] pks = new byte[writes.size()][];
+        byte[][] sks = new byte[writes.size()][];
+        byte[][] payloads = new byte[writes.size()][];
+        byte[][] conditions = new byte[writes.size()][];
+        for (int i = 0; i < writes.size(); i++) {
+            TransactWriteItemsRequest.Write write = writes.get(i);
+            kinds[i] = write.kind().ordinal();
+            pks[i] = write.key().partitionKey();
+            sks[i] = sortKey(write.key());
+            if (write.item() != null) {
+                payloads[i] = Json.utf8(write.item());
+            } else if (write.updateExpression() != null) {
+                payloads[i] = utf8(write.updateExpression());
+            }
+            conditions[i] = write.conditionExpression() == null ? null : utf8(write.conditionExpression());
+        }
+        byte[] values = values(request.expressionAttributeValues());
+        with(db -> {
+            nativeTransactWrite(db, kinds, pks, sks, payloads, conditions, values);
+            return null;
+        });
+    }
+
+    /**
+     * Read {@code keys} from one consistent snapshot. The list is in key order,
+     * with null where an item doesn't exist.
+     */
+    public List<Map<String, AttributeValue>> transactGetItems(List<ItemKey> keys) {
+        byte[] json = keysJson(keys);
+        List<Map<String, AttributeValue>> items = new ArrayList<>();
+        for (Object item : (List<?>) Json.read(with(db -> nativeTransactGet(db, json)))) {
+            items.add(item == null ? null : Json.item(item));
+        }
+        return items;
+    }
+
+    /** Run a PartiQL statement */
+    public ExecuteStatementResponse executeStatement(String statement) {
+        return executeStatement(statement, List.of());
+    }
+
+    /** Run a PartiQL statement whose {@code ?} placeholders are bound to {@code parameters} in order */
+    public ExecuteStatementResponse executeStatement(String statement, List<AttributeValue> parameters) {
+        byte[] sql = utf8(statement);
+        byte[] params = Json.utf8(parameters);
+        @SuppressWarnings("unchecked")
+        Map<String, Object> result = (Map<String, Object>) Json.read(with(db -> nativeExecuteStatement(db, sql, params)));
+        switch ((String) result.get("kind")) {
+            case "select": {
+                Page page = new Page(result);
+                return new ExecuteStatementResponse(ExecuteStatementResponse.Kind.SELECT, page.items, page.lastKey, null);
+            }
+            case "item":
+                return new ExecuteStatementResponse(
+                    ExecuteStatementResponse.Kind.UPDATE, List.of(), null, Json.item(result.get("item")));
+            case "success": {
+                // The FFI reports INSERT and DELETE alike; the statement says which ran
+                boolean delete = statement.strip().toUpperCase(Locale.ROOT).startsWith("DELETE");
+                ExecuteStatementResponse.Kind kind =
+                    delete ? ExecuteStatementResponse.Kind.DELETE : ExecuteStatementResponse.Kind.INSERT;
+                return new ExecuteStatementResponse(kind, List.of(), null, null);
+            }
+            default:
+                throw new ValidationException("INVALID_ARGUMENT", "EXPLAIN isn't supported by executeStatement", null);
+        }
+    }
+
+    /** Write buffered changes to disk */
+    public void flush() {
+        with(db -> {
+            nativeFlush(db);
+            return null;
+        });
+    }
+
+    /** Flush and close the database, waiting for calls in flight. Closing twice is harmless. */
+    @Override
+    public void close() {
+        lock.writeLock().lock();
+        try {
+            if (handle != 0) {
+                long db = handle;
+                handle = 0;
+                nativeClose(db);
+            }
+        } finally {
+            lock.writeLock().unlock();
+        }
+    }
+
+    /** Run a native call with the open handle */
+    private <T> T with(LongFunction<T> call) {
+        lock.readLock().lock();
+        try {
+            if (handle == 0) {
+                throw new KeystoneException("FAILED_PRECONDITION", "Database is closed", null);
+            }
+            return call.apply(handle);
+        } finally {
+            lock.readLock().unlock();
+        }
+    }
+
+    /** One page of results as the FFI returns it */
+    private static final class Page {
+        final List<Map<String, AttributeValue>> items = new ArrayList<>();
+        final int scannedCount;
+        final ItemKey lastKey;
+
+        Page(byte[] json) {
+            this(asMap(Json.read(json)));
+        }
+
+        Page(Map<String, Object> json) {
+            for (Object item : (List<?>) json.get("items")) {
+                items.add(Json.item(item));
+            }
+            scannedCount = Integer.parseInt(((Json.Num) json.get("scanned_count")).text);
+            Object last = json.get("last_key");
+            if (last == null) {
+                lastKey = null;
+            } else {
+                Map<String, Object> key = asMap(last);
+                String sk = (String) key.get("sk");
+                lastKey = sk == null ? ItemKey.of((String) key.get("pk")) : ItemKey.of((String) key.get("pk"), sk);
+            }
+        }
+
+        @SuppressWarnings("unchecked")
+        private static Map<String, Object> asMap(Object json) {
+            return (Map<String, Object>) json;
+        }
+    }
+
+    private static byte[] utf8(String text) {
+        return text.getBytes(StandardCharsets.UTF_8);
+    }
+
+    private static byte[] sortKey(ItemKey key) {
+        return key.sortKey().orElse(null);
+    }
+
+    /** A sort key condition operand: the bytes of a string or binary value */
+    private static byte[] keyBytes(AttributeValue value) {
+        return value.type() == AttributeValue.Type.B ? value.b() : utf8(value.s());
+    }
+
+    private static byte[] values(Map<String, AttributeValue> values) {
+        return values.isEmpty() ? null : Json.utf8(values);
+    }
+
+    private static Map<String, Object> keyJson(ItemKey key) {
+        Map<String, Object> json = new LinkedHashMap<>();
+        json.put("pk", new String(key.partitionKey(), StandardCharsets.UTF_8));
+        key.sortKey().ifPresent(sk -> json.put("sk", new String(sk, StandardCharsets.UTF_8)));
+        return json;
+    }
+
+    private static byte[] keysJson(Collection<ItemKey> keys) {
+        List<Object> json = new ArrayList<>();
+        for (ItemKey key : keys) {
+            json.add(keyJson(Objects.requireNonNull(key, "key")));
+        }
+        return Json.utf8(json);
+    }
+
+    /**
+     * The exception for a failed native call; the JNI library throws what this
+     * returns. {@code message} is UTF-8.
+     */
+    static KeystoneException error(int code, byte[] message) {
+        String text = message == null ? "KeystoneDB call failed" : new String(message, StandardCharsets.UTF_8);
+        switch (code) {
+            case NULL_POINTER:
+            case INVALID_UTF8:
+            case INVALID_ARGUMENT:
+                return new ValidationException("INVALID_ARGUMENT", text, null);
+            case NOT_FOUND:
+                return new ResourceNotFoundException("NOT_FOUND", text, null);
+            case CONDITIONAL_CHECK_FAILED:
+                return new ConditionalCheckFailedException("FAILED_PRECONDITION", text, null);
+            case TRANSACTION_CANCELED:
+                return new TransactionCanceledException("ABORTED", text, null);
+            default:
+                return new KeystoneException("INTERNAL", text, null);
+        }
+    }
+
+    // Strings cross as UTF-8 byte arrays rather than Java strings, whose JNI
+    // encoding (modified UTF-8) isn't what the FFI expects. Null means absent.
+    private static native long nativeOpen(int mode, byte[] path);
+
+    private static native void nativeClose(long db);
+
+    private static native void nativeFlush(long db);
+
+    private static native void nativePut(long db, byte[] pk, byte[] sk, byte[] item, byte[] condition, byte[] values);
+
+    /** The item as JSON, or null if it doesn't exist */
+    private static native byte[] nativeGet(long db, byte[] pk, byte[] sk);
+
+    private static native void nativeDelete(long db, byte[] pk, byte[] sk, byte[] condition, byte[] values);
+
+    private static native byte[] nativeUpdate(
+        long db, byte[] pk, byte[] sk, byte[] expression, byte[] condition, byte[] values);
+
+    /** {@code operator} is a {@link SortKeyCondition.Operator} ordinal or -1; a zero limit means none */
+    private static native byte[] nativeQuery(
+        long db, byte[] pk, int operator, byte[] low, byte[] high, boolean forward, int limit,
+        byte[] index, byte[] filter, byte[] values, byte[] startPk, byte[] startSk);
+
+    /** Zero {@code limit} or {@code totalSegments} means none */
+    private static native byte[] nativeScan(
+        long db, int limit, byte[] pkPrefix, int segment, int totalSegments,
+        byte[] filter, byte[] values, byte[] startPk, byte[] startSk);
+
+    private static native byte[] nativeBatchGet(long db, byte[] keys);
+
+    private static native void nativeBatchWrite(long db, byte[] writes);
+
+    private static native byte[] nativeTransactGet(long db, byte[] keys);
+
+    /** {@code kinds} are {@link TransactWriteItemsRequest.Write.Kind} ordinals; payloads are items or update expressions */
+    private static native void nativeTransactWrite(
+        long db, int[] kinds, byte[][] pks, byte[][] sks, byte[][] payloads, byte[][] conditions, byte[] values);
+
+    private static native byte[] nativeExecuteStatement(long db, byte[] sql, byte[] params);
+}
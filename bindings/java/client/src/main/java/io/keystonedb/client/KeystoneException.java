@@ -0,0 +1,26 @@
+package io.keystonedb.client;
+
+/**
+ * A call to the server (or the embedded database) failed.
+ *
+ * <p>{@link #errorCode()} is the name of the gRPC status the server answered with,
+ * e.g. {@code UNAVAILABLE}; {@link KeystoneDatabase} uses the closest status name.
+ * The subclasses cover the errors callers usually handle.
+ */
+public class KeystoneException extends RuntimeException {
+    private final String errorCode;
+
+    public KeystoneException(String errorCode, String message, Throwable cause) {
+        super(message, cause);
+        this.errorCode = errorCode;
+    }
+
+    public String errorCode() {
+        return errorCode;
+    }
+
+    /** Whether the server was unavailable or overloaded, so sending the call again may succeed */
+    public boolean isRetryable() {
+        return "UNAVAILABLE".equals(errorCode) || "RESOURCE_EXHAUSTED".equals(errorCode);
+    }
+}
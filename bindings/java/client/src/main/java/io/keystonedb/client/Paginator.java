@@ -0,0 +1,43 @@
+package io.keystonedb.client;
+
+import java.util.Iterator;
+import java.util.NoSuchElementException;
+import java.util.Optional;
+import java.util.function.BiFunction;
+import java.util.function.Function;
+
+/** Iterates pages by sending each request with the previous page's last key */
+final class Paginator<Q, R> implements Iterator<R> {
+    private final Function<Q, R> fetch;
+    private final Function<R, Optional<ItemKey>> lastKey;
+    private final BiFunction<Q, ItemKey, Q> continueAfter;
+    private Q nextRequest;
+
+    Paginator(
+        Q first,
+        Function<Q, R> fetch,
+        Function<R, Optional<ItemKey>> lastKey,
+        BiFunction<Q, ItemKey, Q> continueAfter
+    ) {
+        this.nextRequest = first;
+        this.fetch = fetch;
+        this.lastKey = lastKey;
+        this.continueAfter = continueAfter;
+    }
+
+    @Override
+    public boolean hasNext() {
+        return nextRequest != null;
+    }
+
+    @Override
+    public R next() {
+        if (nextRequest == null) {
+            throw new NoSuchElementException();
+        }
+        Q request = nextRequest;
+        R page = fetch.apply(request);
+        nextRequest = lastKey.apply(page).map(key -> continueAfter.apply(request, key)).orElse(null);
+        return page;
+    }
+}
@@ -0,0 +1,191 @@
+package io.keystonedb.client;
+
+import com.google.protobuf.ByteString;
+import io.grpc.Status;
+import io.grpc.StatusRuntimeException;
+import io.keystonedb.proto.BetweenCondition;
+import io.keystonedb.proto.Item;
+import io.keystonedb.proto.Key;
+import io.keystonedb.proto.LastKey;
+import io.keystonedb.proto.ListValue;
+import io.keystonedb.proto.MapValue;
+import io.keystonedb.proto.NullValue;
+import io.keystonedb.proto.Value;
+import io.keystonedb.proto.VectorValue;
+import java.util.ArrayList;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+
+/** Conversion between the client's model and the generated protobuf messages */
+final class Protos {
+    private Protos() {}
+
+    static Value value(AttributeValue value) {
+        Value.Builder builder = Value.newBuilder();
+        switch (value.type()) {
+            case S:
+                return builder.setStringValue(value.s()).build();
+            case N:
+                return builder.setNumberValue(value.n()).build();
+            case B:
+                return builder.setBinaryValue(ByteString.copyFrom(value.b())).build();
+            case BOOL:
+                return builder.setBoolValue(value.bool()).build();
+            case NUL:
+                return builder.setNullValue(NullValue.NULL_VALUE).build();
+            case L: {
+                ListValue.Builder list = ListValue.newBuilder();
+                for (AttributeValue element : value.l()) {
+                    list.addItems(value(element));
+                }
+                return builder.setListValue(list).build();
+            }
+            case M:
+                return builder.setMapValue(MapValue.newBuilder().putAllFields(values(value.m()))).build();
+            case VECTOR: {
+                VectorValue.Builder vector = VectorValue.newBuilder();
+                for (float element : value.vector()) {
+                    vector.addValues(element);
+                }
+                return builder.setVectorValue(vector).build();
+            }
+            case TIMESTAMP:
+                return builder.setTimestampValue(value.timestamp()).build();
+            default:
+                throw new IllegalArgumentException("Unknown attribute value type " + value.type());
+        }
+    }
+
+    static AttributeValue attributeValue(Value value) {
+        switch (value.getValueCase()) {
+            case STRING_VALUE:
+                return AttributeValue.fromS(value.getStringValue());
+            case NUMBER_VALUE:
+                return AttributeValue.fromN(value.getNumberValue());
+            case BINARY_VALUE:
+                return AttributeValue.fromB(value.getBinaryValue().toByteArray());
+            case BOOL_VALUE:
+                return AttributeValue.fromBool(value.getBoolValue());
+            case NULL_VALUE:
+            case VALUE_NOT_SET:
+                return AttributeValue.nul();
+            case LIST_VALUE: {
+                List<AttributeValue> list = new ArrayList<>();
+                for (Value element : value.getListValue().getItemsList()) {
+                    list.add(attributeValue(element));
+                }
+                return AttributeValue.fromL(list);
+            }
+            case MAP_VALUE:
+                return AttributeValue.fromM(attributeValues(value.getMapValue().getFieldsMap()));
+            case VECTOR_VALUE: {
+                List<Float> elements = value.getVectorValue().getValuesList();
+                float[] vector = new float[elements.size()];
+                for (int i = 0; i < vector.length; i++) {
+                    vector[i] = elements.get(i);
+                }
+                return AttributeValue.fromVector(vector);
+            }
+            case TIMESTAMP_VALUE:
+                return AttributeValue.fromTimestamp(value.getTimestampValue());
+            default:
+                throw new IllegalArgumentException("Unknown value case " + value.getValueCase());
+        }
+    }
+
+    static Map<String, Value> values(Map<String, AttributeValue> values) {
+        Map<String, Value> converted = new LinkedHashMap<>();
+        values.forEach((name, value) -> converted.put(name, value(value)));
+        return converted;
+    }
+
+    static Map<String, AttributeValue> attributeValues(Map<String, Value> values) {
+        Map<String, AttributeValue> converted = new LinkedHashMap<>();
+        values.forEach((name, value) -> converted.put(name, attributeValue(value)));
+        return converted;
+    }
+
+    static Item item(Map<String, AttributeValue> item) {
+        return Item.newBuilder().putAllAttributes(values(item)).build();
+    }
+
+    static Map<String, AttributeValue> item(Item item) {
+        return attributeValues(item.getAttributesMap());
+    }
+
+    static List<Map<String, AttributeValue>> items(List<Item> items) {
+        List<Map<String, AttributeValue>> converted = new ArrayList<>(items.size());
+        for (Item item : items) {
+            converted.add(item(item));
+        }
+        return converted;
+    }
+
+    static Key key(ItemKey key) {
+        Key.Builder builder = Key.newBuilder().setPartitionKey(ByteString.copyFrom(key.partitionKey()));
+        key.sortKey().ifPresent(sk -> builder.setSortKey(ByteString.copyFrom(sk)));
+        return builder.build();
+    }
+
+    static LastKey lastKey(ItemKey key) {
+        LastKey.Builder builder = LastKey.newBuilder().setPartitionKey(ByteString.copyFrom(key.partitionKey()));
+        key.sortKey().ifPresent(sk -> builder.setSortKey(ByteString.copyFrom(sk)));
+        return builder.build();
+    }
+
+    static ItemKey itemKey(LastKey key) {
+        byte[] pk = key.getPartitionKey().toByteArray();
+        return key.hasSortKey() ? ItemKey.of(pk, key.getSortKey().toByteArray()) : ItemKey.of(pk);
+    }
+
+    static io.keystonedb.proto.SortKeyCondition sortKeyCondition(SortKeyCondition condition) {
+        io.keystonedb.proto.SortKeyCondition.Builder builder = io.keystonedb.proto.SortKeyCondition.newBuilder();
+        Value value = value(condition.value());
+        switch (condition.operator()) {
+            case EQUAL_TO:
+                return builder.setEqualTo(value).build();
+            case LESS_THAN:
+                return builder.setLessThan(value).build();
+            case LESS_THAN_OR_EQUAL:
+                return builder.setLessThanOrEqual(value).build();
+            case GREATER_THAN:
+                return builder.setGreaterThan(value).build();
+            case GREATER_THAN_OR_EQUAL:
+                return builder.setGreaterThanOrEqual(value).build();
+            case BETWEEN:
+                Value upper = value(condition.upper().orElseThrow());
+                return builder.setBetween(BetweenCondition.newBuilder().setLower(value).setUpper(upper)).build();
+            case BEGINS_WITH:
+                return builder.setBeginsWith(value).build();
+            default:
+                throw new IllegalArgumentException("Unknown sort key operator " + condition.operator());
+        }
+    }
+
+    /** The client exception for a failed call, chosen by status code as the server's map_error assigns them */
+    static KeystoneException exception(StatusRuntimeException error) {
+        Status status = error.getStatus();
+        String code = status.getCode().name();
+        String message = status.getDescription() != null ? status.getDescription() : code;
+        switch (status.getCode()) {
+            case NOT_FOUND:
+                return new ResourceNotFoundException(code, message, error);
+            case INVALID_ARGUMENT:
+                return new ValidationException(code, message, error);
+            case FAILED_PRECONDITION:
+                return new ConditionalCheckFailedException(code, message, error);
+            case ABORTED:
+                return new TransactionCanceledException(code, message, error);
+            default:
+                return new KeystoneException(code, message, error);
+        }
+    }
+
+    /** Raise the error a response carries in its {@code error} field */
+    static void check(boolean hasError, String error) {
+        if (hasError) {
+            throw new KeystoneException(Status.Code.UNKNOWN.name(), error, null);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+package io.keystonedb.client;
+
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.Objects;
+import java.util.Optional;
+
+/** Store an item, replacing any item with the same key */
+public final class PutItemRequest {
+    private final ItemKey key;
+    private final Map<String, AttributeValue> item;
+    private final String conditionExpression;
+    private final Map<String, AttributeValue> expressionAttributeValues;
+
+    private PutItemRequest(Builder builder) {
+        this.key = Keys.require(builder.partitionKey, builder.sortKey);
+        this.item = Map.copyOf(builder.item);
+        this.conditionExpression = builder.conditionExpression;
+        this.expressionAttributeValues = Map.copyOf(builder.expressionAttributeValues);
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    public ItemKey key() {
+        return key;
+    }
+
+    public Map<String, AttributeValue> item() {
+        return item;
+    }
+
+    public Optional<String> conditionExpression() {
+        return Optional.ofNullable(conditionExpression);
+    }
+
+    public Map<String, AttributeValue> expressionAttributeValues() {
+        return expressionAttributeValues;
+    }
+
+    public static final class Builder {
+        private byte[] partitionKey;
+        private byte[] sortKey;
+        private final Map<String, AttributeValue> item = new LinkedHashMap<>();
+        private String conditionExpression;
+        private final Map<String, AttributeValue> expressionAttributeValues = new LinkedHashMap<>();
+
+        private Builder() {}
+
+        public Builder partitionKey(byte[] partitionKey) {
+            this.partitionKey = partitionKey;
+            return this;
+        }
+
+        public Builder partitionKey(String partitionKey) {
+            return partitionKey(ItemKey.utf8(partitionKey));
+        }
+
+        public Builder sortKey(byte[] sortKey) {
+            this.sortKey = sortKey;
+            return this;
+        }
+
+        public Builder sortKey(String sortKey) {
+            return sortKey(ItemKey.utf8(sortKey));
+        }
+
+        public Builder item(Map<String, AttributeValue> item) {
+            this.item.clear();
+            this.item.putAll(item);
+            return this;
+        }
+
+        public Builder attribute(String name, AttributeValue value) {
+            this.item.put(Objects.requireNonNull(name, "name"), Objects.requireNonNull(value, "value"));
+            return this;
+        }
+
+        /** Only put the item if this condition holds for the current one */
+        public Builder conditionExpression(String conditionExpression) {
+            this.conditionExpression = conditionExpression;
+            return this;
+        }
+
+        public Builder expressionAttributeValues(Map<String, AttributeValue> values) {
+            this.expressionAttributeValues.putAll(values);
+            return this;
+        }
+
+        public Builder expressionAttributeValue(String placeholder, AttributeValue value) {
+            this.expressionAttributeValues.put(placeholder, value);
+            return this;
+        }
+
+        public PutItemRequest build() {
+            return new PutItemRequest(this);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+package io.keystonedb.client;
+
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.Optional;
+
+/** Read the items in one partition, in sort key order */
+public final class QueryRequest {
+    private final byte[] partitionKey;
+    private final SortKeyCondition sortKeyCondition;
+    private final String filterExpression;
+    private final Map<String, AttributeValue> expressionAttributeValues;
+    private final String indexName;
+    private final Integer limit;
+    private final ItemKey exclusiveStartKey;
+    private final boolean scanIndexForward;
+
+    private QueryRequest(Builder builder) {
+        if (builder.partitionKey == null) {
+            throw new IllegalStateException("A partition key is required");
+        }
+        this.partitionKey = builder.partitionKey.clone();
+        this.sortKeyCondition = builder.sortKeyCondition;
+        this.filterExpression = builder.filterExpression;
+        this.expressionAttributeValues = Map.copyOf(builder.expressionAttributeValues);
+        this.indexName = builder.indexName;
+        this.limit = builder.limit;
+        this.exclusiveStartKey = builder.exclusiveStartKey;
+        this.scanIndexForward = builder.scanIndexForward;
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    /** A builder with every setting of this request, to change some of them */
+    public Builder toBuilder() {
+        Builder builder = new Builder()
+            .partitionKey(partitionKey)
+            .sortKeyCondition(sortKeyCondition)
+            .filterExpression(filterExpression)
+            .expressionAttributeValues(expressionAttributeValues)
+            .indexName(indexName)
+            .exclusiveStartKey(exclusiveStartKey)
+            .scanIndexForward(scanIndexForward);
+        builder.limit = limit;
+        return builder;
+    }
+
+    public byte[] partitionKey() {
+        return partitionKey.clone();
+    }
+
+    public Optional<SortKeyCondition> sortKeyCondition() {
+        return Optional.ofNullable(sortKeyCondition);
+    }
+
+    public Optional<String> filterExpression() {
+        return Optional.ofNullable(filterExpression);
+    }
+
+    public Map<String, AttributeValue> expressionAttributeValues() {
+        return expressionAttributeValues;
+    }
+
+    public Optional<String> indexName() {
+        return Optional.ofNullable(indexName);
+    }
+
+    public Optional<Integer> limit() {
+        return Optional.ofNullable(limit);
+    }
+
+    public Optional<ItemKey> exclusiveStartKey() {
+        return Optional.ofNullable(exclusiveStartKey);
+    }
+
+    public boolean scanIndexForward() {
+        return scanIndexForward;
+    }
+
+    public static final class Builder {
+        private byte[] partitionKey;
+        private SortKeyCondition sortKeyCondition;
+        private String filterExpression;
+        private final Map<String, AttributeValue> expressionAttributeValues = new LinkedHashMap<>();
+        private String indexName;
+        private Integer limit;
+        private ItemKey exclusiveStartKey;
+        private boolean scanIndexForward = true;
+
+        private Builder() {}
+
+        public Builder partitionKey(byte[] partitionKey) {
+            this.partitionKey = partitionKey;
+            return this;
+        }
+
+        public Builder partitionKey(String partitionKey) {
+            return partitionKey(ItemKey.utf8(partitionKey));
+        }
+
+        public Builder sortKeyCondition(SortKeyCondition sortKeyCondition) {
+            this.sortKeyCondition = sortKeyCondition;
+            return this;
+        }
+
+        /** Drop items for which this expression is false, after reading them */
+        public Builder filterExpression(String filterExpression) {
+            this.filterExpression = filterExpression;
+            return this;
+        }
+
+        public Builder expressionAttributeValues(Map<String, AttributeValue> values) {
+            this.expressionAttributeValues.putAll(values);
+            return this;
+        }
+
+        public Builder expressionAttributeValue(String placeholder, AttributeValue value) {
+            this.expressionAttributeValues.put(placeholder, value);
+            return this;
+        }
+
+        /** Query a local or global secondary index instead of the table */
+        public Builder indexName(String indexName) {
+            this.indexName = indexName;
+            return this;
+        }
+
+        public Builder limit(int limit) {
+            this.limit = limit;
+            return this;
+        }
+
+        /** Continue after this key, the {@code lastEvaluatedKey} of the previous page */
+        public Builder exclusiveStartKey(ItemKey exclusiveStartKey) {
+            this.exclusiveStartKey = exclusiveStartKey;
+            return this;
+        }
+
+        public Builder scanIndexForward(boolean scanIndexForward) {
+            this.scanIndexForward = scanIndexForward;
+            return this;
+        }
+
+        public QueryRequest build() {
+            return new QueryRequest(this);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+package io.keystonedb.client;
+
+/** A table, index or other named resource doesn't exist */
+public class ResourceNotFoundException extends KeystoneException {
+    public ResourceNotFoundException(String errorCode, String message, Throwable cause) {
+        super(errorCode, message, cause);
+    }
+}
@@ -0,0 +1,52 @@
+package io.keystonedb.client;
+
+import java.time.Duration;
+import java.util.concurrent.ThreadLocalRandom;
+
+/**
+ * How calls failing with {@code UNAVAILABLE} or {@code RESOURCE_EXHAUSTED} are retried.
+ *
+ * <p>The defaults match {@code RetryPolicy::standard()} in kstone-core: 5 retries,
+ * backing off from 100ms up to 5s. {@code maxAttempts} counts retries, not
+ * including the first attempt.
+ */
+public final class RetryPolicy {
+    private final int maxAttempts;
+    private final Duration initialBackoff;
+    private final Duration maxBackoff;
+    private final double multiplier;
+    private final boolean jitter;
+
+    public RetryPolicy(int maxAttempts, Duration initialBackoff, Duration maxBackoff, double multiplier, boolean jitter) {
+        if (maxAttempts < 0) {
+            throw new IllegalArgumentException("maxAttempts must not be negative");
+        }
+        this.maxAttempts = maxAttempts;
+        this.initialBackoff = initialBackoff;
+        this.maxBackoff = maxBackoff;
+        this.multiplier = multiplier;
+        this.jitter = jitter;
+    }
+
+    public static RetryPolicy standard() {
+        return new RetryPolicy(5, Duration.ofMillis(100), Duration.ofSeconds(5), 2.0, true);
+    }
+
+    public static RetryPolicy none() {
+        return new RetryPolicy(0, Duration.ZERO, Duration.ZERO, 1.0, false);
+    }
+
+    public int maxAttempts() {
+        return maxAttempts;
+    }
+
+    /** The wait before retry {@code attempt} (0-indexed) */
+    public Duration backoff(int attempt) {
+        double millis = Math.min(initialBackoff.toMillis() * Math.pow(multiplier, attempt), maxBackoff.toMillis());
+        if (jitter) {
+            // Stay in the upper half so retries still back off
+            millis = millis / 2 + millis * ThreadLocalRandom.current().nextDouble() / 2;
+        }
+        return Duration.ofMillis((long) millis);
+    }
+}
@@ -0,0 +1,133 @@
+package io.keystonedb.client;
+
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.Optional;
+
+/** Read every item in the table or an index */
+public final class ScanRequest {
+    private final String filterExpression;
+    private final Map<String, AttributeValue> expressionAttributeValues;
+    private final String indexName;
+    private final Integer limit;
+    private final ItemKey exclusiveStartKey;
+    private final Integer segment;
+    private final Integer totalSegments;
+
+    private ScanRequest(Builder builder) {
+        if ((builder.segment == null) != (builder.totalSegments == null)) {
+            throw new IllegalStateException("segment and totalSegments must be set together");
+        }
+        this.filterExpression = builder.filterExpression;
+        this.expressionAttributeValues = Map.copyOf(builder.expressionAttributeValues);
+        this.indexName = builder.indexName;
+        this.limit = builder.limit;
+        this.exclusiveStartKey = builder.exclusiveStartKey;
+        this.segment = builder.segment;
+        this.totalSegments = builder.totalSegments;
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    /** A builder with every setting of this request, to change some of them */
+    public Builder toBuilder() {
+        Builder builder = new Builder()
+            .filterExpression(filterExpression)
+            .expressionAttributeValues(expressionAttributeValues)
+            .indexName(indexName)
+            .exclusiveStartKey(exclusiveStartKey);
+        builder.limit = limit;
+        builder.segment = segment;
+        builder.totalSegments = totalSegments;
+        return builder;
+    }
+
+    public Optional<String> filterExpression() {
+        return Optional.ofNullable(filterExpression);
+    }
+
+    public Map<String, AttributeValue> expressionAttributeValues() {
+        return expressionAttributeValues;
+    }
+
+    public Optional<String> indexName() {
+        return Optional.ofNullable(indexName);
+    }
+
+    public Optional<Integer> limit() {
+        return Optional.ofNullable(limit);
+    }
+
+    public Optional<ItemKey> exclusiveStartKey() {
+        return Optional.ofNullable(exclusiveStartKey);
+    }
+
+    public Optional<Integer> segment() {
+        return Optional.ofNullable(segment);
+    }
+
+    public Optional<Integer> totalSegments() {
+        return Optional.ofNullable(totalSegments);
+    }
+
+    public static final class Builder {
+        private String filterExpression;
+        private final Map<String, AttributeValue> expressionAttributeValues = new LinkedHashMap<>();
+        private String indexName;
+        private Integer limit;
+        private ItemKey exclusiveStartKey;
+        private Integer segment;
+        private Integer totalSegments;
+
+        private Builder() {}
+
+        /** Drop items for which this expression is false, after reading them */
+        public Builder filterExpression(String filterExpression) {
+            this.filterExpression = filterExpression;
+            return this;
+        }
+
+        public Builder expressionAttributeValues(Map<String, AttributeValue> values) {
+            this.expressionAttributeValues.putAll(values);
+            return this;
+        }
+
+        public Builder expressionAttributeValue(String placeholder, AttributeValue value) {
+            this.expressionAttributeValues.put(placeholder, value);
+            return this;
+        }
+
+        /** Scan a secondary index instead of the table */
+        public Builder indexName(String indexName) {
+            this.indexName = indexName;
+            return this;
+        }
+
+        public Builder limit(int limit) {
+            this.limit = limit;
+            return this;
+        }
+
+        /** Continue after this key, the {@code lastEvaluatedKey} of the previous page */
+        public Builder exclusiveStartKey(ItemKey exclusiveStartKey) {
+            this.exclusiveStartKey = exclusiveStartKey;
+            return this;
+        }
+
+        /** Scan only segment {@code segment} of {@code totalSegments}, for parallel scans */
+        public Builder segment(int segment, int totalSegments) {
+            if (segment < 0 || segment >= totalSegments) {
+                throw new IllegalArgumentException("segment must be in [0, totalSegments)");
+            }
+            this.segment = segment;
+            this.totalSegments = totalSegments;
+            return this;
+        }
+
+        public ScanRequest build() {
+            return new ScanRequest(this);
+        }
+    }
+}
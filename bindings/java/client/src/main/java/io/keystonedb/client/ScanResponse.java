@@ -0,0 +1,46 @@
+package io.keystonedb.client;
+
+import java.util.List;
+import java.util.Map;
+import java.util.Optional;
+
+/**
+ * One page of scan results.
+ *
+ * <p>If {@link #lastEvaluatedKey()} is present there may be more items; pass it
+ * as {@code exclusiveStartKey} to read the next page.
+ */
+public final class ScanResponse {
+    private final List<Map<String, AttributeValue>> items;
+    private final int count;
+    private final int scannedCount;
+    private final ItemKey lastEvaluatedKey;
+
+    ScanResponse(List<Map<String, AttributeValue>> items, int count, int scannedCount, ItemKey lastEvaluatedKey) {
+        this.items = List.copyOf(items);
+        this.count = count;
+        this.scannedCount = scannedCount;
+        this.lastEvaluatedKey = lastEvaluatedKey;
+    }
+
+    public List<Map<String, AttributeValue>> items() {
+        return items;
+    }
+
+    public int count() {
+        return count;
+    }
+
+    /** Items read before the filter expression was applied */
+    public int scannedCount() {
+        return scannedCount;
+    }
+
+    public Optional<ItemKey> lastEvaluatedKey() {
+        return Optional.ofNullable(lastEvaluatedKey);
+    }
+
+    public boolean hasMorePages() {
+        return lastEvaluatedKey != null;
+    }
+}
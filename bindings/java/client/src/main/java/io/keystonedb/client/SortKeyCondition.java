@@ -0,0 +1,84 @@
+package io.keystonedb.client;
+
+import java.util.Objects;
+import java.util.Optional;
+
+/**
+ * A condition on the sort key of a query.
+ *
+ * <p>The server compares sort keys as bytes, so values must be S, N or B.
+ */
+public final class SortKeyCondition {
+    /** The comparison a condition makes */
+    public enum Operator {
+        EQUAL_TO, LESS_THAN, LESS_THAN_OR_EQUAL, GREATER_THAN, GREATER_THAN_OR_EQUAL, BETWEEN, BEGINS_WITH
+    }
+
+    private final Operator operator;
+    private final AttributeValue value;
+    private final AttributeValue upper;
+
+    private SortKeyCondition(Operator operator, AttributeValue value, AttributeValue upper) {
+        this.operator = operator;
+        this.value = requireKeyValue(value);
+        this.upper = upper == null ? null : requireKeyValue(upper);
+    }
+
+    public static SortKeyCondition equalTo(AttributeValue value) {
+        return new SortKeyCondition(Operator.EQUAL_TO, value, null);
+    }
+
+    public static SortKeyCondition lessThan(AttributeValue value) {
+        return new SortKeyCondition(Operator.LESS_THAN, value, null);
+    }
+
+    public static SortKeyCondition lessThanOrEqual(AttributeValue value) {
+        return new SortKeyCondition(Operator.LESS_THAN_OR_EQUAL, value, null);
+    }
+
+    public static SortKeyCondition greaterThan(AttributeValue value) {
+        return new SortKeyCondition(Operator.GREATER_THAN, value, null);
+    }
+
+    public static SortKeyCondition greaterThanOrEqual(AttributeValue value) {
+        return new SortKeyCondition(Operator.GREATER_THAN_OR_EQUAL, value, null);
+    }
+
+    public static SortKeyCondition between(AttributeValue lower, AttributeValue upper) {
+        return new SortKeyCondition(Operator.BETWEEN, lower, Objects.requireNonNull(upper, "upper"));
+    }
+
+    public static SortKeyCondition beginsWith(String prefix) {
+        return new SortKeyCondition(Operator.BEGINS_WITH, AttributeValue.fromS(prefix), null);
+    }
+
+    public static SortKeyCondition beginsWith(AttributeValue prefix) {
+        return new SortKeyCondition(Operator.BEGINS_WITH, prefix, null);
+    }
+
+    public Operator operator() {
+        return operator;
+    }
+
+    /** The value compared against, or the lower bound of {@code BETWEEN} */
+    public AttributeValue value() {
+        return value;
+    }
+
+    /** The upper bound of {@code BETWEEN} */
+    public Optional<AttributeValue> upper() {
+        return Optional.ofNullable(upper);
+    }
+
+    private static AttributeValue requireKeyValue(AttributeValue value) {
+        Objects.requireNonNull(value, "value");
+        switch (value.type()) {
+            case S:
+            case N:
+            case B:
+                return value;
+            default:
+                throw new IllegalArgumentException("Sort key values must be S, N or B, not " + value.type());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+package io.keystonedb.client;
+
+import java.util.ArrayList;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.Objects;
+
+/**
+ * Writes applied all or nothing by {@link KeystoneDatabase#transactWriteItems}.
+ *
+ * <p>Each write is an ordinary put, update or delete request; their conditions
+ * are checked together and, if any fails, nothing is written and the call throws
+ * {@link TransactionCanceledException}. Expression attribute values are shared
+ * by every write of a transaction, so a placeholder must mean the same value
+ * wherever it appears.
+ */
+public final class TransactWriteItemsRequest {
+    /** One write of a transaction */
+    public static final class Write {
+        /** What a write does */
+        public enum Kind {
+            PUT, UPDATE, DELETE, CONDITION_CHECK
+        }
+
+        private final Kind kind;
+        private final ItemKey key;
+        private final Map<String, AttributeValue> item;
+        private final String updateExpression;
+        private final String conditionExpression;
+
+        private Write(Kind kind, ItemKey key, Map<String, AttributeValue> item, String updateExpression, String conditionExpression) {
+            this.kind = kind;
+            this.key = key;
+            this.item = item;
+            this.updateExpression = updateExpression;
+            this.conditionExpression = conditionExpression;
+        }
+
+        public Kind kind() {
+            return kind;
+        }
+
+        public ItemKey key() {
+            return key;
+        }
+
+        /** The item of a put, else null */
+        public Map<String, AttributeValue> item() {
+            return item;
+        }
+
+        /** The expression of an update, else null */
+        public String updateExpression() {
+            return updateExpression;
+        }
+
+        /** The condition, or null if the write has none */
+        public String conditionExpression() {
+            return conditionExpression;
+        }
+    }
+
+    private final List<Write> writes;
+    private final Map<String, AttributeValue> expressionAttributeValues;
+
+    private TransactWriteItemsRequest(Builder builder) {
+        this.writes = List.copyOf(builder.writes);
+        this.expressionAttributeValues = Map.copyOf(builder.expressionAttributeValues);
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    public List<Write> writes() {
+        return writes;
+    }
+
+    /** The placeholders of every write */
+    public Map<String, AttributeValue> expressionAttributeValues() {
+        return expressionAttributeValues;
+    }
+
+    public static final class Builder {
+        private final List<Write> writes = new ArrayList<>();
+        private final Map<String, AttributeValue> expressionAttributeValues = new LinkedHashMap<>();
+
+        private Builder() {}
+
+        public Builder put(PutItemRequest put) {
+            values(put.expressionAttributeValues());
+            writes.add(new Write(Write.Kind.PUT, put.key(), put.item(), null, put.conditionExpression().orElse(null)));
+            return this;
+        }
+
+        public Builder update(UpdateItemRequest update) {
+            values(update.expressionAttributeValues());
+            writes.add(new Write(Write.Kind.UPDATE, update.key(), null, update.updateExpression(),
+                update.conditionExpression().orElse(null)));
+            return this;
+        }
+
+        public Builder delete(DeleteItemRequest delete) {
+            values(delete.expressionAttributeValues());
+            writes.add(new Write(Write.Kind.DELETE, delete.key(), null, null, delete.conditionExpression().orElse(null)));
+            return this;
+        }
+
+        /** Cancel the transaction unless {@code conditionExpression} holds for the item at {@code key} */
+        public Builder conditionCheck(ItemKey key, String conditionExpression) {
+            Objects.requireNonNull(key, "key");
+            Objects.requireNonNull(conditionExpression, "conditionExpression");
+            writes.add(new Write(Write.Kind.CONDITION_CHECK, key, null, null, conditionExpression));
+            return this;
+        }
+
+        public Builder expressionAttributeValue(String placeholder, AttributeValue value) {
+            values(Map.of(Objects.requireNonNull(placeholder, "placeholder"), Objects.requireNonNull(value, "value")));
+            return this;
+        }
+
+        private void values(Map<String, AttributeValue> values) {
+            values.forEach((placeholder, value) -> {
+                AttributeValue previous = expressionAttributeValues.putIfAbsent(placeholder, value);
+                if (previous != null && !previous.equals(value)) {
+                    throw new IllegalArgumentException(
+                        "Placeholder " + placeholder + " is bound to different values within one transaction");
+                }
+            });
+        }
+
+        public TransactWriteItemsRequest build() {
+            return new TransactWriteItemsRequest(this);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+package io.keystonedb.client;
+
+/** A transaction was canceled; no part of it was applied */
+public class TransactionCanceledException extends KeystoneException {
+    public TransactionCanceledException(String errorCode, String message, Throwable cause) {
+        super(errorCode, message, cause);
+    }
+}
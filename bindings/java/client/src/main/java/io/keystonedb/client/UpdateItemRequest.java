@@ -0,0 +1,94 @@
+package io.keystonedb.client;
+
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.Objects;
+import java.util.Optional;
+
+/** Apply an update expression such as {@code SET age = age + :inc} to an item */
+public final class UpdateItemRequest {
+    private final ItemKey key;
+    private final String updateExpression;
+    private final String conditionExpression;
+    private final Map<String, AttributeValue> expressionAttributeValues;
+
+    private UpdateItemRequest(Builder builder) {
+        this.key = Keys.require(builder.partitionKey, builder.sortKey);
+        this.updateExpression = Objects.requireNonNull(builder.updateExpression, "updateExpression");
+        this.conditionExpression = builder.conditionExpression;
+        this.expressionAttributeValues = Map.copyOf(builder.expressionAttributeValues);
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    public ItemKey key() {
+        return key;
+    }
+
+    public String updateExpression() {
+        return updateExpression;
+    }
+
+    public Optional<String> conditionExpression() {
+        return Optional.ofNullable(conditionExpression);
+    }
+
+    public Map<String, AttributeValue> expressionAttributeValues() {
+        return expressionAttributeValues;
+    }
+
+    public static final class Builder {
+        private byte[] partitionKey;
+        private byte[] sortKey;
+        private String updateExpression;
+        private String conditionExpression;
+        private final Map<String, AttributeValue> expressionAttributeValues = new LinkedHashMap<>();
+
+        private Builder() {}
+
+        public Builder partitionKey(byte[] partitionKey) {
+            this.partitionKey = partitionKey;
+            return this;
+        }
+
+        public Builder partitionKey(String partitionKey) {
+            return partitionKey(ItemKey.utf8(partitionKey));
+        }
+
+        public Builder sortKey(byte[] sortKey) {
+            this.sortKey = sortKey;
+            return this;
+        }
+
+        public Builder sortKey(String sortKey) {
+            return sortKey(ItemKey.utf8(sortKey));
+        }
+
+        public Builder updateExpression(String updateExpression) {
+            this.updateExpression = updateExpression;
+            return this;
+        }
+
+        /** Only update the item if this condition holds for it */
+        public Builder conditionExpression(String conditionExpression) {
+            this.conditionExpression = conditionExpression;
+            return this;
+        }
+
+        public Builder expressionAttributeValues(Map<String, AttributeValue> values) {
+            this.expressionAttributeValues.putAll(values);
+            return this;
+        }
+
+        public Builder expressionAttributeValue(String placeholder, AttributeValue value) {
+            this.expressionAttributeValues.put(placeholder, value);
+            return this;
+        }
+
+        public UpdateItemRequest build() {
+            return new UpdateItemRequest(this);
+        }
+    }
+}
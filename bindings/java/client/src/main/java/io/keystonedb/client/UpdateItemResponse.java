@@ -0,0 +1,16 @@
+package io.keystonedb.client;
+
+import java.util.Map;
+
+/** The item as it is after {@link KeystoneClient#updateItem} */
+public final class UpdateItemResponse {
+    private final Map<String, AttributeValue> attributes;
+
+    UpdateItemResponse(Map<String, AttributeValue> attributes) {
+        this.attributes = Map.copyOf(attributes);
+    }
+
+    public Map<String, AttributeValue> attributes() {
+        return attributes;
+    }
+}
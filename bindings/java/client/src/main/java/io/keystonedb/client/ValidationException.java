@@ -0,0 +1,8 @@
+package io.keystonedb.client;
+
+/** The request was malformed: a bad expression, key or value */
+public class ValidationException extends KeystoneException {
+    public ValidationException(String errorCode, String message, Throwable cause) {
+        super(errorCode, message, cause);
+    }
+}
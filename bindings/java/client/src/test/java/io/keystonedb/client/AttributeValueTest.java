@@ -0,0 +1,46 @@
+package io.keystonedb.client;
+
+import static org.junit.jupiter.api.Assertions.assertArrayEquals;
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertThrows;
+
+import java.util.List;
+import java.util.Map;
+import org.junit.jupiter.api.Test;
+
+class AttributeValueTest {
+    @Test
+    void roundTripsThroughProtobuf() {
+        AttributeValue value = AttributeValue.fromM(Map.of(
+            "name", AttributeValue.fromS("Alice"),
+            "age", AttributeValue.fromN(30),
+            "avatar", AttributeValue.fromB(new byte[] {1, 2, 3}),
+            "active", AttributeValue.fromBool(true),
+            "manager", AttributeValue.nul(),
+            "tags", AttributeValue.fromL(List.of(AttributeValue.fromS("a"), AttributeValue.fromN("1.5"))),
+            "embedding", AttributeValue.fromVector(new float[] {0.5f, 1.0f}),
+            "joined", AttributeValue.fromTimestamp(1_700_000_000_000L)));
+
+        assertEquals(value, Protos.attributeValue(Protos.value(value)));
+    }
+
+    @Test
+    void accessorsCheckTheType() {
+        AttributeValue value = AttributeValue.fromS("text");
+        assertEquals("text", value.s());
+        assertThrows(IllegalStateException.class, value::n);
+    }
+
+    @Test
+    void binaryValuesAreCopied() {
+        byte[] bytes = {1, 2};
+        AttributeValue value = AttributeValue.fromB(bytes);
+        bytes[0] = 9;
+        assertArrayEquals(new byte[] {1, 2}, value.b());
+    }
+
+    @Test
+    void sortKeyConditionsRejectNonKeyTypes() {
+        assertThrows(IllegalArgumentException.class, () -> SortKeyCondition.equalTo(AttributeValue.fromBool(true)));
+    }
+}
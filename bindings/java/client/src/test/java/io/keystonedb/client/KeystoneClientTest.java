@@ -0,0 +1,197 @@
+package io.keystonedb.client;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertFalse;
+import static org.junit.jupiter.api.Assertions.assertThrows;
+import static org.junit.jupiter.api.Assertions.assertTrue;
+
+import com.google.protobuf.ByteString;
+import io.grpc.Metadata;
+import io.grpc.Server;
+import io.grpc.ServerCall;
+import io.grpc.ServerCallHandler;
+import io.grpc.ServerInterceptor;
+import io.grpc.ServerInterceptors;
+import io.grpc.Status;
+import io.grpc.inprocess.InProcessChannelBuilder;
+import io.grpc.inprocess.InProcessServerBuilder;
+import io.grpc.stub.StreamObserver;
+import io.keystonedb.proto.GetRequest;
+import io.keystonedb.proto.GetResponse;
+import io.keystonedb.proto.Item;
+import io.keystonedb.proto.KeystoneDBGrpc;
+import io.keystonedb.proto.LastKey;
+import io.keystonedb.proto.PutRequest;
+import io.keystonedb.proto.PutResponse;
+import io.keystonedb.proto.Value;
+import java.time.Duration;
+import java.util.ArrayList;
+import java.util.HashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.concurrent.atomic.AtomicInteger;
+import org.junit.jupiter.api.AfterEach;
+import org.junit.jupiter.api.BeforeEach;
+import org.junit.jupiter.api.Test;
+
+class KeystoneClientTest {
+    private static final RetryPolicy NO_WAIT = new RetryPolicy(3, Duration.ZERO, Duration.ZERO, 1.0, false);
+
+    /** Stores items by partition key and fails the first {@code failures} calls with UNAVAILABLE */
+    private static final class FakeServer extends KeystoneDBGrpc.KeystoneDBImplBase {
+        final Map<ByteString, Item> items = new HashMap<>();
+        final AtomicInteger failures = new AtomicInteger();
+        final AtomicInteger calls = new AtomicInteger();
+
+        private boolean fail(StreamObserver<?> observer) {
+            calls.incrementAndGet();
+            if (failures.getAndUpdate(n -> Math.max(0, n - 1)) > 0) {
+                observer.onError(Status.UNAVAILABLE.withDescription("restarting").asRuntimeException());
+                return true;
+            }
+            return false;
+        }
+
+        @Override
+        public void put(PutRequest request, StreamObserver<PutResponse> observer) {
+            if (fail(observer)) {
+                return;
+            }
+            if (request.hasConditionExpression() && items.containsKey(request.getPartitionKey())) {
+                observer.onError(Status.FAILED_PRECONDITION.withDescription("exists").asRuntimeException());
+                return;
+            }
+            items.put(request.getPartitionKey(), request.getItem());
+            observer.onNext(PutResponse.newBuilder().setSuccess(true).build());
+            observer.onCompleted();
+        }
+
+        @Override
+        public void get(GetRequest request, StreamObserver<GetResponse> observer) {
+            if (fail(observer)) {
+                return;
+            }
+            GetResponse.Builder response = GetResponse.newBuilder();
+            Item item = items.get(request.getPartitionKey());
+            if (item != null) {
+                response.setItem(item);
+            }
+            observer.onNext(response.build());
+            observer.onCompleted();
+        }
+
+        @Override
+        public void query(io.keystonedb.proto.QueryRequest request, StreamObserver<io.keystonedb.proto.QueryResponse> observer) {
+            // Two pages of one item each, keyed by sort key
+            boolean secondPage = request.hasExclusiveStartKey();
+            String sk = secondPage ? "b" : "a";
+            Item item = Item.newBuilder().putAttributes("sk", Value.newBuilder().setStringValue(sk).build()).build();
+            io.keystonedb.proto.QueryResponse.Builder response =
+                io.keystonedb.proto.QueryResponse.newBuilder().addItems(item).setCount(1).setScannedCount(1);
+            if (!secondPage) {
+                response.setLastEvaluatedKey(LastKey.newBuilder()
+                    .setPartitionKey(request.getPartitionKey())
+                    .setSortKey(ByteString.copyFromUtf8(sk)));
+            }
+            observer.onNext(response.build());
+            observer.onCompleted();
+        }
+    }
+
+    private final FakeServer service = new FakeServer();
+    private final List<Metadata> headers = new ArrayList<>();
+    private Server server;
+    private KeystoneClient client;
+
+    @BeforeEach
+    void start() throws Exception {
+        String name = InProcessServerBuilder.generateName();
+        ServerInterceptor recordHeaders = new ServerInterceptor() {
+            @Override
+            public <Q, R> ServerCall.Listener<Q> interceptCall(
+                ServerCall<Q, R> call, Metadata metadata, ServerCallHandler<Q, R> next) {
+                headers.add(metadata);
+                return next.startCall(call, metadata);
+            }
+        };
+        server = InProcessServerBuilder.forName(name)
+            .directExecutor()
+            .addService(ServerInterceptors.intercept(service, recordHeaders))
+            .build()
+            .start();
+        client = KeystoneClient.builder(() -> InProcessChannelBuilder.forName(name).directExecutor())
+            .poolSize(2)
+            .retryPolicy(NO_WAIT)
+            .token("secret")
+            .database("orders")
+            .build();
+    }
+
+    @AfterEach
+    void stop() {
+        client.close();
+        server.shutdownNow();
+    }
+
+    @Test
+    void putThenGet() {
+        client.putItem(PutItemRequest.builder()
+            .partitionKey("user#alice")
+            .attribute("name", AttributeValue.fromS("Alice"))
+            .attribute("age", AttributeValue.fromN(30))
+            .build());
+
+        GetItemResponse response = client.getItem(GetItemRequest.builder().partitionKey("user#alice").build());
+        assertTrue(response.hasItem());
+        assertEquals(AttributeValue.fromS("Alice"), response.item().get().get("name"));
+        assertEquals("30", response.item().get().get("age").n());
+
+        assertFalse(client.getItem(GetItemRequest.builder().partitionKey("user#bob").build()).hasItem());
+    }
+
+    @Test
+    void retriesUnavailable() {
+        service.failures.set(2);
+        assertFalse(client.getItem(GetItemRequest.builder().partitionKey("user#alice").build()).hasItem());
+        assertEquals(3, service.calls.get());
+    }
+
+    @Test
+    void givesUpAfterMaxAttempts() {
+        service.failures.set(10);
+        KeystoneException error = assertThrows(KeystoneException.class,
+            () -> client.getItem(GetItemRequest.builder().partitionKey("user#alice").build()));
+        assertEquals("UNAVAILABLE", error.errorCode());
+        assertEquals(4, service.calls.get());
+    }
+
+    @Test
+    void mapsFailedConditionsWithoutRetrying() {
+        PutItemRequest request = PutItemRequest.builder()
+            .partitionKey("user#alice")
+            .attribute("name", AttributeValue.fromS("Alice"))
+            .conditionExpression("attribute_not_exists(name)")
+            .build();
+        client.putItem(request);
+
+        assertThrows(ConditionalCheckFailedException.class, () -> client.putItem(request));
+        assertEquals(2, service.calls.get());
+    }
+
+    @Test
+    void sendsTokenAndDatabase() {
+        client.getItem(GetItemRequest.builder().partitionKey("user#alice").build());
+        Metadata metadata = headers.get(0);
+        assertEquals("Bearer secret", metadata.get(KeystoneClient.AUTHORIZATION_HEADER));
+        assertEquals("orders", metadata.get(KeystoneClient.DATABASE_HEADER));
+    }
+
+    @Test
+    void paginatesQueries() {
+        List<String> sortKeys = new ArrayList<>();
+        for (QueryResponse page : client.queryPaginator(QueryRequest.builder().partitionKey("org#acme").limit(1).build())) {
+            page.items().forEach(item -> sortKeys.add(item.get("sk").s()));
+        }
+        assertEquals(List.of("a", "b"), sortKeys);
+    }
+}
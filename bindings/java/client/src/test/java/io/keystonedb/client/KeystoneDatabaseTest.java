@@ -0,0 +1,202 @@
+package io.keystonedb.client;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertFalse;
+import static org.junit.jupiter.api.Assertions.assertNull;
+import static org.junit.jupiter.api.Assertions.assertThrows;
+import static org.junit.jupiter.api.Assertions.assertTrue;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Map;
+import java.util.stream.Collectors;
+import org.junit.jupiter.api.Test;
+import org.junit.jupiter.api.io.TempDir;
+
+/** Runs against the JNI library the {@code buildJni} task builds over the C FFI */
+class KeystoneDatabaseTest {
+    private static PutItemRequest put(String pk, String sk, long n) {
+        PutItemRequest.Builder put = PutItemRequest.builder().partitionKey(pk).attribute("n", AttributeValue.fromN(n));
+        if (sk != null) {
+            put.sortKey(sk);
+        }
+        return put.build();
+    }
+
+    private static long n(Map<String, AttributeValue> item) {
+        return Long.parseLong(item.get("n").n());
+    }
+
+    @Test
+    void putGetDeleteSurviveReopen(@TempDir Path dir) {
+        Path path = dir.resolve("test.keystone");
+        try (KeystoneDatabase db = KeystoneDatabase.create(path)) {
+            db.putItem(PutItemRequest.builder()
+                .partitionKey("user#alice")
+                .attribute("name", AttributeValue.fromS("Alice \u00e9\ud83d\ude00"))
+                .attribute("tags", AttributeValue.fromL(List.of(AttributeValue.fromS("a"), AttributeValue.fromBool(true))))
+                .attribute("address", AttributeValue.fromM(Map.of("city", AttributeValue.fromS("Paris"))))
+                .attribute("nothing", AttributeValue.nul())
+                .build());
+            db.putItem(put("user#bob", null, 1));
+            db.deleteItem(DeleteItemRequest.builder().partitionKey("user#bob").build());
+        }
+        try (KeystoneDatabase db = KeystoneDatabase.open(path)) {
+            Map<String, AttributeValue> item = db.getItem(GetItemRequest.builder().partitionKey("user#alice").build())
+                .item().orElseThrow();
+            assertEquals("Alice \u00e9\ud83d\ude00", item.get("name").s());
+            assertEquals(List.of(AttributeValue.fromS("a"), AttributeValue.fromBool(true)), item.get("tags").l());
+            assertEquals("Paris", item.get("address").m().get("city").s());
+            assertTrue(item.get("nothing").isNul());
+            assertFalse(db.getItem(GetItemRequest.builder().partitionKey("user#bob").build()).hasItem());
+        }
+    }
+
+    @Test
+    void closedDatabaseThrows() {
+        KeystoneDatabase db = KeystoneDatabase.createInMemory();
+        db.close();
+        db.close();
+        assertThrows(KeystoneException.class, () -> db.getItem(GetItemRequest.builder().partitionKey("a").build()));
+    }
+
+    @Test
+    void queryAndScanPaginate() {
+        try (KeystoneDatabase db = KeystoneDatabase.createInMemory()) {
+            for (int i = 0; i < 5; i++) {
+                db.putItem(put("org#acme", "user#" + i, i));
+            }
+            db.putItem(put("org#acme", "team#1", 99));
+            db.putItem(put("org#other", "user#9", 9));
+
+            QueryRequest query = QueryRequest.builder()
+                .partitionKey("org#acme")
+                .sortKeyCondition(SortKeyCondition.beginsWith("user#"))
+                .limit(2)
+                .build();
+            List<Long> seen = new ArrayList<>();
+            for (QueryResponse page : db.queryPaginator(query)) {
+                page.items().forEach(item -> seen.add(n(item)));
+            }
+            assertEquals(List.of(0L, 1L, 2L, 3L, 4L), seen);
+
+            QueryResponse between = db.query(QueryRequest.builder()
+                .partitionKey("org#acme")
+                .sortKeyCondition(SortKeyCondition.between(AttributeValue.fromS("user#1"), AttributeValue.fromS("user#3")))
+                .scanIndexForward(false)
+                .build());
+            assertEquals(List.of(3L, 2L, 1L), between.items().stream().map(KeystoneDatabaseTest::n).collect(Collectors.toList()));
+
+            QueryResponse filtered = db.query(QueryRequest.builder()
+                .partitionKey("org#acme")
+                .filterExpression("n >= :min")
+                .expressionAttributeValue(":min", AttributeValue.fromN(3))
+                .build());
+            assertEquals(3, filtered.count());
+            assertEquals(6, filtered.scannedCount());
+
+            int scanned = 0;
+            for (ScanResponse page : db.scanPaginator(ScanRequest.builder().limit(3).build())) {
+                scanned += page.count();
+            }
+            assertEquals(7, scanned);
+
+            int segments = 0;
+            for (int segment = 0; segment < 2; segment++) {
+                segments += db.scan(ScanRequest.builder().segment(segment, 2).build()).count();
+            }
+            assertEquals(7, segments);
+            assertThrows(ValidationException.class, () -> db.scan(ScanRequest.builder().indexName("by-email").build()));
+        }
+    }
+
+    @Test
+    void conditionalWritesAndUpdates() {
+        try (KeystoneDatabase db = KeystoneDatabase.createInMemory()) {
+            PutItemRequest create = PutItemRequest.builder()
+                .partitionKey("user#bob")
+                .attribute("name", AttributeValue.fromS("Bob"))
+                .conditionExpression("attribute_not_exists(name)")
+                .build();
+            db.putItem(create);
+            ConditionalCheckFailedException error = assertThrows(ConditionalCheckFailedException.class, () -> db.putItem(create));
+            assertEquals("FAILED_PRECONDITION", error.errorCode());
+
+            UpdateItemResponse updated = db.updateItem(UpdateItemRequest.builder()
+                .partitionKey("user#bob")
+                .updateExpression("SET visits = :one")
+                .expressionAttributeValue(":one", AttributeValue.fromN(1))
+                .build());
+            assertEquals("1", updated.attributes().get("visits").n());
+
+            assertThrows(ConditionalCheckFailedException.class, () -> db.deleteItem(DeleteItemRequest.builder()
+                .partitionKey("user#bob")
+                .conditionExpression("visits > :n")
+                .expressionAttributeValue(":n", AttributeValue.fromN(5))
+                .build()));
+            assertThrows(ValidationException.class, () -> db.updateItem(UpdateItemRequest.builder()
+                .partitionKey("user#bob")
+                .updateExpression("SET =")
+                .build()));
+        }
+    }
+
+    @Test
+    void transactionsAndBatches() {
+        try (KeystoneDatabase db = KeystoneDatabase.createInMemory()) {
+            db.batchWriteItem(BatchWriteItemRequest.builder()
+                .put(ItemKey.of("account#a"), Map.of("balance", AttributeValue.fromN(100)))
+                .put(ItemKey.of("account#b"), Map.of("balance", AttributeValue.fromN(0)))
+                .put(ItemKey.of("org#acme", "meta"), Map.of("name", AttributeValue.fromS("Acme")))
+                .build());
+            assertEquals(2, db.batchGetItem(List.of(ItemKey.of("org#acme", "meta"), ItemKey.of("account#a"), ItemKey.of("missing"))).size());
+
+            TransactWriteItemsRequest overdraft = transfer(500);
+            assertThrows(TransactionCanceledException.class, () -> db.transactWriteItems(overdraft));
+            db.transactWriteItems(transfer(30));
+
+            List<Map<String, AttributeValue>> items =
+                db.transactGetItems(List.of(ItemKey.of("account#a"), ItemKey.of("missing"), ItemKey.of("account#b")));
+            assertEquals(70, n(Map.of("n", items.get(0).get("balance"))));
+            assertNull(items.get(1));
+            assertEquals(30, n(Map.of("n", items.get(2).get("balance"))));
+        }
+    }
+
+    private static TransactWriteItemsRequest transfer(long amount) {
+        return TransactWriteItemsRequest.builder()
+            .update(UpdateItemRequest.builder()
+                .partitionKey("account#a")
+                .updateExpression("SET balance = balance - :amount")
+                .conditionExpression("balance >= :amount")
+                .expressionAttributeValue(":amount", AttributeValue.fromN(amount))
+                .build())
+            .update(UpdateItemRequest.builder()
+                .partitionKey("account#b")
+                .updateExpression("SET balance = balance + :amount")
+                .build())
+            .conditionCheck(ItemKey.of("org#acme", "meta"), "attribute_exists(name)")
+            .build();
+    }
+
+    @Test
+    void executeStatement() {
+        try (KeystoneDatabase db = KeystoneDatabase.createInMemory()) {
+            ExecuteStatementResponse insert = db.executeStatement(
+                "INSERT INTO items VALUE {'pk': ?, 'name': ?}", List.of(AttributeValue.fromS("stmt#1"), AttributeValue.fromS("Ada")));
+            assertEquals(ExecuteStatementResponse.Kind.INSERT, insert.kind());
+
+            ExecuteStatementResponse select = db.executeStatement("SELECT * FROM items WHERE pk = 'stmt#1'");
+            assertEquals(ExecuteStatementResponse.Kind.SELECT, select.kind());
+            assertEquals("Ada", select.items().get(0).get("name").s());
+
+            ExecuteStatementResponse update = db.executeStatement("UPDATE items SET name = 'Grace' WHERE pk = 'stmt#1'");
+            assertEquals("Grace", update.item().orElseThrow().get("name").s());
+
+            assertEquals(ExecuteStatementResponse.Kind.DELETE,
+                db.executeStatement("DELETE FROM items WHERE pk = 'stmt#1'").kind());
+            assertThrows(ValidationException.class, () -> db.executeStatement("SELEC"));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* auto-generated by NAPI-RS */
+
+/** The key of one item: `{ pk, sk? }` */
+export interface ItemKey {
+  pk: string | Buffer
+  sk?: string | Buffer
+}
+/** The key of the last item examined, to pass back as `startAfter` */
+export interface LastKey {
+  pk: Buffer
+  sk?: Buffer
+}
+/** The `code` of every error thrown by a database call */
+export const enum ErrorCode {
+  /** Any other database error */
+  KeystoneError = 'KeystoneError',
+  /** A table, index or other named resource doesn't exist */
+  NotFound = 'NotFound',
+  /** A bad expression, statement, key or value */
+  InvalidArgument = 'InvalidArgument',
+  /** A condition expression evaluated to false */
+  ConditionalCheckFailed = 'ConditionalCheckFailed',
+  /** A transaction was canceled; no part of it was applied */
+  TransactionCanceled = 'TransactionCanceled',
+  /** The database being created already exists */
+  AlreadyExists = 'AlreadyExists',
+  /** A quota or limit was exceeded */
+  ResourceExhausted = 'ResourceExhausted',
+  /** Reading or writing the database files failed */
+  IoError = 'IoError'
+}
+/** One page of a query or scan */
+export interface Page {
+  items: Array<Record<string, any>>
+  /** Number of items returned */
+  count: number
+  /** Number of items examined, before the filter */
+  scannedCount: number
+  /**
+   * Pass to `startAfter` to fetch the next page; a page may carry a
+   * last key even when nothing follows
+   */
+  lastKey?: LastKey
+}
+/**
+ * A query of one partition, built by chaining:
+ * `new Query(pk).skBeginsWith('user#').limit(10)`
+ *
+ * Setting a sort key condition replaces the previous one.
+ */
+export class Query {
+  /** Query the partition `pk` (of the table, or of `index` if set) */
+  constructor(pk: string | Buffer)
+  /** Only the item whose sort key equals `sk` */
+  skEq(sk: string | Buffer): this
+  /** Items whose sort key sorts before `sk` */
+  skLt(sk: string | Buffer): this
+  /** Items whose sort key sorts before or at `sk` */
+  skLte(sk: string | Buffer): this
+  /** Items whose sort key sorts after `sk` */
+  skGt(sk: string | Buffer): this
+  /** Items whose sort key sorts at or after `sk` */
+  skGte(sk: string | Buffer): this
+  /** Items whose sort key lies between `low` and `high`, inclusive */
+  skBetween(low: string | Buffer, high: string | Buffer): this
+  /** Items whose sort key starts with `prefix` */
+  skBeginsWith(prefix: string | Buffer): this
+  /** Sort key order: ascending (the default) or descending */
+  forward(forward: boolean): this
+  /** Evaluate at most `limit` items per page; items the filter drops count too */
+  limit(limit: number): this
+  /** Query a secondary index instead of the table */
+  index(name: string): this
+  /** Keep only items matching a filter expression */
+  filter(expression: string): this
+  /** Bind a `:placeholder` value used by the filter */
+  value(placeholder: string, value: any): this
+  /** Bind a `#placeholder` attribute name used by the filter */
+  name(placeholder: string, name: string): this
+  /** Continue after the given key (a page's `lastKey`); `null` starts over */
+  startAfter(key?: ItemKey | undefined | null): this
+}
+/**
+ * A scan of the whole table, built by chaining:
+ * `new Scan().pkPrefix('user#').limit(100)`
+ */
+export class Scan {
+  /** Scan every item of the table */
+  constructor()
+  /** Evaluate at most `limit` items per page; items the filter drops count too */
+  limit(limit: number): this
+  /** Only items whose partition key starts with `prefix` */
+  pkPrefix(prefix: string | Buffer): this
+  /** Scan one of `totalSegments` disjoint parts, for parallel workers */
+  segment(segment: number, totalSegments: number): this
+  /** Keep only items matching a filter expression */
+  filter(expression: string): this
+  /** Bind a `:placeholder` value used by the filter */
+  value(placeholder: string, value: any): this
+  /** Bind a `#placeholder` attribute name used by the filter */
+  name(placeholder: string, name: string): this
+  /** Continue after the given key (a page's `lastKey`); `null` starts over */
+  startAfter(key?: ItemKey | undefined | null): this
+}
+/** Options of `put` and `delete` */
+export interface WriteOptions {
+  /** Sort key of the item */
+  sk?: string | Buffer
+  /**
+   * Write only if this condition holds for the current item; throws
+   * `ConditionalCheckFailed` otherwise
+   */
+  condition?: string
+  /** `:placeholder` values used by the condition */
+  values?: Record<string, any>
+  /** `#placeholder` attribute names used by the condition */
+  names?: Record<string, string>
+}
+/** Options of `update` */
+export interface UpdateOptions {
+  /** Sort key of the item */
+  sk?: string | Buffer
+  /** Update only if this condition holds for the current item */
+  condition?: string
+  /** `:placeholder` values used by the expression and condition */
+  values?: Record<string, any>
+  /** `#placeholder` attribute names used by the expression and condition */
+  names?: Record<string, string>
+}
+/** Placeholders shared by every expression of a transaction */
+export interface TransactOptions {
+  values?: Record<string, any>
+  names?: Record<string, string>
+}
+/** Store an item */
+export interface TransactPut {
+  pk: string | Buffer
+  sk?: string | Buffer
+  item: Record<string, any>
+  condition?: string
+}
+/** Apply an update expression */
+export interface TransactUpdate {
+  pk: string | Buffer
+  sk?: string | Buffer
+  expression: string
+  condition?: string
+}
+/** Delete an item */
+export interface TransactDelete {
+  pk: string | Buffer
+  sk?: string | Buffer
+  condition?: string
+}
+/** Require a condition to hold without writing the item */
+export interface TransactConditionCheck {
+  pk: string | Buffer
+  sk?: string | Buffer
+  condition: string
+}
+/** One operation of `transactWrite`; set exactly one member */
+export interface TransactOperation {
+  put?: TransactPut
+  update?: TransactUpdate
+  delete?: TransactDelete
+  conditionCheck?: TransactConditionCheck
+}
+/**
+ * Result of `executeStatement`; the members set depend on `kind`:
+ *
+ * - `select`: `items`, `count`, `scannedCount`, `lastKey`, `warnings`
+ * - `update`: `item`, the item after the update
+ * - `insert`, `delete`: `success`
+ * - `insertSelect`: `inserted`
+ * - `explain`: `plan`
+ */
+export interface StatementResult {
+  kind: 'select' | 'update' | 'insert' | 'delete' | 'insertSelect' | 'explain'
+  items?: Array<Record<string, any>>
+  count?: number
+  scannedCount?: number
+  lastKey?: LastKey
+  warnings?: Array<string>
+  item?: Record<string, any>
+  success?: boolean
+  inserted?: number
+  plan?: any
+}
+/**
+ * An open KeystoneDB database
+ *
+ * Call `close()` when done: the garbage collector may keep the database
+ * (and its lock) open long after the last reference is gone.
+ */
+export class Database {
+  /** Create a new database directory at `path` */
+  static create(path: string): Database
+  /** Open an existing database directory at `path` */
+  static open(path: string): Database
+  /** Create a database that lives only in memory */
+  static createInMemory(): Database
+  /** Store an item, optionally under a sort key and only if a condition holds */
+  put(pk: string | Buffer, item: Record<string, any>, options?: WriteOptions | undefined | null): void
+  /** Get an item, or null if missing */
+  get(pk: string | Buffer, sk?: string | Buffer | undefined | null): Record<string, any> | null
+  /**
+   * Delete an item, optionally only if a condition holds; deleting a
+   * missing item succeeds
+   */
+  delete(pk: string | Buffer, options?: WriteOptions | undefined | null): void
+  /** Flush memtables to disk */
+  flush(): void
+  /**
+   * Flush and close the database, releasing its lock; later calls throw
+   *
+   * Closing twice is harmless.
+   */
+  close(): void
+  /**
+   * Fetch one page of a query; pass `lastKey` to `query.startAfter` for
+   * the next
+   */
+  query(query: Query): Page
+  /**
+   * Fetch one page of a scan; pass `lastKey` to `scan.startAfter` for
+   * the next
+   */
+  scan(scan: Scan): Page
+  /**
+   * Apply an update expression and return the updated item
+   *
+   * ```js
+   * db.update('user#1', 'SET visits = visits + :one', { values: { ':one': 1 } })
+   * ```
+   */
+  update(pk: string | Buffer, expression: string, options?: UpdateOptions | undefined | null): Record<string, any>
+  /**
+   * Apply writes atomically and return how many were committed
+   *
+   * Throws `TransactionCanceled` if any condition fails, in which case
+   * nothing is written.
+   */
+  transactWrite(operations: Array<TransactOperation>, options?: TransactOptions | undefined | null): number
+  /** Read several items consistently, in order; null for each missing item */
+  transactGet(keys: Array<ItemKey>): Array<Record<string, any> | undefined | null>
+  /**
+   * Run a PartiQL statement
+   *
+   * `params` binds `?` placeholders in order when an array and `:name`
+   * placeholders when an object.
+   */
+  executeStatement(sql: string, params?: Array<any> | Record<string, any>): StatementResult
+}
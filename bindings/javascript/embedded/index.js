@@ -0,0 +1,37 @@
+/* Loads the native addon built by `napi build --platform` */
+
+const { existsSync } = require('fs')
+const { join } = require('path')
+
+function abi() {
+  if (process.platform !== 'linux') return ''
+  const report = typeof process.report?.getReport === 'function' ? process.report.getReport() : null
+  return report && !report.header.glibcVersionRuntime ? '-musl' : '-gnu'
+}
+
+function load() {
+  const candidates = [
+    `keystonedb.${process.platform}-${process.arch}${abi()}.node`,
+    `keystonedb.${process.platform}-${process.arch}.node`,
+    'keystonedb.node',
+  ]
+  for (const file of candidates) {
+    const path = join(__dirname, file)
+    if (existsSync(path)) return require(path)
+  }
+  try {
+    return require(`@keystonedb/embedded-${process.platform}-${process.arch}${abi()}`)
+  } catch (e) {
+    throw new Error(
+      `No KeystoneDB native addon for ${process.platform}-${process.arch}; build it with \`npm run build\``,
+      { cause: e },
+    )
+  }
+}
+
+const native = load()
+
+module.exports.Database = native.Database
+module.exports.ErrorCode = native.ErrorCode
+module.exports.Query = native.Query
+module.exports.Scan = native.Scan
@@ -0,0 +1,103 @@
+/// Conversion between JS values and KeystoneDB values
+///
+/// Items cross as plain objects in the JSON shape of `kstone_api::json`:
+/// strings, numbers, booleans, null, arrays and nested objects. Binary
+/// values come back as base64 strings, vectors as arrays of numbers and
+/// timestamps as milliseconds since the epoch.
+
+use bytes::Bytes;
+use kstone_api::json::json_to_value;
+use kstone_api::item_to_json;
+use kstone_core::expression::ExpressionContext;
+use kstone_core::{Item, Value};
+use napi::bindgen_prelude::{Buffer, Either};
+use napi_derive::napi;
+use serde_json::{Map, Value as Json};
+use std::collections::HashMap;
+
+/// The bytes of a partition or sort key given as a string (UTF-8 encoded)
+/// or a Buffer
+pub fn key_bytes(key: Either<String, Buffer>) -> Vec<u8> {
+    match key {
+        Either::A(text) => text.into_bytes(),
+        Either::B(bytes) => bytes.to_vec(),
+    }
+}
+
+/// The key of one item: `{ pk, sk? }`
+#[napi(object)]
+pub struct ItemKey {
+    pub pk: Either<String, Buffer>,
+    pub sk: Option<Either<String, Buffer>>,
+}
+
+impl ItemKey {
+    pub fn into_key(self) -> kstone_core::Key {
+        let pk = Bytes::from(key_bytes(self.pk));
+        match self.sk {
+            Some(sk) => kstone_core::Key::with_sk(pk, Bytes::from(key_bytes(sk))),
+            None => kstone_core::Key::new(pk),
+        }
+    }
+
+    /// The key as owned `(pk, sk)` bytes, as taken by `start_after`
+    pub fn into_bytes(self) -> (Vec<u8>, Option<Vec<u8>>) {
+        (key_bytes(self.pk), self.sk.map(key_bytes))
+    }
+}
+
+/// The key of the last item examined, to pass back as `startAfter`
+#[napi(object)]
+pub struct LastKey {
+    pub pk: Buffer,
+    pub sk: Option<Buffer>,
+}
+
+pub fn last_key(key: Option<(Bytes, Option<Bytes>)>) -> Option<LastKey> {
+    key.map(|(pk, sk)| LastKey {
+        pk: pk.to_vec().into(),
+        sk: sk.map(|sk| sk.to_vec().into()),
+    })
+}
+
+/// An item from a JS object
+pub fn to_item(object: Map<String, Json>) -> Item {
+    object
+        .into_iter()
+        .map(|(name, value)| (name, json_to_value(value)))
+        .collect()
+}
+
+/// An item as a JS object
+pub fn from_item(item: &Item) -> Map<String, Json> {
+    match item_to_json(item) {
+        Json::Object(object) => object,
+        _ => unreachable!("items convert to JSON objects"),
+    }
+}
+
+/// Placeholder values, e.g. `{ ":min": 18 }`
+pub fn placeholder_values(values: Option<Map<String, Json>>) -> Vec<(String, Value)> {
+    values
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(placeholder, value)| (placeholder, json_to_value(value)))
+        .collect()
+}
+
+/// Placeholder attribute names, e.g. `{ "#status": "status" }`
+pub fn placeholder_names(names: Option<HashMap<String, String>>) -> Vec<(String, String)> {
+    names.unwrap_or_default().into_iter().collect()
+}
+
+/// An expression context holding the given placeholders
+pub fn expression_context(values: Option<Map<String, Json>>, names: Option<HashMap<String, String>>) -> ExpressionContext {
+    let mut context = ExpressionContext::new();
+    for (placeholder, value) in placeholder_values(values) {
+        context = context.with_value(placeholder, value);
+    }
+    for (placeholder, name) in placeholder_names(names) {
+        context = context.with_name(placeholder, name);
+    }
+    context
+}
@@ -0,0 +1,69 @@
+/// Errors thrown by the binding
+///
+/// Every error is a JS `Error` whose `code` is an `ErrorCode`, so callers
+/// can branch on `err.code` instead of parsing messages.
+
+use kstone_api::KeystoneError;
+use napi_derive::napi;
+
+/// The `code` of every error thrown by a database call
+#[napi(string_enum)]
+#[derive(Debug)]
+pub enum ErrorCode {
+    /// Any other database error
+    KeystoneError,
+    /// A table, index or other named resource doesn't exist
+    NotFound,
+    /// A bad expression, statement, key or value
+    InvalidArgument,
+    /// A condition expression evaluated to false
+    ConditionalCheckFailed,
+    /// A transaction was canceled; no part of it was applied
+    TransactionCanceled,
+    /// The database being created already exists
+    AlreadyExists,
+    /// A quota or limit was exceeded
+    ResourceExhausted,
+    /// Reading or writing the database files failed
+    IoError,
+}
+
+impl AsRef<str> for ErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            ErrorCode::KeystoneError => "KeystoneError",
+            ErrorCode::NotFound => "NotFound",
+            ErrorCode::InvalidArgument => "InvalidArgument",
+            ErrorCode::ConditionalCheckFailed => "ConditionalCheckFailed",
+            ErrorCode::TransactionCanceled => "TransactionCanceled",
+            ErrorCode::AlreadyExists => "AlreadyExists",
+            ErrorCode::ResourceExhausted => "ResourceExhausted",
+            ErrorCode::IoError => "IoError",
+        }
+    }
+}
+
+pub type Error = napi::Error<ErrorCode>;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The JS error for an engine error
+pub fn to_js_err(error: KeystoneError) -> Error {
+    let code = match &error {
+        KeystoneError::Io(_) => ErrorCode::IoError,
+        KeystoneError::NotFound(_) => ErrorCode::NotFound,
+        KeystoneError::InvalidArgument(_) | KeystoneError::InvalidExpression(_) | KeystoneError::InvalidQuery(_) => {
+            ErrorCode::InvalidArgument
+        }
+        KeystoneError::ConditionalCheckFailed(_) => ErrorCode::ConditionalCheckFailed,
+        KeystoneError::TransactionCanceled(_) => ErrorCode::TransactionCanceled,
+        KeystoneError::AlreadyExists(_) => ErrorCode::AlreadyExists,
+        KeystoneError::ResourceExhausted(_) => ErrorCode::ResourceExhausted,
+        _ => ErrorCode::KeystoneError,
+    };
+    Error::new(code, error.to_string())
+}
+
+/// An `InvalidArgument` error for a bad call from JS
+pub fn invalid(message: impl Into<String>) -> Error {
+    Error::new(ErrorCode::InvalidArgument, message.into())
+}
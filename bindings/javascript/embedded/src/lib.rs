@@ -0,0 +1,473 @@
+/// Embedded KeystoneDB for Node.js
+///
+/// The native half of the `@keystonedb/embedded` package. Calls are
+/// synchronous; items are plain objects (see `convert`), keys are strings
+/// or Buffers, and errors carry an `ErrorCode` in `err.code`.
+
+mod convert;
+mod errors;
+mod requests;
+
+use convert::{expression_context, from_item, key_bytes, last_key, to_item, ItemKey, LastKey};
+use errors::{invalid, to_js_err, Error, Result};
+use kstone_api::json::json_to_value;
+use kstone_api::{
+    ExecuteStatementRequest, ExecuteStatementResponse, TransactGetRequest, TransactWriteOp, TransactWriteRequest,
+    Update,
+};
+use napi::bindgen_prelude::{Buffer, Either};
+use napi_derive::napi;
+use requests::{Page, Query, Scan};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub use errors::ErrorCode;
+
+/// Options of `put` and `delete`
+#[napi(object)]
+pub struct WriteOptions {
+    /// Sort key of the item
+    pub sk: Option<Either<String, Buffer>>,
+    /// Write only if this condition holds for the current item; throws
+    /// `ConditionalCheckFailed` otherwise
+    pub condition: Option<String>,
+    /// `:placeholder` values used by the condition
+    pub values: Option<Map<String, Value>>,
+    /// `#placeholder` attribute names used by the condition
+    pub names: Option<HashMap<String, String>>,
+}
+
+/// Options of `update`
+#[napi(object)]
+pub struct UpdateOptions {
+    /// Sort key of the item
+    pub sk: Option<Either<String, Buffer>>,
+    /// Update only if this condition holds for the current item
+    pub condition: Option<String>,
+    /// `:placeholder` values used by the expression and condition
+    pub values: Option<Map<String, Value>>,
+    /// `#placeholder` attribute names used by the expression and condition
+    pub names: Option<HashMap<String, String>>,
+}
+
+/// Placeholders shared by every expression of a transaction
+#[napi(object)]
+pub struct TransactOptions {
+    pub values: Option<Map<String, Value>>,
+    pub names: Option<HashMap<String, String>>,
+}
+
+/// Store an item
+#[napi(object)]
+pub struct TransactPut {
+    pub pk: Either<String, Buffer>,
+    pub sk: Option<Either<String, Buffer>>,
+    pub item: Map<String, Value>,
+    pub condition: Option<String>,
+}
+
+/// Apply an update expression
+#[napi(object)]
+pub struct TransactUpdate {
+    pub pk: Either<String, Buffer>,
+    pub sk: Option<Either<String, Buffer>>,
+    pub expression: String,
+    pub condition: Option<String>,
+}
+
+/// Delete an item
+#[napi(object)]
+pub struct TransactDelete {
+    pub pk: Either<String, Buffer>,
+    pub sk: Option<Either<String, Buffer>>,
+    pub condition: Option<String>,
+}
+
+/// Require a condition to hold without writing the item
+#[napi(object)]
+pub struct TransactConditionCheck {
+    pub pk: Either<String, Buffer>,
+    pub sk: Option<Either<String, Buffer>>,
+    pub condition: String,
+}
+
+/// One operation of `transactWrite`; set exactly one member
+#[napi(object)]
+pub struct TransactOperation {
+    pub put: Option<TransactPut>,
+    pub update: Option<TransactUpdate>,
+    pub delete: Option<TransactDelete>,
+    pub condition_check: Option<TransactConditionCheck>,
+}
+
+impl TransactOperation {
+    fn into_op(self) -> Result<TransactWriteOp> {
+        let key = |pk, sk| ItemKey { pk, sk }.into_key();
+        let op = match self {
+            TransactOperation {
+                put: Some(put),
+                update: None,
+                delete: None,
+                condition_check: None,
+            } => TransactWriteOp::Put {
+                key: key(put.pk, put.sk),
+                item: to_item(put.item),
+                condition: put.condition,
+            },
+            TransactOperation {
+                put: None,
+                update: Some(update),
+                delete: None,
+                condition_check: None,
+            } => TransactWriteOp::Update {
+                key: key(update.pk, update.sk),
+                update_expression: update.expression,
+                condition: update.condition,
+            },
+            TransactOperation {
+                put: None,
+                update: None,
+                delete: Some(delete),
+                condition_check: None,
+            } => TransactWriteOp::Delete {
+                key: key(delete.pk, delete.sk),
+                condition: delete.condition,
+            },
+            TransactOperation {
+                put: None,
+                update: None,
+                delete: None,
+                condition_check: Some(check),
+            } => TransactWriteOp::ConditionCheck {
+                key: key(check.pk, check.sk),
+                condition: check.condition,
+            },
+            _ => {
+                return Err(invalid(
+                    "Each transaction operation needs exactly one of put, update, delete or conditionCheck",
+                ))
+            }
+        };
+        Ok(op)
+    }
+}
+
+/// Result of `executeStatement`; the members set depend on `kind`:
+///
+/// - `select`: `items`, `count`, `scannedCount`, `lastKey`, `warnings`
+/// - `update`: `item`, the item after the update
+/// - `insert`, `delete`: `success`
+/// - `insertSelect`: `inserted`
+/// - `explain`: `plan`
+#[napi(object)]
+pub struct StatementResult {
+    #[napi(ts_type = "'select' | 'update' | 'insert' | 'delete' | 'insertSelect' | 'explain'")]
+    pub kind: String,
+    pub items: Option<Vec<Map<String, Value>>>,
+    pub count: Option<u32>,
+    pub scanned_count: Option<u32>,
+    pub last_key: Option<LastKey>,
+    pub warnings: Option<Vec<String>>,
+    pub item: Option<Map<String, Value>>,
+    pub success: Option<bool>,
+    pub inserted: Option<u32>,
+    pub plan: Option<Value>,
+}
+
+impl StatementResult {
+    fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            items: None,
+            count: None,
+            scanned_count: None,
+            last_key: None,
+            warnings: None,
+            item: None,
+            success: None,
+            inserted: None,
+            plan: None,
+        }
+    }
+}
+
+/// An open KeystoneDB database
+///
+/// Call `close()` when done: the garbage collector may keep the database
+/// (and its lock) open long after the last reference is gone.
+#[napi]
+pub struct Database {
+    /// None once closed
+    db: Option<kstone_api::Database>,
+}
+
+impl Database {
+    /// The open database, or a `KeystoneError` once closed
+    fn inner(&self) -> Result<&kstone_api::Database> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| Error::new(ErrorCode::KeystoneError, "Database is closed".to_string()))
+    }
+}
+
+#[napi]
+impl Database {
+    /// Create a new database directory at `path`
+    #[napi(factory)]
+    pub fn create(path: String) -> Result<Self> {
+        let db = kstone_api::Database::create(path).map_err(to_js_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Open an existing database directory at `path`
+    #[napi(factory)]
+    pub fn open(path: String) -> Result<Self> {
+        let db = kstone_api::Database::open(path).map_err(to_js_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Create a database that lives only in memory
+    #[napi(factory)]
+    pub fn create_in_memory() -> Result<Self> {
+        let db = kstone_api::Database::create_in_memory().map_err(to_js_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Store an item, optionally under a sort key and only if a condition holds
+    #[napi]
+    pub fn put(&self, pk: Either<String, Buffer>, item: Map<String, Value>, options: Option<WriteOptions>) -> Result<()> {
+        let db = self.inner()?;
+        let pk = key_bytes(pk);
+        let item = to_item(item);
+        let WriteOptions {
+            sk,
+            condition,
+            values,
+            names,
+        } = options.unwrap_or(WriteOptions {
+            sk: None,
+            condition: None,
+            values: None,
+            names: None,
+        });
+        let sk = sk.map(key_bytes);
+        match (sk, condition) {
+            (None, None) => db.put(&pk, item),
+            (Some(sk), None) => db.put_with_sk(&pk, &sk, item),
+            (None, Some(condition)) => {
+                db.put_conditional(&pk, item, &condition, expression_context(values, names))
+            }
+            (Some(sk), Some(condition)) => {
+                db.put_conditional_with_sk(&pk, &sk, item, &condition, expression_context(values, names))
+            }
+        }
+        .map_err(to_js_err)
+    }
+
+    /// Get an item, or null if missing
+    #[napi]
+    pub fn get(&self, pk: Either<String, Buffer>, sk: Option<Either<String, Buffer>>) -> Result<Option<Map<String, Value>>> {
+        let db = self.inner()?;
+        let pk = key_bytes(pk);
+        let item = match sk.map(key_bytes) {
+            Some(sk) => db.get_with_sk(&pk, &sk),
+            None => db.get(&pk),
+        }
+        .map_err(to_js_err)?;
+        Ok(item.as_ref().map(from_item))
+    }
+
+    /// Delete an item, optionally only if a condition holds; deleting a
+    /// missing item succeeds
+    #[napi]
+    pub fn delete(&self, pk: Either<String, Buffer>, options: Option<WriteOptions>) -> Result<()> {
+        let db = self.inner()?;
+        let pk = key_bytes(pk);
+        let WriteOptions {
+            sk,
+            condition,
+            values,
+            names,
+        } = options.unwrap_or(WriteOptions {
+            sk: None,
+            condition: None,
+            values: None,
+            names: None,
+        });
+        let sk = sk.map(key_bytes);
+        match (sk, condition) {
+            (None, None) => db.delete(&pk),
+            (Some(sk), None) => db.delete_with_sk(&pk, &sk),
+            (None, Some(condition)) => {
+                db.delete_conditional(&pk, &condition, expression_context(values, names))
+            }
+            (Some(sk), Some(condition)) => {
+                db.delete_conditional_with_sk(&pk, &sk, &condition, expression_context(values, names))
+            }
+        }
+        .map_err(to_js_err)
+    }
+
+    /// Flush memtables to disk
+    #[napi]
+    pub fn flush(&self) -> Result<()> {
+        self.inner()?.flush().map_err(to_js_err)
+    }
+
+    /// Flush and close the database, releasing its lock; later calls throw
+    ///
+    /// Closing twice is harmless.
+    #[napi]
+    pub fn close(&mut self) -> Result<()> {
+        if let Some(db) = self.db.take() {
+            db.flush().map_err(to_js_err)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch one page of a query; pass `lastKey` to `query.startAfter` for
+    /// the next
+    #[napi]
+    pub fn query(&self, query: &Query) -> Result<Page> {
+        let response = self.inner()?.query(query.build()).map_err(to_js_err)?;
+        Ok(Page::new(&response.items, response.scanned_count, response.last_key))
+    }
+
+    /// Fetch one page of a scan; pass `lastKey` to `scan.startAfter` for
+    /// the next
+    #[napi]
+    pub fn scan(&self, scan: &Scan) -> Result<Page> {
+        let response = self.inner()?.scan(scan.build()?).map_err(to_js_err)?;
+        Ok(Page::new(&response.items, response.scanned_count, response.last_key))
+    }
+
+    /// Apply an update expression and return the updated item
+    ///
+    /// ```js
+    /// db.update('user#1', 'SET visits = visits + :one', { values: { ':one': 1 } })
+    /// ```
+    #[napi]
+    pub fn update(&self, pk: Either<String, Buffer>, expression: String, options: Option<UpdateOptions>) -> Result<Map<String, Value>> {
+        let pk = key_bytes(pk);
+        let UpdateOptions {
+            sk,
+            condition,
+            values,
+            names,
+        } = options.unwrap_or(UpdateOptions {
+            sk: None,
+            condition: None,
+            values: None,
+            names: None,
+        });
+        let mut update = match sk.map(key_bytes) {
+            Some(sk) => Update::with_sk(&pk, &sk),
+            None => Update::new(&pk),
+        }
+        .expression(expression);
+        if let Some(condition) = condition {
+            update = update.condition(condition);
+        }
+        for (placeholder, value) in convert::placeholder_values(values) {
+            update = update.value(placeholder, value);
+        }
+        for (placeholder, name) in convert::placeholder_names(names) {
+            update = update.name(placeholder, name);
+        }
+
+        let response = self.inner()?.update(update).map_err(to_js_err)?;
+        Ok(from_item(&response.item))
+    }
+
+    /// Apply writes atomically and return how many were committed
+    ///
+    /// Throws `TransactionCanceled` if any condition fails, in which case
+    /// nothing is written.
+    #[napi]
+    pub fn transact_write(&self, operations: Vec<TransactOperation>, options: Option<TransactOptions>) -> Result<u32> {
+        let mut request = TransactWriteRequest::new();
+        for op in operations {
+            request.operations.push(op.into_op()?);
+        }
+        if let Some(options) = options {
+            for (placeholder, value) in convert::placeholder_values(options.values) {
+                request = request.value(placeholder, value);
+            }
+            for (placeholder, name) in convert::placeholder_names(options.names) {
+                request = request.name(placeholder, name);
+            }
+        }
+
+        let response = self.inner()?.transact_write(request).map_err(to_js_err)?;
+        Ok(response.committed_count as u32)
+    }
+
+    /// Read several items consistently, in order; null for each missing item
+    #[napi]
+    pub fn transact_get(&self, keys: Vec<ItemKey>) -> Result<Vec<Option<Map<String, Value>>>> {
+        let mut request = TransactGetRequest::new();
+        request.keys = keys.into_iter().map(ItemKey::into_key).collect();
+        let response = self.inner()?.transact_get(request).map_err(to_js_err)?;
+        Ok(response.items.iter().map(|item| item.as_ref().map(from_item)).collect())
+    }
+
+    /// Run a PartiQL statement
+    ///
+    /// `params` binds `?` placeholders in order when an array and `:name`
+    /// placeholders when an object.
+    #[napi(ts_args_type = "sql: string, params?: Array<any> | Record<string, any>")]
+    pub fn execute_statement(&self, sql: String, params: Option<Value>) -> Result<StatementResult> {
+        let mut request = ExecuteStatementRequest::new(sql);
+        match params {
+            None | Some(Value::Null) => {}
+            Some(Value::Array(values)) => {
+                request = request.with_parameters(values.into_iter().map(json_to_value).collect());
+            }
+            Some(Value::Object(named)) => {
+                for (name, value) in named {
+                    request = request.with_named_parameter(name.trim_start_matches(':'), json_to_value(value));
+                }
+            }
+            Some(_) => return Err(invalid("params must be an array or an object")),
+        }
+
+        let response = self.inner()?.execute(request).map_err(to_js_err)?;
+        let result = match response {
+            ExecuteStatementResponse::Select {
+                items,
+                scanned_count,
+                last_key: last,
+                warnings,
+                ..
+            } => StatementResult {
+                items: Some(items.iter().map(from_item).collect()),
+                count: Some(items.len() as u32),
+                scanned_count: Some(scanned_count as u32),
+                last_key: last_key(last),
+                warnings: Some(warnings),
+                ..StatementResult::new("select")
+            },
+            ExecuteStatementResponse::Update { item } => StatementResult {
+                item: Some(from_item(&item)),
+                ..StatementResult::new("update")
+            },
+            ExecuteStatementResponse::Insert { success } => StatementResult {
+                success: Some(success),
+                ..StatementResult::new("insert")
+            },
+            ExecuteStatementResponse::Delete { success } => StatementResult {
+                success: Some(success),
+                ..StatementResult::new("delete")
+            },
+            ExecuteStatementResponse::InsertSelect { inserted } => StatementResult {
+                inserted: Some(inserted as u32),
+                ..StatementResult::new("insertSelect")
+            },
+            ExecuteStatementResponse::Explain { plan } => StatementResult {
+                plan: Some(serde_json::to_value(&plan).map_err(|e| invalid(e.to_string()))?),
+                ..StatementResult::new("explain")
+            },
+        };
+        Ok(result)
+    }
+}
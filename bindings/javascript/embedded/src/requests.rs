@@ -0,0 +1,342 @@
+/// Query and scan builders
+///
+/// `Query` and `Scan` are JS classes whose methods return the builder, so
+/// calls chain. They hold owned options and are turned into a fresh
+/// `kstone_api` request on every call, so one builder can fetch page after
+/// page:
+///
+/// ```js
+/// const query = new Query('org#acme').skBeginsWith('user#').limit(100)
+/// let page = db.query(query)
+/// while (page.lastKey) {
+///   page = db.query(query.startAfter(page.lastKey))
+/// }
+/// ```
+
+use crate::convert::{from_item, key_bytes, last_key, ItemKey, LastKey};
+use crate::errors::{invalid, Result};
+use bytes::Bytes;
+use kstone_api::json::json_to_value;
+use kstone_core::{Item, Value};
+use napi::bindgen_prelude::{Buffer, Either};
+use napi_derive::napi;
+use serde_json::Map;
+
+type OwnedKey = (Vec<u8>, Option<Vec<u8>>);
+
+/// Filter expression and its placeholders
+#[derive(Default)]
+struct Filter {
+    expression: Option<String>,
+    values: Vec<(String, Value)>,
+    names: Vec<(String, String)>,
+}
+
+enum SortKeyCondition {
+    Eq(Vec<u8>),
+    Lt(Vec<u8>),
+    Lte(Vec<u8>),
+    Gt(Vec<u8>),
+    Gte(Vec<u8>),
+    Between(Vec<u8>, Vec<u8>),
+    BeginsWith(Vec<u8>),
+}
+
+/// One page of a query or scan
+#[napi(object)]
+pub struct Page {
+    pub items: Vec<Map<String, serde_json::Value>>,
+    /// Number of items returned
+    pub count: u32,
+    /// Number of items examined, before the filter
+    pub scanned_count: u32,
+    /// Pass to `startAfter` to fetch the next page; a page may carry a
+    /// last key even when nothing follows
+    pub last_key: Option<LastKey>,
+}
+
+impl Page {
+    pub fn new(items: &[Item], scanned_count: usize, key: Option<(Bytes, Option<Bytes>)>) -> Self {
+        Self {
+            items: items.iter().map(from_item).collect(),
+            count: items.len() as u32,
+            scanned_count: scanned_count as u32,
+            last_key: last_key(key),
+        }
+    }
+}
+
+/// A query of one partition, built by chaining:
+/// `new Query(pk).skBeginsWith('user#').limit(10)`
+///
+/// Setting a sort key condition replaces the previous one.
+#[napi]
+pub struct Query {
+    pk: Vec<u8>,
+    sk_condition: Option<SortKeyCondition>,
+    forward: bool,
+    limit: Option<u32>,
+    index: Option<String>,
+    filter: Filter,
+    start_after: Option<OwnedKey>,
+}
+
+#[napi]
+impl Query {
+    /// Query the partition `pk` (of the table, or of `index` if set)
+    #[napi(constructor)]
+    pub fn new(pk: Either<String, Buffer>) -> Self {
+        Self {
+            pk: key_bytes(pk),
+            sk_condition: None,
+            forward: true,
+            limit: None,
+            index: None,
+            filter: Filter::default(),
+            start_after: None,
+        }
+    }
+
+    /// Only the item whose sort key equals `sk`
+    #[napi]
+    pub fn sk_eq(&mut self, sk: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Eq(key_bytes(sk)));
+        self
+    }
+
+    /// Items whose sort key sorts before `sk`
+    #[napi]
+    pub fn sk_lt(&mut self, sk: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Lt(key_bytes(sk)));
+        self
+    }
+
+    /// Items whose sort key sorts before or at `sk`
+    #[napi]
+    pub fn sk_lte(&mut self, sk: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Lte(key_bytes(sk)));
+        self
+    }
+
+    /// Items whose sort key sorts after `sk`
+    #[napi]
+    pub fn sk_gt(&mut self, sk: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Gt(key_bytes(sk)));
+        self
+    }
+
+    /// Items whose sort key sorts at or after `sk`
+    #[napi]
+    pub fn sk_gte(&mut self, sk: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Gte(key_bytes(sk)));
+        self
+    }
+
+    /// Items whose sort key lies between `low` and `high`, inclusive
+    #[napi]
+    pub fn sk_between(&mut self, low: Either<String, Buffer>, high: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::Between(key_bytes(low), key_bytes(high)));
+        self
+    }
+
+    /// Items whose sort key starts with `prefix`
+    #[napi]
+    pub fn sk_begins_with(&mut self, prefix: Either<String, Buffer>) -> &Self {
+        self.sk_condition = Some(SortKeyCondition::BeginsWith(key_bytes(prefix)));
+        self
+    }
+
+    /// Sort key order: ascending (the default) or descending
+    #[napi]
+    pub fn forward(&mut self, forward: bool) -> &Self {
+        self.forward = forward;
+        self
+    }
+
+    /// Evaluate at most `limit` items per page; items the filter drops count too
+    #[napi]
+    pub fn limit(&mut self, limit: u32) -> &Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Query a secondary index instead of the table
+    #[napi]
+    pub fn index(&mut self, name: String) -> &Self {
+        self.index = Some(name);
+        self
+    }
+
+    /// Keep only items matching a filter expression
+    #[napi]
+    pub fn filter(&mut self, expression: String) -> &Self {
+        self.filter.expression = Some(expression);
+        self
+    }
+
+    /// Bind a `:placeholder` value used by the filter
+    #[napi]
+    pub fn value(&mut self, placeholder: String, value: serde_json::Value) -> &Self {
+        self.filter.values.push((placeholder, json_to_value(value)));
+        self
+    }
+
+    /// Bind a `#placeholder` attribute name used by the filter
+    #[napi]
+    pub fn name(&mut self, placeholder: String, name: String) -> &Self {
+        self.filter.names.push((placeholder, name));
+        self
+    }
+
+    /// Continue after the given key (a page's `lastKey`); `null` starts over
+    #[napi]
+    pub fn start_after(&mut self, key: Option<ItemKey>) -> &Self {
+        self.start_after = key.map(ItemKey::into_bytes);
+        self
+    }
+}
+
+impl Query {
+    pub fn build(&self) -> kstone_api::Query {
+        let mut query = kstone_api::Query::new(&self.pk).forward(self.forward);
+        query = match &self.sk_condition {
+            None => query,
+            Some(SortKeyCondition::Eq(sk)) => query.sk_eq(sk),
+            Some(SortKeyCondition::Lt(sk)) => query.sk_lt(sk),
+            Some(SortKeyCondition::Lte(sk)) => query.sk_lte(sk),
+            Some(SortKeyCondition::Gt(sk)) => query.sk_gt(sk),
+            Some(SortKeyCondition::Gte(sk)) => query.sk_gte(sk),
+            Some(SortKeyCondition::Between(low, high)) => query.sk_between(low, high),
+            Some(SortKeyCondition::BeginsWith(prefix)) => query.sk_begins_with(prefix),
+        };
+        if let Some(limit) = self.limit {
+            query = query.limit(limit as usize);
+        }
+        if let Some(index) = &self.index {
+            query = query.index(index.clone());
+        }
+        if let Some(filter) = &self.filter.expression {
+            query = query.filter(filter.clone());
+        }
+        for (placeholder, value) in &self.filter.values {
+            query = query.value(placeholder.clone(), value.clone());
+        }
+        for (placeholder, name) in &self.filter.names {
+            query = query.name(placeholder.clone(), name.clone());
+        }
+        if let Some((pk, sk)) = &self.start_after {
+            query = query.start_after(pk, sk.as_deref());
+        }
+        query
+    }
+}
+
+/// A scan of the whole table, built by chaining:
+/// `new Scan().pkPrefix('user#').limit(100)`
+#[napi]
+pub struct Scan {
+    limit: Option<u32>,
+    pk_prefix: Option<Vec<u8>>,
+    segment: Option<(u32, u32)>,
+    filter: Filter,
+    start_after: Option<OwnedKey>,
+}
+
+#[napi]
+impl Scan {
+    /// Scan every item of the table
+    #[napi(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            limit: None,
+            pk_prefix: None,
+            segment: None,
+            filter: Filter::default(),
+            start_after: None,
+        }
+    }
+
+    /// Evaluate at most `limit` items per page; items the filter drops count too
+    #[napi]
+    pub fn limit(&mut self, limit: u32) -> &Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only items whose partition key starts with `prefix`
+    #[napi]
+    pub fn pk_prefix(&mut self, prefix: Either<String, Buffer>) -> &Self {
+        self.pk_prefix = Some(key_bytes(prefix));
+        self
+    }
+
+    /// Scan one of `totalSegments` disjoint parts, for parallel workers
+    #[napi]
+    pub fn segment(&mut self, segment: u32, total_segments: u32) -> &Self {
+        self.segment = Some((segment, total_segments));
+        self
+    }
+
+    /// Keep only items matching a filter expression
+    #[napi]
+    pub fn filter(&mut self, expression: String) -> &Self {
+        self.filter.expression = Some(expression);
+        self
+    }
+
+    /// Bind a `:placeholder` value used by the filter
+    #[napi]
+    pub fn value(&mut self, placeholder: String, value: serde_json::Value) -> &Self {
+        self.filter.values.push((placeholder, json_to_value(value)));
+        self
+    }
+
+    /// Bind a `#placeholder` attribute name used by the filter
+    #[napi]
+    pub fn name(&mut self, placeholder: String, name: String) -> &Self {
+        self.filter.names.push((placeholder, name));
+        self
+    }
+
+    /// Continue after the given key (a page's `lastKey`); `null` starts over
+    #[napi]
+    pub fn start_after(&mut self, key: Option<ItemKey>) -> &Self {
+        self.start_after = key.map(ItemKey::into_bytes);
+        self
+    }
+}
+
+impl Scan {
+    pub fn build(&self) -> Result<kstone_api::Scan> {
+        let mut scan = kstone_api::Scan::new();
+        if let Some(limit) = self.limit {
+            scan = scan.limit(limit as usize);
+        }
+        if let Some(prefix) = &self.pk_prefix {
+            scan = scan.pk_prefix(prefix);
+        }
+        if let Some((segment, total)) = self.segment {
+            if segment >= total {
+                return Err(invalid(format!(
+                    "Segment {} is out of range for {} segments",
+                    segment, total
+                )));
+            }
+            scan = scan.segment(segment as usize, total as usize);
+        }
+        if let Some(filter) = &self.filter.expression {
+            scan = scan.filter(filter.clone());
+        }
+        for (placeholder, value) in &self.filter.values {
+            scan = scan.value(placeholder.clone(), value.clone());
+        }
+        for (placeholder, name) in &self.filter.names {
+            scan = scan.name(placeholder.clone(), name.clone());
+        }
+        if let Some((pk, sk)) = &self.start_after {
+            scan = scan.start_after(pk, sk.as_deref());
+        }
+        Ok(scan)
+    }
+}
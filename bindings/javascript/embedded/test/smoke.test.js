@@ -0,0 +1,160 @@
+const { test } = require('node:test')
+const assert = require('node:assert')
+const { mkdtempSync, rmSync } = require('node:fs')
+const { tmpdir } = require('node:os')
+const { join } = require('node:path')
+
+const { Database, ErrorCode, Query, Scan } = require('..')
+
+function withDir(fn) {
+  const dir = mkdtempSync(join(tmpdir(), 'keystonedb-'))
+  try {
+    return fn(join(dir, 'test.keystone'))
+  } finally {
+    rmSync(dir, { recursive: true, force: true })
+  }
+}
+
+test('put, get and delete', () => {
+  withDir((path) => {
+    let db = Database.create(path)
+    db.put('user#alice', { name: 'Alice', age: 30, tags: ['a', 'b'], address: { city: 'Paris' } })
+    db.put(Buffer.from('org#acme'), { role: 'admin' }, { sk: 'user#alice' })
+    db.close()
+    db.close()
+    assert.throws(() => db.get('user#alice'), { code: ErrorCode.KeystoneError, message: /closed/ })
+
+    db = Database.open(path)
+    assert.deepStrictEqual(db.get('user#alice'), { name: 'Alice', age: 30, tags: ['a', 'b'], address: { city: 'Paris' } })
+    assert.deepStrictEqual(db.get('org#acme', 'user#alice'), { role: 'admin' })
+    assert.strictEqual(db.get('user#bob'), null)
+
+    db.delete('user#alice')
+    db.delete('org#acme', { sk: 'user#alice' })
+    assert.strictEqual(db.get('user#alice'), null)
+    assert.strictEqual(db.get('org#acme', 'user#alice'), null)
+    db.close()
+  })
+})
+
+test('query pages through a partition', () => {
+  const db = Database.createInMemory()
+  for (let i = 0; i < 5; i++) {
+    db.put('org#acme', { n: i }, { sk: `user#${i}` })
+  }
+  db.put('org#acme', { n: 99 }, { sk: 'team#1' })
+
+  const query = new Query('org#acme').skBeginsWith('user#').limit(2)
+  const seen = []
+  let page = db.query(query)
+  for (;;) {
+    seen.push(...page.items.map((item) => item.n))
+    if (!page.lastKey || page.count < 2) break
+    assert.ok(Buffer.isBuffer(page.lastKey.pk))
+    page = db.query(query.startAfter(page.lastKey))
+  }
+  assert.deepStrictEqual(seen, [0, 1, 2, 3, 4])
+
+  const reversed = db.query(new Query('org#acme').skBetween('user#1', 'user#3').forward(false))
+  assert.deepStrictEqual(reversed.items.map((item) => item.n), [3, 2, 1])
+
+  const filtered = db.query(new Query('org#acme').filter('n >= :min').value(':min', 3))
+  assert.deepStrictEqual(filtered.items.map((item) => item.n), [99, 3, 4])
+})
+
+test('scan with prefix, filter and segments', () => {
+  const db = Database.createInMemory()
+  for (let i = 0; i < 10; i++) {
+    db.put(`user#${i}`, { n: i, active: i % 2 === 0 })
+  }
+  db.put('order#1', { n: 100 })
+
+  const users = db.scan(new Scan().pkPrefix('user#'))
+  assert.strictEqual(users.count, 10)
+
+  const active = db.scan(new Scan().filter('#a = :yes').name('#a', 'active').value(':yes', true))
+  assert.strictEqual(active.count, 5)
+
+  let total = 0
+  for (let segment = 0; segment < 3; segment++) {
+    total += db.scan(new Scan().segment(segment, 3)).count
+  }
+  assert.strictEqual(total, 11)
+
+  assert.throws(() => db.scan(new Scan().segment(3, 3)), { code: ErrorCode.InvalidArgument })
+})
+
+test('update expressions and conditional writes', () => {
+  const db = Database.createInMemory()
+  db.put('counter', { visits: 1 })
+
+  const item = db.update('counter', 'SET visits = visits + :one', { values: { ':one': 1 } })
+  assert.strictEqual(item.visits, 2)
+
+  assert.throws(
+    () => db.update('counter', 'SET visits = :zero', { values: { ':zero': 0, ':max': 1 }, condition: 'visits < :max' }),
+    { code: ErrorCode.ConditionalCheckFailed },
+  )
+  assert.throws(() => db.put('counter', { visits: 0 }, { condition: 'attribute_not_exists(visits)' }), {
+    code: ErrorCode.ConditionalCheckFailed,
+  })
+  db.put('fresh', { visits: 0 }, { condition: 'attribute_not_exists(visits)' })
+
+  assert.throws(() => db.delete('counter', { condition: 'visits = :n', values: { ':n': 5 } }), {
+    code: ErrorCode.ConditionalCheckFailed,
+  })
+  db.delete('counter', { condition: 'visits = :n', values: { ':n': 2 } })
+  assert.strictEqual(db.get('counter'), null)
+
+  assert.throws(() => db.update('fresh', 'SET = oops'), { code: ErrorCode.InvalidArgument })
+})
+
+test('transactions', () => {
+  const db = Database.createInMemory()
+  db.put('account#a', { balance: 100 })
+  db.put('account#b', { balance: 0 })
+
+  const transfer = (amount) =>
+    db.transactWrite(
+      [
+        { update: { pk: 'account#a', expression: 'SET balance = balance - :amount', condition: 'balance >= :amount' } },
+        { update: { pk: 'account#b', expression: 'SET balance = balance + :amount' } },
+      ],
+      { values: { ':amount': amount } },
+    )
+
+  assert.strictEqual(transfer(60), 2)
+  assert.throws(() => transfer(60), { code: ErrorCode.TransactionCanceled })
+
+  const [a, b, missing] = db.transactGet([{ pk: 'account#a' }, { pk: 'account#b' }, { pk: 'account#c' }])
+  assert.strictEqual(a.balance, 40)
+  assert.strictEqual(b.balance, 60)
+  assert.strictEqual(missing, null)
+
+  assert.throws(() => db.transactWrite([{ put: { pk: 'x', item: {} }, delete: { pk: 'y' } }]), {
+    code: ErrorCode.InvalidArgument,
+  })
+})
+
+test('executeStatement', () => {
+  const db = Database.createInMemory()
+  const insert = db.executeStatement("INSERT INTO items VALUE {'pk': ?, 'name': ?, 'age': ?}", ['user#alice', 'Alice', 30])
+  assert.strictEqual(insert.kind, 'insert')
+  assert.strictEqual(insert.success, true)
+
+  const select = db.executeStatement('SELECT * FROM items WHERE pk = :pk', { pk: 'user#alice' })
+  assert.strictEqual(select.kind, 'select')
+  assert.strictEqual(select.count, 1)
+  assert.strictEqual(select.items[0].name, 'Alice')
+
+  const update = db.executeStatement("UPDATE items SET age = 31 WHERE pk = 'user#alice'")
+  assert.strictEqual(update.kind, 'update')
+  assert.strictEqual(update.item.age, 31)
+
+  const explain = db.executeStatement("EXPLAIN SELECT * FROM items WHERE pk = 'user#alice'")
+  assert.strictEqual(explain.kind, 'explain')
+  assert.strictEqual(typeof explain.plan, 'object')
+
+  assert.throws(() => db.executeStatement('SELEC nonsense'), { code: ErrorCode.InvalidArgument })
+  assert.throws(() => db.executeStatement('SELECT * FROM items', 'oops'), { code: ErrorCode.InvalidArgument })
+})
@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+# Generate the protobuf and gRPC modules for keystonedb.aio from kstone-proto.
+#
+# The proto is mapped into the package path so the generated gRPC module
+# imports its messages as keystonedb.aio._proto.keystone_pb2.
+set -euo pipefail
+
+cd "$(dirname "$0")"
+PROTO_DIR=../../../kstone-proto/proto
+OUT_DIR=keystonedb/aio/_proto
+
+mkdir -p "$OUT_DIR"
+python -m grpc_tools.protoc \
+  -I "keystonedb/aio/_proto=$PROTO_DIR" \
+  --python_out=. \
+  --grpc_python_out=. \
+  "$PROTO_DIR/keystone.proto"
+touch "$OUT_DIR/__init__.py"
@@ -0,0 +1,45 @@
+"""Asyncio gRPC client for KeystoneDB
+
+```python
+from keystonedb.aio import Client, SortKey
+
+async with Client("localhost:50051") as client:
+    await client.put("org#acme", {"name": "Alice"}, sk="user#alice")
+    page = await client.query("org#acme", sort_key=SortKey.begins_with("user#"))
+```
+"""
+
+from .client import Client, Page, RetryPolicy, SortKey, StatementResult, StreamRecord, Transact
+from .errors import (
+    AlreadyExistsError,
+    ConditionalCheckFailedError,
+    DeadlineExceededError,
+    InvalidArgumentError,
+    KeystoneError,
+    NotFoundError,
+    ResourceExhaustedError,
+    TransactionCanceledError,
+    UnavailableError,
+)
+from .values import Timestamp, Vector
+
+__all__ = [
+    "AlreadyExistsError",
+    "Client",
+    "ConditionalCheckFailedError",
+    "DeadlineExceededError",
+    "InvalidArgumentError",
+    "KeystoneError",
+    "NotFoundError",
+    "Page",
+    "ResourceExhaustedError",
+    "RetryPolicy",
+    "SortKey",
+    "StatementResult",
+    "StreamRecord",
+    "Timestamp",
+    "Transact",
+    "TransactionCanceledError",
+    "UnavailableError",
+    "Vector",
+]
@@ -0,0 +1,722 @@
+"""Asyncio client for kstone-server.
+
+The client keeps a pool of gRPC channels and sends each call on the next one
+in turn. Calls that fail because the server was unreachable or overloaded
+(``UNAVAILABLE``, ``RESOURCE_EXHAUSTED``) are retried with exponential
+backoff, the same way ``kstone_client::Client`` retries them.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import random
+from dataclasses import dataclass, field
+from typing import (
+    Any,
+    AsyncIterator,
+    Awaitable,
+    Callable,
+    Iterable,
+    List,
+    Mapping,
+    Optional,
+    Sequence,
+    Tuple,
+    TypeVar,
+    Union,
+)
+
+import grpc
+
+from . import errors
+from ._proto import keystone_pb2 as pb
+from ._proto import keystone_pb2_grpc as pb_grpc
+from .values import (
+    Item,
+    KeyPart,
+    item_from_proto,
+    item_to_proto,
+    key_bytes,
+    keys_to_proto,
+    last_key_from_proto,
+    last_key_to_proto,
+    to_proto,
+    values_to_proto,
+)
+
+T = TypeVar("T")
+
+LastKey = Tuple[bytes, Optional[bytes]]
+KeyLike = Union[KeyPart, Tuple[KeyPart, Optional[KeyPart]]]
+
+# Metadata keys the server reads; see kstone-server/src/auth.rs and registry.rs
+AUTHORIZATION_HEADER = "authorization"
+DATABASE_HEADER = "kstone-database"
+
+RETRYABLE_CODES = frozenset({grpc.StatusCode.UNAVAILABLE, grpc.StatusCode.RESOURCE_EXHAUSTED})
+
+
+@dataclass(frozen=True)
+class RetryPolicy:
+    """How failed calls are retried
+
+    The defaults match ``RetryPolicy::standard()`` in kstone-core.
+    ``max_attempts`` counts retries, not including the first attempt.
+    """
+
+    max_attempts: int = 5
+    initial_backoff_ms: int = 100
+    max_backoff_ms: int = 5000
+    backoff_multiplier: float = 2.0
+    jitter: bool = True
+
+    @classmethod
+    def none(cls) -> "RetryPolicy":
+        """Never retry"""
+        return cls(max_attempts=0)
+
+    def backoff(self, attempt: int) -> float:
+        """Seconds to wait before retry ``attempt`` (0-indexed)"""
+        delay_ms = min(
+            self.initial_backoff_ms * self.backoff_multiplier**attempt,
+            self.max_backoff_ms,
+        )
+        if self.jitter:
+            # Stay in the upper half so retries still back off
+            delay_ms = delay_ms / 2 + delay_ms * random.random() / 2
+        return delay_ms / 1000
+
+
+@dataclass
+class Page:
+    """One page of query or scan results
+
+    ``last_key`` is the (partition key, sort key) to pass as ``start_key``
+    for the next page, or ``None`` when there are no more items.
+    """
+
+    items: List[Item] = field(default_factory=list)
+    count: int = 0
+    scanned_count: int = 0
+    last_key: Optional[LastKey] = None
+
+
+@dataclass
+class StatementResult:
+    """Result of a PartiQL statement
+
+    ``kind`` is ``"select"``, ``"insert"``, ``"update"`` or ``"delete"``.
+    SELECT fills ``items``, UPDATE fills ``item``.
+    """
+
+    kind: str
+    items: List[Item] = field(default_factory=list)
+    count: int = 0
+    scanned_count: int = 0
+    last_key: Optional[LastKey] = None
+    item: Optional[Item] = None
+    success: bool = True
+
+
+@dataclass
+class StreamRecord:
+    """A change from the server's stream
+
+    ``event`` is ``"INSERT"``, ``"MODIFY"`` or ``"REMOVE"``.
+    """
+
+    sequence_number: int
+    event: str
+    key: LastKey
+    old_image: Optional[Item]
+    new_image: Optional[Item]
+    timestamp: int
+
+
+class SortKey:
+    """Sort key conditions for ``Client.query``"""
+
+    @staticmethod
+    def eq(value: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(equal_to=_sort_key_value(value))
+
+    @staticmethod
+    def lt(value: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(less_than=_sort_key_value(value))
+
+    @staticmethod
+    def le(value: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(less_than_or_equal=_sort_key_value(value))
+
+    @staticmethod
+    def gt(value: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(greater_than=_sort_key_value(value))
+
+    @staticmethod
+    def ge(value: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(greater_than_or_equal=_sort_key_value(value))
+
+    @staticmethod
+    def between(lower: Any, upper: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(
+            between=pb.BetweenCondition(lower=_sort_key_value(lower), upper=_sort_key_value(upper))
+        )
+
+    @staticmethod
+    def begins_with(prefix: Any) -> pb.SortKeyCondition:
+        return pb.SortKeyCondition(begins_with=_sort_key_value(prefix))
+
+
+def _sort_key_value(value: Any) -> pb.Value:
+    # The server compares sort keys as bytes: strings by their UTF-8 encoding,
+    # numbers by their decimal text
+    if not isinstance(value, (bytes, bytearray, str, int, float)) or isinstance(value, bool):
+        raise TypeError(f"Sort keys must be bytes, str or a number, not {type(value).__name__}")
+    return to_proto(value)
+
+
+class Client:
+    """Asyncio client for a KeystoneDB server
+
+    ```python
+    async with Client("localhost:50051") as client:
+        await client.put("user#alice", {"name": "Alice", "age": 30})
+        item = await client.get("user#alice")
+    ```
+
+    Args:
+        target: Server address, e.g. ``"localhost:50051"``
+        pool_size: Number of channels (connections) calls are spread over
+        retry: How calls failing with ``UNAVAILABLE`` or ``RESOURCE_EXHAUSTED``
+            are retried
+        timeout: Seconds each attempt may take, ``None`` to wait as long as
+            the server takes
+        token: Bearer token sent as ``authorization`` metadata
+        database: Database to address on a multi-database server
+        credentials: ``grpc.ChannelCredentials`` for TLS; plaintext if ``None``
+        options: Extra gRPC channel options
+    """
+
+    def __init__(
+        self,
+        target: str,
+        *,
+        pool_size: int = 4,
+        retry: Optional[RetryPolicy] = None,
+        timeout: Optional[float] = None,
+        token: Optional[str] = None,
+        database: Optional[str] = None,
+        credentials: Optional[grpc.ChannelCredentials] = None,
+        options: Optional[Sequence[Tuple[str, Any]]] = None,
+    ):
+        if pool_size < 1:
+            raise ValueError("pool_size must be at least 1")
+        # A local subchannel pool gives each channel its own connection
+        channel_options = [("grpc.use_local_subchannel_pool", 1), *(options or [])]
+        if credentials is None:
+            self._channels = [grpc.aio.insecure_channel(target, options=channel_options) for _ in range(pool_size)]
+        else:
+            self._channels = [
+                grpc.aio.secure_channel(target, credentials, options=channel_options) for _ in range(pool_size)
+            ]
+        self._stubs = [pb_grpc.KeystoneDBStub(channel) for channel in self._channels]
+        self._next = 0
+        self._retry = retry or RetryPolicy()
+        self._timeout = timeout
+        self._metadata: Tuple[Tuple[str, str], ...] = ()
+        if token is not None:
+            self._metadata += ((AUTHORIZATION_HEADER, f"Bearer {token}"),)
+        if database is not None:
+            self._metadata += ((DATABASE_HEADER, database),)
+
+    async def __aenter__(self) -> "Client":
+        return self
+
+    async def __aexit__(self, *exc_info: Any) -> None:
+        await self.close()
+
+    async def close(self) -> None:
+        """Close every channel in the pool"""
+        await asyncio.gather(*(channel.close() for channel in self._channels))
+
+    # ------------------------------------------------------------------
+    # Items
+    # ------------------------------------------------------------------
+
+    async def put(
+        self,
+        pk: KeyPart,
+        item: Mapping[str, Any],
+        *,
+        sk: Optional[KeyPart] = None,
+        condition: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+    ) -> None:
+        """Store an item, replacing any item with the same key
+
+        Raises ``ConditionalCheckFailedError`` if ``condition`` is false.
+        """
+        request = pb.PutRequest(
+            partition_key=key_bytes(pk),
+            item=item_to_proto(item),
+            expression_values=values_to_proto(values),
+        )
+        if sk is not None:
+            request.sort_key = key_bytes(sk)
+        if condition is not None:
+            request.condition_expression = condition
+        response = await self._call(lambda stub, **kw: stub.Put(request, **kw))
+        _check(response)
+
+    async def get(self, pk: KeyPart, *, sk: Optional[KeyPart] = None) -> Optional[Item]:
+        """The item with this key, or ``None``"""
+        request = pb.GetRequest(partition_key=key_bytes(pk))
+        if sk is not None:
+            request.sort_key = key_bytes(sk)
+        response = await self._call(lambda stub, **kw: stub.Get(request, **kw))
+        _check(response)
+        return item_from_proto(response.item) if response.HasField("item") else None
+
+    async def delete(
+        self,
+        pk: KeyPart,
+        *,
+        sk: Optional[KeyPart] = None,
+        condition: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+    ) -> None:
+        """Delete the item with this key, if any"""
+        request = pb.DeleteRequest(partition_key=key_bytes(pk), expression_values=values_to_proto(values))
+        if sk is not None:
+            request.sort_key = key_bytes(sk)
+        if condition is not None:
+            request.condition_expression = condition
+        response = await self._call(lambda stub, **kw: stub.Delete(request, **kw))
+        _check(response)
+
+    async def update(
+        self,
+        pk: KeyPart,
+        expression: str,
+        *,
+        sk: Optional[KeyPart] = None,
+        condition: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+    ) -> Item:
+        """Apply an update expression and return the updated item
+
+        ```python
+        await client.update("user#alice", "SET age = age + :inc", values={":inc": 1})
+        ```
+        """
+        request = pb.UpdateRequest(
+            partition_key=key_bytes(pk),
+            update_expression=expression,
+            expression_values=values_to_proto(values),
+        )
+        if sk is not None:
+            request.sort_key = key_bytes(sk)
+        if condition is not None:
+            request.condition_expression = condition
+        response = await self._call(lambda stub, **kw: stub.Update(request, **kw))
+        _check(response)
+        return item_from_proto(response.item)
+
+    # ------------------------------------------------------------------
+    # Query and scan
+    # ------------------------------------------------------------------
+
+    async def query(
+        self,
+        pk: KeyPart,
+        *,
+        sort_key: Optional[pb.SortKeyCondition] = None,
+        filter: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+        index: Optional[str] = None,
+        limit: Optional[int] = None,
+        start_key: Optional[LastKey] = None,
+        forward: bool = True,
+    ) -> Page:
+        """One page of the items in a partition, in sort key order
+
+        ```python
+        page = await client.query("org#acme", sort_key=SortKey.begins_with("user#"), limit=10)
+        ```
+        """
+        request = _query_request(pk, sort_key, filter, values, index, limit, start_key, forward, None)
+        response = await self._call(lambda stub, **kw: stub.Query(request, **kw))
+        return _page(response)
+
+    async def query_stream(
+        self,
+        pk: KeyPart,
+        *,
+        sort_key: Optional[pb.SortKeyCondition] = None,
+        filter: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+        index: Optional[str] = None,
+        limit: Optional[int] = None,
+        forward: bool = True,
+        page_size: Optional[int] = None,
+    ) -> AsyncIterator[Item]:
+        """Every matching item in a partition, fetched page by page
+
+        If the connection drops between pages, the query resumes after the
+        last page received.
+        """
+
+        def request(start_key: Optional[LastKey], remaining: Optional[int]) -> pb.QueryRequest:
+            return _query_request(pk, sort_key, filter, values, index, remaining, start_key, forward, page_size)
+
+        async for item in self._stream_pages(lambda stub, req, **kw: stub.QueryStream(req, **kw), request, limit):
+            yield item
+
+    async def scan(
+        self,
+        *,
+        filter: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+        index: Optional[str] = None,
+        limit: Optional[int] = None,
+        start_key: Optional[LastKey] = None,
+        segment: Optional[int] = None,
+        total_segments: Optional[int] = None,
+    ) -> Page:
+        """One page of the items in the table
+
+        The server answers a scan as a stream of pages; this collects them
+        into a single page.
+        """
+        request = _scan_request(filter, values, index, limit, start_key, segment, total_segments, None)
+
+        async def collect(stub: pb_grpc.KeystoneDBStub, **kw: Any) -> Page:
+            page = Page()
+            async for response in stub.Scan(request, **kw):
+                part = _page(response)
+                page.items.extend(part.items)
+                page.count += part.count
+                page.scanned_count += part.scanned_count
+                page.last_key = part.last_key
+            return page
+
+        return await self._call(collect)
+
+    async def scan_stream(
+        self,
+        *,
+        filter: Optional[str] = None,
+        values: Optional[Mapping[str, Any]] = None,
+        index: Optional[str] = None,
+        limit: Optional[int] = None,
+        segment: Optional[int] = None,
+        total_segments: Optional[int] = None,
+        page_size: Optional[int] = None,
+    ) -> AsyncIterator[Item]:
+        """Every matching item in the table, fetched page by page
+
+        If the connection drops between pages, the scan resumes after the
+        last page received.
+        """
+
+        def request(start_key: Optional[LastKey], remaining: Optional[int]) -> pb.ScanRequest:
+            return _scan_request(filter, values, index, remaining, start_key, segment, total_segments, page_size)
+
+        async for item in self._stream_pages(lambda stub, req, **kw: stub.ScanStream(req, **kw), request, limit):
+            yield item
+
+    # ------------------------------------------------------------------
+    # Batches and transactions
+    # ------------------------------------------------------------------
+
+    async def batch_get(self, keys: Iterable[KeyLike]) -> List[Item]:
+        """The items that exist among ``keys``
+
+        Each key is a partition key or a (partition key, sort key) pair.
+        """
+        request = pb.BatchGetRequest(keys=keys_to_proto(keys))
+        response = await self._call(lambda stub, **kw: stub.BatchGet(request, **kw))
+        _check(response)
+        return [item_from_proto(item) for item in response.items]
+
+    async def batch_write(
+        self,
+        puts: Iterable[Tuple[KeyLike, Mapping[str, Any]]] = (),
+        deletes: Iterable[KeyLike] = (),
+    ) -> None:
+        """Put and delete several items in one call
+
+        ```python
+        await client.batch_write(
+            puts=[("user#1", {"name": "Ann"}), (("org#acme", "user#2"), {"name": "Bo"})],
+            deletes=["user#3"],
+        )
+        ```
+        """
+        writes = []
+        for key, item in puts:
+            (key_message,) = keys_to_proto([key])
+            put = pb.PutItem(partition_key=key_message.partition_key, item=item_to_proto(item))
+            if key_message.HasField("sort_key"):
+                put.sort_key = key_message.sort_key
+            writes.append(pb.WriteRequest(put=put))
+        for key_message in keys_to_proto(deletes):
+            delete = pb.DeleteKey(partition_key=key_message.partition_key)
+            if key_message.HasField("sort_key"):
+                delete.sort_key = key_message.sort_key
+            writes.append(pb.WriteRequest(delete=delete))
+        request = pb.BatchWriteRequest(writes=writes)
+        response = await self._call(lambda stub, **kw: stub.BatchWrite(request, **kw))
+        _check(response)
+
+    async def transact_get(self, keys: Iterable[KeyLike]) -> List[Optional[Item]]:
+        """Read several items consistently; ``None`` for each missing item"""
+        request = pb.TransactGetRequest(keys=keys_to_proto(keys))
+        response = await self._call(lambda stub, **kw: stub.TransactGet(request, **kw))
+        _check(response)
+        return [item_from_proto(entry.item) if entry.HasField("item") else None for entry in response.items]
+
+    async def transact_write(self, items: Iterable[pb.TransactWriteItem]) -> None:
+        """Apply writes atomically; build them with ``Transact``
+
+        Raises ``TransactionCanceledError`` if any condition fails.
+        """
+        request = pb.TransactWriteRequest(items=list(items))
+        response = await self._call(lambda stub, **kw: stub.TransactWrite(request, **kw))
+        _check(response)
+
+    # ------------------------------------------------------------------
+    # PartiQL and streams
+    # ------------------------------------------------------------------
+
+    async def execute(self, statement: str) -> StatementResult:
+        """Run a PartiQL statement"""
+        request = pb.ExecuteStatementRequest(statement=statement)
+        response = await self._call(lambda stub, **kw: stub.ExecuteStatement(request, **kw))
+        _check(response)
+        kind = response.WhichOneof("response")
+        if kind == "select":
+            select = response.select
+            return StatementResult(
+                kind=kind,
+                items=[item_from_proto(item) for item in select.items],
+                count=select.count,
+                scanned_count=select.scanned_count,
+                last_key=last_key_from_proto(select, "last_key"),
+            )
+        if kind == "update":
+            return StatementResult(kind=kind, item=item_from_proto(response.update.item))
+        if kind in ("insert", "delete"):
+            return StatementResult(kind=kind, success=getattr(response, kind).success)
+        raise errors.KeystoneError("Server returned no statement result")
+
+    async def subscribe(
+        self,
+        *,
+        after_sequence: Optional[int] = None,
+        from_trim_horizon: bool = False,
+        heartbeat_interval_ms: Optional[int] = None,
+    ) -> AsyncIterator[StreamRecord]:
+        """Changes as they are committed; heartbeats are skipped
+
+        The subscription isn't retried; to resume after an error, subscribe
+        again with ``after_sequence`` set to the last record's sequence number.
+        """
+        request = pb.SubscribeStreamRequest(from_trim_horizon=from_trim_horizon)
+        if after_sequence is not None:
+            request.after_sequence = after_sequence
+        if heartbeat_interval_ms is not None:
+            request.heartbeat_interval_ms = heartbeat_interval_ms
+        # No timeout: a subscription stays open until the caller stops reading
+        call = self._next_stub().SubscribeStream(request, metadata=self._metadata)
+        try:
+            async for response in call:
+                if response.WhichOneof("event") != "record":
+                    continue
+                record = response.record
+                key = record.key
+                yield StreamRecord(
+                    sequence_number=record.sequence_number,
+                    event=pb.StreamEventType.Name(record.event_type),
+                    key=(key.partition_key, key.sort_key if key.HasField("sort_key") else None),
+                    old_image=item_from_proto(record.old_image) if record.HasField("old_image") else None,
+                    new_image=item_from_proto(record.new_image) if record.HasField("new_image") else None,
+                    timestamp=record.timestamp,
+                )
+        except grpc.aio.AioRpcError as e:
+            raise errors.from_rpc_error(e) from e
+        finally:
+            call.cancel()
+
+    # ------------------------------------------------------------------
+    # Calls
+    # ------------------------------------------------------------------
+
+    def _next_stub(self) -> pb_grpc.KeystoneDBStub:
+        stub = self._stubs[self._next % len(self._stubs)]
+        self._next += 1
+        return stub
+
+    async def _call(self, call: Callable[..., Awaitable[T]]) -> T:
+        """Send a call, retrying it on the next channel as configured"""
+        attempt = 0
+        while True:
+            try:
+                return await call(self._next_stub(), metadata=self._metadata, timeout=self._timeout)
+            except grpc.aio.AioRpcError as e:
+                if e.code() not in RETRYABLE_CODES or attempt >= self._retry.max_attempts:
+                    raise errors.from_rpc_error(e) from e
+            await asyncio.sleep(self._retry.backoff(attempt))
+            attempt += 1
+
+    async def _stream_pages(
+        self,
+        open_stream: Callable[..., Any],
+        request: Callable[[Optional[LastKey], Optional[int]], Any],
+        limit: Optional[int],
+    ) -> AsyncIterator[Item]:
+        """Yield the items of a paged stream, resuming it after retryable errors"""
+        start_key: Optional[LastKey] = None
+        remaining = limit
+        received = False
+        attempt = 0
+        while True:
+            try:
+                async for response in open_stream(
+                    self._next_stub(), request(start_key, remaining), metadata=self._metadata, timeout=self._timeout
+                ):
+                    page = _page(response)
+                    received = received or bool(page.items)
+                    for item in page.items:
+                        yield item
+                    if remaining is not None:
+                        remaining -= len(page.items)
+                    start_key = page.last_key
+                    attempt = 0
+                return
+            except grpc.aio.AioRpcError as e:
+                # Without a last key there's nowhere to resume items already yielded
+                resumable = start_key is not None or not received
+                if e.code() not in RETRYABLE_CODES or attempt >= self._retry.max_attempts or not resumable:
+                    raise errors.from_rpc_error(e) from e
+                if remaining is not None and remaining <= 0:
+                    return
+            await asyncio.sleep(self._retry.backoff(attempt))
+            attempt += 1
+
+
+class Transact:
+    """Items for ``Client.transact_write``"""
+
+    @staticmethod
+    def put(
+        pk: KeyPart, item: Mapping[str, Any], *, sk: Optional[KeyPart] = None, condition: Optional[str] = None
+    ) -> pb.TransactWriteItem:
+        put = pb.TransactPut(partition_key=key_bytes(pk), item=item_to_proto(item))
+        if sk is not None:
+            put.sort_key = key_bytes(sk)
+        if condition is not None:
+            put.condition_expression = condition
+        return pb.TransactWriteItem(put=put)
+
+    @staticmethod
+    def update(
+        pk: KeyPart, expression: str, *, sk: Optional[KeyPart] = None, condition: Optional[str] = None
+    ) -> pb.TransactWriteItem:
+        update = pb.TransactUpdate(partition_key=key_bytes(pk), update_expression=expression)
+        if sk is not None:
+            update.sort_key = key_bytes(sk)
+        if condition is not None:
+            update.condition_expression = condition
+        return pb.TransactWriteItem(update=update)
+
+    @staticmethod
+    def delete(pk: KeyPart, *, sk: Optional[KeyPart] = None, condition: Optional[str] = None) -> pb.TransactWriteItem:
+        delete = pb.TransactDelete(partition_key=key_bytes(pk))
+        if sk is not None:
+            delete.sort_key = key_bytes(sk)
+        if condition is not None:
+            delete.condition_expression = condition
+        return pb.TransactWriteItem(delete=delete)
+
+    @staticmethod
+    def condition_check(pk: KeyPart, condition: str, *, sk: Optional[KeyPart] = None) -> pb.TransactWriteItem:
+        check = pb.ConditionCheck(partition_key=key_bytes(pk), condition_expression=condition)
+        if sk is not None:
+            check.sort_key = key_bytes(sk)
+        return pb.TransactWriteItem(condition_check=check)
+
+
+def _check(response: Any) -> None:
+    """Raise the error a response carries, if any"""
+    if response.HasField("error"):
+        raise errors.KeystoneError(response.error)
+
+
+def _page(response: Any) -> Page:
+    _check(response)
+    return Page(
+        items=[item_from_proto(item) for item in response.items],
+        count=response.count,
+        scanned_count=response.scanned_count,
+        last_key=last_key_from_proto(response, "last_evaluated_key"),
+    )
+
+
+def _query_request(
+    pk: KeyPart,
+    sort_key: Optional[pb.SortKeyCondition],
+    filter: Optional[str],
+    values: Optional[Mapping[str, Any]],
+    index: Optional[str],
+    limit: Optional[int],
+    start_key: Optional[LastKey],
+    forward: bool,
+    page_size: Optional[int],
+) -> pb.QueryRequest:
+    request = pb.QueryRequest(
+        partition_key=key_bytes(pk),
+        expression_values=values_to_proto(values),
+        scan_forward=forward,
+    )
+    if sort_key is not None:
+        request.sort_key_condition.CopyFrom(sort_key)
+    if filter is not None:
+        request.filter_expression = filter
+    if index is not None:
+        request.index_name = index
+    if limit is not None:
+        request.limit = limit
+    if start_key is not None:
+        request.exclusive_start_key.CopyFrom(last_key_to_proto(start_key))
+    if page_size is not None:
+        request.page_size = page_size
+    return request
+
+
+def _scan_request(
+    filter: Optional[str],
+    values: Optional[Mapping[str, Any]],
+    index: Optional[str],
+    limit: Optional[int],
+    start_key: Optional[LastKey],
+    segment: Optional[int],
+    total_segments: Optional[int],
+    page_size: Optional[int],
+) -> pb.ScanRequest:
+    request = pb.ScanRequest(expression_values=values_to_proto(values))
+    if filter is not None:
+        request.filter_expression = filter
+    if index is not None:
+        request.index_name = index
+    if limit is not None:
+        request.limit = limit
+    if start_key is not None:
+        request.exclusive_start_key.CopyFrom(last_key_to_proto(start_key))
+    if segment is not None:
+        request.segment = segment
+    if total_segments is not None:
+        request.total_segments = total_segments
+    if page_size is not None:
+        request.page_size = page_size
+    return request
@@ -0,0 +1,69 @@
+"""Errors raised by the asyncio client.
+
+gRPC status codes map to the KeystoneDB error kinds the server reports
+them for; see ``map_error`` in kstone-server/src/service.rs.
+"""
+
+from __future__ import annotations
+
+from typing import Optional
+
+import grpc
+
+
+class KeystoneError(Exception):
+    """Base class for every error the client raises"""
+
+    def __init__(self, message: str, code: Optional[grpc.StatusCode] = None):
+        super().__init__(message)
+        self.code = code
+
+
+class NotFoundError(KeystoneError):
+    """A table, index or other named resource doesn't exist"""
+
+
+class InvalidArgumentError(KeystoneError):
+    """The request was malformed: a bad expression, key or value"""
+
+
+class ConditionalCheckFailedError(KeystoneError):
+    """A condition expression evaluated to false"""
+
+
+class TransactionCanceledError(KeystoneError):
+    """A transaction was canceled; no part of it was applied"""
+
+
+class AlreadyExistsError(KeystoneError):
+    """The resource being created already exists"""
+
+
+class ResourceExhaustedError(KeystoneError):
+    """The server is overloaded or the request exceeded a limit; retried"""
+
+
+class UnavailableError(KeystoneError):
+    """The server could not be reached or is shutting down; retried"""
+
+
+class DeadlineExceededError(KeystoneError):
+    """The call did not finish before its timeout"""
+
+
+_ERRORS = {
+    grpc.StatusCode.NOT_FOUND: NotFoundError,
+    grpc.StatusCode.INVALID_ARGUMENT: InvalidArgumentError,
+    grpc.StatusCode.FAILED_PRECONDITION: ConditionalCheckFailedError,
+    grpc.StatusCode.ABORTED: TransactionCanceledError,
+    grpc.StatusCode.ALREADY_EXISTS: AlreadyExistsError,
+    grpc.StatusCode.RESOURCE_EXHAUSTED: ResourceExhaustedError,
+    grpc.StatusCode.UNAVAILABLE: UnavailableError,
+    grpc.StatusCode.DEADLINE_EXCEEDED: DeadlineExceededError,
+}
+
+
+def from_rpc_error(error: grpc.aio.AioRpcError) -> KeystoneError:
+    """The client error for a failed call"""
+    code = error.code()
+    return _ERRORS.get(code, KeystoneError)(error.details() or str(code), code)
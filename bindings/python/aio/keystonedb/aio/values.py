@@ -0,0 +1,166 @@
+"""Conversion between Python values and KeystoneDB protobuf values.
+
+| Python                         | KeystoneDB |
+|--------------------------------|------------|
+| str                            | S          |
+| int, float, Decimal            | N          |
+| bytes, bytearray               | B          |
+| bool                           | Bool       |
+| None                           | Null       |
+| list, tuple                    | L          |
+| dict (str keys)                | M          |
+| Vector                         | VecF32     |
+| datetime.datetime, Timestamp   | Ts         |
+
+Numbers come back as ``int`` when they have no fraction or exponent and as
+``Decimal`` otherwise, so no precision is lost. Timestamps come back as
+``Timestamp`` (milliseconds since the epoch).
+"""
+
+from __future__ import annotations
+
+import datetime as _dt
+from decimal import Decimal
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Tuple, Union
+
+from ._proto import keystone_pb2 as pb
+
+Item = Dict[str, Any]
+KeyPart = Union[bytes, str]
+
+
+class Vector(list):
+    """A list of floats stored as a KeystoneDB vector (VecF32)"""
+
+
+class Timestamp(int):
+    """Milliseconds since the Unix epoch, stored as a KeystoneDB timestamp (Ts)"""
+
+    @classmethod
+    def from_datetime(cls, value: _dt.datetime) -> "Timestamp":
+        if value.tzinfo is None:
+            value = value.replace(tzinfo=_dt.timezone.utc)
+        return cls(int(value.timestamp() * 1000))
+
+    def to_datetime(self) -> _dt.datetime:
+        return _dt.datetime.fromtimestamp(self / 1000, tz=_dt.timezone.utc)
+
+
+def key_bytes(part: KeyPart) -> bytes:
+    """Encode a partition or sort key; strings are UTF-8 encoded"""
+    if isinstance(part, str):
+        return part.encode("utf-8")
+    if isinstance(part, (bytes, bytearray)):
+        return bytes(part)
+    raise TypeError(f"Keys must be bytes or str, not {type(part).__name__}")
+
+
+def to_proto(value: Any) -> pb.Value:
+    """Convert a Python value to a protobuf Value"""
+    # bool is a subclass of int and Timestamp/Vector of int/list, so they go first
+    if isinstance(value, bool):
+        return pb.Value(bool_value=value)
+    if isinstance(value, Timestamp):
+        return pb.Value(timestamp_value=int(value))
+    if isinstance(value, _dt.datetime):
+        return pb.Value(timestamp_value=int(Timestamp.from_datetime(value)))
+    if isinstance(value, Vector):
+        return pb.Value(vector_value=pb.VectorValue(values=[float(v) for v in value]))
+    if value is None:
+        return pb.Value(null_value=pb.NULL_VALUE)
+    if isinstance(value, str):
+        return pb.Value(string_value=value)
+    if isinstance(value, (int, float, Decimal)):
+        return pb.Value(number_value=str(value))
+    if isinstance(value, (bytes, bytearray)):
+        return pb.Value(binary_value=bytes(value))
+    if isinstance(value, (list, tuple)):
+        return pb.Value(list_value=pb.ListValue(items=[to_proto(v) for v in value]))
+    if isinstance(value, Mapping):
+        return pb.Value(map_value=pb.MapValue(fields=_fields_to_proto(value)))
+    raise TypeError(f"Unsupported value type: {type(value).__name__}")
+
+
+def from_proto(value: pb.Value) -> Any:
+    """Convert a protobuf Value to a Python value"""
+    kind = value.WhichOneof("value")
+    if kind == "string_value":
+        return value.string_value
+    if kind == "number_value":
+        return _parse_number(value.number_value)
+    if kind == "binary_value":
+        return value.binary_value
+    if kind == "bool_value":
+        return value.bool_value
+    if kind == "null_value" or kind is None:
+        return None
+    if kind == "list_value":
+        return [from_proto(v) for v in value.list_value.items]
+    if kind == "map_value":
+        return {name: from_proto(v) for name, v in value.map_value.fields.items()}
+    if kind == "vector_value":
+        return Vector(value.vector_value.values)
+    if kind == "timestamp_value":
+        return Timestamp(value.timestamp_value)
+    raise ValueError(f"Unknown value kind: {kind}")
+
+
+def item_to_proto(item: Mapping[str, Any]) -> pb.Item:
+    """Convert a dict of attributes to a protobuf Item"""
+    return pb.Item(attributes=_fields_to_proto(item))
+
+
+def item_from_proto(item: pb.Item) -> Item:
+    """Convert a protobuf Item to a dict of attributes"""
+    return {name: from_proto(v) for name, v in item.attributes.items()}
+
+
+def values_to_proto(values: Optional[Mapping[str, Any]]) -> Dict[str, pb.Value]:
+    """Convert expression placeholder values (``{":min": 18}``)"""
+    return _fields_to_proto(values or {})
+
+
+def last_key_to_proto(key: Optional[Tuple[bytes, Optional[bytes]]]) -> Optional[pb.LastKey]:
+    if key is None:
+        return None
+    pk, sk = key
+    last_key = pb.LastKey(partition_key=key_bytes(pk))
+    if sk is not None:
+        last_key.sort_key = key_bytes(sk)
+    return last_key
+
+
+def last_key_from_proto(message: Any, field: str) -> Optional[Tuple[bytes, Optional[bytes]]]:
+    """The (partition key, sort key) of an optional LastKey field"""
+    if not message.HasField(field):
+        return None
+    key = getattr(message, field)
+    return key.partition_key, key.sort_key if key.HasField("sort_key") else None
+
+
+def keys_to_proto(keys: Iterable[Union[KeyPart, Tuple[KeyPart, Optional[KeyPart]]]]) -> List[pb.Key]:
+    """Convert keys given as a partition key or a (partition key, sort key) pair"""
+    converted = []
+    for key in keys:
+        pk, sk = key if isinstance(key, tuple) else (key, None)
+        message = pb.Key(partition_key=key_bytes(pk))
+        if sk is not None:
+            message.sort_key = key_bytes(sk)
+        converted.append(message)
+    return converted
+
+
+def _fields_to_proto(fields: Mapping[str, Any]) -> Dict[str, pb.Value]:
+    converted = {}
+    for name, value in fields.items():
+        if not isinstance(name, str):
+            raise TypeError(f"Attribute names must be str, not {type(name).__name__}")
+        converted[name] = to_proto(value)
+    return converted
+
+
+def _parse_number(text: str) -> Union[int, Decimal]:
+    try:
+        return int(text)
+    except ValueError:
+        return Decimal(text)
@@ -0,0 +1,81 @@
+import grpc
+import pytest
+
+from keystonedb.aio import Client, RetryPolicy, UnavailableError, ConditionalCheckFailedError
+from keystonedb.aio._proto import keystone_pb2 as pb
+
+NO_WAIT = RetryPolicy(initial_backoff_ms=0, max_backoff_ms=0)
+
+
+def rpc_error(code):
+    return grpc.aio.AioRpcError(code, grpc.aio.Metadata(), grpc.aio.Metadata(), details=code.name)
+
+
+class FakeStub:
+    """Answers Get from a script of responses and errors"""
+
+    def __init__(self, script):
+        self.script = script
+        self.calls = []
+
+    async def Get(self, request, metadata=None, timeout=None):
+        self.calls.append((request, metadata))
+        outcome = self.script.pop(0)
+        if isinstance(outcome, Exception):
+            raise outcome
+        return outcome
+
+
+async def client_with(stub, **kwargs):
+    client = Client("localhost:1", pool_size=2, **kwargs)
+    client._stubs = [stub, stub]
+    return client
+
+
+async def test_retries_unavailable_then_succeeds():
+    item = pb.Item(attributes={"name": pb.Value(string_value="Alice")})
+    stub = FakeStub([rpc_error(grpc.StatusCode.UNAVAILABLE), pb.GetResponse(item=item)])
+    client = await client_with(stub, retry=NO_WAIT)
+
+    assert await client.get("user#alice") == {"name": "Alice"}
+    assert len(stub.calls) == 2
+    await client.close()
+
+
+async def test_gives_up_after_max_attempts():
+    stub = FakeStub([rpc_error(grpc.StatusCode.UNAVAILABLE) for _ in range(3)])
+    client = await client_with(stub, retry=RetryPolicy(max_attempts=2, initial_backoff_ms=0))
+
+    with pytest.raises(UnavailableError):
+        await client.get("user#alice")
+    assert len(stub.calls) == 3
+    await client.close()
+
+
+async def test_other_errors_are_not_retried():
+    stub = FakeStub([rpc_error(grpc.StatusCode.FAILED_PRECONDITION)])
+    client = await client_with(stub, retry=NO_WAIT)
+
+    with pytest.raises(ConditionalCheckFailedError):
+        await client.get("user#alice")
+    assert len(stub.calls) == 1
+    await client.close()
+
+
+async def test_sends_token_and_database_metadata():
+    stub = FakeStub([pb.GetResponse()])
+    client = await client_with(stub, token="secret", database="orders")
+
+    assert await client.get("user#alice", sk="profile") is None
+    request, metadata = stub.calls[0]
+    assert request.sort_key == b"profile"
+    assert ("authorization", "Bearer secret") in metadata
+    assert ("kstone-database", "orders") in metadata
+    await client.close()
+
+
+def test_backoff_stays_in_upper_half():
+    policy = RetryPolicy(initial_backoff_ms=100, max_backoff_ms=1000)
+    assert policy.backoff(1) >= 0.1
+    assert policy.backoff(1) <= 0.2
+    assert RetryPolicy(jitter=False).backoff(10) == 5.0
@@ -0,0 +1,59 @@
+import datetime as dt
+from decimal import Decimal
+
+import pytest
+
+from keystonedb.aio._proto import keystone_pb2 as pb
+from keystonedb.aio.values import (
+    Timestamp,
+    Vector,
+    from_proto,
+    item_from_proto,
+    item_to_proto,
+    keys_to_proto,
+    to_proto,
+)
+
+
+def test_scalar_round_trip():
+    for value in ["text", 42, -7, b"\x00\x01", True, False, None]:
+        assert from_proto(to_proto(value)) == value
+
+
+def test_bool_is_not_a_number():
+    assert to_proto(True).WhichOneof("value") == "bool_value"
+
+
+def test_numbers_keep_their_precision():
+    assert to_proto(1.5).number_value == "1.5"
+    assert from_proto(pb.Value(number_value="0.1")) == Decimal("0.1")
+    assert from_proto(pb.Value(number_value="12345678901234567890")) == 12345678901234567890
+
+
+def test_nested_item_round_trip():
+    item = {"name": "Alice", "tags": ["a", 1], "address": {"city": "Oslo", "zip": b"0150"}}
+    assert item_from_proto(item_to_proto(item)) == item
+
+
+def test_vector_and_timestamp():
+    assert to_proto(Vector([1.0, 2.5])).WhichOneof("value") == "vector_value"
+    assert from_proto(to_proto(Vector([1.0, 2.5]))) == Vector([1.0, 2.5])
+
+    moment = dt.datetime(2024, 1, 2, 3, 4, 5, tzinfo=dt.timezone.utc)
+    value = from_proto(to_proto(moment))
+    assert isinstance(value, Timestamp)
+    assert value.to_datetime() == moment
+
+
+def test_keys_accept_str_and_pairs():
+    simple, composite = keys_to_proto(["user#1", ("org#acme", b"user#2")])
+    assert simple.partition_key == b"user#1"
+    assert not simple.HasField("sort_key")
+    assert composite.sort_key == b"user#2"
+
+
+def test_unsupported_types_are_rejected():
+    with pytest.raises(TypeError):
+        to_proto(object())
+    with pytest.raises(TypeError):
+        item_to_proto({1: "not a str name"})
@@ -0,0 +1,84 @@
+"""Embedded KeystoneDB for Python
+
+```python
+import keystonedb
+
+with keystonedb.open("app.keystone") as db:
+    db.put(b"user#alice", {"name": "Alice", "age": 30})
+    page = db.query(b"org#acme", sk_begins_with=b"user#", limit=10)
+    for item in db.scan_iter(pk_prefix=b"user#"):
+        ...
+```
+"""
+
+import os
+import pkgutil
+from typing import Any, Dict, Mapping, Optional, Union
+
+# Let other distributions add subpackages, such as the asyncio gRPC client
+# (keystonedb.aio), next to the embedded package
+__path__ = pkgutil.extend_path(__path__, __name__)
+
+from ._keystonedb import (
+    AlreadyExistsError,
+    ConditionalCheckFailedError,
+    Database,
+    InvalidArgumentError,
+    ItemIterator,
+    KeystoneError,
+    NotFoundError,
+    ResourceExhaustedError,
+    TransactionCanceledError,
+)
+
+KeyPart = Union[bytes, str]
+
+
+def open(path: Union[str, "os.PathLike[str]"], *, create: bool = True) -> Database:
+    """Open the database at ``path``, creating it if missing (unless ``create=False``)
+
+    Use it in a ``with`` block to flush and close the database on exit.
+    """
+    path = os.fspath(path)
+    if create and not os.path.exists(path):
+        return Database.create(path)
+    return Database.open(path)
+
+
+class Transact:
+    """Operations for ``Database.transact_write``"""
+
+    @staticmethod
+    def put(
+        pk: KeyPart, item: Mapping[str, Any], *, sk: Optional[KeyPart] = None, condition: Optional[str] = None
+    ) -> Dict[str, Any]:
+        return {"put": {"pk": pk, "sk": sk, "item": dict(item), "condition": condition}}
+
+    @staticmethod
+    def update(
+        pk: KeyPart, expression: str, *, sk: Optional[KeyPart] = None, condition: Optional[str] = None
+    ) -> Dict[str, Any]:
+        return {"update": {"pk": pk, "sk": sk, "expression": expression, "condition": condition}}
+
+    @staticmethod
+    def delete(pk: KeyPart, *, sk: Optional[KeyPart] = None, condition: Optional[str] = None) -> Dict[str, Any]:
+        return {"delete": {"pk": pk, "sk": sk, "condition": condition}}
+
+    @staticmethod
+    def condition_check(pk: KeyPart, condition: str, *, sk: Optional[KeyPart] = None) -> Dict[str, Any]:
+        return {"condition_check": {"pk": pk, "sk": sk, "condition": condition}}
+
+
+__all__ = [
+    "AlreadyExistsError",
+    "ConditionalCheckFailedError",
+    "Database",
+    "InvalidArgumentError",
+    "ItemIterator",
+    "KeystoneError",
+    "NotFoundError",
+    "ResourceExhaustedError",
+    "Transact",
+    "TransactionCanceledError",
+    "open",
+]
@@ -0,0 +1,157 @@
+/// Conversion between Python objects and KeystoneDB values
+///
+/// | Python              | KeystoneDB |
+/// |---------------------|------------|
+/// | str                 | S          |
+/// | int, float          | N          |
+/// | bytes, bytearray    | B          |
+/// | bool                | Bool       |
+/// | None                | Null       |
+/// | list, tuple         | L          |
+/// | dict (str keys)     | M          |
+///
+/// Numbers come back as `int` when they parse as one and as `float`
+/// otherwise. Vectors come back as lists of floats and timestamps as `int`
+/// milliseconds since the epoch.
+
+use bytes::Bytes;
+use kstone_core::{Item, Value};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use std::collections::HashMap;
+
+/// A partition or sort key given as `bytes` or `str` (UTF-8 encoded)
+pub struct Key(pub Vec<u8>);
+
+impl<'py> FromPyObject<'py> for Key {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(text) = ob.downcast::<PyString>() {
+            return Ok(Key(text.to_cow()?.as_bytes().to_vec()));
+        }
+        if let Ok(bytes) = ob.downcast::<PyBytes>() {
+            return Ok(Key(bytes.as_bytes().to_vec()));
+        }
+        if let Ok(bytes) = ob.downcast::<PyByteArray>() {
+            return Ok(Key(bytes.to_vec()));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Keys must be bytes or str, not {}",
+            ob.get_type().name()?
+        )))
+    }
+}
+
+/// An item key given as `pk` or a `(pk, sk)` tuple
+pub struct ItemKey(pub kstone_core::Key);
+
+impl<'py> FromPyObject<'py> for ItemKey {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let (pk, sk) = match ob.downcast::<PyTuple>() {
+            Ok(tuple) => tuple.extract::<(Key, Option<Key>)>()?,
+            Err(_) => (ob.extract::<Key>()?, None),
+        };
+        Ok(ItemKey(match sk {
+            Some(sk) => kstone_core::Key::with_sk(Bytes::from(pk.0), Bytes::from(sk.0)),
+            None => kstone_core::Key::new(Bytes::from(pk.0)),
+        }))
+    }
+}
+
+/// Convert a Python object to a value
+pub fn to_value(ob: &Bound<'_, PyAny>) -> PyResult<Value> {
+    // bool is a subclass of int, so it goes first
+    if let Ok(flag) = ob.downcast::<PyBool>() {
+        return Ok(Value::Bool(flag.is_true()));
+    }
+    if ob.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(text) = ob.downcast::<PyString>() {
+        return Ok(Value::S(text.to_cow()?.into_owned()));
+    }
+    if ob.is_instance_of::<PyInt>() || ob.is_instance_of::<PyFloat>() {
+        return Ok(Value::N(ob.str()?.to_cow()?.into_owned()));
+    }
+    if let Ok(bytes) = ob.downcast::<PyBytes>() {
+        return Ok(Value::B(Bytes::copy_from_slice(bytes.as_bytes())));
+    }
+    if let Ok(bytes) = ob.downcast::<PyByteArray>() {
+        return Ok(Value::B(Bytes::from(bytes.to_vec())));
+    }
+    if let Ok(list) = ob.downcast::<PyList>() {
+        return list.iter().map(|v| to_value(&v)).collect::<PyResult<_>>().map(Value::L);
+    }
+    if let Ok(tuple) = ob.downcast::<PyTuple>() {
+        return tuple.iter().map(|v| to_value(&v)).collect::<PyResult<_>>().map(Value::L);
+    }
+    if let Ok(dict) = ob.downcast::<PyDict>() {
+        return to_item(dict).map(Value::M);
+    }
+    Err(PyTypeError::new_err(format!(
+        "Unsupported value type: {}",
+        ob.get_type().name()?
+    )))
+}
+
+/// Convert a dict of attributes to an item
+pub fn to_item(dict: &Bound<'_, PyDict>) -> PyResult<Item> {
+    dict.iter()
+        .map(|(name, value)| Ok((name.extract::<String>()?, to_value(&value)?)))
+        .collect()
+}
+
+/// Convert a value to a Python object
+pub fn from_value(py: Python<'_>, value: &Value) -> PyObject {
+    match value {
+        Value::S(text) => text.into_py(py),
+        Value::N(number) => match number.parse::<i64>() {
+            Ok(int) => int.into_py(py),
+            Err(_) => number.parse::<f64>().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::B(bytes) => PyBytes::new_bound(py, bytes).into_py(py),
+        Value::Bool(flag) => flag.into_py(py),
+        Value::Null => py.None(),
+        Value::L(values) => PyList::new_bound(py, values.iter().map(|v| from_value(py, v))).into_py(py),
+        Value::M(map) => from_item(py, map).into_py(py),
+        Value::VecF32(values) => values.clone().into_py(py),
+        Value::Ts(millis) => millis.into_py(py),
+    }
+}
+
+/// Convert an item to a dict of attributes
+pub fn from_item<'py>(py: Python<'py>, item: &HashMap<String, Value>) -> Bound<'py, PyDict> {
+    let dict = PyDict::new_bound(py);
+    for (name, value) in item {
+        // Setting a str key on a fresh dict can't fail
+        let _ = dict.set_item(name, from_value(py, value));
+    }
+    dict
+}
+
+/// Convert expression placeholder values (`{":min": 18}`)
+pub fn placeholder_values(values: Option<&Bound<'_, PyDict>>) -> PyResult<Vec<(String, Value)>> {
+    values.map_or(Ok(Vec::new()), |values| {
+        values
+            .iter()
+            .map(|(name, value)| Ok((name.extract::<String>()?, to_value(&value)?)))
+            .collect()
+    })
+}
+
+/// Convert expression attribute names (`{"#n": "name"}`)
+pub fn placeholder_names(names: Option<HashMap<String, String>>) -> Vec<(String, String)> {
+    names.map_or_else(Vec::new, |names| names.into_iter().collect())
+}
+
+/// A last key as `(pk, sk)` bytes, or None
+pub fn last_key(py: Python<'_>, key: Option<(Bytes, Option<Bytes>)>) -> PyObject {
+    match key {
+        Some((pk, sk)) => (
+            PyBytes::new_bound(py, &pk),
+            sk.map(|sk| PyBytes::new_bound(py, &sk)),
+        )
+            .into_py(py),
+        None => py.None(),
+    }
+}
@@ -0,0 +1,47 @@
+/// Exceptions raised by the binding
+///
+/// `KeystoneError` subclasses `RuntimeError`, so code catching the
+/// `RuntimeError` earlier versions raised keeps working.
+
+use kstone_api::KeystoneError as Error;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyOSError, PyRuntimeError};
+use pyo3::prelude::*;
+
+create_exception!(keystonedb, KeystoneError, PyRuntimeError, "Base class for every database error");
+create_exception!(keystonedb, NotFoundError, KeystoneError, "A table, index or other named resource doesn't exist");
+create_exception!(keystonedb, InvalidArgumentError, KeystoneError, "A bad expression, statement, key or value");
+create_exception!(keystonedb, ConditionalCheckFailedError, KeystoneError, "A condition expression evaluated to false");
+create_exception!(keystonedb, TransactionCanceledError, KeystoneError, "A transaction was canceled; no part of it was applied");
+create_exception!(keystonedb, AlreadyExistsError, KeystoneError, "The database being created already exists");
+create_exception!(keystonedb, ResourceExhaustedError, KeystoneError, "A quota or limit was exceeded");
+
+/// The exception for an engine error
+pub fn to_py_err(error: Error) -> PyErr {
+    let message = error.to_string();
+    match error {
+        Error::Io(_) => PyOSError::new_err(message),
+        Error::NotFound(_) => NotFoundError::new_err(message),
+        Error::InvalidArgument(_) | Error::InvalidExpression(_) | Error::InvalidQuery(_) => {
+            InvalidArgumentError::new_err(message)
+        }
+        Error::ConditionalCheckFailed(_) => ConditionalCheckFailedError::new_err(message),
+        Error::TransactionCanceled(_) => TransactionCanceledError::new_err(message),
+        Error::AlreadyExists(_) => AlreadyExistsError::new_err(message),
+        Error::ResourceExhausted(_) => ResourceExhaustedError::new_err(message),
+        _ => KeystoneError::new_err(message),
+    }
+}
+
+/// Register the exception classes on the module
+pub fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("KeystoneError", py.get_type_bound::<KeystoneError>())?;
+    module.add("NotFoundError", py.get_type_bound::<NotFoundError>())?;
+    module.add("InvalidArgumentError", py.get_type_bound::<InvalidArgumentError>())?;
+    module.add("ConditionalCheckFailedError", py.get_type_bound::<ConditionalCheckFailedError>())?;
+    module.add("TransactionCanceledError", py.get_type_bound::<TransactionCanceledError>())?;
+    module.add("AlreadyExistsError", py.get_type_bound::<AlreadyExistsError>())?;
+    module.add("ResourceExhaustedError", py.get_type_bound::<ResourceExhaustedError>())?;
+    Ok(())
+}
@@ -0,0 +1,570 @@
+#![allow(clippy::useless_conversion)] // false positive on #[pymethods] results (pyo3 0.22)
+
+/// Embedded KeystoneDB for Python
+///
+/// The native half of the `keystonedb` package. Database calls release the
+/// GIL, so threads can read and write concurrently.
+
+mod convert;
+mod errors;
+mod pages;
+
+use convert::{from_item, last_key, placeholder_names, placeholder_values, to_item, to_value, ItemKey, Key};
+use errors::to_py_err;
+use kstone_api::{
+    ExecuteStatementRequest, ExecuteStatementResponse, TransactGetRequest, TransactWriteOp, TransactWriteRequest,
+    Update,
+};
+use pages::{
+    Cursor, Filter, ItemIterator, Page, QueryOptions, Request, ScanOptions, SortKeyArgs, StartAfter,
+    DEFAULT_PAGE_SIZE,
+};
+use pyo3::exceptions::{PyImportError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::collections::HashMap;
+
+/// An open KeystoneDB database
+///
+/// Usable as a context manager: leaving the `with` block flushes and
+/// closes it.
+#[pyclass(module = "keystonedb")]
+pub struct Database {
+    /// None once closed
+    db: Option<kstone_api::Database>,
+}
+
+impl Database {
+    /// The open database, or `KeystoneError` once closed
+    pub(crate) fn inner(&self) -> PyResult<&kstone_api::Database> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| errors::KeystoneError::new_err("Database is closed"))
+    }
+
+    /// One page of a query or scan as a dict
+    fn page(py: Python<'_>, page: Page) -> PyResult<Bound<'_, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("count", page.items.len())?;
+        dict.set_item(
+            "items",
+            page.items.iter().map(|item| from_item(py, item)).collect::<Vec<_>>(),
+        )?;
+        dict.set_item("scanned_count", page.scanned_count)?;
+        dict.set_item("last_key", last_key(py, page.last_key))?;
+        Ok(dict)
+    }
+
+    /// Every item of a query or scan as a pandas DataFrame
+    fn data_frame<'py>(&self, py: Python<'py>, request: Request, page_size: usize) -> PyResult<Bound<'py, PyAny>> {
+        let pandas = py.import_bound("pandas").map_err(|_| {
+            PyImportError::new_err("DataFrames need pandas: pip install 'keystonedb[pandas]'")
+        })?;
+        let db = self.inner()?;
+        let mut cursor = Cursor::new(&request, page_size)?;
+        let mut items = Vec::new();
+        while let Some(page) = py
+            .allow_threads(|| cursor.next_page(db, &request))
+            .map_err(to_py_err)?
+        {
+            items.extend(page.iter().map(|item| from_item(py, item)));
+        }
+        pandas
+            .getattr("DataFrame")?
+            .call_method1("from_records", (PyList::new_bound(py, items),))
+    }
+}
+
+/// Parse one `transact_write` operation: a dict with a single `put`,
+/// `update`, `delete` or `condition_check` entry (see `keystonedb.Transact`)
+fn transact_op(op: &Bound<'_, PyAny>) -> PyResult<TransactWriteOp> {
+    let op = op
+        .downcast::<PyDict>()
+        .map_err(|_| PyTypeError::new_err("Transaction operations must be dicts"))?;
+    if op.len() != 1 {
+        return Err(PyValueError::new_err(
+            "Each transaction operation needs exactly one of put, update, delete or condition_check",
+        ));
+    }
+    let (kind, args) = op.iter().next().expect("one entry");
+    let kind: String = kind.extract()?;
+    let args = args
+        .downcast::<PyDict>()
+        .map_err(|_| PyTypeError::new_err(format!("The {} operation must be a dict", kind)))?;
+    let required = |name: &str| {
+        args.get_item(name)?
+            .ok_or_else(|| PyValueError::new_err(format!("The {} operation needs {:?}", kind, name)))
+    };
+    let optional = |name: &str| -> PyResult<Option<String>> {
+        args.get_item(name)?.filter(|v| !v.is_none()).map(|v| v.extract()).transpose()
+    };
+    let pk = required("pk")?.extract::<Key>()?;
+    let key = match args.get_item("sk")?.filter(|v| !v.is_none()) {
+        Some(sk) => kstone_core::Key::with_sk(pk.0, sk.extract::<Key>()?.0),
+        None => kstone_core::Key::new(pk.0),
+    };
+    let condition = optional("condition")?;
+
+    match kind.as_str() {
+        "put" => Ok(TransactWriteOp::Put {
+            key,
+            item: to_item(required("item")?.downcast::<PyDict>()?)?,
+            condition,
+        }),
+        "update" => Ok(TransactWriteOp::Update {
+            key,
+            update_expression: required("expression")?.extract()?,
+            condition,
+        }),
+        "delete" => Ok(TransactWriteOp::Delete { key, condition }),
+        "condition_check" => Ok(TransactWriteOp::ConditionCheck {
+            key,
+            condition: condition.ok_or_else(|| PyValueError::new_err("condition_check needs a condition"))?,
+        }),
+        other => Err(PyValueError::new_err(format!("Unknown transaction operation {:?}", other))),
+    }
+}
+
+#[pymethods]
+impl Database {
+    /// Create a new database directory at `path`
+    #[staticmethod]
+    fn create(path: &str) -> PyResult<Self> {
+        let db = kstone_api::Database::create(path).map_err(to_py_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Open an existing database directory at `path`
+    #[staticmethod]
+    fn open(path: &str) -> PyResult<Self> {
+        let db = kstone_api::Database::open(path).map_err(to_py_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Create a database that lives only in memory
+    #[staticmethod]
+    fn create_in_memory() -> PyResult<Self> {
+        let db = kstone_api::Database::create_in_memory().map_err(to_py_err)?;
+        Ok(Self { db: Some(db) })
+    }
+
+    /// Store an item
+    fn put(&self, py: Python<'_>, pk: Key, item: &Bound<'_, PyDict>) -> PyResult<()> {
+        let db = self.inner()?;
+        let item = to_item(item)?;
+        py.allow_threads(|| db.put(&pk.0, item)).map_err(to_py_err)
+    }
+
+    /// Store an item under a partition and sort key
+    fn put_with_sk(&self, py: Python<'_>, pk: Key, sk: Key, item: &Bound<'_, PyDict>) -> PyResult<()> {
+        let db = self.inner()?;
+        let item = to_item(item)?;
+        py.allow_threads(|| db.put_with_sk(&pk.0, &sk.0, item))
+            .map_err(to_py_err)
+    }
+
+    /// Get an item as a dict, or None
+    fn get<'py>(&self, py: Python<'py>, pk: Key) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let db = self.inner()?;
+        let item = py.allow_threads(|| db.get(&pk.0)).map_err(to_py_err)?;
+        Ok(item.map(|item| from_item(py, &item)))
+    }
+
+    /// Get an item by partition and sort key as a dict, or None
+    fn get_with_sk<'py>(&self, py: Python<'py>, pk: Key, sk: Key) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let db = self.inner()?;
+        let item = py
+            .allow_threads(|| db.get_with_sk(&pk.0, &sk.0))
+            .map_err(to_py_err)?;
+        Ok(item.map(|item| from_item(py, &item)))
+    }
+
+    /// Delete an item; deleting a missing item succeeds
+    fn delete(&self, py: Python<'_>, pk: Key) -> PyResult<()> {
+        let db = self.inner()?;
+        py.allow_threads(|| db.delete(&pk.0)).map_err(to_py_err)
+    }
+
+    /// Delete an item by partition and sort key
+    fn delete_with_sk(&self, py: Python<'_>, pk: Key, sk: Key) -> PyResult<()> {
+        let db = self.inner()?;
+        py.allow_threads(|| db.delete_with_sk(&pk.0, &sk.0))
+            .map_err(to_py_err)
+    }
+
+    /// Flush memtables to disk
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        let db = self.inner()?;
+        py.allow_threads(|| db.flush()).map_err(to_py_err)
+    }
+
+    /// Flush and close the database; later calls raise `KeystoneError`
+    ///
+    /// Closing twice is harmless.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        if let Some(db) = self.db.take() {
+            py.allow_threads(|| db.flush()).map_err(to_py_err)?;
+        }
+        Ok(())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Flush and close when the `with` block ends, even on an exception
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, py: Python<'_>, _args: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+
+    /// Query one partition, returning one page:
+    /// `{"items": [...], "count", "scanned_count", "last_key"}`
+    ///
+    /// Pass `last_key` back as `start_after` to fetch the next page; a page
+    /// may come back with a `last_key` even when nothing follows. At most
+    /// one sort key condition may be given.
+    #[pyo3(signature = (
+        pk, *, sk_eq=None, sk_lt=None, sk_lte=None, sk_gt=None, sk_gte=None,
+        sk_begins_with=None, sk_between=None, forward=true, limit=None, index=None,
+        filter=None, values=None, names=None, start_after=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn query<'py>(
+        &self,
+        py: Python<'py>,
+        pk: Key,
+        sk_eq: Option<Key>,
+        sk_lt: Option<Key>,
+        sk_lte: Option<Key>,
+        sk_gt: Option<Key>,
+        sk_gte: Option<Key>,
+        sk_begins_with: Option<Key>,
+        sk_between: Option<(Key, Key)>,
+        forward: bool,
+        limit: Option<usize>,
+        index: Option<String>,
+        filter: Option<String>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        start_after: Option<StartAfter>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let db = self.inner()?;
+        let sort_key = SortKeyArgs {
+            eq: sk_eq,
+            lt: sk_lt,
+            lte: sk_lte,
+            gt: sk_gt,
+            gte: sk_gte,
+            begins_with: sk_begins_with,
+            between: sk_between,
+        };
+        let filter = Filter::new(filter, values, names)?;
+        let request = Request::Query(QueryOptions::new(pk, sort_key, forward, limit, index, filter)?);
+        let page = py
+            .allow_threads(|| request.fetch_one(db, start_after))
+            .map_err(to_py_err)?;
+        Self::page(py, page)
+    }
+
+    /// Every item of a query as a pandas DataFrame, one row per item
+    ///
+    /// Takes the options of `query`, fetching `page_size` items at a time.
+    /// Needs pandas (`pip install 'keystonedb[pandas]'`).
+    #[pyo3(signature = (
+        pk, *, sk_eq=None, sk_lt=None, sk_lte=None, sk_gt=None, sk_gte=None,
+        sk_begins_with=None, sk_between=None, forward=true, limit=None, index=None,
+        filter=None, values=None, names=None, page_size=DEFAULT_PAGE_SIZE
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn query_df<'py>(
+        &self,
+        py: Python<'py>,
+        pk: Key,
+        sk_eq: Option<Key>,
+        sk_lt: Option<Key>,
+        sk_lte: Option<Key>,
+        sk_gt: Option<Key>,
+        sk_gte: Option<Key>,
+        sk_begins_with: Option<Key>,
+        sk_between: Option<(Key, Key)>,
+        forward: bool,
+        limit: Option<usize>,
+        index: Option<String>,
+        filter: Option<String>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        page_size: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let sort_key = SortKeyArgs {
+            eq: sk_eq,
+            lt: sk_lt,
+            lte: sk_lte,
+            gt: sk_gt,
+            gte: sk_gte,
+            begins_with: sk_begins_with,
+            between: sk_between,
+        };
+        let filter = Filter::new(filter, values, names)?;
+        let request = Request::Query(QueryOptions::new(pk, sort_key, forward, limit, index, filter)?);
+        self.data_frame(py, request, page_size)
+    }
+
+    /// Scan the table, returning one page like `query`
+    ///
+    /// `segment` and `total_segments` split the table for parallel workers;
+    /// give both or neither.
+    #[pyo3(signature = (
+        *, limit=None, pk_prefix=None, segment=None, total_segments=None,
+        filter=None, values=None, names=None, start_after=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn scan<'py>(
+        &self,
+        py: Python<'py>,
+        limit: Option<usize>,
+        pk_prefix: Option<Key>,
+        segment: Option<usize>,
+        total_segments: Option<usize>,
+        filter: Option<String>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        start_after: Option<StartAfter>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let db = self.inner()?;
+        let filter = Filter::new(filter, values, names)?;
+        let request = Request::Scan(ScanOptions::new(limit, pk_prefix, segment, total_segments, filter)?);
+        let page = py
+            .allow_threads(|| request.fetch_one(db, start_after))
+            .map_err(to_py_err)?;
+        Self::page(py, page)
+    }
+
+    /// Iterate over every item of a scan, fetching `page_size` items at a time
+    ///
+    /// ```python
+    /// for item in db.scan_iter(pk_prefix=b"user#"):
+    ///     ...
+    /// ```
+    ///
+    /// Takes the options of `scan`; `limit` caps the total number of items.
+    #[pyo3(signature = (
+        *, limit=None, pk_prefix=None, segment=None, total_segments=None,
+        filter=None, values=None, names=None, page_size=DEFAULT_PAGE_SIZE
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn scan_iter<'py>(
+        slf: &Bound<'py, Self>,
+        limit: Option<usize>,
+        pk_prefix: Option<Key>,
+        segment: Option<usize>,
+        total_segments: Option<usize>,
+        filter: Option<String>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        page_size: usize,
+    ) -> PyResult<ItemIterator> {
+        slf.borrow().inner()?;
+        let filter = Filter::new(filter, values, names)?;
+        let request = Request::Scan(ScanOptions::new(limit, pk_prefix, segment, total_segments, filter)?);
+        ItemIterator::new(slf.clone().unbind(), request, page_size)
+    }
+
+    /// Every item of a scan as a pandas DataFrame, one row per item
+    ///
+    /// Takes the options of `scan`, fetching `page_size` items at a time.
+    /// Needs pandas (`pip install 'keystonedb[pandas]'`).
+    #[pyo3(signature = (
+        *, limit=None, pk_prefix=None, segment=None, total_segments=None,
+        filter=None, values=None, names=None, page_size=DEFAULT_PAGE_SIZE
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn scan_df<'py>(
+        &self,
+        py: Python<'py>,
+        limit: Option<usize>,
+        pk_prefix: Option<Key>,
+        segment: Option<usize>,
+        total_segments: Option<usize>,
+        filter: Option<String>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        page_size: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let filter = Filter::new(filter, values, names)?;
+        let request = Request::Scan(ScanOptions::new(limit, pk_prefix, segment, total_segments, filter)?);
+        self.data_frame(py, request, page_size)
+    }
+
+    /// Apply an update expression and return the updated item
+    ///
+    /// ```python
+    /// db.update(b"user#1", "SET visits = visits + :one", values={":one": 1},
+    ///           condition="attribute_exists(visits)")
+    /// ```
+    #[pyo3(signature = (pk, expression, *, sk=None, values=None, names=None, condition=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn update<'py>(
+        &self,
+        py: Python<'py>,
+        pk: Key,
+        expression: String,
+        sk: Option<Key>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+        condition: Option<String>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let db = self.inner()?;
+        let mut update = match &sk {
+            Some(sk) => Update::with_sk(&pk.0, &sk.0),
+            None => Update::new(&pk.0),
+        }
+        .expression(expression);
+        if let Some(condition) = condition {
+            update = update.condition(condition);
+        }
+        for (placeholder, value) in placeholder_values(values)? {
+            update = update.value(placeholder, value);
+        }
+        for (placeholder, name) in placeholder_names(names) {
+            update = update.name(placeholder, name);
+        }
+
+        let response = py.allow_threads(|| db.update(update)).map_err(to_py_err)?;
+        Ok(from_item(py, &response.item))
+    }
+
+    /// Run a PartiQL statement and return its result as a dict with a `kind`:
+    ///
+    /// - `select`: `items`, `count`, `scanned_count`, `last_key`, `warnings`
+    /// - `update`: `item`, the item after the update
+    /// - `insert`, `delete`: `success`
+    /// - `insert_select`: `inserted`
+    /// - `explain`: `plan`
+    ///
+    /// `params` binds `?` placeholders in order when a list and `:name`
+    /// placeholders when a dict.
+    #[pyo3(signature = (sql, params=None))]
+    fn execute<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let db = self.inner()?;
+        let mut request = ExecuteStatementRequest::new(sql);
+        match params {
+            None => {}
+            Some(params) if params.is_instance_of::<PyDict>() => {
+                for (name, value) in params.downcast::<PyDict>()?.iter() {
+                    let name: String = name.extract()?;
+                    request = request.with_named_parameter(name.trim_start_matches(':'), to_value(&value)?);
+                }
+            }
+            Some(params) => {
+                let values = params
+                    .iter()
+                    .map_err(|_| PyTypeError::new_err("params must be a list or a dict"))?
+                    .map(|value| to_value(&value?))
+                    .collect::<PyResult<_>>()?;
+                request = request.with_parameters(values);
+            }
+        }
+
+        let response = py.allow_threads(|| db.execute(request)).map_err(to_py_err)?;
+        let result = PyDict::new_bound(py);
+        match response {
+            ExecuteStatementResponse::Select {
+                items,
+                scanned_count,
+                last_key: last,
+                warnings,
+                ..
+            } => {
+                result.set_item("kind", "select")?;
+                result.set_item("items", items.iter().map(|item| from_item(py, item)).collect::<Vec<_>>())?;
+                result.set_item("count", items.len())?;
+                result.set_item("scanned_count", scanned_count)?;
+                result.set_item("last_key", last_key(py, last))?;
+                result.set_item("warnings", warnings)?;
+            }
+            ExecuteStatementResponse::Update { item } => {
+                result.set_item("kind", "update")?;
+                result.set_item("item", from_item(py, &item))?;
+            }
+            ExecuteStatementResponse::Insert { success } => {
+                result.set_item("kind", "insert")?;
+                result.set_item("success", success)?;
+            }
+            ExecuteStatementResponse::Delete { success } => {
+                result.set_item("kind", "delete")?;
+                result.set_item("success", success)?;
+            }
+            ExecuteStatementResponse::InsertSelect { inserted } => {
+                result.set_item("kind", "insert_select")?;
+                result.set_item("inserted", inserted)?;
+            }
+            ExecuteStatementResponse::Explain { plan } => {
+                let plan = serde_json::to_string(&plan).map_err(|e| PyValueError::new_err(e.to_string()))?;
+                result.set_item("kind", "explain")?;
+                result.set_item("plan", py.import_bound("json")?.call_method1("loads", (plan,))?)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Apply writes atomically and return how many were committed
+    ///
+    /// Operations are dicts built with `keystonedb.Transact`; `values` and
+    /// `names` are shared by every expression. Raises
+    /// `TransactionCanceledError` if any condition fails, in which case
+    /// nothing is written.
+    #[pyo3(signature = (operations, *, values=None, names=None))]
+    fn transact_write<'py>(
+        &self,
+        py: Python<'py>,
+        operations: &Bound<'py, PyAny>,
+        values: Option<&Bound<'py, PyDict>>,
+        names: Option<HashMap<String, String>>,
+    ) -> PyResult<usize> {
+        let db = self.inner()?;
+        let mut request = TransactWriteRequest::new();
+        for op in operations.iter()? {
+            request.operations.push(transact_op(&op?)?);
+        }
+        for (placeholder, value) in placeholder_values(values)? {
+            request = request.value(placeholder, value);
+        }
+        for (placeholder, name) in placeholder_names(names) {
+            request = request.name(placeholder, name);
+        }
+
+        let response = py.allow_threads(|| db.transact_write(request)).map_err(to_py_err)?;
+        Ok(response.committed_count)
+    }
+
+    /// Read several items consistently, in order; None for each missing item
+    ///
+    /// Keys are `pk` or `(pk, sk)` tuples.
+    fn transact_get<'py>(&self, py: Python<'py>, keys: Vec<ItemKey>) -> PyResult<Bound<'py, PyList>> {
+        let db = self.inner()?;
+        let mut request = TransactGetRequest::new();
+        request.keys = keys.into_iter().map(|key| key.0).collect();
+        let response = py.allow_threads(|| db.transact_get(request)).map_err(to_py_err)?;
+        Ok(PyList::new_bound(
+            py,
+            response
+                .items
+                .iter()
+                .map(|item| item.as_ref().map(|item| from_item(py, item))),
+        ))
+    }
+}
+
+#[pymodule]
+fn _keystonedb(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Database>()?;
+    module.add_class::<ItemIterator>()?;
+    errors::register(module)
+}
@@ -0,0 +1,349 @@
+/// Query and scan options, and iteration over every page
+///
+/// Options are parsed once into owned values so the same request can be
+/// rebuilt for each page: `Query` and `Scan` are consumed by the call.
+
+use crate::convert::{from_item, placeholder_names, placeholder_values, Key};
+use crate::errors::to_py_err;
+use crate::Database;
+use kstone_api::{Query, Scan};
+use kstone_core::{Item, Value};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::{HashMap, VecDeque};
+
+/// A `(pk, sk)` key to continue after, as returned in `last_key`
+pub type StartAfter = (Key, Option<Key>);
+
+type OwnedKey = (Vec<u8>, Option<Vec<u8>>);
+
+/// Items fetched per page by `scan_iter`, `scan_df` and `query_df`
+pub const DEFAULT_PAGE_SIZE: usize = 1000;
+
+fn owned_key(key: Option<StartAfter>) -> Option<OwnedKey> {
+    key.map(|(pk, sk)| (pk.0, sk.map(|sk| sk.0)))
+}
+
+/// Filter expression and its placeholders
+#[derive(Clone, Default)]
+pub struct Filter {
+    expression: Option<String>,
+    values: Vec<(String, Value)>,
+    names: Vec<(String, String)>,
+}
+
+impl Filter {
+    pub fn new(
+        expression: Option<String>,
+        values: Option<&Bound<'_, PyDict>>,
+        names: Option<HashMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            expression,
+            values: placeholder_values(values)?,
+            names: placeholder_names(names),
+        })
+    }
+}
+
+enum SortKeyCondition {
+    Eq(Vec<u8>),
+    Lt(Vec<u8>),
+    Lte(Vec<u8>),
+    Gt(Vec<u8>),
+    Gte(Vec<u8>),
+    Between(Vec<u8>, Vec<u8>),
+    BeginsWith(Vec<u8>),
+}
+
+/// Sort key conditions as given to `query`; at most one may be set
+#[derive(Default)]
+pub struct SortKeyArgs {
+    pub eq: Option<Key>,
+    pub lt: Option<Key>,
+    pub lte: Option<Key>,
+    pub gt: Option<Key>,
+    pub gte: Option<Key>,
+    pub begins_with: Option<Key>,
+    pub between: Option<(Key, Key)>,
+}
+
+impl SortKeyArgs {
+    fn into_condition(self) -> PyResult<Option<SortKeyCondition>> {
+        let mut conditions = Vec::new();
+        conditions.extend(self.eq.map(|sk| SortKeyCondition::Eq(sk.0)));
+        conditions.extend(self.lt.map(|sk| SortKeyCondition::Lt(sk.0)));
+        conditions.extend(self.lte.map(|sk| SortKeyCondition::Lte(sk.0)));
+        conditions.extend(self.gt.map(|sk| SortKeyCondition::Gt(sk.0)));
+        conditions.extend(self.gte.map(|sk| SortKeyCondition::Gte(sk.0)));
+        conditions.extend(self.begins_with.map(|sk| SortKeyCondition::BeginsWith(sk.0)));
+        conditions.extend(self.between.map(|(low, high)| SortKeyCondition::Between(low.0, high.0)));
+        if conditions.len() > 1 {
+            return Err(PyValueError::new_err("Only one sort key condition may be given"));
+        }
+        Ok(conditions.pop())
+    }
+}
+
+/// Options of a query, rebuilt into a `Query` for every page
+pub struct QueryOptions {
+    pk: Vec<u8>,
+    sk_condition: Option<SortKeyCondition>,
+    forward: bool,
+    limit: Option<usize>,
+    index: Option<String>,
+    filter: Filter,
+}
+
+impl QueryOptions {
+    pub fn new(
+        pk: Key,
+        sort_key: SortKeyArgs,
+        forward: bool,
+        limit: Option<usize>,
+        index: Option<String>,
+        filter: Filter,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            pk: pk.0,
+            sk_condition: sort_key.into_condition()?,
+            forward,
+            limit,
+            index,
+            filter,
+        })
+    }
+
+    fn build(&self, limit: Option<usize>, start_after: Option<&OwnedKey>) -> Query {
+        let mut query = Query::new(&self.pk).forward(self.forward);
+        query = match &self.sk_condition {
+            None => query,
+            Some(SortKeyCondition::Eq(sk)) => query.sk_eq(sk),
+            Some(SortKeyCondition::Lt(sk)) => query.sk_lt(sk),
+            Some(SortKeyCondition::Lte(sk)) => query.sk_lte(sk),
+            Some(SortKeyCondition::Gt(sk)) => query.sk_gt(sk),
+            Some(SortKeyCondition::Gte(sk)) => query.sk_gte(sk),
+            Some(SortKeyCondition::Between(low, high)) => query.sk_between(low, high),
+            Some(SortKeyCondition::BeginsWith(prefix)) => query.sk_begins_with(prefix),
+        };
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+        if let Some(index) = &self.index {
+            query = query.index(index.clone());
+        }
+        if let Some(filter) = &self.filter.expression {
+            query = query.filter(filter.clone());
+        }
+        for (placeholder, value) in &self.filter.values {
+            query = query.value(placeholder.clone(), value.clone());
+        }
+        for (placeholder, name) in &self.filter.names {
+            query = query.name(placeholder.clone(), name.clone());
+        }
+        if let Some((pk, sk)) = start_after {
+            query = query.start_after(pk, sk.as_deref());
+        }
+        query
+    }
+}
+
+/// Options of a scan, rebuilt into a `Scan` for every page
+pub struct ScanOptions {
+    limit: Option<usize>,
+    pk_prefix: Option<Vec<u8>>,
+    segment: Option<(usize, usize)>,
+    filter: Filter,
+}
+
+impl ScanOptions {
+    pub fn new(
+        limit: Option<usize>,
+        pk_prefix: Option<Key>,
+        segment: Option<usize>,
+        total_segments: Option<usize>,
+        filter: Filter,
+    ) -> PyResult<Self> {
+        let segment = match (segment, total_segments) {
+            (None, None) => None,
+            (Some(segment), Some(total)) if segment < total => Some((segment, total)),
+            (Some(segment), Some(total)) => {
+                return Err(PyValueError::new_err(format!(
+                    "Segment {} is out of range for {} segments",
+                    segment, total
+                )))
+            }
+            _ => return Err(PyValueError::new_err("Give both segment and total_segments")),
+        };
+        Ok(Self {
+            limit,
+            pk_prefix: pk_prefix.map(|prefix| prefix.0),
+            segment,
+            filter,
+        })
+    }
+
+    fn build(&self, limit: Option<usize>, start_after: Option<&OwnedKey>) -> Scan {
+        let mut scan = Scan::new();
+        if let Some(limit) = limit {
+            scan = scan.limit(limit);
+        }
+        if let Some(prefix) = &self.pk_prefix {
+            scan = scan.pk_prefix(prefix);
+        }
+        if let Some((segment, total)) = self.segment {
+            scan = scan.segment(segment, total);
+        }
+        if let Some(filter) = &self.filter.expression {
+            scan = scan.filter(filter.clone());
+        }
+        for (placeholder, value) in &self.filter.values {
+            scan = scan.value(placeholder.clone(), value.clone());
+        }
+        for (placeholder, name) in &self.filter.names {
+            scan = scan.name(placeholder.clone(), name.clone());
+        }
+        if let Some((pk, sk)) = start_after {
+            scan = scan.start_after(pk, sk.as_deref());
+        }
+        scan
+    }
+}
+
+/// One page: items, scanned count and the key to continue after
+pub struct Page {
+    pub items: Vec<Item>,
+    pub scanned_count: usize,
+    pub last_key: Option<(bytes::Bytes, Option<bytes::Bytes>)>,
+}
+
+/// A query or scan that can be fetched page by page
+pub enum Request {
+    Query(QueryOptions),
+    Scan(ScanOptions),
+}
+
+impl Request {
+    fn limit(&self) -> Option<usize> {
+        match self {
+            Request::Query(options) => options.limit,
+            Request::Scan(options) => options.limit,
+        }
+    }
+
+    /// Fetch the page of at most `limit` items after `start_after`
+    pub fn fetch(
+        &self,
+        db: &kstone_api::Database,
+        limit: Option<usize>,
+        start_after: Option<&OwnedKey>,
+    ) -> kstone_core::Result<Page> {
+        match self {
+            Request::Query(options) => db.query(options.build(limit, start_after)).map(|response| Page {
+                items: response.items,
+                scanned_count: response.scanned_count,
+                last_key: response.last_key,
+            }),
+            Request::Scan(options) => db.scan(options.build(limit, start_after)).map(|response| Page {
+                items: response.items,
+                scanned_count: response.scanned_count,
+                last_key: response.last_key,
+            }),
+        }
+    }
+
+    /// Fetch one page as the caller asked for it (`limit`, `start_after`)
+    pub fn fetch_one(&self, db: &kstone_api::Database, start_after: Option<StartAfter>) -> kstone_core::Result<Page> {
+        self.fetch(db, self.limit(), owned_key(start_after).as_ref())
+    }
+}
+
+/// Where a paged read stands between calls
+pub struct Cursor {
+    page_size: usize,
+    /// Items still allowed by the caller's `limit`
+    remaining: Option<usize>,
+    start_after: Option<OwnedKey>,
+    done: bool,
+}
+
+impl Cursor {
+    pub fn new(request: &Request, page_size: usize) -> PyResult<Self> {
+        if page_size == 0 {
+            return Err(PyValueError::new_err("page_size must be at least 1"));
+        }
+        Ok(Self {
+            page_size,
+            remaining: request.limit(),
+            start_after: None,
+            done: request.limit() == Some(0),
+        })
+    }
+
+    /// Fetch the next page, or None once the read is exhausted
+    pub fn next_page(&mut self, db: &kstone_api::Database, request: &Request) -> kstone_core::Result<Option<Vec<Item>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let limit = self.remaining.map_or(self.page_size, |remaining| remaining.min(self.page_size));
+        let mut page = request.fetch(db, Some(limit), self.start_after.as_ref())?;
+        page.items.truncate(limit);
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= page.items.len();
+        }
+        // A page may carry a last key even when nothing follows; a short
+        // page (by returned or by examined items) is the last one
+        let short = page.items.len() < limit && page.scanned_count < limit;
+        match page.last_key {
+            Some((pk, sk)) if !short && self.remaining != Some(0) => {
+                self.start_after = Some((pk.to_vec(), sk.map(|sk| sk.to_vec())));
+            }
+            _ => self.done = true,
+        }
+        Ok(Some(page.items))
+    }
+}
+
+/// Lazy iterator over every item of a scan (`Database.scan_iter`)
+#[pyclass(module = "keystonedb")]
+pub struct ItemIterator {
+    db: Py<Database>,
+    request: Request,
+    cursor: Cursor,
+    buffer: VecDeque<Item>,
+}
+
+impl ItemIterator {
+    pub fn new(db: Py<Database>, request: Request, page_size: usize) -> PyResult<Self> {
+        let cursor = Cursor::new(&request, page_size)?;
+        Ok(Self {
+            db,
+            request,
+            cursor,
+            buffer: VecDeque::new(),
+        })
+    }
+}
+
+#[pymethods]
+impl ItemIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Ok(Some(from_item(py, &item)));
+            }
+            let db = self.db.borrow(py);
+            let db = db.inner()?;
+            let (cursor, request) = (&mut self.cursor, &self.request);
+            match py.allow_threads(|| cursor.next_page(db, request)).map_err(to_py_err)? {
+                Some(items) => self.buffer.extend(items),
+                None => return Ok(None),
+            }
+        }
+    }
+}
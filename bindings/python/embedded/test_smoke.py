@@ -0,0 +1,174 @@
+import pytest
+
+import keystonedb
+
+
+@pytest.fixture
+def db():
+    return keystonedb.Database.create_in_memory()
+
+
+@pytest.fixture
+def org(db):
+    for i in range(5):
+        db.put_with_sk(b"org#acme", f"user#{i}".encode(), {"n": i, "active": i % 2 == 0})
+    db.put_with_sk(b"org#acme", b"team#1", {"n": 9})
+    db.put(b"solo", {"n": 10})
+    return db
+
+
+def test_put_get_delete(db):
+    item = {"s": "hello", "n": 42, "f": 3.14, "b": b"\x00\x01", "t": True, "z": None,
+            "l": [1, "two"], "m": {"inner": "value"}}
+    db.put(b"key", item)
+    assert db.get(b"key") == item
+    assert db.get("key") == item  # str keys are UTF-8 encoded
+
+    db.put_with_sk(b"org#acme", b"user#alice", {"role": "admin"})
+    assert db.get_with_sk(b"org#acme", b"user#alice") == {"role": "admin"}
+
+    db.delete(b"key")
+    db.delete_with_sk(b"org#acme", b"user#alice")
+    assert db.get(b"key") is None
+    assert db.get_with_sk(b"org#acme", b"user#alice") is None
+
+
+def test_unsupported_values(db):
+    with pytest.raises(TypeError):
+        db.put(b"key", {"bad": object()})
+    with pytest.raises(TypeError):
+        db.get(42)
+
+
+def test_query_pages(org):
+    seen = []
+    start_after = None
+    while True:
+        page = org.query(b"org#acme", sk_begins_with=b"user#", limit=2, start_after=start_after)
+        seen += [item["n"] for item in page["items"]]
+        assert page["count"] == len(page["items"])
+        if page["count"] < 2:
+            break
+        start_after = page["last_key"]
+    assert seen == [0, 1, 2, 3, 4]
+
+
+def test_query_conditions(org):
+    page = org.query(b"org#acme", sk_between=(b"user#1", b"user#3"), forward=False)
+    assert [item["n"] for item in page["items"]] == [3, 2, 1]
+
+    page = org.query(b"org#acme", filter="active = :yes", values={":yes": True})
+    assert [item["n"] for item in page["items"]] == [0, 2, 4]
+    assert page["scanned_count"] == 6
+
+    with pytest.raises(ValueError):
+        org.query(b"org#acme", sk_eq=b"a", sk_gt=b"b")
+
+
+def test_scan_segments(org):
+    assert org.scan(pk_prefix=b"org#")["count"] == 6
+    total = sum(org.scan(segment=s, total_segments=4)["count"] for s in range(4))
+    assert total == 7
+    with pytest.raises(ValueError):
+        org.scan(segment=4, total_segments=4)
+    with pytest.raises(ValueError):
+        org.scan(segment=0)
+
+
+def test_update(org):
+    item = org.update(b"org#acme", "SET n = n + :d, #s = :s", sk=b"user#1",
+                      values={":d": 10, ":s": "x"}, names={"#s": "status"})
+    assert item == {"n": 11, "active": False, "status": "x"}
+
+    with pytest.raises(keystonedb.ConditionalCheckFailedError):
+        org.update(b"solo", "SET n = :n", values={":n": 0}, condition="n < :n")
+
+
+def test_exceptions_keep_runtime_error_base(db):
+    assert issubclass(keystonedb.ConditionalCheckFailedError, keystonedb.KeystoneError)
+    assert issubclass(keystonedb.KeystoneError, RuntimeError)
+    with pytest.raises(keystonedb.InvalidArgumentError):
+        db.update(b"k", "SET = broken")
+
+
+def test_execute(db):
+    assert db.execute("INSERT INTO items VALUE {'pk': ?, 'n': ?}", ["a", 1]) == {"kind": "insert", "success": True}
+    db.execute("INSERT INTO items VALUE {'pk': :pk, 'n': :n}", {"pk": "b", "n": 2})
+
+    result = db.execute("SELECT * FROM items WHERE pk = ?", ["a"])
+    assert result["kind"] == "select"
+    assert result["items"] == [{"n": 1}] and result["count"] == 1
+
+    result = db.execute("UPDATE items SET n = n + 1 WHERE pk = 'b'")
+    assert result == {"kind": "update", "item": {"n": 3}}
+
+    plan = db.execute("EXPLAIN SELECT * FROM items WHERE pk = 'a'")
+    assert plan["plan"]["access_path"]["type"] == "table_query"
+
+    assert db.execute("DELETE FROM items WHERE pk = 'a'")["success"]
+    with pytest.raises(keystonedb.InvalidArgumentError):
+        db.execute("SELEC nothing")
+    with pytest.raises(TypeError):
+        db.execute("SELECT * FROM items", 42)
+
+
+def test_transactions(db):
+    from keystonedb import Transact
+
+    db.put(b"account#a", {"balance": 100})
+    committed = db.transact_write([
+        Transact.update(b"account#a", "SET balance = balance - :amount", condition="balance >= :amount"),
+        Transact.put(b"account#b", {"balance": 30}, condition="attribute_not_exists(balance)"),
+        Transact.put(b"log", {"entry": "moved"}, sk=b"1"),
+    ], values={":amount": 30})
+    assert committed == 3
+    assert db.transact_get([b"account#a", "account#b", (b"log", b"1"), b"missing"]) == [
+        {"balance": 70}, {"balance": 30}, {"entry": "moved"}, None
+    ]
+
+    with pytest.raises(keystonedb.TransactionCanceledError):
+        db.transact_write([
+            Transact.delete(b"account#b"),
+            Transact.condition_check(b"account#a", "balance > :big"),
+        ], values={":big": 1000})
+    assert db.get(b"account#b") == {"balance": 30}
+
+    with pytest.raises(ValueError):
+        db.transact_write([{"put": {"pk": b"x"}}])
+
+
+def test_context_manager(tmp_path):
+    path = tmp_path / "ctx.keystone"
+    with keystonedb.open(path) as db:
+        db.put(b"k", {"n": 1})
+    with pytest.raises(keystonedb.KeystoneError):
+        db.get(b"k")
+    db.close()  # closing twice is harmless
+
+    with pytest.raises(ValueError):
+        with keystonedb.open(path) as db:
+            raise ValueError("boom")
+    with keystonedb.open(path, create=False) as db:
+        assert db.get(b"k") == {"n": 1}
+    with pytest.raises(OSError):
+        keystonedb.open(tmp_path / "missing", create=False)
+
+
+def test_scan_iter_is_lazy_and_complete(org):
+    it = org.scan_iter(page_size=2)
+    assert next(it)["n"] in range(11)
+    assert len(list(it)) == 6
+
+    assert sorted(item["n"] for item in org.scan_iter(pk_prefix=b"org#", page_size=1)) == [0, 1, 2, 3, 4, 9]
+    assert len(list(org.scan_iter(limit=3, page_size=2))) == 3
+    assert [item["n"] for item in org.scan_iter(filter="n > :n", values={":n": 8}, page_size=2)] in ([9, 10], [10, 9])
+    with pytest.raises(ValueError):
+        org.scan_iter(page_size=0)
+
+
+def test_data_frames(org):
+    pandas = pytest.importorskip("pandas")
+    df = org.query_df(b"org#acme", sk_begins_with=b"user#", page_size=2)
+    assert isinstance(df, pandas.DataFrame)
+    assert list(df["n"]) == [0, 1, 2, 3, 4]
+    assert len(org.scan_df(pk_prefix=b"org#")) == 6
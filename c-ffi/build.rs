@@ -0,0 +1,17 @@
+/// Regenerate `include/keystone.h` from the exported functions
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Invalid cbindgen.toml");
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/keystone.h", crate_dir));
+        }
+        // Keep the checked-in header rather than failing the build
+        Err(e) => println!("cargo:warning=Could not regenerate keystone.h: {}", e),
+    }
+}
@@ -0,0 +1,387 @@
+#ifndef KEYSTONE_H
+#define KEYSTONE_H
+
+/* Generated by cbindgen from c-ffi/src; do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Result of a fallible call
+typedef enum KsError {
+  KS_ERROR_OK = 0,
+  // A required pointer argument was NULL
+  KS_ERROR_NULL_POINTER = 1,
+  // A string argument was not valid UTF-8
+  KS_ERROR_INVALID_UTF8 = 2,
+  // Bad JSON, expression or option value
+  KS_ERROR_INVALID_ARGUMENT = 3,
+  // The item (or database) does not exist
+  KS_ERROR_NOT_FOUND = 4,
+  // Filesystem failure
+  KS_ERROR_IO = 5,
+  // Any other engine error; see `ks_last_error()`
+  KS_ERROR_INTERNAL = 6,
+  // A condition expression evaluated to false; nothing was written
+  KS_ERROR_CONDITIONAL_CHECK_FAILED = 7,
+  // A transaction was rolled back (a condition failed or keys repeated)
+  KS_ERROR_TRANSACTION_CANCELED = 8,
+} KsError;
+
+// An open database
+typedef struct KsDatabase KsDatabase;
+
+// An item owned by the caller (or borrowed from a `KsResult`)
+typedef struct KsItem KsItem;
+
+// Query options for one partition
+typedef struct KsQuery KsQuery;
+
+// One page of items
+typedef struct KsResult KsResult;
+
+// Scan options
+typedef struct KsScan KsScan;
+
+// Operations committed together by `ks_database_transact_write`
+typedef struct KsTransactWrite KsTransactWrite;
+
+// Outcome of a statement; read the member matching `tag`
+typedef enum KsStatementResult_Tag {
+  // SELECT: one page of rows (`ks_result_*` functions)
+  KS_STATEMENT_RESULT_SELECT,
+  // UPDATE: the item after the update
+  KS_STATEMENT_RESULT_ITEM,
+  // INSERT, DELETE and INSERT ... SELECT: whether it applied and how
+  // many items it wrote
+  KS_STATEMENT_RESULT_SUCCESS,
+  // EXPLAIN: the query plan as JSON (a `char*` owned by the result)
+  KS_STATEMENT_RESULT_PLAN,
+} KsStatementResult_Tag;
+
+typedef struct KsStatementResult_Select_Body {
+  struct KsResult *rows;
+} KsStatementResult_Select_Body;
+
+typedef struct KsStatementResult_Item_Body {
+  struct KsItem *item;
+} KsStatementResult_Item_Body;
+
+typedef struct KsStatementResult_Success_Body {
+  bool success;
+  uintptr_t affected;
+} KsStatementResult_Success_Body;
+
+typedef struct KsStatementResult_Plan_Body {
+  char *json;
+} KsStatementResult_Plan_Body;
+
+typedef struct KsStatementResult {
+  KsStatementResult_Tag tag;
+  union {
+    KsStatementResult_Select_Body SELECT;
+    KsStatementResult_Item_Body ITEM;
+    KsStatementResult_Success_Body SUCCESS;
+    KsStatementResult_Plan_Body PLAN;
+  };
+} KsStatementResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Get several items into `*out_json`, a JSON array aligned with the keys
+enum KsError ks_database_batch_get(const struct KsDatabase *db,
+                                   const char *keys_json,
+                                   char **out_json);
+
+// Apply puts and deletes as one atomic batch
+//
+// Unlike a transaction there are no conditions. `out_processed` (may be
+// NULL) receives the number of operations applied.
+enum KsError ks_database_batch_write(const struct KsDatabase *db,
+                                     const char *writes_json,
+                                     uintptr_t *out_processed);
+
+// Read several items from one consistent snapshot into `*out_json`, a
+// JSON array aligned with the keys
+enum KsError ks_database_transact_get(const struct KsDatabase *db,
+                                      const char *keys_json,
+                                      char **out_json);
+
+// Create a new database directory at `path`
+enum KsError ks_database_create(const char *path, struct KsDatabase **out_db);
+
+// Open an existing database directory at `path`
+enum KsError ks_database_open(const char *path, struct KsDatabase **out_db);
+
+// Create a database that lives only in memory
+enum KsError ks_database_create_in_memory(struct KsDatabase **out_db);
+
+// Flush memtables to disk
+enum KsError ks_database_flush(const struct KsDatabase *db);
+
+// Close the database and free the handle; NULL is ignored
+void ks_database_close(struct KsDatabase *db);
+
+// Put an item given as a JSON object
+enum KsError ks_database_put(const struct KsDatabase *db,
+                             const char *pk,
+                             const char *sk,
+                             const char *item_json);
+
+// Put an item with a single string attribute
+enum KsError ks_database_put_string(const struct KsDatabase *db,
+                                    const char *pk,
+                                    const char *sk,
+                                    const char *attribute,
+                                    const char *value);
+
+// Get an item into `*out_item` (freed with `ks_item_free`)
+//
+// Returns `KS_ERROR_NOT_FOUND`, leaving `*out_item` NULL, when there is no
+// such item.
+enum KsError ks_database_get(const struct KsDatabase *db,
+                             const char *pk,
+                             const char *sk,
+                             struct KsItem **out_item);
+
+// Delete an item; deleting a missing item succeeds
+enum KsError ks_database_delete(const struct KsDatabase *db, const char *pk, const char *sk);
+
+// Message of the last failed call on this thread, or NULL
+//
+// The pointer stays valid until the next failing call on the same thread.
+const char *ks_last_error(void);
+
+// Serialize an item as a JSON object into `*out_json`
+enum KsError ks_item_to_json(const struct KsItem *item, char **out_json);
+
+// Read a string or number attribute as text into `*out_value`
+//
+// Returns `KS_ERROR_NOT_FOUND` when the attribute is missing and
+// `KS_ERROR_INVALID_ARGUMENT` when it has another type.
+enum KsError ks_item_get_string(const struct KsItem *item, const char *attribute, char **out_value);
+
+// Free an item returned by `ks_database_get`; NULL is ignored
+//
+// Items borrowed from a `KsResult` must not be freed.
+void ks_item_free(struct KsItem *item);
+
+// Free a string returned by this library; NULL is ignored
+void ks_string_free(char *text);
+
+// Start a query on partition `pk`; NULL if `pk` is NULL
+struct KsQuery *ks_query_new(const char *pk);
+
+// Only items whose sort key equals `sk`
+enum KsError ks_query_sk_eq(struct KsQuery *query, const char *sk);
+
+// Only items whose sort key is less than `sk`
+enum KsError ks_query_sk_lt(struct KsQuery *query, const char *sk);
+
+// Only items whose sort key is at most `sk`
+enum KsError ks_query_sk_lte(struct KsQuery *query, const char *sk);
+
+// Only items whose sort key is greater than `sk`
+enum KsError ks_query_sk_gt(struct KsQuery *query, const char *sk);
+
+// Only items whose sort key is at least `sk`
+enum KsError ks_query_sk_gte(struct KsQuery *query, const char *sk);
+
+// Only items whose sort key starts with `prefix`
+enum KsError ks_query_sk_begins_with(struct KsQuery *query, const char *prefix);
+
+// Only items whose sort key is between `low` and `high`, inclusive
+enum KsError ks_query_sk_between(struct KsQuery *query, const char *low, const char *high);
+
+// Evaluate at most `limit` items per call; items the filter drops count too
+enum KsError ks_query_limit(struct KsQuery *query, uintptr_t limit);
+
+// Ascending (true, the default) or descending sort key order
+enum KsError ks_query_forward(struct KsQuery *query, bool forward);
+
+// Query a secondary index instead of the base table
+enum KsError ks_query_index(struct KsQuery *query, const char *index);
+
+// Return only items matching a filter expression such as `age >= :min`
+//
+// `values` (may be NULL) holds its placeholders. Items filtered out still
+// count in `ks_result_scanned_count`.
+enum KsError ks_query_filter(struct KsQuery *query, const char *expression, const char *values);
+
+// Continue after the given key (see `ks_result_last_pk`/`ks_result_last_sk`)
+enum KsError ks_query_start_after(struct KsQuery *query, const char *pk, const char *sk);
+
+// Free a query; NULL is ignored
+void ks_query_free(struct KsQuery *query);
+
+// Run a query into `*out_result` (freed with `ks_result_free`)
+enum KsError ks_database_query(const struct KsDatabase *db,
+                               const struct KsQuery *query,
+                               struct KsResult **out_result);
+
+// Start a scan of the whole table
+struct KsScan *ks_scan_new(void);
+
+// Evaluate at most `limit` items per call; items the filter drops count too
+enum KsError ks_scan_limit(struct KsScan *scan, uintptr_t limit);
+
+// Only items whose partition key starts with `prefix`
+enum KsError ks_scan_pk_prefix(struct KsScan *scan, const char *prefix);
+
+// Scan only segment `segment` of `total_segments` (parallel scans)
+enum KsError ks_scan_segment(struct KsScan *scan, uintptr_t segment, uintptr_t total_segments);
+
+// Return only items matching a filter expression; see `ks_query_filter`
+enum KsError ks_scan_filter(struct KsScan *scan, const char *expression, const char *values);
+
+// Continue after the given key (see `ks_result_last_pk`/`ks_result_last_sk`)
+enum KsError ks_scan_start_after(struct KsScan *scan, const char *pk, const char *sk);
+
+// Free a scan; NULL is ignored
+void ks_scan_free(struct KsScan *scan);
+
+// Run a scan into `*out_result` (freed with `ks_result_free`)
+enum KsError ks_database_scan(const struct KsDatabase *db,
+                              const struct KsScan *scan,
+                              struct KsResult **out_result);
+
+// Number of items in the page; 0 for NULL
+uintptr_t ks_result_count(const struct KsResult *result);
+
+// Item `index` of the page, borrowed from the result; NULL if out of range
+const struct KsItem *ks_result_item_at(const struct KsResult *result, uintptr_t index);
+
+// Number of items examined to produce the page
+uintptr_t ks_result_scanned_count(const struct KsResult *result);
+
+// Whether more items may follow (a last key was returned)
+bool ks_result_has_more(const struct KsResult *result);
+
+// Partition key to continue after, borrowed from the result; NULL when done
+const char *ks_result_last_pk(const struct KsResult *result);
+
+// Sort key to continue after, borrowed from the result; NULL when done or
+// when the last item has no sort key
+const char *ks_result_last_sk(const struct KsResult *result);
+
+// Serialize the page into `*out_json`:
+// `{"items": [...], "count", "scanned_count", "last_key": {"pk", "sk"} | null, "warnings"}`
+enum KsError ks_result_to_json(const struct KsResult *result, char **out_json);
+
+// Free a result and the items borrowed from it; NULL is ignored
+void ks_result_free(struct KsResult *result);
+
+// Execute a PartiQL statement into `*out_result`
+//
+// Release what the result owns with `ks_statement_result_free`.
+enum KsError ks_database_execute_statement(const struct KsDatabase *db,
+                                           const char *sql,
+                                           struct KsStatementResult *out_result);
+
+// Execute a statement with parameters given as JSON
+//
+// `[1, "a"]` binds `?` placeholders in order; `{"name": "a"}` binds
+// `:name` placeholders. `params_json` may be NULL.
+enum KsError ks_database_execute_statement_with_params(const struct KsDatabase *db,
+                                                       const char *sql,
+                                                       const char *params_json,
+                                                       struct KsStatementResult *out_result);
+
+// Execute a statement and serialize the outcome into `*out_json`
+//
+// The document has a `kind` of `select` (with the fields of
+// `ks_result_to_json`), `item`, `success` (`success`, `affected`) or
+// `plan`. `params_json` may be NULL.
+enum KsError ks_database_execute_statement_json(const struct KsDatabase *db,
+                                                const char *sql,
+                                                const char *params_json,
+                                                char **out_json);
+
+// Serialize a statement result into `*out_json`
+enum KsError ks_statement_result_to_json(const struct KsStatementResult *result, char **out_json);
+
+// Free what a statement result owns (not the struct itself); NULL is ignored
+//
+// The result is left as an empty `Success` so freeing twice is harmless.
+void ks_statement_result_free(struct KsStatementResult *result);
+
+// Apply an update expression such as `SET age = age + :inc REMOVE #tmp`
+//
+// `condition`, `values` and `out_item` may be NULL. When `out_item` is
+// given it receives the item as it is after the update.
+enum KsError ks_database_update(const struct KsDatabase *db,
+                                const char *pk,
+                                const char *sk,
+                                const char *expression,
+                                const char *condition,
+                                const char *values,
+                                struct KsItem **out_item);
+
+// Put an item (JSON object) only if `condition` holds for the current one
+//
+// For example `attribute_not_exists(email)` to avoid overwriting.
+enum KsError ks_database_put_conditional(const struct KsDatabase *db,
+                                         const char *pk,
+                                         const char *sk,
+                                         const char *item_json,
+                                         const char *condition,
+                                         const char *values);
+
+// Delete an item only if `condition` holds for it
+enum KsError ks_database_delete_conditional(const struct KsDatabase *db,
+                                            const char *pk,
+                                            const char *sk,
+                                            const char *condition,
+                                            const char *values);
+
+// Start an empty write transaction
+struct KsTransactWrite *ks_transact_write_new(void);
+
+// Add a put of an item (JSON object); `condition` may be NULL
+enum KsError ks_transact_write_put(struct KsTransactWrite *transaction,
+                                   const char *pk,
+                                   const char *sk,
+                                   const char *item_json,
+                                   const char *condition);
+
+// Add an update expression; `condition` may be NULL
+enum KsError ks_transact_write_update(struct KsTransactWrite *transaction,
+                                      const char *pk,
+                                      const char *sk,
+                                      const char *expression,
+                                      const char *condition);
+
+// Add a delete; `condition` may be NULL
+enum KsError ks_transact_write_delete(struct KsTransactWrite *transaction,
+                                      const char *pk,
+                                      const char *sk,
+                                      const char *condition);
+
+// Add a check that `condition` holds for an item, without writing it
+enum KsError ks_transact_write_condition_check(struct KsTransactWrite *transaction,
+                                               const char *pk,
+                                               const char *sk,
+                                               const char *condition);
+
+// Add placeholders shared by every operation of the transaction
+enum KsError ks_transact_write_values(struct KsTransactWrite *transaction, const char *values);
+
+// Number of operations added so far
+uintptr_t ks_transact_write_count(const struct KsTransactWrite *transaction);
+
+// Free a transaction; NULL is ignored
+void ks_transact_write_free(struct KsTransactWrite *transaction);
+
+// Commit every operation or none of them
+//
+// The transaction is not consumed and still has to be freed.
+enum KsError ks_database_transact_write(const struct KsDatabase *db,
+                                        const struct KsTransactWrite *transaction);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KEYSTONE_H */
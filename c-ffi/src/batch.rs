@@ -0,0 +1,191 @@
+/// Batch reads and writes, and consistent multi-item reads
+///
+/// Keys and operations are passed as one JSON document so bindings make a
+/// single call per batch:
+///
+/// - keys: `[{"pk": "user#1"}, {"pk": "org#acme", "sk": "user#2"}]`
+/// - writes: `[{"put": {"pk": "user#1", "item": {...}}}, {"delete": {"pk": "user#2"}}]`
+///
+/// Reads return a JSON array with one entry per key, in order: the item,
+/// or `null` when it is missing.
+
+use crate::database::KsDatabase;
+use crate::error::{handle, run, str_arg, write_out, Failure, FfiResult, KsError};
+use crate::item::into_c_string;
+use bytes::Bytes;
+use kstone_api::{item_to_json, BatchGetRequest, BatchWriteRequest, ItemJsonExt, TransactGetRequest};
+use kstone_core::{Item, Key};
+use std::ffi::c_char;
+
+/// Read a key object: `{"pk": "...", "sk": "..."}` (`sk` optional)
+fn parse_key(json: &serde_json::Value) -> FfiResult<Key> {
+    let part = |name: &str| match json.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(part)) => Ok(Some(Bytes::copy_from_slice(part.as_bytes()))),
+        Some(_) => Err(Failure::invalid(format!("Key member '{}' must be a string", name))),
+    };
+    let pk = part("pk")?.ok_or_else(|| Failure::invalid("Every key needs a 'pk'"))?;
+    Ok(match part("sk")? {
+        Some(sk) => Key::with_sk(pk, sk),
+        None => Key::new(pk),
+    })
+}
+
+fn parse_keys(keys_json: &str) -> FfiResult<Vec<Key>> {
+    let serde_json::Value::Array(keys) = serde_json::from_str(keys_json)? else {
+        return Err(Failure::invalid("Keys must be a JSON array"));
+    };
+    keys.iter().map(parse_key).collect()
+}
+
+/// Items (or nulls) as a JSON array string
+fn items_json(items: &[Option<Item>]) -> FfiResult<*mut c_char> {
+    let items: Vec<_> = items.iter().map(|item| item.as_ref().map(item_to_json)).collect();
+    Ok(into_c_string(serde_json::to_string(&items)?))
+}
+
+/// Get several items into `*out_json`, a JSON array aligned with the keys
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_batch_get(
+    db: *const KsDatabase,
+    keys_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let keys = parse_keys(str_arg(keys_json, "keys_json")?)?;
+        let mut request = BatchGetRequest::new();
+        request.keys = keys.clone();
+        let mut response = db.batch_get(request)?;
+        let items: Vec<_> = keys.iter().map(|key| response.items.remove(key)).collect();
+        write_out(out_json, items_json(&items)?)
+    })
+}
+
+/// Apply puts and deletes as one atomic batch
+///
+/// Unlike a transaction there are no conditions. `out_processed` (may be
+/// NULL) receives the number of operations applied.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_batch_write(
+    db: *const KsDatabase,
+    writes_json: *const c_char,
+    out_processed: *mut usize,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let serde_json::Value::Array(writes) = serde_json::from_str(str_arg(writes_json, "writes_json")?)? else {
+            return Err(Failure::invalid("Writes must be a JSON array"));
+        };
+        let mut request = BatchWriteRequest::new();
+        for write in writes {
+            let serde_json::Value::Object(write) = write else {
+                return Err(Failure::invalid("Each write must be a JSON object"));
+            };
+            if write.len() != 1 {
+                return Err(Failure::invalid("Each write needs exactly one of put or delete"));
+            }
+            let (kind, args) = write.into_iter().next().expect("one entry");
+            let Key { pk, sk } = parse_key(&args)?;
+            request = match kind.as_str() {
+                "put" => {
+                    let item = args
+                        .get("item")
+                        .cloned()
+                        .ok_or_else(|| Failure::invalid("A put needs an 'item'"))?;
+                    let item = Item::from_json(item)?;
+                    match sk {
+                        Some(sk) => request.put_with_sk(&pk, &sk, item),
+                        None => request.put(&pk, item),
+                    }
+                }
+                "delete" => match sk {
+                    Some(sk) => request.delete_with_sk(&pk, &sk),
+                    None => request.delete(&pk),
+                },
+                other => return Err(Failure::invalid(format!("Unknown write '{}'", other))),
+            };
+        }
+        let response = db.batch_write(request)?;
+        if !out_processed.is_null() {
+            out_processed.write(response.processed_count);
+        }
+        Ok(())
+    })
+}
+
+/// Read several items from one consistent snapshot into `*out_json`, a
+/// JSON array aligned with the keys
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_transact_get(
+    db: *const KsDatabase,
+    keys_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let mut request = TransactGetRequest::new();
+        request.keys = parse_keys(str_arg(keys_json, "keys_json")?)?;
+        let response = db.transact_get(request)?;
+        write_out(out_json, items_json(&response.items)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::memory_db;
+    use crate::{ks_database_close, ks_database_get, ks_string_free};
+    use std::ffi::CStr;
+
+    unsafe fn take_json(json: *mut c_char) -> serde_json::Value {
+        let value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        ks_string_free(json);
+        value
+    }
+
+    #[test]
+    fn test_batch_write_then_get() {
+        let db = memory_db();
+        unsafe {
+            let writes = c"[
+                {\"put\": {\"pk\": \"user#1\", \"item\": {\"n\": 1}}},
+                {\"put\": {\"pk\": \"org#acme\", \"sk\": \"user#2\", \"item\": {\"n\": 2}}},
+                {\"put\": {\"pk\": \"user#3\", \"item\": {\"n\": 3}}},
+                {\"delete\": {\"pk\": \"user#3\"}}
+            ]";
+            let mut processed = 0;
+            assert_eq!(ks_database_batch_write(db, writes.as_ptr(), &mut processed), KsError::Ok);
+            assert_eq!(processed, 4);
+
+            let keys = c"[{\"pk\": \"org#acme\", \"sk\": \"user#2\"}, {\"pk\": \"user#3\"}, {\"pk\": \"user#1\"}]";
+            let mut json = std::ptr::null_mut();
+            assert_eq!(ks_database_batch_get(db, keys.as_ptr(), &mut json), KsError::Ok);
+            assert_eq!(take_json(json), serde_json::json!([{"n": 2}, null, {"n": 1}]));
+
+            assert_eq!(ks_database_transact_get(db, keys.as_ptr(), &mut json), KsError::Ok);
+            assert_eq!(take_json(json), serde_json::json!([{"n": 2}, null, {"n": 1}]));
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_bad_batches_write_nothing() {
+        let db = memory_db();
+        unsafe {
+            let writes = c"[{\"put\": {\"pk\": \"user#1\", \"item\": {\"n\": 1}}}, {\"upsert\": {\"pk\": \"x\"}}]";
+            assert_eq!(
+                ks_database_batch_write(db, writes.as_ptr(), std::ptr::null_mut()),
+                KsError::InvalidArgument
+            );
+            let mut item = std::ptr::null_mut();
+            assert_eq!(ks_database_get(db, c"user#1".as_ptr(), std::ptr::null(), &mut item), KsError::NotFound);
+
+            let mut json = std::ptr::null_mut();
+            assert_eq!(ks_database_batch_get(db, c"[{\"sk\": \"a\"}]".as_ptr(), &mut json), KsError::InvalidArgument);
+            assert_eq!(ks_database_batch_get(db, c"{}".as_ptr(), &mut json), KsError::InvalidArgument);
+            assert!(json.is_null());
+            ks_database_close(db);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+/// Opening databases and single-item reads and writes
+
+use crate::error::{handle, key_arg, opt_key_arg, run, str_arg, write_out, Failure, KsError};
+use crate::item::{item_from_json, KsItem};
+use kstone_api::{Database, ItemBuilder};
+use std::ffi::c_char;
+
+/// An open database
+pub struct KsDatabase(pub(crate) Database);
+
+fn open_with(
+    out_db: *mut *mut KsDatabase,
+    open: impl FnOnce() -> kstone_core::Result<Database>,
+) -> KsError {
+    run(|| {
+        let db = open()?;
+        unsafe { write_out(out_db, Box::into_raw(Box::new(KsDatabase(db)))) }
+    })
+}
+
+/// Create a new database directory at `path`
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_create(path: *const c_char, out_db: *mut *mut KsDatabase) -> KsError {
+    match str_arg(path, "path") {
+        Ok(path) => open_with(out_db, || Database::create(path)),
+        Err(failure) => run(|| Err(failure)),
+    }
+}
+
+/// Open an existing database directory at `path`
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_open(path: *const c_char, out_db: *mut *mut KsDatabase) -> KsError {
+    match str_arg(path, "path") {
+        Ok(path) => open_with(out_db, || Database::open(path)),
+        Err(failure) => run(|| Err(failure)),
+    }
+}
+
+/// Create a database that lives only in memory
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_create_in_memory(out_db: *mut *mut KsDatabase) -> KsError {
+    open_with(out_db, Database::create_in_memory)
+}
+
+/// Flush memtables to disk
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_flush(db: *const KsDatabase) -> KsError {
+    run(|| Ok(handle(db, "db")?.0.flush()?))
+}
+
+/// Close the database and free the handle; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_close(db: *mut KsDatabase) {
+    if !db.is_null() {
+        drop(Box::from_raw(db));
+    }
+}
+
+/// Put an item given as a JSON object
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_put(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    item_json: *const c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        let item = item_from_json(str_arg(item_json, "item_json")?)?;
+        match opt_key_arg(sk) {
+            Some(sk) => db.put_with_sk(pk, sk, item)?,
+            None => db.put(pk, item)?,
+        }
+        Ok(())
+    })
+}
+
+/// Put an item with a single string attribute
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_put_string(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    attribute: *const c_char,
+    value: *const c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        let item = ItemBuilder::new()
+            .string(str_arg(attribute, "attribute")?, str_arg(value, "value")?)
+            .build();
+        match opt_key_arg(sk) {
+            Some(sk) => db.put_with_sk(pk, sk, item)?,
+            None => db.put(pk, item)?,
+        }
+        Ok(())
+    })
+}
+
+/// Get an item into `*out_item` (freed with `ks_item_free`)
+///
+/// Returns `KS_ERROR_NOT_FOUND`, leaving `*out_item` NULL, when there is no
+/// such item.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_get(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    out_item: *mut *mut KsItem,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        write_out(out_item, std::ptr::null_mut())?;
+        let item = match opt_key_arg(sk) {
+            Some(sk) => db.get_with_sk(pk, sk)?,
+            None => db.get(pk)?,
+        };
+        match item {
+            Some(item) => write_out(out_item, Box::into_raw(Box::new(KsItem(item)))),
+            None => Err(Failure::new(KsError::NotFound, "Item not found")),
+        }
+    })
+}
+
+/// Delete an item; deleting a missing item succeeds
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_delete(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        match opt_key_arg(sk) {
+            Some(sk) => db.delete_with_sk(pk, sk)?,
+            None => db.delete(pk)?,
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use tempfile::TempDir;
+
+    pub(crate) fn memory_db() -> *mut KsDatabase {
+        let mut db = std::ptr::null_mut();
+        assert_eq!(unsafe { ks_database_create_in_memory(&mut db) }, KsError::Ok);
+        db
+    }
+
+    pub(crate) unsafe fn item_json(item: *const KsItem) -> serde_json::Value {
+        let mut json = std::ptr::null_mut();
+        assert_eq!(crate::ks_item_to_json(item, &mut json), KsError::Ok);
+        let value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        crate::ks_string_free(json);
+        value
+    }
+
+    #[test]
+    fn test_put_get_delete() {
+        let db = memory_db();
+        unsafe {
+            assert_eq!(
+                ks_database_put(db, c"user#1".as_ptr(), std::ptr::null(), c"{\"name\":\"Ada\"}".as_ptr()),
+                KsError::Ok
+            );
+            assert_eq!(
+                ks_database_put_string(db, c"org#1".as_ptr(), c"user#1".as_ptr(), c"role".as_ptr(), c"admin".as_ptr()),
+                KsError::Ok
+            );
+
+            let mut item = std::ptr::null_mut();
+            assert_eq!(ks_database_get(db, c"user#1".as_ptr(), std::ptr::null(), &mut item), KsError::Ok);
+            assert_eq!(item_json(item), serde_json::json!({"name": "Ada"}));
+            crate::ks_item_free(item);
+
+            assert_eq!(ks_database_get(db, c"org#1".as_ptr(), c"user#1".as_ptr(), &mut item), KsError::Ok);
+            assert_eq!(item_json(item), serde_json::json!({"role": "admin"}));
+            crate::ks_item_free(item);
+
+            assert_eq!(ks_database_delete(db, c"user#1".as_ptr(), std::ptr::null()), KsError::Ok);
+            assert_eq!(ks_database_get(db, c"user#1".as_ptr(), std::ptr::null(), &mut item), KsError::NotFound);
+            assert!(item.is_null());
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let db = memory_db();
+        unsafe {
+            assert_eq!(
+                ks_database_put(db, std::ptr::null(), std::ptr::null(), c"{}".as_ptr()),
+                KsError::NullPointer
+            );
+            assert_eq!(
+                ks_database_put(db, c"k".as_ptr(), std::ptr::null(), c"not json".as_ptr()),
+                KsError::InvalidArgument
+            );
+            assert!(CStr::from_ptr(crate::ks_last_error()).to_str().unwrap().contains("Invalid JSON"));
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_create_open_on_disk() {
+        let dir = TempDir::new().unwrap();
+        let path = std::ffi::CString::new(dir.path().join("db").to_str().unwrap()).unwrap();
+        let mut db = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(ks_database_open(path.as_ptr(), &mut db), KsError::Io);
+            assert_eq!(ks_database_create(path.as_ptr(), &mut db), KsError::Ok);
+            assert_eq!(ks_database_put_string(db, c"k".as_ptr(), std::ptr::null(), c"a".as_ptr(), c"1".as_ptr()), KsError::Ok);
+            assert_eq!(ks_database_flush(db), KsError::Ok);
+            ks_database_close(db);
+
+            assert_eq!(ks_database_open(path.as_ptr(), &mut db), KsError::Ok);
+            let mut item = std::ptr::null_mut();
+            assert_eq!(ks_database_get(db, c"k".as_ptr(), std::ptr::null(), &mut item), KsError::Ok);
+            crate::ks_item_free(item);
+            ks_database_close(db);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/// Status codes and the per-thread last error message
+
+use kstone_api::KeystoneError;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Result of a fallible call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KsError {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// Bad JSON, expression or option value
+    InvalidArgument = 3,
+    /// The item (or database) does not exist
+    NotFound = 4,
+    /// Filesystem failure
+    Io = 5,
+    /// Any other engine error; see `ks_last_error()`
+    Internal = 6,
+    /// A condition expression evaluated to false; nothing was written
+    ConditionalCheckFailed = 7,
+    /// A transaction was rolled back (a condition failed or keys repeated)
+    TransactionCanceled = 8,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Message of the last failed call on this thread, or NULL
+///
+/// The pointer stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn ks_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// A failed call: the code returned to C and the message kept for `ks_last_error`
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub code: KsError,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: KsError, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn null(what: &str) -> Self {
+        Self::new(KsError::NullPointer, format!("{} is NULL", what))
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(KsError::InvalidArgument, message)
+    }
+}
+
+impl From<KeystoneError> for Failure {
+    fn from(error: KeystoneError) -> Self {
+        let code = match &error {
+            KeystoneError::Io(_) => KsError::Io,
+            KeystoneError::NotFound(_) => KsError::NotFound,
+            KeystoneError::InvalidArgument(_)
+            | KeystoneError::InvalidExpression(_)
+            | KeystoneError::InvalidQuery(_) => KsError::InvalidArgument,
+            KeystoneError::ConditionalCheckFailed(_) => KsError::ConditionalCheckFailed,
+            KeystoneError::TransactionCanceled(_) => KsError::TransactionCanceled,
+            _ => KsError::Internal,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(error: serde_json::Error) -> Self {
+        Self::invalid(format!("Invalid JSON: {}", error))
+    }
+}
+
+pub(crate) type FfiResult<T> = std::result::Result<T, Failure>;
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run a call body, turning failures (and panics) into a status code
+pub(crate) fn run(body: impl FnOnce() -> FfiResult<()>) -> KsError {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => KsError::Ok,
+        Ok(Err(failure)) => {
+            set_last_error(failure.message);
+            failure.code
+        }
+        Err(_) => {
+            set_last_error("Panic inside KeystoneDB".to_string());
+            KsError::Internal
+        }
+    }
+}
+
+/// Write a value through an out pointer
+pub(crate) unsafe fn write_out<T>(out: *mut T, value: T) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(Failure::null("Output pointer"));
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Borrow a required UTF-8 string argument
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, what: &str) -> FfiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(Failure::null(what));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Failure::new(KsError::InvalidUtf8, format!("{} is not valid UTF-8", what)))
+}
+
+/// Borrow an optional UTF-8 string argument; NULL means none
+pub(crate) unsafe fn opt_str_arg<'a>(ptr: *const c_char, what: &str) -> FfiResult<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    str_arg(ptr, what).map(Some)
+}
+
+/// Borrow a required key argument (any bytes but NUL)
+pub(crate) unsafe fn key_arg<'a>(ptr: *const c_char, what: &str) -> FfiResult<&'a [u8]> {
+    if ptr.is_null() {
+        return Err(Failure::null(what));
+    }
+    Ok(CStr::from_ptr(ptr).to_bytes())
+}
+
+/// Borrow an optional key argument; NULL means none
+pub(crate) unsafe fn opt_key_arg<'a>(ptr: *const c_char) -> Option<&'a [u8]> {
+    (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_bytes())
+}
+
+/// Borrow a handle argument
+pub(crate) unsafe fn handle<'a, T>(ptr: *const T, what: &str) -> FfiResult<&'a T> {
+    ptr.as_ref().ok_or_else(|| Failure::null(what))
+}
+
+/// Borrow a handle argument mutably
+pub(crate) unsafe fn handle_mut<'a, T>(ptr: *mut T, what: &str) -> FfiResult<&'a mut T> {
+    ptr.as_mut().ok_or_else(|| Failure::null(what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_set_last_error() {
+        assert_eq!(run(|| Ok(())), KsError::Ok);
+        assert_eq!(
+            run(|| Err(KeystoneError::InvalidExpression("bad".into()).into())),
+            KsError::InvalidArgument
+        );
+        let message = unsafe { CStr::from_ptr(ks_last_error()) };
+        assert!(message.to_str().unwrap().contains("bad"));
+
+        assert_eq!(run(|| panic!("boom")), KsError::Internal);
+        let message = unsafe { CStr::from_ptr(ks_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Panic inside KeystoneDB");
+    }
+}
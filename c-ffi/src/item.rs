@@ -0,0 +1,117 @@
+/// Items and strings handed back to C
+
+use crate::error::{handle, run, str_arg, write_out, Failure, FfiResult, KsError};
+use kstone_api::{item_to_json, ItemJsonExt, KeystoneValue};
+use kstone_core::Item;
+use std::ffi::{c_char, CString};
+
+/// An item owned by the caller (or borrowed from a `KsResult`)
+pub struct KsItem(pub(crate) Item);
+
+/// Parse an item from a JSON object
+pub(crate) fn item_from_json(json: &str) -> FfiResult<Item> {
+    Ok(Item::from_json(serde_json::from_str(json)?)?)
+}
+
+/// Hand a string to C; freed with `ks_string_free`
+pub(crate) fn into_c_string(text: String) -> *mut c_char {
+    // serde_json escapes NUL, and attribute strings with NUL are cut short
+    match CString::new(text) {
+        Ok(text) => text.into_raw(),
+        Err(e) => {
+            let end = e.nul_position();
+            let mut bytes = e.into_vec();
+            bytes.truncate(end);
+            CString::new(bytes).unwrap_or_default().into_raw()
+        }
+    }
+}
+
+/// Serialize an item as a JSON object into `*out_json`
+#[no_mangle]
+pub unsafe extern "C" fn ks_item_to_json(item: *const KsItem, out_json: *mut *mut c_char) -> KsError {
+    run(|| {
+        let item = handle(item, "item")?;
+        let json = serde_json::to_string(&item_to_json(&item.0))?;
+        write_out(out_json, into_c_string(json))
+    })
+}
+
+/// Read a string or number attribute as text into `*out_value`
+///
+/// Returns `KS_ERROR_NOT_FOUND` when the attribute is missing and
+/// `KS_ERROR_INVALID_ARGUMENT` when it has another type.
+#[no_mangle]
+pub unsafe extern "C" fn ks_item_get_string(
+    item: *const KsItem,
+    attribute: *const c_char,
+    out_value: *mut *mut c_char,
+) -> KsError {
+    run(|| {
+        let item = handle(item, "item")?;
+        let name = str_arg(attribute, "attribute")?;
+        let text = match item.0.get(name) {
+            Some(KeystoneValue::S(s)) => s.clone(),
+            Some(KeystoneValue::N(n)) => n.clone(),
+            Some(_) => {
+                return Err(Failure::invalid(format!(
+                    "Attribute '{}' is not a string or number",
+                    name
+                )))
+            }
+            None => {
+                return Err(Failure::new(
+                    KsError::NotFound,
+                    format!("Attribute '{}' not found", name),
+                ))
+            }
+        };
+        write_out(out_value, into_c_string(text))
+    })
+}
+
+/// Free an item returned by `ks_database_get`; NULL is ignored
+///
+/// Items borrowed from a `KsResult` must not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn ks_item_free(item: *mut KsItem) {
+    if !item.is_null() {
+        drop(Box::from_raw(item));
+    }
+}
+
+/// Free a string returned by this library; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_item_accessors() {
+        let item = KsItem(item_from_json(r#"{"name": "Ada", "age": 36, "admin": true}"#).unwrap());
+        let mut out = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(ks_item_get_string(&item, c"name".as_ptr(), &mut out), KsError::Ok);
+            assert_eq!(CStr::from_ptr(out).to_str().unwrap(), "Ada");
+            ks_string_free(out);
+            assert_eq!(ks_item_get_string(&item, c"age".as_ptr(), &mut out), KsError::Ok);
+            assert_eq!(CStr::from_ptr(out).to_str().unwrap(), "36");
+            ks_string_free(out);
+            assert_eq!(ks_item_get_string(&item, c"admin".as_ptr(), &mut out), KsError::InvalidArgument);
+            assert_eq!(ks_item_get_string(&item, c"email".as_ptr(), &mut out), KsError::NotFound);
+
+            assert_eq!(ks_item_to_json(&item, &mut out), KsError::Ok);
+            let json: serde_json::Value = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+            assert_eq!(json, serde_json::json!({"name": "Ada", "age": 36, "admin": true}));
+            ks_string_free(out);
+        }
+        assert!(item_from_json("[1]").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+#![allow(clippy::missing_safety_doc)]
+
+/// C FFI for KeystoneDB
+///
+/// A C ABI over the embedded `Database` for language bindings that can't
+/// link Rust directly (Go via cgo, the JVM via JNI). The header is
+/// generated into `include/keystone.h` by cbindgen at build time.
+///
+/// Conventions shared by every function:
+/// - Fallible calls return a `KsError`; on anything but `KS_ERROR_OK` the
+///   message is available from `ks_last_error()` on the same thread.
+/// - Keys are NUL-terminated byte strings. A NULL sort key means the item
+///   has none.
+/// - Items and attribute values cross the boundary as JSON text (see
+///   `kstone_api::json`): objects are items, numbers `N`, strings `S`.
+///   Expression placeholders come as one JSON object too:
+///   `{":min": 18, "#name": "name"}` (`:` values, `#` attribute names).
+/// - Handles (`KsDatabase`, `KsItem`, ...) are owned by the caller and
+///   released with their `ks_*_free`/`ks_database_close` function. Strings
+///   returned as `char*` are freed with `ks_string_free`; `const char*`
+///   results are borrowed from the handle they came from.
+///
+/// Every pointer argument must be NULL or valid for the call, which is why
+/// the functions are `unsafe extern "C"` without per-function safety notes.
+
+mod batch;
+mod database;
+mod error;
+mod item;
+mod query;
+mod statement;
+mod write;
+
+pub use batch::*;
+pub use database::*;
+pub use error::*;
+pub use item::*;
+pub use query::*;
+pub use statement::*;
+pub use write::*;
@@ -0,0 +1,654 @@
+/// Query and scan builders with paginated results
+///
+/// Builders keep plain options and build a fresh `Query`/`Scan` on every
+/// call, so one builder can fetch page after page:
+///
+/// ```c
+/// KsQuery *q = ks_query_new("org#acme");
+/// ks_query_sk_begins_with(q, "user#");
+/// ks_query_limit(q, 100);
+/// KsResult *page = NULL;
+/// while (ks_database_query(db, q, &page) == KS_ERROR_OK) {
+///     for (size_t i = 0; i < ks_result_count(page); i++) { ... ks_result_item_at(page, i) ... }
+///     bool more = ks_result_has_more(page);
+///     if (more) ks_query_start_after(q, ks_result_last_pk(page), ks_result_last_sk(page));
+///     ks_result_free(page);
+///     if (!more) break;
+/// }
+/// ks_query_free(q);
+/// ```
+
+use crate::database::KsDatabase;
+use crate::error::{
+    handle, handle_mut, key_arg, opt_key_arg, opt_str_arg, run, str_arg, write_out, Failure, FfiResult, KsError,
+};
+use crate::item::{into_c_string, KsItem};
+use crate::write::parse_context;
+use bytes::Bytes;
+use kstone_api::{item_to_json, Query, Scan};
+use kstone_core::expression::ExpressionContext;
+use kstone_core::Item;
+use std::ffi::{c_char, CString};
+
+enum SortKeyCondition {
+    Eq(Vec<u8>),
+    Lt(Vec<u8>),
+    Lte(Vec<u8>),
+    Gt(Vec<u8>),
+    Gte(Vec<u8>),
+    Between(Vec<u8>, Vec<u8>),
+    BeginsWith(Vec<u8>),
+}
+
+type StartKey = (Vec<u8>, Option<Vec<u8>>);
+
+/// A filter expression and its placeholders
+struct Filter {
+    expression: String,
+    context: ExpressionContext,
+}
+
+unsafe fn parse_filter(expression: *const c_char, values: *const c_char) -> FfiResult<Filter> {
+    Ok(Filter {
+        expression: str_arg(expression, "expression")?.to_string(),
+        context: parse_context(opt_str_arg(values, "values")?)?,
+    })
+}
+
+/// Query options for one partition
+pub struct KsQuery {
+    pk: Vec<u8>,
+    sk_condition: Option<SortKeyCondition>,
+    forward: bool,
+    limit: Option<usize>,
+    index: Option<String>,
+    filter: Option<Filter>,
+    start_after: Option<StartKey>,
+}
+
+impl KsQuery {
+    fn build(&self) -> Query {
+        let mut query = Query::new(&self.pk).forward(self.forward);
+        query = match &self.sk_condition {
+            None => query,
+            Some(SortKeyCondition::Eq(sk)) => query.sk_eq(sk),
+            Some(SortKeyCondition::Lt(sk)) => query.sk_lt(sk),
+            Some(SortKeyCondition::Lte(sk)) => query.sk_lte(sk),
+            Some(SortKeyCondition::Gt(sk)) => query.sk_gt(sk),
+            Some(SortKeyCondition::Gte(sk)) => query.sk_gte(sk),
+            Some(SortKeyCondition::Between(low, high)) => query.sk_between(low, high),
+            Some(SortKeyCondition::BeginsWith(prefix)) => query.sk_begins_with(prefix),
+        };
+        if let Some(limit) = self.limit {
+            query = query.limit(limit);
+        }
+        if let Some(index) = &self.index {
+            query = query.index(index.clone());
+        }
+        if let Some(filter) = &self.filter {
+            query = query.filter(filter.expression.clone());
+            for (placeholder, value) in &filter.context.values {
+                query = query.value(placeholder.clone(), value.clone());
+            }
+            for (placeholder, name) in &filter.context.names {
+                query = query.name(placeholder.clone(), name.clone());
+            }
+        }
+        if let Some((pk, sk)) = &self.start_after {
+            query = query.start_after(pk, sk.as_deref());
+        }
+        query
+    }
+}
+
+/// Scan options
+#[derive(Default)]
+pub struct KsScan {
+    limit: Option<usize>,
+    pk_prefix: Option<Vec<u8>>,
+    segment: Option<(usize, usize)>,
+    filter: Option<Filter>,
+    start_after: Option<StartKey>,
+}
+
+impl KsScan {
+    fn build(&self) -> Scan {
+        let mut scan = Scan::new();
+        if let Some(limit) = self.limit {
+            scan = scan.limit(limit);
+        }
+        if let Some(prefix) = &self.pk_prefix {
+            scan = scan.pk_prefix(prefix);
+        }
+        if let Some((segment, total)) = self.segment {
+            scan = scan.segment(segment, total);
+        }
+        if let Some(filter) = &self.filter {
+            scan = scan.filter(filter.expression.clone());
+            for (placeholder, value) in &filter.context.values {
+                scan = scan.value(placeholder.clone(), value.clone());
+            }
+            for (placeholder, name) in &filter.context.names {
+                scan = scan.name(placeholder.clone(), name.clone());
+            }
+        }
+        if let Some((pk, sk)) = &self.start_after {
+            scan = scan.start_after(pk, sk.as_deref());
+        }
+        scan
+    }
+}
+
+/// One page of items
+pub struct KsResult {
+    pub(crate) items: Vec<KsItem>,
+    pub(crate) scanned_count: usize,
+    pub(crate) last_pk: Option<CString>,
+    pub(crate) last_sk: Option<CString>,
+    /// Planner notes of a PartiQL SELECT
+    pub(crate) warnings: Vec<String>,
+}
+
+impl KsResult {
+    pub(crate) fn new(
+        items: Vec<Item>,
+        scanned_count: usize,
+        last_key: Option<(Bytes, Option<Bytes>)>,
+    ) -> FfiResult<Self> {
+        let key = |bytes: Bytes| {
+            CString::new(bytes.to_vec())
+                .map_err(|_| Failure::invalid("Last key contains a NUL byte and can't be returned"))
+        };
+        let (last_pk, last_sk) = match last_key {
+            Some((pk, sk)) => (Some(key(pk)?), sk.map(key).transpose()?),
+            None => (None, None),
+        };
+        Ok(Self {
+            items: items.into_iter().map(KsItem).collect(),
+            scanned_count,
+            last_pk,
+            last_sk,
+            warnings: Vec::new(),
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let key = |key: &CString| serde_json::Value::String(String::from_utf8_lossy(key.as_bytes()).into_owned());
+        let last_key = self.last_pk.as_ref().map(|pk| {
+            serde_json::json!({
+                "pk": key(pk),
+                "sk": self.last_sk.as_ref().map(key),
+            })
+        });
+        serde_json::json!({
+            "items": self.items.iter().map(|item| item_to_json(&item.0)).collect::<Vec<_>>(),
+            "count": self.items.len(),
+            "scanned_count": self.scanned_count,
+            "last_key": last_key,
+            "warnings": self.warnings,
+        })
+    }
+}
+
+/// Start a query on partition `pk`; NULL if `pk` is NULL
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_new(pk: *const c_char) -> *mut KsQuery {
+    let mut query = std::ptr::null_mut();
+    run(|| {
+        query = Box::into_raw(Box::new(KsQuery {
+            pk: key_arg(pk, "pk")?.to_vec(),
+            sk_condition: None,
+            forward: true,
+            limit: None,
+            index: None,
+            filter: None,
+            start_after: None,
+        }));
+        Ok(())
+    });
+    query
+}
+
+unsafe fn set_sk_condition(
+    query: *mut KsQuery,
+    sk: *const c_char,
+    condition: fn(Vec<u8>) -> SortKeyCondition,
+) -> KsError {
+    run(|| {
+        let query = handle_mut(query, "query")?;
+        query.sk_condition = Some(condition(key_arg(sk, "sk")?.to_vec()));
+        Ok(())
+    })
+}
+
+/// Only items whose sort key equals `sk`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_eq(query: *mut KsQuery, sk: *const c_char) -> KsError {
+    set_sk_condition(query, sk, SortKeyCondition::Eq)
+}
+
+/// Only items whose sort key is less than `sk`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_lt(query: *mut KsQuery, sk: *const c_char) -> KsError {
+    set_sk_condition(query, sk, SortKeyCondition::Lt)
+}
+
+/// Only items whose sort key is at most `sk`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_lte(query: *mut KsQuery, sk: *const c_char) -> KsError {
+    set_sk_condition(query, sk, SortKeyCondition::Lte)
+}
+
+/// Only items whose sort key is greater than `sk`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_gt(query: *mut KsQuery, sk: *const c_char) -> KsError {
+    set_sk_condition(query, sk, SortKeyCondition::Gt)
+}
+
+/// Only items whose sort key is at least `sk`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_gte(query: *mut KsQuery, sk: *const c_char) -> KsError {
+    set_sk_condition(query, sk, SortKeyCondition::Gte)
+}
+
+/// Only items whose sort key starts with `prefix`
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_begins_with(query: *mut KsQuery, prefix: *const c_char) -> KsError {
+    set_sk_condition(query, prefix, SortKeyCondition::BeginsWith)
+}
+
+/// Only items whose sort key is between `low` and `high`, inclusive
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_sk_between(
+    query: *mut KsQuery,
+    low: *const c_char,
+    high: *const c_char,
+) -> KsError {
+    run(|| {
+        let query = handle_mut(query, "query")?;
+        let low = key_arg(low, "low")?.to_vec();
+        let high = key_arg(high, "high")?.to_vec();
+        query.sk_condition = Some(SortKeyCondition::Between(low, high));
+        Ok(())
+    })
+}
+
+/// Evaluate at most `limit` items per call; items the filter drops count too
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_limit(query: *mut KsQuery, limit: usize) -> KsError {
+    run(|| {
+        handle_mut(query, "query")?.limit = Some(limit);
+        Ok(())
+    })
+}
+
+/// Ascending (true, the default) or descending sort key order
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_forward(query: *mut KsQuery, forward: bool) -> KsError {
+    run(|| {
+        handle_mut(query, "query")?.forward = forward;
+        Ok(())
+    })
+}
+
+/// Query a secondary index instead of the base table
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_index(query: *mut KsQuery, index: *const c_char) -> KsError {
+    run(|| {
+        let query = handle_mut(query, "query")?;
+        query.index = Some(str_arg(index, "index")?.to_string());
+        Ok(())
+    })
+}
+
+/// Return only items matching a filter expression such as `age >= :min`
+///
+/// `values` (may be NULL) holds its placeholders. Items filtered out still
+/// count in `ks_result_scanned_count`.
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_filter(
+    query: *mut KsQuery,
+    expression: *const c_char,
+    values: *const c_char,
+) -> KsError {
+    run(|| {
+        let query = handle_mut(query, "query")?;
+        query.filter = Some(parse_filter(expression, values)?);
+        Ok(())
+    })
+}
+
+/// Continue after the given key (see `ks_result_last_pk`/`ks_result_last_sk`)
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_start_after(
+    query: *mut KsQuery,
+    pk: *const c_char,
+    sk: *const c_char,
+) -> KsError {
+    run(|| {
+        let query = handle_mut(query, "query")?;
+        query.start_after = Some((key_arg(pk, "pk")?.to_vec(), opt_key_arg(sk).map(<[u8]>::to_vec)));
+        Ok(())
+    })
+}
+
+/// Free a query; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_query_free(query: *mut KsQuery) {
+    if !query.is_null() {
+        drop(Box::from_raw(query));
+    }
+}
+
+/// Run a query into `*out_result` (freed with `ks_result_free`)
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_query(
+    db: *const KsDatabase,
+    query: *const KsQuery,
+    out_result: *mut *mut KsResult,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let response = db.query(handle(query, "query")?.build())?;
+        let result = KsResult::new(response.items, response.scanned_count, response.last_key)?;
+        write_out(out_result, Box::into_raw(Box::new(result)))
+    })
+}
+
+/// Start a scan of the whole table
+#[no_mangle]
+pub extern "C" fn ks_scan_new() -> *mut KsScan {
+    Box::into_raw(Box::default())
+}
+
+/// Evaluate at most `limit` items per call; items the filter drops count too
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_limit(scan: *mut KsScan, limit: usize) -> KsError {
+    run(|| {
+        handle_mut(scan, "scan")?.limit = Some(limit);
+        Ok(())
+    })
+}
+
+/// Only items whose partition key starts with `prefix`
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_pk_prefix(scan: *mut KsScan, prefix: *const c_char) -> KsError {
+    run(|| {
+        let scan = handle_mut(scan, "scan")?;
+        scan.pk_prefix = Some(key_arg(prefix, "prefix")?.to_vec());
+        Ok(())
+    })
+}
+
+/// Scan only segment `segment` of `total_segments` (parallel scans)
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_segment(scan: *mut KsScan, segment: usize, total_segments: usize) -> KsError {
+    run(|| {
+        let scan = handle_mut(scan, "scan")?;
+        if total_segments == 0 || segment >= total_segments {
+            return Err(Failure::invalid(format!(
+                "Segment {} is out of range for {} segments",
+                segment, total_segments
+            )));
+        }
+        scan.segment = Some((segment, total_segments));
+        Ok(())
+    })
+}
+
+/// Return only items matching a filter expression; see `ks_query_filter`
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_filter(scan: *mut KsScan, expression: *const c_char, values: *const c_char) -> KsError {
+    run(|| {
+        let scan = handle_mut(scan, "scan")?;
+        scan.filter = Some(parse_filter(expression, values)?);
+        Ok(())
+    })
+}
+
+/// Continue after the given key (see `ks_result_last_pk`/`ks_result_last_sk`)
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_start_after(
+    scan: *mut KsScan,
+    pk: *const c_char,
+    sk: *const c_char,
+) -> KsError {
+    run(|| {
+        let scan = handle_mut(scan, "scan")?;
+        scan.start_after = Some((key_arg(pk, "pk")?.to_vec(), opt_key_arg(sk).map(<[u8]>::to_vec)));
+        Ok(())
+    })
+}
+
+/// Free a scan; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_scan_free(scan: *mut KsScan) {
+    if !scan.is_null() {
+        drop(Box::from_raw(scan));
+    }
+}
+
+/// Run a scan into `*out_result` (freed with `ks_result_free`)
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_scan(
+    db: *const KsDatabase,
+    scan: *const KsScan,
+    out_result: *mut *mut KsResult,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let response = db.scan(handle(scan, "scan")?.build())?;
+        let result = KsResult::new(response.items, response.scanned_count, response.last_key)?;
+        write_out(out_result, Box::into_raw(Box::new(result)))
+    })
+}
+
+/// Number of items in the page; 0 for NULL
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_count(result: *const KsResult) -> usize {
+    result.as_ref().map_or(0, |result| result.items.len())
+}
+
+/// Item `index` of the page, borrowed from the result; NULL if out of range
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_item_at(result: *const KsResult, index: usize) -> *const KsItem {
+    result
+        .as_ref()
+        .and_then(|result| result.items.get(index))
+        .map_or(std::ptr::null(), |item| item as *const KsItem)
+}
+
+/// Number of items examined to produce the page
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_scanned_count(result: *const KsResult) -> usize {
+    result.as_ref().map_or(0, |result| result.scanned_count)
+}
+
+/// Whether more items may follow (a last key was returned)
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_has_more(result: *const KsResult) -> bool {
+    result.as_ref().is_some_and(|result| result.last_pk.is_some())
+}
+
+/// Partition key to continue after, borrowed from the result; NULL when done
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_last_pk(result: *const KsResult) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|result| result.last_pk.as_ref())
+        .map_or(std::ptr::null(), |pk| pk.as_ptr())
+}
+
+/// Sort key to continue after, borrowed from the result; NULL when done or
+/// when the last item has no sort key
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_last_sk(result: *const KsResult) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|result| result.last_sk.as_ref())
+        .map_or(std::ptr::null(), |sk| sk.as_ptr())
+}
+
+/// Serialize the page into `*out_json`:
+/// `{"items": [...], "count", "scanned_count", "last_key": {"pk", "sk"} | null, "warnings"}`
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_to_json(result: *const KsResult, out_json: *mut *mut c_char) -> KsError {
+    run(|| {
+        let json = serde_json::to_string(&handle(result, "result")?.to_json())?;
+        write_out(out_json, into_c_string(json))
+    })
+}
+
+/// Free a result and the items borrowed from it; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_result_free(result: *mut KsResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{item_json, memory_db};
+    use crate::{ks_database_close, ks_database_put};
+    use std::ffi::CString;
+
+    unsafe fn fill(db: *mut KsDatabase) {
+        for i in 0..5 {
+            let sk = CString::new(format!("user#{}", i)).unwrap();
+            let item = CString::new(format!("{{\"n\": {}}}", i)).unwrap();
+            assert_eq!(ks_database_put(db, c"org#acme".as_ptr(), sk.as_ptr(), item.as_ptr()), KsError::Ok);
+        }
+        assert_eq!(
+            ks_database_put(db, c"org#acme".as_ptr(), c"team#1".as_ptr(), c"{\"n\": 9}".as_ptr()),
+            KsError::Ok
+        );
+        assert_eq!(ks_database_put(db, c"solo".as_ptr(), std::ptr::null(), c"{\"n\": 10}".as_ptr()), KsError::Ok);
+    }
+
+    unsafe fn numbers(result: *const KsResult) -> Vec<i64> {
+        (0..ks_result_count(result))
+            .map(|i| item_json(ks_result_item_at(result, i))["n"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_pages_through_partition() {
+        let db = memory_db();
+        unsafe {
+            fill(db);
+            let query = ks_query_new(c"org#acme".as_ptr());
+            assert_eq!(ks_query_sk_begins_with(query, c"user#".as_ptr()), KsError::Ok);
+            assert_eq!(ks_query_limit(query, 2), KsError::Ok);
+
+            let mut pages = Vec::new();
+            loop {
+                let mut page = std::ptr::null_mut();
+                assert_eq!(ks_database_query(db, query, &mut page), KsError::Ok);
+                pages.push(numbers(page));
+                let more = ks_result_has_more(page);
+                if more {
+                    assert_eq!(
+                        ks_query_start_after(query, ks_result_last_pk(page), ks_result_last_sk(page)),
+                        KsError::Ok
+                    );
+                }
+                ks_result_free(page);
+                if !more {
+                    break;
+                }
+            }
+            let all: Vec<i64> = pages.concat();
+            assert_eq!(all, vec![0, 1, 2, 3, 4]);
+            assert!(pages[0].len() == 2 && ks_result_item_at(std::ptr::null(), 0).is_null());
+            ks_query_free(query);
+
+            let query = ks_query_new(c"org#acme".as_ptr());
+            ks_query_sk_between(query, c"user#1".as_ptr(), c"user#3".as_ptr());
+            ks_query_forward(query, false);
+            let mut page = std::ptr::null_mut();
+            assert_eq!(ks_database_query(db, query, &mut page), KsError::Ok);
+            assert_eq!(numbers(page), vec![3, 2, 1]);
+            ks_result_free(page);
+            ks_query_free(query);
+
+            assert!(ks_query_new(std::ptr::null()).is_null());
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_scan_with_prefix_segments_and_pages() {
+        let db = memory_db();
+        unsafe {
+            fill(db);
+            let scan = ks_scan_new();
+            assert_eq!(ks_scan_pk_prefix(scan, c"org#".as_ptr()), KsError::Ok);
+            let mut page = std::ptr::null_mut();
+            assert_eq!(ks_database_scan(db, scan, &mut page), KsError::Ok);
+            assert_eq!(ks_result_count(page), 6);
+            ks_result_free(page);
+            ks_scan_free(scan);
+
+            let mut total = 0;
+            for segment in 0..4 {
+                let scan = ks_scan_new();
+                assert_eq!(ks_scan_segment(scan, segment, 4), KsError::Ok);
+                ks_scan_limit(scan, 1);
+                loop {
+                    let mut page = std::ptr::null_mut();
+                    assert_eq!(ks_database_scan(db, scan, &mut page), KsError::Ok);
+                    total += ks_result_count(page);
+                    let more = ks_result_has_more(page);
+                    if more {
+                        ks_scan_start_after(scan, ks_result_last_pk(page), ks_result_last_sk(page));
+                    }
+                    ks_result_free(page);
+                    if !more {
+                        break;
+                    }
+                }
+                ks_scan_free(scan);
+            }
+            assert_eq!(total, 7);
+
+            let scan = ks_scan_new();
+            assert_eq!(ks_scan_segment(scan, 4, 4), KsError::InvalidArgument);
+            ks_scan_free(scan);
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        let db = memory_db();
+        unsafe {
+            fill(db);
+            let query = ks_query_new(c"org#acme".as_ptr());
+            assert_eq!(
+                ks_query_filter(query, c"#n >= :min".as_ptr(), c"{\":min\": 3, \"#n\": \"n\"}".as_ptr()),
+                KsError::Ok
+            );
+            let mut page = std::ptr::null_mut();
+            assert_eq!(ks_database_query(db, query, &mut page), KsError::Ok);
+            assert_eq!(numbers(page), vec![9, 3, 4]);
+            ks_result_free(page);
+            assert_eq!(
+                ks_query_filter(query, c"n >= :min".as_ptr(), c"[1]".as_ptr()),
+                KsError::InvalidArgument
+            );
+            ks_query_free(query);
+
+            let scan = ks_scan_new();
+            assert_eq!(ks_scan_filter(scan, c"n < :max".as_ptr(), c"{\":max\": 2}".as_ptr()), KsError::Ok);
+            let mut page = std::ptr::null_mut();
+            assert_eq!(ks_database_scan(db, scan, &mut page), KsError::Ok);
+            assert_eq!(numbers(page), vec![0, 1]);
+            assert_eq!(ks_result_scanned_count(page), 7);
+            ks_result_free(page);
+            ks_scan_free(scan);
+            ks_database_close(db);
+        }
+    }
+}
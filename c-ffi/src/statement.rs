@@ -0,0 +1,298 @@
+/// PartiQL statements
+///
+/// `ks_database_execute_statement` fills a tagged union so C callers can
+/// switch on the statement kind. Bindings that can't read C unions (cgo)
+/// use `ks_database_execute_statement_json` instead: one call, one JSON
+/// document, same content as `ks_statement_result_to_json`.
+
+use crate::database::KsDatabase;
+use crate::error::{handle, opt_str_arg, run, str_arg, write_out, FfiResult, Failure, KsError};
+use crate::item::{into_c_string, KsItem};
+use crate::query::KsResult;
+use kstone_api::json::json_to_value;
+use kstone_api::{item_to_json, Database, ExecuteStatementRequest, ExecuteStatementResponse};
+use std::ffi::c_char;
+
+/// Outcome of a statement; read the member matching `tag`
+#[repr(C)]
+pub enum KsStatementResult {
+    /// SELECT: one page of rows (`ks_result_*` functions)
+    Select { rows: *mut KsResult },
+    /// UPDATE: the item after the update
+    Item { item: *mut KsItem },
+    /// INSERT, DELETE and INSERT ... SELECT: whether it applied and how
+    /// many items it wrote
+    Success { success: bool, affected: usize },
+    /// EXPLAIN: the query plan as JSON (a `char*` owned by the result)
+    Plan { json: *mut c_char },
+}
+
+impl KsStatementResult {
+    fn new(response: ExecuteStatementResponse) -> FfiResult<Self> {
+        Ok(match response {
+            ExecuteStatementResponse::Select {
+                items,
+                scanned_count,
+                last_key,
+                warnings,
+                ..
+            } => {
+                let mut rows = KsResult::new(items, scanned_count, last_key)?;
+                rows.warnings = warnings;
+                KsStatementResult::Select {
+                    rows: Box::into_raw(Box::new(rows)),
+                }
+            }
+            ExecuteStatementResponse::Update { item } => KsStatementResult::Item {
+                item: Box::into_raw(Box::new(KsItem(item))),
+            },
+            ExecuteStatementResponse::Insert { success } | ExecuteStatementResponse::Delete { success } => {
+                KsStatementResult::Success {
+                    success,
+                    affected: usize::from(success),
+                }
+            }
+            ExecuteStatementResponse::InsertSelect { inserted } => KsStatementResult::Success {
+                success: true,
+                affected: inserted,
+            },
+            ExecuteStatementResponse::Explain { plan } => KsStatementResult::Plan {
+                json: into_c_string(serde_json::to_string(&plan)?),
+            },
+        })
+    }
+
+    unsafe fn to_json(&self) -> FfiResult<serde_json::Value> {
+        Ok(match self {
+            KsStatementResult::Select { rows } => {
+                let mut json = handle(*rows, "rows")?.to_json();
+                json["kind"] = "select".into();
+                json
+            }
+            KsStatementResult::Item { item } => serde_json::json!({
+                "kind": "item",
+                "item": item_to_json(&handle(*item, "item")?.0),
+            }),
+            KsStatementResult::Success { success, affected } => serde_json::json!({
+                "kind": "success",
+                "success": success,
+                "affected": affected,
+            }),
+            KsStatementResult::Plan { json } => serde_json::json!({
+                "kind": "plan",
+                "plan": serde_json::from_str::<serde_json::Value>(str_arg(*json, "plan")?)?,
+            }),
+        })
+    }
+}
+
+/// Parse parameters: a JSON array binds `?` placeholders in order, an
+/// object binds `:name` placeholders
+fn request(sql: &str, params_json: Option<&str>) -> FfiResult<ExecuteStatementRequest> {
+    let request = ExecuteStatementRequest::new(sql);
+    match params_json.map(serde_json::from_str).transpose()? {
+        None => Ok(request),
+        Some(serde_json::Value::Array(values)) => {
+            Ok(request.with_parameters(values.into_iter().map(json_to_value).collect()))
+        }
+        Some(serde_json::Value::Object(named)) => Ok(named.into_iter().fold(request, |request, (name, value)| {
+            request.with_named_parameter(name.trim_start_matches(':'), json_to_value(value))
+        })),
+        Some(_) => Err(Failure::invalid("Statement parameters must be a JSON array or object")),
+    }
+}
+
+unsafe fn execute(
+    db: *const KsDatabase,
+    sql: *const c_char,
+    params_json: *const c_char,
+) -> FfiResult<KsStatementResult> {
+    let db: &Database = &handle(db, "db")?.0;
+    let request = request(str_arg(sql, "sql")?, opt_str_arg(params_json, "params_json")?)?;
+    KsStatementResult::new(db.execute(request)?)
+}
+
+/// Execute a PartiQL statement into `*out_result`
+///
+/// Release what the result owns with `ks_statement_result_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_execute_statement(
+    db: *const KsDatabase,
+    sql: *const c_char,
+    out_result: *mut KsStatementResult,
+) -> KsError {
+    ks_database_execute_statement_with_params(db, sql, std::ptr::null(), out_result)
+}
+
+/// Execute a statement with parameters given as JSON
+///
+/// `[1, "a"]` binds `?` placeholders in order; `{"name": "a"}` binds
+/// `:name` placeholders. `params_json` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_execute_statement_with_params(
+    db: *const KsDatabase,
+    sql: *const c_char,
+    params_json: *const c_char,
+    out_result: *mut KsStatementResult,
+) -> KsError {
+    run(|| {
+        if out_result.is_null() {
+            return Err(Failure::null("Output pointer"));
+        }
+        write_out(out_result, execute(db, sql, params_json)?)
+    })
+}
+
+/// Execute a statement and serialize the outcome into `*out_json`
+///
+/// The document has a `kind` of `select` (with the fields of
+/// `ks_result_to_json`), `item`, `success` (`success`, `affected`) or
+/// `plan`. `params_json` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_execute_statement_json(
+    db: *const KsDatabase,
+    sql: *const c_char,
+    params_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> KsError {
+    run(|| {
+        let mut result = execute(db, sql, params_json)?;
+        let json = result.to_json();
+        ks_statement_result_free(&mut result);
+        write_out(out_json, into_c_string(serde_json::to_string(&json?)?))
+    })
+}
+
+/// Serialize a statement result into `*out_json`
+#[no_mangle]
+pub unsafe extern "C" fn ks_statement_result_to_json(
+    result: *const KsStatementResult,
+    out_json: *mut *mut c_char,
+) -> KsError {
+    run(|| {
+        let json = handle(result, "result")?.to_json()?;
+        write_out(out_json, into_c_string(serde_json::to_string(&json)?))
+    })
+}
+
+/// Free what a statement result owns (not the struct itself); NULL is ignored
+///
+/// The result is left as an empty `Success` so freeing twice is harmless.
+#[no_mangle]
+pub unsafe extern "C" fn ks_statement_result_free(result: *mut KsStatementResult) {
+    let Some(result) = result.as_mut() else {
+        return;
+    };
+    let owned = std::mem::replace(
+        result,
+        KsStatementResult::Success {
+            success: false,
+            affected: 0,
+        },
+    );
+    match owned {
+        KsStatementResult::Select { rows } => crate::ks_result_free(rows),
+        KsStatementResult::Item { item } => crate::ks_item_free(item),
+        KsStatementResult::Plan { json } => crate::ks_string_free(json),
+        KsStatementResult::Success { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{item_json, memory_db};
+    use crate::{ks_database_close, ks_result_count, ks_result_item_at, ks_string_free};
+    use serde_json::json;
+    use std::ffi::CStr;
+
+    unsafe fn execute_json(db: *mut KsDatabase, sql: &CStr, params: Option<&CStr>) -> serde_json::Value {
+        let mut out = std::ptr::null_mut();
+        let status = ks_database_execute_statement_json(db, sql.as_ptr(), params.map_or(std::ptr::null(), CStr::as_ptr), &mut out);
+        assert_eq!(status, KsError::Ok, "{:?}", CStr::from_ptr(crate::ks_last_error()));
+        let json = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+        ks_string_free(out);
+        json
+    }
+
+    #[test]
+    fn test_tagged_union_per_statement_kind() {
+        let db = memory_db();
+        unsafe {
+            let mut result = KsStatementResult::Success { success: false, affected: 0 };
+            assert_eq!(
+                ks_database_execute_statement(db, c"INSERT INTO items VALUE {'pk': 'user#1', 'name': 'Ada', 'age': 36}".as_ptr(), &mut result),
+                KsError::Ok
+            );
+            assert!(matches!(result, KsStatementResult::Success { success: true, affected: 1 }));
+
+            assert_eq!(
+                ks_database_execute_statement_with_params(
+                    db,
+                    c"SELECT * FROM items WHERE pk = ?".as_ptr(),
+                    c"[\"user#1\"]".as_ptr(),
+                    &mut result
+                ),
+                KsError::Ok
+            );
+            let KsStatementResult::Select { rows } = result else {
+                panic!("expected rows");
+            };
+            assert_eq!(ks_result_count(rows), 1);
+            assert_eq!(item_json(ks_result_item_at(rows, 0))["name"], json!("Ada"));
+            ks_statement_result_free(&mut result);
+            ks_statement_result_free(&mut result);
+
+            assert_eq!(
+                ks_database_execute_statement(db, c"UPDATE items SET age = 37 WHERE pk = 'user#1'".as_ptr(), &mut result),
+                KsError::Ok
+            );
+            let KsStatementResult::Item { item } = result else {
+                panic!("expected an item");
+            };
+            assert_eq!(item_json(item)["age"], json!(37));
+            ks_statement_result_free(&mut result);
+
+            assert_eq!(
+                ks_database_execute_statement(db, c"SELEC nothing".as_ptr(), &mut result),
+                KsError::InvalidArgument
+            );
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_json_results() {
+        let db = memory_db();
+        unsafe {
+            assert_eq!(
+                execute_json(db, c"INSERT INTO items VALUE {'pk': :pk, 'n': :n}", Some(c"{\"pk\": \"a\", \"n\": 1}")),
+                json!({"kind": "success", "success": true, "affected": 1})
+            );
+            execute_json(db, c"INSERT INTO items VALUE {'pk': 'b', 'n': 2}", None);
+
+            let select = execute_json(db, c"SELECT n FROM items WHERE pk = 'a'", None);
+            assert_eq!(select["kind"], json!("select"));
+            assert_eq!(select["items"], json!([{"n": 1}]));
+            assert_eq!(select["count"], json!(1));
+
+            let scan = execute_json(db, c"SELECT * FROM items WHERE n > 0", None);
+            assert_eq!(scan["count"], json!(2));
+            assert!(!scan["warnings"].as_array().unwrap().is_empty());
+
+            let plan = execute_json(db, c"EXPLAIN SELECT * FROM items WHERE pk = 'a'", None);
+            assert_eq!(plan["kind"], json!("plan"));
+            assert_eq!(plan["plan"]["access_path"]["type"], json!("table_query"));
+
+            let deleted = execute_json(db, c"DELETE FROM items WHERE pk = 'b'", None);
+            assert_eq!(deleted["affected"], json!(1));
+
+            let mut out = std::ptr::null_mut();
+            assert_eq!(
+                ks_database_execute_statement_json(db, c"SELECT * FROM items".as_ptr(), c"42".as_ptr(), &mut out),
+                KsError::InvalidArgument
+            );
+            ks_database_close(db);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+/// Update expressions, conditional writes and write transactions
+///
+/// Conditions that don't hold fail with `KS_ERROR_CONDITIONAL_CHECK_FAILED`
+/// and a rolled-back transaction with `KS_ERROR_TRANSACTION_CANCELED`, so
+/// callers can branch on the code instead of parsing `ks_last_error()`.
+
+use crate::database::KsDatabase;
+use crate::error::{
+    handle, handle_mut, key_arg, opt_key_arg, opt_str_arg, run, str_arg, write_out, Failure, FfiResult, KsError,
+};
+use crate::item::{item_from_json, KsItem};
+use bytes::Bytes;
+use kstone_api::json::json_to_value;
+use kstone_api::{TransactWriteOp, TransactWriteRequest, Update};
+use kstone_core::expression::ExpressionContext;
+use kstone_core::Key;
+use std::ffi::c_char;
+
+/// Parse placeholders: `:name` keys are values, `#name` keys attribute names
+pub(crate) fn parse_context(values_json: Option<&str>) -> FfiResult<ExpressionContext> {
+    let mut context = ExpressionContext::new();
+    let Some(json) = values_json else {
+        return Ok(context);
+    };
+    let serde_json::Value::Object(map) = serde_json::from_str(json)? else {
+        return Err(Failure::invalid("Expression values must be a JSON object"));
+    };
+    for (placeholder, value) in map {
+        if placeholder.starts_with(':') {
+            context = context.with_value(placeholder, json_to_value(value));
+        } else if placeholder.starts_with('#') {
+            let serde_json::Value::String(name) = value else {
+                return Err(Failure::invalid(format!("Attribute name {} must be a string", placeholder)));
+            };
+            context = context.with_name(placeholder, name);
+        } else {
+            return Err(Failure::invalid(format!(
+                "Placeholder '{}' must start with ':' (value) or '#' (name)",
+                placeholder
+            )));
+        }
+    }
+    Ok(context)
+}
+
+fn key(pk: &[u8], sk: Option<&[u8]>) -> Key {
+    match sk {
+        Some(sk) => Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk)),
+        None => Key::new(Bytes::copy_from_slice(pk)),
+    }
+}
+
+/// Apply an update expression such as `SET age = age + :inc REMOVE #tmp`
+///
+/// `condition`, `values` and `out_item` may be NULL. When `out_item` is
+/// given it receives the item as it is after the update.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_update(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    expression: *const c_char,
+    condition: *const c_char,
+    values: *const c_char,
+    out_item: *mut *mut KsItem,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        let mut update = match opt_key_arg(sk) {
+            Some(sk) => Update::with_sk(pk, sk),
+            None => Update::new(pk),
+        }
+        .expression(str_arg(expression, "expression")?);
+        if let Some(condition) = opt_str_arg(condition, "condition")? {
+            update = update.condition(condition);
+        }
+        let context = parse_context(opt_str_arg(values, "values")?)?;
+        for (placeholder, value) in context.values {
+            update = update.value(placeholder, value);
+        }
+        for (placeholder, name) in context.names {
+            update = update.name(placeholder, name);
+        }
+
+        let response = db.update(update)?;
+        if !out_item.is_null() {
+            write_out(out_item, Box::into_raw(Box::new(KsItem(response.item))))?;
+        }
+        Ok(())
+    })
+}
+
+/// Put an item (JSON object) only if `condition` holds for the current one
+///
+/// For example `attribute_not_exists(email)` to avoid overwriting.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_put_conditional(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    item_json: *const c_char,
+    condition: *const c_char,
+    values: *const c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        let item = item_from_json(str_arg(item_json, "item_json")?)?;
+        let condition = str_arg(condition, "condition")?;
+        let context = parse_context(opt_str_arg(values, "values")?)?;
+        match opt_key_arg(sk) {
+            Some(sk) => db.put_conditional_with_sk(pk, sk, item, condition, context)?,
+            None => db.put_conditional(pk, item, condition, context)?,
+        }
+        Ok(())
+    })
+}
+
+/// Delete an item only if `condition` holds for it
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_delete_conditional(
+    db: *const KsDatabase,
+    pk: *const c_char,
+    sk: *const c_char,
+    condition: *const c_char,
+    values: *const c_char,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let pk = key_arg(pk, "pk")?;
+        let condition = str_arg(condition, "condition")?;
+        let context = parse_context(opt_str_arg(values, "values")?)?;
+        match opt_key_arg(sk) {
+            Some(sk) => db.delete_conditional_with_sk(pk, sk, condition, context)?,
+            None => db.delete_conditional(pk, condition, context)?,
+        }
+        Ok(())
+    })
+}
+
+/// Operations committed together by `ks_database_transact_write`
+pub struct KsTransactWrite(TransactWriteRequest);
+
+/// Start an empty write transaction
+#[no_mangle]
+pub extern "C" fn ks_transact_write_new() -> *mut KsTransactWrite {
+    Box::into_raw(Box::new(KsTransactWrite(TransactWriteRequest::new())))
+}
+
+unsafe fn push_op(
+    transaction: *mut KsTransactWrite,
+    op: impl FnOnce() -> FfiResult<TransactWriteOp>,
+) -> KsError {
+    run(|| {
+        let transaction = handle_mut(transaction, "transaction")?;
+        transaction.0.operations.push(op()?);
+        Ok(())
+    })
+}
+
+/// Add a put of an item (JSON object); `condition` may be NULL
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_put(
+    transaction: *mut KsTransactWrite,
+    pk: *const c_char,
+    sk: *const c_char,
+    item_json: *const c_char,
+    condition: *const c_char,
+) -> KsError {
+    push_op(transaction, || {
+        Ok(TransactWriteOp::Put {
+            key: key(key_arg(pk, "pk")?, opt_key_arg(sk)),
+            item: item_from_json(str_arg(item_json, "item_json")?)?,
+            condition: opt_str_arg(condition, "condition")?.map(str::to_string),
+        })
+    })
+}
+
+/// Add an update expression; `condition` may be NULL
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_update(
+    transaction: *mut KsTransactWrite,
+    pk: *const c_char,
+    sk: *const c_char,
+    expression: *const c_char,
+    condition: *const c_char,
+) -> KsError {
+    push_op(transaction, || {
+        Ok(TransactWriteOp::Update {
+            key: key(key_arg(pk, "pk")?, opt_key_arg(sk)),
+            update_expression: str_arg(expression, "expression")?.to_string(),
+            condition: opt_str_arg(condition, "condition")?.map(str::to_string),
+        })
+    })
+}
+
+/// Add a delete; `condition` may be NULL
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_delete(
+    transaction: *mut KsTransactWrite,
+    pk: *const c_char,
+    sk: *const c_char,
+    condition: *const c_char,
+) -> KsError {
+    push_op(transaction, || {
+        Ok(TransactWriteOp::Delete {
+            key: key(key_arg(pk, "pk")?, opt_key_arg(sk)),
+            condition: opt_str_arg(condition, "condition")?.map(str::to_string),
+        })
+    })
+}
+
+/// Add a check that `condition` holds for an item, without writing it
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_condition_check(
+    transaction: *mut KsTransactWrite,
+    pk: *const c_char,
+    sk: *const c_char,
+    condition: *const c_char,
+) -> KsError {
+    push_op(transaction, || {
+        Ok(TransactWriteOp::ConditionCheck {
+            key: key(key_arg(pk, "pk")?, opt_key_arg(sk)),
+            condition: str_arg(condition, "condition")?.to_string(),
+        })
+    })
+}
+
+/// Add placeholders shared by every operation of the transaction
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_values(
+    transaction: *mut KsTransactWrite,
+    values: *const c_char,
+) -> KsError {
+    run(|| {
+        let transaction = handle_mut(transaction, "transaction")?;
+        let context = parse_context(Some(str_arg(values, "values")?))?;
+        transaction.0.context.values.extend(context.values);
+        transaction.0.context.names.extend(context.names);
+        Ok(())
+    })
+}
+
+/// Number of operations added so far
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_count(transaction: *const KsTransactWrite) -> usize {
+    transaction
+        .as_ref()
+        .map_or(0, |transaction| transaction.0.operations.len())
+}
+
+/// Free a transaction; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn ks_transact_write_free(transaction: *mut KsTransactWrite) {
+    if !transaction.is_null() {
+        drop(Box::from_raw(transaction));
+    }
+}
+
+/// Commit every operation or none of them
+///
+/// The transaction is not consumed and still has to be freed.
+#[no_mangle]
+pub unsafe extern "C" fn ks_database_transact_write(
+    db: *const KsDatabase,
+    transaction: *const KsTransactWrite,
+) -> KsError {
+    run(|| {
+        let db = &handle(db, "db")?.0;
+        let transaction = handle(transaction, "transaction")?;
+        db.transact_write(transaction.0.clone())?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{item_json, memory_db};
+    use crate::{ks_database_close, ks_database_get, ks_database_put, ks_item_free};
+    use serde_json::json;
+    use std::ptr::null;
+
+    unsafe fn get(db: *mut KsDatabase, pk: &std::ffi::CStr) -> Option<serde_json::Value> {
+        let mut item = std::ptr::null_mut();
+        match ks_database_get(db, pk.as_ptr(), null(), &mut item) {
+            KsError::Ok => {
+                let json = item_json(item);
+                ks_item_free(item);
+                Some(json)
+            }
+            KsError::NotFound => None,
+            other => panic!("get failed: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_update_with_condition_and_values() {
+        let db = memory_db();
+        unsafe {
+            ks_database_put(db, c"user#1".as_ptr(), null(), c"{\"age\": 30, \"tmp\": 1}".as_ptr());
+
+            let mut item = std::ptr::null_mut();
+            assert_eq!(
+                ks_database_update(
+                    db,
+                    c"user#1".as_ptr(),
+                    null(),
+                    c"SET age = age + :inc REMOVE #t".as_ptr(),
+                    c"age >= :min".as_ptr(),
+                    c"{\":inc\": 1, \":min\": 18, \"#t\": \"tmp\"}".as_ptr(),
+                    &mut item,
+                ),
+                KsError::Ok
+            );
+            assert_eq!(item_json(item), json!({"age": 31}));
+            ks_item_free(item);
+
+            assert_eq!(
+                ks_database_update(
+                    db,
+                    c"user#1".as_ptr(),
+                    null(),
+                    c"SET age = :age".as_ptr(),
+                    c"age > :max".as_ptr(),
+                    c"{\":age\": 1, \":max\": 100}".as_ptr(),
+                    std::ptr::null_mut(),
+                ),
+                KsError::ConditionalCheckFailed
+            );
+            assert_eq!(get(db, c"user#1"), Some(json!({"age": 31})));
+
+            assert_eq!(
+                ks_database_update(db, c"user#1".as_ptr(), null(), c"SET x = :v".as_ptr(), null(), c"{\"v\": 1}".as_ptr(), std::ptr::null_mut()),
+                KsError::InvalidArgument
+            );
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_conditional_put_and_delete() {
+        let db = memory_db();
+        unsafe {
+            let new_only = c"attribute_not_exists(email)";
+            assert_eq!(
+                ks_database_put_conditional(db, c"user#1".as_ptr(), null(), c"{\"email\": \"a@x\"}".as_ptr(), new_only.as_ptr(), null()),
+                KsError::Ok
+            );
+            assert_eq!(
+                ks_database_put_conditional(db, c"user#1".as_ptr(), null(), c"{\"email\": \"b@x\"}".as_ptr(), new_only.as_ptr(), null()),
+                KsError::ConditionalCheckFailed
+            );
+            assert_eq!(get(db, c"user#1"), Some(json!({"email": "a@x"})));
+
+            assert_eq!(
+                ks_database_delete_conditional(db, c"user#1".as_ptr(), null(), c"email = :e".as_ptr(), c"{\":e\": \"b@x\"}".as_ptr()),
+                KsError::ConditionalCheckFailed
+            );
+            assert_eq!(
+                ks_database_delete_conditional(db, c"user#1".as_ptr(), null(), c"email = :e".as_ptr(), c"{\":e\": \"a@x\"}".as_ptr()),
+                KsError::Ok
+            );
+            assert_eq!(get(db, c"user#1"), None);
+            ks_database_close(db);
+        }
+    }
+
+    #[test]
+    fn test_transact_write_is_all_or_nothing() {
+        let db = memory_db();
+        unsafe {
+            ks_database_put(db, c"account#a".as_ptr(), null(), c"{\"balance\": 100}".as_ptr());
+            ks_database_put(db, c"account#b".as_ptr(), null(), c"{\"balance\": 0}".as_ptr());
+
+            let transfer = |amount: i64| {
+                let tx = ks_transact_write_new();
+                let values = std::ffi::CString::new(format!("{{\":amount\": {}}}", amount)).unwrap();
+                assert_eq!(ks_transact_write_values(tx, values.as_ptr()), KsError::Ok);
+                ks_transact_write_update(tx, c"account#a".as_ptr(), null(), c"SET balance = balance - :amount".as_ptr(), c"balance >= :amount".as_ptr());
+                ks_transact_write_update(tx, c"account#b".as_ptr(), null(), c"SET balance = balance + :amount".as_ptr(), null());
+                ks_transact_write_put(tx, c"log#1".as_ptr(), c"entry".as_ptr(), c"{\"ok\": true}".as_ptr(), null());
+                assert_eq!(ks_transact_write_count(tx), 3);
+                let status = ks_database_transact_write(db, tx);
+                ks_transact_write_free(tx);
+                status
+            };
+
+            assert_eq!(transfer(150), KsError::TransactionCanceled);
+            assert_eq!(get(db, c"account#a"), Some(json!({"balance": 100})));
+            assert_eq!(get(db, c"account#b"), Some(json!({"balance": 0})));
+
+            assert_eq!(transfer(60), KsError::Ok);
+            assert_eq!(get(db, c"account#a"), Some(json!({"balance": 40})));
+            assert_eq!(get(db, c"account#b"), Some(json!({"balance": 60})));
+
+            let tx = ks_transact_write_new();
+            ks_transact_write_condition_check(tx, c"account#a".as_ptr(), null(), c"balance > :min".as_ptr());
+            ks_transact_write_delete(tx, c"account#b".as_ptr(), null(), null());
+            ks_transact_write_values(tx, c"{\":min\": 1000}".as_ptr());
+            assert_eq!(ks_database_transact_write(db, tx), KsError::TransactionCanceled);
+            ks_transact_write_free(tx);
+            assert!(get(db, c"account#b").is_some());
+            ks_database_close(db);
+        }
+    }
+}
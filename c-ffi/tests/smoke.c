@@ -0,0 +1,146 @@
+/*
+ * C smoke test for the FFI, run against the shared library:
+ *
+ *   cargo build -p kstone-ffi
+ *   cc -Ic-ffi/include c-ffi/tests/smoke.c -Ltarget/debug -lkstone_ffi -o /tmp/ks_smoke
+ *   LD_LIBRARY_PATH=target/debug /tmp/ks_smoke
+ */
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "keystone.h"
+
+#define CHECK(call)                                                          \
+    do {                                                                     \
+        KsError _err = (call);                                               \
+        if (_err != KS_ERROR_OK) {                                           \
+            fprintf(stderr, "%s failed (%d): %s\n", #call, _err, ks_last_error()); \
+            return 1;                                                        \
+        }                                                                    \
+    } while (0)
+
+static int test_items_and_queries(KsDatabase *db) {
+    CHECK(ks_database_put(db, "user#1", NULL, "{\"name\": \"Ada\", \"age\": 36}"));
+    CHECK(ks_database_put_string(db, "org#acme", "user#1", "role", "admin"));
+    CHECK(ks_database_put_string(db, "org#acme", "user#2", "role", "dev"));
+    CHECK(ks_database_put_string(db, "org#acme", "user#3", "role", "dev"));
+
+    KsItem *item = NULL;
+    CHECK(ks_database_get(db, "user#1", NULL, &item));
+    char *name = NULL;
+    CHECK(ks_item_get_string(item, "name", &name));
+    assert(strcmp(name, "Ada") == 0);
+    ks_string_free(name);
+    ks_item_free(item);
+
+    assert(ks_database_get(db, "user#9", NULL, &item) == KS_ERROR_NOT_FOUND);
+    assert(item == NULL);
+
+    /* Page through the partition two items at a time */
+    KsQuery *query = ks_query_new("org#acme");
+    CHECK(ks_query_sk_begins_with(query, "user#"));
+    CHECK(ks_query_limit(query, 2));
+    size_t total = 0;
+    for (;;) {
+        KsResult *page = NULL;
+        CHECK(ks_database_query(db, query, &page));
+        for (size_t i = 0; i < ks_result_count(page); i++) {
+            char *role = NULL;
+            CHECK(ks_item_get_string(ks_result_item_at(page, i), "role", &role));
+            ks_string_free(role);
+            total++;
+        }
+        bool more = ks_result_has_more(page);
+        if (more) {
+            CHECK(ks_query_start_after(query, ks_result_last_pk(page), ks_result_last_sk(page)));
+        }
+        ks_result_free(page);
+        if (!more) break;
+    }
+    ks_query_free(query);
+    assert(total == 3);
+
+    KsScan *scan = ks_scan_new();
+    CHECK(ks_scan_pk_prefix(scan, "user#"));
+    KsResult *page = NULL;
+    CHECK(ks_database_scan(db, scan, &page));
+    assert(ks_result_count(page) == 1);
+    char *json = NULL;
+    CHECK(ks_item_to_json(ks_result_item_at(page, 0), &json));
+    printf("scan: %s\n", json);
+    ks_string_free(json);
+    ks_result_free(page);
+    ks_scan_free(scan);
+    return 0;
+}
+
+static int test_writes(KsDatabase *db) {
+    CHECK(ks_database_put(db, "account#a", NULL, "{\"balance\": 100}"));
+    CHECK(ks_database_put(db, "account#b", NULL, "{\"balance\": 0}"));
+
+    KsItem *item = NULL;
+    CHECK(ks_database_update(db, "account#a", NULL, "SET balance = balance + :n", NULL, "{\":n\": 5}", &item));
+    char *balance = NULL;
+    CHECK(ks_item_get_string(item, "balance", &balance));
+    assert(strcmp(balance, "105") == 0);
+    ks_string_free(balance);
+    ks_item_free(item);
+
+    assert(ks_database_put_conditional(db, "account#a", NULL, "{\"balance\": 0}",
+                                       "attribute_not_exists(balance)", NULL) == KS_ERROR_CONDITIONAL_CHECK_FAILED);
+
+    KsTransactWrite *tx = ks_transact_write_new();
+    CHECK(ks_transact_write_values(tx, "{\":amount\": 500}"));
+    CHECK(ks_transact_write_update(tx, "account#a", NULL, "SET balance = balance - :amount", "balance >= :amount"));
+    CHECK(ks_transact_write_update(tx, "account#b", NULL, "SET balance = balance + :amount", NULL));
+    assert(ks_database_transact_write(db, tx) == KS_ERROR_TRANSACTION_CANCELED);
+    ks_transact_write_free(tx);
+    return 0;
+}
+
+static int test_batches(KsDatabase *db) {
+    size_t processed = 0;
+    CHECK(ks_database_batch_write(db, "[{\"put\": {\"pk\": \"batch#1\", \"item\": {\"n\": 1}}},"
+                                      " {\"put\": {\"pk\": \"batch#2\", \"sk\": \"a\", \"item\": {\"n\": 2}}}]",
+                                  &processed));
+    assert(processed == 2);
+
+    char *json = NULL;
+    CHECK(ks_database_batch_get(db, "[{\"pk\": \"batch#2\", \"sk\": \"a\"}, {\"pk\": \"batch#9\"}]", &json));
+    assert(strcmp(json, "[{\"n\":2},null]") == 0);
+    ks_string_free(json);
+
+    CHECK(ks_database_transact_get(db, "[{\"pk\": \"batch#1\"}]", &json));
+    assert(strcmp(json, "[{\"n\":1}]") == 0);
+    ks_string_free(json);
+    return 0;
+}
+
+static int test_statements(KsDatabase *db) {
+    KsStatementResult result;
+    CHECK(ks_database_execute_statement_with_params(
+        db, "INSERT INTO items VALUE {'pk': ?, 'name': ?}", "[\"stmt#1\", \"Ada\"]", &result));
+    assert(result.tag == KS_STATEMENT_RESULT_SUCCESS && result.SUCCESS.affected == 1);
+
+    CHECK(ks_database_execute_statement(db, "SELECT * FROM items WHERE pk = 'stmt#1'", &result));
+    assert(result.tag == KS_STATEMENT_RESULT_SELECT);
+    assert(ks_result_count(result.SELECT.rows) == 1);
+    ks_statement_result_free(&result);
+
+    char *json = NULL;
+    CHECK(ks_database_execute_statement_json(db, "UPDATE items SET name = :name WHERE pk = 'stmt#1'",
+                                             "{\"name\": \"Grace\"}", &json));
+    assert(strstr(json, "\"kind\":\"item\"") && strstr(json, "Grace"));
+    ks_string_free(json);
+    return 0;
+}
+
+int main(void) {
+    KsDatabase *db = NULL;
+    CHECK(ks_database_create_in_memory(&db));
+    int failed = test_items_and_queries(db) || test_writes(db) || test_batches(db) || test_statements(db);
+    ks_database_close(db);
+    if (!failed) printf("ok\n");
+    return failed;
+}
@@ -4,7 +4,7 @@
 
 use anyhow::Result;
 use kstone_api::Database;
-use kstone_sync::{SyncEngine, SyncConfig, ConflictStrategy, SyncEndpoint};
+use kstone_sync::{SyncEngine, SyncConfig, ConflictStrategy, SyncEndpoint, SyncScope};
 use std::sync::Arc;
 use std::path::PathBuf;
 
@@ -109,6 +109,8 @@ async fn upload_snapshot(db_path: &PathBuf, bucket: &str, prefix: &str) -> Resul
         batch_size: 100,
         max_retries: 3,
         enable_compression: false,
+        scope: SyncScope::default(),
+        max_bytes_per_sec: None,
     };
 
     let sync_engine = SyncEngine::new(db, config)?;
@@ -197,6 +199,8 @@ async fn restore_snapshot(db_path: &PathBuf, bucket: &str, prefix: &str, snapsho
         batch_size: 100,
         max_retries: 3,
         enable_compression: false,
+        scope: SyncScope::default(),
+        max_bytes_per_sec: None,
     };
 
     let sync_engine = SyncEngine::new(db, config)?;
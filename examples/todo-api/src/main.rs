@@ -169,13 +169,11 @@ async fn get_todo(
 ) -> Result<Json<TodoResponse>, AppError> {
     let key = format!("todo#{}", id);
 
-    let item = state
+    let todo: Todo = state
         .db
-        .get(key.as_bytes())?
+        .get_t(key.as_bytes())?
         .ok_or(AppError::NotFound("Todo not found".to_string()))?;
 
-    let todo = item_to_todo(item)?;
-
     info!("Retrieved todo: {}", id);
 
     Ok(Json(TodoResponse { todo }))
@@ -635,60 +633,6 @@ async fn stats(State(state): State<AppState>) -> Result<Json<StatsResponse>, App
     }))
 }
 
-/// Helper function to convert item to Todo
-fn item_to_todo(item: std::collections::HashMap<String, KeystoneValue>) -> Result<Todo, AppError> {
-    let id = match item.get("id") {
-        Some(KeystoneValue::S(s)) => s.clone(),
-        _ => return Err(AppError::InvalidData),
-    };
-
-    let title = match item.get("title") {
-        Some(KeystoneValue::S(s)) => s.clone(),
-        _ => return Err(AppError::InvalidData),
-    };
-
-    let description = item.get("description").and_then(|v| match v {
-        KeystoneValue::S(s) => Some(s.clone()),
-        _ => None,
-    });
-
-    let status = match item.get("status") {
-        Some(KeystoneValue::S(s)) => Status::from_str(s).ok_or(AppError::InvalidData)?,
-        _ => return Err(AppError::InvalidData),
-    };
-
-    let priority = match item.get("priority") {
-        Some(KeystoneValue::N(n)) => n.parse::<i64>().unwrap_or(3),
-        _ => 3,
-    };
-
-    let created_at = match item.get("created_at") {
-        Some(KeystoneValue::N(n)) => n.parse::<u64>().unwrap_or(0),
-        _ => 0,
-    };
-
-    let updated_at = match item.get("updated_at") {
-        Some(KeystoneValue::N(n)) => n.parse::<u64>().unwrap_or(0),
-        _ => 0,
-    };
-
-    let completed_at = item.get("completed_at").and_then(|v| match v {
-        KeystoneValue::N(n) => n.parse::<u64>().ok(),
-        _ => None,
-    });
-
-    Ok(Todo {
-        id,
-        title,
-        description,
-        status,
-        priority,
-        created_at,
-        updated_at,
-        completed_at,
-    })
-}
-
 /// Application errors
 #[derive(Debug)]
 enum AppError {
@@ -1,5 +1,6 @@
 /// Data models for Todo API
 
+use kstone_api::KeystoneItem;
 use serde::{Deserialize, Serialize};
 
 /// Todo status
@@ -31,7 +32,7 @@ impl Status {
 }
 
 /// Todo item
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, KeystoneItem)]
 pub struct Todo {
     /// Unique ID
     pub id: String,
@@ -3,7 +3,7 @@
 /// This example demonstrates:
 /// - Basic put/get/delete operations
 /// - TTL for automatic link expiration
-/// - Conditional updates (visit counter)
+/// - Atomic counters (visit counter)
 /// - REST API with Axum
 /// - Health and stats endpoints
 
@@ -150,25 +150,8 @@ async fn redirect_url(
         _ => return Err(AppError::InvalidData),
     };
 
-    // Increment visit counter (using update expression would be better in production)
-    let visits = match item.get("visits") {
-        Some(KeystoneValue::N(n)) => n.parse::<i64>().unwrap_or(0) + 1,
-        _ => 1,
-    };
-
-    let updated_item = ItemBuilder::new()
-        .string("long_url", &long_url)
-        .string("short_code", &code)
-        .number("visits", visits)
-        .number("created_at",
-            match item.get("created_at") {
-                Some(KeystoneValue::N(n)) => n.parse().unwrap_or(0),
-                _ => 0,
-            }
-        )
-        .build();
-
-    state.db.put(key.as_bytes(), updated_item)?;
+    // Increment visit counter
+    let visits = state.db.increment(key.as_bytes(), None, "visits", 1)?;
 
     info!("Redirecting {} to {} (visit #{})", code, long_url, visits);
 
@@ -0,0 +1,306 @@
+/// Async API surface for web services (requires the `async` feature)
+///
+/// All `Database` operations are blocking. `AsyncDatabase` runs each call on
+/// tokio's blocking worker pool so async services (axum, tonic, ...) can use
+/// the database without stalling the runtime.
+
+use crate::{
+    BatchGetRequest, BatchGetResponse, BatchWriteRequest, BatchWriteResponse, Database,
+    ExecuteStatementRequest, ExecuteStatementResponse, Query, QueryResponse, Scan, ScanResponse,
+    TransactGetRequest, TransactGetResponse, TransactWriteRequest, TransactWriteResponse, Update,
+    UpdateResponse, DEFAULT_PAGE_SIZE,
+};
+use kstone_core::{Error, Item, Result};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Async handle to a KeystoneDB database
+///
+/// Cheap to clone; all clones share the same underlying `Database`.
+#[derive(Clone)]
+pub struct AsyncDatabase {
+    db: Arc<Database>,
+}
+
+impl AsyncDatabase {
+    /// Wrap an existing database
+    pub fn new(db: Database) -> Self {
+        Self { db: Arc::new(db) }
+    }
+
+    /// Wrap a database that is already shared
+    pub fn from_arc(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Get the underlying blocking database
+    pub fn inner(&self) -> &Arc<Database> {
+        &self.db
+    }
+
+    /// Run a blocking database operation on the worker pool
+    async fn run<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || f(&db))
+            .await
+            .map_err(|e| Error::Internal(format!("Task join error: {}", e)))?
+    }
+
+    /// Put an item with a simple partition key
+    pub async fn put(&self, pk: &[u8], item: Item) -> Result<()> {
+        let pk = pk.to_vec();
+        self.run(move |db| db.put(&pk, item)).await
+    }
+
+    /// Put an item with partition key and sort key
+    pub async fn put_with_sk(&self, pk: &[u8], sk: &[u8], item: Item) -> Result<()> {
+        let (pk, sk) = (pk.to_vec(), sk.to_vec());
+        self.run(move |db| db.put_with_sk(&pk, &sk, item)).await
+    }
+
+    /// Get an item by partition key
+    pub async fn get(&self, pk: &[u8]) -> Result<Option<Item>> {
+        let pk = pk.to_vec();
+        self.run(move |db| db.get(&pk)).await
+    }
+
+    /// Get an item by partition key and sort key
+    pub async fn get_with_sk(&self, pk: &[u8], sk: &[u8]) -> Result<Option<Item>> {
+        let (pk, sk) = (pk.to_vec(), sk.to_vec());
+        self.run(move |db| db.get_with_sk(&pk, &sk)).await
+    }
+
+    /// Delete an item by partition key
+    pub async fn delete(&self, pk: &[u8]) -> Result<()> {
+        let pk = pk.to_vec();
+        self.run(move |db| db.delete(&pk)).await
+    }
+
+    /// Delete an item by partition key and sort key
+    pub async fn delete_with_sk(&self, pk: &[u8], sk: &[u8]) -> Result<()> {
+        let (pk, sk) = (pk.to_vec(), sk.to_vec());
+        self.run(move |db| db.delete_with_sk(&pk, &sk)).await
+    }
+
+    /// Query items within a partition
+    pub async fn query(&self, query: Query) -> Result<QueryResponse> {
+        self.run(move |db| db.query(query)).await
+    }
+
+    /// Scan all items in the table
+    pub async fn scan(&self, scan: Scan) -> Result<ScanResponse> {
+        self.run(move |db| db.scan(scan)).await
+    }
+
+    /// Stream every item matching a query, fetching pages as needed
+    pub fn query_stream(&self, query: Query) -> ItemStream {
+        self.stream(move |db, tx| {
+            for item in db.query_iter(query)? {
+                if tx.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Stream every item in a scan, fetching pages as needed
+    pub fn scan_stream(&self, scan: Scan) -> ItemStream {
+        self.stream(move |db, tx| {
+            for item in db.scan_iter(scan)? {
+                if tx.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Feed items from a blocking producer into an `ItemStream`
+    ///
+    /// The producer stops once the stream is dropped; an error it returns
+    /// is delivered as the stream's last element.
+    fn stream<F>(&self, f: F) -> ItemStream
+    where
+        F: FnOnce(&Database, &mpsc::Sender<Result<Item>>) -> Result<()> + Send + 'static,
+    {
+        let db = Arc::clone(&self.db);
+        let (tx, rx) = mpsc::channel(DEFAULT_PAGE_SIZE);
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = f(&db, &tx) {
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+        ItemStream { rx }
+    }
+
+    /// Update an item using update expression
+    pub async fn update(&self, update: Update) -> Result<UpdateResponse> {
+        self.run(move |db| db.update(update)).await
+    }
+
+    /// Batch get multiple items
+    pub async fn batch_get(&self, request: BatchGetRequest) -> Result<BatchGetResponse> {
+        self.run(move |db| db.batch_get(request)).await
+    }
+
+    /// Batch write multiple items
+    pub async fn batch_write(&self, request: BatchWriteRequest) -> Result<BatchWriteResponse> {
+        self.run(move |db| db.batch_write(request)).await
+    }
+
+    /// Transactional get - read multiple items atomically
+    pub async fn transact_get(&self, request: TransactGetRequest) -> Result<TransactGetResponse> {
+        self.run(move |db| db.transact_get(request)).await
+    }
+
+    /// Transactional write - write multiple items atomically with conditions
+    pub async fn transact_write(&self, request: TransactWriteRequest) -> Result<TransactWriteResponse> {
+        self.run(move |db| db.transact_write(request)).await
+    }
+
+    /// Execute a PartiQL statement
+    pub async fn execute_statement(&self, sql: impl Into<String>) -> Result<ExecuteStatementResponse> {
+        let sql = sql.into();
+        self.run(move |db| db.execute_statement(&sql)).await
+    }
+
+    /// Execute a PartiQL statement with bound parameters
+    pub async fn execute(&self, request: ExecuteStatementRequest) -> Result<ExecuteStatementResponse> {
+        self.run(move |db| db.execute(request)).await
+    }
+
+    /// Execute INSERT, UPDATE and DELETE statements atomically
+    pub async fn execute_transaction(
+        &self,
+        statements: Vec<ExecuteStatementRequest>,
+    ) -> Result<TransactWriteResponse> {
+        self.run(move |db| db.execute_transaction(&statements)).await
+    }
+
+    /// Flush any pending writes
+    pub async fn flush(&self) -> Result<()> {
+        self.run(|db| db.flush()).await
+    }
+}
+
+/// Async stream of items produced by `query_stream` or `scan_stream`
+///
+/// Pages are fetched on the blocking worker pool while the consumer reads.
+pub struct ItemStream {
+    rx: mpsc::Receiver<Result<Item>>,
+}
+
+impl ItemStream {
+    /// Next item, or `None` once the query or scan is exhausted
+    pub async fn next(&mut self) -> Option<Result<Item>> {
+        self.rx.recv().await
+    }
+
+    /// Drain the remaining items into a `Vec`, stopping at the first error
+    pub async fn collect(mut self) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+impl From<Database> for AsyncDatabase {
+    fn from(db: Database) -> Self {
+        Self::new(db)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_async_put_get_delete() {
+        let dir = TempDir::new().unwrap();
+        let db = AsyncDatabase::new(Database::create(dir.path()).unwrap());
+
+        let item = ItemBuilder::new().string("name", "Alice").build();
+        db.put(b"user#1", item.clone()).await.unwrap();
+        assert_eq!(db.get(b"user#1").await.unwrap(), Some(item));
+
+        db.delete(b"user#1").await.unwrap();
+        assert!(db.get(b"user#1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_async_query_and_scan() {
+        let dir = TempDir::new().unwrap();
+        let db = AsyncDatabase::new(Database::create(dir.path()).unwrap());
+
+        for i in 0..10 {
+            let sk = format!("item#{:03}", i);
+            let item = ItemBuilder::new().number("id", i).build();
+            db.put_with_sk(b"user#1", sk.as_bytes(), item).await.unwrap();
+        }
+
+        let response = db.query(Query::new(b"user#1").limit(5)).await.unwrap();
+        assert_eq!(response.items.len(), 5);
+
+        let response = db.scan(Scan::new()).await.unwrap();
+        assert_eq!(response.items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_async_query_and_scan_streams() {
+        let dir = TempDir::new().unwrap();
+        let db = AsyncDatabase::new(Database::create(dir.path()).unwrap());
+
+        for i in 0..12 {
+            let sk = format!("item#{:03}", i);
+            let item = ItemBuilder::new().number("id", i).build();
+            db.put_with_sk(b"user#1", sk.as_bytes(), item).await.unwrap();
+        }
+
+        let mut stream = db.query_stream(Query::new(b"user#1").page_size(5));
+        let mut count = 0;
+        while let Some(item) = stream.next().await {
+            item.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 12);
+
+        let items = db.scan_stream(Scan::new().page_size(3)).collect().await.unwrap();
+        assert_eq!(items.len(), 12);
+
+        // Filter errors surface through the stream
+        let mut stream = db.scan_stream(Scan::new().filter("id >"));
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_async_concurrent_writes() {
+        let dir = TempDir::new().unwrap();
+        let db = AsyncDatabase::new(Database::create(dir.path()).unwrap());
+
+        let mut handles = Vec::new();
+        for i in 0..20 {
+            let db = db.clone();
+            handles.push(tokio::spawn(async move {
+                let pk = format!("key#{}", i);
+                let item = ItemBuilder::new().number("value", i).build();
+                db.put(pk.as_bytes(), item).await
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let response = db.scan(Scan::new()).await.unwrap();
+        assert_eq!(response.items.len(), 20);
+    }
+}
@@ -0,0 +1,141 @@
+/// JSON interop for items
+///
+/// Plain JSON (not DynamoDB's typed JSON): objects map to items and `M`,
+/// arrays to `L`, numbers to `N`. When converting items to JSON, binary
+/// values become base64 strings, vectors become arrays of numbers and
+/// timestamps become millisecond numbers; those read back as `S`, `L` and `N`.
+
+use base64::Engine as _;
+use kstone_core::{Error, Item, Result, Value};
+
+/// JSON conversions on `Item`
+pub trait ItemJsonExt: Sized {
+    /// Build an item from a JSON object
+    fn from_json(json: serde_json::Value) -> Result<Self>;
+
+    /// Convert the item to a JSON object
+    fn to_json(&self) -> serde_json::Value;
+}
+
+impl ItemJsonExt for Item {
+    fn from_json(json: serde_json::Value) -> Result<Self> {
+        match json {
+            serde_json::Value::Object(map) => Ok(map
+                .into_iter()
+                .map(|(name, value)| (name, json_to_value(value)))
+                .collect()),
+            _ => Err(Error::InvalidArgument(
+                "Item must be a JSON object".to_string(),
+            )),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        item_to_json(self)
+    }
+}
+
+/// Convert an item to a JSON object
+pub fn item_to_json(item: &Item) -> serde_json::Value {
+    serde_json::Value::Object(
+        item.iter()
+            .map(|(name, value)| (name.clone(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Convert an attribute value to JSON
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::S(s) => serde_json::Value::String(s.clone()),
+        Value::N(n) => {
+            if let Ok(i) = n.parse::<i64>() {
+                i.into()
+            } else {
+                n.parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map_or_else(|| serde_json::Value::String(n.clone()), serde_json::Value::Number)
+            }
+        }
+        Value::B(bytes) => {
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+        }
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Null => serde_json::Value::Null,
+        Value::L(values) => serde_json::Value::Array(values.iter().map(value_to_json).collect()),
+        Value::M(map) => item_to_json(map),
+        Value::VecF32(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|&v| {
+                    serde_json::Number::from_f64(v as f64)
+                        .map_or(serde_json::Value::Null, serde_json::Value::Number)
+                })
+                .collect(),
+        ),
+        Value::Ts(ts) => (*ts).into(),
+    }
+}
+
+/// Convert a JSON value to an attribute value
+pub fn json_to_value(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => Value::N(n.to_string()),
+        serde_json::Value::String(s) => Value::S(s),
+        serde_json::Value::Array(values) => {
+            Value::L(values.into_iter().map(json_to_value).collect())
+        }
+        serde_json::Value::Object(map) => Value::M(
+            map.into_iter()
+                .map(|(name, value)| (name, json_to_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let json = json!({
+            "name": "Alice",
+            "age": 30,
+            "score": 4.5,
+            "active": true,
+            "nickname": null,
+            "tags": ["a", 1],
+            "address": {"city": "Oslo"}
+        });
+
+        let item = Item::from_json(json.clone()).unwrap();
+        assert_eq!(item.get("age"), Some(&Value::number(30)));
+        assert_eq!(item.get("tags"), Some(&Value::L(vec![Value::string("a"), Value::number(1)])));
+        assert_eq!(item_to_json(&item), json);
+
+        assert!(Item::from_json(json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_extended_types_to_json() {
+        let mut item = Item::new();
+        item.insert("avatar".to_string(), Value::B(Bytes::from_static(b"hi")));
+        item.insert("embedding".to_string(), Value::VecF32(vec![0.5, 1.0]));
+        item.insert("created".to_string(), Value::Ts(1_700_000_000_000));
+
+        assert_eq!(
+            item.to_json(),
+            json!({
+                "avatar": "aGk=",
+                "embedding": [0.5, 1.0],
+                "created": 1_700_000_000_000i64
+            })
+        );
+    }
+}
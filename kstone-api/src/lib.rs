@@ -1,4 +1,7 @@
-use kstone_core::{Result, Key, Item, Value, lsm::LsmEngine, MemoryLsmEngine};
+use kstone_core::{
+    Result, Key, Item, Value, lsm::LsmEngine, MemoryLsmEngine,
+    iterator::{QueryParams, QueryResult, ScanParams, ScanResult},
+};
 use bytes::Bytes;
 use std::path::Path;
 use std::collections::HashMap;
@@ -6,20 +9,47 @@ use std::collections::HashMap;
 pub use kstone_core::{
     Error as KeystoneError,
     Value as KeystoneValue,
-    index::{LocalSecondaryIndex, GlobalSecondaryIndex, IndexProjection, TableSchema},
-    stream::{StreamRecord, StreamEventType, StreamViewType, StreamConfig},
-    compaction::CompactionStats,
+    iterator::Select,
+    index::{LocalSecondaryIndex, GlobalSecondaryIndex, GeoIndex, IndexBackfill, IndexProjection, TableSchema, VectorIndex},
+    vector::{DistanceMetric, VectorMatch},
+    geo::{GeoBox, GeoMatch, GeoPoint},
+    stream::{StreamRecord, StreamEventType, StreamViewType, StreamConfig, StreamStats, ShardIterator, ShardIteratorType, GetRecordsResult},
+    stream_subscription::{StreamSubscription, SubscriptionConfig},
+    stream_log::StreamTail,
+    compaction::{CompactionFilter, CompactionStats, FilterDecision},
+    background::{FlushStats, TtlStats},
+    block_cache::BlockCacheStats,
+    verify::{Problem, ProblemKind, VerifyReport},
+    storage::{MemoryStorage, Storage},
+    validation::{AttributeSchema, AttributeType, ValidationError, ValidationViolation, ValueConstraint, Validator},
+    generated::{ComputedAttribute, DefaultValue, TimestampAttributes},
+    BackupInfo,
+    BloomStats,
+    BulkLoadStats,
+    ColdStore,
+    FsColdStore,
+    TieringStats,
+    SlowOperation,
+    SlowOperationKind,
+    HotPartition,
+    OperationStats,
     DatabaseConfig,
+    SstStats,
+    StripeStats,
+    StripeUsage,
+    WalSyncMode,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use kstone_core::FsStorage;
 
 pub mod query;
-pub use query::{Query, QueryResponse};
+pub use query::{Query, QueryIter, QueryResponse, DEFAULT_PAGE_SIZE};
 
 pub mod scan;
-pub use scan::{Scan, ScanResponse};
+pub use scan::{Scan, ScanIter, ScanResponse};
 
 pub mod update;
-pub use update::{Update, UpdateResponse};
+pub use update::{Update, UpdateResponse, ReturnValues};
 
 pub mod batch;
 pub use batch::{BatchGetRequest, BatchGetResponse, BatchWriteRequest, BatchWriteResponse, BatchWriteItem};
@@ -28,7 +58,37 @@ pub mod transaction;
 pub use transaction::{TransactGetRequest, TransactGetResponse, TransactWriteRequest, TransactWriteResponse, TransactWriteOp};
 
 pub mod partiql;
-pub use partiql::{ExecuteStatementRequest, ExecuteStatementResponse};
+pub use partiql::{AccessPath, ExecuteStatementRequest, ExecuteStatementResponse, QueryPlan};
+
+pub mod snapshot;
+pub use snapshot::Snapshot;
+
+pub mod json;
+pub use json::{item_to_json, ItemJsonExt};
+
+pub mod table;
+pub use table::{Table, TableStats};
+
+pub mod versioned;
+pub use versioned::{item_version, VERSION_ATTRIBUTE};
+
+pub mod lock_client;
+pub use lock_client::{Lease, LockClient};
+
+pub mod mapper;
+pub use mapper::{from_item, to_item, KeystoneItem};
+pub use kstone_derive::KeystoneItem;
+
+pub mod store;
+pub use kstone_core::store::{KeystoneStore, SkCondition, StatementResult, StorePage, StoreQuery, StoreScan, StoreUpdate, StoreWrite};
+
+// Lets `#[derive(KeystoneItem)]` refer to `::kstone_api` inside this crate too
+extern crate self as kstone_api;
+
+#[cfg(feature = "async")]
+pub mod async_database;
+#[cfg(feature = "async")]
+pub use async_database::{AsyncDatabase, ItemStream};
 
 /// Storage engine type
 enum DatabaseEngine {
@@ -56,6 +116,76 @@ pub struct DatabaseStats {
 
     /// Compaction statistics
     pub compaction: CompactionStats,
+
+    /// Background TTL reaper statistics (Phase 3.3+)
+    pub ttl: TtlStats,
+
+    /// Block cache hit/miss statistics (Phase 1.4+)
+    pub block_cache: BlockCacheStats,
+
+    /// Background flush statistics, including pending flushes (Phase 8+)
+    pub flush: FlushStats,
+
+    /// Size of the largest item written since the database was opened,
+    /// measured like `DatabaseConfig::max_item_size_bytes` (None if not tracked)
+    pub largest_item_bytes: Option<u64>,
+
+    /// Tiered storage statistics (Phase 8+)
+    pub tiering: TieringStats,
+
+    /// Stream retention and subscription lag statistics (Phase 8+)
+    pub stream: StreamStats,
+
+    /// Operations through this handle and the hottest partitions (Phase 8+)
+    pub operations: OperationStats,
+}
+
+/// Database statistics broken down per stripe and per SST (Phase 8+)
+#[derive(Debug, Clone)]
+pub struct DetailedStats {
+    /// Database-wide statistics, as returned by `Database::stats`
+    pub stats: DatabaseStats,
+
+    /// Statistics of every stripe, indexed by stripe id (empty for in-memory databases)
+    pub stripes: Vec<StripeStats>,
+}
+
+impl DetailedStats {
+    /// Stripes holding any records in memtables or SSTs
+    pub fn non_empty_stripes(&self) -> impl Iterator<Item = &StripeStats> {
+        self.stripes
+            .iter()
+            .filter(|stripe| stripe.memtable_records > 0 || !stripe.ssts.is_empty())
+    }
+
+    /// Upper-bound estimate of keys across all stripes; see `StripeStats::estimated_keys`
+    pub fn estimated_keys(&self) -> u64 {
+        self.stripes.iter().map(|stripe| stripe.estimated_keys).sum()
+    }
+
+    /// Bloom filter lookups across all SSTs
+    pub fn bloom(&self) -> BloomStats {
+        let mut bloom = BloomStats::default();
+        for stripe in &self.stripes {
+            bloom.merge(&stripe.bloom());
+        }
+        bloom
+    }
+
+    /// How close the fullest memtable is to being flushed under `config` (0.0 to 1.0)
+    pub fn memtable_fill(&self, config: &DatabaseConfig) -> f64 {
+        self.stripes
+            .iter()
+            .map(|stripe| {
+                let records = stripe.memtable_records as f64 / config.max_memtable_records.max(1) as f64;
+                let bytes = config
+                    .max_memtable_size_bytes
+                    .map_or(0.0, |max| stripe.memtable_size_bytes as f64 / max.max(1) as f64);
+                records.max(bytes)
+            })
+            .fold(0.0, f64::max)
+            .min(1.0)
+    }
 }
 
 /// Database health status
@@ -80,12 +210,52 @@ pub struct DatabaseHealth {
     pub errors: Vec<String>,
 }
 
+/// Description of a database for introspection (Phase 8+)
+#[derive(Debug, Clone)]
+pub struct TableDescription {
+    /// Indexes, TTL attribute, stream configuration and named tables
+    pub schema: TableSchema,
+    /// Creation time in milliseconds since the epoch (None if the database
+    /// predates creation times being recorded)
+    pub created_at: Option<i64>,
+    /// Approximate item count; overwritten and deleted keys may be counted
+    /// more than once
+    pub approximate_item_count: u64,
+    /// Records and SST files per stripe (empty for in-memory databases)
+    pub stripes: Vec<StripeUsage>,
+    /// Engine configuration (None for in-memory databases)
+    pub config: Option<DatabaseConfig>,
+    /// Whether the database was opened read-only
+    pub read_only: bool,
+}
+
+impl TableDescription {
+    /// Total size of all SST files in bytes
+    pub fn sst_bytes(&self) -> u64 {
+        self.stripes.iter().map(|stripe| stripe.sst_bytes).sum()
+    }
+
+    /// Total number of SST files
+    pub fn sst_count(&self) -> usize {
+        self.stripes.iter().map(|stripe| stripe.sst_count).sum()
+    }
+}
+
 /// KeystoneDB Database handle
 pub struct Database {
     engine: DatabaseEngine,
+    /// Parsed PartiQL statements keyed by statement text
+    statements: partiql::StatementCache,
 }
 
 impl Database {
+    fn from_engine(engine: DatabaseEngine) -> Self {
+        Self {
+            engine,
+            statements: partiql::StatementCache::default(),
+        }
+    }
+
     /// Get disk engine or return error (for features not yet supported in memory mode)
     fn disk_engine(&self) -> Result<&LsmEngine> {
         match &self.engine {
@@ -96,16 +266,36 @@ impl Database {
         }
     }
 
+    /// Approximate number of stored records, without reading them
+    ///
+    /// Counts are taken from memtables and SST headers, so overwritten and
+    /// deleted keys may be counted more than once.
+    fn estimated_item_count(&self) -> u64 {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.estimated_record_count(),
+            DatabaseEngine::Memory(e) => e.len() as u64,
+        }
+    }
+
+    /// Schema used to route queries to indexes (`None` for in-memory databases,
+    /// which don't maintain secondary indexes)
+    fn index_schema(&self) -> Option<TableSchema> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => Some(e.schema()),
+            DatabaseEngine::Memory(_) => None,
+        }
+    }
+
     /// Create a new database at the specified path
     pub fn create(path: impl AsRef<Path>) -> Result<Self> {
         let engine = LsmEngine::create(path)?;
-        Ok(Self { engine: DatabaseEngine::Disk(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
     }
 
     /// Create a new database with a table schema (Phase 3.1+)
     pub fn create_with_schema(path: impl AsRef<Path>, schema: TableSchema) -> Result<Self> {
         let engine = LsmEngine::create_with_schema(path, schema)?;
-        Ok(Self { engine: DatabaseEngine::Disk(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
     }
 
     /// Create a new database with custom configuration (Phase 8+)
@@ -114,7 +304,7 @@ impl Database {
         config: DatabaseConfig,
     ) -> Result<Self> {
         let engine = LsmEngine::create_with_config(path, config, TableSchema::new())?;
-        Ok(Self { engine: DatabaseEngine::Disk(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
     }
 
     /// Create a new database with custom configuration and schema (Phase 8+)
@@ -124,13 +314,70 @@ impl Database {
         schema: TableSchema,
     ) -> Result<Self> {
         let engine = LsmEngine::create_with_config(path, config, schema)?;
-        Ok(Self { engine: DatabaseEngine::Disk(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
     }
 
     /// Open an existing database
+    ///
+    /// Only one read-write handle may have a directory open at a time; while
+    /// another one does, this fails with `KeystoneError::DatabaseLocked`, which
+    /// names the owning process when known.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let engine = LsmEngine::open(path)?;
-        Ok(Self { engine: DatabaseEngine::Disk(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
+    }
+
+    /// Open an existing database with custom configuration (Phase 8+)
+    ///
+    /// Configuration isn't stored with the database: pass the one given to
+    /// `create_with_config` again, or `open` uses the defaults.
+    pub fn open_with_config(path: impl AsRef<Path>, config: DatabaseConfig) -> Result<Self> {
+        let engine = LsmEngine::open_with_config(path, config)?;
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
+    }
+
+    /// Open an existing database, breaking a stale directory lock (Phase 8+)
+    ///
+    /// Locks are released automatically when their process exits, so this is
+    /// only needed when the holder is hung or the filesystem keeps locks alive
+    /// (some network mounts). Never use it while the holder may still write.
+    pub fn open_force(path: impl AsRef<Path>) -> Result<Self> {
+        let engine = LsmEngine::open_force(path)?;
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
+    }
+
+    /// Open an existing database for reading only
+    ///
+    /// Nothing on disk is created or modified, so reporting jobs can open a
+    /// directory while a writer is using it, and several read-only handles can
+    /// coexist. Reads see the data as of open time; mutations, flushes and
+    /// compaction fail with `KeystoneError::ReadOnly`.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        let engine = LsmEngine::open_read_only(path)?;
+        Ok(Self::from_engine(DatabaseEngine::Disk(engine)))
+    }
+
+    /// Whether the database was opened with `open_read_only`
+    pub fn is_read_only(&self) -> bool {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.is_read_only(),
+            DatabaseEngine::Memory(_) => false,
+        }
+    }
+
+    /// Restore a backup into a new directory and open it (Phase 8+)
+    ///
+    /// The backup's checksums are verified before anything is copied; `dest`
+    /// must not exist or be an empty directory. The backup itself is left
+    /// untouched and can be restored again.
+    pub fn restore(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> Result<Self> {
+        kstone_core::backup::restore(src, dest.as_ref())?;
+        Self::open(dest)
+    }
+
+    /// Check a backup's checksums without restoring it (Phase 8+)
+    pub fn verify_backup(path: impl AsRef<Path>) -> Result<()> {
+        kstone_core::backup::verify_backup(path)
     }
 
     /// Create a new in-memory database (Phase 5+)
@@ -139,13 +386,31 @@ impl Database {
     /// Useful for testing and temporary databases.
     pub fn create_in_memory() -> Result<Self> {
         let engine = MemoryLsmEngine::create()?;
-        Ok(Self { engine: DatabaseEngine::Memory(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Memory(engine)))
     }
 
     /// Create a new in-memory database with a table schema (Phase 5+)
     pub fn create_in_memory_with_schema(schema: TableSchema) -> Result<Self> {
         let engine = MemoryLsmEngine::create_with_schema(schema)?;
-        Ok(Self { engine: DatabaseEngine::Memory(engine) })
+        Ok(Self::from_engine(DatabaseEngine::Memory(engine)))
+    }
+
+    /// Restore an in-memory database saved with `save_to` (Phase 8+)
+    pub fn load_in_memory(storage: &dyn Storage) -> Result<Self> {
+        let engine = MemoryLsmEngine::load_from(storage)?;
+        Ok(Self::from_engine(DatabaseEngine::Memory(engine)))
+    }
+
+    /// Save an in-memory database to `storage` (Phase 8+)
+    ///
+    /// Disk databases are copied with `backup` instead.
+    pub fn save_to(&self, storage: &dyn Storage) -> Result<()> {
+        match &self.engine {
+            DatabaseEngine::Disk(_) => Err(KeystoneError::InvalidArgument(
+                "Only in-memory databases are saved to a Storage; use backup for disk databases".to_string(),
+            )),
+            DatabaseEngine::Memory(e) => e.save_to(storage),
+        }
     }
 
     /// Put an item with a simple partition key
@@ -204,6 +469,38 @@ impl Database {
         }
     }
 
+    /// Put a typed value with a simple partition key
+    ///
+    /// The value is converted with `KeystoneItem::to_item` (see `#[derive(KeystoneItem)]`).
+    pub fn put_t<T: KeystoneItem>(&self, pk: &[u8], value: &T) -> Result<()> {
+        self.put(pk, value.to_item()?)
+    }
+
+    /// Put a typed value with partition key and sort key
+    pub fn put_t_with_sk<T: KeystoneItem>(&self, pk: &[u8], sk: &[u8], value: &T) -> Result<()> {
+        self.put_with_sk(pk, sk, value.to_item()?)
+    }
+
+    /// Get an item by partition key and convert it to a typed value
+    pub fn get_t<T: KeystoneItem>(&self, pk: &[u8]) -> Result<Option<T>> {
+        self.get(pk)?.map(T::from_item).transpose()
+    }
+
+    /// Get an item by partition key and sort key and convert it to a typed value
+    pub fn get_t_with_sk<T: KeystoneItem>(&self, pk: &[u8], sk: &[u8]) -> Result<Option<T>> {
+        self.get_with_sk(pk, sk)?.map(T::from_item).transpose()
+    }
+
+    /// Put a JSON object as an item with a simple partition key
+    pub fn put_json(&self, pk: &[u8], json: serde_json::Value) -> Result<()> {
+        self.put(pk, Item::from_json(json)?)
+    }
+
+    /// Get an item by partition key as a JSON object
+    pub fn get_json(&self, pk: &[u8]) -> Result<Option<serde_json::Value>> {
+        Ok(self.get(pk)?.as_ref().map(item_to_json))
+    }
+
     /// Get an item by partition key
     pub fn get(&self, pk: &[u8]) -> Result<Option<Item>> {
         let key = Key::new(Bytes::copy_from_slice(pk));
@@ -226,6 +523,25 @@ impl Database {
         }
     }
 
+    /// Get an item by partition key, returning only the given attribute paths
+    ///
+    /// Paths use dot notation for nested map attributes (e.g. `address.city`).
+    pub fn get_with_projection(&self, pk: &[u8], attributes: &[&str]) -> Result<Option<Item>> {
+        let item = self.get(pk)?;
+        Ok(item.map(|item| project(&item, attributes)))
+    }
+
+    /// Get an item by partition key and sort key, returning only the given attribute paths
+    pub fn get_with_sk_projection(
+        &self,
+        pk: &[u8],
+        sk: &[u8],
+        attributes: &[&str],
+    ) -> Result<Option<Item>> {
+        let item = self.get_with_sk(pk, sk)?;
+        Ok(item.map(|item| project(&item, attributes)))
+    }
+
     /// Delete an item by partition key
     pub fn delete(&self, pk: &[u8]) -> Result<()> {
         let key = Key::new(Bytes::copy_from_slice(pk));
@@ -279,6 +595,69 @@ impl Database {
         }
     }
 
+    /// Put an item and return the item it replaced (Phase 2.5+)
+    ///
+    /// Only `ReturnValues::None` and `ReturnValues::AllOld` are valid for puts.
+    /// The old item is read under the same lock as the write.
+    pub fn put_returning(
+        &self,
+        pk: &[u8],
+        item: Item,
+        return_values: ReturnValues,
+    ) -> Result<Option<Item>> {
+        let key = Key::new(Bytes::copy_from_slice(pk));
+        self.put_key_returning(key, item, return_values)
+    }
+
+    /// Put an item with partition key and sort key, returning the item it replaced (Phase 2.5+)
+    pub fn put_with_sk_returning(
+        &self,
+        pk: &[u8],
+        sk: &[u8],
+        item: Item,
+        return_values: ReturnValues,
+    ) -> Result<Option<Item>> {
+        let key = Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk));
+        self.put_key_returning(key, item, return_values)
+    }
+
+    /// Delete an item and return the item that was removed (Phase 2.5+)
+    ///
+    /// Only `ReturnValues::None` and `ReturnValues::AllOld` are valid for deletes.
+    pub fn delete_returning(&self, pk: &[u8], return_values: ReturnValues) -> Result<Option<Item>> {
+        let key = Key::new(Bytes::copy_from_slice(pk));
+        self.delete_key_returning(key, return_values)
+    }
+
+    /// Delete an item with partition key and sort key, returning the removed item (Phase 2.5+)
+    pub fn delete_with_sk_returning(
+        &self,
+        pk: &[u8],
+        sk: &[u8],
+        return_values: ReturnValues,
+    ) -> Result<Option<Item>> {
+        let key = Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk));
+        self.delete_key_returning(key, return_values)
+    }
+
+    fn put_key_returning(&self, key: Key, item: Item, return_values: ReturnValues) -> Result<Option<Item>> {
+        check_old_only(return_values, "put")?;
+        let old_item = match &self.engine {
+            DatabaseEngine::Disk(e) => e.put_returning_old(key, item, None)?,
+            DatabaseEngine::Memory(e) => e.put_returning_old(key, item, None)?,
+        };
+        Ok(return_values.select(old_item.as_ref(), None))
+    }
+
+    fn delete_key_returning(&self, key: Key, return_values: ReturnValues) -> Result<Option<Item>> {
+        check_old_only(return_values, "delete")?;
+        let old_item = match &self.engine {
+            DatabaseEngine::Disk(e) => e.delete_returning_old(key, None)?,
+            DatabaseEngine::Memory(e) => e.delete_returning_old(key, None)?,
+        };
+        Ok(return_values.select(old_item.as_ref(), None))
+    }
+
     /// Get the database path (only for disk-based databases)
     pub fn path(&self) -> Option<&Path> {
         match &self.engine {
@@ -308,45 +687,313 @@ impl Database {
 
     /// Query items within a partition (Phase 2.1+)
     pub fn query(&self, query: Query) -> Result<QueryResponse> {
-        let params = query.into_params();
-        let result = match &self.engine {
-            DatabaseEngine::Disk(e) => e.query(params)?,
-            DatabaseEngine::Memory(e) => e.query(params)?,
-        };
-        Ok(QueryResponse::from_result(result))
+        let params = query.into_params()?;
+        Ok(QueryResponse::from_result(self.query_page(params)?))
+    }
+
+    /// Iterate over every item matching a query, fetching pages as needed
+    ///
+    /// Pages hold `Query::page_size` items; `Query::limit` caps the total.
+    pub fn query_iter(&self, query: Query) -> Result<QueryIter<'_>> {
+        QueryIter::new(self, query)
+    }
+
+    pub(crate) fn query_page(&self, params: QueryParams) -> Result<QueryResult> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.query(params),
+            DatabaseEngine::Memory(e) => e.query(params),
+        }
+    }
+
+    /// Open a consistent point-in-time read view (Phase 2.1+)
+    ///
+    /// Reads through the snapshot ignore every write committed after it was
+    /// taken. Only supported for disk-based databases.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        Ok(Snapshot::new(self.disk_engine()?.snapshot()))
+    }
+
+    /// Write a consistent, self-contained backup to `dest` (Phase 8+)
+    ///
+    /// Writes may continue while the backup runs. The resulting directory
+    /// can be opened directly or passed to `Database::restore`. Only
+    /// supported for disk-based databases.
+    pub fn backup(&self, dest: impl AsRef<Path>) -> Result<BackupInfo> {
+        self.disk_engine()?.backup(dest)
+    }
+
+    /// Write a backup that hard-links the SSTs it shares with `base` (Phase 8+)
+    ///
+    /// `base` must be an earlier backup of this database. Only SSTs written
+    /// since are copied, and the new backup doesn't depend on `base` staying
+    /// around. Only supported for disk-based databases.
+    pub fn backup_incremental(&self, dest: impl AsRef<Path>, base: impl AsRef<Path>) -> Result<BackupInfo> {
+        self.disk_engine()?.backup_incremental(dest, base)
+    }
+
+    /// Check the database for damage and inconsistencies (Phase 8+)
+    ///
+    /// Checks WAL and SST checksums, the manifest, orphaned files and
+    /// dangling index entries; writes wait while it runs. With `repair`,
+    /// damaged SSTs and orphaned files are moved to `<db>/quarantine/` and
+    /// dangling index entries are deleted. Damaged WAL entries are only
+    /// reported; `kstone verify --repair` removes them while the database is
+    /// closed. Only supported for disk-based databases.
+    pub fn verify(&self, repair: bool) -> Result<VerifyReport> {
+        self.disk_engine()?.verify(repair)
+    }
+
+    /// Register a compaction filter, or remove it with `None` (Phase 8+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn set_compaction_filter(&self, filter: Option<std::sync::Arc<dyn CompactionFilter>>) -> Result<()> {
+        self.disk_engine()?.set_compaction_filter(filter);
+        Ok(())
+    }
+
+    /// Operations that exceeded the slow operation threshold, oldest first (Phase 8+)
+    ///
+    /// See `DatabaseConfig::slow_operation_threshold`. Always empty for
+    /// in-memory databases.
+    pub fn slow_queries(&self) -> Vec<SlowOperation> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.slow_operations(),
+            DatabaseEngine::Memory(_) => Vec::new(),
+        }
+    }
+
+    /// Drop all logged slow operations (Phase 8+)
+    pub fn clear_slow_queries(&self) {
+        if let DatabaseEngine::Disk(e) = &self.engine {
+            e.clear_slow_operations();
+        }
+    }
+
+    /// Change the slow operation threshold, or turn the log off with `None` (Phase 8+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn set_slow_operation_threshold(&self, threshold: Option<std::time::Duration>) -> Result<()> {
+        self.disk_engine()?.set_slow_operation_threshold(threshold);
+        Ok(())
+    }
+
+    /// Turn `tracing` spans for engine operations on or off (Phase 8+)
+    ///
+    /// See `DatabaseConfig::trace_operations`. Only supported for disk-based databases.
+    pub fn set_trace_operations(&self, enabled: bool) -> Result<()> {
+        self.disk_engine()?.set_trace_operations(enabled);
+        Ok(())
+    }
+
+    /// Attach the object store cold SSTs are offloaded to, or detach it with `None` (Phase 8+)
+    ///
+    /// SSTs older than `DatabaseConfig::cold_sst_age` are moved there in the
+    /// background. A database with cold SSTs needs the store attached again
+    /// after every open. Only supported for disk-based databases.
+    pub fn set_cold_store(&self, store: Option<std::sync::Arc<dyn ColdStore>>) -> Result<()> {
+        self.disk_engine()?.set_cold_store(store);
+        Ok(())
+    }
+
+    /// Offload SSTs older than `DatabaseConfig::cold_sst_age` now, returning how many moved (Phase 8+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn offload_cold_ssts(&self) -> Result<u64> {
+        self.disk_engine()?.offload_cold_ssts()
+    }
+
+    /// Find the `k` items nearest to `query` in a vector index (Phase 3.5+)
+    ///
+    /// `index` is the name of the `VecF32` attribute declared with
+    /// `TableSchema::add_vector_index`. Only supported for disk-based databases.
+    pub fn vector_search(&self, index: &str, query: &[f32], k: usize) -> Result<Vec<VectorMatch>> {
+        self.disk_engine()?.vector_search(index, query, k)
+    }
+
+    /// Items within `radius_m` meters of `center` in a geo index, nearest first (Phase 3.6+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn geo_query(&self, index: &str, center: GeoPoint, radius_m: f64) -> Result<Vec<GeoMatch>> {
+        self.disk_engine()?.geo_query(index, center, radius_m)
+    }
+
+    /// Items inside a bounding box in a geo index (Phase 3.6+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn geo_query_box(&self, index: &str, bbox: GeoBox) -> Result<Vec<Item>> {
+        self.disk_engine()?.geo_query_box(index, bbox)
+    }
+
+    /// Add a GSI to an existing table (Phase 3.2+)
+    ///
+    /// Items already in the table are indexed by a background backfill that
+    /// resumes after a restart; queries on the index fail until it finishes.
+    /// Only supported for disk-based databases.
+    pub fn create_index(&self, index: GlobalSecondaryIndex) -> Result<()> {
+        self.disk_engine()?.create_index(index)
+    }
+
+    /// Remove an LSI or GSI and delete its entries (Phase 3.2+)
+    ///
+    /// Only supported for disk-based databases.
+    pub fn drop_index(&self, name: &str) -> Result<()> {
+        self.disk_engine()?.drop_index(name)
+    }
+
+    /// Progress of index backfills still running (Phase 3.2+)
+    pub fn index_backfills(&self) -> Result<Vec<IndexBackfill>> {
+        Ok(self.disk_engine()?.index_backfills())
+    }
+
+    /// Block until every index backfill has completed (Phase 3.2+)
+    pub fn wait_for_index_backfills(&self) -> Result<()> {
+        self.disk_engine()?.wait_for_index_backfills();
+        Ok(())
+    }
+
+    /// Create a named table sharing this database's directory (Phase 3.7+)
+    ///
+    /// Names use 3-255 characters from `a-z A-Z 0-9 _ - .`. The schema may set
+    /// TTL and attribute types; indexes and streams aren't supported yet.
+    pub fn create_table(&self, name: &str, schema: TableSchema) -> Result<Table<'_>> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.create_table(name, schema)?,
+            DatabaseEngine::Memory(e) => e.create_table(name, schema)?,
+        }
+        Ok(Table::new(self, name))
+    }
+
+    /// Get a handle to an existing named table (Phase 3.7+)
+    pub fn table(&self, name: &str) -> Result<Table<'_>> {
+        if self.table_schema(name).is_none() {
+            return Err(KeystoneError::NotFound(format!("Table '{}'", name)));
+        }
+        Ok(Table::new(self, name))
+    }
+
+    /// Remove a named table and delete its items (Phase 3.7+)
+    pub fn drop_table(&self, name: &str) -> Result<()> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.drop_table(name),
+            DatabaseEngine::Memory(e) => e.drop_table(name),
+        }
+    }
+
+    /// Names of the named tables, sorted (Phase 3.7+)
+    pub fn list_tables(&self) -> Vec<String> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.list_tables(),
+            DatabaseEngine::Memory(e) => e.list_tables(),
+        }
+    }
+
+    /// Schema of a named table (Phase 3.7+)
+    pub fn table_schema(&self, name: &str) -> Option<TableSchema> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.table_schema(name),
+            DatabaseEngine::Memory(e) => e.table_schema(name),
+        }
+    }
+
+    pub(crate) fn put_key(&self, key: Key, item: Item) -> Result<()> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.put(key, item),
+            DatabaseEngine::Memory(e) => e.put(key, item),
+        }
+    }
+
+    pub(crate) fn get_key(&self, key: &Key) -> Result<Option<Item>> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.get(key),
+            DatabaseEngine::Memory(e) => e.get(key),
+        }
+    }
+
+    pub(crate) fn delete_key(&self, key: Key) -> Result<()> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.delete(key),
+            DatabaseEngine::Memory(e) => e.delete(key),
+        }
     }
 
     /// Scan all items in the table (Phase 2.2+)
     pub fn scan(&self, scan: Scan) -> Result<ScanResponse> {
-        let params = scan.into_params();
-        let result = match &self.engine {
-            DatabaseEngine::Disk(e) => e.scan(params)?,
-            DatabaseEngine::Memory(e) => e.scan(params)?,
-        };
-        Ok(ScanResponse::from_result(result))
+        let params = scan.into_params()?;
+        Ok(ScanResponse::from_result(self.scan_page(params)?))
+    }
+
+    /// Iterate over every item in a scan, fetching pages as needed
+    ///
+    /// Pages hold `Scan::page_size` items; `Scan::limit` caps the total.
+    pub fn scan_iter(&self, scan: Scan) -> Result<ScanIter<'_>> {
+        ScanIter::new(self, scan)
+    }
+
+    pub(crate) fn scan_page(&self, params: ScanParams) -> Result<ScanResult> {
+        match &self.engine {
+            DatabaseEngine::Disk(e) => e.scan(params),
+            DatabaseEngine::Memory(e) => e.scan(params),
+        }
     }
 
     /// Update an item using update expression (Phase 2.4+)
+    ///
+    /// The read, condition check and write happen atomically; use
+    /// `Update::return_values` to get the old or new attributes back.
     pub fn update(&self, update: Update) -> Result<UpdateResponse> {
         let key = update.key().clone();
+        let return_values = update.requested_return_values();
         let (actions, condition_expr, context) = update.into_actions()?;
 
-        let updated_item = if let Some(condition_str) = condition_expr {
-            // Parse condition and call conditional update
-            let condition = kstone_core::expression::ExpressionParser::parse(&condition_str)?;
-            match &self.engine {
-                DatabaseEngine::Disk(e) => e.update_conditional(&key, &actions, &condition, &context)?,
-                DatabaseEngine::Memory(e) => e.update_conditional(&key, &actions, &condition, &context)?,
-            }
-        } else {
-            // No condition, regular update
-            match &self.engine {
-                DatabaseEngine::Disk(e) => e.update(&key, &actions, &context)?,
-                DatabaseEngine::Memory(e) => e.update(&key, &actions, &context)?,
-            }
+        let condition = condition_expr
+            .map(|condition_str| kstone_core::expression::ExpressionParser::parse(&condition_str))
+            .transpose()?;
+
+        let (old_item, updated_item) = match &self.engine {
+            DatabaseEngine::Disk(e) => e.update_returning(&key, &actions, condition.as_ref(), &context)?,
+            DatabaseEngine::Memory(e) => e.update_returning(&key, &actions, condition.as_ref(), &context)?,
+        };
+
+        let attributes = return_values.select(old_item.as_ref(), Some(&updated_item));
+        Ok(UpdateResponse::new(updated_item, attributes))
+    }
+
+    /// Atomically add `delta` to a numeric attribute and return its new value
+    ///
+    /// A missing item or attribute starts at zero. This is an `ADD` update
+    /// without expression parsing, for counters such as visit counts.
+    pub fn increment(&self, pk: &[u8], sk: Option<&[u8]>, attr: &str, delta: i64) -> Result<i64> {
+        use kstone_core::expression::{ExpressionContext, UpdateAction, UpdateValue};
+
+        let key = match sk {
+            Some(sk) => Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk)),
+            None => Key::new(Bytes::copy_from_slice(pk)),
+        };
+        // Address the attribute through a name placeholder so it is never
+        // parsed as a nested path or reserved word
+        let context = ExpressionContext::new().with_name("#counter", attr);
+        let actions = [UpdateAction::Add(
+            "#counter".to_string(),
+            UpdateValue::Value(Value::number(delta)),
+        )];
+
+        let (_, updated_item) = match &self.engine {
+            DatabaseEngine::Disk(e) => e.update_returning(&key, &actions, None, &context)?,
+            DatabaseEngine::Memory(e) => e.update_returning(&key, &actions, None, &context)?,
         };
 
-        Ok(UpdateResponse::new(updated_item))
+        match updated_item.get(attr) {
+            Some(Value::N(n)) => n.parse().map_err(|_| {
+                KeystoneError::InvalidArgument(format!(
+                    "Attribute '{}' is not an integer counter: {}",
+                    attr, n
+                ))
+            }),
+            _ => Err(KeystoneError::Internal(format!(
+                "Attribute '{}' missing after increment",
+                attr
+            ))),
+        }
     }
 
     /// Batch get multiple items (Phase 2.6+)
@@ -367,6 +1014,9 @@ impl Database {
     }
 
     /// Batch write multiple items (Phase 2.6+)
+    ///
+    /// The batch is atomic: it is applied under one lock and logged as a
+    /// single WAL entry, so a crash never leaves part of it behind.
     pub fn batch_write(&self, request: BatchWriteRequest) -> Result<BatchWriteResponse> {
         // Convert batch write request to operations
         let mut operations = Vec::new();
@@ -383,17 +1033,44 @@ impl Database {
         }
 
         let processed = match &self.engine {
-            DatabaseEngine::Disk(e) => e.batch_write(&operations)?,
-            DatabaseEngine::Memory(e) => e.batch_write(&operations)?,
+            DatabaseEngine::Disk(e) => e.write_batch(&operations)?,
+            DatabaseEngine::Memory(e) => e.write_batch(&operations)?,
         };
         Ok(BatchWriteResponse::new(processed))
     }
 
-    /// Transactional get - read multiple items atomically (Phase 2.7+)
-    pub fn transact_get(&self, request: TransactGetRequest) -> Result<TransactGetResponse> {
-        let items = match &self.engine {
-            DatabaseEngine::Disk(e) => e.transact_get(request.keys())?,
-            DatabaseEngine::Memory(e) => e.transact_get(request.keys())?,
+    /// Load items straight into SST files, skipping the WAL (Phase 8+)
+    ///
+    /// Much faster than putting items one by one for an initial ingest. A
+    /// loaded item replaces any existing item at its key, and the last of
+    /// several items with the same key wins. The load isn't atomic, and
+    /// tables with indexes or streams are rejected; see `LsmEngine::bulk_load`.
+    ///
+    /// Only supported for disk-based databases.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kstone_api::{Database, ItemBuilder};
+    /// use kstone_core::Key;
+    ///
+    /// let db = Database::create("/tmp/bulk.keystone").unwrap();
+    /// let items = (0..1_000_000).map(|i| {
+    ///     let key = Key::new(format!("user#{}", i).into_bytes());
+    ///     (key, ItemBuilder::new().number("n", i).build())
+    /// });
+    /// let stats = db.bulk_load(items).unwrap();
+    /// println!("{} items in {} SSTs", stats.items, stats.sst_files);
+    /// ```
+    pub fn bulk_load(&self, items: impl IntoIterator<Item = (Key, Item)>) -> Result<BulkLoadStats> {
+        self.disk_engine()?.bulk_load(items)
+    }
+
+    /// Transactional get - read multiple items atomically (Phase 2.7+)
+    pub fn transact_get(&self, request: TransactGetRequest) -> Result<TransactGetResponse> {
+        let items = match &self.engine {
+            DatabaseEngine::Disk(e) => e.transact_get(request.keys())?,
+            DatabaseEngine::Memory(e) => e.transact_get(request.keys())?,
         };
         Ok(TransactGetResponse::new(items))
     }
@@ -470,41 +1147,135 @@ impl Database {
 
     /// Read stream records (Phase 3.4+)
     ///
-    /// Returns all stream records in the buffer.
+    /// Returns all retained stream records.
     /// Optionally provide after_sequence_number to only get records after that sequence number.
     pub fn read_stream(&self, after_sequence_number: Option<u64>) -> Result<Vec<StreamRecord>> {
         self.disk_engine()?.read_stream(after_sequence_number)
     }
 
+    /// Get a shard iterator for reading the stream (Phase 3.4+)
+    ///
+    /// Stream records are persisted, so a consumer can store the sequence number
+    /// it has processed and resume with `ShardIteratorType::AfterSequenceNumber`
+    /// after a restart.
+    pub fn get_shard_iterator(&self, iterator_type: ShardIteratorType) -> Result<ShardIterator> {
+        self.disk_engine()?.get_shard_iterator(iterator_type)
+    }
+
+    /// Read up to `limit` stream records from a shard iterator (Phase 3.4+)
+    pub fn get_records(&self, iterator: ShardIterator, limit: usize) -> Result<GetRecordsResult> {
+        self.disk_engine()?.get_records(iterator, limit)
+    }
+
+    /// Subscribe to changes as they are committed (Phase 3.4+)
+    ///
+    /// Returns a subscription that delivers Insert/Modify/Remove records
+    /// committed after this call. Dropping the subscription unsubscribes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use kstone_api::{Database, TableSchema, StreamConfig};
+    ///
+    /// let schema = TableSchema::new().with_stream(StreamConfig::enabled());
+    /// let db = Database::create_with_schema("/tmp/streams.keystone", schema).unwrap();
+    ///
+    /// let subscription = db.subscribe_stream().unwrap();
+    /// std::thread::spawn(move || {
+    ///     for record in subscription.iter() {
+    ///         println!("{:?} {:?}", record.event_type, record.key);
+    ///     }
+    /// });
+    /// ```
+    pub fn subscribe_stream(&self) -> Result<StreamSubscription> {
+        self.subscribe_stream_with_config(SubscriptionConfig::default())
+    }
+
+    /// Subscribe to changes with a custom start position and channel capacity (Phase 3.4+)
+    ///
+    /// The channel capacity bounds how far the subscription reads ahead of the
+    /// consumer; writes are never blocked by a slow subscriber.
+    pub fn subscribe_stream_with_config(&self, config: SubscriptionConfig) -> Result<StreamSubscription> {
+        self.disk_engine()?.subscribe_stream(config)
+    }
+
     /// Get database statistics
     ///
     /// Returns comprehensive statistics about the database including
     /// size, file counts, and operation metrics.
     pub fn stats(&self) -> Result<DatabaseStats> {
+        Ok(self.stats_detailed()?.stats)
+    }
+
+    /// Get database statistics per stripe and per SST (Phase 8+)
+    ///
+    /// Adds memtable sizes, key count estimates, bloom filter false-positive
+    /// rates and SST sizes and ages to `stats`, for rendering as tables.
+    pub fn stats_detailed(&self) -> Result<DetailedStats> {
         match &self.engine {
             DatabaseEngine::Disk(e) => {
-                Ok(DatabaseStats {
+                let stripes = e.stripe_stats();
+                let stats = DatabaseStats {
                     total_keys: None, // Would require expensive scan
-                    total_sst_files: 0, // TODO: implement
-                    wal_size_bytes: None,
-                    memtable_size_bytes: None,
-                    total_disk_size_bytes: None,
+                    total_sst_files: stripes.iter().map(|stripe| stripe.sst_count() as u64).sum(),
+                    wal_size_bytes: Some(e.wal_size_bytes()?),
+                    memtable_size_bytes: Some(stripes.iter().map(|stripe| stripe.memtable_size_bytes).sum()),
+                    total_disk_size_bytes: Some(e.disk_size_bytes()?),
                     compaction: e.compaction_stats(),
-                })
+                    ttl: e.ttl_stats(),
+                    block_cache: e.block_cache_stats(),
+                    flush: e.flush_stats(),
+                    largest_item_bytes: Some(e.largest_item_bytes()),
+                    tiering: e.tiering_stats(),
+                    stream: e.stream_stats(),
+                    operations: e.operation_stats(),
+                };
+                Ok(DetailedStats { stats, stripes })
             }
             DatabaseEngine::Memory(_e) => {
-                Ok(DatabaseStats {
+                let stats = DatabaseStats {
                     total_keys: None,
                     total_sst_files: 0,
                     wal_size_bytes: Some(0), // In-memory has no WAL
                     memtable_size_bytes: None,
                     total_disk_size_bytes: Some(0), // In-memory has no disk storage
                     compaction: Default::default(),
-                })
+                    ttl: Default::default(), // In-memory items are only expired lazily
+                    block_cache: Default::default(),
+                    flush: Default::default(), // In-memory writes never flush
+                    largest_item_bytes: None,
+                    tiering: Default::default(), // In-memory databases have no SSTs
+                    stream: Default::default(), // In-memory databases keep no stream log
+                    operations: Default::default(),
+                };
+                Ok(DetailedStats { stats, stripes: Vec::new() })
             }
         }
     }
 
+    /// Describe the database: schema, creation time, size and configuration (Phase 8+)
+    pub fn describe(&self) -> Result<TableDescription> {
+        let description = match &self.engine {
+            DatabaseEngine::Disk(e) => TableDescription {
+                schema: e.schema(),
+                created_at: e.created_at(),
+                approximate_item_count: e.estimated_record_count(),
+                stripes: e.stripe_usage(),
+                config: Some(e.config()),
+                read_only: e.is_read_only(),
+            },
+            DatabaseEngine::Memory(e) => TableDescription {
+                schema: e.schema(),
+                created_at: Some(e.created_at()),
+                approximate_item_count: e.len() as u64,
+                stripes: Vec::new(),
+                config: None,
+                read_only: false,
+            },
+        };
+        Ok(description)
+    }
+
     /// Check database health
     ///
     /// Returns health status including whether database is operational
@@ -518,6 +1289,23 @@ impl Database {
     }
 }
 
+/// Puts and deletes can only return the old item
+fn check_old_only(return_values: ReturnValues, operation: &str) -> Result<()> {
+    match return_values {
+        ReturnValues::None | ReturnValues::AllOld => Ok(()),
+        other => Err(KeystoneError::InvalidArgument(format!(
+            "ReturnValues {:?} is not supported for {}",
+            other, operation
+        ))),
+    }
+}
+
+/// Apply a projection given as attribute path strings
+fn project(item: &Item, attributes: &[&str]) -> Item {
+    let paths: Vec<String> = attributes.iter().map(|a| a.to_string()).collect();
+    kstone_core::iterator::project_item(item, &paths)
+}
+
 /// Helper to build items
 pub struct ItemBuilder {
     item: HashMap<String, Value>,
@@ -545,6 +1333,55 @@ impl ItemBuilder {
         self
     }
 
+    pub fn binary(mut self, key: impl Into<String>, value: impl Into<Bytes>) -> Self {
+        self.item.insert(key.into(), Value::binary(value));
+        self
+    }
+
+    pub fn null(mut self, key: impl Into<String>) -> Self {
+        self.item.insert(key.into(), Value::Null);
+        self
+    }
+
+    /// Timestamp in milliseconds since the Unix epoch
+    pub fn timestamp(mut self, key: impl Into<String>, millis: i64) -> Self {
+        self.item.insert(key.into(), Value::timestamp(millis));
+        self
+    }
+
+    /// Embedding vector (see `VectorIndex`)
+    pub fn vector(mut self, key: impl Into<String>, value: Vec<f32>) -> Self {
+        self.item.insert(key.into(), Value::vector(value));
+        self
+    }
+
+    pub fn list(mut self, key: impl Into<String>, values: impl IntoIterator<Item = Value>) -> Self {
+        self.item.insert(key.into(), Value::L(values.into_iter().collect()));
+        self
+    }
+
+    /// Nested map attribute built with its own `ItemBuilder`
+    ///
+    /// ```
+    /// # use kstone_api::ItemBuilder;
+    /// let item = ItemBuilder::new()
+    ///     .map("address", |address| address.string("city", "Oslo").number("zip", 150))
+    ///     .build();
+    /// ```
+    pub fn map<F>(mut self, key: impl Into<String>, build: F) -> Self
+    where
+        F: FnOnce(ItemBuilder) -> ItemBuilder,
+    {
+        self.item.insert(key.into(), Value::M(build(ItemBuilder::new()).build()));
+        self
+    }
+
+    /// Any attribute value
+    pub fn value(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.item.insert(key.into(), value);
+        self
+    }
+
     pub fn build(self) -> Item {
         self.item
     }
@@ -594,6 +1431,87 @@ mod tests {
         assert_eq!(result, Some(item));
     }
 
+    #[test]
+    fn test_database_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        db.put(b"a", ItemBuilder::new().number("v", 1).build()).unwrap();
+        db.put(b"b", ItemBuilder::new().number("v", 1).build()).unwrap();
+
+        let snapshot = db.snapshot().unwrap();
+
+        db.put(b"a", ItemBuilder::new().number("v", 2).build()).unwrap();
+        db.delete(b"b").unwrap();
+        db.put(b"c", ItemBuilder::new().number("v", 1).build()).unwrap();
+        db.flush().unwrap();
+
+        // The snapshot still sees the state before the writes
+        let a = snapshot.get(b"a").unwrap().unwrap();
+        assert_eq!(a.get("v"), Some(&Value::number(1)));
+        assert!(snapshot.get(b"b").unwrap().is_some());
+        assert!(snapshot.get(b"c").unwrap().is_none());
+        assert_eq!(snapshot.scan(Scan::new()).unwrap().items.len(), 2);
+
+        // Live reads see the new state
+        assert!(db.get(b"b").unwrap().is_none());
+        assert_eq!(db.scan(Scan::new()).unwrap().items.len(), 2);
+        assert!(db.get(b"c").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_database_snapshot_query() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..3 {
+            let sk = format!("item#{}", i);
+            db.put_with_sk(b"user#1", sk.as_bytes(), ItemBuilder::new().number("n", i).build()).unwrap();
+        }
+
+        let snapshot = db.snapshot().unwrap();
+        db.put_with_sk(b"user#1", b"item#9", ItemBuilder::new().number("n", 9).build()).unwrap();
+        db.delete_with_sk(b"user#1", b"item#0").unwrap();
+
+        let response = snapshot.query(Query::new(b"user#1")).unwrap();
+        assert_eq!(response.items.len(), 3);
+        assert_eq!(db.query(Query::new(b"user#1")).unwrap().items.len(), 3);
+
+        drop(snapshot);
+        assert!(db.snapshot().unwrap().get_with_sk(b"user#1", b"item#0").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_database_in_memory_snapshot_unsupported() {
+        let db = Database::create_in_memory().unwrap();
+        assert!(db.snapshot().is_err());
+    }
+
+    #[test]
+    fn test_database_backup_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let backup_dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        db.put(b"key1", ItemBuilder::new().string("name", "alice").build()).unwrap();
+        db.flush().unwrap();
+        db.put(b"key2", ItemBuilder::new().string("name", "bob").build()).unwrap();
+
+        let backup_path = backup_dir.path().join("backup");
+        let info = db.backup(&backup_path).unwrap();
+        assert_eq!(info.sequence_number, 2);
+
+        db.delete(b"key1").unwrap();
+
+        let restored = Database::restore(&backup_path, backup_dir.path().join("restored")).unwrap();
+        assert!(restored.get(b"key1").unwrap().is_some());
+        assert!(restored.get(b"key2").unwrap().is_some());
+        assert!(db.get(b"key1").unwrap().is_none());
+
+        let mem = Database::create_in_memory().unwrap();
+        assert!(mem.backup(backup_dir.path().join("mem")).is_err());
+    }
+
     #[test]
     fn test_database_delete() {
         let dir = TempDir::new().unwrap();
@@ -761,51 +1679,550 @@ mod tests {
             db.put(pk.as_bytes(), item).unwrap();
         }
 
-        // First page
-        let scan1 = Scan::new().limit(10);
-        let response1 = db.scan(scan1).unwrap();
-        assert_eq!(response1.items.len(), 10);
-        assert!(response1.last_key.is_some());
+        // First page
+        let scan1 = Scan::new().limit(10);
+        let response1 = db.scan(scan1).unwrap();
+        assert_eq!(response1.items.len(), 10);
+        assert!(response1.last_key.is_some());
+
+        // Second page
+        let (last_pk, last_sk) = response1.last_key.unwrap();
+        let scan2 = Scan::new()
+            .limit(10)
+            .start_after(&last_pk, last_sk.as_deref());
+        let response2 = db.scan(scan2).unwrap();
+        assert_eq!(response2.items.len(), 10);
+
+        // Third page
+        let (last_pk2, last_sk2) = response2.last_key.unwrap();
+        let scan3 = Scan::new()
+            .limit(10)
+            .start_after(&last_pk2, last_sk2.as_deref());
+        let response3 = db.scan(scan3).unwrap();
+        assert_eq!(response3.items.len(), 10);
+    }
+
+    #[test]
+    fn test_database_query_iter() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..25 {
+            let sk = format!("item#{:03}", i);
+            let item = ItemBuilder::new().number("id", i).build();
+            db.put_with_sk(b"user#999", sk.as_bytes(), item).unwrap();
+        }
+
+        // Pages of 4 are fetched transparently
+        let items: Vec<Item> = db
+            .query_iter(Query::new(b"user#999").page_size(4))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(items.len(), 25);
+        assert_eq!(items[24].get("id"), Some(&Value::number(24)));
+
+        // limit caps the total across pages
+        let count = db
+            .query_iter(Query::new(b"user#999").page_size(4).limit(10))
+            .unwrap()
+            .count();
+        assert_eq!(count, 10);
+
+        // Invalid filters are reported up front
+        assert!(db.query_iter(Query::new(b"user#999").filter("id >")).is_err());
+    }
+
+    #[test]
+    fn test_database_scan_iter() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..30 {
+            let pk = format!("key#{:03}", i);
+            let item = ItemBuilder::new().number("id", i).build();
+            db.put(pk.as_bytes(), item).unwrap();
+        }
+
+        let items = db
+            .scan_iter(Scan::new().page_size(7))
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(items.len(), 30);
+
+        let filtered = db
+            .scan_iter(
+                Scan::new()
+                    .page_size(5)
+                    .filter("id >= :min")
+                    .value(":min", Value::number(20)),
+            )
+            .unwrap()
+            .count();
+        assert_eq!(filtered, 10);
+    }
+
+    #[test]
+    fn test_database_open_read_only() {
+        let dir = TempDir::new().unwrap();
+        let writer = Database::create(dir.path()).unwrap();
+        writer.put(b"flushed", ItemBuilder::new().number("v", 1).build()).unwrap();
+        writer.flush().unwrap();
+        writer.put(b"in-wal", ItemBuilder::new().number("v", 2).build()).unwrap();
+
+        // Several readers can open the directory next to the writer
+        let reader1 = Database::open_read_only(dir.path()).unwrap();
+        let reader2 = Database::open_read_only(dir.path()).unwrap();
+        assert!(reader1.is_read_only());
+        assert!(!writer.is_read_only());
+
+        for reader in [&reader1, &reader2] {
+            assert!(reader.get(b"flushed").unwrap().is_some());
+            assert!(reader.get(b"in-wal").unwrap().is_some());
+            assert_eq!(reader.scan(Scan::new()).unwrap().count, 2);
+        }
+
+        // Mutations are refused with a clear error
+        let item = ItemBuilder::new().number("v", 3).build();
+        assert!(matches!(reader1.put(b"new", item), Err(KeystoneError::ReadOnly(_))));
+        assert!(matches!(reader1.delete(b"flushed"), Err(KeystoneError::ReadOnly(_))));
+        assert!(matches!(reader1.flush(), Err(KeystoneError::ReadOnly(_))));
+
+        // The writer is unaffected
+        writer.put(b"later", ItemBuilder::new().number("v", 4).build()).unwrap();
+        drop(reader1);
+        drop(reader2);
+        drop(writer);
+
+        let reopened = Database::open(dir.path()).unwrap();
+        assert!(reopened.get(b"later").unwrap().is_some());
+        assert!(reopened.get(b"new").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_database_single_writer_lock() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        db.put(b"key", ItemBuilder::new().number("v", 1).build()).unwrap();
+
+        // A second writer is refused, but readers are not
+        match Database::open(dir.path()) {
+            Err(KeystoneError::DatabaseLocked { path, .. }) => {
+                assert_eq!(path, dir.path().display().to_string())
+            }
+            other => panic!("expected DatabaseLocked, got {:?}", other.is_ok()),
+        }
+        assert!(Database::create(dir.path()).is_err());
+        Database::open_read_only(dir.path()).unwrap();
+
+        // Closing releases the lock
+        drop(db);
+        let db = Database::open(dir.path()).unwrap();
+        assert!(db.get(b"key").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_database_named_tables() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let users = db.create_table("users", TableSchema::new()).unwrap();
+        users.put(b"id#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        users.put_with_sk(b"id#2", b"profile", ItemBuilder::new().string("name", "Bob").build()).unwrap();
+        db.put(b"id#1", ItemBuilder::new().string("name", "default").build()).unwrap();
+
+        // Same key, separate items
+        let item = users.get(b"id#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Alice")));
+        let item = db.get(b"id#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("default")));
+
+        // Scans and queries stay within their table, with unprefixed keys
+        assert_eq!(users.scan(Scan::new()).unwrap().count, 2);
+        assert_eq!(db.scan(Scan::new()).unwrap().count, 1);
+        let response = users.query(Query::new(b"id#2").limit(1)).unwrap();
+        assert_eq!(response.items.len(), 1);
+        assert_eq!(response.last_key, Some((Bytes::from("id#2"), Some(Bytes::from("profile")))));
+
+        assert_eq!(users.stats().unwrap().item_count, 2);
+        assert!(db.create_table("users", TableSchema::new()).is_err());
+        assert!(matches!(db.table("missing"), Err(KeystoneError::NotFound(_))));
+
+        // Tables persist with the database
+        drop(users);
+        drop(db);
+        let db = Database::open(dir.path()).unwrap();
+        assert_eq!(db.list_tables(), vec!["users".to_string()]);
+        assert!(db.table("users").unwrap().get(b"id#1").unwrap().is_some());
+
+        // Dropping a table deletes its items
+        db.drop_table("users").unwrap();
+        db.create_table("users", TableSchema::new()).unwrap();
+        assert!(db.table("users").unwrap().get(b"id#1").unwrap().is_none());
+        assert!(db.get(b"id#1").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_database_describe() {
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().with_ttl("expires_at");
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+        for i in 0..10 {
+            db.put(format!("key{}", i).as_bytes(), ItemBuilder::new().number("v", i).build()).unwrap();
+        }
+        db.flush().unwrap();
+
+        let description = db.describe().unwrap();
+        assert_eq!(description.schema.ttl_attribute_name, Some("expires_at".to_string()));
+        assert!(description.created_at.is_some());
+        assert_eq!(description.approximate_item_count, 10);
+        assert_eq!(description.stripes.len(), 256);
+        assert!(description.sst_count() > 0);
+        assert!(description.sst_bytes() > 0);
+        assert!(description.config.is_some());
+        assert!(!description.read_only);
+
+        // The creation time survives reopening
+        let created_at = description.created_at;
+        drop(db);
+        let db = Database::open_read_only(dir.path()).unwrap();
+        let description = db.describe().unwrap();
+        assert_eq!(description.created_at, created_at);
+        assert!(description.read_only);
+
+        let description = Database::create_in_memory().unwrap().describe().unwrap();
+        assert!(description.stripes.is_empty());
+        assert!(description.config.is_none());
+    }
+
+    #[test]
+    fn test_database_increment() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        // Missing items and attributes start at zero
+        assert_eq!(db.increment(b"url#abc", None, "visits", 1).unwrap(), 1);
+        assert_eq!(db.increment(b"url#abc", None, "visits", 1).unwrap(), 2);
+        assert_eq!(db.increment(b"url#abc", None, "visits", -5).unwrap(), -3);
+
+        db.put_with_sk(b"page", b"home", ItemBuilder::new().string("title", "Home").build()).unwrap();
+        assert_eq!(db.increment(b"page", Some(b"home"), "views", 10).unwrap(), 10);
+        let item = db.get_with_sk(b"page", b"home").unwrap().unwrap();
+        assert_eq!(item.get("title"), Some(&Value::string("Home")));
+        assert_eq!(item.get("views"), Some(&Value::number(10)));
+
+        // Non-numeric attributes can't be incremented
+        assert!(db.increment(b"page", Some(b"home"), "title", 1).is_err());
+    }
+
+    #[test]
+    fn test_database_put_get_json() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let json = serde_json::json!({"name": "Alice", "age": 30, "tags": ["admin"]});
+        db.put_json(b"user#1", json.clone()).unwrap();
+
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("age"), Some(&Value::number(30)));
+        assert_eq!(db.get_json(b"user#1").unwrap(), Some(json));
+        assert_eq!(db.get_json(b"user#2").unwrap(), None);
+
+        assert!(db.put_json(b"user#3", serde_json::json!("not an object")).is_err());
+    }
+
+    #[test]
+    fn test_item_builder_complex_types() {
+        let item = ItemBuilder::new()
+            .binary("avatar", vec![0u8, 1, 2])
+            .null("deleted_at")
+            .timestamp("created", 1_700_000_000_000)
+            .vector("embedding", vec![0.5, 1.0])
+            .list("tags", vec![Value::string("a"), Value::number(1)])
+            .map("address", |address| {
+                address
+                    .string("city", "Oslo")
+                    .map("geo", |geo| geo.number("lat", 59.9))
+            })
+            .build();
+
+        assert_eq!(item.get("avatar"), Some(&Value::B(Bytes::from_static(&[0, 1, 2]))));
+        assert_eq!(item.get("deleted_at"), Some(&Value::Null));
+        assert_eq!(item.get("created").unwrap().as_timestamp(), Some(1_700_000_000_000));
+        assert_eq!(item.get("embedding").unwrap().as_vector(), Some(&vec![0.5, 1.0]));
+        assert_eq!(
+            item.get("tags"),
+            Some(&Value::L(vec![Value::string("a"), Value::number(1)]))
+        );
+
+        let address = item.get("address").unwrap().as_map().unwrap();
+        assert_eq!(address.get("city"), Some(&Value::string("Oslo")));
+        let geo = address.get("geo").unwrap().as_map().unwrap();
+        assert_eq!(geo.get("lat"), Some(&Value::number(59.9)));
+    }
+
+    #[test]
+    fn test_database_typed_items() {
+        #[derive(Debug, PartialEq, KeystoneItem)]
+        struct Todo {
+            title: String,
+            #[keystone(rename = "desc")]
+            description: Option<String>,
+            priority: i64,
+            tags: Vec<String>,
+            #[keystone(skip)]
+            dirty: bool,
+        }
+
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let todo = Todo {
+            title: "Write docs".to_string(),
+            description: Some("API guide".to_string()),
+            priority: 2,
+            tags: vec!["docs".to_string()],
+            dirty: true,
+        };
+        db.put_t(b"todo#1", &todo).unwrap();
+
+        // Fields map to plain attributes
+        let item = db.get(b"todo#1").unwrap().unwrap();
+        assert_eq!(item.get("desc"), Some(&Value::string("API guide")));
+        assert_eq!(item.get("priority"), Some(&Value::number(2)));
+        assert!(!item.contains_key("dirty"));
+
+        let loaded: Todo = db.get_t(b"todo#1").unwrap().unwrap();
+        assert_eq!(loaded, Todo { dirty: false, ..todo });
+        assert!(db.get_t::<Todo>(b"todo#2").unwrap().is_none());
+
+        // Missing optional attributes read as None; missing required ones fail
+        let minimal = ItemBuilder::new()
+            .string("title", "Minimal")
+            .number("priority", 1)
+            .build();
+        assert!(Todo::from_item(minimal.clone()).is_err());
+
+        let mut with_tags = minimal;
+        with_tags.insert("tags".to_string(), Value::L(vec![]));
+        let loaded = Todo::from_item(with_tags).unwrap();
+        assert_eq!(loaded.description, None);
+        assert!(loaded.tags.is_empty());
+    }
+
+    #[test]
+    fn test_database_count_only() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..20 {
+            let sk = format!("item#{:03}", i);
+            let item = ItemBuilder::new().number("id", i).build();
+            db.put_with_sk(b"user#1", sk.as_bytes(), item).unwrap();
+        }
+
+        let response = db
+            .query(
+                Query::new(b"user#1")
+                    .select(Select::Count)
+                    .filter("id < :max")
+                    .value(":max", Value::number(15)),
+            )
+            .unwrap();
+        assert!(response.items.is_empty());
+        assert_eq!(response.count, 15);
+        assert_eq!(response.scanned_count, 20);
+
+        // limit applies to counted items
+        let response = db.query(Query::new(b"user#1").select(Select::Count).limit(4)).unwrap();
+        assert_eq!(response.count, 4);
+        assert!(response.last_key.is_some());
+
+        let response = db.scan(Scan::new().select(Select::Count)).unwrap();
+        assert!(response.items.is_empty());
+        assert_eq!(response.count, 20);
+    }
+
+    #[test]
+    fn test_database_scan_select_keys() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        for pk in ["user#1", "user#2", "order#1"] {
+            db.put(pk.as_bytes(), ItemBuilder::new().string("name", pk).build()).unwrap();
+        }
+        db.put_with_sk(b"user#1", b"profile", ItemBuilder::new().build()).unwrap();
+
+        let response = db.scan(Scan::new().pk_prefix(b"user#").select(Select::Keys)).unwrap();
+        assert!(response.items.is_empty());
+        assert_eq!(response.count, 3);
+        let mut keys = response.keys;
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                (Bytes::from("user#1"), None),
+                (Bytes::from("user#1"), Some(Bytes::from("profile"))),
+                (Bytes::from("user#2"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_database_scan_parallel() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        // Insert 100 items
+        for i in 0..100 {
+            let pk = format!("key{}", i);
+            let item = ItemBuilder::new().number("value", i).build();
+            db.put(pk.as_bytes(), item).unwrap();
+        }
+
+        // Parallel scan with 4 segments
+        let mut total_items = 0;
+        for segment in 0..4 {
+            let scan = Scan::new().segment(segment, 4);
+            let response = db.scan(scan).unwrap();
+            total_items += response.items.len();
+        }
+
+        // All segments together should return all items
+        assert_eq!(total_items, 100);
+    }
+
+    #[test]
+    fn test_database_query_with_filter() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..10 {
+            let sk = format!("order#{:03}", i);
+            let status = if i % 2 == 0 { "shipped" } else { "pending" };
+            let item = ItemBuilder::new()
+                .string("status", status)
+                .number("total", i * 10)
+                .build();
+            db.put_with_sk(b"customer#1", sk.as_bytes(), item).unwrap();
+        }
+
+        let query = Query::new(b"customer#1")
+            .filter("status = :s AND total > :t")
+            .value(":s", Value::string("shipped"))
+            .value(":t", Value::number(20));
+        let response = db.query(query).unwrap();
+
+        // order#004, order#006, order#008
+        assert_eq!(response.items.len(), 3);
+        assert_eq!(response.count, 3);
+        assert_eq!(response.scanned_count, 10);
+
+        // The limit counts items before the filter, like DynamoDB's
+        let query = Query::new(b"customer#1")
+            .filter("status = :s AND total > :t")
+            .value(":s", Value::string("shipped"))
+            .value(":t", Value::number(20))
+            .limit(4);
+        let response = db.query(query).unwrap();
+        assert_eq!(response.count, 0);
+        assert_eq!(response.scanned_count, 4);
+        let last_key = response.last_key.unwrap();
+        assert_eq!(last_key.1.as_deref(), Some(&b"order#003"[..]));
+
+        let query = Query::new(b"customer#1")
+            .filter("status = :s AND total > :t")
+            .value(":s", Value::string("shipped"))
+            .value(":t", Value::number(20))
+            .limit(4)
+            .start_after(&last_key.0, last_key.1.as_deref());
+        let response = db.query(query).unwrap();
+        assert_eq!(response.count, 2); // order#004, order#006
+        assert_eq!(response.scanned_count, 4);
+    }
+
+    #[test]
+    fn test_database_scan_with_filter() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..20 {
+            let pk = format!("user#{}", i);
+            let mut builder = ItemBuilder::new().number("age", i * 5);
+            if i % 4 == 0 {
+                builder = builder.bool("admin", true);
+            }
+            db.put(pk.as_bytes(), builder.build()).unwrap();
+        }
+
+        let scan = Scan::new()
+            .filter("attribute_exists(#a) AND age >= :min")
+            .name("#a", "admin")
+            .value(":min", Value::number(40));
+        let response = db.scan(scan).unwrap();
 
-        // Second page
-        let (last_pk, last_sk) = response1.last_key.unwrap();
-        let scan2 = Scan::new()
-            .limit(10)
-            .start_after(&last_pk, last_sk.as_deref());
-        let response2 = db.scan(scan2).unwrap();
-        assert_eq!(response2.items.len(), 10);
+        // user#8, user#12, user#16
+        assert_eq!(response.items.len(), 3);
+        assert_eq!(response.scanned_count, 20);
 
-        // Third page
-        let (last_pk2, last_sk2) = response2.last_key.unwrap();
-        let scan3 = Scan::new()
-            .limit(10)
-            .start_after(&last_pk2, last_sk2.as_deref());
-        let response3 = db.scan(scan3).unwrap();
-        assert_eq!(response3.items.len(), 10);
+        // Each page evaluates `limit` items, whether or not they match
+        let mut start_after: Option<(Bytes, Option<Bytes>)> = None;
+        let mut pages = Vec::new();
+        loop {
+            let mut scan = Scan::new()
+                .filter("attribute_exists(#a) AND age >= :min")
+                .name("#a", "admin")
+                .value(":min", Value::number(40))
+                .limit(10);
+            if let Some((pk, sk)) = &start_after {
+                scan = scan.start_after(pk, sk.as_deref());
+            }
+            let response = db.scan(scan).unwrap();
+            pages.push((response.items.len(), response.scanned_count));
+            match response.last_key {
+                Some(last_key) if response.scanned_count == 10 => start_after = Some(last_key),
+                _ => break,
+            }
+        }
+        // user#0, user#1 and user#10..user#17 first: user#12 and user#16 match
+        assert_eq!(pages, vec![(2, 10), (1, 10), (0, 0)]);
     }
 
     #[test]
-    fn test_database_scan_parallel() {
+    fn test_database_projection() {
         let dir = TempDir::new().unwrap();
         let db = Database::create(dir.path()).unwrap();
 
-        // Insert 100 items
-        for i in 0..100 {
-            let pk = format!("key{}", i);
-            let item = ItemBuilder::new().number("value", i).build();
-            db.put(pk.as_bytes(), item).unwrap();
-        }
+        let mut address = HashMap::new();
+        address.insert("city".to_string(), Value::string("Lisbon"));
+        address.insert("street".to_string(), Value::string("Rua Augusta"));
 
-        // Parallel scan with 4 segments
-        let mut total_items = 0;
-        for segment in 0..4 {
-            let scan = Scan::new().segment(segment, 4);
-            let response = db.scan(scan).unwrap();
-            total_items += response.items.len();
+        for i in 0..5 {
+            let sk = format!("profile#{}", i);
+            let mut item = ItemBuilder::new()
+                .string("name", format!("User {}", i))
+                .string("status", "active")
+                .string("bio", "long text that we don't want to ship around")
+                .build();
+            item.insert("address".to_string(), Value::M(address.clone()));
+            db.put_with_sk(b"org#1", sk.as_bytes(), item).unwrap();
         }
 
-        // All segments together should return all items
-        assert_eq!(total_items, 100);
+        let fields = ["name", "status", "address.city"];
+
+        let item = db.get_with_sk_projection(b"org#1", b"profile#0", &fields).unwrap().unwrap();
+        assert_eq!(item.len(), 3);
+        assert!(!item.contains_key("bio"));
+        let projected_address = item.get("address").unwrap().as_map().unwrap();
+        assert_eq!(projected_address.len(), 1);
+
+        let response = db.query(Query::new(b"org#1").projection(&fields)).unwrap();
+        assert_eq!(response.items.len(), 5);
+        assert!(response.items.iter().all(|item| item.len() == 3 && !item.contains_key("bio")));
+
+        let response = db.scan(Scan::new().projection(&["name"])).unwrap();
+        assert_eq!(response.items.len(), 5);
+        assert!(response.items.iter().all(|item| item.len() == 1));
     }
 
     #[test]
@@ -977,6 +2394,79 @@ mod tests {
         assert!(matches!(result, Err(kstone_core::Error::ConditionalCheckFailed(_))));
     }
 
+    #[test]
+    fn test_database_update_return_values() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let item = ItemBuilder::new()
+            .string("name", "Bob")
+            .number("age", 25)
+            .build();
+        db.put(b"user#789", item).unwrap();
+
+        let update = Update::new(b"user#789")
+            .expression("SET age = :age")
+            .value(":age", Value::number(26))
+            .return_values(ReturnValues::UpdatedOld);
+        let response = db.update(update).unwrap();
+        let attributes = response.attributes.unwrap();
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes.get("age"), Some(&Value::number(25)));
+
+        let update = Update::new(b"user#789")
+            .expression("SET age = :age")
+            .value(":age", Value::number(27))
+            .return_values(ReturnValues::AllNew);
+        let response = db.update(update).unwrap();
+        assert_eq!(response.attributes, Some(response.item.clone()));
+
+        // Default returns no attributes
+        let update = Update::new(b"user#789")
+            .expression("SET age = :age")
+            .value(":age", Value::number(28));
+        assert!(db.update(update).unwrap().attributes.is_none());
+    }
+
+    #[test]
+    fn test_database_put_and_delete_returning() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let first = ItemBuilder::new().string("v", "1").build();
+        let second = ItemBuilder::new().string("v", "2").build();
+
+        assert!(db.put_returning(b"key1", first.clone(), ReturnValues::AllOld).unwrap().is_none());
+        let old = db.put_returning(b"key1", second.clone(), ReturnValues::AllOld).unwrap();
+        assert_eq!(old, Some(first));
+
+        db.flush().unwrap();
+
+        let old = db.delete_returning(b"key1", ReturnValues::AllOld).unwrap();
+        assert_eq!(old, Some(second));
+        assert!(db.get(b"key1").unwrap().is_none());
+        assert!(db.delete_returning(b"key1", ReturnValues::AllOld).unwrap().is_none());
+
+        let item = ItemBuilder::new().string("v", "3").build();
+        assert!(db.put_with_sk_returning(b"pk", b"sk", item.clone(), ReturnValues::None).unwrap().is_none());
+        let old = db.delete_with_sk_returning(b"pk", b"sk", ReturnValues::AllOld).unwrap();
+        assert_eq!(old, Some(item));
+
+        // Only NONE and ALL_OLD are valid for puts and deletes
+        let result = db.put_returning(b"key2", ItemBuilder::new().build(), ReturnValues::AllNew);
+        assert!(matches!(result, Err(KeystoneError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_database_in_memory_put_returning() {
+        let db = Database::create_in_memory().unwrap();
+
+        let item = ItemBuilder::new().number("n", 1).build();
+        db.put(b"key1", item.clone()).unwrap();
+        let old = db.put_returning(b"key1", ItemBuilder::new().number("n", 2).build(), ReturnValues::AllOld).unwrap();
+        assert_eq!(old, Some(item));
+    }
+
     #[test]
     fn test_database_delete_with_condition() {
         let dir = TempDir::new().unwrap();
@@ -1340,7 +2830,7 @@ mod tests {
 
         let db = Database::create_with_schema(dir.path(), schema).unwrap();
 
-        // Put multiple items for the same partition key
+        // Put multiple items under the same partition key, one sort key each
         for i in 0..5 {
             let email = format!("user{}@example.com", i);
             let item = ItemBuilder::new()
@@ -1349,7 +2839,7 @@ mod tests {
                 .number("age", 20 + i)
                 .build();
 
-            db.put(b"org#acme", item).unwrap();
+            db.put_with_sk(b"org#acme", format!("member#{}", i).as_bytes(), item).unwrap();
         }
 
         // Query by email using the LSI
@@ -1390,7 +2880,7 @@ mod tests {
                 .number("score", *score)
                 .build();
 
-            db.put(b"game#123", item).unwrap();
+            db.put_with_sk(b"game#123", format!("player#{}", i).as_bytes(), item).unwrap();
         }
 
         // Query for scores >= 500 using LSI
@@ -1517,6 +3007,220 @@ mod tests {
         assert_eq!(response.items.len(), 6);
     }
 
+    #[test]
+    fn test_database_query_keys_only_gsi() {
+        let dir = TempDir::new().unwrap();
+
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::new("status-index", "status").keys_only());
+
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        for i in 1..=3 {
+            db.put(format!("user#{}", i).as_bytes(), ItemBuilder::new()
+                .string("name", format!("User {}", i))
+                .string("status", "active")
+                .build()).unwrap();
+        }
+
+        // Only the index key is stored, and the base key stays hidden
+        let response = db.query(Query::new(b"active").index("status-index")).unwrap();
+        assert_eq!(response.items.len(), 3);
+        for item in &response.items {
+            assert_eq!(item.len(), 1);
+            assert_eq!(item.get("status").unwrap().as_string().unwrap(), "active");
+        }
+
+        // Asking for an unprojected attribute fetches the base item
+        let response = db.query(Query::new(b"active")
+            .index("status-index")
+            .projection(&["name"])).unwrap();
+        assert_eq!(response.items.len(), 3);
+        for item in &response.items {
+            assert_eq!(item.len(), 1);
+            assert!(item.get("name").unwrap().as_string().unwrap().starts_with("User "));
+        }
+    }
+
+    #[test]
+    fn test_database_query_include_gsi() {
+        let dir = TempDir::new().unwrap();
+
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::new("status-index", "status")
+                .include(vec!["name".to_string()]));
+
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        db.put(b"user#1", ItemBuilder::new()
+            .string("name", "Alice")
+            .string("email", "alice@example.com")
+            .string("status", "active")
+            .build()).unwrap();
+
+        let response = db.query(Query::new(b"active").index("status-index")).unwrap();
+        assert_eq!(response.items.len(), 1);
+        let item = &response.items[0];
+        assert_eq!(item.len(), 2);
+        assert_eq!(item.get("name").unwrap().as_string().unwrap(), "Alice");
+        assert!(item.get("email").is_none());
+
+        let response = db.query(Query::new(b"active")
+            .index("status-index")
+            .projection(&["name", "email"])).unwrap();
+        let item = &response.items[0];
+        assert_eq!(item.get("email").unwrap().as_string().unwrap(), "alice@example.com");
+
+        // Deleted base items aren't returned through the fetch
+        db.delete(b"user#1").unwrap();
+        let response = db.query(Query::new(b"active")
+            .index("status-index")
+            .projection(&["email"])).unwrap();
+        assert!(response.items.is_empty());
+    }
+
+    #[test]
+    fn test_database_gsi_maintained_on_update_and_delete() {
+        let dir = TempDir::new().unwrap();
+
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::new("status-index", "status"));
+
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+        let count = |status: &[u8]| db.query(Query::new(status).index("status-index")).unwrap().items.len();
+
+        for i in 1..=3 {
+            db.put(format!("user#{}", i).as_bytes(), ItemBuilder::new()
+                .string("status", "active")
+                .build()).unwrap();
+        }
+        assert_eq!(count(b"active"), 3);
+
+        // Changing the indexed attribute moves the entry
+        db.update(Update::new(b"user#1")
+            .expression("SET #s = :status")
+            .name("#s", "status")
+            .value(":status", Value::string("inactive"))).unwrap();
+        assert_eq!(count(b"active"), 2);
+        assert_eq!(count(b"inactive"), 1);
+
+        // Removing the indexed attribute drops the entry
+        db.put(b"user#2", ItemBuilder::new().string("name", "Bob").build()).unwrap();
+        assert_eq!(count(b"active"), 1);
+
+        // Deleting the item drops the entry
+        db.delete(b"user#3").unwrap();
+        assert_eq!(count(b"active"), 0);
+        assert_eq!(count(b"inactive"), 1);
+    }
+
+    #[test]
+    fn test_database_vector_search() {
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().add_vector_index("embedding", 3, DistanceMetric::Cosine);
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        let docs = [
+            ("doc#cat", vec![1.0, 0.1, 0.0]),
+            ("doc#dog", vec![0.9, 0.3, 0.0]),
+            ("doc#car", vec![0.0, 0.1, 1.0]),
+        ];
+        for (pk, embedding) in docs {
+            let mut item = ItemBuilder::new().string("title", pk).build();
+            item.insert("embedding".to_string(), Value::VecF32(embedding));
+            db.put(pk.as_bytes(), item).unwrap();
+        }
+
+        let matches = db.vector_search("embedding", &[1.0, 0.0, 0.0], 2).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].key.pk, Bytes::from("doc#cat"));
+        assert_eq!(matches[1].key.pk, Bytes::from("doc#dog"));
+        assert_eq!(matches[0].item.get("title"), Some(&Value::string("doc#cat")));
+
+        db.delete(b"doc#cat").unwrap();
+        let matches = db.vector_search("embedding", &[1.0, 0.0, 0.0], 1).unwrap();
+        assert_eq!(matches[0].key.pk, Bytes::from("doc#dog"));
+    }
+
+    #[test]
+    fn test_database_geo_query() {
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new()
+            .add_geo_index(GeoIndex::new("location-index", "lat", "lon"));
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        let places = [
+            ("place#louvre", 48.8606, 2.3376),
+            ("place#notre-dame", 48.8530, 2.3499),
+            ("place#versailles", 48.8049, 2.1204),
+        ];
+        for (pk, lat, lon) in places {
+            db.put(pk.as_bytes(), ItemBuilder::new()
+                .string("name", pk)
+                .number("lat", lat)
+                .number("lon", lon)
+                .build()).unwrap();
+        }
+
+        let matches = db.geo_query("location-index", GeoPoint::new(48.8584, 2.2945), 5_000.0).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].item.get("name"), Some(&Value::string("place#louvre")));
+
+        let bbox = GeoBox::new(GeoPoint::new(48.80, 2.10), GeoPoint::new(48.82, 2.15));
+        let items = db.geo_query_box("location-index", bbox).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].get("name"), Some(&Value::string("place#versailles")));
+    }
+
+    #[test]
+    fn test_database_create_and_drop_index() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 1..=4 {
+            db.put(format!("user#{}", i).as_bytes(), ItemBuilder::new()
+                .string("status", if i % 2 == 0 { "active" } else { "inactive" })
+                .build()).unwrap();
+        }
+
+        db.create_index(GlobalSecondaryIndex::new("status-index", "status")).unwrap();
+        db.wait_for_index_backfills().unwrap();
+        assert!(db.index_backfills().unwrap().is_empty());
+
+        let active = db.query(Query::new(b"active").index("status-index")).unwrap();
+        assert_eq!(active.items.len(), 2);
+
+        db.drop_index("status-index").unwrap();
+        assert!(db.drop_index("status-index").is_err());
+        assert!(db.query(Query::new(b"active").index("status-index")).unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_database_gsi_maintained_in_transaction() {
+        let dir = TempDir::new().unwrap();
+
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::new("status-index", "status"));
+
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+        let count = |status: &[u8]| db.query(Query::new(status).index("status-index")).unwrap().items.len();
+
+        let request = TransactWriteRequest::new()
+            .put(b"order#1", ItemBuilder::new().string("status", "pending").build())
+            .put(b"order#2", ItemBuilder::new().string("status", "pending").build());
+        db.transact_write(request).unwrap();
+        assert_eq!(count(b"pending"), 2);
+
+        let request = TransactWriteRequest::new()
+            .update(b"order#1", "SET #s = :shipped")
+            .delete(b"order#2")
+            .name("#s", "status")
+            .value(":shipped", Value::string("shipped"));
+        db.transact_write(request).unwrap();
+        assert_eq!(count(b"pending"), 0);
+        assert_eq!(count(b"shipped"), 1);
+    }
+
     #[test]
     fn test_database_gsi_different_stripes() {
         let dir = TempDir::new().unwrap();
@@ -1599,6 +3303,38 @@ mod tests {
         assert_eq!(result.unwrap().get("name").unwrap().as_string().unwrap(), "Valid Item");
     }
 
+    #[test]
+    fn test_database_ttl_background_reaper() {
+        use std::time::{Duration, Instant};
+
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().with_ttl("expiresAt");
+        let config = DatabaseConfig::new().with_ttl_reaper_interval(Duration::from_millis(20));
+        let db = Database::create_with_config_and_schema(dir.path(), config, schema).unwrap();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        for i in 0..3 {
+            let item = ItemBuilder::new().number("expiresAt", now - 10).build();
+            db.put(format!("expired#{}", i).as_bytes(), item).unwrap();
+        }
+        let item = ItemBuilder::new().number("expiresAt", now + 3600).build();
+        db.put(b"live", item).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while db.stats().unwrap().ttl.total_items_reaped < 3 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let stats = db.stats().unwrap();
+        assert_eq!(stats.ttl.total_items_reaped, 3);
+        assert!(stats.ttl.total_passes >= 1);
+        assert!(db.get(b"live").unwrap().is_some());
+    }
+
     #[test]
     fn test_database_ttl_query_filter() {
         use tempfile::TempDir;
@@ -1948,6 +3684,98 @@ mod tests {
         assert_eq!(records[0].sequence_number, 6);
         assert_eq!(records[4].sequence_number, 10);
     }
+
+    #[test]
+    fn test_database_stream_resume_after_reopen() {
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().with_stream(StreamConfig::enabled());
+
+        let processed = {
+            let db = Database::create_with_schema(dir.path(), schema).unwrap();
+            for i in 1..=3 {
+                db.put(format!("item#{}", i).as_bytes(), ItemBuilder::new().number("n", i).build()).unwrap();
+            }
+
+            let iterator = db.get_shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+            let result = db.get_records(iterator, 2).unwrap();
+            assert_eq!(result.records.len(), 2);
+            result.records[1].sequence_number
+        };
+
+        let db = Database::open(dir.path()).unwrap();
+        let iterator = db
+            .get_shard_iterator(ShardIteratorType::AfterSequenceNumber(processed))
+            .unwrap();
+        let result = db.get_records(iterator, 10).unwrap();
+        assert_eq!(result.records.len(), 1);
+        assert_eq!(&result.records[0].key.pk[..], b"item#3");
+    }
+
+    #[test]
+    fn test_database_subscribe_stream() {
+        use tempfile::TempDir;
+        use std::time::Duration;
+
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().with_stream(StreamConfig::enabled());
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        db.put(b"before", ItemBuilder::new().number("n", 0).build()).unwrap();
+
+        let replay = db
+            .subscribe_stream_with_config(
+                SubscriptionConfig::new()
+                    .with_start(ShardIteratorType::TrimHorizon)
+                    .with_capacity(4),
+            )
+            .unwrap();
+        let live = db.subscribe_stream().unwrap();
+
+        for i in 1..=3 {
+            db.put(format!("item#{}", i).as_bytes(), ItemBuilder::new().number("n", i).build()).unwrap();
+        }
+
+        let replayed: Vec<u64> = (0..4)
+            .map(|_| replay.recv_timeout(Duration::from_secs(5)).unwrap().sequence_number)
+            .collect();
+        assert_eq!(replayed.len(), 4);
+        assert!(replayed.windows(2).all(|w| w[0] < w[1]));
+
+        let first_live = live.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(&first_live.key.pk[..], b"item#1");
+
+        live.unsubscribe();
+        replay.unsubscribe();
+    }
+
+    #[test]
+    fn test_database_subscribe_stream_requires_streams() {
+        let db = Database::create_in_memory().unwrap();
+        assert!(db.subscribe_stream().is_err());
+    }
+
+    #[test]
+    fn test_database_stream_trimmed_iterator() {
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new()
+            .with_stream(StreamConfig::enabled().with_buffer_size(2));
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        db.put(b"a", ItemBuilder::new().number("n", 1).build()).unwrap();
+        let iterator = db.get_shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+
+        db.put(b"b", ItemBuilder::new().number("n", 2).build()).unwrap();
+        db.put(b"c", ItemBuilder::new().number("n", 3).build()).unwrap();
+
+        match db.get_records(iterator, 10) {
+            Err(KeystoneError::TrimmedDataAccess(_)) => {}
+            other => panic!("expected TrimmedDataAccess, got {:?}", other.map(|r| r.records.len())),
+        }
+    }
 }
 
 
@@ -0,0 +1,369 @@
+/// Leases for coordinating work through a shared database (Phase 2.5+)
+///
+/// Modeled after the DynamoDB lock client. Each lock is an item in the
+/// default table under `_lock#<name>` holding its owner, the time its lease
+/// expires and a fencing token. Acquiring and renewing are conditional
+/// writes, so at most one owner holds an unexpired lease at a time. An
+/// expired lease can be taken over by anyone.
+///
+/// The fencing token grows with every acquisition and survives releases.
+/// Pass it along with writes to protected resources, which should reject
+/// tokens lower than the newest they have seen: a holder that stalled past
+/// its lease cannot tell that it lost the lock.
+///
+/// Only one read-write handle can open a database directory, so processes
+/// share a lock table through one `Database` (for example behind
+/// kstone-server) rather than opening the file separately.
+
+use crate::{Database, Item, ItemBuilder};
+use kstone_core::{expression::ExpressionContext, Error, Result, Value};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Partition key prefix of lock items
+pub const LOCK_KEY_PREFIX: &str = "_lock#";
+
+const OWNER_ATTRIBUTE: &str = "owner";
+const EXPIRES_ATTRIBUTE: &str = "lease_expires_at";
+const TOKEN_ATTRIBUTE: &str = "fencing_token";
+
+/// Acquires leases on named locks for one owner
+pub struct LockClient {
+    db: Arc<Database>,
+    owner: String,
+    heartbeat_interval: Option<Duration>,
+}
+
+impl LockClient {
+    /// Create a client that acquires locks as `owner`
+    ///
+    /// Owners should be unique per process (a hostname plus PID, or a UUID).
+    pub fn new(db: Arc<Database>, owner: impl Into<String>) -> Self {
+        Self {
+            db,
+            owner: owner.into(),
+            heartbeat_interval: None,
+        }
+    }
+
+    /// Renew every acquired lease from a background thread at this interval
+    ///
+    /// The interval should be well under the lease duration. Without it,
+    /// callers renew with `Lease::heartbeat`.
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Owner name used for acquired locks
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Acquire a lock for `lease_duration`
+    ///
+    /// Fails with `Error::ConditionalCheckFailed` if another owner holds an
+    /// unexpired lease.
+    pub fn acquire(&self, name: &str, lease_duration: Duration) -> Result<Lease> {
+        let key = lock_key(name);
+        let now = now_millis();
+
+        let current = self.db.get(key.as_bytes())?;
+        let token = current.as_ref().and_then(|item| number(item, TOKEN_ATTRIBUTE));
+        if let Some(holder) = current.as_ref().and_then(|item| holder(item, now)) {
+            if holder != self.owner {
+                return Err(held(name, &holder));
+            }
+        }
+
+        // Every acquisition bumps the token, so a matching token means nobody
+        // acquired the lock since it was read
+        let fencing_token = token.unwrap_or(0) + 1;
+        let context = ExpressionContext::new().with_name("#token", TOKEN_ATTRIBUTE);
+        let (condition, context) = match token {
+            Some(token) => ("#token = :token", context.with_value(":token", Value::number(token))),
+            None => ("attribute_not_exists(#token)", context),
+        };
+
+        let item = lease_item(&self.owner, now + millis(lease_duration), fencing_token);
+        self.db
+            .put_conditional(key.as_bytes(), item, condition, context)
+            .map_err(|e| match e {
+                Error::ConditionalCheckFailed(_) => {
+                    Error::ConditionalCheckFailed(format!("Lock '{}' was acquired concurrently", name))
+                }
+                e => e,
+            })?;
+
+        let mut lease = Lease {
+            db: Arc::clone(&self.db),
+            name: name.to_string(),
+            owner: self.owner.clone(),
+            fencing_token,
+            lease_duration,
+            heartbeat: None,
+            released: false,
+        };
+        if let Some(interval) = self.heartbeat_interval {
+            lease.heartbeat = Some(Heartbeat::start(&lease, interval));
+        }
+        Ok(lease)
+    }
+
+    /// Acquire a lock, retrying every `poll_interval` until `timeout` elapses
+    pub fn acquire_with_wait(
+        &self,
+        name: &str,
+        lease_duration: Duration,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Lease> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            match self.acquire(name, lease_duration) {
+                Err(Error::ConditionalCheckFailed(_)) if std::time::Instant::now() < deadline => {
+                    thread::sleep(poll_interval);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A held lease on a lock, released on drop
+pub struct Lease {
+    db: Arc<Database>,
+    name: String,
+    owner: String,
+    fencing_token: u64,
+    lease_duration: Duration,
+    heartbeat: Option<Heartbeat>,
+    released: bool,
+}
+
+impl Lease {
+    /// Lock name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Fencing token of this acquisition
+    pub fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    /// Extend the lease by its duration from now
+    ///
+    /// Fails with `Error::ConditionalCheckFailed` if the lease was lost.
+    pub fn heartbeat(&self) -> Result<()> {
+        renew(&self.db, &self.name, &self.owner, self.fencing_token, self.lease_duration)
+    }
+
+    /// Release the lock so others can acquire it immediately
+    pub fn release(mut self) -> Result<()> {
+        self.released = true;
+        self.stop_heartbeat();
+
+        // Keep the item and its token so later tokens keep growing
+        let item = ItemBuilder::new().number(TOKEN_ATTRIBUTE, self.fencing_token).build();
+        let (condition, context) = owned_condition(&self.owner, self.fencing_token);
+        self.db
+            .put_conditional(lock_key(&self.name).as_bytes(), item, condition, context)
+            .map_err(|e| lost(e, &self.name))
+    }
+
+    fn stop_heartbeat(&mut self) {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.stop();
+        }
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        if !self.released {
+            self.stop_heartbeat();
+            let lease = Lease {
+                db: Arc::clone(&self.db),
+                name: std::mem::take(&mut self.name),
+                owner: std::mem::take(&mut self.owner),
+                fencing_token: self.fencing_token,
+                lease_duration: self.lease_duration,
+                heartbeat: None,
+                released: true,
+            };
+            // Best effort: an unreleased lease expires on its own
+            let _ = lease.release();
+        }
+    }
+}
+
+/// Background thread renewing one lease
+struct Heartbeat {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl Heartbeat {
+    fn start(lease: &Lease, interval: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let db = Arc::clone(&lease.db);
+        let name = lease.name.clone();
+        let owner = lease.owner.clone();
+        let token = lease.fencing_token;
+        let duration = lease.lease_duration;
+
+        let thread = thread::spawn(move || loop {
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {
+                    // A lost lease can't be recovered, so stop renewing it
+                    if renew(&db, &name, &owner, token, duration).is_err() {
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        });
+
+        Self { stop, thread }
+    }
+
+    fn stop(self) {
+        drop(self.stop);
+        let _ = self.thread.join();
+    }
+}
+
+fn renew(db: &Database, name: &str, owner: &str, token: u64, lease_duration: Duration) -> Result<()> {
+    let item = lease_item(owner, now_millis() + millis(lease_duration), token);
+    let (condition, context) = owned_condition(owner, token);
+    db.put_conditional(lock_key(name).as_bytes(), item, condition, context)
+        .map_err(|e| lost(e, name))
+}
+
+/// Condition that the lock is still held by this acquisition
+fn owned_condition(owner: &str, token: u64) -> (&'static str, ExpressionContext) {
+    let context = ExpressionContext::new()
+        .with_name("#owner", OWNER_ATTRIBUTE)
+        .with_name("#token", TOKEN_ATTRIBUTE)
+        .with_value(":owner", Value::string(owner))
+        .with_value(":token", Value::number(token));
+    ("#owner = :owner AND #token = :token", context)
+}
+
+fn lease_item(owner: &str, expires_at: i64, token: u64) -> Item {
+    ItemBuilder::new()
+        .string(OWNER_ATTRIBUTE, owner)
+        .number(EXPIRES_ATTRIBUTE, expires_at)
+        .number(TOKEN_ATTRIBUTE, token)
+        .build()
+}
+
+/// Owner of an unexpired lease
+fn holder(item: &Item, now: i64) -> Option<String> {
+    let owner = item.get(OWNER_ATTRIBUTE)?.as_string()?;
+    let expires_at = number(item, EXPIRES_ATTRIBUTE)?;
+    (expires_at as i64 > now).then(|| owner.to_string())
+}
+
+fn number(item: &Item, attribute: &str) -> Option<u64> {
+    match item.get(attribute) {
+        Some(Value::N(n)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+fn held(name: &str, owner: &str) -> Error {
+    Error::ConditionalCheckFailed(format!("Lock '{}' is held by '{}'", name, owner))
+}
+
+fn lost(err: Error, name: &str) -> Error {
+    match err {
+        Error::ConditionalCheckFailed(_) => {
+            Error::ConditionalCheckFailed(format!("Lease on lock '{}' was lost", name))
+        }
+        err => err,
+    }
+}
+
+fn lock_key(name: &str) -> String {
+    format!("{}{}", LOCK_KEY_PREFIX, name)
+}
+
+fn millis(duration: Duration) -> i64 {
+    duration.as_millis() as i64
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_client_acquire_and_release() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let alice = LockClient::new(Arc::clone(&db), "alice");
+        let bob = LockClient::new(Arc::clone(&db), "bob");
+
+        let lease = alice.acquire("jobs", Duration::from_secs(60)).unwrap();
+        assert_eq!(lease.fencing_token(), 1);
+        assert!(matches!(
+            bob.acquire("jobs", Duration::from_secs(60)),
+            Err(Error::ConditionalCheckFailed(_))
+        ));
+        lease.heartbeat().unwrap();
+
+        // Releasing hands the lock over with a larger token
+        lease.release().unwrap();
+        let lease = bob.acquire("jobs", Duration::from_secs(60)).unwrap();
+        assert_eq!(lease.fencing_token(), 2);
+
+        // Dropping a lease releases it too
+        drop(lease);
+        assert_eq!(alice.acquire("jobs", Duration::from_secs(60)).unwrap().fencing_token(), 3);
+    }
+
+    #[test]
+    fn test_lock_client_expired_lease_is_taken_over() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let alice = LockClient::new(Arc::clone(&db), "alice");
+        let bob = LockClient::new(Arc::clone(&db), "bob");
+
+        let stale = alice.acquire("jobs", Duration::from_millis(50)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        let lease = bob.acquire("jobs", Duration::from_secs(60)).unwrap();
+        assert!(lease.fencing_token() > stale.fencing_token());
+
+        // The old holder finds out when it next renews or releases
+        assert!(stale.heartbeat().is_err());
+        assert!(stale.release().is_err());
+        assert!(alice.acquire("jobs", Duration::from_secs(60)).is_err());
+    }
+
+    #[test]
+    fn test_lock_client_background_heartbeat() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let alice = LockClient::new(Arc::clone(&db), "alice").with_heartbeat(Duration::from_millis(20));
+        let bob = LockClient::new(Arc::clone(&db), "bob");
+
+        let lease = alice.acquire("jobs", Duration::from_millis(100)).unwrap();
+        thread::sleep(Duration::from_millis(250));
+        assert!(bob.acquire("jobs", Duration::from_secs(60)).is_err());
+
+        lease.release().unwrap();
+        bob.acquire("jobs", Duration::from_secs(60)).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+/// Typed item mapping via serde
+///
+/// Converts between Rust types and KeystoneDB items so callers don't have to
+/// build `HashMap<String, Value>` by hand. Values go through `serde_json`:
+/// numbers become `N`, strings `S`, sequences `L`, structs and maps `M`, and
+/// `None`/unit become `Null`. Binary, vector and timestamp attributes read
+/// back as lists of numbers and numbers respectively.
+
+use crate::json::json_to_value;
+use kstone_core::{Error, Item, Result, Value};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A type that maps to and from a KeystoneDB item
+///
+/// Usually implemented with `#[derive(KeystoneItem)]`, which maps each named
+/// field to an attribute of the same name. Fields accept
+/// `#[keystone(rename = "attr")]` and `#[keystone(skip)]`; skipped fields are
+/// filled with `Default::default()` when reading.
+pub trait KeystoneItem: Sized {
+    /// Convert to an item
+    fn to_item(&self) -> Result<Item>;
+
+    /// Build from an item
+    fn from_item(item: Item) -> Result<Self>;
+}
+
+/// Serialize any serde type that encodes as a map (e.g. a struct) into an item
+///
+/// Honors serde attributes such as `#[serde(rename)]` and `#[serde(skip)]`.
+pub fn to_item<T: Serialize + ?Sized>(value: &T) -> Result<Item> {
+    match to_value(value)? {
+        Value::M(item) => Ok(item),
+        _ => Err(Error::InvalidArgument(
+            "Only structs and maps can be stored as items".to_string(),
+        )),
+    }
+}
+
+/// Deserialize an item into any serde type
+pub fn from_item<T: DeserializeOwned>(item: Item) -> Result<T> {
+    from_value(Value::M(item))
+}
+
+/// Serialize a value into a KeystoneDB attribute value
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    let json = serde_json::to_value(value)
+        .map_err(|e| Error::InvalidArgument(format!("Cannot serialize value: {}", e)))?;
+    Ok(json_to_value(json))
+}
+
+/// Deserialize a KeystoneDB attribute value
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value_to_serde_json(value))
+        .map_err(|e| Error::InvalidArgument(format!("Cannot deserialize value: {}", e)))
+}
+
+/// Convert a value for deserialization
+///
+/// Unlike `json::value_to_json`, binary becomes a list of bytes so `Vec<u8>`
+/// fields round-trip.
+fn value_to_serde_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::N(n) => number_to_json(&n),
+        Value::S(s) => serde_json::Value::String(s),
+        Value::B(bytes) => serde_json::Value::Array(bytes.iter().map(|b| (*b).into()).collect()),
+        Value::Bool(b) => serde_json::Value::Bool(b),
+        Value::Null => serde_json::Value::Null,
+        Value::L(values) => serde_json::Value::Array(values.into_iter().map(value_to_serde_json).collect()),
+        Value::M(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(name, value)| (name, value_to_serde_json(value)))
+                .collect(),
+        ),
+        Value::VecF32(values) => serde_json::Value::Array(
+            values
+                .into_iter()
+                .map(|v| number_to_json(&v.to_string()))
+                .collect(),
+        ),
+        Value::Ts(ts) => ts.into(),
+    }
+}
+
+/// Parse a stored number, preferring integers so integer fields round-trip
+fn number_to_json(n: &str) -> serde_json::Value {
+    if let Ok(i) = n.parse::<i64>() {
+        i.into()
+    } else if let Ok(u) = n.parse::<u64>() {
+        u.into()
+    } else {
+        n.parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or_else(|| serde_json::Value::String(n.to_string()), serde_json::Value::Number)
+    }
+}
+
+/// Support code for `#[derive(KeystoneItem)]`; not a stable API
+#[doc(hidden)]
+pub mod __private {
+    pub use kstone_core::{Item, Result, Value};
+
+    use super::from_value;
+    use kstone_core::Error;
+    use serde::de::DeserializeOwned;
+
+    /// Take an attribute out of an item and deserialize it
+    ///
+    /// Missing attributes read as `Null`, so `Option` fields become `None`.
+    pub fn take_attribute<T: DeserializeOwned>(item: &mut Item, name: &str) -> Result<T> {
+        match item.remove(name) {
+            Some(value) => from_value(value).map_err(|e| {
+                Error::InvalidArgument(format!("Attribute '{}': {}", name, e))
+            }),
+            None => from_value(Value::Null)
+                .map_err(|_| Error::InvalidArgument(format!("Missing attribute '{}'", name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Todo {
+        id: String,
+        #[serde(rename = "desc")]
+        description: Option<String>,
+        priority: i64,
+        ratio: f64,
+        tags: Vec<String>,
+        #[serde(skip)]
+        cached: bool,
+    }
+
+    #[test]
+    fn test_to_item_from_item_roundtrip() {
+        let todo = Todo {
+            id: "todo#1".to_string(),
+            description: None,
+            priority: 3,
+            ratio: 0.5,
+            tags: vec!["home".to_string()],
+            cached: true,
+        };
+
+        let item = to_item(&todo).unwrap();
+        assert_eq!(item.get("id"), Some(&Value::string("todo#1")));
+        assert_eq!(item.get("desc"), Some(&Value::Null));
+        assert_eq!(item.get("priority"), Some(&Value::number(3)));
+        assert_eq!(item.get("tags"), Some(&Value::L(vec![Value::string("home")])));
+        assert!(!item.contains_key("cached"));
+
+        let back: Todo = from_item(item).unwrap();
+        assert_eq!(back, Todo { cached: false, ..todo });
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        // Only map-like values can become items
+        assert!(to_item(&42).is_err());
+
+        // Type mismatches are reported
+        let mut item = Item::new();
+        item.insert("id".to_string(), Value::number(1));
+        assert!(from_item::<Todo>(item).is_err());
+    }
+}
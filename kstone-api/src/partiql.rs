@@ -3,25 +3,115 @@
 /// Provides a high-level API for executing PartiQL (SQL-compatible) queries against KeystoneDB.
 /// Supports SELECT, INSERT, UPDATE, and DELETE operations.
 
-use crate::{Database, Item, Query, Scan, Update};
+use crate::{
+    Database, Item, Query, Scan, TransactWriteOp, TransactWriteRequest, TransactWriteResponse,
+    Update, Value,
+};
 use bytes::Bytes;
 use kstone_core::{
     partiql::{
-        PartiQLParser, PartiQLStatement, PartiQLTranslator, SelectTranslation,
-        SortKeyConditionType,
+        compare_values, resolve_attribute_path, Aggregator, CompareOp, Condition,
+        ConflictAction, DynamoDBValidator, InsertStatement, OrderBy, PartiQLParser,
+        PartiQLStatement, PartiQLTranslator, QueryType, SelectExpr, SelectList, SelectStatement,
+        SelectTranslation, SortKeyConditionType, SqlValue, StatementParameters,
     },
-    Result,
+    table::encode_table_key,
+    Error, Key, Result,
 };
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of parsed statements kept per database
+const STATEMENT_CACHE_CAPACITY: usize = 256;
 
 /// Request to execute a PartiQL statement
-#[allow(dead_code)]
+///
+/// Statements may contain `?` placeholders bound in order from `with_parameters`,
+/// and `:name` placeholders bound with `with_named_parameter`.
+#[derive(Debug, Clone)]
 pub struct ExecuteStatementRequest {
     sql: String,
+    parameters: StatementParameters,
 }
 
 impl ExecuteStatementRequest {
     pub fn new(sql: impl Into<String>) -> Self {
-        Self { sql: sql.into() }
+        Self {
+            sql: sql.into(),
+            parameters: StatementParameters::default(),
+        }
+    }
+
+    /// Bind values to `?` placeholders, in order of appearance
+    pub fn with_parameters(mut self, values: Vec<Value>) -> Self {
+        self.parameters.positional = values;
+        self
+    }
+
+    /// Bind a value to a `:name` placeholder (name given without the colon)
+    pub fn with_named_parameter(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.parameters.named.insert(name.into(), value);
+        self
+    }
+}
+
+impl From<&str> for ExecuteStatementRequest {
+    fn from(sql: &str) -> Self {
+        Self::new(sql)
+    }
+}
+
+impl From<String> for ExecuteStatementRequest {
+    fn from(sql: String) -> Self {
+        Self::new(sql)
+    }
+}
+
+/// Prepared statements keyed by statement text
+///
+/// Repeated statements skip re-parsing; parameters are bound per execution.
+/// The oldest entry is evicted once `STATEMENT_CACHE_CAPACITY` is reached.
+#[derive(Default)]
+pub(crate) struct StatementCache {
+    inner: Mutex<StatementCacheInner>,
+}
+
+#[derive(Default)]
+struct StatementCacheInner {
+    statements: HashMap<String, Arc<PartiQLStatement>>,
+    order: VecDeque<String>,
+}
+
+impl StatementCache {
+    /// Get a parsed statement, parsing and caching it on a miss
+    fn get_or_parse(&self, sql: &str) -> Result<Arc<PartiQLStatement>> {
+        if let Some(statement) = self.inner.lock().unwrap().statements.get(sql) {
+            return Ok(Arc::clone(statement));
+        }
+
+        // Parse outside the lock; invalid statements are not cached
+        let statement = Arc::new(PartiQLParser::parse(sql)?);
+
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.statements.contains_key(sql) {
+            if inner.order.len() >= STATEMENT_CACHE_CAPACITY {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.statements.remove(&oldest);
+                }
+            }
+            inner.order.push_back(sql.to_string());
+            inner.statements.insert(sql.to_string(), Arc::clone(&statement));
+        }
+        Ok(statement)
+    }
+
+    /// Number of cached statements
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().statements.len()
     }
 }
 
@@ -34,6 +124,8 @@ pub enum ExecuteStatementResponse {
         count: usize,
         scanned_count: usize,
         last_key: Option<(Bytes, Option<Bytes>)>,
+        /// Planner notes, e.g. a WHERE clause that fell back to a full scan
+        warnings: Vec<String>,
     },
     /// INSERT statement result
     Insert { success: bool },
@@ -41,6 +133,97 @@ pub enum ExecuteStatementResponse {
     Update { item: Item },
     /// DELETE statement result
     Delete { success: bool },
+    /// EXPLAIN SELECT result (the statement is planned but not returned)
+    Explain { plan: QueryPlan },
+    /// INSERT ... SELECT result
+    InsertSelect { inserted: usize },
+}
+
+/// How a SELECT reads its items
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessPath {
+    /// Query a single partition of the base table
+    TableQuery,
+    /// Query a single partition of a secondary index
+    IndexQuery { index_name: String },
+    /// One query per partition key (`pk IN (...)`)
+    MultiQuery {
+        partitions: usize,
+        index_name: Option<String>,
+    },
+    /// Scan every item in the table
+    FullScan,
+}
+
+impl fmt::Display for AccessPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessPath::TableQuery => write!(f, "Query on base table"),
+            AccessPath::IndexQuery { index_name } => write!(f, "Query on index '{}'", index_name),
+            AccessPath::MultiQuery {
+                partitions,
+                index_name: Some(index_name),
+            } => write!(f, "Query on {} partitions of index '{}'", partitions, index_name),
+            AccessPath::MultiQuery { partitions, .. } => {
+                write!(f, "Query on {} partitions of base table", partitions)
+            }
+            AccessPath::FullScan => write!(f, "Full table scan"),
+        }
+    }
+}
+
+/// Execution plan for a SELECT, returned by `EXPLAIN SELECT ...`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryPlan {
+    /// How items are read
+    pub access_path: AccessPath,
+    /// Conditions pushed down to the key lookup
+    pub key_conditions: Vec<String>,
+    /// Conditions evaluated on each item after it is read
+    pub residual_filters: Vec<String>,
+    /// ORDER BY applied after reading (sort key ordering is served by the query)
+    pub sort: Option<String>,
+    /// Aggregates and GROUP BY attributes, if the SELECT aggregates
+    pub aggregation: Option<String>,
+    /// Whether LIMIT/OFFSET is pushed into the read
+    pub limit_pushed_down: bool,
+    /// Estimated number of items read
+    pub estimated_scanned: u64,
+    /// Planner notes, e.g. a WHERE clause that fell back to a full scan
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Access path:       {}", self.access_path)?;
+        if self.key_conditions.is_empty() {
+            writeln!(f, "Key conditions:    (none)")?;
+        } else {
+            writeln!(f, "Key conditions:    {}", self.key_conditions.join(" AND "))?;
+        }
+        if self.residual_filters.is_empty() {
+            writeln!(f, "Residual filters:  (none)")?;
+        } else {
+            writeln!(f, "Residual filters:  {}", self.residual_filters.join(" AND "))?;
+        }
+        if let Some(sort) = &self.sort {
+            writeln!(f, "Sort:              {}", sort)?;
+        }
+        if let Some(aggregation) = &self.aggregation {
+            writeln!(f, "Aggregation:       {}", aggregation)?;
+        }
+        writeln!(
+            f,
+            "Limit pushed down: {}",
+            if self.limit_pushed_down { "yes" } else { "no" }
+        )?;
+        write!(f, "Estimated scanned: {}", self.estimated_scanned)?;
+        for warning in &self.warnings {
+            write!(f, "\nWarning:           {}", warning)?;
+        }
+        Ok(())
+    }
 }
 
 impl Database {
@@ -74,213 +257,87 @@ impl Database {
     /// db.execute_statement(sql).unwrap();
     /// ```
     pub fn execute_statement(&self, sql: &str) -> Result<ExecuteStatementResponse> {
-        // Parse the SQL statement
-        let statement = PartiQLParser::parse(sql)?;
+        self.execute(ExecuteStatementRequest::new(sql))
+    }
+
+    /// Execute a PartiQL statement with bound parameters
+    ///
+    /// Parsed statements are cached by statement text, so repeated executions with
+    /// different parameters only pay for binding.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use kstone_api::{Database, ExecuteStatementRequest, KeystoneValue};
+    /// use tempfile::TempDir;
+    ///
+    /// let dir = TempDir::new().unwrap();
+    /// let db = Database::create(dir.path()).unwrap();
+    ///
+    /// let request = ExecuteStatementRequest::new("SELECT * FROM users WHERE pk = ? AND age > :min_age")
+    ///     .with_parameters(vec![KeystoneValue::string("user#123")])
+    ///     .with_named_parameter("min_age", KeystoneValue::number(18));
+    /// let response = db.execute(request).unwrap();
+    /// ```
+    pub fn execute(&self, request: ExecuteStatementRequest) -> Result<ExecuteStatementResponse> {
+        // Parse (or reuse) the statement and bind its parameters
+        let statement = self
+            .statements
+            .get_or_parse(&request.sql)?
+            .bind(&request.parameters)?;
 
         // Execute based on statement type
         match statement {
-            PartiQLStatement::Select(select_stmt) => {
-                // Translate SELECT to Query or Scan
-                let translation = PartiQLTranslator::translate_select(&select_stmt)?;
-
-                match translation {
-                    SelectTranslation::Query {
-                        pk,
-                        sk_condition,
-                        index_name,
-                        forward,
-                    } => {
-                        // Execute Query operation
-                        let mut query = Query::new(&pk);
-
-                        if let Some(index) = index_name {
-                            query = query.index(&index);
-                        }
-
-                        // Add sort key condition if present
-                        if let Some(sk_cond) = sk_condition {
-                            query = match sk_cond {
-                                SortKeyConditionType::Equal(sk) => query.sk_eq(&sk),
-                                SortKeyConditionType::LessThan(sk) => query.sk_lt(&sk),
-                                SortKeyConditionType::LessThanOrEqual(sk) => {
-                                    query.sk_lte(&sk)
-                                }
-                                SortKeyConditionType::GreaterThan(sk) => {
-                                    query.sk_gt(&sk)
-                                }
-                                SortKeyConditionType::GreaterThanOrEqual(sk) => {
-                                    query.sk_gte(&sk)
-                                }
-                                SortKeyConditionType::Between(low, high) => {
-                                    query.sk_between(&low, &high)
-                                }
-                            };
-                        }
-
-                        // Set scan direction
-                        query = query.forward(forward);
-
-                        // Apply LIMIT - if both LIMIT and OFFSET, fetch enough records
-                        if let Some(limit) = select_stmt.limit {
-                            let fetch_limit = if let Some(offset) = select_stmt.offset {
-                                limit + offset
-                            } else {
-                                limit
-                            };
-                            query = query.limit(fetch_limit);
-                        }
-
-                        // Execute query
-                        let mut response = self.query(query)?;
-
-                        // Apply OFFSET if specified (by skipping items)
-                        if let Some(offset) = select_stmt.offset {
-                            if offset < response.items.len() {
-                                response.items = response.items.into_iter().skip(offset).collect();
-                                response.count = response.items.len();
-                            } else {
-                                // Offset is beyond results, return empty
-                                response.items.clear();
-                                response.count = 0;
-                            }
-                        }
-
-                        // Apply LIMIT if specified (truncate after offset)
-                        if let Some(limit) = select_stmt.limit {
-                            if response.items.len() > limit {
-                                response.items.truncate(limit);
-                                response.count = limit;
-                            }
-                        }
-
-                        // Apply projection
-                        response.items = apply_projection(response.items, &select_stmt.select_list);
-                        response.count = response.items.len();
-
-                        Ok(ExecuteStatementResponse::Select {
-                            items: response.items,
-                            count: response.count,
-                            scanned_count: response.scanned_count,
-                            last_key: response.last_key,
-                        })
-                    }
-                    SelectTranslation::MultiGet { keys, index_name } => {
-                        // Execute multiple get operations
-                        // For now, we'll execute a query for each pk and merge results
-                        // TODO: Optimize with batch_get when available
-                        let mut all_items = Vec::new();
-                        let mut total_scanned = 0;
-
-                        for pk in keys {
-                            let mut query = Query::new(&pk);
-                            if let Some(ref index) = index_name {
-                                query = query.index(index);
-                            }
-
-                            let response = self.query(query)?;
-                            total_scanned += response.scanned_count;
-                            all_items.extend(response.items);
-                        }
-
-                        // Apply OFFSET if specified
-                        if let Some(offset) = select_stmt.offset {
-                            if offset < all_items.len() {
-                                all_items = all_items.into_iter().skip(offset).collect();
-                            } else {
-                                all_items.clear();
-                            }
-                        }
-
-                        // Apply LIMIT if specified
-                        if let Some(limit) = select_stmt.limit {
-                            all_items.truncate(limit);
-                        }
-
-                        // Apply projection
-                        all_items = apply_projection(all_items, &select_stmt.select_list);
-
-                        Ok(ExecuteStatementResponse::Select {
-                            count: all_items.len(),
-                            scanned_count: total_scanned,
-                            items: all_items,
-                            last_key: None,
-                        })
-                    }
-                    SelectTranslation::Scan { filter_conditions } => {
-                        // Execute Scan operation
-                        let mut scan = Scan::new();
-
-                        // Apply LIMIT - if both LIMIT and OFFSET, fetch enough records
-                        if let Some(limit) = select_stmt.limit {
-                            let fetch_limit = if let Some(offset) = select_stmt.offset {
-                                limit + offset
-                            } else {
-                                limit
-                            };
-                            scan = scan.limit(fetch_limit);
-                        }
-
-                        let mut response = self.scan(scan)?;
-
-                        // Apply filter conditions (WHERE clause filtering)
-                        if !filter_conditions.is_empty() {
-                            response.items = apply_filter_conditions(response.items, &filter_conditions);
-                            response.count = response.items.len();
-                        }
-
-                        // Apply OFFSET if specified (by skipping items)
-                        if let Some(offset) = select_stmt.offset {
-                            if offset < response.items.len() {
-                                response.items = response.items.into_iter().skip(offset).collect();
-                                response.count = response.items.len();
-                            } else {
-                                // Offset is beyond results, return empty
-                                response.items.clear();
-                                response.count = 0;
-                            }
-                        }
+            PartiQLStatement::Select(select_stmt) => self.execute_select(&select_stmt),
+            PartiQLStatement::Explain(select_stmt) => Ok(ExecuteStatementResponse::Explain {
+                plan: self.explain_select(&select_stmt)?,
+            }),
+            PartiQLStatement::Insert(insert_stmt) => {
+                // Translate INSERT to Put operation
+                let translation = PartiQLTranslator::translate_insert(&insert_stmt)?;
+                let key = self.table_key(&insert_stmt.table_name, translation.key);
 
-                        // Apply LIMIT if specified (truncate after offset)
-                        if let Some(limit) = select_stmt.limit {
-                            if response.items.len() > limit {
-                                response.items.truncate(limit);
-                                response.count = limit;
-                            }
-                        }
+                // Execute put (success is false if ON CONFLICT DO NOTHING kept an existing item)
+                let success = self.insert_item(key, translation.item, insert_stmt.on_conflict)?;
 
-                        // Apply projection
-                        response.items = apply_projection(response.items, &select_stmt.select_list);
-                        response.count = response.items.len();
+                Ok(ExecuteStatementResponse::Insert { success })
+            }
+            PartiQLStatement::InsertSelect(insert_stmt) => {
+                let rows = match self.execute_select(&insert_stmt.select)? {
+                    ExecuteStatementResponse::Select { items, .. } => items,
+                    _ => unreachable!("execute_select returns a Select response"),
+                };
 
-                        Ok(ExecuteStatementResponse::Select {
-                            items: response.items,
-                            count: response.count,
-                            scanned_count: response.scanned_count,
-                            last_key: response.last_key,
+                // Each row is inserted like an INSERT VALUE map, so it must carry pk/sk.
+                // Rows are translated up front so a bad row fails before anything is written.
+                let translations = rows
+                    .into_iter()
+                    .map(|row| {
+                        PartiQLTranslator::translate_insert(&InsertStatement {
+                            table_name: insert_stmt.table_name.clone(),
+                            value: SqlValue::from_kstone_value(&Value::M(row)),
+                            on_conflict: insert_stmt.on_conflict,
                         })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let mut inserted = 0;
+                for translation in translations {
+                    let key = self.table_key(&insert_stmt.table_name, translation.key);
+                    if self.insert_item(key, translation.item, insert_stmt.on_conflict)? {
+                        inserted += 1;
                     }
                 }
-            }
-            PartiQLStatement::Insert(insert_stmt) => {
-                // Translate INSERT to Put operation
-                let translation = PartiQLTranslator::translate_insert(&insert_stmt)?;
-
-                // Execute put
-                let pk = translation.key.pk.as_ref();
-                if let Some(sk) = translation.key.sk.as_ref() {
-                    self.put_with_sk(pk, sk.as_ref(), translation.item)?;
-                } else {
-                    self.put(pk, translation.item)?;
-                }
 
-                Ok(ExecuteStatementResponse::Insert { success: true })
+                Ok(ExecuteStatementResponse::InsertSelect { inserted })
             }
             PartiQLStatement::Update(update_stmt) => {
                 // Translate UPDATE to Update operation
                 let translation = PartiQLTranslator::translate_update(&update_stmt)?;
 
                 // Build Update request
-                let mut update = Update::new_from_key(translation.key);
+                let key = self.table_key(&update_stmt.table_name, translation.key);
+                let mut update = Update::new_from_key(key);
                 update = update.expression(&translation.expression);
 
                 // Add placeholder values
@@ -300,12 +357,7 @@ impl Database {
                 let translation = PartiQLTranslator::translate_delete(&delete_stmt)?;
 
                 // Execute delete
-                let pk = translation.key.pk.as_ref();
-                if let Some(sk) = translation.key.sk.as_ref() {
-                    self.delete_with_sk(pk, sk.as_ref())?;
-                } else {
-                    self.delete(pk)?;
-                }
+                self.delete_key(self.table_key(&delete_stmt.table_name, translation.key))?;
 
                 Ok(ExecuteStatementResponse::Delete { success: true })
             }
@@ -313,24 +365,692 @@ impl Database {
     }
 }
 
+impl Database {
+    /// Execute INSERT, UPDATE and DELETE statements atomically
+    ///
+    /// Statements map one-to-one onto `transact_write` operations. Non-key WHERE
+    /// conditions on UPDATE and DELETE become the operation's condition; if any
+    /// fails nothing is written and the `TransactionCanceled` error lists one
+    /// cancellation reason per statement, in order. INSERT supports `REPLACE INTO`
+    /// and `UPSERT INTO` but not `ON CONFLICT DO NOTHING` or `INSERT ... SELECT`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use kstone_api::Database;
+    /// use tempfile::TempDir;
+    ///
+    /// let dir = TempDir::new().unwrap();
+    /// let db = Database::create(dir.path()).unwrap();
+    ///
+    /// db.execute_transaction(&[
+    ///     "UPDATE accounts SET balance = balance - 10 WHERE pk = 'alice' AND balance >= 10",
+    ///     "UPDATE accounts SET balance = balance + 10 WHERE pk = 'bob'",
+    /// ])
+    /// .unwrap();
+    /// ```
+    pub fn execute_transaction<S>(&self, statements: &[S]) -> Result<TransactWriteResponse>
+    where
+        S: Clone + Into<ExecuteStatementRequest>,
+    {
+        if statements.is_empty() {
+            return Err(Error::InvalidQuery(
+                "Transaction must contain at least one statement".into(),
+            ));
+        }
+
+        let mut request = TransactWriteRequest::new();
+        for (i, statement) in statements.iter().enumerate() {
+            let statement: ExecuteStatementRequest = statement.clone().into();
+            let parsed = self
+                .statements
+                .get_or_parse(&statement.sql)?
+                .bind(&statement.parameters)?;
+
+            // Placeholders are shared across the transaction, so scope them per statement
+            let scope = format!("t{}_", i);
+            let prefix = format!(":{}", scope);
+            let mut names = Vec::new();
+            let mut values = Vec::new();
+
+            let op = match parsed {
+                PartiQLStatement::Insert(insert_stmt) => {
+                    let translation = PartiQLTranslator::translate_insert(&insert_stmt)?;
+                    match insert_stmt.on_conflict {
+                        ConflictAction::Replace => TransactWriteOp::Put {
+                            key: self.table_key(&insert_stmt.table_name, translation.key),
+                            item: translation.item,
+                            condition: None,
+                        },
+                        ConflictAction::DoUpdate if !translation.item.is_empty() => {
+                            let (update_expression, merge_names, merge_values) =
+                                merge_update_expression(&translation.item, &scope);
+                            names = merge_names;
+                            values = merge_values;
+                            TransactWriteOp::Update {
+                                key: self.table_key(&insert_stmt.table_name, translation.key),
+                                update_expression,
+                                condition: None,
+                            }
+                        }
+                        ConflictAction::DoUpdate | ConflictAction::DoNothing => {
+                            return Err(Error::InvalidQuery(format!(
+                                "Transaction statement {} has an unsupported ON CONFLICT action",
+                                i
+                            )));
+                        }
+                    }
+                }
+                PartiQLStatement::Update(update_stmt) => {
+                    let translation = PartiQLTranslator::translate_update(&update_stmt)?;
+
+                    // Rename longest placeholders first so `:v1` never matches inside `:v10`
+                    let mut placeholders: Vec<_> = translation.values.into_iter().collect();
+                    placeholders.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+                    let mut update_expression = translation.expression;
+                    for (placeholder, value) in placeholders {
+                        let scoped = format!("{}{}", prefix, &placeholder[1..]);
+                        update_expression = update_expression.replace(&placeholder, &scoped);
+                        values.push((scoped, value));
+                    }
+
+                    TransactWriteOp::Update {
+                        key: self.table_key(&update_stmt.table_name, translation.key),
+                        update_expression,
+                        condition: condition_expression(
+                            &update_stmt.where_clause.conditions,
+                            &prefix,
+                            &mut values,
+                        ),
+                    }
+                }
+                PartiQLStatement::Delete(delete_stmt) => {
+                    let translation = PartiQLTranslator::translate_delete(&delete_stmt)?;
+                    TransactWriteOp::Delete {
+                        key: self.table_key(&delete_stmt.table_name, translation.key),
+                        condition: condition_expression(
+                            &delete_stmt.where_clause.conditions,
+                            &prefix,
+                            &mut values,
+                        ),
+                    }
+                }
+                PartiQLStatement::Select(_)
+                | PartiQLStatement::Explain(_)
+                | PartiQLStatement::InsertSelect(_) => {
+                    return Err(Error::InvalidQuery(format!(
+                        "Transaction statement {} must be INSERT, UPDATE or DELETE",
+                        i
+                    )));
+                }
+            };
+
+            request.operations.push(op);
+            for (placeholder, name) in names {
+                request = request.name(placeholder, name);
+            }
+            for (placeholder, value) in values {
+                request = request.value(placeholder, value);
+            }
+        }
+
+        self.transact_write(request)
+    }
+
+    /// Write an inserted item according to its conflict action
+    ///
+    /// Returns false if `DoNothing` found an existing item. The existence check
+    /// is a separate read, so concurrent writers to the same key can interleave.
+    fn insert_item(&self, key: Key, item: Item, on_conflict: ConflictAction) -> Result<bool> {
+        let pk = key.pk.as_ref();
+        let sk = key.sk.as_deref();
+
+        let existing = || match sk {
+            Some(sk) => self.get_with_sk(pk, sk),
+            None => self.get(pk),
+        };
+
+        match on_conflict {
+            ConflictAction::DoNothing if existing()?.is_some() => Ok(false),
+            // Nothing to merge: only create the item if it is missing
+            ConflictAction::DoUpdate if item.is_empty() => {
+                if existing()?.is_none() {
+                    self.put_key(key.clone(), item)?;
+                }
+                Ok(true)
+            }
+            ConflictAction::DoUpdate => {
+                let (expression, names, values) = merge_update_expression(&item, "");
+                let mut update = Update::new_from_key(key.clone()).expression(expression);
+                for (placeholder, name) in names {
+                    update = update.name(placeholder, name);
+                }
+                for (placeholder, value) in values {
+                    update = update.value(placeholder, value);
+                }
+                self.update(update)?;
+                Ok(true)
+            }
+            ConflictAction::Replace | ConflictAction::DoNothing => {
+                self.put_key(key.clone(), item)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Named table a statement's table name refers to, if one exists
+    ///
+    /// Any other name addresses the default table.
+    fn named_table<'a>(&self, table_name: &'a str) -> Option<&'a str> {
+        self.table_schema(table_name).map(|_| table_name)
+    }
+
+    /// Scope a translated key to the statement's table
+    fn table_key(&self, table_name: &str, key: Key) -> Key {
+        match self.named_table(table_name) {
+            Some(table) => encode_table_key(table, &key),
+            None => key,
+        }
+    }
+
+    /// Execute a SELECT and return its (filtered, sorted, projected) items
+    fn execute_select(&self, select_stmt: &SelectStatement) -> Result<ExecuteStatementResponse> {
+        // Translate SELECT to Query or Scan
+        let select_stmt = &*self.route_select(select_stmt);
+        let table = self.named_table(&select_stmt.table_name);
+        let translation = PartiQLTranslator::translate_select(select_stmt)?;
+        let warnings = full_scan_warning(&translation).into_iter().collect();
+
+        let (items, scanned_count, last_key, filter_conditions, sort_by) = match translation {
+            SelectTranslation::Query {
+                pk,
+                sk_condition,
+                index_name,
+                forward,
+                filter_conditions,
+                sort_by,
+            } => {
+                // Execute Query operation
+                let mut query = build_query(&pk, sk_condition, index_name, forward, table);
+
+                // Apply LIMIT - if both LIMIT and OFFSET, fetch enough records
+                if let Some(fetch_limit) = pushdown_limit(
+                    select_stmt,
+                    !filter_conditions.is_empty() || sort_by.is_some(),
+                ) {
+                    query = query.limit(fetch_limit);
+                }
+
+                // Execute query
+                let response = self.query(query)?;
+                (
+                    response.items,
+                    response.scanned_count,
+                    response.last_key,
+                    filter_conditions,
+                    sort_by,
+                )
+            }
+            SelectTranslation::MultiGet {
+                keys,
+                index_name,
+                filter_conditions,
+                sort_by,
+            } => {
+                // Execute multiple get operations
+                // For now, we'll execute a query for each pk and merge results
+                // TODO: Optimize with batch_get when available
+                let mut all_items = Vec::new();
+                let mut total_scanned = 0;
+
+                for pk in keys {
+                    let query = build_query(&pk, None, index_name.clone(), true, table);
+                    let response = self.query(query)?;
+                    total_scanned += response.scanned_count;
+                    all_items.extend(response.items);
+                }
+
+                (all_items, total_scanned, None, filter_conditions, sort_by)
+            }
+            SelectTranslation::Scan {
+                filter_conditions,
+                sort_by,
+            } => {
+                // Execute Scan operation
+                let mut scan = Scan::new();
+                if let Some(table) = table {
+                    scan = scan.in_table(table);
+                }
+
+                // Apply LIMIT - if both LIMIT and OFFSET, fetch enough records
+                if let Some(fetch_limit) = pushdown_limit(
+                    select_stmt,
+                    !filter_conditions.is_empty() || sort_by.is_some(),
+                ) {
+                    scan = scan.limit(fetch_limit);
+                }
+
+                let response = self.scan(scan)?;
+                (
+                    response.items,
+                    response.scanned_count,
+                    response.last_key,
+                    filter_conditions,
+                    sort_by,
+                )
+            }
+        };
+
+        let items = if let SelectList::Aggregates(exprs) = &select_stmt.select_list {
+            // Fold matching items into per-group accumulators one at a time
+            let mut aggregator = Aggregator::new(exprs.clone(), select_stmt.group_by.clone());
+            for item in items
+                .into_iter()
+                .filter(|item| matches_filter_conditions(item, &filter_conditions))
+            {
+                aggregator.add(&item);
+            }
+
+            // ORDER BY applies to the result rows (group attributes or aggregates)
+            let mut rows = aggregator.finish();
+            if let Some(order_by) = &select_stmt.order_by {
+                apply_order_by(&mut rows, order_by);
+            }
+            rows
+        } else {
+            // Apply residual filter conditions (WHERE clause filtering)
+            let mut items = apply_filter_conditions(items, &filter_conditions);
+
+            // Apply ORDER BY on a non-key attribute
+            if let Some(order_by) = sort_by {
+                apply_order_by(&mut items, &order_by);
+            }
+            items
+        };
+
+        // Apply OFFSET and LIMIT
+        let items: Vec<Item> = items
+            .into_iter()
+            .skip(select_stmt.offset.unwrap_or(0))
+            .take(select_stmt.limit.unwrap_or(usize::MAX))
+            .collect();
+
+        // Apply projection
+        let items = apply_projection(items, &select_stmt.select_list);
+
+        Ok(ExecuteStatementResponse::Select {
+            count: items.len(),
+            scanned_count,
+            items,
+            last_key,
+            warnings,
+        })
+    }
+
+    /// Route a SELECT without a partition key condition to a matching GSI
+    ///
+    /// Named tables have no indexes, so their statements are never routed.
+    fn route_select<'a>(&self, select_stmt: &'a SelectStatement) -> Cow<'a, SelectStatement> {
+        let needs_routing = select_stmt.index_name.is_none()
+            && self.named_table(&select_stmt.table_name).is_none()
+            && select_stmt
+                .where_clause
+                .as_ref()
+                .map_or(false, |wc| !wc.has_condition("pk"));
+
+        let routed = if needs_routing {
+            self.index_schema()
+                .and_then(|schema| PartiQLTranslator::route_to_index(select_stmt, &schema))
+        } else {
+            None
+        };
+
+        match routed {
+            Some(routed) => Cow::Owned(routed),
+            None => Cow::Borrowed(select_stmt),
+        }
+    }
+
+    /// Describe how a SELECT would be executed (EXPLAIN)
+    ///
+    /// Query paths run their key lookup to count the items read; full scans
+    /// are costed from stored record counts without reading any items.
+    fn explain_select(&self, select_stmt: &SelectStatement) -> Result<QueryPlan> {
+        let select_stmt = &*self.route_select(select_stmt);
+        let table = self.named_table(&select_stmt.table_name);
+        let translation = PartiQLTranslator::translate_select(select_stmt)?;
+        let warnings = full_scan_warning(&translation).into_iter().collect();
+
+        let key_conditions = match DynamoDBValidator::validate_select(select_stmt)? {
+            QueryType::Query {
+                pk_condition,
+                sk_condition,
+            } => std::iter::once(pk_condition)
+                .chain(sk_condition)
+                .map(|c| describe_condition(&c))
+                .collect(),
+            QueryType::Scan => Vec::new(),
+        };
+
+        let (access_path, estimated_scanned, filter_conditions, sort_by) = match translation {
+            SelectTranslation::Query {
+                pk,
+                sk_condition,
+                index_name,
+                forward,
+                filter_conditions,
+                sort_by,
+            } => {
+                let access_path = match &index_name {
+                    Some(index_name) => AccessPath::IndexQuery {
+                        index_name: index_name.clone(),
+                    },
+                    None => AccessPath::TableQuery,
+                };
+                let mut query = build_query(&pk, sk_condition, index_name, forward, table);
+                if let Some(fetch_limit) = pushdown_limit(
+                    select_stmt,
+                    !filter_conditions.is_empty() || sort_by.is_some(),
+                ) {
+                    query = query.limit(fetch_limit);
+                }
+                let scanned = self.query(query)?.scanned_count as u64;
+                (access_path, scanned, filter_conditions, sort_by)
+            }
+            SelectTranslation::MultiGet {
+                keys,
+                index_name,
+                filter_conditions,
+                sort_by,
+            } => {
+                let mut scanned = 0;
+                for pk in &keys {
+                    let query = build_query(pk, None, index_name.clone(), true, table);
+                    scanned += self.query(query)?.scanned_count as u64;
+                }
+                let access_path = AccessPath::MultiQuery {
+                    partitions: keys.len(),
+                    index_name,
+                };
+                (access_path, scanned, filter_conditions, sort_by)
+            }
+            SelectTranslation::Scan {
+                filter_conditions,
+                sort_by,
+            } => {
+                let stored = self.estimated_item_count();
+                let scanned = match pushdown_limit(
+                    select_stmt,
+                    !filter_conditions.is_empty() || sort_by.is_some(),
+                ) {
+                    Some(fetch_limit) => stored.min(fetch_limit as u64),
+                    None => stored,
+                };
+                (AccessPath::FullScan, scanned, filter_conditions, sort_by)
+            }
+        };
+
+        let has_residual = !filter_conditions.is_empty() || sort_by.is_some();
+        let aggregation = match &select_stmt.select_list {
+            SelectList::Aggregates(exprs) => Some(describe_aggregation(exprs, &select_stmt.group_by)),
+            _ => None,
+        };
+
+        // Aggregate queries sort their result rows by the statement's ORDER BY
+        let sort = if aggregation.is_some() {
+            select_stmt.order_by.as_ref()
+        } else {
+            sort_by.as_ref()
+        };
+
+        Ok(QueryPlan {
+            access_path,
+            key_conditions,
+            residual_filters: filter_conditions.iter().map(describe_condition).collect(),
+            sort: sort.map(describe_order_by),
+            aggregation,
+            limit_pushed_down: pushdown_limit(select_stmt, has_residual).is_some(),
+            estimated_scanned,
+            warnings,
+        })
+    }
+}
+
+/// Build a `SET` expression that writes every attribute of `item`
+///
+/// Names and values use `#{scope}aN` / `:{scope}aN` placeholders so attribute
+/// names never collide with reserved words.
+fn merge_update_expression(
+    item: &Item,
+    scope: &str,
+) -> (String, Vec<(String, String)>, Vec<(String, Value)>) {
+    let mut attributes: Vec<_> = item.iter().collect();
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut assignments = Vec::new();
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for (i, (name, value)) in attributes.into_iter().enumerate() {
+        let name_placeholder = format!("#{}a{}", scope, i + 1);
+        let value_placeholder = format!(":{}a{}", scope, i + 1);
+        assignments.push(format!("{} = {}", name_placeholder, value_placeholder));
+        names.push((name_placeholder, name.clone()));
+        values.push((value_placeholder, value.clone()));
+    }
+
+    (format!("SET {}", assignments.join(", ")), names, values)
+}
+
+/// Build a condition expression from the non-key WHERE conditions of a write
+///
+/// Values are added to `values` under placeholders starting with `prefix`.
+fn condition_expression(
+    conditions: &[Condition],
+    prefix: &str,
+    values: &mut Vec<(String, Value)>,
+) -> Option<String> {
+    let mut next_placeholder = |value: &SqlValue| {
+        let placeholder = format!("{}c{}", prefix, values.len() + 1);
+        values.push((placeholder.clone(), value.to_kstone_value()));
+        placeholder
+    };
+
+    let clauses: Vec<String> = conditions
+        .iter()
+        .filter(|c| !c.is_key_attribute())
+        .map(|c| {
+            let op = match c.operator {
+                CompareOp::Equal => "=",
+                CompareOp::NotEqual => "<>",
+                CompareOp::LessThan => "<",
+                CompareOp::LessThanOrEqual => "<=",
+                CompareOp::GreaterThan => ">",
+                CompareOp::GreaterThanOrEqual => ">=",
+                CompareOp::In => {
+                    let options: Vec<String> = match &c.value {
+                        SqlValue::List(options) => options
+                            .iter()
+                            .map(|v| format!("{} = {}", c.attribute, next_placeholder(v)))
+                            .collect(),
+                        other => vec![format!("{} = {}", c.attribute, next_placeholder(other))],
+                    };
+                    return format!("({})", options.join(" OR "));
+                }
+                CompareOp::Between => {
+                    let (low, high) = match &c.value {
+                        SqlValue::List(bounds) if bounds.len() == 2 => (&bounds[0], &bounds[1]),
+                        other => (other, other),
+                    };
+                    let low = next_placeholder(low);
+                    let high = next_placeholder(high);
+                    return format!("({} >= {} AND {} <= {})", c.attribute, low, c.attribute, high);
+                }
+            };
+            format!("{} {} {}", c.attribute, op, next_placeholder(&c.value))
+        })
+        .collect();
+
+    if clauses.is_empty() {
+        None
+    } else {
+        Some(clauses.join(" AND "))
+    }
+}
+
+/// Render a WHERE condition for EXPLAIN output
+fn describe_condition(condition: &Condition) -> String {
+    let op = match condition.operator {
+        CompareOp::Equal => "=",
+        CompareOp::NotEqual => "<>",
+        CompareOp::LessThan => "<",
+        CompareOp::LessThanOrEqual => "<=",
+        CompareOp::GreaterThan => ">",
+        CompareOp::GreaterThanOrEqual => ">=",
+        CompareOp::In => {
+            let values = match &condition.value {
+                SqlValue::List(values) => values.iter().map(describe_value).collect::<Vec<_>>(),
+                other => vec![describe_value(other)],
+            };
+            return format!("{} IN ({})", condition.attribute, values.join(", "));
+        }
+        CompareOp::Between => {
+            if let SqlValue::List(values) = &condition.value {
+                if let [low, high] = values.as_slice() {
+                    return format!(
+                        "{} BETWEEN {} AND {}",
+                        condition.attribute,
+                        describe_value(low),
+                        describe_value(high)
+                    );
+                }
+            }
+            return format!("{} BETWEEN {}", condition.attribute, describe_value(&condition.value));
+        }
+    };
+    format!("{} {} {}", condition.attribute, op, describe_value(&condition.value))
+}
+
+/// Render a literal in PartiQL syntax
+fn describe_value(value: &SqlValue) -> String {
+    match value {
+        SqlValue::Number(n) => n.clone(),
+        SqlValue::String(s) => format!("'{}'", s.replace('\'', "''")),
+        SqlValue::Boolean(b) => b.to_string(),
+        SqlValue::Null => "NULL".to_string(),
+        SqlValue::List(values) => format!(
+            "[{}]",
+            values.iter().map(describe_value).collect::<Vec<_>>().join(", ")
+        ),
+        SqlValue::Map(map) => {
+            let mut entries: Vec<_> = map
+                .iter()
+                .map(|(k, v)| format!("'{}': {}", k, describe_value(v)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        SqlValue::Parameter(name) => name.clone(),
+    }
+}
+
+fn describe_order_by(order_by: &OrderBy) -> String {
+    format!(
+        "{} {}",
+        order_by.attribute,
+        if order_by.ascending { "ASC" } else { "DESC" }
+    )
+}
+
+fn describe_aggregation(exprs: &[SelectExpr], group_by: &[String]) -> String {
+    let outputs: Vec<String> = exprs
+        .iter()
+        .map(|expr| match expr {
+            SelectExpr::Attribute(attr) => attr.clone(),
+            SelectExpr::Aggregate(agg) => agg.output_name(),
+        })
+        .collect();
+    if group_by.is_empty() {
+        outputs.join(", ")
+    } else {
+        format!("{} GROUP BY {}", outputs.join(", "), group_by.join(", "))
+    }
+}
+
+/// Warning for a SELECT whose WHERE clause matched no key or index
+fn full_scan_warning(translation: &SelectTranslation) -> Option<String> {
+    match translation {
+        SelectTranslation::Scan {
+            filter_conditions, ..
+        } if !filter_conditions.is_empty() => Some(
+            "No partition key or index matches the WHERE clause; scanning the full table"
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// Build a Query for a single partition
+fn build_query(
+    pk: &[u8],
+    sk_condition: Option<SortKeyConditionType>,
+    index_name: Option<String>,
+    forward: bool,
+    table: Option<&str>,
+) -> Query {
+    let mut query = Query::new(pk);
+
+    if let Some(table) = table {
+        query = query.in_table(table);
+    }
+
+    if let Some(index) = index_name {
+        query = query.index(&index);
+    }
+
+    // Add sort key condition if present
+    if let Some(sk_cond) = sk_condition {
+        query = match sk_cond {
+            SortKeyConditionType::Equal(sk) => query.sk_eq(&sk),
+            SortKeyConditionType::LessThan(sk) => query.sk_lt(&sk),
+            SortKeyConditionType::LessThanOrEqual(sk) => query.sk_lte(&sk),
+            SortKeyConditionType::GreaterThan(sk) => query.sk_gt(&sk),
+            SortKeyConditionType::GreaterThanOrEqual(sk) => query.sk_gte(&sk),
+            SortKeyConditionType::Between(low, high) => query.sk_between(&low, &high),
+        };
+    }
+
+    // Set scan direction
+    query.forward(forward)
+}
+
+/// LIMIT (plus OFFSET) to push into the fetch
+///
+/// Only possible when nothing is filtered, re-ordered or aggregated after the fetch.
+fn pushdown_limit(select_stmt: &SelectStatement, has_residual: bool) -> Option<usize> {
+    match select_stmt.limit {
+        Some(limit) if !has_residual && !select_stmt.is_aggregate() => {
+            Some(limit + select_stmt.offset.unwrap_or(0))
+        }
+        _ => None,
+    }
+}
+
 /// Apply projection to filter items to only include selected attributes
 fn apply_projection(
     items: Vec<Item>,
-    select_list: &kstone_core::partiql::SelectList,
+    select_list: &SelectList,
 ) -> Vec<Item> {
-    use kstone_core::partiql::SelectList;
-    use std::collections::HashMap;
-
     match select_list {
-        SelectList::All => items,
+        // Aggregate rows are already shaped by the SELECT list
+        SelectList::All | SelectList::Aggregates(_) => items,
         SelectList::Attributes(attrs) => {
             items
                 .into_iter()
                 .map(|item| {
                     let mut projected = HashMap::new();
                     for attr in attrs {
-                        if let Some(value) = item.get(attr) {
-                            projected.insert(attr.clone(), value.clone());
+                        if let Some(value) = resolve_attribute_path(&item, attr) {
+                            insert_attribute_path(&mut projected, attr, value.clone());
                         }
                     }
                     projected
@@ -340,69 +1060,120 @@ fn apply_projection(
     }
 }
 
-/// Apply filter conditions to items (for Scan filtering)
+/// Insert a value at a dotted attribute path, creating intermediate maps
+fn insert_attribute_path(item: &mut Item, path: &str, value: crate::Value) {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let last = match segments.pop() {
+        Some(last) => last,
+        None => return,
+    };
+
+    let mut map = item;
+    for segment in segments {
+        let entry = map
+            .entry(segment.to_string())
+            .or_insert_with(|| crate::Value::M(std::collections::HashMap::new()));
+        map = match entry {
+            crate::Value::M(inner) => inner,
+            _ => return,
+        };
+    }
+    map.insert(last.to_string(), value);
+}
+
+/// Sort items by an attribute path (items missing the attribute sort last)
+fn apply_order_by(items: &mut [Item], order_by: &kstone_core::partiql::OrderBy) {
+    use std::cmp::Ordering;
+
+    items.sort_by(|a, b| {
+        match (
+            resolve_attribute_path(a, &order_by.attribute),
+            resolve_attribute_path(b, &order_by.attribute),
+        ) {
+            (Some(va), Some(vb)) => {
+                let ordering = compare_values(va, vb);
+                if order_by.ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+}
+
+/// Apply filter conditions to items (WHERE clause filtering)
 fn apply_filter_conditions(
     items: Vec<Item>,
     conditions: &[kstone_core::partiql::Condition],
 ) -> Vec<Item> {
-    use kstone_core::partiql::CompareOp;
-
     items
         .into_iter()
-        .filter(|item| {
-            // Item must match ALL conditions (AND logic)
-            conditions.iter().all(|condition| {
-                // Get attribute value from item
-                let item_value = match item.get(&condition.attribute) {
-                    Some(v) => v,
-                    None => return false, // Attribute not present, doesn't match
-                };
+        .filter(|item| matches_filter_conditions(item, conditions))
+        .collect()
+}
 
-                // Compare based on operator
-                match &condition.operator {
-                    CompareOp::Equal => compare_values_eq(item_value, &condition.value),
-                    CompareOp::NotEqual => !compare_values_eq(item_value, &condition.value),
-                    CompareOp::LessThan => compare_values_lt(item_value, &condition.value),
-                    CompareOp::LessThanOrEqual => {
-                        compare_values_lt(item_value, &condition.value)
-                            || compare_values_eq(item_value, &condition.value)
-                    }
-                    CompareOp::GreaterThan => {
-                        !compare_values_lt(item_value, &condition.value)
-                            && !compare_values_eq(item_value, &condition.value)
-                    }
-                    CompareOp::GreaterThanOrEqual => {
-                        !compare_values_lt(item_value, &condition.value)
-                    }
-                    CompareOp::In => {
-                        // Check if item_value is in the list
-                        if let kstone_core::partiql::SqlValue::List(values) = &condition.value {
-                            values.iter().any(|v| compare_values_eq(item_value, v))
-                        } else {
-                            false
-                        }
-                    }
-                    CompareOp::Between => {
-                        // BETWEEN x AND y
-                        if let kstone_core::partiql::SqlValue::List(values) = &condition.value {
-                            if values.len() == 2 {
-                                let lower = &values[0];
-                                let upper = &values[1];
-                                (compare_values_eq(item_value, lower)
-                                    || !compare_values_lt(item_value, lower))
-                                    && (compare_values_eq(item_value, upper)
-                                        || compare_values_lt(item_value, upper))
-                            } else {
-                                false
-                            }
-                        } else {
-                            false
-                        }
+/// Check an item against filter conditions
+fn matches_filter_conditions(
+    item: &Item,
+    conditions: &[kstone_core::partiql::Condition],
+) -> bool {
+    use kstone_core::partiql::CompareOp;
+
+    // Item must match ALL conditions (AND logic)
+    conditions.iter().all(|condition| {
+        // Get attribute value from item (supports nested paths)
+        let item_value = match resolve_attribute_path(item, &condition.attribute) {
+            Some(v) => v,
+            None => return false, // Attribute not present, doesn't match
+        };
+
+        // Compare based on operator
+        match &condition.operator {
+            CompareOp::Equal => compare_values_eq(item_value, &condition.value),
+            CompareOp::NotEqual => !compare_values_eq(item_value, &condition.value),
+            CompareOp::LessThan => compare_values_lt(item_value, &condition.value),
+            CompareOp::LessThanOrEqual => {
+                compare_values_lt(item_value, &condition.value)
+                    || compare_values_eq(item_value, &condition.value)
+            }
+            CompareOp::GreaterThan => {
+                !compare_values_lt(item_value, &condition.value)
+                    && !compare_values_eq(item_value, &condition.value)
+            }
+            CompareOp::GreaterThanOrEqual => {
+                !compare_values_lt(item_value, &condition.value)
+            }
+            CompareOp::In => {
+                // Check if item_value is in the list
+                if let kstone_core::partiql::SqlValue::List(values) = &condition.value {
+                    values.iter().any(|v| compare_values_eq(item_value, v))
+                } else {
+                    false
+                }
+            }
+            CompareOp::Between => {
+                // BETWEEN x AND y
+                if let kstone_core::partiql::SqlValue::List(values) = &condition.value {
+                    if values.len() == 2 {
+                        let lower = &values[0];
+                        let upper = &values[1];
+                        (compare_values_eq(item_value, lower)
+                            || !compare_values_lt(item_value, lower))
+                            && (compare_values_eq(item_value, upper)
+                                || compare_values_lt(item_value, upper))
+                    } else {
+                        false
                     }
+                } else {
+                    false
                 }
-            })
-        })
-        .collect()
+            }
+        }
+    })
 }
 
 /// Compare two values for equality
@@ -442,7 +1213,7 @@ fn compare_values_lt(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ItemBuilder;
+    use crate::{ItemBuilder, TableSchema};
     use tempfile::TempDir;
 
     #[test]
@@ -827,4 +1598,468 @@ mod tests {
             _ => panic!("Expected Select response"),
         }
     }
+
+    #[test]
+    fn test_execute_statement_query_with_residual_filter() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..10 {
+            db.put_with_sk(
+                b"org#acme",
+                format!("user#{:03}", i).as_bytes(),
+                ItemBuilder::new()
+                    .number("age", 20 + i)
+                    .string("team", if i % 2 == 0 { "red" } else { "blue" })
+                    .build(),
+            )
+            .unwrap();
+        }
+
+        // Key conditions go to the Query, team/age are applied as filters
+        // before LIMIT so the limit counts matching items only
+        let sql = "SELECT * FROM users WHERE pk = 'org#acme' AND sk >= 'user#002' \
+                   AND team IN ('red') AND age BETWEEN 22 AND 28 LIMIT 2";
+        let response = db.execute_statement(sql).unwrap();
+
+        match response {
+            ExecuteStatementResponse::Select { items, count, .. } => {
+                assert_eq!(count, 2);
+                let ages: Vec<_> = items
+                    .iter()
+                    .map(|item| item.get("age").unwrap().clone())
+                    .collect();
+                assert_eq!(
+                    ages,
+                    vec![crate::Value::number(22), crate::Value::number(24)]
+                );
+            }
+            _ => panic!("Expected Select response"),
+        }
+    }
+
+    #[test]
+    fn test_execute_statement_order_by_non_key_with_offset() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let scores = [50, 90, 10, 70, 30];
+        for (i, score) in scores.iter().enumerate() {
+            db.put(
+                format!("player#{}", i).as_bytes(),
+                ItemBuilder::new().number("score", score).build(),
+            )
+            .unwrap();
+        }
+
+        let sql = "SELECT score FROM players WHERE score > 10 ORDER BY score DESC LIMIT 2 OFFSET 1";
+        let response = db.execute_statement(sql).unwrap();
+
+        match response {
+            ExecuteStatementResponse::Select { items, count, .. } => {
+                assert_eq!(count, 2);
+                assert_eq!(items[0].get("score"), Some(&crate::Value::number(70)));
+                assert_eq!(items[1].get("score"), Some(&crate::Value::number(50)));
+            }
+            _ => panic!("Expected Select response"),
+        }
+    }
+
+    #[test]
+    fn test_execute_statement_nested_path() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for (i, city) in ["Paris", "Berlin", "Paris"].iter().enumerate() {
+            let mut address = std::collections::HashMap::new();
+            address.insert("city".to_string(), crate::Value::string(*city));
+            address.insert("zip".to_string(), crate::Value::string(format!("{:05}", i)));
+
+            let mut item = ItemBuilder::new().string("name", format!("User{}", i)).build();
+            item.insert("address".to_string(), crate::Value::map(address));
+            db.put(format!("user#{}", i).as_bytes(), item).unwrap();
+        }
+
+        let sql = "SELECT name, address.zip FROM users WHERE address.city = 'Paris' ORDER BY name";
+        let response = db.execute_statement(sql).unwrap();
+
+        match response {
+            ExecuteStatementResponse::Select { items, count, .. } => {
+                assert_eq!(count, 2);
+                assert_eq!(items[0].get("name"), Some(&crate::Value::string("User0")));
+                assert_eq!(items[1].get("name"), Some(&crate::Value::string("User2")));
+
+                // Projected nested attribute keeps its map structure
+                match items[1].get("address") {
+                    Some(crate::Value::M(address)) => {
+                        assert_eq!(address.len(), 1);
+                        assert_eq!(address.get("zip"), Some(&crate::Value::string("00002")));
+                    }
+                    _ => panic!("Expected projected address map"),
+                }
+            }
+            _ => panic!("Expected Select response"),
+        }
+    }
+
+    #[test]
+    fn test_execute_statement_aggregates_group_by() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let orders = [("open", 10), ("closed", 25), ("open", 30), ("shipped", 5), ("open", 20)];
+        for (i, (status, price)) in orders.iter().enumerate() {
+            db.put(
+                format!("order#{}", i).as_bytes(),
+                ItemBuilder::new()
+                    .string("status", *status)
+                    .number("price", price)
+                    .build(),
+            )
+            .unwrap();
+        }
+
+        let sql = "SELECT status, COUNT(*) AS n, SUM(price) AS total, MAX(price) \
+                   FROM orders WHERE price > 5 GROUP BY status ORDER BY n DESC";
+        let response = db.execute_statement(sql).unwrap();
+
+        match response {
+            ExecuteStatementResponse::Select { items, count, .. } => {
+                // 'shipped' is filtered out entirely by the WHERE clause
+                assert_eq!(count, 2);
+                assert_eq!(items[0].get("status"), Some(&crate::Value::string("open")));
+                assert_eq!(items[0].get("n"), Some(&crate::Value::number(3)));
+                assert_eq!(items[0].get("total"), Some(&crate::Value::number(60)));
+                assert_eq!(items[0].get("MAX(price)"), Some(&crate::Value::number(30)));
+                assert_eq!(items[1].get("status"), Some(&crate::Value::string("closed")));
+                assert_eq!(items[1].get("n"), Some(&crate::Value::number(1)));
+            }
+            _ => panic!("Expected Select response"),
+        }
+
+        // Without GROUP BY a single row summarises the whole result
+        let response = db
+            .execute_statement("SELECT COUNT(*), AVG(price) FROM orders LIMIT 1")
+            .unwrap();
+        match response {
+            ExecuteStatementResponse::Select { items, .. } => {
+                assert_eq!(items.len(), 1);
+                assert_eq!(items[0].get("COUNT(*)"), Some(&crate::Value::number(5)));
+                assert_eq!(items[0].get("AVG(price)"), Some(&crate::Value::number(18)));
+            }
+            _ => panic!("Expected Select response"),
+        }
+    }
+
+    #[test]
+    fn test_execute_statement_explain() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        for i in 0..10 {
+            db.put_with_sk(
+                b"org#acme",
+                format!("user#{:03}", i).as_bytes(),
+                ItemBuilder::new().number("age", 20 + i).build(),
+            )
+            .unwrap();
+        }
+
+        // Key conditions are pushed down, age is filtered after the Query
+        let sql = "EXPLAIN SELECT * FROM users WHERE pk = 'org#acme' AND sk >= 'user#005' AND age > 26";
+        match db.execute_statement(sql).unwrap() {
+            ExecuteStatementResponse::Explain { plan } => {
+                assert_eq!(plan.access_path, AccessPath::TableQuery);
+                assert_eq!(
+                    plan.key_conditions,
+                    vec!["pk = 'org#acme'".to_string(), "sk >= 'user#005'".to_string()]
+                );
+                assert_eq!(plan.residual_filters, vec!["age > 26".to_string()]);
+                assert_eq!(plan.estimated_scanned, 5);
+                assert!(!plan.limit_pushed_down);
+            }
+            _ => panic!("Expected Explain response"),
+        }
+
+        // Without a partition key the whole table is scanned
+        let sql = "EXPLAIN SELECT * FROM users LIMIT 3";
+        match db.execute_statement(sql).unwrap() {
+            ExecuteStatementResponse::Explain { plan } => {
+                assert_eq!(plan.access_path, AccessPath::FullScan);
+                assert!(plan.key_conditions.is_empty());
+                assert!(plan.limit_pushed_down);
+                assert_eq!(plan.estimated_scanned, 3);
+                assert!(plan.to_string().contains("Full table scan"));
+            }
+            _ => panic!("Expected Explain response"),
+        }
+
+        // Only SELECT can be explained
+        assert!(db
+            .execute_statement("EXPLAIN DELETE FROM users WHERE pk = 'org#acme'")
+            .is_err());
+    }
+
+    #[test]
+    fn test_execute_statement_routes_to_gsi() {
+        use crate::{GlobalSecondaryIndex, TableSchema};
+
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::with_sort_key("status-index", "status", "ts"));
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+        for i in 0..6 {
+            let status = if i % 2 == 0 { "active" } else { "inactive" };
+            db.put(
+                format!("task#{}", i).as_bytes(),
+                ItemBuilder::new()
+                    .string("status", status)
+                    .number("ts", i)
+                    .build(),
+            )
+            .unwrap();
+        }
+
+        // status = ... selects the GSI; the ts range stays a residual filter
+        let sql = "SELECT * FROM tasks WHERE status = 'active' AND ts > 1";
+        match db.execute_statement(sql).unwrap() {
+            ExecuteStatementResponse::Select {
+                items,
+                count,
+                warnings,
+                ..
+            } => {
+                assert_eq!(count, 2);
+                assert!(warnings.is_empty());
+                for item in &items {
+                    assert_eq!(item.get("status").unwrap().as_string().unwrap(), "active");
+                }
+            }
+            _ => panic!("Expected Select response"),
+        }
+
+        match db.execute_statement(&format!("EXPLAIN {}", sql)).unwrap() {
+            ExecuteStatementResponse::Explain { plan } => {
+                assert_eq!(
+                    plan.access_path,
+                    AccessPath::IndexQuery {
+                        index_name: "status-index".to_string()
+                    }
+                );
+                assert!(plan.warnings.is_empty());
+            }
+            _ => panic!("Expected Explain response"),
+        }
+
+        // No index covers ts alone, so the table is scanned with a warning
+        match db.execute_statement("SELECT * FROM tasks WHERE ts > 3").unwrap() {
+            ExecuteStatementResponse::Select { count, warnings, .. } => {
+                assert_eq!(count, 2);
+                assert_eq!(warnings.len(), 1);
+            }
+            _ => panic!("Expected Select response"),
+        }
+    }
+
+    #[test]
+    fn test_execute_statement_named_table() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        db.create_table("orders", TableSchema::new()).unwrap();
+
+        db.execute_statement("INSERT INTO orders VALUE {'pk': 'order#1', 'total': 10}")
+            .unwrap();
+        db.execute_statement("INSERT INTO users VALUE {'pk': 'order#1', 'name': 'Alice'}")
+            .unwrap();
+        db.execute_statement("UPDATE orders SET total = total + 5 WHERE pk = 'order#1'")
+            .unwrap();
+
+        // FROM orders reads the named table; other names use the default table
+        match db.execute_statement("SELECT * FROM orders WHERE pk = 'order#1'").unwrap() {
+            ExecuteStatementResponse::Select { items, .. } => {
+                assert_eq!(items.len(), 1);
+                assert_eq!(items[0].get("total"), Some(&Value::number(15)));
+            }
+            _ => panic!("Expected Select response"),
+        }
+        match db.execute_statement("SELECT * FROM orders").unwrap() {
+            ExecuteStatementResponse::Select { items, .. } => assert_eq!(items.len(), 1),
+            _ => panic!("Expected Select response"),
+        }
+        let item = db.get(b"order#1").unwrap().unwrap();
+        assert!(item.get("total").is_none());
+
+        db.execute_statement("DELETE FROM orders WHERE pk = 'order#1'").unwrap();
+        assert!(db.table("orders").unwrap().get(b"order#1").unwrap().is_none());
+        assert!(db.get(b"order#1").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_execute_transaction() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        db.execute_transaction(&[
+            "INSERT INTO accounts VALUE {'pk': 'alice', 'balance': 100}",
+            "INSERT INTO accounts VALUE {'pk': 'bob', 'balance': 0}",
+        ])
+        .unwrap();
+
+        let response = db
+            .execute_transaction(&[
+                ExecuteStatementRequest::new(
+                    "UPDATE accounts SET balance = balance - ? WHERE pk = 'alice' AND balance >= ?",
+                )
+                .with_parameters(vec![Value::number(30), Value::number(30)]),
+                ExecuteStatementRequest::new(
+                    "UPDATE accounts SET balance = balance + ? WHERE pk = 'bob'",
+                )
+                .with_parameters(vec![Value::number(30)]),
+            ])
+            .unwrap();
+        assert_eq!(response.committed_count, 2);
+        assert_eq!(db.get(b"alice").unwrap().unwrap().get("balance"), Some(&Value::number(70)));
+        assert_eq!(db.get(b"bob").unwrap().unwrap().get("balance"), Some(&Value::number(30)));
+
+        // The failed condition cancels every statement and is reported per statement
+        let err = db
+            .execute_transaction(&[
+                "UPDATE accounts SET balance = balance + 500 WHERE pk = 'bob'",
+                "UPDATE accounts SET balance = balance - 500 WHERE pk = 'alice' AND balance >= 500",
+            ])
+            .unwrap_err();
+        match err {
+            Error::TransactionCanceled(msg) => {
+                assert!(msg.contains("[None, ConditionalCheckFailed]"), "{}", msg)
+            }
+            other => panic!("Expected TransactionCanceled, got {:?}", other),
+        }
+        assert_eq!(db.get(b"bob").unwrap().unwrap().get("balance"), Some(&Value::number(30)));
+
+        // Reads are not part of write transactions
+        assert!(db
+            .execute_transaction(&["SELECT * FROM accounts WHERE pk = 'bob'"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_execute_statement_insert_on_conflict() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        db.execute_statement("INSERT INTO users VALUE {'pk': 'user#1', 'name': 'Alice', 'age': 30}")
+            .unwrap();
+
+        // DO NOTHING keeps the existing item
+        let sql = "INSERT INTO users VALUE {'pk': 'user#1', 'name': 'Bob'} ON CONFLICT DO NOTHING";
+        match db.execute_statement(sql).unwrap() {
+            ExecuteStatementResponse::Insert { success } => assert!(!success),
+            _ => panic!("Expected Insert response"),
+        }
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Alice")));
+
+        // UPSERT overwrites the given attributes and keeps the rest
+        db.execute_statement("UPSERT INTO users VALUE {'pk': 'user#1', 'name': 'Bob'}")
+            .unwrap();
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Bob")));
+        assert_eq!(item.get("age"), Some(&Value::number(30)));
+
+        // REPLACE swaps the whole item
+        db.execute_statement("REPLACE INTO users VALUE {'pk': 'user#1', 'name': 'Carol'}")
+            .unwrap();
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Carol")));
+        assert!(item.get("age").is_none());
+    }
+
+    #[test]
+    fn test_execute_statement_insert_select() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        // Rows carry the key they are copied to
+        for (i, status) in ["open", "closed", "closed"].iter().enumerate() {
+            db.put(
+                format!("order#{}", i).as_bytes(),
+                ItemBuilder::new()
+                    .string("pk", format!("archive#{}", i))
+                    .string("status", *status)
+                    .build(),
+            )
+            .unwrap();
+        }
+
+        let sql = "INSERT INTO orders SELECT pk, status FROM orders WHERE status = 'closed'";
+        match db.execute_statement(sql).unwrap() {
+            ExecuteStatementResponse::InsertSelect { inserted } => assert_eq!(inserted, 2),
+            _ => panic!("Expected InsertSelect response"),
+        }
+        assert!(db.get(b"archive#0").unwrap().is_none());
+        let item = db.get(b"archive#2").unwrap().unwrap();
+        assert_eq!(item.get("status"), Some(&Value::string("closed")));
+        assert!(item.get("pk").is_none());
+
+        // Rows without a pk attribute cannot be inserted
+        assert!(db
+            .execute_statement("INSERT INTO orders SELECT status FROM orders")
+            .is_err());
+    }
+
+    #[test]
+    fn test_execute_with_parameters() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let insert = "INSERT INTO users VALUE {'pk': ?, 'name': ?, 'age': :age}";
+        for (i, name) in ["Alice", "Bob"].iter().enumerate() {
+            let request = ExecuteStatementRequest::new(insert)
+                .with_parameters(vec![
+                    Value::string(format!("user#{}", i)),
+                    Value::string(*name),
+                ])
+                .with_named_parameter("age", Value::number(30 + i));
+            db.execute(request).unwrap();
+        }
+
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Bob")));
+        assert_eq!(item.get("age"), Some(&Value::number(31)));
+
+        let select = "SELECT name FROM users WHERE pk = ? AND age >= :min_age";
+        let request = ExecuteStatementRequest::new(select)
+            .with_parameters(vec![Value::string("user#0")])
+            .with_named_parameter("min_age", Value::number(30));
+        match db.execute(request).unwrap() {
+            ExecuteStatementResponse::Select { items, .. } => {
+                assert_eq!(items.len(), 1);
+                assert_eq!(items[0].get("name"), Some(&Value::string("Alice")));
+            }
+            _ => panic!("Expected Select response"),
+        }
+
+        // Parsed once per distinct statement text
+        assert_eq!(db.statements.len(), 2);
+
+        // Unbound placeholders are rejected
+        assert!(db.execute_statement(select).is_err());
+    }
+
+    #[test]
+    fn test_statement_cache_eviction() {
+        let cache = StatementCache::default();
+
+        for i in 0..STATEMENT_CACHE_CAPACITY + 10 {
+            cache
+                .get_or_parse(&format!("SELECT * FROM users WHERE pk = 'user#{}'", i))
+                .unwrap();
+        }
+        assert_eq!(cache.len(), STATEMENT_CACHE_CAPACITY);
+
+        // Invalid statements are not cached
+        assert!(cache.get_or_parse("INVALID SQL STATEMENT").is_err());
+        assert_eq!(cache.len(), STATEMENT_CACHE_CAPACITY);
+    }
 }
@@ -2,12 +2,27 @@
 ///
 /// Provides a high-level API for querying items within a partition.
 
-use kstone_core::{Item, Key, iterator::{QueryParams, QueryResult, SortKeyCondition}};
+use crate::{table::caller_key, Database};
+use kstone_core::{
+    Error, Item, Key, Result,
+    expression::{ExpressionContext, ExpressionParser},
+    iterator::{QueryParams, QueryResult, Select, SortKeyCondition},
+    table::encode_table_key,
+};
 use bytes::Bytes;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Items fetched per page by `query_iter` and `scan_iter` unless overridden
+pub const DEFAULT_PAGE_SIZE: usize = 100;
 
 /// Query builder
 pub struct Query {
     params: QueryParams,
+    filter: Option<String>,
+    context: ExpressionContext,
+    page_size: usize,
+    table: Option<String>,
 }
 
 impl Query {
@@ -15,6 +30,10 @@ impl Query {
     pub fn new(pk: &[u8]) -> Self {
         Self {
             params: QueryParams::new(Bytes::copy_from_slice(pk)),
+            filter: None,
+            context: ExpressionContext::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+            table: None,
         }
     }
 
@@ -94,12 +113,32 @@ impl Query {
         self
     }
 
-    /// Set the maximum number of items to return
+    /// Set the maximum number of items to evaluate
+    ///
+    /// Like DynamoDB's `Limit`, this counts items before the filter is
+    /// applied, so a filtered page can hold fewer items (even none) while
+    /// its last key shows that more may follow.
     pub fn limit(mut self, limit: usize) -> Self {
         self.params = self.params.with_limit(limit);
         self
     }
 
+    /// Set how many items each page fetches when iterating with `query_iter`
+    ///
+    /// With an iterator, `limit` caps the total number of items yielded instead.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Abort the query with `DeadlineExceeded` once `deadline` passes
+    ///
+    /// With an iterator, the deadline covers every page.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.params = self.params.with_deadline(deadline);
+        self
+    }
+
     /// Set the exclusive start key for pagination
     pub fn start_after(mut self, pk: &[u8], sk: Option<&[u8]>) -> Self {
         let key = if let Some(sk_bytes) = sk {
@@ -117,9 +156,69 @@ impl Query {
         self
     }
 
-    /// Get the underlying QueryParams
-    pub(crate) fn into_params(self) -> QueryParams {
-        self.params
+    /// Set a filter expression applied after key matching
+    ///
+    /// Filtered items are excluded from `items` but still counted in `scanned_count`.
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Return only the given attribute paths (e.g. `["name", "address.city"]`)
+    pub fn projection(mut self, attributes: &[&str]) -> Self {
+        let paths = attributes.iter().map(|a| a.to_string()).collect();
+        self.params = self.params.with_projection(paths);
+        self
+    }
+
+    /// Choose whether to return matching items or only count them
+    ///
+    /// With `Select::Count`, `items` is empty and `count` holds the number of matches.
+    pub fn select(mut self, select: Select) -> Self {
+        self.params = self.params.with_select(select);
+        self
+    }
+
+    /// Add an expression attribute value
+    pub fn value(mut self, placeholder: impl Into<String>, value: kstone_core::Value) -> Self {
+        self.context = self.context.with_value(placeholder, value);
+        self
+    }
+
+    /// Add an expression attribute name
+    pub fn name(mut self, placeholder: impl Into<String>, name: impl Into<String>) -> Self {
+        self.context = self.context.with_name(placeholder, name);
+        self
+    }
+
+    /// Query a named table instead of the default table (Phase 3.7+)
+    pub(crate) fn in_table(mut self, table: impl Into<String>) -> Self {
+        self.table = Some(table.into());
+        self
+    }
+
+    /// Get the underlying QueryParams, parsing the filter expression if present
+    pub(crate) fn into_params(self) -> kstone_core::Result<QueryParams> {
+        let mut params = self.params;
+
+        if let Some(table) = &self.table {
+            if params.index_name.is_some() {
+                return Err(Error::InvalidArgument(format!(
+                    "Table '{}' has no indexes",
+                    table
+                )));
+            }
+            params.pk = encode_table_key(table, &Key::new(params.pk)).pk;
+            params.start_key = params.start_key.map(|key| encode_table_key(table, &key));
+        }
+
+        match self.filter {
+            Some(filter) => {
+                let expr = ExpressionParser::parse(&filter)?;
+                Ok(params.with_filter(expr, self.context))
+            }
+            None => Ok(params),
+        }
     }
 }
 
@@ -127,23 +226,92 @@ impl Query {
 pub struct QueryResponse {
     /// Items found
     pub items: Vec<Item>,
-    /// Number of items returned
+    /// Number of matching items (returned, or only counted with `Select::Count`)
     pub count: usize,
     /// Last evaluated key (for pagination)
     pub last_key: Option<(Bytes, Option<Bytes>)>,
     /// Number of items examined
     pub scanned_count: usize,
+    /// Base table keys of the matching items, for index queries (Phase 8+)
+    pub keys: Vec<(Bytes, Option<Bytes>)>,
 }
 
 impl QueryResponse {
     pub(crate) fn from_result(result: QueryResult) -> Self {
-        let last_key = result.last_key.map(|k| (k.pk, k.sk));
-        let count = result.items.len();
+        let last_key = result.last_key.map(caller_key).map(|k| (k.pk, k.sk));
+        let keys = result.keys.into_iter().map(caller_key).map(|k| (k.pk, k.sk)).collect();
         Self {
             items: result.items,
-            count,
+            count: result.count,
             last_key,
             scanned_count: result.scanned_count,
+            keys,
+        }
+    }
+}
+
+/// Iterator over every item matching a query, fetching pages on demand
+///
+/// Created by `Database::query_iter`. Each page resumes after the previous
+/// page's last key; an error ends the iteration.
+pub struct QueryIter<'a> {
+    db: &'a Database,
+    params: QueryParams,
+    page_size: usize,
+    remaining: Option<usize>,
+    buffer: VecDeque<Item>,
+    done: bool,
+}
+
+impl<'a> QueryIter<'a> {
+    pub(crate) fn new(db: &'a Database, query: Query) -> Result<Self> {
+        let page_size = query.page_size;
+        let params = query.into_params()?;
+        Ok(Self {
+            db,
+            remaining: params.limit,
+            params,
+            page_size,
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        let page_limit = match self.remaining {
+            Some(remaining) => remaining.min(self.page_size),
+            None => self.page_size,
+        };
+        self.params.limit = Some(page_limit);
+
+        let result = self.db.query_page(self.params.clone())?;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(result.items.len());
+        }
+        match result.last_key {
+            Some(last_key) if self.remaining != Some(0) => self.params.start_key = Some(last_key),
+            _ => self.done = true,
+        }
+        self.buffer.extend(result.items);
+        Ok(())
+    }
+}
+
+impl Iterator for QueryIter<'_> {
+    type Item = Result<Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
         }
     }
 }
@@ -159,7 +327,7 @@ mod tests {
             .forward(true)
             .limit(10);
 
-        let params = query.into_params();
+        let params = query.into_params().unwrap();
         assert_eq!(params.pk, Bytes::from("user#123"));
         assert_eq!(params.limit, Some(10));
         assert_eq!(params.forward, true);
@@ -172,7 +340,7 @@ mod tests {
             .sk_between(b"2024-01-01", b"2024-12-31")
             .limit(50);
 
-        let params = query.into_params();
+        let params = query.into_params().unwrap();
         assert_eq!(params.limit, Some(50));
 
         if let Some((condition, val1, val2)) = params.sk_condition {
@@ -183,4 +351,48 @@ mod tests {
             panic!("Expected Between condition");
         }
     }
+
+    #[test]
+    fn test_query_builder_filter() {
+        let query = Query::new(b"user#123")
+            .filter("#s = :s AND age > :a")
+            .name("#s", "status")
+            .value(":s", kstone_core::Value::string("active"))
+            .value(":a", kstone_core::Value::number(18));
+
+        let params = query.into_params().unwrap();
+        assert!(params.filter.is_some());
+        assert_eq!(params.filter_context.values.len(), 2);
+        assert_eq!(params.filter_context.names.get("#s"), Some(&"status".to_string()));
+    }
+
+    #[test]
+    fn test_query_builder_projection() {
+        let query = Query::new(b"user#123").projection(&["name", "address.city"]);
+        let params = query.into_params().unwrap();
+        assert_eq!(
+            params.projection,
+            Some(vec!["name".to_string(), "address.city".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_query_builder_table() {
+        let params = Query::new(b"user#123")
+            .start_after(b"user#123", Some(b"post#1"))
+            .in_table("users")
+            .into_params()
+            .unwrap();
+        assert_eq!(params.pk, encode_table_key("users", &Key::new(Bytes::from("user#123"))).pk);
+        assert_eq!(params.start_key.unwrap().pk, params.pk);
+
+        let query = Query::new(b"user#123").index("by-email").in_table("users");
+        assert!(query.into_params().is_err());
+    }
+
+    #[test]
+    fn test_query_builder_invalid_filter() {
+        let query = Query::new(b"user#123").filter("age >");
+        assert!(query.into_params().is_err());
+    }
 }
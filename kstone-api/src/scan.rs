@@ -2,12 +2,23 @@
 ///
 /// Provides a high-level API for scanning all items in a table.
 
-use kstone_core::{Item, Key, iterator::{ScanParams, ScanResult}};
+use crate::{query::DEFAULT_PAGE_SIZE, table::caller_key, Database};
+use kstone_core::{
+    Item, Key, Result,
+    expression::{ExpressionContext, ExpressionParser},
+    iterator::{ScanParams, ScanResult, Select},
+    table::encode_table_key,
+};
 use bytes::Bytes;
+use std::collections::VecDeque;
+use std::time::Instant;
 
 /// Scan builder
 pub struct Scan {
     params: ScanParams,
+    filter: Option<String>,
+    context: ExpressionContext,
+    page_size: usize,
 }
 
 impl Scan {
@@ -15,15 +26,38 @@ impl Scan {
     pub fn new() -> Self {
         Self {
             params: ScanParams::new(),
+            filter: None,
+            context: ExpressionContext::new(),
+            page_size: DEFAULT_PAGE_SIZE,
         }
     }
 
-    /// Set the maximum number of items to return
+    /// Set the maximum number of items to evaluate
+    ///
+    /// Like DynamoDB's `Limit`, this counts items before the filter is
+    /// applied, so a filtered page can hold fewer items (even none) while
+    /// its last key shows that more may follow.
     pub fn limit(mut self, limit: usize) -> Self {
         self.params = self.params.with_limit(limit);
         self
     }
 
+    /// Abort the scan with `DeadlineExceeded` once `deadline` passes
+    ///
+    /// With an iterator, the deadline covers every page.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.params = self.params.with_deadline(deadline);
+        self
+    }
+
+    /// Set how many items each page fetches when iterating with `scan_iter`
+    ///
+    /// With an iterator, `limit` caps the total number of items yielded instead.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
     /// Set the exclusive start key for pagination
     pub fn start_after(mut self, pk: &[u8], sk: Option<&[u8]>) -> Self {
         let key = if let Some(sk_bytes) = sk {
@@ -35,15 +69,75 @@ impl Scan {
         self
     }
 
+    /// Only scan items whose partition key starts with `prefix`
+    pub fn pk_prefix(mut self, prefix: &[u8]) -> Self {
+        self.params = self.params.with_pk_prefix(Bytes::copy_from_slice(prefix));
+        self
+    }
+
     /// Configure parallel scan (segment must be < total_segments)
     pub fn segment(mut self, segment: usize, total_segments: usize) -> Self {
         self.params = self.params.with_segment(segment, total_segments);
         self
     }
 
-    /// Get the underlying ScanParams
-    pub(crate) fn into_params(self) -> ScanParams {
-        self.params
+    /// Set a filter expression applied to every scanned item
+    ///
+    /// Filtered items are excluded from `items` but still counted in `scanned_count`.
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Return only the given attribute paths (e.g. `["name", "address.city"]`)
+    pub fn projection(mut self, attributes: &[&str]) -> Self {
+        let paths = attributes.iter().map(|a| a.to_string()).collect();
+        self.params = self.params.with_projection(paths);
+        self
+    }
+
+    /// Choose whether to return matching items, only their keys, or only count them
+    ///
+    /// With `Select::Count`, `items` is empty and `count` holds the number of
+    /// matches; with `Select::Keys`, `keys` holds their keys instead.
+    pub fn select(mut self, select: Select) -> Self {
+        self.params = self.params.with_select(select);
+        self
+    }
+
+    /// Add an expression attribute value
+    pub fn value(mut self, placeholder: impl Into<String>, value: kstone_core::Value) -> Self {
+        self.context = self.context.with_value(placeholder, value);
+        self
+    }
+
+    /// Add an expression attribute name
+    pub fn name(mut self, placeholder: impl Into<String>, name: impl Into<String>) -> Self {
+        self.context = self.context.with_name(placeholder, name);
+        self
+    }
+
+    /// Scan a named table instead of the default table (Phase 3.7+)
+    pub(crate) fn in_table(mut self, table: impl Into<String>) -> Self {
+        self.params = self.params.with_table(table);
+        self
+    }
+
+    /// Get the underlying ScanParams, parsing the filter expression if present
+    pub(crate) fn into_params(self) -> kstone_core::Result<ScanParams> {
+        let mut params = self.params;
+
+        if let Some(table) = &params.table {
+            params.start_key = params.start_key.map(|key| encode_table_key(table, &key));
+        }
+
+        match self.filter {
+            Some(filter) => {
+                let expr = ExpressionParser::parse(&filter)?;
+                Ok(params.with_filter(expr, self.context))
+            }
+            None => Ok(params),
+        }
     }
 }
 
@@ -57,23 +151,92 @@ impl Default for Scan {
 pub struct ScanResponse {
     /// Items found
     pub items: Vec<Item>,
-    /// Number of items returned
+    /// Number of matching items (returned, or only counted with `Select::Count`)
     pub count: usize,
     /// Last evaluated key (for pagination)
     pub last_key: Option<(Bytes, Option<Bytes>)>,
     /// Number of items examined
     pub scanned_count: usize,
+    /// Keys of the matching items, with `Select::Keys` (Phase 8+)
+    pub keys: Vec<(Bytes, Option<Bytes>)>,
 }
 
 impl ScanResponse {
     pub(crate) fn from_result(result: ScanResult) -> Self {
-        let last_key = result.last_key.map(|k| (k.pk, k.sk));
-        let count = result.items.len();
+        let last_key = result.last_key.map(caller_key).map(|k| (k.pk, k.sk));
+        let keys = result.keys.into_iter().map(caller_key).map(|k| (k.pk, k.sk)).collect();
         Self {
             items: result.items,
-            count,
+            count: result.count,
             last_key,
             scanned_count: result.scanned_count,
+            keys,
+        }
+    }
+}
+
+/// Iterator over every item in a scan, fetching pages on demand
+///
+/// Created by `Database::scan_iter`. Each page resumes after the previous
+/// page's last key; an error ends the iteration.
+pub struct ScanIter<'a> {
+    db: &'a Database,
+    params: ScanParams,
+    page_size: usize,
+    remaining: Option<usize>,
+    buffer: VecDeque<Item>,
+    done: bool,
+}
+
+impl<'a> ScanIter<'a> {
+    pub(crate) fn new(db: &'a Database, scan: Scan) -> Result<Self> {
+        let page_size = scan.page_size;
+        let params = scan.into_params()?;
+        Ok(Self {
+            db,
+            remaining: params.limit,
+            params,
+            page_size,
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        let page_limit = match self.remaining {
+            Some(remaining) => remaining.min(self.page_size),
+            None => self.page_size,
+        };
+        self.params.limit = Some(page_limit);
+
+        let result = self.db.scan_page(self.params.clone())?;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(result.items.len());
+        }
+        match result.last_key {
+            Some(last_key) if self.remaining != Some(0) => self.params.start_key = Some(last_key),
+            _ => self.done = true,
+        }
+        self.buffer.extend(result.items);
+        Ok(())
+    }
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = Result<Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
         }
     }
 }
@@ -85,19 +248,45 @@ mod tests {
     #[test]
     fn test_scan_builder() {
         let scan = Scan::new().limit(100);
-        let params = scan.into_params();
+        let params = scan.into_params().unwrap();
         assert_eq!(params.limit, Some(100));
     }
 
+    #[test]
+    fn test_scan_builder_pk_prefix() {
+        let params = Scan::new().pk_prefix(b"user#").into_params().unwrap();
+        assert_eq!(params.pk_prefix, Some(Bytes::from_static(b"user#")));
+        assert!(params.in_scope(&Key::new(Bytes::from_static(b"user#1"))));
+        assert!(!params.in_scope(&Key::new(Bytes::from_static(b"order#1"))));
+    }
+
     #[test]
     fn test_scan_builder_parallel() {
         let scan = Scan::new().segment(2, 4).limit(50);
-        let params = scan.into_params();
+        let params = scan.into_params().unwrap();
         assert_eq!(params.segment, Some(2));
         assert_eq!(params.total_segments, Some(4));
         assert_eq!(params.limit, Some(50));
     }
 
+    #[test]
+    fn test_scan_builder_filter() {
+        let scan = Scan::new()
+            .filter("status = :s")
+            .value(":s", kstone_core::Value::string("active"));
+        let params = scan.into_params().unwrap();
+        assert!(params.filter.is_some());
+        assert_eq!(params.filter_context.values.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_builder_deadline() {
+        let deadline = Instant::now();
+        let params = Scan::new().deadline(deadline).into_params().unwrap();
+        assert_eq!(params.deadline, Some(deadline));
+        assert!(params.check_deadline().is_err());
+    }
+
     #[test]
     fn test_scan_segment_distribution() {
         // Segment 0 of 4 should scan stripes 0, 4, 8, 12, etc.
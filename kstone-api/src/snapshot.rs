@@ -0,0 +1,50 @@
+/// Point-in-time read views (Phase 2.1+)
+///
+/// A snapshot sees the database exactly as it was when the snapshot was
+/// taken, so long scans and exports are not affected by concurrent writes.
+
+use crate::{Query, QueryResponse, Scan, ScanResponse};
+use kstone_core::{Item, Key, Result};
+use bytes::Bytes;
+
+/// Consistent read view of a `Database`
+///
+/// Created by `Database::snapshot()`. The view stays valid until the
+/// snapshot is dropped.
+pub struct Snapshot {
+    inner: kstone_core::Snapshot,
+}
+
+impl Snapshot {
+    pub(crate) fn new(inner: kstone_core::Snapshot) -> Self {
+        Self { inner }
+    }
+
+    /// Sequence number the snapshot is pinned to
+    pub fn sequence_number(&self) -> u64 {
+        self.inner.sequence_number()
+    }
+
+    /// Get an item by partition key as of the snapshot
+    pub fn get(&self, pk: &[u8]) -> Result<Option<Item>> {
+        self.inner.get(&Key::new(Bytes::copy_from_slice(pk)))
+    }
+
+    /// Get an item by partition key and sort key as of the snapshot
+    pub fn get_with_sk(&self, pk: &[u8], sk: &[u8]) -> Result<Option<Item>> {
+        let key = Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk));
+        self.inner.get(&key)
+    }
+
+    /// Query items within a partition as of the snapshot
+    pub fn query(&self, query: Query) -> Result<QueryResponse> {
+        let result = self.inner.query(query.into_params()?)?;
+        Ok(QueryResponse::from_result(result))
+    }
+
+    /// Scan all items as of the snapshot
+    pub fn scan(&self, scan: Scan) -> Result<ScanResponse> {
+        let result = self.inner.scan(scan.into_params()?)?;
+        Ok(ScanResponse::from_result(result))
+    }
+}
@@ -0,0 +1,215 @@
+/// `KeystoneStore` for the embedded database
+///
+/// Operations run on the calling task, like the synchronous API. Code that
+/// must not block its async runtime should use a remote client or wrap the
+/// database in `AsyncDatabase` (`async` feature) instead.
+
+use crate::{
+    Database, ExecuteStatementResponse, Query, Scan, TransactGetRequest, TransactWriteOp,
+    TransactWriteRequest, Update,
+};
+use async_trait::async_trait;
+use kstone_core::{
+    store::last_key_from_parts, Error, Item, Key, KeystoneStore, SkCondition, StatementResult,
+    StorePage, StoreQuery, StoreScan, StoreUpdate, StoreWrite,
+};
+
+#[async_trait]
+impl KeystoneStore for Database {
+    type Error = Error;
+
+    async fn put(&mut self, key: Key, item: Item) -> Result<(), Error> {
+        match &key.sk {
+            Some(sk) => Database::put_with_sk(self, &key.pk, sk, item),
+            None => Database::put(self, &key.pk, item),
+        }
+    }
+
+    async fn get(&mut self, key: Key) -> Result<Option<Item>, Error> {
+        match &key.sk {
+            Some(sk) => Database::get_with_sk(self, &key.pk, sk),
+            None => Database::get(self, &key.pk),
+        }
+    }
+
+    async fn delete(&mut self, key: Key) -> Result<(), Error> {
+        match &key.sk {
+            Some(sk) => Database::delete_with_sk(self, &key.pk, sk),
+            None => Database::delete(self, &key.pk),
+        }
+    }
+
+    async fn query(&mut self, query: StoreQuery) -> Result<StorePage, Error> {
+        let mut request = Query::new(&query.pk).forward(query.forward);
+        request = match &query.sk_condition {
+            Some(SkCondition::Equal(sk)) => request.sk_eq(sk),
+            Some(SkCondition::LessThan(sk)) => request.sk_lt(sk),
+            Some(SkCondition::LessThanOrEqual(sk)) => request.sk_lte(sk),
+            Some(SkCondition::GreaterThan(sk)) => request.sk_gt(sk),
+            Some(SkCondition::GreaterThanOrEqual(sk)) => request.sk_gte(sk),
+            Some(SkCondition::Between(low, high)) => request.sk_between(low, high),
+            Some(SkCondition::BeginsWith(prefix)) => request.sk_begins_with(prefix),
+            None => request,
+        };
+        if let Some(index) = query.index {
+            request = request.index(index);
+        }
+        if let Some(limit) = query.limit {
+            request = request.limit(limit);
+        }
+        if let Some(key) = &query.start_after {
+            request = request.start_after(&key.pk, key.sk.as_deref());
+        }
+
+        let response = Database::query(self, request)?;
+        Ok(StorePage {
+            items: response.items,
+            scanned_count: response.scanned_count,
+            last_key: last_key_from_parts(response.last_key),
+        })
+    }
+
+    async fn scan(&mut self, scan: StoreScan) -> Result<StorePage, Error> {
+        let mut request = Scan::new();
+        if let Some(limit) = scan.limit {
+            request = request.limit(limit);
+        }
+        if let Some(key) = &scan.start_after {
+            request = request.start_after(&key.pk, key.sk.as_deref());
+        }
+        if let Some((segment, total_segments)) = scan.segment {
+            request = request.segment(segment, total_segments);
+        }
+
+        let response = Database::scan(self, request)?;
+        Ok(StorePage {
+            items: response.items,
+            scanned_count: response.scanned_count,
+            last_key: last_key_from_parts(response.last_key),
+        })
+    }
+
+    async fn update(&mut self, update: StoreUpdate) -> Result<Item, Error> {
+        let mut request = match &update.key.sk {
+            Some(sk) => Update::with_sk(&update.key.pk, sk),
+            None => Update::new(&update.key.pk),
+        }
+        .expression(update.expression);
+        if let Some(condition) = update.condition {
+            request = request.condition(condition);
+        }
+        for (placeholder, value) in update.values {
+            request = request.value(placeholder, value);
+        }
+
+        Ok(Database::update(self, request)?.item)
+    }
+
+    async fn transact_get(&mut self, keys: Vec<Key>) -> Result<Vec<Option<Item>>, Error> {
+        let request = TransactGetRequest { keys };
+        Ok(Database::transact_get(self, request)?.items)
+    }
+
+    async fn transact_write(&mut self, writes: Vec<StoreWrite>) -> Result<(), Error> {
+        let mut request = TransactWriteRequest::new();
+        request.operations = writes
+            .into_iter()
+            .map(|write| match write {
+                StoreWrite::Put { key, item, condition } => TransactWriteOp::Put { key, item, condition },
+                StoreWrite::Update { key, expression, condition } => TransactWriteOp::Update {
+                    key,
+                    update_expression: expression,
+                    condition,
+                },
+                StoreWrite::Delete { key, condition } => TransactWriteOp::Delete { key, condition },
+                StoreWrite::ConditionCheck { key, condition } => {
+                    TransactWriteOp::ConditionCheck { key, condition }
+                }
+            })
+            .collect();
+
+        Database::transact_write(self, request)?;
+        Ok(())
+    }
+
+    async fn execute_statement(&mut self, statement: &str) -> Result<StatementResult, Error> {
+        match Database::execute_statement(self, statement)? {
+            ExecuteStatementResponse::Select { items, scanned_count, last_key, .. } => {
+                Ok(StatementResult::Select {
+                    items,
+                    scanned_count,
+                    last_key: last_key_from_parts(last_key),
+                })
+            }
+            ExecuteStatementResponse::Insert { success } => Ok(StatementResult::Insert { success }),
+            // Reported like the server does, which has no result for it either
+            ExecuteStatementResponse::InsertSelect { .. } => Ok(StatementResult::Insert { success: true }),
+            ExecuteStatementResponse::Update { item } => Ok(StatementResult::Update { item }),
+            ExecuteStatementResponse::Delete { success } => Ok(StatementResult::Delete { success }),
+            ExecuteStatementResponse::Explain { .. } => Err(Error::InvalidArgument(
+                "EXPLAIN is only supported by Database::execute_statement".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemBuilder;
+    use kstone_core::Value;
+
+    /// Application code written against the trait
+    async fn bump_visits<S: KeystoneStore>(store: &mut S, user: &str) -> Result<i64, S::Error> {
+        let update = StoreUpdate::new(Key::new(user.as_bytes().to_vec()), "SET visits = visits + :one")
+            .value(":one", Value::number(1));
+        let item = store.update(update).await?;
+        Ok(match item.get("visits") {
+            Some(Value::N(n)) => n.parse().unwrap(),
+            _ => 0,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_database_as_store() {
+        let mut db = Database::create_in_memory().unwrap();
+        let user = Key::new(b"user#1".to_vec());
+
+        KeystoneStore::put(&mut db, user.clone(), ItemBuilder::new().number("visits", 0).build())
+            .await
+            .unwrap();
+        assert_eq!(bump_visits(&mut db, "user#1").await.unwrap(), 1);
+        assert_eq!(bump_visits(&mut db, "user#1").await.unwrap(), 2);
+
+        for i in 0..5 {
+            let key = Key::with_sk(b"orders".to_vec(), format!("order#{}", i).into_bytes());
+            KeystoneStore::put(&mut db, key, ItemBuilder::new().number("n", i).build())
+                .await
+                .unwrap();
+        }
+        let query = StoreQuery::new("orders")
+            .sk(SkCondition::BeginsWith("order#".into()))
+            .limit(3);
+        let page = KeystoneStore::query(&mut db, query).await.unwrap();
+        assert_eq!(page.items.len(), 3);
+        let last_key = page.last_key.expect("more pages");
+
+        let query = StoreQuery::new("orders").start_after(last_key);
+        let page = KeystoneStore::query(&mut db, query).await.unwrap();
+        assert_eq!(page.items.len(), 2);
+
+        KeystoneStore::transact_write(&mut db, vec![StoreWrite::Delete { key: user.clone(), condition: None }])
+            .await
+            .unwrap();
+        let items = KeystoneStore::transact_get(&mut db, vec![user]).await.unwrap();
+        assert!(items[0].is_none());
+
+        match KeystoneStore::execute_statement(&mut db, "SELECT * FROM items WHERE pk = 'orders'")
+            .await
+            .unwrap()
+        {
+            StatementResult::Select { items, .. } => assert_eq!(items.len(), 5),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
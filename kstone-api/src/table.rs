@@ -0,0 +1,139 @@
+/// Named tables within one database (Phase 3.7+)
+///
+/// A `Table` is a handle to a table created with `Database::create_table`.
+/// Tables share the database's WAL, manifest and background work but keep
+/// their own items and schema. Keys passed to and returned from a handle are
+/// plain keys; the table prefix is added and stripped internally.
+
+use crate::{Database, Query, QueryResponse, Scan, ScanResponse, Update, UpdateResponse};
+use bytes::Bytes;
+use kstone_core::{
+    index::TableSchema,
+    iterator::Select,
+    table::{decode_table_key, encode_table_key},
+    Error, Item, Key, Result,
+};
+
+/// Handle to a named table
+pub struct Table<'a> {
+    db: &'a Database,
+    name: String,
+}
+
+/// Per-table statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    /// Table name
+    pub name: String,
+    /// Number of live (unexpired) items
+    pub item_count: u64,
+}
+
+impl<'a> Table<'a> {
+    pub(crate) fn new(db: &'a Database, name: impl Into<String>) -> Self {
+        Self {
+            db,
+            name: name.into(),
+        }
+    }
+
+    /// Table name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The table's schema
+    pub fn schema(&self) -> Result<TableSchema> {
+        self.db
+            .table_schema(&self.name)
+            .ok_or_else(|| Error::NotFound(format!("Table '{}'", self.name)))
+    }
+
+    /// Put an item with a simple partition key
+    pub fn put(&self, pk: &[u8], item: Item) -> Result<()> {
+        self.db.put_key(self.key(pk, None), item)
+    }
+
+    /// Put an item with partition key and sort key
+    pub fn put_with_sk(&self, pk: &[u8], sk: &[u8], item: Item) -> Result<()> {
+        self.db.put_key(self.key(pk, Some(sk)), item)
+    }
+
+    /// Get an item by partition key
+    pub fn get(&self, pk: &[u8]) -> Result<Option<Item>> {
+        self.db.get_key(&self.key(pk, None))
+    }
+
+    /// Get an item by partition key and sort key
+    pub fn get_with_sk(&self, pk: &[u8], sk: &[u8]) -> Result<Option<Item>> {
+        self.db.get_key(&self.key(pk, Some(sk)))
+    }
+
+    /// Delete an item by partition key
+    pub fn delete(&self, pk: &[u8]) -> Result<()> {
+        self.db.delete_key(self.key(pk, None))
+    }
+
+    /// Delete an item by partition key and sort key
+    pub fn delete_with_sk(&self, pk: &[u8], sk: &[u8]) -> Result<()> {
+        self.db.delete_key(self.key(pk, Some(sk)))
+    }
+
+    /// Update an item using an update expression
+    pub fn update(&self, update: Update) -> Result<UpdateResponse> {
+        self.db.update(update.in_table(&self.name))
+    }
+
+    /// Query items within a partition of this table
+    ///
+    /// Named tables have no indexes, so `Query::index` is rejected.
+    pub fn query(&self, query: Query) -> Result<QueryResponse> {
+        self.db.query(query.in_table(&self.name))
+    }
+
+    /// Scan every item in this table
+    pub fn scan(&self, scan: Scan) -> Result<ScanResponse> {
+        self.db.scan(scan.in_table(&self.name))
+    }
+
+    /// Count the table's items
+    pub fn stats(&self) -> Result<TableStats> {
+        let mut item_count = 0;
+        let mut start_key: Option<(Bytes, Option<Bytes>)> = None;
+
+        loop {
+            let mut scan = Scan::new().select(Select::Count);
+            if let Some((pk, sk)) = &start_key {
+                scan = scan.start_after(pk, sk.as_deref());
+            }
+
+            let response = self.scan(scan)?;
+            item_count += response.count as u64;
+            start_key = response.last_key;
+            if start_key.is_none() {
+                break;
+            }
+        }
+
+        Ok(TableStats {
+            name: self.name.clone(),
+            item_count,
+        })
+    }
+
+    fn key(&self, pk: &[u8], sk: Option<&[u8]>) -> Key {
+        let key = match sk {
+            Some(sk) => Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk)),
+            None => Key::new(Bytes::copy_from_slice(pk)),
+        };
+        encode_table_key(&self.name, &key)
+    }
+}
+
+/// Strip the table prefix from a stored key returned to callers
+pub(crate) fn caller_key(key: Key) -> Key {
+    match decode_table_key(&key) {
+        Some((_, key)) => key,
+        None => key,
+    }
+}
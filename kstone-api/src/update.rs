@@ -5,12 +5,52 @@
 use kstone_core::{Item, Key, expression::{UpdateAction, UpdateExpressionParser, ExpressionContext}};
 use bytes::Bytes;
 
+/// Which attributes a write returns (Phase 2.5+)
+///
+/// Mirrors DynamoDB's `ReturnValues` parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReturnValues {
+    /// Return nothing
+    #[default]
+    None,
+    /// The whole item as it was before the write
+    AllOld,
+    /// Attributes changed by the write, as they were before it
+    UpdatedOld,
+    /// The whole item as it is after the write
+    AllNew,
+    /// Attributes changed by the write, as they are after it
+    UpdatedNew,
+}
+
+impl ReturnValues {
+    /// Select the attributes to return from the old and new images
+    pub(crate) fn select(self, old: Option<&Item>, new: Option<&Item>) -> Option<Item> {
+        match self {
+            ReturnValues::None => None,
+            ReturnValues::AllOld => old.cloned(),
+            ReturnValues::AllNew => new.cloned(),
+            ReturnValues::UpdatedOld => old.map(|old| changed_attributes(old, new)),
+            ReturnValues::UpdatedNew => new.map(|new| changed_attributes(new, old)),
+        }
+    }
+}
+
+/// Attributes of `item` whose value differs in `other`
+fn changed_attributes(item: &Item, other: Option<&Item>) -> Item {
+    item.iter()
+        .filter(|(name, value)| other.and_then(|other| other.get(*name)) != Some(*value))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
 /// Update builder
 pub struct Update {
     key: Key,
     expression: String,
     condition: Option<String>,
     context: ExpressionContext,
+    return_values: ReturnValues,
 }
 
 impl Update {
@@ -21,6 +61,7 @@ impl Update {
             expression: String::new(),
             condition: None,
             context: ExpressionContext::new(),
+            return_values: ReturnValues::None,
         }
     }
 
@@ -31,6 +72,7 @@ impl Update {
             expression: String::new(),
             condition: None,
             context: ExpressionContext::new(),
+            return_values: ReturnValues::None,
         }
     }
 
@@ -41,6 +83,7 @@ impl Update {
             expression: String::new(),
             condition: None,
             context: ExpressionContext::new(),
+            return_values: ReturnValues::None,
         }
     }
 
@@ -56,6 +99,15 @@ impl Update {
         self
     }
 
+    /// Choose which attributes the response returns (Phase 2.5+)
+    ///
+    /// Defaults to `ReturnValues::None`; the updated item is always available
+    /// in `UpdateResponse::item`.
+    pub fn return_values(mut self, return_values: ReturnValues) -> Self {
+        self.return_values = return_values;
+        self
+    }
+
     /// Add an expression attribute value
     pub fn value(mut self, placeholder: impl Into<String>, value: kstone_core::Value) -> Self {
         self.context = self.context.with_value(placeholder, value);
@@ -68,11 +120,22 @@ impl Update {
         self
     }
 
+    /// Target an item in a named table (Phase 3.7+)
+    pub(crate) fn in_table(mut self, table: &str) -> Self {
+        self.key = kstone_core::table::encode_table_key(table, &self.key);
+        self
+    }
+
     /// Get the key
     pub(crate) fn key(&self) -> &Key {
         &self.key
     }
 
+    /// Get the requested return values
+    pub(crate) fn requested_return_values(&self) -> ReturnValues {
+        self.return_values
+    }
+
     /// Parse the update expression into actions
     pub(crate) fn into_actions(self) -> kstone_core::Result<(Vec<UpdateAction>, Option<String>, ExpressionContext)> {
         let actions = UpdateExpressionParser::parse(&self.expression)?;
@@ -84,11 +147,13 @@ impl Update {
 pub struct UpdateResponse {
     /// The updated item
     pub item: Item,
+    /// Attributes selected by `ReturnValues` (None for `ReturnValues::None`)
+    pub attributes: Option<Item>,
 }
 
 impl UpdateResponse {
-    pub(crate) fn new(item: Item) -> Self {
-        Self { item }
+    pub(crate) fn new(item: Item, attributes: Option<Item>) -> Self {
+        Self { item, attributes }
     }
 }
 
@@ -130,4 +195,30 @@ mod tests {
         assert!(condition.is_some());
         assert_eq!(condition.unwrap(), "age = :old_age");
     }
+
+    #[test]
+    fn test_return_values_select() {
+        let mut old = Item::new();
+        old.insert("name".to_string(), Value::string("Alice"));
+        old.insert("age".to_string(), Value::number(30));
+        let mut new = old.clone();
+        new.insert("age".to_string(), Value::number(31));
+        new.insert("city".to_string(), Value::string("Paris"));
+
+        assert!(ReturnValues::None.select(Some(&old), Some(&new)).is_none());
+        assert_eq!(ReturnValues::AllOld.select(Some(&old), Some(&new)), Some(old.clone()));
+        assert_eq!(ReturnValues::AllNew.select(Some(&old), Some(&new)), Some(new.clone()));
+
+        let updated_old = ReturnValues::UpdatedOld.select(Some(&old), Some(&new)).unwrap();
+        assert_eq!(updated_old.len(), 1);
+        assert_eq!(updated_old.get("age"), Some(&Value::number(30)));
+
+        let updated_new = ReturnValues::UpdatedNew.select(Some(&old), Some(&new)).unwrap();
+        assert_eq!(updated_new.len(), 2);
+        assert_eq!(updated_new.get("age"), Some(&Value::number(31)));
+        assert_eq!(updated_new.get("city"), Some(&Value::string("Paris")));
+
+        // No old item: nothing to return for the *_OLD variants
+        assert!(ReturnValues::AllOld.select(None, Some(&new)).is_none());
+    }
 }
@@ -0,0 +1,117 @@
+/// Optimistic locking with version attributes (Phase 2.5+)
+///
+/// Versioned writes keep a numeric `_version` attribute on the item. Each
+/// write states the version it expects to replace and stores the next one;
+/// if another writer got there first, the write fails with
+/// `Error::VersionConflict` and nothing is written.
+
+use crate::{Database, DatabaseEngine};
+use bytes::Bytes;
+use kstone_core::{
+    expression::{ExpressionContext, ExpressionParser},
+    Error, Item, Key, Result, Value,
+};
+
+/// Attribute holding an item's version
+pub const VERSION_ATTRIBUTE: &str = "_version";
+
+/// The version of an item written with `put_versioned` (None if unversioned)
+pub fn item_version(item: &Item) -> Option<u64> {
+    match item.get(VERSION_ATTRIBUTE) {
+        Some(Value::N(n)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+impl Database {
+    /// Put an item if its current version is `expected_version`
+    ///
+    /// Pass `None` to create the item: the write then fails if a versioned
+    /// item already exists (an existing unversioned item is adopted). Returns
+    /// the version now stored in the item's `_version` attribute.
+    pub fn put_versioned(&self, pk: &[u8], item: Item, expected_version: Option<u64>) -> Result<u64> {
+        let key = Key::new(Bytes::copy_from_slice(pk));
+        self.put_versioned_key(key, item, expected_version)
+    }
+
+    /// Put an item with partition key and sort key if its current version is `expected_version`
+    pub fn put_versioned_with_sk(
+        &self,
+        pk: &[u8],
+        sk: &[u8],
+        item: Item,
+        expected_version: Option<u64>,
+    ) -> Result<u64> {
+        let key = Key::with_sk(Bytes::copy_from_slice(pk), Bytes::copy_from_slice(sk));
+        self.put_versioned_key(key, item, expected_version)
+    }
+
+    fn put_versioned_key(&self, key: Key, mut item: Item, expected_version: Option<u64>) -> Result<u64> {
+        let version = expected_version.map_or(1, |expected| expected + 1);
+        item.insert(VERSION_ATTRIBUTE.to_string(), Value::number(version));
+
+        let context = ExpressionContext::new().with_name("#version", VERSION_ATTRIBUTE);
+        let (condition, context) = match expected_version {
+            Some(expected) => ("#version = :version", context.with_value(":version", Value::number(expected))),
+            None => ("attribute_not_exists(#version)", context),
+        };
+        let condition = ExpressionParser::parse(condition)?;
+
+        let result = match &self.engine {
+            DatabaseEngine::Disk(e) => e.put_returning_old(key.clone(), item, Some((&condition, &context))),
+            DatabaseEngine::Memory(e) => e.put_returning_old(key.clone(), item, Some((&condition, &context))),
+        };
+
+        match result {
+            Ok(_) => Ok(version),
+            Err(Error::ConditionalCheckFailed(_)) => {
+                // Read after the failed check, so only informative under contention
+                let actual = self.get_key(&key)?.as_ref().and_then(item_version);
+                Err(Error::VersionConflict {
+                    expected: expected_version,
+                    actual,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_versioned() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let item = ItemBuilder::new().number("balance", 100).build();
+        assert_eq!(db.put_versioned(b"account#1", item.clone(), None).unwrap(), 1);
+        assert_eq!(db.put_versioned(b"account#1", item.clone(), Some(1)).unwrap(), 2);
+
+        let stored = db.get(b"account#1").unwrap().unwrap();
+        assert_eq!(item_version(&stored), Some(2));
+
+        // A stale writer is rejected and the item is unchanged
+        match db.put_versioned(b"account#1", ItemBuilder::new().number("balance", 0).build(), Some(1)) {
+            Err(Error::VersionConflict { expected, actual }) => {
+                assert_eq!(expected, Some(1));
+                assert_eq!(actual, Some(2));
+            }
+            other => panic!("expected VersionConflict, got {:?}", other),
+        }
+        match db.put_versioned(b"account#1", item.clone(), None) {
+            Err(Error::VersionConflict { expected: None, actual: Some(2) }) => {}
+            other => panic!("expected VersionConflict, got {:?}", other),
+        }
+        let stored = db.get(b"account#1").unwrap().unwrap();
+        assert_eq!(stored.get("balance"), Some(&Value::number(100)));
+
+        // Items with a sort key are versioned the same way
+        assert_eq!(db.put_versioned_with_sk(b"account#1", b"audit", item.clone(), None).unwrap(), 1);
+        assert!(db.put_versioned_with_sk(b"account#1", b"audit", item, Some(5)).is_err());
+    }
+}
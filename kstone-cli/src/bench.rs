@@ -0,0 +1,252 @@
+/// `kstone bench`: drive the embedded engine and measure it
+///
+/// Each thread runs its share of the operations back to back and records
+/// the latency of every one. Gets need items to read, so the `get` and
+/// `mixed` workloads write all of them first; that load isn't measured.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use kstone_api::{Database, ItemBuilder};
+use kstone_core::Item;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// Operations a benchmark runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Workload {
+    /// Put every item
+    Put,
+    /// Get every item, in scattered order
+    Get,
+    /// Alternate gets and puts
+    Mixed,
+}
+
+impl Workload {
+    fn name(self) -> &'static str {
+        match self {
+            Workload::Put => "put",
+            Workload::Get => "get",
+            Workload::Mixed => "mixed",
+        }
+    }
+}
+
+/// What to run
+pub struct BenchOptions {
+    pub workload: Workload,
+    pub threads: usize,
+    /// Operations to run, and distinct keys they touch
+    pub items: u64,
+    /// Bytes in each item's value attribute
+    pub value_size: usize,
+}
+
+/// Throughput and latencies of a finished benchmark
+pub struct BenchReport {
+    pub workload: Workload,
+    pub threads: usize,
+    pub operations: u64,
+    pub value_size: usize,
+    /// Gets that found no item
+    pub not_found: u64,
+    pub elapsed: Duration,
+    /// Latency of every operation, sorted
+    latencies: Vec<Duration>,
+}
+
+impl BenchReport {
+    /// Operations per second across all threads
+    pub fn ops_per_sec(&self) -> f64 {
+        self.operations as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Latency below which `percentile` percent of operations finished
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    /// The report as JSON, for tracking performance in CI
+    pub fn to_json(&self) -> serde_json::Value {
+        let micros = |latency: Duration| latency.as_secs_f64() * 1e6;
+        json!({
+            "workload": self.workload.name(),
+            "threads": self.threads,
+            "operations": self.operations,
+            "value_size": self.value_size,
+            "not_found": self.not_found,
+            "elapsed_secs": self.elapsed.as_secs_f64(),
+            "ops_per_sec": self.ops_per_sec(),
+            "latency_us": {
+                "p50": micros(self.percentile(50.0)),
+                "p90": micros(self.percentile(90.0)),
+                "p99": micros(self.percentile(99.0)),
+                "p999": micros(self.percentile(99.9)),
+                "max": micros(self.percentile(100.0)),
+            },
+        })
+    }
+
+    /// Print the report for people
+    pub fn print(&self) {
+        let micros = |percentile: f64| self.percentile(percentile).as_secs_f64() * 1e6;
+        println!(
+            "Workload: {}, {} threads, {} operations, {} byte values",
+            self.workload.name(),
+            self.threads,
+            self.operations,
+            self.value_size
+        );
+        println!(
+            "Throughput: {:.0} ops/s ({:.2}s)",
+            self.ops_per_sec(),
+            self.elapsed.as_secs_f64()
+        );
+        println!(
+            "Latency (µs): p50 {:.1}, p90 {:.1}, p99 {:.1}, p99.9 {:.1}, max {:.1}",
+            micros(50.0),
+            micros(90.0),
+            micros(99.0),
+            micros(99.9),
+            micros(100.0)
+        );
+        if self.not_found > 0 {
+            println!("Warning: {} gets found no item", self.not_found);
+        }
+    }
+}
+
+/// Run the benchmark described by `options` against `db`
+pub fn run(db: &Database, options: &BenchOptions) -> Result<BenchReport> {
+    if options.threads == 0 || options.items == 0 {
+        anyhow::bail!("--threads and --items must be at least 1");
+    }
+
+    let item = ItemBuilder::new().string("value", "x".repeat(options.value_size)).build();
+    if options.workload != Workload::Put {
+        for i in 0..options.items {
+            db.put(&key(i), item.clone()).context("Failed to load items")?;
+        }
+    }
+
+    let start = Instant::now();
+    let results: Vec<Result<(Vec<Duration>, u64)>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads)
+            .map(|thread| {
+                let item = &item;
+                scope.spawn(move || run_thread(db, options, thread, item))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap_or_else(|_| Err(anyhow::anyhow!("Benchmark thread panicked"))))
+            .collect()
+    });
+    let elapsed = start.elapsed();
+
+    let mut latencies = Vec::with_capacity(options.items as usize);
+    let mut not_found = 0;
+    for result in results {
+        let (thread_latencies, thread_not_found) = result?;
+        latencies.extend(thread_latencies);
+        not_found += thread_not_found;
+    }
+    latencies.sort_unstable();
+
+    Ok(BenchReport {
+        workload: options.workload,
+        threads: options.threads,
+        operations: latencies.len() as u64,
+        value_size: options.value_size,
+        not_found,
+        elapsed,
+        latencies,
+    })
+}
+
+/// Run every `threads`-th operation, starting at `thread`
+fn run_thread(db: &Database, options: &BenchOptions, thread: usize, item: &Item) -> Result<(Vec<Duration>, u64)> {
+    let mut latencies = Vec::with_capacity((options.items / options.threads as u64 + 1) as usize);
+    let mut not_found = 0;
+
+    for i in (thread as u64..options.items).step_by(options.threads) {
+        let get = match options.workload {
+            Workload::Put => false,
+            Workload::Get => true,
+            Workload::Mixed => i % 2 == 0,
+        };
+        let started = Instant::now();
+        if get {
+            // Reads hop around the key space instead of following write order
+            let index = i.wrapping_mul(2_654_435_761) % options.items;
+            if db.get(&key(index)).context("Get failed")?.is_none() {
+                not_found += 1;
+            }
+        } else {
+            db.put(&key(i), item.clone()).context("Put failed")?;
+        }
+        latencies.push(started.elapsed());
+    }
+    Ok((latencies, not_found))
+}
+
+fn key(i: u64) -> Vec<u8> {
+    format!("bench#{:010}", i).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(workload: Workload) -> BenchOptions {
+        BenchOptions {
+            workload,
+            threads: 3,
+            items: 200,
+            value_size: 16,
+        }
+    }
+
+    #[test]
+    fn test_bench_workloads() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+
+        let report = run(&db, &options(Workload::Put)).unwrap();
+        assert_eq!(report.operations, 200);
+        assert!(db.get(&key(199)).unwrap().is_some());
+
+        for workload in [Workload::Get, Workload::Mixed] {
+            let report = run(&db, &options(workload)).unwrap();
+            assert_eq!(report.operations, 200);
+            assert_eq!(report.not_found, 0);
+        }
+
+        let json = report.to_json();
+        assert_eq!(json["workload"], "put");
+        assert!(json["latency_us"]["p99"].as_f64().unwrap() <= json["latency_us"]["max"].as_f64().unwrap());
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let report = BenchReport {
+            workload: Workload::Get,
+            threads: 1,
+            operations: 100,
+            value_size: 0,
+            not_found: 0,
+            elapsed: Duration::from_secs(2),
+            latencies: (1..=100).map(Duration::from_millis).collect(),
+        };
+        assert_eq!(report.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(report.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(report.percentile(100.0), Duration::from_millis(100));
+        assert_eq!(report.percentile(0.0), Duration::from_millis(1));
+        assert_eq!(report.ops_per_sec(), 50.0);
+    }
+}
@@ -0,0 +1,242 @@
+/// `kstone sync conflicts` / `kstone sync resolve`: the conflict inbox
+///
+/// Lists the conflicts a sync left for manual resolution, and resolves them
+/// either directly (`--keep local|remote`) or by walking through them and
+/// asking which version to keep.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use kstone_api::{item_to_json, Database, ItemJsonExt};
+use kstone_core::Item;
+use kstone_sync::{Conflict, ConflictChoice, ConflictInbox};
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+/// Which version `--keep` resolves conflicts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictSide {
+    Local,
+    Remote,
+}
+
+/// Print the pending conflicts of the database
+pub fn list(db: Arc<Database>, json: bool) -> Result<()> {
+    let conflicts = ConflictInbox::new(db).list()?;
+
+    if json {
+        let conflicts: Vec<_> = conflicts.iter().map(conflict_json).collect();
+        println!("{}", serde_json::to_string_pretty(&conflicts)?);
+        return Ok(());
+    }
+
+    if conflicts.is_empty() {
+        println!("No pending conflicts");
+        return Ok(());
+    }
+    let mut out = std::io::stdout().lock();
+    for conflict in &conflicts {
+        print_conflict(&mut out, conflict)?;
+    }
+    writeln!(out, "{} pending conflict(s); resolve with 'kstone sync resolve'", conflicts.len())?;
+    Ok(())
+}
+
+/// Resolve one conflict, or every pending conflict, asking which version
+/// to keep unless `keep` says
+pub fn resolve(db: Arc<Database>, conflict_id: Option<&str>, keep: Option<ConflictSide>) -> Result<()> {
+    let inbox = ConflictInbox::new(db);
+    let conflicts = match conflict_id {
+        Some(id) => vec![inbox
+            .get(id)?
+            .with_context(|| format!("Conflict not found: {}", id))?],
+        None => inbox.list()?,
+    };
+
+    if conflicts.is_empty() {
+        println!("No pending conflicts");
+        return Ok(());
+    }
+
+    match keep {
+        Some(side) => {
+            for conflict in &conflicts {
+                inbox.resolve(&conflict.id, choice(side))?;
+            }
+            println!("✓ Resolved {} conflict(s)", conflicts.len());
+            Ok(())
+        }
+        None => {
+            let stdin = std::io::stdin();
+            let resolved = resolve_interactively(&inbox, &conflicts, &mut stdin.lock(), &mut std::io::stdout())?;
+            println!("✓ Resolved {} of {} conflict(s)", resolved, conflicts.len());
+            Ok(())
+        }
+    }
+}
+
+/// Ask about each conflict in turn, returning how many were resolved
+fn resolve_interactively(
+    inbox: &ConflictInbox,
+    conflicts: &[Conflict],
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<usize> {
+    let mut resolved = 0;
+
+    'conflicts: for (i, conflict) in conflicts.iter().enumerate() {
+        writeln!(out, "Conflict {}/{}", i + 1, conflicts.len())?;
+        print_conflict(out, conflict)?;
+
+        loop {
+            write!(out, "Keep [l]ocal, [r]emote, [e]nter an item as JSON, [s]kip, [q]uit? ")?;
+            out.flush()?;
+            let Some(answer) = read_line(input)? else {
+                break 'conflicts;
+            };
+
+            let choice = match answer.as_str() {
+                "l" | "local" => choice(ConflictSide::Local),
+                "r" | "remote" => choice(ConflictSide::Remote),
+                "e" | "enter" => {
+                    write!(out, "Item JSON (empty to delete the item): ")?;
+                    out.flush()?;
+                    let json = read_line(input)?.unwrap_or_default();
+                    if json.is_empty() {
+                        ConflictChoice::Merged(None)
+                    } else {
+                        match parse_item(&json) {
+                            Ok(item) => ConflictChoice::Merged(Some(item)),
+                            Err(e) => {
+                                writeln!(out, "Invalid item: {}", e)?;
+                                continue;
+                            }
+                        }
+                    }
+                }
+                "s" | "skip" => continue 'conflicts,
+                "q" | "quit" => break 'conflicts,
+                _ => continue,
+            };
+
+            inbox.resolve(&conflict.id, choice)?;
+            resolved += 1;
+            writeln!(out, "✓ Resolved\n")?;
+            continue 'conflicts;
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn choice(side: ConflictSide) -> ConflictChoice {
+    match side {
+        ConflictSide::Local => ConflictChoice::KeepLocal,
+        ConflictSide::Remote => ConflictChoice::KeepRemote,
+    }
+}
+
+fn parse_item(json: &str) -> Result<Item> {
+    let json: serde_json::Value = serde_json::from_str(json)?;
+    Ok(Item::from_json(json)?)
+}
+
+/// A trimmed line of input, or None at end of input
+fn read_line(input: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+fn print_conflict(out: &mut impl Write, conflict: &Conflict) -> Result<()> {
+    let version = |item: &Option<Item>| match item {
+        Some(item) => item_to_json(item).to_string(),
+        None => "(deleted)".to_string(),
+    };
+
+    writeln!(out, "{}", conflict.id)?;
+    writeln!(out, "  Key:      {}", format_key(conflict))?;
+    let detected = chrono::DateTime::from_timestamp_millis(conflict.detected_at)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| conflict.detected_at.to_string());
+    writeln!(out, "  Detected: {}", detected)?;
+    writeln!(out, "  Local:    {}", version(&conflict.local_item))?;
+    writeln!(out, "  Remote:   {}", version(&conflict.remote_item))?;
+    Ok(())
+}
+
+fn format_key(conflict: &Conflict) -> String {
+    let pk = String::from_utf8_lossy(&conflict.key.pk);
+    match &conflict.key.sk {
+        Some(sk) => format!("{} / {}", pk, String::from_utf8_lossy(sk)),
+        None => pk.to_string(),
+    }
+}
+
+fn conflict_json(conflict: &Conflict) -> serde_json::Value {
+    let version = |item: &Option<Item>| {
+        item.as_ref().map_or(serde_json::Value::Null, item_to_json)
+    };
+    serde_json::json!({
+        "id": conflict.id,
+        "pk": String::from_utf8_lossy(&conflict.key.pk),
+        "sk": conflict.key.sk.as_ref().map(|sk| String::from_utf8_lossy(sk).to_string()),
+        "detected_at": conflict.detected_at,
+        "local": version(&conflict.local_item),
+        "remote": version(&conflict.remote_item),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use kstone_core::Key;
+    use kstone_sync::{ConflictStrategy, VectorClock};
+    use std::io::Cursor;
+
+    fn conflict(pk: &[u8]) -> Conflict {
+        Conflict::new(
+            Key::new(pk.to_vec()),
+            Some(ItemBuilder::new().string("name", "local").build()),
+            Some(ItemBuilder::new().string("name", "remote").build()),
+            VectorClock::new(),
+            VectorClock::new(),
+            100,
+            200,
+            ConflictStrategy::Manual,
+        )
+    }
+
+    #[test]
+    fn test_resolve_interactively() {
+        let db = Arc::new(Database::create_in_memory().unwrap());
+        let inbox = ConflictInbox::new(db.clone());
+        let conflicts = vec![conflict(b"a"), conflict(b"b"), conflict(b"c"), conflict(b"d")];
+        for conflict in &conflicts {
+            inbox.add(conflict).unwrap();
+        }
+
+        // Remote for a; an unknown answer then local for b; skip c; a typed item for d
+        let mut input = Cursor::new("r\nx\nl\ns\ne\n{\"name\": \"merged\"}\n");
+        let mut out = Vec::new();
+        let resolved = resolve_interactively(&inbox, &conflicts, &mut input, &mut out).unwrap();
+        assert_eq!(resolved, 3);
+
+        let name = |pk: &[u8]| {
+            db.get(pk)
+                .unwrap()
+                .and_then(|item| item.get("name").and_then(|v| v.as_string()).map(str::to_string))
+        };
+        assert_eq!(name(b"a").as_deref(), Some("remote"));
+        assert_eq!(name(b"b").as_deref(), Some("local"));
+        assert_eq!(name(b"c"), None);
+        assert_eq!(name(b"d").as_deref(), Some("merged"));
+
+        let pending = inbox.list().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, conflicts[2].id);
+        assert!(String::from_utf8(out).unwrap().contains("Conflict 4/4"));
+    }
+}
@@ -0,0 +1,249 @@
+/// `kstone diff`: compare the items of two databases
+///
+/// Each database is summarized as a merkle tree (from kstone-sync) of its
+/// keys and item hashes; comparing the trees skips every subtree whose hash
+/// matches, so only the differing keys are looked at again.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use colored::Colorize;
+use kstone_api::{item_to_json, Database, Scan, Select};
+use kstone_core::{Item, Key};
+use kstone_sync::MerkleTree;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Keys listed per scan page
+const KEYS_PAGE_SIZE: usize = 1000;
+
+/// Children per merkle tree node, as sync uses
+const BRANCHING_FACTOR: usize = 16;
+
+/// How to print the differences
+pub struct DiffOptions {
+    /// Print only keys, not items
+    pub keys_only: bool,
+    /// Print one JSON document
+    pub json: bool,
+}
+
+/// Keys whose items differ between database A and database B
+#[derive(Debug, Default)]
+pub struct DbDiff {
+    pub only_in_a: Vec<Key>,
+    pub only_in_b: Vec<Key>,
+    /// Keys in both databases with different items
+    pub changed: Vec<Key>,
+}
+
+impl DbDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn count(&self) -> usize {
+        self.only_in_a.len() + self.only_in_b.len() + self.changed.len()
+    }
+}
+
+/// Compare `a` with `b` and print the differences
+///
+/// Fails if the databases differ, so scripts can check the exit status.
+pub fn run(a: &Database, b: &Database, options: &DiffOptions) -> Result<()> {
+    let diff = diff(a, b)?;
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&diff_json(a, b, &diff, options.keys_only)?)?);
+    } else {
+        print(a, b, &diff, options.keys_only)?;
+    }
+
+    if !diff.is_empty() {
+        anyhow::bail!("Databases differ in {} keys", diff.count());
+    }
+    Ok(())
+}
+
+/// Keys that are only in `a`, only in `b`, or hold different items
+pub fn diff(a: &Database, b: &Database) -> Result<DbDiff> {
+    let (tree_a, keys_a) = build_tree(a).context("Failed to read database A")?;
+    let (tree_b, keys_b) = build_tree(b).context("Failed to read database B")?;
+    let merkle_diff = tree_a.diff(&tree_b);
+
+    let mut diff = DbDiff {
+        only_in_a: merkle_diff.only_in_left.iter().map(|(key, _)| keys_a[key].clone()).collect(),
+        only_in_b: merkle_diff.only_in_right.iter().map(|(key, _)| keys_b[key].clone()).collect(),
+        changed: merkle_diff.modified.iter().map(|(key, _, _)| keys_a[key].clone()).collect(),
+    };
+    diff.only_in_a.sort();
+    diff.only_in_b.sort();
+    diff.changed.sort();
+    Ok(diff)
+}
+
+/// Merkle tree of every item in `db`, and the keys behind its leaves
+fn build_tree(db: &Database) -> Result<(MerkleTree, HashMap<Bytes, Key>)> {
+    let mut leaves = Vec::new();
+    let mut keys = HashMap::new();
+    for key in list_keys(db)? {
+        // Keys listed a moment ago may have been deleted since
+        let Some(item) = get(db, &key)? else { continue };
+        let encoded = key.encode();
+        leaves.push((encoded.clone(), Bytes::from(item_bytes(&item)?)));
+        keys.insert(encoded, key);
+    }
+
+    // Both trees must order their leaves the same way
+    leaves.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    Ok((MerkleTree::build(leaves, BRANCHING_FACTOR)?, keys))
+}
+
+fn list_keys(db: &Database) -> Result<Vec<Key>> {
+    let mut keys = Vec::new();
+    let mut last_key: Option<(Bytes, Option<Bytes>)> = None;
+    loop {
+        let mut scan = Scan::new().select(Select::Keys).limit(KEYS_PAGE_SIZE);
+        if let Some((pk, sk)) = &last_key {
+            scan = scan.start_after(pk, sk.as_deref());
+        }
+        let page = db.scan(scan).context("Failed to list keys")?;
+        keys.extend(page.keys.into_iter().map(|(pk, sk)| Key { pk, sk }));
+        last_key = page.last_key;
+        if last_key.is_none() {
+            return Ok(keys);
+        }
+    }
+}
+
+fn get(db: &Database, key: &Key) -> Result<Option<Item>> {
+    let item = match &key.sk {
+        Some(sk) => db.get_with_sk(&key.pk, sk),
+        None => db.get(&key.pk),
+    };
+    item.context("Failed to read item")
+}
+
+/// Bytes hashed for an item: equal items give equal bytes
+fn item_bytes(item: &Item) -> Result<Vec<u8>> {
+    // serde_json::Value sorts map keys, unlike the HashMaps inside Item
+    Ok(serde_json::to_vec(&serde_json::to_value(item)?)?)
+}
+
+fn format_key(key: &Key) -> String {
+    match &key.sk {
+        Some(sk) => format!("{} / {}", String::from_utf8_lossy(&key.pk), String::from_utf8_lossy(sk)),
+        None => String::from_utf8_lossy(&key.pk).to_string(),
+    }
+}
+
+fn item_json(db: &Database, key: &Key) -> Result<serde_json::Value> {
+    Ok(get(db, key)?.as_ref().map(item_to_json).unwrap_or(serde_json::Value::Null))
+}
+
+fn print(a: &Database, b: &Database, diff: &DbDiff, keys_only: bool) -> Result<()> {
+    if diff.is_empty() {
+        println!("{}", "Databases are identical".green());
+        return Ok(());
+    }
+
+    let sections = [
+        ("Only in A", &diff.only_in_a, Some(a), None),
+        ("Only in B", &diff.only_in_b, None, Some(b)),
+        ("Different", &diff.changed, Some(a), Some(b)),
+    ];
+    for (title, keys, from_a, from_b) in sections {
+        if keys.is_empty() {
+            continue;
+        }
+        println!("{}", format!("{} ({}):", title, keys.len()).bold());
+        for key in keys {
+            println!("  {}", format_key(key));
+            if keys_only {
+                continue;
+            }
+            if let Some(db) = from_a {
+                println!("    A: {}", serde_json::to_string(&item_json(db, key)?)?);
+            }
+            if let Some(db) = from_b {
+                println!("    B: {}", serde_json::to_string(&item_json(db, key)?)?);
+            }
+        }
+        println!();
+    }
+
+    println!(
+        "{} only in A, {} only in B, {} different",
+        diff.only_in_a.len(),
+        diff.only_in_b.len(),
+        diff.changed.len()
+    );
+    Ok(())
+}
+
+/// The differences as JSON, for scripts
+fn diff_json(a: &Database, b: &Database, diff: &DbDiff, keys_only: bool) -> Result<serde_json::Value> {
+    let entries = |keys: &[Key], from_a: Option<&Database>, from_b: Option<&Database>| -> Result<Vec<serde_json::Value>> {
+        keys.iter()
+            .map(|key| {
+                let mut entry = json!({
+                    "pk": String::from_utf8_lossy(&key.pk),
+                    "sk": key.sk.as_ref().map(|sk| String::from_utf8_lossy(sk).to_string()),
+                });
+                if !keys_only {
+                    if let Some(db) = from_a {
+                        entry["a"] = item_json(db, key)?;
+                    }
+                    if let Some(db) = from_b {
+                        entry["b"] = item_json(db, key)?;
+                    }
+                }
+                Ok(entry)
+            })
+            .collect()
+    };
+
+    Ok(json!({
+        "identical": diff.is_empty(),
+        "only_in_a": entries(&diff.only_in_a, Some(a), None)?,
+        "only_in_b": entries(&diff.only_in_b, None, Some(b))?,
+        "different": entries(&diff.changed, Some(a), Some(b))?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diff_databases() {
+        let dir_a = TempDir::new().unwrap();
+        let dir_b = TempDir::new().unwrap();
+        let a = Database::create(dir_a.path()).unwrap();
+        let b = Database::create(dir_b.path()).unwrap();
+
+        for i in 0..100 {
+            let item = ItemBuilder::new().number("n", i).build();
+            a.put(format!("user#{:03}", i).as_bytes(), item.clone()).unwrap();
+            b.put(format!("user#{:03}", i).as_bytes(), item).unwrap();
+        }
+        a.flush().unwrap();
+        assert!(diff(&a, &b).unwrap().is_empty());
+
+        a.delete(b"user#005").unwrap();
+        b.put(b"user#050", ItemBuilder::new().number("n", -1).build()).unwrap();
+        b.put_with_sk(b"order", b"1", ItemBuilder::new().string("status", "new").build()).unwrap();
+
+        let diff = diff(&a, &b).unwrap();
+        assert!(diff.only_in_a.is_empty());
+        assert_eq!(diff.only_in_b, vec![Key::with_sk(b"order".to_vec(), b"1".to_vec()), Key::new(b"user#005".to_vec())]);
+        assert_eq!(diff.changed, vec![Key::new(b"user#050".to_vec())]);
+
+        let json = diff_json(&a, &b, &diff, false).unwrap();
+        assert_eq!(json["identical"], false);
+        assert_eq!(json["different"][0]["a"]["n"], 50);
+        assert_eq!(json["different"][0]["b"]["n"], -1);
+        assert_eq!(json["only_in_b"][0]["sk"], "1");
+        assert!(diff_json(&a, &b, &diff, true).unwrap()["different"][0].get("a").is_none());
+    }
+}
@@ -0,0 +1,120 @@
+/// `kstone load`: bulk load a JSON Lines file
+///
+/// Each line is one item; its keys are taken from the `--pk` (and `--sk`)
+/// attributes. Items are written straight to SST files with
+/// `Database::bulk_load`, which is much faster than putting them one by one.
+
+use anyhow::{Context, Result};
+use kstone_api::{Database, ItemJsonExt, KeystoneValue};
+use kstone_core::{Item, Key};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Where item keys come from
+pub struct LoadOptions {
+    /// Attribute holding the partition key
+    pub pk_attr: String,
+    /// Attribute holding the sort key, if the items have one
+    pub sk_attr: Option<String>,
+}
+
+/// Load the items in `file` ("-" for stdin) into `db`
+pub fn run(db: &Database, file: &Path, options: &LoadOptions) -> Result<()> {
+    let reader: Box<dyn BufRead> = if file == Path::new("-") {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        let input = File::open(file).with_context(|| format!("Failed to open {}", file.display()))?;
+        Box::new(BufReader::new(input))
+    };
+
+    // The first bad line ends the input; bulk_load takes plain items
+    let mut error = None;
+    let items = reader
+        .lines()
+        .enumerate()
+        .map_while(|(index, line)| {
+            match line.map_err(anyhow::Error::from).and_then(|line| parse_line(&line, options)) {
+                Ok(parsed) => Some(parsed),
+                Err(err) => {
+                    error = Some(err.context(format!("Line {}", index + 1)));
+                    None
+                }
+            }
+        })
+        .flatten();
+
+    let stats = db.bulk_load(items).context("Failed to load items")?;
+    if let Some(err) = error {
+        // Bulk loads aren't atomic; rerunning the fixed file is safe
+        return Err(err.context(format!(
+            "Stopped after loading {} items; fix the input and load it again",
+            stats.items
+        )));
+    }
+
+    println!("Loaded {} items into {} SST files", stats.items, stats.sst_files);
+    Ok(())
+}
+
+/// Parse one line into a key and item (`None` for blank lines)
+fn parse_line(line: &str, options: &LoadOptions) -> Result<Option<(Key, Item)>> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let json: serde_json::Value = serde_json::from_str(line).context("Invalid JSON")?;
+    let item = Item::from_json(json)?;
+    let pk = key_attribute(&item, &options.pk_attr)?;
+    let key = match &options.sk_attr {
+        Some(sk_attr) => Key::with_sk(pk, key_attribute(&item, sk_attr)?),
+        None => Key::new(pk),
+    };
+    Ok(Some((key, item)))
+}
+
+/// Bytes of a key attribute, which must be a string, number or binary
+fn key_attribute(item: &Item, name: &str) -> Result<Vec<u8>> {
+    match item.get(name) {
+        Some(KeystoneValue::S(s)) | Some(KeystoneValue::N(s)) => Ok(s.as_bytes().to_vec()),
+        Some(KeystoneValue::B(b)) => Ok(b.to_vec()),
+        Some(_) => anyhow::bail!("Key attribute '{}' must be a string, number or binary", name),
+        None => anyhow::bail!("Missing key attribute '{}'", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(sk_attr: Option<&str>) -> LoadOptions {
+        LoadOptions {
+            pk_attr: "id".to_string(),
+            sk_attr: sk_attr.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_line_keys() {
+        let (key, item) = parse_line(r#"{"id": "user#1", "name": "Alice"}"#, &options(None))
+            .unwrap()
+            .unwrap();
+        assert_eq!(key, Key::new(b"user#1".to_vec()));
+        // The key attributes stay in the item
+        assert_eq!(item.len(), 2);
+
+        let (key, _) = parse_line(r#"{"id": "user#1", "ts": 42}"#, &options(Some("ts")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(key, Key::with_sk(b"user#1".to_vec(), b"42".to_vec()));
+
+        assert!(parse_line("   ", &options(None)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert!(parse_line(r#"{"name": "Alice"}"#, &options(None)).is_err());
+        assert!(parse_line(r#"{"id": true}"#, &options(None)).is_err());
+        assert!(parse_line("not json", &options(None)).is_err());
+    }
+}
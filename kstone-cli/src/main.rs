@@ -1,13 +1,23 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand, ValueEnum};
-use kstone_api::{Database, KeystoneValue, ExecuteStatementResponse};
+use kstone_api::{item_to_json, Database, KeystoneError, KeystoneValue, ExecuteStatementResponse, VerifyReport};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 mod shell;
 mod table;
 mod notebook;
+mod remote;
+mod scan;
+mod load;
+mod stats;
+mod bench;
+mod watch;
+mod diff;
+mod top;
+mod sync_progress;
+mod conflicts;
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
 enum OutputFormat {
@@ -27,6 +37,9 @@ enum OutputFormat {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Break a stale database lock (only if its owner is no longer running)
+    #[arg(long, global = true)]
+    force: bool,
 }
 
 #[derive(Subcommand)]
@@ -72,15 +85,152 @@ enum Commands {
         #[arg(short, long, value_enum, default_value = "table")]
         output: OutputFormat,
     },
+    /// Scan items, printing them page by page
+    Scan {
+        /// Database file path
+        path: PathBuf,
+        /// Only items whose partition key starts with this
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Maximum number of items to return
+        #[arg(short, long)]
+        limit: Option<usize>,
+        /// Scan one segment of a parallel scan (0-based)
+        #[arg(long, requires = "total_segments")]
+        segment: Option<usize>,
+        /// Number of segments the scan is split into
+        #[arg(long, requires = "segment")]
+        total_segments: Option<usize>,
+        /// Output format (table, json, jsonl, csv)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Bulk load items from a JSON Lines file, bypassing the WAL
+    Load {
+        /// Database file path
+        path: PathBuf,
+        /// JSON Lines file with one item per line ("-" for stdin)
+        file: PathBuf,
+        /// Attribute holding the partition key
+        #[arg(long)]
+        pk: String,
+        /// Attribute holding the sort key
+        #[arg(long)]
+        sk: Option<String>,
+    },
+    /// Write a consistent backup of a database (checked after writing)
+    Backup {
+        /// Database file path
+        path: PathBuf,
+        /// Backup directory (must not exist or be empty)
+        dest: PathBuf,
+        /// Hard-link the SSTs this earlier backup already holds instead of copying them
+        #[arg(long, value_name = "BASE")]
+        incremental: Option<PathBuf>,
+    },
+    /// Restore a backup into a new database directory
+    Restore {
+        /// Backup directory
+        backup: PathBuf,
+        /// Database file path to restore into (must not exist or be empty)
+        #[arg(required_unless_present = "verify")]
+        path: Option<PathBuf>,
+        /// Only check the backup's checksums, without restoring it
+        #[arg(long, conflicts_with = "path")]
+        verify: bool,
+    },
+    /// Check a database for damage and inconsistencies
+    Verify {
+        /// Database file path
+        path: PathBuf,
+        /// Move damaged and orphaned files to <path>/quarantine and delete dangling index entries
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Show database statistics per stripe
+    Stats {
+        /// Database file path
+        path: PathBuf,
+        /// Also list every SST file
+        #[arg(long)]
+        ssts: bool,
+        /// Print JSON instead of tables
+        #[arg(long)]
+        json: bool,
+        /// Refresh every SECS seconds (default 2) until interrupted
+        #[arg(long, value_name = "SECS", num_args = 0..=1, default_missing_value = "2")]
+        watch: Option<u64>,
+    },
+    /// Benchmark the embedded engine
+    Bench {
+        /// Database file path (created if missing)
+        path: PathBuf,
+        /// Operations to run
+        #[arg(long, value_enum, default_value = "put")]
+        workload: bench::Workload,
+        /// Threads running operations
+        #[arg(long, default_value = "1")]
+        threads: usize,
+        /// Number of operations (and distinct keys)
+        #[arg(long, default_value = "100000")]
+        items: u64,
+        /// Bytes per item value
+        #[arg(long, default_value = "100")]
+        value_size: usize,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print change stream events, optionally following new ones
+    Watch {
+        /// Database file path
+        path: PathBuf,
+        /// Only print events with a higher sequence number
+        #[arg(long, value_name = "N")]
+        after_seq: Option<u64>,
+        /// Keep waiting for new events until interrupted
+        #[arg(short, long)]
+        follow: bool,
+        /// Print one JSON object per event
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the items of two databases
+    Diff {
+        /// First database (A)
+        a: PathBuf,
+        /// Second database (B)
+        b: PathBuf,
+        /// Print only the differing keys, not their items
+        #[arg(long)]
+        keys_only: bool,
+        /// Print the differences as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Live dashboard of ops/sec, memtables, compaction, hot partitions and stream lag
+    Top {
+        /// Database path, or a server's metrics URL (e.g. http://localhost:9090)
+        target: String,
+    },
     /// Start interactive shell
     Shell {
         /// Database file path (optional, defaults to :memory:)
         path: Option<PathBuf>,
     },
-    /// Launch notebook interface
+    /// Run a command against a kstone-server (defaults to the shell)
+    Connect {
+        #[command(flatten)]
+        args: remote::ConnectArgs,
+        #[command(subcommand)]
+        command: Option<remote::RemoteCommands>,
+    },
+    /// Launch notebook interface, or manage stored notebooks
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Notebook {
         /// Database file path
-        path: PathBuf,
+        #[arg(required = true)]
+        path: Option<PathBuf>,
         /// Port to serve on
         #[arg(short, long, default_value = "8080")]
         port: u16,
@@ -90,6 +240,8 @@ enum Commands {
         /// Don't automatically open browser
         #[arg(long)]
         no_browser: bool,
+        #[command(subcommand)]
+        command: Option<NotebookCommands>,
     },
     /// Cloud sync operations
     Sync {
@@ -98,6 +250,35 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum NotebookCommands {
+    /// List the notebooks stored in a database
+    List {
+        /// Database file path
+        path: PathBuf,
+    },
+    /// Export a notebook (cells and outputs) to a JSON or Markdown file
+    Export {
+        /// Database file path
+        path: PathBuf,
+        /// Notebook id or title
+        notebook: String,
+        /// File format
+        #[arg(short, long, value_enum, default_value = "json")]
+        format: notebook::ExportFormat,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import an exported notebook into a database
+    Import {
+        /// Database file path
+        path: PathBuf,
+        /// Exported notebook (JSON or Markdown)
+        file: PathBuf,
+    },
+}
+
 #[derive(Subcommand)]
 enum SyncCommands {
     /// Initialize sync metadata
@@ -136,6 +317,30 @@ enum SyncCommands {
         /// Sync interval in seconds (for continuous sync)
         #[arg(long, default_value = "30")]
         interval: u64,
+        /// Only sync partition keys starting with this prefix (repeatable)
+        #[arg(long = "include-prefix")]
+        include_prefixes: Vec<String>,
+        /// Never sync partition keys starting with this prefix (repeatable)
+        #[arg(long = "exclude-prefix")]
+        exclude_prefixes: Vec<String>,
+        /// Only sync items matching this filter expression (e.g. "tenant = :tenant")
+        #[arg(long)]
+        filter: Option<String>,
+        /// String value for a filter placeholder, as :placeholder=value (repeatable)
+        #[arg(long = "value")]
+        values: Vec<String>,
+        /// Maximum transfer rate in KiB per second
+        #[arg(long)]
+        bandwidth_limit: Option<u64>,
+        /// POST sync completions, conflicts and repeated failures to this URL
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Sign webhook payloads with this secret (X-Kstone-Signature header)
+        #[arg(long, requires = "webhook")]
+        webhook_secret: Option<String>,
+        /// Failed syncs in a row before the webhook is alerted
+        #[arg(long, default_value = "3")]
+        alert_after: u32,
     },
     /// Check sync status
     Status {
@@ -149,6 +354,27 @@ enum SyncCommands {
         /// Number of entries to show
         #[arg(short = 'n', long, default_value = "10")]
         limit: usize,
+        /// Print the runs as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List conflicts waiting for manual resolution
+    Conflicts {
+        /// Database file path
+        path: PathBuf,
+        /// Print the conflicts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Resolve pending conflicts, interactively unless --keep is given
+    Resolve {
+        /// Database file path
+        path: PathBuf,
+        /// Conflict ID (default: every pending conflict)
+        id: Option<String>,
+        /// Keep this version without asking
+        #[arg(long, value_enum)]
+        keep: Option<conflicts::ConflictSide>,
     },
 }
 
@@ -156,6 +382,7 @@ fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
     let cli = Cli::parse();
+    let force = cli.force;
 
     match cli.command {
         Commands::Create { path } => {
@@ -164,21 +391,19 @@ fn main() -> Result<()> {
         }
 
         Commands::Put { path, key, item } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
 
             // Parse JSON item
             let json: serde_json::Value =
                 serde_json::from_str(&item).context("Invalid JSON")?;
 
-            let item = json_to_item(&json)?;
-
-            db.put(key.as_bytes(), item)
+            db.put_json(key.as_bytes(), json)
                 .context("Failed to put item")?;
             println!("Item stored");
         }
 
         Commands::Get { path, key } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
 
             match db.get(key.as_bytes()).context("Failed to get item")? {
                 Some(item) => {
@@ -192,14 +417,14 @@ fn main() -> Result<()> {
         }
 
         Commands::Delete { path, key } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
             db.delete(key.as_bytes())
                 .context("Failed to delete item")?;
             println!("Item deleted");
         }
 
         Commands::Query { path, sql, limit, output } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
 
             // Append LIMIT clause if provided
             let sql_with_limit = if let Some(limit_val) = limit {
@@ -208,74 +433,145 @@ fn main() -> Result<()> {
                 sql
             };
 
-            match db.execute_statement(&sql_with_limit).context("Failed to execute statement")? {
-                ExecuteStatementResponse::Select {
-                    items,
-                    count,
-                    scanned_count,
-                    last_key,
-                } => {
-                    // Format and print results based on output format
-                    match output {
-                        OutputFormat::Table => {
-                            let table = table::format_items_table(&items);
-                            println!("{}", table);
-                            println!();
-                            println!("Count: {}, Scanned: {}", count, scanned_count);
-
-                            if last_key.is_some() {
-                                println!("(More results available - use LIMIT/OFFSET for pagination)");
-                            }
-                        }
-                        OutputFormat::Json => {
-                            // Pretty JSON array
-                            let json_items: Vec<_> = items.iter()
-                                .map(item_to_json)
-                                .collect();
-                            println!("{}", serde_json::to_string_pretty(&json_items)?);
-                        }
-                        OutputFormat::Jsonl => {
-                            // JSON Lines - one item per line
-                            for item in &items {
-                                let json = item_to_json(item);
-                                println!("{}", serde_json::to_string(&json)?);
-                            }
-                        }
-                        OutputFormat::Csv => {
-                            // CSV format
-                            format_csv(&items)?;
-                        }
-                    }
-                }
-                ExecuteStatementResponse::Insert { success } => {
-                    if success {
-                        println!("✓ Item inserted successfully");
-                    } else {
-                        println!("✗ Insert failed");
-                    }
+            let response = db.execute_statement(&sql_with_limit).context("Failed to execute statement")?;
+            print_statement_response(response, output)?;
+        }
+
+        Commands::Scan { path, prefix, limit, segment, total_segments, output } => {
+            let db = open_database(&path, force)?;
+            let options = scan::ScanOptions {
+                prefix,
+                limit,
+                segment: segment.zip(total_segments),
+            };
+            scan::run(&db, options, output)?;
+        }
+
+        Commands::Load { path, file, pk, sk } => {
+            let db = open_database(&path, force)?;
+            let options = load::LoadOptions { pk_attr: pk, sk_attr: sk };
+            load::run(&db, &file, &options)?;
+        }
+
+        Commands::Backup { path, dest, incremental } => {
+            let db = open_database(&path, force)?;
+            let info = match &incremental {
+                Some(base) => db.backup_incremental(&dest, base),
+                None => db.backup(&dest),
+            }
+            .context("Failed to back up database")?;
+
+            println!("Backup written to {}", dest.display());
+            println!("  Sequence number: {}", info.sequence_number);
+            println!(
+                "  SST files:       {} ({} linked, {} from the base backup)",
+                info.sst_files, info.linked_files, info.reused_files
+            );
+            println!("  Bytes copied:    {}", info.bytes_copied);
+        }
+
+        // Without a path clap has required --verify
+        Commands::Restore { backup, path, verify: _ } => match path {
+            Some(path) => {
+                Database::restore(&backup, &path).context("Failed to restore backup")?;
+                println!("Backup restored to {}", path.display());
+            }
+            None => {
+                Database::verify_backup(&backup).context("Backup verification failed")?;
+                println!("Backup {} is intact", backup.display());
+            }
+        },
+
+        Commands::Verify { path, repair } => {
+            let mut unrepaired = 0;
+            if repair {
+                // Damaged WAL entries can only be removed while the database is closed
+                let report = kstone_core::verify::verify_dir(&path, true)
+                    .context("Failed to verify database files")?;
+                print_verify_report("Database files", &report);
+                unrepaired += report.unrepaired().count();
+            }
+
+            match open_database(&path, force) {
+                Ok(db) => {
+                    let report = db.verify(repair).context("Failed to verify database")?;
+                    print_verify_report("Database", &report);
+                    unrepaired += report.unrepaired().count();
                 }
-                ExecuteStatementResponse::Update { item } => {
-                    println!("✓ Item updated successfully");
-                    println!();
-                    let json = item_to_json(&item);
-                    println!("{}", serde_json::to_string_pretty(&json)?);
+                // Too damaged to open: the file checks still say why
+                Err(err) if !repair => {
+                    eprintln!("{:#}", err);
+                    let report = kstone_core::verify::verify_dir(&path, false)
+                        .context("Failed to verify database files")?;
+                    print_verify_report("Database files", &report);
+                    unrepaired += report.unrepaired().count().max(1);
                 }
-                ExecuteStatementResponse::Delete { success } => {
-                    if success {
-                        println!("✓ Item deleted successfully");
-                    } else {
-                        println!("✗ Delete failed");
-                    }
+                Err(err) => return Err(err),
+            }
+
+            if unrepaired > 0 {
+                if repair {
+                    anyhow::bail!("{} problems could not be repaired", unrepaired);
                 }
+                anyhow::bail!("{} problems found; run with --repair to fix them", unrepaired);
             }
         }
 
+        Commands::Stats { path, ssts, json, watch } => {
+            stats::run(&path, &stats::StatsOptions { ssts, json, watch }, force)?;
+        }
+
+        Commands::Bench { path, workload, threads, items, value_size, json } => {
+            let db = if path.exists() {
+                open_database(&path, force)?
+            } else {
+                Database::create(&path).context("Failed to create database")?
+            };
+            let options = bench::BenchOptions { workload, threads, items, value_size };
+            let report = bench::run(&db, &options)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+            } else {
+                report.print();
+            }
+        }
+
+        Commands::Watch { path, after_seq, follow, json } => {
+            watch::run(&path, &watch::WatchOptions { after_seq, follow, json })?;
+        }
+
+        Commands::Diff { a, b, keys_only, json } => {
+            let db_a = Database::open_read_only(&a).with_context(|| format!("Failed to open {}", a.display()))?;
+            let db_b = Database::open_read_only(&b).with_context(|| format!("Failed to open {}", b.display()))?;
+            diff::run(&db_a, &db_b, &diff::DiffOptions { keys_only, json })?;
+        }
+
+        Commands::Top { target } => {
+            top::run(&top::Target::parse(&target))?;
+        }
+
         Commands::Shell { path } => {
-            let mut shell = shell::Shell::new(path.as_deref())?;
+            let mut shell = shell::Shell::new(path.as_deref(), force)?;
             shell.run()?;
         }
 
-        Commands::Notebook { path, port, host, no_browser } => {
+        Commands::Notebook { command: Some(command), .. } => match command {
+            NotebookCommands::List { path } => {
+                let db = Database::open_read_only(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+                notebook::list_notebooks(db)?;
+            }
+            NotebookCommands::Export { path, notebook, format, output } => {
+                let db = Database::open_read_only(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+                notebook::export_notebook(db, &notebook, format, output.as_deref())?;
+            }
+            NotebookCommands::Import { path, file } => {
+                let db = open_database(&path, force)?;
+                notebook::import_notebook(db, &file)?;
+            }
+        },
+
+        Commands::Notebook { path, port, host, no_browser, command: None } => {
+            let path = path.context("A database path is required")?;
             let config = notebook::NotebookConfig {
                 host,
                 port,
@@ -288,24 +584,126 @@ fn main() -> Result<()> {
             runtime.block_on(notebook::launch_notebook(&path, config))?;
         }
 
+        Commands::Connect { args, command } => {
+            remote::run(args, command)?;
+        }
+
         Commands::Sync { command } => {
-            handle_sync_command(command)?;
+            handle_sync_command(command, force)?;
         }
     }
 
     Ok(())
 }
 
-fn handle_sync_command(command: SyncCommands) -> Result<()> {
+/// Print the result of a PartiQL statement in `output` format
+fn print_statement_response(response: ExecuteStatementResponse, output: OutputFormat) -> Result<()> {
+    match response {
+        ExecuteStatementResponse::Select {
+            items,
+            count,
+            scanned_count,
+            last_key,
+            warnings,
+        } => {
+            for warning in &warnings {
+                eprintln!("Warning: {}", warning);
+            }
+
+            // Format and print results based on output format
+            match output {
+                OutputFormat::Table => {
+                    let table = table::format_items_table(&items);
+                    println!("{}", table);
+                    println!();
+                    println!("Count: {}, Scanned: {}", count, scanned_count);
+
+                    if last_key.is_some() {
+                        println!("(More results available - use LIMIT/OFFSET for pagination)");
+                    }
+                }
+                OutputFormat::Json => {
+                    // Pretty JSON array
+                    let json_items: Vec<_> = items.iter()
+                        .map(item_to_json)
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json_items)?);
+                }
+                OutputFormat::Jsonl => {
+                    // JSON Lines - one item per line
+                    for item in &items {
+                        let json = item_to_json(item);
+                        println!("{}", serde_json::to_string(&json)?);
+                    }
+                }
+                OutputFormat::Csv => {
+                    // CSV format
+                    format_csv(&items)?;
+                }
+            }
+        }
+        ExecuteStatementResponse::Insert { success } => {
+            if success {
+                println!("✓ Item inserted successfully");
+            } else {
+                println!("✗ Insert failed");
+            }
+        }
+        ExecuteStatementResponse::Update { item } => {
+            println!("✓ Item updated successfully");
+            println!();
+            let json = item_to_json(&item);
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        ExecuteStatementResponse::Delete { success } => {
+            if success {
+                println!("✓ Item deleted successfully");
+            } else {
+                println!("✗ Delete failed");
+            }
+        }
+        ExecuteStatementResponse::InsertSelect { inserted } => {
+            println!("✓ {} item(s) inserted successfully", inserted);
+        }
+        ExecuteStatementResponse::Explain { plan } => match output {
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                println!("{}", serde_json::to_string_pretty(&plan)?);
+            }
+            OutputFormat::Table | OutputFormat::Csv => {
+                println!("{}", plan);
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Open a database, breaking its lock with `--force`
+fn open_database(path: &Path, force: bool) -> Result<Database> {
+    let result = if force {
+        Database::open_force(path)
+    } else {
+        Database::open(path)
+    };
+
+    match result {
+        Ok(db) => Ok(db),
+        Err(err @ KeystoneError::DatabaseLocked { .. }) => Err(anyhow::Error::new(err).context(
+            "Database is in use; if the owning process is no longer running, retry with --force",
+        )),
+        Err(err) => Err(anyhow::Error::new(err).context("Failed to open database")),
+    }
+}
+
+fn handle_sync_command(command: SyncCommands, force: bool) -> Result<()> {
     use kstone_sync::{
         CloudSyncBuilder, SyncEndpoint, ConflictStrategy,
-        SyncMetadataStore, EndpointInfo,
+        SyncMetadataStore, EndpointInfo, SyncScope, SyncDirection, WebhookNotifier,
     };
     use std::time::Duration;
 
     match command {
         SyncCommands::Init { path } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
 
             // Initialize sync metadata
             let metadata_store = SyncMetadataStore::new(Arc::new(db));
@@ -321,7 +719,7 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
             region,
             table,
         } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
             let metadata_store = SyncMetadataStore::new(Arc::new(db));
 
             // Create endpoint based on type
@@ -376,8 +774,33 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
             strategy,
             continuous,
             interval,
+            include_prefixes,
+            exclude_prefixes,
+            filter,
+            values,
+            bandwidth_limit,
+            webhook,
+            webhook_secret,
+            alert_after,
         } => {
-            let _db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
+
+            let mut scope = SyncScope::all();
+            for prefix in include_prefixes {
+                scope = scope.with_include_prefix(prefix);
+            }
+            for prefix in exclude_prefixes {
+                scope = scope.with_exclude_prefix(prefix);
+            }
+            if let Some(filter) = filter {
+                scope = scope.with_filter(filter);
+            }
+            for value in values {
+                let (placeholder, text) = value
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Invalid --value {}. Use: :placeholder=value", value))?;
+                scope = scope.with_value(placeholder, KeystoneValue::string(text));
+            }
 
             // Parse conflict strategy
             let conflict_strategy = match strategy.as_str() {
@@ -424,19 +847,36 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
                 None
             };
 
-            let mut sync_engine = CloudSyncBuilder::new()
+            let mut builder = CloudSyncBuilder::new()
+                .with_database(Arc::new(db))
                 .with_endpoint(sync_endpoint)
                 .with_conflict_strategy(conflict_strategy)
-                .with_sync_interval(sync_interval.unwrap_or(Duration::from_secs(30)))
-                .build()
-                .context("Failed to create sync engine")?;
+                .with_scope(scope)
+                .with_sync_interval(sync_interval.unwrap_or(Duration::from_secs(30)));
+            if let Some(kib_per_sec) = bandwidth_limit {
+                builder = builder.with_bandwidth_limit(kib_per_sec * 1024);
+            }
+            if let Some(url) = webhook {
+                let mut notifier = WebhookNotifier::new(url);
+                if let Some(secret) = webhook_secret {
+                    notifier = notifier.with_secret(secret);
+                }
+                builder = builder
+                    .with_notifier(Arc::new(notifier))
+                    .with_failure_alert_threshold(alert_after);
+            }
+            let mut sync_engine = builder.build().context("Failed to create sync engine")?;
+
+            // Show progress when someone is watching
+            let show_progress = std::io::IsTerminal::is_terminal(&std::io::stderr());
+            let runtime = tokio::runtime::Runtime::new()?;
+            let progress = show_progress
+                .then(|| runtime.spawn(sync_progress::render(sync_engine.subscribe())));
 
             if continuous {
                 println!("✓ Starting continuous sync with {} (interval: {}s)", endpoint, interval);
                 println!("Press Ctrl+C to stop...");
 
-                // Use tokio runtime for async sync
-                let runtime = tokio::runtime::Runtime::new()?;
                 runtime.block_on(async {
                     sync_engine.start().await?;
                     // Keep running until interrupted
@@ -447,18 +887,21 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
             } else {
                 println!("✓ Starting one-time sync with {}", endpoint);
 
-                // Use tokio runtime for async sync
-                let runtime = tokio::runtime::Runtime::new()?;
-                runtime.block_on(async {
-                    sync_engine.sync_once().await
-                })?;
+                let result = runtime.block_on(sync_engine.sync_once());
+
+                // Dropping the engine ends the event stream, so the display finishes
+                drop(sync_engine);
+                if let Some(progress) = progress {
+                    let _ = runtime.block_on(progress);
+                }
+                result?;
 
                 println!("✓ Sync completed successfully");
             }
         }
 
         SyncCommands::Status { path } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+            let db = open_database(&path, force)?;
             let metadata_store = SyncMetadataStore::new(Arc::new(db));
 
             // Load metadata
@@ -471,6 +914,23 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
             println!("Local Endpoint: {}", metadata.local_endpoint.0);
             println!("Remote Endpoints: {}", metadata.remote_endpoints.len());
 
+            let scope = &metadata.config.scope;
+            if !scope.is_all() {
+                let prefixes = |prefixes: &[bytes::Bytes]| {
+                    prefixes.iter().map(|p| String::from_utf8_lossy(p).to_string()).collect::<Vec<_>>().join(", ")
+                };
+                println!("Scope:");
+                if !scope.include_prefixes.is_empty() {
+                    println!("  Include: {}", prefixes(&scope.include_prefixes));
+                }
+                if !scope.exclude_prefixes.is_empty() {
+                    println!("  Exclude: {}", prefixes(&scope.exclude_prefixes));
+                }
+                if let Some(filter) = &scope.filter {
+                    println!("  Filter: {}", filter);
+                }
+            }
+
             for endpoint in &metadata.remote_endpoints {
                 let status = if endpoint.active { "active" } else { "inactive" };
                 println!("  - {} ({}) [{}]", endpoint.url, endpoint.endpoint_type, status);
@@ -491,6 +951,13 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
 
                     println!("    Last sync: {}", time_str);
                 }
+
+                if let Some(transfer) = metadata_store.load_transfer_checkpoint(&endpoint.id)? {
+                    println!(
+                        "    Interrupted transfer: {}/{} changes, resumes on next sync",
+                        transfer.completed, transfer.total
+                    );
+                }
             }
 
             println!("\nSync Statistics:");
@@ -501,21 +968,60 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
             println!("  Conflicts resolved: {}", metadata.stats.conflicts_resolved);
         }
 
-        SyncCommands::History { path, limit } => {
-            let db = Database::open(&path).context("Failed to open database")?;
+        SyncCommands::History { path, limit, json } => {
+            let db = open_database(&path, force)?;
             let metadata_store = SyncMetadataStore::new(Arc::new(db));
 
             // Load metadata
             let metadata = metadata_store
                 .load_metadata()?
                 .ok_or_else(|| anyhow::anyhow!("Sync metadata not initialized"))?;
+            let runs = metadata_store.load_sync_history(limit)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&runs)?);
+                return Ok(());
+            }
 
             println!("Sync History for: {}", path.display());
             println!("─────────────────────────────────────────");
 
-            // In a real implementation, we'd store sync history records
-            // For now, just show basic info from metadata
-            println!("Total syncs performed: {}", metadata.stats.total_syncs);
+            if runs.is_empty() {
+                println!("No sync runs recorded");
+            }
+            for run in &runs {
+                let started = chrono::DateTime::from_timestamp_millis(run.started_at)
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| run.started_at.to_string());
+                let direction = match run.direction {
+                    SyncDirection::Bidirectional => "sync",
+                    SyncDirection::Push => "push",
+                    SyncDirection::Pull => "pull",
+                };
+                let status = if run.succeeded() { "✓" } else { "✗" };
+
+                println!(
+                    "{} {}  {:<4}  {}  ({:.1}s)",
+                    status,
+                    started,
+                    direction,
+                    run.endpoint_id.0,
+                    run.duration_ms() as f64 / 1000.0
+                );
+                println!(
+                    "    ↑ {} items ({})  ↓ {} items ({})  {} conflicts",
+                    run.items_sent,
+                    table::format_bytes(run.bytes_sent as u64),
+                    run.items_received,
+                    table::format_bytes(run.bytes_received as u64),
+                    run.conflicts
+                );
+                if let Some(error) = &run.error {
+                    println!("    Error: {}", error);
+                }
+            }
+
+            println!("\nTotal syncs performed: {}", metadata.stats.total_syncs);
             println!("Successful syncs: {}", metadata.stats.successful_syncs);
             println!("Failed syncs: {}", metadata.stats.failed_syncs);
 
@@ -523,125 +1029,20 @@ fn handle_sync_command(command: SyncCommands) -> Result<()> {
                 let success_rate = (metadata.stats.successful_syncs as f64 / metadata.stats.total_syncs as f64) * 100.0;
                 println!("Success rate: {:.1}%", success_rate);
             }
-
-            println!("\nNote: Detailed sync history will be available in a future version.");
-            println!("Showing last {} entries (when available)", limit);
         }
-    }
 
-    Ok(())
-}
-
-fn json_to_item(json: &serde_json::Value) -> Result<HashMap<String, KeystoneValue>> {
-    let obj = json
-        .as_object()
-        .context("Item must be a JSON object")?;
-
-    let mut item = HashMap::new();
-
-    for (key, value) in obj {
-        let ks_value = match value {
-            serde_json::Value::String(s) => KeystoneValue::string(s.clone()),
-            serde_json::Value::Number(n) => KeystoneValue::number(n),
-            serde_json::Value::Bool(b) => KeystoneValue::Bool(*b),
-            serde_json::Value::Null => KeystoneValue::Null,
-            serde_json::Value::Array(arr) => {
-                let mut list = Vec::new();
-                for item in arr {
-                    list.push(json_value_to_keystone(item)?);
-                }
-                KeystoneValue::L(list)
-            }
-            serde_json::Value::Object(_) => {
-                let nested = json_to_item(value)?;
-                KeystoneValue::M(nested)
-            }
-        };
-        item.insert(key.clone(), ks_value);
-    }
-
-    Ok(item)
-}
-
-fn json_value_to_keystone(value: &serde_json::Value) -> Result<KeystoneValue> {
-    Ok(match value {
-        serde_json::Value::String(s) => KeystoneValue::string(s.clone()),
-        serde_json::Value::Number(n) => KeystoneValue::number(n),
-        serde_json::Value::Bool(b) => KeystoneValue::Bool(*b),
-        serde_json::Value::Null => KeystoneValue::Null,
-        serde_json::Value::Array(arr) => {
-            let mut list = Vec::new();
-            for item in arr {
-                list.push(json_value_to_keystone(item)?);
-            }
-            KeystoneValue::L(list)
+        SyncCommands::Conflicts { path, json } => {
+            let db = open_database(&path, force)?;
+            conflicts::list(Arc::new(db), json)?;
         }
-        serde_json::Value::Object(_) => {
-            let nested = json_to_item(value)?;
-            KeystoneValue::M(nested)
-        }
-    })
-}
-
-fn item_to_json(item: &HashMap<String, KeystoneValue>) -> serde_json::Value {
-    let mut obj = serde_json::Map::new();
-
-    for (key, value) in item {
-        obj.insert(key.clone(), keystone_value_to_json(value));
-    }
-
-    serde_json::Value::Object(obj)
-}
 
-fn keystone_value_to_json(value: &KeystoneValue) -> serde_json::Value {
-    match value {
-        KeystoneValue::S(s) => serde_json::Value::String(s.clone()),
-        KeystoneValue::N(n) => {
-            // Try to parse as number
-            if let Ok(i) = n.parse::<i64>() {
-                serde_json::Value::Number(i.into())
-            } else if let Ok(f) = n.parse::<f64>() {
-                serde_json::Number::from_f64(f)
-                    .map(serde_json::Value::Number)
-                    .unwrap_or_else(|| serde_json::Value::String(n.clone()))
-            } else {
-                serde_json::Value::String(n.clone())
-            }
-        }
-        KeystoneValue::Bool(b) => serde_json::Value::Bool(*b),
-        KeystoneValue::Null => serde_json::Value::Null,
-        KeystoneValue::L(list) => {
-            let arr: Vec<_> = list.iter().map(keystone_value_to_json).collect();
-            serde_json::Value::Array(arr)
-        }
-        KeystoneValue::M(map) => item_to_json(map),
-        KeystoneValue::B(bytes) => {
-            // Encode binary as base64
-            serde_json::Value::String(base64_encode(bytes))
-        }
-        KeystoneValue::VecF32(vec) => {
-            // Encode f32 vector as JSON array
-            let arr: Vec<_> = vec.iter()
-                .map(|&f| serde_json::Number::from_f64(f as f64)
-                    .map(serde_json::Value::Number)
-                    .unwrap_or(serde_json::Value::Null))
-                .collect();
-            serde_json::Value::Array(arr)
-        }
-        KeystoneValue::Ts(ts) => {
-            // Encode timestamp as number
-            serde_json::Value::Number((*ts).into())
+        SyncCommands::Resolve { path, id, keep } => {
+            let db = open_database(&path, force)?;
+            conflicts::resolve(Arc::new(db), id.as_deref(), keep)?;
         }
     }
-}
 
-fn base64_encode(bytes: &[u8]) -> String {
-    use std::io::Write;
-    let mut buf = Vec::new();
-    let mut encoder = base64::write::EncoderWriter::new(&mut buf, &base64::engine::general_purpose::STANDARD);
-    encoder.write_all(bytes).unwrap();
-    drop(encoder);
-    String::from_utf8(buf).unwrap()
+    Ok(())
 }
 
 fn format_csv(items: &[HashMap<String, KeystoneValue>]) -> Result<()> {
@@ -706,75 +1107,23 @@ fn escape_csv(s: &str) -> String {
     }
 }
 
-mod base64 {
-    pub mod write {
-        use std::io::{self, Write};
-
-        pub struct EncoderWriter<'a, W: Write> {
-            writer: &'a mut W,
-            engine: &'a super::super::base64::engine::Engine,
-        }
-
-        impl<'a, W: Write> EncoderWriter<'a, W> {
-            pub fn new(writer: &'a mut W, engine: &'a super::super::base64::engine::Engine) -> Self {
-                Self { writer, engine }
-            }
-        }
-
-        impl<W: Write> Write for EncoderWriter<'_, W> {
-            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-                let encoded = (self.engine.encode)(buf);
-                self.writer.write_all(encoded.as_bytes())?;
-                Ok(buf.len())
-            }
+/// Print database statistics: a summary, the non-empty stripes and optionally every SST
+/// Print what a consistency check found
+pub fn print_verify_report(title: &str, report: &VerifyReport) {
+    use colored::Colorize;
 
-            fn flush(&mut self) -> io::Result<()> {
-                self.writer.flush()
-            }
-        }
+    println!("{}", title.bold());
+    println!("  WAL entries:   {}", report.wal_entries);
+    println!("  SST files:     {}", report.sst_files);
+    println!("  Index entries: {}", report.index_entries);
+    if report.is_clean() {
+        println!("  {}", "✓ No problems found".green());
+        return;
     }
 
-    pub mod engine {
-        pub mod general_purpose {
-            use super::Engine;
-
-            pub const STANDARD: Engine = Engine {
-                encode: |data| {
-                    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-                    let mut result = String::new();
-                    let mut i = 0;
-                    while i + 2 < data.len() {
-                        let b1 = data[i];
-                        let b2 = data[i + 1];
-                        let b3 = data[i + 2];
-                        result.push(CHARS[(b1 >> 2) as usize] as char);
-                        result.push(CHARS[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
-                        result.push(CHARS[(((b2 & 0x0f) << 2) | (b3 >> 6)) as usize] as char);
-                        result.push(CHARS[(b3 & 0x3f) as usize] as char);
-                        i += 3;
-                    }
-                    if i < data.len() {
-                        let b1 = data[i];
-                        result.push(CHARS[(b1 >> 2) as usize] as char);
-                        if i + 1 < data.len() {
-                            let b2 = data[i + 1];
-                            result.push(CHARS[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
-                            result.push(CHARS[((b2 & 0x0f) << 2) as usize] as char);
-                            result.push('=');
-                        } else {
-                            result.push(CHARS[((b1 & 0x03) << 4) as usize] as char);
-                            result.push('=');
-                            result.push('=');
-                        }
-                    }
-                    result
-                },
-            };
-        }
-
-        pub struct Engine {
-            pub encode: fn(&[u8]) -> String,
-        }
+    for problem in &report.problems {
+        let status = if problem.repaired { "repaired".green() } else { "damaged".red() };
+        println!("  [{}] {:?} {}: {}", status, problem.kind, problem.location, problem.detail);
     }
 }
 
@@ -802,6 +1151,13 @@ pub fn format_response_table(response: &ExecuteStatementResponse) -> Result<()>
         ExecuteStatementResponse::Delete { .. } => {
             println!("{}", "✓ Item deleted successfully".green());
         }
+        ExecuteStatementResponse::InsertSelect { inserted } => {
+            println!("{}", format!("✓ {} item(s) inserted successfully", inserted).green());
+        }
+        ExecuteStatementResponse::Explain { plan } => {
+            println!("{}", "Query plan".bold());
+            println!("{}", plan);
+        }
     }
     Ok(())
 }
@@ -836,6 +1192,17 @@ pub fn format_response_json(response: &ExecuteStatementResponse) -> Result<()> {
         ExecuteStatementResponse::Delete { .. } => {
             println!("{}", r#"{"success": true, "operation": "DELETE"}"#.green());
         }
+        ExecuteStatementResponse::InsertSelect { inserted } => {
+            let result = serde_json::json!({
+                "success": true,
+                "operation": "INSERT",
+                "inserted": inserted
+            });
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        ExecuteStatementResponse::Explain { plan } => {
+            println!("{}", serde_json::to_string_pretty(plan)?);
+        }
     }
     Ok(())
 }
@@ -872,6 +1239,17 @@ pub fn format_response_compact(response: &ExecuteStatementResponse) -> Result<()
         ExecuteStatementResponse::Delete { .. } => {
             println!("{}", "✓ DELETE completed".green());
         }
+        ExecuteStatementResponse::InsertSelect { inserted } => {
+            println!("{}", format!("✓ INSERT completed ({} items)", inserted).green());
+        }
+        ExecuteStatementResponse::Explain { plan } => {
+            println!(
+                "{} {} (~{} items scanned)",
+                "EXPLAIN".dimmed(),
+                plan.access_path,
+                plan.estimated_scanned
+            );
+        }
     }
     Ok(())
 }
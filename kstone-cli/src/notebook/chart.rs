@@ -0,0 +1,208 @@
+/// Chart cells: turn SELECT rows into labelled numeric series
+///
+/// Charts are meant for aggregation results such as
+/// `SELECT status, COUNT(*) AS n FROM items GROUP BY status`: one column
+/// labels the x axis (or the pie slices) and one or more numeric columns
+/// become series. The browser only draws what this module returns.
+
+use kstone_api::KeystoneValue;
+use kstone_core::Item;
+use serde::{Deserialize, Serialize};
+
+/// How a chart is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    #[default]
+    Bar,
+    Line,
+    /// Uses only the first series
+    Pie,
+}
+
+/// Axis and series selection stored in the cell metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartSpec {
+    #[serde(default)]
+    pub kind: ChartKind,
+    /// Label column; defaults to the first non-numeric column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    /// Value columns; defaults to every numeric column except `x`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<String>,
+}
+
+/// One named series of values, aligned with `ChartData::labels`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartSeries {
+    pub name: String,
+    /// `None` where a row has no numeric value for the column
+    pub values: Vec<Option<f64>>,
+}
+
+/// Everything the browser needs to draw a chart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartData {
+    pub kind: ChartKind,
+    pub x: String,
+    pub labels: Vec<String>,
+    pub series: Vec<ChartSeries>,
+}
+
+/// Build chart data from result rows
+///
+/// `columns` is the sorted union of the rows' attribute names.
+pub fn build_chart(spec: &ChartSpec, columns: &[String], items: &[Item]) -> Result<ChartData, String> {
+    if items.is_empty() {
+        return Err("The statement returned no rows to chart".to_string());
+    }
+
+    let x = match &spec.x {
+        Some(x) if columns.contains(x) => x.clone(),
+        Some(x) => return Err(format!("Column '{}' is not in the result", x)),
+        None => columns
+            .iter()
+            .find(|column| !is_numeric_column(items, column))
+            .or_else(|| columns.first())
+            .cloned()
+            .ok_or_else(|| "The result has no columns".to_string())?,
+    };
+
+    let mut series_columns: Vec<String> = if spec.series.is_empty() {
+        columns
+            .iter()
+            .filter(|column| **column != x && is_numeric_column(items, column))
+            .cloned()
+            .collect()
+    } else {
+        for column in &spec.series {
+            if !columns.contains(column) {
+                return Err(format!("Column '{}' is not in the result", column));
+            }
+        }
+        spec.series.clone()
+    };
+    if series_columns.is_empty() {
+        return Err("The result has no numeric column to plot; pick a series".to_string());
+    }
+    if spec.kind == ChartKind::Pie {
+        series_columns.truncate(1);
+    }
+
+    let labels = items
+        .iter()
+        .map(|item| item.get(&x).map(label).unwrap_or_default())
+        .collect();
+    let series = series_columns
+        .into_iter()
+        .map(|name| ChartSeries {
+            values: items
+                .iter()
+                .map(|item| item.get(&name).and_then(number))
+                .collect(),
+            name,
+        })
+        .collect();
+
+    Ok(ChartData {
+        kind: spec.kind,
+        x,
+        labels,
+        series,
+    })
+}
+
+/// A column is numeric if every row that has it holds a number
+fn is_numeric_column(items: &[Item], column: &str) -> bool {
+    let mut values = items.iter().filter_map(|item| item.get(column)).peekable();
+    values.peek().is_some() && values.all(|value| number(value).is_some())
+}
+
+fn number(value: &KeystoneValue) -> Option<f64> {
+    match value {
+        KeystoneValue::N(n) => n.parse().ok(),
+        KeystoneValue::Ts(ts) => Some(*ts as f64),
+        _ => None,
+    }
+}
+
+fn label(value: &KeystoneValue) -> String {
+    match value {
+        KeystoneValue::S(s) => s.clone(),
+        KeystoneValue::N(n) => n.clone(),
+        KeystoneValue::Bool(b) => b.to_string(),
+        KeystoneValue::Null => "null".to_string(),
+        other => kstone_api::json::value_to_json(other).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+
+    fn rows() -> (Vec<String>, Vec<Item>) {
+        let items = vec![
+            ItemBuilder::new().string("status", "open").number("n", 3).number("total", 10.5).build(),
+            ItemBuilder::new().string("status", "closed").number("n", 5).build(),
+        ];
+        let columns = vec!["n".to_string(), "status".to_string(), "total".to_string()];
+        (columns, items)
+    }
+
+    #[test]
+    fn test_defaults_pick_label_and_numeric_columns() {
+        let (columns, items) = rows();
+        let chart = build_chart(&ChartSpec::default(), &columns, &items).unwrap();
+
+        assert_eq!(chart.kind, ChartKind::Bar);
+        assert_eq!(chart.x, "status");
+        assert_eq!(chart.labels, vec!["open", "closed"]);
+        assert_eq!(chart.series.len(), 2);
+        assert_eq!(chart.series[0].name, "n");
+        assert_eq!(chart.series[0].values, vec![Some(3.0), Some(5.0)]);
+        assert_eq!(chart.series[1].values, vec![Some(10.5), None]);
+    }
+
+    #[test]
+    fn test_explicit_axis_and_series() {
+        let (columns, items) = rows();
+        let spec = ChartSpec {
+            kind: ChartKind::Line,
+            x: Some("n".to_string()),
+            series: vec!["total".to_string()],
+        };
+        let chart = build_chart(&spec, &columns, &items).unwrap();
+
+        assert_eq!(chart.labels, vec!["3", "5"]);
+        assert_eq!(chart.series.len(), 1);
+        assert_eq!(chart.series[0].name, "total");
+    }
+
+    #[test]
+    fn test_pie_keeps_first_series() {
+        let (columns, items) = rows();
+        let spec = ChartSpec {
+            kind: ChartKind::Pie,
+            ..ChartSpec::default()
+        };
+        let chart = build_chart(&spec, &columns, &items).unwrap();
+        assert_eq!(chart.series.len(), 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (columns, items) = rows();
+        let missing = ChartSpec {
+            x: Some("nope".to_string()),
+            ..ChartSpec::default()
+        };
+        assert!(build_chart(&missing, &columns, &items).is_err());
+        assert!(build_chart(&ChartSpec::default(), &columns, &[]).is_err());
+
+        let labels_only = vec![ItemBuilder::new().string("status", "open").build()];
+        let err = build_chart(&ChartSpec::default(), &["status".to_string()], &labels_only).unwrap_err();
+        assert!(err.contains("numeric"));
+    }
+}
@@ -0,0 +1,260 @@
+/// Run notebook cells against the database
+
+use super::chart::build_chart;
+use super::model::{Cell, CellOutput, CellType};
+use super::params;
+use kstone_api::{item_to_json, Database, ExecuteStatementRequest, ExecuteStatementResponse};
+use kstone_core::Item;
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+/// Run a cell and describe its result
+///
+/// Markdown cells have no output. `{{name}}` placeholders are bound from
+/// the cell's parameters. Errors are returned as `CellOutput::Error` so
+/// they show up under the cell.
+pub fn execute_cell(db: &Database, cell: &Cell) -> Option<CellOutput> {
+    if cell.cell_type == CellType::Markdown {
+        return None;
+    }
+    let statement = cell.source.trim().trim_end_matches(';').trim();
+    if statement.is_empty() {
+        return Some(CellOutput::Error {
+            message: "The cell is empty".to_string(),
+        });
+    }
+
+    let is_select = statement
+        .split_whitespace()
+        .next()
+        .is_some_and(|word| word.eq_ignore_ascii_case("select"));
+    if cell.cell_type == CellType::Chart && !is_select {
+        return Some(CellOutput::Error {
+            message: "Chart cells need a SELECT statement".to_string(),
+        });
+    }
+
+    let (statement, parameters) = match params::bind(statement, &cell.metadata.params) {
+        Ok(bound) => bound,
+        Err(message) => return Some(CellOutput::Error { message }),
+    };
+
+    let started = Instant::now();
+    let request = ExecuteStatementRequest::new(statement).with_parameters(parameters);
+    let response = match db.execute(request) {
+        Ok(response) => response,
+        Err(e) => return Some(CellOutput::Error { message: e.to_string() }),
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    Some(match (cell.cell_type, response) {
+        (
+            CellType::Chart,
+            ExecuteStatementResponse::Select { items, count, .. },
+        ) => {
+            let columns = columns(&items);
+            let spec = cell.metadata.chart.clone().unwrap_or_default();
+            match build_chart(&spec, &columns, &items) {
+                Ok(chart) => CellOutput::Chart {
+                    chart,
+                    columns,
+                    count,
+                    elapsed_ms,
+                },
+                Err(message) => CellOutput::Error { message },
+            }
+        }
+        (CellType::Chart, _) => CellOutput::Error {
+            message: "Chart cells need a SELECT statement".to_string(),
+        },
+        (
+            _,
+            ExecuteStatementResponse::Select {
+                items,
+                count,
+                scanned_count,
+                last_key,
+                warnings,
+            },
+        ) => table_output(&items, count, scanned_count, last_key.is_some(), warnings, elapsed_ms),
+        (_, ExecuteStatementResponse::Update { item }) => {
+            table_output(&[item], 1, 1, false, Vec::new(), elapsed_ms)
+        }
+        (_, ExecuteStatementResponse::Insert { success }) => CellOutput::Message {
+            text: if success {
+                "Inserted 1 item".to_string()
+            } else {
+                "An item with that key exists; nothing inserted".to_string()
+            },
+            elapsed_ms,
+        },
+        (_, ExecuteStatementResponse::Delete { success }) => CellOutput::Message {
+            text: if success {
+                "Deleted 1 item".to_string()
+            } else {
+                "Nothing deleted".to_string()
+            },
+            elapsed_ms,
+        },
+        (_, ExecuteStatementResponse::InsertSelect { inserted }) => CellOutput::Message {
+            text: format!("Inserted {} item(s)", inserted),
+            elapsed_ms,
+        },
+        (_, ExecuteStatementResponse::Explain { plan }) => CellOutput::Message {
+            text: serde_json::to_string_pretty(&plan).unwrap_or_else(|e| e.to_string()),
+            elapsed_ms,
+        },
+    })
+}
+
+/// Sorted union of the items' attribute names
+pub fn columns(items: &[Item]) -> Vec<String> {
+    items
+        .iter()
+        .flat_map(|item| item.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn table_output(
+    items: &[Item],
+    count: usize,
+    scanned_count: usize,
+    has_more: bool,
+    warnings: Vec<String>,
+    elapsed_ms: u64,
+) -> CellOutput {
+    let columns = columns(items);
+    let rows = items
+        .iter()
+        .map(|item| {
+            let json = item_to_json(item);
+            columns
+                .iter()
+                .map(|column| json.get(column).cloned().unwrap_or(serde_json::Value::Null))
+                .collect()
+        })
+        .collect();
+
+    CellOutput::Table {
+        columns,
+        rows,
+        count,
+        scanned_count,
+        has_more,
+        warnings,
+        elapsed_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::chart::{ChartKind, ChartSpec};
+    use tempfile::TempDir;
+
+    fn seeded() -> (TempDir, Database) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        for (pk, status, amount) in [("o#1", "open", 5), ("o#2", "open", 7), ("o#3", "closed", 1)] {
+            db.execute_statement(&format!(
+                "INSERT INTO items VALUE {{'pk': '{}', 'status': '{}', 'amount': {}}}",
+                pk, status, amount
+            ))
+            .unwrap();
+        }
+        (dir, db)
+    }
+
+    #[test]
+    fn test_query_cell_returns_table() {
+        let (_dir, db) = seeded();
+        let cell = Cell::new(CellType::Query, "SELECT * FROM items WHERE pk = 'o#1';");
+
+        match execute_cell(&db, &cell) {
+            Some(CellOutput::Table { columns, rows, count, .. }) => {
+                assert_eq!(count, 1);
+                assert_eq!(columns, vec!["amount", "status"]);
+                assert_eq!(rows[0][0], serde_json::json!(5));
+            }
+            other => panic!("unexpected output: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_chart_cell_over_aggregation() {
+        let (_dir, db) = seeded();
+        let mut cell = Cell::new(
+            CellType::Chart,
+            "SELECT status, SUM(amount) AS total FROM items GROUP BY status",
+        );
+        cell.metadata.chart = Some(ChartSpec {
+            kind: ChartKind::Pie,
+            ..ChartSpec::default()
+        });
+
+        match execute_cell(&db, &cell) {
+            Some(CellOutput::Chart { chart, columns, .. }) => {
+                assert_eq!(columns, vec!["status", "total"]);
+                assert_eq!(chart.kind, ChartKind::Pie);
+                assert_eq!(chart.x, "status");
+                let mut points: Vec<_> = chart
+                    .labels
+                    .iter()
+                    .cloned()
+                    .zip(chart.series[0].values.iter().cloned())
+                    .collect();
+                points.sort_by(|a, b| a.0.cmp(&b.0));
+                assert_eq!(
+                    points,
+                    vec![("closed".to_string(), Some(1.0)), ("open".to_string(), Some(12.0))]
+                );
+            }
+            other => panic!("unexpected output: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parameters_are_bound() {
+        let (_dir, db) = seeded();
+        let mut cell = Cell::new(
+            CellType::Query,
+            "SELECT * FROM items WHERE pk = {{order}} AND amount > {{min}}",
+        );
+        cell.metadata.params.insert("order".to_string(), serde_json::json!("o#2"));
+        cell.metadata.params.insert("min".to_string(), serde_json::json!(6));
+
+        match execute_cell(&db, &cell) {
+            Some(CellOutput::Table { rows, count, .. }) => {
+                assert_eq!(count, 1);
+                assert_eq!(rows[0][0], serde_json::json!(7));
+            }
+            other => panic!("unexpected output: {:?}", other),
+        }
+
+        // A value that looks like PartiQL is still just a value
+        cell.metadata.params.insert("order".to_string(), serde_json::json!("o#2' OR 'a' = 'a"));
+        match execute_cell(&db, &cell) {
+            Some(CellOutput::Table { count, .. }) => assert_eq!(count, 0),
+            other => panic!("unexpected output: {:?}", other),
+        }
+
+        cell.metadata.params.remove("min");
+        assert!(matches!(execute_cell(&db, &cell), Some(CellOutput::Error { .. })));
+    }
+
+    #[test]
+    fn test_errors_and_markdown() {
+        let (_dir, db) = seeded();
+        assert!(execute_cell(&db, &Cell::new(CellType::Markdown, "# Notes")).is_none());
+
+        let bad = Cell::new(CellType::Query, "SELEC nothing");
+        assert!(matches!(execute_cell(&db, &bad), Some(CellOutput::Error { .. })));
+
+        // Rejected before it runs
+        let not_select = Cell::new(CellType::Chart, "DELETE FROM items WHERE pk = 'o#1'");
+        assert!(matches!(execute_cell(&db, &not_select), Some(CellOutput::Error { .. })));
+        assert!(db.get(b"o#1").unwrap().is_some());
+    }
+}
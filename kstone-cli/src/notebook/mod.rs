@@ -0,0 +1,149 @@
+/// `kstone notebook`: a browser notebook for PartiQL
+///
+/// Serves a single-page app from the binary (rust-embed) and runs cells
+/// over a websocket. Notebooks are stored in the database they query (see
+/// `storage`), so sharing the directory shares the analyses. Scheduled
+/// cells run in the background while the server is up (see `scheduler`).
+
+mod chart;
+mod executor;
+mod model;
+mod params;
+mod scheduler;
+mod server;
+mod storage;
+mod transfer;
+
+pub use transfer::ExportFormat;
+
+use anyhow::{Context, Result};
+use kstone_api::Database;
+use model::Notebook;
+use std::path::Path;
+use std::sync::Arc;
+use storage::NotebookStorage;
+
+/// How the notebook server runs
+pub struct NotebookConfig {
+    /// Address to bind
+    pub host: String,
+    /// Port to listen on
+    pub port: u16,
+    /// Open the database read-only; saving notebooks and writes fail
+    pub read_only: bool,
+    /// Open the notebook in the default browser once listening
+    pub auto_open_browser: bool,
+}
+
+/// Open (or create) the database at `path` and serve the notebook until Ctrl+C
+pub async fn launch_notebook(path: &Path, config: NotebookConfig) -> Result<()> {
+    let db = if config.read_only {
+        Database::open_read_only(path)
+    } else if path.exists() {
+        Database::open(path)
+    } else {
+        Database::create(path)
+    }
+    .with_context(|| format!("Failed to open database: {}", path.display()))?;
+
+    let db = Arc::new(db);
+    let state = server::AppState::new(db.clone(), config.read_only);
+    let listener = tokio::net::TcpListener::bind((config.host.as_str(), config.port))
+        .await
+        .with_context(|| format!("Failed to bind {}:{}", config.host, config.port))?;
+
+    let url = format!("http://{}", listener.local_addr()?);
+    println!("Notebook for {} running at {}", path.display(), url);
+    println!("Press Ctrl+C to stop");
+    if config.auto_open_browser {
+        open_browser(&url);
+    }
+
+    // Scheduled cells can write, so a read-only server never runs them
+    let scheduler = (!config.read_only)
+        .then(|| tokio::spawn(scheduler::run(db.clone(), NotebookStorage::new(db))));
+
+    let served = axum::serve(listener, server::router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await;
+    if let Some(scheduler) = scheduler {
+        scheduler.abort();
+    }
+    served.context("Notebook server failed")
+}
+
+/// Best effort: the URL is printed either way
+fn open_browser(url: &str) {
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("cmd").args(["/C", "start", url]).spawn();
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let result = std::process::Command::new("xdg-open").arg(url).spawn();
+
+    if let Err(e) = result {
+        eprintln!("Could not open a browser ({}); open {} manually", e, url);
+    }
+}
+
+/// `kstone notebook list`: print the stored notebooks
+pub fn list_notebooks(db: Database) -> Result<()> {
+    let notebooks = NotebookStorage::new(Arc::new(db)).list()?;
+    if notebooks.is_empty() {
+        println!("No notebooks");
+    }
+    for notebook in notebooks {
+        println!(
+            "{}  {}  ({} cells, updated {})",
+            notebook.id,
+            notebook.title,
+            notebook.cell_count,
+            notebook.updated_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+/// `kstone notebook export`: write a notebook to `output`, or stdout
+///
+/// `notebook` is an id or a title.
+pub fn export_notebook(db: Database, notebook: &str, format: ExportFormat, output: Option<&Path>) -> Result<()> {
+    let storage = NotebookStorage::new(Arc::new(db));
+    let notebook = find_notebook(&storage, notebook)?;
+    let text = transfer::export(&notebook, format)?;
+    match output {
+        Some(output) => {
+            std::fs::write(output, text).with_context(|| format!("Failed to write {}", output.display()))?;
+            eprintln!("Exported '{}' to {}", notebook.title, output.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// `kstone notebook import`: add an exported notebook to the database
+pub fn import_notebook(db: Database, file: &Path) -> Result<()> {
+    let text = std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let notebook = transfer::import(&text).with_context(|| format!("Failed to import {}", file.display()))?;
+    NotebookStorage::new(Arc::new(db)).save(&notebook)?;
+    println!("Imported '{}' as {}", notebook.title, notebook.id);
+    Ok(())
+}
+
+fn find_notebook(storage: &NotebookStorage, id_or_title: &str) -> Result<Notebook> {
+    if let Some(notebook) = storage.get(id_or_title)? {
+        return Ok(notebook);
+    }
+    let mut matches: Vec<Notebook> = storage
+        .load_all()?
+        .into_iter()
+        .filter(|notebook| notebook.title == id_or_title)
+        .collect();
+    match matches.len() {
+        0 => anyhow::bail!("No notebook with id or title '{}'", id_or_title),
+        1 => Ok(matches.remove(0)),
+        n => anyhow::bail!("{} notebooks are titled '{}'; use an id (kstone notebook list)", n, id_or_title),
+    }
+}
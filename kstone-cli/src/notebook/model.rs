@@ -0,0 +1,170 @@
+/// Notebook documents: cells, their metadata and their last output
+
+use super::chart::{ChartData, ChartSpec};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A notebook: an ordered list of cells
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notebook {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub cells: Vec<Cell>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Notebook {
+    /// Create an empty notebook with a fresh id
+    pub fn new(title: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.into(),
+            cells: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Listing entry for the sidebar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotebookSummary {
+    pub id: String,
+    pub title: String,
+    pub cell_count: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Notebook> for NotebookSummary {
+    fn from(notebook: &Notebook) -> Self {
+        Self {
+            id: notebook.id.clone(),
+            title: notebook.title.clone(),
+            cell_count: notebook.cells.len(),
+            updated_at: notebook.updated_at,
+        }
+    }
+}
+
+/// What a cell contains and how its output is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellType {
+    /// PartiQL statement, output shown as a table
+    Query,
+    /// PartiQL statement, output shown as a chart
+    Chart,
+    /// Markdown text, rendered by the browser
+    Markdown,
+}
+
+/// One notebook cell
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub cell_type: CellType,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub metadata: CellMetadata,
+    /// Output of the last run, saved with the notebook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<CellOutput>,
+}
+
+impl Cell {
+    /// Create a cell with a fresh id
+    pub fn new(cell_type: CellType, source: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            cell_type,
+            source: source.into(),
+            metadata: CellMetadata::default(),
+            output: None,
+        }
+    }
+}
+
+/// Per-cell settings kept alongside the source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CellMetadata {
+    /// Chart kind, axis and series (chart cells)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart: Option<ChartSpec>,
+    /// Values for the `{{name}}` placeholders in the source
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Run the cell periodically while the notebook server is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<CellSchedule>,
+}
+
+/// How often a scheduled cell runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellSchedule {
+    pub every_minutes: u32,
+    /// Keep the schedule but skip runs
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// One run of a scheduled cell, kept in the run history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellRun {
+    pub notebook_id: String,
+    pub cell_id: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub ok: bool,
+    /// Output, with table rows capped (`count` stays the full count)
+    pub output: Option<CellOutput>,
+}
+
+/// A named statement in the query library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    /// PartiQL, possibly with `{{name}}` placeholders
+    pub statement: String,
+    /// Placeholder names in the statement, for the sidebar
+    #[serde(default)]
+    pub parameters: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Result of running a cell
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CellOutput {
+    /// SELECT rows (or the item an UPDATE returned)
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<serde_json::Value>>,
+        count: usize,
+        scanned_count: usize,
+        /// The statement stopped at a page boundary
+        has_more: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+        elapsed_ms: u64,
+    },
+    /// SELECT rows rendered as a chart
+    Chart {
+        chart: ChartData,
+        /// Every column of the result, for the axis and series pickers
+        columns: Vec<String>,
+        count: usize,
+        elapsed_ms: u64,
+    },
+    /// Statements without rows (INSERT, DELETE, EXPLAIN, ...)
+    Message { text: String, elapsed_ms: u64 },
+    /// The statement failed
+    Error { message: String },
+}
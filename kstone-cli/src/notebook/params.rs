@@ -0,0 +1,142 @@
+/// Cell parameters: `{{name}}` placeholders bound before execution
+///
+/// Placeholders are replaced by `?` and their values passed as statement
+/// parameters, so a value can never change the statement itself. Values
+/// come from the cell metadata as JSON: numbers bind as `N`, booleans as
+/// `Bool` and strings as `S`. A placeholder stands for a whole value
+/// (`WHERE pk = {{user_id}}`), not for text inside a string literal.
+
+use kstone_api::json::json_to_value;
+use kstone_api::KeystoneValue;
+use std::collections::BTreeMap;
+
+/// Parameter names in order of first appearance
+pub fn placeholders(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for segment in segments(source) {
+        if let Segment::Placeholder(name) = segment {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Replace placeholders with `?` and collect their values in order
+pub fn bind(
+    source: &str,
+    values: &BTreeMap<String, serde_json::Value>,
+) -> Result<(String, Vec<KeystoneValue>), String> {
+    let mut statement = String::with_capacity(source.len());
+    let mut parameters = Vec::new();
+    for segment in segments(source) {
+        match segment {
+            Segment::Text(text) => statement.push_str(text),
+            Segment::Placeholder(name) => {
+                let value = values
+                    .get(name)
+                    .filter(|value| !value.is_null())
+                    .ok_or_else(|| format!("Parameter '{}' has no value", name))?;
+                statement.push('?');
+                parameters.push(json_to_value(value.clone()));
+            }
+            Segment::Quoted(name) => {
+                return Err(format!(
+                    "Parameter '{{{{{}}}}}' is inside a string literal; remove the quotes around it",
+                    name
+                ))
+            }
+        }
+    }
+    Ok((statement, parameters))
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+    /// A placeholder inside a single-quoted literal
+    Quoted(&'a str),
+}
+
+/// Split a statement into text and placeholders, tracking string literals
+fn segments(source: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    let mut i = 0;
+    let bytes = source.as_bytes();
+
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            in_string = !in_string;
+            i += 1;
+            continue;
+        }
+        if source[i..].starts_with("{{") {
+            if let Some(len) = source[i + 2..].find("}}") {
+                let name = source[i + 2..i + 2 + len].trim();
+                if is_name(name) {
+                    segments.push(Segment::Text(&source[start..i]));
+                    segments.push(if in_string {
+                        Segment::Quoted(name)
+                    } else {
+                        Segment::Placeholder(name)
+                    });
+                    i += len + 4;
+                    start = i;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    segments.push(Segment::Text(&source[start..]));
+    segments
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_placeholders_in_order() {
+        let source = "SELECT * FROM items WHERE pk = {{user}} AND age > {{ min_age }} OR pk = {{user}}";
+        assert_eq!(placeholders(source), vec!["user", "min_age"]);
+        assert!(placeholders("SELECT * FROM items WHERE x = '{not}' AND y = {{1bad}}").is_empty());
+    }
+
+    #[test]
+    fn test_bind_replaces_with_parameters() {
+        let values = BTreeMap::from([
+            ("user".to_string(), json!("user#1")),
+            ("min_age".to_string(), json!(18)),
+        ]);
+        let (statement, parameters) =
+            bind("SELECT * FROM items WHERE pk = {{user}} AND age > {{min_age}}", &values).unwrap();
+
+        assert_eq!(statement, "SELECT * FROM items WHERE pk = ? AND age > ?");
+        assert_eq!(
+            parameters,
+            vec![KeystoneValue::string("user#1"), KeystoneValue::number(18)]
+        );
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        let values = BTreeMap::from([("user".to_string(), json!("u"))]);
+        assert!(bind("SELECT * FROM items WHERE pk = {{other}}", &values)
+            .unwrap_err()
+            .contains("other"));
+        assert!(bind("SELECT * FROM items WHERE pk = '{{user}}'", &values)
+            .unwrap_err()
+            .contains("string literal"));
+    }
+}
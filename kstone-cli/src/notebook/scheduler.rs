@@ -0,0 +1,153 @@
+/// Scheduled cells: run a cell every N minutes while the server is up
+///
+/// A cell is scheduled through `CellMetadata::schedule` in the saved
+/// notebook (unsaved changes in the browser don't count). Every tick the
+/// scheduler runs the cells whose last recorded run is at least their
+/// interval old, one at a time, and records each run in the history.
+/// Nothing catches up on runs missed while the server was down; an overdue
+/// cell just runs once at the next tick.
+
+use super::executor::execute_cell;
+use super::model::{CellOutput, CellRun, CellType};
+use super::storage::NotebookStorage;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use kstone_api::Database;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the scheduler looks for due cells
+pub const TICK: Duration = Duration::from_secs(30);
+
+/// Table rows kept in a recorded run
+const MAX_STORED_ROWS: usize = 100;
+
+/// Run every due cell; returns how many ran
+pub fn run_due(db: &Database, storage: &NotebookStorage, now: DateTime<Utc>) -> Result<usize> {
+    let mut ran = 0;
+    for notebook in storage.load_all()? {
+        for cell in &notebook.cells {
+            let Some(schedule) = &cell.metadata.schedule else {
+                continue;
+            };
+            if schedule.paused || schedule.every_minutes == 0 || cell.cell_type == CellType::Markdown {
+                continue;
+            }
+
+            let interval = chrono::Duration::minutes(i64::from(schedule.every_minutes));
+            let last = storage.runs(&notebook.id, &cell.id, 1)?.into_iter().next();
+            if last.is_some_and(|run| now - run.started_at < interval) {
+                continue;
+            }
+
+            let started = Instant::now();
+            let output = execute_cell(db, cell).map(cap_rows);
+            let run = CellRun {
+                notebook_id: notebook.id.clone(),
+                cell_id: cell.id.clone(),
+                started_at: now,
+                duration_ms: started.elapsed().as_millis() as u64,
+                ok: !matches!(output, Some(CellOutput::Error { .. })),
+                output,
+            };
+            if !run.ok {
+                tracing::warn!(notebook = %notebook.title, cell = %cell.id, "Scheduled cell failed");
+            }
+            storage.record_run(&run)?;
+            ran += 1;
+        }
+    }
+    Ok(ran)
+}
+
+/// Run due cells every `TICK` until the task is dropped
+pub async fn run(db: Arc<Database>, storage: NotebookStorage) {
+    let mut ticks = tokio::time::interval(TICK);
+    loop {
+        ticks.tick().await;
+        let db = db.clone();
+        let storage = storage.clone();
+        match tokio::task::spawn_blocking(move || run_due(&db, &storage, Utc::now())).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => tracing::warn!("Notebook scheduler failed: {:#}", e),
+            Err(e) => tracing::warn!("Notebook scheduler panicked: {}", e),
+        }
+    }
+}
+
+fn cap_rows(output: CellOutput) -> CellOutput {
+    match output {
+        CellOutput::Table {
+            columns,
+            mut rows,
+            count,
+            scanned_count,
+            has_more,
+            warnings,
+            elapsed_ms,
+        } => {
+            rows.truncate(MAX_STORED_ROWS);
+            CellOutput::Table {
+                columns,
+                rows,
+                count,
+                scanned_count,
+                has_more,
+                warnings,
+                elapsed_ms,
+            }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::model::{Cell, CellSchedule, Notebook};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_runs_due_cells_and_records_history() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let storage = NotebookStorage::new(db.clone());
+
+        let mut notebook = Notebook::new("Jobs");
+        let mut cleanup = Cell::new(CellType::Query, "DELETE FROM items WHERE pk = 'stale'");
+        cleanup.metadata.schedule = Some(CellSchedule {
+            every_minutes: 10,
+            paused: false,
+        });
+        let mut paused = Cell::new(CellType::Query, "SELECT * FROM items");
+        paused.metadata.schedule = Some(CellSchedule {
+            every_minutes: 1,
+            paused: true,
+        });
+        let mut broken = Cell::new(CellType::Query, "SELEC oops");
+        broken.metadata.schedule = Some(CellSchedule {
+            every_minutes: 5,
+            paused: false,
+        });
+        let unscheduled = Cell::new(CellType::Query, "SELECT * FROM items");
+        notebook.cells = vec![cleanup.clone(), paused.clone(), broken.clone(), unscheduled];
+        storage.save(&notebook).unwrap();
+        db.put(b"stale", kstone_api::ItemBuilder::new().number("n", 1).build()).unwrap();
+
+        let t0 = Utc::now();
+        assert_eq!(run_due(&db, &storage, t0).unwrap(), 2);
+        assert!(db.get(b"stale").unwrap().is_none());
+
+        let runs = storage.runs(&notebook.id, &cleanup.id, 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].ok);
+        assert!(!storage.runs(&notebook.id, &broken.id, 10).unwrap()[0].ok);
+        assert!(storage.runs(&notebook.id, &paused.id, 10).unwrap().is_empty());
+
+        // Nothing is due again until an interval has passed
+        assert_eq!(run_due(&db, &storage, t0 + chrono::Duration::minutes(4)).unwrap(), 0);
+        assert_eq!(run_due(&db, &storage, t0 + chrono::Duration::minutes(5)).unwrap(), 1);
+        assert_eq!(run_due(&db, &storage, t0 + chrono::Duration::minutes(10)).unwrap(), 2);
+        assert_eq!(storage.runs(&notebook.id, &cleanup.id, 10).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,392 @@
+/// HTTP routes and the websocket cell pipeline
+///
+/// REST endpoints manage notebooks; `/api/ws` runs cells. The browser
+/// sends `{"type": "execute", "cell": {...}}` and receives
+/// `{"type": "output", "cell_id": "...", "output": {...}}` once the
+/// statement finishes. Statements run on the blocking pool, so a slow scan
+/// doesn't stall other sockets.
+
+use super::executor::execute_cell;
+use super::model::{Cell, CellOutput, CellRun, Notebook, NotebookSummary, SavedQuery};
+use super::params;
+use super::storage::NotebookStorage;
+use super::transfer::{self, ExportFormat};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::Utc;
+use kstone_api::Database;
+use rust_embed::RustEmbed;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(RustEmbed)]
+#[folder = "src/notebook/static/"]
+struct Assets;
+
+/// Shared server state
+#[derive(Clone)]
+pub struct AppState {
+    db: Arc<Database>,
+    storage: NotebookStorage,
+    read_only: bool,
+}
+
+impl AppState {
+    pub fn new(db: Arc<Database>, read_only: bool) -> Self {
+        Self {
+            storage: NotebookStorage::new(db.clone()),
+            db,
+            read_only,
+        }
+    }
+
+    fn check_writable(&self) -> std::result::Result<(), ApiError> {
+        if self.read_only {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "The notebook server is read-only",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Build the notebook router
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/static/*path", get(asset))
+        .route("/api/info", get(info))
+        .route("/api/notebooks", get(list_notebooks).post(create_notebook))
+        .route(
+            "/api/notebooks/:id",
+            get(get_notebook).put(save_notebook).delete(delete_notebook),
+        )
+        .route("/api/notebooks/:id/export", get(export_notebook))
+        .route("/api/notebooks/:id/cells/:cell_id/runs", get(cell_runs))
+        .route("/api/notebooks/import", post(import_notebook))
+        .route("/api/queries", get(list_queries))
+        .route("/api/queries/:name", put(save_query).delete(delete_query))
+        .route("/api/ws", get(websocket))
+        .with_state(state)
+}
+
+/// Error returned by the REST endpoints, as `{"error": "..."}`
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(id: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, format!("Notebook '{}' not found", id))
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(serde_json::json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+/// Run storage work on the blocking pool
+async fn blocking<T, F>(f: F) -> std::result::Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(ApiError::from)
+}
+
+async fn index() -> Response {
+    serve_asset("index.html")
+}
+
+async fn asset(Path(path): Path<String>) -> Response {
+    serve_asset(&path)
+}
+
+fn serve_asset(path: &str) -> Response {
+    match Assets::get(path) {
+        Some(file) => {
+            let content_type = match path.rsplit('.').next() {
+                Some("html") => "text/html; charset=utf-8",
+                Some("js") => "text/javascript; charset=utf-8",
+                Some("css") => "text/css; charset=utf-8",
+                Some("svg") => "image/svg+xml",
+                _ => "application/octet-stream",
+            };
+            ([(header::CONTENT_TYPE, content_type)], file.data.into_owned()).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct Info {
+    path: Option<String>,
+    read_only: bool,
+}
+
+async fn info(State(state): State<AppState>) -> Json<Info> {
+    Json(Info {
+        path: state.db.path().map(|path| path.display().to_string()),
+        read_only: state.read_only,
+    })
+}
+
+async fn list_notebooks(State(state): State<AppState>) -> ApiResult<Vec<NotebookSummary>> {
+    let storage = state.storage.clone();
+    Ok(Json(blocking(move || storage.list()).await?))
+}
+
+#[derive(Deserialize)]
+struct CreateNotebook {
+    #[serde(default)]
+    title: Option<String>,
+}
+
+async fn create_notebook(
+    State(state): State<AppState>,
+    Json(request): Json<CreateNotebook>,
+) -> ApiResult<Notebook> {
+    state.check_writable()?;
+    let notebook = Notebook::new(request.title.unwrap_or_else(|| "Untitled".to_string()));
+    let storage = state.storage.clone();
+    let saved = notebook.clone();
+    blocking(move || storage.save(&saved)).await?;
+    Ok(Json(notebook))
+}
+
+async fn get_notebook(State(state): State<AppState>, Path(id): Path<String>) -> ApiResult<Notebook> {
+    let storage = state.storage.clone();
+    let lookup = id.clone();
+    blocking(move || storage.get(&lookup))
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(&id))
+}
+
+async fn save_notebook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(mut notebook): Json<Notebook>,
+) -> ApiResult<Notebook> {
+    state.check_writable()?;
+    if notebook.id != id {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Notebook id does not match the URL",
+        ));
+    }
+    notebook.updated_at = Utc::now();
+    let storage = state.storage.clone();
+    let saved = notebook.clone();
+    blocking(move || storage.save(&saved)).await?;
+    Ok(Json(notebook))
+}
+
+async fn delete_notebook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> std::result::Result<StatusCode, ApiError> {
+    state.check_writable()?;
+    let storage = state.storage.clone();
+    let lookup = id.clone();
+    if blocking(move || storage.delete(&lookup)).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found(&id))
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    #[serde(default)]
+    format: Option<ExportFormat>,
+}
+
+/// Download a notebook as JSON (default) or Markdown
+async fn export_notebook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<ExportParams>,
+) -> std::result::Result<Response, ApiError> {
+    let format = params.format.unwrap_or(ExportFormat::Json);
+    let storage = state.storage.clone();
+    let lookup = id.clone();
+    let notebook = blocking(move || storage.get(&lookup))
+        .await?
+        .ok_or_else(|| ApiError::not_found(&id))?;
+    let text = transfer::export(&notebook, format)?;
+
+    let content_type = match format {
+        ExportFormat::Json => "application/json",
+        ExportFormat::Markdown => "text/markdown; charset=utf-8",
+    };
+    let file_name: String = notebook
+        .title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let disposition = format!("attachment; filename=\"{}.{}\"", file_name, format.extension());
+    Ok((
+        [(header::CONTENT_TYPE, content_type.to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        text,
+    )
+        .into_response())
+}
+
+/// Import an exported notebook (the file content is the request body)
+async fn import_notebook(State(state): State<AppState>, body: String) -> ApiResult<Notebook> {
+    state.check_writable()?;
+    let notebook = transfer::import(&body).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    let storage = state.storage.clone();
+    let saved = notebook.clone();
+    blocking(move || storage.save(&saved)).await?;
+    Ok(Json(notebook))
+}
+
+#[derive(Deserialize)]
+struct RunsParams {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Run history of a scheduled cell, newest first
+async fn cell_runs(
+    State(state): State<AppState>,
+    Path((id, cell_id)): Path<(String, String)>,
+    Query(params): Query<RunsParams>,
+) -> ApiResult<Vec<CellRun>> {
+    let limit = params.limit.unwrap_or(20);
+    let storage = state.storage.clone();
+    Ok(Json(blocking(move || storage.runs(&id, &cell_id, limit)).await?))
+}
+
+async fn list_queries(State(state): State<AppState>) -> ApiResult<Vec<SavedQuery>> {
+    let storage = state.storage.clone();
+    Ok(Json(blocking(move || storage.list_queries()).await?))
+}
+
+#[derive(Deserialize)]
+struct SaveQuery {
+    statement: String,
+    #[serde(default)]
+    description: String,
+}
+
+async fn save_query(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<SaveQuery>,
+) -> ApiResult<SavedQuery> {
+    state.check_writable()?;
+    let query = SavedQuery {
+        name,
+        parameters: params::placeholders(&request.statement),
+        statement: request.statement,
+        description: request.description,
+        updated_at: Utc::now(),
+    };
+    let storage = state.storage.clone();
+    let saved = query.clone();
+    blocking(move || storage.save_query(&saved)).await?;
+    Ok(Json(query))
+}
+
+async fn delete_query(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> std::result::Result<StatusCode, ApiError> {
+    state.check_writable()?;
+    let storage = state.storage.clone();
+    let lookup = name.clone();
+    if blocking(move || storage.delete_query(&lookup)).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Saved query '{}' not found", name),
+        ))
+    }
+}
+
+/// Messages from the browser
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Execute { cell: Cell },
+}
+
+/// Messages to the browser
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Output {
+        cell_id: String,
+        output: Option<CellOutput>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+async fn websocket(State(state): State<AppState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| run_socket(socket, state))
+}
+
+async fn run_socket(mut socket: WebSocket, state: AppState) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let reply = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(ClientMessage::Execute { cell }) => {
+                let db = state.db.clone();
+                let cell_id = cell.id.clone();
+                match tokio::task::spawn_blocking(move || execute_cell(&db, &cell)).await {
+                    Ok(output) => ServerMessage::Output { cell_id, output },
+                    Err(e) => ServerMessage::Error {
+                        message: e.to_string(),
+                    },
+                }
+            }
+            Err(e) => ServerMessage::Error {
+                message: format!("Invalid message: {}", e),
+            },
+        };
+
+        let Ok(json) = serde_json::to_string(&reply) else {
+            continue;
+        };
+        if socket.send(Message::Text(json)).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,671 @@
+// KeystoneDB notebook front end.
+//
+// Notebooks are loaded and saved over REST; cells run over the websocket
+// at /api/ws. Chart data is computed by the server, this file only draws it.
+
+const COLORS = ['#3b6fd4', '#e07b39', '#2f9e6e', '#c0396b', '#8a63d2', '#c9a227', '#3aa6b9', '#7a7f8a'];
+
+const state = {
+  notebook: null,
+  dirty: false,
+  socket: null,
+  pending: new Map(),
+};
+
+const $ = (selector) => document.querySelector(selector);
+
+function el(tag, attrs = {}, ...children) {
+  const node = document.createElement(tag);
+  for (const [key, value] of Object.entries(attrs)) {
+    if (key.startsWith('on')) node.addEventListener(key.slice(2), value);
+    else if (key === 'class') node.className = value;
+    else node.setAttribute(key, value);
+  }
+  for (const child of children.flat()) {
+    if (child != null) node.append(child);
+  }
+  return node;
+}
+
+async function api(method, path, body) {
+  const response = await fetch(path, {
+    method,
+    headers: body && typeof body !== 'string' ? { 'Content-Type': 'application/json' } : {},
+    body: body ? (typeof body === 'string' ? body : JSON.stringify(body)) : undefined,
+  });
+  if (response.status === 204) return null;
+  const data = await response.json();
+  if (!response.ok) throw new Error(data.error || response.statusText);
+  return data;
+}
+
+function setStatus(text) {
+  $('#status').textContent = text;
+}
+
+function markDirty() {
+  state.dirty = true;
+  setStatus('Unsaved changes');
+}
+
+// ---- websocket --------------------------------------------------------------
+
+function connect() {
+  return new Promise((resolve) => {
+    const protocol = location.protocol === 'https:' ? 'wss' : 'ws';
+    const socket = new WebSocket(`${protocol}://${location.host}/api/ws`);
+    socket.onopen = () => resolve(socket);
+    socket.onmessage = (event) => handleMessage(JSON.parse(event.data));
+    socket.onclose = () => {
+      state.socket = null;
+      for (const done of state.pending.values()) done();
+      state.pending.clear();
+    };
+    state.socket = socket;
+  });
+}
+
+function handleMessage(message) {
+  if (message.type === 'output') {
+    const cell = state.notebook && state.notebook.cells.find((c) => c.id === message.cell_id);
+    if (cell) {
+      cell.output = message.output || undefined;
+      renderOutput(cell);
+      markDirty();
+    }
+    const done = state.pending.get(message.cell_id);
+    if (done) {
+      state.pending.delete(message.cell_id);
+      done();
+    }
+  } else if (message.type === 'error') {
+    setStatus(message.message);
+  }
+}
+
+async function runCell(cell) {
+  if (cell.type === 'markdown') {
+    renderOutput(cell);
+    return;
+  }
+  const socket = state.socket || (await connect());
+  const node = document.getElementById(`output-${cell.id}`);
+  if (node) node.textContent = 'Running…';
+  await new Promise((resolve) => {
+    state.pending.set(cell.id, resolve);
+    socket.send(JSON.stringify({ type: 'execute', cell }));
+  });
+}
+
+// ---- notebooks --------------------------------------------------------------
+
+async function refreshList() {
+  const notebooks = await api('GET', '/api/notebooks');
+  const list = $('#notebook-list');
+  list.replaceChildren(
+    ...notebooks.map((summary) =>
+      el(
+        'li',
+        {
+          class: state.notebook && state.notebook.id === summary.id ? 'active' : '',
+          onclick: () => openNotebook(summary.id),
+        },
+        el('span', {}, summary.title),
+        el(
+          'span',
+          { class: 'actions' },
+          el('button', { title: 'Delete', onclick: (e) => { e.stopPropagation(); deleteNotebook(summary); } }, '×'),
+        ),
+      ),
+    ),
+  );
+}
+
+async function openNotebook(id) {
+  if (state.dirty && !confirm('Discard unsaved changes?')) return;
+  state.notebook = await api('GET', `/api/notebooks/${id}`);
+  state.dirty = false;
+  render();
+  refreshList();
+}
+
+async function createNotebook() {
+  const title = prompt('Notebook title', 'Untitled');
+  if (title === null) return;
+  const notebook = await api('POST', '/api/notebooks', { title });
+  state.dirty = false;
+  await openNotebook(notebook.id);
+}
+
+async function deleteNotebook(summary) {
+  if (!confirm(`Delete notebook "${summary.title}"?`)) return;
+  await api('DELETE', `/api/notebooks/${summary.id}`);
+  if (state.notebook && state.notebook.id === summary.id) {
+    state.notebook = null;
+    state.dirty = false;
+    render();
+  }
+  refreshList();
+}
+
+async function saveNotebook() {
+  if (!state.notebook) return;
+  state.notebook.title = $('#title').value;
+  try {
+    state.notebook = await api('PUT', `/api/notebooks/${state.notebook.id}`, state.notebook);
+    state.dirty = false;
+    setStatus('Saved');
+    refreshList();
+  } catch (e) {
+    setStatus(`Save failed: ${e.message}`);
+  }
+}
+
+function exportNotebook(format) {
+  if (!state.notebook) return;
+  if (state.dirty) setStatus('Exporting the last saved version');
+  location.href = `/api/notebooks/${state.notebook.id}/export?format=${format}`;
+}
+
+async function importNotebook(file) {
+  const text = await file.text();
+  try {
+    const notebook = await api('POST', '/api/notebooks/import', text);
+    await openNotebook(notebook.id);
+  } catch (e) {
+    alert(`Import failed: ${e.message}`);
+  }
+}
+
+// ---- saved queries ------------------------------------------------------------
+
+async function refreshQueries() {
+  const queries = await api('GET', '/api/queries');
+  $('#query-list').replaceChildren(
+    ...queries.map((query) =>
+      el(
+        'li',
+        { title: query.description || query.statement, onclick: () => useQuery(query) },
+        el(
+          'span',
+          { class: 'row' },
+          el('span', {}, query.name),
+          el(
+            'span',
+            { class: 'actions' },
+            el('button', { title: 'Delete', onclick: (e) => { e.stopPropagation(); deleteQuery(query); } }, '×'),
+          ),
+        ),
+        query.parameters.length ? el('span', { class: 'muted' }, query.parameters.map((p) => `{{${p}}}`).join(' ')) : null,
+      ),
+    ),
+  );
+}
+
+// Add a saved query to the open notebook and run it when it needs no input
+async function useQuery(query) {
+  if (!state.notebook) {
+    alert('Open a notebook first');
+    return;
+  }
+  const cell = newCell('query');
+  cell.source = query.statement;
+  state.notebook.cells.push(cell);
+  markDirty();
+  render();
+  if (!query.parameters.length) await runCell(cell);
+}
+
+async function saveQuery(cell) {
+  const name = prompt('Save query as', '');
+  if (!name) return;
+  const description = prompt('Description (optional)', '') || '';
+  try {
+    await api('PUT', `/api/queries/${encodeURIComponent(name)}`, { statement: cell.source, description });
+    refreshQueries();
+  } catch (e) {
+    alert(`Save failed: ${e.message}`);
+  }
+}
+
+async function deleteQuery(query) {
+  if (!confirm(`Delete saved query "${query.name}"?`)) return;
+  await api('DELETE', `/api/queries/${encodeURIComponent(query.name)}`);
+  refreshQueries();
+}
+
+// ---- schedules ----------------------------------------------------------------
+
+function scheduleBadge(cell) {
+  const schedule = cell.metadata.schedule;
+  if (!schedule) return null;
+  const text = schedule.paused ? 'paused' : `every ${schedule.every_minutes} min`;
+  return el('span', {
+    class: 'badge',
+    title: 'Click to pause or resume',
+    onclick: () => { schedule.paused = !schedule.paused; markDirty(); render(); },
+  }, text);
+}
+
+// The server runs saved schedules only, so remind the user to save
+async function scheduleCell(cell) {
+  const current = cell.metadata.schedule ? String(cell.metadata.schedule.every_minutes) : '';
+  const answer = prompt('Run every how many minutes? (empty to unschedule)', current);
+  if (answer === null) return;
+  const minutes = parseInt(answer, 10);
+  if (answer.trim() === '' || minutes === 0) delete cell.metadata.schedule;
+  else if (Number.isInteger(minutes) && minutes > 0) cell.metadata.schedule = { every_minutes: minutes, paused: false };
+  else return alert('Enter a whole number of minutes');
+  markDirty();
+  render();
+  setStatus('Unsaved changes — save to apply the schedule');
+}
+
+async function toggleHistory(cell) {
+  const node = document.getElementById(`history-${cell.id}`);
+  if (!node) return;
+  if (!node.hidden) {
+    node.hidden = true;
+    return;
+  }
+  node.hidden = false;
+  node.replaceChildren(el('div', { class: 'muted' }, 'Loading…'));
+  try {
+    const runs = await api('GET', `/api/notebooks/${state.notebook.id}/cells/${cell.id}/runs?limit=20`);
+    if (!runs.length) {
+      node.replaceChildren(el('div', { class: 'muted' }, 'No scheduled runs yet'));
+      return;
+    }
+    node.replaceChildren(
+      el('table', { class: 'result' },
+        el('tr', {}, el('th', {}, 'Started'), el('th', {}, 'Result'), el('th', {}, 'Duration'), el('th', {})),
+        runs.map((run) => el('tr', {},
+          el('td', {}, new Date(run.started_at).toLocaleString()),
+          el('td', { class: run.ok ? '' : 'error' }, run.ok ? 'ok' : (run.output?.message || 'failed')),
+          el('td', {}, `${run.duration_ms} ms`),
+          el('td', {}, run.output
+            ? el('button', { onclick: () => { cell.output = run.output; renderOutput(cell); } }, 'Show')
+            : null),
+        )),
+      ),
+    );
+  } catch (e) {
+    node.replaceChildren(el('div', { class: 'error' }, e.message));
+  }
+}
+
+// ---- parameters -----------------------------------------------------------------
+
+function placeholders(source) {
+  const names = [];
+  for (const match of source.matchAll(/\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}/g)) {
+    if (!names.includes(match[1])) names.push(match[1]);
+  }
+  return names;
+}
+
+// Numbers and booleans bind as such; anything else (or "quoted") is a string
+function parseParam(text) {
+  const trimmed = text.trim();
+  if (/^(-?\d+(\.\d+)?|true|false|"[^]*")$/.test(trimmed)) {
+    try { return JSON.parse(trimmed); } catch (_) { /* fall through */ }
+  }
+  return text;
+}
+
+function formatParam(value) {
+  if (value === undefined || value === null) return '';
+  if (typeof value !== 'string') return JSON.stringify(value);
+  return /^(-?\d+(\.\d+)?|true|false)$/.test(value.trim()) ? JSON.stringify(value) : value;
+}
+
+function renderParams(cell) {
+  const node = document.getElementById(`params-${cell.id}`);
+  if (!node) return;
+  const params = cell.metadata.params || (cell.metadata.params = {});
+  const names = placeholders(cell.source);
+  for (const name of Object.keys(params)) if (!names.includes(name)) delete params[name];
+  node.replaceChildren(
+    ...names.map((name) => {
+      const input = el('input', {
+        placeholder: 'value',
+        oninput: (e) => { params[name] = parseParam(e.target.value); markDirty(); },
+        onkeydown: (e) => { if (e.key === 'Enter') runCell(cell); },
+      });
+      input.value = formatParam(params[name]);
+      return el('label', {}, name, input);
+    }),
+  );
+}
+
+async function runAll() {
+  for (const cell of state.notebook.cells) await runCell(cell);
+}
+
+function newCell(type) {
+  const cell = { id: crypto.randomUUID(), type, source: '', metadata: {} };
+  if (type === 'chart') cell.metadata.chart = { kind: 'bar' };
+  return cell;
+}
+
+// ---- rendering ----------------------------------------------------------------
+
+function render() {
+  const notebook = state.notebook;
+  $('#toolbar').hidden = !notebook;
+  $('#add-cell').hidden = !notebook;
+  $('#empty').hidden = !!notebook;
+  $('#cells').replaceChildren();
+  if (!notebook) return;
+  $('#title').value = notebook.title;
+  setStatus('');
+  for (const cell of notebook.cells) $('#cells').append(renderCell(cell));
+  for (const cell of notebook.cells) {
+    renderParams(cell);
+    renderOutput(cell);
+  }
+}
+
+function renderCell(cell) {
+  const cells = state.notebook.cells;
+  const move = (delta) => {
+    const index = cells.indexOf(cell);
+    const target = index + delta;
+    if (target < 0 || target >= cells.length) return;
+    cells.splice(index, 1);
+    cells.splice(target, 0, cell);
+    markDirty();
+    render();
+  };
+
+  const source = el('textarea', {
+    spellcheck: 'false',
+    placeholder: cell.type === 'markdown' ? 'Markdown' : 'SELECT * FROM items WHERE pk = …',
+    oninput: (e) => { cell.source = e.target.value; renderParams(cell); markDirty(); },
+    onkeydown: (e) => {
+      if (e.key === 'Enter' && (e.shiftKey || e.ctrlKey || e.metaKey)) {
+        e.preventDefault();
+        runCell(cell);
+      }
+    },
+  });
+  source.value = cell.source;
+
+  const bar = el(
+    'div',
+    { class: 'cell-bar' },
+    el('span', { class: 'muted' }, cell.type),
+    el('span', { class: 'spacer' }),
+    cell.type === 'markdown' ? null : scheduleBadge(cell),
+    cell.type === 'markdown' ? null : el('button', { onclick: () => scheduleCell(cell), title: 'Run this cell every N minutes while the server is up' }, 'Schedule'),
+    cell.type === 'markdown' ? null : el('button', { onclick: () => toggleHistory(cell), title: 'Runs of the scheduled cell' }, 'History'),
+    cell.type === 'markdown' ? null : el('button', { onclick: () => saveQuery(cell), title: 'Add to the saved query library' }, 'Save query'),
+    el('button', { onclick: () => runCell(cell), title: 'Shift+Enter' }, 'Run'),
+    el('button', { onclick: () => move(-1) }, '↑'),
+    el('button', { onclick: () => move(1) }, '↓'),
+    el('button', {
+      onclick: () => {
+        cells.splice(cells.indexOf(cell), 1);
+        markDirty();
+        render();
+      },
+    }, '×'),
+  );
+
+  return el(
+    'div',
+    { class: 'cell', id: `cell-${cell.id}` },
+    bar,
+    source,
+    cell.type === 'markdown' ? null : el('div', { class: 'params', id: `params-${cell.id}` }),
+    cell.type === 'chart' ? el('div', { class: 'chart-options', id: `chart-options-${cell.id}` }) : null,
+    el('div', { class: 'output', id: `output-${cell.id}` }),
+    el('div', { class: 'history', id: `history-${cell.id}`, hidden: '' }),
+  );
+}
+
+function renderOutput(cell) {
+  const node = document.getElementById(`output-${cell.id}`);
+  if (!node) return;
+  node.replaceChildren();
+  if (cell.type === 'markdown') {
+    node.append(el('div', { class: 'markdown' }, renderMarkdown(cell.source)));
+    return;
+  }
+  if (cell.type === 'chart') renderChartOptions(cell);
+
+  const output = cell.output;
+  if (!output) return;
+  switch (output.kind) {
+    case 'table':
+      node.append(renderTable(output.columns, output.rows));
+      node.append(el('div', { class: 'muted' },
+        `${output.count} item(s), ${output.scanned_count} scanned, ${output.elapsed_ms} ms` +
+        (output.has_more ? ' — more results available' : '')));
+      for (const warning of output.warnings || []) node.append(el('div', { class: 'muted' }, `⚠ ${warning}`));
+      break;
+    case 'chart':
+      node.append(renderChart(output.chart));
+      node.append(el('div', { class: 'muted' }, `${output.count} row(s), ${output.elapsed_ms} ms`));
+      break;
+    case 'message':
+      node.append(el('pre', { class: 'message' }, output.text));
+      node.append(el('div', { class: 'muted' }, `${output.elapsed_ms} ms`));
+      break;
+    case 'error':
+      node.append(el('div', { class: 'error' }, output.message));
+      break;
+  }
+}
+
+function renderTable(columns, rows) {
+  const format = (value) => (value === null ? '' : typeof value === 'object' ? JSON.stringify(value) : String(value));
+  return el(
+    'table',
+    { class: 'result' },
+    el('thead', {}, el('tr', {}, columns.map((c) => el('th', {}, c)))),
+    el('tbody', {}, rows.map((row) => el('tr', {}, row.map((v) => el('td', {}, format(v)))))),
+  );
+}
+
+// Minimal markdown: headings, bold, italics, inline code and paragraphs.
+function renderMarkdown(text) {
+  const escape = (s) => s.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+  const inline = (s) => escape(s)
+    .replace(/`([^`]+)`/g, '<code>$1</code>')
+    .replace(/\*\*([^*]+)\*\*/g, '<strong>$1</strong>')
+    .replace(/\*([^*]+)\*/g, '<em>$1</em>');
+  const html = text.split(/\n{2,}/).map((block) => {
+    const heading = block.match(/^(#{1,4})\s+(.*)$/);
+    if (heading) return `<h${heading[1].length}>${inline(heading[2])}</h${heading[1].length}>`;
+    return `<p>${inline(block).replace(/\n/g, '<br>')}</p>`;
+  }).join('');
+  const container = el('div');
+  container.innerHTML = html;
+  return container;
+}
+
+// ---- charts -----------------------------------------------------------------
+
+function renderChartOptions(cell) {
+  const node = document.getElementById(`chart-options-${cell.id}`);
+  if (!node) return;
+  const spec = cell.metadata.chart || (cell.metadata.chart = { kind: 'bar' });
+  const columns = cell.output && cell.output.kind === 'chart' ? cell.output.columns : [];
+  const chart = cell.output && cell.output.kind === 'chart' ? cell.output.chart : null;
+  const update = () => { markDirty(); runCell(cell); };
+
+  const kind = el('select', { onchange: (e) => { spec.kind = e.target.value; update(); } },
+    ['bar', 'line', 'pie'].map((k) => el('option', { value: k }, k)));
+  kind.value = spec.kind || 'bar';
+
+  const x = el('select', { onchange: (e) => { spec.x = e.target.value || undefined; update(); } },
+    el('option', { value: '' }, '(auto)'),
+    columns.map((c) => el('option', { value: c }, c)));
+  x.value = spec.x || '';
+
+  const chosen = spec.series && spec.series.length ? spec.series : chart ? chart.series.map((s) => s.name) : [];
+  const series = columns
+    .filter((c) => c !== (chart ? chart.x : spec.x))
+    .map((c) => {
+      const box = el('input', {
+        type: 'checkbox',
+        onchange: () => {
+          const selected = [...node.querySelectorAll('input[type=checkbox]:checked')].map((i) => i.value);
+          spec.series = selected;
+          update();
+        },
+      });
+      box.value = c;
+      box.checked = chosen.includes(c);
+      return el('label', {}, box, c);
+    });
+
+  node.replaceChildren(
+    el('label', {}, 'Chart', kind),
+    el('label', {}, 'X axis', x),
+    columns.length ? el('span', { class: 'muted' }, 'Series:') : el('span', { class: 'muted' }, 'Run the cell to pick columns'),
+    series,
+  );
+}
+
+const SVG = 'http://www.w3.org/2000/svg';
+
+function svg(tag, attrs = {}, text) {
+  const node = document.createElementNS(SVG, tag);
+  for (const [key, value] of Object.entries(attrs)) node.setAttribute(key, value);
+  if (text != null) node.textContent = text;
+  return node;
+}
+
+function renderChart(chart) {
+  const container = el('div');
+  const legend = el('div', { class: 'legend' });
+  const names = chart.kind === 'pie' ? chart.labels : chart.series.map((s) => s.name);
+  names.forEach((name, i) => {
+    const item = el('span', {}, name);
+    item.style.setProperty('--color', COLORS[i % COLORS.length]);
+    legend.append(item);
+  });
+  container.append(chart.kind === 'pie' ? drawPie(chart) : drawAxes(chart), legend);
+  return container;
+}
+
+function drawPie(chart) {
+  const size = 260;
+  const r = size / 2 - 10;
+  const root = svg('svg', { width: size, height: size, viewBox: `0 0 ${size} ${size}` });
+  const values = chart.series[0].values.map((v) => Math.max(v || 0, 0));
+  const total = values.reduce((a, b) => a + b, 0) || 1;
+  let angle = -Math.PI / 2;
+  values.forEach((value, i) => {
+    const sweep = (value / total) * Math.PI * 2;
+    const point = (a) => [size / 2 + r * Math.cos(a), size / 2 + r * Math.sin(a)];
+    const [x1, y1] = point(angle);
+    const [x2, y2] = point(angle + sweep);
+    const path = sweep >= Math.PI * 2 - 1e-9
+      ? `M ${size / 2 - r} ${size / 2} a ${r} ${r} 0 1 0 ${2 * r} 0 a ${r} ${r} 0 1 0 ${-2 * r} 0`
+      : `M ${size / 2} ${size / 2} L ${x1} ${y1} A ${r} ${r} 0 ${sweep > Math.PI ? 1 : 0} 1 ${x2} ${y2} Z`;
+    const slice = svg('path', { d: path, fill: COLORS[i % COLORS.length], stroke: '#fff' });
+    slice.append(svg('title', {}, `${chart.labels[i]}: ${value}`));
+    root.append(slice);
+    angle += sweep;
+  });
+  return root;
+}
+
+function drawAxes(chart) {
+  const width = 720;
+  const height = 300;
+  const pad = { left: 56, right: 12, top: 12, bottom: 48 };
+  const plotW = width - pad.left - pad.right;
+  const plotH = height - pad.top - pad.bottom;
+  const all = chart.series.flatMap((s) => s.values.filter((v) => v != null));
+  const max = Math.max(0, ...all);
+  const min = Math.min(0, ...all);
+  const span = max - min || 1;
+  const y = (v) => pad.top + plotH - ((v - min) / span) * plotH;
+  const slot = plotW / Math.max(chart.labels.length, 1);
+  const root = svg('svg', { width, height, viewBox: `0 0 ${width} ${height}` });
+
+  for (let i = 0; i <= 4; i++) {
+    const value = min + (span * i) / 4;
+    root.append(svg('line', { x1: pad.left, x2: width - pad.right, y1: y(value), y2: y(value), stroke: '#e6e9ef' }));
+    root.append(svg('text', { x: pad.left - 6, y: y(value) + 4, 'text-anchor': 'end', 'font-size': 11, fill: '#6b7384' },
+      Number(value.toPrecision(4)).toString()));
+  }
+  chart.labels.forEach((label, i) => {
+    root.append(svg('text', {
+      x: pad.left + slot * (i + 0.5), y: height - pad.bottom + 16,
+      'text-anchor': 'middle', 'font-size': 11, fill: '#1d2330',
+    }, label.length > 14 ? `${label.slice(0, 13)}…` : label));
+  });
+
+  chart.series.forEach((series, s) => {
+    const color = COLORS[s % COLORS.length];
+    if (chart.kind === 'bar') {
+      const barW = (slot * 0.8) / chart.series.length;
+      series.values.forEach((value, i) => {
+        if (value == null) return;
+        const x = pad.left + slot * i + slot * 0.1 + barW * s;
+        const top = y(Math.max(value, 0));
+        const bar = svg('rect', { x, y: top, width: Math.max(barW - 2, 1), height: Math.abs(y(value) - y(0)), fill: color });
+        bar.append(svg('title', {}, `${series.name} @ ${chart.labels[i]}: ${value}`));
+        root.append(bar);
+      });
+    } else {
+      const points = series.values
+        .map((value, i) => (value == null ? null : `${pad.left + slot * (i + 0.5)},${y(value)}`))
+        .filter(Boolean);
+      root.append(svg('polyline', { points: points.join(' '), fill: 'none', stroke: color, 'stroke-width': 2 }));
+      series.values.forEach((value, i) => {
+        if (value == null) return;
+        const dot = svg('circle', { cx: pad.left + slot * (i + 0.5), cy: y(value), r: 3, fill: color });
+        dot.append(svg('title', {}, `${series.name} @ ${chart.labels[i]}: ${value}`));
+        root.append(dot);
+      });
+    }
+  });
+  return root;
+}
+
+// ---- startup ----------------------------------------------------------------
+
+async function start() {
+  const info = await api('GET', '/api/info');
+  $('#db-path').textContent = info.path || ':memory:';
+  if (info.read_only) $('#db-path').textContent += ' (read-only)';
+
+  $('#new-notebook').addEventListener('click', createNotebook);
+  $('#save').addEventListener('click', saveNotebook);
+  $('#run-all').addEventListener('click', runAll);
+  $('#export-json').addEventListener('click', () => exportNotebook('json'));
+  $('#export-markdown').addEventListener('click', () => exportNotebook('markdown'));
+  $('#import-notebook').addEventListener('click', () => $('#import-file').click());
+  $('#import-file').addEventListener('change', (e) => {
+    if (e.target.files.length) importNotebook(e.target.files[0]);
+    e.target.value = '';
+  });
+  $('#title').addEventListener('input', markDirty);
+  for (const button of document.querySelectorAll('#add-cell button')) {
+    button.addEventListener('click', () => {
+      state.notebook.cells.push(newCell(button.dataset.type));
+      markDirty();
+      render();
+    });
+  }
+  document.addEventListener('keydown', (e) => {
+    if (e.key === 's' && (e.ctrlKey || e.metaKey)) {
+      e.preventDefault();
+      saveNotebook();
+    }
+  });
+  window.addEventListener('beforeunload', (e) => {
+    if (state.dirty) e.preventDefault();
+  });
+
+  await Promise.all([refreshList(), refreshQueries()]);
+  render();
+}
+
+start();
@@ -0,0 +1,322 @@
+/// Notebook storage inside the database itself
+///
+/// Notebooks live in the `_notebooks` named table, so they travel with the
+/// database directory and never show up in scans of the default table.
+/// Each notebook is one item: partition key = notebook id, sort key
+/// `notebook`, and the document as JSON in the `doc` attribute. Saved
+/// queries use partition key `query#<name>` and sort key `query`. The
+/// `kind` attribute tells records apart when scanning.
+///
+/// Runs of scheduled cells go to `_notebook_runs`, one partition per cell
+/// (`<notebook id>#<cell id>`) sorted by start time, so history reads are
+/// a single query and never slow down notebook listing.
+
+use super::model::{CellRun, Notebook, NotebookSummary, SavedQuery};
+use anyhow::{Context, Result};
+use kstone_api::{Database, ItemBuilder, KeystoneValue, Query, Scan, TableSchema};
+use std::sync::Arc;
+
+/// Named table holding the notebooks
+pub const NOTEBOOK_TABLE: &str = "_notebooks";
+
+const NOTEBOOK_SK: &[u8] = b"notebook";
+const DOC_ATTRIBUTE: &str = "doc";
+const KIND_ATTRIBUTE: &str = "kind";
+const NOTEBOOK_KIND: &str = "notebook";
+const QUERY_SK: &[u8] = b"query";
+const QUERY_KIND: &str = "query";
+
+/// Named table holding the run history of scheduled cells
+pub const RUNS_TABLE: &str = "_notebook_runs";
+
+/// Runs kept per cell; older ones are deleted as new ones are recorded
+pub const MAX_RUNS_PER_CELL: usize = 50;
+
+/// Load and save notebooks
+#[derive(Clone)]
+pub struct NotebookStorage {
+    db: Arc<Database>,
+}
+
+impl NotebookStorage {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Every notebook, most recently updated first
+    pub fn list(&self) -> Result<Vec<NotebookSummary>> {
+        let mut summaries: Vec<NotebookSummary> = self
+            .load_all()?
+            .iter()
+            .map(NotebookSummary::from)
+            .collect();
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.updated_at));
+        Ok(summaries)
+    }
+
+    /// Load one notebook
+    pub fn get(&self, id: &str) -> Result<Option<Notebook>> {
+        if !self.table_exists() {
+            return Ok(None);
+        }
+        let table = self.db.table(NOTEBOOK_TABLE)?;
+        match table.get_with_sk(id.as_bytes(), NOTEBOOK_SK)? {
+            Some(item) => Ok(Some(decode(&item)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Create or replace a notebook
+    pub fn save(&self, notebook: &Notebook) -> Result<()> {
+        self.put(notebook.id.as_bytes(), NOTEBOOK_SK, NOTEBOOK_KIND, notebook)
+    }
+
+    /// Delete a notebook; returns whether it existed
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        if self.get(id)?.is_none() {
+            return Ok(false);
+        }
+        self.db
+            .table(NOTEBOOK_TABLE)?
+            .delete_with_sk(id.as_bytes(), NOTEBOOK_SK)?;
+        Ok(true)
+    }
+
+    /// Every stored notebook, in key order
+    pub fn load_all(&self) -> Result<Vec<Notebook>> {
+        self.load_kind(NOTEBOOK_KIND)
+    }
+
+    /// The saved query library, sorted by name
+    pub fn list_queries(&self) -> Result<Vec<SavedQuery>> {
+        let mut queries: Vec<SavedQuery> = self.load_kind(QUERY_KIND)?;
+        queries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(queries)
+    }
+
+    /// Create or replace a saved query
+    pub fn save_query(&self, query: &SavedQuery) -> Result<()> {
+        if query.name.trim().is_empty() {
+            anyhow::bail!("Saved queries need a name");
+        }
+        self.put(&query_pk(&query.name), QUERY_SK, QUERY_KIND, query)
+    }
+
+    /// Delete a saved query; returns whether it existed
+    pub fn delete_query(&self, name: &str) -> Result<bool> {
+        if !self.table_exists() {
+            return Ok(false);
+        }
+        let table = self.db.table(NOTEBOOK_TABLE)?;
+        let pk = query_pk(name);
+        if table.get_with_sk(&pk, QUERY_SK)?.is_none() {
+            return Ok(false);
+        }
+        table.delete_with_sk(&pk, QUERY_SK)?;
+        Ok(true)
+    }
+
+    /// Record a run and drop the cell's runs beyond `MAX_RUNS_PER_CELL`
+    pub fn record_run(&self, run: &CellRun) -> Result<()> {
+        ensure_table(&self.db, RUNS_TABLE)?;
+        let table = self.db.table(RUNS_TABLE)?;
+        let pk = run_pk(&run.notebook_id, &run.cell_id);
+        let item = ItemBuilder::new()
+            .string(DOC_ATTRIBUTE, serde_json::to_string(run)?)
+            .build();
+        table.put_with_sk(&pk, &run_sk(run), item)?;
+
+        for old in self.runs(&run.notebook_id, &run.cell_id, usize::MAX)?
+            .iter()
+            .skip(MAX_RUNS_PER_CELL)
+        {
+            table.delete_with_sk(&pk, &run_sk(old))?;
+        }
+        Ok(())
+    }
+
+    /// A cell's runs, newest first
+    pub fn runs(&self, notebook_id: &str, cell_id: &str, limit: usize) -> Result<Vec<CellRun>> {
+        if self.db.table_schema(RUNS_TABLE).is_none() {
+            return Ok(Vec::new());
+        }
+        let table = self.db.table(RUNS_TABLE)?;
+        let pk = run_pk(notebook_id, cell_id);
+        let mut runs = Vec::new();
+        let mut start_key: Option<(bytes::Bytes, Option<bytes::Bytes>)> = None;
+        while runs.len() < limit {
+            let mut query = Query::new(&pk).forward(false);
+            if let Some((pk, sk)) = &start_key {
+                query = query.start_after(pk, sk.as_deref());
+            }
+            let page = table.query(query)?;
+            for item in &page.items {
+                runs.push(decode(item)?);
+            }
+            start_key = page.last_key;
+            if start_key.is_none() {
+                break;
+            }
+        }
+        runs.truncate(limit);
+        Ok(runs)
+    }
+
+    fn put<T: serde::Serialize>(&self, pk: &[u8], sk: &[u8], kind: &str, doc: &T) -> Result<()> {
+        self.ensure_table()?;
+        let item = ItemBuilder::new()
+            .string(KIND_ATTRIBUTE, kind)
+            .string(DOC_ATTRIBUTE, serde_json::to_string(doc)?)
+            .build();
+        self.db.table(NOTEBOOK_TABLE)?.put_with_sk(pk, sk, item)?;
+        Ok(())
+    }
+
+    /// Every record of one kind, in key order
+    fn load_kind<T: serde::de::DeserializeOwned>(&self, kind: &str) -> Result<Vec<T>> {
+        if !self.table_exists() {
+            return Ok(Vec::new());
+        }
+        let table = self.db.table(NOTEBOOK_TABLE)?;
+        let mut records = Vec::new();
+        let mut start_key: Option<(bytes::Bytes, Option<bytes::Bytes>)> = None;
+        loop {
+            let mut scan = Scan::new();
+            if let Some((pk, sk)) = &start_key {
+                scan = scan.start_after(pk, sk.as_deref());
+            }
+            let page = table.scan(scan)?;
+            for item in &page.items {
+                if item.get(KIND_ATTRIBUTE).and_then(KeystoneValue::as_string) == Some(kind) {
+                    records.push(decode(item)?);
+                }
+            }
+            start_key = page.last_key;
+            if start_key.is_none() {
+                return Ok(records);
+            }
+        }
+    }
+
+    fn table_exists(&self) -> bool {
+        self.db.table_schema(NOTEBOOK_TABLE).is_some()
+    }
+
+    fn ensure_table(&self) -> Result<()> {
+        ensure_table(&self.db, NOTEBOOK_TABLE)
+    }
+}
+
+fn ensure_table(db: &Database, name: &str) -> Result<()> {
+    if db.table_schema(name).is_none() {
+        db.create_table(name, TableSchema::new())
+            .with_context(|| format!("Failed to create the {} table", name))?;
+    }
+    Ok(())
+}
+
+fn run_pk(notebook_id: &str, cell_id: &str) -> Vec<u8> {
+    format!("{}#{}", notebook_id, cell_id).into_bytes()
+}
+
+/// Zero-padded start time, so sort key order is time order
+fn run_sk(run: &CellRun) -> Vec<u8> {
+    format!("{:020}", run.started_at.timestamp_micros().max(0)).into_bytes()
+}
+
+fn query_pk(name: &str) -> Vec<u8> {
+    format!("query#{}", name).into_bytes()
+}
+
+fn decode<T: serde::de::DeserializeOwned>(item: &kstone_core::Item) -> Result<T> {
+    match item.get(DOC_ATTRIBUTE) {
+        Some(KeystoneValue::S(doc)) => {
+            serde_json::from_str(doc).context("Stored notebook record is not valid JSON")
+        }
+        _ => anyhow::bail!("Stored notebook record has no '{}' attribute", DOC_ATTRIBUTE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::model::{Cell, CellType};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_list_get_delete() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let storage = NotebookStorage::new(db.clone());
+        assert!(storage.list().unwrap().is_empty());
+
+        let mut notebook = Notebook::new("Orders");
+        notebook.cells.push(Cell::new(CellType::Query, "SELECT * FROM items"));
+        storage.save(&notebook).unwrap();
+
+        let listed = storage.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].title, "Orders");
+        assert_eq!(listed[0].cell_count, 1);
+        assert_eq!(storage.get(&notebook.id).unwrap(), Some(notebook.clone()));
+
+        // Notebooks stay out of the default table
+        assert!(db.scan(Scan::new()).unwrap().items.is_empty());
+
+        assert!(storage.delete(&notebook.id).unwrap());
+        assert!(!storage.delete(&notebook.id).unwrap());
+        assert!(storage.get(&notebook.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_saved_queries() {
+        let dir = TempDir::new().unwrap();
+        let storage = NotebookStorage::new(Arc::new(Database::create(dir.path()).unwrap()));
+        let query = |name: &str| SavedQuery {
+            name: name.to_string(),
+            statement: "SELECT * FROM items WHERE pk = {{user}}".to_string(),
+            parameters: vec!["user".to_string()],
+            description: String::new(),
+            updated_at: chrono::Utc::now(),
+        };
+
+        storage.save_query(&query("orders by user")).unwrap();
+        storage.save_query(&query("active users")).unwrap();
+        storage.save(&Notebook::new("Not a query")).unwrap();
+
+        let names: Vec<String> = storage.list_queries().unwrap().into_iter().map(|q| q.name).collect();
+        assert_eq!(names, vec!["active users", "orders by user"]);
+        assert_eq!(storage.list().unwrap().len(), 1);
+
+        assert!(storage.delete_query("active users").unwrap());
+        assert!(!storage.delete_query("active users").unwrap());
+        assert_eq!(storage.list_queries().unwrap().len(), 1);
+        assert!(storage.save_query(&query(" ")).is_err());
+    }
+
+    #[test]
+    fn test_run_history_is_capped() {
+        let dir = TempDir::new().unwrap();
+        let storage = NotebookStorage::new(Arc::new(Database::create(dir.path()).unwrap()));
+        let start = chrono::Utc::now();
+        for i in 0..MAX_RUNS_PER_CELL + 5 {
+            storage
+                .record_run(&CellRun {
+                    notebook_id: "nb".to_string(),
+                    cell_id: "c1".to_string(),
+                    started_at: start + chrono::Duration::seconds(i as i64),
+                    duration_ms: 1,
+                    ok: true,
+                    output: None,
+                })
+                .unwrap();
+        }
+
+        let runs = storage.runs("nb", "c1", usize::MAX).unwrap();
+        assert_eq!(runs.len(), MAX_RUNS_PER_CELL);
+        assert_eq!(runs[0].started_at, start + chrono::Duration::seconds(MAX_RUNS_PER_CELL as i64 + 4));
+        assert_eq!(storage.runs("nb", "c1", 3).unwrap().len(), 3);
+        assert!(storage.runs("nb", "other", 10).unwrap().is_empty());
+        assert!(storage.list().unwrap().is_empty());
+    }
+}
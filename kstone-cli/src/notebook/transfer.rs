@@ -0,0 +1,334 @@
+/// Export and import notebooks as portable files
+///
+/// JSON exports are the notebook document wrapped in a small envelope and
+/// round-trip exactly (outputs included). Markdown exports are meant for
+/// reading and sharing: statements become ```` ```partiql ```` fences,
+/// outputs are rendered between `<!-- kstone:output -->` markers, and an
+/// import reads the statements back and skips the outputs.
+
+use super::chart::ChartSpec;
+use super::model::{Cell, CellMetadata, CellOutput, CellType, Notebook};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// `format` value of JSON exports
+const FORMAT_NAME: &str = "kstone-notebook";
+const FORMAT_VERSION: u32 = 1;
+
+const OUTPUT_START: &str = "<!-- kstone:output -->";
+const OUTPUT_END: &str = "<!-- /kstone:output -->";
+
+/// File format for `kstone notebook export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Conventional file extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    notebook: Notebook,
+}
+
+/// Render a notebook in the given format
+pub fn export(notebook: &Notebook, format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Json => {
+            let envelope = Envelope {
+                format: FORMAT_NAME.to_string(),
+                version: FORMAT_VERSION,
+                notebook: notebook.clone(),
+            };
+            Ok(serde_json::to_string_pretty(&envelope)?)
+        }
+        ExportFormat::Markdown => Ok(to_markdown(notebook)),
+    }
+}
+
+/// Read an exported notebook (JSON or Markdown, detected from the content)
+///
+/// The notebook gets a fresh id so importing never replaces an existing one.
+pub fn import(text: &str) -> Result<Notebook> {
+    let mut notebook = if text.trim_start().starts_with('{') {
+        let envelope: Envelope = serde_json::from_str(text).context("Invalid notebook JSON")?;
+        if envelope.format != FORMAT_NAME {
+            anyhow::bail!("Not a notebook export (format '{}')", envelope.format);
+        }
+        if envelope.version > FORMAT_VERSION {
+            anyhow::bail!(
+                "Notebook export version {} is newer than this kstone supports ({})",
+                envelope.version,
+                FORMAT_VERSION
+            );
+        }
+        envelope.notebook
+    } else {
+        from_markdown(text)?
+    };
+
+    let now = Utc::now();
+    notebook.id = uuid::Uuid::new_v4().to_string();
+    notebook.created_at = now;
+    notebook.updated_at = now;
+    Ok(notebook)
+}
+
+fn to_markdown(notebook: &Notebook) -> String {
+    let mut out = format!("# {}\n", notebook.title);
+    for cell in &notebook.cells {
+        out.push('\n');
+        match cell.cell_type {
+            CellType::Markdown => {
+                out.push_str(cell.source.trim_end());
+                out.push('\n');
+            }
+            CellType::Query | CellType::Chart => {
+                out.push_str("```partiql");
+                if cell.cell_type == CellType::Chart {
+                    let spec = cell.metadata.chart.clone().unwrap_or_default();
+                    out.push_str(" chart ");
+                    out.push_str(&serde_json::to_string(&spec).unwrap_or_default());
+                }
+                out.push('\n');
+                out.push_str(cell.source.trim_end());
+                out.push_str("\n```\n");
+                if let Some(output) = &cell.output {
+                    out.push('\n');
+                    out.push_str(OUTPUT_START);
+                    out.push('\n');
+                    out.push_str(&output_markdown(output));
+                    out.push_str(OUTPUT_END);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+fn output_markdown(output: &CellOutput) -> String {
+    match output {
+        CellOutput::Table { columns, rows, count, .. } => {
+            let mut out = markdown_table(columns, rows);
+            out.push_str(&format!("\n_{} item(s)_\n", count));
+            out
+        }
+        CellOutput::Chart { chart, .. } => {
+            let mut columns = vec![chart.x.clone()];
+            columns.extend(chart.series.iter().map(|series| series.name.clone()));
+            let rows: Vec<Vec<serde_json::Value>> = chart
+                .labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| {
+                    let mut row = vec![serde_json::Value::String(label.clone())];
+                    row.extend(chart.series.iter().map(|series| {
+                        series.values[i].map_or(serde_json::Value::Null, serde_json::Value::from)
+                    }));
+                    row
+                })
+                .collect();
+            markdown_table(&columns, &rows)
+        }
+        CellOutput::Message { text, .. } => format!("```\n{}\n```\n", text),
+        CellOutput::Error { message } => format!("> **Error:** {}\n", message),
+    }
+}
+
+fn markdown_table(columns: &[String], rows: &[Vec<serde_json::Value>]) -> String {
+    let escape = |text: String| text.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!("| {} |\n", columns.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(columns.len())));
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|value| match value {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(s) => escape(s.clone()),
+                other => escape(other.to_string()),
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+fn from_markdown(text: &str) -> Result<Notebook> {
+    let mut title = None;
+    let mut cells = Vec::new();
+    let mut prose: Vec<&str> = Vec::new();
+    let mut lines = text.lines();
+
+    let flush_prose = |prose: &mut Vec<&str>, cells: &mut Vec<Cell>| {
+        let source = prose.join("\n").trim().to_string();
+        if !source.is_empty() {
+            cells.push(Cell::new(CellType::Markdown, source));
+        }
+        prose.clear();
+    };
+
+    while let Some(line) = lines.next() {
+        if title.is_none() && cells.is_empty() && prose.iter().all(|l| l.trim().is_empty()) {
+            if let Some(heading) = line.strip_prefix("# ") {
+                title = Some(heading.trim().to_string());
+                continue;
+            }
+        }
+
+        if line.trim() == OUTPUT_START {
+            for line in lines.by_ref() {
+                if line.trim() == OUTPUT_END {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        if let Some(info) = line.strip_prefix("```partiql") {
+            flush_prose(&mut prose, &mut cells);
+            let info = info.trim();
+            let (cell_type, metadata) = match info.strip_prefix("chart") {
+                Some(spec) => {
+                    let spec = spec.trim();
+                    let chart = if spec.is_empty() {
+                        ChartSpec::default()
+                    } else {
+                        serde_json::from_str(spec)
+                            .with_context(|| format!("Invalid chart settings: {}", spec))?
+                    };
+                    (
+                        CellType::Chart,
+                        CellMetadata {
+                            chart: Some(chart),
+                            ..CellMetadata::default()
+                        },
+                    )
+                }
+                None => (CellType::Query, CellMetadata::default()),
+            };
+
+            let mut source = Vec::new();
+            let mut closed = false;
+            for line in lines.by_ref() {
+                if line.trim_end() == "```" {
+                    closed = true;
+                    break;
+                }
+                source.push(line);
+            }
+            if !closed {
+                anyhow::bail!("Unterminated ```partiql block");
+            }
+            let mut cell = Cell::new(cell_type, source.join("\n"));
+            cell.metadata = metadata;
+            cells.push(cell);
+            continue;
+        }
+
+        prose.push(line);
+    }
+    flush_prose(&mut prose, &mut cells);
+
+    let mut notebook = Notebook::new(title.unwrap_or_else(|| "Imported notebook".to_string()));
+    notebook.cells = cells;
+    Ok(notebook)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::chart::{ChartData, ChartKind, ChartSeries};
+
+    fn sample() -> Notebook {
+        let mut notebook = Notebook::new("Order report");
+        notebook.cells.push(Cell::new(CellType::Markdown, "Orders by **status**."));
+
+        let mut query = Cell::new(CellType::Query, "SELECT * FROM items WHERE pk = 'o#1'");
+        query.output = Some(CellOutput::Table {
+            columns: vec!["amount".to_string(), "status".to_string()],
+            rows: vec![vec![serde_json::json!(5), serde_json::json!("open|new")]],
+            count: 1,
+            scanned_count: 1,
+            has_more: false,
+            warnings: Vec::new(),
+            elapsed_ms: 2,
+        });
+        notebook.cells.push(query);
+
+        let mut chart = Cell::new(
+            CellType::Chart,
+            "SELECT status, COUNT(*) AS n\nFROM items GROUP BY status",
+        );
+        chart.metadata.chart = Some(ChartSpec {
+            kind: ChartKind::Pie,
+            x: Some("status".to_string()),
+            series: vec!["n".to_string()],
+        });
+        chart.output = Some(CellOutput::Chart {
+            chart: ChartData {
+                kind: ChartKind::Pie,
+                x: "status".to_string(),
+                labels: vec!["open".to_string()],
+                series: vec![ChartSeries {
+                    name: "n".to_string(),
+                    values: vec![Some(2.0)],
+                }],
+            },
+            columns: vec!["n".to_string(), "status".to_string()],
+            count: 1,
+            elapsed_ms: 1,
+        });
+        notebook.cells.push(chart);
+        notebook
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let notebook = sample();
+        let text = export(&notebook, ExportFormat::Json).unwrap();
+        let imported = import(&text).unwrap();
+
+        assert_ne!(imported.id, notebook.id);
+        assert_eq!(imported.title, notebook.title);
+        assert_eq!(imported.cells, notebook.cells);
+    }
+
+    #[test]
+    fn test_markdown_round_trip_keeps_statements() {
+        let notebook = sample();
+        let text = export(&notebook, ExportFormat::Markdown).unwrap();
+        assert!(text.starts_with("# Order report\n"));
+        assert!(text.contains("| amount | status |"));
+        assert!(text.contains("open\\|new"));
+
+        let imported = import(&text).unwrap();
+        assert_eq!(imported.title, "Order report");
+        assert_eq!(imported.cells.len(), 3);
+        for (imported, original) in imported.cells.iter().zip(&notebook.cells) {
+            assert_eq!(imported.cell_type, original.cell_type);
+            assert_eq!(imported.source, original.source);
+            assert_eq!(imported.metadata, original.metadata);
+            assert!(imported.output.is_none());
+        }
+    }
+
+    #[test]
+    fn test_import_rejects_other_json() {
+        assert!(import(r#"{"format": "something-else", "version": 1, "notebook": {}}"#).is_err());
+        assert!(import("```partiql\nSELECT * FROM items\n").is_err());
+    }
+}
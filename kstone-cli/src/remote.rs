@@ -0,0 +1,264 @@
+/// Remote mode: running commands against a kstone-server
+///
+/// `kstone connect <addr> <command>` runs `put`, `get`, `delete`, `query`
+/// and `shell` through kstone-client instead of opening a local directory.
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use kstone_api::{ExecuteStatementResponse, ItemJsonExt};
+use kstone_client::{Certificate, Client, ClientTlsConfig, Credentials, RemoteExecuteStatementResponse};
+use kstone_core::Item;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::OutputFormat;
+
+/// How to reach the server
+#[derive(Args, Clone)]
+pub struct ConnectArgs {
+    /// Server address (host:port or http[s]://host:port)
+    pub server: String,
+    /// API key or JWT, for servers with authentication enabled
+    #[arg(long)]
+    pub token: Option<String>,
+    /// Database to use on a server started with --root-dir
+    #[arg(long)]
+    pub database: Option<String>,
+    /// Connect over TLS (implied by an https:// address)
+    #[arg(long)]
+    pub tls: bool,
+    /// PEM file of the CA that signed the server's certificate
+    #[arg(long, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+    /// Name to verify the server's certificate against, if not the host
+    #[arg(long, value_name = "NAME")]
+    pub tls_domain: Option<String>,
+    /// Fail requests after this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+}
+
+/// Commands run against the server
+#[derive(Subcommand)]
+pub enum RemoteCommands {
+    /// Put an item
+    Put {
+        /// Partition key
+        key: String,
+        /// Item as JSON
+        item: String,
+    },
+    /// Get an item
+    Get {
+        /// Partition key
+        key: String,
+    },
+    /// Delete an item
+    Delete {
+        /// Partition key
+        key: String,
+    },
+    /// Execute a PartiQL query
+    Query {
+        /// PartiQL SQL statement
+        sql: String,
+        /// Maximum number of items to return
+        #[arg(short, long)]
+        limit: Option<usize>,
+        /// Output format (table, json, jsonl, csv)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Start interactive shell (the default)
+    Shell,
+}
+
+impl ConnectArgs {
+    /// The address with a scheme
+    fn url(&self) -> String {
+        if self.server.contains("://") {
+            self.server.clone()
+        } else if self.tls || self.ca_cert.is_some() {
+            format!("https://{}", self.server)
+        } else {
+            format!("http://{}", self.server)
+        }
+    }
+
+    fn tls_config(&self) -> Result<Option<ClientTlsConfig>> {
+        let url = self.url();
+        if !url.starts_with("https://") {
+            if self.tls || self.ca_cert.is_some() || self.tls_domain.is_some() {
+                anyhow::bail!("TLS options need an https:// address, got {}", url);
+            }
+            return Ok(None);
+        }
+
+        let mut config = ClientTlsConfig::new();
+        if let Some(path) = &self.ca_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read CA certificate {}", path.display()))?;
+            config = config.ca_certificate(Certificate::from_pem(pem));
+        }
+        if let Some(domain) = &self.tls_domain {
+            config = config.domain_name(domain.clone());
+        }
+        Ok(Some(config))
+    }
+}
+
+/// A connection to a server, with the runtime driving it
+pub struct RemoteSession {
+    client: Client,
+    runtime: Runtime,
+    /// Address shown to the user
+    pub addr: String,
+}
+
+impl RemoteSession {
+    /// Connect to the server described by `args`
+    pub fn connect(args: &ConnectArgs) -> Result<Self> {
+        let runtime = Runtime::new().context("Failed to start async runtime")?;
+        let addr = args.url();
+
+        let credentials = match &args.token {
+            Some(token) => Credentials::bearer(token).context("Invalid token")?,
+            None => Credentials::none(),
+        };
+        let mut builder = Client::builder(addr.clone())
+            .credentials(credentials)
+            .connect_timeout(Duration::from_secs(10));
+        if let Some(database) = &args.database {
+            builder = builder.database(database.clone());
+        }
+        if let Some(timeout) = args.timeout {
+            builder = builder.request_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(tls) = args.tls_config()? {
+            builder = builder.tls(tls);
+        }
+
+        let client = runtime
+            .block_on(builder.connect())
+            .with_context(|| format!("Failed to connect to {}", addr))?;
+
+        let addr = match &args.database {
+            Some(database) => format!("{}/{}", addr, database),
+            None => addr,
+        };
+        Ok(Self { client, runtime, addr })
+    }
+
+    /// Store `item` at partition key `key`
+    pub fn put(&mut self, key: &str, item: Item) -> Result<()> {
+        self.runtime.block_on(self.client.put(key.as_bytes(), item))?;
+        Ok(())
+    }
+
+    /// The item at partition key `key`
+    pub fn get(&mut self, key: &str) -> Result<Option<Item>> {
+        Ok(self.runtime.block_on(self.client.get(key.as_bytes()))?)
+    }
+
+    /// Remove the item at partition key `key`
+    pub fn delete(&mut self, key: &str) -> Result<()> {
+        self.runtime.block_on(self.client.delete(key.as_bytes()))?;
+        Ok(())
+    }
+
+    /// Run a PartiQL statement on the server
+    pub fn execute_statement(&mut self, sql: &str) -> Result<ExecuteStatementResponse> {
+        let response = self.runtime.block_on(self.client.execute_statement(sql))?;
+        Ok(match response {
+            RemoteExecuteStatementResponse::Select { items, count, scanned_count, last_key } => {
+                ExecuteStatementResponse::Select {
+                    items,
+                    count,
+                    scanned_count,
+                    last_key,
+                    warnings: Vec::new(),
+                }
+            }
+            RemoteExecuteStatementResponse::Insert { success } => ExecuteStatementResponse::Insert { success },
+            RemoteExecuteStatementResponse::Update { item } => ExecuteStatementResponse::Update { item },
+            RemoteExecuteStatementResponse::Delete { success } => ExecuteStatementResponse::Delete { success },
+        })
+    }
+}
+
+/// Run `command` against the server described by `args`
+pub fn run(args: ConnectArgs, command: Option<RemoteCommands>) -> Result<()> {
+    let mut session = RemoteSession::connect(&args)?;
+
+    match command.unwrap_or(RemoteCommands::Shell) {
+        RemoteCommands::Put { key, item } => {
+            let json: serde_json::Value = serde_json::from_str(&item).context("Invalid JSON")?;
+            let item = Item::from_json(json)?;
+            session.put(&key, item).context("Failed to put item")?;
+            println!("Item stored");
+        }
+
+        RemoteCommands::Get { key } => match session.get(&key).context("Failed to get item")? {
+            Some(item) => println!("{}", serde_json::to_string_pretty(&item.to_json())?),
+            None => println!("Item not found"),
+        },
+
+        RemoteCommands::Delete { key } => {
+            session.delete(&key).context("Failed to delete item")?;
+            println!("Item deleted");
+        }
+
+        RemoteCommands::Query { sql, limit, output } => {
+            let sql = match limit {
+                Some(limit) => format!("{} LIMIT {}", sql, limit),
+                None => sql,
+            };
+            let response = session
+                .execute_statement(&sql)
+                .context("Failed to execute statement")?;
+            crate::print_statement_response(response, output)?;
+        }
+
+        RemoteCommands::Shell => {
+            let mut shell = crate::shell::Shell::remote(session)?;
+            shell.run()?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(server: &str) -> ConnectArgs {
+        ConnectArgs {
+            server: server.to_string(),
+            token: None,
+            database: None,
+            tls: false,
+            ca_cert: None,
+            tls_domain: None,
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_connect_url() {
+        assert_eq!(args("localhost:50051").url(), "http://localhost:50051");
+        assert_eq!(args("https://db.example.com").url(), "https://db.example.com");
+
+        let mut tls = args("db.example.com:443");
+        tls.tls = true;
+        assert_eq!(tls.url(), "https://db.example.com:443");
+        assert!(tls.tls_config().unwrap().is_some());
+
+        // TLS options can't apply to a plaintext address
+        let mut plain = args("http://localhost:50051");
+        plain.tls_domain = Some("db.example.com".to_string());
+        assert!(plain.tls_config().is_err());
+        assert!(args("localhost:50051").tls_config().unwrap().is_none());
+    }
+}
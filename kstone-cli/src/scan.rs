@@ -0,0 +1,192 @@
+/// `kstone scan`: print every item, one page at a time
+///
+/// Items are written as each page arrives, so scanning a large database
+/// doesn't hold it all in memory.
+
+use anyhow::{Context, Result};
+use kstone_api::{item_to_json, Database, Scan};
+use kstone_core::Item;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::OutputFormat;
+
+/// Items fetched and printed at a time
+pub const SCAN_PAGE_SIZE: usize = 100;
+
+/// What to scan
+pub struct ScanOptions {
+    pub prefix: Option<String>,
+    pub limit: Option<usize>,
+    /// Segment and total segments of a parallel scan
+    pub segment: Option<(usize, usize)>,
+}
+
+/// Scan `db` and write the items to stdout in `format`
+pub fn run(db: &Database, options: ScanOptions, format: OutputFormat) -> Result<()> {
+    let mut scan = Scan::new().page_size(SCAN_PAGE_SIZE);
+    if let Some(prefix) = &options.prefix {
+        scan = scan.pk_prefix(prefix.as_bytes());
+    }
+    if let Some(limit) = options.limit {
+        scan = scan.limit(limit);
+    }
+    if let Some((segment, total_segments)) = options.segment {
+        if total_segments == 0 || segment >= total_segments {
+            anyhow::bail!("--segment must be less than --total-segments");
+        }
+        scan = scan.segment(segment, total_segments);
+    }
+
+    let mut items = db.scan_iter(scan).context("Failed to scan")?;
+    let stdout = std::io::stdout();
+    let mut writer = ItemWriter::new(stdout.lock(), format);
+    let mut page = Vec::with_capacity(SCAN_PAGE_SIZE);
+    loop {
+        page.clear();
+        for item in items.by_ref().take(SCAN_PAGE_SIZE) {
+            page.push(item.context("Failed to scan")?);
+        }
+        if page.is_empty() {
+            break;
+        }
+        writer.write_page(&page)?;
+    }
+    writer.finish()
+}
+
+/// Writes pages of items in an output format
+pub struct ItemWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    written: usize,
+    /// CSV columns, taken from the first page
+    csv_columns: Option<Vec<String>>,
+    warned_new_columns: bool,
+}
+
+impl<W: Write> ItemWriter<W> {
+    pub fn new(out: W, format: OutputFormat) -> Self {
+        Self {
+            out,
+            format,
+            written: 0,
+            csv_columns: None,
+            warned_new_columns: false,
+        }
+    }
+
+    /// Write one page of items
+    pub fn write_page(&mut self, items: &[Item]) -> Result<()> {
+        match self.format {
+            // One table per page keeps memory bounded
+            OutputFormat::Table => writeln!(self.out, "{}", crate::table::format_items_table(items))?,
+            OutputFormat::Json => {
+                for (i, item) in items.iter().enumerate() {
+                    let separator = if self.written + i == 0 { "[\n" } else { ",\n" };
+                    let json = serde_json::to_string_pretty(&item_to_json(item))?;
+                    write!(self.out, "{}{}", separator, json)?;
+                }
+            }
+            OutputFormat::Jsonl => {
+                for item in items {
+                    writeln!(self.out, "{}", serde_json::to_string(&item_to_json(item))?)?;
+                }
+            }
+            OutputFormat::Csv => self.write_csv(items)?,
+        }
+        self.written += items.len();
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn write_csv(&mut self, items: &[Item]) -> Result<()> {
+        let columns = match &self.csv_columns {
+            Some(columns) => columns.clone(),
+            None => {
+                let columns: Vec<String> = items
+                    .iter()
+                    .flat_map(|item| item.keys().cloned())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                writeln!(self.out, "{}", columns.join(","))?;
+                self.csv_columns = Some(columns.clone());
+                columns
+            }
+        };
+
+        for item in items {
+            if !self.warned_new_columns && item.keys().any(|name| !columns.contains(name)) {
+                eprintln!("Warning: attributes missing from the first page are left out of the CSV");
+                self.warned_new_columns = true;
+            }
+            let row: Vec<String> = columns
+                .iter()
+                .map(|name| item.get(name).map(crate::format_csv_value).unwrap_or_default())
+                .collect();
+            writeln!(self.out, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Close the output after the last page
+    pub fn finish(mut self) -> Result<()> {
+        match self.format {
+            OutputFormat::Json if self.written == 0 => writeln!(self.out, "[]")?,
+            OutputFormat::Json => writeln!(self.out, "\n]")?,
+            OutputFormat::Table if self.written == 0 => writeln!(self.out, "No items found")?,
+            OutputFormat::Table => writeln!(self.out, "\nCount: {}", self.written)?,
+            OutputFormat::Jsonl | OutputFormat::Csv => {}
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::{ItemBuilder, KeystoneValue};
+
+    fn write(format: OutputFormat, pages: &[Vec<Item>]) -> String {
+        let mut out = Vec::new();
+        let mut writer = ItemWriter::new(&mut out, format);
+        for page in pages {
+            writer.write_page(page).unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_json_spans_pages() {
+        let pages = vec![
+            vec![ItemBuilder::new().number("n", 1).build()],
+            vec![ItemBuilder::new().number("n", 2).build()],
+        ];
+        let json: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json, &pages)).unwrap();
+        assert_eq!(json, serde_json::json!([{"n": 1}, {"n": 2}]));
+
+        assert_eq!(write(OutputFormat::Json, &[]).trim(), "[]");
+    }
+
+    #[test]
+    fn test_csv_header_from_first_page() {
+        let mut late = ItemBuilder::new().string("name", "b").build();
+        late.insert("extra".to_string(), KeystoneValue::Bool(true));
+        let pages = vec![vec![ItemBuilder::new().string("name", "a").number("n", 1).build()], vec![late]];
+
+        let csv = write(OutputFormat::Csv, &pages);
+        assert_eq!(csv, "n,name\n1,a\n,b\n");
+    }
+
+    #[test]
+    fn test_jsonl_one_item_per_line() {
+        let pages = vec![vec![
+            ItemBuilder::new().number("n", 1).build(),
+            ItemBuilder::new().number("n", 2).build(),
+        ]];
+        assert_eq!(write(OutputFormat::Jsonl, &pages).lines().count(), 2);
+    }
+}
@@ -14,13 +14,32 @@ use rustyline::{
     validate::Validator,
     Helper,
 };
-use std::path::Path;
+use kstone_api::{ExecuteStatementResponse, Scan, Select};
+use kstone_core::Item;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
-/// Autocomplete helper for PartiQL and meta-commands
+use crate::remote::RemoteSession;
+
+/// History file in the home directory
+const HISTORY_FILE: &str = ".kstone_history";
+
+/// History file used before it was renamed, loaded if the new one doesn't exist yet
+const LEGACY_HISTORY_FILE: &str = ".keystone_history";
+
+/// Items read at startup to learn attribute names for completion
+const ATTRIBUTE_SAMPLE_SIZE: usize = 100;
+
+/// Keys `.keys` lists when no limit is given
+const DEFAULT_KEYS_LIMIT: usize = 50;
+
+/// Autocomplete helper for PartiQL, attribute names and meta-commands
 #[derive(Clone)]
 struct KeystoneCompleter {
     meta_commands: Vec<String>,
     partiql_keywords: Vec<String>,
+    /// Attribute names seen in items returned so far
+    attributes: BTreeSet<String>,
 }
 
 impl KeystoneCompleter {
@@ -32,6 +51,10 @@ impl KeystoneCompleter {
                 ".quit".to_string(),
                 ".schema".to_string(),
                 ".indexes".to_string(),
+                ".count".to_string(),
+                ".keys".to_string(),
+                ".stats".to_string(),
+                ".slow".to_string(),
                 ".format".to_string(),
                 ".timer".to_string(),
                 ".clear".to_string(),
@@ -49,8 +72,28 @@ impl KeystoneCompleter {
                 "AND".to_string(),
                 "OR".to_string(),
                 "NOT".to_string(),
+                "IN".to_string(),
+                "BETWEEN".to_string(),
+                "REMOVE".to_string(),
+                "ORDER".to_string(),
+                "GROUP".to_string(),
+                "BY".to_string(),
+                "ASC".to_string(),
+                "DESC".to_string(),
+                "LIMIT".to_string(),
+                "OFFSET".to_string(),
+                "COUNT".to_string(),
+                "EXPLAIN".to_string(),
                 "items".to_string(), // table name
             ],
+            attributes: BTreeSet::new(),
+        }
+    }
+
+    /// Learn the attribute names of `items`
+    fn add_attributes<'a>(&mut self, items: impl IntoIterator<Item = &'a Item>) {
+        for item in items {
+            self.attributes.extend(item.keys().cloned());
         }
     }
 
@@ -65,11 +108,35 @@ impl KeystoneCompleter {
             .collect()
     }
 
+    /// Values a meta-command's argument can take
+    fn complete_meta_argument(&self, command: &str, word: &str) -> Vec<Pair> {
+        let values: &[&str] = match command {
+            ".format" => &["table", "json", "compact"],
+            ".timer" => &["on", "off"],
+            ".stats" => &["ssts"],
+            ".slow" => &["off", "clear"],
+            _ => &[],
+        };
+        values
+            .iter()
+            .filter(|value| value.starts_with(word))
+            .map(|value| Pair {
+                display: value.to_string(),
+                replacement: value.to_string(),
+            })
+            .collect()
+    }
+
     fn complete_keyword(&self, word: &str) -> Vec<Pair> {
         let word_upper = word.to_uppercase();
-        self.partiql_keywords
+        let keywords = self
+            .partiql_keywords
             .iter()
-            .filter(|kw| kw.to_uppercase().starts_with(&word_upper))
+            .filter(|kw| kw.to_uppercase().starts_with(&word_upper));
+        // Attribute names are case-sensitive
+        let attributes = self.attributes.iter().filter(|name| name.starts_with(word));
+        keywords
+            .chain(attributes)
             .map(|kw| Pair {
                 display: kw.clone(),
                 replacement: kw.clone(),
@@ -91,22 +158,23 @@ impl Completer for KeystoneCompleter {
 
         // Complete meta-commands if line starts with '.'
         if line_prefix.starts_with('.') {
+            if let Some((command, word)) = line_prefix.split_once(' ') {
+                let word = word.trim_start();
+                return Ok((pos - word.len(), self.complete_meta_argument(command, word)));
+            }
             let candidates = self.complete_meta(line_prefix);
             return Ok((0, candidates));
         }
 
-        // Complete PartiQL keywords
-        // Find the last word before cursor
-        if let Some(last_space) = line_prefix.rfind(|c: char| c.is_whitespace()) {
-            let word_start = last_space + 1;
-            let word = &line_prefix[word_start..];
-            let candidates = self.complete_keyword(word);
-            return Ok((word_start, candidates));
+        // Complete PartiQL keywords and attribute names in the word before the cursor
+        let word_start = line_prefix
+            .rfind(|c: char| c.is_whitespace() || "(),=<>.".contains(c))
+            .map_or(0, |i| i + 1);
+        let word = &line_prefix[word_start..];
+        if word.starts_with(['\'', '"']) {
+            return Ok((word_start, Vec::new()));
         }
-
-        // First word completion
-        let candidates = self.complete_keyword(line_prefix);
-        Ok((0, candidates))
+        Ok((word_start, self.complete_keyword(word)))
     }
 }
 
@@ -120,11 +188,19 @@ impl Validator for KeystoneCompleter {}
 
 impl Helper for KeystoneCompleter {}
 
+/// Where the shell's statements run
+enum Backend {
+    /// A database opened in this process
+    Local(Database),
+    /// A kstone-server
+    Remote(RemoteSession),
+}
+
 /// Interactive shell session state
 pub struct Shell {
     /// Database instance
-    db: Database,
-    /// Database path for display
+    backend: Backend,
+    /// Database path (or server address) for display
     db_path: String,
     /// Line editor with history and autocomplete
     editor: rustyline::Editor<KeystoneCompleter, rustyline::history::FileHistory>,
@@ -144,7 +220,9 @@ pub enum OutputFormat {
 
 impl Shell {
     /// Create a new shell session
-    pub fn new(db_path: Option<&Path>) -> Result<Self> {
+    ///
+    /// `force` breaks a stale lock on an on-disk database.
+    pub fn new(db_path: Option<&Path>, force: bool) -> Result<Self> {
         // Determine if we should use in-memory mode
         let (db, display_path) = match db_path {
             // No path provided - use in-memory
@@ -161,13 +239,27 @@ impl Shell {
                         .context("Failed to create in-memory database")?;
                     (db, ":memory:".to_string())
                 } else {
-                    let db = Database::open(path)
-                        .context(format!("Failed to open database at {:?}", path))?;
+                    let db = if force {
+                        Database::open_force(path)
+                    } else {
+                        Database::open(path)
+                    }
+                    .context(format!("Failed to open database at {:?}", path))?;
                     (db, path.display().to_string())
                 }
             }
         };
 
+        Self::with_backend(Backend::Local(db), display_path)
+    }
+
+    /// Create a shell session on a server
+    pub fn remote(session: RemoteSession) -> Result<Self> {
+        let display_path = session.addr.clone();
+        Self::with_backend(Backend::Remote(session), display_path)
+    }
+
+    fn with_backend(backend: Backend, db_path: String) -> Result<Self> {
         // Create editor with custom completer
         let completer = KeystoneCompleter::new();
         let mut editor = rustyline::Editor::new()
@@ -175,21 +267,48 @@ impl Shell {
         editor.set_helper(Some(completer));
 
         // Load history from file
-        let history_path = dirs::home_dir()
-            .map(|p| p.join(".keystone_history"))
-            .unwrap_or_else(|| ".keystone_history".into());
-
+        let legacy_path = history_path(LEGACY_HISTORY_FILE);
+        let history_path = history_path(HISTORY_FILE);
         if history_path.exists() {
             let _ = editor.load_history(&history_path);
+        } else if legacy_path.exists() {
+            let _ = editor.load_history(&legacy_path);
         }
 
-        Ok(Self {
-            db,
-            db_path: display_path,
+        let mut shell = Self {
+            backend,
+            db_path,
             editor,
             format: OutputFormat::Table,
             show_timing: true,
-        })
+        };
+        shell.sample_attributes();
+        Ok(shell)
+    }
+
+    /// Learn attribute names for completion from a sample of items
+    fn sample_attributes(&mut self) {
+        let sql = format!("SELECT * FROM items LIMIT {}", ATTRIBUTE_SAMPLE_SIZE);
+        let response = match &mut self.backend {
+            Backend::Local(db) => db.execute_statement(&sql).map_err(anyhow::Error::from),
+            Backend::Remote(session) => session.execute_statement(&sql),
+        };
+        // Completion works without attributes, so failures are ignored
+        if let Ok(response) = response {
+            self.learn_attributes(&response);
+        }
+    }
+
+    /// Add the attribute names of items in `response` to completion
+    fn learn_attributes(&mut self, response: &ExecuteStatementResponse) {
+        let Some(completer) = self.editor.helper_mut() else {
+            return;
+        };
+        match response {
+            ExecuteStatementResponse::Select { items, .. } => completer.add_attributes(items),
+            ExecuteStatementResponse::Update { item } => completer.add_attributes([item]),
+            _ => {}
+        }
     }
 
     /// Run the interactive REPL
@@ -197,63 +316,60 @@ impl Shell {
         self.print_welcome();
 
         let mut buffer = String::new();
-        let mut in_multiline = false;
 
         loop {
-            let prompt = if in_multiline {
-                format!("{}    ", "...>".dimmed())
-            } else {
+            let prompt = if buffer.is_empty() {
                 format!("{} ", "kstone>".green().bold())
+            } else {
+                format!("{}    ", "...>".dimmed())
             };
 
             match self.editor.readline(&prompt) {
                 Ok(line) => {
                     let line = line.trim();
 
-                    // Skip empty lines in single-line mode
-                    if line.is_empty() && !in_multiline {
-                        continue;
-                    }
+                    if buffer.is_empty() {
+                        // Skip empty lines in single-line mode
+                        if line.is_empty() {
+                            continue;
+                        }
 
-                    // Handle exit in single-line mode
-                    if !in_multiline && (line == ".exit" || line == ".quit") {
-                        break;
+                        // Meta-commands are always single line
+                        if line.starts_with('.') {
+                            self.remember(line);
+                            if line == ".exit" || line == ".quit" {
+                                break;
+                            }
+                            if let Err(e) = self.execute_meta_command(line) {
+                                eprintln!("{} {}", "Error:".red().bold(), e);
+                            }
+                            continue;
+                        }
                     }
 
-                    // Accumulate input
-                    if !buffer.is_empty() {
+                    // Accumulate input until statements end with a semicolon
+                    if !buffer.is_empty() && !line.is_empty() {
                         buffer.push(' ');
                     }
                     buffer.push_str(line);
 
-                    // Check if query is complete
-                    let complete = if buffer.starts_with('.') {
-                        // Meta-commands are always single line
-                        true
-                    } else {
-                        // PartiQL queries end with semicolon
-                        buffer.trim_end().ends_with(';')
-                    };
-
-                    if complete {
-                        // Execute the complete query
-                        let query = buffer.trim().to_string();
-                        buffer.clear();
-                        in_multiline = false;
+                    let (statements, rest) = split_statements(&buffer);
+                    if statements.is_empty() {
+                        continue;
+                    }
+                    self.remember(&statements.join(" "));
+                    buffer = rest;
 
-                        if let Err(e) = self.execute(&query) {
+                    for statement in &statements {
+                        if let Err(e) = self.execute_query(statement) {
                             eprintln!("{} {}", "Error:".red().bold(), e);
                         }
-                    } else {
-                        // Continue accumulating
-                        in_multiline = true;
                     }
                 }
                 Err(ReadlineError::Interrupted) => {
                     // Ctrl+C - cancel current input and start fresh
                     println!("^C");
                     buffer.clear();
-                    in_multiline = false;
                     continue;
                 }
                 Err(ReadlineError::Eof) => {
@@ -273,17 +389,11 @@ impl Shell {
         Ok(())
     }
 
-    /// Execute a command or query
-    fn execute(&mut self, input: &str) -> Result<()> {
-        // Check if it's a meta-command
-        if input.starts_with('.') {
-            self.execute_meta_command(input)?;
-        } else {
-            // It's a PartiQL query
-            self.execute_query(input)?;
+    /// Add an entry to the history, saving it right away so it survives crashes
+    fn remember(&mut self, entry: &str) {
+        if self.editor.add_history_entry(entry).unwrap_or(false) {
+            let _ = self.editor.append_history(&history_path(HISTORY_FILE));
         }
-
-        Ok(())
     }
 
     /// Execute a meta-command (dot-command)
@@ -299,6 +409,26 @@ impl Shell {
             }
             ".schema" => self.show_schema(),
             ".indexes" => self.show_indexes(),
+            ".count" => self.count_items(parts.get(1).copied()),
+            ".keys" => match parts.get(2).map(|limit| limit.parse::<usize>()) {
+                None => self.list_keys(parts.get(1).copied(), DEFAULT_KEYS_LIMIT),
+                Some(Ok(limit)) if limit > 0 => self.list_keys(parts.get(1).copied(), limit),
+                Some(_) => {
+                    println!("Usage: .keys [<pk prefix>] [<limit>]");
+                    Ok(())
+                }
+            },
+            ".stats" => {
+                let Backend::Local(db) = &self.backend else {
+                    println!("{}", "Statistics are not available over a server connection".yellow());
+                    return Ok(());
+                };
+                let stats = db.stats_detailed().context("Failed to get statistics")?;
+                println!();
+                crate::stats::print(&stats, parts.get(1) == Some(&"ssts"));
+                Ok(())
+            }
+            ".slow" => self.slow_operations(parts.get(1).copied()),
             ".format" => {
                 if parts.len() < 2 {
                     println!("Usage: .format <table|json|compact>");
@@ -329,14 +459,50 @@ impl Shell {
         }
     }
 
+    /// Show the slow operation log, change its threshold or clear it
+    fn slow_operations(&mut self, arg: Option<&str>) -> Result<()> {
+        let Backend::Local(db) = &self.backend else {
+            println!("{}", "The slow operation log is not available over a server connection".yellow());
+            return Ok(());
+        };
+
+        match arg {
+            None => {
+                println!();
+                println!("{}", crate::table::format_slow_operations_table(&db.slow_queries()));
+            }
+            Some("off") => {
+                db.set_slow_operation_threshold(None).context("Failed to disable slow operation log")?;
+                println!("Slow operation log disabled");
+            }
+            Some("clear") => {
+                db.clear_slow_queries();
+                println!("Slow operation log cleared");
+            }
+            Some(ms) => match ms.parse::<u64>() {
+                Ok(ms) => {
+                    db.set_slow_operation_threshold(Some(std::time::Duration::from_millis(ms)))
+                        .context("Failed to set slow operation threshold")?;
+                    println!("Logging operations taking at least {}ms", ms);
+                }
+                Err(_) => println!("Usage: .slow [<ms>|off|clear]"),
+            },
+        }
+        Ok(())
+    }
+
     /// Execute a PartiQL query
     fn execute_query(&mut self, sql: &str) -> Result<()> {
         let start = std::time::Instant::now();
 
-        let response = self.db.execute_statement(sql)
-            .context("Query execution failed")?;
+        let response = match &mut self.backend {
+            Backend::Local(db) => db.execute_statement(sql).map_err(anyhow::Error::from),
+            Backend::Remote(session) => session.execute_statement(sql),
+        }
+        .context("Query execution failed")?;
 
         let elapsed = start.elapsed();
+        self.learn_attributes(&response);
 
         // Display results based on format
         match self.format {
@@ -358,6 +524,8 @@ impl Shell {
                 kstone_api::ExecuteStatementResponse::Insert { .. } => 1,
                 kstone_api::ExecuteStatementResponse::Update { .. } => 1,
                 kstone_api::ExecuteStatementResponse::Delete { .. } => 1,
+                kstone_api::ExecuteStatementResponse::InsertSelect { inserted } => inserted,
+                kstone_api::ExecuteStatementResponse::Explain { .. } => 0,
             };
 
             println!(
@@ -376,8 +544,12 @@ impl Shell {
         println!("\n  {}", "Meta-commands:".cyan());
         println!("    .help              Show this help message");
         println!("    .exit, .quit       Exit the shell");
-        println!("    .schema            Display database schema");
-        println!("    .indexes           List all indexes (LSI/GSI)");
+        println!("    .schema            Show indexes, TTL and stream configuration");
+        println!("    .indexes           List all indexes (LSI/GSI/vector/geo)");
+        println!("    .count [prefix]    Count items whose partition key starts with prefix");
+        println!("    .keys [prefix] [n] List up to n keys (default {}) starting with prefix", DEFAULT_KEYS_LIMIT);
+        println!("    .stats [ssts]      Show statistics per stripe (and per SST)");
+        println!("    .slow [ms|off|clear]  Show slow operations, or set the threshold");
         println!("    .format <type>     Set output format (table|json|compact)");
         println!("    .timer <on|off>    Toggle query timing display");
         println!("    .clear             Clear the screen");
@@ -389,13 +561,14 @@ impl Shell {
         println!("    DELETE FROM items WHERE pk = 'key';");
         println!("\n  {}", "Multi-line Queries:".cyan());
         println!("    Queries without a semicolon will continue on the next line.");
+        println!("    Several statements on one line run in order.");
         println!("    Use Ctrl+C to cancel a multi-line query.");
 
         println!("\n  {}", "Keyboard Shortcuts:".cyan());
         println!("    Ctrl+C             Cancel current input");
         println!("    Ctrl+D             Exit shell");
-        println!("    Up/Down Arrow      Navigate command history");
-        println!("    Tab                Autocomplete commands and keywords");
+        println!("    Up/Down Arrow      Navigate command history (saved to ~/{})", HISTORY_FILE);
+        println!("    Tab                Autocomplete commands, keywords and attribute names");
         println!();
 
         Ok(())
@@ -404,54 +577,54 @@ impl Shell {
     /// Show database schema
     fn show_schema(&self) -> Result<()> {
         println!("\n{}", "Database Schema:".bold());
-        println!("  {}: {}", "Path".cyan(), self.db_path);
+        let label = match self.backend {
+            Backend::Local(_) => "Path",
+            Backend::Remote(_) => "Server",
+        };
+        println!("  {}: {}", label.cyan(), self.db_path);
         println!();
 
-        // Check if in-memory mode
+        let db = match &self.backend {
+            Backend::Local(db) => db,
+            Backend::Remote(_) => {
+                println!("  {}", "Mode:".cyan());
+                println!("    Connected to a kstone-server");
+                println!("    Statements run on the server; .schema details, .indexes, .count, .keys,");
+                println!("    .stats and .slow are unavailable");
+                println!();
+                return Ok(());
+            }
+        };
+
         if self.db_path == ":memory:" {
             println!("  {}", "Mode:".cyan());
             println!("    In-memory database (no disk persistence)");
             println!("    All data will be lost when shell exits");
-            println!("    Full PartiQL support available");
             println!();
-        } else {
-            // Show database files
-            let db_path = std::path::Path::new(&self.db_path);
-            if db_path.exists() {
-                let mut sst_count = 0;
-                let mut wal_exists = false;
-                let mut total_size: u64 = 0;
-
-                if let Ok(entries) = std::fs::read_dir(db_path) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if let Ok(metadata) = entry.metadata() {
-                            total_size += metadata.len();
-
-                            if let Some(name) = path.file_name() {
-                                let name_str = name.to_string_lossy();
-                                if name_str.ends_with(".sst") {
-                                    sst_count += 1;
-                                } else if name_str == "wal.log" {
-                                    wal_exists = true;
-                                }
-                            }
-                        }
-                    }
-                }
+        }
 
-                println!("  {}", "Storage:".cyan());
-                println!("    SST files: {}", sst_count);
-                println!("    WAL: {}", if wal_exists { "present" } else { "missing" });
-                println!("    Total size: {} bytes", total_size);
-                println!();
-            }
+        let description = db.describe().context("Failed to describe database")?;
+        let schema = &description.schema;
+        println!("  {}", "Table:".cyan());
+        println!("    Approximate items: {}", description.approximate_item_count);
+        println!("    TTL attribute: {}", schema.ttl_attribute_name.as_deref().unwrap_or("none"));
+        if schema.stream_config.enabled {
+            let retention = match schema.stream_config.retention_period_ms {
+                Some(ms) => format!("{}s retention", ms / 1000),
+                None => "unlimited retention".to_string(),
+            };
+            println!("    Stream: {:?} ({})", schema.stream_config.view_type, retention);
+        } else {
+            println!("    Stream: disabled");
+        }
+        if !schema.tables.is_empty() {
+            let names: Vec<&str> = schema.tables.keys().map(String::as_str).collect();
+            println!("    Named tables: {}", names.join(", "));
         }
+        println!();
 
-        // Future features note
-        println!("  {}", "Note:".yellow());
-        println!("    Full schema inspection (table schema, partition/sort keys)");
-        println!("    will be available in Phase 3+");
+        println!("  {}", "Indexes:".cyan());
+        println!("{}", crate::table::format_indexes_table(schema));
         println!();
 
         Ok(())
@@ -459,21 +632,55 @@ impl Shell {
 
     /// Show indexes
     fn show_indexes(&self) -> Result<()> {
-        println!("\n{}", "Indexes:".bold());
-        println!();
+        let Backend::Local(db) = &self.backend else {
+            println!("{}", "Indexes are not available over a server connection".yellow());
+            return Ok(());
+        };
 
-        println!("  {}", "Current Phase:".cyan());
-        println!("    KeystoneDB is currently in Phase 0 (Walking Skeleton)");
-        println!("    Indexes are not yet implemented.");
+        let description = db.describe().context("Failed to describe database")?;
         println!();
+        println!("{}", crate::table::format_indexes_table(&description.schema));
+        Ok(())
+    }
 
-        println!("  {}", "Coming in Phase 3:".cyan());
-        println!("    • Local Secondary Indexes (LSI) - Alternate sort keys");
-        println!("    • Global Secondary Indexes (GSI) - Alternate partition keys");
-        println!("    • Full-text search indexes");
-        println!("    • Vector similarity indexes");
-        println!();
+    /// Count the items whose partition key starts with `prefix`
+    fn count_items(&self, prefix: Option<&str>) -> Result<()> {
+        let Backend::Local(db) = &self.backend else {
+            println!("{}", "Counting is not available over a server connection; use SELECT".yellow());
+            return Ok(());
+        };
+
+        // Counting keeps no items, so one unlimited scan is enough
+        let mut scan = Scan::new().select(Select::Count);
+        if let Some(prefix) = prefix {
+            scan = scan.pk_prefix(prefix.as_bytes());
+        }
+        let response = db.scan(scan).context("Failed to count items")?;
+        println!("{}", response.count);
+        Ok(())
+    }
+
+    /// List up to `limit` keys whose partition key starts with `prefix`
+    fn list_keys(&self, prefix: Option<&str>, limit: usize) -> Result<()> {
+        let Backend::Local(db) = &self.backend else {
+            println!("{}", "Listing keys is not available over a server connection".yellow());
+            return Ok(());
+        };
 
+        let mut scan = Scan::new().select(Select::Keys).limit(limit);
+        if let Some(prefix) = prefix {
+            scan = scan.pk_prefix(prefix.as_bytes());
+        }
+        let response = db.scan(scan).context("Failed to list keys")?;
+        for (pk, sk) in &response.keys {
+            match sk {
+                Some(sk) => println!("{} / {}", String::from_utf8_lossy(pk), String::from_utf8_lossy(sk)),
+                None => println!("{}", String::from_utf8_lossy(pk)),
+            }
+        }
+
+        let more = if response.keys.len() == limit && response.last_key.is_some() { "+" } else { "" };
+        println!("\n{}", format!("{}{} key{}", response.keys.len(), more, if response.keys.len() == 1 { "" } else { "s" }).dimmed());
         Ok(())
     }
 
@@ -555,13 +762,84 @@ impl Shell {
 
     /// Save command history
     fn save_history(&mut self) -> Result<()> {
-        let history_path = dirs::home_dir()
-            .map(|p| p.join(".keystone_history"))
-            .unwrap_or_else(|| ".keystone_history".into());
-
-        self.editor.save_history(&history_path)
+        self.editor.save_history(&history_path(HISTORY_FILE))
             .context("Failed to save command history")?;
 
         Ok(())
     }
 }
+
+/// Path of a history file in the home directory (or the current one)
+fn history_path(name: &str) -> PathBuf {
+    dirs::home_dir()
+        .map(|p| p.join(name))
+        .unwrap_or_else(|| name.into())
+}
+
+/// Split complete statements off `input`, returning them and the unfinished rest
+///
+/// Statements end with a semicolon outside quotes, which stays part of them.
+fn split_statements(input: &str) -> (Vec<String>, String) {
+    let mut statements = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, ';') => {
+                let statement = input[start..=i].trim();
+                if statement != ";" {
+                    statements.push(statement.to_string());
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    (statements, input[start..].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+
+    #[test]
+    fn test_split_statements() {
+        let (statements, rest) = split_statements("SELECT * FROM items WHERE pk = 'a;b'; DELETE FROM items");
+        assert_eq!(statements, vec!["SELECT * FROM items WHERE pk = 'a;b';"]);
+        assert_eq!(rest, "DELETE FROM items");
+
+        let (statements, rest) = split_statements("SELECT * FROM items; ; SELECT 1;");
+        assert_eq!(statements, vec!["SELECT * FROM items;", "SELECT 1;"]);
+        assert!(rest.is_empty());
+
+        // An open quote keeps the statement going across lines
+        let (statements, rest) = split_statements("INSERT INTO items VALUE {'pk': 'x;");
+        assert!(statements.is_empty());
+        assert_eq!(rest, "INSERT INTO items VALUE {'pk': 'x;");
+    }
+
+    #[test]
+    fn test_complete_attributes_and_keywords() {
+        let mut completer = KeystoneCompleter::new();
+        completer.add_attributes([&ItemBuilder::new().string("name", "Alice").number("age", 30).build()]);
+        let complete = |word: &str| -> Vec<String> {
+            completer.complete_keyword(word).into_iter().map(|pair| pair.replacement).collect()
+        };
+
+        assert_eq!(complete("n"), vec!["NOT", "name"]);
+        assert_eq!(complete("lim"), vec!["LIMIT"]);
+        assert_eq!(complete("ag"), vec!["age"]);
+
+        let formats: Vec<String> = completer
+            .complete_meta_argument(".format", "j")
+            .into_iter()
+            .map(|pair| pair.replacement)
+            .collect();
+        assert_eq!(formats, vec!["json"]);
+    }
+}
@@ -0,0 +1,177 @@
+/// `kstone stats`: database statistics as tables or JSON
+///
+/// `--watch` reopens the database read-only on every refresh, so it can
+/// follow a database another process is writing. Counters such as
+/// compactions cover the process that opened the database, so they only
+/// grow while `kstone stats` itself holds it.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use kstone_api::{Database, DetailedStats};
+use serde_json::json;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::table;
+
+/// What to print, and how often
+pub struct StatsOptions {
+    /// Also list every SST file
+    pub ssts: bool,
+    /// Print JSON instead of tables (one document per line with `watch`)
+    pub json: bool,
+    /// Refresh every this many seconds until interrupted
+    pub watch: Option<u64>,
+}
+
+/// Print the statistics of the database at `path`
+pub fn run(path: &Path, options: &StatsOptions, force: bool) -> Result<()> {
+    let Some(interval) = options.watch else {
+        let db = crate::open_database(path, force)?;
+        let stats = db.stats_detailed().context("Failed to get statistics")?;
+        return show(&stats, options);
+    };
+
+    loop {
+        let stats = Database::open_read_only(path)
+            .context("Failed to open database")?
+            .stats_detailed()
+            .context("Failed to get statistics")?;
+        if !options.json {
+            // Clear the screen and move the cursor home
+            print!("\x1B[2J\x1B[H");
+            println!("Every {}s: {} (Ctrl-C to stop)\n", interval, path.display());
+        }
+        show(&stats, options)?;
+        std::io::stdout().flush()?;
+        std::thread::sleep(Duration::from_secs(interval.max(1)));
+    }
+}
+
+fn show(stats: &DetailedStats, options: &StatsOptions) -> Result<()> {
+    if options.json {
+        println!("{}", serde_json::to_string(&stats_json(stats))?);
+    } else {
+        print(stats, options.ssts);
+    }
+    Ok(())
+}
+
+/// Print `stats` as tables
+pub fn print(stats: &DetailedStats, ssts: bool) {
+    let db = &stats.stats;
+    let bloom = stats.bloom();
+    println!("{}", "Database statistics".bold());
+    println!("  Keys (estimated): {}", stats.estimated_keys());
+    println!("  Disk usage:       {}", table::format_bytes(db.total_disk_size_bytes.unwrap_or(0)));
+    println!("  WAL size:         {}", table::format_bytes(db.wal_size_bytes.unwrap_or(0)));
+    println!("  SST files:        {}", db.total_sst_files);
+    println!("  Memtable bytes:   {}", table::format_bytes(db.memtable_size_bytes.unwrap_or(0)));
+    println!(
+        "  Bloom filter:     {} checks, {} false-positive rate",
+        bloom.checks,
+        table::format_rate(bloom.false_positive_rate())
+    );
+    println!();
+
+    let compaction = &db.compaction;
+    println!("{}", "Compaction".bold());
+    println!(
+        "  Compactions:      {} ({} active)",
+        compaction.total_compactions, compaction.active_compactions
+    );
+    println!(
+        "  SSTs:             {} merged, {} created",
+        compaction.total_ssts_merged, compaction.total_ssts_created
+    );
+    println!(
+        "  Bytes:            {} read, {} written, {} reclaimed",
+        table::format_bytes(compaction.total_bytes_read),
+        table::format_bytes(compaction.total_bytes_written),
+        table::format_bytes(compaction.total_bytes_reclaimed)
+    );
+    println!("  Pending flushes:  {}", db.flush.pending_flushes);
+    println!();
+
+    let stream = &db.stream;
+    println!("{}", "Stream".bold());
+    println!("  Retained records: {}", stream.retained_records);
+    println!("  Subscriptions:    {}", stream.subscriptions);
+    println!("  Subscriber lag:   {}", stream.max_subscriber_lag);
+    println!();
+
+    let stripes: Vec<_> = stats.non_empty_stripes().collect();
+    println!("{}", table::format_stripe_stats_table(&stripes));
+    if ssts {
+        println!();
+        println!("{}", table::format_sst_stats_table(&stripes));
+    }
+}
+
+/// `stats` as JSON, for scripts
+pub fn stats_json(stats: &DetailedStats) -> serde_json::Value {
+    let db = &stats.stats;
+    let compaction = &db.compaction;
+    let stripes: Vec<serde_json::Value> = stats
+        .non_empty_stripes()
+        .map(|stripe| {
+            json!({
+                "stripe": stripe.stripe,
+                "memtable_records": stripe.memtable_records,
+                "memtable_size_bytes": stripe.memtable_size_bytes,
+                "estimated_keys": stripe.estimated_keys,
+                "sst_files": stripe.sst_count(),
+                "sst_bytes": stripe.sst_bytes(),
+            })
+        })
+        .collect();
+
+    json!({
+        "estimated_keys": stats.estimated_keys(),
+        "disk_size_bytes": db.total_disk_size_bytes,
+        "wal_size_bytes": db.wal_size_bytes,
+        "sst_files": db.total_sst_files,
+        "memtable_size_bytes": db.memtable_size_bytes,
+        "compaction": {
+            "total_compactions": compaction.total_compactions,
+            "active_compactions": compaction.active_compactions,
+            "total_ssts_merged": compaction.total_ssts_merged,
+            "total_ssts_created": compaction.total_ssts_created,
+            "total_bytes_read": compaction.total_bytes_read,
+            "total_bytes_written": compaction.total_bytes_written,
+            "total_bytes_reclaimed": compaction.total_bytes_reclaimed,
+            "pending_flushes": db.flush.pending_flushes,
+        },
+        "stream": {
+            "retained_records": db.stream.retained_records,
+            "last_sequence_number": db.stream.last_sequence_number,
+            "subscriptions": db.stream.subscriptions,
+            "max_subscriber_lag": db.stream.max_subscriber_lag,
+        },
+        "stripes": stripes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stats_json() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        db.put(b"user#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        db.flush().unwrap();
+
+        let json = stats_json(&db.stats_detailed().unwrap());
+        assert_eq!(json["sst_files"], 1);
+        assert_eq!(json["estimated_keys"], 1);
+        assert!(json["wal_size_bytes"].as_u64().unwrap() > 0);
+        assert!(json["disk_size_bytes"].as_u64() >= json["wal_size_bytes"].as_u64());
+        assert_eq!(json["stripes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["stream"]["retained_records"], 0);
+    }
+}
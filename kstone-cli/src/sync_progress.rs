@@ -0,0 +1,134 @@
+/// Progress display for `kstone sync start`
+///
+/// Renders a sync engine's events on stderr: a line per phase and a
+/// progress bar redrawn in place after each transferred batch.
+
+use kstone_sync::{SyncEvent, SyncState};
+use std::io::Write;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::table::format_bytes;
+
+/// Width of the progress bar in characters
+const BAR_WIDTH: usize = 30;
+
+/// Print sync events until the engine is dropped
+pub async fn render(mut events: broadcast::Receiver<SyncEvent>) {
+    let mut conflicts = 0;
+    let mut deferred = 0;
+    let mut bar_drawn = false;
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        let mut stderr = std::io::stderr().lock();
+        match event {
+            SyncEvent::StateChanged { new_state, .. } => {
+                if let Some(phase) = phase_name(&new_state) {
+                    if bar_drawn {
+                        let _ = writeln!(stderr);
+                        bar_drawn = false;
+                    }
+                    let _ = writeln!(stderr, "{}...", phase);
+                }
+            }
+            SyncEvent::Progress {
+                completed,
+                total,
+                sent,
+                received,
+                bytes_sent,
+                bytes_received,
+            } => {
+                let _ = write!(
+                    stderr,
+                    "\r{} {}/{} changes  ↑ {} ({})  ↓ {} ({})  conflicts: {}",
+                    progress_bar(completed, total),
+                    completed,
+                    total,
+                    sent,
+                    format_bytes(bytes_sent as u64),
+                    received,
+                    format_bytes(bytes_received as u64),
+                    conflicts
+                );
+                bar_drawn = true;
+            }
+            SyncEvent::ConflictDetected { .. } => conflicts += 1,
+            SyncEvent::ConflictDeferred { .. } => deferred += 1,
+            SyncEvent::Completed { stats } => {
+                if bar_drawn {
+                    let _ = writeln!(stderr);
+                    bar_drawn = false;
+                }
+                let _ = writeln!(
+                    stderr,
+                    "Pushed {} and pulled {} items in {} ms ({} conflicts)",
+                    stats.items_sent, stats.items_received, stats.duration_ms, conflicts
+                );
+                if deferred > 0 {
+                    let _ = writeln!(
+                        stderr,
+                        "{} conflict(s) need manual resolution; see 'kstone sync conflicts'",
+                        deferred
+                    );
+                }
+                conflicts = 0;
+                deferred = 0;
+            }
+            SyncEvent::Failed { error } => {
+                if bar_drawn {
+                    let _ = writeln!(stderr);
+                    bar_drawn = false;
+                }
+                let _ = writeln!(stderr, "Sync failed: {}", error);
+                conflicts = 0;
+                deferred = 0;
+            }
+            SyncEvent::Started { .. } | SyncEvent::ConflictResolved { .. } => {}
+        }
+        let _ = stderr.flush();
+    }
+}
+
+/// What to print when a phase starts; None for phases shown another way
+fn phase_name(state: &SyncState) -> Option<&'static str> {
+    match state {
+        SyncState::Connecting => Some("Connecting"),
+        SyncState::Discovering => Some("Discovering changes"),
+        SyncState::ResolvingConflicts => Some("Resolving conflicts"),
+        SyncState::Committing => Some("Committing"),
+        _ => None,
+    }
+}
+
+/// A bar like `[#######.......]  50%`
+fn progress_bar(completed: usize, total: usize) -> String {
+    let fraction = if total == 0 { 1.0 } else { completed as f64 / total as f64 };
+    let filled = ((fraction * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        ".".repeat(BAR_WIDTH - filled),
+        (fraction * 100.0) as usize
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0, 10), format!("[{}]   0%", ".".repeat(BAR_WIDTH)));
+        assert_eq!(
+            progress_bar(5, 10),
+            format!("[{}{}]  50%", "#".repeat(BAR_WIDTH / 2), ".".repeat(BAR_WIDTH / 2))
+        );
+        assert_eq!(progress_bar(0, 0), format!("[{}] 100%", "#".repeat(BAR_WIDTH)));
+    }
+}
@@ -1,8 +1,9 @@
 /// Table formatting for query results using comfy-table
 
 use comfy_table::{presets::UTF8_FULL, Cell, ContentArrangement, Table};
-use kstone_api::KeystoneValue;
+use kstone_api::{IndexProjection, KeystoneValue, SlowOperation, StripeStats, TableSchema};
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 /// Format a list of items as a table
 ///
@@ -56,6 +57,186 @@ pub fn format_items_table(items: &[HashMap<String, KeystoneValue>]) -> String {
     table.to_string()
 }
 
+/// Format per-stripe statistics as a table, one row per stripe
+pub fn format_stripe_stats_table(stripes: &[&StripeStats]) -> String {
+    if stripes.is_empty() {
+        return "No stripes hold data".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Stripe", "Memtable records", "Memtable bytes", "SSTs", "SST bytes", "Est. keys", "Bloom FP rate",
+    ]);
+
+    for stripe in stripes {
+        table.add_row(vec![
+            Cell::new(stripe.stripe),
+            Cell::new(stripe.memtable_records),
+            Cell::new(stripe.memtable_size_bytes),
+            Cell::new(stripe.sst_count()),
+            Cell::new(stripe.sst_bytes()),
+            Cell::new(stripe.estimated_keys),
+            Cell::new(format_rate(stripe.bloom().false_positive_rate())),
+        ]);
+    }
+
+    table.to_string()
+}
+
+/// Format per-SST statistics as a table, one row per SST, newest first within a stripe
+pub fn format_sst_stats_table(stripes: &[&StripeStats]) -> String {
+    if stripes.iter().all(|stripe| stripe.ssts.is_empty()) {
+        return "No SST files".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Stripe", "File", "Bytes", "Records", "Age", "Cold", "Bloom checks", "Bloom FP rate",
+    ]);
+
+    for stripe in stripes {
+        for sst in &stripe.ssts {
+            table.add_row(vec![
+                Cell::new(stripe.stripe),
+                Cell::new(&sst.file_name),
+                Cell::new(sst.size_bytes),
+                Cell::new(sst.records),
+                Cell::new(format_age(sst.age)),
+                Cell::new(if sst.cold { "yes" } else { "no" }),
+                Cell::new(sst.bloom.checks),
+                Cell::new(format_rate(sst.bloom.false_positive_rate())),
+            ]);
+        }
+    }
+
+    table.to_string()
+}
+
+/// Format logged slow operations as a table, one row per operation
+pub fn format_slow_operations_table(operations: &[SlowOperation]) -> String {
+    if operations.is_empty() {
+        return "No slow operations".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Operation", "Key", "Scanned", "Returned", "Duration"]);
+
+    for op in operations {
+        let key = match &op.key {
+            Some(key) => match &key.sk {
+                Some(sk) => format!("{} / {}", String::from_utf8_lossy(&key.pk), String::from_utf8_lossy(sk)),
+                None => String::from_utf8_lossy(&key.pk).to_string(),
+            },
+            None => "-".to_string(),
+        };
+        table.add_row(vec![
+            Cell::new(op.kind),
+            Cell::new(key),
+            Cell::new(op.scanned_count),
+            Cell::new(op.returned_count),
+            Cell::new(format!("{:.2}ms", op.duration.as_secs_f64() * 1000.0)),
+        ]);
+    }
+
+    table.to_string()
+}
+
+/// Format a schema's indexes as a table, one row per index
+pub fn format_indexes_table(schema: &TableSchema) -> String {
+    let mut rows = Vec::new();
+    for lsi in &schema.local_indexes {
+        rows.push(["LSI".to_string(), lsi.name.clone(), format!("sk: {}", lsi.sort_key_attribute), format_projection(&lsi.projection)]);
+    }
+    for gsi in &schema.global_indexes {
+        let keys = match &gsi.sort_key_attribute {
+            Some(sk) => format!("pk: {}, sk: {}", gsi.partition_key_attribute, sk),
+            None => format!("pk: {}", gsi.partition_key_attribute),
+        };
+        rows.push(["GSI".to_string(), gsi.name.clone(), keys, format_projection(&gsi.projection)]);
+    }
+    for vector in &schema.vector_indexes {
+        let keys = format!("{} ({} dims, {:?})", vector.attribute_name, vector.dimensions, vector.metric);
+        rows.push(["Vector".to_string(), vector.attribute_name.clone(), keys, "-".to_string()]);
+    }
+    for geo in &schema.geo_indexes {
+        let keys = format!("lat: {}, lon: {}", geo.latitude_attribute, geo.longitude_attribute);
+        rows.push(["Geo".to_string(), geo.name.clone(), keys, "-".to_string()]);
+    }
+    if rows.is_empty() {
+        return "No indexes".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Type", "Name", "Keys", "Projection", "Status"]);
+
+    for [kind, name, keys, projection] in rows {
+        let status = match schema.index_backfills.iter().find(|backfill| backfill.index_name == name) {
+            Some(backfill) => format!("backfilling {}/{} stripes", backfill.next_stripe, backfill.total_stripes),
+            None => "active".to_string(),
+        };
+        table.add_row(vec![kind, name, keys, projection, status]);
+    }
+
+    table.to_string()
+}
+
+fn format_projection(projection: &IndexProjection) -> String {
+    match projection {
+        IndexProjection::All => "all".to_string(),
+        IndexProjection::KeysOnly => "keys only".to_string(),
+        IndexProjection::Include(attributes) => format!("include {}", attributes.join(", ")),
+    }
+}
+
+/// Format a rate as a percentage, "-" if unknown
+pub fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) => format!("{:.2}%", rate * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+/// Format a byte count in its largest binary unit, e.g. "1.5 MiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Format an age in its largest whole unit, "-" if unknown
+fn format_age(age: Option<Duration>) -> String {
+    let Some(age) = age else {
+        return "-".to_string();
+    };
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
 /// Format a KeystoneValue for display in a table cell
 fn format_value(value: &KeystoneValue) -> String {
     match value {
@@ -169,6 +350,90 @@ mod tests {
         assert_eq!(formatted, "[a, 1]");
     }
 
+    #[test]
+    fn test_format_stats_tables() {
+        use kstone_api::SstStats;
+
+        assert_eq!(format_stripe_stats_table(&[]), "No stripes hold data");
+
+        let stripe = StripeStats {
+            stripe: 7,
+            memtable_records: 3,
+            estimated_keys: 13,
+            ssts: vec![SstStats {
+                file_name: "007-42.sst".to_string(),
+                size_bytes: 4096,
+                records: 10,
+                age: Some(Duration::from_secs(90)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let output = format_stripe_stats_table(&[&stripe]);
+        assert!(output.contains("4096"));
+        assert!(output.contains("13"));
+
+        let output = format_sst_stats_table(&[&stripe]);
+        assert!(output.contains("007-42.sst"));
+        assert!(output.contains("1m"));
+    }
+
+    #[test]
+    fn test_format_slow_operations_table() {
+        use kstone_api::SlowOperationKind;
+        use kstone_core::Key;
+
+        assert_eq!(format_slow_operations_table(&[]), "No slow operations");
+
+        let op = SlowOperation {
+            kind: SlowOperationKind::Query,
+            key: Some(Key::with_sk(b"user#1".to_vec(), b"orders".to_vec())),
+            scanned_count: 120,
+            returned_count: 4,
+            duration: Duration::from_millis(250),
+            timestamp_ms: 0,
+        };
+        let output = format_slow_operations_table(&[op]);
+        assert!(output.contains("query"));
+        assert!(output.contains("user#1 / orders"));
+        assert!(output.contains("250.00ms"));
+    }
+
+    #[test]
+    fn test_format_rate_and_age() {
+        assert_eq!(format_rate(None), "-");
+        assert_eq!(format_rate(Some(0.0125)), "1.25%");
+        assert_eq!(format_age(None), "-");
+        assert_eq!(format_age(Some(Duration::from_secs(5))), "5s");
+        assert_eq!(format_age(Some(Duration::from_secs(7200))), "2h");
+        assert_eq!(format_age(Some(Duration::from_secs(3 * 86400))), "3d");
+    }
+
+    #[test]
+    fn test_format_indexes_table() {
+        use kstone_api::{GlobalSecondaryIndex, IndexBackfill, LocalSecondaryIndex};
+
+        assert_eq!(format_indexes_table(&TableSchema::new()), "No indexes");
+
+        let mut schema = TableSchema::new()
+            .add_local_index(LocalSecondaryIndex::new("by-score", "score"))
+            .add_global_index(GlobalSecondaryIndex::new("by-status", "status"));
+        schema.index_backfills.push(IndexBackfill::new("by-status", 256));
+        let output = format_indexes_table(&schema);
+        assert!(output.contains("by-score"));
+        assert!(output.contains("sk: score"));
+        assert!(output.contains("pk: status"));
+        assert!(output.contains("backfilling 0/256 stripes"));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
     #[test]
     fn test_format_map_value() {
         let mut map = HashMap::new();
@@ -0,0 +1,441 @@
+/// `kstone top`: live dashboard of a database or server
+///
+/// A path is reopened read-only every refresh, like `kstone stats --watch`,
+/// so it can watch a database another process is writing. Only what that
+/// process leaves on disk is visible then: writes are counted by sequence
+/// number, and reads and hot partitions are unknown. A server's metrics URL
+/// (`http://host:9090`) exposes everything the engine counts.
+
+use anyhow::{Context, Result};
+use kstone_api::Database;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Sparkline, Table};
+use ratatui::{backend::CrosstermBackend, Frame, Terminal};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::table::format_bytes;
+
+/// Time between samples
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Samples of ops/sec kept for the sparklines
+const HISTORY_LEN: usize = 120;
+
+/// Longest wait for a server's metrics
+const HTTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What `kstone top` watches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Database directory
+    Local(PathBuf),
+    /// Metrics URL of a kstone-server
+    Server(String),
+}
+
+impl Target {
+    /// `http://` and `https://` URLs are servers; anything else is a path
+    pub fn parse(target: &str) -> Self {
+        if target.starts_with("http://") || target.starts_with("https://") {
+            Target::Server(target.to_string())
+        } else {
+            Target::Local(PathBuf::from(target))
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Target::Local(path) => path.display().to_string(),
+            Target::Server(url) => url.clone(),
+        }
+    }
+}
+
+/// Engine state at one moment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sample {
+    /// Reads so far (None when the source can't see them)
+    pub reads: Option<u64>,
+    /// Writes so far
+    pub writes: u64,
+    pub memtable_fill: f64,
+    pub memtable_bytes: u64,
+    pub pending_flushes: u64,
+    pub active_compactions: u64,
+    pub compactions: u64,
+    pub sst_files: u64,
+    pub stream_records: u64,
+    pub stream_lag: u64,
+    /// Busiest partitions and their estimated recent operations
+    pub hot_partitions: Vec<(String, u64)>,
+}
+
+/// Take a sample of `target`
+pub fn sample(target: &Target) -> Result<Sample> {
+    match target {
+        Target::Local(path) => sample_database(path),
+        Target::Server(url) => Ok(sample_metrics(&fetch_metrics(url)?)),
+    }
+}
+
+fn sample_database(path: &Path) -> Result<Sample> {
+    let db = Database::open_read_only(path).context("Failed to open database")?;
+    let detailed = db.stats_detailed().context("Failed to get statistics")?;
+    let config = db.describe().context("Failed to describe database")?.config;
+    let stats = &detailed.stats;
+
+    Ok(Sample {
+        // A fresh handle has counted nothing; the sequence number counts
+        // every write, whichever process made it
+        reads: None,
+        writes: stats.operations.last_sequence_number,
+        memtable_fill: config.map_or(0.0, |config| detailed.memtable_fill(&config)),
+        memtable_bytes: stats.memtable_size_bytes.unwrap_or(0),
+        pending_flushes: stats.flush.pending_flushes,
+        active_compactions: stats.compaction.active_compactions,
+        compactions: stats.compaction.total_compactions,
+        sst_files: stats.total_sst_files,
+        stream_records: stats.stream.retained_records,
+        stream_lag: stats.stream.max_subscriber_lag,
+        hot_partitions: Vec::new(),
+    })
+}
+
+/// Read a sample from Prometheus text exposition
+pub fn sample_metrics(text: &str) -> Sample {
+    let mut sample = Sample::default();
+    for line in text.lines() {
+        let Some((name, labels, value)) = parse_metric_line(line) else { continue };
+        let count = value.max(0.0) as u64;
+        match name.as_str() {
+            "kstone_engine_reads_total" => sample.reads = Some(count),
+            "kstone_engine_writes_total" => sample.writes = count,
+            "kstone_memtable_fill_ratio" => sample.memtable_fill = value,
+            "kstone_memtable_bytes" => sample.memtable_bytes = count,
+            "kstone_pending_flushes" => sample.pending_flushes = count,
+            "kstone_active_compactions" => sample.active_compactions = count,
+            "kstone_compactions_total" => sample.compactions = count,
+            "kstone_sst_files" => sample.sst_files = count,
+            "kstone_stream_records" => sample.stream_records = count,
+            "kstone_stream_subscriber_lag_records" => sample.stream_lag = count,
+            "kstone_hot_partition_operations" => {
+                if let Some((_, pk)) = labels.into_iter().find(|(label, _)| label == "pk") {
+                    sample.hot_partitions.push((pk, count));
+                }
+            }
+            _ => {}
+        }
+    }
+    sample.hot_partitions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sample
+}
+
+/// Split `name{label="value",...} 1.5` into its parts; None for comments
+fn parse_metric_line(line: &str) -> Option<(String, Vec<(String, String)>, f64)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (series, value) = line.rsplit_once(' ')?;
+    let value = value.parse().ok()?;
+    let Some((name, rest)) = series.split_once('{') else {
+        return Some((series.to_string(), Vec::new(), value));
+    };
+
+    let mut labels = Vec::new();
+    let mut chars = rest.chars();
+    loop {
+        let label: String = chars.by_ref().take_while(|c| *c != '=').collect();
+        let label = label.trim_start_matches(',').trim();
+        if label.is_empty() || label == "}" || chars.next() != Some('"') {
+            break;
+        }
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some(escaped) => value.push(escaped),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        labels.push((label.to_string(), value));
+    }
+    Some((name.to_string(), labels, value))
+}
+
+/// GET a plain-HTTP metrics URL (`/metrics` if it has no path)
+fn fetch_metrics(url: &str) -> Result<String> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        anyhow::bail!("Only http:// metrics URLs are supported");
+    };
+    let (host, path) = match rest.find('/') {
+        Some(i) if i + 1 < rest.len() => (&rest[..i], &rest[i..]),
+        Some(i) => (&rest[..i], "/metrics"),
+        None => (rest, "/metrics"),
+    };
+
+    let addr = host
+        .to_socket_addrs()
+        .with_context(|| format!("Invalid address {}", host))?
+        .next()
+        .with_context(|| format!("No address for {}", host))?;
+    let mut stream = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT)
+        .with_context(|| format!("Failed to connect to {}", host))?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT))?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, host)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).context("Failed to read metrics")?;
+    let (head, body) = response.split_once("\r\n\r\n").context("Malformed HTTP response")?;
+    let status = head.lines().next().unwrap_or_default();
+    if !status.split_whitespace().nth(1).map_or(false, |code| code == "200") {
+        anyhow::bail!("{} returned {}", url, status);
+    }
+    Ok(body.to_string())
+}
+
+/// What the dashboard shows: the latest sample and recent rates
+struct Dashboard {
+    name: String,
+    previous: Option<(Instant, Sample)>,
+    current: Option<Sample>,
+    reads_per_sec: Vec<u64>,
+    writes_per_sec: Vec<u64>,
+    error: Option<String>,
+}
+
+impl Dashboard {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            previous: None,
+            current: None,
+            reads_per_sec: Vec::new(),
+            writes_per_sec: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, sample: Result<Sample>, now: Instant) {
+        let sample = match sample {
+            Ok(sample) => sample,
+            Err(err) => {
+                self.error = Some(format!("{:#}", err));
+                return;
+            }
+        };
+        self.error = None;
+
+        if let Some((at, previous)) = &self.previous {
+            let secs = now.duration_since(*at).as_secs_f64().max(f64::EPSILON);
+            // Counters restart with the server; don't report a negative rate
+            let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / secs).round() as u64;
+            push_bounded(&mut self.writes_per_sec, rate(sample.writes, previous.writes));
+            if let (Some(reads), Some(before)) = (sample.reads, previous.reads) {
+                push_bounded(&mut self.reads_per_sec, rate(reads, before));
+            }
+        }
+        self.previous = Some((now, sample.clone()));
+        self.current = Some(sample);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, rates, engine, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(8),
+            Constraint::Min(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let status = match &self.error {
+            Some(err) => Line::from(format!(" {}  {}", self.name, err)).fg(Color::Red),
+            None => Line::from(format!(" kstone top: {}", self.name)).bold(),
+        };
+        frame.render_widget(status, header);
+        frame.render_widget(Line::from(" q to quit").dim(), footer);
+
+        let Some(sample) = &self.current else {
+            frame.render_widget(Paragraph::new("Waiting for the first sample..."), rates);
+            return;
+        };
+
+        let [reads_area, writes_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(rates);
+        let reads_title = match (sample.reads, self.reads_per_sec.last()) {
+            (None, _) => "Reads/s (not visible from a path)".to_string(),
+            (Some(_), last) => format!("Reads/s: {}", last.copied().unwrap_or(0)),
+        };
+        frame.render_widget(sparkline(reads_title, &self.reads_per_sec, Color::Cyan), reads_area);
+        let writes_title = format!("Writes/s: {}", self.writes_per_sec.last().copied().unwrap_or(0));
+        frame.render_widget(sparkline(writes_title, &self.writes_per_sec, Color::Yellow), writes_area);
+
+        let [left, hot_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(engine);
+        let [fill_area, activity_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(3)]).areas(left);
+
+        let fill = Gauge::default()
+            .block(Block::bordered().title("Memtable fill"))
+            .gauge_style(Style::default().fg(if sample.memtable_fill > 0.9 { Color::Red } else { Color::Green }))
+            .ratio(sample.memtable_fill.clamp(0.0, 1.0))
+            .label(format!(
+                "{:.0}% ({})",
+                sample.memtable_fill * 100.0,
+                format_bytes(sample.memtable_bytes)
+            ));
+        frame.render_widget(fill, fill_area);
+
+        let activity = Paragraph::new(vec![
+            Line::from(format!("SST files:          {}", sample.sst_files)),
+            Line::from(format!("Pending flushes:    {}", sample.pending_flushes)),
+            Line::from(format!(
+                "Compactions:        {} ({} running)",
+                sample.compactions, sample.active_compactions
+            )),
+            Line::from(format!("Stream records:     {}", sample.stream_records)),
+            Line::from(format!("Stream lag:         {}", sample.stream_lag)),
+        ])
+        .block(Block::bordered().title("Flush, compaction and stream"));
+        frame.render_widget(activity, activity_area);
+
+        let block = Block::bordered().title("Hot partitions");
+        if sample.hot_partitions.is_empty() {
+            let hint = match sample.reads {
+                None => "Tracked per process; watch a server's metrics URL to see them",
+                Some(_) => "No traffic yet",
+            };
+            frame.render_widget(Paragraph::new(hint).dim().block(block), hot_area);
+        } else {
+            let rows = sample
+                .hot_partitions
+                .iter()
+                .map(|(pk, operations)| Row::new(vec![pk.clone(), operations.to_string()]));
+            let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(12)])
+                .header(Row::new(vec!["Partition", "Ops (est.)"]).bold())
+                .block(block);
+            frame.render_widget(table, hot_area);
+        }
+    }
+}
+
+fn push_bounded(history: &mut Vec<u64>, value: u64) {
+    if history.len() == HISTORY_LEN {
+        history.remove(0);
+    }
+    history.push(value);
+}
+
+fn sparkline(title: String, history: &[u64], color: Color) -> Sparkline<'_> {
+    Sparkline::default()
+        .block(Block::bordered().title(title))
+        .data(history)
+        .style(Style::default().fg(color))
+}
+
+/// Restores the terminal when the dashboard exits, even on errors
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Run the dashboard until the user quits
+pub fn run(target: &Target) -> Result<()> {
+    if let Target::Local(path) = target {
+        if !path.exists() {
+            anyhow::bail!("Database not found: {}", path.display());
+        }
+    }
+
+    enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+
+    let mut dashboard = Dashboard::new(target.name());
+    let mut next_sample = Instant::now();
+    loop {
+        if Instant::now() >= next_sample {
+            dashboard.update(sample(target), Instant::now());
+            next_sample = Instant::now() + REFRESH_INTERVAL;
+        }
+        terminal.draw(|frame| dashboard.draw(frame))?;
+
+        let wait = next_sample.saturating_duration_since(Instant::now());
+        if event::poll(wait)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sample_metrics() {
+        let text = r#"# HELP kstone_engine_writes_total Puts, deletes and updates run by the engine
+# TYPE kstone_engine_writes_total counter
+kstone_engine_writes_total 42
+kstone_engine_reads_total 7
+kstone_memtable_fill_ratio 0.25
+kstone_hot_partition_operations{pk="user#1"} 16
+kstone_hot_partition_operations{pk="say \"hi\""} 32
+kstone_rpc_requests_total{method="put",status="success"} 42
+"#;
+        let sample = sample_metrics(text);
+        assert_eq!(sample.writes, 42);
+        assert_eq!(sample.reads, Some(7));
+        assert_eq!(sample.memtable_fill, 0.25);
+        assert_eq!(
+            sample.hot_partitions,
+            vec![("say \"hi\"".to_string(), 32), ("user#1".to_string(), 16)]
+        );
+    }
+
+    #[test]
+    fn test_dashboard_rates() {
+        let dir = TempDir::new().unwrap();
+        let target = Target::parse(dir.path().to_str().unwrap());
+        Database::create(dir.path()).unwrap();
+
+        let start = Instant::now();
+        let mut dashboard = Dashboard::new(target.name());
+        dashboard.update(sample(&target), start);
+
+        let db = Database::open(dir.path()).unwrap();
+        for i in 0..10 {
+            db.put(format!("key{}", i).as_bytes(), ItemBuilder::new().number("n", i).build()).unwrap();
+        }
+        drop(db);
+
+        dashboard.update(sample(&target), start + Duration::from_secs(2));
+        assert_eq!(dashboard.writes_per_sec, vec![5]);
+        assert!(dashboard.reads_per_sec.is_empty());
+        assert_eq!(dashboard.current.as_ref().unwrap().reads, None);
+
+        assert_eq!(Target::parse("http://localhost:9090"), Target::Server("http://localhost:9090".to_string()));
+    }
+}
@@ -0,0 +1,133 @@
+/// `kstone watch`: print change stream events
+///
+/// Reads the stream segments directly instead of opening the database, so
+/// it can follow a database another process holds open. Only changes made
+/// while the stream is enabled are recorded.
+
+use anyhow::{Context, Result};
+use kstone_api::{item_to_json, StreamEventType, StreamRecord, StreamTail};
+use serde_json::json;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// How often `--follow` looks for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Which events to print, and how
+pub struct WatchOptions {
+    /// Skip events up to and including this sequence number
+    pub after_seq: Option<u64>,
+    /// Keep polling for new events until interrupted
+    pub follow: bool,
+    /// Print JSON Lines instead of text
+    pub json: bool,
+}
+
+/// Print the stream events of the database at `path`
+pub fn run(path: &Path, options: &WatchOptions) -> Result<()> {
+    if !path.exists() {
+        anyhow::bail!("Database not found: {}", path.display());
+    }
+    if !path.join("streams").exists() && !options.follow {
+        eprintln!("No stream records; streams are enabled with TableSchema::with_stream");
+        return Ok(());
+    }
+
+    let mut tail = StreamTail::new(path, options.after_seq);
+    let stdout = std::io::stdout();
+    loop {
+        let records = tail.poll().context("Failed to read the stream")?;
+        let mut out = stdout.lock();
+        for record in &records {
+            if options.json {
+                writeln!(out, "{}", serde_json::to_string(&record_json(record))?)?;
+            } else {
+                writeln!(out, "{}", format_record(record))?;
+            }
+        }
+        out.flush()?;
+
+        if !options.follow {
+            return Ok(());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn event_name(event_type: &StreamEventType) -> &'static str {
+    match event_type {
+        StreamEventType::Insert => "INSERT",
+        StreamEventType::Modify => "MODIFY",
+        StreamEventType::Remove => "REMOVE",
+    }
+}
+
+fn format_key(record: &StreamRecord) -> String {
+    match &record.key.sk {
+        Some(sk) => format!("{} / {}", String::from_utf8_lossy(&record.key.pk), String::from_utf8_lossy(sk)),
+        None => String::from_utf8_lossy(&record.key.pk).to_string(),
+    }
+}
+
+/// One line per event: sequence number, event, key and images
+fn format_record(record: &StreamRecord) -> String {
+    let mut line = format!("#{} {} {}", record.sequence_number, event_name(&record.event_type), format_key(record));
+    let image = |item| serde_json::to_string(&item_to_json(item)).unwrap_or_default();
+    match (&record.old_image, &record.new_image) {
+        (Some(old), Some(new)) => line.push_str(&format!(" {} -> {}", image(old), image(new))),
+        (None, Some(new)) => line.push_str(&format!(" {}", image(new))),
+        (Some(old), None) => line.push_str(&format!(" (was {})", image(old))),
+        (None, None) => {}
+    }
+    line
+}
+
+/// An event as JSON, for scripts
+fn record_json(record: &StreamRecord) -> serde_json::Value {
+    json!({
+        "sequence_number": record.sequence_number,
+        "event": event_name(&record.event_type),
+        "pk": String::from_utf8_lossy(&record.key.pk),
+        "sk": record.key.sk.as_ref().map(|sk| String::from_utf8_lossy(sk).to_string()),
+        "timestamp": record.timestamp,
+        "old_image": record.old_image.as_ref().map(item_to_json),
+        "new_image": record.new_image.as_ref().map(item_to_json),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::{Database, ItemBuilder, StreamConfig, TableSchema};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watch_formats_events() {
+        let dir = TempDir::new().unwrap();
+        let schema = TableSchema::new().with_stream(StreamConfig::enabled());
+        let db = Database::create_with_schema(dir.path(), schema).unwrap();
+        db.put(b"user#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        db.put(b"user#1", ItemBuilder::new().string("name", "Bob").build()).unwrap();
+        db.delete(b"user#1").unwrap();
+
+        let records = StreamTail::new(dir.path(), None).poll().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            format_record(&records[0]),
+            format!(r#"#{} INSERT user#1 {{"name":"Alice"}}"#, records[0].sequence_number)
+        );
+        assert_eq!(
+            format_record(&records[1]),
+            format!(r#"#{} MODIFY user#1 {{"name":"Alice"}} -> {{"name":"Bob"}}"#, records[1].sequence_number)
+        );
+
+        let json = record_json(&records[2]);
+        assert_eq!(json["event"], "REMOVE");
+        assert_eq!(json["sk"], serde_json::Value::Null);
+        assert_eq!(json["old_image"]["name"], "Bob");
+
+        let after = Some(records[1].sequence_number);
+        assert_eq!(StreamTail::new(dir.path(), after).poll().unwrap().len(), 1);
+    }
+}
@@ -1,11 +1,12 @@
 /// Remote batch operations
+use crate::client::RpcClient;
 use crate::convert::*;
 use crate::error::Result;
 use kstone_core::Item;
-use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
-use tonic::transport::Channel;
+use kstone_proto as proto;
 
 /// Remote batch get request builder
+#[derive(Clone)]
 pub struct RemoteBatchGetRequest {
     keys: Vec<proto::Key>,
 }
@@ -35,7 +36,7 @@ impl RemoteBatchGetRequest {
     }
 
     /// Execute the batch get operation
-    pub async fn execute(self, client: &mut KeystoneDbClient<Channel>) -> Result<RemoteBatchGetResponse> {
+    pub async fn execute(self, client: &mut RpcClient) -> Result<RemoteBatchGetResponse> {
         let request = proto::BatchGetRequest {
             keys: self.keys,
         };
@@ -74,6 +75,7 @@ pub struct RemoteBatchGetResponse {
 }
 
 /// Remote batch write request builder
+#[derive(Clone)]
 pub struct RemoteBatchWriteRequest {
     writes: Vec<proto::WriteRequest>,
 }
@@ -131,7 +133,7 @@ impl RemoteBatchWriteRequest {
     }
 
     /// Execute the batch write operation
-    pub async fn execute(self, client: &mut KeystoneDbClient<Channel>) -> Result<RemoteBatchWriteResponse> {
+    pub async fn execute(self, client: &mut RpcClient) -> Result<RemoteBatchWriteResponse> {
         let request = proto::BatchWriteRequest {
             writes: self.writes,
         };
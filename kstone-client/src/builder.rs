@@ -0,0 +1,134 @@
+/// Client configuration: connection pool, retries and timeouts
+use crate::client::{Client, Credentials, RequestMetadata};
+use crate::error::{ClientError, Result};
+use crate::retry::CallOptions;
+use kstone_core::RetryPolicy;
+use std::time::Duration;
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::Endpoint;
+
+/// Builder for a `Client`
+///
+/// By default a client opens one connection, retries calls failing with
+/// `UNAVAILABLE` or `RESOURCE_EXHAUSTED` with `RetryPolicy::default()` and
+/// jittered backoff, and waits for responses as long as the server takes.
+pub struct ClientBuilder {
+    addr: String,
+    credentials: Credentials,
+    database: Option<String>,
+    pool_size: usize,
+    connect_timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<ClientTlsConfig>,
+    options: CallOptions,
+}
+
+impl ClientBuilder {
+    /// Configure a client of the server at `addr` (e.g., "http://127.0.0.1:50051")
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            credentials: Credentials::none(),
+            database: None,
+            pool_size: 1,
+            connect_timeout: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+            options: CallOptions::default(),
+        }
+    }
+
+    /// Send `credentials` with every request
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Use database `name` of a server started with `--root-dir`
+    pub fn database(mut self, name: impl Into<String>) -> Self {
+        self.database = Some(name.into());
+        self
+    }
+
+    /// Open `size` connections and spread requests over them (at least 1)
+    ///
+    /// Each connection multiplexes any number of concurrent requests, so
+    /// more only help when one connection's throughput is the bottleneck.
+    pub fn pool_size(mut self, size: usize) -> Self {
+        self.pool_size = size.max(1);
+        self
+    }
+
+    /// Retry calls failing with `UNAVAILABLE` or `RESOURCE_EXHAUSTED` per `policy`
+    ///
+    /// Writes are retried too, so a write the server applied just before
+    /// becoming unavailable may be applied twice.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.options.retry = policy;
+        self
+    }
+
+    /// Fail calls at once instead of retrying them
+    pub fn no_retry(self) -> Self {
+        self.retry_policy(RetryPolicy::no_retry())
+    }
+
+    /// Randomize each backoff within its upper half (on by default)
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.options.jitter = enabled;
+        self
+    }
+
+    /// Fail each attempt of a call with `ClientError::Timeout` after `timeout`
+    ///
+    /// Streaming calls are only limited until the stream is open.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Give up connecting after `timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Connect over TLS configured by `config` (`tls` feature)
+    ///
+    /// Use an `https://` address. Without a CA certificate in `config`, the
+    /// system's trusted roots verify the server.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, config: ClientTlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Open the pool's connections
+    pub async fn connect(self) -> Result<Client> {
+        let metadata = RequestMetadata::new(self.credentials, self.database.as_deref())?;
+
+        let mut endpoint = Endpoint::from_shared(self.addr)
+            .map_err(|e| ClientError::ConnectionError(format!("Invalid address: {}", e)))?;
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        #[cfg(feature = "tls")]
+        if let Some(tls) = self.tls {
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|e| ClientError::ConnectionError(format!("Invalid TLS configuration: {}", e)))?;
+        }
+
+        let mut channels = Vec::with_capacity(self.pool_size);
+        for _ in 0..self.pool_size {
+            let channel = endpoint
+                .connect()
+                .await
+                .map_err(|e| ClientError::ConnectionError(format!("Failed to connect: {}", e)))?;
+            channels.push(channel);
+        }
+
+        Ok(Client::from_channels(channels, metadata, self.options))
+    }
+}
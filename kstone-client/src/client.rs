@@ -1,12 +1,95 @@
 /// KeystoneDB gRPC client implementation
+use crate::builder::ClientBuilder;
 use crate::error::{ClientError, Result};
+use crate::retry::{with_retry, CallOptions};
+use std::future::Future;
 use kstone_core::Item;
 use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
 use tonic::transport::Channel;
+use tonic::{Request, Status};
+
+/// gRPC stub that sends the client's credentials and database with every request
+pub type RpcClient = KeystoneDbClient<InterceptedService<Channel, RequestMetadata>>;
+
+/// Metadata key naming the database a request is for
+pub const DATABASE_HEADER: &str = "kstone-database";
+
+/// Credentials attached to every request as `authorization: Bearer <token>`
+///
+/// The token is an API key or a JWT the server was configured to accept.
+#[derive(Clone, Default)]
+pub struct Credentials {
+    authorization: Option<MetadataValue<Ascii>>,
+}
+
+impl Credentials {
+    /// Send no credentials (for servers without authentication)
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Authenticate with an API key or JWT
+    pub fn bearer(token: &str) -> Result<Self> {
+        let authorization = format!("Bearer {}", token)
+            .parse()
+            .map_err(|_| ClientError::InvalidArgument("Token contains invalid characters".to_string()))?;
+        Ok(Self {
+            authorization: Some(authorization),
+        })
+    }
+}
+
+impl Interceptor for Credentials {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        if let Some(authorization) = &self.authorization {
+            request.metadata_mut().insert("authorization", authorization.clone());
+        }
+        Ok(request)
+    }
+}
+
+/// Metadata attached to every request of a `Client`
+#[derive(Clone, Default)]
+pub struct RequestMetadata {
+    credentials: Credentials,
+    database: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for RequestMetadata {
+    fn call(&mut self, request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        let mut request = self.credentials.call(request)?;
+        if let Some(database) = &self.database {
+            request.metadata_mut().insert(DATABASE_HEADER, database.clone());
+        }
+        Ok(request)
+    }
+}
+
+impl RequestMetadata {
+    pub(crate) fn new(credentials: Credentials, database: Option<&str>) -> Result<Self> {
+        let database = database.map(database_value).transpose()?;
+        Ok(Self { credentials, database })
+    }
+}
+
+fn database_value(name: &str) -> Result<MetadataValue<Ascii>> {
+    name.parse()
+        .map_err(|_| ClientError::InvalidArgument("Database name contains invalid characters".to_string()))
+}
 
 /// KeystoneDB remote client
+///
+/// Requests are spread round-robin over the client's connections and
+/// retried as configured with `ClientBuilder`.
 pub struct Client {
-    inner: KeystoneDbClient<Channel>,
+    channels: Vec<Channel>,
+    pool: Vec<RpcClient>,
+    next: usize,
+    metadata: RequestMetadata,
+    options: CallOptions,
 }
 
 impl Client {
@@ -24,15 +107,103 @@ impl Client {
     /// # }
     /// ```
     pub async fn connect(addr: impl Into<String>) -> Result<Self> {
-        let addr = addr.into();
-        let channel = Channel::from_shared(addr)
-            .map_err(|e| ClientError::ConnectionError(format!("Invalid address: {}", e)))?
-            .connect()
-            .await
-            .map_err(|e| ClientError::ConnectionError(format!("Failed to connect: {}", e)))?;
-
-        let inner = KeystoneDbClient::new(channel);
-        Ok(Self { inner })
+        Self::connect_with_credentials(addr, Credentials::none()).await
+    }
+
+    /// Connect to a KeystoneDB server that requires authentication
+    ///
+    /// # Arguments
+    /// * `addr` - Server address (e.g., "http://127.0.0.1:50051")
+    /// * `token` - API key or JWT accepted by the server
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::connect_with_token("http://localhost:50051", "my-api-key").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_with_token(addr: impl Into<String>, token: &str) -> Result<Self> {
+        Self::connect_with_credentials(addr, Credentials::bearer(token)?).await
+    }
+
+    /// Connect to a KeystoneDB server, sending `credentials` with every request
+    pub async fn connect_with_credentials(addr: impl Into<String>, credentials: Credentials) -> Result<Self> {
+        ClientBuilder::new(addr).credentials(credentials).connect().await
+    }
+
+    /// Configure a client's connection pool, retries and timeouts
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::Client;
+    /// # use kstone_core::RetryPolicy;
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::builder("http://localhost:50051")
+    ///     .pool_size(4)
+    ///     .retry_policy(RetryPolicy::fast())
+    ///     .request_timeout(Duration::from_secs(5))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(addr: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(addr)
+    }
+
+    /// Use database `name` of a server started with `--root-dir`
+    ///
+    /// The returned client shares this client's connection and credentials.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::connect("http://localhost:50051").await?;
+    /// let mut orders = client.database("orders")?;
+    /// let mut users = client.database("users")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn database(&self, name: &str) -> Result<Self> {
+        let metadata = RequestMetadata {
+            database: Some(database_value(name)?),
+            ..self.metadata.clone()
+        };
+        Ok(Self::from_channels(self.channels.clone(), metadata, self.options.clone()))
+    }
+
+    pub(crate) fn from_channels(channels: Vec<Channel>, metadata: RequestMetadata, options: CallOptions) -> Self {
+        let pool = channels
+            .iter()
+            .map(|channel| KeystoneDbClient::with_interceptor(channel.clone(), metadata.clone()))
+            .collect();
+        Self {
+            channels,
+            pool,
+            next: 0,
+            metadata,
+            options,
+        }
+    }
+
+    /// Send a call, retrying it on the next connection as configured
+    async fn call<T, F, Fut>(&mut self, mut call: F) -> Result<T>
+    where
+        F: FnMut(RpcClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let pool = &self.pool;
+        let next = &mut self.next;
+        with_retry(&self.options, || {
+            let client = pool[*next % pool.len()].clone();
+            *next = next.wrapping_add(1);
+            call(client)
+        })
+        .await
     }
 
     /// Put an item with a simple partition key
@@ -66,11 +237,11 @@ impl Client {
             expression_values: std::collections::HashMap::new(),
         };
 
-        self.inner
-            .put(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.put(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Put an item with partition key and sort key
@@ -88,11 +259,11 @@ impl Client {
             expression_values: std::collections::HashMap::new(),
         };
 
-        self.inner
-            .put(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.put(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Put an item with a condition expression
@@ -122,11 +293,11 @@ impl Client {
             expression_values: proto_values,
         };
 
-        self.inner
-            .put(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.put(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Get an item with a simple partition key
@@ -143,10 +314,11 @@ impl Client {
         };
 
         let response = self
-            .inner
-            .get(request)
-            .await
-            .map_err(|e| ClientError::from(e))?
+            .call(|mut client| {
+                let request = request.clone();
+                async move { client.get(request).await.map_err(ClientError::from) }
+            })
+            .await?
             .into_inner();
 
         Ok(response.item.map(|proto_item| {
@@ -170,10 +342,11 @@ impl Client {
         };
 
         let response = self
-            .inner
-            .get(request)
-            .await
-            .map_err(|e| ClientError::from(e))?
+            .call(|mut client| {
+                let request = request.clone();
+                async move { client.get(request).await.map_err(ClientError::from) }
+            })
+            .await?
             .into_inner();
 
         Ok(response.item.map(|proto_item| {
@@ -194,11 +367,11 @@ impl Client {
             expression_values: std::collections::HashMap::new(),
         };
 
-        self.inner
-            .delete(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.delete(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Delete an item with partition key and sort key
@@ -214,11 +387,11 @@ impl Client {
             expression_values: std::collections::HashMap::new(),
         };
 
-        self.inner
-            .delete(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.delete(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Delete an item with a condition expression
@@ -245,11 +418,11 @@ impl Client {
             expression_values: proto_values,
         };
 
-        self.inner
-            .delete(request)
-            .await
-            .map_err(|e| e.into())
-            .map(|_| ())
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { client.delete(request).await.map(|_| ()).map_err(ClientError::from) }
+        })
+        .await
     }
 
     /// Execute a query operation
@@ -273,7 +446,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn query(&mut self, query: crate::query::RemoteQuery) -> Result<crate::query::RemoteQueryResponse> {
-        query.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let query = query.clone();
+            async move { query.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a scan operation
@@ -296,7 +473,102 @@ impl Client {
     /// # }
     /// ```
     pub async fn scan(&mut self, scan: crate::scan::RemoteScan) -> Result<crate::scan::RemoteScanResponse> {
-        scan.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let scan = scan.clone();
+            async move { scan.execute(&mut client).await }
+        })
+        .await
+    }
+
+    /// Stream every item matching a query
+    ///
+    /// The server pushes pages of `page_size` items as it reads them and
+    /// `limit` caps the total. The stream doesn't borrow the client, and
+    /// the server only reads ahead as far as the stream is consumed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::{Client, RemoteQuery};
+    /// # use futures::StreamExt;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = Client::connect("http://localhost:50051").await?;
+    ///
+    /// let query = RemoteQuery::new(b"user#org1").page_size(500);
+    /// let mut items = client.query_stream(query).await?;
+    /// while let Some(item) = items.next().await {
+    ///     println!("{:?}", item?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_stream(&mut self, query: crate::query::RemoteQuery) -> Result<crate::stream::ItemStream> {
+        self.call(|mut client| {
+            let query = query.clone();
+            async move { query.execute_stream(&mut client).await }
+        })
+        .await
+    }
+
+    /// Stream every item in the database
+    ///
+    /// Like `query_stream`, pages of `page_size` items are pushed as the
+    /// server reads them and `limit` caps the total.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::{Client, RemoteScan};
+    /// # use futures::StreamExt;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = Client::connect("http://localhost:50051").await?;
+    ///
+    /// let mut items = client.scan_stream(RemoteScan::new()).await?;
+    /// let mut count = 0;
+    /// while let Some(item) = items.next().await {
+    ///     item?;
+    ///     count += 1;
+    /// }
+    /// println!("Scanned {} items", count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn scan_stream(&mut self, scan: crate::scan::RemoteScan) -> Result<crate::stream::ItemStream> {
+        self.call(|mut client| {
+            let scan = scan.clone();
+            async move { scan.execute_stream(&mut client).await }
+        })
+        .await
+    }
+
+    /// Subscribe to the database's change stream
+    ///
+    /// Records are pushed as writes commit. The table must have streams
+    /// enabled. Keep the sequence number of the last record handled to
+    /// resume from it with `RemoteSubscription::after_sequence`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kstone_client::{Client, RemoteSubscription};
+    /// # use futures::StreamExt;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = Client::connect("http://localhost:50051").await?;
+    ///
+    /// let mut records = client.subscribe_stream(RemoteSubscription::new().after_sequence(42)).await?;
+    /// while let Some(record) = records.next().await {
+    ///     let record = record?;
+    ///     println!("{} {:?}", record.sequence_number, record.event_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_stream(
+        &mut self,
+        subscription: crate::subscription::RemoteSubscription,
+    ) -> Result<crate::subscription::RecordStream> {
+        self.call(|mut client| {
+            let subscription = subscription.clone();
+            async move { subscription.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a batch get operation
@@ -320,7 +592,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn batch_get(&mut self, request: crate::batch::RemoteBatchGetRequest) -> Result<crate::batch::RemoteBatchGetResponse> {
-        request.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { request.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a batch write operation
@@ -349,7 +625,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn batch_write(&mut self, request: crate::batch::RemoteBatchWriteRequest) -> Result<crate::batch::RemoteBatchWriteResponse> {
-        request.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { request.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a transactional get operation
@@ -373,7 +653,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn transact_get(&mut self, request: crate::transaction::RemoteTransactGetRequest) -> Result<crate::transaction::RemoteTransactGetResponse> {
-        request.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { request.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a transactional write operation
@@ -400,7 +684,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn transact_write(&mut self, request: crate::transaction::RemoteTransactWriteRequest) -> Result<()> {
-        request.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { request.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Update an item using update expression
@@ -425,7 +713,11 @@ impl Client {
     /// # }
     /// ```
     pub async fn update(&mut self, request: crate::update::RemoteUpdate) -> Result<crate::update::RemoteUpdateResponse> {
-        request.execute(&mut self.inner).await
+        self.call(|mut client| {
+            let request = request.clone();
+            async move { request.execute(&mut client).await }
+        })
+        .await
     }
 
     /// Execute a PartiQL statement
@@ -451,16 +743,14 @@ impl Client {
         let statement = statement.into();
         let request = kstone_proto::ExecuteStatementRequest { statement };
 
-        let response = self.inner
-            .execute_statement(request)
+        let response = self
+            .call(|mut client| {
+                let request = request.clone();
+                async move { client.execute_statement(request).await.map_err(ClientError::from) }
+            })
             .await?
             .into_inner();
 
         crate::partiql::parse_execute_statement_response(response)
     }
-
-    /// Get a reference to the underlying gRPC client
-    pub(crate) fn inner_mut(&mut self) -> &mut KeystoneDbClient<Channel> {
-        &mut self.inner
-    }
 }
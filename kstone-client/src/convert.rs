@@ -7,6 +7,7 @@
 /// trait implementations.
 
 use bytes::Bytes;
+use kstone_core::stream::{StreamEventType, StreamRecord};
 use kstone_core::{Key, Value as KsValue};
 use kstone_proto::{self as proto, value::Value as ProtoValueEnum};
 use std::collections::HashMap;
@@ -166,6 +167,31 @@ pub fn ks_last_key_opt_to_proto(last_key: Option<(Bytes, Option<Bytes>)>) -> Opt
     last_key.map(|(pk, sk)| ks_last_key_to_proto(pk, sk))
 }
 
+// ============================================================================
+// Stream Record Conversions
+// ============================================================================
+
+/// Convert protobuf StreamRecord to kstone_core StreamRecord
+pub fn proto_stream_record_to_ks(record: proto::StreamRecord) -> Result<StreamRecord, Status> {
+    let event_type = match record.event_type() {
+        proto::StreamEventType::Insert => StreamEventType::Insert,
+        proto::StreamEventType::Modify => StreamEventType::Modify,
+        proto::StreamEventType::Remove => StreamEventType::Remove,
+    };
+    let key = record
+        .key
+        .ok_or_else(|| Status::invalid_argument("Stream record key is missing"))?;
+
+    Ok(StreamRecord {
+        sequence_number: record.sequence_number,
+        event_type,
+        key: proto_key_to_core_key(key),
+        old_image: record.old_image.map(proto_item_to_ks).transpose()?,
+        new_image: record.new_image.map(proto_item_to_ks).transpose()?,
+        timestamp: record.timestamp,
+    })
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -266,6 +292,29 @@ mod tests {
         assert_eq!(item, converted);
     }
 
+    #[test]
+    fn test_stream_record_from_proto() {
+        let mut item = HashMap::new();
+        item.insert("name".to_string(), KsValue::S("Alice".to_string()));
+
+        let proto_record = proto::StreamRecord {
+            sequence_number: 7,
+            event_type: proto::StreamEventType::Modify as i32,
+            key: Some(ks_key_to_proto(b"user#1".to_vec(), Some(b"profile".to_vec()))),
+            old_image: None,
+            new_image: Some(ks_item_to_proto(&item)),
+            timestamp: 1_700_000_000_000,
+        };
+
+        let record = proto_stream_record_to_ks(proto_record).unwrap();
+        assert_eq!(record.sequence_number, 7);
+        assert_eq!(record.event_type, StreamEventType::Modify);
+        assert_eq!(record.key, Key::with_sk(Bytes::from("user#1"), Bytes::from("profile")));
+        assert_eq!(record.old_image, None);
+        assert_eq!(record.new_image, Some(item));
+        assert_eq!(record.timestamp, 1_700_000_000_000);
+    }
+
     #[test]
     fn test_key_with_sort_key() {
         let proto_key = ks_key_to_proto(b"pk123".to_vec(), Some(b"sk456".to_vec()));
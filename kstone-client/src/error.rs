@@ -43,6 +43,9 @@ pub enum ClientError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Unauthenticated: {0}")]
+    Unauthenticated(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -67,6 +70,7 @@ impl From<Status> for ClientError {
             tonic::Code::ResourceExhausted => ClientError::ResourceExhausted(msg),
             tonic::Code::Unimplemented => ClientError::Unimplemented(msg),
             tonic::Code::PermissionDenied => ClientError::PermissionDenied(msg),
+            tonic::Code::Unauthenticated => ClientError::Unauthenticated(msg),
             _ => ClientError::Unknown(msg),
         }
     }
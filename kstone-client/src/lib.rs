@@ -3,6 +3,7 @@
 /// This crate provides a Rust client for connecting to KeystoneDB gRPC servers.
 
 pub mod error;
+pub mod builder;
 pub mod client;
 pub mod convert;
 pub mod query;
@@ -11,14 +12,24 @@ pub mod batch;
 pub mod transaction;
 pub mod update;
 pub mod partiql;
+pub mod stream;
+pub mod subscription;
+pub mod store;
+mod retry;
 
 // Re-export key types
-pub use client::Client;
+pub use builder::ClientBuilder;
+pub use client::{Client, Credentials};
 pub use error::{ClientError, Result};
-pub use kstone_core::{Item, Value};
+pub use kstone_core::{Item, RetryPolicy, Value};
+pub use kstone_core::store::{KeystoneStore, SkCondition, StatementResult, StorePage, StoreQuery, StoreScan, StoreUpdate, StoreWrite};
 pub use query::{RemoteQuery, RemoteQueryResponse};
 pub use scan::{RemoteScan, RemoteScanResponse};
+pub use stream::ItemStream;
+pub use subscription::{RecordStream, RemoteSubscription};
 pub use batch::{RemoteBatchGetRequest, RemoteBatchGetResponse, RemoteBatchWriteRequest, RemoteBatchWriteResponse};
 pub use transaction::{RemoteTransactGetRequest, RemoteTransactGetResponse, RemoteTransactWriteRequest};
 pub use update::{RemoteUpdate, RemoteUpdateResponse};
 pub use partiql::RemoteExecuteStatementResponse;
+#[cfg(feature = "tls")]
+pub use tonic::transport::{Certificate, ClientTlsConfig};
@@ -1,12 +1,14 @@
 /// Remote query builder and response types
+use crate::client::RpcClient;
 use crate::convert::*;
 use crate::error::Result;
+use crate::stream::{item_stream, ItemStream};
 use bytes::Bytes;
 use kstone_core::Item;
-use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
-use tonic::transport::Channel;
+use kstone_proto as proto;
 
 /// Remote query builder
+#[derive(Clone)]
 pub struct RemoteQuery {
     partition_key: Vec<u8>,
     sort_key_condition: Option<proto::SortKeyCondition>,
@@ -14,6 +16,7 @@ pub struct RemoteQuery {
     exclusive_start_key: Option<proto::LastKey>,
     scan_forward: Option<bool>,
     index_name: Option<String>,
+    page_size: Option<u32>,
 }
 
 impl RemoteQuery {
@@ -26,6 +29,7 @@ impl RemoteQuery {
             exclusive_start_key: None,
             scan_forward: None,
             index_name: None,
+            page_size: None,
         }
     }
 
@@ -114,6 +118,14 @@ impl RemoteQuery {
         self
     }
 
+    /// Set how many items each page holds when streaming with `execute_stream`
+    ///
+    /// With a stream, `limit` caps the total number of items instead.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1) as u32);
+        self
+    }
+
     /// Set the exclusive start key for pagination
     pub fn start_after(mut self, pk: &[u8], sk: Option<&[u8]>) -> Self {
         self.exclusive_start_key = Some(proto::LastKey {
@@ -129,12 +141,8 @@ impl RemoteQuery {
         self
     }
 
-    /// Execute the query
-    pub async fn execute(
-        self,
-        client: &mut KeystoneDbClient<Channel>,
-    ) -> Result<RemoteQueryResponse> {
-        let request = proto::QueryRequest {
+    fn into_request(self) -> proto::QueryRequest {
+        proto::QueryRequest {
             partition_key: self.partition_key,
             sort_key_condition: self.sort_key_condition,
             filter_expression: None,
@@ -143,10 +151,17 @@ impl RemoteQuery {
             limit: self.limit,
             exclusive_start_key: self.exclusive_start_key,
             scan_forward: self.scan_forward,
-        };
+            page_size: self.page_size,
+        }
+    }
 
+    /// Execute the query
+    pub async fn execute(
+        self,
+        client: &mut RpcClient,
+    ) -> Result<RemoteQueryResponse> {
         let response = client
-            .query(request)
+            .query(self.into_request())
             .await?
             .into_inner();
 
@@ -169,6 +184,12 @@ impl RemoteQuery {
             last_key,
         })
     }
+
+    /// Execute the query, streaming every matching item page by page
+    pub async fn execute_stream(self, client: &mut RpcClient) -> Result<ItemStream> {
+        let responses = client.query_stream(self.into_request()).await?.into_inner();
+        Ok(item_stream(responses, |page: proto::QueryResponse| page.items))
+    }
 }
 
 /// Query response
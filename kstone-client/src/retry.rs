@@ -0,0 +1,162 @@
+/// Retrying calls with exponential backoff
+///
+/// Calls failing with `UNAVAILABLE` or `RESOURCE_EXHAUSTED` are retried
+/// according to a `kstone_core::RetryPolicy`, waiting the policy's backoff
+/// between attempts. With jitter, each wait is drawn from the upper half of
+/// the backoff so clients that failed together don't retry in lockstep.
+use crate::error::{ClientError, Result};
+use kstone_core::RetryPolicy;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How a client retries and times out its calls
+#[derive(Debug, Clone)]
+pub(crate) struct CallOptions {
+    pub(crate) retry: RetryPolicy,
+    pub(crate) jitter: bool,
+    /// Limit on each attempt, `None` to wait as long as the server takes
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl Default for CallOptions {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            jitter: true,
+            timeout: None,
+        }
+    }
+}
+
+/// Whether a failed call may succeed if sent again
+///
+/// The server was down or overloaded, so it most likely didn't act on the
+/// request; writes are retried too.
+pub(crate) fn is_retryable(error: &ClientError) -> bool {
+    matches!(error, ClientError::Unavailable(_) | ClientError::ResourceExhausted(_))
+}
+
+/// Wait before retry `attempt` (0-indexed)
+pub(crate) fn backoff(policy: &RetryPolicy, attempt: u32, jitter: bool) -> Duration {
+    let backoff = policy.backoff_duration(attempt);
+    if !jitter {
+        return backoff;
+    }
+    backoff / 2 + backoff.mul_f64(random_fraction() / 2.0)
+}
+
+/// Run `call` until it succeeds, fails for good or the policy gives up
+///
+/// Each attempt that outlives `options.timeout` fails with
+/// `ClientError::Timeout`, which isn't retried.
+pub(crate) async fn with_retry<T, F, Fut>(options: &CallOptions, mut call: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = match options.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, call()).await {
+                Ok(result) => result,
+                Err(_) => Err(ClientError::Timeout(format!("No response within {:?}", timeout))),
+            },
+            None => call().await,
+        };
+
+        match result {
+            Err(e) if is_retryable(&e) && attempt < options.retry.max_attempts => {
+                tokio::time::sleep(backoff(&options.retry, attempt, options.jitter)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A number in [0, 1), from the randomly seeded hasher of the standard library
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_backoff_jitter_stays_in_upper_half() {
+        let policy = RetryPolicy::new(5, 100, 1000, 2.0);
+        assert_eq!(backoff(&policy, 1, false), Duration::from_millis(200));
+
+        for _ in 0..100 {
+            let wait = backoff(&policy, 1, true);
+            assert!(wait >= Duration::from_millis(100) && wait <= Duration::from_millis(200));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_retries_unavailable() {
+        let options = CallOptions {
+            retry: RetryPolicy::new(3, 1, 5, 2.0),
+            ..CallOptions::default()
+        };
+        let calls = AtomicU32::new(0);
+
+        let result = with_retry(&options, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(ClientError::Unavailable("down".to_string())),
+                1 => Err(ClientError::ResourceExhausted("busy".to_string())),
+                _ => Ok(42),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_gives_up() {
+        let options = CallOptions {
+            retry: RetryPolicy::new(2, 1, 5, 2.0),
+            ..CallOptions::default()
+        };
+        let calls = AtomicU32::new(0);
+
+        let result: Result<()> = with_retry(&options, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ClientError::Unavailable("down".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(ClientError::Unavailable(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Other errors fail at once
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = with_retry(&options, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ClientError::NotFound("gone".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(ClientError::NotFound(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_times_out_attempts() {
+        let options = CallOptions {
+            timeout: Some(Duration::from_millis(20)),
+            ..CallOptions::default()
+        };
+
+        let result: Result<()> = with_retry(&options, || async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        assert!(matches!(result, Err(ClientError::Timeout(_))));
+    }
+}
@@ -1,19 +1,22 @@
 /// Remote scan builder and response types
+use crate::client::RpcClient;
 use crate::convert::*;
 use crate::error::Result;
+use crate::stream::{item_stream, ItemStream};
 use bytes::Bytes;
 use kstone_core::Item;
-use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
-use tonic::transport::Channel;
+use kstone_proto as proto;
 use tonic::Streaming;
 
 /// Remote scan builder
+#[derive(Clone)]
 pub struct RemoteScan {
     limit: Option<u32>,
     exclusive_start_key: Option<proto::LastKey>,
     index_name: Option<String>,
     segment: Option<u32>,
     total_segments: Option<u32>,
+    page_size: Option<u32>,
 }
 
 impl RemoteScan {
@@ -25,6 +28,7 @@ impl RemoteScan {
             index_name: None,
             segment: None,
             total_segments: None,
+            page_size: None,
         }
     }
 
@@ -34,6 +38,14 @@ impl RemoteScan {
         self
     }
 
+    /// Set how many items each page holds when streaming with `execute_stream`
+    ///
+    /// With a stream, `limit` caps the total number of items instead.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1) as u32);
+        self
+    }
+
     /// Set the exclusive start key for pagination
     pub fn start_after(mut self, pk: &[u8], sk: Option<&[u8]>) -> Self {
         self.exclusive_start_key = Some(proto::LastKey {
@@ -56,15 +68,8 @@ impl RemoteScan {
         self
     }
 
-    /// Execute the scan and get a stream of responses
-    ///
-    /// Note: The server currently returns a single response, but this
-    /// interface is prepared for future streaming support.
-    pub async fn execute(
-        self,
-        client: &mut KeystoneDbClient<Channel>,
-    ) -> Result<RemoteScanResponse> {
-        let request = proto::ScanRequest {
+    fn into_request(self) -> proto::ScanRequest {
+        proto::ScanRequest {
             filter_expression: None,
             expression_values: std::collections::HashMap::new(),
             limit: self.limit,
@@ -72,10 +77,19 @@ impl RemoteScan {
             index_name: self.index_name,
             segment: self.segment,
             total_segments: self.total_segments,
-        };
+            page_size: self.page_size,
+        }
+    }
 
+    /// Execute the scan and collect its single page
+    ///
+    /// Use `execute_stream` to read every item without paginating by hand.
+    pub async fn execute(
+        self,
+        client: &mut RpcClient,
+    ) -> Result<RemoteScanResponse> {
         let mut stream: Streaming<proto::ScanResponse> = client
-            .scan(request)
+            .scan(self.into_request())
             .await?
             .into_inner();
 
@@ -110,6 +124,12 @@ impl RemoteScan {
             last_key,
         })
     }
+
+    /// Execute the scan, streaming every item page by page
+    pub async fn execute_stream(self, client: &mut RpcClient) -> Result<ItemStream> {
+        let responses = client.scan_stream(self.into_request()).await?.into_inner();
+        Ok(item_stream(responses, |page: proto::ScanResponse| page.items))
+    }
 }
 
 impl Default for RemoteScan {
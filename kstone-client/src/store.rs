@@ -0,0 +1,162 @@
+/// `KeystoneStore` for the remote client
+use crate::client::Client;
+use crate::convert::ks_item_to_proto;
+use crate::error::{ClientError, Result};
+use crate::partiql::RemoteExecuteStatementResponse;
+use crate::query::RemoteQuery;
+use crate::scan::RemoteScan;
+use crate::transaction::{RemoteTransactGetRequest, RemoteTransactWriteRequest};
+use crate::update::RemoteUpdate;
+use async_trait::async_trait;
+use kstone_core::{
+    store::last_key_from_parts, Item, Key, KeystoneStore, SkCondition, StatementResult, StorePage,
+    StoreQuery, StoreScan, StoreUpdate, StoreWrite,
+};
+use kstone_proto as proto;
+use proto::transact_write_item::Item as Write;
+
+#[async_trait]
+impl KeystoneStore for Client {
+    type Error = ClientError;
+
+    async fn put(&mut self, key: Key, item: Item) -> Result<()> {
+        match &key.sk {
+            Some(sk) => Client::put_with_sk(self, &key.pk, sk, item).await,
+            None => Client::put(self, &key.pk, item).await,
+        }
+    }
+
+    async fn get(&mut self, key: Key) -> Result<Option<Item>> {
+        match &key.sk {
+            Some(sk) => Client::get_with_sk(self, &key.pk, sk).await,
+            None => Client::get(self, &key.pk).await,
+        }
+    }
+
+    async fn delete(&mut self, key: Key) -> Result<()> {
+        match &key.sk {
+            Some(sk) => Client::delete_with_sk(self, &key.pk, sk).await,
+            None => Client::delete(self, &key.pk).await,
+        }
+    }
+
+    async fn query(&mut self, query: StoreQuery) -> Result<StorePage> {
+        let mut request = RemoteQuery::new(&query.pk).forward(query.forward);
+        request = match &query.sk_condition {
+            Some(SkCondition::Equal(sk)) => request.sk_eq(sk),
+            Some(SkCondition::LessThan(sk)) => request.sk_lt(sk),
+            Some(SkCondition::LessThanOrEqual(sk)) => request.sk_lte(sk),
+            Some(SkCondition::GreaterThan(sk)) => request.sk_gt(sk),
+            Some(SkCondition::GreaterThanOrEqual(sk)) => request.sk_gte(sk),
+            Some(SkCondition::Between(low, high)) => request.sk_between(low, high),
+            Some(SkCondition::BeginsWith(prefix)) => request.sk_begins_with(prefix),
+            None => request,
+        };
+        if let Some(index) = query.index {
+            request = request.index(index);
+        }
+        if let Some(limit) = query.limit {
+            request = request.limit(limit);
+        }
+        if let Some(key) = &query.start_after {
+            request = request.start_after(&key.pk, key.sk.as_deref());
+        }
+
+        let response = Client::query(self, request).await?;
+        Ok(StorePage {
+            items: response.items,
+            scanned_count: response.scanned_count,
+            last_key: last_key_from_parts(response.last_key),
+        })
+    }
+
+    async fn scan(&mut self, scan: StoreScan) -> Result<StorePage> {
+        let mut request = RemoteScan::new();
+        if let Some(limit) = scan.limit {
+            request = request.limit(limit);
+        }
+        if let Some(key) = &scan.start_after {
+            request = request.start_after(&key.pk, key.sk.as_deref());
+        }
+        if let Some((segment, total_segments)) = scan.segment {
+            request = request.segment(segment, total_segments);
+        }
+
+        let response = Client::scan(self, request).await?;
+        Ok(StorePage {
+            items: response.items,
+            scanned_count: response.scanned_count,
+            last_key: last_key_from_parts(response.last_key),
+        })
+    }
+
+    async fn update(&mut self, update: StoreUpdate) -> Result<Item> {
+        let mut request = match &update.key.sk {
+            Some(sk) => RemoteUpdate::with_sk(&update.key.pk, sk),
+            None => RemoteUpdate::new(&update.key.pk),
+        }
+        .expression(update.expression);
+        if let Some(condition) = update.condition {
+            request = request.condition(condition);
+        }
+        for (placeholder, value) in update.values {
+            request = request.value(placeholder, value);
+        }
+
+        Ok(Client::update(self, request).await?.item)
+    }
+
+    async fn transact_get(&mut self, keys: Vec<Key>) -> Result<Vec<Option<Item>>> {
+        let request = keys.iter().fold(RemoteTransactGetRequest::new(), |request, key| match &key.sk {
+            Some(sk) => request.get_with_sk(&key.pk, sk),
+            None => request.get(&key.pk),
+        });
+        Ok(Client::transact_get(self, request).await?.items)
+    }
+
+    async fn transact_write(&mut self, writes: Vec<StoreWrite>) -> Result<()> {
+        // The request builder has no conditions, so build the messages here
+        let request = writes.into_iter().fold(RemoteTransactWriteRequest::new(), |request, write| {
+            request.push(match write {
+                StoreWrite::Put { key, item, condition } => Write::Put(proto::TransactPut {
+                    partition_key: key.pk.to_vec(),
+                    sort_key: key.sk.map(|sk| sk.to_vec()),
+                    item: Some(ks_item_to_proto(&item)),
+                    condition_expression: condition,
+                }),
+                StoreWrite::Update { key, expression, condition } => Write::Update(proto::TransactUpdate {
+                    partition_key: key.pk.to_vec(),
+                    sort_key: key.sk.map(|sk| sk.to_vec()),
+                    update_expression: expression,
+                    condition_expression: condition,
+                }),
+                StoreWrite::Delete { key, condition } => Write::Delete(proto::TransactDelete {
+                    partition_key: key.pk.to_vec(),
+                    sort_key: key.sk.map(|sk| sk.to_vec()),
+                    condition_expression: condition,
+                }),
+                StoreWrite::ConditionCheck { key, condition } => Write::ConditionCheck(proto::ConditionCheck {
+                    partition_key: key.pk.to_vec(),
+                    sort_key: key.sk.map(|sk| sk.to_vec()),
+                    condition_expression: condition,
+                }),
+            })
+        });
+        Client::transact_write(self, request).await
+    }
+
+    async fn execute_statement(&mut self, statement: &str) -> Result<StatementResult> {
+        Ok(match Client::execute_statement(self, statement).await? {
+            RemoteExecuteStatementResponse::Select { items, scanned_count, last_key, .. } => {
+                StatementResult::Select {
+                    items,
+                    scanned_count,
+                    last_key: last_key_from_parts(last_key),
+                }
+            }
+            RemoteExecuteStatementResponse::Insert { success } => StatementResult::Insert { success },
+            RemoteExecuteStatementResponse::Update { item } => StatementResult::Update { item },
+            RemoteExecuteStatementResponse::Delete { success } => StatementResult::Delete { success },
+        })
+    }
+}
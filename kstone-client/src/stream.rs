@@ -0,0 +1,59 @@
+/// Item streams over the server's streaming query and scan RPCs
+///
+/// The server sends a response per page and only runs a couple of pages
+/// ahead of the client. Pages are read off the connection as the stream is
+/// polled, so a slow consumer holds back the server through gRPC flow
+/// control instead of buffering the whole result in memory.
+use crate::convert::proto_item_to_ks;
+use crate::error::{ClientError, Result};
+use futures::Stream;
+use kstone_core::Item;
+use kstone_proto as proto;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use tonic::Streaming;
+
+/// Stream of items returned by `Client::query_stream` and `Client::scan_stream`
+///
+/// Ends after the first error.
+pub type ItemStream = Pin<Box<dyn Stream<Item = Result<Item>> + Send>>;
+
+/// State of an `ItemStream` between polls
+struct Pages<T> {
+    responses: Streaming<T>,
+    buffer: VecDeque<proto::Item>,
+    done: bool,
+}
+
+/// Flatten a stream of page responses into their items
+pub(crate) fn item_stream<T, F>(responses: Streaming<T>, items: F) -> ItemStream
+where
+    T: Send + 'static,
+    F: Fn(T) -> Vec<proto::Item> + Copy + Send + 'static,
+{
+    let pages = Pages {
+        responses,
+        buffer: VecDeque::new(),
+        done: false,
+    };
+
+    Box::pin(futures::stream::unfold(pages, move |mut pages| async move {
+        loop {
+            if let Some(item) = pages.buffer.pop_front() {
+                let item = proto_item_to_ks(item).map_err(ClientError::from);
+                return Some((item, pages));
+            }
+            if pages.done {
+                return None;
+            }
+            match pages.responses.message().await {
+                Ok(Some(page)) => pages.buffer.extend(items(page)),
+                Ok(None) => return None,
+                Err(status) => {
+                    pages.done = true;
+                    return Some((Err(ClientError::from(status)), pages));
+                }
+            }
+        }
+    }))
+}
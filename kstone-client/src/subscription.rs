@@ -0,0 +1,118 @@
+/// Remote stream subscriptions (change data capture)
+///
+/// The server pushes stream records as writes commit and sends heartbeats
+/// while there are none. Heartbeats are not surfaced as items: they only
+/// show that the server is alive, and a subscription that hears nothing for
+/// `MISSED_HEARTBEATS` intervals ends with `ClientError::Timeout`. To
+/// resume after a disconnect, subscribe again with `after_sequence` set to
+/// the last record received.
+use crate::client::RpcClient;
+use crate::convert::proto_stream_record_to_ks;
+use crate::error::{ClientError, Result};
+use futures::Stream;
+use kstone_core::stream::StreamRecord;
+use kstone_proto as proto;
+use std::pin::Pin;
+use std::time::Duration;
+use tonic::Streaming;
+
+/// Heartbeat interval used when a subscription doesn't set one
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Heartbeat intervals without any message before a subscription times out
+pub const MISSED_HEARTBEATS: u32 = 3;
+
+/// Stream of records returned by `Client::subscribe_stream`
+///
+/// Records arrive in sequence number order. The stream ends after the
+/// first error.
+pub type RecordStream = Pin<Box<dyn Stream<Item = Result<StreamRecord>> + Send>>;
+
+/// Remote stream subscription builder
+#[derive(Clone)]
+pub struct RemoteSubscription {
+    after_sequence: Option<u64>,
+    from_trim_horizon: bool,
+    heartbeat_interval: Duration,
+}
+
+impl RemoteSubscription {
+    /// Subscribe to records committed after subscribing
+    pub fn new() -> Self {
+        Self {
+            after_sequence: None,
+            from_trim_horizon: false,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+        }
+    }
+
+    /// Resume after the record with this sequence number
+    pub fn after_sequence(mut self, sequence_number: u64) -> Self {
+        self.after_sequence = Some(sequence_number);
+        self
+    }
+
+    /// Start at the oldest record still retained (ignored with `after_sequence`)
+    pub fn from_trim_horizon(mut self) -> Self {
+        self.from_trim_horizon = true;
+        self
+    }
+
+    /// Set how often the server sends heartbeats while no records arrive
+    ///
+    /// The server sends them at most every 100ms.
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval.max(Duration::from_millis(100));
+        self
+    }
+
+    /// Open the subscription
+    pub async fn execute(self, client: &mut RpcClient) -> Result<RecordStream> {
+        let request = proto::SubscribeStreamRequest {
+            after_sequence: self.after_sequence,
+            from_trim_horizon: self.from_trim_horizon,
+            heartbeat_interval_ms: Some(self.heartbeat_interval.as_millis().min(u32::MAX as u128) as u32),
+        };
+
+        let responses = client.subscribe_stream(request).await?.into_inner();
+        Ok(record_stream(responses, self.heartbeat_interval * MISSED_HEARTBEATS))
+    }
+}
+
+impl Default for RemoteSubscription {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turn subscription responses into records, dropping heartbeats
+fn record_stream(responses: Streaming<proto::SubscribeStreamResponse>, timeout: Duration) -> RecordStream {
+    use proto::subscribe_stream_response::Event;
+
+    Box::pin(futures::stream::unfold(Some(responses), move |responses| async move {
+        let mut responses = responses?;
+        loop {
+            let message = match tokio::time::timeout(timeout, responses.message()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    let error = ClientError::Timeout(format!("No stream heartbeat for {:?}", timeout));
+                    return Some((Err(error), None));
+                }
+            };
+
+            match message {
+                Ok(Some(response)) => match response.event {
+                    Some(Event::Record(record)) => {
+                        return match proto_stream_record_to_ks(record) {
+                            Ok(record) => Some((Ok(record), Some(responses))),
+                            Err(status) => Some((Err(ClientError::from(status)), None)),
+                        };
+                    }
+                    Some(Event::Heartbeat(_)) | None => continue,
+                },
+                Ok(None) => return None,
+                Err(status) => return Some((Err(ClientError::from(status)), None)),
+            }
+        }
+    }))
+}
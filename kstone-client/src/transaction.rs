@@ -1,11 +1,12 @@
 /// Remote transaction operations
+use crate::client::RpcClient;
 use crate::convert::*;
 use crate::error::Result;
 use kstone_core::Item;
-use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
-use tonic::transport::Channel;
+use kstone_proto as proto;
 
 /// Remote transact get request builder
+#[derive(Clone)]
 pub struct RemoteTransactGetRequest {
     keys: Vec<proto::Key>,
 }
@@ -35,7 +36,7 @@ impl RemoteTransactGetRequest {
     }
 
     /// Execute the transact get operation
-    pub async fn execute(self, client: &mut KeystoneDbClient<Channel>) -> Result<RemoteTransactGetResponse> {
+    pub async fn execute(self, client: &mut RpcClient) -> Result<RemoteTransactGetResponse> {
         let request = proto::TransactGetRequest {
             keys: self.keys,
         };
@@ -73,6 +74,7 @@ pub struct RemoteTransactGetResponse {
 }
 
 /// Remote transact write request builder
+#[derive(Clone)]
 pub struct RemoteTransactWriteRequest {
     writes: Vec<proto::TransactWriteItem>,
 }
@@ -146,8 +148,14 @@ impl RemoteTransactWriteRequest {
         self
     }
 
+    /// Add a write built by the caller
+    pub(crate) fn push(mut self, write: proto::transact_write_item::Item) -> Self {
+        self.writes.push(proto::TransactWriteItem { item: Some(write) });
+        self
+    }
+
     /// Execute the transact write operation
-    pub async fn execute(self, client: &mut KeystoneDbClient<Channel>) -> Result<()> {
+    pub async fn execute(self, client: &mut RpcClient) -> Result<()> {
         let request = proto::TransactWriteRequest {
             items: self.writes,
         };
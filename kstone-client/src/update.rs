@@ -1,12 +1,13 @@
 /// Remote update operations
+use crate::client::RpcClient;
 use crate::convert::*;
 use crate::error::Result;
 use kstone_core::Item;
-use kstone_proto::{self as proto, keystone_db_client::KeystoneDbClient};
-use tonic::transport::Channel;
+use kstone_proto as proto;
 use std::collections::HashMap;
 
 /// Remote update request builder
+#[derive(Clone)]
 pub struct RemoteUpdate {
     partition_key: Vec<u8>,
     sort_key: Option<Vec<u8>>,
@@ -57,7 +58,7 @@ impl RemoteUpdate {
     }
 
     /// Execute the update operation
-    pub async fn execute(self, client: &mut KeystoneDbClient<Channel>) -> Result<RemoteUpdateResponse> {
+    pub async fn execute(self, client: &mut RpcClient) -> Result<RemoteUpdateResponse> {
         // Convert expression values to protobuf
         let proto_values: HashMap<String, proto::Value> = self
             .expression_values
@@ -3,6 +3,7 @@
 /// These tests start a real server and connect with the client to test
 /// end-to-end functionality.
 
+use futures::StreamExt;
 use kstone_api::Database;
 use kstone_client::{
     Client, RemoteQuery, RemoteScan, RemoteBatchGetRequest, RemoteBatchWriteRequest,
@@ -141,6 +142,68 @@ async fn test_scan() {
     assert!(response.count <= 5);
 }
 
+#[tokio::test]
+async fn test_query_stream() {
+    let (_dir, addr, _handle) = start_test_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    for i in 0..25 {
+        let mut item = HashMap::new();
+        item.insert("id".to_string(), Value::N(i.to_string()));
+        client.put_with_sk(b"org#1", format!("user#{:02}", i).as_bytes(), item).await.unwrap();
+    }
+
+    // Every item arrives, in sort key order, across several pages
+    let query = RemoteQuery::new(b"org#1").sk_begins_with(b"user#").page_size(4);
+    let items: Vec<_> = client.query_stream(query).await.unwrap().collect().await;
+    assert_eq!(items.len(), 25);
+    for (i, item) in items.into_iter().enumerate() {
+        assert_eq!(item.unwrap().get("id"), Some(&Value::N(i.to_string())));
+    }
+
+    // The limit caps the total, not the page
+    let query = RemoteQuery::new(b"org#1").page_size(4).limit(10);
+    let stream = client.query_stream(query).await.unwrap();
+    assert_eq!(stream.count().await, 10);
+
+    // Resuming after a key streams the rest
+    let query = RemoteQuery::new(b"org#1").page_size(7).start_after(b"org#1", Some(b"user#19"));
+    let stream = client.query_stream(query).await.unwrap();
+    assert_eq!(stream.count().await, 5);
+}
+
+#[tokio::test]
+async fn test_scan_stream() {
+    let (_dir, addr, _handle) = start_test_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    for i in 0..50 {
+        let mut item = HashMap::new();
+        item.insert("id".to_string(), Value::N(i.to_string()));
+        client.put(format!("item#{}", i).as_bytes(), item).await.unwrap();
+    }
+
+    let mut stream = client.scan_stream(RemoteScan::new().page_size(8)).await.unwrap();
+    let mut ids = Vec::new();
+    while let Some(item) = stream.next().await {
+        match item.unwrap().get("id") {
+            Some(Value::N(id)) => ids.push(id.parse::<u32>().unwrap()),
+            other => panic!("unexpected id {:?}", other),
+        }
+    }
+    ids.sort_unstable();
+    assert_eq!(ids, (0..50).collect::<Vec<_>>());
+
+    let stream = client.scan_stream(RemoteScan::new().page_size(8).limit(20)).await.unwrap();
+    assert_eq!(stream.count().await, 20);
+
+    // Dropping a stream part way through leaves the connection usable
+    let mut stream = client.scan_stream(RemoteScan::new().page_size(1)).await.unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    drop(stream);
+    assert!(client.get(b"item#0").await.unwrap().is_some());
+}
+
 #[tokio::test]
 async fn test_batch_get() {
     let (_dir, addr, _handle) = start_test_server().await;
@@ -633,3 +696,403 @@ async fn test_transact_get_with_missing_items() {
     assert!(response.items[1].is_none());
     assert!(response.items[2].is_none());
 }
+
+/// Start a test server that only accepts the given API keys
+///
+/// Its table has a global secondary index `status-index` on `status`.
+async fn start_auth_server(keys: Vec<(&'static str, kstone_server::Principal)>) -> (TempDir, String, tokio::task::JoinHandle<()>) {
+    use kstone_api::{GlobalSecondaryIndex, TableSchema};
+    use kstone_server::{AuthInterceptor, Authenticator};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    let dir = TempDir::new().unwrap();
+    let schema = TableSchema::new().add_global_index(GlobalSecondaryIndex::new("status-index", "status"));
+    let db = Database::create_with_schema(dir.path(), schema).unwrap();
+
+    let mut authenticator = Authenticator::new();
+    for (key, principal) in keys {
+        authenticator = authenticator.with_api_key(key, principal);
+    }
+    let authenticator = Arc::new(authenticator);
+    let service = KeystoneService::new(db).with_authenticator(authenticator.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let addr_str = format!("127.0.0.1:{}", port);
+    let client_addr = format!("http://{}", addr_str);
+
+    let handle = tokio::spawn(async move {
+        let addr = addr_str.parse().unwrap();
+        Server::builder()
+            .add_service(KeystoneDbServer::with_interceptor(service, AuthInterceptor::new(authenticator)))
+            .serve(addr)
+            .await
+            .unwrap();
+    });
+
+    sleep(Duration::from_millis(200)).await;
+
+    (dir, client_addr, handle)
+}
+
+#[tokio::test]
+async fn test_authentication_and_policies() {
+    use kstone_client::ClientError;
+    use kstone_server::{Policy, Principal};
+
+    let (_dir, addr, _handle) = start_auth_server(vec![
+        ("admin-key", Principal { name: "admin".to_string(), policy: Policy::read_write() }),
+        ("reader-key", Principal { name: "reader".to_string(), policy: Policy::read_only() }),
+        ("tenant-key", Principal { name: "tenant".to_string(), policy: Policy::read_write().with_key_prefix("tenant1#") }),
+    ])
+    .await;
+
+    let mut item = HashMap::new();
+    item.insert("name".to_string(), Value::S("Alice".to_string()));
+
+    // No credentials, or unknown ones
+    let mut anonymous = Client::connect(addr.clone()).await.unwrap();
+    assert!(matches!(anonymous.get(b"user#1").await, Err(ClientError::Unauthenticated(_))));
+    let mut unknown = Client::connect_with_token(addr.clone(), "wrong-key").await.unwrap();
+    assert!(matches!(unknown.get(b"user#1").await, Err(ClientError::Unauthenticated(_))));
+
+    let mut admin = Client::connect_with_token(addr.clone(), "admin-key").await.unwrap();
+    admin.put(b"user#1", item.clone()).await.unwrap();
+    admin.scan(RemoteScan::new()).await.unwrap();
+
+    // Read-only keys can read but not write
+    let mut reader = Client::connect_with_token(addr.clone(), "reader-key").await.unwrap();
+    assert!(reader.get(b"user#1").await.unwrap().is_some());
+    assert!(matches!(reader.put(b"user#2", item.clone()).await, Err(ClientError::PermissionDenied(_))));
+    assert!(matches!(
+        reader.execute_statement("DELETE FROM items WHERE pk = 'user#1'").await,
+        Err(ClientError::PermissionDenied(_))
+    ));
+
+    // Prefix-restricted keys stay within their partitions
+    let mut tenant = Client::connect_with_token(addr.clone(), "tenant-key").await.unwrap();
+    tenant.put(b"tenant1#user", item.clone()).await.unwrap();
+    assert!(matches!(tenant.get(b"user#1").await, Err(ClientError::PermissionDenied(_))));
+    assert!(matches!(tenant.scan(RemoteScan::new()).await, Err(ClientError::PermissionDenied(_))));
+
+    let batch = RemoteBatchGetRequest::new().add_key(b"tenant1#user").add_key(b"user#1");
+    assert!(matches!(tenant.batch_get(batch).await, Err(ClientError::PermissionDenied(_))));
+}
+
+#[tokio::test]
+async fn test_index_queries_authorize_base_items() {
+    use kstone_client::ClientError;
+    use kstone_server::{Policy, Principal};
+
+    let (_dir, addr, _handle) = start_auth_server(vec![
+        ("admin-key", Principal { name: "admin".to_string(), policy: Policy::read_write() }),
+        ("tenant-key", Principal { name: "tenant".to_string(), policy: Policy::read_only().with_key_prefix("tenant1#") }),
+    ])
+    .await;
+
+    let mut admin = Client::connect_with_token(addr.clone(), "admin-key").await.unwrap();
+    for (pk, status) in [("tenant1#a", "archived"), ("tenant1#b", "active"), ("tenant2#c", "active")] {
+        let mut item = HashMap::new();
+        item.insert("status".to_string(), Value::S(status.to_string()));
+        admin.put(pk.as_bytes(), item).await.unwrap();
+    }
+
+    // The index partition key isn't a base partition key, so it isn't checked against the prefixes
+    let mut tenant = Client::connect_with_token(addr.clone(), "tenant-key").await.unwrap();
+    let response = tenant.query(RemoteQuery::new(b"archived").index("status-index")).await.unwrap();
+    assert_eq!(response.items.len(), 1);
+
+    // Matches from another tenant's partitions refuse the whole query
+    let active = || RemoteQuery::new(b"active").index("status-index");
+    assert!(matches!(tenant.query(active()).await, Err(ClientError::PermissionDenied(_))));
+    let mut items = tenant.query_stream(active()).await.unwrap();
+    assert!(matches!(items.next().await, Some(Err(ClientError::PermissionDenied(_)))));
+
+    let response = admin.query(active()).await.unwrap();
+    assert_eq!(response.items.len(), 2);
+}
+
+/// Helper to start a test server whose table has streams enabled
+async fn start_stream_server() -> (TempDir, String, tokio::task::JoinHandle<()>) {
+    use kstone_api::{StreamConfig, TableSchema};
+    use std::net::TcpListener;
+
+    let dir = TempDir::new().unwrap();
+    let schema = TableSchema::new().with_stream(StreamConfig::enabled());
+    let db = Database::create_with_schema(dir.path(), schema).unwrap();
+    let service = KeystoneService::new(db);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let addr_str = format!("127.0.0.1:{}", port);
+    let client_addr = format!("http://{}", addr_str);
+
+    let handle = tokio::spawn(async move {
+        let addr = addr_str.parse().unwrap();
+        Server::builder()
+            .add_service(KeystoneDbServer::new(service))
+            .serve(addr)
+            .await
+            .unwrap();
+    });
+
+    sleep(Duration::from_millis(200)).await;
+
+    (dir, client_addr, handle)
+}
+
+#[tokio::test]
+async fn test_subscribe_stream() {
+    use kstone_api::StreamEventType;
+    use kstone_client::{RecordStream, RemoteSubscription};
+
+    async fn next_record(records: &mut RecordStream) -> kstone_api::StreamRecord {
+        tokio::time::timeout(Duration::from_secs(5), records.next())
+            .await
+            .expect("no stream record within 5s")
+            .expect("stream ended")
+            .unwrap()
+    }
+
+    let (_dir, addr, _handle) = start_stream_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    for i in 0..3 {
+        let mut item = HashMap::new();
+        item.insert("id".to_string(), Value::N(i.to_string()));
+        client.put(format!("item#{}", i).as_bytes(), item).await.unwrap();
+    }
+
+    // Retained records first, then live writes
+    let subscription = RemoteSubscription::new()
+        .from_trim_horizon()
+        .heartbeat_interval(Duration::from_millis(100));
+    let mut records = client.subscribe_stream(subscription).await.unwrap();
+    for i in 0..3 {
+        let record = next_record(&mut records).await;
+        assert_eq!(record.event_type, StreamEventType::Insert);
+        assert_eq!(record.key.pk.as_ref(), format!("item#{}", i).as_bytes());
+    }
+
+    // Idle for several heartbeats without timing out
+    sleep(Duration::from_millis(500)).await;
+    client.delete(b"item#0").await.unwrap();
+    let record = next_record(&mut records).await;
+    assert_eq!(record.event_type, StreamEventType::Remove);
+    let last_sequence = record.sequence_number;
+    drop(records);
+
+    // Resume after the last record handled
+    client.delete(b"item#1").await.unwrap();
+    client.delete(b"item#2").await.unwrap();
+    let mut records = client
+        .subscribe_stream(RemoteSubscription::new().after_sequence(last_sequence))
+        .await
+        .unwrap();
+    for pk in [b"item#1", b"item#2"] {
+        let record = next_record(&mut records).await;
+        assert_eq!(record.event_type, StreamEventType::Remove);
+        assert_eq!(record.key.pk.as_ref(), pk);
+        assert!(record.sequence_number > last_sequence);
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_stream_requires_streams() {
+    use kstone_client::{ClientError, RemoteSubscription};
+
+    let (_dir, addr, _handle) = start_test_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let result = client.subscribe_stream(RemoteSubscription::new()).await;
+    assert!(matches!(result, Err(ClientError::InvalidArgument(_))));
+}
+
+/// Helper to start a server with a database per name under a root directory
+async fn start_registry_server() -> (TempDir, String, tokio::task::JoinHandle<()>) {
+    use kstone_server::DatabaseRegistry;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    let dir = TempDir::new().unwrap();
+    let registry = DatabaseRegistry::new(dir.path())
+        .with_create_missing(true)
+        .with_max_open(1);
+    let service = KeystoneService::from_registry(Arc::new(registry));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let addr_str = format!("127.0.0.1:{}", port);
+    let client_addr = format!("http://{}", addr_str);
+
+    let handle = tokio::spawn(async move {
+        let addr = addr_str.parse().unwrap();
+        Server::builder()
+            .add_service(KeystoneDbServer::new(service))
+            .serve(addr)
+            .await
+            .unwrap();
+    });
+
+    sleep(Duration::from_millis(200)).await;
+
+    (dir, client_addr, handle)
+}
+
+#[tokio::test]
+async fn test_multiple_databases() {
+    use kstone_client::ClientError;
+
+    let (dir, addr, _handle) = start_registry_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let mut orders = client.database("orders").unwrap();
+    let mut users = client.database("users").unwrap();
+
+    let mut item = HashMap::new();
+    item.insert("name".to_string(), Value::S("Alice".to_string()));
+    users.put(b"user#1", item).await.unwrap();
+
+    let mut item = HashMap::new();
+    item.insert("total".to_string(), Value::N("42".to_string()));
+    orders.put(b"order#1", item).await.unwrap();
+
+    // Each database only sees its own items, even after being closed and
+    // reopened (only one is kept open)
+    assert!(users.get(b"user#1").await.unwrap().is_some());
+    assert!(users.get(b"order#1").await.unwrap().is_none());
+    assert!(orders.get(b"order#1").await.unwrap().is_some());
+    assert!(orders.get(b"user#1").await.unwrap().is_none());
+    assert!(dir.path().join("orders").exists());
+    assert!(dir.path().join("users").exists());
+
+    // Requests must name a valid database
+    assert!(matches!(client.get(b"order#1").await, Err(ClientError::InvalidArgument(_))));
+    let mut invalid = client.database("../escape").unwrap();
+    assert!(matches!(invalid.get(b"x").await, Err(ClientError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn test_client_pool_and_retry() {
+    use kstone_client::{ClientError, RetryPolicy};
+    use kstone_server::RateLimiter;
+    use std::net::TcpListener;
+
+    // A server admitting one request per second
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+    let service = KeystoneService::new(db).with_rate_limiter(RateLimiter::new(0, 1));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let addr_str = format!("127.0.0.1:{}", port);
+    let addr = format!("http://{}", addr_str);
+    let _handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(KeystoneDbServer::new(service))
+            .serve(addr_str.parse().unwrap())
+            .await
+            .unwrap();
+    });
+    sleep(Duration::from_millis(200)).await;
+
+    let mut client = Client::builder(addr.clone())
+        .pool_size(3)
+        .retry_policy(RetryPolicy::new(6, 200, 1000, 2.0))
+        .request_timeout(Duration::from_secs(5))
+        .connect()
+        .await
+        .unwrap();
+
+    // The second request is throttled and succeeds once retried
+    let mut item = HashMap::new();
+    item.insert("name".to_string(), Value::S("Alice".to_string()));
+    client.put(b"user#1", item).await.unwrap();
+    assert!(client.get(b"user#1").await.unwrap().is_some());
+
+    // Without retries the throttled request fails
+    let mut impatient = Client::builder(addr).no_retry().connect().await.unwrap();
+    let mut throttled = false;
+    for _ in 0..3 {
+        match impatient.get(b"user#1").await {
+            Ok(_) => {}
+            Err(ClientError::ResourceExhausted(_)) => throttled = true,
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
+    assert!(throttled);
+}
+
+/// Exercise a store through the backend-neutral trait only
+async fn exercise_store<S: kstone_client::KeystoneStore>(store: &mut S) -> Vec<String> {
+    use kstone_client::{SkCondition, StatementResult, StoreQuery, StoreUpdate, StoreWrite};
+    use kstone_core::Key;
+
+    for i in 0..4 {
+        let key = Key::with_sk(b"team#1".to_vec(), format!("member#{}", i).into_bytes());
+        let mut item = HashMap::new();
+        item.insert("score".to_string(), Value::N(i.to_string()));
+        store.put(key, item).await.unwrap();
+    }
+
+    let page = store
+        .query(StoreQuery::new("team#1").sk(SkCondition::GreaterThanOrEqual("member#2".into())))
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 2);
+
+    let member = Key::with_sk(b"team#1".to_vec(), b"member#0".to_vec());
+    let updated = store
+        .update(StoreUpdate::new(member.clone(), "SET score = score + :bonus").value(":bonus", Value::N("10".to_string())))
+        .await
+        .unwrap();
+    assert_eq!(updated.get("score"), Some(&Value::N("10".to_string())));
+
+    // A failed condition aborts the whole transaction
+    let writes = vec![
+        StoreWrite::Delete { key: member.clone(), condition: None },
+        StoreWrite::ConditionCheck { key: member.clone(), condition: "attribute_not_exists(score)".to_string() },
+    ];
+    assert!(store.transact_write(writes).await.is_err());
+    assert!(store.get(member.clone()).await.unwrap().is_some());
+
+    store
+        .transact_write(vec![StoreWrite::Delete { key: member.clone(), condition: None }])
+        .await
+        .unwrap();
+    assert!(store.transact_get(vec![member]).await.unwrap()[0].is_none());
+
+    match store.execute_statement("SELECT * FROM team WHERE pk = 'team#1'").await.unwrap() {
+        StatementResult::Select { items, .. } => {
+            let mut scores: Vec<String> = items
+                .iter()
+                .map(|item| match item.get("score") {
+                    Some(Value::N(n)) => n.clone(),
+                    _ => String::new(),
+                })
+                .collect();
+            scores.sort();
+            scores
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_store_trait_local_and_remote() {
+    let (_dir, addr, _handle) = start_test_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let mut db = Database::create_in_memory().unwrap();
+
+    let remote = exercise_store(&mut client).await;
+    let local = exercise_store(&mut db).await;
+    assert_eq!(remote, vec!["1", "2", "3"]);
+    assert_eq!(local, remote);
+}
@@ -1,11 +1,16 @@
 /// Background task management for LSM operations
 ///
 /// Provides a background worker thread that performs compaction operations
-/// asynchronously without blocking database operations.
+/// asynchronously without blocking database operations, a TTL reaper
+/// thread that deletes expired items (Phase 3.3+), a WAL syncer for
+/// interval-based durability (Phase 8+), and a flush thread that writes full
+/// memtables to SSTs off the write path (Phase 8+).
 
 use crate::compaction::{CompactionConfig, CompactionStatsAtomic};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use crate::Result;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 use tracing::{debug, info, warn};
@@ -166,6 +171,451 @@ impl Drop for BackgroundWorker {
     }
 }
 
+/// TTL reaper statistics (Phase 3.3+)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TtlStats {
+    /// Number of completed reaper passes
+    pub total_passes: u64,
+
+    /// Total number of expired items deleted
+    pub total_items_reaped: u64,
+
+    /// Number of expired items deleted by the most recent pass
+    pub last_pass_items_reaped: u64,
+}
+
+/// Thread-safe TTL reaper statistics
+#[derive(Debug, Clone, Default)]
+pub struct TtlStatsAtomic {
+    total_passes: Arc<AtomicU64>,
+    total_items_reaped: Arc<AtomicU64>,
+    last_pass_items_reaped: Arc<AtomicU64>,
+}
+
+impl TtlStatsAtomic {
+    /// Create new atomic statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed pass
+    pub fn record_pass(&self, items_reaped: u64) {
+        self.total_passes.fetch_add(1, Ordering::Relaxed);
+        self.total_items_reaped.fetch_add(items_reaped, Ordering::Relaxed);
+        self.last_pass_items_reaped.store(items_reaped, Ordering::Relaxed);
+    }
+
+    /// Get a snapshot of current statistics
+    pub fn snapshot(&self) -> TtlStats {
+        TtlStats {
+            total_passes: self.total_passes.load(Ordering::Relaxed),
+            total_items_reaped: self.total_items_reaped.load(Ordering::Relaxed),
+            last_pass_items_reaped: self.last_pass_items_reaped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Thread that runs a closure every `interval` until shut down
+///
+/// The closure returns false to stop the thread early. A condvar lets
+/// shutdown interrupt the wait instead of sleeping out the interval.
+struct PeriodicThread {
+    /// Worker thread handle
+    handle: Option<JoinHandle<()>>,
+
+    /// Shutdown flag
+    shutdown: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl PeriodicThread {
+    fn start<F>(interval: Duration, mut tick: F) -> Self
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_shutdown = Arc::clone(&shutdown);
+
+        let handle = thread::spawn(move || {
+            let (lock, condvar) = &*thread_shutdown;
+
+            loop {
+                {
+                    let stopped = lock.lock().unwrap();
+                    let (stopped, _) = condvar
+                        .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+                        .unwrap();
+                    if *stopped {
+                        break;
+                    }
+                }
+
+                if !tick() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            handle: Some(handle),
+            shutdown,
+        }
+    }
+
+    fn shutdown(&mut self, name: &str) {
+        let (lock, condvar) = &*self.shutdown;
+        *lock.lock().unwrap() = true;
+        condvar.notify_all();
+
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                warn!("Error joining {} thread: {:?}", name, e);
+            }
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
+}
+
+/// Background thread that periodically deletes expired TTL items (Phase 3.3+)
+///
+/// Each pass calls the supplied closure, which returns the number of items
+/// it deleted, or `None` once the database it works on has gone away.
+pub struct TtlReaper {
+    thread: PeriodicThread,
+}
+
+impl TtlReaper {
+    /// Start a reaper that runs `pass` every `interval`
+    pub fn start<F>(interval: Duration, mut pass: F) -> Self
+    where
+        F: FnMut() -> Option<Result<u64>> + Send + 'static,
+    {
+        info!("Starting background TTL reaper (interval {:?})", interval);
+
+        let thread = PeriodicThread::start(interval, move || match pass() {
+            Some(Ok(0)) => true,
+            Some(Ok(reaped)) => {
+                debug!("TTL reaper deleted {} expired items", reaped);
+                true
+            }
+            Some(Err(e)) => {
+                warn!("TTL reaper pass failed: {}", e);
+                true
+            }
+            None => false,
+        });
+
+        Self { thread }
+    }
+
+    /// Stop the reaper and wait for the current pass to finish
+    pub fn shutdown(&mut self) {
+        self.thread.shutdown("TTL reaper");
+    }
+
+    /// Check if the reaper is running
+    pub fn is_running(&self) -> bool {
+        self.thread.is_running()
+    }
+}
+
+impl Drop for TtlReaper {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Background thread that offloads cold SSTs to an object store (Phase 8+)
+///
+/// Each pass calls the supplied closure, which returns the number of SSTs
+/// it offloaded, or `None` once the database it works on has gone away.
+pub struct ColdOffloader {
+    thread: PeriodicThread,
+}
+
+impl ColdOffloader {
+    /// Start an offloader that runs `pass` every `interval`
+    pub fn start<F>(interval: Duration, mut pass: F) -> Self
+    where
+        F: FnMut() -> Option<Result<u64>> + Send + 'static,
+    {
+        info!("Starting background cold SST offloader (interval {:?})", interval);
+
+        let thread = PeriodicThread::start(interval, move || match pass() {
+            Some(Ok(0)) => true,
+            Some(Ok(offloaded)) => {
+                debug!("Offloaded {} cold SSTs", offloaded);
+                true
+            }
+            Some(Err(e)) => {
+                warn!("Cold SST offload pass failed: {}", e);
+                true
+            }
+            None => false,
+        });
+
+        Self { thread }
+    }
+
+    /// Stop the offloader and wait for the current pass to finish
+    pub fn shutdown(&mut self) {
+        self.thread.shutdown("cold SST offloader");
+    }
+
+    /// Check if the offloader is running
+    pub fn is_running(&self) -> bool {
+        self.thread.is_running()
+    }
+}
+
+impl Drop for ColdOffloader {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Background thread that backfills indexes created online (Phase 3.2+)
+///
+/// Each step calls the supplied closure, which backfills a slice of the data
+/// and returns whether work remains, or `None` once the database it works on
+/// has gone away. The thread exits when no work remains.
+pub struct IndexBackfiller {
+    thread: PeriodicThread,
+}
+
+impl IndexBackfiller {
+    /// Start a backfiller that runs `step` every `interval` until it is done
+    pub fn start<F>(interval: Duration, mut step: F) -> Self
+    where
+        F: FnMut() -> Option<Result<bool>> + Send + 'static,
+    {
+        info!("Starting background index backfill");
+
+        let thread = PeriodicThread::start(interval, move || match step() {
+            Some(Ok(true)) => true,
+            Some(Ok(false)) => {
+                info!("Index backfill complete");
+                false
+            }
+            Some(Err(e)) => {
+                warn!("Index backfill step failed: {}", e);
+                true
+            }
+            None => false,
+        });
+
+        Self { thread }
+    }
+
+    /// Stop the backfiller and wait for the current step to finish
+    pub fn shutdown(&mut self) {
+        self.thread.shutdown("index backfill");
+    }
+
+    /// Check if the backfiller is running
+    pub fn is_running(&self) -> bool {
+        self.thread.is_running()
+    }
+}
+
+impl Drop for IndexBackfiller {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Background thread that fsyncs the WAL on a fixed interval (Phase 8+)
+///
+/// Used with `WalSyncMode::EveryNms` so that acknowledged writes reach disk
+/// within the interval even when no further writes arrive.
+pub struct WalSyncer {
+    thread: PeriodicThread,
+}
+
+impl WalSyncer {
+    /// Start a syncer that runs `sync` every `interval`
+    pub fn start<F>(interval: Duration, mut sync: F) -> Self
+    where
+        F: FnMut() -> Result<()> + Send + 'static,
+    {
+        debug!("Starting background WAL syncer (interval {:?})", interval);
+
+        let thread = PeriodicThread::start(interval, move || {
+            if let Err(e) = sync() {
+                warn!("WAL sync failed: {}", e);
+            }
+            true
+        });
+
+        Self { thread }
+    }
+
+    /// Stop the syncer
+    pub fn shutdown(&mut self) {
+        self.thread.shutdown("WAL syncer");
+    }
+
+    /// Check if the syncer is running
+    pub fn is_running(&self) -> bool {
+        self.thread.is_running()
+    }
+}
+
+impl Drop for WalSyncer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Background flush statistics (Phase 8+)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushStats {
+    /// Full memtables waiting for the background flush thread
+    pub pending_flushes: u64,
+
+    /// Memtables written to SSTs by the background flush thread
+    pub background_flushes: u64,
+
+    /// Writes that had to flush inline because the flush queue was full or
+    /// their stripe's previous memtable was still being flushed
+    pub write_stalls: u64,
+}
+
+/// Thread-safe background flush statistics
+#[derive(Debug, Clone, Default)]
+pub struct FlushStatsAtomic {
+    pending_flushes: Arc<AtomicU64>,
+    background_flushes: Arc<AtomicU64>,
+    write_stalls: Arc<AtomicU64>,
+}
+
+impl FlushStatsAtomic {
+    /// Create new atomic statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a memtable handed to the flush thread
+    pub fn record_queued(&self) {
+        self.pending_flushes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a queued flush the flush thread has finished with
+    pub fn record_dequeued(&self) {
+        self.pending_flushes.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Record a memtable written by the flush thread
+    pub fn record_background_flush(&self) {
+        self.background_flushes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a write that flushed inline
+    pub fn record_stall(&self) {
+        self.write_stalls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get a snapshot of current statistics
+    pub fn snapshot(&self) -> FlushStats {
+        FlushStats {
+            pending_flushes: self.pending_flushes.load(Ordering::Relaxed),
+            background_flushes: self.background_flushes.load(Ordering::Relaxed),
+            write_stalls: self.write_stalls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Message for the flush thread
+enum FlushJob {
+    Flush(usize),
+    Shutdown,
+}
+
+/// Handle for queueing stripes with the flush thread (Phase 8+)
+#[derive(Clone)]
+pub struct FlushQueue {
+    sender: SyncSender<FlushJob>,
+}
+
+impl FlushQueue {
+    /// Queue a stripe for flushing, returning false if the queue is full
+    pub fn try_push(&self, stripe_id: usize) -> bool {
+        self.sender.try_send(FlushJob::Flush(stripe_id)).is_ok()
+    }
+}
+
+/// Background thread that writes full memtables to SSTs (Phase 8+)
+///
+/// Writers freeze a full memtable, queue its stripe and return without
+/// waiting for the SST write or any compaction it triggers. The queue is
+/// bounded: once it is full, writers flush inline instead (a write stall),
+/// which caps the memory held by memtables waiting to be flushed.
+pub struct FlushWorker {
+    /// Worker thread handle
+    handle: Option<JoinHandle<()>>,
+
+    /// Sending half of the queue, also used to signal shutdown
+    queue: FlushQueue,
+}
+
+impl FlushWorker {
+    /// Start a flush thread whose queue holds up to `capacity` stripes
+    ///
+    /// `flush` is called for each queued stripe and returns `None` once the
+    /// database it works on has gone away.
+    pub fn start<F>(capacity: usize, mut flush: F) -> Self
+    where
+        F: FnMut(usize) -> Option<Result<()>> + Send + 'static,
+    {
+        debug!("Starting background flush worker (queue capacity {})", capacity);
+
+        let (sender, jobs) = mpsc::sync_channel(capacity);
+        let handle = thread::spawn(move || {
+            while let Ok(FlushJob::Flush(stripe_id)) = jobs.recv() {
+                match flush(stripe_id) {
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => warn!("Background flush of stripe {} failed: {}", stripe_id, e),
+                    None => break,
+                }
+            }
+        });
+
+        Self {
+            handle: Some(handle),
+            queue: FlushQueue { sender },
+        }
+    }
+
+    /// Handle for queueing stripes
+    pub fn queue(&self) -> FlushQueue {
+        self.queue.clone()
+    }
+
+    /// Stop the flush thread once it has worked through the queue
+    pub fn shutdown(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            // Fails only if the thread has already exited
+            let _ = self.queue.sender.send(FlushJob::Shutdown);
+            if let Err(e) = handle.join() {
+                warn!("Error joining flush worker thread: {:?}", e);
+            }
+        }
+    }
+
+    /// Check if the flush thread is running
+    pub fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
+}
+
+impl Drop for FlushWorker {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +695,77 @@ mod tests {
 
         assert_eq!(worker.queue_size(), 10);
     }
+
+    #[test]
+    fn test_ttl_reaper_runs_passes() {
+        let passes = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&passes);
+
+        let mut reaper = TtlReaper::start(Duration::from_millis(10), move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Some(Ok(0))
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        reaper.shutdown();
+        assert!(!reaper.is_running());
+        assert!(passes.load(Ordering::Relaxed) >= 1);
+    }
+
+    #[test]
+    fn test_ttl_reaper_shutdown_is_prompt() {
+        let mut reaper = TtlReaper::start(Duration::from_secs(3600), || Some(Ok(0)));
+        let started = std::time::Instant::now();
+        reaper.shutdown();
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_index_backfiller_stops_when_done() {
+        let steps = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&steps);
+
+        let mut backfiller = IndexBackfiller::start(Duration::from_millis(1), move || {
+            let step = counter.fetch_add(1, Ordering::Relaxed) + 1;
+            Some(Ok(step < 3))
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(steps.load(Ordering::Relaxed), 3);
+        backfiller.shutdown();
+        assert!(!backfiller.is_running());
+    }
+
+    #[test]
+    fn test_ttl_stats() {
+        let stats = TtlStatsAtomic::new();
+        stats.record_pass(3);
+        stats.record_pass(2);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_passes, 2);
+        assert_eq!(snapshot.total_items_reaped, 5);
+        assert_eq!(snapshot.last_pass_items_reaped, 2);
+    }
+
+    #[test]
+    fn test_flush_worker_processes_queue() {
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&flushed);
+        let mut worker = FlushWorker::start(4, move |stripe_id| {
+            seen.lock().unwrap().push(stripe_id);
+            Some(Ok(()))
+        });
+        assert!(worker.is_running());
+
+        let queue = worker.queue();
+        assert!(queue.try_push(1));
+        assert!(queue.try_push(2));
+
+        // Shutdown waits for queued flushes
+        worker.shutdown();
+        assert!(!worker.is_running());
+        assert_eq!(*flushed.lock().unwrap(), vec![1, 2]);
+        assert!(!queue.try_push(3));
+    }
 }
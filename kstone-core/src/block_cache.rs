@@ -0,0 +1,230 @@
+/// Shared LRU cache of decoded SST data blocks (Phase 1.4+)
+///
+/// Blocks are keyed by (SST id, block offset) and stored decoded, so a hit
+/// skips both the disk read and the decompression/deserialization work.
+/// The cache is bounded by an approximate byte budget; the least recently
+/// used blocks are evicted first.
+
+use crate::Record;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Default block cache size (8MB)
+pub const DEFAULT_BLOCK_CACHE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Cache key: (SST id, block offset)
+type BlockKey = (u64, u64);
+
+/// Decoded records of one data block
+pub type CachedBlock = Arc<Vec<Record>>;
+
+/// Block cache statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+
+    /// Lookups that had to read the block from disk
+    pub misses: u64,
+
+    /// Blocks evicted to stay within capacity
+    pub evictions: u64,
+
+    /// Approximate bytes currently cached
+    pub size_bytes: u64,
+
+    /// Configured capacity in bytes
+    pub capacity_bytes: u64,
+}
+
+struct CacheEntry {
+    block: CachedBlock,
+    charge: usize,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<BlockKey, CacheEntry>,
+    lru: BTreeMap<u64, BlockKey>, // tick -> key, oldest first
+    next_tick: u64,
+    size_bytes: usize,
+}
+
+/// Shared LRU block cache
+pub struct BlockCache {
+    capacity_bytes: usize,
+    state: Mutex<CacheState>,
+    next_sst_id: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl BlockCache {
+    /// Create a cache holding up to `capacity_bytes` of decoded blocks
+    ///
+    /// A capacity of 0 disables caching (every lookup is a miss).
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            state: Mutex::new(CacheState::default()),
+            next_sst_id: AtomicU64::new(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Allocate an id that distinguishes one SST's blocks from another's
+    pub fn next_sst_id(&self) -> u64 {
+        self.next_sst_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Look up a block, marking it most recently used
+    pub fn get(&self, sst_id: u64, offset: u64) -> Option<CachedBlock> {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let tick = state.next_tick;
+        match state.entries.get_mut(&(sst_id, offset)) {
+            Some(entry) => {
+                state.lru.remove(&entry.tick);
+                state.lru.insert(tick, (sst_id, offset));
+                entry.tick = tick;
+                state.next_tick += 1;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(Arc::clone(&entry.block))
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Insert a block, evicting least recently used blocks to make room
+    ///
+    /// `charge` is the approximate size of the block in bytes. Blocks larger
+    /// than the whole cache are not cached.
+    pub fn insert(&self, sst_id: u64, offset: u64, block: CachedBlock, charge: usize) {
+        if charge > self.capacity_bytes {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let tick = state.next_tick;
+        state.next_tick += 1;
+
+        if let Some(old) = state.entries.insert((sst_id, offset), CacheEntry { block, charge, tick }) {
+            state.lru.remove(&old.tick);
+            state.size_bytes -= old.charge;
+        }
+        state.lru.insert(tick, (sst_id, offset));
+        state.size_bytes += charge;
+
+        while state.size_bytes > self.capacity_bytes {
+            let Some((_, key)) = state.lru.pop_first() else { break };
+            if let Some(evicted) = state.entries.remove(&key) {
+                state.size_bytes -= evicted.charge;
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drop every cached block of an SST (e.g. after compaction deletes it)
+    pub fn evict_sst(&self, sst_id: u64) {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let keys: Vec<BlockKey> = state.entries.keys().filter(|(id, _)| *id == sst_id).copied().collect();
+        for key in keys {
+            if let Some(entry) = state.entries.remove(&key) {
+                state.lru.remove(&entry.tick);
+                state.size_bytes -= entry.charge;
+            }
+        }
+    }
+
+    /// Get a snapshot of cache statistics
+    pub fn stats(&self) -> BlockCacheStats {
+        BlockCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            size_bytes: self.state.lock().size_bytes as u64,
+            capacity_bytes: self.capacity_bytes as u64,
+        }
+    }
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOCK_CACHE_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    fn block(n: usize) -> CachedBlock {
+        Arc::new((0..n).map(|i| Record::delete(Key::new(format!("k{}", i).into_bytes()), i as u64)).collect())
+    }
+
+    #[test]
+    fn test_block_cache_hit_and_miss() {
+        let cache = BlockCache::new(1024);
+        assert!(cache.get(1, 0).is_none());
+
+        cache.insert(1, 0, block(2), 100);
+        let cached = cache.get(1, 0).unwrap();
+        assert_eq!(cached.len(), 2);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size_bytes, 100);
+    }
+
+    #[test]
+    fn test_block_cache_evicts_least_recently_used() {
+        let cache = BlockCache::new(250);
+        cache.insert(1, 0, block(1), 100);
+        cache.insert(1, 4096, block(1), 100);
+
+        // Touch the first block so the second becomes the LRU entry
+        assert!(cache.get(1, 0).is_some());
+        cache.insert(2, 0, block(1), 100);
+
+        assert!(cache.get(1, 0).is_some());
+        assert!(cache.get(1, 4096).is_none());
+        assert!(cache.get(2, 0).is_some());
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().size_bytes, 200);
+    }
+
+    #[test]
+    fn test_block_cache_evict_sst() {
+        let cache = BlockCache::new(1024);
+        cache.insert(1, 0, block(1), 100);
+        cache.insert(2, 0, block(1), 100);
+
+        cache.evict_sst(1);
+        assert!(cache.get(1, 0).is_none());
+        assert!(cache.get(2, 0).is_some());
+        assert_eq!(cache.stats().size_bytes, 100);
+    }
+
+    #[test]
+    fn test_block_cache_zero_capacity() {
+        let cache = BlockCache::new(0);
+        cache.insert(1, 0, block(1), 10);
+        assert!(cache.get(1, 0).is_none());
+    }
+}
@@ -4,6 +4,7 @@
 /// Supports false positives but no false negatives.
 
 use bytes::{Bytes, BytesMut, BufMut};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Bloom filter with configurable bits per key
 #[derive(Clone)]
@@ -140,6 +141,69 @@ impl BloomFilter {
     }
 }
 
+/// How a bloom filter answered lookups (Phase 8+)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BloomStats {
+    /// Lookups that probed the filter
+    pub checks: u64,
+
+    /// Lookups the filter ruled out
+    pub negatives: u64,
+
+    /// Lookups the filter passed for keys that were not there
+    pub false_positives: u64,
+}
+
+impl BloomStats {
+    /// Fraction of absent keys the filter failed to rule out
+    ///
+    /// None until the filter has been probed for an absent key.
+    pub fn false_positive_rate(&self) -> Option<f64> {
+        let absent = self.negatives + self.false_positives;
+        if absent == 0 {
+            None
+        } else {
+            Some(self.false_positives as f64 / absent as f64)
+        }
+    }
+
+    /// Add another filter's counters to these
+    pub fn merge(&mut self, other: &BloomStats) {
+        self.checks += other.checks;
+        self.negatives += other.negatives;
+        self.false_positives += other.false_positives;
+    }
+}
+
+/// Thread-safe bloom filter statistics
+#[derive(Debug, Default)]
+pub struct BloomStatsAtomic {
+    checks: AtomicU64,
+    negatives: AtomicU64,
+    false_positives: AtomicU64,
+}
+
+impl BloomStatsAtomic {
+    /// Record a lookup: whether the filter passed it and whether the key was found
+    pub fn record(&self, passed: bool, found: bool) {
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        if !passed {
+            self.negatives.fetch_add(1, Ordering::Relaxed);
+        } else if !found {
+            self.false_positives.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Get a snapshot of current statistics
+    pub fn snapshot(&self) -> BloomStats {
+        BloomStats {
+            checks: self.checks.load(Ordering::Relaxed),
+            negatives: self.negatives.load(Ordering::Relaxed),
+            false_positives: self.false_positives.load(Ordering::Relaxed),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +227,29 @@ mod tests {
         assert!(!bloom.contains(b"key5"));
     }
 
+    #[test]
+    fn test_bloom_stats() {
+        let stats = BloomStatsAtomic::default();
+        assert_eq!(stats.snapshot().false_positive_rate(), None);
+
+        stats.record(true, true);
+        stats.record(false, false);
+        stats.record(false, false);
+        stats.record(false, false);
+        stats.record(true, false);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.checks, 5);
+        assert_eq!(snapshot.negatives, 3);
+        assert_eq!(snapshot.false_positives, 1);
+        assert_eq!(snapshot.false_positive_rate(), Some(0.25));
+
+        let mut total = BloomStats::default();
+        total.merge(&snapshot);
+        total.merge(&snapshot);
+        assert_eq!(total.checks, 10);
+    }
+
     #[test]
     fn test_bloom_encode_decode() {
         let mut bloom = BloomFilter::new(50, 10);
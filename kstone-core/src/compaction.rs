@@ -10,13 +10,22 @@
 /// - Merges all SSTs in a stripe into a single new SST
 /// - Removes tombstones (deleted records) during merge
 /// - Keeps newest version of each key (highest SeqNo)
-
-use crate::{Error, Result, Record, sst::{SstWriter, SstReader}};
-use std::collections::BTreeMap;
-use std::path::PathBuf;
+/// - Runs an optional `CompactionFilter` over surviving items (Phase 8+)
+/// - Paces SST reads and writes through an optional `IoThrottle` (Phase 8+)
+/// - Carries value log pointers over and deletes value log files no
+///   surviving SST references (Phase 8+)
+
+use crate::{Error, Result, Record, Key, Item, sst::{SstWriter, SstReader}};
+use crate::index::is_index_key;
+use crate::vlog::{BlobRefs, ValueLog};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Default compaction trigger: compact when stripe has this many SSTs
 pub const DEFAULT_SST_THRESHOLD: usize = 10;
@@ -27,6 +36,9 @@ pub const MIN_SSTS_TO_COMPACT: usize = 2;
 /// Legacy constant for backward compatibility
 pub const COMPACTION_THRESHOLD: usize = DEFAULT_SST_THRESHOLD;
 
+/// Window over which I/O throttle utilization is measured
+const UTILIZATION_WINDOW: Duration = Duration::from_secs(1);
+
 /// Compaction configuration
 #[derive(Clone, Debug)]
 pub struct CompactionConfig {
@@ -41,6 +53,11 @@ pub struct CompactionConfig {
 
     /// Maximum number of stripes to compact concurrently
     pub max_concurrent_compactions: usize,
+
+    /// I/O budget shared by compaction and SST flushes, in bytes/sec (Phase 8+)
+    ///
+    /// `None` leaves compaction and flush I/O unthrottled.
+    pub io_rate_limit_bytes_per_sec: Option<u64>,
 }
 
 impl Default for CompactionConfig {
@@ -50,6 +67,7 @@ impl Default for CompactionConfig {
             sst_threshold: DEFAULT_SST_THRESHOLD,
             check_interval_secs: 60, // Check every minute
             max_concurrent_compactions: 4, // Compact up to 4 stripes at once
+            io_rate_limit_bytes_per_sec: None, // Unthrottled
         }
     }
 }
@@ -85,6 +103,169 @@ impl CompactionConfig {
         self.max_concurrent_compactions = max.max(1);
         self
     }
+
+    /// Limit compaction and flush I/O to this many bytes per second (Phase 8+)
+    pub fn with_io_rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.io_rate_limit_bytes_per_sec = Some(bytes_per_sec.max(1));
+        self
+    }
+}
+
+/// Token-bucket limiter for compaction and flush I/O (Phase 8+)
+///
+/// Callers charge the bytes they read or write; once the bucket runs dry the
+/// caller sleeps until the budget catches up. The bucket holds at most one
+/// second of budget, so short bursts go through without waiting. Clones share
+/// the same bucket.
+#[derive(Clone)]
+pub struct IoThrottle {
+    state: Arc<Mutex<ThrottleState>>,
+}
+
+struct ThrottleState {
+    bytes_per_sec: Option<u64>,
+    available: f64,
+    last_refill: Instant,
+    window_start: Instant,
+    window_bytes: u64,
+    last_utilization: f64,
+    total_wait: Duration,
+}
+
+impl ThrottleState {
+    /// Close the utilization window once it is at least a second old
+    fn roll_window(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed >= UTILIZATION_WINDOW {
+            self.last_utilization = match self.bytes_per_sec {
+                Some(rate) => {
+                    (self.window_bytes as f64 / (rate as f64 * elapsed.as_secs_f64())).min(1.0)
+                }
+                None => 0.0,
+            };
+            self.window_start = now;
+            self.window_bytes = 0;
+        }
+    }
+}
+
+impl Default for IoThrottle {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl IoThrottle {
+    /// Create a throttle with a bytes/sec budget, or unlimited with `None`
+    pub fn new(bytes_per_sec: Option<u64>) -> Self {
+        let now = Instant::now();
+        let bytes_per_sec = bytes_per_sec.map(|rate| rate.max(1));
+        Self {
+            state: Arc::new(Mutex::new(ThrottleState {
+                bytes_per_sec,
+                available: bytes_per_sec.unwrap_or(0) as f64,
+                last_refill: now,
+                window_start: now,
+                window_bytes: 0,
+                last_utilization: 0.0,
+                total_wait: Duration::ZERO,
+            })),
+        }
+    }
+
+    /// Change the budget; the bucket starts full at the new rate
+    pub fn set_rate(&self, bytes_per_sec: Option<u64>) {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        state.roll_window(now);
+        state.bytes_per_sec = bytes_per_sec.map(|rate| rate.max(1));
+        state.available = state.bytes_per_sec.unwrap_or(0) as f64;
+        state.last_refill = now;
+    }
+
+    /// Current budget in bytes/sec, `None` if unlimited
+    pub fn rate(&self) -> Option<u64> {
+        self.state.lock().bytes_per_sec
+    }
+
+    /// Charge `bytes` of I/O, sleeping if the budget is exhausted
+    ///
+    /// The bytes are reserved before sleeping, so concurrent callers queue up
+    /// behind each other instead of all waking at once.
+    pub fn request(&self, bytes: u64) {
+        let wait = {
+            let mut state = self.state.lock();
+            let rate = match state.bytes_per_sec {
+                Some(rate) => rate as f64,
+                None => return,
+            };
+
+            let now = Instant::now();
+            state.roll_window(now);
+            state.window_bytes += bytes;
+
+            let refill = now.duration_since(state.last_refill).as_secs_f64() * rate;
+            state.available = (state.available + refill).min(rate) - bytes as f64;
+            state.last_refill = now;
+
+            if state.available >= 0.0 {
+                return;
+            }
+            let wait = Duration::from_secs_f64(-state.available / rate);
+            state.total_wait += wait;
+            wait
+        };
+
+        thread::sleep(wait);
+    }
+
+    /// Charge the size of a file on disk
+    pub fn request_file(&self, path: &Path) -> Result<u64> {
+        let bytes = fs::metadata(path)?.len();
+        self.request(bytes);
+        Ok(bytes)
+    }
+
+    /// Fraction of the budget used over the last second (0.0 to 1.0)
+    ///
+    /// Always 0.0 when the throttle is unlimited.
+    pub fn utilization(&self) -> f64 {
+        let mut state = self.state.lock();
+        state.roll_window(Instant::now());
+        state.last_utilization
+    }
+
+    /// Total time callers have spent waiting on the budget
+    pub fn total_wait(&self) -> Duration {
+        self.state.lock().total_wait
+    }
+}
+
+/// What a `CompactionFilter` wants done with an item (Phase 8+)
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterDecision {
+    /// Keep the item unchanged
+    Keep,
+    /// Drop the item from the compacted SST
+    Remove,
+    /// Replace the item's attributes, keeping its key and sequence number
+    ChangeValue(Item),
+}
+
+/// User hook invoked for every live item a compaction rewrites (Phase 8+)
+///
+/// Filters only see the newest version of each base-table item; tombstones and
+/// index entries are never passed in. Removing an item drops it without a
+/// tombstone, so an older version still sitting in another stripe's SSTs or in
+/// the memtable is unaffected.
+pub trait CompactionFilter: Send + Sync {
+    /// Decide what to do with an item
+    fn filter(&self, key: &Key, item: &Item) -> FilterDecision;
+
+    /// Name used in logs
+    fn name(&self) -> &str {
+        "CompactionFilter"
+    }
 }
 
 /// Statistics about compaction operations
@@ -105,7 +286,7 @@ pub struct CompactionStats {
     /// Total bytes written during compaction
     pub total_bytes_written: u64,
 
-    /// Total bytes reclaimed (deleted records)
+    /// Total bytes reclaimed (deleted records and dead value log files)
     pub total_bytes_reclaimed: u64,
 
     /// Total number of records deduplicated
@@ -114,6 +295,20 @@ pub struct CompactionStats {
     /// Total number of tombstones removed
     pub total_tombstones_removed: u64,
 
+    /// Total number of items removed by a compaction filter (Phase 8+)
+    pub total_records_filtered: u64,
+
+    /// Total number of items rewritten by a compaction filter (Phase 8+)
+    pub total_records_changed: u64,
+
+    /// Fraction of the I/O budget used over the last second (Phase 8+)
+    ///
+    /// 0.0 when compaction and flush I/O are unthrottled.
+    pub throttle_utilization: f64,
+
+    /// Total milliseconds compactions and flushes waited on the I/O budget (Phase 8+)
+    pub total_throttle_wait_ms: u64,
+
     /// Number of currently active compactions
     pub active_compactions: u64,
 }
@@ -129,6 +324,8 @@ pub struct CompactionStatsAtomic {
     total_bytes_reclaimed: Arc<AtomicU64>,
     total_records_deduplicated: Arc<AtomicU64>,
     total_tombstones_removed: Arc<AtomicU64>,
+    total_records_filtered: Arc<AtomicU64>,
+    total_records_changed: Arc<AtomicU64>,
     active_compactions: Arc<AtomicU64>,
 }
 
@@ -150,6 +347,8 @@ impl CompactionStatsAtomic {
             total_bytes_reclaimed: Arc::new(AtomicU64::new(0)),
             total_records_deduplicated: Arc::new(AtomicU64::new(0)),
             total_tombstones_removed: Arc::new(AtomicU64::new(0)),
+            total_records_filtered: Arc::new(AtomicU64::new(0)),
+            total_records_changed: Arc::new(AtomicU64::new(0)),
             active_compactions: Arc::new(AtomicU64::new(0)),
         }
     }
@@ -165,6 +364,11 @@ impl CompactionStatsAtomic {
             total_bytes_reclaimed: self.total_bytes_reclaimed.load(Ordering::Relaxed),
             total_records_deduplicated: self.total_records_deduplicated.load(Ordering::Relaxed),
             total_tombstones_removed: self.total_tombstones_removed.load(Ordering::Relaxed),
+            total_records_filtered: self.total_records_filtered.load(Ordering::Relaxed),
+            total_records_changed: self.total_records_changed.load(Ordering::Relaxed),
+            // Throttle figures live on the `IoThrottle`; the engine fills them in
+            throttle_utilization: 0.0,
+            total_throttle_wait_ms: 0,
             active_compactions: self.active_compactions.load(Ordering::Relaxed),
         }
     }
@@ -214,6 +418,18 @@ impl CompactionStatsAtomic {
         self.total_tombstones_removed
             .fetch_add(count, Ordering::Relaxed);
     }
+
+    /// Record items removed by a compaction filter
+    pub fn record_records_filtered(&self, count: u64) {
+        self.total_records_filtered
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Record items rewritten by a compaction filter
+    pub fn record_records_changed(&self, count: u64) {
+        self.total_records_changed
+            .fetch_add(count, Ordering::Relaxed);
+    }
 }
 
 /// RAII guard that decrements active compaction count on drop
@@ -233,12 +449,60 @@ impl Drop for CompactionGuard {
 pub struct CompactionManager {
     stripe_id: usize,
     dir: PathBuf,
+    filter: Option<Arc<dyn CompactionFilter>>,
+    stats: Option<CompactionStatsAtomic>,
+    throttle: IoThrottle,
+    value_log_threshold: Option<usize>,
+    keep_tombstones: bool,
 }
 
 impl CompactionManager {
     /// Create a new compaction manager
     pub fn new(stripe_id: usize, dir: PathBuf) -> Self {
-        Self { stripe_id, dir }
+        Self {
+            stripe_id,
+            dir,
+            filter: None,
+            stats: None,
+            throttle: IoThrottle::default(),
+            value_log_threshold: None,
+            keep_tombstones: false,
+        }
+    }
+
+    /// Keep tombstones, for compactions that leave older SSTs behind (Phase 8+)
+    ///
+    /// Items the filter removes become tombstones too, so versions in the
+    /// older SSTs stay hidden.
+    pub fn with_tombstones_kept(mut self, keep: bool) -> Self {
+        self.keep_tombstones = keep;
+        self
+    }
+
+    /// Move binary attributes of at least `threshold` bytes to the value log (Phase 8+)
+    ///
+    /// Attributes already in the value log stay there either way.
+    pub fn with_value_log_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.value_log_threshold = threshold;
+        self
+    }
+
+    /// Pace SST reads and writes through a shared I/O throttle (Phase 8+)
+    pub fn with_throttle(mut self, throttle: IoThrottle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Run a compaction filter over surviving items (Phase 8+)
+    pub fn with_filter(mut self, filter: Option<Arc<dyn CompactionFilter>>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Record filter activity into shared statistics (Phase 8+)
+    pub fn with_stats(mut self, stats: CompactionStatsAtomic) -> Self {
+        self.stats = Some(stats);
+        self
     }
 
     /// Check if compaction is needed for this stripe
@@ -251,9 +515,10 @@ impl CompactionManager {
     /// Algorithm:
     /// 1. Read all records from all input SSTs
     /// 2. Merge by key, keeping only the latest version (highest SeqNo)
-    /// 3. Filter out tombstones (deleted records)
-    /// 4. Write merged records to new SST
-    /// 5. Return new SST reader and paths of old SSTs to delete
+    /// 3. Filter out tombstones (deleted records), unless they are kept
+    /// 4. Apply the compaction filter, if any
+    /// 5. Write merged records to new SST, reusing their value log pointers
+    /// 6. Return new SST reader and paths of old SSTs to delete
     pub fn compact(
         &self,
         ssts: &[SstReader],
@@ -267,47 +532,66 @@ impl CompactionManager {
 
         // Step 1: Collect all records from all SSTs into a map
         // Key: encoded key, Value: latest record for that key
-        let mut records_by_key: BTreeMap<Vec<u8>, Record> = BTreeMap::new();
+        let mut records_by_key: BTreeMap<Vec<u8>, (Record, BlobRefs)> = BTreeMap::new();
 
         for sst in ssts {
-            for record in sst.scan()? {
+            let bytes_read = self.throttle.request_file(sst.path())?;
+            if let Some(stats) = &self.stats {
+                stats.record_bytes_read(bytes_read);
+            }
+
+            for (record, blobs) in sst.scan_with_blobs()? {
                 let encoded_key = record.key.encode().to_vec();
 
                 // Keep record with highest SeqNo (latest version)
                 records_by_key
                     .entry(encoded_key)
                     .and_modify(|existing| {
-                        if record.seq > existing.seq {
-                            *existing = record.clone();
+                        if record.seq > existing.0.seq {
+                            *existing = (record.clone(), blobs.clone());
                         }
                     })
-                    .or_insert(record);
+                    .or_insert((record, blobs));
             }
         }
 
         // Step 2: Filter out tombstones and collect records to write
-        let mut records_to_write: Vec<Record> = records_by_key
+        let mut records_to_write: Vec<(Record, BlobRefs)> = records_by_key
             .into_values()
-            .filter(|record| !record.is_tombstone())
+            .filter(|(record, _)| self.keep_tombstones || !record.is_tombstone())
             .collect();
 
+        // Step 3: Let the compaction filter drop or rewrite items
+        if let Some(filter) = &self.filter {
+            records_to_write = self.apply_filter(filter.as_ref(), records_to_write);
+        }
+
         // Sort by encoded key (already sorted from BTreeMap, but ensure consistency)
-        records_to_write.sort_by(|a, b| a.key.encode().cmp(&b.key.encode()));
+        records_to_write.sort_by(|(a, _), (b, _)| a.key.encode().cmp(&b.key.encode()));
 
-        // Step 3: Write new SST with compression settings
+        // Step 4: Write new SST with compression settings
         let new_sst_path = self.dir.join(format!("{:03}-{}.sst", self.stripe_id, next_sst_id));
         let mut writer = SstWriter::with_compression(compress, compression_level);
+        if let Some(threshold) = self.value_log_threshold {
+            let value_log = ValueLog::new(&self.dir).writer(self.stripe_id as u16, next_sst_id);
+            writer = writer.with_value_log(value_log, threshold);
+        }
 
-        for record in records_to_write {
-            writer.add(record);
+        for (record, blobs) in records_to_write {
+            writer.add_with_blobs(record, blobs);
         }
 
         writer.finish(&new_sst_path)?;
 
-        // Step 4: Open new SST reader
+        let bytes_written = self.throttle.request_file(&new_sst_path)?;
+        if let Some(stats) = &self.stats {
+            stats.record_bytes_written(bytes_written);
+        }
+
+        // Step 5: Open new SST reader
         let new_reader = SstReader::open(&new_sst_path)?;
 
-        // Step 5: Collect paths of old SSTs to delete
+        // Step 6: Collect paths of old SSTs to delete
         let old_sst_paths: Vec<PathBuf> = ssts
             .iter()
             .map(|sst| sst.path().to_path_buf())
@@ -316,6 +600,57 @@ impl CompactionManager {
         Ok((new_reader, old_sst_paths))
     }
 
+    /// Run the filter over live base-table records, leaving index entries alone
+    ///
+    /// Rewritten items drop their value log pointers, since the filter may
+    /// have changed those attributes; large ones are written out again.
+    fn apply_filter(&self, filter: &dyn CompactionFilter, records: Vec<(Record, BlobRefs)>) -> Vec<(Record, BlobRefs)> {
+        let mut filtered = 0u64;
+        let mut changed = 0u64;
+
+        let kept: Vec<(Record, BlobRefs)> = records
+            .into_iter()
+            .filter_map(|(mut record, blobs)| {
+                if is_index_key(&record.key.pk) {
+                    return Some((record, blobs));
+                }
+                let decision = match &record.value {
+                    Some(item) => filter.filter(&record.key, item),
+                    None => return Some((record, blobs)),
+                };
+                match decision {
+                    FilterDecision::Keep => Some((record, blobs)),
+                    FilterDecision::Remove => {
+                        filtered += 1;
+                        self.keep_tombstones
+                            .then(|| (Record::delete(record.key, record.seq), BlobRefs::new()))
+                    }
+                    FilterDecision::ChangeValue(item) => {
+                        changed += 1;
+                        record.value = Some(item);
+                        Some((record, BlobRefs::new()))
+                    }
+                }
+            })
+            .collect();
+
+        if filtered > 0 || changed > 0 {
+            tracing::debug!(
+                "{} on stripe {}: removed {}, changed {}",
+                filter.name(),
+                self.stripe_id,
+                filtered,
+                changed
+            );
+        }
+        if let Some(stats) = &self.stats {
+            stats.record_records_filtered(filtered);
+            stats.record_records_changed(changed);
+        }
+
+        kept
+    }
+
     /// Delete old SST files after successful compaction
     pub fn cleanup_old_ssts(&self, old_sst_paths: Vec<PathBuf>) -> Result<()> {
         for path in old_sst_paths {
@@ -327,6 +662,21 @@ impl CompactionManager {
         }
         Ok(())
     }
+
+    /// Delete value log files the compacted SSTs used that none of `live` points into (Phase 8+)
+    ///
+    /// `compacted` is the set of value log files referenced by the SSTs that
+    /// were merged. Must run after `cleanup_old_ssts`, so a crash never leaves
+    /// an SST on disk whose blobs are gone. Returns the number of bytes freed.
+    pub fn collect_value_log_garbage(&self, compacted: &BTreeSet<u64>, live: &[SstReader]) -> Result<u64> {
+        let referenced: BTreeSet<u64> = live.iter().flat_map(|sst| sst.value_log_files()).collect();
+        let freed = ValueLog::new(&self.dir)
+            .remove_files(self.stripe_id as u16, compacted.difference(&referenced))?;
+        if let Some(stats) = &self.stats {
+            stats.record_bytes_reclaimed(freed);
+        }
+        Ok(freed)
+    }
 }
 
 #[cfg(test)]
@@ -425,6 +775,89 @@ mod tests {
         assert_eq!(records[0].key.pk.as_ref(), b"key2");
     }
 
+    #[test]
+    fn test_compact_keeps_tombstones() {
+        let dir = TempDir::new().unwrap();
+        let manager = CompactionManager::new(0, dir.path().to_path_buf())
+            .with_tombstones_kept(true)
+            .with_filter(Some(Arc::new(SoftDeleteFilter)));
+
+        let sst1_path = dir.path().join("000-1.sst");
+        let mut writer1 = SstWriter::new();
+        writer1.add(create_test_record(b"key1", 1, "v1"));
+        writer1.add(create_test_record(b"key2", 1, "deleted"));
+        writer1.finish(&sst1_path).unwrap();
+
+        let sst2_path = dir.path().join("000-2.sst");
+        let mut writer2 = SstWriter::new();
+        writer2.add(create_delete_record(b"key1", 2));
+        writer2.finish(&sst2_path).unwrap();
+
+        let sst1 = SstReader::open(&sst1_path).unwrap();
+        let sst2 = SstReader::open(&sst2_path).unwrap();
+        let (new_sst, _) = manager.compact(&[sst1, sst2], 3, false, 3).unwrap();
+
+        // Both keys stay deleted, shadowing any older SSTs left out of the merge
+        let records: Vec<Record> = new_sst.scan().unwrap().collect();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.is_tombstone()));
+    }
+
+    /// Drops items marked deleted and upper-cases everything else
+    struct SoftDeleteFilter;
+
+    impl CompactionFilter for SoftDeleteFilter {
+        fn filter(&self, _key: &Key, item: &crate::Item) -> FilterDecision {
+            match item.get("value").and_then(|value| value.as_string()) {
+                Some("deleted") => FilterDecision::Remove,
+                Some(value) => {
+                    let mut item = item.clone();
+                    item.insert("value".to_string(), Value::string(value.to_uppercase()));
+                    FilterDecision::ChangeValue(item)
+                }
+                None => FilterDecision::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_applies_filter() {
+        let dir = TempDir::new().unwrap();
+        let stats = CompactionStatsAtomic::new();
+        let manager = CompactionManager::new(0, dir.path().to_path_buf())
+            .with_filter(Some(Arc::new(SoftDeleteFilter)))
+            .with_stats(stats.clone());
+
+        let sst1_path = dir.path().join("000-1.sst");
+        let mut writer1 = SstWriter::new();
+        writer1.add(create_test_record(b"key1", 1, "v1"));
+        writer1.add(create_test_record(b"key2", 1, "v1"));
+        writer1.finish(&sst1_path).unwrap();
+
+        // key2 is soft-deleted by a newer version
+        let sst2_path = dir.path().join("000-2.sst");
+        let mut writer2 = SstWriter::new();
+        writer2.add(create_test_record(b"key2", 2, "deleted"));
+        writer2.finish(&sst2_path).unwrap();
+
+        let sst1 = SstReader::open(&sst1_path).unwrap();
+        let sst2 = SstReader::open(&sst2_path).unwrap();
+        let (new_sst, _) = manager.compact(&[sst1, sst2], 3, false, 3).unwrap();
+
+        let records: Vec<Record> = new_sst.scan().unwrap().collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key.pk.as_ref(), b"key1");
+        assert_eq!(records[0].seq, 1);
+        assert_eq!(
+            records[0].value.as_ref().unwrap().get("value").unwrap().as_string(),
+            Some("V1")
+        );
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_records_filtered, 1);
+        assert_eq!(snapshot.total_records_changed, 1);
+    }
+
     #[test]
     fn test_compact_empty_result() {
         let dir = TempDir::new().unwrap();
@@ -445,6 +878,52 @@ mod tests {
         assert_eq!(records.len(), 0);
     }
 
+    #[test]
+    fn test_compact_value_log_garbage() {
+        let dir = TempDir::new().unwrap();
+        let stats = CompactionStatsAtomic::new();
+        let manager = CompactionManager::new(0, dir.path().to_path_buf())
+            .with_value_log_threshold(Some(1024))
+            .with_stats(stats.clone());
+        let vlog = ValueLog::new(dir.path());
+
+        let blob_record = |seq: SeqNo, byte: u8| {
+            let mut item = HashMap::new();
+            item.insert("data".to_string(), Value::B(vec![byte; 2048].into()));
+            Record::put(Key::new(b"key1".to_vec()), item, seq)
+        };
+
+        // The second SST overwrites the blob written by the first
+        let sst1_path = dir.path().join("000-1.sst");
+        let mut writer1 = SstWriter::new().with_value_log(vlog.writer(0, 1), 1024);
+        writer1.add(blob_record(1, 1));
+        writer1.finish(&sst1_path).unwrap();
+
+        let sst2_path = dir.path().join("000-2.sst");
+        let mut writer2 = SstWriter::new().with_value_log(vlog.writer(0, 2), 1024);
+        writer2.add(blob_record(2, 2));
+        writer2.finish(&sst2_path).unwrap();
+
+        let sst1 = SstReader::open(&sst1_path).unwrap();
+        let sst2 = SstReader::open(&sst2_path).unwrap();
+        let compacted: BTreeSet<u64> = sst1.value_log_files().union(&sst2.value_log_files()).copied().collect();
+        let (new_sst, old_paths) = manager.compact(&[sst1, sst2], 3, false, 3).unwrap();
+
+        // The surviving blob is referenced in place, not rewritten
+        assert_eq!(new_sst.value_log_files(), BTreeSet::from([2]));
+        assert_eq!(vlog.files(0).unwrap(), BTreeSet::from([1, 2]));
+
+        manager.cleanup_old_ssts(old_paths).unwrap();
+        let freed = manager.collect_value_log_garbage(&compacted, std::slice::from_ref(&new_sst)).unwrap();
+        assert_eq!(freed, 2048);
+        assert_eq!(vlog.files(0).unwrap(), BTreeSet::from([2]));
+        assert_eq!(stats.snapshot().total_bytes_reclaimed, 2048);
+
+        let reopened = SstReader::open(dir.path().join("000-3.sst")).unwrap();
+        let record = reopened.get(&Key::new(b"key1".to_vec())).unwrap().unwrap();
+        assert_eq!(record.value.as_ref().unwrap().get("data"), Some(&Value::B(vec![2u8; 2048].into())));
+    }
+
     #[test]
     fn test_cleanup_old_ssts() {
         let dir = TempDir::new().unwrap();
@@ -525,6 +1004,81 @@ mod tests {
         assert_eq!(config.max_concurrent_compactions, 2);
     }
 
+    #[test]
+    fn test_compaction_config_io_rate_limit() {
+        assert_eq!(CompactionConfig::default().io_rate_limit_bytes_per_sec, None);
+
+        let config = CompactionConfig::new().with_io_rate_limit(4 * 1024 * 1024);
+        assert_eq!(config.io_rate_limit_bytes_per_sec, Some(4 * 1024 * 1024));
+
+        // A zero budget would never refill
+        let config = CompactionConfig::new().with_io_rate_limit(0);
+        assert_eq!(config.io_rate_limit_bytes_per_sec, Some(1));
+    }
+
+    #[test]
+    fn test_io_throttle_unlimited() {
+        let throttle = IoThrottle::default();
+        let start = Instant::now();
+        throttle.request(u64::MAX / 2);
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert_eq!(throttle.total_wait(), Duration::ZERO);
+        assert_eq!(throttle.utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_io_throttle_limits_rate() {
+        let throttle = IoThrottle::new(Some(10_000));
+
+        // The bucket starts with one second of budget
+        let start = Instant::now();
+        throttle.request(10_000);
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // Anything beyond it waits for the budget to refill
+        throttle.request(2_000);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert!(throttle.total_wait() >= Duration::from_millis(150));
+
+        // Once the window closes, utilization reflects the bytes charged
+        thread::sleep(UTILIZATION_WINDOW);
+        let utilization = throttle.utilization();
+        assert!(utilization > 0.5 && utilization <= 1.0, "utilization {}", utilization);
+
+        throttle.set_rate(None);
+        assert_eq!(throttle.rate(), None);
+    }
+
+    #[test]
+    fn test_compact_records_bytes() {
+        let dir = TempDir::new().unwrap();
+        let stats = CompactionStatsAtomic::new();
+        let throttle = IoThrottle::new(Some(1024 * 1024));
+        let manager = CompactionManager::new(0, dir.path().to_path_buf())
+            .with_stats(stats.clone())
+            .with_throttle(throttle);
+
+        let sst1_path = dir.path().join("000-1.sst");
+        let mut writer1 = SstWriter::new();
+        writer1.add(create_test_record(b"key1", 1, "v1"));
+        writer1.finish(&sst1_path).unwrap();
+
+        let sst2_path = dir.path().join("000-2.sst");
+        let mut writer2 = SstWriter::new();
+        writer2.add(create_test_record(b"key2", 2, "v1"));
+        writer2.finish(&sst2_path).unwrap();
+
+        let input_bytes = fs::metadata(&sst1_path).unwrap().len() + fs::metadata(&sst2_path).unwrap().len();
+
+        let sst1 = SstReader::open(&sst1_path).unwrap();
+        let sst2 = SstReader::open(&sst2_path).unwrap();
+        let (new_sst, _) = manager.compact(&[sst1, sst2], 3, false, 3).unwrap();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_bytes_read, input_bytes);
+        assert_eq!(snapshot.total_bytes_written, fs::metadata(new_sst.path()).unwrap().len());
+    }
+
     #[test]
     fn test_compaction_stats_atomic_snapshot() {
         let stats = CompactionStatsAtomic::new();
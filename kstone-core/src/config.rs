@@ -1,3 +1,47 @@
+use crate::block_cache::DEFAULT_BLOCK_CACHE_BYTES;
+use std::time::Duration;
+
+/// Default interval between background TTL reaper passes
+pub const DEFAULT_TTL_REAPER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default number of full memtables that may wait for a background flush
+pub const DEFAULT_MAX_PENDING_FLUSHES: usize = 16;
+
+/// Default largest item accepted by a write (see `types::item_size`)
+pub const DEFAULT_MAX_ITEM_SIZE_BYTES: usize = 1024 * 1024;
+
+/// Default largest key (partition key plus sort key) accepted by a write
+pub const DEFAULT_MAX_KEY_SIZE_BYTES: usize = 64 * 1024;
+
+/// Interval between background passes that offload cold SSTs (Phase 8+)
+pub const COLD_OFFLOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When WAL writes are forced to disk (Phase 8+)
+///
+/// Durability tradeoffs:
+/// - `Always`: every write is fsynced before it is acknowledged. Nothing
+///   acknowledged is ever lost. Concurrent writers share fsyncs (group
+///   commit), but each write still waits for the disk.
+/// - `EveryNms(n)`: the WAL is fsynced at most every `n` milliseconds, by
+///   writers or a background thread. A crash (power loss, kernel panic) can
+///   lose up to the last `n` ms of acknowledged writes; a process crash
+///   loses nothing because the data is already in the OS page cache.
+/// - `Os`: the WAL is never fsynced explicitly (except on flush, backup and
+///   close). Fastest, but a machine crash can lose any write the OS has not
+///   yet written back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WalSyncMode {
+    /// Fsync on every write (default)
+    #[default]
+    Always,
+
+    /// Fsync at most every N milliseconds
+    EveryNms(u64),
+
+    /// Leave write-back to the operating system
+    Os,
+}
+
 /// Database configuration for resource limits and operational parameters
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
@@ -22,6 +66,42 @@ pub struct DatabaseConfig {
     /// Compression level (1-22, where 1 is fastest, 22 is best compression)
     /// Default: 3 (balanced speed/ratio)
     pub compression_level: i32,
+
+    /// Interval between background passes that delete expired TTL items
+    /// (None = expired items are only hidden on read, never reclaimed)
+    pub ttl_reaper_interval: Option<Duration>,
+
+    /// Capacity of the shared block cache in bytes (0 = disabled)
+    pub block_cache_bytes: usize,
+
+    /// When WAL writes are forced to disk
+    pub wal_sync_mode: WalSyncMode,
+
+    /// Full memtables that may wait for the background flush thread before
+    /// writers stall and flush inline (0 = always flush inline)
+    pub max_pending_flushes: usize,
+
+    /// Largest item a write may store, in bytes (None = unlimited)
+    pub max_item_size_bytes: Option<usize>,
+
+    /// Largest key a write may use, in bytes (None = unlimited)
+    pub max_key_size_bytes: Option<usize>,
+
+    /// Binary attributes at least this large are stored in the value log
+    /// instead of SSTs (None = keep everything in SSTs)
+    pub value_log_threshold_bytes: Option<usize>,
+
+    /// SSTs older than this are offloaded to the attached cold store
+    /// (None = keep every SST local; see `tiering`)
+    pub cold_sst_age: Option<Duration>,
+
+    /// Emit `tracing` spans for puts, gets, queries, scans, flushes and
+    /// compactions (off by default; spans are only recorded by a subscriber)
+    pub trace_operations: bool,
+
+    /// Operations taking at least this long are recorded in the slow
+    /// operation log (None = log off; see `slow_log`)
+    pub slow_operation_threshold: Option<Duration>,
 }
 
 impl Default for DatabaseConfig {
@@ -36,6 +116,16 @@ impl Default for DatabaseConfig {
             write_buffer_size: 1024,
             compression_enabled: false,
             compression_level: 3,
+            ttl_reaper_interval: Some(DEFAULT_TTL_REAPER_INTERVAL),
+            block_cache_bytes: DEFAULT_BLOCK_CACHE_BYTES,
+            wal_sync_mode: WalSyncMode::Always,
+            max_pending_flushes: DEFAULT_MAX_PENDING_FLUSHES,
+            max_item_size_bytes: Some(DEFAULT_MAX_ITEM_SIZE_BYTES),
+            max_key_size_bytes: Some(DEFAULT_MAX_KEY_SIZE_BYTES),
+            value_log_threshold_bytes: None,
+            cold_sst_age: None,
+            trace_operations: false,
+            slow_operation_threshold: None,
         }
     }
 }
@@ -91,6 +181,79 @@ impl DatabaseConfig {
         self
     }
 
+    /// Set how often the background TTL reaper deletes expired items
+    pub fn with_ttl_reaper_interval(mut self, interval: Duration) -> Self {
+        self.ttl_reaper_interval = Some(interval);
+        self
+    }
+
+    /// Disable the background TTL reaper (expired items are still hidden on read)
+    pub fn without_ttl_reaper(mut self) -> Self {
+        self.ttl_reaper_interval = None;
+        self
+    }
+
+    /// Set the block cache capacity in bytes (0 disables the cache)
+    pub fn with_block_cache_bytes(mut self, bytes: usize) -> Self {
+        self.block_cache_bytes = bytes;
+        self
+    }
+
+    /// Set when WAL writes are forced to disk (see `WalSyncMode` for the tradeoffs)
+    pub fn with_wal_sync_mode(mut self, mode: WalSyncMode) -> Self {
+        self.wal_sync_mode = mode;
+        self
+    }
+
+    /// Set how many full memtables may queue for a background flush (0 flushes inline)
+    pub fn with_max_pending_flushes(mut self, flushes: usize) -> Self {
+        self.max_pending_flushes = flushes;
+        self
+    }
+
+    /// Set the largest item a write may store, in bytes
+    pub fn with_max_item_size_bytes(mut self, size: usize) -> Self {
+        self.max_item_size_bytes = Some(size);
+        self
+    }
+
+    /// Set the largest key a write may use, in bytes
+    pub fn with_max_key_size_bytes(mut self, size: usize) -> Self {
+        self.max_key_size_bytes = Some(size);
+        self
+    }
+
+    /// Store binary attributes of at least `size` bytes in the value log
+    pub fn with_value_log_threshold_bytes(mut self, size: usize) -> Self {
+        self.value_log_threshold_bytes = Some(size);
+        self
+    }
+
+    /// Offload SSTs older than `age` to the attached cold store
+    pub fn with_cold_sst_age(mut self, age: Duration) -> Self {
+        self.cold_sst_age = Some(age);
+        self
+    }
+
+    /// Enable or disable `tracing` spans for engine operations
+    pub fn with_trace_operations(mut self, enabled: bool) -> Self {
+        self.trace_operations = enabled;
+        self
+    }
+
+    /// Record operations taking at least `threshold` in the slow operation log
+    pub fn with_slow_operation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_operation_threshold = Some(threshold);
+        self
+    }
+
+    /// Accept items and keys of any size
+    pub fn without_size_limits(mut self) -> Self {
+        self.max_item_size_bytes = None;
+        self.max_key_size_bytes = None;
+        self
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> Result<(), String> {
         if self.max_memtable_records == 0 {
@@ -111,6 +274,22 @@ impl DatabaseConfig {
             return Err("compression_level must be between 1 and 22".to_string());
         }
 
+        if self.ttl_reaper_interval == Some(Duration::ZERO) {
+            return Err("ttl_reaper_interval must be greater than 0 when set".to_string());
+        }
+
+        if self.wal_sync_mode == WalSyncMode::EveryNms(0) {
+            return Err("wal_sync_mode interval must be greater than 0".to_string());
+        }
+
+        if self.max_item_size_bytes == Some(0) || self.max_key_size_bytes == Some(0) {
+            return Err("max_item_size_bytes and max_key_size_bytes must be greater than 0 when set".to_string());
+        }
+
+        if self.value_log_threshold_bytes == Some(0) {
+            return Err("value_log_threshold_bytes must be greater than 0 when set".to_string());
+        }
+
         Ok(())
     }
 }
@@ -160,4 +339,87 @@ mod tests {
         let config = DatabaseConfig::new().with_write_buffer_size(0);
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_size_limits() {
+        let config = DatabaseConfig::default();
+        assert_eq!(config.max_item_size_bytes, Some(DEFAULT_MAX_ITEM_SIZE_BYTES));
+        assert_eq!(config.max_key_size_bytes, Some(DEFAULT_MAX_KEY_SIZE_BYTES));
+
+        let config = DatabaseConfig::new().without_size_limits();
+        assert!(config.max_item_size_bytes.is_none());
+        assert!(config.max_key_size_bytes.is_none());
+
+        assert!(DatabaseConfig::new().with_max_item_size_bytes(0).validate().is_err());
+    }
+
+    #[test]
+    fn test_value_log_threshold() {
+        assert!(DatabaseConfig::default().value_log_threshold_bytes.is_none());
+
+        let config = DatabaseConfig::new().with_value_log_threshold_bytes(64 * 1024);
+        assert_eq!(config.value_log_threshold_bytes, Some(64 * 1024));
+        assert!(config.validate().is_ok());
+
+        assert!(DatabaseConfig::new().with_value_log_threshold_bytes(0).validate().is_err());
+    }
+
+    #[test]
+    fn test_trace_operations() {
+        assert!(!DatabaseConfig::default().trace_operations);
+        assert!(DatabaseConfig::new().with_trace_operations(true).trace_operations);
+    }
+
+    #[test]
+    fn test_slow_operation_threshold() {
+        assert!(DatabaseConfig::default().slow_operation_threshold.is_none());
+
+        let config = DatabaseConfig::new().with_slow_operation_threshold(Duration::from_millis(100));
+        assert_eq!(config.slow_operation_threshold, Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_cold_sst_age() {
+        assert!(DatabaseConfig::default().cold_sst_age.is_none());
+
+        let config = DatabaseConfig::new().with_cold_sst_age(Duration::from_secs(86400));
+        assert_eq!(config.cold_sst_age, Some(Duration::from_secs(86400)));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ttl_reaper_interval() {
+        let config = DatabaseConfig::default();
+        assert_eq!(config.ttl_reaper_interval, Some(DEFAULT_TTL_REAPER_INTERVAL));
+
+        let config = DatabaseConfig::new().with_ttl_reaper_interval(Duration::from_secs(5));
+        assert_eq!(config.ttl_reaper_interval, Some(Duration::from_secs(5)));
+        assert!(config.validate().is_ok());
+
+        assert!(DatabaseConfig::new().without_ttl_reaper().ttl_reaper_interval.is_none());
+        assert!(DatabaseConfig::new().with_ttl_reaper_interval(Duration::ZERO).validate().is_err());
+    }
+
+    #[test]
+    fn test_block_cache_bytes() {
+        assert_eq!(DatabaseConfig::default().block_cache_bytes, DEFAULT_BLOCK_CACHE_BYTES);
+        assert_eq!(DatabaseConfig::new().with_block_cache_bytes(0).block_cache_bytes, 0);
+    }
+
+    #[test]
+    fn test_wal_sync_mode() {
+        assert_eq!(DatabaseConfig::default().wal_sync_mode, WalSyncMode::Always);
+
+        let config = DatabaseConfig::new().with_wal_sync_mode(WalSyncMode::EveryNms(10));
+        assert_eq!(config.wal_sync_mode, WalSyncMode::EveryNms(10));
+        assert!(config.validate().is_ok());
+
+        assert!(DatabaseConfig::new().with_wal_sync_mode(WalSyncMode::EveryNms(0)).validate().is_err());
+    }
+
+    #[test]
+    fn test_max_pending_flushes() {
+        assert_eq!(DatabaseConfig::default().max_pending_flushes, DEFAULT_MAX_PENDING_FLUSHES);
+        assert_eq!(DatabaseConfig::new().with_max_pending_flushes(0).max_pending_flushes, 0);
+    }
 }
@@ -62,6 +62,43 @@ pub enum Error {
     // Phase 8 additions
     #[error("Resource exhausted: {0}")]
     ResourceExhausted(String),
+
+    // Phase 3.4 additions
+    #[error("Trimmed data access: {0}")]
+    TrimmedDataAccess(String),
+
+    #[error("Database is read-only: {0}")]
+    ReadOnly(String),
+
+    #[error(
+        "Database is locked by another process{}: {path}",
+        .pid.map(|pid| format!(" (pid {})", pid)).unwrap_or_default()
+    )]
+    DatabaseLocked { path: String, pid: Option<u32> },
+
+    #[error(
+        "Version conflict: expected {}, found {}",
+        describe_version(.expected),
+        describe_version(.actual)
+    )]
+    VersionConflict { expected: Option<u64>, actual: Option<u64> },
+
+    #[error("Item too large: {size} bytes exceeds the limit of {limit} bytes")]
+    ItemTooLarge { size: usize, limit: usize },
+
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+
+    #[error("Validation failed: {0}")]
+    ValidationFailed(crate::validation::ValidationError),
+}
+
+/// Describe an item version for `Error::VersionConflict`
+fn describe_version(version: &Option<u64>) -> String {
+    match version {
+        Some(version) => format!("version {}", version),
+        None => "no item".to_string(),
+    }
 }
 
 impl Error {
@@ -87,6 +124,13 @@ impl Error {
             Error::TransactionCanceled(_) => "TRANSACTION_CANCELED",
             Error::InvalidQuery(_) => "INVALID_QUERY",
             Error::ResourceExhausted(_) => "RESOURCE_EXHAUSTED",
+            Error::TrimmedDataAccess(_) => "TRIMMED_DATA_ACCESS",
+            Error::ReadOnly(_) => "READ_ONLY",
+            Error::DatabaseLocked { .. } => "DATABASE_LOCKED",
+            Error::VersionConflict { .. } => "VERSION_CONFLICT",
+            Error::ItemTooLarge { .. } => "ITEM_TOO_LARGE",
+            Error::DeadlineExceeded(_) => "DEADLINE_EXCEEDED",
+            Error::ValidationFailed(_) => "VALIDATION_FAILED",
         }
     }
 
@@ -102,6 +146,7 @@ impl Error {
             Error::ResourceExhausted(_) => true,
             Error::CompactionError(_) => true,
             Error::StripeError(_) => true,
+            Error::DeadlineExceeded(_) => true,
 
             // Non-retryable errors (logical/permanent)
             Error::Corruption(_) => false,
@@ -117,6 +162,12 @@ impl Error {
             Error::ConditionalCheckFailed(_) => false,
             Error::TransactionCanceled(_) => false,
             Error::InvalidQuery(_) => false,
+            Error::TrimmedDataAccess(_) => false,
+            Error::ReadOnly(_) => false,
+            Error::DatabaseLocked { .. } => false,
+            Error::VersionConflict { .. } => false,
+            Error::ItemTooLarge { .. } => false,
+            Error::ValidationFailed(_) => false,
         }
     }
 
@@ -2,8 +2,11 @@
 ///
 /// Supports:
 /// - **Condition expressions**: =, <>, <, <=, >, >=, AND, OR, NOT, functions
-/// - **Update expressions**: SET, REMOVE, ADD, DELETE actions
+///   (`attribute_exists`, `attribute_not_exists`, `begins_with`, `contains`, `size`)
+/// - **Update expressions**: SET, REMOVE, ADD, DELETE actions, with
+///   `list_append` and `if_not_exists` in SET values
 /// - Attribute paths and value placeholders
+/// - Nested document paths (`profile.address.city`, `tags[2]`)
 ///
 /// # Condition Expression Examples
 ///
@@ -56,6 +59,9 @@ pub enum Expr {
     AttributeExists(String),
     AttributeNotExists(String),
     BeginsWith(Box<Expr>, Box<Expr>),
+    Contains(Box<Expr>, Box<Expr>),
+    /// size(path) - operand that evaluates to a number
+    Size(Box<Expr>),
 
     // Operands
     AttributePath(String),
@@ -88,6 +94,228 @@ impl ExpressionContext {
     }
 }
 
+/// Evaluate an optional write condition against the current item (Phase 2.5+)
+///
+/// A missing item is evaluated as an empty item. Returns
+/// `Error::ConditionalCheckFailed` with `message` if the condition is false.
+pub(crate) fn check_condition(
+    current: Option<&Item>,
+    condition: Option<(&Expr, &ExpressionContext)>,
+    message: &str,
+) -> Result<()> {
+    if let Some((condition, context)) = condition {
+        let empty = Item::new();
+        let evaluator = ExpressionEvaluator::new(current.unwrap_or(&empty), context);
+        if !evaluator.evaluate(condition)? {
+            return Err(Error::ConditionalCheckFailed(message.into()));
+        }
+    }
+    Ok(())
+}
+
+/// One step of a document path
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Map key (or top-level attribute name)
+    Attribute(String),
+    /// List index
+    Index(usize),
+}
+
+/// Document path into an item, e.g. `profile.address.city` or `tags[2]` (Phase 2.4+)
+///
+/// Paths are parsed from the canonical string form produced by the expression
+/// parsers. `#name` placeholders are resolved per segment, so an attribute
+/// name containing a dot can still be addressed through a placeholder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentPath {
+    segments: Vec<PathSegment>,
+}
+
+impl DocumentPath {
+    /// Parse a path string, resolving `#name` placeholders from the context
+    pub fn parse(path: &str, context: &ExpressionContext) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut chars = path.chars().peekable();
+        let mut name = String::new();
+        // A name is expected at the start and after every '.'
+        let mut expect_name = true;
+
+        let invalid = || Error::InvalidExpression(format!("Invalid document path: {}", path));
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '.' | '[' => {
+                    if expect_name {
+                        if name.is_empty() {
+                            return Err(invalid());
+                        }
+                        segments.push(PathSegment::Attribute(Self::resolve_name(&name, context)));
+                        name.clear();
+                    }
+
+                    if ch == '.' {
+                        expect_name = true;
+                    } else {
+                        let mut digits = String::new();
+                        loop {
+                            match chars.next() {
+                                Some(']') => break,
+                                Some(d) if d.is_ascii_digit() => digits.push(d),
+                                _ => return Err(invalid()),
+                            }
+                        }
+                        let index = digits.parse().map_err(|_| invalid())?;
+                        segments.push(PathSegment::Index(index));
+                        expect_name = false;
+                        if !matches!(chars.peek(), None | Some('.') | Some('[')) {
+                            return Err(invalid());
+                        }
+                    }
+                }
+                _ if expect_name => name.push(ch),
+                _ => return Err(invalid()),
+            }
+        }
+
+        if expect_name {
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            segments.push(PathSegment::Attribute(Self::resolve_name(&name, context)));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn resolve_name(name: &str, context: &ExpressionContext) -> String {
+        if name.starts_with('#') {
+            context.names.get(name).cloned().unwrap_or_else(|| name.to_string())
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Path segments, starting with the top-level attribute
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Look up the value at this path
+    pub fn get<'a>(&self, item: &'a Item) -> Option<&'a Value> {
+        let (first, rest) = self.segments.split_first()?;
+        let mut current = match first {
+            PathSegment::Attribute(name) => item.get(name)?,
+            PathSegment::Index(_) => return None,
+        };
+        for segment in rest {
+            current = match (segment, current) {
+                (PathSegment::Attribute(name), Value::M(map)) => map.get(name)?,
+                (PathSegment::Index(index), Value::L(list)) => list.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Set the value at this path
+    ///
+    /// Missing intermediate maps are created. Setting a list index past the
+    /// end appends to the list, as in DynamoDB. Traversing through a value of
+    /// the wrong type is an error.
+    pub fn set(&self, item: &mut Item, value: Value) -> Result<()> {
+        let (first, rest) = match self.segments.split_first() {
+            Some((PathSegment::Attribute(name), rest)) => (name, rest),
+            _ => return Err(Error::InvalidExpression("Document path must start with an attribute name".into())),
+        };
+
+        if rest.is_empty() {
+            item.insert(first.clone(), value);
+            return Ok(());
+        }
+
+        let entry = item.entry(first.clone()).or_insert_with(|| Self::container_for(&rest[0]));
+        Self::set_in(entry, rest, value)
+    }
+
+    fn set_in(current: &mut Value, segments: &[PathSegment], value: Value) -> Result<()> {
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                *current = value;
+                return Ok(());
+            }
+        };
+
+        let child = match (segment, current) {
+            (PathSegment::Attribute(name), Value::M(map)) => {
+                if rest.is_empty() {
+                    map.insert(name.clone(), value);
+                    return Ok(());
+                }
+                map.entry(name.clone()).or_insert_with(|| Self::container_for(&rest[0]))
+            }
+            (PathSegment::Index(index), Value::L(list)) => {
+                if *index >= list.len() {
+                    if !rest.is_empty() {
+                        return Err(Error::InvalidExpression(format!("List index {} out of range", index)));
+                    }
+                    list.push(value);
+                    return Ok(());
+                }
+                &mut list[*index]
+            }
+            (PathSegment::Attribute(name), _) => {
+                return Err(Error::InvalidExpression(format!("Cannot set '{}': parent is not a map", name)));
+            }
+            (PathSegment::Index(index), _) => {
+                return Err(Error::InvalidExpression(format!("Cannot set [{}]: parent is not a list", index)));
+            }
+        };
+
+        Self::set_in(child, rest, value)
+    }
+
+    /// Empty container to create for a missing intermediate value
+    fn container_for(next: &PathSegment) -> Value {
+        match next {
+            PathSegment::Attribute(_) => Value::M(HashMap::new()),
+            PathSegment::Index(_) => Value::L(Vec::new()),
+        }
+    }
+
+    /// Remove the value at this path, returning it if it existed
+    ///
+    /// Removing a list element shifts later elements down.
+    pub fn remove(&self, item: &mut Item) -> Option<Value> {
+        let (first, rest) = self.segments.split_first()?;
+        let name = match first {
+            PathSegment::Attribute(name) => name,
+            PathSegment::Index(_) => return None,
+        };
+
+        if rest.is_empty() {
+            return item.remove(name);
+        }
+
+        let mut current = item.get_mut(name)?;
+        let (last, parents) = rest.split_last()?;
+        for segment in parents {
+            current = match (segment, current) {
+                (PathSegment::Attribute(name), Value::M(map)) => map.get_mut(name)?,
+                (PathSegment::Index(index), Value::L(list)) => list.get_mut(*index)?,
+                _ => return None,
+            };
+        }
+
+        match (last, current) {
+            (PathSegment::Attribute(name), Value::M(map)) => map.remove(name),
+            (PathSegment::Index(index), Value::L(list)) if *index < list.len() => Some(list.remove(*index)),
+            _ => None,
+        }
+    }
+}
+
 /// Expression evaluator
 pub struct ExpressionEvaluator<'a> {
     item: &'a Item,
@@ -142,12 +370,12 @@ impl<'a> ExpressionEvaluator<'a> {
                 Ok(!self.evaluate(expr)?)
             }
             Expr::AttributeExists(path) => {
-                let attr_name = self.resolve_attribute_name(path);
-                Ok(self.item.contains_key(&attr_name))
+                let path = DocumentPath::parse(path, self.context)?;
+                Ok(path.get(self.item).is_some())
             }
             Expr::AttributeNotExists(path) => {
-                let attr_name = self.resolve_attribute_name(path);
-                Ok(!self.item.contains_key(&attr_name))
+                let path = DocumentPath::parse(path, self.context)?;
+                Ok(path.get(self.item).is_none())
             }
             Expr::BeginsWith(path_expr, value_expr) => {
                 let path_value = self.resolve_value(path_expr)?;
@@ -159,7 +387,24 @@ impl<'a> ExpressionEvaluator<'a> {
                     _ => Err(Error::InvalidExpression("begins_with requires string or binary operands".into()))
                 }
             }
-            Expr::AttributePath(_) | Expr::ValuePlaceholder(_) | Expr::Literal(_) => {
+            Expr::Contains(path_expr, value_expr) => {
+                // A missing attribute contains nothing
+                let path_value = match self.resolve_optional_value(path_expr)? {
+                    Some(value) => value,
+                    None => return Ok(false),
+                };
+                let operand = self.resolve_value(value_expr)?;
+
+                match (&path_value, &operand) {
+                    (Value::S(s), Value::S(sub)) => Ok(s.contains(sub.as_str())),
+                    (Value::B(b), Value::B(sub)) => {
+                        Ok(sub.is_empty() || b.windows(sub.len()).any(|window| window == sub.as_ref()))
+                    }
+                    (Value::L(list), element) => Ok(list.contains(element)),
+                    _ => Ok(false),
+                }
+            }
+            Expr::AttributePath(_) | Expr::ValuePlaceholder(_) | Expr::Literal(_) | Expr::Size(_) => {
                 Err(Error::InvalidExpression("Cannot evaluate operand as boolean expression".into()))
             }
         }
@@ -169,10 +414,10 @@ impl<'a> ExpressionEvaluator<'a> {
     fn resolve_value(&self, expr: &Expr) -> Result<Value> {
         match expr {
             Expr::AttributePath(path) => {
-                let attr_name = self.resolve_attribute_name(path);
-                self.item.get(&attr_name)
+                DocumentPath::parse(path, self.context)?
+                    .get(self.item)
                     .cloned()
-                    .ok_or_else(|| Error::InvalidExpression(format!("Attribute '{}' not found", attr_name)))
+                    .ok_or_else(|| Error::InvalidExpression(format!("Attribute '{}' not found", path)))
             }
             Expr::ValuePlaceholder(placeholder) => {
                 self.context.values.get(placeholder)
@@ -180,18 +425,27 @@ impl<'a> ExpressionEvaluator<'a> {
                     .ok_or_else(|| Error::InvalidExpression(format!("Value placeholder '{}' not found", placeholder)))
             }
             Expr::Literal(value) => Ok(value.clone()),
+            Expr::Size(path_expr) => {
+                let value = self.resolve_value(path_expr)?;
+                let size = match &value {
+                    Value::S(s) => s.len(),
+                    Value::B(b) => b.len(),
+                    Value::L(list) => list.len(),
+                    Value::M(map) => map.len(),
+                    Value::VecF32(v) => v.len(),
+                    _ => return Err(Error::InvalidExpression("size() requires a string, binary, list or map".into())),
+                };
+                Ok(Value::number(size))
+            }
             _ => Err(Error::InvalidExpression("Cannot resolve non-value expression to value".into()))
         }
     }
 
-    /// Resolve attribute name (handle #placeholder)
-    fn resolve_attribute_name(&self, path: &str) -> String {
-        if path.starts_with('#') {
-            self.context.names.get(path)
-                .cloned()
-                .unwrap_or_else(|| path.to_string())
-        } else {
-            path.to_string()
+    /// Resolve an operand, returning None for a missing attribute path
+    fn resolve_optional_value(&self, expr: &Expr) -> Result<Option<Value>> {
+        match expr {
+            Expr::AttributePath(path) => Ok(DocumentPath::parse(path, self.context)?.get(self.item).cloned()),
+            _ => self.resolve_value(expr).map(Some),
         }
     }
 
@@ -236,9 +490,13 @@ pub enum UpdateValue {
     Placeholder(String),
     /// Attribute path reference
     Path(String),
-    /// Arithmetic: path + value or path - value
-    Add(String, Box<UpdateValue>),
-    Sub(String, Box<UpdateValue>),
+    /// Arithmetic: left + right or left - right
+    Add(Box<UpdateValue>, Box<UpdateValue>),
+    Sub(Box<UpdateValue>, Box<UpdateValue>),
+    /// list_append(first, second) - concatenation of two lists
+    ListAppend(Box<UpdateValue>, Box<UpdateValue>),
+    /// if_not_exists(path, default) - the value at path, or default if it is missing
+    IfNotExists(String, Box<UpdateValue>),
 }
 
 /// Update executor
@@ -258,32 +516,32 @@ impl<'a> UpdateExecutor<'a> {
         for action in actions {
             match action {
                 UpdateAction::Set(path, value) => {
-                    let attr_name = self.resolve_attribute_name(path);
+                    let path = DocumentPath::parse(path, self.context)?;
                     let resolved_value = self.resolve_update_value(value, &result)?;
-                    result.insert(attr_name, resolved_value);
+                    path.set(&mut result, resolved_value)?;
                 }
                 UpdateAction::Remove(path) => {
-                    let attr_name = self.resolve_attribute_name(path);
-                    result.remove(&attr_name);
+                    let path = DocumentPath::parse(path, self.context)?;
+                    path.remove(&mut result);
                 }
                 UpdateAction::Add(path, value) => {
-                    let attr_name = self.resolve_attribute_name(path);
+                    let path = DocumentPath::parse(path, self.context)?;
                     let add_value = self.resolve_update_value(value, &result)?;
 
-                    if let Some(existing) = result.get(&attr_name) {
+                    let new_value = match path.get(&result) {
                         // Add to existing number
-                        match (existing, &add_value) {
+                        Some(existing) => match (existing, &add_value) {
                             (Value::N(n1), Value::N(n2)) => {
                                 let num1: f64 = n1.parse().map_err(|_| Error::InvalidExpression("Invalid number".into()))?;
                                 let num2: f64 = n2.parse().map_err(|_| Error::InvalidExpression("Invalid number".into()))?;
-                                result.insert(attr_name, Value::number(num1 + num2));
+                                Value::number(num1 + num2)
                             }
                             _ => return Err(Error::InvalidExpression("ADD requires numbers".into()))
-                        }
-                    } else {
+                        },
                         // Initialize with value
-                        result.insert(attr_name, add_value);
-                    }
+                        None => add_value,
+                    };
+                    path.set(&mut result, new_value)?;
                 }
                 UpdateAction::Delete(_path, _value) => {
                     // DELETE is for sets - not implementing full set support in this phase
@@ -303,18 +561,10 @@ impl<'a> UpdateExecutor<'a> {
                     .cloned()
                     .ok_or_else(|| Error::InvalidExpression(format!("Placeholder {} not found", placeholder)))
             }
-            UpdateValue::Path(path) => {
-                let attr_name = self.resolve_attribute_name(path);
-                item.get(&attr_name)
-                    .cloned()
-                    .ok_or_else(|| Error::InvalidExpression(format!("Attribute {} not found", attr_name)))
-            }
-            UpdateValue::Add(path, val) => {
-                let attr_name = self.resolve_attribute_name(path);
-                let base = item.get(&attr_name)
-                    .cloned()
-                    .ok_or_else(|| Error::InvalidExpression(format!("Attribute {} not found", attr_name)))?;
-                let increment = self.resolve_update_value(val, item)?;
+            UpdateValue::Path(path) => self.resolve_path(path, item),
+            UpdateValue::Add(left, right) => {
+                let base = self.resolve_update_value(left, item)?;
+                let increment = self.resolve_update_value(right, item)?;
 
                 match (&base, &increment) {
                     (Value::N(n1), Value::N(n2)) => {
@@ -325,12 +575,9 @@ impl<'a> UpdateExecutor<'a> {
                     _ => Err(Error::InvalidExpression("Addition requires numbers".into()))
                 }
             }
-            UpdateValue::Sub(path, val) => {
-                let attr_name = self.resolve_attribute_name(path);
-                let base = item.get(&attr_name)
-                    .cloned()
-                    .ok_or_else(|| Error::InvalidExpression(format!("Attribute {} not found", attr_name)))?;
-                let decrement = self.resolve_update_value(val, item)?;
+            UpdateValue::Sub(left, right) => {
+                let base = self.resolve_update_value(left, item)?;
+                let decrement = self.resolve_update_value(right, item)?;
 
                 match (&base, &decrement) {
                     (Value::N(n1), Value::N(n2)) => {
@@ -341,17 +588,32 @@ impl<'a> UpdateExecutor<'a> {
                     _ => Err(Error::InvalidExpression("Subtraction requires numbers".into()))
                 }
             }
+            UpdateValue::ListAppend(first, second) => {
+                let first = self.resolve_update_value(first, item)?;
+                let second = self.resolve_update_value(second, item)?;
+
+                match (first, second) {
+                    (Value::L(mut list), Value::L(tail)) => {
+                        list.extend(tail);
+                        Ok(Value::L(list))
+                    }
+                    _ => Err(Error::InvalidExpression("list_append requires two lists".into()))
+                }
+            }
+            UpdateValue::IfNotExists(path, default) => {
+                match DocumentPath::parse(path, self.context)?.get(item) {
+                    Some(value) => Ok(value.clone()),
+                    None => self.resolve_update_value(default, item),
+                }
+            }
         }
     }
 
-    fn resolve_attribute_name(&self, path: &str) -> String {
-        if path.starts_with('#') {
-            self.context.names.get(path)
-                .cloned()
-                .unwrap_or_else(|| path.to_string())
-        } else {
-            path.to_string()
-        }
+    fn resolve_path(&self, path: &str, item: &Item) -> Result<Value> {
+        DocumentPath::parse(path, self.context)?
+            .get(item)
+            .cloned()
+            .ok_or_else(|| Error::InvalidExpression(format!("Attribute {} not found", path)))
     }
 }
 
@@ -396,6 +658,10 @@ enum Token {
     RightParen,
     Comma,
 
+    // Document paths
+    Dot,
+    Index(usize),  // [n]
+
     Eof,
 }
 
@@ -460,6 +726,29 @@ impl Lexer {
                 self.advance();
                 Ok(Token::Comma)
             }
+            Some('.') => {
+                self.advance();
+                Ok(Token::Dot)
+            }
+            Some('[') => {
+                self.advance();
+                let start = self.pos;
+                while let Some(ch) = self.current() {
+                    if ch.is_ascii_digit() {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                let digits: String = self.input[start..self.pos].iter().collect();
+                if self.current() != Some(']') || digits.is_empty() {
+                    return Err(Error::InvalidExpression("Expected list index like [0]".into()));
+                }
+                self.advance();
+                let index = digits.parse()
+                    .map_err(|_| Error::InvalidExpression(format!("Invalid list index: {}", digits)))?;
+                Ok(Token::Index(index))
+            }
             Some('+') => {
                 self.advance();
                 Ok(Token::Plus)
@@ -524,6 +813,52 @@ impl Lexer {
     }
 }
 
+/// Whether the token at `pos` calls one of the named functions
+///
+/// Function names are only special when followed by `(`, so they remain
+/// usable as plain attribute names.
+fn is_function_call(tokens: &[Token], pos: usize, names: &[&str]) -> bool {
+    match (tokens.get(pos), tokens.get(pos + 1)) {
+        (Some(Token::Identifier(name)), Some(Token::LeftParen)) => {
+            names.iter().any(|candidate| name.eq_ignore_ascii_case(candidate))
+        }
+        _ => false,
+    }
+}
+
+/// Read a document path starting at `pos`, returning its canonical string form
+///
+/// A path is an attribute name (or `#name` placeholder) followed by any
+/// number of `.name` and `[index]` segments. Returns None if `pos` does not
+/// start a path.
+fn read_path(tokens: &[Token], pos: &mut usize) -> Result<Option<String>> {
+    let mut path = match tokens.get(*pos) {
+        Some(Token::Identifier(name)) | Some(Token::NamePlaceholder(name)) => name.clone(),
+        _ => return Ok(None),
+    };
+    *pos += 1;
+
+    loop {
+        match tokens.get(*pos) {
+            Some(Token::Dot) => {
+                match tokens.get(*pos + 1) {
+                    Some(Token::Identifier(name)) | Some(Token::NamePlaceholder(name)) => {
+                        path.push('.');
+                        path.push_str(name);
+                    }
+                    _ => return Err(Error::InvalidExpression(format!("Expected attribute name after '{}.'", path))),
+                }
+                *pos += 2;
+            }
+            Some(Token::Index(index)) => {
+                path.push_str(&format!("[{}]", index));
+                *pos += 1;
+            }
+            _ => return Ok(Some(path)),
+        }
+    }
+}
+
 /// Expression parser
 pub struct ExpressionParser {
     tokens: Vec<Token>,
@@ -651,13 +986,23 @@ impl ExpressionParser {
                 self.expect(Token::RightParen)?;
                 Ok(expr)
             }
-            Token::Identifier(name) => {
+            Token::Identifier(name) if is_function_call(&self.tokens, self.pos, &["size", "contains"]) => {
                 self.advance();
-                Ok(Expr::AttributePath(name))
+                self.expect(Token::LeftParen)?;
+                let path = self.parse_operand()?;
+                if name.eq_ignore_ascii_case("size") {
+                    self.expect(Token::RightParen)?;
+                    Ok(Expr::Size(Box::new(path)))
+                } else {
+                    self.expect(Token::Comma)?;
+                    let operand = self.parse_operand()?;
+                    self.expect(Token::RightParen)?;
+                    Ok(Expr::Contains(Box::new(path), Box::new(operand)))
+                }
             }
-            Token::NamePlaceholder(name) => {
-                self.advance();
-                Ok(Expr::AttributePath(name))
+            Token::Identifier(_) | Token::NamePlaceholder(_) => {
+                let path = self.expect_path()?;
+                Ok(Expr::AttributePath(path))
             }
             Token::ValuePlaceholder(name) => {
                 self.advance();
@@ -666,24 +1011,14 @@ impl ExpressionParser {
             Token::AttributeExists => {
                 self.advance();
                 self.expect(Token::LeftParen)?;
-                let path = match self.current().clone() {
-                    Token::Identifier(p) => p,
-                    Token::NamePlaceholder(p) => p,
-                    _ => return Err(Error::InvalidExpression("Expected attribute path".into()))
-                };
-                self.advance();
+                let path = self.expect_path()?;
                 self.expect(Token::RightParen)?;
                 Ok(Expr::AttributeExists(path))
             }
             Token::AttributeNotExists => {
                 self.advance();
                 self.expect(Token::LeftParen)?;
-                let path = match self.current().clone() {
-                    Token::Identifier(p) => p,
-                    Token::NamePlaceholder(p) => p,
-                    _ => return Err(Error::InvalidExpression("Expected attribute path".into()))
-                };
-                self.advance();
+                let path = self.expect_path()?;
                 self.expect(Token::RightParen)?;
                 Ok(Expr::AttributeNotExists(path))
             }
@@ -699,6 +1034,11 @@ impl ExpressionParser {
             _ => Err(Error::InvalidExpression(format!("Unexpected token: {:?}", self.current())))
         }
     }
+
+    fn expect_path(&mut self) -> Result<String> {
+        read_path(&self.tokens, &mut self.pos)?
+            .ok_or_else(|| Error::InvalidExpression("Expected attribute path".into()))
+    }
 }
 
 /// Update expression parser
@@ -768,12 +1108,8 @@ impl UpdateExpressionParser {
 
         loop {
             // Get attribute path
-            let path = match self.current() {
-                Token::Identifier(p) => p.clone(),
-                Token::NamePlaceholder(p) => p.clone(),
-                _ => return Err(Error::InvalidExpression("Expected attribute path in SET".into()))
-            };
-            self.advance();
+            let path = read_path(&self.tokens, &mut self.pos)?
+                .ok_or_else(|| Error::InvalidExpression("Expected attribute path in SET".into()))?;
 
             // Expect =
             if self.current() != &Token::Equal {
@@ -801,12 +1137,8 @@ impl UpdateExpressionParser {
         let mut actions = Vec::new();
 
         loop {
-            let path = match self.current() {
-                Token::Identifier(p) => p.clone(),
-                Token::NamePlaceholder(p) => p.clone(),
-                _ => return Err(Error::InvalidExpression("Expected attribute path in REMOVE".into()))
-            };
-            self.advance();
+            let path = read_path(&self.tokens, &mut self.pos)?
+                .ok_or_else(|| Error::InvalidExpression("Expected attribute path in REMOVE".into()))?;
 
             actions.push(UpdateAction::Remove(path));
 
@@ -824,12 +1156,8 @@ impl UpdateExpressionParser {
         let mut actions = Vec::new();
 
         loop {
-            let path = match self.current() {
-                Token::Identifier(p) => p.clone(),
-                Token::NamePlaceholder(p) => p.clone(),
-                _ => return Err(Error::InvalidExpression("Expected attribute path in ADD".into()))
-            };
-            self.advance();
+            let path = read_path(&self.tokens, &mut self.pos)?
+                .ok_or_else(|| Error::InvalidExpression("Expected attribute path in ADD".into()))?;
 
             let value = self.parse_update_value()?;
 
@@ -849,12 +1177,8 @@ impl UpdateExpressionParser {
         let mut actions = Vec::new();
 
         loop {
-            let path = match self.current() {
-                Token::Identifier(p) => p.clone(),
-                Token::NamePlaceholder(p) => p.clone(),
-                _ => return Err(Error::InvalidExpression("Expected attribute path in DELETE".into()))
-            };
-            self.advance();
+            let path = read_path(&self.tokens, &mut self.pos)?
+                .ok_or_else(|| Error::InvalidExpression("Expected attribute path in DELETE".into()))?;
 
             let value = self.parse_update_value()?;
 
@@ -871,41 +1195,70 @@ impl UpdateExpressionParser {
     }
 
     fn parse_update_value(&mut self) -> Result<UpdateValue> {
-        // First, get the base value (path or placeholder)
-        let base = match self.current() {
-            Token::Identifier(p) => {
-                let path = p.clone();
-                self.advance();
+        let mut value = self.parse_update_operand()?;
 
-                // Check for arithmetic
-                match self.current() {
-                    Token::Plus => {
-                        self.advance();
-                        let operand = self.parse_update_value()?;
-                        return Ok(UpdateValue::Add(path, Box::new(operand)));
-                    }
-                    Token::Minus => {
-                        self.advance();
-                        let operand = self.parse_update_value()?;
-                        return Ok(UpdateValue::Sub(path, Box::new(operand)));
-                    }
-                    _ => UpdateValue::Path(path)
+        // Arithmetic is left-associative: a - b + c = (a - b) + c
+        loop {
+            match self.current() {
+                Token::Plus => {
+                    self.advance();
+                    let operand = self.parse_update_operand()?;
+                    value = UpdateValue::Add(Box::new(value), Box::new(operand));
                 }
+                Token::Minus => {
+                    self.advance();
+                    let operand = self.parse_update_operand()?;
+                    value = UpdateValue::Sub(Box::new(value), Box::new(operand));
+                }
+                _ => return Ok(value),
             }
-            Token::NamePlaceholder(p) => {
-                let path = p.clone();
-                self.advance();
-                UpdateValue::Path(path)
-            }
+        }
+    }
+
+    fn parse_update_operand(&mut self) -> Result<UpdateValue> {
+        if is_function_call(&self.tokens, self.pos, &["list_append", "if_not_exists"]) {
+            let is_list_append = matches!(self.current(), Token::Identifier(name) if name.eq_ignore_ascii_case("list_append"));
+            self.advance();
+            self.expect(Token::LeftParen)?;
+
+            let value = if is_list_append {
+                let first = self.parse_update_value()?;
+                self.expect(Token::Comma)?;
+                let second = self.parse_update_value()?;
+                UpdateValue::ListAppend(Box::new(first), Box::new(second))
+            } else {
+                let path = read_path(&self.tokens, &mut self.pos)?
+                    .ok_or_else(|| Error::InvalidExpression("Expected attribute path in if_not_exists".into()))?;
+                self.expect(Token::Comma)?;
+                let default = self.parse_update_value()?;
+                UpdateValue::IfNotExists(path, Box::new(default))
+            };
+
+            self.expect(Token::RightParen)?;
+            return Ok(value);
+        }
+
+        if let Some(path) = read_path(&self.tokens, &mut self.pos)? {
+            return Ok(UpdateValue::Path(path));
+        }
+
+        match self.current() {
             Token::ValuePlaceholder(p) => {
                 let placeholder = p.clone();
                 self.advance();
-                UpdateValue::Placeholder(placeholder)
+                Ok(UpdateValue::Placeholder(placeholder))
             }
-            _ => return Err(Error::InvalidExpression(format!("Unexpected token in update value: {:?}", self.current())))
-        };
+            _ => Err(Error::InvalidExpression(format!("Unexpected token in update value: {:?}", self.current())))
+        }
+    }
 
-        Ok(base)
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        if self.current() == &expected {
+            self.advance();
+            Ok(())
+        } else {
+            Err(Error::InvalidExpression(format!("Expected {:?}, got {:?}", expected, self.current())))
+        }
     }
 }
 
@@ -1197,4 +1550,204 @@ mod tests {
             _ => panic!("Expected number"),
         }
     }
+
+    fn profile_item() -> Item {
+        let mut address = HashMap::new();
+        address.insert("city".to_string(), Value::string("Paris"));
+        let mut profile = HashMap::new();
+        profile.insert("address".to_string(), Value::M(address));
+
+        let mut item = HashMap::new();
+        item.insert("profile".to_string(), Value::M(profile));
+        item.insert(
+            "tags".to_string(),
+            Value::L(vec![Value::string("a"), Value::string("b"), Value::string("c")]),
+        );
+        item
+    }
+
+    #[test]
+    fn test_document_path_parse() {
+        let context = ExpressionContext::new().with_name("#n", "dotted.name");
+        let path = DocumentPath::parse("#n.items[2][0].x", &context).unwrap();
+        assert_eq!(
+            path.segments(),
+            &[
+                PathSegment::Attribute("dotted.name".to_string()),
+                PathSegment::Attribute("items".to_string()),
+                PathSegment::Index(2),
+                PathSegment::Index(0),
+                PathSegment::Attribute("x".to_string()),
+            ]
+        );
+
+        assert!(DocumentPath::parse("a..b", &context).is_err());
+        assert!(DocumentPath::parse("a[x]", &context).is_err());
+        assert!(DocumentPath::parse("a[1]b", &context).is_err());
+    }
+
+    #[test]
+    fn test_condition_on_nested_paths() {
+        let item = profile_item();
+        let context = ExpressionContext::new()
+            .with_value(":city", Value::string("Paris"))
+            .with_value(":tag", Value::string("b"));
+
+        let expr = ExpressionParser::parse("profile.address.city = :city AND tags[1] = :tag").unwrap();
+        let evaluator = ExpressionEvaluator::new(&item, &context);
+        assert!(evaluator.evaluate(&expr).unwrap());
+
+        let expr = ExpressionParser::parse("attribute_exists(profile.address) AND attribute_not_exists(tags[5])").unwrap();
+        assert!(evaluator.evaluate(&expr).unwrap());
+    }
+
+    #[test]
+    fn test_update_set_nested_creates_maps() {
+        let item = profile_item();
+        let actions = UpdateExpressionParser::parse(
+            "SET profile.address.city = :c, settings.theme.color = :color, tags[1] = :t, tags[9] = :last"
+        ).unwrap();
+        let context = ExpressionContext::new()
+            .with_value(":c", Value::string("Berlin"))
+            .with_value(":color", Value::string("dark"))
+            .with_value(":t", Value::string("B"))
+            .with_value(":last", Value::string("z"));
+
+        let result = UpdateExecutor::new(&context).execute(&item, &actions).unwrap();
+        let lookup = |path: &str| DocumentPath::parse(path, &context).unwrap().get(&result).cloned();
+
+        assert_eq!(lookup("profile.address.city"), Some(Value::string("Berlin")));
+        assert_eq!(lookup("settings.theme.color"), Some(Value::string("dark")));
+        assert_eq!(lookup("tags[1]"), Some(Value::string("B")));
+        // Index past the end appends
+        assert_eq!(lookup("tags[3]"), Some(Value::string("z")));
+    }
+
+    #[test]
+    fn test_update_set_through_non_map_fails() {
+        let item = profile_item();
+        let actions = UpdateExpressionParser::parse("SET tags.name = :v").unwrap();
+        let context = ExpressionContext::new().with_value(":v", Value::string("x"));
+        assert!(UpdateExecutor::new(&context).execute(&item, &actions).is_err());
+    }
+
+    #[test]
+    fn test_update_remove_nested() {
+        let item = profile_item();
+        let actions = UpdateExpressionParser::parse("REMOVE tags[0], profile.address.city, missing.path").unwrap();
+        let context = ExpressionContext::new();
+        let result = UpdateExecutor::new(&context).execute(&item, &actions).unwrap();
+
+        assert_eq!(
+            result.get("tags"),
+            Some(&Value::L(vec![Value::string("b"), Value::string("c")]))
+        );
+        let address = DocumentPath::parse("profile.address", &context).unwrap().get(&result).cloned();
+        assert_eq!(address, Some(Value::M(HashMap::new())));
+    }
+
+    #[test]
+    fn test_update_nested_increment() {
+        let mut stats = HashMap::new();
+        stats.insert("views".to_string(), Value::number(10));
+        let mut item = HashMap::new();
+        item.insert("stats".to_string(), Value::M(stats));
+
+        let actions = UpdateExpressionParser::parse("SET stats.views = stats.views + :one ADD stats.likes :one").unwrap();
+        let context = ExpressionContext::new().with_value(":one", Value::number(1));
+        let result = UpdateExecutor::new(&context).execute(&item, &actions).unwrap();
+
+        let stats = result.get("stats").and_then(|v| v.as_map()).unwrap();
+        assert_eq!(stats.get("views"), Some(&Value::number(11)));
+        assert_eq!(stats.get("likes"), Some(&Value::number(1)));
+    }
+
+    #[test]
+    fn test_condition_size_and_contains() {
+        let item = profile_item();
+        let context = ExpressionContext::new()
+            .with_value(":three", Value::number(3))
+            .with_value(":tag", Value::string("c"))
+            .with_value(":sub", Value::string("ari"))
+            .with_value(":missing", Value::string("zzz"));
+        let evaluator = ExpressionEvaluator::new(&item, &context);
+
+        let expr = ExpressionParser::parse("size(tags) = :three AND contains(tags, :tag)").unwrap();
+        assert!(evaluator.evaluate(&expr).unwrap());
+
+        let expr = ExpressionParser::parse("contains(profile.address.city, :sub)").unwrap();
+        assert!(evaluator.evaluate(&expr).unwrap());
+
+        let expr = ExpressionParser::parse("contains(tags, :missing) OR contains(nothing, :tag)").unwrap();
+        assert!(!evaluator.evaluate(&expr).unwrap());
+
+        let expr = ExpressionParser::parse("size(profile) < :three").unwrap();
+        assert!(evaluator.evaluate(&expr).unwrap());
+    }
+
+    #[test]
+    fn test_function_names_usable_as_attributes() {
+        let mut item = HashMap::new();
+        item.insert("size".to_string(), Value::number(10));
+        let context = ExpressionContext::new().with_value(":v", Value::number(10));
+
+        let expr = ExpressionParser::parse("size = :v").unwrap();
+        assert!(ExpressionEvaluator::new(&item, &context).evaluate(&expr).unwrap());
+    }
+
+    #[test]
+    fn test_update_list_append() {
+        let item = profile_item();
+        let actions = UpdateExpressionParser::parse(
+            "SET tags = list_append(tags, :more), history = list_append(:first, if_not_exists(history, :empty))"
+        ).unwrap();
+        let context = ExpressionContext::new()
+            .with_value(":more", Value::L(vec![Value::string("d")]))
+            .with_value(":first", Value::L(vec![Value::number(1)]))
+            .with_value(":empty", Value::L(vec![]));
+
+        let result = UpdateExecutor::new(&context).execute(&item, &actions).unwrap();
+        assert_eq!(
+            result.get("tags"),
+            Some(&Value::L(vec![
+                Value::string("a"),
+                Value::string("b"),
+                Value::string("c"),
+                Value::string("d"),
+            ]))
+        );
+        assert_eq!(result.get("history"), Some(&Value::L(vec![Value::number(1)])));
+
+        let actions = UpdateExpressionParser::parse("SET tags = list_append(tags, :s)").unwrap();
+        let context = ExpressionContext::new().with_value(":s", Value::string("x"));
+        assert!(UpdateExecutor::new(&context).execute(&item, &actions).is_err());
+    }
+
+    #[test]
+    fn test_update_if_not_exists_counter() {
+        let actions = UpdateExpressionParser::parse("SET views = if_not_exists(views, :zero) + :one").unwrap();
+        let context = ExpressionContext::new()
+            .with_value(":zero", Value::number(0))
+            .with_value(":one", Value::number(1));
+        let executor = UpdateExecutor::new(&context);
+
+        let first = executor.execute(&HashMap::new(), &actions).unwrap();
+        assert_eq!(first.get("views"), Some(&Value::number(1)));
+
+        let second = executor.execute(&first, &actions).unwrap();
+        assert_eq!(second.get("views"), Some(&Value::number(2)));
+    }
+
+    #[test]
+    fn test_update_arithmetic_left_associative() {
+        let mut item = HashMap::new();
+        item.insert("a".to_string(), Value::number(10));
+
+        let actions = UpdateExpressionParser::parse("SET b = a - :x + :y").unwrap();
+        let context = ExpressionContext::new()
+            .with_value(":x", Value::number(3))
+            .with_value(":y", Value::number(1));
+        let result = UpdateExecutor::new(&context).execute(&item, &actions).unwrap();
+        assert_eq!(result.get("b"), Some(&Value::number(8)));
+    }
 }
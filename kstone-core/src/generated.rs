@@ -0,0 +1,213 @@
+/// Generated attributes: defaults, timestamps and computed attributes (Phase 8+)
+///
+/// The engine fills these in on every put and update, before the item is
+/// validated, so every client of a database gets the same behavior.
+/// Attributes a write sets explicitly are left alone.
+
+use crate::expression::{ExpressionContext, UpdateAction, UpdateExecutor, UpdateExpressionParser};
+use crate::index::TableSchema;
+use crate::stream::current_timestamp_millis;
+use crate::validation::AttributeType;
+use crate::{Error, Item, Result, Value};
+use serde::{Deserialize, Serialize};
+
+/// Name placeholder a computed attribute is written through
+const COMPUTED_NAME: &str = "#computed";
+
+/// Value an attribute gets when a write leaves it out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DefaultValue {
+    /// A fixed value
+    Literal(Value),
+    /// `now()`: the time of the write, as milliseconds since the epoch for
+    /// `Number` attributes and as a timestamp otherwise
+    Now,
+    /// `uuid()`: a random (v4) UUID string
+    Uuid,
+}
+
+impl DefaultValue {
+    /// The value to fill in for an attribute of this type
+    fn generate(&self, attr_type: &AttributeType, now: i64) -> Value {
+        match self {
+            DefaultValue::Literal(value) => value.clone(),
+            DefaultValue::Now if *attr_type == AttributeType::Number => Value::number(now),
+            DefaultValue::Now => Value::Ts(now),
+            DefaultValue::Uuid => Value::string(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// Attributes recording when an item was created and last written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampAttributes {
+    /// Set on the first write of an item, then carried over
+    pub created_at: String,
+    /// Set on every write
+    pub updated_at: String,
+}
+
+impl TimestampAttributes {
+    pub fn new(created_at: impl Into<String>, updated_at: impl Into<String>) -> Self {
+        Self {
+            created_at: created_at.into(),
+            updated_at: updated_at.into(),
+        }
+    }
+}
+
+impl Default for TimestampAttributes {
+    fn default() -> Self {
+        Self::new("created_at", "updated_at")
+    }
+}
+
+/// An attribute derived from the rest of the item on every write
+///
+/// The expression is the value side of an update expression's SET: attribute
+/// paths, `+`, `-`, `list_append(...)` and `if_not_exists(...)`, but no
+/// placeholders. When it can't be evaluated (an input is missing, say) the
+/// attribute is left out of the item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputedAttribute {
+    /// Attribute name
+    pub name: String,
+    /// Expression computing its value, e.g. `subtotal + tax`
+    pub expression: String,
+}
+
+impl ComputedAttribute {
+    /// Create a computed attribute, failing with `Error::InvalidExpression`
+    /// if the expression doesn't parse
+    pub fn new(name: impl Into<String>, expression: impl Into<String>) -> Result<Self> {
+        let computed = Self {
+            name: name.into(),
+            expression: expression.into(),
+        };
+        computed.action()?;
+        Ok(computed)
+    }
+
+    /// The expression as a single SET action
+    fn action(&self) -> Result<UpdateAction> {
+        let mut actions = UpdateExpressionParser::parse(&format!("SET {} = {}", COMPUTED_NAME, self.expression))?;
+        match (actions.pop(), actions.is_empty()) {
+            (Some(action @ UpdateAction::Set(..)), true) => Ok(action),
+            _ => Err(Error::InvalidExpression(format!(
+                "Computed attribute '{}' must be a single value expression: {}",
+                self.name, self.expression
+            ))),
+        }
+    }
+
+    /// Compute the attribute into an item, or remove it if it can't be computed
+    fn apply(&self, item: &mut Item) -> Result<()> {
+        let action = self.action()?;
+        let context = ExpressionContext::new().with_name(COMPUTED_NAME, self.name.as_str());
+        match UpdateExecutor::new(&context).execute(item, &[action]) {
+            Ok(computed) => *item = computed,
+            Err(_) => {
+                item.remove(&self.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fill in an item's generated attributes before it replaces `old`
+///
+/// Defaults come first, then timestamps, then computed attributes, so
+/// computed attributes can use the other two.
+pub(crate) fn apply(schema: &TableSchema, old: Option<&Item>, item: &mut Item) -> Result<()> {
+    let now = current_timestamp_millis();
+
+    for attribute in &schema.attribute_schemas {
+        if let Some(default) = &attribute.default {
+            if !item.contains_key(&attribute.name) {
+                item.insert(attribute.name.clone(), default.generate(&attribute.attr_type, now));
+            }
+        }
+    }
+
+    if let Some(timestamps) = &schema.timestamps {
+        if !item.contains_key(&timestamps.created_at) {
+            let created_at = old
+                .and_then(|old| old.get(&timestamps.created_at))
+                .cloned()
+                .unwrap_or(Value::Ts(now));
+            item.insert(timestamps.created_at.clone(), created_at);
+        }
+
+        // A value that differs from the stored one was set on purpose (e.g.
+        // by a sync carrying the remote's timestamp) and is kept
+        let updated_at = item.get(&timestamps.updated_at);
+        let explicit = updated_at.is_some() && updated_at != old.and_then(|old| old.get(&timestamps.updated_at));
+        if !explicit {
+            item.insert(timestamps.updated_at.clone(), Value::Ts(now));
+        }
+    }
+
+    for computed in &schema.computed_attributes {
+        computed.apply(item)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::AttributeSchema;
+
+    #[test]
+    fn test_defaults_and_computed() {
+        let schema = TableSchema::new()
+            .with_attribute(AttributeSchema::new("status", AttributeType::String)
+                .with_default(DefaultValue::Literal(Value::string("active"))))
+            .with_attribute(AttributeSchema::new("id", AttributeType::String).with_default(DefaultValue::Uuid))
+            .with_attribute(AttributeSchema::new("joined", AttributeType::Number).with_default(DefaultValue::Now))
+            .with_computed(ComputedAttribute::new("total", "subtotal + tax").unwrap());
+
+        let mut item = Item::new();
+        item.insert("status".to_string(), Value::string("pending"));
+        item.insert("subtotal".to_string(), Value::number(10));
+        item.insert("tax".to_string(), Value::number(2));
+        apply(&schema, None, &mut item).unwrap();
+
+        assert_eq!(item.get("status"), Some(&Value::string("pending")));
+        assert_eq!(item.get("id").and_then(|v| v.as_string()).map(str::len), Some(36));
+        assert!(matches!(item.get("joined"), Some(Value::N(_))));
+        assert_eq!(item.get("total"), Some(&Value::number(12)));
+
+        // Without its inputs a computed attribute is dropped
+        item.remove("tax");
+        apply(&schema, None, &mut item).unwrap();
+        assert!(!item.contains_key("total"));
+
+        assert!(ComputedAttribute::new("bad", "a +").is_err());
+        assert!(ComputedAttribute::new("bad", "a REMOVE b").is_err());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let schema = TableSchema::new().with_timestamps();
+
+        let mut first = Item::new();
+        apply(&schema, None, &mut first).unwrap();
+        let created_at = first.get("created_at").cloned().unwrap();
+        assert!(matches!(created_at, Value::Ts(_)));
+
+        // Carried over on later writes; updated_at is refreshed unless set explicitly
+        let mut stale = first.clone();
+        stale.insert("updated_at".to_string(), Value::Ts(0));
+        let mut second = Item::new();
+        apply(&schema, Some(&stale), &mut second).unwrap();
+        assert_eq!(second.get("created_at"), Some(&created_at));
+        assert_ne!(second.get("updated_at"), Some(&Value::Ts(0)));
+
+        let mut synced = stale.clone();
+        synced.insert("updated_at".to_string(), Value::Ts(5));
+        apply(&schema, Some(&stale), &mut synced).unwrap();
+        assert_eq!(synced.get("updated_at"), Some(&Value::Ts(5)));
+    }
+}
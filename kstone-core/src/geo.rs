@@ -0,0 +1,347 @@
+/// Geospatial indexing (Phase 3.6+)
+///
+/// Geo indexes store each item under the geohash of its latitude/longitude
+/// attributes. Index entries are partitioned by a short geohash prefix and
+/// sorted by the full geohash, so a radius or bounding-box query becomes a
+/// handful of prefix queries over the cells covering the area, followed by
+/// an exact distance check.
+
+use crate::{Error, Item, Result, Value};
+
+/// Earth's mean radius in meters
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Geohash length of index partition keys (cells of about 156km x 156km)
+pub const PARTITION_PRECISION: usize = 3;
+/// Geohash length stored in index sort keys (cells of a few centimeters)
+pub const STORED_PRECISION: usize = 12;
+/// Finest geohash length used to cover a query area
+const MAX_COVER_PRECISION: usize = 8;
+/// Most cells a query area is covered with, unless even partition-sized cells need more
+const MAX_COVER_CELLS: usize = 64;
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// A latitude/longitude pair in degrees (Phase 3.6+)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Create a point from latitude and longitude in degrees
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Check that the point is a valid coordinate
+    pub fn validate(&self) -> Result<()> {
+        if !(-90.0..=90.0).contains(&self.lat) || !(-180.0..=180.0).contains(&self.lon) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid coordinate ({}, {}): latitude must be within [-90, 90] and longitude within [-180, 180]",
+                self.lat, self.lon
+            )));
+        }
+        Ok(())
+    }
+
+    /// Great-circle distance to another point in meters (haversine formula)
+    pub fn distance_m(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// A latitude/longitude rectangle (Phase 3.6+)
+///
+/// A box whose `min.lon` is greater than its `max.lon` crosses the
+/// antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoBox {
+    /// South-west corner
+    pub min: GeoPoint,
+    /// North-east corner
+    pub max: GeoPoint,
+}
+
+impl GeoBox {
+    /// Create a box from its south-west and north-east corners
+    pub fn new(min: GeoPoint, max: GeoPoint) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box holding every point within `radius_m` of `center`
+    pub fn around(center: GeoPoint, radius_m: f64) -> Self {
+        let dlat = (radius_m / EARTH_RADIUS_M).to_degrees();
+        let min_lat = center.lat - dlat;
+        let max_lat = center.lat + dlat;
+
+        // Circles reaching a pole span every longitude
+        let cos_lat = center.lat.to_radians().cos();
+        if min_lat <= -90.0 || max_lat >= 90.0 || cos_lat <= f64::EPSILON || dlat / cos_lat >= 180.0 {
+            return Self::new(
+                GeoPoint::new(min_lat.max(-90.0), -180.0),
+                GeoPoint::new(max_lat.min(90.0), 180.0),
+            );
+        }
+
+        let dlon = dlat / cos_lat;
+        Self::new(
+            GeoPoint::new(min_lat, wrap_lon(center.lon - dlon)),
+            GeoPoint::new(max_lat, wrap_lon(center.lon + dlon)),
+        )
+    }
+
+    /// Check that the corners are valid and in order
+    pub fn validate(&self) -> Result<()> {
+        self.min.validate()?;
+        self.max.validate()?;
+        if self.min.lat > self.max.lat {
+            return Err(Error::InvalidArgument(format!(
+                "Bounding box minimum latitude {} is above its maximum {}",
+                self.min.lat, self.max.lat
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check if a point lies inside the box
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        if point.lat < self.min.lat || point.lat > self.max.lat {
+            return false;
+        }
+        if self.min.lon <= self.max.lon {
+            point.lon >= self.min.lon && point.lon <= self.max.lon
+        } else {
+            point.lon >= self.min.lon || point.lon <= self.max.lon
+        }
+    }
+
+    /// Longitude ranges the box spans, split at the antimeridian
+    fn lon_ranges(&self) -> Vec<(f64, f64)> {
+        if self.min.lon <= self.max.lon {
+            vec![(self.min.lon, self.max.lon)]
+        } else {
+            vec![(self.min.lon, 180.0), (-180.0, self.max.lon)]
+        }
+    }
+
+    /// Geohash cells covering the box, all of the same length
+    ///
+    /// Uses the finest precision that needs at most `MAX_COVER_CELLS` cells,
+    /// but never cells larger than an index partition.
+    pub fn covering_cells(&self) -> Vec<String> {
+        let mut precision = PARTITION_PRECISION;
+        while precision < MAX_COVER_PRECISION && self.cell_count(precision + 1) <= MAX_COVER_CELLS {
+            precision += 1;
+        }
+
+        let (lat_bits, lon_bits) = cell_bits(precision);
+        let (lat_min, lat_max) = (
+            cell_index(self.min.lat, -90.0, 90.0, lat_bits),
+            cell_index(self.max.lat, -90.0, 90.0, lat_bits),
+        );
+        let lat_step = 180.0 / (1u64 << lat_bits) as f64;
+        let lon_step = 360.0 / (1u64 << lon_bits) as f64;
+
+        let mut cells = Vec::new();
+        for (west, east) in self.lon_ranges() {
+            let lon_min = cell_index(west, -180.0, 180.0, lon_bits);
+            let lon_max = cell_index(east, -180.0, 180.0, lon_bits);
+            for lat in lat_min..=lat_max {
+                for lon in lon_min..=lon_max {
+                    // Encode the cell's center to get its hash
+                    let center = GeoPoint::new(
+                        -90.0 + (lat as f64 + 0.5) * lat_step,
+                        -180.0 + (lon as f64 + 0.5) * lon_step,
+                    );
+                    cells.push(encode_geohash(&center, precision));
+                }
+            }
+        }
+
+        cells.sort();
+        cells.dedup();
+        cells
+    }
+
+    /// Number of cells of a given geohash length the box touches
+    fn cell_count(&self, precision: usize) -> usize {
+        let (lat_bits, lon_bits) = cell_bits(precision);
+        let lat_cells = cell_index(self.max.lat, -90.0, 90.0, lat_bits)
+            - cell_index(self.min.lat, -90.0, 90.0, lat_bits)
+            + 1;
+        let lon_cells: u64 = self
+            .lon_ranges()
+            .into_iter()
+            .map(|(west, east)| {
+                cell_index(east, -180.0, 180.0, lon_bits) - cell_index(west, -180.0, 180.0, lon_bits) + 1
+            })
+            .sum();
+        lat_cells.saturating_mul(lon_cells).min(usize::MAX as u64) as usize
+    }
+}
+
+/// Nearest-first radius query result (Phase 3.6+)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoMatch {
+    /// The matching item
+    pub item: Item,
+    /// Distance from the query center in meters
+    pub distance_m: f64,
+}
+
+/// Encode a point as a geohash of `precision` characters
+pub fn encode_geohash(point: &GeoPoint, precision: usize) -> String {
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true; // Bits alternate longitude, latitude
+    let mut bits = 0;
+    let mut index = 0usize;
+
+    while hash.len() < precision {
+        let (range, value) = if even_bit {
+            (&mut lon_range, point.lon)
+        } else {
+            (&mut lat_range, point.lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        index <<= 1;
+        if value >= mid {
+            index |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even_bit = !even_bit;
+
+        bits += 1;
+        if bits == 5 {
+            hash.push(BASE32[index] as char);
+            bits = 0;
+            index = 0;
+        }
+    }
+
+    hash
+}
+
+/// Read a coordinate pair from an item's latitude and longitude attributes
+///
+/// Both must be numbers within range; anything else yields `None`.
+pub fn item_point(item: &Item, lat_attribute: &str, lon_attribute: &str) -> Option<GeoPoint> {
+    let coordinate = |attribute: &str| match item.get(attribute) {
+        Some(Value::N(n)) => n.parse::<f64>().ok(),
+        _ => None,
+    };
+    let point = GeoPoint::new(coordinate(lat_attribute)?, coordinate(lon_attribute)?);
+    point.validate().ok().map(|_| point)
+}
+
+/// Latitude and longitude bits in a geohash of `precision` characters
+fn cell_bits(precision: usize) -> (u32, u32) {
+    let bits = (precision * 5) as u32;
+    (bits / 2, bits - bits / 2)
+}
+
+/// Index of the cell holding `value` when `[min, max]` is split into 2^bits cells
+fn cell_index(value: f64, min: f64, max: f64, bits: u32) -> u64 {
+    let cells = 1u64 << bits;
+    let index = ((value - min) / (max - min) * cells as f64).floor();
+    (index.max(0.0) as u64).min(cells - 1)
+}
+
+/// Bring a longitude back into [-180, 180]
+fn wrap_lon(lon: f64) -> f64 {
+    if lon < -180.0 {
+        lon + 360.0
+    } else if lon > 180.0 {
+        lon - 360.0
+    } else {
+        lon
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_geohash() {
+        // Well-known reference hashes
+        assert_eq!(encode_geohash(&GeoPoint::new(57.64911, 10.40744), 11), "u4pruydqqvj");
+        assert_eq!(encode_geohash(&GeoPoint::new(42.6, -5.6), 5), "ezs42");
+        assert_eq!(encode_geohash(&GeoPoint::new(0.0, 0.0), 1), "s");
+    }
+
+    #[test]
+    fn test_distance() {
+        let london = GeoPoint::new(51.5074, -0.1278);
+        let paris = GeoPoint::new(48.8566, 2.3522);
+        let distance = london.distance_m(&paris);
+        assert!((distance - 343_500.0).abs() < 1_000.0, "{}", distance);
+        assert_eq!(london.distance_m(&london), 0.0);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(GeoPoint::new(45.0, 90.0).validate().is_ok());
+        assert!(GeoPoint::new(91.0, 0.0).validate().is_err());
+        assert!(GeoPoint::new(0.0, -180.5).validate().is_err());
+        assert!(GeoBox::new(GeoPoint::new(10.0, 0.0), GeoPoint::new(5.0, 1.0)).validate().is_err());
+    }
+
+    #[test]
+    fn test_box_around_and_contains() {
+        let center = GeoPoint::new(40.0, -74.0);
+        let bbox = GeoBox::around(center, 10_000.0);
+        assert!(bbox.contains(&center));
+        assert!(bbox.contains(&GeoPoint::new(40.08, -74.0)));
+        assert!(!bbox.contains(&GeoPoint::new(40.2, -74.0)));
+
+        // Near the antimeridian the box wraps around
+        let bbox = GeoBox::around(GeoPoint::new(0.0, 179.99), 10_000.0);
+        assert!(bbox.min.lon > bbox.max.lon);
+        assert!(bbox.contains(&GeoPoint::new(0.0, -179.99)));
+
+        // Circles over a pole cover every longitude
+        let bbox = GeoBox::around(GeoPoint::new(89.99, 0.0), 10_000.0);
+        assert!(bbox.contains(&GeoPoint::new(89.995, 180.0)));
+    }
+
+    #[test]
+    fn test_covering_cells() {
+        let point = GeoPoint::new(37.7749, -122.4194);
+        let cells = GeoBox::around(point, 500.0).covering_cells();
+        assert!(cells.len() <= MAX_COVER_CELLS);
+        assert!(cells.iter().all(|cell| cell.len() == cells[0].len()));
+        assert!(cells[0].len() > PARTITION_PRECISION);
+
+        // Some cell holds the center's geohash
+        let hash = encode_geohash(&point, STORED_PRECISION);
+        assert!(cells.iter().any(|cell| hash.starts_with(cell.as_str())));
+
+        // Very large areas fall back to partition-sized cells
+        let cells = GeoBox::around(point, 2_000_000.0).covering_cells();
+        assert!(cells.iter().all(|cell| cell.len() == PARTITION_PRECISION));
+    }
+
+    #[test]
+    fn test_item_point() {
+        let mut item = Item::new();
+        item.insert("lat".to_string(), Value::number(37.5));
+        item.insert("lon".to_string(), Value::number(-122.25));
+        assert_eq!(item_point(&item, "lat", "lon"), Some(GeoPoint::new(37.5, -122.25)));
+
+        item.insert("lon".to_string(), Value::string("west"));
+        assert_eq!(item_point(&item, "lat", "lon"), None);
+
+        item.insert("lon".to_string(), Value::number(200));
+        assert_eq!(item_point(&item, "lat", "lon"), None);
+    }
+}
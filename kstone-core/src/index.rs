@@ -2,9 +2,23 @@
 ///
 /// Phase 3.1: LSI - alternative sort key on same partition key
 /// Phase 3.2: GSI - alternative partition key and sort key
+/// Phase 3.5: Vector indexes - nearest-neighbour search over `VecF32` attributes
+/// Phase 3.6: Geo indexes - radius and bounding-box search over coordinates
 
+use crate::{Item, Key, Value};
+use crate::vector::DistanceMetric;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Attribute holding the base table key in index records
+///
+/// Queries use it to fetch the base item when asked for attributes the index
+/// doesn't store, and report it so callers can authorize the base items
+/// behind an index partition; it is never returned as an attribute.
+/// Entries of `All` indexes written before Phase 8 lack it.
+pub const BASE_KEY_ATTRIBUTE: &str = "__kstone_base_key";
 
 /// Index projection type - which attributes to include in index
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -23,6 +37,55 @@ impl Default for IndexProjection {
     }
 }
 
+impl IndexProjection {
+    /// Build the item stored in an index record
+    ///
+    /// `key_attributes` are the index's own key attributes, which every
+    /// projection keeps, along with the base key.
+    pub fn project(&self, item: &Item, key_attributes: &[&str], base_key: &Key) -> Item {
+        let included: &[String] = match self {
+            IndexProjection::All => {
+                let mut projected = item.clone();
+                projected.insert(BASE_KEY_ATTRIBUTE.to_string(), encode_base_key(base_key));
+                return projected;
+            }
+            IndexProjection::KeysOnly => &[],
+            IndexProjection::Include(attributes) => attributes,
+        };
+
+        let mut projected: Item = key_attributes
+            .iter()
+            .copied()
+            .chain(included.iter().map(String::as_str))
+            .filter_map(|name| item.get(name).map(|value| (name.to_string(), value.clone())))
+            .collect();
+        projected.insert(BASE_KEY_ATTRIBUTE.to_string(), encode_base_key(base_key));
+        projected
+    }
+
+    /// Whether an index record holds every attribute a query asks for
+    ///
+    /// `paths` is the query's projection (`None` asks for all attributes
+    /// stored in the index, which is always satisfied); only the top-level
+    /// attribute of each path is considered.
+    pub fn covers(&self, key_attributes: &[&str], paths: Option<&[String]>) -> bool {
+        let included: &[String] = match self {
+            IndexProjection::All => return true,
+            IndexProjection::KeysOnly => &[],
+            IndexProjection::Include(attributes) => attributes,
+        };
+        let paths = match paths {
+            Some(paths) => paths,
+            None => return true,
+        };
+
+        paths.iter().all(|path| {
+            let attribute = path.split('.').next().unwrap_or(path);
+            key_attributes.contains(&attribute) || included.iter().any(|name| name == attribute)
+        })
+    }
+}
+
 /// Local Secondary Index definition
 ///
 /// LSI shares the same partition key as the base table but uses
@@ -58,6 +121,11 @@ impl LocalSecondaryIndex {
         self.projection = IndexProjection::Include(attributes);
         self
     }
+
+    /// Attributes that make up the index key
+    pub fn key_attributes(&self) -> Vec<&str> {
+        vec![self.sort_key_attribute.as_str()]
+    }
 }
 
 /// Global Secondary Index definition (Phase 3.2+)
@@ -111,6 +179,91 @@ impl GlobalSecondaryIndex {
         self.projection = IndexProjection::Include(attributes);
         self
     }
+
+    /// Attributes that make up the index key
+    pub fn key_attributes(&self) -> Vec<&str> {
+        let mut attributes = vec![self.partition_key_attribute.as_str()];
+        attributes.extend(self.sort_key_attribute.as_deref());
+        attributes
+    }
+}
+
+/// Vector index definition (Phase 3.5+)
+///
+/// Indexes a `VecF32` attribute for nearest-neighbour search. The index is
+/// named after its attribute; items whose vector has a different number of
+/// dimensions are left out of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorIndex {
+    /// Attribute holding the vector (also the index name)
+    pub attribute_name: String,
+    /// Number of dimensions every indexed vector has
+    pub dimensions: usize,
+    /// How distance between vectors is measured
+    pub metric: DistanceMetric,
+}
+
+impl VectorIndex {
+    /// Create a new vector index on an attribute
+    pub fn new(attribute_name: impl Into<String>, dimensions: usize, metric: DistanceMetric) -> Self {
+        Self {
+            attribute_name: attribute_name.into(),
+            dimensions,
+            metric,
+        }
+    }
+
+    /// The item's vector, if it has one with the right number of dimensions
+    pub fn vector_of<'a>(&self, item: &'a Item) -> Option<&'a [f32]> {
+        match item.get(&self.attribute_name) {
+            Some(Value::VecF32(vector)) if vector.len() == self.dimensions => Some(vector),
+            _ => None,
+        }
+    }
+}
+
+/// Geo index definition (Phase 3.6+)
+///
+/// Indexes items by the geohash of two numeric attributes holding latitude
+/// and longitude in degrees. Items missing either attribute, or holding an
+/// out-of-range coordinate, are left out of the index. Entries store only
+/// the coordinates; queries fetch the matching base items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoIndex {
+    /// Index name (unique per table)
+    pub name: String,
+    /// Attribute holding the latitude
+    pub latitude_attribute: String,
+    /// Attribute holding the longitude
+    pub longitude_attribute: String,
+}
+
+/// Projection of every geo index: coordinates and the base key
+static GEO_INDEX_PROJECTION: IndexProjection = IndexProjection::KeysOnly;
+
+impl GeoIndex {
+    /// Create a new geo index over latitude and longitude attributes
+    pub fn new(
+        name: impl Into<String>,
+        latitude_attribute: impl Into<String>,
+        longitude_attribute: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            latitude_attribute: latitude_attribute.into(),
+            longitude_attribute: longitude_attribute.into(),
+        }
+    }
+
+    /// Which attributes are projected into the index
+    pub fn projection(&self) -> &IndexProjection {
+        &GEO_INDEX_PROJECTION
+    }
+
+    /// Attributes that make up the index key
+    pub fn key_attributes(&self) -> Vec<&str> {
+        vec![self.latitude_attribute.as_str(), self.longitude_attribute.as_str()]
+    }
 }
 
 /// Table schema with index definitions
@@ -129,6 +282,65 @@ pub struct TableSchema {
     /// Attribute schemas for validation
     #[serde(default)]
     pub attribute_schemas: Vec<crate::validation::AttributeSchema>,
+    /// Indexes created online that are still being backfilled (Phase 3.2+)
+    #[serde(default)]
+    pub index_backfills: Vec<IndexBackfill>,
+    /// Vector indexes (Phase 3.5+)
+    #[serde(default)]
+    pub vector_indexes: Vec<VectorIndex>,
+    /// Geo indexes (Phase 3.6+)
+    #[serde(default)]
+    pub geo_indexes: Vec<GeoIndex>,
+    /// Attributes stamped with creation and last write times (Phase 8+)
+    #[serde(default)]
+    pub timestamps: Option<crate::generated::TimestampAttributes>,
+    /// Attributes computed from the rest of the item on every write (Phase 8+)
+    #[serde(default)]
+    pub computed_attributes: Vec<crate::generated::ComputedAttribute>,
+    /// Named tables sharing this database, with their own schemas (Phase 3.7+)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tables: BTreeMap<String, TableSchema>,
+}
+
+/// Progress of an online index backfill (Phase 3.2+)
+///
+/// Stored in the schema so a reopened database resumes the backfill at the
+/// next unfinished stripe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexBackfill {
+    /// Index being backfilled
+    pub index_name: String,
+    /// Next stripe to backfill
+    pub next_stripe: usize,
+    /// Total number of stripes to backfill
+    pub total_stripes: usize,
+    /// Index entries written so far
+    pub items_indexed: u64,
+}
+
+impl IndexBackfill {
+    /// Start a backfill at the first stripe
+    pub fn new(index_name: impl Into<String>, total_stripes: usize) -> Self {
+        Self {
+            index_name: index_name.into(),
+            next_stripe: 0,
+            total_stripes,
+            items_indexed: 0,
+        }
+    }
+
+    /// Fraction of stripes backfilled (0.0 to 1.0)
+    pub fn progress(&self) -> f64 {
+        if self.total_stripes == 0 {
+            return 1.0;
+        }
+        self.next_stripe as f64 / self.total_stripes as f64
+    }
+
+    /// Whether every stripe has been backfilled
+    pub fn is_complete(&self) -> bool {
+        self.next_stripe >= self.total_stripes
+    }
 }
 
 impl TableSchema {
@@ -159,6 +371,62 @@ impl TableSchema {
         self.global_indexes.iter().find(|idx| idx.name == name)
     }
 
+    /// Add a vector index on a `VecF32` attribute (Phase 3.5+)
+    pub fn add_vector_index(
+        mut self,
+        attribute_name: impl Into<String>,
+        dimensions: usize,
+        metric: DistanceMetric,
+    ) -> Self {
+        self.vector_indexes.push(VectorIndex::new(attribute_name, dimensions, metric));
+        self
+    }
+
+    /// Get a vector index by attribute name (Phase 3.5+)
+    pub fn get_vector_index(&self, attribute_name: &str) -> Option<&VectorIndex> {
+        self.vector_indexes.iter().find(|idx| idx.attribute_name == attribute_name)
+    }
+
+    /// Add a geo index (Phase 3.6+)
+    pub fn add_geo_index(mut self, index: GeoIndex) -> Self {
+        self.geo_indexes.push(index);
+        self
+    }
+
+    /// Get a geo index by name (Phase 3.6+)
+    pub fn get_geo_index(&self, name: &str) -> Option<&GeoIndex> {
+        self.geo_indexes.iter().find(|idx| idx.name == name)
+    }
+
+    /// Whether an index is still being backfilled (Phase 3.2+)
+    pub fn is_backfilling(&self, name: &str) -> bool {
+        self.index_backfills.iter().any(|backfill| backfill.index_name == name)
+    }
+
+    /// Whether any LSI, GSI or geo index is defined
+    pub fn has_indexes(&self) -> bool {
+        !self.local_indexes.is_empty() || self.has_cross_stripe_indexes()
+    }
+
+    /// Whether any index stores entries away from their base item's stripe
+    ///
+    /// GSI and geo index entries are routed by their own partition key.
+    pub fn has_cross_stripe_indexes(&self) -> bool {
+        !self.global_indexes.is_empty() || !self.geo_indexes.is_empty()
+    }
+
+    /// Projection and key attributes of an LSI or GSI by name (Phase 3.1+)
+    pub fn index_projection(&self, name: &str) -> Option<(&IndexProjection, Vec<&str>)> {
+        if let Some(lsi) = self.get_local_index(name) {
+            return Some((&lsi.projection, lsi.key_attributes()));
+        }
+        if let Some(geo) = self.get_geo_index(name) {
+            return Some((geo.projection(), geo.key_attributes()));
+        }
+        self.get_global_index(name)
+            .map(|gsi| (&gsi.projection, gsi.key_attributes()))
+    }
+
     /// Enable TTL (Time To Live) with the specified attribute name (Phase 3.3+)
     ///
     /// Items with this attribute containing a Unix timestamp (seconds since epoch)
@@ -177,6 +445,28 @@ impl TableSchema {
         self
     }
 
+    /// Schema that governs a stored key (Phase 3.7+)
+    ///
+    /// Items of a named table follow that table's schema; keys of a table that
+    /// no longer exists get an empty one, so no indexes or TTL apply.
+    pub fn for_key(&self, key: &Key) -> &TableSchema {
+        static EMPTY: OnceLock<TableSchema> = OnceLock::new();
+
+        match crate::table::table_name(&key.pk) {
+            Some(name) => self
+                .tables
+                .get(name)
+                .unwrap_or_else(|| EMPTY.get_or_init(TableSchema::new)),
+            None => self,
+        }
+    }
+
+    /// Whether this schema or any named table has TTL enabled (Phase 3.7+)
+    pub fn uses_ttl(&self) -> bool {
+        self.ttl_attribute_name.is_some()
+            || self.tables.values().any(|table| table.ttl_attribute_name.is_some())
+    }
+
     /// Check if an item is expired based on TTL (Phase 3.3+)
     ///
     /// Returns true if:
@@ -216,6 +506,44 @@ impl TableSchema {
         self
     }
 
+    /// Enforce a validator's attribute schemas on every write (Phase 8+)
+    ///
+    /// Puts, updates and transactional writes whose resulting item breaks a
+    /// schema fail with `Error::ValidationFailed`. A schema replaces any
+    /// earlier one for the same attribute.
+    pub fn with_validation(mut self, validator: crate::validation::Validator) -> Self {
+        for schema in validator.schemas() {
+            self.attribute_schemas.retain(|existing| existing.name != schema.name);
+            self.attribute_schemas.push(schema.clone());
+        }
+        self
+    }
+
+    /// Maintain `created_at` and `updated_at` timestamps on every write (Phase 8+)
+    pub fn with_timestamps(self) -> Self {
+        self.with_timestamp_attributes(crate::generated::TimestampAttributes::default())
+    }
+
+    /// Maintain creation and last write timestamps under other names (Phase 8+)
+    pub fn with_timestamp_attributes(mut self, timestamps: crate::generated::TimestampAttributes) -> Self {
+        self.timestamps = Some(timestamps);
+        self
+    }
+
+    /// Compute an attribute from the rest of the item on every write (Phase 8+)
+    pub fn with_computed(mut self, computed: crate::generated::ComputedAttribute) -> Self {
+        self.computed_attributes.retain(|existing| existing.name != computed.name);
+        self.computed_attributes.push(computed);
+        self
+    }
+
+    /// Whether writes fill in defaults, timestamps or computed attributes (Phase 8+)
+    pub fn has_generated_attributes(&self) -> bool {
+        self.timestamps.is_some()
+            || !self.computed_attributes.is_empty()
+            || self.attribute_schemas.iter().any(|schema| schema.default.is_some())
+    }
+
     /// Validate an item against the attribute schemas
     pub fn validate_item(&self, item: &crate::Item) -> crate::Result<()> {
         if self.attribute_schemas.is_empty() {
@@ -301,6 +629,25 @@ pub fn decode_index_key(encoded: &[u8]) -> Option<(String, Bytes, Bytes)> {
     Some((index_name, pk, index_sk))
 }
 
+/// Encode a base table key for `BASE_KEY_ATTRIBUTE`
+pub fn encode_base_key(key: &Key) -> Value {
+    let mut parts = vec![Value::B(key.pk.clone())];
+    parts.extend(key.sk.clone().map(Value::B));
+    Value::L(parts)
+}
+
+/// Decode a base table key stored by `encode_base_key`
+pub fn decode_base_key(value: &Value) -> Option<Key> {
+    match value {
+        Value::L(parts) => match parts.as_slice() {
+            [Value::B(pk)] => Some(Key::new(pk.clone())),
+            [Value::B(pk), Value::B(sk)] => Some(Key::with_sk(pk.clone(), sk.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /// Check if an encoded key is an index key
 pub fn is_index_key(encoded: &[u8]) -> bool {
     const INDEX_MARKER: u8 = 0xFF;
@@ -368,6 +715,105 @@ mod tests {
         assert!(!is_index_key(&base_key));
     }
 
+    #[test]
+    fn test_projection_project() {
+        let mut item = Item::new();
+        item.insert("email".to_string(), Value::string("a@example.com"));
+        item.insert("name".to_string(), Value::string("Alice"));
+        item.insert("age".to_string(), Value::number(30));
+        let base_key = Key::with_sk(b"org#1".to_vec(), b"user#1".to_vec());
+
+        let mut all = IndexProjection::All.project(&item, &["email"], &base_key);
+        assert_eq!(all.remove(BASE_KEY_ATTRIBUTE).as_ref().and_then(decode_base_key), Some(base_key.clone()));
+        assert_eq!(all, item);
+
+        let keys_only = IndexProjection::KeysOnly.project(&item, &["email"], &base_key);
+        assert_eq!(keys_only.len(), 2);
+        assert!(keys_only.contains_key("email"));
+        assert_eq!(decode_base_key(&keys_only[BASE_KEY_ATTRIBUTE]), Some(base_key.clone()));
+
+        let include = IndexProjection::Include(vec!["name".to_string(), "missing".to_string()])
+            .project(&item, &["email"], &base_key);
+        assert_eq!(include.len(), 3);
+        assert!(include.contains_key("name"));
+        assert!(!include.contains_key("age"));
+    }
+
+    #[test]
+    fn test_projection_covers() {
+        let paths = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert!(IndexProjection::All.covers(&["email"], Some(&paths(&["anything"])[..])));
+        assert!(IndexProjection::KeysOnly.covers(&["email"], None));
+        assert!(IndexProjection::KeysOnly.covers(&["email"], Some(&paths(&["email"])[..])));
+        assert!(!IndexProjection::KeysOnly.covers(&["email"], Some(&paths(&["name"])[..])));
+
+        let include = IndexProjection::Include(vec!["address".to_string()]);
+        assert!(include.covers(&["email"], Some(&paths(&["email", "address.city"])[..])));
+        assert!(!include.covers(&["email"], Some(&paths(&["age"])[..])));
+    }
+
+    #[test]
+    fn test_encode_decode_base_key() {
+        let key = Key::new(b"user#1".to_vec());
+        assert_eq!(decode_base_key(&encode_base_key(&key)), Some(key));
+
+        let key = Key::with_sk(b"user#1".to_vec(), b"profile".to_vec());
+        assert_eq!(decode_base_key(&encode_base_key(&key)), Some(key));
+
+        assert_eq!(decode_base_key(&Value::string("user#1")), None);
+    }
+
+    #[test]
+    fn test_index_backfill_progress() {
+        let mut backfill = IndexBackfill::new("status-index", 4);
+        assert_eq!(backfill.progress(), 0.0);
+        assert!(!backfill.is_complete());
+
+        backfill.next_stripe = 2;
+        assert_eq!(backfill.progress(), 0.5);
+
+        backfill.next_stripe = 4;
+        assert!(backfill.is_complete());
+
+        let schema = TableSchema {
+            index_backfills: vec![backfill],
+            ..TableSchema::new()
+        };
+        assert!(schema.is_backfilling("status-index"));
+        assert!(!schema.is_backfilling("other-index"));
+    }
+
+    #[test]
+    fn test_vector_index_vector_of() {
+        let schema = TableSchema::new().add_vector_index("embedding", 3, DistanceMetric::Cosine);
+        let index = schema.get_vector_index("embedding").unwrap();
+
+        let mut item = Item::new();
+        item.insert("embedding".to_string(), Value::VecF32(vec![1.0, 0.0, 0.0]));
+        assert_eq!(index.vector_of(&item), Some(&[1.0, 0.0, 0.0][..]));
+
+        // Wrong dimensions and other types aren't indexed
+        item.insert("embedding".to_string(), Value::VecF32(vec![1.0, 0.0]));
+        assert_eq!(index.vector_of(&item), None);
+        item.insert("embedding".to_string(), Value::string("not a vector"));
+        assert_eq!(index.vector_of(&item), None);
+
+        assert!(schema.get_vector_index("other").is_none());
+    }
+
+    #[test]
+    fn test_geo_index_schema() {
+        let schema = TableSchema::new().add_geo_index(GeoIndex::new("location-index", "lat", "lon"));
+        assert!(schema.has_indexes());
+        assert!(schema.has_cross_stripe_indexes());
+
+        let (projection, key_attributes) = schema.index_projection("location-index").unwrap();
+        assert_eq!(*projection, IndexProjection::KeysOnly);
+        assert_eq!(key_attributes, vec!["lat", "lon"]);
+        assert!(schema.get_geo_index("other").is_none());
+    }
+
     #[test]
     fn test_gsi_creation() {
         let gsi = GlobalSecondaryIndex::new("status-index", "status");
@@ -3,8 +3,14 @@
 /// Provides efficient iteration over memtable and SST files within a stripe,
 /// merging results with proper ordering (newest version wins).
 
-use crate::{Key, Item};
+use crate::{Error, Key, Item, Record, Result, Value};
+use crate::expression::{Expr, ExpressionContext, ExpressionEvaluator};
 use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+/// Records examined between deadline checks in long reads (Phase 8+)
+pub(crate) const DEADLINE_CHECK_INTERVAL: usize = 1024;
 
 /// Sort key comparison operator
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,6 +31,19 @@ pub enum SortKeyCondition {
     BeginsWith,
 }
 
+/// What a query or scan returns for matching items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Select {
+    /// Return the matching items (after projection)
+    #[default]
+    AllAttributes,
+    /// Only count matching items; `items` stays empty
+    Count,
+    /// Only return the keys of matching items, in `keys`; `items` stays
+    /// empty. Scans only: queries treat it like `Count` (Phase 8+)
+    Keys,
+}
+
 /// Query parameters for stripe iteration
 #[derive(Debug, Clone)]
 pub struct QueryParams {
@@ -34,12 +53,23 @@ pub struct QueryParams {
     pub sk_condition: Option<(SortKeyCondition, Bytes, Option<Bytes>)>,
     /// Scan direction
     pub forward: bool,
-    /// Maximum items to return
+    /// Maximum items to evaluate; like DynamoDB's `Limit`, items the filter
+    /// drops count too
     pub limit: Option<usize>,
     /// Start key for pagination (exclusive)
     pub start_key: Option<Key>,
     /// Index name for LSI queries (Phase 3.1+)
     pub index_name: Option<String>,
+    /// Filter expression applied after key matching
+    pub filter: Option<Expr>,
+    /// Placeholder values and names for the filter expression
+    pub filter_context: ExpressionContext,
+    /// Attribute paths to return (None = all attributes)
+    pub projection: Option<Vec<String>>,
+    /// Return items or only count them
+    pub select: Select,
+    /// Abort with `Error::DeadlineExceeded` once this passes (Phase 8+)
+    pub deadline: Option<Instant>,
 }
 
 impl QueryParams {
@@ -52,6 +82,11 @@ impl QueryParams {
             limit: None,
             start_key: None,
             index_name: None,
+            filter: None,
+            filter_context: ExpressionContext::new(),
+            projection: None,
+            select: Select::AllAttributes,
+            deadline: None,
         }
     }
 
@@ -84,12 +119,55 @@ impl QueryParams {
         self
     }
 
+    /// Abort the query with `Error::DeadlineExceeded` once `deadline` passes (Phase 8+)
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Fail if the deadline has passed
+    pub fn check_deadline(&self) -> Result<()> {
+        check_deadline(self.deadline, "query")
+    }
+
     /// Set index name for LSI query (Phase 3.1+)
     pub fn with_index_name(mut self, index_name: impl Into<String>) -> Self {
         self.index_name = Some(index_name.into());
         self
     }
 
+    /// Set filter expression and its context
+    pub fn with_filter(mut self, filter: Expr, context: ExpressionContext) -> Self {
+        self.filter = Some(filter);
+        self.filter_context = context;
+        self
+    }
+
+    /// Check if an item passes the filter expression
+    pub fn matches_filter(&self, item: &Item) -> bool {
+        matches_filter(self.filter.as_ref(), &self.filter_context, item)
+    }
+
+    /// Set attribute paths to return
+    pub fn with_projection(mut self, paths: Vec<String>) -> Self {
+        self.projection = Some(paths);
+        self
+    }
+
+    /// Set whether to return items or only count them
+    pub fn with_select(mut self, select: Select) -> Self {
+        self.select = select;
+        self
+    }
+
+    /// Apply the projection (if any) to a result item
+    pub fn project(&self, item: Item) -> Item {
+        match &self.projection {
+            Some(paths) => project_item(&item, paths),
+            None => item,
+        }
+    }
+
     /// Check if a sort key matches the condition
     pub fn matches_sk(&self, sk: &Option<Bytes>) -> bool {
         match &self.sk_condition {
@@ -138,28 +216,48 @@ impl QueryParams {
 /// Query result with pagination support
 #[derive(Debug, Clone)]
 pub struct QueryResult {
-    /// Items found
+    /// Items found (empty for `Select::Count`)
     pub items: Vec<Item>,
+    /// Number of matching items
+    pub count: usize,
     /// Last evaluated key (for pagination)
     pub last_key: Option<Key>,
     /// Count of items examined (before filter)
     pub scanned_count: usize,
+    /// Keys of the matching items: for scans with `Select::Keys`, and the
+    /// base table keys behind the matches of an index query
+    pub keys: Vec<Key>,
 }
 
 impl QueryResult {
     pub fn new(items: Vec<Item>, last_key: Option<Key>, scanned_count: usize) -> Self {
         Self {
+            count: items.len(),
             items,
             last_key,
             scanned_count,
+            keys: Vec::new(),
         }
     }
+
+    /// Set the keys of the matching items (for `Select::Keys` and index queries)
+    pub fn with_keys(mut self, keys: Vec<Key>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Override the match count (for `Select::Count`, where no items are kept)
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
 }
 
 /// Scan parameters for table/stripe scanning
 #[derive(Debug, Clone)]
 pub struct ScanParams {
-    /// Maximum items to return
+    /// Maximum items to evaluate; like DynamoDB's `Limit`, items the filter
+    /// drops count too
     pub limit: Option<usize>,
     /// Start key for pagination (exclusive)
     pub start_key: Option<Key>,
@@ -167,6 +265,20 @@ pub struct ScanParams {
     pub segment: Option<usize>,
     /// Total number of segments (for parallel scans)
     pub total_segments: Option<usize>,
+    /// Filter expression applied to every scanned item
+    pub filter: Option<Expr>,
+    /// Placeholder values and names for the filter expression
+    pub filter_context: ExpressionContext,
+    /// Attribute paths to return (None = all attributes)
+    pub projection: Option<Vec<String>>,
+    /// Return items or only count them
+    pub select: Select,
+    /// Named table to scan; None scans the default table (Phase 3.7+)
+    pub table: Option<String>,
+    /// Abort with `Error::DeadlineExceeded` once this passes (Phase 8+)
+    pub deadline: Option<Instant>,
+    /// Only scan items whose partition key starts with this
+    pub pk_prefix: Option<Bytes>,
 }
 
 impl ScanParams {
@@ -177,6 +289,13 @@ impl ScanParams {
             start_key: None,
             segment: None,
             total_segments: None,
+            filter: None,
+            filter_context: ExpressionContext::new(),
+            projection: None,
+            select: Select::AllAttributes,
+            table: None,
+            deadline: None,
+            pk_prefix: None,
         }
     }
 
@@ -192,6 +311,52 @@ impl ScanParams {
         self
     }
 
+    /// Only scan items whose partition key starts with `prefix`
+    ///
+    /// Items outside the prefix aren't counted in `scanned_count`.
+    pub fn with_pk_prefix(mut self, prefix: impl Into<Bytes>) -> Self {
+        self.pk_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Abort the scan with `Error::DeadlineExceeded` once `deadline` passes (Phase 8+)
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Fail if the deadline has passed
+    pub fn check_deadline(&self) -> Result<()> {
+        check_deadline(self.deadline, "scan")
+    }
+
+    /// Scan a named table instead of the default table (Phase 3.7+)
+    ///
+    /// Keys (start key and returned last key) are stored table keys.
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = Some(table.into());
+        self
+    }
+
+    /// Check if a stored key belongs to the table being scanned
+    pub fn in_table(&self, key: &Key) -> bool {
+        crate::table::table_name(&key.pk) == self.table.as_deref()
+    }
+
+    /// Check if a stored key is in the scanned table and partition key prefix
+    pub fn in_scope(&self, key: &Key) -> bool {
+        if !self.in_table(key) {
+            return false;
+        }
+        let Some(prefix) = &self.pk_prefix else {
+            return true;
+        };
+        match crate::table::decode_table_key(key) {
+            Some((_, key)) => key.pk.starts_with(prefix),
+            None => key.pk.starts_with(prefix),
+        }
+    }
+
     /// Set parallel scan parameters
     pub fn with_segment(mut self, segment: usize, total_segments: usize) -> Self {
         self.segment = Some(segment);
@@ -199,6 +364,38 @@ impl ScanParams {
         self
     }
 
+    /// Set filter expression and its context
+    pub fn with_filter(mut self, filter: Expr, context: ExpressionContext) -> Self {
+        self.filter = Some(filter);
+        self.filter_context = context;
+        self
+    }
+
+    /// Check if an item passes the filter expression
+    pub fn matches_filter(&self, item: &Item) -> bool {
+        matches_filter(self.filter.as_ref(), &self.filter_context, item)
+    }
+
+    /// Set attribute paths to return
+    pub fn with_projection(mut self, paths: Vec<String>) -> Self {
+        self.projection = Some(paths);
+        self
+    }
+
+    /// Set whether to return items or only count them
+    pub fn with_select(mut self, select: Select) -> Self {
+        self.select = select;
+        self
+    }
+
+    /// Apply the projection (if any) to a result item
+    pub fn project(&self, item: Item) -> Item {
+        match &self.projection {
+            Some(paths) => project_item(&item, paths),
+            None => item,
+        }
+    }
+
     /// Check if a stripe should be scanned by this segment
     pub fn should_scan_stripe(&self, stripe_id: usize) -> bool {
         match (self.segment, self.total_segments) {
@@ -230,6 +427,115 @@ impl Default for ScanParams {
 /// Scan result (same structure as QueryResult)
 pub type ScanResult = QueryResult;
 
+/// Fail with `Error::DeadlineExceeded` if `deadline` has passed
+fn check_deadline(deadline: Option<Instant>, operation: &str) -> Result<()> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(Error::DeadlineExceeded(format!(
+            "{} aborted at its deadline",
+            operation
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Evaluate an optional filter expression against an item
+///
+/// Items for which the filter cannot be evaluated (for example because a
+/// referenced attribute is missing) do not match, as in DynamoDB.
+fn matches_filter(filter: Option<&Expr>, context: &ExpressionContext, item: &Item) -> bool {
+    match filter {
+        None => true,
+        Some(expr) => ExpressionEvaluator::new(item, context)
+            .evaluate(expr)
+            .unwrap_or(false),
+    }
+}
+
+/// Build a copy of an item containing only the given attribute paths
+///
+/// Paths use dot notation for nested map attributes (e.g. `address.city`);
+/// intermediate maps are recreated with only the projected children.
+/// Paths that don't exist in the item are ignored.
+pub fn project_item(item: &Item, paths: &[String]) -> Item {
+    let mut projected = Item::new();
+    for path in paths {
+        let segments: Vec<&str> = path.split('.').collect();
+        if let Some(value) = lookup_path(item, &segments) {
+            insert_path(&mut projected, &segments, value.clone());
+        }
+    }
+    projected
+}
+
+/// Look up a (possibly nested) attribute path in an item
+fn lookup_path<'a>(item: &'a Item, segments: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = segments.split_first()?;
+    let mut current = item.get(*first)?;
+    for segment in rest {
+        current = current.as_map()?.get(*segment)?;
+    }
+    Some(current)
+}
+
+/// Insert a value at a nested path, creating intermediate maps as needed
+fn insert_path(item: &mut Item, segments: &[&str], value: Value) {
+    match segments {
+        [] => {}
+        [last] => {
+            item.insert(last.to_string(), value);
+        }
+        [first, rest @ ..] => {
+            let entry = item
+                .entry(first.to_string())
+                .or_insert_with(|| Value::M(HashMap::new()));
+            if let Value::M(map) = entry {
+                insert_path(map, rest, value);
+            }
+        }
+    }
+}
+
+/// Merge a record into a sorted result set, keeping the newest version
+///
+/// Records from the memtable and every SST of a stripe are fed through this
+/// function; when the same key appears more than once, the record with the
+/// highest sequence number wins (tombstones included, so deletes shadow
+/// older puts). Results are ordered by `Key`, the order `should_skip`
+/// paginates in; encoded keys are length-prefixed and sort differently.
+pub(crate) fn merge_newest(records: &mut BTreeMap<Key, Record>, record: Record) {
+    match records.get(&record.key) {
+        Some(existing) if existing.seq >= record.seq => {}
+        _ => {
+            records.insert(record.key.clone(), record);
+        }
+    }
+}
+
+/// Records in `Key` order, one of the inputs of `merge_sorted_newest`
+pub(crate) type SortedRecords<'a> = Box<dyn Iterator<Item = &'a Record> + 'a>;
+
+/// Merge sources that are each sorted by `Key`, keeping the newest version (Phase 8+)
+///
+/// The lazy counterpart of `merge_newest`: records come out in `Key` order,
+/// one per key, the one with the highest sequence number (tombstones
+/// included). A caller that stops early only reads the records before
+/// that point.
+pub(crate) fn merge_sorted_newest<'a>(sources: Vec<SortedRecords<'a>>) -> impl Iterator<Item = &'a Record> + 'a {
+    let mut sources: Vec<_> = sources.into_iter().map(Iterator::peekable).collect();
+    std::iter::from_fn(move || {
+        let key = sources.iter_mut().filter_map(|source| source.peek()).map(|record| &record.key).min()?.clone();
+        let mut newest: Option<&'a Record> = None;
+        for source in &mut sources {
+            while let Some(record) = source.next_if(|record| record.key == key) {
+                if newest.is_none_or(|newest| record.seq > newest.seq) {
+                    newest = Some(record);
+                }
+            }
+        }
+        newest
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,4 +610,90 @@ mod tests {
         assert!(params.should_skip(&key2)); // sk3 == sk3
         assert!(!params.should_skip(&key3)); // sk5 > sk3
     }
+
+    #[test]
+    fn test_query_params_filter() {
+        use crate::expression::ExpressionParser;
+        use crate::Value;
+
+        let filter = ExpressionParser::parse("status = :s").unwrap();
+        let context = ExpressionContext::new().with_value(":s", Value::string("active"));
+        let params = QueryParams::new(Bytes::from("pk1")).with_filter(filter, context);
+
+        let mut active = Item::new();
+        active.insert("status".to_string(), Value::string("active"));
+        let mut inactive = Item::new();
+        inactive.insert("status".to_string(), Value::string("inactive"));
+
+        assert!(params.matches_filter(&active));
+        assert!(!params.matches_filter(&inactive));
+        // Missing attribute never matches
+        assert!(!params.matches_filter(&Item::new()));
+        // No filter matches everything
+        assert!(QueryParams::new(Bytes::from("pk1")).matches_filter(&Item::new()));
+    }
+
+    #[test]
+    fn test_project_item() {
+        let mut address = HashMap::new();
+        address.insert("city".to_string(), Value::string("Berlin"));
+        address.insert("zip".to_string(), Value::string("10115"));
+
+        let mut item = Item::new();
+        item.insert("name".to_string(), Value::string("Alice"));
+        item.insert("status".to_string(), Value::string("active"));
+        item.insert("bio".to_string(), Value::string("a very long text"));
+        item.insert("address".to_string(), Value::M(address));
+
+        let paths = vec![
+            "name".to_string(),
+            "address.city".to_string(),
+            "missing".to_string(),
+            "name.nested".to_string(),
+        ];
+        let projected = project_item(&item, &paths);
+
+        assert_eq!(projected.len(), 2);
+        assert_eq!(projected.get("name"), Some(&Value::string("Alice")));
+
+        let projected_address = projected.get("address").unwrap().as_map().unwrap();
+        assert_eq!(projected_address.len(), 1);
+        assert_eq!(projected_address.get("city"), Some(&Value::string("Berlin")));
+    }
+
+    #[test]
+    fn test_merge_newest_keeps_highest_seq() {
+        let key = Key::new(b"pk1".to_vec());
+        let mut records = BTreeMap::new();
+
+        merge_newest(&mut records, Record::put(key.clone(), Item::new(), 5));
+        merge_newest(&mut records, Record::put(key.clone(), Item::new(), 3));
+        assert_eq!(records[&key].seq, 5);
+
+        merge_newest(&mut records, Record::delete(key.clone(), 7));
+        assert_eq!(records[&key].seq, 7);
+        assert!(records[&key].is_tombstone());
+    }
+    #[test]
+    fn test_merge_sorted_newest_is_lazy() {
+        let key = |pk: &str| Key::new(pk.as_bytes().to_vec());
+        let newer = [Record::put(key("a"), Item::new(), 4), Record::delete(key("c"), 6)];
+        let older = [
+            Record::put(key("a"), Item::new(), 1),
+            Record::put(key("b"), Item::new(), 2),
+            Record::put(key("c"), Item::new(), 3),
+        ];
+
+        let merged: Vec<_> = merge_sorted_newest(vec![Box::new(newer.iter()), Box::new(older.iter())])
+            .map(|record| (record.key.clone(), record.seq))
+            .collect();
+        assert_eq!(merged, vec![(key("a"), 4), (key("b"), 2), (key("c"), 6)]);
+
+        // Taking one record reads no further than the first key
+        let mut read = 0;
+        let counted = older.iter().inspect(|_| read += 1);
+        let first = merge_sorted_newest(vec![Box::new(counted)]).next().unwrap();
+        assert_eq!(first.seq, 1);
+        assert_eq!(read, 2); // The record after it, peeked to see the key ends
+    }
 }
@@ -3,6 +3,7 @@ pub mod types;
 pub mod layout;
 pub mod block;
 pub mod extent;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod mmap;
 pub mod bloom; // Phase 1.4+ bloom filters
 pub mod wal;
@@ -12,6 +13,7 @@ pub mod memory_sst; // Phase 5+ in-memory SST
 pub mod memory_lsm; // Phase 5+ in-memory LSM engine
 pub mod sst;
 pub mod sst_block; // Phase 1.4+ block-based SST
+pub mod block_cache; // Phase 1.4+ shared LRU block cache
 pub mod compaction; // Phase 5+ background compaction
 pub mod background; // Phase 1.7+ background task management
 pub mod manifest; // Phase 1.5+ metadata catalog
@@ -20,16 +22,45 @@ pub mod iterator; // Phase 2.1+ query/scan support
 pub mod expression; // Phase 2.3+ expression system
 pub mod index; // Phase 3.1+ index support (LSI, GSI)
 pub mod stream; // Phase 3.4+ change data capture (streams)
+pub mod stream_log; // Phase 3.4+ durable stream segments
+pub mod stream_subscription; // Phase 3.4+ push-based stream consumers
 pub mod partiql; // Phase 4+ PartiQL (SQL-compatible query language)
 pub mod config; // Phase 8+ database configuration
 pub mod retry; // Phase 8+ retry logic with exponential backoff
+pub mod backup; // Phase 8+ online backup and restore
+pub mod lock; // Phase 8+ single-writer directory lock
 pub mod validation; // Schema validation and constraints
+pub mod generated; // Phase 8+ default, timestamp and computed attributes
+pub mod vector; // Phase 3.5+ vector similarity indexes
+pub mod geo; // Phase 3.6+ geospatial indexes
+pub mod table; // Phase 3.7+ named tables
+pub mod vlog; // Phase 8+ value log for large binary values
+pub mod tiering; // Phase 8+ tiered storage for cold SSTs
+pub mod slow_log; // Phase 8+ slow operation log
+pub mod op_stats; // Phase 8+ operation counters and hot partitions
+pub mod store; // Phase 6+ backend-neutral store trait
+pub mod verify; // Phase 8+ consistency checker
+pub mod storage; // Phase 8+ pluggable storage for in-memory databases
 
 pub use error::{Error, Result};
 pub use types::*;
-pub use lsm::{LsmEngine, TransactWriteOperation};
+pub use lsm::{BulkLoadStats, LsmEngine, Snapshot, SstStats, StripeStats, StripeUsage, TransactWriteOperation};
 pub use memory_lsm::MemoryLsmEngine;
-pub use compaction::{CompactionConfig, CompactionStats};
-pub use config::DatabaseConfig;
+pub use compaction::{CompactionConfig, CompactionFilter, CompactionStats, FilterDecision};
+pub use config::{DatabaseConfig, WalSyncMode};
+pub use backup::BackupInfo;
+pub use verify::{Problem, ProblemKind, VerifyReport};
+pub use stream_log::StreamTail;
+pub use storage::{MemoryStorage, Storage};
+#[cfg(not(target_arch = "wasm32"))]
+pub use storage::FsStorage;
+pub use bloom::BloomStats;
+pub use tiering::{ColdStore, FsColdStore, TieringStats};
+pub use slow_log::{SlowOperation, SlowOperationKind};
+pub use op_stats::{HotPartition, OperationStats};
 pub use retry::{RetryPolicy, retry_with_policy, retry};
-pub use validation::{AttributeSchema, AttributeType, ValueConstraint, Validator};
+pub use validation::{AttributeSchema, AttributeType, ValidationError, ValidationViolation, ValueConstraint, Validator};
+pub use generated::{ComputedAttribute, DefaultValue, TimestampAttributes};
+pub use vector::{DistanceMetric, VectorMatch};
+pub use geo::{GeoBox, GeoMatch, GeoPoint};
+pub use store::{KeystoneStore, SkCondition, StatementResult, StorePage, StoreQuery, StoreScan, StoreUpdate, StoreWrite};
@@ -27,6 +27,12 @@ use crate::{
 /// Manifest sequence number
 pub type ManifestSeq = u64;
 
+/// Current table schema format version
+///
+/// Bump this when `TableSchema` gains fields whose absence would change
+/// behavior for older readers. Version 2 added named tables (Phase 3.7+).
+pub const SCHEMA_VERSION: u32 = 2;
+
 /// Manifest record types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ManifestRecord {
@@ -61,6 +67,20 @@ pub enum ManifestRecord {
     UpdateSchema {
         schema: TableSchema,
     },
+
+    /// Update table schema with a format version
+    ///
+    /// The schema is stored as JSON so fields added in later versions
+    /// deserialize with their defaults instead of breaking the manifest.
+    UpdateSchemaVersioned {
+        version: u32,
+        schema_json: Vec<u8>,
+    },
+
+    /// Database creation time in milliseconds since the epoch (Phase 8+)
+    Created {
+        created_at: i64,
+    },
 }
 
 /// SST metadata
@@ -87,6 +107,10 @@ pub struct ManifestState {
     pub stripe_assignments: BTreeMap<u8, Vec<u64>>, // stripe -> sst_ids
     /// Table schema with index definitions (Phase 3.1+)
     pub schema: TableSchema,
+    /// Format version of the stored schema (0 = unversioned)
+    pub schema_version: u32,
+    /// Creation time in milliseconds since the epoch (None for older databases)
+    pub created_at: Option<i64>,
 }
 
 impl Default for ManifestState {
@@ -97,6 +121,8 @@ impl Default for ManifestState {
             checkpoint_seq: 0,
             stripe_assignments: BTreeMap::new(),
             schema: TableSchema::new(),
+            schema_version: 0,
+            created_at: None,
         }
     }
 }
@@ -144,9 +170,18 @@ impl Manifest {
 
     /// Open existing manifest and recover state
     pub fn open(path: impl AsRef<Path>, region: Region) -> Result<Self> {
+        Self::open_with(path, region, false)
+    }
+
+    /// Open existing manifest without write access; appends will fail
+    pub fn open_read_only(path: impl AsRef<Path>, region: Region) -> Result<Self> {
+        Self::open_with(path, region, true)
+    }
+
+    fn open_with(path: impl AsRef<Path>, region: Region, read_only: bool) -> Result<Self> {
         let mut file = OpenOptions::new()
             .read(true)
-            .write(true)
+            .write(!read_only)
             .open(path)?;
 
         // Recover all records
@@ -158,7 +193,7 @@ impl Manifest {
 
         for (seq, record) in records {
             max_seq = max_seq.max(seq);
-            Self::apply_record(&mut state, record);
+            Self::apply_record(&mut state, record)?;
         }
 
         Ok(Self {
@@ -180,10 +215,10 @@ impl Manifest {
         let seq = inner.next_seq;
         inner.next_seq += 1;
 
-        inner.pending.push((seq, record.clone()));
-
         // Apply to in-memory state
-        Self::apply_record(&mut inner.state, record);
+        Self::apply_record(&mut inner.state, record.clone())?;
+
+        inner.pending.push((seq, record));
 
         Ok(seq)
     }
@@ -244,8 +279,10 @@ impl Manifest {
     }
 
     /// Update table schema (Phase 3.1+)
+    ///
+    /// Written as a versioned record tagged with `SCHEMA_VERSION`.
     pub fn update_schema(&self, schema: TableSchema) -> Result<ManifestSeq> {
-        self.append(ManifestRecord::UpdateSchema { schema })
+        self.append(Self::schema_record(&schema)?)
     }
 
     /// Get current table schema (Phase 3.1+)
@@ -254,6 +291,37 @@ impl Manifest {
         inner.state.schema.clone()
     }
 
+    /// Record when the database was created (Phase 8+)
+    pub fn set_created_at(&self, created_at: i64) -> Result<ManifestSeq> {
+        self.append(ManifestRecord::Created { created_at })
+    }
+
+    /// Get the database creation time, if recorded (Phase 8+)
+    pub fn created_at(&self) -> Option<i64> {
+        let inner = self.inner.lock();
+        inner.state.created_at
+    }
+
+    /// Record that WAL records up to `lsn` are in SSTs (Phase 8+)
+    ///
+    /// `seq` is the highest sequence number in use, which recovery can't
+    /// learn from the skipped WAL records.
+    pub fn set_checkpoint(&self, lsn: Lsn, seq: SeqNo) -> Result<ManifestSeq> {
+        self.append(ManifestRecord::Checkpoint { lsn, seq })
+    }
+
+    /// Get the checkpoint LSN and sequence number (0 if never set)
+    pub fn checkpoint(&self) -> (Lsn, SeqNo) {
+        let inner = self.inner.lock();
+        (inner.state.checkpoint_lsn, inner.state.checkpoint_seq)
+    }
+
+    /// Get the format version of the current table schema
+    pub fn schema_version(&self) -> u32 {
+        let inner = self.inner.lock();
+        inner.state.schema_version
+    }
+
     /// Compact manifest by rewriting only active records
     pub fn compact(&self) -> Result<()> {
         let mut inner = self.inner.lock();
@@ -288,13 +356,13 @@ impl Manifest {
         inner.next_seq += 1;
 
         // Add schema record (Phase 3.1+)
-        if !inner.state.schema.local_indexes.is_empty() || !inner.state.schema.global_indexes.is_empty() {
-            records.push((
-                inner.next_seq,
-                ManifestRecord::UpdateSchema {
-                    schema: inner.state.schema.clone(),
-                },
-            ));
+        // Always written: TTL and stream settings live in the schema too
+        let schema_record = Self::schema_record(&inner.state.schema)?;
+        records.push((inner.next_seq, schema_record));
+        inner.next_seq += 1;
+
+        if let Some(created_at) = inner.state.created_at {
+            records.push((inner.next_seq, ManifestRecord::Created { created_at }));
             inner.next_seq += 1;
         }
 
@@ -310,7 +378,16 @@ impl Manifest {
 
     // Internal helpers
 
-    fn apply_record(state: &mut ManifestState, record: ManifestRecord) {
+    fn schema_record(schema: &TableSchema) -> Result<ManifestRecord> {
+        let schema_json = serde_json::to_vec(schema)
+            .map_err(|e| Error::Internal(format!("Schema serialize error: {}", e)))?;
+        Ok(ManifestRecord::UpdateSchemaVersioned {
+            version: SCHEMA_VERSION,
+            schema_json,
+        })
+    }
+
+    fn apply_record(state: &mut ManifestState, record: ManifestRecord) -> Result<()> {
         match record {
             ManifestRecord::AddSst {
                 sst_id,
@@ -362,8 +439,27 @@ impl Manifest {
 
             ManifestRecord::UpdateSchema { schema } => {
                 state.schema = schema;
+                state.schema_version = 0;
+            }
+
+            ManifestRecord::UpdateSchemaVersioned { version, schema_json } => {
+                if version > SCHEMA_VERSION {
+                    return Err(Error::ManifestCorruption(format!(
+                        "Schema version {} is newer than supported version {}",
+                        version, SCHEMA_VERSION
+                    )));
+                }
+                state.schema = serde_json::from_slice(&schema_json)
+                    .map_err(|e| Error::ManifestCorruption(format!("Invalid schema: {}", e)))?;
+                state.schema_version = version;
+            }
+
+            ManifestRecord::Created { created_at } => {
+                state.created_at = Some(created_at);
             }
         }
+
+        Ok(())
     }
 
     fn recover(file: &mut File, region: &Region) -> Result<Vec<(ManifestSeq, ManifestRecord)>> {
@@ -619,4 +715,60 @@ mod tests {
         let state = manifest.state();
         assert_eq!(state.ssts.len(), 5);
     }
+
+    #[test]
+    fn test_manifest_created_at() {
+        let tmp = NamedTempFile::new().unwrap();
+        let region = Region::new(0, 64 * 1024);
+
+        {
+            let manifest = Manifest::create(tmp.path(), region).unwrap();
+            assert_eq!(manifest.created_at(), None);
+            manifest.set_created_at(1_700_000_000_000).unwrap();
+            manifest.flush().unwrap();
+            manifest.compact().unwrap();
+        }
+
+        let manifest = Manifest::open(tmp.path(), region).unwrap();
+        assert_eq!(manifest.created_at(), Some(1_700_000_000_000));
+    }
+
+    #[test]
+    fn test_manifest_schema_persistence() {
+        use crate::index::LocalSecondaryIndex;
+        use crate::stream::StreamConfig;
+
+        let tmp = NamedTempFile::new().unwrap();
+        let region = Region::new(0, 64 * 1024);
+
+        {
+            let manifest = Manifest::create(tmp.path(), region).unwrap();
+            let schema = TableSchema::new()
+                .add_local_index(LocalSecondaryIndex::new("email-index", "email"))
+                .with_ttl("expires_at")
+                .with_stream(StreamConfig::enabled());
+            manifest.update_schema(schema).unwrap();
+            manifest.flush().unwrap();
+        }
+
+        let manifest = Manifest::open(tmp.path(), region).unwrap();
+        let schema = manifest.get_schema();
+        assert_eq!(manifest.schema_version(), SCHEMA_VERSION);
+        assert_eq!(schema.local_indexes.len(), 1);
+        assert_eq!(schema.ttl_attribute_name, Some("expires_at".to_string()));
+        assert!(schema.stream_config.enabled);
+    }
+
+    #[test]
+    fn test_manifest_rejects_newer_schema_version() {
+        let tmp = NamedTempFile::new().unwrap();
+        let region = Region::new(0, 64 * 1024);
+
+        let manifest = Manifest::create(tmp.path(), region).unwrap();
+        let result = manifest.append(ManifestRecord::UpdateSchemaVersioned {
+            version: SCHEMA_VERSION + 1,
+            schema_json: b"{}".to_vec(),
+        });
+        assert!(matches!(result, Err(Error::ManifestCorruption(_))));
+    }
 }
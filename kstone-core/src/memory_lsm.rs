@@ -4,20 +4,37 @@
 /// All data is lost when the MemoryLsmEngine is dropped.
 
 use crate::{
-    Result, Key, Item, Record, Error,
+    Error, Result, Key, Item, Record,
     memory_wal::MemoryWal,
     memory_sst::{MemorySstWriter, MemorySstReader},
     index::TableSchema,
-    iterator::{QueryParams, QueryResult, ScanParams, ScanResult},
-    expression::{UpdateAction, UpdateExecutor, ExpressionContext, ExpressionEvaluator, Expr},
-    lsm::TransactWriteOperation,
+    iterator::{QueryParams, QueryResult, ScanParams, ScanResult, Select, DEADLINE_CHECK_INTERVAL},
+    expression::{UpdateAction, UpdateExecutor, ExpressionContext, ExpressionEvaluator, Expr, check_condition},
+    lsm::{transaction_canceled, TransactWriteOperation},
+    storage::Storage,
+    table,
+    generated,
 };
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 const NUM_STRIPES: usize = 256;
 const MEMTABLE_THRESHOLD: usize = 1000;
 
+/// Name of the object `save_to` writes (Phase 8+)
+pub const SNAPSHOT_OBJECT: &str = "keystone.snapshot";
+
+/// Everything `save_to` persists; the schema is JSON, as in the manifest
+#[derive(Serialize, Deserialize)]
+struct MemorySnapshot {
+    schema_json: Vec<u8>,
+    created_at: i64,
+    next_seq: u64,
+    records: Vec<Record>,
+}
+
 /// Calculate stripe ID from partition key
 fn stripe_id(pk: &[u8]) -> usize {
     crc32fast::hash(pk) as usize % NUM_STRIPES
@@ -52,6 +69,8 @@ struct MemoryLsmInner {
     next_sst_id: u64,
     /// Table schema (for indexes, TTL, streams)
     schema: TableSchema,
+    /// Creation time in milliseconds since the epoch
+    created_at: i64,
 }
 
 /// In-memory LSM Engine
@@ -78,14 +97,71 @@ impl MemoryLsmEngine {
                 next_seq: 1,
                 next_sst_id: 1,
                 schema,
+                created_at: crate::stream::current_timestamp_millis(),
             })),
         })
     }
 
+    /// When the database was created, in milliseconds since the epoch (Phase 8+)
+    pub fn created_at(&self) -> i64 {
+        self.inner.read().unwrap().created_at
+    }
+
     /// Put an item
     pub fn put(&self, key: Key, item: Item) -> Result<()> {
         let mut inner = self.inner.write().unwrap();
+        let old_item = if Self::generates_attributes(&inner, &key) {
+            Self::current_item(&inner, &key)
+        } else {
+            None
+        };
+        let item = Self::generate_attributes(&inner, &key, old_item.as_ref(), item)?;
+        Self::put_locked(&mut inner, key, item)
+    }
+
+    /// Put an item and return the item it replaced, atomically
+    pub fn put_returning_old(
+        &self,
+        key: Key,
+        item: Item,
+        condition: Option<(&Expr, &ExpressionContext)>,
+    ) -> Result<Option<Item>> {
+        let mut inner = self.inner.write().unwrap();
+
+        let old_item = Self::current_item(&inner, &key);
+        check_condition(old_item.as_ref(), condition, "Put condition failed")?;
+
+        let item = Self::generate_attributes(&inner, &key, old_item.as_ref(), item)?;
+        Self::put_locked(&mut inner, key, item)?;
+        Ok(old_item)
+    }
+
+    /// Reject items that break the table's attribute schemas (Phase 8+)
+    ///
+    /// Sync metadata is internal and never validated.
+    fn check_item_schema(inner: &MemoryLsmInner, key: &Key, item: &Item) -> Result<()> {
+        if key.pk.starts_with(b"_sync#") {
+            return Ok(());
+        }
+        inner.schema.for_key(key).validate_item(item)
+    }
+
+    /// Whether writes to a key fill in generated attributes (Phase 8+)
+    fn generates_attributes(inner: &MemoryLsmInner, key: &Key) -> bool {
+        !key.pk.starts_with(b"_sync#") && inner.schema.for_key(key).has_generated_attributes()
+    }
+
+    /// Fill in the defaults, timestamps and computed attributes of an item replacing `old` (Phase 8+)
+    fn generate_attributes(inner: &MemoryLsmInner, key: &Key, old: Option<&Item>, mut item: Item) -> Result<Item> {
+        if Self::generates_attributes(inner, key) {
+            generated::apply(inner.schema.for_key(key), old, &mut item)?;
+        }
+        Ok(item)
+    }
 
+    /// Write a put while holding the write lock
+    fn put_locked(inner: &mut MemoryLsmInner, key: Key, item: Item) -> Result<()> {
+        Self::check_item_schema(inner, &key, &item)?;
         let seq = inner.next_seq;
         inner.next_seq += 1;
 
@@ -100,12 +176,28 @@ impl MemoryLsmEngine {
 
         // Check if memtable needs flushing
         if inner.stripes[stripe_idx].memtable.len() >= MEMTABLE_THRESHOLD {
-            Self::flush_stripe(&mut inner, stripe_idx)?;
+            Self::flush_stripe(inner, stripe_idx)?;
         }
 
         Ok(())
     }
 
+    /// Current value of an item (caller holds the lock)
+    fn current_item(inner: &MemoryLsmInner, key: &Key) -> Option<Item> {
+        let stripe = &inner.stripes[stripe_id(&key.pk)];
+        let key_bytes = key.encode();
+
+        match stripe.memtable.get(key_bytes.as_ref()) {
+            Some(record) => record.value.clone(),
+            None => stripe
+                .ssts
+                .iter()
+                .rev()
+                .find_map(|sst| sst.get(key))
+                .and_then(|record| record.value.clone()),
+        }
+    }
+
     /// Get an item
     pub fn get(&self, key: &Key) -> Result<Option<Item>> {
         let inner = self.inner.read().unwrap();
@@ -131,7 +223,26 @@ impl MemoryLsmEngine {
     /// Delete an item
     pub fn delete(&self, key: Key) -> Result<()> {
         let mut inner = self.inner.write().unwrap();
+        Self::delete_locked(&mut inner, key)
+    }
+
+    /// Delete an item and return the item that was removed, atomically
+    pub fn delete_returning_old(
+        &self,
+        key: Key,
+        condition: Option<(&Expr, &ExpressionContext)>,
+    ) -> Result<Option<Item>> {
+        let mut inner = self.inner.write().unwrap();
+
+        let old_item = Self::current_item(&inner, &key);
+        check_condition(old_item.as_ref(), condition, "Delete condition failed")?;
 
+        Self::delete_locked(&mut inner, key)?;
+        Ok(old_item)
+    }
+
+    /// Write a delete while holding the write lock
+    fn delete_locked(inner: &mut MemoryLsmInner, key: Key) -> Result<()> {
         let seq = inner.next_seq;
         inner.next_seq += 1;
 
@@ -146,7 +257,7 @@ impl MemoryLsmEngine {
 
         // Check if memtable needs flushing
         if inner.stripes[stripe_idx].memtable.len() >= MEMTABLE_THRESHOLD {
-            Self::flush_stripe(&mut inner, stripe_idx)?;
+            Self::flush_stripe(inner, stripe_idx)?;
         }
 
         Ok(())
@@ -211,6 +322,130 @@ impl MemoryLsmEngine {
         Ok(())
     }
 
+    /// Save the schema and every live record to `storage` (Phase 8+)
+    ///
+    /// Writes one `SNAPSHOT_OBJECT`, so a failed save leaves the previous
+    /// snapshot in place. Tombstones and overwritten versions are dropped.
+    pub fn save_to(&self, storage: &dyn Storage) -> Result<()> {
+        let inner = self.inner.read().unwrap();
+
+        let mut records = Vec::new();
+        for stripe in &inner.stripes {
+            // Newest version first: memtable, then SSTs newest to oldest
+            let mut seen = HashSet::new();
+            let versions = stripe
+                .memtable
+                .values()
+                .chain(stripe.ssts.iter().rev().flat_map(|sst| sst.iter()));
+            for record in versions {
+                if seen.insert(record.key.encode()) && record.value.is_some() {
+                    records.push(record.clone());
+                }
+            }
+        }
+
+        let snapshot = MemorySnapshot {
+            schema_json: serde_json::to_vec(&inner.schema)
+                .map_err(|e| Error::Internal(format!("Schema serialize error: {}", e)))?,
+            created_at: inner.created_at,
+            next_seq: inner.next_seq,
+            records,
+        };
+        let data = bincode::serialize(&snapshot)
+            .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+
+        // [data | crc32(4)]
+        let mut buf = BytesMut::with_capacity(data.len() + 4);
+        buf.put_slice(&data);
+        buf.put_u32_le(crc32fast::hash(&data));
+        storage.write(SNAPSHOT_OBJECT, buf.freeze())
+    }
+
+    /// Restore a database saved with `save_to` (Phase 8+)
+    pub fn load_from(storage: &dyn Storage) -> Result<Self> {
+        let buf: Bytes = storage
+            .read(SNAPSHOT_OBJECT)?
+            .ok_or_else(|| Error::NotFound(format!("No {} in storage", SNAPSHOT_OBJECT)))?;
+        if buf.len() < 4 {
+            return Err(Error::Corruption("Truncated database snapshot".to_string()));
+        }
+        let (data, crc) = buf.split_at(buf.len() - 4);
+        if crc32fast::hash(data) != u32::from_le_bytes(crc.try_into().unwrap()) {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let snapshot: MemorySnapshot = bincode::deserialize(data)
+            .map_err(|e| Error::Corruption(format!("Invalid database snapshot: {}", e)))?;
+        let schema: TableSchema = serde_json::from_slice(&snapshot.schema_json)
+            .map_err(|e| Error::Corruption(format!("Invalid schema in snapshot: {}", e)))?;
+
+        let engine = Self::create_with_schema(schema)?;
+        {
+            let mut inner = engine.inner.write().unwrap();
+            inner.created_at = snapshot.created_at;
+            inner.next_seq = snapshot.next_seq;
+            for record in snapshot.records {
+                let stripe_idx = stripe_id(&record.key.pk);
+                inner.stripes[stripe_idx].memtable.insert(record.key.encode().to_vec(), record);
+            }
+            for stripe_idx in 0..NUM_STRIPES {
+                Self::flush_stripe(&mut inner, stripe_idx)?;
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Create a named table (Phase 3.7+)
+    pub fn create_table(&self, name: &str, schema: TableSchema) -> Result<()> {
+        table::validate_table_name(name)?;
+        table::validate_table_schema(&schema)?;
+
+        let mut inner = self.inner.write().unwrap();
+        if inner.schema.tables.contains_key(name) {
+            return Err(Error::AlreadyExists(format!("Table '{}'", name)));
+        }
+        inner.schema.tables.insert(name.to_string(), schema);
+        Ok(())
+    }
+
+    /// Remove a named table and delete its items (Phase 3.7+)
+    pub fn drop_table(&self, name: &str) -> Result<()> {
+        let mut inner = self.inner.write().unwrap();
+        if inner.schema.tables.remove(name).is_none() {
+            return Err(Error::NotFound(format!("Table '{}'", name)));
+        }
+
+        let mut keys = HashSet::new();
+        for stripe in &inner.stripes {
+            let ssts = stripe.ssts.iter().flat_map(|sst| sst.iter());
+            for record in stripe.memtable.values().chain(ssts) {
+                if table::table_name(&record.key.pk) == Some(name) {
+                    keys.insert(record.key.clone());
+                }
+            }
+        }
+
+        for key in keys {
+            Self::delete_locked(&mut inner, key)?;
+        }
+        Ok(())
+    }
+
+    /// Schema of a named table, if it exists (Phase 3.7+)
+    pub fn table_schema(&self, name: &str) -> Option<TableSchema> {
+        self.inner.read().unwrap().schema.tables.get(name).cloned()
+    }
+
+    /// Names of all named tables, sorted (Phase 3.7+)
+    pub fn list_tables(&self) -> Vec<String> {
+        self.inner.read().unwrap().schema.tables.keys().cloned().collect()
+    }
+
+    /// Get the table schema
+    pub fn schema(&self) -> TableSchema {
+        self.inner.read().unwrap().schema.clone()
+    }
+
     /// Get the number of items in memory (approximate)
     pub fn len(&self) -> usize {
         let inner = self.inner.read().unwrap();
@@ -233,17 +468,18 @@ impl MemoryLsmEngine {
 
     /// Query items within a partition
     pub fn query(&self, params: QueryParams) -> Result<QueryResult> {
+        params.check_deadline()?;
         let inner = self.inner.read().unwrap();
 
         // Route to correct stripe
         let stripe_id = stripe_id(&params.pk);
         let stripe = &inner.stripes[stripe_id];
 
-        let mut all_records: BTreeMap<Vec<u8>, Record> = BTreeMap::new();
+        let mut all_records: BTreeMap<Key, Record> = BTreeMap::new();
         let mut scanned_count = 0;
 
         // Collect from memtable
-        for (key_enc, record) in &stripe.memtable {
+        for record in stripe.memtable.values() {
             // Check if PK matches
             if record.key.pk != params.pk {
                 continue;
@@ -254,7 +490,7 @@ impl MemoryLsmEngine {
                 continue;
             }
 
-            all_records.insert(key_enc.clone(), record.clone());
+            all_records.insert(record.key.clone(), record.clone());
         }
 
         // Collect from SSTs
@@ -270,14 +506,13 @@ impl MemoryLsmEngine {
                     continue;
                 }
 
-                let key_enc = record.key.encode().to_vec();
                 // Only add if we don't already have this key (memtable is newer)
-                all_records.entry(key_enc).or_insert(record.clone());
+                all_records.entry(record.key.clone()).or_insert(record.clone());
             }
         }
 
         // Convert to sorted vec
-        let mut sorted_records: Vec<(Vec<u8>, Record)> = all_records.into_iter().collect();
+        let mut sorted_records: Vec<(Key, Record)> = all_records.into_iter().collect();
 
         if !params.forward {
             sorted_records.reverse();
@@ -285,22 +520,26 @@ impl MemoryLsmEngine {
 
         // Apply pagination and limit
         let mut items = Vec::new();
+        let mut count = 0;
         let mut last_key = None;
-        let mut seen_keys: HashSet<Vec<u8>> = HashSet::new();
+        let mut seen_keys: HashSet<Key> = HashSet::new();
 
-        for (key_enc, record) in sorted_records {
+        for (key, record) in sorted_records {
             // Skip based on pagination
             if params.should_skip(&record.key) {
                 continue;
             }
 
             scanned_count += 1;
+            if scanned_count % DEADLINE_CHECK_INTERVAL == 0 {
+                params.check_deadline()?;
+            }
 
             // Skip if already seen
-            if seen_keys.contains(&key_enc) {
+            if seen_keys.contains(&key) {
                 continue;
             }
-            seen_keys.insert(key_enc);
+            seen_keys.insert(key);
 
             // Skip tombstones
             if record.value.is_none() {
@@ -310,18 +549,26 @@ impl MemoryLsmEngine {
             last_key = Some(record.key.clone());
 
             if let Some(item) = record.value {
-                items.push(item);
+                // Apply filter expression (filtered items still count as scanned)
+                if !params.matches_filter(&item) {
+                    continue;
+                }
+
+                count += 1;
+                if params.select == Select::AllAttributes {
+                    items.push(params.project(item));
+                }
 
                 // Check limit
                 if let Some(limit) = params.limit {
-                    if items.len() >= limit {
+                    if count >= limit {
                         break;
                     }
                 }
             }
         }
 
-        Ok(QueryResult::new(items, last_key, scanned_count))
+        Ok(QueryResult::new(items, last_key, scanned_count).with_count(count))
     }
 
     /// Scan all items across all stripes
@@ -329,43 +576,45 @@ impl MemoryLsmEngine {
         let inner = self.inner.read().unwrap();
 
         // Collect all records from all stripes
-        let mut all_records: BTreeMap<Vec<u8>, Record> = BTreeMap::new();
+        let mut all_records: BTreeMap<Key, Record> = BTreeMap::new();
 
         for stripe_id in 0..NUM_STRIPES {
             // Skip stripes not assigned to this segment
             if !params.should_scan_stripe(stripe_id) {
                 continue;
             }
+            params.check_deadline()?;
 
             let stripe = &inner.stripes[stripe_id];
 
             // Collect from memtable
-            for (key_enc, record) in &stripe.memtable {
-                // Skip tombstones
-                if record.value.is_none() {
+            for record in stripe.memtable.values() {
+                // Skip tombstones, other tables' items and keys outside the prefix
+                if record.value.is_none() || !params.in_scope(&record.key) {
                     continue;
                 }
 
-                all_records.insert(key_enc.clone(), record.clone());
+                all_records.insert(record.key.clone(), record.clone());
             }
 
             // Collect from SSTs
             for sst in &stripe.ssts {
                 for record in sst.iter() {
-                    // Skip tombstones
-                    if record.value.is_none() {
+                    // Skip tombstones, other tables' items and keys outside the prefix
+                    if record.value.is_none() || !params.in_scope(&record.key) {
                         continue;
                     }
 
-                    let key_enc = record.key.encode().to_vec();
                     // Only add if we don't already have this key (memtable is newer)
-                    all_records.entry(key_enc).or_insert(record.clone());
+                    all_records.entry(record.key.clone()).or_insert(record.clone());
                 }
             }
         }
 
         // Apply pagination and limit
         let mut items = Vec::new();
+        let mut keys = Vec::new();
+        let mut count = 0;
         let mut scanned_count = 0;
         let mut last_key = None;
 
@@ -376,37 +625,40 @@ impl MemoryLsmEngine {
             }
 
             scanned_count += 1;
+            if scanned_count % DEADLINE_CHECK_INTERVAL == 0 {
+                params.check_deadline()?;
+            }
 
             last_key = Some(record.key.clone());
 
             if let Some(item) = record.value {
-                items.push(item);
+                // Apply filter expression (filtered items still count as scanned)
+                if !params.matches_filter(&item) {
+                    continue;
+                }
+
+                count += 1;
+                match params.select {
+                    Select::AllAttributes => items.push(params.project(item)),
+                    Select::Keys => keys.push(record.key.clone()),
+                    Select::Count => {}
+                }
 
                 // Check limit
                 if let Some(limit) = params.limit {
-                    if items.len() >= limit {
-                        return Ok(ScanResult::new(items, last_key, scanned_count));
+                    if count >= limit {
+                        return Ok(ScanResult::new(items, last_key, scanned_count).with_count(count).with_keys(keys));
                     }
                 }
             }
         }
 
-        Ok(ScanResult::new(items, last_key, scanned_count))
+        Ok(ScanResult::new(items, last_key, scanned_count).with_count(count).with_keys(keys))
     }
 
     /// Update an item using update expression
     pub fn update(&self, key: &Key, actions: &[UpdateAction], context: &ExpressionContext) -> Result<Item> {
-        // Get current item (or create empty if doesn't exist)
-        let current_item = self.get(key)?.unwrap_or_else(|| HashMap::new());
-
-        // Execute update actions
-        let executor = UpdateExecutor::new(context);
-        let updated_item = executor.execute(&current_item, actions)?;
-
-        // Put the updated item
-        self.put(key.clone(), updated_item.clone())?;
-
-        Ok(updated_item)
+        self.update_returning(key, actions, None, context).map(|(_, updated)| updated)
     }
 
     /// Update an item with a condition expression
@@ -417,59 +669,43 @@ impl MemoryLsmEngine {
         condition: &Expr,
         context: &ExpressionContext,
     ) -> Result<Item> {
-        // Get current item (or create empty if doesn't exist)
-        let current_item = self.get(key)?.unwrap_or_else(|| HashMap::new());
+        self.update_returning(key, actions, Some(condition), context).map(|(_, updated)| updated)
+    }
 
-        // Evaluate condition
-        let evaluator = ExpressionEvaluator::new(&current_item, context);
-        let condition_passed = evaluator.evaluate(condition)?;
+    /// Update an item and return both the old and the new item, atomically
+    pub fn update_returning(
+        &self,
+        key: &Key,
+        actions: &[UpdateAction],
+        condition: Option<&Expr>,
+        context: &ExpressionContext,
+    ) -> Result<(Option<Item>, Item)> {
+        let mut inner = self.inner.write().unwrap();
 
-        if !condition_passed {
-            return Err(Error::ConditionalCheckFailed("Update condition failed".into()));
-        }
+        let old_item = Self::current_item(&inner, key);
+        check_condition(
+            old_item.as_ref(),
+            condition.map(|condition| (condition, context)),
+            "Update condition failed",
+        )?;
 
-        // Condition passed, execute update
+        // Apply actions to the current item (or an empty one if it doesn't exist)
         let executor = UpdateExecutor::new(context);
-        let updated_item = executor.execute(&current_item, actions)?;
-
-        // Put the updated item
-        self.put(key.clone(), updated_item.clone())?;
+        let updated_item = executor.execute(old_item.as_ref().unwrap_or(&HashMap::new()), actions)?;
+        let updated_item = Self::generate_attributes(&inner, key, old_item.as_ref(), updated_item)?;
 
-        Ok(updated_item)
+        Self::put_locked(&mut inner, key.clone(), updated_item.clone())?;
+        Ok((old_item, updated_item))
     }
 
     /// Put an item with a condition expression
     pub fn put_conditional(&self, key: Key, item: Item, condition: &Expr, context: &ExpressionContext) -> Result<()> {
-        // Get current item (or empty if doesn't exist)
-        let current_item = self.get(&key)?.unwrap_or_else(|| HashMap::new());
-
-        // Evaluate condition
-        let evaluator = ExpressionEvaluator::new(&current_item, context);
-        let condition_passed = evaluator.evaluate(condition)?;
-
-        if !condition_passed {
-            return Err(Error::ConditionalCheckFailed("Put condition failed".into()));
-        }
-
-        // Condition passed, perform put
-        self.put(key, item)
+        self.put_returning_old(key, item, Some((condition, context))).map(|_| ())
     }
 
     /// Delete an item with a condition expression
     pub fn delete_conditional(&self, key: Key, condition: &Expr, context: &ExpressionContext) -> Result<()> {
-        // Get current item (or empty if doesn't exist)
-        let current_item = self.get(&key)?.unwrap_or_else(|| HashMap::new());
-
-        // Evaluate condition
-        let evaluator = ExpressionEvaluator::new(&current_item, context);
-        let condition_passed = evaluator.evaluate(condition)?;
-
-        if !condition_passed {
-            return Err(Error::ConditionalCheckFailed("Delete condition failed".into()));
-        }
-
-        // Condition passed, perform delete
-        self.delete(key)
+        self.delete_returning_old(key, Some((condition, context))).map(|_| ())
     }
 
     /// Batch get multiple items
@@ -486,22 +722,41 @@ impl MemoryLsmEngine {
 
     /// Batch write multiple items
     pub fn batch_write(&self, operations: &[(Key, Option<Item>)]) -> Result<usize> {
-        let mut processed = 0;
+        self.write_batch(operations)
+    }
 
-        for (key, item_opt) in operations {
-            match item_opt {
-                Some(item) => {
-                    self.put(key.clone(), item.clone())?;
-                    processed += 1;
-                }
-                None => {
-                    self.delete(key.clone())?;
-                    processed += 1;
+    /// Apply puts and deletes under a single write lock
+    ///
+    /// Readers never observe part of the batch. (There is no crash recovery
+    /// to consider for an in-memory database.)
+    pub fn write_batch(&self, operations: &[(Key, Option<Item>)]) -> Result<usize> {
+        let mut inner = self.inner.write().unwrap();
+
+        // Fill in generated attributes, then reject invalid items before
+        // anything in the batch is written
+        let mut items = Vec::with_capacity(operations.len());
+        for (key, item) in operations {
+            let item = match item {
+                Some(item) if Self::generates_attributes(&inner, key) => {
+                    let old_item = Self::current_item(&inner, key);
+                    Some(Self::generate_attributes(&inner, key, old_item.as_ref(), item.clone())?)
                 }
+                item => item.clone(),
+            };
+            if let Some(item) = &item {
+                Self::check_item_schema(&inner, key, item)?;
             }
+            items.push(item);
         }
 
-        Ok(processed)
+        for ((key, _), item_opt) in operations.iter().zip(items) {
+            match item_opt {
+                Some(item) => Self::put_locked(&mut inner, key.clone(), item)?,
+                None => Self::delete_locked(&mut inner, key.clone())?,
+            }
+        }
+
+        Ok(operations.len())
     }
 
     /// Transaction get - read multiple items atomically
@@ -529,6 +784,7 @@ impl MemoryLsmEngine {
 
         // Phase 1: Read all items and check all conditions
         let mut current_items: Vec<Option<Item>> = Vec::new();
+        let mut failed = Vec::with_capacity(operations.len());
         for (key, op) in operations {
             let item = {
                 let stripe_id = stripe_id(&key.pk);
@@ -557,81 +813,63 @@ impl MemoryLsmEngine {
             if let Some(condition_expr) = op.condition() {
                 let current_item = item.unwrap_or_else(|| HashMap::new());
                 let evaluator = ExpressionEvaluator::new(&current_item, context);
-                let condition_passed = evaluator.evaluate(condition_expr)?;
-
-                if !condition_passed {
-                    return Err(Error::TransactionCanceled(format!(
-                        "Condition failed for key {:?}",
-                        key
-                    )));
-                }
+                failed.push(!evaluator.evaluate(condition_expr)?);
+            } else {
+                failed.push(false);
             }
         }
 
-        // Phase 2: All conditions passed, perform all writes
-        let mut committed = 0;
-        for (i, (key, op)) in operations.iter().enumerate() {
-            match op {
-                TransactWriteOperation::Put { item, .. } => {
-                    // Perform put
-                    let seq = inner.next_seq;
-                    inner.next_seq += 1;
-                    let record = Record::put(key.clone(), item.clone(), seq);
-                    inner.wal.append(record.clone())?;
-
-                    let stripe_id = stripe_id(&key.pk);
-                    let key_enc = key.encode().to_vec();
-                    inner.stripes[stripe_id].memtable.insert(key_enc, record);
-
-                    if inner.stripes[stripe_id].memtable.len() >= MEMTABLE_THRESHOLD {
-                        Self::flush_stripe(&mut inner, stripe_id)?;
-                    }
-
-                    committed += 1;
-                }
-                TransactWriteOperation::Delete { .. } => {
-                    // Perform delete
-                    let seq = inner.next_seq;
-                    inner.next_seq += 1;
-                    let record = Record::delete(key.clone(), seq);
-                    inner.wal.append(record.clone())?;
-
-                    let stripe_id = stripe_id(&key.pk);
-                    let key_enc = key.encode().to_vec();
-                    inner.stripes[stripe_id].memtable.insert(key_enc, record);
-
-                    if inner.stripes[stripe_id].memtable.len() >= MEMTABLE_THRESHOLD {
-                        Self::flush_stripe(&mut inner, stripe_id)?;
-                    }
+        if failed.contains(&true) {
+            return Err(transaction_canceled(&failed));
+        }
 
-                    committed += 1;
-                }
+        // Work out the items to write (with their generated attributes) and
+        // reject invalid ones before anything is written
+        let mut new_items = Vec::with_capacity(operations.len());
+        for ((key, op), current_item) in operations.iter().zip(&current_items) {
+            let new_item = match op {
+                TransactWriteOperation::Put { item, .. } => item.clone(),
                 TransactWriteOperation::Update { actions, .. } => {
-                    // Perform update
-                    let current_item = current_items[i].clone().unwrap_or_else(|| HashMap::new());
                     let executor = UpdateExecutor::new(context);
-                    let updated_item = executor.execute(&current_item, actions)?;
+                    executor.execute(current_item.as_ref().unwrap_or(&HashMap::new()), actions)?
+                }
+                TransactWriteOperation::Delete { .. } | TransactWriteOperation::ConditionCheck { .. } => {
+                    new_items.push(None);
+                    continue;
+                }
+            };
+            let new_item = Self::generate_attributes(&inner, key, current_item.as_ref(), new_item)?;
+            Self::check_item_schema(&inner, key, &new_item)?;
+            new_items.push(Some(new_item));
+        }
 
-                    let seq = inner.next_seq;
-                    inner.next_seq += 1;
-                    let record = Record::put(key.clone(), updated_item, seq);
-                    inner.wal.append(record.clone())?;
+        // Phase 2: All conditions passed, perform all writes
+        let mut committed = 0;
+        for ((key, op), new_item) in operations.iter().zip(new_items) {
+            if let TransactWriteOperation::ConditionCheck { .. } = op {
+                // Condition already checked in phase 1, no write needed
+                committed += 1;
+                continue;
+            }
 
-                    let stripe_id = stripe_id(&key.pk);
-                    let key_enc = key.encode().to_vec();
-                    inner.stripes[stripe_id].memtable.insert(key_enc, record);
+            // Puts and updates write their prepared item; deletes a tombstone
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            let record = match new_item {
+                Some(item) => Record::put(key.clone(), item, seq),
+                None => Record::delete(key.clone(), seq),
+            };
+            inner.wal.append(record.clone())?;
 
-                    if inner.stripes[stripe_id].memtable.len() >= MEMTABLE_THRESHOLD {
-                        Self::flush_stripe(&mut inner, stripe_id)?;
-                    }
+            let stripe_id = stripe_id(&key.pk);
+            let key_enc = key.encode().to_vec();
+            inner.stripes[stripe_id].memtable.insert(key_enc, record);
 
-                    committed += 1;
-                }
-                TransactWriteOperation::ConditionCheck { .. } => {
-                    // Condition already checked in phase 1, no write needed
-                    committed += 1;
-                }
+            if inner.stripes[stripe_id].memtable.len() >= MEMTABLE_THRESHOLD {
+                Self::flush_stripe(&mut inner, stripe_id)?;
             }
+
+            committed += 1;
         }
 
         Ok(committed)
@@ -650,6 +888,62 @@ mod tests {
         item
     }
 
+    #[test]
+    fn test_memory_lsm_schema_validation() {
+        use crate::validation::{AttributeSchema, AttributeType, Validator};
+
+        let validator = Validator::from_schemas(vec![AttributeSchema::new("test", AttributeType::String).required()]);
+        let engine = MemoryLsmEngine::create_with_schema(TableSchema::new().with_validation(validator)).unwrap();
+
+        engine.put(Key::new(b"a".to_vec()), create_test_item("ok")).unwrap();
+        assert!(matches!(
+            engine.put(Key::new(b"b".to_vec()), HashMap::new()),
+            Err(Error::ValidationFailed(_))
+        ));
+
+        let batch = vec![
+            (Key::new(b"c".to_vec()), Some(create_test_item("ok"))),
+            (Key::new(b"d".to_vec()), Some(HashMap::new())),
+        ];
+        assert!(engine.write_batch(&batch).is_err());
+        assert!(engine.get(&Key::new(b"c".to_vec())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_lsm_save_and_load() {
+        use crate::index::LocalSecondaryIndex;
+        use crate::storage::MemoryStorage;
+
+        let schema = TableSchema::new().add_local_index(LocalSecondaryIndex::new("by-test", "test"));
+        let engine = MemoryLsmEngine::create_with_schema(schema).unwrap();
+        for i in 0..10 {
+            engine.put(Key::new(format!("key{}", i).into_bytes()), create_test_item("old")).unwrap();
+        }
+        engine.flush().unwrap();
+        engine.put(Key::new(b"key1".to_vec()), create_test_item("new")).unwrap();
+        engine.delete(Key::new(b"key2".to_vec())).unwrap();
+
+        let storage = MemoryStorage::new();
+        engine.save_to(&storage).unwrap();
+
+        let loaded = MemoryLsmEngine::load_from(&storage).unwrap();
+        assert_eq!(loaded.get(&Key::new(b"key1".to_vec())).unwrap(), Some(create_test_item("new")));
+        assert_eq!(loaded.get(&Key::new(b"key2".to_vec())).unwrap(), None);
+        assert_eq!(loaded.get(&Key::new(b"key9".to_vec())).unwrap(), Some(create_test_item("old")));
+        assert_eq!(loaded.schema().local_indexes.len(), 1);
+        assert_eq!(loaded.created_at(), engine.created_at());
+
+        // New writes don't reuse sequence numbers
+        assert_eq!(loaded.inner.read().unwrap().next_seq, engine.inner.read().unwrap().next_seq);
+
+        let data = storage.read(SNAPSHOT_OBJECT).unwrap().unwrap();
+        let mut corrupt = data.to_vec();
+        corrupt[0] ^= 0xFF;
+        storage.write(SNAPSHOT_OBJECT, Bytes::from(corrupt)).unwrap();
+        assert!(MemoryLsmEngine::load_from(&storage).is_err());
+        assert!(matches!(MemoryLsmEngine::load_from(&MemoryStorage::new()), Err(Error::NotFound(_))));
+    }
+
     #[test]
     fn test_memory_lsm_create() {
         let engine = MemoryLsmEngine::create().unwrap();
@@ -761,4 +1055,18 @@ mod tests {
         let key2 = Key::with_sk(b"pk1".to_vec(), b"sk2".to_vec());
         assert!(engine.get(&key2).unwrap().is_none());
     }
+
+    #[test]
+    fn test_memory_lsm_returning_old() {
+        let engine = MemoryLsmEngine::create().unwrap();
+        let key = Key::new(b"user1".to_vec());
+
+        assert_eq!(engine.put_returning_old(key.clone(), create_test_item("v1"), None).unwrap(), None);
+        let old = engine.put_returning_old(key.clone(), create_test_item("v2"), None).unwrap();
+        assert_eq!(old, Some(create_test_item("v1")));
+
+        let old = engine.delete_returning_old(key.clone(), None).unwrap();
+        assert_eq!(old, Some(create_test_item("v2")));
+        assert!(engine.get(&key).unwrap().is_none());
+    }
 }
@@ -0,0 +1,175 @@
+/// Operation counters and hot partitions (Phase 8+)
+///
+/// Every get, put, delete, update, query and scan is counted. One in
+/// `HOT_SAMPLE_INTERVAL` operations on a partition is also fed to a
+/// space-saving summary of `HOT_PARTITION_CAPACITY` partitions, whose counts
+/// are halved every `HOT_DECAY_SAMPLES` samples so recent traffic dominates.
+/// Counts cover this handle only and start at zero when the database opens.
+
+use crate::slow_log::SlowOperationKind;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One in this many operations is sampled for hot partitions
+pub const HOT_SAMPLE_INTERVAL: u64 = 16;
+
+/// Partitions tracked by the hot partition summary
+const HOT_PARTITION_CAPACITY: usize = 64;
+
+/// Samples between halvings of the hot partition counts
+const HOT_DECAY_SAMPLES: u64 = 10_000;
+
+/// Hot partitions reported by `OperationStats`
+const HOT_PARTITIONS_REPORTED: usize = 10;
+
+/// A frequently accessed partition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotPartition {
+    pub pk: Bytes,
+    /// Estimated recent operations (sampled counts scaled up)
+    pub operations: u64,
+}
+
+/// Operation counts since the database was opened
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    /// Gets, queries and scans
+    pub reads: u64,
+    /// Puts, deletes and updates
+    pub writes: u64,
+    /// Sequence number of the latest write, including writes replayed at open
+    pub last_sequence_number: u64,
+    /// Most accessed partitions recently, busiest first
+    pub hot_partitions: Vec<HotPartition>,
+}
+
+/// Counts operations for `OperationStats`
+pub struct OperationCounters {
+    reads: AtomicU64,
+    writes: AtomicU64,
+    hot: Mutex<HotPartitions>,
+}
+
+impl OperationCounters {
+    pub fn new() -> Self {
+        Self {
+            reads: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+            hot: Mutex::new(HotPartitions::default()),
+        }
+    }
+
+    /// Count an operation on partition `pk` (None for scans)
+    pub fn record(&self, kind: SlowOperationKind, pk: Option<&[u8]>) {
+        let counter = match kind {
+            SlowOperationKind::Get | SlowOperationKind::Query | SlowOperationKind::Scan => &self.reads,
+            SlowOperationKind::Put | SlowOperationKind::Delete | SlowOperationKind::Update => &self.writes,
+        };
+        let count = counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(pk) = pk {
+            if count % HOT_SAMPLE_INTERVAL == 0 {
+                self.hot.lock().record(pk);
+            }
+        }
+    }
+
+    /// Counts so far, with `last_sequence_number` left at zero
+    pub fn stats(&self) -> OperationStats {
+        OperationStats {
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            last_sequence_number: 0,
+            hot_partitions: self.hot.lock().top(HOT_PARTITIONS_REPORTED),
+        }
+    }
+}
+
+impl Default for OperationCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Space-saving summary: a full table replaces its smallest count
+#[derive(Default)]
+struct HotPartitions {
+    counts: Vec<(Bytes, u64)>,
+    samples: u64,
+}
+
+impl HotPartitions {
+    fn record(&mut self, pk: &[u8]) {
+        self.samples += 1;
+        if self.samples % HOT_DECAY_SAMPLES == 0 {
+            for (_, count) in &mut self.counts {
+                *count /= 2;
+            }
+            self.counts.retain(|(_, count)| *count > 0);
+        }
+
+        if let Some((_, count)) = self.counts.iter_mut().find(|(key, _)| key.as_ref() == pk) {
+            *count += 1;
+        } else if self.counts.len() < HOT_PARTITION_CAPACITY {
+            self.counts.push((Bytes::copy_from_slice(pk), 1));
+        } else if let Some(smallest) = self.counts.iter_mut().min_by_key(|(_, count)| *count) {
+            *smallest = (Bytes::copy_from_slice(pk), smallest.1 + 1);
+        }
+    }
+
+    fn top(&self, n: usize) -> Vec<HotPartition> {
+        let mut counts = self.counts.clone();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+            .into_iter()
+            .take(n)
+            .map(|(pk, count)| HotPartition {
+                pk,
+                operations: count * HOT_SAMPLE_INTERVAL,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_counters() {
+        let counters = OperationCounters::new();
+        // Only the operations on user#1 happen to be sampled, including the put
+        for i in 0..HOT_SAMPLE_INTERVAL * 100 {
+            let pk: &[u8] = if i % HOT_SAMPLE_INTERVAL == 0 { b"user#1" } else { b"user#2" };
+            counters.record(SlowOperationKind::Get, Some(pk));
+        }
+        counters.record(SlowOperationKind::Put, Some(b"user#1"));
+        counters.record(SlowOperationKind::Scan, None);
+
+        let stats = counters.stats();
+        assert_eq!(stats.reads, HOT_SAMPLE_INTERVAL * 100 + 1);
+        assert_eq!(stats.writes, 1);
+        assert_eq!(
+            stats.hot_partitions,
+            vec![HotPartition { pk: Bytes::from("user#1"), operations: HOT_SAMPLE_INTERVAL * 101 }]
+        );
+    }
+
+    #[test]
+    fn test_hot_partitions_keep_heavy_hitters() {
+        let mut hot = HotPartitions::default();
+        for i in 0..10_000u64 {
+            if i % 2 == 0 {
+                hot.record(b"hot");
+            } else {
+                hot.record(format!("key#{}", i).as_bytes());
+            }
+        }
+
+        let top = hot.top(3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].pk, Bytes::from("hot"));
+        assert!(top[0].operations > top[1].operations);
+        assert!(hot.counts.len() <= HOT_PARTITION_CAPACITY);
+    }
+}
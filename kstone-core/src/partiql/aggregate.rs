@@ -0,0 +1,318 @@
+/// Streaming aggregation for PartiQL SELECT (COUNT, SUM, AVG, MIN, MAX, GROUP BY)
+///
+/// Items are folded into per-group accumulators one at a time, so memory is bounded
+/// by the number of distinct groups rather than the number of items aggregated.
+
+use crate::partiql::ast::{
+    compare_values, resolve_attribute_path, AggregateExpr, AggregateFunction, SelectExpr,
+};
+use crate::{Item, Value};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Streaming aggregator producing one result row per group
+pub struct Aggregator {
+    exprs: Vec<SelectExpr>,
+    group_by: Vec<String>,
+    /// Group key -> position in `groups` (keeps first-seen group order)
+    index: HashMap<GroupKey, usize>,
+    groups: Vec<(GroupKey, Vec<Accumulator>)>,
+}
+
+impl Aggregator {
+    /// Create an aggregator for a SELECT list and GROUP BY attributes
+    pub fn new(exprs: Vec<SelectExpr>, group_by: Vec<String>) -> Self {
+        Self {
+            exprs,
+            group_by,
+            index: HashMap::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Fold a single item into its group
+    pub fn add(&mut self, item: &Item) {
+        let key = GroupKey(
+            self.group_by
+                .iter()
+                .map(|attr| resolve_attribute_path(item, attr).cloned())
+                .collect(),
+        );
+
+        let position = match self.index.get(&key) {
+            Some(&position) => position,
+            None => {
+                let accumulators = self.exprs.iter().map(|_| Accumulator::default()).collect();
+                self.groups.push((key.clone(), accumulators));
+                self.index.insert(key, self.groups.len() - 1);
+                self.groups.len() - 1
+            }
+        };
+
+        let accumulators = &mut self.groups[position].1;
+        for (expr, acc) in self.exprs.iter().zip(accumulators.iter_mut()) {
+            if let SelectExpr::Aggregate(agg) = expr {
+                acc.add(agg, item);
+            }
+        }
+    }
+
+    /// Produce result rows in first-seen group order
+    ///
+    /// Without GROUP BY a single row is returned even when no items were added.
+    pub fn finish(mut self) -> Vec<Item> {
+        if self.groups.is_empty() && self.group_by.is_empty() {
+            let accumulators = self.exprs.iter().map(|_| Accumulator::default()).collect();
+            self.groups.push((GroupKey(Vec::new()), accumulators));
+        }
+
+        let exprs = self.exprs;
+        let group_by = self.group_by;
+
+        self.groups
+            .into_iter()
+            .map(|(key, accumulators)| {
+                let mut row = HashMap::new();
+                for (expr, acc) in exprs.iter().zip(accumulators) {
+                    match expr {
+                        SelectExpr::Attribute(attr) => {
+                            let value = group_by
+                                .iter()
+                                .position(|g| g == attr)
+                                .and_then(|i| key.0[i].clone());
+                            if let Some(value) = value {
+                                row.insert(attr.clone(), value);
+                            }
+                        }
+                        SelectExpr::Aggregate(agg) => {
+                            row.insert(agg.output_name(), acc.finish(agg.function));
+                        }
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+}
+
+/// Running state for a single aggregate in a single group
+#[derive(Default)]
+struct Accumulator {
+    count: u64,
+    sum: f64,
+    numeric_count: u64,
+    min: Option<Value>,
+    max: Option<Value>,
+}
+
+impl Accumulator {
+    fn add(&mut self, agg: &AggregateExpr, item: &Item) {
+        let value = match &agg.attribute {
+            // COUNT(*) counts every item
+            None => {
+                self.count += 1;
+                return;
+            }
+            Some(attr) => match resolve_attribute_path(item, attr) {
+                Some(Value::Null) | None => return,
+                Some(value) => value,
+            },
+        };
+
+        self.count += 1;
+
+        match agg.function {
+            AggregateFunction::Count => {}
+            AggregateFunction::Sum | AggregateFunction::Avg => {
+                if let Value::N(n) = value {
+                    if let Ok(n) = n.parse::<f64>() {
+                        self.sum += n;
+                        self.numeric_count += 1;
+                    }
+                }
+            }
+            AggregateFunction::Min => {
+                if self.min.as_ref().map_or(true, |min| compare_values(value, min).is_lt()) {
+                    self.min = Some(value.clone());
+                }
+            }
+            AggregateFunction::Max => {
+                if self.max.as_ref().map_or(true, |max| compare_values(value, max).is_gt()) {
+                    self.max = Some(value.clone());
+                }
+            }
+        }
+    }
+
+    fn finish(self, function: AggregateFunction) -> Value {
+        match function {
+            AggregateFunction::Count => Value::number(self.count),
+            AggregateFunction::Sum if self.numeric_count > 0 => Value::number(self.sum),
+            AggregateFunction::Avg if self.numeric_count > 0 => {
+                Value::number(self.sum / self.numeric_count as f64)
+            }
+            AggregateFunction::Sum | AggregateFunction::Avg => Value::Null,
+            AggregateFunction::Min => self.min.unwrap_or(Value::Null),
+            AggregateFunction::Max => self.max.unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Values of the GROUP BY attributes for one group (None = attribute missing)
+#[derive(Clone, PartialEq)]
+struct GroupKey(Vec<Option<Value>>);
+
+impl Eq for GroupKey {}
+
+impl Hash for GroupKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in &self.0 {
+            match value {
+                Some(value) => hash_value(value, state),
+                None => state.write_u8(0),
+            }
+        }
+    }
+}
+
+/// Hash a value consistently with its `PartialEq` (map entries in key order)
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::N(n) => {
+            state.write_u8(1);
+            n.hash(state);
+        }
+        Value::S(s) => {
+            state.write_u8(2);
+            s.hash(state);
+        }
+        Value::B(b) => {
+            state.write_u8(3);
+            b.hash(state);
+        }
+        Value::Bool(b) => {
+            state.write_u8(4);
+            b.hash(state);
+        }
+        Value::Null => state.write_u8(5),
+        Value::L(list) => {
+            state.write_u8(6);
+            state.write_usize(list.len());
+            for v in list {
+                hash_value(v, state);
+            }
+        }
+        Value::M(map) => {
+            state.write_u8(7);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            state.write_usize(entries.len());
+            for (k, v) in entries {
+                k.hash(state);
+                hash_value(v, state);
+            }
+        }
+        Value::VecF32(vec) => {
+            state.write_u8(8);
+            for f in vec {
+                state.write_u32(f.to_bits());
+            }
+        }
+        Value::Ts(ts) => {
+            state.write_u8(9);
+            ts.hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(status: &str, price: i64) -> Item {
+        let mut item = HashMap::new();
+        item.insert("status".to_string(), Value::string(status));
+        item.insert("price".to_string(), Value::number(price));
+        item
+    }
+
+    fn aggregate(function: AggregateFunction, attribute: Option<&str>) -> SelectExpr {
+        SelectExpr::Aggregate(AggregateExpr {
+            function,
+            attribute: attribute.map(|a| a.to_string()),
+            alias: None,
+        })
+    }
+
+    #[test]
+    fn test_aggregate_without_group_by() {
+        let mut aggregator = Aggregator::new(
+            vec![
+                aggregate(AggregateFunction::Count, None),
+                aggregate(AggregateFunction::Sum, Some("price")),
+                aggregate(AggregateFunction::Avg, Some("price")),
+                aggregate(AggregateFunction::Min, Some("price")),
+                aggregate(AggregateFunction::Max, Some("price")),
+            ],
+            Vec::new(),
+        );
+
+        for (status, price) in [("open", 10), ("closed", 30), ("open", 20)] {
+            aggregator.add(&item(status, price));
+        }
+
+        let rows = aggregator.finish();
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.get("COUNT(*)"), Some(&Value::number(3)));
+        assert_eq!(row.get("SUM(price)"), Some(&Value::number(60)));
+        assert_eq!(row.get("AVG(price)"), Some(&Value::number(20)));
+        assert_eq!(row.get("MIN(price)"), Some(&Value::number(10)));
+        assert_eq!(row.get("MAX(price)"), Some(&Value::number(30)));
+    }
+
+    #[test]
+    fn test_aggregate_with_group_by() {
+        let mut aggregator = Aggregator::new(
+            vec![
+                SelectExpr::Attribute("status".to_string()),
+                aggregate(AggregateFunction::Count, None),
+                aggregate(AggregateFunction::Sum, Some("price")),
+            ],
+            vec!["status".to_string()],
+        );
+
+        for (status, price) in [("open", 10), ("closed", 30), ("open", 20)] {
+            aggregator.add(&item(status, price));
+        }
+
+        let rows = aggregator.finish();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("status"), Some(&Value::string("open")));
+        assert_eq!(rows[0].get("COUNT(*)"), Some(&Value::number(2)));
+        assert_eq!(rows[0].get("SUM(price)"), Some(&Value::number(30)));
+        assert_eq!(rows[1].get("status"), Some(&Value::string("closed")));
+        assert_eq!(rows[1].get("COUNT(*)"), Some(&Value::number(1)));
+    }
+
+    #[test]
+    fn test_aggregate_empty_input() {
+        let aggregator = Aggregator::new(
+            vec![
+                aggregate(AggregateFunction::Count, None),
+                aggregate(AggregateFunction::Sum, Some("price")),
+            ],
+            Vec::new(),
+        );
+        let rows = aggregator.finish();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get("COUNT(*)"), Some(&Value::number(0)));
+        assert_eq!(rows[0].get("SUM(price)"), Some(&Value::Null));
+
+        let grouped = Aggregator::new(
+            vec![aggregate(AggregateFunction::Count, None)],
+            vec!["status".to_string()],
+        );
+        assert!(grouped.finish().is_empty());
+    }
+}
@@ -13,6 +13,10 @@ pub enum PartiQLStatement {
     Insert(InsertStatement),
     Update(UpdateStatement),
     Delete(DeleteStatement),
+    /// EXPLAIN SELECT: describe the access path without returning items
+    Explain(SelectStatement),
+    /// INSERT INTO ... SELECT: insert every row a SELECT returns
+    InsertSelect(InsertSelectStatement),
 }
 
 /// SELECT statement
@@ -32,6 +36,15 @@ pub struct SelectStatement {
     pub limit: Option<usize>,
     /// OFFSET clause (number of items to skip)
     pub offset: Option<usize>,
+    /// GROUP BY attributes (empty = no grouping)
+    pub group_by: Vec<String>,
+}
+
+impl SelectStatement {
+    /// Check if the statement aggregates items into result rows
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self.select_list, SelectList::Aggregates(_))
+    }
 }
 
 /// SELECT attribute list
@@ -41,6 +54,82 @@ pub enum SelectList {
     All,
     /// SELECT attr1, attr2, ...
     Attributes(Vec<String>),
+    /// SELECT with aggregate functions and/or GROUP BY attributes
+    Aggregates(Vec<SelectExpr>),
+}
+
+/// Single output column of an aggregate SELECT
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectExpr {
+    /// Grouping attribute
+    Attribute(String),
+    /// Aggregate function call
+    Aggregate(AggregateExpr),
+}
+
+/// Aggregate function call, e.g. `SUM(price) AS total`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateExpr {
+    /// Aggregate function
+    pub function: AggregateFunction,
+    /// Argument attribute (None = `*`, only valid for COUNT)
+    pub attribute: Option<String>,
+    /// Output name from `AS alias`
+    pub alias: Option<String>,
+}
+
+impl AggregateExpr {
+    /// Name of the output attribute (alias, or e.g. `COUNT(*)`)
+    pub fn output_name(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.clone(),
+            None => format!(
+                "{}({})",
+                self.function.name(),
+                self.attribute.as_deref().unwrap_or("*")
+            ),
+        }
+    }
+}
+
+/// Supported aggregate functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AggregateFunction {
+    /// COUNT(*) or COUNT(attr)
+    Count,
+    /// SUM(attr)
+    Sum,
+    /// AVG(attr)
+    Avg,
+    /// MIN(attr)
+    Min,
+    /// MAX(attr)
+    Max,
+}
+
+impl AggregateFunction {
+    /// Parse a function name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+
+    /// SQL name of the function
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
 }
 
 /// WHERE clause with conditions
@@ -116,6 +205,8 @@ pub enum SqlValue {
     List(Vec<SqlValue>),
     /// Map/Object
     Map(HashMap<String, SqlValue>),
+    /// Unbound placeholder (`?1` positional, `:name` named)
+    Parameter(String),
 }
 
 impl SqlValue {
@@ -139,6 +230,8 @@ impl SqlValue {
                 }
                 crate::Value::M(kv_map)
             }
+            // Statements are bound before execution, so this is never stored
+            SqlValue::Parameter(_) => crate::Value::Null,
         }
     }
 
@@ -198,6 +291,52 @@ pub struct OrderBy {
     pub ascending: bool,
 }
 
+impl OrderBy {
+    /// Check if ordering can be served by the sort key order of a Query
+    pub fn is_sort_key(&self) -> bool {
+        self.attribute == "sk"
+    }
+}
+
+/// Resolve a dotted attribute path (e.g. `address.city`) against an item
+///
+/// Each segment after the first descends into a map; a numeric segment
+/// also indexes into a list (e.g. `tags.0`).
+pub fn resolve_attribute_path<'a>(item: &'a crate::Item, path: &str) -> Option<&'a crate::Value> {
+    let mut segments = path.split('.');
+    let mut current = item.get(segments.next()?)?;
+
+    for segment in segments {
+        current = match current {
+            crate::Value::M(map) => map.get(segment)?,
+            crate::Value::L(list) => list.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+/// Order two attribute values of the same type
+///
+/// Numbers compare numerically; values of different types compare equal.
+pub fn compare_values(a: &crate::Value, b: &crate::Value) -> std::cmp::Ordering {
+    use crate::Value;
+    use std::cmp::Ordering;
+
+    match (a, b) {
+        (Value::N(n1), Value::N(n2)) => match (n1.parse::<f64>(), n2.parse::<f64>()) {
+            (Ok(num1), Ok(num2)) => num1.partial_cmp(&num2).unwrap_or(Ordering::Equal),
+            _ => n1.cmp(n2),
+        },
+        (Value::S(s1), Value::S(s2)) => s1.cmp(s2),
+        (Value::B(b1), Value::B(b2)) => b1.cmp(b2),
+        (Value::Bool(b1), Value::Bool(b2)) => b1.cmp(b2),
+        (Value::Ts(t1), Value::Ts(t2)) => t1.cmp(t2),
+        _ => Ordering::Equal,
+    }
+}
+
 /// INSERT statement
 #[derive(Debug, Clone, PartialEq)]
 pub struct InsertStatement {
@@ -205,6 +344,35 @@ pub struct InsertStatement {
     pub table_name: String,
     /// Value to insert (must be a Map with pk and optional sk)
     pub value: SqlValue,
+    /// What to do when an item with the same key already exists
+    pub on_conflict: ConflictAction,
+}
+
+/// INSERT INTO ... SELECT statement
+///
+/// Each row returned by the SELECT is inserted like an INSERT VALUE map,
+/// so rows must carry `pk` (and optionally `sk`) attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertSelectStatement {
+    /// Table name
+    pub table_name: String,
+    /// Query producing the rows to insert
+    pub select: SelectStatement,
+    /// What to do when an item with the same key already exists
+    pub on_conflict: ConflictAction,
+}
+
+/// Handling of an INSERT whose key already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictAction {
+    /// Replace the whole item (`INSERT`, `REPLACE INTO`)
+    #[default]
+    Replace,
+    /// Keep the existing item (`ON CONFLICT DO NOTHING`)
+    DoNothing,
+    /// Overwrite the given attributes and keep the rest
+    /// (`ON CONFLICT DO UPDATE`, `UPSERT INTO`)
+    DoUpdate,
 }
 
 /// UPDATE statement
@@ -332,4 +500,23 @@ mod tests {
         let kv_bool = crate::Value::Bool(true);
         assert_eq!(SqlValue::from_kstone_value(&kv_bool), SqlValue::Boolean(true));
     }
+
+    #[test]
+    fn test_resolve_attribute_path() {
+        let mut address = HashMap::new();
+        address.insert("city".to_string(), crate::Value::S("Paris".to_string()));
+
+        let mut item = HashMap::new();
+        item.insert("address".to_string(), crate::Value::M(address));
+        item.insert(
+            "tags".to_string(),
+            crate::Value::L(vec![crate::Value::S("a".to_string()), crate::Value::S("b".to_string())]),
+        );
+
+        assert!(matches!(resolve_attribute_path(&item, "address.city"), Some(crate::Value::S(s)) if s == "Paris"));
+        assert!(matches!(resolve_attribute_path(&item, "tags.1"), Some(crate::Value::S(s)) if s == "b"));
+        assert!(resolve_attribute_path(&item, "address.zip").is_none());
+        assert!(resolve_attribute_path(&item, "tags.5").is_none());
+        assert!(resolve_attribute_path(&item, "missing").is_none());
+    }
 }
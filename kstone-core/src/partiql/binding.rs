@@ -0,0 +1,246 @@
+/// Parameter binding for prepared PartiQL statements
+///
+/// Placeholders are parsed into `SqlValue::Parameter` (`?1`, `?2`, ... for positional `?`,
+/// `:name` for named parameters) and replaced with concrete values before translation.
+
+use crate::partiql::ast::*;
+use crate::{Error, Result, Value};
+use std::collections::HashMap;
+
+/// Values bound to a statement's placeholders
+#[derive(Debug, Clone, Default)]
+pub struct StatementParameters {
+    /// Values for `?` placeholders, in order of appearance
+    pub positional: Vec<Value>,
+    /// Values for `:name` placeholders (keyed without the leading colon)
+    pub named: HashMap<String, Value>,
+}
+
+impl StatementParameters {
+    /// Check if no parameters are bound
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty() && self.named.is_empty()
+    }
+}
+
+impl PartiQLStatement {
+    /// Replace every placeholder with its bound value
+    ///
+    /// Fails if a placeholder has no value or if the number of positional
+    /// values does not match the number of `?` placeholders.
+    pub fn bind(&self, params: &StatementParameters) -> Result<PartiQLStatement> {
+        let mut binder = Binder {
+            params,
+            positional_used: 0,
+        };
+
+        let bound = match self {
+            PartiQLStatement::Select(stmt) | PartiQLStatement::Explain(stmt) => {
+                let mut stmt = stmt.clone();
+                if let Some(where_clause) = stmt.where_clause.as_mut() {
+                    binder.bind_where(where_clause)?;
+                }
+                if matches!(self, PartiQLStatement::Explain(_)) {
+                    PartiQLStatement::Explain(stmt)
+                } else {
+                    PartiQLStatement::Select(stmt)
+                }
+            }
+            PartiQLStatement::Insert(stmt) => {
+                let mut stmt = stmt.clone();
+                binder.bind_value(&mut stmt.value)?;
+                PartiQLStatement::Insert(stmt)
+            }
+            PartiQLStatement::InsertSelect(stmt) => {
+                let mut stmt = stmt.clone();
+                if let Some(where_clause) = stmt.select.where_clause.as_mut() {
+                    binder.bind_where(where_clause)?;
+                }
+                PartiQLStatement::InsertSelect(stmt)
+            }
+            PartiQLStatement::Update(stmt) => {
+                let mut stmt = stmt.clone();
+                for assignment in &mut stmt.set_assignments {
+                    match &mut assignment.value {
+                        SetValue::Literal(value)
+                        | SetValue::Add { value, .. }
+                        | SetValue::Subtract { value, .. } => binder.bind_value(value)?,
+                    }
+                }
+                binder.bind_where(&mut stmt.where_clause)?;
+                PartiQLStatement::Update(stmt)
+            }
+            PartiQLStatement::Delete(stmt) => {
+                let mut stmt = stmt.clone();
+                binder.bind_where(&mut stmt.where_clause)?;
+                PartiQLStatement::Delete(stmt)
+            }
+        };
+
+        if binder.positional_used != params.positional.len() {
+            return Err(Error::InvalidQuery(format!(
+                "Statement has {} positional parameters but {} values were provided",
+                binder.positional_used,
+                params.positional.len()
+            )));
+        }
+
+        Ok(bound)
+    }
+}
+
+struct Binder<'a> {
+    params: &'a StatementParameters,
+    /// Highest positional placeholder index seen so far
+    positional_used: usize,
+}
+
+impl<'a> Binder<'a> {
+    fn bind_where(&mut self, where_clause: &mut WhereClause) -> Result<()> {
+        for condition in &mut where_clause.conditions {
+            self.bind_value(&mut condition.value)?;
+        }
+        Ok(())
+    }
+
+    fn bind_value(&mut self, value: &mut SqlValue) -> Result<()> {
+        match value {
+            SqlValue::Parameter(name) => {
+                let bound = self.lookup(name)?;
+                *value = SqlValue::from_kstone_value(bound);
+            }
+            SqlValue::List(values) => {
+                for v in values {
+                    self.bind_value(v)?;
+                }
+            }
+            SqlValue::Map(map) => {
+                for v in map.values_mut() {
+                    self.bind_value(v)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn lookup(&mut self, name: &str) -> Result<&'a Value> {
+        if let Some(index) = name.strip_prefix('?') {
+            let index: usize = index
+                .parse()
+                .ok()
+                .filter(|i| *i > 0)
+                .ok_or_else(|| Error::InvalidQuery(format!("Invalid placeholder: {}", name)))?;
+            self.positional_used = self.positional_used.max(index);
+            self.params.positional.get(index - 1).ok_or_else(|| {
+                Error::InvalidQuery(format!("No value bound for parameter {}", name))
+            })
+        } else {
+            let key = name.strip_prefix(':').unwrap_or(name);
+            self.params
+                .named
+                .get(key)
+                .ok_or_else(|| Error::InvalidQuery(format!("No value bound for parameter {}", name)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partiql::PartiQLParser;
+
+    #[test]
+    fn test_bind_positional_parameters() {
+        let stmt = PartiQLParser::parse("SELECT * FROM users WHERE pk = ? AND age BETWEEN ? AND ?").unwrap();
+        let params = StatementParameters {
+            positional: vec![Value::string("user#1"), Value::number(18), Value::number(65)],
+            named: HashMap::new(),
+        };
+
+        match stmt.bind(&params).unwrap() {
+            PartiQLStatement::Select(select) => {
+                let where_clause = select.where_clause.unwrap();
+                assert_eq!(
+                    where_clause.get_condition("pk").unwrap().value,
+                    SqlValue::String("user#1".to_string())
+                );
+                assert_eq!(
+                    where_clause.get_condition("age").unwrap().value,
+                    SqlValue::List(vec![
+                        SqlValue::Number("18".to_string()),
+                        SqlValue::Number("65".to_string()),
+                    ])
+                );
+            }
+            _ => panic!("Expected SELECT statement"),
+        }
+    }
+
+    #[test]
+    fn test_bind_named_parameters() {
+        let stmt = PartiQLParser::parse("UPDATE users SET score = score + :delta WHERE pk = :id").unwrap();
+        let mut params = StatementParameters::default();
+        params.named.insert("delta".to_string(), Value::number(5));
+        params.named.insert("id".to_string(), Value::string("user#1"));
+
+        match stmt.bind(&params).unwrap() {
+            PartiQLStatement::Update(update) => {
+                assert_eq!(
+                    update.where_clause.get_condition("pk").unwrap().value,
+                    SqlValue::String("user#1".to_string())
+                );
+                match &update.set_assignments[0].value {
+                    SetValue::Add { value, .. } => {
+                        assert_eq!(*value, SqlValue::Number("5".to_string()))
+                    }
+                    other => panic!("Expected Add, got {:?}", other),
+                }
+            }
+            _ => panic!("Expected UPDATE statement"),
+        }
+    }
+
+    #[test]
+    fn test_bind_insert_map_parameters() {
+        let stmt = PartiQLParser::parse("INSERT INTO users VALUE {'pk': ?, 'name': :name, 'tags': [?, 'x']}").unwrap();
+        let mut params = StatementParameters {
+            positional: vec![Value::string("user#1"), Value::string("a")],
+            named: HashMap::new(),
+        };
+        params.named.insert("name".to_string(), Value::string("Alice"));
+
+        match stmt.bind(&params).unwrap() {
+            PartiQLStatement::Insert(insert) => match insert.value {
+                SqlValue::Map(map) => {
+                    assert_eq!(map["pk"], SqlValue::String("user#1".to_string()));
+                    assert_eq!(map["name"], SqlValue::String("Alice".to_string()));
+                    assert_eq!(
+                        map["tags"],
+                        SqlValue::List(vec![
+                            SqlValue::String("a".to_string()),
+                            SqlValue::String("x".to_string()),
+                        ])
+                    );
+                }
+                _ => panic!("Expected map value"),
+            },
+            _ => panic!("Expected INSERT statement"),
+        }
+    }
+
+    #[test]
+    fn test_bind_parameter_count_mismatch() {
+        let stmt = PartiQLParser::parse("SELECT * FROM users WHERE pk = ?").unwrap();
+
+        // Missing value
+        assert!(stmt.bind(&StatementParameters::default()).is_err());
+
+        // Too many values
+        let params = StatementParameters {
+            positional: vec![Value::string("a"), Value::string("b")],
+            named: HashMap::new(),
+        };
+        assert!(stmt.bind(&params).is_err());
+    }
+}
@@ -7,8 +7,12 @@ pub mod ast;
 pub mod parser;
 pub mod validator;
 pub mod translator;
+pub mod aggregate;
+pub mod binding;
 
 pub use ast::*;
 pub use parser::*;
 pub use validator::*;
 pub use translator::*;
+pub use aggregate::*;
+pub use binding::*;
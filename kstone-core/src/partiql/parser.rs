@@ -26,6 +26,35 @@ impl PartiQLParser {
             )));
         }
 
+        // EXPLAIN SELECT ... describes the access path of the wrapped SELECT
+        let mut words = sql.trim_start().splitn(2, char::is_whitespace);
+        if words.next().map_or(false, |w| w.eq_ignore_ascii_case("EXPLAIN")) {
+            return match Self::parse(words.next().unwrap_or(""))? {
+                PartiQLStatement::Select(select) => Ok(PartiQLStatement::Explain(select)),
+                _ => Err(Error::InvalidQuery("EXPLAIN only supports SELECT statements".into())),
+            };
+        }
+
+        // REPLACE/UPSERT INTO, ON CONFLICT and INSERT ... SELECT
+        let words = Self::word_spans(sql);
+        if let Some((_, first)) = words.first() {
+            if ["INSERT", "REPLACE", "UPSERT"]
+                .iter()
+                .any(|k| first.eq_ignore_ascii_case(k))
+            {
+                return Self::parse_insert(sql, &words);
+            }
+        }
+
+        Self::parse_statement(sql)
+    }
+
+    /// Parse a single SELECT, INSERT VALUE, UPDATE or DELETE statement
+    fn parse_statement(sql: &str) -> Result<PartiQLStatement> {
+        // Number positional `?` placeholders so each binds to its own value
+        let numbered_sql = Self::number_positional_placeholders(sql);
+        let sql = numbered_sql.as_str();
+
         // Special handling for INSERT with JSON map
         if sql.trim().to_uppercase().starts_with("INSERT") && sql.contains('{') {
             return Self::parse_insert_with_json_map(sql);
@@ -58,6 +87,114 @@ impl PartiQLParser {
         Self::convert_statement(&statements[0])
     }
 
+    /// Parse INSERT, REPLACE INTO and UPSERT INTO, with VALUE or SELECT source
+    ///
+    /// `REPLACE INTO` behaves like plain INSERT; `UPSERT INTO` is shorthand for
+    /// `INSERT ... ON CONFLICT DO UPDATE`.
+    fn parse_insert(sql: &str, words: &[(usize, &str)]) -> Result<PartiQLStatement> {
+        let keyword = words[0].1.to_ascii_uppercase();
+        let mut on_conflict = match keyword.as_str() {
+            "UPSERT" => ConflictAction::DoUpdate,
+            _ => ConflictAction::Replace,
+        };
+
+        // Trailing ON CONFLICT DO NOTHING / DO UPDATE
+        let mut end = sql.len();
+        if let [.., (on_start, on), (_, conflict), (_, do_kw), (_, action)] = words {
+            if on.eq_ignore_ascii_case("ON")
+                && conflict.eq_ignore_ascii_case("CONFLICT")
+                && do_kw.eq_ignore_ascii_case("DO")
+            {
+                if keyword != "INSERT" {
+                    return Err(Error::InvalidQuery(format!(
+                        "ON CONFLICT is not supported with {}",
+                        keyword
+                    )));
+                }
+                on_conflict = if action.eq_ignore_ascii_case("NOTHING") {
+                    ConflictAction::DoNothing
+                } else if action.eq_ignore_ascii_case("UPDATE") {
+                    ConflictAction::DoUpdate
+                } else {
+                    return Err(Error::InvalidQuery(format!(
+                        "Unsupported ON CONFLICT action: {}",
+                        action
+                    )));
+                };
+                end = *on_start;
+            }
+        }
+
+        // INSERT INTO <table> SELECT ...
+        if let [_, (_, into), (_, table), (select_start, select), ..] = words {
+            if into.eq_ignore_ascii_case("INTO") && select.eq_ignore_ascii_case("SELECT") {
+                return match Self::parse_statement(&sql[*select_start..end])? {
+                    PartiQLStatement::Select(select) => {
+                        Ok(PartiQLStatement::InsertSelect(InsertSelectStatement {
+                            table_name: table.to_string(),
+                            select,
+                            on_conflict,
+                        }))
+                    }
+                    _ => Err(Error::InvalidQuery("INSERT source must be a SELECT".into())),
+                };
+            }
+        }
+
+        // INSERT INTO <table> VALUE {...}
+        let insert_sql = format!("INSERT{}", &sql[words[0].0 + words[0].1.len()..end]);
+        match Self::parse_statement(&insert_sql)? {
+            PartiQLStatement::Insert(mut insert) => {
+                insert.on_conflict = on_conflict;
+                Ok(PartiQLStatement::Insert(insert))
+            }
+            _ => Err(Error::InvalidQuery("Expected INSERT statement".into())),
+        }
+    }
+
+    /// Whitespace-separated words of a statement with their byte offsets
+    fn word_spans(sql: &str) -> Vec<(usize, &str)> {
+        let mut spans = Vec::new();
+        let mut start = None;
+        for (i, ch) in sql.char_indices() {
+            match (ch.is_whitespace(), start) {
+                (true, Some(s)) => {
+                    spans.push((s, &sql[s..i]));
+                    start = None;
+                }
+                (false, None) => start = Some(i),
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            spans.push((s, &sql[s..]));
+        }
+        spans
+    }
+
+    /// Rewrite each bare `?` outside string literals to `?1`, `?2`, ...
+    fn number_positional_placeholders(sql: &str) -> String {
+        let mut result = String::with_capacity(sql.len());
+        let mut quote: Option<char> = None;
+        let mut next_index = 1;
+        let mut chars = sql.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            result.push(ch);
+            match (quote, ch) {
+                (Some(q), c) if c == q => quote = None,
+                (None, '\'' | '"') => quote = Some(ch),
+                (None, '?') if !chars.peek().map_or(false, |c| c.is_ascii_digit()) => {
+                    result.push_str(&next_index.to_string());
+                    next_index += 1;
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
     /// Special parser for INSERT statements with JSON map syntax
     /// Handles: INSERT INTO table VALUE {'pk': 'value', ...}
     fn parse_insert_with_json_map(sql: &str) -> Result<PartiQLStatement> {
@@ -96,6 +233,7 @@ impl PartiQLParser {
         Ok(PartiQLStatement::Insert(InsertStatement {
             table_name,
             value: value_map,
+            on_conflict: ConflictAction::Replace,
         }))
     }
 
@@ -155,9 +293,6 @@ impl PartiQLParser {
         if !set_expr.distribute_by.is_empty() {
             return Err(Error::InvalidQuery("DISTRIBUTE BY not supported".into()));
         }
-        if set_expr.group_by != sql_ast::GroupByExpr::Expressions(vec![], vec![]) {
-            return Err(Error::InvalidQuery("GROUP BY not supported".into()));
-        }
         if set_expr.having.is_some() {
             return Err(Error::InvalidQuery("HAVING clause not supported".into()));
         }
@@ -180,8 +315,10 @@ impl PartiQLParser {
             Self::extract_table_reference(&set_expr.from[0])?
         };
 
-        // Extract SELECT list
+        // Extract SELECT list and GROUP BY
+        let group_by = Self::convert_group_by(&set_expr.group_by)?;
         let select_list = Self::convert_select_list(&set_expr.projection)?;
+        let select_list = Self::validate_grouping(select_list, &group_by)?;
 
         // Extract WHERE clause
         let where_clause = match &set_expr.selection {
@@ -215,6 +352,7 @@ impl PartiQLParser {
             order_by,
             limit,
             offset,
+            group_by,
         })
     }
 
@@ -263,17 +401,15 @@ impl PartiQLParser {
             }
         }
 
-        // Extract attribute names
-        let mut attributes = Vec::new();
+        // Extract attribute names and aggregate calls
+        let mut exprs = Vec::new();
         for item in projection {
             match item {
                 sql_ast::SelectItem::UnnamedExpr(expr) => {
-                    let attr_name = Self::extract_attribute_name(expr)?;
-                    attributes.push(attr_name);
+                    exprs.push(Self::convert_select_expr(expr, None)?);
                 }
-                sql_ast::SelectItem::ExprWithAlias { expr, alias: _ } => {
-                    let attr_name = Self::extract_attribute_name(expr)?;
-                    attributes.push(attr_name);
+                sql_ast::SelectItem::ExprWithAlias { expr, alias } => {
+                    exprs.push(Self::convert_select_expr(expr, Some(alias.value.clone()))?);
                 }
                 sql_ast::SelectItem::Wildcard(_) => {
                     return Err(Error::InvalidQuery("Cannot mix * with other columns".into()));
@@ -284,23 +420,158 @@ impl PartiQLParser {
             }
         }
 
+        if exprs.iter().any(|e| matches!(e, SelectExpr::Aggregate(_))) {
+            return Ok(SelectList::Aggregates(exprs));
+        }
+
+        let attributes = exprs
+            .into_iter()
+            .filter_map(|e| match e {
+                SelectExpr::Attribute(attr) => Some(attr),
+                SelectExpr::Aggregate(_) => None,
+            })
+            .collect();
         Ok(SelectList::Attributes(attributes))
     }
 
+    /// Convert a single SELECT expression (attribute or aggregate call)
+    fn convert_select_expr(expr: &sql_ast::Expr, alias: Option<String>) -> Result<SelectExpr> {
+        match expr {
+            sql_ast::Expr::Function(func) => {
+                Ok(SelectExpr::Aggregate(Self::convert_aggregate(func, alias)?))
+            }
+            _ => Ok(SelectExpr::Attribute(Self::extract_attribute_name(expr)?)),
+        }
+    }
+
+    /// Convert an aggregate function call (COUNT, SUM, AVG, MIN, MAX)
+    fn convert_aggregate(func: &sql_ast::Function, alias: Option<String>) -> Result<AggregateExpr> {
+        let name = func
+            .name
+            .0
+            .last()
+            .map(|ident| ident.value.clone())
+            .unwrap_or_default();
+        let function = AggregateFunction::from_name(&name).ok_or_else(|| {
+            Error::InvalidQuery(format!("Unsupported function in SELECT list: {}", name))
+        })?;
+
+        if func.over.is_some() || func.filter.is_some() {
+            return Err(Error::InvalidQuery(format!(
+                "Window and FILTER clauses not supported on {}",
+                function.name()
+            )));
+        }
+
+        let args = match &func.args {
+            sql_ast::FunctionArguments::List(list) => {
+                if list.duplicate_treatment.is_some() {
+                    return Err(Error::InvalidQuery(format!(
+                        "DISTINCT not supported in {}",
+                        function.name()
+                    )));
+                }
+                &list.args
+            }
+            _ => {
+                return Err(Error::InvalidQuery(format!(
+                    "{} requires exactly one argument",
+                    function.name()
+                )))
+            }
+        };
+
+        if args.len() != 1 {
+            return Err(Error::InvalidQuery(format!(
+                "{} requires exactly one argument",
+                function.name()
+            )));
+        }
+
+        let attribute = match &args[0] {
+            sql_ast::FunctionArg::Unnamed(sql_ast::FunctionArgExpr::Wildcard) => {
+                if function != AggregateFunction::Count {
+                    return Err(Error::InvalidQuery(format!(
+                        "{}(*) not supported (only COUNT(*))",
+                        function.name()
+                    )));
+                }
+                None
+            }
+            sql_ast::FunctionArg::Unnamed(sql_ast::FunctionArgExpr::Expr(expr)) => {
+                Some(Self::extract_attribute_name(expr)?)
+            }
+            _ => {
+                return Err(Error::InvalidQuery(format!(
+                    "Unsupported argument to {}",
+                    function.name()
+                )))
+            }
+        };
+
+        Ok(AggregateExpr {
+            function,
+            attribute,
+            alias,
+        })
+    }
+
+    /// Convert GROUP BY clause to attribute paths
+    fn convert_group_by(group_by: &sql_ast::GroupByExpr) -> Result<Vec<String>> {
+        match group_by {
+            sql_ast::GroupByExpr::Expressions(exprs, modifiers) if modifiers.is_empty() => {
+                exprs.iter().map(Self::extract_attribute_name).collect()
+            }
+            _ => Err(Error::InvalidQuery(
+                "GROUP BY ALL and grouping modifiers not supported".into(),
+            )),
+        }
+    }
+
+    /// Check SELECT list against GROUP BY
+    ///
+    /// Plain attributes must be grouped when aggregating; `SELECT attr ... GROUP BY attr`
+    /// becomes an aggregate SELECT returning one row per distinct value.
+    fn validate_grouping(select_list: SelectList, group_by: &[String]) -> Result<SelectList> {
+        let select_list = match select_list {
+            SelectList::All if !group_by.is_empty() => {
+                return Err(Error::InvalidQuery(
+                    "SELECT * cannot be used with GROUP BY".into(),
+                ));
+            }
+            SelectList::Attributes(attrs) if !group_by.is_empty() => {
+                SelectList::Aggregates(attrs.into_iter().map(SelectExpr::Attribute).collect())
+            }
+            other => other,
+        };
+
+        if let SelectList::Aggregates(exprs) = &select_list {
+            for expr in exprs {
+                if let SelectExpr::Attribute(attr) = expr {
+                    if !group_by.contains(attr) {
+                        return Err(Error::InvalidQuery(format!(
+                            "Attribute '{}' must appear in GROUP BY or be used in an aggregate function",
+                            attr
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(select_list)
+    }
+
     /// Extract attribute name from expression
+    ///
+    /// Compound identifiers become dotted nested paths (e.g. `address.city`).
     fn extract_attribute_name(expr: &sql_ast::Expr) -> Result<String> {
         match expr {
             sql_ast::Expr::Identifier(ident) => Ok(ident.value.clone()),
-            sql_ast::Expr::CompoundIdentifier(parts) => {
-                if parts.len() == 1 {
-                    Ok(parts[0].value.clone())
-                } else {
-                    Err(Error::InvalidQuery(format!(
-                        "Compound identifiers not supported: {:?}",
-                        parts
-                    )))
-                }
-            }
+            sql_ast::Expr::CompoundIdentifier(parts) => Ok(parts
+                .iter()
+                .map(|part| part.value.as_str())
+                .collect::<Vec<_>>()
+                .join(".")),
             _ => Err(Error::InvalidQuery(format!(
                 "Unsupported expression in SELECT list: {:?}",
                 expr
@@ -355,6 +626,9 @@ impl PartiQLParser {
                 let condition = Self::convert_in_condition(expr, list)?;
                 conditions.push(condition);
             }
+            sql_ast::Expr::Nested(inner) => {
+                Self::extract_conditions(inner, conditions)?;
+            }
             sql_ast::Expr::Between { expr, negated, low, high } => {
                 if *negated {
                     return Err(Error::InvalidQuery("NOT BETWEEN not supported".into()));
@@ -451,6 +725,7 @@ impl PartiQLParser {
             }
             sql_ast::Value::Boolean(b) => Ok(SqlValue::Boolean(*b)),
             sql_ast::Value::Null => Ok(SqlValue::Null),
+            sql_ast::Value::Placeholder(p) => Ok(SqlValue::Parameter(p.clone())),
             _ => Err(Error::InvalidQuery(format!("Unsupported SQL value: {:?}", val))),
         }
     }
@@ -519,6 +794,7 @@ impl PartiQLParser {
         Ok(InsertStatement {
             table_name,
             value: value_map,
+            on_conflict: ConflictAction::Replace,
         })
     }
 
@@ -660,7 +936,7 @@ impl PartiQLParser {
     fn parse_json_string(s: &str) -> Result<SqlValue> {
         // DynamoDB uses single quotes, but JSON requires double quotes
         // Convert single quotes to double quotes (simple approach - may need refinement)
-        let json_normalized = s.replace('\'', "\"");
+        let json_normalized = Self::quote_json_placeholders(&s.replace('\'', "\""));
 
         let json_value: serde_json::Value = serde_json::from_str(&json_normalized).map_err(|e| {
             Error::InvalidQuery(format!("Failed to parse JSON: {}", e))
@@ -669,13 +945,72 @@ impl PartiQLParser {
         Self::json_to_sql_value(&json_value)
     }
 
+    /// Turn placeholders in a JSON map into marked strings so serde_json accepts them
+    ///
+    /// `?1` and `:name` (in value position, i.e. after `:`, `,` or `[`) become
+    /// `"\u0000?1"` / `"\u0000:name"`, which `json_to_sql_value` maps to parameters.
+    fn quote_json_placeholders(json: &str) -> String {
+        let mut result = String::with_capacity(json.len());
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut prev_token: Option<char> = None;
+        let mut chars = json.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if in_string {
+                result.push(ch);
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == '"' {
+                    in_string = false;
+                }
+                prev_token = Some(ch);
+                continue;
+            }
+
+            let is_named = ch == ':'
+                && matches!(prev_token, Some(':' | ',' | '['))
+                && chars.peek().map_or(false, |c| c.is_alphabetic() || *c == '_');
+
+            if ch == '?' || is_named {
+                let mut name = String::from(ch);
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        name.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                result.push_str(&format!("\"\\u0000{}\"", name));
+                prev_token = Some('"');
+                continue;
+            }
+
+            if ch == '"' {
+                in_string = true;
+            }
+            if !ch.is_whitespace() {
+                prev_token = Some(ch);
+            }
+            result.push(ch);
+        }
+
+        result
+    }
+
     /// Convert serde_json::Value to SqlValue
     fn json_to_sql_value(value: &serde_json::Value) -> Result<SqlValue> {
         match value {
             serde_json::Value::Null => Ok(SqlValue::Null),
             serde_json::Value::Bool(b) => Ok(SqlValue::Boolean(*b)),
             serde_json::Value::Number(n) => Ok(SqlValue::Number(n.to_string())),
-            serde_json::Value::String(s) => Ok(SqlValue::String(s.clone())),
+            serde_json::Value::String(s) => match s.strip_prefix('\0') {
+                Some(placeholder) => Ok(SqlValue::Parameter(placeholder.to_string())),
+                None => Ok(SqlValue::String(s.clone())),
+            },
             serde_json::Value::Array(arr) => {
                 let items: Result<Vec<SqlValue>> = arr.iter().map(Self::json_to_sql_value).collect();
                 Ok(SqlValue::List(items?))
@@ -1010,6 +1345,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_select_with_nested_path() {
+        let sql = "SELECT name, address.city FROM users WHERE pk = 'user#123' AND (address.zip = '75001') ORDER BY address.city";
+        let stmt = PartiQLParser::parse(sql).unwrap();
+
+        match stmt {
+            PartiQLStatement::Select(select) => {
+                assert_eq!(
+                    select.select_list,
+                    SelectList::Attributes(vec!["name".to_string(), "address.city".to_string()])
+                );
+
+                let where_clause = select.where_clause.unwrap();
+                assert_eq!(where_clause.conditions.len(), 2);
+                assert!(where_clause.has_condition("address.zip"));
+
+                assert_eq!(select.order_by.unwrap().attribute, "address.city");
+            }
+            _ => panic!("Expected SELECT statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_explain_select() {
+        let sql = "explain SELECT * FROM users WHERE pk = 'user#123' AND age > 30";
+        match PartiQLParser::parse(sql).unwrap() {
+            PartiQLStatement::Explain(select) => {
+                assert_eq!(select.table_name, "users");
+                assert_eq!(select.where_clause.unwrap().conditions.len(), 2);
+            }
+            _ => panic!("Expected EXPLAIN statement"),
+        }
+
+        assert!(PartiQLParser::parse("EXPLAIN DELETE FROM users WHERE pk = 'user#123'").is_err());
+    }
+
     #[test]
     fn test_reject_join() {
         let sql = "SELECT * FROM users JOIN orders ON users.pk = orders.user_id";
@@ -1028,12 +1399,63 @@ mod tests {
 
     #[test]
     fn test_reject_group_by() {
-        let sql = "SELECT COUNT(*) FROM users GROUP BY status";
+        let sql = "SELECT * FROM users GROUP BY status";
+        let result = PartiQLParser::parse(sql);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("GROUP BY"));
+
+        // Non-aggregated attributes must be grouped
+        let sql = "SELECT name, COUNT(*) FROM users GROUP BY status";
         let result = PartiQLParser::parse(sql);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("GROUP BY"));
     }
 
+    #[test]
+    fn test_parse_select_with_aggregates_and_group_by() {
+        let sql = "SELECT status, COUNT(*), SUM(price) AS total FROM items GROUP BY status";
+        let stmt = PartiQLParser::parse(sql).unwrap();
+
+        match stmt {
+            PartiQLStatement::Select(select) => {
+                assert_eq!(select.group_by, vec!["status"]);
+                assert!(select.is_aggregate());
+
+                match select.select_list {
+                    SelectList::Aggregates(exprs) => {
+                        assert_eq!(exprs.len(), 3);
+                        assert_eq!(exprs[0], SelectExpr::Attribute("status".to_string()));
+                        match &exprs[1] {
+                            SelectExpr::Aggregate(agg) => {
+                                assert_eq!(agg.function, AggregateFunction::Count);
+                                assert_eq!(agg.attribute, None);
+                                assert_eq!(agg.output_name(), "COUNT(*)");
+                            }
+                            _ => panic!("Expected aggregate"),
+                        }
+                        match &exprs[2] {
+                            SelectExpr::Aggregate(agg) => {
+                                assert_eq!(agg.function, AggregateFunction::Sum);
+                                assert_eq!(agg.attribute.as_deref(), Some("price"));
+                                assert_eq!(agg.output_name(), "total");
+                            }
+                            _ => panic!("Expected aggregate"),
+                        }
+                    }
+                    _ => panic!("Expected aggregate select list"),
+                }
+            }
+            _ => panic!("Expected SELECT statement"),
+        }
+    }
+
+    #[test]
+    fn test_reject_unsupported_aggregate() {
+        assert!(PartiQLParser::parse("SELECT SUM(*) FROM items").is_err());
+        assert!(PartiQLParser::parse("SELECT COUNT(DISTINCT status) FROM items").is_err());
+        assert!(PartiQLParser::parse("SELECT UPPER(name) FROM items").is_err());
+    }
+
     #[test]
     fn test_reject_too_long() {
         let sql = "SELECT * FROM users WHERE pk = '".to_string() + &"x".repeat(10000) + "'";
@@ -1198,6 +1620,44 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_insert_conflict_actions() {
+        let cases = [
+            ("INSERT INTO users VALUE {'pk': 'user#1'}", ConflictAction::Replace),
+            ("REPLACE INTO users VALUE {'pk': 'user#1'}", ConflictAction::Replace),
+            ("UPSERT INTO users VALUE {'pk': 'user#1'}", ConflictAction::DoUpdate),
+            ("INSERT INTO users VALUE {'pk': 'user#1'} ON CONFLICT DO NOTHING", ConflictAction::DoNothing),
+            ("insert into users VALUE {'pk': 'user#1'} on conflict do update", ConflictAction::DoUpdate),
+        ];
+
+        for (sql, expected) in cases {
+            match PartiQLParser::parse(sql).unwrap() {
+                PartiQLStatement::Insert(insert) => {
+                    assert_eq!(insert.table_name, "users");
+                    assert_eq!(insert.on_conflict, expected, "{}", sql);
+                }
+                _ => panic!("Expected INSERT statement for {}", sql),
+            }
+        }
+
+        assert!(PartiQLParser::parse("REPLACE INTO users VALUE {'pk': 'user#1'} ON CONFLICT DO NOTHING").is_err());
+        assert!(PartiQLParser::parse("INSERT INTO users VALUE {'pk': 'user#1'} ON CONFLICT DO SOMETHING").is_err());
+    }
+
+    #[test]
+    fn test_parse_insert_select() {
+        let sql = "INSERT INTO archive SELECT pk, sk, status FROM users WHERE status = 'closed' ON CONFLICT DO NOTHING";
+        match PartiQLParser::parse(sql).unwrap() {
+            PartiQLStatement::InsertSelect(insert) => {
+                assert_eq!(insert.table_name, "archive");
+                assert_eq!(insert.on_conflict, ConflictAction::DoNothing);
+                assert_eq!(insert.select.table_name, "users");
+                assert!(insert.select.where_clause.unwrap().has_condition("status"));
+            }
+            _ => panic!("Expected INSERT ... SELECT statement"),
+        }
+    }
+
     // UPDATE tests
     #[test]
     fn test_parse_update_simple() {
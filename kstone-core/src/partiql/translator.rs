@@ -3,6 +3,7 @@
 /// Maps SELECT to Query/Scan, INSERT to Put, UPDATE to Update, DELETE to Delete.
 
 use crate::partiql::ast::*;
+use crate::index::{IndexProjection, TableSchema};
 use crate::partiql::validator::{DynamoDBValidator, QueryType};
 use crate::{Error, Key, Result};
 use bytes::Bytes;
@@ -12,20 +13,33 @@ pub struct PartiQLTranslator;
 
 impl PartiQLTranslator {
     /// Translate SELECT statement to Query or Scan parameters
+    ///
+    /// Key conditions are pushed into the Query; every other WHERE condition is
+    /// returned as a residual filter. ORDER BY on the sort key sets the scan
+    /// direction, any other attribute is returned as an in-memory sort.
     pub fn translate_select(stmt: &SelectStatement) -> Result<SelectTranslation> {
         // Validate and determine query type
         let query_type = DynamoDBValidator::validate_select(stmt)?;
 
+        let sort_by = stmt
+            .order_by
+            .as_ref()
+            .filter(|o| !o.is_sort_key())
+            .cloned();
+
         match query_type {
             QueryType::Query { pk_condition, sk_condition } => {
                 // Translate to Query
                 let (pk_bytes, multiple_pks) = Self::extract_pk_bytes(&pk_condition)?;
+                let filter_conditions = Self::residual_conditions(stmt);
 
                 if multiple_pks {
                     // IN clause with multiple PKs - need to execute multiple gets
                     Ok(SelectTranslation::MultiGet {
                         keys: pk_bytes,
                         index_name: stmt.index_name.clone(),
+                        filter_conditions,
+                        sort_by,
                     })
                 } else {
                     // Single PK - regular Query
@@ -39,7 +53,13 @@ impl PartiQLTranslator {
                         pk,
                         sk_condition: sk_condition_translated,
                         index_name: stmt.index_name.clone(),
-                        forward: stmt.order_by.as_ref().map_or(true, |o| o.ascending),
+                        forward: stmt
+                            .order_by
+                            .as_ref()
+                            .filter(|o| o.is_sort_key())
+                            .map_or(true, |o| o.ascending),
+                        filter_conditions,
+                        sort_by,
                     })
                 }
             }
@@ -51,11 +71,113 @@ impl PartiQLTranslator {
                         .as_ref()
                         .map(|wc| wc.conditions.clone())
                         .unwrap_or_default(),
+                    sort_by,
                 })
             }
         }
     }
 
+    /// Route a SELECT without a partition key condition to a matching GSI (Phase 4+)
+    ///
+    /// Returns the statement rewritten as a query on the first index whose partition
+    /// key attribute has an `=` or `IN` condition. Every other condition, including
+    /// one on the index sort key, stays a residual filter. Indexes are skipped while
+    /// backfilling, when their projection lacks attributes the statement reads, and
+    /// when they have a sort key the WHERE clause doesn't constrain (items without
+    /// it are not indexed). Returns `None` if no index applies.
+    pub fn route_to_index(stmt: &SelectStatement, schema: &TableSchema) -> Option<SelectStatement> {
+        if stmt.index_name.is_some() {
+            return None;
+        }
+        let where_clause = stmt.where_clause.as_ref()?;
+        if where_clause.has_condition("pk") || where_clause.has_condition("sk") {
+            return None;
+        }
+
+        let constrains = |attribute: &str| {
+            where_clause
+                .conditions
+                .iter()
+                .any(|c| c.attribute == attribute && c.operator != CompareOp::NotEqual)
+        };
+
+        let (gsi, pk_position) = schema.global_indexes.iter().find_map(|gsi| {
+            if schema.is_backfilling(&gsi.name) {
+                return None;
+            }
+            if let Some(sk_attribute) = &gsi.sort_key_attribute {
+                if !constrains(sk_attribute) {
+                    return None;
+                }
+            }
+            if !Self::index_covers(stmt, &gsi.projection, &gsi.key_attributes()) {
+                return None;
+            }
+            where_clause
+                .conditions
+                .iter()
+                .position(|c| {
+                    c.attribute == gsi.partition_key_attribute && Self::is_key_lookup(c)
+                })
+                .map(|position| (gsi, position))
+        })?;
+
+        let mut routed = stmt.clone();
+        routed.index_name = Some(gsi.name.clone());
+        if let Some(where_clause) = routed.where_clause.as_mut() {
+            where_clause.conditions[pk_position].attribute = "pk".to_string();
+        }
+        Some(routed)
+    }
+
+    /// Whether a condition can become a partition key condition
+    fn is_key_lookup(condition: &Condition) -> bool {
+        let is_key_value = |v: &SqlValue| matches!(v, SqlValue::String(_) | SqlValue::Number(_));
+        match (&condition.operator, &condition.value) {
+            (CompareOp::Equal, value) => is_key_value(value),
+            (CompareOp::In, SqlValue::List(values)) => values.iter().all(is_key_value),
+            _ => false,
+        }
+    }
+
+    /// Whether an index holds every attribute a SELECT reads
+    fn index_covers(stmt: &SelectStatement, projection: &IndexProjection, key_attributes: &[&str]) -> bool {
+        let mut paths: Vec<String> = match &stmt.select_list {
+            // SELECT * must return the same items as the base table would
+            SelectList::All => return matches!(projection, IndexProjection::All),
+            SelectList::Attributes(attributes) => attributes.clone(),
+            SelectList::Aggregates(exprs) => exprs
+                .iter()
+                .filter_map(|expr| match expr {
+                    SelectExpr::Attribute(attribute) => Some(attribute.clone()),
+                    SelectExpr::Aggregate(agg) => agg.attribute.clone(),
+                })
+                .chain(stmt.group_by.iter().cloned())
+                .collect(),
+        };
+        if let Some(where_clause) = &stmt.where_clause {
+            paths.extend(where_clause.conditions.iter().map(|c| c.attribute.clone()));
+        }
+        if let Some(order_by) = &stmt.order_by {
+            paths.push(order_by.attribute.clone());
+        }
+        projection.covers(key_attributes, Some(&paths))
+    }
+
+    /// Non-key WHERE conditions left to filter after a Query
+    fn residual_conditions(stmt: &SelectStatement) -> Vec<Condition> {
+        stmt.where_clause
+            .as_ref()
+            .map(|wc| {
+                wc.conditions
+                    .iter()
+                    .filter(|c| !c.is_key_attribute())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Extract partition key bytes from condition
     fn extract_pk_bytes(condition: &Condition) -> Result<(Vec<Bytes>, bool)> {
         match &condition.operator {
@@ -279,15 +401,25 @@ pub enum SelectTranslation {
         sk_condition: Option<SortKeyConditionType>,
         index_name: Option<String>,
         forward: bool,
+        /// Residual non-key conditions applied after the Query
+        filter_conditions: Vec<Condition>,
+        /// ORDER BY that cannot be served by the sort key
+        sort_by: Option<OrderBy>,
     },
     /// Multiple get operations (IN clause on pk)
     MultiGet {
         keys: Vec<Bytes>,
         index_name: Option<String>,
+        /// Residual non-key conditions applied after each Query
+        filter_conditions: Vec<Condition>,
+        /// ORDER BY applied across all partitions
+        sort_by: Option<OrderBy>,
     },
     /// Scan operation (full table scan)
     Scan {
         filter_conditions: Vec<Condition>,
+        /// ORDER BY applied to the scanned items
+        sort_by: Option<OrderBy>,
     },
 }
 
@@ -343,6 +475,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let translation = PartiQLTranslator::translate_select(&stmt).unwrap();
@@ -364,6 +497,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let translation = PartiQLTranslator::translate_select(&stmt).unwrap();
@@ -373,6 +507,154 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_route_to_index() {
+        use crate::index::GlobalSecondaryIndex;
+        use crate::partiql::{PartiQLParser, PartiQLStatement};
+
+        let select = |sql: &str| match PartiQLParser::parse(sql).unwrap() {
+            PartiQLStatement::Select(select) => select,
+            _ => panic!("Expected SELECT statement"),
+        };
+        let schema = TableSchema::new()
+            .add_global_index(GlobalSecondaryIndex::new("status-index", "status"))
+            .add_global_index(
+                GlobalSecondaryIndex::with_sort_key("status-ts-index", "status", "ts").keys_only(),
+            );
+
+        // The partition key condition is routed to the index, the rest stays a filter
+        let stmt = select("SELECT * FROM orders WHERE status = 'active' AND ts > 5");
+        let routed = PartiQLTranslator::route_to_index(&stmt, &schema).unwrap();
+        assert_eq!(routed.index_name.as_deref(), Some("status-index"));
+        match PartiQLTranslator::translate_select(&routed).unwrap() {
+            SelectTranslation::Query {
+                pk,
+                index_name,
+                filter_conditions,
+                ..
+            } => {
+                assert_eq!(pk.as_ref(), b"active");
+                assert_eq!(index_name.as_deref(), Some("status-index"));
+                assert_eq!(filter_conditions.len(), 1);
+                assert_eq!(filter_conditions[0].attribute, "ts");
+            }
+            other => panic!("Expected Query translation, got {:?}", other),
+        }
+
+        // A keys-only index covers a projection of its key attributes
+        let keys_only = TableSchema::new().add_global_index(
+            GlobalSecondaryIndex::with_sort_key("status-ts-index", "status", "ts").keys_only(),
+        );
+        let stmt = select("SELECT status, ts FROM orders WHERE status = 'active' AND ts > 5");
+        assert!(PartiQLTranslator::route_to_index(&stmt, &keys_only).is_some());
+
+        // ... but not SELECT *, and not without a condition on its sort key
+        let stmt = select("SELECT * FROM orders WHERE status = 'active' AND ts > 5");
+        assert!(PartiQLTranslator::route_to_index(&stmt, &keys_only).is_none());
+        let stmt = select("SELECT status FROM orders WHERE status = 'active'");
+        assert!(PartiQLTranslator::route_to_index(&stmt, &keys_only).is_none());
+
+        // No matching index, or a range condition on the index partition key
+        let stmt = select("SELECT * FROM orders WHERE region = 'eu'");
+        assert!(PartiQLTranslator::route_to_index(&stmt, &schema).is_none());
+        let stmt = select("SELECT * FROM orders WHERE status > 'a'");
+        assert!(PartiQLTranslator::route_to_index(&stmt, &schema).is_none());
+    }
+
+    #[test]
+    fn test_translate_select_query_residual_filter_and_sort() {
+        let stmt = SelectStatement {
+            table_name: "users".to_string(),
+            index_name: None,
+            select_list: SelectList::All,
+            where_clause: Some(WhereClause {
+                conditions: vec![
+                    Condition {
+                        attribute: "pk".to_string(),
+                        operator: CompareOp::Equal,
+                        value: SqlValue::String("org#acme".to_string()),
+                    },
+                    Condition {
+                        attribute: "sk".to_string(),
+                        operator: CompareOp::GreaterThan,
+                        value: SqlValue::String("user#".to_string()),
+                    },
+                    Condition {
+                        attribute: "age".to_string(),
+                        operator: CompareOp::In,
+                        value: SqlValue::List(vec![
+                            SqlValue::Number("30".to_string()),
+                            SqlValue::Number("40".to_string()),
+                        ]),
+                    },
+                ],
+            }),
+            order_by: Some(OrderBy {
+                attribute: "age".to_string(),
+                ascending: false,
+            }),
+            limit: Some(10),
+            offset: None,
+            group_by: Vec::new(),
+        };
+
+        let translation = PartiQLTranslator::translate_select(&stmt).unwrap();
+        match translation {
+            SelectTranslation::Query {
+                sk_condition,
+                forward,
+                filter_conditions,
+                sort_by,
+                ..
+            } => {
+                assert!(matches!(sk_condition, Some(SortKeyConditionType::GreaterThan(_))));
+                // Non-key ORDER BY does not change the scan direction
+                assert!(forward);
+                assert_eq!(filter_conditions.len(), 1);
+                assert_eq!(filter_conditions[0].attribute, "age");
+                assert_eq!(sort_by.unwrap().attribute, "age");
+            }
+            _ => panic!("Expected Query translation"),
+        }
+    }
+
+    #[test]
+    fn test_translate_select_sort_key_order_pushed_down() {
+        let stmt = SelectStatement {
+            table_name: "users".to_string(),
+            index_name: None,
+            select_list: SelectList::All,
+            where_clause: Some(WhereClause {
+                conditions: vec![Condition {
+                    attribute: "pk".to_string(),
+                    operator: CompareOp::Equal,
+                    value: SqlValue::String("org#acme".to_string()),
+                }],
+            }),
+            order_by: Some(OrderBy {
+                attribute: "sk".to_string(),
+                ascending: false,
+            }),
+            limit: None,
+            offset: None,
+            group_by: Vec::new(),
+        };
+
+        match PartiQLTranslator::translate_select(&stmt).unwrap() {
+            SelectTranslation::Query {
+                forward,
+                filter_conditions,
+                sort_by,
+                ..
+            } => {
+                assert!(!forward);
+                assert!(filter_conditions.is_empty());
+                assert!(sort_by.is_none());
+            }
+            _ => panic!("Expected Query translation"),
+        }
+    }
+
     #[test]
     fn test_translate_insert() {
         let mut map = std::collections::HashMap::new();
@@ -383,6 +665,7 @@ mod tests {
         let stmt = InsertStatement {
             table_name: "users".to_string(),
             value: SqlValue::Map(map),
+            on_conflict: ConflictAction::Replace,
         };
 
         let translation = PartiQLTranslator::translate_insert(&stmt).unwrap();
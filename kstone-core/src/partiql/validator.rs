@@ -119,6 +119,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let query_type = DynamoDBValidator::validate_select(&stmt).unwrap();
@@ -155,6 +156,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let query_type = DynamoDBValidator::validate_select(&stmt).unwrap();
@@ -178,6 +180,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let query_type = DynamoDBValidator::validate_select(&stmt).unwrap();
@@ -200,6 +203,7 @@ mod tests {
             order_by: None,
             limit: None,
             offset: None,
+            group_by: Vec::new(),
         };
 
         let query_type = DynamoDBValidator::validate_select(&stmt).unwrap();
@@ -215,6 +219,7 @@ mod tests {
         let stmt = InsertStatement {
             table_name: "users".to_string(),
             value: SqlValue::Map(map),
+            on_conflict: ConflictAction::Replace,
         };
 
         assert!(DynamoDBValidator::validate_insert(&stmt).is_ok());
@@ -228,6 +233,7 @@ mod tests {
         let stmt = InsertStatement {
             table_name: "users".to_string(),
             value: SqlValue::Map(map),
+            on_conflict: ConflictAction::Replace,
         };
 
         let result = DynamoDBValidator::validate_insert(&stmt);
@@ -0,0 +1,196 @@
+/// Slow operation log (Phase 8+)
+///
+/// With `DatabaseConfig::slow_operation_threshold` set, every get, put,
+/// delete, update, query and scan that takes at least that long is recorded
+/// with its key (or partition), how many items it examined and returned, and
+/// its duration. The newest `SLOW_LOG_CAPACITY` entries are kept in memory;
+/// nothing is written to disk.
+
+use crate::stream::current_timestamp_millis;
+use crate::Key;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of slow operations kept; older entries are dropped
+pub const SLOW_LOG_CAPACITY: usize = 1024;
+
+/// Threshold value meaning the log is off
+const DISABLED: u64 = u64::MAX;
+
+/// Kind of a logged operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlowOperationKind {
+    Get,
+    Put,
+    Delete,
+    Update,
+    Query,
+    Scan,
+}
+
+impl fmt::Display for SlowOperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SlowOperationKind::Get => "get",
+            SlowOperationKind::Put => "put",
+            SlowOperationKind::Delete => "delete",
+            SlowOperationKind::Update => "update",
+            SlowOperationKind::Query => "query",
+            SlowOperationKind::Scan => "scan",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An operation that exceeded the slow operation threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowOperation {
+    /// Operation type
+    pub kind: SlowOperationKind,
+
+    /// Key of a point operation, or the partition (no sort key) of a
+    /// query; `None` for scans
+    pub key: Option<Key>,
+
+    /// Items examined, before filters (0 for writes)
+    pub scanned_count: usize,
+
+    /// Items returned (0 for writes)
+    pub returned_count: usize,
+
+    /// Time the operation took
+    pub duration: Duration,
+
+    /// When the operation finished (milliseconds since the Unix epoch)
+    pub timestamp_ms: i64,
+}
+
+/// Bounded log of slow operations shared by an engine's callers
+pub struct SlowLog {
+    threshold_micros: AtomicU64,
+    entries: Mutex<VecDeque<SlowOperation>>,
+}
+
+impl SlowLog {
+    /// Create a log recording operations of at least `threshold` (None = off)
+    pub fn new(threshold: Option<Duration>) -> Self {
+        let log = Self {
+            threshold_micros: AtomicU64::new(DISABLED),
+            entries: Mutex::new(VecDeque::new()),
+        };
+        log.set_threshold(threshold);
+        log
+    }
+
+    /// Change the threshold; `None` turns the log off but keeps its entries
+    pub fn set_threshold(&self, threshold: Option<Duration>) {
+        let micros = threshold.map_or(DISABLED, |t| t.as_micros().min(DISABLED as u128 - 1) as u64);
+        self.threshold_micros.store(micros, Ordering::Relaxed);
+    }
+
+    /// The current threshold
+    pub fn threshold(&self) -> Option<Duration> {
+        match self.threshold_micros.load(Ordering::Relaxed) {
+            DISABLED => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+
+    /// Start timing an operation; does nothing while the log is off
+    pub fn start(&self, kind: SlowOperationKind, key: Option<&Key>) -> SlowTimer<'_> {
+        let started = self.threshold().is_some().then(|| (Instant::now(), key.cloned()));
+        SlowTimer { log: self, kind, started }
+    }
+
+    /// Logged operations, oldest first
+    pub fn entries(&self) -> Vec<SlowOperation> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    /// Drop all logged operations
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    fn record(&self, operation: SlowOperation) {
+        let mut entries = self.entries.lock();
+        if entries.len() == SLOW_LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(operation);
+    }
+}
+
+/// Times one operation for a `SlowLog`
+pub struct SlowTimer<'a> {
+    log: &'a SlowLog,
+    kind: SlowOperationKind,
+    started: Option<(Instant, Option<Key>)>,
+}
+
+impl SlowTimer<'_> {
+    /// Finish the operation, logging it if it took at least the threshold
+    pub fn finish(self, scanned_count: usize, returned_count: usize) {
+        let (Some((started, key)), Some(threshold)) = (self.started, self.log.threshold()) else {
+            return;
+        };
+
+        let duration = started.elapsed();
+        if duration >= threshold {
+            self.log.record(SlowOperation {
+                kind: self.kind,
+                key,
+                scanned_count,
+                returned_count,
+                duration,
+                timestamp_ms: current_timestamp_millis(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_log_threshold() {
+        let log = SlowLog::new(None);
+        assert_eq!(log.threshold(), None);
+        log.start(SlowOperationKind::Get, None).finish(1, 1);
+        assert!(log.entries().is_empty());
+
+        // A zero threshold records everything
+        log.set_threshold(Some(Duration::ZERO));
+        let key = Key::new(b"user#1".to_vec());
+        log.start(SlowOperationKind::Get, Some(&key)).finish(1, 0);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, SlowOperationKind::Get);
+        assert_eq!(entries[0].key, Some(key));
+        assert_eq!((entries[0].scanned_count, entries[0].returned_count), (1, 0));
+
+        log.set_threshold(Some(Duration::from_secs(60)));
+        log.start(SlowOperationKind::Scan, None).finish(10, 10);
+        assert_eq!(log.entries().len(), 1);
+
+        log.clear();
+        assert!(log.entries().is_empty());
+    }
+
+    #[test]
+    fn test_slow_log_capacity() {
+        let log = SlowLog::new(Some(Duration::ZERO));
+        for i in 0..SLOW_LOG_CAPACITY + 5 {
+            log.start(SlowOperationKind::Query, None).finish(i, 0);
+        }
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), SLOW_LOG_CAPACITY);
+        assert_eq!(entries[0].scanned_count, 5);
+    }
+}
@@ -1,20 +1,37 @@
-use crate::{Error, Result, Record, Key};
+use crate::{Error, Result, Record, Key, SeqNo, Value};
+use crate::bloom::{BloomFilter, BloomStats, BloomStatsAtomic};
+use crate::iterator::{QueryParams, SortKeyCondition};
+use crate::tiering::{ColdSstStub, ColdTier};
+use crate::vlog::{BlobRefs, ValueLog, ValueLogWriter};
 use bytes::{Bytes, BytesMut, BufMut};
-use std::fs::{File, OpenOptions};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::collections::BTreeSet;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 const SST_HEADER_SIZE: usize = 16;
 const SST_MAGIC: u32 = 0x53535400; // "SST\0"
+const BLOOM_BITS_PER_KEY: usize = 10; // ~1% false positive rate
+
+/// Version 2 adds the key range footer and version 3 value log pointers;
+/// older files are still readable
+const SST_VERSION: u32 = 3;
 
 /// Minimal SST for walking skeleton
-/// Format: [magic(4) | version(4) | count(4) | flags(4)] [record...] [crc(4)]
+/// Format: [magic(4) | version(4) | count(4) | flags(4)] [record...] [footer | footer_len(4)] [crc(4)]
 /// Records are sorted by key
 /// flags bit 0: compression enabled (1 = compressed, 0 = uncompressed)
+/// The footer (version 2+) is an uncompressed `SstKeyRange`; the CRC covers the
+/// uncompressed records followed by the footer.
+/// Records (version 3+) are stored as `bincode((Record, BlobRefs))`; attributes
+/// listed in the `BlobRefs` are left out of the record and live in the value log.
 pub struct SstWriter {
-    records: Vec<Record>,
+    records: Vec<(Record, BlobRefs)>,
     compress: bool,
     compression_level: i32,
+    value_log: Option<(ValueLogWriter, usize)>, // Writer and externalization threshold (Phase 8+)
 }
 
 impl SstWriter {
@@ -23,6 +40,7 @@ impl SstWriter {
             records: Vec::new(),
             compress: false,
             compression_level: 3,
+            value_log: None,
         }
     }
 
@@ -31,21 +49,50 @@ impl SstWriter {
             records: Vec::new(),
             compress,
             compression_level: level.clamp(1, 22),
+            value_log: None,
         }
     }
 
+    /// Move top-level binary attributes of at least `threshold` bytes to a value log file (Phase 8+)
+    pub fn with_value_log(mut self, writer: ValueLogWriter, threshold: usize) -> Self {
+        self.value_log = Some((writer, threshold));
+        self
+    }
+
     pub fn add(&mut self, record: Record) {
-        self.records.push(record);
+        self.records.push((record, BlobRefs::new()));
+    }
+
+    /// Add a record whose `blobs` attributes are already in the value log (Phase 8+)
+    ///
+    /// The attributes are stored as the existing pointers rather than written again.
+    pub fn add_with_blobs(&mut self, record: Record, blobs: BlobRefs) {
+        self.records.push((record, blobs));
     }
 
     pub fn finish(mut self, path: impl AsRef<Path>) -> Result<()> {
         // Sort records by key
-        self.records.sort_by(|a, b| {
+        self.records.sort_by(|(a, _), (b, _)| {
             let a_enc = a.key.encode();
             let b_enc = b.key.encode();
             a_enc.cmp(&b_enc)
         });
 
+        // Blobs must be durable before any SST points at them
+        if let Some((mut writer, threshold)) = self.value_log.take() {
+            for (record, blobs) in &mut self.records {
+                externalize_blobs(record, blobs, &mut writer, threshold)?;
+            }
+            writer.finish()?;
+        }
+        for (record, blobs) in &mut self.records {
+            if let Some(item) = &mut record.value {
+                for (name, _) in blobs.iter() {
+                    item.remove(name);
+                }
+            }
+        }
+
         let mut file = OpenOptions::new()
             .write(true)
             .create_new(true)
@@ -54,7 +101,7 @@ impl SstWriter {
         // Write header (big-endian for magic, little-endian for rest)
         let mut buf = BytesMut::new();
         buf.put_u32(SST_MAGIC); // big-endian for magic
-        buf.put_u32_le(1); // version
+        buf.put_u32_le(SST_VERSION);
         buf.put_u32_le(self.records.len() as u32);
 
         // flags: bit 0 = compression
@@ -63,8 +110,8 @@ impl SstWriter {
 
         // Serialize all records
         let mut data = Vec::new();
-        for record in &self.records {
-            let rec_data = bincode::serialize(record)
+        for entry in &self.records {
+            let rec_data = bincode::serialize(entry)
                 .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
             data.extend_from_slice(&(rec_data.len() as u32).to_le_bytes());
             data.extend_from_slice(&rec_data);
@@ -85,9 +132,17 @@ impl SstWriter {
 
         buf.put_slice(&final_data);
 
+        // Key range footer (Phase 2.1+)
+        let footer = bincode::serialize(&SstKeyRange::from_records(self.records.iter().map(|(record, _)| record)))
+            .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+        buf.put_slice(&footer);
+        buf.put_u32_le(footer.len() as u32);
+
         // Write CRC (of original uncompressed data for consistency)
-        let crc = crc32fast::hash(&data);
-        buf.put_u32_le(crc);
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&data);
+        hasher.update(&footer);
+        buf.put_u32_le(hasher.finalize());
 
         file.write_all(&buf)?;
         file.sync_all()?;
@@ -96,130 +151,528 @@ impl SstWriter {
     }
 }
 
-pub struct SstReader {
-    records: Vec<Record>,
-    path: PathBuf,
-}
+/// Append a record's large binary attributes to the value log (Phase 8+)
+///
+/// Attributes already in `blobs` keep their existing pointers.
+fn externalize_blobs(
+    record: &Record,
+    blobs: &mut BlobRefs,
+    writer: &mut ValueLogWriter,
+    threshold: usize,
+) -> Result<()> {
+    let item = match &record.value {
+        Some(item) => item,
+        None => return Ok(()),
+    };
 
-impl SstReader {
-    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
-        let mut file = File::open(&path)?;
+    // Sorted so the value log layout does not depend on hash order
+    let mut names: Vec<&String> = item.iter()
+        .filter(|(name, value)| {
+            matches!(value, Value::B(data) if data.len() >= threshold)
+                && !blobs.iter().any(|(blob_name, _)| blob_name == *name)
+        })
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+
+    for name in names {
+        if let Some(Value::B(data)) = item.get(name) {
+            blobs.push((name.clone(), writer.append(data)?));
+        }
+    }
+    Ok(())
+}
 
-        // Read header
-        let mut header = [0u8; SST_HEADER_SIZE];
-        file.read_exact(&mut header)?;
+/// Key and sequence number bounds of an SST, stored in its footer (Phase 2.1+)
+///
+/// Keys are bounded by `Key`'s ordering (partition key, then sort key, compared
+/// bytewise), which is the order sort key conditions and pagination use. That
+/// differs from the length-prefixed order records are stored in, so the bounds
+/// are not simply the first and last record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SstKeyRange {
+    /// Smallest key in the file
+    pub min_key: Key,
+    /// Largest key in the file
+    pub max_key: Key,
+    /// Oldest sequence number in the file
+    pub min_seq: SeqNo,
+    /// Newest sequence number in the file
+    pub max_seq: SeqNo,
+}
 
-        let magic = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
-        if magic != SST_MAGIC {
-            return Err(Error::Corruption("Invalid SST magic".to_string()));
+impl SstKeyRange {
+    /// Compute the bounds of a set of records, `None` if there are none
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a Record>) -> Option<Self> {
+        let mut records = records.into_iter();
+        let first = records.next()?;
+        let mut range = Self {
+            min_key: first.key.clone(),
+            max_key: first.key.clone(),
+            min_seq: first.seq,
+            max_seq: first.seq,
+        };
+        for record in records {
+            if record.key < range.min_key {
+                range.min_key = record.key.clone();
+            }
+            if record.key > range.max_key {
+                range.max_key = record.key.clone();
+            }
+            range.min_seq = range.min_seq.min(record.seq);
+            range.max_seq = range.max_seq.max(record.seq);
         }
+        Some(range)
+    }
 
-        let count = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
-        let flags = u32::from_le_bytes([header[12], header[13], header[14], header[15]]);
-        let compressed = (flags & 1) != 0;
+    /// Whether any key in the range can belong to partition `pk`
+    pub fn may_contain_partition(&self, pk: &[u8]) -> bool {
+        self.min_key.pk.as_ref() <= pk && pk <= self.max_key.pk.as_ref()
+    }
 
-        // Read all data
-        let mut file_data = Vec::new();
-        file.read_to_end(&mut file_data)?;
+    /// Whether any key in the range can be greater than `key`
+    pub fn may_contain_after(&self, key: &Key) -> bool {
+        self.max_key > *key
+    }
 
-        // Verify CRC is present (last 4 bytes)
-        if file_data.len() < 4 {
-            return Err(Error::Corruption("SST file too short".to_string()));
+    /// Whether any key in the range can match a partition and sort key condition
+    pub fn may_match(&self, pk: &[u8], sk_condition: Option<&(SortKeyCondition, Bytes, Option<Bytes>)>) -> bool {
+        if !self.may_contain_partition(pk) {
+            return false;
         }
+        let (condition, value, value2) = match sk_condition {
+            Some(sk_condition) => sk_condition,
+            None => return true,
+        };
 
-        let crc_offset = file_data.len() - 4;
-        let expected_crc = u32::from_le_bytes([
-            file_data[crc_offset],
-            file_data[crc_offset + 1],
-            file_data[crc_offset + 2],
-            file_data[crc_offset + 3],
-        ]);
-
-        // Decompress if needed
-        let data = if compressed {
-            use std::io::Read;
-            let mut decoder = zstd::Decoder::new(&file_data[..crc_offset])
-                .map_err(|e| Error::CompressionError(format!("Failed to create decoder: {}", e)))?;
-            let mut decompressed = Vec::new();
-            decoder.read_to_end(&mut decompressed)
-                .map_err(|e| Error::CompressionError(format!("Failed to decompress: {}", e)))?;
-            decompressed
+        // Sort keys of `pk` in this file lie within [lower, upper]; a bound is
+        // only known when `pk` is at that end of the range
+        let lower = if self.min_key.pk.as_ref() == pk {
+            self.min_key.sk.as_ref()
         } else {
-            file_data[..crc_offset].to_vec()
+            None
+        };
+        let upper = if self.max_key.pk.as_ref() == pk {
+            match &self.max_key.sk {
+                Some(sk) => Some(sk),
+                // Every key of `pk` here lacks a sort key, which never matches
+                None => return false,
+            }
+        } else {
+            None
+        };
+        let above_lower = |v: &Bytes, inclusive: bool| {
+            lower.map_or(true, |lower| if inclusive { lower <= v } else { lower < v })
+        };
+        let below_upper = |v: &Bytes, inclusive: bool| {
+            upper.map_or(true, |upper| if inclusive { upper >= v } else { upper > v })
         };
 
-        // Verify CRC (of decompressed data)
-        let actual_crc = crc32fast::hash(&data);
-        if expected_crc != actual_crc {
-            return Err(Error::ChecksumMismatch);
+        match condition {
+            SortKeyCondition::Equal => above_lower(value, true) && below_upper(value, true),
+            SortKeyCondition::LessThan => above_lower(value, false),
+            SortKeyCondition::LessThanOrEqual => above_lower(value, true),
+            SortKeyCondition::GreaterThan => below_upper(value, false),
+            SortKeyCondition::GreaterThanOrEqual => below_upper(value, true),
+            SortKeyCondition::Between => match value2 {
+                Some(value2) => above_lower(value2, true) && below_upper(value, true),
+                None => false,
+            },
+            SortKeyCondition::BeginsWith => {
+                // Keys with the prefix form the interval starting at the prefix
+                let lower_ok = lower.map_or(true, |lower| {
+                    lower <= value || lower.starts_with(value.as_ref())
+                });
+                lower_ok && below_upper(value, true)
+            }
         }
+    }
+}
 
-        // Deserialize records
-        let mut records = Vec::with_capacity(count);
-        let mut offset = 0;
-
-        while offset < data.len() {
-            let len = u32::from_le_bytes([
-                data[offset],
-                data[offset + 1],
-                data[offset + 2],
-                data[offset + 3],
-            ]) as usize;
-            offset += 4;
+/// Reads a whole SST into memory
+///
+/// Value log attributes (version 3+) are read back when the file is opened,
+/// so records are always complete; their pointers are kept for compaction.
+///
+/// A cold SST (Phase 8+) is opened from its stub and fetched from the cold
+/// store on first use; the record accessors fail if that fetch does.
+///
+/// Point lookups are screened by a bloom filter built when the records are
+/// loaded; the filter is not stored in the file (Phase 8+).
+pub struct SstReader {
+    content: OnceLock<SstContent>,
+    cold: Option<ColdSource>,
+    path: PathBuf,
+    key_range: Option<SstKeyRange>,
+    count: usize,
+    size_bytes: u64,
+    value_log_files: BTreeSet<u64>,
+    bloom_stats: BloomStatsAtomic,
+}
 
-            let record: Record = bincode::deserialize(&data[offset..offset + len])
-                .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
-            offset += len;
+/// Records of an SST and their value log pointers
+struct SstContent {
+    records: Vec<Record>,
+    blobs: Vec<BlobRefs>,     // Value log pointers, parallel to `records` (Phase 8+)
+    bloom: BloomFilter,       // Encoded keys of `records` (Phase 8+)
+    by_key: OnceLock<Vec<u32>>, // Positions of `records` in `Key` order, built by the first scan (Phase 8+)
+}
 
-            records.push(record);
-        }
+/// Where a cold SST lives (Phase 8+)
+struct ColdSource {
+    stub: ColdSstStub,
+    tier: Arc<ColdTier>,
+}
 
-        if records.len() != count {
-            return Err(Error::Corruption(format!(
-                "Record count mismatch: expected {}, got {}",
-                count,
-                records.len()
-            )));
-        }
+impl SstReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file_data = std::fs::read(&path)?;
+        let value_log = ValueLog::new(path.as_ref().parent().unwrap_or_else(|| Path::new(".")));
+        let (content, key_range) = parse_sst(&file_data, &value_log)?;
 
+        let value_log_files = content.blobs.iter().flatten().map(|(_, pointer)| pointer.file_id).collect();
         Ok(Self {
-            records,
+            count: content.records.len(),
+            content: OnceLock::from(content),
+            cold: None,
             path: path.as_ref().to_path_buf(),
+            key_range,
+            size_bytes: file_data.len() as u64,
+            value_log_files,
+            bloom_stats: BloomStatsAtomic::default(),
         })
     }
 
+    /// Open a cold SST from its local stub without fetching it (Phase 8+)
+    pub fn open_cold(stub_path: impl AsRef<Path>, tier: Arc<ColdTier>) -> Result<Self> {
+        let stub = ColdSstStub::read(&stub_path)?;
+        Ok(Self {
+            content: OnceLock::new(),
+            path: stub_path.as_ref().to_path_buf(),
+            key_range: stub.key_range.clone(),
+            count: stub.count as usize,
+            size_bytes: stub.size,
+            value_log_files: stub.value_log_files.clone(),
+            bloom_stats: BloomStatsAtomic::default(),
+            cold: Some(ColdSource { stub, tier }),
+        })
+    }
+
+    /// Turn this reader into a cold one after its file was offloaded (Phase 8+)
+    ///
+    /// The cached records are dropped and fetched again on next use.
+    pub fn make_cold(&mut self, stub_path: impl AsRef<Path>, stub: ColdSstStub, tier: Arc<ColdTier>) {
+        self.content = OnceLock::new();
+        self.path = stub_path.as_ref().to_path_buf();
+        self.cold = Some(ColdSource { stub, tier });
+    }
+
+    /// Whether the SST lives in the cold store (Phase 8+)
+    pub fn is_cold(&self) -> bool {
+        self.cold.is_some()
+    }
+
+    /// Whether the records are in memory; always true for local SSTs (Phase 8+)
+    pub fn is_loaded(&self) -> bool {
+        self.content.get().is_some()
+    }
+
+    /// Fetch a cold SST if it is not cached yet (Phase 8+)
+    pub fn load(&self) -> Result<()> {
+        if self.content.get().is_none() {
+            let content = self.fetch()?;
+            let _ = self.content.set(content);
+        }
+        Ok(())
+    }
+
+    fn fetch(&self) -> Result<SstContent> {
+        let cold = self.cold.as_ref().ok_or_else(|| {
+            Error::Internal(format!("SST {} has no records loaded", self.path.display()))
+        })?;
+        let file_data = cold.tier.fetch(&cold.stub)?;
+        let value_log = ValueLog::new(self.path.parent().unwrap_or_else(|| Path::new(".")));
+        let (content, _) = parse_sst(&file_data, &value_log)?;
+        Ok(content)
+    }
+
+    fn content(&self) -> Result<&SstContent> {
+        self.load()?;
+        self.content.get().ok_or_else(|| {
+            Error::Internal(format!("SST {} has no records loaded", self.path.display()))
+        })
+    }
+
+    /// Key and sequence number bounds, `None` for an empty SST (Phase 2.1+)
+    pub fn key_range(&self) -> Option<&SstKeyRange> {
+        self.key_range.as_ref()
+    }
+
+    /// Whether this SST can hold records matching a base table query (Phase 2.1+)
+    pub fn may_match_query(&self, params: &QueryParams) -> bool {
+        self.key_range
+            .as_ref()
+            .map_or(false, |range| range.may_match(&params.pk, params.sk_condition.as_ref()))
+    }
+
+    /// Whether this SST can hold keys past a scan's start key (Phase 2.1+)
+    pub fn may_contain_after(&self, start_key: Option<&Key>) -> bool {
+        match (&self.key_range, start_key) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(range), Some(start)) => range.may_contain_after(start),
+        }
+    }
+
+    /// Whether this SST can hold `key` (Phase 8+)
+    pub fn may_contain_key(&self, key: &Key) -> bool {
+        self.key_range
+            .as_ref()
+            .map_or(false, |range| range.min_key <= *key && *key <= range.max_key)
+    }
+
     /// Get a record by exact key match
-    pub fn get(&self, key: &Key) -> Option<&Record> {
+    pub fn get(&self, key: &Key) -> Result<Option<&Record>> {
+        if !self.may_contain_key(key) {
+            return Ok(None);
+        }
+        let content = self.content()?;
         let key_enc = key.encode();
-        self.records
+        if !content.bloom.contains(&key_enc) {
+            self.bloom_stats.record(false, false);
+            return Ok(None);
+        }
+        let records = &content.records;
+        let found = records
             .binary_search_by(|rec| rec.key.encode().cmp(&key_enc))
             .ok()
-            .map(|idx| &self.records[idx])
+            .map(|idx| &records[idx]);
+        self.bloom_stats.record(true, found.is_some());
+        Ok(found)
+    }
+
+    /// Number of records, including tombstones
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Size of the SST file in bytes, also for cold SSTs (Phase 8+)
+    pub fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    /// How the bloom filter answered point lookups since the SST was opened (Phase 8+)
+    pub fn bloom_stats(&self) -> BloomStats {
+        self.bloom_stats.snapshot()
+    }
+
+    /// Check if the SST holds no records
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
     }
 
     /// Iterate all records
-    pub fn iter(&self) -> impl Iterator<Item = &Record> {
-        self.records.iter()
+    pub fn iter(&self) -> Result<impl Iterator<Item = &Record>> {
+        Ok(self.content()?.records.iter())
+    }
+
+    /// Iterate records after `start` in `Key` order (Phase 8+)
+    ///
+    /// Records are stored in encoded-key order, which is length-prefixed and
+    /// differs from `Key` order; the first call works out the `Key` order
+    /// and keeps it with the records, so later pages seek with a binary
+    /// search.
+    pub fn iter_after<'a>(&'a self, start: Option<&Key>) -> Result<impl Iterator<Item = &'a Record> + 'a> {
+        let content = self.content()?;
+        let records = &content.records;
+        let by_key = content.by_key.get_or_init(|| {
+            let mut order: Vec<u32> = (0..records.len() as u32).collect();
+            order.sort_by(|&a, &b| records[a as usize].key.cmp(&records[b as usize].key));
+            order
+        });
+        let from = start.map_or(0, |start| by_key.partition_point(|&i| records[i as usize].key <= *start));
+        Ok(by_key[from..].iter().map(move |&i| &records[i as usize]))
     }
 
     /// Scan records with key prefix
-    pub fn scan_prefix<'a>(&'a self, pk: &'a Bytes) -> impl Iterator<Item = &'a Record> + 'a {
-        self.records
+    pub fn scan_prefix<'a>(&'a self, pk: &'a Bytes) -> Result<impl Iterator<Item = &'a Record> + 'a> {
+        Ok(self.content()?
+            .records
             .iter()
-            .filter(move |rec| rec.key.pk == *pk)
+            .filter(move |rec| rec.key.pk == *pk))
     }
 
-    /// Get the path to this SST file
+    /// Iterate records of a single partition in sorted order (Phase 2.1+)
+    ///
+    /// Encoded keys are length-prefixed by partition key, so all records for
+    /// a partition are contiguous and can be located with a binary search.
+    pub fn scan_partition<'a>(&'a self, pk: &'a Bytes) -> Result<impl Iterator<Item = &'a Record> + 'a> {
+        let in_range = self.key_range.as_ref().map_or(false, |range| range.may_contain_partition(pk));
+        let records: &[Record] = if in_range {
+            let records = &self.content()?.records;
+            let start_key = Key::new(pk.clone()).encode();
+            let start = records.partition_point(|rec| rec.key.encode() < start_key);
+            &records[start..]
+        } else {
+            &[]
+        };
+        Ok(records
+            .iter()
+            .take_while(move |rec| rec.key.pk == *pk))
+    }
+
+    /// Get the path to this SST file, or to its stub if it is cold
     pub fn path(&self) -> &Path {
         &self.path
     }
 
     /// Scan all records (returns owned records for compaction)
     pub fn scan(&self) -> Result<impl Iterator<Item = Record> + '_> {
-        Ok(self.records.iter().cloned())
+        Ok(self.content()?.records.iter().cloned())
+    }
+
+    /// Scan all records with their value log pointers (Phase 8+)
+    pub fn scan_with_blobs(&self) -> Result<impl Iterator<Item = (Record, BlobRefs)> + '_> {
+        let content = self.content()?;
+        Ok(content.records.iter().cloned().zip(content.blobs.iter().cloned()))
+    }
+
+    /// IDs of the value log files this SST points into (Phase 8+)
+    pub fn value_log_files(&self) -> BTreeSet<u64> {
+        self.value_log_files.clone()
     }
 }
 
+/// Parse a whole SST file, returning its records and key range
+fn parse_sst(file_data: &[u8], value_log: &ValueLog) -> Result<(SstContent, Option<SstKeyRange>)> {
+    if file_data.len() < SST_HEADER_SIZE {
+        return Err(Error::Corruption("SST file too short".to_string()));
+    }
+    let (header, file_data) = file_data.split_at(SST_HEADER_SIZE);
+
+    let magic = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    if magic != SST_MAGIC {
+        return Err(Error::Corruption("Invalid SST magic".to_string()));
+    }
+
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let count = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+    let flags = u32::from_le_bytes([header[12], header[13], header[14], header[15]]);
+    let compressed = (flags & 1) != 0;
+
+    // Verify CRC is present (last 4 bytes)
+    if file_data.len() < 4 {
+        return Err(Error::Corruption("SST file too short".to_string()));
+    }
+
+    let crc_offset = file_data.len() - 4;
+    let expected_crc = u32::from_le_bytes([
+        file_data[crc_offset],
+        file_data[crc_offset + 1],
+        file_data[crc_offset + 2],
+        file_data[crc_offset + 3],
+    ]);
+
+    // Split off the key range footer (version 2+)
+    let (data_end, footer) = if version >= 2 {
+        if crc_offset < 4 {
+            return Err(Error::Corruption("SST footer missing".to_string()));
+        }
+        let len_offset = crc_offset - 4;
+        let footer_len = u32::from_le_bytes([
+            file_data[len_offset],
+            file_data[len_offset + 1],
+            file_data[len_offset + 2],
+            file_data[len_offset + 3],
+        ]) as usize;
+        if footer_len > len_offset {
+            return Err(Error::Corruption("SST footer length out of range".to_string()));
+        }
+        let footer_offset = len_offset - footer_len;
+        (footer_offset, &file_data[footer_offset..len_offset])
+    } else {
+        (crc_offset, &file_data[crc_offset..crc_offset])
+    };
+
+    // Decompress if needed
+    let data = if compressed {
+        let mut decoder = zstd::Decoder::new(&file_data[..data_end])
+            .map_err(|e| Error::CompressionError(format!("Failed to create decoder: {}", e)))?;
+        let mut decompressed = Vec::new();
+        decoder.read_to_end(&mut decompressed)
+            .map_err(|e| Error::CompressionError(format!("Failed to decompress: {}", e)))?;
+        decompressed
+    } else {
+        file_data[..data_end].to_vec()
+    };
+
+    // Verify CRC (of decompressed data and footer)
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&data);
+    hasher.update(footer);
+    let actual_crc = hasher.finalize();
+    if expected_crc != actual_crc {
+        return Err(Error::ChecksumMismatch);
+    }
+
+    // Deserialize records
+    let mut records = Vec::with_capacity(count);
+    let mut blobs = Vec::with_capacity(count);
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let len = u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize;
+        offset += 4;
+
+        let entry = &data[offset..offset + len];
+        let decoded = if version >= 3 {
+            bincode::deserialize::<(Record, BlobRefs)>(entry)
+        } else {
+            bincode::deserialize::<Record>(entry).map(|record| (record, BlobRefs::new()))
+        };
+        let (mut record, record_blobs) = decoded
+            .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
+        offset += len;
+
+        if let Some(item) = &mut record.value {
+            for (name, pointer) in &record_blobs {
+                item.insert(name.clone(), Value::B(value_log.read(pointer)?));
+            }
+        }
+
+        records.push(record);
+        blobs.push(record_blobs);
+    }
+
+    if records.len() != count {
+        return Err(Error::Corruption(format!(
+            "Record count mismatch: expected {}, got {}",
+            count,
+            records.len()
+        )));
+    }
+
+    // Version 1 files predate the footer, so derive the range instead
+    let key_range = if version >= 2 {
+        bincode::deserialize(footer)
+            .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?
+    } else {
+        SstKeyRange::from_records(&records)
+    };
+
+    let mut bloom = BloomFilter::new(records.len().max(1), BLOOM_BITS_PER_KEY);
+    for record in &records {
+        bloom.add(&record.key.encode());
+    }
+
+    Ok((SstContent { records, blobs, bloom, by_key: OnceLock::new() }, key_range))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,15 +699,15 @@ mod tests {
 
         // Read
         let reader = SstReader::open(&path).unwrap();
-        assert_eq!(reader.records.len(), 10);
+        assert_eq!(reader.len(), 10);
 
         // Get specific key
         let key = Key::new(b"key005".to_vec());
-        let rec = reader.get(&key).unwrap();
+        let rec = reader.get(&key).unwrap().unwrap();
         assert_eq!(rec.key, key);
 
         // Iterate
-        let count = reader.iter().count();
+        let count = reader.iter().unwrap().count();
         assert_eq!(count, 10);
     }
 
@@ -276,7 +729,7 @@ mod tests {
 
         // Read - should be sorted
         let reader = SstReader::open(&path).unwrap();
-        let keys: Vec<_> = reader.iter().map(|r| r.key.pk.clone()).collect();
+        let keys: Vec<_> = reader.iter().unwrap().map(|r| r.key.pk.clone()).collect();
 
         let mut sorted_keys = keys.clone();
         sorted_keys.sort();
@@ -310,10 +763,42 @@ mod tests {
 
         let reader = SstReader::open(&path).unwrap();
         let pk = Bytes::from("user#1");
-        let user1_recs: Vec<_> = reader.scan_prefix(&pk).collect();
+        let user1_recs: Vec<_> = reader.scan_prefix(&pk).unwrap().collect();
         assert_eq!(user1_recs.len(), 2);
     }
 
+    #[test]
+    fn test_sst_scan_partition() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("test.sst");
+
+        {
+            let mut writer = SstWriter::new();
+            for pk in ["a", "user#1", "user#2", "user#10"] {
+                for sk in ["x", "y", "z"] {
+                    writer.add(Record::put(
+                        Key::with_sk(pk.as_bytes().to_vec(), sk.as_bytes().to_vec()),
+                        HashMap::new(),
+                        1,
+                    ));
+                }
+            }
+            writer.finish(&path).unwrap();
+        }
+
+        let reader = SstReader::open(&path).unwrap();
+        let pk = Bytes::from("user#1");
+        let recs: Vec<_> = reader.scan_partition(&pk).unwrap().collect();
+        assert_eq!(recs.len(), 3);
+        assert!(recs.iter().all(|r| r.key.pk == pk));
+
+        let sks: Vec<_> = recs.iter().map(|r| r.key.sk.clone().unwrap()).collect();
+        assert_eq!(sks, vec![Bytes::from("x"), Bytes::from("y"), Bytes::from("z")]);
+
+        let missing = Bytes::from("user#3");
+        assert_eq!(reader.scan_partition(&missing).unwrap().count(), 0);
+    }
+
     #[test]
     fn test_sst_compression() {
         let tmp = TempDir::new().unwrap();
@@ -335,12 +820,12 @@ mod tests {
 
         // Read and verify
         let reader = SstReader::open(&path).unwrap();
-        assert_eq!(reader.records.len(), 100);
+        assert_eq!(reader.len(), 100);
 
         // Verify data integrity
         for i in 0..100 {
             let key = Key::new(format!("key{:03}", i).into_bytes());
-            let rec = reader.get(&key).unwrap();
+            let rec = reader.get(&key).unwrap().unwrap();
             assert_eq!(rec.key, key);
             if let Some(item) = &rec.value {
                 assert!(item.contains_key("data"));
@@ -372,4 +857,227 @@ mod tests {
             "Compressed size ({}) should be less than uncompressed size ({})",
             compressed_size, uncompressed_size);
     }
+
+    #[test]
+    fn test_sst_key_range_footer() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("range.sst");
+
+        {
+            let mut writer = SstWriter::with_compression(true, 3);
+            // "b" is stored before "aa" (shorter encoding) but sorts after it
+            for (pk, seq) in [("b", 7), ("aa", 3), ("ab", 9)] {
+                writer.add(Record::put(Key::new(pk.as_bytes().to_vec()), HashMap::new(), seq));
+            }
+            writer.finish(&path).unwrap();
+        }
+
+        let reader = SstReader::open(&path).unwrap();
+        let range = reader.key_range().unwrap();
+        assert_eq!(range.min_key, Key::new(b"aa".to_vec()));
+        assert_eq!(range.max_key, Key::new(b"b".to_vec()));
+        assert_eq!((range.min_seq, range.max_seq), (3, 9));
+
+        assert!(reader.may_contain_after(None));
+        assert!(reader.may_contain_after(Some(&Key::new(b"ab".to_vec()))));
+        assert!(!reader.may_contain_after(Some(&Key::new(b"b".to_vec()))));
+
+        // Empty SSTs have no range and never match
+        let empty_path = tmp.path().join("empty.sst");
+        SstWriter::new().finish(&empty_path).unwrap();
+        let empty = SstReader::open(&empty_path).unwrap();
+        assert!(empty.key_range().is_none());
+        assert!(!empty.may_contain_after(None));
+    }
+
+    #[test]
+    fn test_sst_key_range_pruning() {
+        let range = SstKeyRange {
+            min_key: Key::with_sk(b"user#1".to_vec(), b"b".to_vec()),
+            max_key: Key::with_sk(b"user#1".to_vec(), b"d".to_vec()),
+            min_seq: 1,
+            max_seq: 1,
+        };
+        let may_match = |condition, value: &str, value2: Option<&str>| {
+            let sk_condition = (condition, Bytes::from(value.to_string()), value2.map(|v| Bytes::from(v.to_string())));
+            range.may_match(b"user#1", Some(&sk_condition))
+        };
+
+        assert!(range.may_match(b"user#1", None));
+        assert!(!range.may_match(b"user#0", None));
+        assert!(!range.may_match(b"user#2", None));
+
+        assert!(may_match(SortKeyCondition::Equal, "c", None));
+        assert!(!may_match(SortKeyCondition::Equal, "a", None));
+        assert!(!may_match(SortKeyCondition::LessThan, "b", None));
+        assert!(may_match(SortKeyCondition::LessThanOrEqual, "b", None));
+        assert!(!may_match(SortKeyCondition::GreaterThan, "d", None));
+        assert!(may_match(SortKeyCondition::GreaterThanOrEqual, "d", None));
+        assert!(may_match(SortKeyCondition::Between, "a", Some("b")));
+        assert!(!may_match(SortKeyCondition::Between, "e", Some("f")));
+        assert!(may_match(SortKeyCondition::BeginsWith, "c", None));
+        assert!(may_match(SortKeyCondition::BeginsWith, "", None));
+        assert!(!may_match(SortKeyCondition::BeginsWith, "e", None));
+        assert!(!may_match(SortKeyCondition::BeginsWith, "a", None));
+
+        // A partition strictly inside the range has no known sort key bounds
+        let wide = SstKeyRange {
+            min_key: Key::with_sk(b"a".to_vec(), b"z".to_vec()),
+            max_key: Key::with_sk(b"c".to_vec(), b"a".to_vec()),
+            min_seq: 1,
+            max_seq: 1,
+        };
+        let sk_condition = (SortKeyCondition::Equal, Bytes::from("q"), None);
+        assert!(wide.may_match(b"b", Some(&sk_condition)));
+        assert!(!wide.may_match(b"a", Some(&sk_condition)));
+        assert!(!wide.may_match(b"c", Some(&sk_condition)));
+    }
+
+    #[test]
+    fn test_sst_value_log() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("000-1.sst");
+        let vlog = ValueLog::new(tmp.path());
+
+        let mut item = HashMap::new();
+        item.insert("blob".to_string(), Value::B(Bytes::from(vec![7u8; 4096])));
+        item.insert("small".to_string(), Value::B(Bytes::from_static(b"tiny")));
+        item.insert("name".to_string(), Value::string("x"));
+
+        let mut writer = SstWriter::new().with_value_log(vlog.writer(0, 1), 1024);
+        writer.add(Record::put(Key::new(b"key1".to_vec()), item.clone(), 1));
+        writer.finish(&path).unwrap();
+
+        // Only the large binary attribute moved out of the SST
+        assert!(std::fs::metadata(&path).unwrap().len() < 1024);
+        assert_eq!(vlog.size_bytes().unwrap(), 4096);
+
+        let reader = SstReader::open(&path).unwrap();
+        let rec = reader.get(&Key::new(b"key1".to_vec())).unwrap().unwrap();
+        assert_eq!(rec.value.as_ref(), Some(&item));
+        assert_eq!(reader.value_log_files(), BTreeSet::from([1]));
+
+        // Carried-over pointers are not written again
+        let copy_path = tmp.path().join("000-2.sst");
+        let mut writer = SstWriter::new().with_value_log(vlog.writer(0, 2), 1024);
+        for (record, blobs) in reader.scan_with_blobs().unwrap() {
+            writer.add_with_blobs(record, blobs);
+        }
+        writer.finish(&copy_path).unwrap();
+        assert_eq!(vlog.size_bytes().unwrap(), 4096);
+
+        let copy = SstReader::open(&copy_path).unwrap();
+        assert_eq!(copy.get(&Key::new(b"key1".to_vec())).unwrap().unwrap().value.as_ref(), Some(&item));
+        assert_eq!(copy.value_log_files(), BTreeSet::from([1]));
+    }
+
+    #[test]
+    fn test_sst_bloom_stats() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("test.sst");
+
+        // Even keys only, so odd keys fall inside the key range but are absent
+        let mut writer = SstWriter::new();
+        for i in (0..200).step_by(2) {
+            writer.add(Record::put(Key::new(format!("key{:03}", i).into_bytes()), HashMap::new(), i));
+        }
+        writer.finish(&path).unwrap();
+
+        let reader = SstReader::open(&path).unwrap();
+        assert_eq!(reader.size_bytes(), std::fs::metadata(&path).unwrap().len());
+
+        for i in 0..200 {
+            let found = reader.get(&Key::new(format!("key{:03}", i).into_bytes())).unwrap().is_some();
+            assert_eq!(found, i % 2 == 0);
+        }
+        // Out of range: answered by the key range without probing the filter
+        assert!(reader.get(&Key::new(b"zzz".to_vec())).unwrap().is_none());
+
+        // key199 sorts after key198, the last key, so it is out of range too
+        let stats = reader.bloom_stats();
+        assert_eq!(stats.checks, 199);
+        assert_eq!(stats.negatives + stats.false_positives, 99);
+        assert!(stats.false_positive_rate().unwrap() < 0.2);
+    }
+
+    #[test]
+    fn test_sst_open_cold() {
+        use crate::tiering::{ColdStore, FsColdStore};
+
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("000-1.sst");
+        {
+            let mut writer = SstWriter::new();
+            for i in 0..10 {
+                writer.add(Record::put(Key::new(format!("key{:03}", i).into_bytes()), HashMap::new(), i));
+            }
+            writer.finish(&path).unwrap();
+        }
+
+        let local = SstReader::open(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        let stub = ColdSstStub {
+            object_key: "000-1.sst".to_string(),
+            size: data.len() as u64,
+            crc: crc32c::crc32c(&data),
+            count: local.len() as u64,
+            key_range: local.key_range().cloned(),
+            value_log_files: local.value_log_files(),
+        };
+        let store = Arc::new(FsColdStore::new(tmp.path().join("cold")).unwrap());
+        store.put(&stub.object_key, Bytes::from(data)).unwrap();
+        let stub_path = tmp.path().join("000-1.cold");
+        stub.write(&stub_path).unwrap();
+
+        let tier = Arc::new(ColdTier::new());
+        tier.set_store(Some(store));
+        let cold = SstReader::open_cold(&stub_path, tier.clone()).unwrap();
+        assert!(cold.is_cold());
+        assert!(!cold.is_loaded());
+        assert_eq!(cold.len(), 10);
+
+        // Keys outside the range are answered from the stub
+        assert!(cold.get(&Key::new(b"zzz".to_vec())).unwrap().is_none());
+        assert_eq!(cold.scan_partition(&Bytes::from("aaa")).unwrap().count(), 0);
+        assert_eq!(tier.stats().fetched_ssts, 0);
+
+        cold.load().unwrap();
+        assert!(cold.is_loaded());
+        assert!(cold.get(&Key::new(b"key005".to_vec())).unwrap().is_some());
+        assert_eq!(cold.iter().unwrap().count(), 10);
+        assert_eq!(tier.stats().fetched_ssts, 1);
+
+        // Without a store, reads that need the records fail
+        let detached = SstReader::open_cold(&stub_path, Arc::new(ColdTier::new())).unwrap();
+        assert!(detached.get(&Key::new(b"key005".to_vec())).is_err());
+        assert!(detached.iter().is_err());
+    }
+
+    #[test]
+    fn test_sst_reads_version_1() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("v1.sst");
+
+        // Hand-build a version 1 file, which has no footer
+        let record = Record::put(Key::new(b"key1".to_vec()), HashMap::new(), 5);
+        let rec_data = bincode::serialize(&record).unwrap();
+        let mut data = Vec::new();
+        data.extend_from_slice(&(rec_data.len() as u32).to_le_bytes());
+        data.extend_from_slice(&rec_data);
+
+        let mut buf = BytesMut::new();
+        buf.put_u32(SST_MAGIC);
+        buf.put_u32_le(1);
+        buf.put_u32_le(1);
+        buf.put_u32_le(0);
+        buf.put_slice(&data);
+        buf.put_u32_le(crc32fast::hash(&data));
+        std::fs::write(&path, &buf).unwrap();
+
+        let reader = SstReader::open(&path).unwrap();
+        assert!(reader.get(&Key::new(b"key1".to_vec())).unwrap().is_some());
+        let range = reader.key_range().unwrap();
+        assert_eq!(range.min_key, Key::new(b"key1".to_vec()));
+        assert_eq!(range.max_seq, 5);
+    }
 }
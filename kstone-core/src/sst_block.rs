@@ -7,13 +7,16 @@
 /// - Footer with metadata
 ///
 /// Each data block contains sorted records with prefix compression.
-/// Bloom filters reduce unnecessary block reads.
+/// Bloom filters reduce unnecessary block reads, and an optional shared
+/// block cache keeps hot decoded blocks in memory.
 
 use bytes::{Bytes, BytesMut, BufMut, Buf};
 use std::collections::BTreeMap;
 use std::fs::File;
+use std::sync::Arc;
 use crate::{
     Error, Result, Record, Key,
+    block_cache::{BlockCache, CachedBlock},
     layout::BLOCK_SIZE,
     block::{Block, BlockWriter, BlockReader},
     bloom::BloomFilter,
@@ -291,6 +294,7 @@ pub struct SstBlockReader {
     index: BTreeMap<Bytes, u64>, // key -> block offset
     blooms: Vec<BloomFilter>,
     compressed: bool,
+    cache: Option<(Arc<BlockCache>, u64)>, // shared cache and this SST's id in it
 }
 
 impl SstBlockReader {
@@ -310,9 +314,18 @@ impl SstBlockReader {
             index,
             blooms,
             compressed: handle.compressed,
+            cache: None,
         })
     }
 
+    /// Open a reader whose data blocks are served through a shared block cache
+    pub fn open_with_cache(file: File, handle: SstBlockHandle, cache: Arc<BlockCache>) -> Result<Self> {
+        let mut reader = Self::open(file, handle)?;
+        let sst_id = cache.next_sst_id();
+        reader.cache = Some((cache, sst_id));
+        Ok(reader)
+    }
+
     pub fn get(&self, key: &Key) -> Result<Option<Record>> {
         let key_enc = key.encode();
 
@@ -327,19 +340,30 @@ impl SstBlockReader {
             }
 
             // Read and search block
-            let mut reader = BlockReader::new(self.file.try_clone()?);
-            let block = reader.read(block_idx as u64, offset)?;
+            let records = self.load_data_block(block_idx, offset)?;
+            return Ok(records.iter().find(|record| record.key == *key).cloned());
+        }
 
-            let records = self.decode_data_block(&block.data)?;
+        Ok(None)
+    }
 
-            for record in records {
-                if record.key == *key {
-                    return Ok(Some(record));
-                }
+    /// Load a decoded data block, going through the block cache if there is one
+    fn load_data_block(&self, block_idx: usize, offset: u64) -> Result<CachedBlock> {
+        if let Some((cache, sst_id)) = &self.cache {
+            if let Some(records) = cache.get(*sst_id, offset) {
+                return Ok(records);
             }
         }
 
-        Ok(None)
+        let mut reader = BlockReader::new(self.file.try_clone()?);
+        let block = reader.read(block_idx as u64, offset)?;
+        let records = Arc::new(self.decode_data_block(&block.data)?);
+
+        if let Some((cache, sst_id)) = &self.cache {
+            cache.insert(*sst_id, offset, Arc::clone(&records), BLOCK_SIZE);
+        }
+
+        Ok(records)
     }
 
     fn find_block(&self, key: &Bytes) -> Result<Option<u64>> {
@@ -458,4 +482,33 @@ mod tests {
         let rec = reader.get(&key).unwrap().unwrap();
         assert_eq!(rec.key, key);
     }
+
+    #[test]
+    fn test_sst_block_reads_through_cache() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut file = tmp.reopen().unwrap();
+
+        let allocator = ExtentAllocator::new(0);
+        let mut writer = SstBlockWriter::new();
+
+        for i in 0..10 {
+            let key = Key::new(format!("key{:03}", i).into_bytes());
+            let mut item = HashMap::new();
+            item.insert("value".to_string(), Value::number(i));
+            writer.add(Record::put(key, item, i));
+        }
+
+        let handle = writer.finish(&mut file, &allocator).unwrap();
+
+        let cache = Arc::new(BlockCache::new(1024 * 1024));
+        let reader = SstBlockReader::open_with_cache(tmp.reopen().unwrap(), handle, Arc::clone(&cache)).unwrap();
+
+        let key = Key::new(b"key005".to_vec());
+        assert_eq!(reader.get(&key).unwrap().unwrap().key, key);
+        assert_eq!(reader.get(&key).unwrap().unwrap().key, key);
+
+        let stats = cache.stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+    }
 }
@@ -0,0 +1,167 @@
+/// Pluggable storage for in-memory databases (Phase 8+)
+///
+/// An in-memory database is persisted through a `Storage`:
+/// `MemoryLsmEngine::save_to` writes a snapshot object and
+/// `MemoryLsmEngine::load_from` restores it. `MemoryStorage` keeps objects
+/// in memory and `FsStorage` in a directory; hosts without a file system
+/// can implement the trait over what they have (a KV namespace, say).
+
+use crate::{Error, Result};
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+/// Named objects a database can be saved to
+pub trait Storage: Send + Sync {
+    /// Read a whole object, or None if it doesn't exist
+    fn read(&self, name: &str) -> Result<Option<Bytes>>;
+
+    /// Write an object, replacing any existing object with the same name
+    fn write(&self, name: &str, data: Bytes) -> Result<()>;
+
+    /// Delete an object; deleting a missing object is not an error
+    fn delete(&self, name: &str) -> Result<()>;
+
+    /// Names of all objects, sorted
+    fn list(&self) -> Result<Vec<String>>;
+}
+
+/// Storage holding objects in memory, for tests and short-lived hosts
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    objects: Mutex<BTreeMap<String, Bytes>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, name: &str) -> Result<Option<Bytes>> {
+        Ok(self.objects.lock().get(name).cloned())
+    }
+
+    fn write(&self, name: &str, data: Bytes) -> Result<()> {
+        validate_name(name)?;
+        self.objects.lock().insert(name.to_string(), data);
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        self.objects.lock().remove(name);
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.objects.lock().keys().cloned().collect())
+    }
+}
+
+/// Storage keeping objects as files in a directory
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FsStorage {
+    /// Store objects as files in `dir`, which is created if needed
+    pub fn new(dir: impl AsRef<std::path::Path>) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FsStorage {
+    fn read(&self, name: &str) -> Result<Option<Bytes>> {
+        validate_name(name)?;
+        match std::fs::read(self.dir.join(name)) {
+            Ok(data) => Ok(Some(Bytes::from(data))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(&self, name: &str, data: Bytes) -> Result<()> {
+        use std::io::Write;
+
+        validate_name(name)?;
+        let path = self.dir.join(name);
+        let tmp = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        match std::fs::remove_file(self.dir.join(name)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                if let Some(name) = entry.file_name().to_str() {
+                    if !name.ends_with(".tmp") {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Names are single path components, so every backend can store them
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name.starts_with('.') {
+        return Err(Error::InvalidArgument(format!("Invalid storage object name: {}", name)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn exercise(storage: &dyn Storage) {
+        assert_eq!(storage.read("snapshot").unwrap(), None);
+        storage.write("snapshot", Bytes::from("v1")).unwrap();
+        storage.write("snapshot", Bytes::from("v2")).unwrap();
+        storage.write("other", Bytes::from("x")).unwrap();
+        assert_eq!(storage.read("snapshot").unwrap(), Some(Bytes::from("v2")));
+        assert_eq!(storage.list().unwrap(), vec!["other".to_string(), "snapshot".to_string()]);
+
+        storage.delete("snapshot").unwrap();
+        storage.delete("snapshot").unwrap();
+        assert_eq!(storage.list().unwrap(), vec!["other".to_string()]);
+        assert!(storage.write("../escape", Bytes::new()).is_err());
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        exercise(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn test_fs_storage() {
+        let dir = TempDir::new().unwrap();
+        exercise(&FsStorage::new(dir.path()).unwrap());
+    }
+}
@@ -0,0 +1,228 @@
+/// Backend-neutral database access (Phase 6+)
+///
+/// `KeystoneStore` covers the operations an embedded database
+/// (`kstone_api::Database`) and a remote one (`kstone_client::Client`) have
+/// in common, so application code can be written once and run against
+/// either. The request and result types here are the common denominator of
+/// both APIs; backend-specific features (filters, projections, streams,
+/// indexes on scans) stay on the concrete types.
+
+use crate::{Item, Key, Value};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::collections::HashMap;
+
+/// Condition on the sort key of a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkCondition {
+    Equal(Bytes),
+    LessThan(Bytes),
+    LessThanOrEqual(Bytes),
+    GreaterThan(Bytes),
+    GreaterThanOrEqual(Bytes),
+    Between(Bytes, Bytes),
+    BeginsWith(Bytes),
+}
+
+/// Query of one partition
+#[derive(Debug, Clone)]
+pub struct StoreQuery {
+    pub pk: Bytes,
+    pub sk_condition: Option<SkCondition>,
+    /// Query this index instead of the base table
+    pub index: Option<String>,
+    /// Ascending sort key order (default true)
+    pub forward: bool,
+    pub limit: Option<usize>,
+    /// Resume after this key (the previous page's `last_key`)
+    pub start_after: Option<Key>,
+}
+
+impl StoreQuery {
+    /// Query partition `pk`
+    pub fn new(pk: impl AsRef<[u8]>) -> Self {
+        Self {
+            pk: Bytes::copy_from_slice(pk.as_ref()),
+            sk_condition: None,
+            index: None,
+            forward: true,
+            limit: None,
+            start_after: None,
+        }
+    }
+
+    /// Only return items whose sort key matches `condition`
+    pub fn sk(mut self, condition: SkCondition) -> Self {
+        self.sk_condition = Some(condition);
+        self
+    }
+
+    /// Query index `name`
+    pub fn index(mut self, name: impl Into<String>) -> Self {
+        self.index = Some(name.into());
+        self
+    }
+
+    /// Set the sort key order
+    pub fn forward(mut self, forward: bool) -> Self {
+        self.forward = forward;
+        self
+    }
+
+    /// Return at most `limit` items
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Resume after `key`
+    pub fn start_after(mut self, key: Key) -> Self {
+        self.start_after = Some(key);
+        self
+    }
+}
+
+/// Scan of the whole table
+#[derive(Debug, Clone, Default)]
+pub struct StoreScan {
+    pub limit: Option<usize>,
+    /// Resume after this key (the previous page's `last_key`)
+    pub start_after: Option<Key>,
+    /// Scan only segment `.0` of `.1` (parallel scan)
+    pub segment: Option<(usize, usize)>,
+}
+
+impl StoreScan {
+    /// Scan every item
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return at most `limit` items
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Resume after `key`
+    pub fn start_after(mut self, key: Key) -> Self {
+        self.start_after = Some(key);
+        self
+    }
+
+    /// Scan only `segment` of `total_segments`
+    pub fn segment(mut self, segment: usize, total_segments: usize) -> Self {
+        self.segment = Some((segment, total_segments));
+        self
+    }
+}
+
+/// One page of query or scan results
+#[derive(Debug, Clone, Default)]
+pub struct StorePage {
+    pub items: Vec<Item>,
+    /// Number of items examined
+    pub scanned_count: usize,
+    /// Key to resume after, `None` on the last page
+    pub last_key: Option<Key>,
+}
+
+/// Update of one item by update expression
+#[derive(Debug, Clone)]
+pub struct StoreUpdate {
+    pub key: Key,
+    pub expression: String,
+    pub condition: Option<String>,
+    /// Values of the `:placeholders` in the expression and condition
+    pub values: HashMap<String, Value>,
+}
+
+impl StoreUpdate {
+    /// Apply `expression` (e.g. "SET age = age + :inc") to the item at `key`
+    pub fn new(key: Key, expression: impl Into<String>) -> Self {
+        Self {
+            key,
+            expression: expression.into(),
+            condition: None,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Only update if `condition` holds
+    pub fn condition(mut self, condition: impl Into<String>) -> Self {
+        self.condition = Some(condition.into());
+        self
+    }
+
+    /// Bind `placeholder` (e.g. ":inc") to `value`
+    pub fn value(mut self, placeholder: impl Into<String>, value: Value) -> Self {
+        self.values.insert(placeholder.into(), value);
+        self
+    }
+}
+
+/// One operation of an atomic write
+#[derive(Debug, Clone)]
+pub enum StoreWrite {
+    Put { key: Key, item: Item, condition: Option<String> },
+    /// Expressions can't bind values in a transaction; use literals
+    Update { key: Key, expression: String, condition: Option<String> },
+    Delete { key: Key, condition: Option<String> },
+    /// Fail the transaction unless `condition` holds, without writing
+    ConditionCheck { key: Key, condition: String },
+}
+
+/// Result of a PartiQL statement
+#[derive(Debug, Clone)]
+pub enum StatementResult {
+    Select {
+        items: Vec<Item>,
+        scanned_count: usize,
+        last_key: Option<Key>,
+    },
+    Insert { success: bool },
+    Update { item: Item },
+    Delete { success: bool },
+}
+
+/// Operations shared by embedded and remote databases
+///
+/// Methods take `&mut self` because a remote client needs it; an embedded
+/// database doesn't mutate itself.
+#[async_trait]
+pub trait KeystoneStore: Send {
+    /// The backend's error type
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Store `item` at `key`, replacing any existing item
+    async fn put(&mut self, key: Key, item: Item) -> Result<(), Self::Error>;
+
+    /// The item at `key`, if any
+    async fn get(&mut self, key: Key) -> Result<Option<Item>, Self::Error>;
+
+    /// Remove the item at `key`
+    async fn delete(&mut self, key: Key) -> Result<(), Self::Error>;
+
+    /// One page of a partition's items
+    async fn query(&mut self, query: StoreQuery) -> Result<StorePage, Self::Error>;
+
+    /// One page of the table's items
+    async fn scan(&mut self, scan: StoreScan) -> Result<StorePage, Self::Error>;
+
+    /// Apply an update expression, returning the updated item
+    async fn update(&mut self, update: StoreUpdate) -> Result<Item, Self::Error>;
+
+    /// Read `keys` atomically, in order
+    async fn transact_get(&mut self, keys: Vec<Key>) -> Result<Vec<Option<Item>>, Self::Error>;
+
+    /// Apply `writes` atomically: all of them or none
+    async fn transact_write(&mut self, writes: Vec<StoreWrite>) -> Result<(), Self::Error>;
+
+    /// Run a PartiQL statement
+    async fn execute_statement(&mut self, statement: &str) -> Result<StatementResult, Self::Error>;
+}
+
+/// Convert a `(pk, sk)` pagination key to a `Key`
+pub fn last_key_from_parts(last_key: Option<(Bytes, Option<Bytes>)>) -> Option<Key> {
+    last_key.map(|(pk, sk)| Key { pk, sk })
+}
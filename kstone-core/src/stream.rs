@@ -123,9 +123,19 @@ pub struct StreamConfig {
     pub enabled: bool,
     /// What data to include in stream records
     pub view_type: StreamViewType,
-    /// Maximum number of records to retain in the stream buffer
-    /// (oldest records are dropped when buffer is full)
+    /// Maximum number of records to retain in the stream
+    /// (oldest records are trimmed first)
     pub buffer_size: usize,
+    /// How long records are retained in milliseconds (None = no time limit)
+    #[serde(default = "default_retention_period_ms")]
+    pub retention_period_ms: Option<u64>,
+}
+
+/// Default stream retention: 24 hours, matching DynamoDB Streams
+pub const DEFAULT_RETENTION_PERIOD_MS: u64 = 24 * 60 * 60 * 1000;
+
+fn default_retention_period_ms() -> Option<u64> {
+    Some(DEFAULT_RETENTION_PERIOD_MS)
 }
 
 impl Default for StreamConfig {
@@ -134,6 +144,7 @@ impl Default for StreamConfig {
             enabled: false,
             view_type: StreamViewType::NewAndOldImages,
             buffer_size: 1000,
+            retention_period_ms: default_retention_period_ms(),
         }
     }
 }
@@ -158,10 +169,97 @@ impl StreamConfig {
         self.buffer_size = size;
         self
     }
+
+    /// Set how long records are retained before being trimmed
+    pub fn with_retention_period(mut self, period: std::time::Duration) -> Self {
+        self.retention_period_ms = Some(period.as_millis() as u64);
+        self
+    }
+
+    /// Retain records regardless of age (only `buffer_size` trims the stream)
+    pub fn without_retention_period(mut self) -> Self {
+        self.retention_period_ms = None;
+        self
+    }
+}
+
+/// Where a shard iterator starts reading the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardIteratorType {
+    /// Start at the oldest record still retained in the stream
+    TrimHorizon,
+    /// Start after the most recent record (only new records are returned)
+    Latest,
+    /// Start at the record with this sequence number
+    AtSequenceNumber(u64),
+    /// Start at the first record after this sequence number
+    AfterSequenceNumber(u64),
+}
+
+/// A position in the stream, handed out by `get_shard_iterator` and `get_records`
+///
+/// Iterators are plain values: a consumer can persist `sequence_number()` (or
+/// `to_token()`) and resume from it after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShardIterator {
+    /// Sequence number of the next record to read
+    position: u64,
+}
+
+impl ShardIterator {
+    pub(crate) fn new(position: u64) -> Self {
+        Self { position }
+    }
+
+    /// Sequence number of the next record this iterator will return
+    pub fn sequence_number(&self) -> u64 {
+        self.position
+    }
+
+    /// Encode the iterator as an opaque string token
+    pub fn to_token(&self) -> String {
+        format!("{:016x}", self.position)
+    }
+
+    /// Decode an iterator previously produced by `to_token`
+    pub fn from_token(token: &str) -> crate::Result<Self> {
+        u64::from_str_radix(token, 16)
+            .map(Self::new)
+            .map_err(|_| crate::Error::InvalidArgument(format!("Invalid shard iterator: {}", token)))
+    }
+}
+
+/// Result of reading records from a shard iterator
+#[derive(Debug, Clone)]
+pub struct GetRecordsResult {
+    /// Records in sequence number order
+    pub records: Vec<StreamRecord>,
+    /// Iterator to pass to the next `get_records` call
+    pub next_shard_iterator: ShardIterator,
+}
+
+/// Stream retention and subscription statistics (Phase 8+)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Records currently retained
+    pub retained_records: u64,
+
+    /// Sequence number of the newest record (0 if none was written)
+    pub last_sequence_number: u64,
+
+    /// Age of the oldest retained record in milliseconds (None if empty)
+    pub oldest_record_age_ms: Option<u64>,
+
+    /// Live push subscriptions
+    pub subscriptions: u64,
+
+    /// Retained records the furthest-behind subscription has not yet
+    /// dispatched; its channel may buffer up to its capacity more
+    pub max_subscriber_lag: u64,
 }
 
 /// Get current timestamp in milliseconds since epoch
-fn current_timestamp_millis() -> i64 {
+pub(crate) fn current_timestamp_millis() -> i64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
@@ -185,6 +283,32 @@ mod tests {
         assert_eq!(config.enabled, false);
         assert_eq!(config.view_type, StreamViewType::NewAndOldImages);
         assert_eq!(config.buffer_size, 1000);
+        assert_eq!(config.retention_period_ms, Some(DEFAULT_RETENTION_PERIOD_MS));
+    }
+
+    #[test]
+    fn test_stream_config_retention() {
+        let config = StreamConfig::enabled()
+            .with_retention_period(std::time::Duration::from_secs(60));
+        assert_eq!(config.retention_period_ms, Some(60_000));
+
+        let config = config.without_retention_period();
+        assert_eq!(config.retention_period_ms, None);
+    }
+
+    #[test]
+    fn test_stream_config_deserialize_without_retention() {
+        let json = r#"{"enabled":true,"view_type":"KeysOnly","buffer_size":10}"#;
+        let config: StreamConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.retention_period_ms, Some(DEFAULT_RETENTION_PERIOD_MS));
+    }
+
+    #[test]
+    fn test_shard_iterator_token_roundtrip() {
+        let iterator = ShardIterator::new(42);
+        let token = iterator.to_token();
+        assert_eq!(ShardIterator::from_token(&token).unwrap(), iterator);
+        assert!(ShardIterator::from_token("not-a-token").is_err());
     }
 
     #[test]
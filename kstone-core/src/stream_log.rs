@@ -0,0 +1,735 @@
+/// Durable stream log (Phase 3.4+)
+///
+/// Stream records are appended to segment files under `<db>/streams/` so that
+/// change data capture survives restarts. Retained records are also kept in
+/// memory for reads; a segment file is deleted once every record in it has
+/// been trimmed by the stream's retention settings.
+///
+/// Segment file: `{segment_id:010}.seg`
+/// Record: [len(4) | bincode(StreamRecord) | crc(4)]
+///
+/// The highest trimmed sequence number is persisted in `TRIM_HORIZON` whenever
+/// segments are deleted, so iterators that point at trimmed data are detected
+/// after a restart.
+///
+/// Appends hand records to the OS; `StreamSync` makes them durable under the
+/// WAL's sync mode (Phase 8+).
+
+use crate::config::WalSyncMode;
+use crate::lsm::STREAMS_DIR;
+use crate::stream::{GetRecordsResult, ShardIterator, ShardIteratorType, StreamConfig, StreamRecord};
+use crate::{Error, Result};
+use bytes::{BufMut, BytesMut};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SEGMENT_EXTENSION: &str = "seg";
+const SEGMENT_MAX_RECORDS: usize = 1024;
+const TRIM_HORIZON_FILE: &str = "TRIM_HORIZON";
+
+struct Segment {
+    path: PathBuf,
+    last_sequence_number: u64,
+    count: usize,
+}
+
+/// Append-only, segmented log of stream records
+pub struct StreamLog {
+    dir: PathBuf,
+    records: VecDeque<StreamRecord>,
+    segments: Vec<Segment>, // Oldest first; the last one receives appends
+    active: Option<File>,
+    next_segment_id: u64,
+    segment_max_records: usize,
+    trimmed_through: u64, // Highest trimmed sequence number (0 = nothing trimmed)
+    sync: StreamSync,     // Shared with the engine, which syncs outside the log's lock (Phase 8+)
+}
+
+/// Makes appended stream records durable (Phase 8+)
+///
+/// The engine syncs after a write has released its locks, with the WAL's
+/// sync mode, so stream records are exactly as durable as the WAL records
+/// written with them. As with the WAL's group commit, one fsync covers
+/// every record appended before it started and later callers skip theirs.
+/// Cloned handles share the same state.
+#[derive(Clone)]
+pub struct StreamSync {
+    active: Arc<Mutex<ActiveSegment>>,
+    synced: Arc<Mutex<SyncedState>>,
+}
+
+/// The segment receiving appends and how many records it was handed
+struct ActiveSegment {
+    file: Option<File>,
+    appended: u64,
+}
+
+struct SyncedState {
+    appended: u64, // Appends covered by the last fsync
+    last_sync: Instant,
+}
+
+impl StreamSync {
+    fn new() -> Self {
+        Self {
+            active: Arc::new(Mutex::new(ActiveSegment { file: None, appended: 0 })),
+            synced: Arc::new(Mutex::new(SyncedState { appended: 0, last_sync: Instant::now() })),
+        }
+    }
+
+    /// Make appended records durable as the sync mode requires
+    pub fn sync_to(&self, mode: WalSyncMode) -> Result<()> {
+        match mode {
+            WalSyncMode::Always => self.sync(),
+            WalSyncMode::EveryNms(interval_ms) => {
+                if self.synced.lock().last_sync.elapsed() >= Duration::from_millis(interval_ms) {
+                    self.sync()
+                } else {
+                    Ok(())
+                }
+            }
+            WalSyncMode::Os => Ok(()),
+        }
+    }
+
+    /// Fsync every record appended so far, unless an earlier fsync did
+    pub fn sync(&self) -> Result<()> {
+        let mut synced = self.synced.lock();
+        let (file, target) = {
+            let active = self.active.lock();
+            match &active.file {
+                Some(file) if active.appended > synced.appended => (file.try_clone()?, active.appended),
+                _ => return Ok(()),
+            }
+        };
+        file.sync_data()?;
+
+        synced.appended = target;
+        synced.last_sync = Instant::now();
+        Ok(())
+    }
+
+    fn set_file(&self, file: File) {
+        self.active.lock().file = Some(file);
+    }
+
+    fn record_append(&self) {
+        self.active.lock().appended += 1;
+    }
+}
+
+impl StreamLog {
+    /// Open the stream log in `dir`, loading retained records
+    ///
+    /// The directory is created lazily on the first append.
+    pub fn open(dir: impl AsRef<Path>, config: &StreamConfig) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let mut log = Self {
+            dir,
+            records: VecDeque::new(),
+            segments: Vec::new(),
+            active: None,
+            next_segment_id: 1,
+            segment_max_records: SEGMENT_MAX_RECORDS,
+            trimmed_through: 0,
+            sync: StreamSync::new(),
+        };
+
+        if !log.dir.exists() {
+            return Ok(log);
+        }
+
+        let horizon_path = log.dir.join(TRIM_HORIZON_FILE);
+        if horizon_path.exists() {
+            let content = fs::read_to_string(&horizon_path)?;
+            log.trimmed_through = content.trim().parse().map_err(|_| {
+                Error::Corruption(format!("Invalid stream trim horizon: {}", content.trim()))
+            })?;
+        }
+
+        for id in segment_ids(&log.dir)? {
+            let path = segment_path(&log.dir, id);
+            let (records, valid_len) = read_segment(&path)?;
+
+            // Cut off a torn tail so new appends land after the last intact record
+            let file = OpenOptions::new().write(true).open(&path)?;
+            if file.metadata()?.len() > valid_len {
+                file.set_len(valid_len)?;
+                file.sync_all()?;
+            }
+            let last_sequence_number = records.last().map_or(0, |r| r.sequence_number);
+
+            log.segments.push(Segment {
+                path,
+                last_sequence_number,
+                count: records.len(),
+            });
+            log.next_segment_id = id + 1;
+
+            let trimmed_through = log.trimmed_through;
+            log.records.extend(records.into_iter().filter(|r| r.sequence_number > trimmed_through));
+        }
+
+        log.trim(config, current_timestamp_millis())?;
+        Ok(log)
+    }
+
+    /// Append a record, then apply retention
+    ///
+    /// The record reaches disk when the log's `StreamSync` is synced.
+    pub fn append(&mut self, record: StreamRecord, config: &StreamConfig) -> Result<()> {
+        let data = bincode::serialize(&record)
+            .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+
+        let mut buf = BytesMut::with_capacity(data.len() + 8);
+        buf.put_u32_le(data.len() as u32);
+        buf.put_slice(&data);
+        buf.put_u32_le(crc32fast::hash(&data));
+
+        let file = self.active_segment()?;
+        file.write_all(&buf)?;
+        self.sync.record_append();
+
+        let segment = self.segments.last_mut().expect("active segment exists");
+        segment.last_sequence_number = record.sequence_number;
+        segment.count += 1;
+
+        self.records.push_back(record);
+        self.trim(config, current_timestamp_millis())
+    }
+
+    /// Records with sequence number > `after_sequence_number` (all if None)
+    pub fn records_after(&self, after_sequence_number: Option<u64>) -> Vec<StreamRecord> {
+        let start = after_sequence_number.map_or(0, |after| after.saturating_add(1));
+        self.records_from(start).cloned().collect()
+    }
+
+    /// Resolve an iterator type to a position in the stream
+    pub fn shard_iterator(&self, iterator_type: ShardIteratorType) -> Result<ShardIterator> {
+        let position = match iterator_type {
+            ShardIteratorType::TrimHorizon => self.trimmed_through + 1,
+            ShardIteratorType::Latest => self.last_sequence_number().max(self.trimmed_through) + 1,
+            ShardIteratorType::AtSequenceNumber(seq) => seq,
+            ShardIteratorType::AfterSequenceNumber(seq) => seq.saturating_add(1),
+        };
+
+        self.check_not_trimmed(position)?;
+        Ok(ShardIterator::new(position))
+    }
+
+    /// Read up to `limit` records starting at the iterator's position
+    pub fn get_records(&self, iterator: ShardIterator, limit: usize) -> Result<GetRecordsResult> {
+        if limit == 0 {
+            return Err(Error::InvalidArgument("Limit must be greater than 0".to_string()));
+        }
+
+        let position = iterator.sequence_number();
+        self.check_not_trimmed(position)?;
+
+        let records: Vec<StreamRecord> = self.records_from(position).take(limit).cloned().collect();
+        let next_position = records.last().map_or(position, |r| r.sequence_number + 1);
+
+        Ok(GetRecordsResult {
+            records,
+            next_shard_iterator: ShardIterator::new(next_position),
+        })
+    }
+
+    /// Sequence number of the oldest retained record
+    pub fn first_sequence_number(&self) -> Option<u64> {
+        self.records.front().map(|r| r.sequence_number)
+    }
+
+    /// Sequence number of the newest record (0 if the stream is empty)
+    pub fn last_sequence_number(&self) -> u64 {
+        self.records.back().map_or(0, |r| r.sequence_number)
+    }
+
+    /// Handle for syncing appended records without holding the log (Phase 8+)
+    pub fn sync_handle(&self) -> StreamSync {
+        self.sync.clone()
+    }
+
+    /// Number of retained records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the stream has no retained records
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Number of retained records at or after `position` (Phase 8+)
+    pub fn count_from(&self, position: u64) -> usize {
+        self.records.len() - self.records.partition_point(|r| r.sequence_number < position)
+    }
+
+    /// Timestamp of the oldest retained record (Phase 8+)
+    pub fn oldest_timestamp(&self) -> Option<i64> {
+        self.records.front().map(|r| r.timestamp)
+    }
+
+    fn records_from(&self, position: u64) -> impl Iterator<Item = &StreamRecord> {
+        let start = self.records.partition_point(|r| r.sequence_number < position);
+        self.records.range(start..)
+    }
+
+    fn check_not_trimmed(&self, position: u64) -> Result<()> {
+        if position <= self.trimmed_through {
+            return Err(Error::TrimmedDataAccess(format!(
+                "Sequence number {} is older than the trim horizon {}",
+                position, self.trimmed_through
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get the file receiving appends, rolling to a new segment when full
+    fn active_segment(&mut self) -> Result<&mut File> {
+        let full = self.segments.last().map_or(true, |s| s.count >= self.segment_max_records);
+
+        if full {
+            // Later syncs only reach the new segment
+            if let Some(previous) = &self.active {
+                previous.sync_data()?;
+            }
+
+            fs::create_dir_all(&self.dir)?;
+            let path = segment_path(&self.dir, self.next_segment_id);
+            self.next_segment_id += 1;
+
+            let file = OpenOptions::new().create_new(true).append(true).open(&path)?;
+            self.segments.push(Segment {
+                path,
+                last_sequence_number: 0,
+                count: 0,
+            });
+            self.sync.set_file(file.try_clone()?);
+            self.active = Some(file);
+        } else if self.active.is_none() {
+            let path = &self.segments.last().expect("segment exists").path;
+            let file = OpenOptions::new().append(true).open(path)?;
+            self.sync.set_file(file.try_clone()?);
+            self.active = Some(file);
+        }
+
+        Ok(self.active.as_mut().expect("active segment is open"))
+    }
+
+    /// Drop records beyond the configured count or age, deleting segments
+    /// whose records have all been trimmed
+    fn trim(&mut self, config: &StreamConfig, now_millis: i64) -> Result<()> {
+        while let Some(front) = self.records.front() {
+            let over_capacity = self.records.len() > config.buffer_size;
+            let expired = config
+                .retention_period_ms
+                .map_or(false, |period| now_millis.saturating_sub(front.timestamp) > period as i64);
+
+            if !over_capacity && !expired {
+                break;
+            }
+
+            self.trimmed_through = self.trimmed_through.max(front.sequence_number);
+            self.records.pop_front();
+        }
+
+        // The newest segment is kept so appends continue where they left off
+        let mut deleted = false;
+        while self.segments.len() > 1 && self.segments[0].last_sequence_number <= self.trimmed_through {
+            let segment = self.segments.remove(0);
+            fs::remove_file(&segment.path)?;
+            deleted = true;
+        }
+
+        if deleted {
+            self.write_trim_horizon()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_trim_horizon(&self) -> Result<()> {
+        let tmp_path = self.dir.join(format!("{}.tmp", TRIM_HORIZON_FILE));
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(self.trimmed_through.to_string().as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, self.dir.join(TRIM_HORIZON_FILE))?;
+        Ok(())
+    }
+}
+
+/// Follows the stream log of a database, possibly open in another process (Phase 8+)
+///
+/// Reads the segment files without locking or modifying them: a record
+/// still being written is returned by a later `poll`, and segments deleted
+/// by retention in the meantime are skipped.
+pub struct StreamTail {
+    dir: PathBuf,
+    after_sequence_number: u64,
+    segment_id: u64, // Segment the last record came from; earlier ones are done
+}
+
+impl StreamTail {
+    /// Follow the stream of the database in `db_dir` from after
+    /// `after_sequence_number` (from the oldest retained record if None)
+    pub fn new(db_dir: impl AsRef<Path>, after_sequence_number: Option<u64>) -> Self {
+        Self {
+            dir: db_dir.as_ref().join(STREAMS_DIR),
+            after_sequence_number: after_sequence_number.unwrap_or(0),
+            segment_id: 0,
+        }
+    }
+
+    /// Records appended since the previous call, oldest first
+    pub fn poll(&mut self) -> Result<Vec<StreamRecord>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut records = Vec::new();
+        for id in segment_ids(&self.dir)? {
+            if id < self.segment_id {
+                continue;
+            }
+            let segment_records = match read_segment(&segment_path(&self.dir, id)) {
+                Ok((segment_records, _)) => segment_records,
+                Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+
+            for record in segment_records {
+                if record.sequence_number > self.after_sequence_number {
+                    self.after_sequence_number = record.sequence_number;
+                    records.push(record);
+                }
+            }
+            self.segment_id = id;
+        }
+        Ok(records)
+    }
+}
+
+/// Ids of the segment files in `dir`, oldest first
+fn segment_ids(dir: &Path) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == SEGMENT_EXTENSION) {
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok()) {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:010}.{}", id, SEGMENT_EXTENSION))
+}
+
+/// Read all records from a segment, ignoring a torn record at the tail
+///
+/// Returns the records and the length of the intact prefix of the file.
+fn read_segment(path: &Path) -> Result<(Vec<StreamRecord>, u64)> {
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+
+    while offset + 4 <= data.len() {
+        let len = u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+        let end = offset + 4 + len + 4;
+        if end > data.len() {
+            // Partially written record from a crash; everything before it is intact
+            break;
+        }
+
+        let payload = &data[offset + 4..offset + 4 + len];
+        let crc = u32::from_le_bytes([data[end - 4], data[end - 3], data[end - 2], data[end - 1]]);
+        if crc32fast::hash(payload) != crc {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let record: StreamRecord = bincode::deserialize(payload)
+            .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
+        records.push(record);
+        offset = end;
+    }
+
+    Ok((records, offset as u64))
+}
+
+fn current_timestamp_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+impl std::fmt::Debug for StreamLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamLog")
+            .field("dir", &self.dir)
+            .field("records", &self.records.len())
+            .field("segments", &self.segments.len())
+            .field("trimmed_through", &self.trimmed_through)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::StreamViewType;
+    use crate::{Key, Value};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn record(seq: u64) -> StreamRecord {
+        let mut item = HashMap::new();
+        item.insert("n".to_string(), Value::number(seq));
+        StreamRecord::insert(seq, Key::new(format!("key{}", seq).into_bytes()), item, StreamViewType::NewImage)
+    }
+
+    fn config(buffer_size: usize) -> StreamConfig {
+        StreamConfig::enabled().with_buffer_size(buffer_size)
+    }
+
+    #[test]
+    fn test_stream_log_persists_records() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100);
+
+        {
+            let mut log = StreamLog::open(dir.path(), &config).unwrap();
+            for seq in 1..=5 {
+                log.append(record(seq), &config).unwrap();
+            }
+        }
+
+        let log = StreamLog::open(dir.path(), &config).unwrap();
+        assert_eq!(log.len(), 5);
+        assert_eq!(log.first_sequence_number(), Some(1));
+        assert_eq!(log.last_sequence_number(), 5);
+        assert_eq!(log.records_after(Some(3)).len(), 2);
+        assert_eq!(log.count_from(4), 2);
+        assert_eq!(log.count_from(6), 0);
+        assert!(log.oldest_timestamp().is_some());
+    }
+
+    #[test]
+    fn test_stream_sync_group_commit() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100);
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+        let sync = log.sync_handle();
+
+        // Nothing appended yet: no segment to sync
+        sync.sync().unwrap();
+
+        log.append(record(1), &config).unwrap();
+        log.append(record(2), &config).unwrap();
+        let first_sync = sync.synced.lock().last_sync;
+        sync.sync_to(WalSyncMode::Os).unwrap();
+        assert_eq!(sync.synced.lock().appended, 0);
+        sync.sync_to(WalSyncMode::Always).unwrap();
+        assert_eq!(sync.synced.lock().appended, 2);
+        assert!(sync.synced.lock().last_sync > first_sync);
+
+        // Covered appends don't fsync again
+        let last_sync = sync.synced.lock().last_sync;
+        sync.sync_to(WalSyncMode::Always).unwrap();
+        assert_eq!(sync.synced.lock().last_sync, last_sync);
+    }
+
+    #[test]
+    fn test_stream_log_open_missing_dir() {
+        let dir = TempDir::new().unwrap();
+        let log = StreamLog::open(dir.path().join("streams"), &config(10)).unwrap();
+        assert!(log.is_empty());
+        assert!(!dir.path().join("streams").exists());
+    }
+
+    #[test]
+    fn test_shard_iterator_types() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100);
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+        for seq in [2, 4, 6] {
+            log.append(record(seq), &config).unwrap();
+        }
+
+        let horizon = log.shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+        assert_eq!(log.get_records(horizon, 10).unwrap().records.len(), 3);
+
+        let at = log.shard_iterator(ShardIteratorType::AtSequenceNumber(4)).unwrap();
+        let result = log.get_records(at, 10).unwrap();
+        assert_eq!(result.records[0].sequence_number, 4);
+
+        let after = log.shard_iterator(ShardIteratorType::AfterSequenceNumber(4)).unwrap();
+        let result = log.get_records(after, 10).unwrap();
+        assert_eq!(result.records.len(), 1);
+        assert_eq!(result.records[0].sequence_number, 6);
+
+        let latest = log.shard_iterator(ShardIteratorType::Latest).unwrap();
+        assert!(log.get_records(latest, 10).unwrap().records.is_empty());
+        log.append(record(8), &config).unwrap();
+        let result = log.get_records(latest, 10).unwrap();
+        assert_eq!(result.records.len(), 1);
+        assert_eq!(result.records[0].sequence_number, 8);
+    }
+
+    #[test]
+    fn test_get_records_pagination() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100);
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+        for seq in 1..=5 {
+            log.append(record(seq), &config).unwrap();
+        }
+
+        let iterator = log.shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+        let page1 = log.get_records(iterator, 2).unwrap();
+        assert_eq!(page1.records.len(), 2);
+
+        let page2 = log.get_records(page1.next_shard_iterator, 10).unwrap();
+        assert_eq!(page2.records.len(), 3);
+        assert_eq!(page2.records[0].sequence_number, 3);
+
+        // Iterator at the end stays put until new records arrive
+        let page3 = log.get_records(page2.next_shard_iterator, 10).unwrap();
+        assert!(page3.records.is_empty());
+        assert_eq!(page3.next_shard_iterator, page2.next_shard_iterator);
+
+        assert!(log.get_records(iterator, 0).is_err());
+    }
+
+    #[test]
+    fn test_trimmed_iterator_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let config = config(3);
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+
+        log.append(record(1), &config).unwrap();
+        let iterator = log.shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+
+        for seq in 2..=5 {
+            log.append(record(seq), &config).unwrap();
+        }
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.first_sequence_number(), Some(3));
+
+        assert!(matches!(log.get_records(iterator, 10), Err(Error::TrimmedDataAccess(_))));
+        assert!(matches!(
+            log.shard_iterator(ShardIteratorType::AtSequenceNumber(2)),
+            Err(Error::TrimmedDataAccess(_))
+        ));
+
+        let horizon = log.shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+        assert_eq!(log.get_records(horizon, 10).unwrap().records[0].sequence_number, 3);
+    }
+
+    #[test]
+    fn test_trim_by_retention_period() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100).with_retention_period(std::time::Duration::from_secs(60));
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+
+        let mut old = record(1);
+        old.timestamp -= 120_000;
+        log.append(old, &config).unwrap();
+        log.append(record(2), &config).unwrap();
+
+        assert_eq!(log.len(), 1);
+        assert_eq!(log.first_sequence_number(), Some(2));
+    }
+
+    #[test]
+    fn test_segments_deleted_and_horizon_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        let config = config(4);
+
+        {
+            let mut log = StreamLog::open(dir.path(), &config).unwrap();
+            log.segment_max_records = 2;
+            for seq in 1..=10 {
+                log.append(record(seq), &config).unwrap();
+            }
+            assert_eq!(log.first_sequence_number(), Some(7));
+            assert!(log.segments.len() <= 3);
+        }
+
+        let log = StreamLog::open(dir.path(), &config).unwrap();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log.first_sequence_number(), Some(7));
+        assert!(matches!(
+            log.shard_iterator(ShardIteratorType::AtSequenceNumber(1)),
+            Err(Error::TrimmedDataAccess(_))
+        ));
+    }
+
+    #[test]
+    fn test_torn_tail_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        let config = config(100);
+
+        {
+            let mut log = StreamLog::open(dir.path(), &config).unwrap();
+            log.append(record(1), &config).unwrap();
+            log.append(record(2), &config).unwrap();
+        }
+
+        // Simulate a crash halfway through writing a record
+        let segment = dir.path().join(format!("{:010}.{}", 1, SEGMENT_EXTENSION));
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(&[100, 0, 0, 0, 1, 2, 3]).unwrap();
+
+        let mut log = StreamLog::open(dir.path(), &config).unwrap();
+        assert_eq!(log.len(), 2);
+
+        // Appends after recovery are readable on the next open
+        log.append(record(3), &config).unwrap();
+        drop(log);
+        let log = StreamLog::open(dir.path(), &config).unwrap();
+        assert_eq!(log.len(), 3);
+    }
+
+    #[test]
+    fn test_stream_tail_follows_appends() {
+        let dir = TempDir::new().unwrap();
+        let streams = dir.path().join(STREAMS_DIR);
+        let config = config(100);
+        let mut tail = StreamTail::new(dir.path(), Some(1));
+        assert!(tail.poll().unwrap().is_empty());
+
+        let mut log = StreamLog::open(&streams, &config).unwrap();
+        log.segment_max_records = 2;
+        for seq in 1..=3 {
+            log.append(record(seq), &config).unwrap();
+        }
+        let sequence_numbers = |records: Vec<StreamRecord>| -> Vec<u64> {
+            records.iter().map(|r| r.sequence_number).collect()
+        };
+        assert_eq!(sequence_numbers(tail.poll().unwrap()), vec![2, 3]);
+
+        // A record still being written shows up once it is complete
+        let segment = streams.join(format!("{:010}.{}", 2, SEGMENT_EXTENSION));
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(&[100, 0, 0, 0, 1, 2, 3]).unwrap();
+        assert!(tail.poll().unwrap().is_empty());
+
+        let mut log = StreamLog::open(&streams, &config).unwrap();
+        log.append(record(4), &config).unwrap();
+        assert_eq!(sequence_numbers(tail.poll().unwrap()), vec![4]);
+    }
+}
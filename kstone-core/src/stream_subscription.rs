@@ -0,0 +1,404 @@
+/// Push-based stream subscriptions (Phase 3.4+)
+///
+/// A subscription runs a dispatcher thread that reads the durable stream log
+/// through a shard iterator and forwards records into a bounded channel.
+/// Writers never block on subscribers: a slow consumer only delays its own
+/// dispatcher, which waits for channel capacity before reading further.
+/// If a consumer falls behind the stream's trim horizon the subscription
+/// ends and the error is available from [`StreamSubscription::take_error`].
+
+use crate::stream::{GetRecordsResult, ShardIterator, ShardIteratorType, StreamRecord};
+use crate::{Error, Result};
+use crossbeam::channel::{self, Receiver, RecvError, RecvTimeoutError, SendTimeoutError, TryRecvError};
+use parking_lot::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How long the dispatcher waits between checks for new records or capacity
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Number of records read from the log per batch
+const FETCH_BATCH_SIZE: usize = 256;
+
+/// Subscription configuration
+#[derive(Debug, Clone, Copy)]
+pub struct SubscriptionConfig {
+    /// Where the subscription starts reading
+    pub start: ShardIteratorType,
+    /// Capacity of the channel; the dispatcher pauses when it is full
+    pub capacity: usize,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            start: ShardIteratorType::Latest,
+            capacity: 1024,
+        }
+    }
+}
+
+impl SubscriptionConfig {
+    /// Create a config that delivers records committed after subscribing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set where the subscription starts reading
+    pub fn with_start(mut self, start: ShardIteratorType) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Set the channel capacity
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+}
+
+/// Wakes dispatcher threads when new stream records are committed
+///
+/// Also tracks how far each live subscription has dispatched, for lag
+/// statistics (Phase 8+).
+#[derive(Clone, Default)]
+pub struct StreamNotifier {
+    state: Arc<(Mutex<u64>, Condvar)>,
+    positions: Arc<Mutex<Vec<Weak<AtomicU64>>>>,
+}
+
+impl StreamNotifier {
+    /// Create a notifier
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `sequence_number` has been committed and wake waiters
+    pub fn notify(&self, sequence_number: u64) {
+        let (latest, condvar) = &*self.state;
+        let mut latest = latest.lock();
+        *latest = (*latest).max(sequence_number);
+        condvar.notify_all();
+    }
+
+    /// Next position each live subscription will dispatch from (Phase 8+)
+    pub(crate) fn subscriber_positions(&self) -> Vec<u64> {
+        let mut positions = self.positions.lock();
+        positions.retain(|position| position.strong_count() > 0);
+        positions
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|position| position.load(Ordering::Acquire))
+            .collect()
+    }
+
+    fn register(&self, position: &Arc<AtomicU64>) {
+        self.positions.lock().push(Arc::downgrade(position));
+    }
+
+    /// Wake all waiters without recording a new record
+    fn wake(&self) {
+        let (_, condvar) = &*self.state;
+        condvar.notify_all();
+    }
+
+    /// Wait until a record at or after `position` is committed, or the timeout elapses
+    fn wait_for(&self, position: u64, timeout: Duration) {
+        let (latest, condvar) = &*self.state;
+        let mut latest = latest.lock();
+        if *latest < position {
+            condvar.wait_for(&mut latest, timeout);
+        }
+    }
+}
+
+/// Live subscription to a table's stream
+///
+/// Records arrive in sequence number order. Dropping the subscription (or
+/// calling [`unsubscribe`](Self::unsubscribe)) stops the dispatcher thread.
+pub struct StreamSubscription {
+    receiver: Receiver<StreamRecord>,
+    stopped: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<Error>>>,
+    notifier: StreamNotifier,
+    position: Arc<AtomicU64>, // Next sequence number to dispatch (Phase 8+)
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StreamSubscription {
+    /// Start a dispatcher reading records with `fetch`, beginning at `start`
+    pub(crate) fn spawn<F>(
+        fetch: F,
+        notifier: StreamNotifier,
+        start: ShardIterator,
+        capacity: usize,
+    ) -> Result<Self>
+    where
+        F: Fn(ShardIterator, usize) -> Result<GetRecordsResult> + Send + 'static,
+    {
+        let (sender, receiver) = channel::bounded(capacity.max(1));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let error = Arc::new(Mutex::new(None));
+        let position = Arc::new(AtomicU64::new(start.sequence_number()));
+        notifier.register(&position);
+
+        let thread_stopped = stopped.clone();
+        let thread_error = error.clone();
+        let thread_notifier = notifier.clone();
+        let thread_position = position.clone();
+
+        let thread = std::thread::Builder::new()
+            .name("kstone-stream-subscription".to_string())
+            .spawn(move || {
+                let mut iterator = start;
+
+                'dispatch: while !thread_stopped.load(Ordering::Acquire) {
+                    let result = match fetch(iterator, FETCH_BATCH_SIZE) {
+                        Ok(result) => result,
+                        Err(e) => {
+                            *thread_error.lock() = Some(e);
+                            break;
+                        }
+                    };
+
+                    if result.records.is_empty() {
+                        thread_notifier.wait_for(iterator.sequence_number(), POLL_INTERVAL);
+                        continue;
+                    }
+
+                    for record in result.records {
+                        let next = record.sequence_number + 1;
+                        let mut pending = record;
+                        loop {
+                            match sender.send_timeout(pending, POLL_INTERVAL) {
+                                Ok(()) => {
+                                    thread_position.store(next, Ordering::Release);
+                                    break;
+                                }
+                                Err(SendTimeoutError::Timeout(record)) => {
+                                    if thread_stopped.load(Ordering::Acquire) {
+                                        break 'dispatch;
+                                    }
+                                    pending = record;
+                                }
+                                // Receiver dropped
+                                Err(SendTimeoutError::Disconnected(_)) => break 'dispatch,
+                            }
+                        }
+                    }
+
+                    iterator = result.next_shard_iterator;
+                }
+            })
+            .map_err(|e| Error::Internal(format!("Failed to spawn stream subscription: {}", e)))?;
+
+        Ok(Self {
+            receiver,
+            stopped,
+            error,
+            notifier,
+            position,
+            thread: Some(thread),
+        })
+    }
+
+    /// Sequence number of the next record to be handed to the channel (Phase 8+)
+    ///
+    /// Records before it are delivered or buffered in the channel.
+    pub fn position(&self) -> u64 {
+        self.position.load(Ordering::Acquire)
+    }
+
+    /// Block until the next record arrives
+    ///
+    /// Returns an error once the subscription has ended.
+    pub fn recv(&self) -> std::result::Result<StreamRecord, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Wait up to `timeout` for the next record
+    pub fn recv_timeout(&self, timeout: Duration) -> std::result::Result<StreamRecord, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Return the next record if one is ready
+    pub fn try_recv(&self) -> std::result::Result<StreamRecord, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// The underlying channel, for use with `select!` or iterator adapters
+    pub fn receiver(&self) -> &Receiver<StreamRecord> {
+        &self.receiver
+    }
+
+    /// Iterate over records until the subscription ends
+    pub fn iter(&self) -> channel::Iter<'_, StreamRecord> {
+        self.receiver.iter()
+    }
+
+    /// Take the error that ended the subscription, if any
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.lock().take()
+    }
+
+    /// Stop delivering records and wait for the dispatcher to exit
+    pub fn unsubscribe(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        self.notifier.wake();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for StreamSubscription {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{StreamConfig, StreamViewType};
+    use crate::stream_log::StreamLog;
+    use crate::Key;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn record(seq: u64) -> StreamRecord {
+        StreamRecord::insert(seq, Key::new(format!("k{}", seq).into_bytes()), HashMap::new(), StreamViewType::KeysOnly)
+    }
+
+    fn subscribe(log: &Arc<Mutex<StreamLog>>, notifier: &StreamNotifier, start: ShardIteratorType, capacity: usize) -> StreamSubscription {
+        let start = log.lock().shard_iterator(start).unwrap();
+        let fetch_log = log.clone();
+        StreamSubscription::spawn(
+            move |iterator, limit| fetch_log.lock().get_records(iterator, limit),
+            notifier.clone(),
+            start,
+            capacity,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_subscription_receives_new_records() {
+        let dir = TempDir::new().unwrap();
+        let config = StreamConfig::enabled();
+        let log = Arc::new(Mutex::new(StreamLog::open(dir.path(), &config).unwrap()));
+        let notifier = StreamNotifier::new();
+
+        log.lock().append(record(1), &config).unwrap();
+        let subscription = subscribe(&log, &notifier, ShardIteratorType::Latest, 16);
+
+        for seq in 2..=4 {
+            log.lock().append(record(seq), &config).unwrap();
+            notifier.notify(seq);
+        }
+
+        for expected in 2..=4 {
+            let record = subscription.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(record.sequence_number, expected);
+        }
+        subscription.unsubscribe();
+    }
+
+    #[test]
+    fn test_subscription_backpressure_preserves_order() {
+        let dir = TempDir::new().unwrap();
+        let config = StreamConfig::enabled();
+        let log = Arc::new(Mutex::new(StreamLog::open(dir.path(), &config).unwrap()));
+        let notifier = StreamNotifier::new();
+
+        for seq in 1..=20 {
+            log.lock().append(record(seq), &config).unwrap();
+        }
+
+        // A capacity of 2 forces the dispatcher to wait for the consumer
+        let subscription = subscribe(&log, &notifier, ShardIteratorType::TrimHorizon, 2);
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(subscription.receiver().len() <= 2);
+
+        let received: Vec<u64> = (0..20)
+            .map(|_| subscription.recv_timeout(Duration::from_secs(5)).unwrap().sequence_number)
+            .collect();
+        assert_eq!(received, (1..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_subscription_position() {
+        let dir = TempDir::new().unwrap();
+        let config = StreamConfig::enabled();
+        let log = Arc::new(Mutex::new(StreamLog::open(dir.path(), &config).unwrap()));
+        let notifier = StreamNotifier::new();
+
+        for seq in 1..=5 {
+            log.lock().append(record(seq), &config).unwrap();
+        }
+
+        // With a capacity of 1 only the first record is dispatched until the consumer reads
+        let subscription = subscribe(&log, &notifier, ShardIteratorType::TrimHorizon, 1);
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(subscription.position(), 2);
+        assert_eq!(notifier.subscriber_positions(), vec![2]);
+
+        for _ in 1..=5 {
+            subscription.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while subscription.position() < 6 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(subscription.position(), 6);
+
+        drop(subscription);
+        assert!(notifier.subscriber_positions().is_empty());
+    }
+
+    #[test]
+    fn test_subscription_ends_when_trimmed() {
+        let dir = TempDir::new().unwrap();
+        let config = StreamConfig::enabled().with_buffer_size(2);
+        let log = Arc::new(Mutex::new(StreamLog::open(dir.path(), &config).unwrap()));
+        let notifier = StreamNotifier::new();
+
+        log.lock().append(record(1), &config).unwrap();
+        let start = log.lock().shard_iterator(ShardIteratorType::TrimHorizon).unwrap();
+        for seq in 2..=5 {
+            log.lock().append(record(seq), &config).unwrap();
+        }
+
+        let fetch_log = log.clone();
+        let subscription = StreamSubscription::spawn(
+            move |iterator, limit| fetch_log.lock().get_records(iterator, limit),
+            notifier,
+            start,
+            4,
+        )
+        .unwrap();
+
+        assert!(subscription.recv_timeout(Duration::from_secs(5)).is_err());
+        assert!(matches!(subscription.take_error(), Some(Error::TrimmedDataAccess(_))));
+    }
+
+    #[test]
+    fn test_drop_stops_dispatcher() {
+        let dir = TempDir::new().unwrap();
+        let config = StreamConfig::enabled();
+        let log = Arc::new(Mutex::new(StreamLog::open(dir.path(), &config).unwrap()));
+        let notifier = StreamNotifier::new();
+
+        let subscription = subscribe(&log, &notifier, ShardIteratorType::Latest, 1);
+        drop(subscription);
+
+        // The dispatcher released its handle on the log
+        assert_eq!(Arc::strong_count(&log), 1);
+    }
+}
@@ -0,0 +1,136 @@
+/// Named tables within one database (Phase 3.7+)
+///
+/// Every database has an unnamed default table. Named tables share its WAL,
+/// manifest and stripes: their items are stored under partition keys prefixed
+/// with the table name, and their schemas are kept in the default table's
+/// schema (`TableSchema::tables`), so they persist with it.
+///
+/// Table keys start with `TABLE_MARKER` and index keys with `0xFF`, so neither
+/// shows up in the other's queries and scans.
+
+use crate::index::TableSchema;
+use crate::{Error, Key, Result};
+use bytes::{BufMut, BytesMut};
+
+/// First byte of every partition key stored in a named table
+pub const TABLE_MARKER: u8 = 0xFE;
+
+/// Longest allowed table name
+pub const MAX_TABLE_NAME_LEN: usize = 255;
+
+/// Store a key under a named table
+///
+/// Format: [marker:1][name_len:4][name][pk]; the sort key is unchanged.
+pub fn encode_table_key(table: &str, key: &Key) -> Key {
+    let mut pk = BytesMut::with_capacity(1 + 4 + table.len() + key.pk.len());
+    pk.put_u8(TABLE_MARKER);
+    pk.put_u32_le(table.len() as u32);
+    pk.put_slice(table.as_bytes());
+    pk.put_slice(&key.pk);
+
+    Key {
+        pk: pk.freeze(),
+        sk: key.sk.clone(),
+    }
+}
+
+/// Split a stored key into its table name and the caller's key
+pub fn decode_table_key(key: &Key) -> Option<(&str, Key)> {
+    let name = table_name(&key.pk)?;
+    let pk = key.pk.slice(1 + 4 + name.len()..);
+    Some((name, Key { pk, sk: key.sk.clone() }))
+}
+
+/// Name of the table a stored partition key belongs to (None for the default table)
+pub fn table_name(pk: &[u8]) -> Option<&str> {
+    if !is_table_key(pk) || pk.len() < 5 {
+        return None;
+    }
+    let len = u32::from_le_bytes(pk[1..5].try_into().ok()?) as usize;
+    std::str::from_utf8(pk.get(5..5 + len)?).ok()
+}
+
+/// Check if a stored partition key belongs to a named table
+pub fn is_table_key(pk: &[u8]) -> bool {
+    pk.first() == Some(&TABLE_MARKER)
+}
+
+/// Check a table name: 3-255 characters from `a-z A-Z 0-9 _ - .`, as in DynamoDB
+pub fn validate_table_name(name: &str) -> Result<()> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if name.len() < 3 || name.len() > MAX_TABLE_NAME_LEN || !valid_chars {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid table name '{}': use 3-{} characters from a-z, A-Z, 0-9, '_', '-' and '.'",
+            name, MAX_TABLE_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a schema can be used for a named table
+///
+/// Named tables support TTL and attribute schemas; indexes, streams and
+/// nested tables are only available on the default table for now.
+pub fn validate_table_schema(schema: &TableSchema) -> Result<()> {
+    let unsupported = if !schema.local_indexes.is_empty()
+        || !schema.global_indexes.is_empty()
+        || !schema.vector_indexes.is_empty()
+        || !schema.geo_indexes.is_empty()
+    {
+        Some("indexes")
+    } else if schema.stream_config.enabled {
+        Some("streams")
+    } else if !schema.tables.is_empty() {
+        Some("nested tables")
+    } else {
+        None
+    };
+
+    match unsupported {
+        Some(feature) => Err(Error::InvalidArgument(format!(
+            "Named tables don't support {} yet",
+            feature
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::LocalSecondaryIndex;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_table_key_roundtrip() {
+        let key = Key::with_sk(Bytes::from("user#1"), Bytes::from("profile"));
+        let encoded = encode_table_key("users", &key);
+
+        assert!(is_table_key(&encoded.pk));
+        assert_eq!(table_name(&encoded.pk), Some("users"));
+        assert_eq!(decode_table_key(&encoded), Some(("users", key.clone())));
+
+        assert!(!is_table_key(&key.pk));
+        assert_eq!(decode_table_key(&key), None);
+    }
+
+    #[test]
+    fn test_table_name_validation() {
+        assert!(validate_table_name("users").is_ok());
+        assert!(validate_table_name("order_items-v2.0").is_ok());
+        assert!(validate_table_name("ab").is_err());
+        assert!(validate_table_name("has space").is_err());
+        assert!(validate_table_name(&"x".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn test_table_schema_validation() {
+        let ttl = TableSchema::new().with_ttl("expires_at");
+        assert!(validate_table_schema(&ttl).is_ok());
+
+        let indexed = TableSchema::new().add_local_index(LocalSecondaryIndex::new("by-email", "email"));
+        assert!(validate_table_schema(&indexed).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+/// Tiered storage: offload cold SSTs to an object store (Phase 8+)
+///
+/// With `DatabaseConfig::cold_sst_age` set and a `ColdStore` attached, SSTs
+/// older than that age are uploaded to the store and replaced locally by a
+/// small stub, `{stripe:03}-{sst_id}.cold`, holding the object key, checksum
+/// and key range. A cold SST is fetched the first time a read may touch it
+/// and then stays cached in memory; key range pruning keeps point reads and
+/// queries from fetching SSTs they cannot match.
+///
+/// Cold SSTs are never compacted again, so newer SSTs keep their tombstones
+/// while a stripe has cold SSTs. Value log files that cold SSTs point into
+/// stay local, and objects are never deleted from the store.
+///
+/// `FsColdStore` keeps objects in a directory; kstone-sync provides an S3
+/// store (`S3ColdStore`).
+///
+/// Stub file: [bincode(ColdSstStub) | crc(4)]
+
+use crate::{Error, Result};
+use crate::sst::SstKeyRange;
+use bytes::Bytes;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Extension of the local stubs that replace offloaded SSTs
+pub const COLD_STUB_EXTENSION: &str = "cold";
+
+/// Object store holding offloaded SSTs
+///
+/// Calls block until the store answers; the engine makes them without
+/// holding any stripe lock while uploading.
+pub trait ColdStore: Send + Sync {
+    /// Upload an object, replacing any existing object with the same key
+    fn put(&self, key: &str, data: Bytes) -> Result<()>;
+
+    /// Download a whole object
+    fn get(&self, key: &str) -> Result<Bytes>;
+
+    /// Delete an object; deleting a missing object is not an error
+    fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Cold store backed by a local (or mounted network) directory
+#[derive(Debug, Clone)]
+pub struct FsColdStore {
+    dir: PathBuf,
+}
+
+impl FsColdStore {
+    /// Store objects as files in `dir`, which is created if needed
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn object_path(&self, key: &str) -> Result<PathBuf> {
+        if key.is_empty() || key.contains('/') || key.contains('\\') || key.starts_with('.') {
+            return Err(Error::InvalidArgument(format!("Invalid cold object key: {}", key)));
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+impl ColdStore for FsColdStore {
+    fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        let path = self.object_path(key)?;
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Bytes> {
+        let path = self.object_path(key)?;
+        match fs::read(&path) {
+            Ok(data) => Ok(Bytes::from(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(Error::NotFound(format!("Cold object {}", key)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.object_path(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Local stand-in for an SST that lives in the cold store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColdSstStub {
+    /// Object key in the cold store
+    pub object_key: String,
+    /// Size of the SST file in bytes
+    pub size: u64,
+    /// CRC32C of the SST file
+    pub crc: u32,
+    /// Number of records, including tombstones
+    pub count: u64,
+    /// Key and sequence number bounds, `None` for an empty SST
+    pub key_range: Option<SstKeyRange>,
+    /// Value log files the SST points into, which stay local
+    pub value_log_files: BTreeSet<u64>,
+}
+
+impl ColdSstStub {
+    /// Durably write the stub to `path`
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut data = bincode::serialize(self)
+            .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+        let crc = crc32c::crc32c(&data);
+        data.extend_from_slice(&crc.to_le_bytes());
+
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Read a stub written by `write`
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let data = fs::read(path.as_ref())?;
+        if data.len() < 4 {
+            return Err(Error::Corruption(format!("Cold SST stub {} too short", path.as_ref().display())));
+        }
+        let (body, crc) = data.split_at(data.len() - 4);
+        if crc32c::crc32c(body) != u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+            return Err(Error::ChecksumMismatch);
+        }
+        bincode::deserialize(body).map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))
+    }
+}
+
+/// Tiered storage statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TieringStats {
+    /// SSTs currently in the cold store
+    pub cold_ssts: u64,
+
+    /// SSTs uploaded since the database was opened
+    pub offloaded_ssts: u64,
+
+    /// Bytes uploaded since the database was opened
+    pub offloaded_bytes: u64,
+
+    /// Cold SSTs fetched for reads since the database was opened
+    pub fetched_ssts: u64,
+
+    /// Bytes fetched for reads since the database was opened
+    pub fetched_bytes: u64,
+}
+
+/// The attached cold store and its statistics, shared with cold SST readers
+#[derive(Default)]
+pub struct ColdTier {
+    store: RwLock<Option<Arc<dyn ColdStore>>>,
+    offloaded_ssts: AtomicU64,
+    offloaded_bytes: AtomicU64,
+    fetched_ssts: AtomicU64,
+    fetched_bytes: AtomicU64,
+}
+
+impl ColdTier {
+    /// Create a tier with no store attached
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a store, or detach it with `None`
+    pub fn set_store(&self, store: Option<Arc<dyn ColdStore>>) {
+        *self.store.write() = store;
+    }
+
+    /// The attached store, if any
+    pub fn store(&self) -> Option<Arc<dyn ColdStore>> {
+        self.store.read().clone()
+    }
+
+    /// Download a cold SST, verifying it against its stub
+    pub fn fetch(&self, stub: &ColdSstStub) -> Result<Bytes> {
+        let store = self.store().ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "SST {} is in the cold store, but no cold store is attached",
+                stub.object_key
+            ))
+        })?;
+
+        let data = store.get(&stub.object_key)?;
+        if data.len() as u64 != stub.size || crc32c::crc32c(&data) != stub.crc {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        self.fetched_ssts.fetch_add(1, Ordering::Relaxed);
+        self.fetched_bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        Ok(data)
+    }
+
+    /// Record an SST uploaded to the store
+    pub fn record_offload(&self, bytes: u64) {
+        self.offloaded_ssts.fetch_add(1, Ordering::Relaxed);
+        self.offloaded_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Get a snapshot of the statistics; `cold_ssts` is filled in by the engine
+    pub fn stats(&self) -> TieringStats {
+        TieringStats {
+            cold_ssts: 0,
+            offloaded_ssts: self.offloaded_ssts.load(Ordering::Relaxed),
+            offloaded_bytes: self.offloaded_bytes.load(Ordering::Relaxed),
+            fetched_ssts: self.fetched_ssts.load(Ordering::Relaxed),
+            fetched_bytes: self.fetched_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fs_cold_store() {
+        let dir = TempDir::new().unwrap();
+        let store = FsColdStore::new(dir.path().join("cold")).unwrap();
+
+        store.put("000-1.sst", Bytes::from_static(b"data")).unwrap();
+        assert_eq!(store.get("000-1.sst").unwrap(), Bytes::from_static(b"data"));
+
+        store.delete("000-1.sst").unwrap();
+        store.delete("000-1.sst").unwrap();
+        assert!(matches!(store.get("000-1.sst"), Err(Error::NotFound(_))));
+        assert!(matches!(store.put("../escape", Bytes::new()), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_cold_stub_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("000-1.cold");
+        let stub = ColdSstStub {
+            object_key: "000-1.sst".to_string(),
+            size: 4,
+            crc: crc32c::crc32c(b"data"),
+            count: 1,
+            key_range: Some(SstKeyRange {
+                min_key: Key::new(b"a".to_vec()),
+                max_key: Key::new(b"a".to_vec()),
+                min_seq: 1,
+                max_seq: 1,
+            }),
+            value_log_files: BTreeSet::new(),
+        };
+        stub.write(&path).unwrap();
+        assert_eq!(ColdSstStub::read(&path).unwrap(), stub);
+
+        let mut data = fs::read(&path).unwrap();
+        data[0] ^= 1;
+        fs::write(&path, data).unwrap();
+        assert!(matches!(ColdSstStub::read(&path), Err(Error::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn test_cold_tier_fetch() {
+        let dir = TempDir::new().unwrap();
+        let tier = ColdTier::new();
+        let stub = ColdSstStub {
+            object_key: "000-1.sst".to_string(),
+            size: 4,
+            crc: crc32c::crc32c(b"data"),
+            count: 1,
+            key_range: None,
+            value_log_files: BTreeSet::new(),
+        };
+
+        // Nothing attached yet
+        assert!(matches!(tier.fetch(&stub), Err(Error::InvalidArgument(_))));
+
+        let store = Arc::new(FsColdStore::new(dir.path()).unwrap());
+        store.put("000-1.sst", Bytes::from_static(b"data")).unwrap();
+        tier.set_store(Some(store.clone()));
+        assert_eq!(tier.fetch(&stub).unwrap(), Bytes::from_static(b"data"));
+        assert_eq!(tier.stats().fetched_bytes, 4);
+
+        store.put("000-1.sst", Bytes::from_static(b"DATA")).unwrap();
+        assert!(matches!(tier.fetch(&stub), Err(Error::ChecksumMismatch)));
+    }
+}
@@ -83,11 +83,35 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Size of the value in bytes, counted like DynamoDB item sizes
+    ///
+    /// Strings and binaries count their length, numbers their text, and
+    /// lists and maps their elements plus 3 bytes of overhead (Phase 8+).
+    pub fn size_bytes(&self) -> usize {
+        match self {
+            Value::S(s) => s.len(),
+            Value::N(n) => n.len(),
+            Value::B(b) => b.len(),
+            Value::Bool(_) | Value::Null => 1,
+            Value::L(list) => 3 + list.iter().map(|value| 1 + value.size_bytes()).sum::<usize>(),
+            Value::M(map) => 3 + item_size(map) + map.len(),
+            Value::VecF32(vec) => vec.len() * 4,
+            Value::Ts(_) => 8,
+        }
+    }
 }
 
 /// Item - a map of attribute names to values
 pub type Item = HashMap<String, Value>;
 
+/// Size of an item in bytes: attribute names plus value sizes (Phase 8+)
+pub fn item_size(item: &Item) -> usize {
+    item.iter()
+        .map(|(name, value)| name.len() + value.size_bytes())
+        .sum()
+}
+
 /// Composite key: partition key + optional sort key
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 pub struct Key {
@@ -232,6 +256,17 @@ mod tests {
         assert_eq!(before_epoch.as_timestamp(), Some(-1000));
     }
 
+    #[test]
+    fn test_item_size() {
+        let mut item = Item::new();
+        item.insert("name".to_string(), Value::string("Alice"));
+        item.insert("age".to_string(), Value::number(30));
+        assert_eq!(item_size(&item), 4 + 5 + 3 + 2);
+
+        let list = Value::L(vec![Value::string("ab"), Value::Bool(true)]);
+        assert_eq!(list.size_bytes(), 3 + (1 + 2) + (1 + 1));
+    }
+
     #[test]
     fn test_crc32c_compute() {
         let data = b"hello world";
@@ -5,6 +5,7 @@
 use crate::{Error, Result, Item, Value};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// Type constraint for an attribute
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -153,6 +154,9 @@ pub struct AttributeSchema {
     pub constraints: Vec<ValueConstraint>,
     /// Description (for documentation)
     pub description: Option<String>,
+    /// Value filled in when a write leaves the attribute out (Phase 8+)
+    #[serde(default)]
+    pub default: Option<crate::generated::DefaultValue>,
 }
 
 impl AttributeSchema {
@@ -164,6 +168,7 @@ impl AttributeSchema {
             required: false,
             constraints: Vec::new(),
             description: None,
+            default: None,
         }
     }
 
@@ -185,6 +190,12 @@ impl AttributeSchema {
         self
     }
 
+    /// Fill in this value when a put or update leaves the attribute out (Phase 8+)
+    pub fn with_default(mut self, default: crate::generated::DefaultValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+
     /// Validate a value against this schema
     pub fn validate(&self, value: Option<&Value>) -> Result<()> {
         match value {
@@ -213,6 +224,66 @@ impl AttributeSchema {
             }
         }
     }
+
+    /// Every way a value breaks this schema (Phase 8+)
+    ///
+    /// A value of the wrong type is reported once, without checking its
+    /// constraints.
+    pub fn violations(&self, value: Option<&Value>) -> Vec<ValidationViolation> {
+        let violation = |message: String| ValidationViolation {
+            attribute: self.name.clone(),
+            message,
+        };
+
+        match value {
+            None if self.required => vec![violation("required attribute is missing".to_string())],
+            None => Vec::new(),
+            Some(val) if !self.attr_type.matches(val) => {
+                vec![violation(format!("wrong type (expected {:?})", self.attr_type))]
+            }
+            Some(val) => self
+                .constraints
+                .iter()
+                .filter_map(|constraint| match constraint.validate(val) {
+                    Ok(()) => None,
+                    Err(Error::InvalidArgument(message)) => Some(violation(message)),
+                    Err(e) => Some(violation(e.to_string())),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One attribute breaking its schema (Phase 8+)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationViolation {
+    /// Attribute name
+    pub attribute: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for ValidationViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}': {}", self.attribute, self.message)
+    }
+}
+
+/// Every violation that made an item fail validation (Phase 8+)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    /// Violations, ordered by attribute name
+    pub violations: Vec<ValidationViolation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} violation(s)", self.violations.len())?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            write!(f, "{} {}", if i == 0 { ":" } else { ";" }, violation)?;
+        }
+        Ok(())
+    }
 }
 
 /// Validator for items based on a schema
@@ -243,14 +314,39 @@ impl Validator {
         self.schemas.insert(schema.name.clone(), schema);
     }
 
+    /// Attribute schemas, ordered by name
+    pub fn schemas(&self) -> Vec<&AttributeSchema> {
+        let mut schemas: Vec<_> = self.schemas.values().collect();
+        schemas.sort_by(|a, b| a.name.cmp(&b.name));
+        schemas
+    }
+
     /// Validate an item against the schemas
+    ///
+    /// Fails with `Error::ValidationFailed` listing every violation.
     pub fn validate(&self, item: &Item) -> Result<()> {
-        // Check all defined attributes
-        for (attr_name, schema) in &self.schemas {
-            schema.validate(item.get(attr_name))?;
+        self.check(item).map_err(Error::ValidationFailed)
+    }
+
+    /// Check every defined attribute of an item, collecting all violations (Phase 8+)
+    pub fn check(&self, item: &Item) -> std::result::Result<(), ValidationError> {
+        let violations: Vec<_> = self
+            .schemas()
+            .into_iter()
+            .flat_map(|schema| schema.violations(item.get(&schema.name)))
+            .collect();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
         }
+    }
+}
 
-        Ok(())
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -332,4 +428,29 @@ mod tests {
         invalid_item.insert("age".to_string(), Value::N("30".to_string()));
         assert!(validator.validate(&invalid_item).is_err());
     }
+
+    #[test]
+    fn test_validator_lists_every_violation() {
+        let validator = Validator::from_schemas(vec![
+            AttributeSchema::new("name", AttributeType::String).required(),
+            AttributeSchema::new("age", AttributeType::Number),
+            AttributeSchema::new("username", AttributeType::String)
+                .with_constraint(ValueConstraint::MinLength(3))
+                .with_constraint(ValueConstraint::Pattern("^[a-z]+$".to_string())),
+        ]);
+
+        let mut item = HashMap::new();
+        item.insert("age".to_string(), Value::S("thirty".to_string()));
+        item.insert("username".to_string(), Value::S("A".to_string()));
+
+        let error = validator.check(&item).unwrap_err();
+        let attributes: Vec<_> = error.violations.iter().map(|v| v.attribute.as_str()).collect();
+        assert_eq!(attributes, vec!["age", "name", "username", "username"]);
+        assert!(error.to_string().starts_with("4 violation(s): 'age': wrong type"));
+
+        match validator.validate(&item) {
+            Err(Error::ValidationFailed(e)) => assert_eq!(e, error),
+            other => panic!("expected ValidationFailed, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,518 @@
+/// Vector similarity search (Phase 3.5+)
+///
+/// Approximate nearest-neighbour search over `Value::VecF32` attributes with
+/// an HNSW (hierarchical navigable small world) graph. Each vector index is
+/// declared in the table schema, kept up to date on every put and delete,
+/// and saved next to the SSTs so reopening a database doesn't rebuild it.
+
+use crate::index::VectorIndex;
+use crate::{Error, Item, Key, Result, SeqNo};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Maximum neighbours per node on the upper layers
+const MAX_NEIGHBORS: usize = 16;
+/// Maximum neighbours per node on the bottom layer
+const MAX_NEIGHBORS_BOTTOM: usize = 2 * MAX_NEIGHBORS;
+/// Candidates considered while linking a new node
+const EF_CONSTRUCTION: usize = 100;
+/// Minimum candidates considered per search
+const EF_SEARCH: usize = 64;
+/// Highest layer a node can be placed on
+const MAX_LEVEL: usize = 16;
+/// Deleted nodes tolerated before they may trigger a rebuild
+const REBUILD_MIN_DELETED: usize = 64;
+
+/// How distance between two vectors is measured (Phase 3.5+)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceMetric {
+    /// 1 - cosine similarity
+    Cosine,
+    /// Straight-line (L2) distance
+    Euclidean,
+    /// Negated dot product, so larger products rank closer
+    DotProduct,
+}
+
+impl DistanceMetric {
+    /// Distance between two vectors of the same length; smaller is closer
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            DistanceMetric::Cosine => {
+                let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+                for (x, y) in a.iter().zip(b) {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    return 1.0;
+                }
+                1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+            }
+            DistanceMetric::Euclidean => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+            DistanceMetric::DotProduct => -a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>(),
+        }
+    }
+}
+
+/// A node and its distance from the vector being searched for
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+/// A vector in the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    key: Key,
+    vector: Vec<f32>,
+    neighbors: Vec<Vec<u32>>, // One list per layer the node is on
+    deleted: bool,
+}
+
+/// HNSW graph over the vectors of one index (Phase 3.5+)
+///
+/// Deleted vectors stay in the graph to route searches, but are never
+/// returned; once they outnumber the live ones the graph is rebuilt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswGraph {
+    metric: DistanceMetric,
+    nodes: Vec<Node>,
+    live: HashMap<Key, u32>, // Live node per base key
+    entry_point: Option<u32>,
+    deleted: usize,
+    rng_state: u64,
+}
+
+impl HnswGraph {
+    /// Create an empty graph
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self {
+            metric,
+            nodes: Vec::new(),
+            live: HashMap::new(),
+            entry_point: None,
+            deleted: 0,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Number of vectors that searches can return
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Check if the graph holds no live vectors
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    /// Add an item's vector, replacing any previous vector for the key
+    pub fn insert(&mut self, key: Key, vector: Vec<f32>) {
+        self.remove(&key);
+
+        let level = self.random_level();
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            key: key.clone(),
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.live.insert(key, id);
+
+        let entry = match self.entry_point {
+            Some(entry) => entry,
+            None => {
+                self.entry_point = Some(id);
+                return;
+            }
+        };
+
+        let query = self.nodes[id as usize].vector.clone();
+        let top = self.level_of(entry);
+
+        // Descend greedily to the node's own top layer, then link it on every
+        // layer from there down
+        let mut entry_points = vec![entry];
+        for layer in (level + 1..=top).rev() {
+            entry_points = self.closest(&query, &entry_points, layer);
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entry_points, EF_CONSTRUCTION, layer);
+            let selected: Vec<u32> = found
+                .iter()
+                .take(max_neighbors(layer))
+                .map(|candidate| candidate.node)
+                .collect();
+            for &neighbor in &selected {
+                self.connect(neighbor, id, layer);
+            }
+            self.nodes[id as usize].neighbors[layer] = selected;
+            entry_points = found.into_iter().map(|candidate| candidate.node).collect();
+        }
+
+        if level > top {
+            self.entry_point = Some(id);
+        }
+    }
+
+    /// Remove the vector for a key, returning whether there was one
+    pub fn remove(&mut self, key: &Key) -> bool {
+        let id = match self.live.remove(key) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        self.nodes[id as usize].deleted = true;
+        self.deleted += 1;
+        if self.deleted > REBUILD_MIN_DELETED && self.deleted > self.live.len() {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// The `k` nearest live vectors to `query`, closest first, with their distances
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(Key, f32)> {
+        let entry = match self.entry_point {
+            Some(entry) if k > 0 => entry,
+            _ => return Vec::new(),
+        };
+
+        let mut entry_points = vec![entry];
+        for layer in (1..=self.level_of(entry)).rev() {
+            entry_points = self.closest(query, &entry_points, layer);
+        }
+
+        // Deleted nodes take up candidate slots without being returned
+        let ef = EF_SEARCH.max(k) + self.deleted;
+        self.search_layer(query, &entry_points, ef, 0)
+            .into_iter()
+            .filter(|candidate| !self.nodes[candidate.node as usize].deleted)
+            .take(k)
+            .map(|candidate| (self.nodes[candidate.node as usize].key.clone(), candidate.distance))
+            .collect()
+    }
+
+    /// Highest layer a node is on
+    fn level_of(&self, node: u32) -> usize {
+        self.nodes[node as usize].neighbors.len() - 1
+    }
+
+    /// A node's neighbours on a layer
+    fn neighbors(&self, node: u32, layer: usize) -> &[u32] {
+        self.nodes[node as usize]
+            .neighbors
+            .get(layer)
+            .map_or(&[][..], |neighbors| &neighbors[..])
+    }
+
+    fn distance_to(&self, query: &[f32], node: u32) -> f32 {
+        self.metric.distance(query, &self.nodes[node as usize].vector)
+    }
+
+    /// The single closest node to `query` on a layer, starting from `entry_points`
+    fn closest(&self, query: &[f32], entry_points: &[u32], layer: usize) -> Vec<u32> {
+        self.search_layer(query, entry_points, 1, layer)
+            .into_iter()
+            .take(1)
+            .map(|candidate| candidate.node)
+            .collect()
+    }
+
+    /// Best-first search of one layer, returning up to `ef` nodes closest first
+    fn search_layer(&self, query: &[f32], entry_points: &[u32], ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = entry_points.iter().copied().collect();
+        let mut candidates = BinaryHeap::new(); // Closest on top
+        let mut results = BinaryHeap::new(); // Farthest on top
+
+        for &node in entry_points {
+            let candidate = Candidate { distance: self.distance_to(query, node), node };
+            candidates.push(Reverse(candidate));
+            results.push(candidate);
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let farthest = results.peek().map_or(f32::INFINITY, |c: &Candidate| c.distance);
+            if results.len() >= ef && current.distance > farthest {
+                break;
+            }
+
+            for &neighbor in self.neighbors(current.node, layer) {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+
+                let distance = self.distance_to(query, neighbor);
+                let farthest = results.peek().map_or(f32::INFINITY, |c: &Candidate| c.distance);
+                if results.len() < ef || distance < farthest {
+                    let candidate = Candidate { distance, node: neighbor };
+                    candidates.push(Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Link `new` into a node's neighbour list, keeping only the closest
+    fn connect(&mut self, node: u32, new: u32, layer: usize) {
+        let max = max_neighbors(layer);
+        let mut neighbors = self.neighbors(node, layer).to_vec();
+        neighbors.push(new);
+
+        if neighbors.len() > max {
+            let base = &self.nodes[node as usize].vector;
+            let mut scored: Vec<Candidate> = neighbors
+                .iter()
+                .map(|&neighbor| Candidate {
+                    distance: self.metric.distance(base, &self.nodes[neighbor as usize].vector),
+                    node: neighbor,
+                })
+                .collect();
+            scored.sort();
+            neighbors = scored.into_iter().take(max).map(|candidate| candidate.node).collect();
+        }
+
+        self.nodes[node as usize].neighbors[layer] = neighbors;
+    }
+
+    /// Pick a node's top layer; each layer holds about 1/MAX_NEIGHBORS of the one below
+    fn random_level(&mut self) -> usize {
+        // splitmix64, so graphs build the same way every time
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        let uniform = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64; // (0, 1]
+        let level = -uniform.ln() / (MAX_NEIGHBORS as f64).ln();
+        (level as usize).min(MAX_LEVEL)
+    }
+
+    /// Rebuild the graph from its live vectors, dropping deleted ones
+    fn rebuild(&mut self) {
+        let mut graph = HnswGraph::new(self.metric);
+        graph.rng_state = self.rng_state;
+        for node in std::mem::take(&mut self.nodes) {
+            if !node.deleted {
+                graph.insert(node.key, node.vector);
+            }
+        }
+        *self = graph;
+    }
+}
+
+fn max_neighbors(layer: usize) -> usize {
+    if layer == 0 {
+        MAX_NEIGHBORS_BOTTOM
+    } else {
+        MAX_NEIGHBORS
+    }
+}
+
+/// A vector index's graph and the newest write it reflects (Phase 3.5+)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorIndexData {
+    /// Definition the graph was built for
+    pub definition: VectorIndex,
+    /// Vectors of the indexed items
+    pub graph: HnswGraph,
+    /// Highest sequence number applied to the graph
+    pub last_seq: SeqNo,
+}
+
+impl VectorIndexData {
+    /// Create an empty index
+    pub fn new(definition: VectorIndex) -> Self {
+        let graph = HnswGraph::new(definition.metric);
+        Self {
+            definition,
+            graph,
+            last_seq: 0,
+        }
+    }
+
+    /// Apply a write of `item` (`None` for a delete) to the index
+    pub fn apply(&mut self, key: &Key, item: Option<&Item>, seq: SeqNo) {
+        match item.and_then(|item| self.definition.vector_of(item)) {
+            Some(vector) => self.graph.insert(key.clone(), vector.to_vec()),
+            None => {
+                self.graph.remove(key);
+            }
+        }
+        self.last_seq = self.last_seq.max(seq);
+    }
+}
+
+/// Nearest-neighbour search result (Phase 3.5+)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorMatch {
+    /// Key of the matching item
+    pub key: Key,
+    /// The matching item
+    pub item: Item,
+    /// Distance from the query vector under the index's metric
+    pub distance: f32,
+}
+
+/// Write vector indexes to `path`, replacing the previous file atomically
+pub fn save_vector_indexes(path: &Path, indexes: &[VectorIndexData]) -> Result<()> {
+    let data = bincode::serialize(indexes)
+        .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+
+    let mut buf = Vec::with_capacity(data.len() + 4);
+    buf.extend_from_slice(&data);
+    buf.extend_from_slice(&crc32fast::hash(&data).to_le_bytes());
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &buf)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Read vector indexes saved by `save_vector_indexes`
+pub fn load_vector_indexes(path: &Path) -> Result<Vec<VectorIndexData>> {
+    let buf = fs::read(path)?;
+    if buf.len() < 4 {
+        return Err(Error::Corruption("Vector index file too short".to_string()));
+    }
+
+    let (data, crc) = buf.split_at(buf.len() - 4);
+    let expected = u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]);
+    if crc32fast::hash(data) != expected {
+        return Err(Error::ChecksumMismatch);
+    }
+
+    bincode::deserialize(data).map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn key(i: usize) -> Key {
+        Key::new(format!("item#{}", i).into_bytes())
+    }
+
+    /// Points on a line, so the nearest neighbours of any point are obvious
+    fn line_graph(count: usize) -> HnswGraph {
+        let mut graph = HnswGraph::new(DistanceMetric::Euclidean);
+        for i in 0..count {
+            graph.insert(key(i), vec![i as f32, 0.0]);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_distance_metrics() {
+        let a = [1.0, 0.0];
+        let b = [0.0, 1.0];
+
+        assert!(DistanceMetric::Cosine.distance(&a, &a).abs() < 1e-6);
+        assert!((DistanceMetric::Cosine.distance(&a, &b) - 1.0).abs() < 1e-6);
+        assert!((DistanceMetric::Euclidean.distance(&a, &b) - 2f32.sqrt()).abs() < 1e-6);
+        assert_eq!(DistanceMetric::DotProduct.distance(&[2.0, 3.0], &[4.0, 5.0]), -23.0);
+    }
+
+    #[test]
+    fn test_hnsw_search_finds_nearest() {
+        let graph = line_graph(500);
+        assert_eq!(graph.len(), 500);
+
+        let results = graph.search(&[250.2, 0.0], 3);
+        let keys: Vec<Key> = results.iter().map(|(key, _)| key.clone()).collect();
+        assert_eq!(keys, vec![key(250), key(251), key(249)]);
+        assert!(results[0].1 < results[1].1);
+    }
+
+    #[test]
+    fn test_hnsw_remove_and_replace() {
+        let mut graph = line_graph(100);
+
+        assert!(graph.remove(&key(50)));
+        assert!(!graph.remove(&key(50)));
+        assert_eq!(graph.search(&[50.0, 0.0], 1)[0].0, key(49));
+
+        // Re-inserting a key moves its vector
+        graph.insert(key(10), vec![50.1, 0.0]);
+        assert_eq!(graph.len(), 99);
+        assert_eq!(graph.search(&[50.0, 0.0], 1)[0].0, key(10));
+    }
+
+    #[test]
+    fn test_hnsw_rebuilds_after_many_deletes() {
+        let mut graph = line_graph(300);
+        for i in 0..200 {
+            graph.remove(&key(i));
+        }
+
+        assert_eq!(graph.len(), 100);
+        assert!(graph.deleted <= REBUILD_MIN_DELETED + 1);
+        assert_eq!(graph.search(&[0.0, 0.0], 1)[0].0, key(200));
+    }
+
+    #[test]
+    fn test_vector_index_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vectors.idx");
+
+        let definition = VectorIndex::new("embedding", 2, DistanceMetric::Euclidean);
+        let mut index = VectorIndexData::new(definition.clone());
+        let mut item = Item::new();
+        item.insert("embedding".to_string(), crate::Value::VecF32(vec![1.0, 2.0]));
+        index.apply(&key(1), Some(&item), 7);
+
+        save_vector_indexes(&path, &[index]).unwrap();
+        let loaded = load_vector_indexes(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].definition, definition);
+        assert_eq!(loaded[0].last_seq, 7);
+        assert_eq!(loaded[0].graph.search(&[1.0, 2.0], 1)[0].0, key(1));
+
+        // A damaged file is rejected rather than misread
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[0] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+        assert!(load_vector_indexes(&path).is_err());
+    }
+}
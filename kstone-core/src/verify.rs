@@ -0,0 +1,350 @@
+/// Consistency checker (Phase 8+)
+///
+/// Checks:
+/// - WAL entries: checksums and encoding
+/// - SSTs: checksums (and those of the value log blobs they point to), and
+///   cold SST stubs
+/// - Manifest: readable, and every SST it lists exists
+/// - Orphaned files: SST-like files recovery skips, and value log files no
+///   SST points into
+/// - Dangling index entries: entries no base item produces any more
+///
+/// `LsmEngine::verify` checks an open database. `verify_dir` runs the file
+/// checks on a closed one, including a database too damaged to open.
+///
+/// Repair moves damaged WAL entries, damaged SSTs and orphaned files to
+/// `<db>/quarantine/`, where they are kept for inspection; their items are
+/// gone from the database. Dangling index entries are deleted.
+
+use crate::{Error, Result};
+use crate::layout::Region;
+use crate::lock::DirLock;
+use crate::lsm::{sst_file_id, MANIFEST_FILE, MANIFEST_SIZE, WAL_FILE};
+use crate::manifest::{Manifest, ManifestRecord};
+use crate::sst::SstReader;
+use crate::tiering::{ColdSstStub, COLD_STUB_EXTENSION};
+use crate::vlog::VLOG_DIR;
+use crate::wal::{self, DamagedEntry};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory damaged and orphaned files are moved to, inside the database directory
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Kind of problem found by a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// A WAL entry that fails its checksum or can't be decoded
+    WalEntry,
+    /// An SST or cold SST stub that fails its checksum or can't be read
+    Sst,
+    /// An unreadable manifest, or an SST it lists that doesn't exist
+    Manifest,
+    /// A file the database doesn't use
+    OrphanedFile,
+    /// An index entry no base item produces
+    DanglingIndexEntry,
+}
+
+/// One problem found by a check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    /// File (or index) the problem is in
+    pub location: String,
+    /// What is wrong
+    pub detail: String,
+    /// Whether the repair fixed it
+    pub repaired: bool,
+}
+
+/// Result of a consistency check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Intact WAL entries
+    pub wal_entries: u64,
+    /// SSTs and cold SST stubs checked
+    pub sst_files: usize,
+    /// Live index entries checked (only by `LsmEngine::verify`)
+    pub index_entries: u64,
+    pub problems: Vec<Problem>,
+}
+
+impl VerifyReport {
+    /// Whether no problems were found
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Problems the repair didn't fix (all of them without repair)
+    pub fn unrepaired(&self) -> impl Iterator<Item = &Problem> {
+        self.problems.iter().filter(|problem| !problem.repaired)
+    }
+
+    pub(crate) fn add(&mut self, kind: ProblemKind, location: impl Into<String>, detail: impl Into<String>) -> &mut Problem {
+        self.problems.push(Problem {
+            kind,
+            location: location.into(),
+            detail: detail.into(),
+            repaired: false,
+        });
+        self.problems.last_mut().expect("just pushed")
+    }
+}
+
+/// Run the file checks on the closed database in `dir`
+///
+/// Fails with `Error::DatabaseLocked` if the database is open. With `repair`,
+/// damaged and orphaned files are quarantined; afterwards the database can
+/// be opened and `LsmEngine::verify` can check its indexes.
+pub fn verify_dir(dir: impl AsRef<Path>, repair: bool) -> Result<VerifyReport> {
+    let dir = dir.as_ref();
+    if !dir.join(WAL_FILE).exists() {
+        return Err(Error::InvalidArgument(format!(
+            "{} is not a database directory (missing {})",
+            dir.display(),
+            WAL_FILE
+        )));
+    }
+    let _lock = DirLock::acquire(dir)?;
+    let mut report = VerifyReport::default();
+
+    let damaged = check_wal(dir, &mut report)?;
+    if repair && !damaged.is_empty() {
+        wal::remove_entries(dir.join(WAL_FILE), &damaged, dir.join(QUARANTINE_DIR))?;
+        mark_repaired(&mut report, ProblemKind::WalEntry);
+    }
+
+    let mut ssts = Vec::new();
+    let mut unreadable_stripes = BTreeSet::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let stripe = match sst_file_id(&path) {
+            Some((stripe, _)) => stripe,
+            None => continue,
+        };
+        report.sst_files += 1;
+        match read_sst_file(&path) {
+            Ok(value_log_files) => ssts.push((stripe, value_log_files)),
+            Err(err) => {
+                unreadable_stripes.insert(stripe);
+                let problem = report.add(ProblemKind::Sst, path.display().to_string(), err.to_string());
+                if repair {
+                    quarantine(dir, &path)?;
+                    problem.repaired = true;
+                }
+            }
+        }
+    }
+
+    let manifest_path = dir.join(MANIFEST_FILE);
+    if manifest_path.exists() {
+        match Manifest::open(&manifest_path, Region::new(0, MANIFEST_SIZE)) {
+            Ok(manifest) => check_manifest(&manifest, dir, repair, &mut report)?,
+            Err(err) => {
+                report.add(ProblemKind::Manifest, manifest_path.display().to_string(), err.to_string());
+            }
+        }
+    }
+
+    let mut referenced = BTreeMap::new();
+    for (stripe, value_log_files) in ssts {
+        referenced.entry(stripe).or_insert_with(BTreeSet::new).extend(value_log_files);
+    }
+    // A damaged SST's value log files can't be told apart from orphans
+    check_orphans(dir, &referenced, &unreadable_stripes, repair, &mut report)?;
+
+    Ok(report)
+}
+
+/// Check every WAL entry, returning the damaged ones
+pub(crate) fn check_wal(dir: &Path, report: &mut VerifyReport) -> Result<Vec<DamagedEntry>> {
+    let path = dir.join(WAL_FILE);
+    let (intact, damaged) = match wal::check_entries(&path) {
+        Ok(result) => result,
+        Err(err) => {
+            report.add(ProblemKind::WalEntry, path.display().to_string(), err.to_string());
+            return Ok(Vec::new());
+        }
+    };
+
+    report.wal_entries = intact;
+    for entry in &damaged {
+        report.add(
+            ProblemKind::WalEntry,
+            path.display().to_string(),
+            format!("entry at offset {} (LSN {}): {}", entry.offset, entry.lsn, entry.reason),
+        );
+    }
+    Ok(damaged)
+}
+
+/// Read an SST or cold SST stub back from disk, returning the value log files it points into
+pub(crate) fn read_sst_file(path: &Path) -> Result<BTreeSet<u64>> {
+    if path.extension().map_or(false, |ext| ext == COLD_STUB_EXTENSION) {
+        Ok(ColdSstStub::read(path)?.value_log_files)
+    } else {
+        Ok(SstReader::open(path)?.value_log_files())
+    }
+}
+
+/// Check that the manifest of the database in `dir` lists only SSTs that exist
+pub(crate) fn check_manifest(manifest: &Manifest, dir: &Path, repair: bool, report: &mut VerifyReport) -> Result<()> {
+    let path = dir.join(MANIFEST_FILE);
+    let mut removed = false;
+    for sst in manifest.state().ssts.values() {
+        let sst_path = dir.join(format!("{:03}-{}.sst", sst.stripe, sst.sst_id));
+        if sst_path.exists() || sst_path.with_extension(COLD_STUB_EXTENSION).exists() {
+            continue;
+        }
+        let problem = report.add(
+            ProblemKind::Manifest,
+            path.display().to_string(),
+            format!("lists missing SST {}", sst_path.display()),
+        );
+        if repair {
+            manifest.append(ManifestRecord::RemoveSst { sst_id: sst.sst_id })?;
+            problem.repaired = true;
+            removed = true;
+        }
+    }
+    if removed {
+        manifest.flush()?;
+    }
+    Ok(())
+}
+
+/// Report SST-like files recovery skips, and value log files no SST points into
+///
+/// `referenced` holds, per stripe, the value log files its SSTs point into.
+/// The value log files of `unchecked` stripes are left alone.
+pub(crate) fn check_orphans(
+    dir: &Path,
+    referenced: &BTreeMap<usize, BTreeSet<u64>>,
+    unchecked: &BTreeSet<usize>,
+    repair: bool,
+    report: &mut VerifyReport,
+) -> Result<()> {
+    let mut orphans = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let sst_like = path
+            .extension()
+            .map_or(false, |ext| ext == "sst" || ext == COLD_STUB_EXTENSION);
+        if sst_like && sst_file_id(&path).is_none() {
+            orphans.push((path, "not named like an SST, so never loaded"));
+        }
+    }
+
+    let vlog_dir = dir.join(VLOG_DIR);
+    if vlog_dir.exists() {
+        for entry in fs::read_dir(&vlog_dir)? {
+            let path = entry?.path();
+            let parsed = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split_once('-'))
+                .and_then(|(stripe, id)| Some((stripe.parse::<usize>().ok()?, id.parse::<u64>().ok()?)));
+            let orphaned = match parsed {
+                Some((stripe, _)) if unchecked.contains(&stripe) => false,
+                Some((stripe, id)) => !referenced.get(&stripe).map_or(false, |ids| ids.contains(&id)),
+                None => true,
+            };
+            if orphaned {
+                orphans.push((path, "value log file no SST points into"));
+            }
+        }
+    }
+
+    for (path, detail) in orphans {
+        let problem = report.add(ProblemKind::OrphanedFile, path.display().to_string(), detail);
+        if repair {
+            quarantine(dir, &path)?;
+            problem.repaired = true;
+        }
+    }
+    Ok(())
+}
+
+/// Move a file of the database in `dir` to its quarantine directory
+pub(crate) fn quarantine(dir: &Path, path: &Path) -> Result<PathBuf> {
+    let quarantine_dir = dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&quarantine_dir)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::Internal(format!("Invalid database file path: {}", path.display())))?;
+    let target = quarantine_dir.join(name);
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+fn mark_repaired(report: &mut VerifyReport, kind: ProblemKind) {
+    for problem in report.problems.iter_mut().filter(|problem| problem.kind == kind) {
+        problem.repaired = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, LsmEngine, Value};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn put(db: &LsmEngine, pk: &str) {
+        let mut item = HashMap::new();
+        item.insert("v".to_string(), Value::string(pk));
+        db.put(Key::new(pk.as_bytes().to_vec()), item).unwrap();
+    }
+
+    #[test]
+    fn test_verify_dir_clean() {
+        let dir = TempDir::new().unwrap();
+        {
+            let db = LsmEngine::create(dir.path()).unwrap();
+            put(&db, "a");
+            db.flush().unwrap();
+            put(&db, "b");
+        }
+
+        let report = verify_dir(dir.path(), false).unwrap();
+        assert!(report.is_clean(), "{:?}", report.problems);
+        assert!(report.wal_entries >= 2);
+        assert_eq!(report.sst_files, 1);
+    }
+
+    #[test]
+    fn test_verify_dir_quarantines_damage() {
+        let dir = TempDir::new().unwrap();
+        {
+            let db = LsmEngine::create(dir.path()).unwrap();
+            put(&db, "a");
+            db.flush().unwrap();
+            put(&db, "b");
+        }
+
+        // Flip a byte in the middle of the first WAL entry's data
+        let wal_path = dir.path().join(WAL_FILE);
+        let mut wal = fs::read(&wal_path).unwrap();
+        wal[16 + 12 + 4] ^= 0xFF;
+        fs::write(&wal_path, wal).unwrap();
+        fs::write(dir.path().join("stray.sst"), b"junk").unwrap();
+        assert!(LsmEngine::open(dir.path()).is_err());
+
+        let report = verify_dir(dir.path(), false).unwrap();
+        let kinds: Vec<ProblemKind> = report.problems.iter().map(|problem| problem.kind).collect();
+        assert_eq!(kinds, vec![ProblemKind::WalEntry, ProblemKind::OrphanedFile]);
+        assert_eq!(report.unrepaired().count(), 2);
+
+        let report = verify_dir(dir.path(), true).unwrap();
+        assert_eq!(report.unrepaired().count(), 0);
+        assert!(dir.path().join(QUARANTINE_DIR).join("stray.sst").exists());
+        assert!(verify_dir(dir.path(), false).unwrap().is_clean());
+
+        // Only the damaged entry is lost
+        let db = LsmEngine::open(dir.path()).unwrap();
+        assert!(db.get(&Key::new(b"a".to_vec())).unwrap().is_some());
+        assert!(db.get(&Key::new(b"b".to_vec())).unwrap().is_some());
+    }
+}
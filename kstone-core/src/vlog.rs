@@ -0,0 +1,250 @@
+/// Value log for large binary values (Phase 8+)
+///
+/// WiscKey-style key/value separation: when `DatabaseConfig::value_log_threshold_bytes`
+/// is set, top-level `Value::B` attributes at least that large are written to
+/// a value log file as the SST is written, and the SST stores only a
+/// `BlobPointer`. Compaction carries pointers over instead of rewriting the
+/// blobs, so large values are written once no matter how often their SSTs
+/// are merged.
+///
+/// Each flush or compaction writes its blobs to its own file, named after the
+/// SST that first referenced them, so a file is only ever referenced by SSTs
+/// of a single stripe. A file is garbage collected once compaction leaves no
+/// SST of that stripe pointing into it; files a flush is still writing are
+/// never referenced by the compacted SSTs, so they are left alone.
+///
+/// Directory: `<db>/vlog/`
+/// File: `{stripe:03}-{file_id}.vlog`
+/// Blob: [bytes] (length and CRC32C live in the pointer)
+
+use crate::{Error, Result};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Directory holding value log files, relative to the database directory
+pub const VLOG_DIR: &str = "vlog";
+
+const VLOG_EXTENSION: &str = "vlog";
+
+/// Attributes of a record stored in the value log, by attribute name
+pub type BlobRefs = Vec<(String, BlobPointer)>;
+
+/// Location of a blob in the value log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobPointer {
+    /// Stripe whose SSTs reference the file
+    pub stripe: u16,
+    /// Value log file ID (the ID of the SST that wrote it)
+    pub file_id: u64,
+    /// Byte offset of the blob in the file
+    pub offset: u64,
+    /// Blob length in bytes
+    pub len: u32,
+    /// CRC32C of the blob
+    pub crc: u32,
+}
+
+/// Handle to the value log directory of one database
+#[derive(Debug, Clone)]
+pub struct ValueLog {
+    dir: PathBuf,
+}
+
+impl ValueLog {
+    /// Value log of the database in `db_dir`
+    ///
+    /// The directory is created lazily when the first blob is written.
+    pub fn new(db_dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: db_dir.as_ref().join(VLOG_DIR),
+        }
+    }
+
+    /// Value log directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file_path(&self, stripe: u16, file_id: u64) -> PathBuf {
+        self.dir.join(format!("{:03}-{}.{}", stripe, file_id, VLOG_EXTENSION))
+    }
+
+    /// Start a new value log file; nothing is created until the first append
+    pub fn writer(&self, stripe: u16, file_id: u64) -> ValueLogWriter {
+        ValueLogWriter {
+            path: self.file_path(stripe, file_id),
+            stripe,
+            file_id,
+            file: None,
+            offset: 0,
+        }
+    }
+
+    /// Read a blob, verifying its checksum
+    pub fn read(&self, pointer: &BlobPointer) -> Result<Bytes> {
+        let path = self.file_path(pointer.stripe, pointer.file_id);
+        let mut file = File::open(&path).map_err(|e| {
+            Error::Corruption(format!("Value log file {} unreadable: {}", path.display(), e))
+        })?;
+        file.seek(SeekFrom::Start(pointer.offset))?;
+
+        let mut data = vec![0u8; pointer.len as usize];
+        file.read_exact(&mut data)?;
+        if crc32c::crc32c(&data) != pointer.crc {
+            return Err(Error::ChecksumMismatch);
+        }
+        Ok(Bytes::from(data))
+    }
+
+    /// Value log files of one stripe
+    pub fn files(&self, stripe: u16) -> Result<BTreeSet<u64>> {
+        let mut ids = BTreeSet::new();
+        if !self.dir.exists() {
+            return Ok(ids);
+        }
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == VLOG_EXTENSION) {
+                let parsed = path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.split_once('-'))
+                    .and_then(|(s, id)| Some((s.parse::<u16>().ok()?, id.parse::<u64>().ok()?)));
+                if let Some((file_stripe, id)) = parsed {
+                    if file_stripe == stripe {
+                        ids.insert(id);
+                    }
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Delete value log files of a stripe, returning the number of bytes freed
+    ///
+    /// Files that are already gone are skipped.
+    pub fn remove_files<'a>(&self, stripe: u16, file_ids: impl IntoIterator<Item = &'a u64>) -> Result<u64> {
+        let mut freed = 0;
+        for id in file_ids {
+            let path = self.file_path(stripe, *id);
+            if path.exists() {
+                freed += fs::metadata(&path)?.len();
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(freed)
+    }
+
+    /// Total size of all value log files in bytes
+    pub fn size_bytes(&self) -> Result<u64> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        let mut total = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            total += entry?.metadata()?.len();
+        }
+        Ok(total)
+    }
+}
+
+/// Appends blobs to a single value log file
+pub struct ValueLogWriter {
+    path: PathBuf,
+    stripe: u16,
+    file_id: u64,
+    file: Option<File>,
+    offset: u64,
+}
+
+impl ValueLogWriter {
+    /// Append a blob, returning a pointer to it
+    pub fn append(&mut self, data: &[u8]) -> Result<BlobPointer> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| Error::InvalidArgument(format!("Blob of {} bytes is too large for the value log", data.len())))?;
+
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            self.file = Some(OpenOptions::new().write(true).create_new(true).open(&self.path)?);
+        }
+        let file = self.file.as_mut().expect("value log file is open");
+        file.write_all(data)?;
+
+        let pointer = BlobPointer {
+            stripe: self.stripe,
+            file_id: self.file_id,
+            offset: self.offset,
+            len,
+            crc: crc32c::crc32c(data),
+        };
+        self.offset += data.len() as u64;
+        Ok(pointer)
+    }
+
+    /// Make appended blobs durable; must happen before the SST pointing at them is written
+    pub fn finish(self) -> Result<()> {
+        if let Some(file) = self.file {
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_vlog_append_read() {
+        let dir = TempDir::new().unwrap();
+        let vlog = ValueLog::new(dir.path());
+
+        let mut writer = vlog.writer(7, 1);
+        let a = writer.append(b"first blob").unwrap();
+        let b = writer.append(b"second").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(b.offset, 10);
+        assert_eq!(vlog.read(&a).unwrap(), Bytes::from_static(b"first blob"));
+        assert_eq!(vlog.read(&b).unwrap(), Bytes::from_static(b"second"));
+        assert_eq!(vlog.files(7).unwrap(), BTreeSet::from([1]));
+        assert!(vlog.files(8).unwrap().is_empty());
+
+        let bad = BlobPointer { crc: a.crc ^ 1, ..a };
+        assert!(matches!(vlog.read(&bad), Err(Error::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn test_vlog_writer_without_appends_creates_nothing() {
+        let dir = TempDir::new().unwrap();
+        let vlog = ValueLog::new(dir.path());
+        vlog.writer(0, 1).finish().unwrap();
+        assert!(!vlog.dir().exists());
+        assert_eq!(vlog.size_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_vlog_remove_files() {
+        let dir = TempDir::new().unwrap();
+        let vlog = ValueLog::new(dir.path());
+        for id in 1..=3 {
+            let mut writer = vlog.writer(0, id);
+            writer.append(b"blob").unwrap();
+            writer.finish().unwrap();
+        }
+        let mut other = vlog.writer(1, 4);
+        other.append(b"blob").unwrap();
+        other.finish().unwrap();
+
+        let freed = vlog.remove_files(0, &[1, 3, 5]).unwrap();
+        assert_eq!(freed, 8);
+        assert_eq!(vlog.files(0).unwrap(), BTreeSet::from([2]));
+        assert_eq!(vlog.files(1).unwrap(), BTreeSet::from([4]));
+    }
+}
@@ -1,26 +1,74 @@
 use crate::{Error, Result, Record, Lsn};
+use crate::config::WalSyncMode;
 use bytes::{BytesMut, BufMut};
 use parking_lot::Mutex;
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write, Seek, SeekFrom};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Read, Write, Seek, SeekFrom};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const WAL_HEADER_SIZE: usize = 16;
 const WAL_MAGIC: u32 = 0x57414C00; // "WAL\0"
 const RECORD_HEADER_SIZE: usize = 12; // lsn(8) + len(4)
+/// Set in the length field when the entry holds a batch of records (Phase 8+)
+const BATCH_FLAG: u32 = 1 << 31;
 
 /// Minimal WAL for walking skeleton
 /// Format: [magic(4) | version(4) | reserved(8)] [record...]
 /// Record: [lsn(8) | len(4) | data | crc(4)]
+///
+/// A batch entry sets the high bit of `len` and holds several records under
+/// one LSN and one checksum, so recovery replays either all of them or none.
+///
+/// Writing and syncing are separate steps (Phase 8+): `write` hands pending
+/// records to the OS, and `sync_to` makes them durable according to the
+/// configured `WalSyncMode`. Cloned handles share the same log.
+#[derive(Clone)]
 pub struct Wal {
     inner: Arc<Mutex<WalInner>>,
+    sync: Arc<Mutex<SyncState>>,
+}
+
+/// One log entry waiting to be written
+enum WalEntry {
+    Record(Record),
+    Batch(Vec<Record>),
 }
 
 struct WalInner {
     file: File,
     next_lsn: Lsn,
-    pending: Vec<Record>,
+    pending: Vec<(Lsn, WalEntry)>,
+    batch: Option<(Lsn, Vec<Record>)>, // Open batch collecting appends
+    written_lsn: Lsn, // Highest LSN handed to the OS
+    sync_mode: WalSyncMode,
+    read_only: bool,
+}
+
+impl WalInner {
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly("WAL opened read-only".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Group commit state, guarded separately so writers can keep appending
+/// while an fsync is in flight
+struct SyncState {
+    synced_lsn: Lsn, // Highest LSN known to be on disk
+    last_sync: Instant,
+}
+
+impl SyncState {
+    fn new(synced_lsn: Lsn) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            synced_lsn,
+            last_sync: Instant::now(),
+        }))
+    }
 }
 
 impl Wal {
@@ -45,15 +93,32 @@ impl Wal {
                 file,
                 next_lsn: 1,
                 pending: Vec::new(),
+                batch: None,
+                written_lsn: 0,
+                sync_mode: WalSyncMode::default(),
+                read_only: false,
             })),
+            sync: SyncState::new(0),
         })
     }
 
     /// Open existing WAL file
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with(path, false)
+    }
+
+    /// Open an existing WAL file for recovery only
+    ///
+    /// The file is never modified (a torn tail is left in place) and appends
+    /// fail with `Error::ReadOnly`.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with(path, true)
+    }
+
+    fn open_with(path: impl AsRef<Path>, read_only: bool) -> Result<Self> {
         let mut file = OpenOptions::new()
             .read(true)
-            .write(true)
+            .write(!read_only)
             .open(path)?;
 
         // Verify header
@@ -65,7 +130,9 @@ impl Wal {
         }
 
         // Scan to find last LSN
-        file.seek(SeekFrom::Start(WAL_HEADER_SIZE as u64))?;
+        let file_len = file.metadata()?.len();
+        let mut offset = WAL_HEADER_SIZE as u64;
+        file.seek(SeekFrom::Start(offset))?;
         let mut max_lsn = 0u64;
 
         loop {
@@ -76,43 +143,120 @@ impl Wal {
                         rec_header[0], rec_header[1], rec_header[2], rec_header[3],
                         rec_header[4], rec_header[5], rec_header[6], rec_header[7],
                     ]);
-                    let len = u32::from_le_bytes([
+                    let len = (u32::from_le_bytes([
                         rec_header[8], rec_header[9], rec_header[10], rec_header[11],
-                    ]) as u64;
+                    ]) & !BATCH_FLAG) as u64;
+
+                    // Stop at a torn final entry
+                    let entry_end = offset + RECORD_HEADER_SIZE as u64 + len + 4;
+                    if entry_end > file_len {
+                        break;
+                    }
 
                     max_lsn = max_lsn.max(lsn);
 
                     // Skip data + crc
-                    file.seek(SeekFrom::Current(len as i64 + 4))?;
+                    offset = entry_end;
+                    file.seek(SeekFrom::Start(offset))?;
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
                 Err(e) => return Err(e.into()),
             }
         }
 
+        // Drop any torn tail so new entries follow the last complete one
+        if offset < file_len && !read_only {
+            file.set_len(offset)?;
+            file.sync_all()?;
+        }
+
         Ok(Self {
             inner: Arc::new(Mutex::new(WalInner {
                 file,
                 next_lsn: max_lsn + 1,
                 pending: Vec::new(),
+                batch: None,
+                written_lsn: max_lsn,
+                sync_mode: WalSyncMode::default(),
+                read_only,
             })),
+            sync: SyncState::new(max_lsn),
         })
     }
 
     /// Append a record (buffered, not yet durable)
+    ///
+    /// While a batch is open the record joins the batch and shares its LSN.
     pub fn append(&self, record: Record) -> Result<Lsn> {
         let mut inner = self.inner.lock();
+        inner.check_writable()?;
+        if let Some((lsn, records)) = inner.batch.as_mut() {
+            records.push(record);
+            return Ok(*lsn);
+        }
+
         let lsn = inner.next_lsn;
         inner.next_lsn += 1;
-        inner.pending.push(record);
+        inner.pending.push((lsn, WalEntry::Record(record)));
         Ok(lsn)
     }
 
-    /// Flush pending records to disk (group commit)
+    /// Start collecting appends into a single atomic entry (Phase 8+)
+    pub fn begin_batch(&self) -> Result<Lsn> {
+        let mut inner = self.inner.lock();
+        inner.check_writable()?;
+        if inner.batch.is_some() {
+            return Err(Error::Internal("WAL batch already open".to_string()));
+        }
+
+        let lsn = inner.next_lsn;
+        inner.next_lsn += 1;
+        inner.batch = Some((lsn, Vec::new()));
+        Ok(lsn)
+    }
+
+    /// Close the open batch, queueing it as one entry
+    pub fn end_batch(&self) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let (lsn, records) = inner.batch.take()
+            .ok_or_else(|| Error::Internal("No WAL batch open".to_string()))?;
+        inner.pending.push((lsn, WalEntry::Batch(records)));
+        Ok(())
+    }
+
+    /// Drop the open batch without logging any of its records
+    pub fn abort_batch(&self) {
+        self.inner.lock().batch = None;
+    }
+
+    /// Check whether a batch is open
+    pub fn in_batch(&self) -> bool {
+        self.inner.lock().batch.is_some()
+    }
+
+    /// Set when writes become durable (Phase 8+)
+    pub fn set_sync_mode(&self, mode: WalSyncMode) {
+        self.inner.lock().sync_mode = mode;
+    }
+
+    /// Get the current sync mode
+    pub fn sync_mode(&self) -> WalSyncMode {
+        self.inner.lock().sync_mode
+    }
+
+    /// Flush pending records to disk, regardless of sync mode
     pub fn flush(&self) -> Result<()> {
+        let lsn = self.write()?;
+        self.sync_through(lsn)
+    }
+
+    /// Write pending records to the file without forcing them to disk
+    ///
+    /// Returns the highest LSN written so far, to pass to `sync_to`.
+    pub fn write(&self) -> Result<Lsn> {
         let mut inner = self.inner.lock();
         if inner.pending.is_empty() {
-            return Ok(());
+            return Ok(inner.written_lsn);
         }
 
         // Seek to end
@@ -120,30 +264,87 @@ impl Wal {
 
         // Prepare all records into a single buffer
         let mut full_buf = BytesMut::new();
-        let base_lsn = inner.next_lsn - inner.pending.len() as u64;
-
-        for (i, record) in inner.pending.iter().enumerate() {
-            let lsn = base_lsn + i as u64;
-
-            let data = bincode::serialize(record)
-                .map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+        let mut last_lsn = inner.written_lsn;
+
+        for (lsn, entry) in &inner.pending {
+            let (data, flag) = match entry {
+                WalEntry::Record(record) => (bincode::serialize(record), 0),
+                WalEntry::Batch(records) => (bincode::serialize(records), BATCH_FLAG),
+            };
+            let data = data.map_err(|e| Error::Internal(format!("Serialize error: {}", e)))?;
+            if data.len() as u64 >= BATCH_FLAG as u64 {
+                return Err(Error::InvalidArgument("WAL entry too large".to_string()));
+            }
             let crc = crc32fast::hash(&data);
 
-            full_buf.put_u64_le(lsn);
-            full_buf.put_u32_le(data.len() as u32);
+            full_buf.put_u64_le(*lsn);
+            full_buf.put_u32_le(data.len() as u32 | flag);
             full_buf.put_slice(&data);
             full_buf.put_u32_le(crc);
+            last_lsn = last_lsn.max(*lsn);
         }
 
         // Write all at once
         inner.file.write_all(&full_buf)?;
 
-        inner.file.sync_all()?;
+        inner.written_lsn = last_lsn;
         inner.pending.clear();
 
+        Ok(inner.written_lsn)
+    }
+
+    /// Make everything up to `lsn` durable as the sync mode requires
+    ///
+    /// - `Always`: fsync before returning (shared with concurrent callers)
+    /// - `EveryNms`: fsync only if the interval has elapsed since the last one
+    /// - `Os`: never fsync; the OS writes the data back on its own schedule
+    pub fn sync_to(&self, lsn: Lsn) -> Result<()> {
+        match self.sync_mode() {
+            WalSyncMode::Always => self.sync_through(lsn),
+            WalSyncMode::EveryNms(interval_ms) => {
+                if self.sync.lock().last_sync.elapsed() >= Duration::from_millis(interval_ms) {
+                    self.sync_through(lsn)
+                } else {
+                    Ok(())
+                }
+            }
+            WalSyncMode::Os => Ok(()),
+        }
+    }
+
+    /// Fsync everything written so far (group commit)
+    ///
+    /// Callers queue on the sync lock. The first one syncs every record
+    /// written up to that point, so callers behind it whose LSN is already
+    /// covered return without issuing another fsync.
+    fn sync_through(&self, lsn: Lsn) -> Result<()> {
+        let mut sync = self.sync.lock();
+        if sync.synced_lsn >= lsn {
+            return Ok(());
+        }
+
+        let (file, target) = {
+            let inner = self.inner.lock();
+            (inner.file.try_clone()?, inner.written_lsn)
+        };
+        file.sync_data()?;
+
+        sync.synced_lsn = target;
+        sync.last_sync = Instant::now();
         Ok(())
     }
 
+    /// Fsync everything already written, leaving pending records alone
+    pub fn sync(&self) -> Result<()> {
+        let lsn = self.inner.lock().written_lsn;
+        self.sync_through(lsn)
+    }
+
+    /// Highest LSN known to be on disk
+    pub fn synced_lsn(&self) -> Lsn {
+        self.sync.lock().synced_lsn
+    }
+
     /// Read all records from WAL
     pub fn read_all(&self) -> Result<Vec<(Lsn, Record)>> {
         let inner = self.inner.lock();
@@ -161,15 +362,21 @@ impl Wal {
                         rec_header[0], rec_header[1], rec_header[2], rec_header[3],
                         rec_header[4], rec_header[5], rec_header[6], rec_header[7],
                     ]);
-                    let len = u32::from_le_bytes([
+                    let raw_len = u32::from_le_bytes([
                         rec_header[8], rec_header[9], rec_header[10], rec_header[11],
-                    ]) as usize;
+                    ]);
+                    let len = (raw_len & !BATCH_FLAG) as usize;
 
+                    // A crash mid-write leaves a torn final entry; everything
+                    // before it is intact, so recovery stops there
                     let mut data = vec![0u8; len];
-                    file.read_exact(&mut data)?;
-
                     let mut crc_bytes = [0u8; 4];
-                    file.read_exact(&mut crc_bytes)?;
+                    match file.read_exact(&mut data).and_then(|_| file.read_exact(&mut crc_bytes)) {
+                        Ok(_) => {}
+                        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                        Err(e) => return Err(e.into()),
+                    }
+
                     let expected_crc = u32::from_le_bytes(crc_bytes);
                     let actual_crc = crc32fast::hash(&data);
 
@@ -177,10 +384,15 @@ impl Wal {
                         return Err(Error::ChecksumMismatch);
                     }
 
-                    let record: Record = bincode::deserialize(&data)
-                        .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
-
-                    records.push((lsn, record));
+                    if raw_len & BATCH_FLAG != 0 {
+                        let batch: Vec<Record> = bincode::deserialize(&data)
+                            .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
+                        records.extend(batch.into_iter().map(|record| (lsn, record)));
+                    } else {
+                        let record: Record = bincode::deserialize(&data)
+                            .map_err(|e| Error::Corruption(format!("Deserialize error: {}", e)))?;
+                        records.push((lsn, record));
+                    }
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
                 Err(e) => return Err(e.into()),
@@ -195,6 +407,103 @@ impl Wal {
     }
 }
 
+/// A WAL entry that fails its checksum or can't be decoded (Phase 8+)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamagedEntry {
+    /// LSN in the entry header (which may itself be damaged)
+    pub lsn: Lsn,
+    /// Byte offset of the entry in the file
+    pub offset: u64,
+    /// Length of the entry in bytes, header and checksum included
+    pub len: u64,
+    pub reason: String,
+}
+
+/// Check every entry of a WAL file, continuing past damaged ones (Phase 8+)
+///
+/// Returns the number of intact entries and the damaged ones. A torn final
+/// entry is what a crash mid-write leaves behind and isn't reported.
+pub fn check_entries(path: impl AsRef<Path>) -> Result<(u64, Vec<DamagedEntry>)> {
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; WAL_HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    if u32::from_be_bytes([header[0], header[1], header[2], header[3]]) != WAL_MAGIC {
+        return Err(Error::Corruption("Invalid WAL magic".to_string()));
+    }
+
+    let mut intact = 0;
+    let mut damaged = Vec::new();
+    let mut offset = WAL_HEADER_SIZE as u64;
+    loop {
+        let mut rec_header = [0u8; RECORD_HEADER_SIZE];
+        match reader.read_exact(&mut rec_header) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let lsn = u64::from_le_bytes(rec_header[..8].try_into().unwrap());
+        let raw_len = u32::from_le_bytes(rec_header[8..].try_into().unwrap());
+        let len = (raw_len & !BATCH_FLAG) as u64;
+
+        let entry_len = RECORD_HEADER_SIZE as u64 + len + 4;
+        if offset + entry_len > file_len {
+            break; // Torn tail
+        }
+
+        let mut data = vec![0u8; len as usize];
+        let mut crc_bytes = [0u8; 4];
+        reader.read_exact(&mut data)?;
+        reader.read_exact(&mut crc_bytes)?;
+
+        let reason = if u32::from_le_bytes(crc_bytes) != crc32fast::hash(&data) {
+            Some("checksum mismatch".to_string())
+        } else if raw_len & BATCH_FLAG != 0 {
+            bincode::deserialize::<Vec<Record>>(&data).err().map(|e| format!("undecodable batch: {}", e))
+        } else {
+            bincode::deserialize::<Record>(&data).err().map(|e| format!("undecodable record: {}", e))
+        };
+        match reason {
+            Some(reason) => damaged.push(DamagedEntry { lsn, offset, len: entry_len, reason }),
+            None => intact += 1,
+        }
+        offset += entry_len;
+    }
+
+    Ok((intact, damaged))
+}
+
+/// Rewrite a WAL file without some of its entries (Phase 8+)
+///
+/// `entries` are in file order, as `check_entries` returns them. Each
+/// removed entry is saved as `wal-{offset}.entry` in `quarantine`. The WAL
+/// must not be open; the file is replaced atomically.
+pub fn remove_entries(path: impl AsRef<Path>, entries: &[DamagedEntry], quarantine: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let quarantine = quarantine.as_ref();
+    let data = fs::read(path)?;
+
+    fs::create_dir_all(quarantine)?;
+    let mut kept = Vec::with_capacity(data.len());
+    let mut start = 0usize;
+    for entry in entries {
+        let (offset, end) = (entry.offset as usize, (entry.offset + entry.len) as usize);
+        kept.extend_from_slice(&data[start..offset]);
+        fs::write(quarantine.join(format!("wal-{}.entry", entry.offset)), &data[offset..end])?;
+        start = end;
+    }
+    kept.extend_from_slice(&data[start..]);
+
+    let tmp_path = path.with_extension("repair");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&kept)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +575,114 @@ mod tests {
         let records = wal.read_all().unwrap();
         assert_eq!(records.len(), 10);
     }
+
+    #[test]
+    fn test_wal_sync_modes() {
+        let tmp = TempDir::new().unwrap();
+        let wal = Wal::create(tmp.path().join("wal.log")).unwrap();
+        assert_eq!(wal.sync_mode(), WalSyncMode::Always);
+
+        let record = |i: u64| Record::put(Key::new(format!("key{}", i).into_bytes()), HashMap::new(), i);
+
+        // Always: durable once sync_to returns
+        wal.append(record(1)).unwrap();
+        let lsn = wal.write().unwrap();
+        wal.sync_to(lsn).unwrap();
+        assert_eq!(wal.synced_lsn(), 1);
+
+        // Os: written but never synced by sync_to
+        wal.set_sync_mode(WalSyncMode::Os);
+        wal.append(record(2)).unwrap();
+        let lsn = wal.write().unwrap();
+        wal.sync_to(lsn).unwrap();
+        assert_eq!(lsn, 2);
+        assert_eq!(wal.synced_lsn(), 1);
+
+        // Interval not yet elapsed: sync is deferred
+        wal.set_sync_mode(WalSyncMode::EveryNms(60_000));
+        wal.sync_to(lsn).unwrap();
+        assert_eq!(wal.synced_lsn(), 1);
+
+        // flush always forces the data to disk
+        wal.flush().unwrap();
+        assert_eq!(wal.synced_lsn(), 2);
+        assert_eq!(wal.read_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_wal_group_commit_covers_earlier_writes() {
+        let tmp = TempDir::new().unwrap();
+        let wal = Wal::create(tmp.path().join("wal.log")).unwrap();
+
+        for i in 1..=3 {
+            wal.append(Record::put(Key::new(format!("key{}", i).into_bytes()), HashMap::new(), i)).unwrap();
+            wal.write().unwrap();
+        }
+
+        // One sync covers every record written before it
+        wal.sync_to(3).unwrap();
+        assert_eq!(wal.synced_lsn(), 3);
+        wal.sync_to(1).unwrap();
+        assert_eq!(wal.synced_lsn(), 3);
+    }
+
+    #[test]
+    fn test_wal_batch_is_one_entry() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("wal.log");
+
+        {
+            let wal = Wal::create(&path).unwrap();
+            wal.append(Record::put(Key::new(b"single".to_vec()), HashMap::new(), 1)).unwrap();
+
+            let batch_lsn = wal.begin_batch().unwrap();
+            assert!(wal.in_batch());
+            for i in 2..5 {
+                let lsn = wal.append(Record::put(Key::new(format!("key{}", i).into_bytes()), HashMap::new(), i)).unwrap();
+                assert_eq!(lsn, batch_lsn);
+            }
+            wal.end_batch().unwrap();
+            assert!(!wal.in_batch());
+            wal.flush().unwrap();
+        }
+
+        let wal = Wal::open(&path).unwrap();
+        assert_eq!(wal.next_lsn(), 3);
+
+        let records = wal.read_all().unwrap();
+        assert_eq!(records.len(), 4);
+        assert!(records[1..].iter().all(|(lsn, _)| *lsn == 2));
+    }
+
+    #[test]
+    fn test_wal_ignores_torn_batch() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("wal.log");
+
+        {
+            let wal = Wal::create(&path).unwrap();
+            wal.append(Record::put(Key::new(b"single".to_vec()), HashMap::new(), 1)).unwrap();
+            wal.begin_batch().unwrap();
+            for i in 2..5 {
+                wal.append(Record::put(Key::new(format!("key{}", i).into_bytes()), HashMap::new(), i)).unwrap();
+            }
+            wal.end_batch().unwrap();
+            wal.flush().unwrap();
+        }
+
+        // Simulate a crash halfway through writing the batch entry
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 10).unwrap();
+        drop(file);
+
+        let wal = Wal::open(&path).unwrap();
+        let records = wal.read_all().unwrap();
+        assert_eq!(records.len(), 1);
+
+        // New entries are readable after the torn tail is discarded
+        wal.append(Record::put(Key::new(b"after".to_vec()), HashMap::new(), 5)).unwrap();
+        wal.flush().unwrap();
+        assert_eq!(wal.read_all().unwrap().len(), 2);
+    }
 }
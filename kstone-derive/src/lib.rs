@@ -0,0 +1,125 @@
+/// Derive macros for KeystoneDB
+///
+/// `#[derive(KeystoneItem)]` implements `kstone_api::KeystoneItem` for structs
+/// with named fields, mapping each field to an attribute of the same name.
+///
+/// Field options:
+/// - `#[keystone(rename = "attr")]` stores the field under a different attribute name
+/// - `#[keystone(skip)]` never stores the field; it reads back as `Default::default()`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+#[proc_macro_derive(KeystoneItem, attributes(keystone))]
+pub fn derive_keystone_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a single struct field maps to an attribute
+struct FieldMapping {
+    ident: syn::Ident,
+    attribute: String,
+    skip: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "KeystoneItem can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "KeystoneItem can only be derived for structs",
+            ))
+        }
+    };
+
+    let mappings = fields
+        .iter()
+        .map(field_mapping)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let to_item = mappings.iter().filter(|m| !m.skip).map(|m| {
+        let ident = &m.ident;
+        let attribute = &m.attribute;
+        quote! {
+            item.insert(
+                #attribute.to_string(),
+                ::kstone_api::mapper::to_value(&self.#ident)?,
+            );
+        }
+    });
+
+    let from_item = mappings.iter().map(|m| {
+        let ident = &m.ident;
+        let attribute = &m.attribute;
+        if m.skip {
+            quote! { #ident: ::core::default::Default::default(), }
+        } else {
+            quote! {
+                #ident: ::kstone_api::mapper::__private::take_attribute(&mut item, #attribute)?,
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::kstone_api::KeystoneItem for #name #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn to_item(&self) -> ::kstone_api::mapper::__private::Result<::kstone_api::mapper::__private::Item> {
+                let mut item = ::kstone_api::mapper::__private::Item::new();
+                #(#to_item)*
+                Ok(item)
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn from_item(
+                mut item: ::kstone_api::mapper::__private::Item,
+            ) -> ::kstone_api::mapper::__private::Result<Self> {
+                Ok(Self {
+                    #(#from_item)*
+                })
+            }
+        }
+    })
+}
+
+fn field_mapping(field: &syn::Field) -> syn::Result<FieldMapping> {
+    let ident = field.ident.clone().expect("named field");
+    let mut mapping = FieldMapping {
+        attribute: ident.to_string().trim_start_matches("r#").to_string(),
+        ident,
+        skip: false,
+    };
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("keystone")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                mapping.attribute = name.value();
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                mapping.skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename = \"...\"` or `skip`"))
+            }
+        })?;
+    }
+
+    Ok(mapping)
+}
@@ -0,0 +1,390 @@
+/// Authentication and authorization for the gRPC service
+///
+/// Clients authenticate with `authorization: Bearer <token>` metadata, where
+/// the token is either a static API key or an HS256-signed JWT. Each caller
+/// resolves to a `Principal` whose `Policy` grants read-only or read-write
+/// access, optionally restricted to partition keys starting with one of a
+/// set of prefixes.
+///
+/// `AuthInterceptor` rejects unauthenticated requests before they reach the
+/// service and attaches the `Principal`; `KeystoneService` checks the policy
+/// against the keys each request touches before running it. Requests that
+/// are not confined to known partitions (scans, PartiQL statements) need a
+/// policy without key prefixes.
+///
+/// JWT claims: `sub` (principal name), `exp` (required), `access`
+/// (`"read-only"` or `"read-write"`, default read-only) and `key_prefixes`
+/// (list of strings, default unrestricted).
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tonic::metadata::MetadataMap;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Metadata key carrying the bearer token
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Kind of access a request needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Access level granted to a principal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Permission {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// What a principal may do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    /// Access level
+    pub permission: Permission,
+
+    /// Partition key prefixes the principal is confined to (empty = all keys)
+    pub key_prefixes: Vec<Vec<u8>>,
+}
+
+impl Policy {
+    /// Read access to every key
+    pub fn read_only() -> Self {
+        Self {
+            permission: Permission::ReadOnly,
+            key_prefixes: Vec::new(),
+        }
+    }
+
+    /// Read and write access to every key
+    pub fn read_write() -> Self {
+        Self {
+            permission: Permission::ReadWrite,
+            key_prefixes: Vec::new(),
+        }
+    }
+
+    /// Confine the policy to partition keys starting with `prefix` (may be repeated)
+    pub fn with_key_prefix(mut self, prefix: impl Into<Vec<u8>>) -> Self {
+        self.key_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Check access to a single partition
+    pub fn check_key(&self, access: Access, pk: &[u8]) -> Result<(), Status> {
+        self.check_permission(access)?;
+        if self.key_prefixes.is_empty() || self.key_prefixes.iter().any(|prefix| pk.starts_with(prefix)) {
+            Ok(())
+        } else {
+            Err(Status::permission_denied(format!(
+                "Access to partition key {} is not allowed",
+                String::from_utf8_lossy(pk)
+            )))
+        }
+    }
+
+    /// Check access to the whole database
+    pub fn check_all(&self, access: Access) -> Result<(), Status> {
+        self.check_permission(access)?;
+        if self.key_prefixes.is_empty() {
+            Ok(())
+        } else {
+            Err(Status::permission_denied(
+                "Access is restricted to key prefixes; scans and statements are not allowed",
+            ))
+        }
+    }
+
+    fn check_permission(&self, access: Access) -> Result<(), Status> {
+        match (access, self.permission) {
+            (Access::Write, Permission::ReadOnly) => Err(Status::permission_denied("Write access denied")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An authenticated caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// API key name or JWT subject
+    pub name: String,
+
+    /// What the caller may do
+    pub policy: Policy,
+}
+
+/// Validates HS256-signed JWTs
+pub struct JwtValidator {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    access: Option<Permission>,
+    #[serde(default)]
+    key_prefixes: Vec<String>,
+}
+
+impl JwtValidator {
+    /// Accept tokens signed with `secret`
+    pub fn hs256(secret: &[u8]) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_aud = false;
+        Self {
+            key: DecodingKey::from_secret(secret),
+            validation,
+        }
+    }
+
+    /// Only accept tokens issued by `issuer`
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.validation.set_issuer(&[issuer]);
+        self
+    }
+
+    /// Only accept tokens for `audience`
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.validation.set_audience(&[audience]);
+        self.validation.validate_aud = true;
+        self
+    }
+
+    fn validate(&self, token: &str) -> Result<Principal, Status> {
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)
+            .map_err(|e| Status::unauthenticated(format!("Invalid token: {}", e)))?
+            .claims;
+
+        Ok(Principal {
+            name: claims.sub,
+            policy: Policy {
+                permission: claims.access.unwrap_or(Permission::ReadOnly),
+                key_prefixes: claims.key_prefixes.into_iter().map(String::into_bytes).collect(),
+            },
+        })
+    }
+}
+
+/// Entry of an API key file
+#[derive(Deserialize)]
+struct ApiKeyEntry {
+    name: String,
+    key: String,
+    access: Permission,
+    #[serde(default)]
+    key_prefixes: Vec<String>,
+}
+
+/// Resolves bearer tokens to principals
+#[derive(Default)]
+pub struct Authenticator {
+    api_keys: HashMap<String, Principal>,
+    jwt: Option<JwtValidator>,
+}
+
+impl Authenticator {
+    /// Create an authenticator that accepts no tokens yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept a static API key
+    pub fn with_api_key(mut self, key: impl Into<String>, principal: Principal) -> Self {
+        self.api_keys.insert(key.into(), principal);
+        self
+    }
+
+    /// Accept API keys listed in a JSON file
+    ///
+    /// The file holds an array of
+    /// `{"name": ..., "key": ..., "access": "read-only" | "read-write", "key_prefixes": [...]}`
+    /// objects; `key_prefixes` is optional.
+    pub fn with_api_key_file(mut self, path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let entries: Vec<ApiKeyEntry> = serde_json::from_slice(&std::fs::read(path)?)?;
+        for entry in entries {
+            let principal = Principal {
+                name: entry.name,
+                policy: Policy {
+                    permission: entry.access,
+                    key_prefixes: entry.key_prefixes.into_iter().map(String::into_bytes).collect(),
+                },
+            };
+            self.api_keys.insert(entry.key, principal);
+        }
+        Ok(self)
+    }
+
+    /// Accept JWTs passing `validator`
+    pub fn with_jwt(mut self, validator: JwtValidator) -> Self {
+        self.jwt = Some(validator);
+        self
+    }
+
+    /// Resolve a token; API keys are tried before JWT validation
+    pub fn authenticate(&self, token: &str) -> Result<Principal, Status> {
+        if let Some(principal) = self.api_keys.get(token) {
+            return Ok(principal.clone());
+        }
+        match &self.jwt {
+            Some(jwt) => jwt.validate(token),
+            None => Err(Status::unauthenticated("Invalid API key")),
+        }
+    }
+
+    /// Resolve the bearer token in request metadata
+    pub fn authenticate_metadata(&self, metadata: &MetadataMap) -> Result<Principal, Status> {
+        let header = metadata
+            .get(AUTHORIZATION_HEADER)
+            .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))?
+            .to_str()
+            .map_err(|_| Status::unauthenticated("Malformed authorization metadata"))?;
+        let token = header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| Status::unauthenticated("Authorization must be a Bearer token"))?;
+        self.authenticate(token.trim())
+    }
+}
+
+/// Authenticates requests and attaches their `Principal`
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    authenticator: Option<Arc<Authenticator>>,
+}
+
+impl AuthInterceptor {
+    /// Reject requests `authenticator` does not accept
+    pub fn new(authenticator: Arc<Authenticator>) -> Self {
+        Self {
+            authenticator: Some(authenticator),
+        }
+    }
+
+    /// Let every request through unauthenticated
+    pub fn disabled() -> Self {
+        Self { authenticator: None }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(authenticator) = &self.authenticator {
+            let principal = authenticator.authenticate_metadata(request.metadata())?;
+            request.extensions_mut().insert(principal);
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn token(claims: serde_json::Value, secret: &[u8]) -> String {
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn expiry() -> u64 {
+        jsonwebtoken::get_current_timestamp() + 3600
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = Policy::read_only().with_key_prefix("tenant1#");
+        assert!(policy.check_key(Access::Read, b"tenant1#user").is_ok());
+        assert_eq!(policy.check_key(Access::Read, b"tenant2#user").unwrap_err().code(), tonic::Code::PermissionDenied);
+        assert_eq!(policy.check_key(Access::Write, b"tenant1#user").unwrap_err().code(), tonic::Code::PermissionDenied);
+        assert!(policy.check_all(Access::Read).is_err());
+
+        let policy = Policy::read_write();
+        assert!(policy.check_key(Access::Write, b"anything").is_ok());
+        assert!(policy.check_all(Access::Write).is_ok());
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let principal = Principal {
+            name: "reporting".to_string(),
+            policy: Policy::read_only(),
+        };
+        let auth = Authenticator::new().with_api_key("secret-key", principal.clone());
+
+        assert_eq!(auth.authenticate("secret-key").unwrap(), principal);
+        assert_eq!(auth.authenticate("wrong").unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        let mut metadata = MetadataMap::new();
+        assert!(auth.authenticate_metadata(&metadata).is_err());
+        metadata.insert(AUTHORIZATION_HEADER, "Bearer secret-key".parse().unwrap());
+        assert_eq!(auth.authenticate_metadata(&metadata).unwrap(), principal);
+        metadata.insert(AUTHORIZATION_HEADER, "Basic secret-key".parse().unwrap());
+        assert!(auth.authenticate_metadata(&metadata).is_err());
+    }
+
+    #[test]
+    fn test_api_key_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("keys.json");
+        std::fs::write(
+            &path,
+            r#"[{"name": "app", "key": "k1", "access": "read-write", "key_prefixes": ["app#"]}]"#,
+        )
+        .unwrap();
+
+        let auth = Authenticator::new().with_api_key_file(&path).unwrap();
+        let principal = auth.authenticate("k1").unwrap();
+        assert_eq!(principal.name, "app");
+        assert_eq!(principal.policy, Policy::read_write().with_key_prefix("app#"));
+    }
+
+    #[test]
+    fn test_jwt() {
+        let auth = Authenticator::new().with_jwt(JwtValidator::hs256(b"jwt-secret").with_issuer("issuer"));
+
+        let valid = token(
+            serde_json::json!({"sub": "svc", "exp": expiry(), "iss": "issuer", "access": "read-write", "key_prefixes": ["svc#"]}),
+            b"jwt-secret",
+        );
+        let principal = auth.authenticate(&valid).unwrap();
+        assert_eq!(principal.name, "svc");
+        assert_eq!(principal.policy, Policy::read_write().with_key_prefix("svc#"));
+
+        // Access defaults to read-only
+        let minimal = token(serde_json::json!({"sub": "svc", "exp": expiry(), "iss": "issuer"}), b"jwt-secret");
+        assert_eq!(auth.authenticate(&minimal).unwrap().policy, Policy::read_only());
+
+        let wrong_secret = token(serde_json::json!({"sub": "svc", "exp": expiry(), "iss": "issuer"}), b"other");
+        assert!(auth.authenticate(&wrong_secret).is_err());
+
+        let wrong_issuer = token(serde_json::json!({"sub": "svc", "exp": expiry(), "iss": "other"}), b"jwt-secret");
+        assert!(auth.authenticate(&wrong_issuer).is_err());
+
+        let expired = token(serde_json::json!({"sub": "svc", "exp": 1, "iss": "issuer"}), b"jwt-secret");
+        assert!(auth.authenticate(&expired).is_err());
+    }
+
+    #[test]
+    fn test_interceptor() {
+        let principal = Principal {
+            name: "app".to_string(),
+            policy: Policy::read_write(),
+        };
+        let mut interceptor = AuthInterceptor::new(Arc::new(Authenticator::new().with_api_key("k1", principal.clone())));
+
+        assert_eq!(interceptor.call(Request::new(())).unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request.metadata_mut().insert(AUTHORIZATION_HEADER, "Bearer k1".parse().unwrap());
+        let request = interceptor.call(request).unwrap();
+        assert_eq!(request.extensions().get::<Principal>(), Some(&principal));
+
+        assert!(AuthInterceptor::disabled().call(Request::new(())).is_ok());
+    }
+}
@@ -2,15 +2,22 @@
 ///
 /// Starts a gRPC server that exposes the KeystoneDB API over the network.
 
-use axum::{routing::get, Router};
 use clap::Parser;
 use kstone_api::Database;
-use kstone_server::{ConnectionManager, KeystoneDbServer, KeystoneService, RateLimiter, metrics};
+use kstone_server::{
+    dynamodb, metrics, AuthInterceptor, Authenticator, ConnectionManager, DatabaseRegistry, DynamoDbConfig,
+    JwtValidator, KeystoneDbServer, KeystoneService, RateLimiter, RpcTimeouts, Shutdown,
+};
+#[cfg(feature = "otel")]
+use kstone_server::trace_context;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
 use tonic::transport::Server;
 use tracing::{info, warn};
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
@@ -18,8 +25,26 @@ use tracing_subscriber::EnvFilter;
 #[command(about = "KeystoneDB gRPC Server", long_about = None)]
 struct Args {
     /// Path to the database directory
-    #[arg(short, long, value_name = "PATH")]
-    db_path: PathBuf,
+    #[arg(short, long, value_name = "PATH", required_unless_present = "root_dir", conflicts_with = "root_dir")]
+    db_path: Option<PathBuf>,
+
+    /// Serve every database in this directory instead of one; requests pick
+    /// theirs with the kstone-database metadata key
+    #[arg(long, value_name = "PATH")]
+    root_dir: Option<PathBuf>,
+
+    /// Databases kept open at once with --root-dir; the least recently used
+    /// idle one is closed beyond this
+    #[arg(long, default_value = "16")]
+    max_open_databases: usize,
+
+    /// Max requests per second to each database with --root-dir (0 = unlimited)
+    #[arg(long, default_value = "0")]
+    max_rps_per_database: u32,
+
+    /// Create a database under --root-dir the first time a request names it
+    #[arg(long)]
+    create_databases: bool,
 
     /// Port to listen on
     #[arg(short, long, default_value = "50051")]
@@ -37,10 +62,21 @@ struct Args {
     #[arg(long, default_value = "60")]
     connection_timeout: u64,
 
-    /// Graceful shutdown timeout in seconds
+    /// Seconds to wait for in-flight requests after SIGTERM/SIGINT before
+    /// closing them and flushing the database
     #[arg(long, default_value = "30")]
     shutdown_timeout: u64,
 
+    /// Seconds a unary RPC may run before failing with DEADLINE_EXCEEDED
+    /// (0 = unlimited); streams are only limited by --max-rpc-time-for
+    #[arg(long, default_value = "0")]
+    max_rpc_time: u64,
+
+    /// Limit of one RPC method as METHOD=SECS, e.g. scan=120 (0 = unlimited);
+    /// may be repeated
+    #[arg(long, value_name = "METHOD=SECS", value_parser = parse_method_limit)]
+    max_rpc_time_for: Vec<(String, u64)>,
+
     /// Max requests per second per connection (0 = unlimited)
     #[arg(long, default_value = "0")]
     max_rps_per_connection: u32,
@@ -48,21 +84,164 @@ struct Args {
     /// Max total requests per second (0 = unlimited)
     #[arg(long, default_value = "0")]
     max_rps_global: u32,
+
+    /// Address of the HTTP endpoint serving /metrics, /health and /ready
+    /// [default: <host>:9090]
+    #[arg(long, value_name = "ADDR")]
+    metrics_addr: Option<SocketAddr>,
+
+    /// Port for the DynamoDB-compatible HTTP endpoint (disabled if not set)
+    ///
+    /// The endpoint can't check credentials, so it is refused while
+    /// authentication is enabled.
+    #[arg(long, value_name = "PORT", conflicts_with_all = ["root_dir", "api_keys", "jwt_secret_file"])]
+    dynamodb_port: Option<u16>,
+
+    /// Attribute name used as the partition key by the DynamoDB endpoint
+    #[arg(long, default_value = "pk")]
+    dynamodb_pk_attr: String,
+
+    /// Attribute name used as the sort key by the DynamoDB endpoint
+    #[arg(long, default_value = "sk")]
+    dynamodb_sk_attr: String,
+
+    /// JSON file of API keys clients may authenticate with (enables authentication)
+    #[arg(long, value_name = "FILE")]
+    api_keys: Option<PathBuf>,
+
+    /// File holding the HS256 secret of accepted JWTs (enables authentication)
+    #[arg(long, value_name = "FILE")]
+    jwt_secret_file: Option<PathBuf>,
+
+    /// Only accept JWTs issued by this issuer
+    #[arg(long, requires = "jwt_secret_file")]
+    jwt_issuer: Option<String>,
+
+    /// Only accept JWTs for this audience
+    #[arg(long, requires = "jwt_secret_file")]
+    jwt_audience: Option<String>,
+
+    /// Emit spans for engine operations (puts, gets, queries, scans,
+    /// flushes, compactions) under each request span
+    #[arg(long)]
+    trace_operations: bool,
+
+    /// OTLP collector to export spans to, e.g. http://localhost:4317
+    /// (disabled if not set)
+    #[cfg(feature = "otel")]
+    #[arg(long, value_name = "URL")]
+    otlp_endpoint: Option<String>,
 }
 
-async fn metrics_handler() -> String {
-    metrics::encode_metrics().unwrap_or_else(|e| {
-        tracing::error!("Failed to encode metrics: {}", e);
-        String::from("# Error encoding metrics\n")
-    })
+/// What the server serves: one database, or every database under a root
+enum Served {
+    Database(Arc<Database>),
+    Registry(Arc<DatabaseRegistry>),
 }
 
-async fn health_handler() -> &'static str {
-    "OK"
+impl Served {
+    /// Open the database of `--db-path`, or set up the registry of `--root-dir`
+    fn open(args: &Args) -> anyhow::Result<Self> {
+        if let Some(root) = &args.root_dir {
+            info!(
+                "Serving databases under {:?}: max_open={}, max_rps_per_database={}, create_missing={}",
+                root,
+                args.max_open_databases,
+                if args.max_rps_per_database == 0 { "unlimited".to_string() } else { args.max_rps_per_database.to_string() },
+                args.create_databases
+            );
+            std::fs::create_dir_all(root)?;
+            let registry = DatabaseRegistry::new(root)
+                .with_max_open(args.max_open_databases)
+                .with_max_rps_per_database(args.max_rps_per_database)
+                .with_create_missing(args.create_databases)
+                .with_trace_operations(args.trace_operations);
+            return Ok(Served::Registry(Arc::new(registry)));
+        }
+
+        let db_path = args.db_path.as_ref().expect("clap requires --db-path without --root-dir");
+        info!("Opening database at {:?}", db_path);
+        let db = if db_path.exists() {
+            Database::open(db_path)?
+        } else {
+            info!("Database not found, creating new database");
+            Database::create(db_path)?
+        };
+
+        if args.trace_operations {
+            db.set_trace_operations(true)?;
+            info!("Engine operation spans enabled");
+        }
+
+        let db = Arc::new(db);
+        metrics::register_engine_metrics(db.clone());
+        Ok(Served::Database(db))
+    }
+
+    fn service(&self) -> KeystoneService {
+        match self {
+            Served::Database(db) => KeystoneService::from_arc(db.clone()),
+            Served::Registry(registry) => KeystoneService::from_registry(registry.clone()),
+        }
+    }
+
+    /// The databases open now, which are flushed on shutdown
+    fn open_databases(&self) -> Vec<(String, Arc<Database>)> {
+        match self {
+            Served::Database(db) => vec![("default".to_string(), db.clone())],
+            Served::Registry(registry) => registry.open_databases(),
+        }
+    }
 }
 
-async fn ready_handler() -> &'static str {
-    "OK"
+/// Parse a `METHOD=SECS` limit of `--max-rpc-time-for`
+fn parse_method_limit(value: &str) -> Result<(String, u64), String> {
+    let (method, secs) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected METHOD=SECS, got '{}'", value))?;
+    let secs = secs
+        .parse()
+        .map_err(|_| format!("invalid number of seconds '{}'", secs))?;
+    Ok((method.to_string(), secs))
+}
+
+/// Build the per-method execution limits from the timeout flags
+fn build_timeouts(args: &Args) -> RpcTimeouts {
+    let mut timeouts = RpcTimeouts::new();
+    if args.max_rpc_time > 0 {
+        timeouts = timeouts.with_default(Duration::from_secs(args.max_rpc_time));
+    }
+    for (method, secs) in &args.max_rpc_time_for {
+        timeouts = match secs {
+            0 => timeouts.without_limit(method.as_str()),
+            secs => timeouts.with_method(method.as_str(), Duration::from_secs(*secs)),
+        };
+    }
+    timeouts
+}
+
+/// Build the authenticator from the auth flags, `None` if none were given
+fn build_authenticator(args: &Args) -> anyhow::Result<Option<Authenticator>> {
+    if args.api_keys.is_none() && args.jwt_secret_file.is_none() {
+        return Ok(None);
+    }
+
+    let mut authenticator = Authenticator::new();
+    if let Some(path) = &args.api_keys {
+        authenticator = authenticator.with_api_key_file(path)?;
+    }
+    if let Some(path) = &args.jwt_secret_file {
+        let secret = std::fs::read_to_string(path)?;
+        let mut validator = JwtValidator::hs256(secret.trim().as_bytes());
+        if let Some(issuer) = &args.jwt_issuer {
+            validator = validator.with_issuer(issuer);
+        }
+        if let Some(audience) = &args.jwt_audience {
+            validator = validator.with_audience(audience);
+        }
+        authenticator = authenticator.with_jwt(validator);
+    }
+    Ok(Some(authenticator))
 }
 
 /// Wait for shutdown signal (SIGTERM, SIGINT, or Ctrl+C)
@@ -92,34 +271,47 @@ async fn shutdown_signal() {
             info!("Received SIGTERM signal");
         }
     }
-
-    warn!("Shutting down gracefully...");
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Parse command line arguments
+    let args = Args::parse();
+
     // Initialize tracing with environment filter
     // Default to info level, can override with RUST_LOG env var
     // Example: RUST_LOG=debug cargo run --bin kstone-server
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
+    let fmt_layer = tracing_subscriber::fmt::layer()
         .with_target(false)
         .with_thread_ids(true)
         .with_file(true)
         .with_line_number(true)
-        .with_level(true)
-        .init();
+        .with_level(true);
+    let subscriber = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    // Export spans over OTLP if a collector was given
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(match &args.otlp_endpoint {
+        Some(endpoint) => Some(
+            tracing_opentelemetry::layer().with_tracer(trace_context::init_otlp(endpoint, "kstone-server")?),
+        ),
+        None => None,
+    });
+
+    subscriber.init();
+
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &args.otlp_endpoint {
+        info!("Exporting spans to OTLP collector at {}", endpoint);
+    }
 
     // Initialize Prometheus metrics
     metrics::register_metrics();
     info!("Initialized Prometheus metrics");
 
-    // Parse command line arguments
-    let args = Args::parse();
-
     // Create connection manager
     // Note: Full integration with tonic would require custom middleware layers
     // For now, we demonstrate the infrastructure and use TCP-level settings
@@ -134,8 +326,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Create rate limiter
-    let _rate_limiter = RateLimiter::new(args.max_rps_per_connection, args.max_rps_global);
-    if _rate_limiter.is_enabled() {
+    let rate_limiter = RateLimiter::new(args.max_rps_per_connection, args.max_rps_global);
+    if rate_limiter.is_enabled() {
         info!(
             "Rate limiting enabled: per_connection={} rps, global={} rps",
             if args.max_rps_per_connection == 0 { "unlimited".to_string() } else { args.max_rps_per_connection.to_string() },
@@ -146,26 +338,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Open or create database
-    info!("Opening database at {:?}", args.db_path);
-    let db = if args.db_path.exists() {
-        Database::open(&args.db_path)?
-    } else {
-        info!("Database not found, creating new database");
-        Database::create(&args.db_path)?
-    };
+    let served = Served::open(&args)?;
 
     // Create gRPC service
-    let service = KeystoneService::new(db);
+    let shutdown = Shutdown::new();
+    let mut service = served
+        .service()
+        .with_rate_limiter(rate_limiter)
+        .with_timeouts(build_timeouts(&args))
+        .with_shutdown(shutdown.clone());
+    let auth_interceptor = match build_authenticator(&args)? {
+        Some(authenticator) => {
+            let authenticator = Arc::new(authenticator);
+            service = service.with_authenticator(authenticator.clone());
+            info!("Authentication enabled");
+            AuthInterceptor::new(authenticator)
+        }
+        None => {
+            warn!("Authentication disabled - every client has full access");
+            AuthInterceptor::disabled()
+        }
+    };
     let grpc_addr = format!("{}:{}", args.host, args.port).parse()?;
 
     info!("Starting KeystoneDB gRPC server on {}", grpc_addr);
 
     // Create HTTP server for metrics and health checks
-    let metrics_app = Router::new()
-        .route("/metrics", get(metrics_handler))
-        .route("/health", get(health_handler))
-        .route("/ready", get(ready_handler));
-    let metrics_addr = format!("{}:9090", args.host);
+    let metrics_app = metrics::router_with_shutdown(shutdown.clone());
+    let metrics_addr = match args.metrics_addr {
+        Some(addr) => addr.to_string(),
+        None => format!("{}:9090", args.host),
+    };
 
     info!("Starting HTTP server on {} with /metrics, /health, /ready endpoints", metrics_addr);
 
@@ -177,12 +380,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Spawn DynamoDB-compatible endpoint if requested (clap rules it out with --root-dir);
+    // it drains in-flight requests on shutdown like the gRPC server
+    let mut dynamodb_task = None;
+    if let (Some(dynamodb_port), Served::Database(db)) = (args.dynamodb_port, &served) {
+        let dynamodb_addr = format!("{}:{}", args.host, dynamodb_port);
+        let dynamodb_app = dynamodb::router(
+            db.clone(),
+            DynamoDbConfig::new(args.dynamodb_pk_attr.clone(), args.dynamodb_sk_attr.clone()),
+        );
+
+        info!(
+            "Starting DynamoDB-compatible endpoint on {} (pk={}, sk={})",
+            dynamodb_addr, args.dynamodb_pk_attr, args.dynamodb_sk_attr
+        );
+
+        let dynamodb_listener = tokio::net::TcpListener::bind(&dynamodb_addr).await?;
+        let shutdown = shutdown.clone();
+        dynamodb_task = Some(tokio::spawn(async move {
+            let server = axum::serve(dynamodb_listener, dynamodb_app)
+                .with_graceful_shutdown(async move { shutdown.triggered().await });
+            if let Err(e) = server.await {
+                tracing::error!("DynamoDB endpoint error: {}", e);
+            }
+        }));
+    }
+
     // Configure server with connection settings
     let server = Server::builder()
         .timeout(Duration::from_secs(args.connection_timeout))
         .tcp_keepalive(Some(Duration::from_secs(30)))
         .tcp_nodelay(true)
-        .add_service(KeystoneDbServer::new(service));
+        .add_service(KeystoneDbServer::with_interceptor(service, auth_interceptor));
 
     // Start gRPC server with graceful shutdown
     info!(
@@ -192,16 +421,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Server ready - listening for connections");
 
-    // Serve with graceful shutdown on signal
-    server
-        .serve_with_shutdown(grpc_addr, shutdown_signal())
-        .await?;
+    // Serve until the shutdown is triggered, then stop accepting RPCs and
+    // let in-flight ones finish
+    let mut server_task = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { server.serve_with_shutdown(grpc_addr, shutdown.triggered()).await }
+    });
+
+    tokio::select! {
+        _ = shutdown_signal() => {}
+        result = &mut server_task => {
+            // The server stopped on its own, e.g. it failed to bind
+            result??;
+            return Ok(());
+        }
+    }
+
+    warn!("Shutting down gracefully, draining in-flight requests for up to {}s...", args.shutdown_timeout);
+    shutdown.trigger();
 
-    info!("gRPC server shutdown complete");
+    // Both servers drain at once, within the same deadline
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(args.shutdown_timeout);
+    match tokio::time::timeout_at(deadline, &mut server_task).await {
+        Ok(result) => {
+            result??;
+            info!("gRPC server shutdown complete");
+        }
+        Err(_) => {
+            warn!("Shutdown timeout elapsed, closing remaining connections");
+            server_task.abort();
+        }
+    }
+    if let Some(mut dynamodb_task) = dynamodb_task {
+        match tokio::time::timeout_at(deadline, &mut dynamodb_task).await {
+            Ok(result) => {
+                result?;
+                info!("DynamoDB endpoint shutdown complete");
+            }
+            Err(_) => {
+                warn!("Shutdown timeout elapsed, closing remaining DynamoDB connections");
+                dynamodb_task.abort();
+            }
+        }
+    }
+
+    // Flush memtables and sync the WAL so the next start has nothing to replay
+    for (name, db) in served.open_databases() {
+        info!("Flushing database '{}'...", name);
+        match tokio::task::spawn_blocking(move || db.flush()).await? {
+            Ok(()) => info!("Database '{}' flushed", name),
+            Err(e) => tracing::error!("Failed to flush database '{}' on shutdown: {}", name, e),
+        }
+    }
 
-    // Give shutdown timeout for connection draining
-    info!("Waiting up to {}s for connections to drain...", args.shutdown_timeout);
-    tokio::time::sleep(Duration::from_secs(args.shutdown_timeout)).await;
+    #[cfg(feature = "otel")]
+    trace_context::shutdown_otlp();
 
     info!("Shutdown complete");
     Ok(())
@@ -7,6 +7,7 @@
 /// trait implementations.
 
 use bytes::Bytes;
+use kstone_core::stream::{StreamEventType, StreamRecord};
 use kstone_core::{Key, Value as KsValue};
 use kstone_proto::{self as proto, value::Value as ProtoValueEnum};
 use std::collections::HashMap;
@@ -166,6 +167,28 @@ pub fn ks_last_key_opt_to_proto(last_key: Option<(Bytes, Option<Bytes>)>) -> Opt
     last_key.map(|(pk, sk)| ks_last_key_to_proto(pk, sk))
 }
 
+// ============================================================================
+// Stream Record Conversions
+// ============================================================================
+
+/// Convert a kstone_core StreamRecord to protobuf
+pub fn ks_stream_record_to_proto(record: &StreamRecord) -> proto::StreamRecord {
+    let event_type = match record.event_type {
+        StreamEventType::Insert => proto::StreamEventType::Insert,
+        StreamEventType::Modify => proto::StreamEventType::Modify,
+        StreamEventType::Remove => proto::StreamEventType::Remove,
+    };
+
+    proto::StreamRecord {
+        sequence_number: record.sequence_number,
+        event_type: event_type as i32,
+        key: Some(core_key_to_proto(&record.key)),
+        old_image: record.old_image.as_ref().map(ks_item_to_proto),
+        new_image: record.new_image.as_ref().map(ks_item_to_proto),
+        timestamp: record.timestamp,
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
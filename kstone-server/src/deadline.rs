@@ -0,0 +1,165 @@
+/// Request deadlines and per-method execution limits
+///
+/// An RPC's deadline is the earlier of the client's `grpc-timeout` and the
+/// server's limit for the method. Queries and scans check it as they read,
+/// so an abandoned request stops instead of running to completion, and the
+/// RPC fails with `DEADLINE_EXCEEDED`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tonic::metadata::MetadataMap;
+
+/// Metadata key carrying the client's timeout
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Server-streaming methods, which only get a limit set for the method itself
+///
+/// Their default is unlimited since a subscription is expected to stay open.
+const STREAMING_METHODS: &[&str] = &["query_stream", "scan_stream", "subscribe_stream"];
+
+/// Parse a `grpc-timeout` value such as `500m` or `30S`
+///
+/// The value is up to 8 digits followed by a unit: `H`, `M`, `S`, `m`
+/// (milliseconds), `u` (microseconds) or `n` (nanoseconds).
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 || !value.is_ascii() {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// The client's timeout, `None` if it didn't send a valid one
+pub fn grpc_timeout(metadata: &MetadataMap) -> Option<Duration> {
+    metadata
+        .get(GRPC_TIMEOUT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_grpc_timeout)
+}
+
+/// Deadline of a request started now
+///
+/// The earlier of the client's timeout and `limit`, or `None` if neither is set.
+pub fn request_deadline(metadata: &MetadataMap, limit: Option<Duration>) -> Option<Instant> {
+    let now = Instant::now();
+    // A timeout too long to represent is as good as none
+    let client = grpc_timeout(metadata).and_then(|timeout| now.checked_add(timeout));
+    let server = limit.and_then(|limit| now.checked_add(limit));
+    client.into_iter().chain(server).min()
+}
+
+/// Maximum execution time of each RPC method
+///
+/// Methods are named as in the metrics, e.g. `scan` or `batch_write`.
+#[derive(Debug, Clone, Default)]
+pub struct RpcTimeouts {
+    default: Option<Duration>,
+    methods: HashMap<String, Option<Duration>>,
+}
+
+impl RpcTimeouts {
+    /// No limits: requests only stop at the client's deadline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit every unary method without a limit of its own
+    pub fn with_default(mut self, limit: Duration) -> Self {
+        self.default = Some(limit);
+        self
+    }
+
+    /// Limit one method, overriding the default
+    pub fn with_method(mut self, method: impl Into<String>, limit: Duration) -> Self {
+        self.methods.insert(method.into(), Some(limit));
+        self
+    }
+
+    /// Exempt one method from the default limit
+    pub fn without_limit(mut self, method: impl Into<String>) -> Self {
+        self.methods.insert(method.into(), None);
+        self
+    }
+
+    /// The limit of `method`, `None` if unlimited
+    pub fn for_method(&self, method: &str) -> Option<Duration> {
+        match self.methods.get(method) {
+            Some(limit) => *limit,
+            None if STREAMING_METHODS.contains(&method) => None,
+            None => self.default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_grpc_timeout("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(parse_grpc_timeout("30S"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_grpc_timeout("500m"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_grpc_timeout("250u"), Some(Duration::from_micros(250)));
+        assert_eq!(parse_grpc_timeout("99999999n"), Some(Duration::from_nanos(99_999_999)));
+
+        assert_eq!(parse_grpc_timeout(""), None);
+        assert_eq!(parse_grpc_timeout("S"), None);
+        assert_eq!(parse_grpc_timeout("10"), None);
+        assert_eq!(parse_grpc_timeout("10s"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+    }
+
+    #[test]
+    fn test_request_deadline_takes_earliest() {
+        let mut metadata = MetadataMap::new();
+        assert!(request_deadline(&metadata, None).is_none());
+
+        let before = Instant::now();
+        let deadline = request_deadline(&metadata, Some(Duration::from_secs(60))).unwrap();
+        assert!(deadline >= before + Duration::from_secs(60));
+
+        metadata.insert(GRPC_TIMEOUT_HEADER, "1S".parse().unwrap());
+        let deadline = request_deadline(&metadata, Some(Duration::from_secs(60))).unwrap();
+        assert!(deadline < before + Duration::from_secs(60));
+
+        // The server's limit caps a longer client timeout
+        metadata.insert(GRPC_TIMEOUT_HEADER, "99999999H".parse().unwrap());
+        let deadline = request_deadline(&metadata, Some(Duration::from_secs(60))).unwrap();
+        assert!(deadline < Instant::now() + Duration::from_secs(61));
+    }
+
+    #[test]
+    fn test_rpc_timeouts() {
+        let timeouts = RpcTimeouts::new()
+            .with_default(Duration::from_secs(10))
+            .with_method("scan", Duration::from_secs(60))
+            .without_limit("batch_write")
+            .with_method("subscribe_stream", Duration::from_secs(3600));
+
+        assert_eq!(timeouts.for_method("get"), Some(Duration::from_secs(10)));
+        assert_eq!(timeouts.for_method("scan"), Some(Duration::from_secs(60)));
+        assert_eq!(timeouts.for_method("batch_write"), None);
+
+        // Streams ignore the default
+        assert_eq!(timeouts.for_method("scan_stream"), None);
+        assert_eq!(timeouts.for_method("subscribe_stream"), Some(Duration::from_secs(3600)));
+
+        assert_eq!(RpcTimeouts::new().for_method("get"), None);
+    }
+}
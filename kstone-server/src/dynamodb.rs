@@ -0,0 +1,1358 @@
+/// DynamoDB-compatible HTTP endpoint
+///
+/// Speaks the DynamoDB JSON wire protocol (`application/x-amz-json-1.0`) so the
+/// official AWS SDKs can be pointed at a KeystoneDB server the same way they are
+/// pointed at DynamoDB Local. Requests are dispatched on the `x-amz-target`
+/// header and translated into kstone-api calls.
+///
+/// KeystoneDB stores a single table per database, so the `TableName` of every
+/// request is accepted but ignored. The attribute names that hold the partition
+/// and sort key are configured with [`DynamoDbConfig`].
+
+use axum::{
+    body::Bytes as BodyBytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bytes::Bytes;
+use kstone_api::{
+    BatchWriteRequest, Database, KeystoneError as KsError, Query, ReturnValues, Scan,
+    TransactWriteOp, TransactWriteRequest, Update,
+};
+use kstone_core::{expression::ExpressionContext, Item, Key, Value};
+use serde_json::{json, Map, Value as Json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+/// Target prefix used by every DynamoDB API version 2012-08-10 request
+const TARGET_PREFIX: &str = "DynamoDB_20120810.";
+
+/// Prefix of the `__type` field in error responses
+const ERROR_TYPE_PREFIX: &str = "com.amazonaws.dynamodb.v20120810#";
+
+/// Content type used by the DynamoDB JSON protocol
+const CONTENT_TYPE: &str = "application/x-amz-json-1.0";
+
+/// Configuration for the DynamoDB-compatible endpoint
+#[derive(Debug, Clone)]
+pub struct DynamoDbConfig {
+    /// Attribute name holding the partition key
+    pub pk_attr: String,
+    /// Attribute name holding the sort key
+    pub sk_attr: String,
+}
+
+impl DynamoDbConfig {
+    /// Create a configuration with custom key attribute names
+    pub fn new(pk_attr: impl Into<String>, sk_attr: impl Into<String>) -> Self {
+        Self {
+            pk_attr: pk_attr.into(),
+            sk_attr: sk_attr.into(),
+        }
+    }
+}
+
+impl Default for DynamoDbConfig {
+    fn default() -> Self {
+        Self::new("pk", "sk")
+    }
+}
+
+#[derive(Clone)]
+struct DynamoDbState {
+    db: Arc<Database>,
+    config: Arc<DynamoDbConfig>,
+}
+
+/// Build an axum router serving the DynamoDB JSON protocol on `POST /`
+pub fn router(db: Arc<Database>, config: DynamoDbConfig) -> Router {
+    let state = DynamoDbState {
+        db,
+        config: Arc::new(config),
+    };
+
+    Router::new().route("/", post(handle)).with_state(state)
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Error returned in the DynamoDB error shape
+#[derive(Debug)]
+pub struct DynamoDbError {
+    status: StatusCode,
+    kind: &'static str,
+    message: String,
+}
+
+impl DynamoDbError {
+    fn new(status: StatusCode, kind: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            kind,
+            message: message.into(),
+        }
+    }
+
+    fn validation(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "ValidationException", message)
+    }
+
+    fn serialization(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "SerializationException", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalServerError", message)
+    }
+
+    /// DynamoDB exception name (e.g. `ValidationException`)
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// HTTP status code of the error response
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl From<KsError> for DynamoDbError {
+    fn from(err: KsError) -> Self {
+        match err {
+            KsError::ConditionalCheckFailed(msg) => Self::new(
+                StatusCode::BAD_REQUEST,
+                "ConditionalCheckFailedException",
+                msg,
+            ),
+            KsError::TransactionCanceled(msg) => Self::new(
+                StatusCode::BAD_REQUEST,
+                "TransactionCanceledException",
+                format!("Transaction cancelled: {}", msg),
+            ),
+            KsError::InvalidQuery(msg)
+            | KsError::InvalidArgument(msg)
+            | KsError::InvalidExpression(msg) => Self::validation(msg),
+            KsError::NotFound(msg) => {
+                Self::new(StatusCode::BAD_REQUEST, "ResourceNotFoundException", msg)
+            }
+            KsError::ResourceExhausted(msg) => Self::new(
+                StatusCode::BAD_REQUEST,
+                "ProvisionedThroughputExceededException",
+                msg,
+            ),
+            KsError::TrimmedDataAccess(msg) => {
+                Self::new(StatusCode::BAD_REQUEST, "TrimmedDataAccessException", msg)
+            }
+            err @ KsError::ItemTooLarge { .. } => Self::validation(err.to_string()),
+            err @ KsError::ValidationFailed(_) => Self::validation(err.to_string()),
+            other => Self::internal(other.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for DynamoDbError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "__type": format!("{}{}", ERROR_TYPE_PREFIX, self.kind),
+            "message": self.message,
+        });
+        json_response(self.status, body)
+    }
+}
+
+type DynamoResult<T> = std::result::Result<T, DynamoDbError>;
+
+fn json_response(status: StatusCode, body: Json) -> Response {
+    let mut response = (status, body.to_string()).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+    if let Ok(request_id) = HeaderValue::from_str(&uuid::Uuid::new_v4().to_string()) {
+        headers.insert("x-amzn-requestid", request_id);
+    }
+    response
+}
+
+// ============================================================================
+// Dispatch
+// ============================================================================
+
+async fn handle(State(state): State<DynamoDbState>, headers: HeaderMap, body: BodyBytes) -> Response {
+    let target = headers
+        .get("x-amz-target")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    let operation = match target.strip_prefix(TARGET_PREFIX) {
+        Some(op) => op.to_string(),
+        None => {
+            return DynamoDbError::new(
+                StatusCode::BAD_REQUEST,
+                "UnknownOperationException",
+                format!("Unknown target: {}", target),
+            )
+            .into_response();
+        }
+    };
+
+    let request: Json = if body.is_empty() {
+        Json::Object(Map::new())
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return DynamoDbError::serialization(format!("Invalid JSON body: {}", e))
+                    .into_response();
+            }
+        }
+    };
+
+    debug!("DynamoDB request: {}", operation);
+
+    let db = state.db.clone();
+    let config = state.config.clone();
+    let result = tokio::task::spawn_blocking(move || dispatch(&db, &config, &operation, &request))
+        .await
+        .unwrap_or_else(|e| Err(DynamoDbError::internal(format!("Task join error: {}", e))));
+
+    match result {
+        Ok(body) => json_response(StatusCode::OK, body),
+        Err(err) => {
+            if err.status.is_server_error() {
+                error!("DynamoDB request failed: {}", err.message);
+            }
+            err.into_response()
+        }
+    }
+}
+
+/// Execute a single DynamoDB operation against the database
+pub fn dispatch(
+    db: &Database,
+    config: &DynamoDbConfig,
+    operation: &str,
+    request: &Json,
+) -> DynamoResult<Json> {
+    match operation {
+        "PutItem" => put_item(db, config, request),
+        "GetItem" => get_item(db, config, request),
+        "DeleteItem" => delete_item(db, config, request),
+        "UpdateItem" => update_item(db, config, request),
+        "Query" => query(db, config, request),
+        "Scan" => scan(db, config, request),
+        "BatchWriteItem" => batch_write_item(db, config, request),
+        "TransactWriteItems" => transact_write_items(db, config, request),
+        other => Err(DynamoDbError::new(
+            StatusCode::BAD_REQUEST,
+            "UnknownOperationException",
+            format!("Unsupported operation: {}", other),
+        )),
+    }
+}
+
+// ============================================================================
+// Operations
+// ============================================================================
+
+fn put_item(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let item = item_from_json(required(request, "Item")?)?;
+    let (pk, sk) = key_from_item(&item, config)?;
+
+    match optional_str(request, "ConditionExpression")? {
+        Some(condition) => {
+            let context = Placeholders::from_request(request)?.context();
+            match sk {
+                Some(sk) => db.put_conditional_with_sk(&pk, &sk, item, condition, context)?,
+                None => db.put_conditional(&pk, item, condition, context)?,
+            }
+        }
+        None => match sk {
+            Some(sk) => db.put_with_sk(&pk, &sk, item)?,
+            None => db.put(&pk, item)?,
+        },
+    }
+
+    Ok(json!({}))
+}
+
+fn get_item(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let key = item_from_json(required(request, "Key")?)?;
+    let (pk, sk) = key_from_item(&key, config)?;
+
+    let item = match optional_str(request, "ProjectionExpression")? {
+        Some(projection) => {
+            let paths = Placeholders::from_request(request)?.projection(projection)?;
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            match sk {
+                Some(sk) => db.get_with_sk_projection(&pk, &sk, &paths)?,
+                None => db.get_with_projection(&pk, &paths)?,
+            }
+        }
+        None => match sk {
+            Some(sk) => db.get_with_sk(&pk, &sk)?,
+            None => db.get(&pk)?,
+        },
+    };
+
+    Ok(match item {
+        Some(item) => json!({ "Item": item_to_json(&item) }),
+        None => json!({}),
+    })
+}
+
+fn delete_item(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let key = item_from_json(required(request, "Key")?)?;
+    let (pk, sk) = key_from_item(&key, config)?;
+
+    match optional_str(request, "ConditionExpression")? {
+        Some(condition) => {
+            let context = Placeholders::from_request(request)?.context();
+            match sk {
+                Some(sk) => db.delete_conditional_with_sk(&pk, &sk, condition, context)?,
+                None => db.delete_conditional(&pk, condition, context)?,
+            }
+        }
+        None => match sk {
+            Some(sk) => db.delete_with_sk(&pk, &sk)?,
+            None => db.delete(&pk)?,
+        },
+    }
+
+    Ok(json!({}))
+}
+
+fn update_item(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let key = item_from_json(required(request, "Key")?)?;
+    let (pk, sk) = key_from_item(&key, config)?;
+    let expression = optional_str(request, "UpdateExpression")?
+        .ok_or_else(|| DynamoDbError::validation("UpdateExpression is required"))?;
+
+    let mut update = match &sk {
+        Some(sk) => Update::with_sk(&pk, sk),
+        None => Update::new(&pk),
+    }
+    .expression(expression)
+    .return_values(parse_return_values(optional_str(request, "ReturnValues")?)?);
+
+    if let Some(condition) = optional_str(request, "ConditionExpression")? {
+        update = update.condition(condition);
+    }
+
+    let placeholders = Placeholders::from_request(request)?;
+    for (placeholder, name) in placeholders.names {
+        update = update.name(placeholder, name);
+    }
+    for (placeholder, value) in placeholders.values {
+        update = update.value(placeholder, value);
+    }
+
+    let response = db.update(update)?;
+
+    Ok(match response.attributes {
+        Some(attributes) => json!({ "Attributes": item_to_json(&attributes) }),
+        None => json!({}),
+    })
+}
+
+fn parse_return_values(value: Option<&str>) -> DynamoResult<ReturnValues> {
+    match value {
+        None | Some("NONE") => Ok(ReturnValues::None),
+        Some("ALL_OLD") => Ok(ReturnValues::AllOld),
+        Some("UPDATED_OLD") => Ok(ReturnValues::UpdatedOld),
+        Some("ALL_NEW") => Ok(ReturnValues::AllNew),
+        Some("UPDATED_NEW") => Ok(ReturnValues::UpdatedNew),
+        Some(other) => Err(DynamoDbError::validation(format!(
+            "Unsupported ReturnValues: {}",
+            other
+        ))),
+    }
+}
+
+fn query(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let placeholders = Placeholders::from_request(request)?;
+    let key_condition = optional_str(request, "KeyConditionExpression")?
+        .ok_or_else(|| DynamoDbError::validation("KeyConditionExpression is required"))?;
+    let index_name = optional_str(request, "IndexName")?;
+
+    let terms = parse_key_condition(key_condition, &placeholders)?;
+    let (pk_term, sk_term) = split_key_terms(terms, config, index_name.is_some())?;
+
+    let mut query = Query::new(&key_value_bytes(&pk_term.operands[0])?);
+    if let Some(term) = sk_term {
+        let sk = key_value_bytes(&term.operands[0])?;
+        query = match term.op {
+            KeyOp::Eq => query.sk_eq(&sk),
+            KeyOp::Lt => query.sk_lt(&sk),
+            KeyOp::Lte => query.sk_lte(&sk),
+            KeyOp::Gt => query.sk_gt(&sk),
+            KeyOp::Gte => query.sk_gte(&sk),
+            KeyOp::BeginsWith => query.sk_begins_with(&sk),
+            KeyOp::Between => query.sk_between(&sk, &key_value_bytes(&term.operands[1])?),
+        };
+    }
+
+    if let Some(index_name) = index_name {
+        query = query.index(index_name);
+    }
+    if let Some(forward) = request.get("ScanIndexForward") {
+        let forward = forward
+            .as_bool()
+            .ok_or_else(|| DynamoDbError::validation("ScanIndexForward must be a boolean"))?;
+        query = query.forward(forward);
+    }
+    if let Some(limit) = optional_limit(request)? {
+        query = query.limit(limit);
+    }
+    if let Some(start) = request.get("ExclusiveStartKey") {
+        let (pk, sk) = key_from_item(&item_from_json(start)?, config)?;
+        query = query.start_after(&pk, sk.as_deref());
+    }
+    if let Some(filter) = optional_str(request, "FilterExpression")? {
+        query = query.filter(filter);
+    }
+    if let Some(projection) = optional_str(request, "ProjectionExpression")? {
+        let paths = placeholders.projection(projection)?;
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        query = query.projection(&paths);
+    }
+    for (placeholder, name) in placeholders.names {
+        query = query.name(placeholder, name);
+    }
+    for (placeholder, value) in placeholders.values {
+        query = query.value(placeholder, value);
+    }
+
+    let response = db.query(query)?;
+    Ok(page_to_json(
+        &response.items,
+        response.scanned_count,
+        response.last_key.as_ref(),
+        config,
+    ))
+}
+
+fn scan(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let placeholders = Placeholders::from_request(request)?;
+    let mut scan = Scan::new();
+
+    if let Some(limit) = optional_limit(request)? {
+        scan = scan.limit(limit);
+    }
+    if let Some(start) = request.get("ExclusiveStartKey") {
+        let (pk, sk) = key_from_item(&item_from_json(start)?, config)?;
+        scan = scan.start_after(&pk, sk.as_deref());
+    }
+    match (request.get("Segment"), request.get("TotalSegments")) {
+        (Some(segment), Some(total)) => {
+            let segment = segment
+                .as_u64()
+                .ok_or_else(|| DynamoDbError::validation("Segment must be a non-negative integer"))?;
+            let total = total
+                .as_u64()
+                .ok_or_else(|| DynamoDbError::validation("TotalSegments must be a positive integer"))?;
+            if total == 0 || segment >= total {
+                return Err(DynamoDbError::validation(
+                    "Segment must be less than TotalSegments",
+                ));
+            }
+            scan = scan.segment(segment as usize, total as usize);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(DynamoDbError::validation(
+                "Segment and TotalSegments must be specified together",
+            ));
+        }
+    }
+    if let Some(filter) = optional_str(request, "FilterExpression")? {
+        scan = scan.filter(filter);
+    }
+    if let Some(projection) = optional_str(request, "ProjectionExpression")? {
+        let paths = placeholders.projection(projection)?;
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        scan = scan.projection(&paths);
+    }
+    for (placeholder, name) in placeholders.names {
+        scan = scan.name(placeholder, name);
+    }
+    for (placeholder, value) in placeholders.values {
+        scan = scan.value(placeholder, value);
+    }
+
+    let response = db.scan(scan)?;
+    Ok(page_to_json(
+        &response.items,
+        response.scanned_count,
+        response.last_key.as_ref(),
+        config,
+    ))
+}
+
+fn batch_write_item(db: &Database, config: &DynamoDbConfig, request: &Json) -> DynamoResult<Json> {
+    let tables = required(request, "RequestItems")?
+        .as_object()
+        .ok_or_else(|| DynamoDbError::validation("RequestItems must be a map"))?;
+
+    let mut batch = BatchWriteRequest::new();
+    for writes in tables.values() {
+        let writes = writes
+            .as_array()
+            .ok_or_else(|| DynamoDbError::validation("RequestItems entries must be lists"))?;
+
+        for write in writes {
+            if let Some(put) = write.get("PutRequest") {
+                let item = item_from_json(required(put, "Item")?)?;
+                let (pk, sk) = key_from_item(&item, config)?;
+                batch = match sk {
+                    Some(sk) => batch.put_with_sk(&pk, &sk, item),
+                    None => batch.put(&pk, item),
+                };
+            } else if let Some(delete) = write.get("DeleteRequest") {
+                let key = item_from_json(required(delete, "Key")?)?;
+                let (pk, sk) = key_from_item(&key, config)?;
+                batch = match sk {
+                    Some(sk) => batch.delete_with_sk(&pk, &sk),
+                    None => batch.delete(&pk),
+                };
+            } else {
+                return Err(DynamoDbError::validation(
+                    "Write request must contain PutRequest or DeleteRequest",
+                ));
+            }
+        }
+    }
+
+    db.batch_write(batch)?;
+    Ok(json!({ "UnprocessedItems": {} }))
+}
+
+fn transact_write_items(
+    db: &Database,
+    config: &DynamoDbConfig,
+    request: &Json,
+) -> DynamoResult<Json> {
+    let items = required(request, "TransactItems")?
+        .as_array()
+        .ok_or_else(|| DynamoDbError::validation("TransactItems must be a list"))?;
+
+    // DynamoDB scopes placeholders to each action, while kstone-api shares one
+    // expression context across the transaction. Merge them and reject clashes.
+    let mut shared = Placeholders::default();
+    let mut operations = Vec::with_capacity(items.len());
+
+    for entry in items {
+        let (action, body) = entry
+            .as_object()
+            .and_then(|obj| obj.iter().next())
+            .ok_or_else(|| DynamoDbError::validation("TransactItems entries must be maps"))?;
+
+        shared.merge(Placeholders::from_request(body)?)?;
+        let condition = optional_str(body, "ConditionExpression")?.map(str::to_string);
+
+        let op = match action.as_str() {
+            "Put" => {
+                let item = item_from_json(required(body, "Item")?)?;
+                let key = to_key(key_from_item(&item, config)?);
+                TransactWriteOp::Put { key, item, condition }
+            }
+            "Update" => {
+                let key = to_key(key_from_item(&item_from_json(required(body, "Key")?)?, config)?);
+                let update_expression = optional_str(body, "UpdateExpression")?
+                    .ok_or_else(|| DynamoDbError::validation("UpdateExpression is required"))?
+                    .to_string();
+                TransactWriteOp::Update {
+                    key,
+                    update_expression,
+                    condition,
+                }
+            }
+            "Delete" => {
+                let key = to_key(key_from_item(&item_from_json(required(body, "Key")?)?, config)?);
+                TransactWriteOp::Delete { key, condition }
+            }
+            "ConditionCheck" => {
+                let key = to_key(key_from_item(&item_from_json(required(body, "Key")?)?, config)?);
+                let condition = condition.ok_or_else(|| {
+                    DynamoDbError::validation("ConditionCheck requires a ConditionExpression")
+                })?;
+                TransactWriteOp::ConditionCheck { key, condition }
+            }
+            other => {
+                return Err(DynamoDbError::validation(format!(
+                    "Unsupported transaction action: {}",
+                    other
+                )));
+            }
+        };
+        operations.push(op);
+    }
+
+    db.transact_write(TransactWriteRequest {
+        operations,
+        context: shared.context(),
+    })?;
+    Ok(json!({}))
+}
+
+fn page_to_json(
+    items: &[Item],
+    scanned_count: usize,
+    last_key: Option<&(Bytes, Option<Bytes>)>,
+    config: &DynamoDbConfig,
+) -> Json {
+    let mut response = Map::new();
+    response.insert(
+        "Items".to_string(),
+        Json::Array(items.iter().map(item_to_json).collect()),
+    );
+    response.insert("Count".to_string(), json!(items.len()));
+    response.insert("ScannedCount".to_string(), json!(scanned_count));
+    if let Some((pk, sk)) = last_key {
+        let mut key = Item::new();
+        key.insert(config.pk_attr.clone(), key_bytes_to_value(pk));
+        if let Some(sk) = sk {
+            key.insert(config.sk_attr.clone(), key_bytes_to_value(sk));
+        }
+        response.insert("LastEvaluatedKey".to_string(), item_to_json(&key));
+    }
+    Json::Object(response)
+}
+
+// ============================================================================
+// Request helpers
+// ============================================================================
+
+fn required<'a>(request: &'a Json, field: &str) -> DynamoResult<&'a Json> {
+    request
+        .get(field)
+        .ok_or_else(|| DynamoDbError::validation(format!("{} is required", field)))
+}
+
+fn optional_str<'a>(request: &'a Json, field: &str) -> DynamoResult<Option<&'a str>> {
+    match request.get(field) {
+        None | Some(Json::Null) => Ok(None),
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| DynamoDbError::validation(format!("{} must be a string", field))),
+    }
+}
+
+fn optional_limit(request: &Json) -> DynamoResult<Option<usize>> {
+    match request.get("Limit") {
+        None => Ok(None),
+        Some(limit) => match limit.as_u64() {
+            Some(n) if n > 0 => Ok(Some(n as usize)),
+            _ => Err(DynamoDbError::validation("Limit must be a positive integer")),
+        },
+    }
+}
+
+/// Expression attribute names and values supplied with a request
+#[derive(Debug, Default)]
+struct Placeholders {
+    names: HashMap<String, String>,
+    values: HashMap<String, Value>,
+}
+
+impl Placeholders {
+    fn from_request(request: &Json) -> DynamoResult<Self> {
+        let mut placeholders = Self::default();
+
+        if let Some(names) = request.get("ExpressionAttributeNames") {
+            let names = names
+                .as_object()
+                .ok_or_else(|| DynamoDbError::validation("ExpressionAttributeNames must be a map"))?;
+            for (placeholder, name) in names {
+                let name = name.as_str().ok_or_else(|| {
+                    DynamoDbError::validation("ExpressionAttributeNames values must be strings")
+                })?;
+                placeholders.names.insert(placeholder.clone(), name.to_string());
+            }
+        }
+
+        if let Some(values) = request.get("ExpressionAttributeValues") {
+            let values = values
+                .as_object()
+                .ok_or_else(|| DynamoDbError::validation("ExpressionAttributeValues must be a map"))?;
+            for (placeholder, value) in values {
+                placeholders
+                    .values
+                    .insert(placeholder.clone(), attribute_from_json(value)?);
+            }
+        }
+
+        Ok(placeholders)
+    }
+
+    fn merge(&mut self, other: Placeholders) -> DynamoResult<()> {
+        for (placeholder, name) in other.names {
+            match self.names.get(&placeholder) {
+                Some(existing) if *existing != name => {
+                    return Err(DynamoDbError::validation(format!(
+                        "Expression attribute name {} is bound to different names across actions",
+                        placeholder
+                    )));
+                }
+                _ => {
+                    self.names.insert(placeholder, name);
+                }
+            }
+        }
+        for (placeholder, value) in other.values {
+            match self.values.get(&placeholder) {
+                Some(existing) if *existing != value => {
+                    return Err(DynamoDbError::validation(format!(
+                        "Expression attribute value {} is bound to different values across actions",
+                        placeholder
+                    )));
+                }
+                _ => {
+                    self.values.insert(placeholder, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn context(&self) -> ExpressionContext {
+        let mut context = ExpressionContext::new();
+        for (placeholder, name) in &self.names {
+            context = context.with_name(placeholder.clone(), name.clone());
+        }
+        for (placeholder, value) in &self.values {
+            context = context.with_value(placeholder.clone(), value.clone());
+        }
+        context
+    }
+
+    fn resolve_name(&self, token: &str) -> DynamoResult<String> {
+        if token.starts_with('#') {
+            self.names.get(token).cloned().ok_or_else(|| {
+                DynamoDbError::validation(format!("Undefined expression attribute name: {}", token))
+            })
+        } else {
+            Ok(token.to_string())
+        }
+    }
+
+    fn resolve_value(&self, token: &str) -> DynamoResult<Value> {
+        self.values.get(token).cloned().ok_or_else(|| {
+            DynamoDbError::validation(format!("Undefined expression attribute value: {}", token))
+        })
+    }
+
+    /// Resolve a ProjectionExpression into dot-separated attribute paths
+    fn projection(&self, expression: &str) -> DynamoResult<Vec<String>> {
+        expression
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(|path| {
+                let segments = path
+                    .split('.')
+                    .map(|segment| self.resolve_name(segment.trim()))
+                    .collect::<DynamoResult<Vec<_>>>()?;
+                Ok(segments.join("."))
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+/// Extract partition and sort key bytes from an item (or a Key map)
+fn key_from_item(item: &Item, config: &DynamoDbConfig) -> DynamoResult<(Bytes, Option<Bytes>)> {
+    let pk = item.get(&config.pk_attr).ok_or_else(|| {
+        DynamoDbError::validation(format!("Missing partition key attribute: {}", config.pk_attr))
+    })?;
+    let pk = key_value_bytes(pk)?;
+    let sk = item.get(&config.sk_attr).map(key_value_bytes).transpose()?;
+    Ok((pk, sk))
+}
+
+fn to_key((pk, sk): (Bytes, Option<Bytes>)) -> Key {
+    match sk {
+        Some(sk) => Key::with_sk(pk, sk),
+        None => Key::new(pk),
+    }
+}
+
+/// Key attributes must be scalar: string, number, or binary
+fn key_value_bytes(value: &Value) -> DynamoResult<Bytes> {
+    match value {
+        Value::S(s) => Ok(Bytes::copy_from_slice(s.as_bytes())),
+        Value::N(n) => Ok(Bytes::copy_from_slice(n.as_bytes())),
+        Value::B(b) => Ok(b.clone()),
+        _ => Err(DynamoDbError::validation(
+            "Key attributes must be of type S, N, or B",
+        )),
+    }
+}
+
+/// Key bytes carry no type, so report UTF-8 keys as strings and anything else as binary
+fn key_bytes_to_value(bytes: &Bytes) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Value::S(s.to_string()),
+        Err(_) => Value::B(bytes.clone()),
+    }
+}
+
+// ============================================================================
+// AttributeValue conversion
+// ============================================================================
+
+/// Convert a DynamoDB JSON AttributeValue (e.g. `{"S": "hello"}`) to a Value
+pub fn attribute_from_json(json: &Json) -> DynamoResult<Value> {
+    let obj = json
+        .as_object()
+        .filter(|obj| obj.len() == 1)
+        .ok_or_else(|| DynamoDbError::validation("AttributeValue must contain exactly one type"))?;
+    let (tag, inner) = obj.iter().next().expect("checked length");
+
+    let invalid = || DynamoDbError::validation(format!("Invalid {} attribute value", tag));
+
+    match tag.as_str() {
+        "S" => Ok(Value::S(inner.as_str().ok_or_else(invalid)?.to_string())),
+        "N" => {
+            let n = inner.as_str().ok_or_else(invalid)?;
+            if n.trim().parse::<f64>().is_err() {
+                return Err(DynamoDbError::validation(format!("Invalid number: {}", n)));
+            }
+            Ok(Value::N(n.trim().to_string()))
+        }
+        "B" => {
+            let encoded = inner.as_str().ok_or_else(invalid)?;
+            let decoded = BASE64
+                .decode(encoded)
+                .map_err(|e| DynamoDbError::validation(format!("Invalid base64 binary: {}", e)))?;
+            Ok(Value::B(Bytes::from(decoded)))
+        }
+        "BOOL" => Ok(Value::Bool(inner.as_bool().ok_or_else(invalid)?)),
+        "NULL" => Ok(Value::Null),
+        "L" => inner
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(attribute_from_json)
+            .collect::<DynamoResult<Vec<_>>>()
+            .map(Value::L),
+        "M" => item_from_json(inner).map(Value::M),
+        "SS" | "NS" | "BS" => Err(DynamoDbError::validation(format!(
+            "Set type {} is not supported",
+            tag
+        ))),
+        other => Err(DynamoDbError::validation(format!(
+            "Unknown attribute type: {}",
+            other
+        ))),
+    }
+}
+
+/// Convert a Value to a DynamoDB JSON AttributeValue
+///
+/// Vectors are returned as lists of numbers and timestamps as numbers, since
+/// DynamoDB has no equivalent types.
+pub fn attribute_to_json(value: &Value) -> Json {
+    match value {
+        Value::S(s) => json!({ "S": s }),
+        Value::N(n) => json!({ "N": n }),
+        Value::B(b) => json!({ "B": BASE64.encode(b) }),
+        Value::Bool(b) => json!({ "BOOL": b }),
+        Value::Null => json!({ "NULL": true }),
+        Value::L(list) => json!({ "L": list.iter().map(attribute_to_json).collect::<Vec<_>>() }),
+        Value::M(map) => json!({ "M": item_to_json(map) }),
+        Value::VecF32(v) => json!({
+            "L": v.iter().map(|f| json!({ "N": f.to_string() })).collect::<Vec<_>>()
+        }),
+        Value::Ts(ts) => json!({ "N": ts.to_string() }),
+    }
+}
+
+/// Convert a DynamoDB JSON item (map of AttributeValues) to an Item
+pub fn item_from_json(json: &Json) -> DynamoResult<Item> {
+    json.as_object()
+        .ok_or_else(|| DynamoDbError::validation("Item must be a map of attribute values"))?
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), attribute_from_json(value)?)))
+        .collect()
+}
+
+/// Convert an Item to a DynamoDB JSON item
+pub fn item_to_json(item: &Item) -> Json {
+    Json::Object(
+        item.iter()
+            .map(|(name, value)| (name.clone(), attribute_to_json(value)))
+            .collect(),
+    )
+}
+
+// ============================================================================
+// KeyConditionExpression
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyOp {
+    Eq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Between,
+    BeginsWith,
+}
+
+/// One condition of a KeyConditionExpression, with names and values resolved
+#[derive(Debug, Clone, PartialEq)]
+struct KeyTerm {
+    attr: String,
+    op: KeyOp,
+    operands: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(KeyOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize_key_condition(input: &str) -> DynamoResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '=' => {
+                chars.next();
+                tokens.push(Token::Op(KeyOp::Eq));
+            }
+            '<' | '>' => {
+                chars.next();
+                let inclusive = chars.peek() == Some(&'=');
+                if inclusive {
+                    chars.next();
+                }
+                tokens.push(Token::Op(match (c, inclusive) {
+                    ('<', false) => KeyOp::Lt,
+                    ('<', true) => KeyOp::Lte,
+                    ('>', false) => KeyOp::Gt,
+                    _ => KeyOp::Gte,
+                }));
+            }
+            c if c.is_alphanumeric() || matches!(c, '#' | ':' | '_' | '-' | '.') => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || matches!(c, '#' | ':' | '_' | '-' | '.') {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            other => {
+                return Err(DynamoDbError::validation(format!(
+                    "Unexpected character in KeyConditionExpression: {}",
+                    other
+                )));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parse a KeyConditionExpression such as
+/// `#pk = :pk AND begins_with(#sk, :prefix)` into resolved terms
+fn parse_key_condition(input: &str, placeholders: &Placeholders) -> DynamoResult<Vec<KeyTerm>> {
+    let tokens = tokenize_key_condition(input)?;
+    let mut pos = 0;
+    let mut terms = Vec::new();
+
+    let invalid = || DynamoDbError::validation(format!("Invalid KeyConditionExpression: {}", input));
+
+    let next_word = |pos: &mut usize| -> DynamoResult<String> {
+        match tokens.get(*pos) {
+            Some(Token::Word(w)) => {
+                *pos += 1;
+                Ok(w.clone())
+            }
+            _ => Err(invalid()),
+        }
+    };
+    let expect = |pos: &mut usize, token: Token| -> DynamoResult<()> {
+        if tokens.get(*pos) == Some(&token) {
+            *pos += 1;
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    };
+
+    loop {
+        let first = next_word(&mut pos)?;
+
+        let term = if first.eq_ignore_ascii_case("begins_with") {
+            expect(&mut pos, Token::LParen)?;
+            let attr = placeholders.resolve_name(&next_word(&mut pos)?)?;
+            expect(&mut pos, Token::Comma)?;
+            let prefix = placeholders.resolve_value(&next_word(&mut pos)?)?;
+            expect(&mut pos, Token::RParen)?;
+            KeyTerm {
+                attr,
+                op: KeyOp::BeginsWith,
+                operands: vec![prefix],
+            }
+        } else {
+            let attr = placeholders.resolve_name(&first)?;
+            match tokens.get(pos) {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    pos += 1;
+                    let value = placeholders.resolve_value(&next_word(&mut pos)?)?;
+                    KeyTerm {
+                        attr,
+                        op,
+                        operands: vec![value],
+                    }
+                }
+                Some(Token::Word(w)) if w.eq_ignore_ascii_case("BETWEEN") => {
+                    pos += 1;
+                    let low = placeholders.resolve_value(&next_word(&mut pos)?)?;
+                    match next_word(&mut pos)? {
+                        w if w.eq_ignore_ascii_case("AND") => {}
+                        _ => return Err(invalid()),
+                    }
+                    let high = placeholders.resolve_value(&next_word(&mut pos)?)?;
+                    KeyTerm {
+                        attr,
+                        op: KeyOp::Between,
+                        operands: vec![low, high],
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        };
+        terms.push(term);
+
+        match tokens.get(pos) {
+            None => break,
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("AND") => pos += 1,
+            _ => return Err(invalid()),
+        }
+    }
+
+    if terms.len() > 2 {
+        return Err(DynamoDbError::validation(
+            "KeyConditionExpression supports at most a partition key and a sort key condition",
+        ));
+    }
+
+    Ok(terms)
+}
+
+/// Split parsed terms into the partition key equality and optional sort key condition.
+///
+/// For the base table the terms must name the configured key attributes. Index
+/// key attribute names are not known here, so for index queries the equality
+/// term written first is taken as the partition key.
+fn split_key_terms(
+    mut terms: Vec<KeyTerm>,
+    config: &DynamoDbConfig,
+    is_index: bool,
+) -> DynamoResult<(KeyTerm, Option<KeyTerm>)> {
+    let pk_index = terms
+        .iter()
+        .position(|t| t.op == KeyOp::Eq && (is_index || t.attr == config.pk_attr))
+        .ok_or_else(|| {
+            DynamoDbError::validation("KeyConditionExpression must include a partition key equality")
+        })?;
+
+    let pk_term = terms.remove(pk_index);
+    let sk_term = terms.pop();
+
+    if let Some(term) = &sk_term {
+        if !is_index && term.attr != config.sk_attr {
+            return Err(DynamoDbError::validation(format!(
+                "Query key condition not supported on attribute: {}",
+                term.attr
+            )));
+        }
+    }
+
+    Ok((pk_term, sk_term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(values: &[(&str, Value)], names: &[(&str, &str)]) -> Placeholders {
+        Placeholders {
+            names: names
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            values: values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_attribute_roundtrip() {
+        let json = json!({
+            "name": { "S": "Alice" },
+            "age": { "N": "30" },
+            "data": { "B": "AQID" },
+            "active": { "BOOL": true },
+            "nothing": { "NULL": true },
+            "tags": { "L": [{ "S": "a" }, { "N": "1" }] },
+            "address": { "M": { "city": { "S": "Paris" } } }
+        });
+
+        let item = item_from_json(&json).unwrap();
+        assert_eq!(item.get("name"), Some(&Value::string("Alice")));
+        assert_eq!(item.get("age"), Some(&Value::number(30)));
+        assert_eq!(item.get("data"), Some(&Value::B(Bytes::from(vec![1, 2, 3]))));
+        assert_eq!(item.get("active"), Some(&Value::Bool(true)));
+        assert_eq!(item.get("nothing"), Some(&Value::Null));
+
+        assert_eq!(item_to_json(&item), json);
+    }
+
+    #[test]
+    fn test_attribute_rejects_invalid() {
+        assert!(attribute_from_json(&json!({ "N": "abc" })).is_err());
+        assert!(attribute_from_json(&json!({ "S": "a", "N": "1" })).is_err());
+        assert!(attribute_from_json(&json!({ "SS": ["a"] })).is_err());
+        assert!(attribute_from_json(&json!({ "B": "not base64!" })).is_err());
+    }
+
+    #[test]
+    fn test_attribute_to_json_extended_types() {
+        assert_eq!(attribute_to_json(&Value::Ts(1000)), json!({ "N": "1000" }));
+        assert_eq!(
+            attribute_to_json(&Value::VecF32(vec![1.0, 0.5])),
+            json!({ "L": [{ "N": "1" }, { "N": "0.5" }] })
+        );
+    }
+
+    #[test]
+    fn test_parse_key_condition() {
+        let p = placeholders(
+            &[(":pk", Value::string("user#1")), (":prefix", Value::string("post#"))],
+            &[("#s", "sk")],
+        );
+
+        let terms = parse_key_condition("pk = :pk AND begins_with(#s, :prefix)", &p).unwrap();
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].attr, "pk");
+        assert_eq!(terms[0].op, KeyOp::Eq);
+        assert_eq!(terms[1].attr, "sk");
+        assert_eq!(terms[1].op, KeyOp::BeginsWith);
+        assert_eq!(terms[1].operands, vec![Value::string("post#")]);
+    }
+
+    #[test]
+    fn test_parse_key_condition_between_and_comparisons() {
+        let p = placeholders(
+            &[
+                (":pk", Value::string("a")),
+                (":lo", Value::string("1")),
+                (":hi", Value::string("5")),
+            ],
+            &[],
+        );
+
+        let terms = parse_key_condition("pk = :pk AND sk BETWEEN :lo AND :hi", &p).unwrap();
+        assert_eq!(terms[1].op, KeyOp::Between);
+        assert_eq!(terms[1].operands.len(), 2);
+
+        let terms = parse_key_condition("pk = :pk AND sk >= :lo", &p).unwrap();
+        assert_eq!(terms[1].op, KeyOp::Gte);
+
+        let terms = parse_key_condition("pk = :pk and sk < :hi", &p).unwrap();
+        assert_eq!(terms[1].op, KeyOp::Lt);
+    }
+
+    #[test]
+    fn test_parse_key_condition_errors() {
+        let p = placeholders(&[(":pk", Value::string("a"))], &[]);
+
+        assert!(parse_key_condition("pk = :missing", &p).is_err());
+        assert!(parse_key_condition("#undefined = :pk", &p).is_err());
+        assert!(parse_key_condition("pk = :pk AND", &p).is_err());
+        assert!(parse_key_condition("pk :pk", &p).is_err());
+    }
+
+    #[test]
+    fn test_split_key_terms() {
+        let config = DynamoDbConfig::default();
+        let p = placeholders(&[(":pk", Value::string("a")), (":sk", Value::string("b"))], &[]);
+
+        let terms = parse_key_condition("sk > :sk AND pk = :pk", &p).unwrap();
+        let (pk, sk) = split_key_terms(terms, &config, false).unwrap();
+        assert_eq!(pk.attr, "pk");
+        assert_eq!(sk.unwrap().op, KeyOp::Gt);
+
+        let terms = parse_key_condition("other = :pk", &p).unwrap();
+        assert!(split_key_terms(terms, &config, false).is_err());
+
+        let terms = parse_key_condition("other = :pk", &p).unwrap();
+        assert!(split_key_terms(terms, &config, true).is_ok());
+    }
+
+    #[test]
+    fn test_projection_resolves_names() {
+        let p = placeholders(&[], &[("#n", "name"), ("#a", "address")]);
+        let paths = p.projection("#n, #a.city ,age").unwrap();
+        assert_eq!(paths, vec!["name", "address.city", "age"]);
+    }
+
+    #[test]
+    fn test_dispatch_put_get_query() {
+        let db = Database::create_in_memory().unwrap();
+        let config = DynamoDbConfig::default();
+
+        for i in 1..=3 {
+            dispatch(
+                &db,
+                &config,
+                "PutItem",
+                &json!({
+                    "TableName": "t",
+                    "Item": {
+                        "pk": { "S": "user#1" },
+                        "sk": { "S": format!("post#{}", i) },
+                        "n": { "N": i.to_string() }
+                    }
+                }),
+            )
+            .unwrap();
+        }
+
+        let got = dispatch(
+            &db,
+            &config,
+            "GetItem",
+            &json!({ "Key": { "pk": { "S": "user#1" }, "sk": { "S": "post#2" } } }),
+        )
+        .unwrap();
+        assert_eq!(got["Item"]["n"], json!({ "N": "2" }));
+
+        let page = dispatch(
+            &db,
+            &config,
+            "Query",
+            &json!({
+                "KeyConditionExpression": "pk = :pk AND sk > :sk",
+                "ExpressionAttributeValues": {
+                    ":pk": { "S": "user#1" },
+                    ":sk": { "S": "post#1" }
+                },
+                "Limit": 1
+            }),
+        )
+        .unwrap();
+        assert_eq!(page["Count"], json!(1));
+        assert_eq!(page["Items"][0]["sk"], json!({ "S": "post#2" }));
+        assert_eq!(
+            page["LastEvaluatedKey"],
+            json!({ "pk": { "S": "user#1" }, "sk": { "S": "post#2" } })
+        );
+    }
+
+    #[test]
+    fn test_dispatch_conditional_put_error_shape() {
+        let db = Database::create_in_memory().unwrap();
+        let config = DynamoDbConfig::default();
+        let request = json!({
+            "Item": { "pk": { "S": "k" } },
+            "ConditionExpression": "attribute_not_exists(pk)"
+        });
+
+        dispatch(&db, &config, "PutItem", &request).unwrap();
+        let err = dispatch(&db, &config, "PutItem", &request).unwrap_err();
+        assert_eq!(err.kind(), "ConditionalCheckFailedException");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+
+        let err = dispatch(&db, &config, "CreateBackup", &json!({})).unwrap_err();
+        assert_eq!(err.kind(), "UnknownOperationException");
+    }
+
+    #[test]
+    fn test_dispatch_batch_and_transact_write() {
+        let db = Database::create_in_memory().unwrap();
+        let config = DynamoDbConfig::default();
+
+        let response = dispatch(
+            &db,
+            &config,
+            "BatchWriteItem",
+            &json!({
+                "RequestItems": {
+                    "t": [
+                        { "PutRequest": { "Item": { "pk": { "S": "a" }, "v": { "N": "1" } } } },
+                        { "PutRequest": { "Item": { "pk": { "S": "b" }, "v": { "N": "2" } } } }
+                    ]
+                }
+            }),
+        )
+        .unwrap();
+        assert_eq!(response["UnprocessedItems"], json!({}));
+
+        dispatch(
+            &db,
+            &config,
+            "TransactWriteItems",
+            &json!({
+                "TransactItems": [
+                    { "Delete": { "Key": { "pk": { "S": "a" } } } },
+                    {
+                        "Update": {
+                            "Key": { "pk": { "S": "b" } },
+                            "UpdateExpression": "SET v = :v",
+                            "ExpressionAttributeValues": { ":v": { "N": "5" } }
+                        }
+                    }
+                ]
+            }),
+        )
+        .unwrap();
+
+        assert!(db.get(b"a").unwrap().is_none());
+        assert_eq!(db.get(b"b").unwrap().unwrap().get("v"), Some(&Value::number(5)));
+
+        let page = dispatch(&db, &config, "Scan", &json!({})).unwrap();
+        assert_eq!(page["Count"], json!(1));
+    }
+}
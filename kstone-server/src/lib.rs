@@ -3,15 +3,27 @@
 /// This crate implements a gRPC server for KeystoneDB, enabling remote access
 /// to the database over the network.
 
+pub mod auth;
 pub mod connection;
 pub mod convert;
+pub mod deadline;
+pub mod dynamodb;
 pub mod metrics;
 pub mod rate_limit;
+pub mod registry;
 pub mod service;
+pub mod shutdown;
+pub mod trace_context;
 
 // Re-export key types
+pub use auth::{AuthInterceptor, Authenticator, JwtValidator, Permission, Policy, Principal};
 pub use connection::ConnectionManager;
+pub use deadline::RpcTimeouts;
+pub use dynamodb::DynamoDbConfig;
 pub use kstone_api::Database;
 pub use kstone_proto::keystone_db_server::KeystoneDbServer;
 pub use rate_limit::RateLimiter;
+pub use registry::DatabaseRegistry;
 pub use service::KeystoneService;
+pub use shutdown::Shutdown;
+pub use trace_context::TraceParent;
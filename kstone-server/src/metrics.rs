@@ -3,12 +3,23 @@
 /// This module defines and manages all metrics exposed by the server.
 /// Metrics are collected automatically by instrumented RPC handlers and
 /// exposed at the /metrics endpoint in Prometheus format.
+///
+/// Engine statistics (memtables, SSTs, compaction, flushes, streams) are read
+/// from the database on every scrape by an `EngineCollector`.
 
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use kstone_api::Database;
 use lazy_static::lazy_static;
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
 use prometheus::{
     opts, histogram_opts, register_histogram_vec, register_int_counter_vec, register_int_gauge,
-    HistogramVec, IntCounterVec, IntGauge, Registry, TextEncoder, Encoder,
+    Gauge, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry, TextEncoder, Encoder,
 };
+use std::sync::{Arc, Mutex};
+use tonic::{Response, Status};
+
+use crate::shutdown::Shutdown;
 
 lazy_static! {
     /// Global Prometheus registry
@@ -121,11 +132,279 @@ pub fn register_metrics() {
         .expect("Failed to register RATE_LIMITED_REQUESTS");
 }
 
+/// Register engine statistics of `db` with the global registry
+pub fn register_engine_metrics(db: Arc<Database>) {
+    REGISTRY
+        .register(Box::new(EngineCollector::new(db)))
+        .expect("Failed to register engine metrics");
+}
+
 /// Encode metrics in Prometheus text format
 pub fn encode_metrics() -> Result<String, Box<dyn std::error::Error>> {
+    encode_registry(&REGISTRY)
+}
+
+fn encode_registry(registry: &Registry) -> Result<String, Box<dyn std::error::Error>> {
     let encoder = TextEncoder::new();
-    let metric_families = REGISTRY.gather();
+    let metric_families = registry.gather();
     let mut buffer = vec![];
     encoder.encode(&metric_families, &mut buffer)?;
     Ok(String::from_utf8(buffer)?)
 }
+
+/// HTTP routes for scraping and probes: `/metrics`, `/health` and `/ready`
+pub fn router() -> Router {
+    router_with_shutdown(Shutdown::new())
+}
+
+/// Like `router`, with `/ready` returning 503 once `shutdown` is triggered
+pub fn router_with_shutdown(shutdown: Shutdown) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/health", get(health_handler))
+        .route("/ready", get(ready_handler))
+        .with_state(shutdown)
+}
+
+async fn metrics_handler() -> String {
+    encode_metrics().unwrap_or_else(|e| {
+        tracing::error!("Failed to encode metrics: {}", e);
+        String::from("# Error encoding metrics\n")
+    })
+}
+
+async fn health_handler() -> &'static str {
+    "OK"
+}
+
+async fn ready_handler(State(shutdown): State<Shutdown>) -> (StatusCode, &'static str) {
+    if shutdown.is_triggered() {
+        (StatusCode::SERVICE_UNAVAILABLE, "SHUTTING DOWN")
+    } else {
+        (StatusCode::OK, "OK")
+    }
+}
+
+/// Counts and times one RPC
+///
+/// The request is counted as an error unless it finishes through `ok`, so
+/// early returns with `?` are recorded without extra code.
+pub struct RpcMetrics {
+    method: &'static str,
+    timer: Option<HistogramTimer>,
+    succeeded: bool,
+}
+
+impl RpcMetrics {
+    /// Start timing an RPC
+    pub fn start(method: &'static str) -> Self {
+        Self {
+            method,
+            timer: Some(RPC_DURATION_SECONDS.with_label_values(&[method]).start_timer()),
+            succeeded: false,
+        }
+    }
+
+    /// Finish the RPC successfully with `response`
+    pub fn ok<T>(mut self, response: Response<T>) -> Result<Response<T>, Status> {
+        self.succeeded = true;
+        Ok(response)
+    }
+}
+
+impl Drop for RpcMetrics {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.observe_duration();
+        }
+        let status = if self.succeeded { "success" } else { "error" };
+        RPC_REQUESTS_TOTAL.with_label_values(&[self.method, status]).inc();
+    }
+}
+
+/// Collector exporting a database's engine statistics at scrape time
+pub struct EngineCollector {
+    db: Arc<Database>,
+    /// Serializes scrapes, since counters are reset and refilled
+    lock: Mutex<()>,
+    memtable_bytes: IntGauge,
+    memtable_fill: Gauge,
+    sst_files: IntGauge,
+    compactions: IntCounter,
+    compaction_bytes_read: IntCounter,
+    compaction_bytes_written: IntCounter,
+    compaction_bytes_reclaimed: IntCounter,
+    active_compactions: IntGauge,
+    pending_flushes: IntGauge,
+    write_stalls: IntCounter,
+    stream_records: IntGauge,
+    stream_oldest_record_age: Gauge,
+    stream_subscriptions: IntGauge,
+    stream_subscriber_lag: IntGauge,
+    reads: IntCounter,
+    writes: IntCounter,
+    hot_partitions: IntGaugeVec,
+}
+
+impl EngineCollector {
+    /// Create a collector for `db`
+    pub fn new(db: Arc<Database>) -> Self {
+        let int_gauge = |name: &str, help: &str| IntGauge::with_opts(opts!(name, help)).unwrap();
+        let counter = |name: &str, help: &str| IntCounter::with_opts(opts!(name, help)).unwrap();
+
+        Self {
+            db,
+            lock: Mutex::new(()),
+            memtable_bytes: int_gauge("kstone_memtable_bytes", "Approximate size of the active memtables in bytes"),
+            memtable_fill: Gauge::with_opts(opts!(
+                "kstone_memtable_fill_ratio",
+                "How close the fullest memtable is to being flushed (0 to 1)"
+            ))
+            .unwrap(),
+            sst_files: int_gauge("kstone_sst_files", "Number of SST files"),
+            compactions: counter("kstone_compactions_total", "Total number of compactions"),
+            compaction_bytes_read: counter("kstone_compaction_read_bytes_total", "Total bytes read by compactions"),
+            compaction_bytes_written: counter("kstone_compaction_written_bytes_total", "Total bytes written by compactions"),
+            compaction_bytes_reclaimed: counter("kstone_compaction_reclaimed_bytes_total", "Total bytes reclaimed by compactions"),
+            active_compactions: int_gauge("kstone_active_compactions", "Number of running compactions"),
+            pending_flushes: int_gauge("kstone_pending_flushes", "Full memtables waiting to be flushed"),
+            write_stalls: counter("kstone_write_stalls_total", "Writes that had to flush inline"),
+            stream_records: int_gauge("kstone_stream_records", "Stream records currently retained"),
+            stream_oldest_record_age: Gauge::with_opts(opts!(
+                "kstone_stream_oldest_record_age_seconds",
+                "Age of the oldest retained stream record"
+            ))
+            .unwrap(),
+            stream_subscriptions: int_gauge("kstone_stream_subscriptions", "Live stream subscriptions"),
+            stream_subscriber_lag: int_gauge(
+                "kstone_stream_subscriber_lag_records",
+                "Stream records the furthest-behind subscription has not yet dispatched",
+            ),
+            reads: counter("kstone_engine_reads_total", "Gets, queries and scans run by the engine"),
+            writes: counter("kstone_engine_writes_total", "Puts, deletes and updates run by the engine"),
+            hot_partitions: IntGaugeVec::new(
+                opts!(
+                    "kstone_hot_partition_operations",
+                    "Estimated recent operations on the busiest partitions"
+                ),
+                &["pk"],
+            )
+            .unwrap(),
+        }
+    }
+
+    fn collectors(&self) -> [&dyn Collector; 17] {
+        [
+            &self.memtable_bytes,
+            &self.memtable_fill,
+            &self.sst_files,
+            &self.compactions,
+            &self.compaction_bytes_read,
+            &self.compaction_bytes_written,
+            &self.compaction_bytes_reclaimed,
+            &self.active_compactions,
+            &self.pending_flushes,
+            &self.write_stalls,
+            &self.stream_records,
+            &self.stream_oldest_record_age,
+            &self.stream_subscriptions,
+            &self.stream_subscriber_lag,
+            &self.reads,
+            &self.writes,
+            &self.hot_partitions,
+        ]
+    }
+
+    fn update(&self) -> kstone_core::Result<()> {
+        let detailed = self.db.stats_detailed()?;
+        let stats = &detailed.stats;
+        let set_counter = |counter: &IntCounter, value: u64| {
+            counter.reset();
+            counter.inc_by(value);
+        };
+
+        self.memtable_bytes.set(stats.memtable_size_bytes.unwrap_or(0) as i64);
+        let config = self.db.describe()?.config;
+        self.memtable_fill.set(config.map_or(0.0, |config| detailed.memtable_fill(&config)));
+        self.sst_files.set(stats.total_sst_files as i64);
+        set_counter(&self.compactions, stats.compaction.total_compactions);
+        set_counter(&self.compaction_bytes_read, stats.compaction.total_bytes_read);
+        set_counter(&self.compaction_bytes_written, stats.compaction.total_bytes_written);
+        set_counter(&self.compaction_bytes_reclaimed, stats.compaction.total_bytes_reclaimed);
+        self.active_compactions.set(stats.compaction.active_compactions as i64);
+        self.pending_flushes.set(stats.flush.pending_flushes as i64);
+        set_counter(&self.write_stalls, stats.flush.write_stalls);
+        self.stream_records.set(stats.stream.retained_records as i64);
+        self.stream_oldest_record_age
+            .set(stats.stream.oldest_record_age_ms.unwrap_or(0) as f64 / 1000.0);
+        self.stream_subscriptions.set(stats.stream.subscriptions as i64);
+        self.stream_subscriber_lag.set(stats.stream.max_subscriber_lag as i64);
+        set_counter(&self.reads, stats.operations.reads);
+        set_counter(&self.writes, stats.operations.writes);
+
+        // Only the current busiest partitions, so the label set stays small
+        self.hot_partitions.reset();
+        for partition in &stats.operations.hot_partitions {
+            self.hot_partitions
+                .with_label_values(&[&String::from_utf8_lossy(&partition.pk)])
+                .set(partition.operations as i64);
+        }
+        Ok(())
+    }
+}
+
+impl Collector for EngineCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.collectors().into_iter().flat_map(|collector| collector.desc()).collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(e) = self.update() {
+            // Export the last values rather than failing the whole scrape
+            tracing::warn!("Failed to read engine statistics: {}", e);
+        }
+        self.collectors().into_iter().flat_map(|collector| collector.collect()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_engine_collector() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let registry = Registry::new();
+        registry.register(Box::new(EngineCollector::new(db.clone()))).unwrap();
+
+        db.put(b"key1", kstone_api::ItemBuilder::new().number("n", 1).build()).unwrap();
+        db.flush().unwrap();
+
+        let output = encode_registry(&registry).unwrap();
+        assert!(output.contains("kstone_sst_files 1"));
+        assert!(output.contains("# TYPE kstone_compactions_total counter"));
+        assert!(output.contains("kstone_stream_subscriber_lag_records 0"));
+        assert!(output.contains("kstone_engine_writes_total 1"));
+        assert!(output.contains(r#"kstone_hot_partition_operations{pk="key1"} 16"#));
+
+        // Scraping again does not double-count counters
+        let output = encode_registry(&registry).unwrap();
+        assert!(output.contains("kstone_compactions_total 0"));
+    }
+
+    #[test]
+    fn test_rpc_metrics_counts_errors_by_default() {
+        let errors = || RPC_REQUESTS_TOTAL.with_label_values(&["test_rpc", "error"]).get();
+        let successes = || RPC_REQUESTS_TOTAL.with_label_values(&["test_rpc", "success"]).get();
+
+        drop(RpcMetrics::start("test_rpc"));
+        assert_eq!(errors(), 1);
+
+        RpcMetrics::start("test_rpc").ok(Response::new(())).unwrap();
+        assert_eq!(successes(), 1);
+        assert_eq!(errors(), 1);
+    }
+}
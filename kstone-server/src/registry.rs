@@ -0,0 +1,331 @@
+/// Serving many databases from one server
+///
+/// A `DatabaseRegistry` maps database names to directories under a root
+/// directory. Each request names its database with the `kstone-database`
+/// metadata key; databases are opened on first use and the least recently
+/// used idle one is closed once more than `max_open` are open. A database
+/// can also be held to its own request rate, so one busy database doesn't
+/// starve the others.
+
+use governor::{
+    clock::DefaultClock,
+    state::{InMemoryState, NotKeyed},
+    Quota, RateLimiter as GovernorRateLimiter,
+};
+use kstone_api::Database;
+use kstone_core::{Error as KsError, Result};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::metrics::RATE_LIMITED_REQUESTS;
+
+/// Metadata key naming the database a request is for
+pub const DATABASE_HEADER: &str = "kstone-database";
+
+/// Databases kept open unless configured otherwise
+pub const DEFAULT_MAX_OPEN_DATABASES: usize = 16;
+
+/// Longest accepted database name
+const MAX_DATABASE_NAME_LEN: usize = 64;
+
+type Limiter = GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+/// An open database and when it was last used
+struct OpenDatabase {
+    db: Arc<Database>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    open: HashMap<String, OpenDatabase>,
+    /// Kept across closes so reopening a database doesn't reset its rate
+    limiters: HashMap<String, Arc<Limiter>>,
+    /// Ticks on every use, ordering `last_used`
+    clock: u64,
+}
+
+/// Databases under a root directory, opened lazily by name
+pub struct DatabaseRegistry {
+    root: PathBuf,
+    max_open: usize,
+    create_missing: bool,
+    max_rps_per_database: u32,
+    trace_operations: bool,
+    state: Mutex<State>,
+    /// Serializes opens so a database is never opened twice
+    opening: Mutex<()>,
+}
+
+impl DatabaseRegistry {
+    /// Serve the databases in `root`, each a subdirectory named after it
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_open: DEFAULT_MAX_OPEN_DATABASES,
+            create_missing: false,
+            max_rps_per_database: 0,
+            trace_operations: false,
+            state: Mutex::new(State::default()),
+            opening: Mutex::new(()),
+        }
+    }
+
+    /// Keep at most `max_open` databases open (at least 1)
+    ///
+    /// Only idle databases are closed, so while the open ones are serving
+    /// requests the registry may briefly hold more.
+    pub fn with_max_open(mut self, max_open: usize) -> Self {
+        self.max_open = max_open.max(1);
+        self
+    }
+
+    /// Create a database the first time a request names it
+    ///
+    /// Without this, requests for a database that doesn't exist fail with
+    /// `NOT_FOUND`.
+    pub fn with_create_missing(mut self, create_missing: bool) -> Self {
+        self.create_missing = create_missing;
+        self
+    }
+
+    /// Limit each database to `rps` requests per second (0 = unlimited)
+    pub fn with_max_rps_per_database(mut self, rps: u32) -> Self {
+        self.max_rps_per_database = rps;
+        self
+    }
+
+    /// Emit engine operation spans in every database opened
+    pub fn with_trace_operations(mut self, enabled: bool) -> Self {
+        self.trace_operations = enabled;
+        self
+    }
+
+    /// The root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Admit a request to `name` and return the database if it's open
+    ///
+    /// Fails with `ResourceExhausted` past the database's rate. Returns
+    /// `None` if the database has to be opened with `open` first.
+    pub fn lookup(&self, name: &str) -> Result<Option<Arc<Database>>> {
+        validate_name(name)?;
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(limiter) = state.limiters.get(name) {
+            if limiter.check().is_err() {
+                RATE_LIMITED_REQUESTS.with_label_values(&["per_database"]).inc();
+                return Err(KsError::ResourceExhausted(format!(
+                    "Rate limit exceeded for database '{}'",
+                    name
+                )));
+            }
+        }
+
+        state.clock += 1;
+        let now = state.clock;
+        Ok(state.open.get_mut(name).map(|open| {
+            open.last_used = now;
+            Arc::clone(&open.db)
+        }))
+    }
+
+    /// Return database `name`, opening it if needed
+    ///
+    /// Blocks while the database is opened, which may replay its WAL.
+    pub fn open(&self, name: &str) -> Result<Arc<Database>> {
+        validate_name(name)?;
+
+        let _opening = self.opening.lock().unwrap();
+        if let Some(open) = self.state.lock().unwrap().open.get(name) {
+            return Ok(Arc::clone(&open.db));
+        }
+
+        let path = self.root.join(name);
+        let db = if path.exists() {
+            Database::open(&path)?
+        } else if self.create_missing {
+            info!("Creating database '{}' at {:?}", name, path);
+            Database::create(&path)?
+        } else {
+            return Err(KsError::NotFound(format!("Database '{}' does not exist", name)));
+        };
+        if self.trace_operations {
+            db.set_trace_operations(true)?;
+        }
+        let db = Arc::new(db);
+        info!("Opened database '{}'", name);
+
+        let closed = {
+            let mut state = self.state.lock().unwrap();
+            state.clock += 1;
+            let last_used = state.clock;
+            state.open.insert(name.to_string(), OpenDatabase { db: Arc::clone(&db), last_used });
+            // Only databases that exist get a limiter, so made-up names can't grow the map
+            if self.max_rps_per_database > 0 && !state.limiters.contains_key(name) {
+                let limiter = Arc::new(new_limiter(self.max_rps_per_database));
+                state.limiters.insert(name.to_string(), limiter);
+            }
+            self.close_idle(&mut state)
+        };
+        // Closing flushes the WAL, so do it outside the lock
+        drop(closed);
+
+        Ok(db)
+    }
+
+    /// Every database currently open, by name
+    pub fn open_databases(&self) -> Vec<(String, Arc<Database>)> {
+        let state = self.state.lock().unwrap();
+        state
+            .open
+            .iter()
+            .map(|(name, open)| (name.clone(), Arc::clone(&open.db)))
+            .collect()
+    }
+
+    /// Remove least recently used idle databases until at most `max_open` remain
+    fn close_idle(&self, state: &mut State) -> Vec<Arc<Database>> {
+        let mut closed = Vec::new();
+        while state.open.len() > self.max_open {
+            // Handles held by running requests keep their directory locked
+            let victim = state
+                .open
+                .iter()
+                .filter(|(_, open)| Arc::strong_count(&open.db) == 1)
+                .min_by_key(|(_, open)| open.last_used)
+                .map(|(name, _)| name.clone());
+            let Some(name) = victim else {
+                warn!("All {} open databases are busy, keeping them open", state.open.len());
+                break;
+            };
+            info!("Closing idle database '{}'", name);
+            closed.extend(state.open.remove(&name).map(|open| open.db));
+        }
+        closed
+    }
+}
+
+fn new_limiter(rps: u32) -> Limiter {
+    let rps = NonZeroU32::new(rps).unwrap_or(NonZeroU32::MIN);
+    GovernorRateLimiter::direct(Quota::per_second(rps))
+}
+
+/// Accept names of letters, digits, `-` and `_`, which are safe as directory names
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_DATABASE_NAME_LEN {
+        return Err(KsError::InvalidArgument(format!(
+            "Database name must be 1 to {} characters",
+            MAX_DATABASE_NAME_LEN
+        )));
+    }
+    if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return Err(KsError::InvalidArgument(format!(
+            "Invalid database name '{}': use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("orders").is_ok());
+        assert!(validate_name("tenant-42_prod").is_ok());
+
+        assert!(validate_name("").is_err());
+        assert!(validate_name("..").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name(&"x".repeat(MAX_DATABASE_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_registry_opens_lazily() {
+        let root = TempDir::new().unwrap();
+        let registry = DatabaseRegistry::new(root.path());
+
+        // Missing databases aren't created by default
+        assert!(matches!(registry.open("orders"), Err(KsError::NotFound(_))));
+
+        let registry = registry.with_create_missing(true);
+        assert!(registry.lookup("orders").unwrap().is_none());
+
+        let db = registry.open("orders").unwrap();
+        db.put(b"order#1", ItemBuilder::new().string("status", "new").build()).unwrap();
+        drop(db);
+
+        let db = registry.lookup("orders").unwrap().unwrap();
+        assert!(db.get(b"order#1").unwrap().is_some());
+        assert!(root.path().join("orders").exists());
+    }
+
+    #[test]
+    fn test_registry_closes_least_recently_used() {
+        let root = TempDir::new().unwrap();
+        let registry = DatabaseRegistry::new(root.path())
+            .with_create_missing(true)
+            .with_max_open(2);
+
+        registry.open("a").unwrap();
+        registry.open("b").unwrap();
+        registry.lookup("a").unwrap();
+
+        // "b" is the least recently used
+        registry.open("c").unwrap();
+        let mut open: Vec<String> = registry.open_databases().into_iter().map(|(name, _)| name).collect();
+        open.sort();
+        assert_eq!(open, vec!["a", "c"]);
+
+        // Reopening a closed database works, its lock was released
+        registry.open("b").unwrap();
+        assert_eq!(registry.open_databases().len(), 2);
+    }
+
+    #[test]
+    fn test_registry_keeps_busy_databases_open() {
+        let root = TempDir::new().unwrap();
+        let registry = DatabaseRegistry::new(root.path())
+            .with_create_missing(true)
+            .with_max_open(1);
+
+        // "a" is in use, so both stay open
+        let a = registry.open("a").unwrap();
+        registry.open("b").unwrap();
+        assert_eq!(registry.open_databases().len(), 2);
+
+        // Once idle, both give way to "c"
+        drop(a);
+        registry.open("c").unwrap();
+        let open = registry.open_databases();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].0, "c");
+    }
+
+    #[test]
+    fn test_registry_rate_limits_each_database() {
+        let root = TempDir::new().unwrap();
+        let registry = DatabaseRegistry::new(root.path())
+            .with_create_missing(true)
+            .with_max_rps_per_database(2);
+        registry.open("a").unwrap();
+        registry.open("b").unwrap();
+
+        assert!(registry.lookup("a").is_ok());
+        assert!(registry.lookup("a").is_ok());
+        assert!(matches!(registry.lookup("a"), Err(KsError::ResourceExhausted(_))));
+
+        // Other databases have their own budget
+        assert!(registry.lookup("b").is_ok());
+    }
+}
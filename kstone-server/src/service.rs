@@ -4,26 +4,208 @@
 /// protocol buffer interface to the KeystoneDB Database API.
 
 use bytes::Bytes;
-use kstone_api::Database;
+use futures::Stream;
+use kstone_api::{Database, ShardIteratorType, StreamSubscription, SubscriptionConfig};
 use kstone_core::Error as KsError;
 use kstone_proto::{self as proto, keystone_db_server::KeystoneDb};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tonic::metadata::MetadataMap;
 use tonic::{Request, Response, Status};
 use tracing::{error, info, instrument};
-use uuid::Uuid;
 
+use crate::auth::{Access, Authenticator, Principal};
 use crate::convert::*;
-use crate::metrics::{RPC_REQUESTS_TOTAL, RPC_DURATION_SECONDS};
+use crate::deadline::{request_deadline, RpcTimeouts};
+use crate::metrics::RpcMetrics;
+use crate::rate_limit::RateLimiter;
+use crate::registry::{DatabaseRegistry, DATABASE_HEADER};
+use crate::shutdown::Shutdown;
+use crate::trace_context::{spawn_blocking_in_span, trace_request};
+
+/// The database or databases a service serves
+enum Databases {
+    Single(Arc<Database>),
+    Registry(Arc<DatabaseRegistry>),
+}
 
 /// KeystoneDB gRPC service implementation
 pub struct KeystoneService {
-    db: Arc<Database>,
+    databases: Databases,
+    rate_limiter: RateLimiter,
+    authenticator: Option<Arc<Authenticator>>,
+    shutdown: Shutdown,
+    timeouts: RpcTimeouts,
 }
 
 impl KeystoneService {
     /// Create a new KeystoneService wrapping a Database
     pub fn new(db: Database) -> Self {
-        Self { db: Arc::new(db) }
+        Self::from_arc(Arc::new(db))
+    }
+
+    /// Create a KeystoneService sharing an existing Database handle
+    pub fn from_arc(db: Arc<Database>) -> Self {
+        Self::with_databases(Databases::Single(db))
+    }
+
+    /// Create a KeystoneService serving every database of a registry
+    ///
+    /// Each request names its database with the `kstone-database` metadata
+    /// key, which is then opened on first use.
+    pub fn from_registry(registry: Arc<DatabaseRegistry>) -> Self {
+        Self::with_databases(Databases::Registry(registry))
+    }
+
+    fn with_databases(databases: Databases) -> Self {
+        Self {
+            databases,
+            rate_limiter: RateLimiter::new(0, 0),
+            authenticator: None,
+            shutdown: Shutdown::new(),
+            timeouts: RpcTimeouts::new(),
+        }
+    }
+
+    /// Reject requests beyond the limiter's rate with `RESOURCE_EXHAUSTED`
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Require every request to be authenticated and allowed by its principal's policy
+    ///
+    /// Requests are authorized against the `Principal` an `AuthInterceptor`
+    /// attached, or against the request's own bearer token if the service
+    /// is served without one.
+    pub fn with_authenticator(mut self, authenticator: Arc<Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Drain when `shutdown` is triggered
+    ///
+    /// New requests are then rejected with `UNAVAILABLE` and stream
+    /// subscriptions end; requests already running finish normally.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Cap how long each RPC method may run
+    ///
+    /// Requests past their limit, or past the client's own deadline, fail
+    /// with `DEADLINE_EXCEEDED`.
+    pub fn with_timeouts(mut self, timeouts: RpcTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Deadline of a request to `method`, `None` if it may run indefinitely
+    fn deadline<T>(&self, request: &Request<T>, method: &'static str) -> Option<Instant> {
+        request_deadline(request.metadata(), self.timeouts.for_method(method))
+    }
+
+    /// The database a request is for
+    ///
+    /// A single-database service rejects requests naming a database, so a
+    /// client can't mistake it for one that keeps databases apart.
+    async fn database(&self, metadata: &MetadataMap) -> Result<Arc<Database>, Status> {
+        let name = match metadata.get(DATABASE_HEADER) {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .map_err(|_| Status::invalid_argument("Database name must be ASCII"))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let registry = match (&self.databases, name.as_deref()) {
+            (Databases::Single(db), None) => return Ok(Arc::clone(db)),
+            (Databases::Single(_), Some(_)) => {
+                return Err(Status::invalid_argument(format!(
+                    "This server serves a single database; don't send {} metadata",
+                    DATABASE_HEADER
+                )));
+            }
+            (Databases::Registry(registry), _) => registry,
+        };
+        let name = name.ok_or_else(|| {
+            Status::invalid_argument(format!("Requests must name a database with {} metadata", DATABASE_HEADER))
+        })?;
+
+        if let Some(db) = registry.lookup(&name).map_err(map_error)? {
+            return Ok(db);
+        }
+        let registry = Arc::clone(registry);
+        spawn_blocking_in_span(move || registry.open(&name))
+            .await
+            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+            .map_err(map_error)
+    }
+
+    /// The caller of a request, `None` if authentication is off
+    fn principal<T>(&self, request: &Request<T>) -> Result<Option<Principal>, Status> {
+        let Some(authenticator) = &self.authenticator else {
+            return Ok(None);
+        };
+        match request.extensions().get::<Principal>() {
+            Some(principal) => Ok(Some(principal.clone())),
+            None => authenticator.authenticate_metadata(request.metadata()).map(Some),
+        }
+    }
+
+    /// Check that the caller may access the given partitions
+    fn authorize_keys<'k, T>(
+        &self,
+        request: &Request<T>,
+        access: Access,
+        keys: impl IntoIterator<Item = &'k [u8]>,
+    ) -> Result<(), Status> {
+        if let Some(principal) = self.principal(request)? {
+            for pk in keys {
+                principal.policy.check_key(access, pk)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the caller may read the partition a query names
+    ///
+    /// A global secondary index partition gathers items of any base
+    /// partition, so index queries are checked item by item instead: the
+    /// returned principal is to be passed to `authorize_base_keys` once the
+    /// query has run.
+    fn authorize_query(&self, request: &Request<proto::QueryRequest>) -> Result<Option<Principal>, Status> {
+        let query = request.get_ref();
+        if query.index_name.is_some() {
+            return self.principal(request);
+        }
+        self.authorize_keys(request, Access::Read, [query.partition_key.as_slice()])?;
+        Ok(None)
+    }
+
+    /// Check that the caller may access the whole database
+    fn authorize_all<T>(&self, request: &Request<T>, access: Access) -> Result<(), Status> {
+        match self.principal(request)? {
+            Some(principal) => principal.policy.check_all(access),
+            None => Ok(()),
+        }
+    }
+
+    /// Start metrics for an RPC, refuse it while draining and apply rate limiting
+    ///
+    /// Rejected requests are counted as errors of their method.
+    fn start_rpc(&self, method: &'static str) -> Result<RpcMetrics, Status> {
+        let rpc = RpcMetrics::start(method);
+        if self.shutdown.is_triggered() {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        self.rate_limiter.check_rate_limit()?;
+        Ok(rpc)
     }
 }
 
@@ -31,6 +213,23 @@ impl KeystoneService {
 // Helper Functions
 // ============================================================================
 
+/// Check that the caller may read the base items an index query matched
+fn authorize_base_keys(principal: Option<&Principal>, response: &kstone_api::QueryResponse) -> Result<(), Status> {
+    let Some(principal) = principal.filter(|principal| !principal.policy.key_prefixes.is_empty()) else {
+        return Ok(());
+    };
+    // Entries of older indexes don't record their base key
+    if response.keys.len() < response.count {
+        return Err(Status::permission_denied(
+            "This index can't be queried with a key prefix restriction until it is rebuilt",
+        ));
+    }
+    for (pk, _) in &response.keys {
+        principal.policy.check_key(Access::Read, pk)?;
+    }
+    Ok(())
+}
+
 /// Map KeystoneDB errors to gRPC Status
 fn map_error(err: KsError) -> Status {
     match err {
@@ -52,6 +251,23 @@ fn map_error(err: KsError) -> Status {
         KsError::CompactionError(msg) => Status::internal(format!("Compaction error: {}", msg)),
         KsError::StripeError(msg) => Status::internal(format!("Stripe error: {}", msg)),
         KsError::ResourceExhausted(msg) => Status::resource_exhausted(format!("Resource exhausted: {}", msg)),
+        KsError::TrimmedDataAccess(msg) => Status::out_of_range(format!("Trimmed data access: {}", msg)),
+        KsError::ReadOnly(msg) => Status::permission_denied(format!("Database is read-only: {}", msg)),
+        KsError::DeadlineExceeded(msg) => Status::deadline_exceeded(msg),
+        err @ KsError::DatabaseLocked { .. } => Status::unavailable(err.to_string()),
+        err @ KsError::VersionConflict { .. } => Status::failed_precondition(err.to_string()),
+        err @ KsError::ItemTooLarge { .. } => Status::invalid_argument(err.to_string()),
+        err @ KsError::ValidationFailed(_) => Status::invalid_argument(err.to_string()),
+    }
+}
+
+/// Access a PartiQL statement needs: reads for SELECT and EXPLAIN, writes otherwise
+fn statement_access(statement: &str) -> Access {
+    let keyword = statement.trim_start().split_whitespace().next().unwrap_or("");
+    if keyword.eq_ignore_ascii_case("SELECT") || keyword.eq_ignore_ascii_case("EXPLAIN") {
+        Access::Read
+    } else {
+        Access::Write
     }
 }
 
@@ -73,6 +289,35 @@ fn value_to_key_bytes(value: proto::Value) -> Result<Bytes, Status> {
     }
 }
 
+/// Run a blocking database call, failing with `DEADLINE_EXCEEDED` at `deadline`
+///
+/// A call that is already running isn't interrupted: queries and scans
+/// given the same deadline stop on their own, but a write may still be
+/// applied after the client was told it timed out.
+async fn run_blocking<F, R>(deadline: Option<Instant>, f: F) -> Result<R, Status>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let result = match deadline {
+        Some(deadline) => {
+            if Instant::now() >= deadline {
+                return Err(deadline_exceeded());
+            }
+            let task = spawn_blocking_in_span(f);
+            tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), task)
+                .await
+                .map_err(|_| deadline_exceeded())?
+        }
+        None => spawn_blocking_in_span(f).await,
+    };
+    result.map_err(|e| Status::internal(format!("Task join error: {}", e)))
+}
+
+fn deadline_exceeded() -> Status {
+    Status::deadline_exceeded("Request deadline exceeded")
+}
+
 /// Apply sort key condition to query builder
 fn apply_sort_key_condition(
     query: kstone_api::Query,
@@ -125,6 +370,291 @@ fn apply_sort_key_condition(
     }
 }
 
+/// Build a query from its protobuf request
+fn query_from_proto(req: proto::QueryRequest, deadline: Option<Instant>) -> Result<kstone_api::Query, Status> {
+    // Build query starting with partition key
+    let mut query = kstone_api::Query::new(&req.partition_key);
+
+    if let Some(deadline) = deadline {
+        query = query.deadline(deadline);
+    }
+
+    // Apply sort key condition if present
+    if let Some(sk_cond) = req.sort_key_condition {
+        query = apply_sort_key_condition(query, sk_cond)?;
+    }
+
+    // Apply limit
+    if let Some(limit) = req.limit {
+        query = query.limit(limit as usize);
+    }
+
+    // Apply exclusive start key for pagination
+    if let Some(start_key) = req.exclusive_start_key {
+        let (pk, sk) = proto_last_key_to_ks(start_key);
+        query = query.start_after(&pk, sk.as_deref());
+    }
+
+    // Apply scan direction
+    if let Some(forward) = req.scan_forward {
+        query = query.forward(forward);
+    }
+
+    // Apply index name
+    if let Some(index_name) = req.index_name {
+        query = query.index(index_name);
+    }
+
+    // Apply filter expression and its values
+    if let Some(filter) = req.filter_expression {
+        query = query.filter(filter);
+        for (placeholder, proto_value) in req.expression_values {
+            let value = proto_value_to_ks(proto_value)?;
+            query = query.value(placeholder, value);
+        }
+    }
+
+    Ok(query)
+}
+
+/// Build a scan from its protobuf request
+fn scan_from_proto(req: proto::ScanRequest, deadline: Option<Instant>) -> Result<kstone_api::Scan, Status> {
+    // TODO: Support index_name for GSI/LSI
+    if req.index_name.is_some() {
+        return Err(Status::unimplemented(
+            "Index scans not yet supported in server",
+        ));
+    }
+
+    // Build scan starting with defaults
+    let mut scan = kstone_api::Scan::new();
+
+    if let Some(deadline) = deadline {
+        scan = scan.deadline(deadline);
+    }
+
+    // Apply limit
+    if let Some(limit) = req.limit {
+        scan = scan.limit(limit as usize);
+    }
+
+    // Apply exclusive start key for pagination
+    if let Some(start_key) = req.exclusive_start_key {
+        let (pk, sk) = proto_last_key_to_ks(start_key);
+        scan = scan.start_after(&pk, sk.as_deref());
+    }
+
+    // Apply parallel scan segments
+    if let (Some(segment), Some(total_segments)) = (req.segment, req.total_segments) {
+        scan = scan.segment(segment as usize, total_segments as usize);
+    }
+
+    // Apply filter expression and its values
+    if let Some(filter) = req.filter_expression {
+        scan = scan.filter(filter);
+        for (placeholder, proto_value) in req.expression_values {
+            let value = proto_value_to_ks(proto_value)?;
+            scan = scan.value(placeholder, value);
+        }
+    }
+
+    Ok(scan)
+}
+
+/// Convert a query response to protobuf
+fn query_response_to_proto(response: kstone_api::QueryResponse) -> proto::QueryResponse {
+    proto::QueryResponse {
+        items: response.items.iter().map(ks_item_to_proto).collect(),
+        count: response.count as u32,
+        scanned_count: response.scanned_count as u32,
+        last_evaluated_key: ks_last_key_opt_to_proto(response.last_key),
+        error: None,
+    }
+}
+
+/// Convert a scan response to protobuf
+fn scan_response_to_proto(response: kstone_api::ScanResponse) -> proto::ScanResponse {
+    proto::ScanResponse {
+        items: response.items.iter().map(ks_item_to_proto).collect(),
+        count: response.count as u32,
+        scanned_count: response.scanned_count as u32,
+        last_evaluated_key: ks_last_key_opt_to_proto(response.last_key),
+        error: None,
+    }
+}
+
+// ============================================================================
+// Streaming Pagination
+// ============================================================================
+
+/// Page size of streamed queries and scans that don't set one
+pub const DEFAULT_STREAM_PAGE_SIZE: u32 = 100;
+
+/// Pages a stream produces ahead of the client before the producer waits
+const STREAM_BUFFER_PAGES: usize = 2;
+
+/// Response stream of the server-streaming RPCs
+pub type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// A page of a streamed query or scan
+trait StreamPage {
+    fn returned(&self) -> usize;
+    fn last_key(&self) -> Option<proto::LastKey>;
+}
+
+impl StreamPage for proto::QueryResponse {
+    fn returned(&self) -> usize {
+        self.items.len()
+    }
+
+    fn last_key(&self) -> Option<proto::LastKey> {
+        self.last_evaluated_key.clone()
+    }
+}
+
+impl StreamPage for proto::ScanResponse {
+    fn returned(&self) -> usize {
+        self.items.len()
+    }
+
+    fn last_key(&self) -> Option<proto::LastKey> {
+        self.last_evaluated_key.clone()
+    }
+}
+
+/// Fetch pages on the blocking pool and stream them as they are produced
+///
+/// `fetch(limit, start_key)` reads one page. Each page resumes after the
+/// previous page's last key until there is none or `limit` items were
+/// returned. The producer runs at most `STREAM_BUFFER_PAGES` ahead of the
+/// client and stops once the client goes away; an error ends the stream.
+fn stream_pages<P, F>(
+    limit: Option<u32>,
+    page_size: Option<u32>,
+    start_key: Option<proto::LastKey>,
+    mut fetch: F,
+) -> ResponseStream<P>
+where
+    P: StreamPage + Send + 'static,
+    F: FnMut(u32, Option<proto::LastKey>) -> Result<P, Status> + Send + 'static,
+{
+    let page_size = page_size.filter(|&size| size > 0).unwrap_or(DEFAULT_STREAM_PAGE_SIZE);
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_PAGES);
+
+    spawn_blocking_in_span(move || {
+        let mut remaining = limit;
+        let mut start_key = start_key;
+        loop {
+            let page_limit = remaining.map_or(page_size, |remaining| remaining.min(page_size));
+            let page = match fetch(page_limit, start_key.take()) {
+                Ok(page) => page,
+                Err(status) => {
+                    let _ = tx.blocking_send(Err(status));
+                    return;
+                }
+            };
+
+            if let Some(remaining) = remaining.as_mut() {
+                *remaining = remaining.saturating_sub(page.returned() as u32);
+            }
+            let next_key = page.last_key().filter(|_| remaining != Some(0));
+
+            // Waits while the buffer is full; fails once the client is gone
+            if tx.blocking_send(Ok(page)).is_err() || next_key.is_none() {
+                return;
+            }
+            start_key = next_key;
+        }
+    });
+
+    receiver_stream(rx)
+}
+
+// ============================================================================
+// Stream Subscriptions
+// ============================================================================
+
+/// Heartbeat interval of subscriptions that don't set one
+pub const DEFAULT_HEARTBEAT_INTERVAL_MS: u32 = 5000;
+
+/// Shortest heartbeat interval a subscription may ask for
+const MIN_HEARTBEAT_INTERVAL_MS: u32 = 100;
+
+/// Records forwarded ahead of the client before the subscription waits
+const SUBSCRIBE_BUFFER_RECORDS: usize = 256;
+
+/// Forward a subscription's records to the client, with heartbeats while idle
+///
+/// A slow client holds back the subscription's dispatcher rather than
+/// buffering records. The stream ends with an error if the subscription
+/// does, for example once it falls behind the stream's trim horizon, or
+/// when the server shuts down or the deadline passes; the forwarding task
+/// exits at most one heartbeat after any of these or the client going away.
+fn stream_records(
+    subscription: StreamSubscription,
+    heartbeat_interval: Duration,
+    deadline: Option<Instant>,
+    shutdown: Shutdown,
+) -> ResponseStream<proto::SubscribeStreamResponse> {
+    use proto::subscribe_stream_response::Event;
+
+    let (tx, rx) = mpsc::channel(SUBSCRIBE_BUFFER_RECORDS);
+
+    spawn_blocking_in_span(move || {
+        let mut last_sequence_number = None;
+        loop {
+            if shutdown.is_triggered() {
+                let _ = tx.blocking_send(Err(Status::unavailable("Server is shutting down")));
+                return;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                let _ = tx.blocking_send(Err(deadline_exceeded()));
+                return;
+            }
+
+            let event = match subscription.recv_timeout(heartbeat_interval) {
+                Ok(record) => {
+                    last_sequence_number = Some(record.sequence_number);
+                    Event::Record(ks_stream_record_to_proto(&record))
+                }
+                Err(e) if e.is_timeout() => Event::Heartbeat(proto::Heartbeat {
+                    last_sequence_number,
+                    timestamp: now_millis(),
+                }),
+                Err(_) => {
+                    let status = match subscription.take_error() {
+                        Some(e) => map_error(e),
+                        None => Status::unavailable("Stream subscription ended"),
+                    };
+                    let _ = tx.blocking_send(Err(status));
+                    return;
+                }
+            };
+
+            let response = proto::SubscribeStreamResponse { event: Some(event) };
+            if tx.blocking_send(Ok(response)).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver_stream(rx)
+}
+
+/// Milliseconds since the Unix epoch
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// Adapt the receiving end of a producer task's channel into a response stream
+fn receiver_stream<T: Send + 'static>(rx: mpsc::Receiver<Result<T, Status>>) -> ResponseStream<T> {
+    Box::pin(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|message| (message, rx))
+    }))
+}
+
 // ============================================================================
 // gRPC Service Implementation
 // ============================================================================
@@ -137,14 +667,15 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::PutRequest>,
     ) -> Result<Response<proto::PutResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
-        // Start timing
-        let timer = RPC_DURATION_SECONDS.with_label_values(&["put"]).start_timer();
+        let rpc = self.start_rpc("put")?;
+        let deadline = self.deadline(&request, "put");
+        self.authorize_keys(&request, Access::Write, [request.get_ref().partition_key.as_slice()])?;
 
         info!("Received put request");
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Convert key
@@ -162,9 +693,8 @@ impl KeystoneDb for KeystoneService {
                 .ok_or_else(|| Status::invalid_argument("Item required"))?,
         )?;
 
-        // Execute put operation (blocking DB call in spawn_blocking)
-        let db = Arc::clone(&self.db);
-        let result = tokio::task::spawn_blocking(move || {
+        // Execute put operation (blocking DB call on the blocking pool)
+        let result = run_blocking(deadline, move || {
             // Check if this is a conditional put
             if let Some(condition_expr) = req.condition_expression {
                 // Build expression context from expression_values
@@ -190,22 +720,17 @@ impl KeystoneDb for KeystoneService {
             }
             Ok::<_, KsError>(())
         })
-        .await
-        .map_err(|e| Status::internal(format!("Task join error: {}", e)))?;
+        .await?;
 
         match result {
             Ok(_) => {
-                timer.observe_duration();
-                RPC_REQUESTS_TOTAL.with_label_values(&["put", "success"]).inc();
                 info!("Put operation completed successfully");
-                Ok(Response::new(proto::PutResponse {
+                rpc.ok(Response::new(proto::PutResponse {
                     success: true,
                     error: None,
                 }))
             }
             Err(e) => {
-                timer.observe_duration();
-                RPC_REQUESTS_TOTAL.with_label_values(&["put", "error"]).inc();
                 error!(?e, "Put operation failed");
                 Err(map_error(e))
             }
@@ -218,11 +743,15 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::GetRequest>,
     ) -> Result<Response<proto::GetResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("get")?;
+        let deadline = self.deadline(&request, "get");
+        self.authorize_keys(&request, Access::Read, [request.get_ref().partition_key.as_slice()])?;
 
         info!("Received get request");
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Convert key
@@ -234,22 +763,20 @@ impl KeystoneDb for KeystoneService {
         tracing::Span::current().record("has_sk", sk.is_some());
 
         // Execute get operation
-        let db = Arc::clone(&self.db);
-        let result = tokio::task::spawn_blocking(move || {
+        let result = run_blocking(deadline, move || {
             if let Some(sk_bytes) = sk {
                 db.get_with_sk(&pk, &sk_bytes)
             } else {
                 db.get(&pk)
             }
         })
-        .await
-        .map_err(|e| Status::internal(format!("Task join error: {}", e)))?;
+        .await?;
 
         match result {
             Ok(item_opt) => {
                 tracing::Span::current().record("found", item_opt.is_some());
                 info!("Get operation completed");
-                Ok(Response::new(proto::GetResponse {
+                rpc.ok(Response::new(proto::GetResponse {
                     item: item_opt.map(|item| ks_item_to_proto(&item)),
                     error: None,
                 }))
@@ -267,10 +794,14 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::DeleteRequest>,
     ) -> Result<Response<proto::DeleteResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
+        let rpc = self.start_rpc("delete")?;
+        let deadline = self.deadline(&request, "delete");
+        self.authorize_keys(&request, Access::Write, [request.get_ref().partition_key.as_slice()])?;
+
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Convert key
@@ -280,8 +811,7 @@ impl KeystoneDb for KeystoneService {
         });
 
         // Execute delete operation
-        let db = Arc::clone(&self.db);
-        tokio::task::spawn_blocking(move || {
+        run_blocking(deadline, move || {
             // Check if this is a conditional delete
             if let Some(condition_expr) = req.condition_expression {
                 // Build expression context from expression_values
@@ -307,11 +837,10 @@ impl KeystoneDb for KeystoneService {
             }
             Ok::<_, KsError>(())
         })
-        .await
-        .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        .await?
         .map_err(map_error)?;
 
-        Ok(Response::new(proto::DeleteResponse {
+        rpc.ok(Response::new(proto::DeleteResponse {
             success: true,
             error: None,
         }))
@@ -333,63 +862,24 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::QueryRequest>,
     ) -> Result<Response<proto::QueryResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
-
-        let req = request.into_inner();
-
-        // Build query starting with partition key
-        let mut query = kstone_api::Query::new(&req.partition_key);
-
-        // Apply sort key condition if present
-        if let Some(sk_cond) = req.sort_key_condition {
-            query = apply_sort_key_condition(query, sk_cond)?;
-        }
-
-        // Apply limit
-        if let Some(limit) = req.limit {
-            query = query.limit(limit as usize);
-        }
-
-        // Apply exclusive start key for pagination
-        if let Some(start_key) = req.exclusive_start_key {
-            let (pk, sk) = proto_last_key_to_ks(start_key);
-            query = query.start_after(&pk, sk.as_deref());
-        }
-
-        // Apply scan direction
-        if let Some(forward) = req.scan_forward {
-            query = query.forward(forward);
-        }
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
-        // Apply index name
-        if let Some(index_name) = req.index_name {
-            query = query.index(index_name);
-        }
+        let rpc = self.start_rpc("query")?;
+        let deadline = self.deadline(&request, "query");
+        let principal = self.authorize_query(&request)?;
 
-        // TODO: Support filter_expression (needs expression context)
-        if req.filter_expression.is_some() {
-            return Err(Status::unimplemented(
-                "Filter expressions not yet supported in server",
-            ));
-        }
+        let db = self.database(request.metadata()).await?;
+        let req = request.into_inner();
+        let query = query_from_proto(req, deadline)?;
 
         // Execute query
-        let db = Arc::clone(&self.db);
-        let response = tokio::task::spawn_blocking(move || db.query(query))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        let response = run_blocking(deadline, move || db.query(query))
+            .await?
             .map_err(map_error)?;
+        authorize_base_keys(principal.as_ref(), &response)?;
 
-        // Convert response to protobuf
-        Ok(Response::new(proto::QueryResponse {
-            items: response.items.iter().map(ks_item_to_proto).collect(),
-            count: response.count as u32,
-            scanned_count: response.scanned_count as u32,
-            last_evaluated_key: ks_last_key_opt_to_proto(response.last_key),
-            error: None,
-        }))
+        rpc.ok(Response::new(query_response_to_proto(response)))
     }
 
     /// Scan items (streaming response)
@@ -402,64 +892,94 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::ScanRequest>,
     ) -> Result<Response<Self::ScanStream>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
+        let rpc = self.start_rpc("scan")?;
+        let deadline = self.deadline(&request, "scan");
+        self.authorize_all(&request, Access::Read)?;
+
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
+        let scan = scan_from_proto(req, deadline)?;
 
-        // Build scan starting with defaults
-        let mut scan = kstone_api::Scan::new();
+        // Execute scan
+        let response = run_blocking(deadline, move || db.scan(scan))
+            .await?
+            .map_err(map_error)?;
 
-        // Apply limit
-        if let Some(limit) = req.limit {
-            scan = scan.limit(limit as usize);
-        }
+        // Return as a single-item stream
+        let stream = futures::stream::once(futures::future::ready(Ok(scan_response_to_proto(response))));
+        rpc.ok(Response::new(stream))
+    }
 
-        // Apply exclusive start key for pagination
-        if let Some(start_key) = req.exclusive_start_key {
-            let (pk, sk) = proto_last_key_to_ks(start_key);
-            scan = scan.start_after(&pk, sk.as_deref());
-        }
+    /// Query items, streaming one response per page
+    type QueryStreamStream = ResponseStream<proto::QueryResponse>;
 
-        // Apply parallel scan segments
-        if let (Some(segment), Some(total_segments)) = (req.segment, req.total_segments) {
-            scan = scan.segment(segment as usize, total_segments as usize);
-        }
+    #[instrument(skip(self, request), fields(trace_id))]
+    async fn query_stream(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<Self::QueryStreamStream>, Status> {
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
-        // TODO: Support filter_expression (needs expression context)
-        if req.filter_expression.is_some() {
-            return Err(Status::unimplemented(
-                "Filter expressions not yet supported in server",
-            ));
-        }
+        let rpc = self.start_rpc("query_stream")?;
+        let deadline = self.deadline(&request, "query_stream");
+        let principal = self.authorize_query(&request)?;
 
-        // TODO: Support index_name for GSI/LSI
-        if req.index_name.is_some() {
-            return Err(Status::unimplemented(
-                "Index scans not yet supported in server",
-            ));
-        }
+        let db = self.database(request.metadata()).await?;
+        let req = request.into_inner();
 
-        // Execute scan
-        let db = Arc::clone(&self.db);
-        let response = tokio::task::spawn_blocking(move || db.scan(scan))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
-            .map_err(map_error)?;
+        // Reject a malformed request as the RPC's status rather than mid-stream
+        query_from_proto(req.clone(), deadline)?;
+
+        let stream = stream_pages(req.limit, req.page_size, req.exclusive_start_key.clone(), move |limit, start_key| {
+            let query = query_from_proto(proto::QueryRequest {
+                limit: Some(limit),
+                exclusive_start_key: start_key,
+                ..req.clone()
+            }, deadline)?;
+            let response = db.query(query).map_err(map_error)?;
+            authorize_base_keys(principal.as_ref(), &response)?;
+            Ok(query_response_to_proto(response))
+        });
 
-        // Convert response to protobuf
-        let proto_response = proto::ScanResponse {
-            items: response.items.iter().map(ks_item_to_proto).collect(),
-            count: response.count as u32,
-            scanned_count: response.scanned_count as u32,
-            last_evaluated_key: ks_last_key_opt_to_proto(response.last_key),
-            error: None,
-        };
+        rpc.ok(Response::new(stream))
+    }
 
-        // Return as a single-item stream
-        let stream = futures::stream::once(futures::future::ready(Ok(proto_response)));
-        Ok(Response::new(stream))
+    /// Scan items, streaming one response per page
+    type ScanStreamStream = ResponseStream<proto::ScanResponse>;
+
+    #[instrument(skip(self, request), fields(trace_id))]
+    async fn scan_stream(
+        &self,
+        request: Request<proto::ScanRequest>,
+    ) -> Result<Response<Self::ScanStreamStream>, Status> {
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("scan_stream")?;
+        let deadline = self.deadline(&request, "scan_stream");
+        self.authorize_all(&request, Access::Read)?;
+
+        let db = self.database(request.metadata()).await?;
+        let req = request.into_inner();
+
+        // Reject a malformed request as the RPC's status rather than mid-stream
+        scan_from_proto(req.clone(), deadline)?;
+
+        let stream = stream_pages(req.limit, req.page_size, req.exclusive_start_key.clone(), move |limit, start_key| {
+            let scan = scan_from_proto(proto::ScanRequest {
+                limit: Some(limit),
+                exclusive_start_key: start_key,
+                ..req.clone()
+            }, deadline)?;
+            let response = db.scan(scan).map_err(map_error)?;
+            Ok(scan_response_to_proto(response))
+        });
+
+        rpc.ok(Response::new(stream))
     }
 
     /// Batch get multiple items
@@ -468,10 +988,15 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::BatchGetRequest>,
     ) -> Result<Response<proto::BatchGetResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("batch_get")?;
+        let deadline = self.deadline(&request, "batch_get");
+        let keys = request.get_ref().keys.iter().map(|key| key.partition_key.as_slice());
+        self.authorize_keys(&request, Access::Read, keys)?;
 
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Convert protobuf keys to core Keys
@@ -486,10 +1011,8 @@ impl KeystoneDb for KeystoneService {
         }
 
         // Execute batch get
-        let db = Arc::clone(&self.db);
-        let response = tokio::task::spawn_blocking(move || db.batch_get(batch_request))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        let response = run_blocking(deadline, move || db.batch_get(batch_request))
+            .await?
             .map_err(map_error)?;
 
         // Convert items to protobuf
@@ -499,7 +1022,7 @@ impl KeystoneDb for KeystoneService {
             .map(ks_item_to_proto)
             .collect();
 
-        Ok(Response::new(proto::BatchGetResponse {
+        rpc.ok(Response::new(proto::BatchGetResponse {
             items,
             count: response.items.len() as u32,
             error: None,
@@ -512,12 +1035,21 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::BatchWriteRequest>,
     ) -> Result<Response<proto::BatchWriteResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("batch_write")?;
+        let deadline = self.deadline(&request, "batch_write");
+        let keys = request.get_ref().writes.iter().filter_map(|write| match &write.request {
+            Some(proto::write_request::Request::Put(put)) => Some(put.partition_key.as_slice()),
+            Some(proto::write_request::Request::Delete(delete)) => Some(delete.partition_key.as_slice()),
+            None => None,
+        });
+        self.authorize_keys(&request, Access::Write, keys)?;
 
         use proto::write_request::Request as WriteRequestEnum;
 
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Build batch write request
@@ -562,13 +1094,11 @@ impl KeystoneDb for KeystoneService {
         }
 
         // Execute batch write
-        let db = Arc::clone(&self.db);
-        tokio::task::spawn_blocking(move || db.batch_write(batch_request))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        run_blocking(deadline, move || db.batch_write(batch_request))
+            .await?
             .map_err(map_error)?;
 
-        Ok(Response::new(proto::BatchWriteResponse {
+        rpc.ok(Response::new(proto::BatchWriteResponse {
             success: true,
             error: None,
         }))
@@ -580,10 +1110,15 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::TransactGetRequest>,
     ) -> Result<Response<proto::TransactGetResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
+        let rpc = self.start_rpc("transact_get")?;
+        let deadline = self.deadline(&request, "transact_get");
+        let keys = request.get_ref().keys.iter().map(|key| key.partition_key.as_slice());
+        self.authorize_keys(&request, Access::Read, keys)?;
+
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Build transact get request with all keys
@@ -598,10 +1133,8 @@ impl KeystoneDb for KeystoneService {
         }
 
         // Execute transactional get
-        let db = Arc::clone(&self.db);
-        let response = tokio::task::spawn_blocking(move || db.transact_get(transact_request))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        let response = run_blocking(deadline, move || db.transact_get(transact_request))
+            .await?
             .map_err(map_error)?;
 
         // Convert response items to protobuf
@@ -613,7 +1146,7 @@ impl KeystoneDb for KeystoneService {
             })
             .collect();
 
-        Ok(Response::new(proto::TransactGetResponse {
+        rpc.ok(Response::new(proto::TransactGetResponse {
             items,
             error: None,
         }))
@@ -625,12 +1158,23 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::TransactWriteRequest>,
     ) -> Result<Response<proto::TransactWriteResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("transact_write")?;
+        let deadline = self.deadline(&request, "transact_write");
+        let keys = request.get_ref().items.iter().filter_map(|item| match &item.item {
+            Some(proto::transact_write_item::Item::Put(put)) => Some(put.partition_key.as_slice()),
+            Some(proto::transact_write_item::Item::Update(update)) => Some(update.partition_key.as_slice()),
+            Some(proto::transact_write_item::Item::Delete(delete)) => Some(delete.partition_key.as_slice()),
+            Some(proto::transact_write_item::Item::ConditionCheck(check)) => Some(check.partition_key.as_slice()),
+            None => None,
+        });
+        self.authorize_keys(&request, Access::Write, keys)?;
 
         use proto::transact_write_item::Item as ProtoTxItem;
 
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Build transact write request with all operations
@@ -719,13 +1263,11 @@ impl KeystoneDb for KeystoneService {
         }
 
         // Execute transactional write
-        let db = Arc::clone(&self.db);
-        tokio::task::spawn_blocking(move || db.transact_write(transact_request))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        run_blocking(deadline, move || db.transact_write(transact_request))
+            .await?
             .map_err(map_error)?;
 
-        Ok(Response::new(proto::TransactWriteResponse {
+        rpc.ok(Response::new(proto::TransactWriteResponse {
             success: true,
             error: None,
         }))
@@ -737,10 +1279,14 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::UpdateRequest>,
     ) -> Result<Response<proto::UpdateResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
 
+        let rpc = self.start_rpc("update")?;
+        let deadline = self.deadline(&request, "update");
+        self.authorize_keys(&request, Access::Write, [request.get_ref().partition_key.as_slice()])?;
+
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Build update operation
@@ -765,13 +1311,11 @@ impl KeystoneDb for KeystoneService {
         }
 
         // Execute update
-        let db = Arc::clone(&self.db);
-        let response = tokio::task::spawn_blocking(move || db.update(update))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        let response = run_blocking(deadline, move || db.update(update))
+            .await?
             .map_err(map_error)?;
 
-        Ok(Response::new(proto::UpdateResponse {
+        rpc.ok(Response::new(proto::UpdateResponse {
             item: Some(ks_item_to_proto(&response.item)),
             error: None,
         }))
@@ -783,20 +1327,22 @@ impl KeystoneDb for KeystoneService {
         &self,
         request: Request<proto::ExecuteStatementRequest>,
     ) -> Result<Response<proto::ExecuteStatementResponse>, Status> {
-        // Generate trace ID for request correlation
-        let trace_id = Uuid::new_v4().to_string();
-        tracing::Span::current().record("trace_id", &trace_id);
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("execute_statement")?;
+        let deadline = self.deadline(&request, "execute_statement");
+        self.authorize_all(&request, statement_access(&request.get_ref().statement))?;
 
         use proto::execute_statement_response::Response as ProtoStmtResponse;
 
+        let db = self.database(request.metadata()).await?;
         let req = request.into_inner();
 
         // Execute the statement
-        let db = Arc::clone(&self.db);
         let statement = req.statement;
-        let response = tokio::task::spawn_blocking(move || db.execute_statement(&statement))
-            .await
-            .map_err(|e| Status::internal(format!("Task join error: {}", e)))?
+        let response = run_blocking(deadline, move || db.execute_statement(&statement))
+            .await?
             .map_err(map_error)?;
 
         // Convert response based on statement type
@@ -806,6 +1352,7 @@ impl KeystoneDb for KeystoneService {
                 count,
                 scanned_count,
                 last_key,
+                ..
             } => ProtoStmtResponse::Select(proto::SelectResult {
                 items: items.iter().map(ks_item_to_proto).collect(),
                 count: count as u32,
@@ -823,11 +1370,58 @@ impl KeystoneDb for KeystoneService {
             kstone_api::ExecuteStatementResponse::Delete { success } => {
                 ProtoStmtResponse::Delete(proto::DeleteResult { success })
             }
+            kstone_api::ExecuteStatementResponse::InsertSelect { .. } => {
+                ProtoStmtResponse::Insert(proto::InsertResult { success: true })
+            }
+            kstone_api::ExecuteStatementResponse::Explain { .. } => {
+                return Err(Status::unimplemented(
+                    "EXPLAIN is not supported over the remote protocol",
+                ));
+            }
         };
 
-        Ok(Response::new(proto::ExecuteStatementResponse {
+        rpc.ok(Response::new(proto::ExecuteStatementResponse {
             response: Some(proto_response),
             error: None,
         }))
     }
+
+    /// Push stream records to the client as writes commit
+    type SubscribeStreamStream = ResponseStream<proto::SubscribeStreamResponse>;
+
+    #[instrument(skip(self, request), fields(trace_id))]
+    async fn subscribe_stream(
+        &self,
+        request: Request<proto::SubscribeStreamRequest>,
+    ) -> Result<Response<Self::SubscribeStreamStream>, Status> {
+        // Join the caller's trace, if it sent one
+        trace_request(&request);
+
+        let rpc = self.start_rpc("subscribe_stream")?;
+        let deadline = self.deadline(&request, "subscribe_stream");
+        self.authorize_all(&request, Access::Read)?;
+
+        let db = self.database(request.metadata()).await?;
+        let req = request.into_inner();
+
+        // Resuming after a sequence number takes precedence over the start position
+        let start = match req.after_sequence {
+            Some(sequence_number) => ShardIteratorType::AfterSequenceNumber(sequence_number),
+            None if req.from_trim_horizon => ShardIteratorType::TrimHorizon,
+            None => ShardIteratorType::Latest,
+        };
+        let heartbeat_interval_ms = req
+            .heartbeat_interval_ms
+            .map_or(DEFAULT_HEARTBEAT_INTERVAL_MS, |ms| ms.max(MIN_HEARTBEAT_INTERVAL_MS));
+
+        // Subscribe up front so a table without streams fails the RPC itself
+        let config = SubscriptionConfig::new().with_start(start);
+        let subscription = run_blocking(deadline, move || db.subscribe_stream_with_config(config))
+            .await?
+            .map_err(map_error)?;
+
+        let heartbeat_interval = Duration::from_millis(heartbeat_interval_ms as u64);
+        let stream = stream_records(subscription, heartbeat_interval, deadline, self.shutdown.clone());
+        rpc.ok(Response::new(stream))
+    }
 }
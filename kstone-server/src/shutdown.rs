@@ -0,0 +1,78 @@
+/// Graceful shutdown coordination
+///
+/// One `Shutdown` is shared by the server loop, the service and the `/ready`
+/// probe. Once triggered, `/ready` fails so load balancers stop routing to
+/// the server, new RPCs are rejected with `UNAVAILABLE` and stream
+/// subscriptions end, while requests already running are left to finish.
+/// The binary then waits for them up to `--shutdown-timeout` and flushes the
+/// database before exiting.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Shared shutdown flag that async tasks can wait on
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    triggered: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl Shutdown {
+    /// Create a shutdown that hasn't been triggered
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start draining; later calls do nothing
+    pub fn trigger(&self) {
+        if !self.triggered.swap(true, Ordering::AcqRel) {
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Whether the server is draining
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::Acquire)
+    }
+
+    /// Wait until the shutdown is triggered
+    ///
+    /// Suitable as the signal of `Server::serve_with_shutdown`.
+    pub async fn triggered(&self) {
+        loop {
+            // Register before checking so a trigger in between isn't missed
+            let notified = self.notify.notified();
+            if self.is_triggered() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_shutdown_wakes_waiters() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.is_triggered());
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.triggered().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(5), waiter).await.unwrap().unwrap();
+        assert!(shutdown.is_triggered());
+
+        // Waiting after the trigger returns at once
+        tokio::time::timeout(Duration::from_secs(5), shutdown.triggered()).await.unwrap();
+    }
+}
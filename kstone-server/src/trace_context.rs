@@ -0,0 +1,219 @@
+/// Trace context propagation for gRPC requests
+///
+/// Clients join a request to their own trace by sending a W3C `traceparent`
+/// header (`00-<trace-id>-<parent-id>-<flags>`). Its trace id becomes the
+/// `trace_id` field of the request span, so server logs line up with the
+/// caller's trace; requests without one get a fresh id.
+///
+/// With the `otel` feature the header also parents the request span in the
+/// exported trace, and `init_otlp` exports spans to an OTLP collector.
+
+use tonic::Request;
+use uuid::Uuid;
+
+/// Metadata key of the W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// A parsed W3C `traceparent` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParent {
+    /// Trace id, 32 lowercase hex digits
+    pub trace_id: String,
+
+    /// Span id of the caller, 16 lowercase hex digits
+    pub parent_id: String,
+
+    /// Whether the caller sampled the trace
+    pub sampled: bool,
+}
+
+impl TraceParent {
+    /// Parse a header value, returning `None` if it is malformed
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+
+        // Version 00 has exactly four fields; later versions may append more
+        if !is_lower_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_lower_hex(trace_id, 32) || !is_lower_hex(parent_id, 16) || !is_lower_hex(flags, 2) {
+            return None;
+        }
+        if trace_id.bytes().all(|b| b == b'0') || parent_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            parent_id: parent_id.to_string(),
+            sampled: flags & 1 == 1,
+        })
+    }
+
+    /// Read the header from a request's metadata
+    pub fn from_request<T>(request: &Request<T>) -> Option<Self> {
+        request
+            .metadata()
+            .get(TRACEPARENT_HEADER)?
+            .to_str()
+            .ok()
+            .and_then(Self::parse)
+    }
+}
+
+fn is_lower_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Attach the current (request) span to the caller's trace
+///
+/// Records the span's `trace_id` field from the request's `traceparent`,
+/// or a fresh id if there is none, and returns it.
+pub fn trace_request<T>(request: &Request<T>) -> String {
+    let span = tracing::Span::current();
+    let trace_id = match TraceParent::from_request(request) {
+        Some(parent) => parent.trace_id,
+        None => Uuid::new_v4().simple().to_string(),
+    };
+    span.record("trace_id", trace_id.as_str());
+
+    #[cfg(feature = "otel")]
+    otel::set_parent(&span, request.metadata());
+
+    trace_id
+}
+
+/// Run blocking database work on tokio's blocking pool inside the current span
+///
+/// Engine spans (`DatabaseConfig::trace_operations`) then nest under the
+/// request span instead of starting traces of their own.
+pub fn spawn_blocking_in_span<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
+
+/// Export spans to an OTLP collector at `endpoint` (`otel` feature)
+///
+/// Installs the W3C trace context propagator and returns the tracer to hand
+/// to `tracing_opentelemetry::layer`. Call `shutdown_otlp` before exiting so
+/// buffered spans are sent.
+#[cfg(feature = "otel")]
+pub fn init_otlp(
+    endpoint: &str,
+    service_name: &str,
+) -> Result<opentelemetry_sdk::trace::Tracer, opentelemetry::trace::TraceError> {
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace, Resource};
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .install_batch(runtime::Tokio)
+}
+
+/// Flush buffered spans and stop the OTLP exporter (`otel` feature)
+#[cfg(feature = "otel")]
+pub fn shutdown_otlp() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::propagation::{Extractor, TextMapPropagator};
+    use tonic::metadata::{KeyRef, MetadataMap};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    /// Reads propagation headers from gRPC metadata
+    struct MetadataExtractor<'a>(&'a MetadataMap);
+
+    impl Extractor for MetadataExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|value| value.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0
+                .keys()
+                .map(|key| match key {
+                    KeyRef::Ascii(key) => key.as_str(),
+                    KeyRef::Binary(key) => key.as_str(),
+                })
+                .collect()
+        }
+    }
+
+    /// Parent `span` on the remote context carried by the request, if any
+    pub(super) fn set_parent(span: &tracing::Span, metadata: &MetadataMap) {
+        let context = opentelemetry::global::get_text_map_propagator(|propagator: &dyn TextMapPropagator| {
+            propagator.extract(&MetadataExtractor(metadata))
+        });
+        span.set_parent(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_traceparent() {
+        let parent = TraceParent::parse(HEADER).unwrap();
+        assert_eq!(parent.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(parent.parent_id, "00f067aa0ba902b7");
+        assert!(parent.sampled);
+
+        let unsampled = TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+        assert!(!unsampled.sampled);
+
+        // Future versions may carry extra fields
+        assert!(TraceParent::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_some());
+    }
+
+    #[test]
+    fn test_parse_traceparent_rejects_malformed() {
+        for value in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(TraceParent::parse(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_trace_request_uses_traceparent() {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(TRACEPARENT_HEADER, HEADER.parse().unwrap());
+        assert_eq!(trace_request(&request), "4bf92f3577b34da6a3ce929d0e0e4736");
+
+        // Without one, a fresh id in the same format
+        let trace_id = trace_request(&Request::new(()));
+        assert_eq!(trace_id.len(), 32);
+        assert_ne!(trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+    }
+}
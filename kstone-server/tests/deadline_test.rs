@@ -0,0 +1,73 @@
+/// Integration tests for request deadlines
+///
+/// Tests that requests past the client's deadline or the server's limit
+/// fail with DEADLINE_EXCEEDED
+
+use kstone_api::{Database, ItemBuilder};
+use kstone_proto::keystone_db_server::KeystoneDb;
+use kstone_proto::{GetRequest, QueryRequest, ScanRequest};
+use kstone_server::{KeystoneService, RpcTimeouts};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn populated_db(dir: &TempDir) -> Database {
+    let db = Database::create(dir.path()).unwrap();
+    for i in 0..100 {
+        let key = format!("item#{}", i);
+        db.put(key.as_bytes(), ItemBuilder::new().number("n", i).build()).unwrap();
+    }
+    db
+}
+
+/// Test that a scan fails once the client's grpc-timeout has passed
+#[tokio::test]
+async fn test_client_deadline_exceeded() {
+    let temp_dir = TempDir::new().unwrap();
+    let service = KeystoneService::new(populated_db(&temp_dir));
+
+    let mut request = tonic::Request::new(ScanRequest::default());
+    request.metadata_mut().insert("grpc-timeout", "1n".parse().unwrap());
+    let status = service.scan(request).await.err().expect("scan should time out");
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // A generous deadline leaves the scan alone
+    let mut request = tonic::Request::new(ScanRequest::default());
+    request.metadata_mut().insert("grpc-timeout", "30S".parse().unwrap());
+    assert!(service.scan(request).await.is_ok());
+}
+
+/// Test that the server's per-method limits apply without a client deadline
+#[tokio::test]
+async fn test_server_method_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let timeouts = RpcTimeouts::new()
+        .with_default(Duration::from_secs(30))
+        .with_method("scan", Duration::ZERO);
+    let service = KeystoneService::new(populated_db(&temp_dir)).with_timeouts(timeouts);
+
+    let status = service
+        .scan(tonic::Request::new(ScanRequest::default()))
+        .await
+        .err()
+        .expect("scan should time out");
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // Other methods get the default
+    let response = service
+        .query(tonic::Request::new(QueryRequest {
+            partition_key: b"item#7".to_vec(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.into_inner().count, 1);
+
+    let response = service
+        .get(tonic::Request::new(GetRequest {
+            partition_key: b"item#7".to_vec(),
+            sort_key: None,
+        }))
+        .await
+        .unwrap();
+    assert!(response.into_inner().item.is_some());
+}
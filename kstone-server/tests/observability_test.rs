@@ -149,6 +149,36 @@ async fn test_trace_id_in_spans() {
     // For now, we verify the operation completes successfully with tracing enabled
 }
 
+/// Test that requests carrying a W3C traceparent are served with engine spans on
+#[tokio::test]
+async fn test_traceparent_propagation() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::create(temp_dir.path()).unwrap();
+    db.set_trace_operations(true).unwrap();
+    let service = KeystoneService::new(db);
+
+    let mut get_request = tonic::Request::new(GetRequest {
+        partition_key: b"testkey".to_vec(),
+        sort_key: None,
+    });
+    get_request.metadata_mut().insert(
+        "traceparent",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".parse().unwrap(),
+    );
+
+    use kstone_proto::keystone_db_server::KeystoneDb;
+    let result = service.get(get_request).await;
+    assert!(result.unwrap().into_inner().item.is_none());
+
+    // A malformed header is ignored rather than failing the request
+    let mut get_request = tonic::Request::new(GetRequest {
+        partition_key: b"testkey".to_vec(),
+        sort_key: None,
+    });
+    get_request.metadata_mut().insert("traceparent", "not-a-trace".parse().unwrap());
+    assert!(service.get(get_request).await.is_ok());
+}
+
 /// Test that validation errors are handled correctly
 #[tokio::test]
 async fn test_validation_errors() {
@@ -174,3 +204,56 @@ async fn test_validation_errors() {
     let status = result.unwrap_err();
     assert_eq!(status.code(), tonic::Code::InvalidArgument);
 }
+
+/// Test that every RPC is counted, not only puts
+#[tokio::test]
+async fn test_metrics_per_rpc() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::create(temp_dir.path()).unwrap();
+    let service = KeystoneService::new(db);
+
+    let baseline = metrics::RPC_REQUESTS_TOTAL
+        .with_label_values(&["get", "success"])
+        .get();
+
+    use kstone_proto::keystone_db_server::KeystoneDb;
+    let get_request = tonic::Request::new(GetRequest {
+        partition_key: b"nonexistent".to_vec(),
+        sort_key: None,
+    });
+    assert!(service.get(get_request).await.is_ok());
+
+    let after = metrics::RPC_REQUESTS_TOTAL
+        .with_label_values(&["get", "success"])
+        .get();
+    assert!(after > baseline);
+}
+
+/// Test that requests over the rate limit are rejected and counted
+#[tokio::test]
+async fn test_rate_limited_requests() {
+    use kstone_server::RateLimiter;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::create(temp_dir.path()).unwrap();
+    let service = KeystoneService::new(db).with_rate_limiter(RateLimiter::new(0, 1));
+
+    let baseline = metrics::RATE_LIMITED_REQUESTS
+        .with_label_values(&["global"])
+        .get();
+
+    use kstone_proto::keystone_db_server::KeystoneDb;
+    let get_request = || tonic::Request::new(GetRequest {
+        partition_key: b"key".to_vec(),
+        sort_key: None,
+    });
+    assert!(service.get(get_request()).await.is_ok());
+
+    let status = service.get(get_request()).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+    let after = metrics::RATE_LIMITED_REQUESTS
+        .with_label_values(&["global"])
+        .get();
+    assert_eq!(after, baseline + 1);
+}
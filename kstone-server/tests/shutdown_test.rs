@@ -0,0 +1,53 @@
+/// Integration tests for graceful shutdown
+///
+/// Tests that a draining service refuses new requests
+
+use kstone_api::Database;
+use kstone_proto::keystone_db_server::KeystoneDb;
+use kstone_proto::{GetRequest, Item, PutRequest, Value, value::Value as ProtoValue};
+use kstone_server::{KeystoneService, Shutdown};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+fn put_request(pk: &[u8]) -> tonic::Request<PutRequest> {
+    let mut attributes = HashMap::new();
+    attributes.insert(
+        "name".to_string(),
+        Value {
+            value: Some(ProtoValue::StringValue("value".to_string())),
+        },
+    );
+
+    tonic::Request::new(PutRequest {
+        partition_key: pk.to_vec(),
+        sort_key: None,
+        item: Some(Item { attributes }),
+        condition_expression: None,
+        expression_values: HashMap::new(),
+    })
+}
+
+/// Test that requests are refused once the shutdown is triggered
+#[tokio::test]
+async fn test_shutdown_refuses_new_requests() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = Database::create(temp_dir.path()).unwrap();
+    let shutdown = Shutdown::new();
+    let service = KeystoneService::new(db).with_shutdown(shutdown.clone());
+
+    assert!(service.put(put_request(b"before")).await.is_ok());
+
+    shutdown.trigger();
+
+    let status = service.put(put_request(b"after")).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+
+    let status = service
+        .get(tonic::Request::new(GetRequest {
+            partition_key: b"before".to_vec(),
+            sort_key: None,
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+}
@@ -4,11 +4,13 @@
 /// is modified concurrently on different endpoints.
 
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
 use kstone_core::{Item, Key, Value};
-use crate::{VectorClock, EndpointId, SyncRecord};
+use crate::{crdt, VectorClock, EndpointId, SyncRecord};
 
 /// Conflict resolution strategy
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -21,9 +23,13 @@ pub enum ConflictStrategy {
     VectorClock,
     /// Merge changes at the attribute level
     AttributeMerge,
-    /// Custom resolution logic
-    Custom(String), // Name of custom resolver
-    /// Queue for manual resolution
+    /// Application-defined resolution (Phase 8+)
+    ///
+    /// Only the resolver's name is serialized. A strategy deserialized from
+    /// storage uses the resolver registered under that name with the
+    /// `ConflictManager`, and otherwise defers conflicts to the inbox.
+    Custom(#[serde(with = "resolver_by_name")] Arc<dyn ConflictResolver>),
+    /// Queue for manual resolution in the conflict inbox
     Manual,
 }
 
@@ -113,17 +119,22 @@ impl Conflict {
             ConflictStrategy::Manual => {
                 ConflictResolution::Deferred
             }
-            ConflictStrategy::Custom(name) => {
-                // Would call custom resolver here
-                ConflictResolution::Deferred
+            ConflictStrategy::Custom(resolver) => {
+                resolver.resolve(self)?
             }
         };
+        let resolution = self.merge_crdts(resolution);
 
         self.resolved = !matches!(resolution, ConflictResolution::Deferred);
         self.resolution = Some(resolution.clone());
         Ok(resolution)
     }
 
+    /// Merge CRDT attributes into a strategy's resolution, whatever the strategy
+    fn merge_crdts(&self, resolution: ConflictResolution) -> ConflictResolution {
+        crdt::merge_resolution(self.local_item.as_ref(), self.remote_item.as_ref(), resolution)
+    }
+
     /// Resolve using last writer wins strategy
     fn resolve_last_writer_wins(&self) -> ConflictResolution {
         if self.local_timestamp >= self.remote_timestamp {
@@ -265,6 +276,9 @@ impl ConflictResolution {
 }
 
 /// Trait for custom conflict resolvers
+///
+/// Resolvers may merge the two versions into a new item, pick one, or return
+/// `ConflictResolution::Deferred` to leave the conflict in the inbox.
 pub trait ConflictResolver: Send + Sync {
     /// Resolve a conflict
     fn resolve(&self, conflict: &Conflict) -> Result<ConflictResolution>;
@@ -273,6 +287,50 @@ pub trait ConflictResolver: Send + Sync {
     fn name(&self) -> &str;
 }
 
+impl fmt::Debug for dyn ConflictResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConflictResolver").field(&self.name()).finish()
+    }
+}
+
+/// Resolvers are the same if their names are
+impl PartialEq for dyn ConflictResolver {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for dyn ConflictResolver {}
+
+/// Stands in for a custom resolver known only by name, deferring every conflict
+struct UnregisteredResolver {
+    name: String,
+}
+
+impl ConflictResolver for UnregisteredResolver {
+    fn resolve(&self, _conflict: &Conflict) -> Result<ConflictResolution> {
+        Ok(ConflictResolution::Deferred)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Serializes custom resolvers as their names
+mod resolver_by_name {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(resolver: &Arc<dyn ConflictResolver>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(resolver.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<dyn ConflictResolver>, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Arc::new(UnregisteredResolver { name }))
+    }
+}
+
 /// Manager for tracking and resolving conflicts
 pub struct ConflictManager {
     /// Pending conflicts
@@ -325,13 +383,17 @@ impl ConflictManager {
         let mut pending = self.pending.write();
 
         if let Some(mut conflict) = pending.remove(conflict_id) {
-            // Try custom resolver if specified
-            if let ConflictStrategy::Custom(ref name) = conflict.strategy {
-                if let Some(resolver) = self.resolvers.get(name) {
-                    let resolution = resolver.resolve(&conflict)?;
+            // A registered resolver stands in for one known only by name
+            if let ConflictStrategy::Custom(ref custom) = conflict.strategy {
+                if let Some(resolver) = self.resolvers.get(custom.name()) {
+                    let resolution = conflict.merge_crdts(resolver.resolve(&conflict)?);
+                    conflict.resolved = !matches!(resolution, ConflictResolution::Deferred);
                     conflict.resolution = Some(resolution.clone());
-                    conflict.resolved = true;
-                    self.add_resolved(conflict);
+                    if conflict.resolved {
+                        self.add_resolved(conflict);
+                    } else {
+                        pending.insert(conflict_id.to_string(), conflict);
+                    }
                     return Ok(resolution);
                 }
             }
@@ -362,6 +424,11 @@ impl ConflictManager {
             .collect()
     }
 
+    /// Remove a pending conflict, e.g. to hand it to the conflict inbox
+    pub fn take_pending(&self, conflict_id: &str) -> Option<Conflict> {
+        self.pending.write().remove(conflict_id)
+    }
+
     /// Get pending conflicts
     pub fn get_pending(&self) -> Vec<Conflict> {
         self.pending.read().values().cloned().collect()
@@ -452,7 +519,8 @@ mod tests {
 
     #[test]
     fn test_vector_clock_resolution() {
-        let local_clock = VectorClock::with_local(EndpointId::from_str("local"), 5);
+        let mut local_clock = VectorClock::with_local(EndpointId::from_str("local"), 5);
+        local_clock.update(EndpointId::from_str("remote"), 3); // Local has seen every remote write
         let mut remote_clock = VectorClock::with_local(EndpointId::from_str("remote"), 3);
         remote_clock.update(EndpointId::from_str("local"), 3); // Remote is behind local
 
@@ -502,4 +570,68 @@ mod tests {
             panic!("Expected merged resolution");
         }
     }
+
+    /// Sums the `count` attribute of both versions
+    struct SumCounts;
+
+    impl ConflictResolver for SumCounts {
+        fn resolve(&self, conflict: &Conflict) -> Result<ConflictResolution> {
+            let count = |item: &Option<Item>| {
+                match item.as_ref().and_then(|item| item.get("count")) {
+                    Some(Value::N(n)) => n.parse::<i64>().unwrap_or(0),
+                    _ => 0,
+                }
+            };
+            let mut merged = conflict.local_item.clone().unwrap_or_default();
+            let total = count(&conflict.local_item) + count(&conflict.remote_item);
+            merged.insert("count".to_string(), Value::number(total));
+            Ok(ConflictResolution::Merged(Some(merged)))
+        }
+
+        fn name(&self) -> &str {
+            "sum-counts"
+        }
+    }
+
+    fn counter(count: i64) -> Item {
+        let mut item = HashMap::new();
+        item.insert("count".to_string(), Value::number(count));
+        item
+    }
+
+    #[test]
+    fn test_custom_resolver() {
+        let strategy = ConflictStrategy::Custom(Arc::new(SumCounts));
+        let mut conflict = Conflict::new(
+            Key::new(b"key1".to_vec()),
+            Some(counter(2)),
+            Some(counter(3)),
+            VectorClock::new(),
+            VectorClock::new(),
+            100,
+            200,
+            strategy.clone(),
+        );
+
+        let resolution = conflict.resolve().unwrap();
+        assert_eq!(resolution, ConflictResolution::Merged(Some(counter(5))));
+        assert!(conflict.resolved);
+
+        // Serialized by name; deserialized, it defers until a resolver is registered
+        let json = serde_json::to_string(&strategy).unwrap();
+        assert_eq!(json, r#"{"Custom":"sum-counts"}"#);
+        let restored: ConflictStrategy = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, strategy);
+
+        conflict.strategy = restored;
+        assert_eq!(conflict.resolve().unwrap(), ConflictResolution::Deferred);
+
+        let mut manager = ConflictManager::new(ConflictStrategy::Manual);
+        manager.register_resolver(Box::new(SumCounts));
+        let id = manager.add_conflict(conflict).unwrap();
+        assert_eq!(
+            manager.resolve_conflict(&id).unwrap(),
+            ConflictResolution::Merged(Some(counter(5)))
+        );
+    }
 }
\ No newline at end of file
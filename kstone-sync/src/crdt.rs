@@ -0,0 +1,521 @@
+/// CRDT attribute types (Phase 8+)
+///
+/// Conflict-free replicated data types stored as ordinary map attributes
+/// tagged with `__crdt`. When the same item is changed on two replicas, the
+/// conflict resolver merges these attributes instead of picking one side, so
+/// counters and sets updated offline on several devices lose no updates.
+/// The configured strategy still decides every other attribute.
+
+use std::collections::{HashMap, HashSet};
+
+use kstone_core::{Item, Value};
+
+use crate::conflict::ConflictResolution;
+
+/// Map key marking a map attribute as a CRDT, holding its type name
+pub const CRDT_TAG: &str = "__crdt";
+
+/// Grow-only counter: one count per replica, summed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GCounter {
+    counts: HashMap<String, u64>,
+}
+
+impl GCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `by` on behalf of `replica`
+    pub fn increment(&mut self, replica: &str, by: u64) {
+        *self.counts.entry(replica.to_string()).or_insert(0) += by;
+    }
+
+    /// Current value
+    pub fn value(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Merge another replica's state (per-replica maximum)
+    pub fn merge(&mut self, other: &GCounter) {
+        for (replica, &count) in &other.counts {
+            let ours = self.counts.entry(replica.clone()).or_insert(0);
+            *ours = (*ours).max(count);
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        tagged("g_counter", [("counts", counts_to_value(&self.counts))])
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = untag(value, "g_counter")?;
+        Some(Self {
+            counts: counts_from_value(map.get("counts")?)?,
+        })
+    }
+}
+
+/// Counter that can go up and down: a grow-only counter for each direction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PNCounter {
+    increments: GCounter,
+    decrements: GCounter,
+}
+
+impl PNCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `by` on behalf of `replica`
+    pub fn increment(&mut self, replica: &str, by: u64) {
+        self.increments.increment(replica, by);
+    }
+
+    /// Subtract `by` on behalf of `replica`
+    pub fn decrement(&mut self, replica: &str, by: u64) {
+        self.decrements.increment(replica, by);
+    }
+
+    /// Current value
+    pub fn value(&self) -> i64 {
+        self.increments.value() as i64 - self.decrements.value() as i64
+    }
+
+    /// Merge another replica's state
+    pub fn merge(&mut self, other: &PNCounter) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+
+    pub fn to_value(&self) -> Value {
+        tagged(
+            "pn_counter",
+            [
+                ("p", counts_to_value(&self.increments.counts)),
+                ("n", counts_to_value(&self.decrements.counts)),
+            ],
+        )
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = untag(value, "pn_counter")?;
+        Some(Self {
+            increments: GCounter {
+                counts: counts_from_value(map.get("p")?)?,
+            },
+            decrements: GCounter {
+                counts: counts_from_value(map.get("n")?)?,
+            },
+        })
+    }
+}
+
+/// Last-writer-wins register: the value written last, by timestamp
+///
+/// Writes with equal timestamps are ordered by replica ID, so every replica
+/// picks the same value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LwwRegister {
+    value: Value,
+    timestamp: i64,
+    replica: String,
+}
+
+impl LwwRegister {
+    pub fn new(value: Value, timestamp: i64, replica: &str) -> Self {
+        Self {
+            value,
+            timestamp,
+            replica: replica.to_string(),
+        }
+    }
+
+    /// Write a value, unless the register already holds a later write
+    pub fn set(&mut self, value: Value, timestamp: i64, replica: &str) {
+        if (timestamp, replica) > (self.timestamp, self.replica.as_str()) {
+            *self = Self::new(value, timestamp, replica);
+        }
+    }
+
+    /// Current value
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// When the current value was written
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Merge another replica's state
+    pub fn merge(&mut self, other: &LwwRegister) {
+        self.set(other.value.clone(), other.timestamp, &other.replica);
+    }
+
+    pub fn to_value(&self) -> Value {
+        tagged(
+            "lww_register",
+            [
+                ("value", self.value.clone()),
+                ("ts", Value::timestamp(self.timestamp)),
+                ("replica", Value::string(self.replica.clone())),
+            ],
+        )
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = untag(value, "lww_register")?;
+        Some(Self {
+            value: map.get("value")?.clone(),
+            timestamp: map.get("ts")?.as_timestamp()?,
+            replica: map.get("replica")?.as_string()?.to_string(),
+        })
+    }
+}
+
+/// Observed-remove set
+///
+/// Every add gets a unique tag, and a remove only removes the tags it has
+/// seen, so an element added on one replica while removed on another stays
+/// in the set after they merge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrSet {
+    adds: HashMap<String, Value>,
+    removed: HashSet<String>,
+}
+
+impl OrSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an element
+    pub fn add(&mut self, element: Value) {
+        self.adds.insert(uuid::Uuid::new_v4().to_string(), element);
+    }
+
+    /// Remove an element, as far as this replica has seen it added
+    pub fn remove(&mut self, element: &Value) {
+        let tags: Vec<String> = self
+            .adds
+            .iter()
+            .filter(|(_, value)| *value == element)
+            .map(|(tag, _)| tag.clone())
+            .collect();
+        for tag in tags {
+            self.adds.remove(&tag);
+            self.removed.insert(tag);
+        }
+    }
+
+    pub fn contains(&self, element: &Value) -> bool {
+        self.adds.values().any(|value| value == element)
+    }
+
+    /// The elements in the set, each once
+    pub fn elements(&self) -> Vec<Value> {
+        let mut elements: Vec<Value> = Vec::new();
+        for value in self.adds.values() {
+            if !elements.contains(value) {
+                elements.push(value.clone());
+            }
+        }
+        elements
+    }
+
+    /// Merge another replica's state
+    pub fn merge(&mut self, other: &OrSet) {
+        self.removed.extend(other.removed.iter().cloned());
+        for (tag, value) in &other.adds {
+            if !self.removed.contains(tag) {
+                self.adds.insert(tag.clone(), value.clone());
+            }
+        }
+        let removed = &self.removed;
+        self.adds.retain(|tag, _| !removed.contains(tag));
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut removed: Vec<&String> = self.removed.iter().collect();
+        removed.sort();
+        tagged(
+            "or_set",
+            [
+                ("adds", Value::M(self.adds.clone())),
+                (
+                    "removed",
+                    Value::L(removed.into_iter().map(|tag| Value::string(tag.clone())).collect()),
+                ),
+            ],
+        )
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = untag(value, "or_set")?;
+        let removed = match map.get("removed")? {
+            Value::L(tags) => tags
+                .iter()
+                .map(|tag| tag.as_string().map(str::to_string))
+                .collect::<Option<HashSet<_>>>()?,
+            _ => return None,
+        };
+        Some(Self {
+            adds: map.get("adds")?.as_map()?.clone(),
+            removed,
+        })
+    }
+}
+
+/// Any CRDT attribute
+#[derive(Debug, Clone, PartialEq)]
+pub enum Crdt {
+    GCounter(GCounter),
+    PNCounter(PNCounter),
+    LwwRegister(LwwRegister),
+    OrSet(OrSet),
+}
+
+impl Crdt {
+    /// Read a CRDT attribute; None if the value isn't one
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = value.as_map()?;
+        match map.get(CRDT_TAG)?.as_string()? {
+            "g_counter" => GCounter::from_value(value).map(Crdt::GCounter),
+            "pn_counter" => PNCounter::from_value(value).map(Crdt::PNCounter),
+            "lww_register" => LwwRegister::from_value(value).map(Crdt::LwwRegister),
+            "or_set" => OrSet::from_value(value).map(Crdt::OrSet),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Crdt::GCounter(counter) => counter.to_value(),
+            Crdt::PNCounter(counter) => counter.to_value(),
+            Crdt::LwwRegister(register) => register.to_value(),
+            Crdt::OrSet(set) => set.to_value(),
+        }
+    }
+
+    /// Merge another replica's state; false if the types differ
+    pub fn merge(&mut self, other: &Crdt) -> bool {
+        match (self, other) {
+            (Crdt::GCounter(ours), Crdt::GCounter(theirs)) => ours.merge(theirs),
+            (Crdt::PNCounter(ours), Crdt::PNCounter(theirs)) => ours.merge(theirs),
+            (Crdt::LwwRegister(ours), Crdt::LwwRegister(theirs)) => ours.merge(theirs),
+            (Crdt::OrSet(ours), Crdt::OrSet(theirs)) => ours.merge(theirs),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Whether a value is a CRDT attribute
+pub fn is_crdt(value: &Value) -> bool {
+    value.as_map().is_some_and(|map| map.contains_key(CRDT_TAG))
+}
+
+/// Merge the CRDT attributes of a conflicting item into a resolution
+///
+/// The strategy's pick supplies every other attribute. A deferred conflict
+/// is resolved too when only CRDT attributes differ. Deletions are left to
+/// the strategy.
+pub(crate) fn merge_resolution(
+    local: Option<&Item>,
+    remote: Option<&Item>,
+    resolution: ConflictResolution,
+) -> ConflictResolution {
+    let (Some(local), Some(remote)) = (local, remote) else {
+        return resolution;
+    };
+
+    let mut crdt_attributes: Vec<&String> = local
+        .iter()
+        .chain(remote.iter())
+        .filter(|(_, value)| is_crdt(value))
+        .map(|(name, _)| name)
+        .collect();
+    if crdt_attributes.is_empty() {
+        return resolution;
+    }
+    crdt_attributes.sort();
+    crdt_attributes.dedup();
+
+    let mut merged = match &resolution {
+        ConflictResolution::UseLocal(Some(item))
+        | ConflictResolution::UseRemote(Some(item))
+        | ConflictResolution::Merged(Some(item)) => item.clone(),
+        ConflictResolution::Deferred if plain_attributes_equal(local, remote) => local.clone(),
+        _ => return resolution,
+    };
+
+    for name in crdt_attributes {
+        let local_crdt = local.get(name).and_then(Crdt::from_value);
+        let remote_crdt = remote.get(name).and_then(Crdt::from_value);
+        let value = match (local_crdt, remote_crdt) {
+            (Some(mut ours), Some(theirs)) => {
+                if !ours.merge(&theirs) {
+                    // Different types on each side: keep the strategy's pick
+                    continue;
+                }
+                ours.to_value()
+            }
+            (Some(crdt), None) | (None, Some(crdt)) => crdt.to_value(),
+            (None, None) => continue,
+        };
+        merged.insert(name.clone(), value);
+    }
+
+    ConflictResolution::Merged(Some(merged))
+}
+
+/// Whether two items agree on every attribute that isn't a CRDT
+fn plain_attributes_equal(local: &Item, remote: &Item) -> bool {
+    let plain = |item: &Item| -> Vec<(String, Value)> {
+        let mut attributes: Vec<(String, Value)> = item
+            .iter()
+            .filter(|(_, value)| !is_crdt(value))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        attributes.sort_by(|a, b| a.0.cmp(&b.0));
+        attributes
+    };
+    plain(local) == plain(remote)
+}
+
+fn tagged<const N: usize>(kind: &str, fields: [(&str, Value); N]) -> Value {
+    let mut map: HashMap<String, Value> = fields
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    map.insert(CRDT_TAG.to_string(), Value::string(kind));
+    Value::M(map)
+}
+
+fn untag<'a>(value: &'a Value, kind: &str) -> Option<&'a HashMap<String, Value>> {
+    let map = value.as_map()?;
+    (map.get(CRDT_TAG)?.as_string()? == kind).then_some(map)
+}
+
+fn counts_to_value(counts: &HashMap<String, u64>) -> Value {
+    Value::M(
+        counts
+            .iter()
+            .map(|(replica, count)| (replica.clone(), Value::number(count)))
+            .collect(),
+    )
+}
+
+fn counts_from_value(value: &Value) -> Option<HashMap<String, u64>> {
+    value
+        .as_map()?
+        .iter()
+        .map(|(replica, count)| match count {
+            Value::N(n) => n.parse().ok().map(|n| (replica.clone(), n)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflict::{Conflict, ConflictStrategy};
+    use crate::VectorClock;
+    use kstone_api::ItemBuilder;
+    use kstone_core::Key;
+
+    #[test]
+    fn test_counters_merge() {
+        let mut a = PNCounter::new();
+        let mut b = PNCounter::new();
+        a.increment("a", 5);
+        b.increment("b", 3);
+        b.decrement("b", 1);
+
+        let mut merged = a.clone();
+        merged.merge(&b);
+        merged.merge(&b); // Idempotent
+        assert_eq!(merged.value(), 7);
+        assert_eq!(PNCounter::from_value(&merged.to_value()), Some(merged.clone()));
+
+        let mut g = GCounter::new();
+        g.increment("a", 2);
+        let mut other = g.clone();
+        other.increment("a", 1);
+        g.merge(&other);
+        assert_eq!(g.value(), 3);
+    }
+
+    #[test]
+    fn test_lww_register_and_or_set() {
+        let mut a = LwwRegister::new(Value::string("old"), 100, "a");
+        let b = LwwRegister::new(Value::string("new"), 200, "b");
+        a.merge(&b);
+        assert_eq!(a.value(), &Value::string("new"));
+        a.set(Value::string("stale"), 150, "a");
+        assert_eq!(a.value(), &Value::string("new"));
+
+        let mut a = OrSet::new();
+        a.add(Value::string("x"));
+        let mut b = a.clone();
+        // Concurrently: a removes x, b adds it again and adds y
+        a.remove(&Value::string("x"));
+        b.add(Value::string("x"));
+        b.add(Value::string("y"));
+
+        a.merge(&b);
+        assert!(a.contains(&Value::string("x")));
+        assert!(a.contains(&Value::string("y")));
+        assert_eq!(a.elements().len(), 2);
+        assert_eq!(OrSet::from_value(&a.to_value()), Some(a));
+    }
+
+    #[test]
+    fn test_conflict_merges_crdt_attributes() {
+        let mut local_views = GCounter::new();
+        local_views.increment("local", 4);
+        let mut remote_views = GCounter::new();
+        remote_views.increment("remote", 6);
+
+        let mut local = ItemBuilder::new().string("title", "mine").build();
+        local.insert("views".to_string(), local_views.to_value());
+        let mut remote = ItemBuilder::new().string("title", "theirs").build();
+        remote.insert("views".to_string(), remote_views.to_value());
+
+        let mut conflict = Conflict::new(
+            Key::new(b"post#1".to_vec()),
+            Some(local.clone()),
+            Some(remote.clone()),
+            VectorClock::new(),
+            VectorClock::new(),
+            200,
+            100,
+            ConflictStrategy::LastWriterWins,
+        );
+        let item = match conflict.resolve().unwrap() {
+            ConflictResolution::Merged(Some(item)) => item,
+            other => panic!("unexpected resolution: {:?}", other),
+        };
+        assert_eq!(item.get("title"), Some(&Value::string("mine")));
+        let views = GCounter::from_value(item.get("views").unwrap()).unwrap();
+        assert_eq!(views.value(), 10);
+
+        // Manual conflicts resolve automatically when only CRDTs differ
+        remote.insert("title".to_string(), Value::string("mine"));
+        let mut conflict = Conflict::new(
+            Key::new(b"post#1".to_vec()),
+            Some(local),
+            Some(remote),
+            VectorClock::new(),
+            VectorClock::new(),
+            200,
+            100,
+            ConflictStrategy::Manual,
+        );
+        assert!(matches!(conflict.resolve().unwrap(), ConflictResolution::Merged(Some(_))));
+        assert!(conflict.resolved);
+    }
+}
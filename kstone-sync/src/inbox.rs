@@ -0,0 +1,133 @@
+/// Conflict inbox: conflicts left for a person to resolve (Phase 8+)
+///
+/// Conflicts whose strategy defers them (`ConflictStrategy::Manual`, or a
+/// custom resolver returning `Deferred`) are saved in the database instead
+/// of being dropped at the end of a sync. Resolving one writes the chosen
+/// version locally; the next sync carries it to the remote.
+
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+use kstone_api::Database;
+use kstone_core::{Item, Key};
+
+use crate::conflict::{Conflict, ConflictResolution};
+use crate::merkle_store::MerkleStore;
+use crate::metadata::SyncMetadataStore;
+
+/// How to resolve a conflict from the inbox
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictChoice {
+    /// Keep the local version
+    KeepLocal,
+    /// Take the remote version
+    KeepRemote,
+    /// Write this item instead, e.g. a hand merge; None deletes the item
+    Merged(Option<Item>),
+}
+
+/// Pending conflicts saved in a database
+pub struct ConflictInbox {
+    db: Arc<Database>,
+    store: SyncMetadataStore,
+}
+
+impl ConflictInbox {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            store: SyncMetadataStore::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Pending conflicts, oldest first
+    pub fn list(&self) -> Result<Vec<Conflict>> {
+        self.store.load_conflicts(true)
+    }
+
+    /// A conflict by ID
+    pub fn get(&self, conflict_id: &str) -> Result<Option<Conflict>> {
+        self.store.load_conflict(conflict_id)
+    }
+
+    /// Add a conflict to the inbox
+    pub fn add(&self, conflict: &Conflict) -> Result<()> {
+        self.store.save_conflict(conflict)
+    }
+
+    /// Resolve a conflict: write the chosen version and remove it from the inbox
+    pub fn resolve(&self, conflict_id: &str, choice: ConflictChoice) -> Result<ConflictResolution> {
+        let conflict = self
+            .get(conflict_id)?
+            .ok_or_else(|| anyhow!("Conflict not found: {}", conflict_id))?;
+
+        let resolution = match choice {
+            ConflictChoice::KeepLocal => ConflictResolution::UseLocal(conflict.local_item),
+            ConflictChoice::KeepRemote => ConflictResolution::UseRemote(conflict.remote_item),
+            ConflictChoice::Merged(item) => ConflictResolution::Merged(item),
+        };
+
+        apply_resolution(&self.db, &conflict.key, &resolution)?;
+        self.store.delete_conflict(conflict_id)?;
+        Ok(resolution)
+    }
+}
+
+/// Write a resolution's item to the local database, or delete the item
+pub(crate) fn apply_resolution(db: &Arc<Database>, key: &Key, resolution: &ConflictResolution) -> Result<()> {
+    if matches!(resolution, ConflictResolution::Deferred) {
+        return Ok(());
+    }
+
+    match (resolution.get_item(), &key.sk) {
+        (Some(item), Some(sk)) => db.put_with_sk(&key.pk, sk, item.clone())?,
+        (Some(item), None) => db.put(&key.pk, item.clone())?,
+        (None, Some(sk)) => db.delete_with_sk(&key.pk, sk)?,
+        (None, None) => db.delete(&key.pk)?,
+    }
+    MerkleStore::new(db.clone()).update(key, resolution.get_item())
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflict::ConflictStrategy;
+    use crate::VectorClock;
+    use kstone_api::ItemBuilder;
+
+    fn conflict(pk: &[u8], local: &str, remote: &str) -> Conflict {
+        Conflict::new(
+            Key::new(pk.to_vec()),
+            Some(ItemBuilder::new().string("name", local).build()),
+            Some(ItemBuilder::new().string("name", remote).build()),
+            VectorClock::new(),
+            VectorClock::new(),
+            100,
+            200,
+            ConflictStrategy::Manual,
+        )
+    }
+
+    #[test]
+    fn test_conflict_inbox() {
+        let db = Arc::new(Database::create_in_memory().unwrap());
+        let inbox = ConflictInbox::new(db.clone());
+
+        let first = conflict(b"user#1", "local", "remote");
+        let second = conflict(b"user#2", "mine", "theirs");
+        inbox.add(&first).unwrap();
+        inbox.add(&second).unwrap();
+        assert_eq!(inbox.list().unwrap().len(), 2);
+
+        inbox.resolve(&first.id, ConflictChoice::KeepRemote).unwrap();
+        let item = db.get(b"user#1").unwrap().unwrap();
+        assert_eq!(item.get("name").and_then(|v| v.as_string()), Some("remote"));
+
+        inbox.resolve(&second.id, ConflictChoice::Merged(None)).unwrap();
+        assert!(db.get(b"user#2").unwrap().is_none());
+
+        assert!(inbox.list().unwrap().is_empty());
+        assert!(inbox.resolve(&first.id, ConflictChoice::KeepLocal).is_err());
+    }
+}
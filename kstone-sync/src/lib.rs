@@ -6,27 +6,44 @@
 
 pub mod vector_clock;
 pub mod merkle;
+pub mod merkle_store; // Phase 8+ persisted, incrementally updated merkle tree
 pub mod change_tracker;
 pub mod conflict;
 pub mod sync_engine;
 pub mod offline_queue;
 pub mod metadata;
 pub mod protocol;
-
-// #[cfg(feature = "dynamodb")]
-// pub mod dynamodb;
+pub mod scope;
+pub mod throttle; // Phase 8+ bandwidth limiting for sync transfers
+pub mod inbox; // Phase 8+ conflict inbox for manual resolution
+pub mod crdt; // Phase 8+ CRDT attribute types merged during sync
+pub mod notify; // Phase 8+ webhook and callback notifications
 
 pub use vector_clock::VectorClock;
 pub use merkle::{MerkleTree, MerkleNode};
+pub use merkle_store::MerkleStore;
 pub use change_tracker::{ChangeTracker, SyncRecord};
 pub use conflict::{ConflictStrategy, ConflictResolver, ConflictResolution, Conflict};
 pub use sync_engine::{SyncEngine, SyncConfig, SyncState, SyncEvent};
 pub use offline_queue::{OfflineQueue, PendingOperation};
-pub use metadata::{SyncMetadata, SyncMetadataStore, EndpointInfo};
+pub use metadata::{
+    SyncMetadata, SyncMetadataStore, EndpointInfo, TransferCheckpoint, SyncDirection, SyncRunRecord,
+};
 pub use protocol::{SyncProtocol, SyncEndpoint};
+pub use scope::{ScopeMatcher, SyncScope};
+pub use throttle::BandwidthLimiter;
+pub use inbox::{ConflictChoice, ConflictInbox};
+pub use crdt::{Crdt, GCounter, LwwRegister, OrSet, PNCounter};
+pub use notify::{CallbackNotifier, SyncNotification, SyncNotifier, WebhookPayload};
+
+#[cfg(feature = "webhooks")]
+pub use notify::WebhookNotifier;
+
+#[cfg(feature = "s3-sync")]
+pub use protocol::s3::S3ColdStore;
 
-// #[cfg(feature = "dynamodb")]
-// pub use dynamodb::DynamoDBSync;
+#[cfg(feature = "dynamodb")]
+pub use protocol::dynamodb::DynamoDBProtocol;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -91,6 +108,10 @@ pub struct CloudSyncBuilder {
     batch_size: usize,
     max_retries: u32,
     enable_compression: bool,
+    scope: SyncScope,
+    max_bytes_per_sec: Option<u64>,
+    notifiers: Vec<Arc<dyn SyncNotifier>>,
+    failure_alert_threshold: Option<u32>,
 }
 
 impl CloudSyncBuilder {
@@ -103,6 +124,10 @@ impl CloudSyncBuilder {
             batch_size: 100,
             max_retries: 3,
             enable_compression: true,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
+            notifiers: Vec::new(),
+            failure_alert_threshold: None,
         }
     }
 
@@ -141,6 +166,30 @@ impl CloudSyncBuilder {
         self
     }
 
+    /// Only sync items in `scope`
+    pub fn with_scope(mut self, scope: SyncScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Limit transfers to an average of `bytes_per_sec`
+    pub fn with_bandwidth_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.max_bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+
+    /// Notify `notifier` of completed syncs, conflicts and repeated failures
+    pub fn with_notifier(mut self, notifier: Arc<dyn SyncNotifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Send `RepeatedFailure` after this many failed runs in a row
+    pub fn with_failure_alert_threshold(mut self, failures: u32) -> Self {
+        self.failure_alert_threshold = Some(failures);
+        self
+    }
+
     pub fn build(self) -> Result<SyncEngine> {
         let db = self.db.ok_or_else(|| {
             anyhow::anyhow!("Database is required")
@@ -157,9 +206,18 @@ impl CloudSyncBuilder {
             batch_size: self.batch_size,
             max_retries: self.max_retries,
             enable_compression: self.enable_compression,
+            scope: self.scope,
+            max_bytes_per_sec: self.max_bytes_per_sec,
         };
 
-        SyncEngine::new(db, config)
+        let engine = SyncEngine::new(db, config)?;
+        for notifier in self.notifiers {
+            engine.add_notifier(notifier);
+        }
+        if let Some(failures) = self.failure_alert_threshold {
+            engine.set_failure_alert_threshold(failures);
+        }
+        Ok(engine)
     }
 }
 
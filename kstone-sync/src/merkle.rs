@@ -123,23 +123,44 @@ impl MerkleTree {
     where
         I: IntoIterator<Item = (Bytes, Bytes)>,
     {
-        let mut tree = Self::new(branching_factor);
-
         // Create leaf nodes
         let leaves: Vec<MerkleNode> = items
             .into_iter()
             .map(|(key, value)| MerkleNode::leaf(&key, &value))
             .collect();
 
+        Ok(Self::from_leaves(leaves, branching_factor))
+    }
+
+    /// Build a Merkle tree from sorted keys and their already computed leaf
+    /// hashes, such as those kept by a `MerkleStore`
+    pub fn from_leaf_hashes<I>(leaves: I, branching_factor: usize) -> Self
+    where
+        I: IntoIterator<Item = (Bytes, Bytes)>,
+    {
+        let leaves = leaves
+            .into_iter()
+            .map(|(key, hash)| MerkleNode {
+                hash,
+                level: 0,
+                key_range: Some((key.clone(), key)),
+                children: Vec::new(),
+            })
+            .collect();
+        Self::from_leaves(leaves, branching_factor)
+    }
+
+    fn from_leaves(leaves: Vec<MerkleNode>, branching_factor: usize) -> Self {
+        let mut tree = Self::new(branching_factor);
         tree.item_count = leaves.len();
 
         if leaves.is_empty() {
-            return Ok(tree);
+            return tree;
         }
 
         // Build tree bottom-up
-        tree.root = Some(Self::build_level(leaves, branching_factor));
-        Ok(tree)
+        tree.root = Some(Self::build_level(leaves, tree.branching_factor));
+        tree
     }
 
     /// Build one level of the tree
@@ -164,6 +185,12 @@ impl MerkleTree {
         Self::build_level(parents, branching_factor)
     }
 
+    /// Get the root node; comparing two roots' hashes tells whether the
+    /// trees differ at all
+    pub fn root(&self) -> Option<&MerkleNode> {
+        self.root.as_ref()
+    }
+
     /// Get the root hash
     pub fn root_hash(&self) -> Option<Bytes> {
         self.root.as_ref().map(|r| r.hash.clone())
@@ -194,6 +221,7 @@ impl MerkleTree {
             }
         }
 
+        diff.pair_moved_keys();
         diff
     }
 
@@ -219,6 +247,13 @@ impl MerkleTree {
             return;
         }
 
+        // Trees of different heights don't line up; compare all their leaves
+        if left.level != right.level {
+            diff.only_in_left.extend(left.leaf_hashes());
+            diff.only_in_right.extend(right.leaf_hashes());
+            return;
+        }
+
         // Compare children
         let mut left_idx = 0;
         let mut right_idx = 0;
@@ -334,6 +369,27 @@ impl MerkleDiff {
     pub fn count(&self) -> usize {
         self.only_in_left.len() + self.only_in_right.len() + self.modified.len()
     }
+
+    /// Match up keys reported on both sides
+    ///
+    /// An insert or delete shifts the leaves after it, so subtrees that no
+    /// longer line up report their keys as only in one tree. Keys found on
+    /// both sides are modified, or unchanged if their hashes agree.
+    fn pair_moved_keys(&mut self) {
+        let mut right: BTreeMap<Bytes, Bytes> = self.only_in_right.drain(..).collect();
+        let mut only_in_left = Vec::new();
+        for (key, left_hash) in self.only_in_left.drain(..) {
+            match right.remove(&key) {
+                Some(right_hash) if right_hash == left_hash => {}
+                Some(right_hash) => self.modified.push((key, left_hash, right_hash)),
+                None => only_in_left.push((key, left_hash)),
+            }
+        }
+        self.only_in_left = only_in_left;
+        self.only_in_right = right.into_iter().collect();
+        self.only_in_left.sort();
+        self.modified.sort();
+    }
 }
 
 /// Proof of inclusion for a key in the tree
@@ -408,4 +464,31 @@ mod tests {
         assert!(!diff.is_empty());
         assert!(diff.only_in_left.len() > 0 || diff.only_in_right.len() > 0);
     }
+
+    #[test]
+    fn test_diff_after_shifted_keys() {
+        let items = |keys: &[u32], changed: u32| -> Vec<(Bytes, Bytes)> {
+            keys.iter()
+                .map(|&k| {
+                    let value = if k == changed { "new".to_string() } else { format!("value{}", k) };
+                    (Bytes::from(format!("key{:04}", k)), Bytes::from(value))
+                })
+                .collect()
+        };
+
+        // An extra key up front shifts every leaf of the right tree, and
+        // 50 more keys make it a level taller
+        let left_keys: Vec<u32> = (1..=20).collect();
+        let right_keys: Vec<u32> = (0..=70).filter(|&k| k != 7).collect();
+        let tree1 = MerkleTree::build(items(&left_keys, 0), 4).unwrap();
+        let tree2 = MerkleTree::build(items(&right_keys, 12), 4).unwrap();
+
+        let diff = tree1.diff(&tree2);
+        let keys = |entries: &[(Bytes, Bytes)]| -> Vec<Bytes> { entries.iter().map(|(k, _)| k.clone()).collect() };
+        assert_eq!(keys(&diff.only_in_left), vec![Bytes::from("key0007")]);
+        assert_eq!(diff.only_in_right.len(), 51);
+        assert_eq!(diff.only_in_right[0].0, Bytes::from("key0000"));
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].0, Bytes::from("key0012"));
+    }
 }
\ No newline at end of file
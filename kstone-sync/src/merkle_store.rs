@@ -0,0 +1,453 @@
+/// Persisted merkle tree, maintained as items change (Phase 8+)
+///
+/// Building a `MerkleTree` from a scan hashes every item on every sync,
+/// which gets slow on big tables. `MerkleStore` keeps the tree in the
+/// database instead, under the reserved `_sync#merkle` partitions, and
+/// updates only the path from a changed item's bucket to the root.
+///
+/// The tree has a fixed shape: keys hash into 4096 buckets, and each level
+/// above combines 16 nodes, up to a single root. Two stores can therefore
+/// be compared node by node, and equal roots mean equal contents.
+
+use anyhow::Result;
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use kstone_api::{Database, Query};
+use kstone_core::{Item, Key, Value};
+
+use crate::merkle::MerkleTree;
+use crate::protocol::DiffType;
+
+/// Partition of the leaves: one item per key, sorted by bucket
+const MERKLE_LEAVES_PK: &str = "_sync#merkle#leaves";
+/// Partition of the bucket and internal node hashes
+const MERKLE_NODES_PK: &str = "_sync#merkle#nodes";
+/// Marks the store as built
+const MERKLE_META_PK: &str = "_sync#merkle#meta";
+
+/// Children per node
+pub const MERKLE_FANOUT: usize = 16;
+/// Levels above the buckets (16^3 = 4096 buckets); the root is the single
+/// node at this level
+pub const MERKLE_DEPTH: u32 = 3;
+
+/// Children per node of the in-memory trees built by `tree()`, as sync uses
+const TREE_BRANCHING_FACTOR: usize = 16;
+
+/// Serializes path updates, so concurrent writes can't interleave them
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A merkle tree kept in a database
+pub struct MerkleStore {
+    db: Arc<Database>,
+}
+
+impl MerkleStore {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Whether the tree has been built; until then updates are skipped
+    pub fn is_built(&self) -> Result<bool> {
+        Ok(self.db.get(MERKLE_META_PK.as_bytes())?.is_some())
+    }
+
+    /// Build the tree from scratch by scanning the database
+    pub fn rebuild(&self) -> Result<()> {
+        let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.clear()?;
+
+        // Leaves, grouped by bucket in key order
+        let mut buckets: BTreeMap<usize, BTreeMap<Bytes, Bytes>> = BTreeMap::new();
+        for (key, item) in self.db.scan_with_keys(usize::MAX)? {
+            let encoded = key.encode();
+            let hash = leaf_hash(&encoded, &item)?;
+            let bucket = bucket_of(&encoded);
+            self.db.put_with_sk(
+                MERKLE_LEAVES_PK.as_bytes(),
+                &leaf_sk(bucket, &encoded),
+                leaf_item(&key, &encoded, &hash),
+            )?;
+            buckets.entry(bucket).or_default().insert(encoded, hash);
+        }
+
+        // Nodes, level by level up to the root
+        let mut level: HashMap<usize, Bytes> = buckets
+            .into_iter()
+            .map(|(bucket, leaves)| (bucket, bucket_hash(leaves.values())))
+            .collect();
+        for depth in 0..=MERKLE_DEPTH {
+            for (&index, hash) in &level {
+                self.put_node(depth, index, hash)?;
+            }
+
+            let mut parents: HashMap<usize, Vec<(usize, Bytes)>> = HashMap::new();
+            for (index, hash) in level {
+                parents
+                    .entry(index / MERKLE_FANOUT)
+                    .or_default()
+                    .push((index, hash));
+            }
+            level = parents
+                .into_iter()
+                .map(|(parent, mut children)| {
+                    children.sort();
+                    (parent, node_hash(&children))
+                })
+                .collect();
+        }
+
+        let mut meta = HashMap::new();
+        meta.insert(
+            "built_at".to_string(),
+            Value::timestamp(chrono::Utc::now().timestamp_millis()),
+        );
+        self.db.put(MERKLE_META_PK.as_bytes(), meta)?;
+        Ok(())
+    }
+
+    /// Record a write (`Some(item)`) or deletion (`None`) of an item
+    ///
+    /// Rehashes the item's bucket and the nodes above it.
+    pub fn update(&self, key: &Key, item: Option<&Item>) -> Result<()> {
+        if key.pk.starts_with(b"_sync#") || !self.is_built()? {
+            return Ok(());
+        }
+
+        let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let encoded = key.encode();
+        let bucket = bucket_of(&encoded);
+        let sk = leaf_sk(bucket, &encoded);
+        match item {
+            Some(item) => {
+                let hash = leaf_hash(&encoded, item)?;
+                self.db
+                    .put_with_sk(MERKLE_LEAVES_PK.as_bytes(), &sk, leaf_item(key, &encoded, &hash))?;
+            }
+            None => self.db.delete_with_sk(MERKLE_LEAVES_PK.as_bytes(), &sk)?,
+        }
+
+        let leaves: Vec<Bytes> = self
+            .bucket_leaves(bucket)?
+            .into_iter()
+            .map(|(_, hash)| hash)
+            .collect();
+        self.put_node(0, bucket, &bucket_hash(leaves.iter()))?;
+
+        let mut index = bucket;
+        for depth in 1..=MERKLE_DEPTH {
+            index /= MERKLE_FANOUT;
+            let children = self.children(depth, index)?;
+            self.put_node(depth, index, &node_hash(&children))?;
+        }
+        Ok(())
+    }
+
+    /// The root hash, with a single read; None for an empty tree
+    pub fn root(&self) -> Result<Option<Bytes>> {
+        self.node(MERKLE_DEPTH, 0)
+    }
+
+    /// The tree as a `MerkleTree`, built from the stored leaf hashes
+    /// without reading any items
+    pub fn tree(&self) -> Result<MerkleTree> {
+        let mut leaves: Vec<(Bytes, Bytes)> = Vec::new();
+        for item in self.db.query_iter(Query::new(MERKLE_LEAVES_PK.as_bytes()))? {
+            let item = item?;
+            if let (Some(key), Some(hash)) = (binary(&item, "key"), binary(&item, "h")) {
+                leaves.push((key, hash));
+            }
+        }
+        leaves.sort();
+        Ok(MerkleTree::from_leaf_hashes(leaves, TREE_BRANCHING_FACTOR))
+    }
+
+    /// Keys that differ from another store, from this store's side
+    ///
+    /// Only subtrees whose hashes differ are read, so similar databases
+    /// compare quickly.
+    pub fn diff(&self, other: &MerkleStore) -> Result<Vec<(Key, DiffType)>> {
+        let mut diffs = Vec::new();
+        if self.root()? != other.root()? {
+            self.diff_node(other, MERKLE_DEPTH, 0, &mut diffs)?;
+        }
+        Ok(diffs)
+    }
+
+    fn diff_node(
+        &self,
+        other: &MerkleStore,
+        depth: u32,
+        index: usize,
+        diffs: &mut Vec<(Key, DiffType)>,
+    ) -> Result<()> {
+        if depth == 0 {
+            let ours: BTreeMap<Bytes, (Key, Bytes)> = self.bucket_leaves_with_keys(index)?;
+            let mut theirs = other.bucket_leaves_with_keys(index)?;
+            for (encoded, (key, hash)) in ours {
+                match theirs.remove(&encoded) {
+                    Some((_, their_hash)) if their_hash == hash => {}
+                    Some(_) => diffs.push((key, DiffType::Modified)),
+                    None => diffs.push((key, DiffType::LocalOnly)),
+                }
+            }
+            diffs.extend(theirs.into_values().map(|(key, _)| (key, DiffType::RemoteOnly)));
+            return Ok(());
+        }
+
+        let ours: HashMap<usize, Bytes> = self.children(depth, index)?.into_iter().collect();
+        let theirs: HashMap<usize, Bytes> = other.children(depth, index)?.into_iter().collect();
+        let first = index * MERKLE_FANOUT;
+        for child in first..first + MERKLE_FANOUT {
+            if ours.get(&child) != theirs.get(&child) {
+                self.diff_node(other, depth - 1, child, diffs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hash of a node; None if the subtree is empty
+    fn node(&self, depth: u32, index: usize) -> Result<Option<Bytes>> {
+        let item = self
+            .db
+            .get_with_sk(MERKLE_NODES_PK.as_bytes(), node_sk(depth, index).as_bytes())?;
+        Ok(item.as_ref().and_then(|item| binary(item, "h")))
+    }
+
+    /// Store a node's hash; empty subtrees aren't stored
+    fn put_node(&self, depth: u32, index: usize, hash: &Bytes) -> Result<()> {
+        let sk = node_sk(depth, index);
+        if hash.is_empty() {
+            self.db.delete_with_sk(MERKLE_NODES_PK.as_bytes(), sk.as_bytes())?;
+        } else {
+            let mut item = HashMap::new();
+            item.insert("h".to_string(), Value::B(hash.clone()));
+            item.insert("d".to_string(), Value::number(depth));
+            item.insert("i".to_string(), Value::number(index));
+            self.db.put_with_sk(MERKLE_NODES_PK.as_bytes(), sk.as_bytes(), item)?;
+        }
+        Ok(())
+    }
+
+    /// Non-empty children of a node at `depth`, as (index, hash) in order
+    fn children(&self, depth: u32, index: usize) -> Result<Vec<(usize, Bytes)>> {
+        let first = index * MERKLE_FANOUT;
+        let query = Query::new(MERKLE_NODES_PK.as_bytes()).sk_between(
+            node_sk(depth - 1, first).as_bytes(),
+            node_sk(depth - 1, first + MERKLE_FANOUT - 1).as_bytes(),
+        );
+
+        let mut children = Vec::new();
+        for item in self.db.query_iter(query)? {
+            let item = item?;
+            if let (Some(index), Some(hash)) = (number(&item, "i"), binary(&item, "h")) {
+                children.push((index, hash));
+            }
+        }
+        children.sort();
+        Ok(children)
+    }
+
+    /// Leaf hashes of a bucket in key order, with their encoded keys
+    fn bucket_leaves(&self, bucket: usize) -> Result<Vec<(Bytes, Bytes)>> {
+        Ok(self
+            .bucket_leaves_with_keys(bucket)?
+            .into_iter()
+            .map(|(encoded, (_, hash))| (encoded, hash))
+            .collect())
+    }
+
+    fn bucket_leaves_with_keys(&self, bucket: usize) -> Result<BTreeMap<Bytes, (Key, Bytes)>> {
+        let query = Query::new(MERKLE_LEAVES_PK.as_bytes()).sk_begins_with(bucket_prefix(bucket).as_bytes());
+        let mut leaves = BTreeMap::new();
+        for item in self.db.query_iter(query)? {
+            let item = item?;
+            let (Some(encoded), Some(hash), Some(pk)) =
+                (binary(&item, "key"), binary(&item, "h"), binary(&item, "pk"))
+            else {
+                continue;
+            };
+            let key = match binary(&item, "sk") {
+                Some(sk) => Key::with_sk(pk.to_vec(), sk.to_vec()),
+                None => Key::new(pk.to_vec()),
+            };
+            leaves.insert(encoded, (key, hash));
+        }
+        Ok(leaves)
+    }
+
+    /// Delete every leaf and node
+    fn clear(&self) -> Result<()> {
+        let mut leaves = Vec::new();
+        for item in self.db.query_iter(Query::new(MERKLE_LEAVES_PK.as_bytes()))? {
+            if let Some(encoded) = binary(&item?, "key") {
+                leaves.push(leaf_sk(bucket_of(&encoded), &encoded));
+            }
+        }
+        for sk in leaves {
+            self.db.delete_with_sk(MERKLE_LEAVES_PK.as_bytes(), &sk)?;
+        }
+
+        let mut nodes = Vec::new();
+        for item in self.db.query_iter(Query::new(MERKLE_NODES_PK.as_bytes()))? {
+            let item = item?;
+            if let (Some(depth), Some(index)) = (number(&item, "d"), number(&item, "i")) {
+                nodes.push(node_sk(depth as u32, index));
+            }
+        }
+        for sk in nodes {
+            self.db.delete_with_sk(MERKLE_NODES_PK.as_bytes(), sk.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Bucket of an encoded key: the first 12 bits of its SHA-256
+fn bucket_of(encoded: &[u8]) -> usize {
+    let digest = Sha256::digest(encoded);
+    ((digest[0] as usize) << 8 | digest[1] as usize) >> 4
+}
+
+fn bucket_prefix(bucket: usize) -> String {
+    format!("{:03x}#", bucket)
+}
+
+fn leaf_sk(bucket: usize, encoded: &[u8]) -> Vec<u8> {
+    let mut sk = bucket_prefix(bucket).into_bytes();
+    sk.extend_from_slice(encoded);
+    sk
+}
+
+fn node_sk(depth: u32, index: usize) -> String {
+    format!("{}#{:04x}", depth, index)
+}
+
+fn leaf_item(key: &Key, encoded: &Bytes, hash: &Bytes) -> Item {
+    let mut item = HashMap::new();
+    item.insert("h".to_string(), Value::B(hash.clone()));
+    item.insert("key".to_string(), Value::B(encoded.clone()));
+    item.insert("pk".to_string(), Value::B(key.pk.clone()));
+    if let Some(sk) = &key.sk {
+        item.insert("sk".to_string(), Value::B(sk.clone()));
+    }
+    item
+}
+
+/// Hash of a key and its item
+///
+/// The item is hashed as JSON with sorted attributes, so the same item
+/// always hashes the same.
+fn leaf_hash(encoded: &[u8], item: &Item) -> Result<Bytes> {
+    let canonical = serde_json::to_vec(&serde_json::to_value(item)?)?;
+    let mut hasher = Sha256::new();
+    hasher.update(encoded);
+    hasher.update(&canonical);
+    Ok(Bytes::from(hasher.finalize().to_vec()))
+}
+
+/// Hash of a bucket's leaf hashes in key order; empty for an empty bucket
+fn bucket_hash<'a>(leaves: impl Iterator<Item = &'a Bytes>) -> Bytes {
+    let mut hasher = Sha256::new();
+    let mut empty = true;
+    for hash in leaves {
+        hasher.update(hash);
+        empty = false;
+    }
+    if empty {
+        Bytes::new()
+    } else {
+        Bytes::from(hasher.finalize().to_vec())
+    }
+}
+
+/// Hash of a node's non-empty children, each with its position
+fn node_hash(children: &[(usize, Bytes)]) -> Bytes {
+    if children.is_empty() {
+        return Bytes::new();
+    }
+    let mut hasher = Sha256::new();
+    for (index, hash) in children {
+        hasher.update([(index % MERKLE_FANOUT) as u8]);
+        hasher.update(hash);
+    }
+    Bytes::from(hasher.finalize().to_vec())
+}
+
+fn binary(item: &Item, name: &str) -> Option<Bytes> {
+    match item.get(name) {
+        Some(Value::B(bytes)) => Some(bytes.clone()),
+        _ => None,
+    }
+}
+
+fn number(item: &Item, name: &str) -> Option<usize> {
+    match item.get(name) {
+        Some(Value::N(n)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    fn store_with(dir: &TempDir, keys: &[&str]) -> (Arc<Database>, MerkleStore) {
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        for key in keys {
+            db.put(key.as_bytes(), ItemBuilder::new().string("name", *key).build()).unwrap();
+        }
+        let store = MerkleStore::new(db.clone());
+        store.rebuild().unwrap();
+        (db, store)
+    }
+
+    #[test]
+    fn test_incremental_updates_match_rebuild() {
+        let dir = TempDir::new().unwrap();
+        let (db, store) = store_with(&dir, &["a", "b", "c"]);
+        assert!(store.is_built().unwrap());
+        let initial = store.root().unwrap();
+        assert!(initial.is_some());
+
+        // Write and delete through the store, as sync does
+        let item = ItemBuilder::new().string("name", "changed").build();
+        db.put(b"b", item.clone()).unwrap();
+        store.update(&Key::new(b"b".to_vec()), Some(&item)).unwrap();
+        db.delete(b"c").unwrap();
+        store.update(&Key::new(b"c".to_vec()), None).unwrap();
+        let updated = store.root().unwrap();
+        assert_ne!(updated, initial);
+
+        // The same as hashing everything again
+        store.rebuild().unwrap();
+        assert_eq!(store.root().unwrap(), updated);
+        assert_eq!(store.tree().unwrap().item_count, 2);
+    }
+
+    #[test]
+    fn test_store_diff() {
+        let (dir_a, dir_b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (_db_a, a) = store_with(&dir_a, &["same", "changed", "ours"]);
+        let (db_b, b) = store_with(&dir_b, &["same", "theirs"]);
+        let item = ItemBuilder::new().string("name", "different").build();
+        db_b.put(b"changed", item.clone()).unwrap();
+        b.update(&Key::new(b"changed".to_vec()), Some(&item)).unwrap();
+
+        let mut diffs = a.diff(&b).unwrap();
+        diffs.sort_by(|x, y| x.0.cmp(&y.0));
+        assert_eq!(
+            diffs,
+            vec![
+                (Key::new(b"changed".to_vec()), DiffType::Modified),
+                (Key::new(b"ours".to_vec()), DiffType::LocalOnly),
+                (Key::new(b"theirs".to_vec()), DiffType::RemoteOnly),
+            ]
+        );
+        assert!(a.diff(&a).unwrap().is_empty());
+    }
+}
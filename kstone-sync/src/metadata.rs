@@ -9,20 +9,32 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use kstone_api::{Database, ItemBuilder, KeystoneValue as Value};
+use kstone_api::{Database, ItemBuilder, KeystoneValue as Value, Query};
 use kstone_core::{Item, Key};
 
 use crate::{
     EndpointId, VectorClock, SyncRecord, Conflict, ConflictStrategy,
-    SyncStats, change_tracker::SyncRecord as TrackedRecord,
+    SyncStats, SyncScope, change_tracker::SyncRecord as TrackedRecord,
+    protocol::DiffType,
 };
 
 /// Prefixes for sync metadata tables
 const SYNC_METADATA_PREFIX: &str = "_sync#metadata#";
 const SYNC_CHECKPOINT_PREFIX: &str = "_sync#checkpoint#";
 const SYNC_PENDING_PREFIX: &str = "_sync#pending#";
-const SYNC_CONFLICT_PREFIX: &str = "_sync#conflict#";
+/// Partition holding the conflict inbox, one sort key per conflict
+const SYNC_CONFLICT_PK: &str = "_sync#conflicts";
 const SYNC_ENDPOINT_PREFIX: &str = "_sync#endpoint#";
+const SYNC_TRANSFER_PREFIX: &str = "_sync#transfer#";
+/// Partition holding the sync history, one sort key per run
+const SYNC_HISTORY_PK: &str = "_sync#history";
+
+/// Sync runs kept in the history; the oldest are deleted as runs are recorded
+pub const SYNC_HISTORY_LIMIT: usize = 1000;
+
+/// Changes per stored page of a transfer checkpoint, keeping pages well under
+/// the item size limit
+const TRANSFER_PAGE_SIZE: usize = 500;
 
 /// Sync metadata stored in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +127,9 @@ pub struct SyncMetadataConfig {
     pub max_pending_changes: usize,
     /// Maximum retry attempts
     pub max_retry_attempts: u32,
+    /// Items synced, as last configured
+    #[serde(default)]
+    pub scope: SyncScope,
 }
 
 impl Default for SyncMetadataConfig {
@@ -125,6 +140,7 @@ impl Default for SyncMetadataConfig {
             sync_interval_ms: Some(30_000), // 30 seconds
             max_pending_changes: 10_000,
             max_retry_attempts: 3,
+            scope: SyncScope::default(),
         }
     }
 }
@@ -144,6 +160,119 @@ pub struct SyncCheckpoint {
     pub timestamp: i64,
 }
 
+/// Progress of a transfer, so an interrupted sync resumes where it left off (Phase 8+)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferCheckpoint {
+    /// Endpoint the transfer is with
+    pub endpoint_id: EndpointId,
+    /// Changes to transfer, in transfer order (stored in pages)
+    #[serde(skip)]
+    pub changes: Vec<(Key, DiffType)>,
+    /// Number of changes
+    pub total: usize,
+    /// Changes transferred so far
+    pub completed: usize,
+    /// Items pushed so far
+    pub items_sent: usize,
+    /// Items pulled so far
+    pub items_received: usize,
+    /// Bytes pushed so far
+    pub bytes_sent: usize,
+    /// Bytes pulled so far
+    pub bytes_received: usize,
+    /// When the changes were discovered
+    pub started_at: i64,
+    /// When progress was last saved
+    pub updated_at: i64,
+}
+
+impl TransferCheckpoint {
+    /// A transfer of `changes` that hasn't started
+    pub fn new(endpoint_id: EndpointId, changes: Vec<(Key, DiffType)>) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            endpoint_id,
+            total: changes.len(),
+            changes,
+            completed: 0,
+            items_sent: 0,
+            items_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Changes not yet transferred
+    pub fn remaining(&self) -> usize {
+        self.total - self.completed
+    }
+}
+
+/// What a sync run moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncDirection {
+    /// Items both ways (`SyncEngine::sync`)
+    Bidirectional,
+    /// To the remote only (snapshot uploads)
+    Push,
+    /// From the remote only (snapshot restores)
+    Pull,
+}
+
+/// One sync run, as kept in the sync history (Phase 8+)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncRunRecord {
+    /// Unique ID; IDs sort by start time
+    pub id: String,
+    /// Endpoint synced with
+    pub endpoint_id: EndpointId,
+    pub direction: SyncDirection,
+    /// Start time (ms since epoch)
+    pub started_at: i64,
+    /// End time (ms since epoch)
+    pub finished_at: i64,
+    pub items_sent: usize,
+    pub items_received: usize,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub conflicts: usize,
+    /// Why the run failed; None if it succeeded
+    pub error: Option<String>,
+}
+
+impl SyncRunRecord {
+    /// A run that started at `started_at` and has just finished
+    pub fn new(endpoint_id: EndpointId, direction: SyncDirection, started_at: i64) -> Self {
+        let id = format!("{:013}-{}", started_at, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        Self {
+            id,
+            endpoint_id,
+            direction,
+            started_at,
+            finished_at: chrono::Utc::now().timestamp_millis(),
+            items_sent: 0,
+            items_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            conflicts: 0,
+            error: None,
+        }
+    }
+
+    /// Whether the run succeeded
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// How long the run took in milliseconds
+    pub fn duration_ms(&self) -> i64 {
+        self.finished_at - self.started_at
+    }
+}
+
 /// Storage operations for sync metadata
 pub struct SyncMetadataStore {
     db: Arc<Database>,
@@ -227,6 +356,153 @@ impl SyncMetadataStore {
         Ok(None)
     }
 
+    /// Save a new transfer checkpoint, including its changes
+    pub fn start_transfer(&self, checkpoint: &TransferCheckpoint) -> Result<()> {
+        // Pages first, so a header is only ever seen with all its pages
+        for (page, changes) in checkpoint.changes.chunks(TRANSFER_PAGE_SIZE).enumerate() {
+            let key = transfer_page_key(&checkpoint.endpoint_id, page);
+            let item = ItemBuilder::new()
+                .string("type", "sync_transfer_page")
+                .string("content", serde_json::to_string(changes)?)
+                .build();
+            self.db.put(key.as_bytes(), item)?;
+        }
+        self.save_transfer_progress(checkpoint)
+    }
+
+    /// Save how far a started transfer has got
+    pub fn save_transfer_progress(&self, checkpoint: &TransferCheckpoint) -> Result<()> {
+        let key = format!("{}{}", SYNC_TRANSFER_PREFIX, checkpoint.endpoint_id.0);
+        let json = serde_json::to_string(checkpoint)?;
+
+        let item = ItemBuilder::new()
+            .string("type", "sync_transfer")
+            .string("endpoint_id", &checkpoint.endpoint_id.0)
+            .string("content", json)
+            .number("completed", checkpoint.completed as i64)
+            .number("updated_at", checkpoint.updated_at)
+            .build();
+
+        self.db.put(key.as_bytes(), item)?;
+        Ok(())
+    }
+
+    /// Load the unfinished transfer with an endpoint, if any
+    pub fn load_transfer_checkpoint(&self, endpoint_id: &EndpointId) -> Result<Option<TransferCheckpoint>> {
+        let Some(mut checkpoint) = self.load_transfer_header(endpoint_id)? else {
+            return Ok(None);
+        };
+
+        let pages = checkpoint.total.div_ceil(TRANSFER_PAGE_SIZE);
+        for page in 0..pages {
+            let item = self.db.get(transfer_page_key(endpoint_id, page).as_bytes())?;
+            match item.as_ref().and_then(|item| item.get("content")) {
+                Some(Value::S(content)) => {
+                    let changes: Vec<(Key, DiffType)> = serde_json::from_str(content)?;
+                    checkpoint.changes.extend(changes);
+                }
+                _ => {
+                    tracing::warn!("Transfer checkpoint for {} is missing page {}", endpoint_id.0, page);
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Delete the transfer checkpoint for an endpoint, once the transfer is done
+    pub fn delete_transfer_checkpoint(&self, endpoint_id: &EndpointId) -> Result<()> {
+        let Some(checkpoint) = self.load_transfer_header(endpoint_id)? else {
+            return Ok(());
+        };
+
+        // Header first, so a half-deleted checkpoint is never resumed
+        let key = format!("{}{}", SYNC_TRANSFER_PREFIX, endpoint_id.0);
+        self.db.delete(key.as_bytes())?;
+        for page in 0..checkpoint.total.div_ceil(TRANSFER_PAGE_SIZE) {
+            self.db.delete(transfer_page_key(endpoint_id, page).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Load a transfer checkpoint without its changes
+    fn load_transfer_header(&self, endpoint_id: &EndpointId) -> Result<Option<TransferCheckpoint>> {
+        let key = format!("{}{}", SYNC_TRANSFER_PREFIX, endpoint_id.0);
+
+        if let Some(item) = self.db.get(key.as_bytes())? {
+            if let Some(Value::S(content)) = item.get("content") {
+                let checkpoint: TransferCheckpoint = serde_json::from_str(content)?;
+                return Ok(Some(checkpoint));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Add a run to the sync history, deleting the oldest runs beyond
+    /// `SYNC_HISTORY_LIMIT`
+    pub fn record_sync_run(&self, run: &SyncRunRecord) -> Result<()> {
+        let json = serde_json::to_string(run)?;
+
+        let item = ItemBuilder::new()
+            .string("type", "sync_run")
+            .string("run_id", &run.id)
+            .string("endpoint_id", &run.endpoint_id.0)
+            .string("content", json)
+            .number("started_at", run.started_at)
+            .bool("succeeded", run.succeeded())
+            .build();
+
+        self.db.put_with_sk(SYNC_HISTORY_PK.as_bytes(), run.id.as_bytes(), item)?;
+        self.prune_sync_history(SYNC_HISTORY_LIMIT)
+    }
+
+    /// Load up to `limit` sync runs, newest first
+    pub fn load_sync_history(&self, limit: usize) -> Result<Vec<SyncRunRecord>> {
+        let response = self.db.query(
+            Query::new(SYNC_HISTORY_PK.as_bytes())
+                .forward(false)
+                .limit(limit),
+        )?;
+
+        let mut runs = Vec::with_capacity(response.items.len());
+        for item in response.items {
+            if let Some(Value::S(content)) = item.get("content") {
+                runs.push(serde_json::from_str(content)?);
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Delete all but the newest `keep` sync runs
+    fn prune_sync_history(&self, keep: usize) -> Result<()> {
+        let newest = self.db.query(
+            Query::new(SYNC_HISTORY_PK.as_bytes())
+                .forward(false)
+                .limit(keep)
+                .projection(&["run_id"]),
+        )?;
+        if newest.items.len() < keep {
+            return Ok(());
+        }
+        let Some(Value::S(oldest_kept)) = newest.items.last().and_then(|item| item.get("run_id")) else {
+            return Ok(());
+        };
+
+        let older = self.db.query(
+            Query::new(SYNC_HISTORY_PK.as_bytes())
+                .sk_lt(oldest_kept.as_bytes())
+                .projection(&["run_id"]),
+        )?;
+        for item in older.items {
+            if let Some(Value::S(run_id)) = item.get("run_id") {
+                self.db.delete_with_sk(SYNC_HISTORY_PK.as_bytes(), run_id.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
     /// Save a pending sync record
     pub fn save_pending_record(&self, record: &TrackedRecord) -> Result<()> {
         let key = format!("{}{}", SYNC_PENDING_PREFIX, record.id);
@@ -266,7 +542,6 @@ impl SyncMetadataStore {
 
     /// Save a conflict
     pub fn save_conflict(&self, conflict: &Conflict) -> Result<()> {
-        let key = format!("{}{}", SYNC_CONFLICT_PREFIX, conflict.id);
         let json = serde_json::to_string(conflict)?;
 
         let item = ItemBuilder::new()
@@ -277,24 +552,42 @@ impl SyncMetadataStore {
             .bool("resolved", conflict.resolved)
             .build();
 
-        self.db.put(key.as_bytes(), item)?;
+        self.db.put_with_sk(SYNC_CONFLICT_PK.as_bytes(), conflict.id.as_bytes(), item)?;
         Ok(())
     }
 
-    /// Load conflicts
+    /// Load a conflict by ID
+    pub fn load_conflict(&self, conflict_id: &str) -> Result<Option<Conflict>> {
+        if let Some(item) = self.db.get_with_sk(SYNC_CONFLICT_PK.as_bytes(), conflict_id.as_bytes())? {
+            if let Some(Value::S(content)) = item.get("content") {
+                let conflict: Conflict = serde_json::from_str(content)?;
+                return Ok(Some(conflict));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Load conflicts, oldest first
     pub fn load_conflicts(&self, only_pending: bool) -> Result<Vec<Conflict>> {
         let mut conflicts = Vec::new();
 
-        // Placeholder implementation - would need proper prefix scan
-        // with filtering based on resolved status
+        for item in self.db.query_iter(Query::new(SYNC_CONFLICT_PK.as_bytes()))? {
+            if let Some(Value::S(content)) = item?.get("content") {
+                let conflict: Conflict = serde_json::from_str(content)?;
+                if !(only_pending && conflict.resolved) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
 
+        conflicts.sort_by(|a, b| a.detected_at.cmp(&b.detected_at).then_with(|| a.id.cmp(&b.id)));
         Ok(conflicts)
     }
 
     /// Delete a conflict
     pub fn delete_conflict(&self, conflict_id: &str) -> Result<()> {
-        let key = format!("{}{}", SYNC_CONFLICT_PREFIX, conflict_id);
-        self.db.delete(key.as_bytes())?;
+        self.db.delete_with_sk(SYNC_CONFLICT_PK.as_bytes(), conflict_id.as_bytes())?;
         Ok(())
     }
 
@@ -342,6 +635,11 @@ impl SyncMetadataStore {
     }
 }
 
+/// Key of one page of a transfer checkpoint's changes
+fn transfer_page_key(endpoint_id: &EndpointId, page: usize) -> String {
+    format!("{}{}#page#{:08}", SYNC_TRANSFER_PREFIX, endpoint_id.0, page)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,7 +649,7 @@ mod tests {
     fn test_sync_metadata_store() {
         let dir = TempDir::new().unwrap();
         let db = Database::create(dir.path()).unwrap();
-        let store = SyncMetadataStore::new(&db);
+        let store = SyncMetadataStore::new(Arc::new(db));
 
         // Initialize
         store.initialize().unwrap();
@@ -382,7 +680,7 @@ mod tests {
     fn test_checkpoint_storage() {
         let dir = TempDir::new().unwrap();
         let db = Database::create(dir.path()).unwrap();
-        let store = SyncMetadataStore::new(&db);
+        let store = SyncMetadataStore::new(Arc::new(db));
 
         let checkpoint = SyncCheckpoint {
             endpoint_id: EndpointId::from_str("remote1"),
@@ -398,4 +696,59 @@ mod tests {
         assert!(loaded.is_some());
         assert_eq!(loaded.unwrap().last_sequence, 100);
     }
+
+    #[test]
+    fn test_transfer_checkpoint_storage() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let store = SyncMetadataStore::new(db.clone());
+        let endpoint_id = EndpointId::from_str("remote1");
+
+        // Spans several pages
+        let changes: Vec<_> = (0..TRANSFER_PAGE_SIZE * 2 + 1)
+            .map(|i| (Key::new(format!("user#{}", i).into_bytes()), DiffType::LocalOnly))
+            .collect();
+        let mut checkpoint = TransferCheckpoint::new(endpoint_id.clone(), changes.clone());
+        store.start_transfer(&checkpoint).unwrap();
+
+        checkpoint.completed = 600;
+        checkpoint.items_sent = 600;
+        store.save_transfer_progress(&checkpoint).unwrap();
+
+        let loaded = store.load_transfer_checkpoint(&endpoint_id).unwrap().unwrap();
+        assert_eq!(loaded.changes, changes);
+        assert_eq!(loaded.completed, 600);
+        assert_eq!(loaded.remaining(), TRANSFER_PAGE_SIZE * 2 + 1 - 600);
+
+        store.delete_transfer_checkpoint(&endpoint_id).unwrap();
+        assert!(store.load_transfer_checkpoint(&endpoint_id).unwrap().is_none());
+        assert!(db.get(transfer_page_key(&endpoint_id, 0).as_bytes()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sync_history() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path()).unwrap());
+        let store = SyncMetadataStore::new(db);
+        let endpoint_id = EndpointId::from_str("remote1");
+
+        for i in 0..5 {
+            let mut run = SyncRunRecord::new(endpoint_id.clone(), SyncDirection::Bidirectional, 1_000 + i);
+            run.items_sent = i as usize;
+            if i == 3 {
+                run.error = Some("connection reset".to_string());
+            }
+            store.record_sync_run(&run).unwrap();
+        }
+
+        let runs = store.load_sync_history(10).unwrap();
+        assert_eq!(runs.iter().map(|r| r.items_sent).collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+        assert!(!runs[1].succeeded());
+        assert_eq!(store.load_sync_history(2).unwrap().len(), 2);
+
+        // Pruning keeps the newest runs
+        store.prune_sync_history(3).unwrap();
+        let runs = store.load_sync_history(10).unwrap();
+        assert_eq!(runs.iter().map(|r| r.items_sent).collect::<Vec<_>>(), vec![4, 3, 2]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+/// Sync notifications: webhooks and callbacks (Phase 8+)
+///
+/// A sync engine tells its notifiers when a sync run completes, when a
+/// conflict is detected, and when runs keep failing, so operators can wire
+/// up alerts without polling `kstone sync status`. Notifications are
+/// delivered in the background and waited for at the end of each run; a
+/// notifier that fails is logged and never fails the sync.
+///
+/// `WebhookNotifier` POSTs each notification as JSON, signed with
+/// HMAC-SHA256 when given a secret.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use kstone_core::Key;
+
+use crate::metadata::SyncRunRecord;
+
+/// Consecutive failed runs that trigger a `RepeatedFailure` notification
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Header carrying a webhook payload's signature, as `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-Kstone-Signature";
+
+/// Something operators may want to hear about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SyncNotification {
+    /// A sync run finished successfully
+    SyncCompleted { run: SyncRunRecord },
+    /// Local and remote changes to an item conflicted
+    ConflictDetected {
+        conflict_id: String,
+        pk: String,
+        sk: Option<String>,
+    },
+    /// Sync runs failed this many times in a row (sent again every
+    /// threshold failures until a run succeeds)
+    RepeatedFailure {
+        run: SyncRunRecord,
+        consecutive_failures: u32,
+    },
+}
+
+impl SyncNotification {
+    pub(crate) fn conflict_detected(conflict_id: &str, key: &Key) -> Self {
+        Self::ConflictDetected {
+            conflict_id: conflict_id.to_string(),
+            pk: String::from_utf8_lossy(&key.pk).to_string(),
+            sk: key.sk.as_ref().map(|sk| String::from_utf8_lossy(sk).to_string()),
+        }
+    }
+}
+
+/// The JSON body of a webhook request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// Unique per delivery, for deduplicating retries
+    pub id: String,
+    /// When the notification was sent (ms since epoch)
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub notification: SyncNotification,
+}
+
+impl WebhookPayload {
+    pub fn new(notification: SyncNotification) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            notification,
+        }
+    }
+}
+
+/// Receives sync notifications
+#[async_trait]
+pub trait SyncNotifier: Send + Sync {
+    async fn notify(&self, notification: &SyncNotification) -> Result<()>;
+}
+
+/// Calls a function for each notification
+pub struct CallbackNotifier<F>(F);
+
+impl<F> CallbackNotifier<F>
+where
+    F: Fn(&SyncNotification) + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        Self(callback)
+    }
+}
+
+#[async_trait]
+impl<F> SyncNotifier for CallbackNotifier<F>
+where
+    F: Fn(&SyncNotification) + Send + Sync,
+{
+    async fn notify(&self, notification: &SyncNotification) -> Result<()> {
+        (self.0)(notification);
+        Ok(())
+    }
+}
+
+/// POSTs notifications as JSON to a URL
+#[cfg(feature = "webhooks")]
+pub struct WebhookNotifier {
+    url: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "webhooks")]
+impl WebhookNotifier {
+    /// Requests time out after this long
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            client: reqwest::Client::builder()
+                .timeout(Self::TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Sign payloads with this secret (see `SIGNATURE_HEADER`)
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+}
+
+#[cfg(feature = "webhooks")]
+#[async_trait]
+impl SyncNotifier for WebhookNotifier {
+    async fn notify(&self, notification: &SyncNotification) -> Result<()> {
+        let body = serde_json::to_vec(&WebhookPayload::new(notification.clone()))?;
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret.as_bytes(), &body));
+        }
+
+        request.body(body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The signature header value for a payload: `sha256=` and the hex HMAC-SHA256
+pub fn sign_payload(secret: &[u8], body: &[u8]) -> String {
+    let mac = hmac_sha256(secret, body).finalize().into_bytes();
+    let hex: String = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Check a signature header value against a payload, in constant time
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return false;
+    }
+    let bytes: Option<Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect();
+    match bytes {
+        Some(bytes) => hmac_sha256(secret, body).verify_slice(&bytes).is_ok(),
+        None => false,
+    }
+}
+
+fn hmac_sha256(secret: &[u8], body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac
+}
+
+/// A sync engine's notifiers, and the failure streak they're alerted to
+pub(crate) struct Notifications {
+    notifiers: RwLock<Vec<Arc<dyn SyncNotifier>>>,
+    failure_threshold: AtomicU32,
+    consecutive_failures: AtomicU32,
+    deliveries: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Notifications {
+    pub(crate) fn new() -> Self {
+        Self {
+            notifiers: RwLock::new(Vec::new()),
+            failure_threshold: AtomicU32::new(DEFAULT_FAILURE_THRESHOLD),
+            consecutive_failures: AtomicU32::new(0),
+            deliveries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn add(&self, notifier: Arc<dyn SyncNotifier>) {
+        self.notifiers.write().push(notifier);
+    }
+
+    pub(crate) fn set_failure_threshold(&self, failures: u32) {
+        self.failure_threshold.store(failures.max(1), Ordering::Relaxed);
+    }
+
+    /// Notify about a finished run: its completion, or a failure streak
+    pub(crate) fn run_finished(&self, run: &SyncRunRecord) {
+        if run.succeeded() {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+            self.send(SyncNotification::SyncCompleted { run: run.clone() });
+            return;
+        }
+
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures % self.failure_threshold.load(Ordering::Relaxed) == 0 {
+            self.send(SyncNotification::RepeatedFailure {
+                run: run.clone(),
+                consecutive_failures: failures,
+            });
+        }
+    }
+
+    /// Start delivering a notification to every notifier
+    pub(crate) fn send(&self, notification: SyncNotification) {
+        let notifiers = self.notifiers.read().clone();
+        if notifiers.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("Sync notification dropped outside a Tokio runtime");
+            return;
+        };
+
+        let mut deliveries = self.deliveries.lock();
+        deliveries.retain(|delivery| !delivery.is_finished());
+        for notifier in notifiers {
+            let notification = notification.clone();
+            deliveries.push(runtime.spawn(async move {
+                if let Err(e) = notifier.notify(&notification).await {
+                    tracing::warn!("Failed to deliver sync notification: {:#}", e);
+                }
+            }));
+        }
+    }
+
+    /// Wait for notifications still being delivered
+    pub(crate) async fn flush(&self) {
+        let deliveries = std::mem::take(&mut *self.deliveries.lock());
+        for delivery in deliveries {
+            if let Err(e) = delivery.await {
+                tracing::warn!("Sync notification task failed: {}", e);
+            }
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A notifier that was given as a closure, for `SyncEngine::on_notification`
+pub(crate) fn callback<F>(callback: F) -> Arc<dyn SyncNotifier>
+where
+    F: Fn(&SyncNotification) + Send + Sync + 'static,
+{
+    Arc::new(CallbackNotifier::new(callback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::SyncDirection;
+    use crate::EndpointId;
+
+    fn run(error: Option<&str>) -> SyncRunRecord {
+        let mut run = SyncRunRecord::new(EndpointId::from_str("remote"), SyncDirection::Bidirectional, 0);
+        run.error = error.map(str::to_string);
+        run
+    }
+
+    #[test]
+    fn test_payload_signature() {
+        let body = serde_json::to_vec(&WebhookPayload::new(SyncNotification::conflict_detected(
+            "c1",
+            &Key::new(b"user#1".to_vec()),
+        )))
+        .unwrap();
+
+        let signature = sign_payload(b"secret", &body);
+        assert!(signature.starts_with("sha256="));
+        assert!(verify_signature(b"secret", &body, &signature));
+        assert!(!verify_signature(b"other", &body, &signature));
+        assert!(!verify_signature(b"secret", b"tampered", &signature));
+        assert!(!verify_signature(b"secret", &body, "sha256=zz"));
+
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["event"], "conflict_detected");
+        assert_eq!(json["pk"], "user#1");
+    }
+
+    #[tokio::test]
+    async fn test_notifications() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let notifications = Notifications::new();
+        let sink = received.clone();
+        notifications.add(callback(move |notification| sink.lock().push(notification.clone())));
+        notifications.set_failure_threshold(2);
+
+        notifications.run_finished(&run(Some("unreachable")));
+        notifications.run_finished(&run(Some("unreachable")));
+        notifications.run_finished(&run(Some("unreachable")));
+        notifications.run_finished(&run(None));
+        notifications.flush().await;
+
+        let received = received.lock();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(
+            received[0],
+            SyncNotification::RepeatedFailure { consecutive_failures: 2, .. }
+        ));
+        assert!(matches!(received[1], SyncNotification::SyncCompleted { .. }));
+    }
+}
@@ -131,7 +131,8 @@ impl PendingOperation {
         }
 
         if let Some(last_retry) = self.last_retry_at {
-            let backoff_duration = backoff_ms * 2_u64.pow(self.retry_count.min(10));
+            // The first retry waits the initial backoff, each later one twice as long
+            let backoff_duration = backoff_ms * 2_u64.pow(self.retry_count.saturating_sub(1).min(10));
             let next_retry_time = last_retry + backoff_duration as i64;
             let now = chrono::Utc::now().timestamp_millis();
             now >= next_retry_time
@@ -423,6 +424,7 @@ struct QueueState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_offline_queue_basic() {
@@ -22,6 +22,10 @@ pub mod filesystem;
 #[cfg(feature = "s3-sync")]
 pub mod s3;
 
+/// DynamoDB protocol implementation
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
+
 /// Sync endpoint configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SyncEndpoint {
@@ -230,7 +234,7 @@ pub trait SyncProtocol: Send + Sync {
 }
 
 /// Type of difference detected
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiffType {
     /// Item only exists locally
     LocalOnly,
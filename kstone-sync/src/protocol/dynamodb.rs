@@ -0,0 +1,841 @@
+/// DynamoDB sync protocol implementation
+///
+/// Enables bidirectional synchronization between KeystoneDB and a DynamoDB
+/// table. The table's key schema is read on connect: its partition key holds
+/// `Key::pk` and its sort key, if it has one, `Key::sk`.
+///
+/// Every item written by sync also carries `_kstone_version` and
+/// `_kstone_clock`. Writes are conditional on what was last read for the key,
+/// so an item changed in DynamoDB after the diff is rejected rather than
+/// overwritten, and shows up as modified on the next sync.
+
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_dynamodb::config::Credentials;
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::{AttributeValue, KeyType, KeysAndAttributes, ScalarAttributeType};
+use aws_sdk_dynamodb::Client;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use kstone_api::{Database, Scan, Select};
+use kstone_core::{Item, Key, Value};
+
+use crate::{
+    EndpointId, VectorClock,
+    protocol::{capabilities, AwsCredentials, SyncProtocol, SyncMessage, SyncSessionStats, DiffType},
+    merkle::{MerkleNode, MerkleTree},
+};
+
+/// Attribute counting the writes sync made to an item
+pub const VERSION_ATTRIBUTE: &str = "_kstone_version";
+
+/// Attribute holding the vector clock of the last sync write, as JSON
+pub const CLOCK_ATTRIBUTE: &str = "_kstone_clock";
+
+/// Keys per BatchGetItem request, DynamoDB's limit
+const BATCH_GET_SIZE: usize = 100;
+
+/// Attempts at keys DynamoDB leaves unprocessed before giving up
+const MAX_BATCH_GET_ATTEMPTS: u32 = 8;
+
+/// Local keys listed per scan page
+const KEYS_PAGE_SIZE: usize = 1000;
+
+/// Children per merkle tree node, as the sync engine uses
+const BRANCHING_FACTOR: usize = 16;
+
+/// Prefix of the sync metadata records kept in the local database
+const SYNC_METADATA_PREFIX: &[u8] = b"_sync#";
+
+/// A key attribute of the table
+#[derive(Debug, Clone)]
+struct KeyAttribute {
+    name: String,
+    kind: ScalarAttributeType,
+}
+
+impl KeyAttribute {
+    /// Key bytes as a value of this attribute's type
+    fn to_attribute(&self, bytes: &[u8]) -> Result<AttributeValue> {
+        if self.kind == ScalarAttributeType::B {
+            return Ok(AttributeValue::B(Blob::new(bytes.to_vec())));
+        }
+
+        let text = String::from_utf8(bytes.to_vec()).map_err(|_| {
+            anyhow!(
+                "Key attribute {} holds {} values, but the key is not UTF-8",
+                self.name,
+                self.kind.as_str()
+            )
+        })?;
+        if self.kind == ScalarAttributeType::N {
+            Ok(AttributeValue::N(text))
+        } else {
+            Ok(AttributeValue::S(text))
+        }
+    }
+
+    fn from_attribute(&self, value: &AttributeValue) -> Result<Bytes> {
+        match value {
+            AttributeValue::S(s) | AttributeValue::N(s) => Ok(Bytes::from(s.clone())),
+            AttributeValue::B(b) => Ok(Bytes::copy_from_slice(b.as_ref())),
+            _ => Err(anyhow!("Key attribute {} has an unsupported type", self.name)),
+        }
+    }
+}
+
+/// What a key held in DynamoDB when last read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Observed {
+    /// No item
+    Absent,
+    /// An item sync never wrote
+    Unversioned,
+    /// An item sync wrote, at this version
+    Version(u64),
+}
+
+/// An item read from the table
+struct RemoteItem {
+    key: Key,
+    item: Item,
+    clock: VectorClock,
+    observed: Observed,
+}
+
+/// Condition for a write, in the form the request builders take
+#[derive(Debug, Default)]
+struct WriteCondition {
+    expression: Option<String>,
+    names: Option<HashMap<String, String>>,
+    values: Option<HashMap<String, AttributeValue>>,
+}
+
+/// DynamoDB sync protocol
+pub struct DynamoDBProtocol {
+    /// AWS region of the table
+    region: String,
+    /// DynamoDB table name
+    table_name: String,
+    /// Credentials; the default AWS provider chain is used if None
+    credentials: Option<AwsCredentials>,
+    /// Endpoint to use instead of AWS (e.g. DynamoDB Local)
+    endpoint_url: Option<String>,
+    /// DynamoDB client, set on connect
+    client: Option<Client>,
+    /// Partition key attribute, read on connect
+    partition_key: Option<KeyAttribute>,
+    /// Sort key attribute, if the table has one
+    sort_key: Option<KeyAttribute>,
+    /// Local database reference for comparisons
+    local_db: Option<Arc<Database>>,
+    /// Remote endpoint ID
+    remote_endpoint_id: Option<EndpointId>,
+    /// Remote vector clock
+    remote_clock: Option<VectorClock>,
+    /// What each key held when last read, for conditional writes
+    observed: HashMap<Key, Observed>,
+    /// Keys whose writes were rejected since connecting
+    rejected: Vec<Key>,
+    capabilities: Vec<String>,
+}
+
+impl DynamoDBProtocol {
+    /// Create a new DynamoDB protocol
+    pub fn new(region: String, table_name: String, credentials: Option<AwsCredentials>) -> Self {
+        Self {
+            region,
+            table_name,
+            credentials,
+            endpoint_url: None,
+            client: None,
+            partition_key: None,
+            sort_key: None,
+            local_db: None,
+            remote_endpoint_id: None,
+            remote_clock: None,
+            observed: HashMap::new(),
+            rejected: Vec::new(),
+            capabilities: vec![
+                capabilities::BATCH_SYNC.to_string(),
+                capabilities::BIDIRECTIONAL.to_string(),
+                capabilities::CONFLICT_RESOLUTION.to_string(),
+            ],
+        }
+    }
+
+    /// Connect to `endpoint_url` instead of AWS, e.g. DynamoDB Local
+    pub fn with_endpoint_url(mut self, endpoint_url: String) -> Self {
+        self.endpoint_url = Some(endpoint_url);
+        self
+    }
+
+    /// Set the local database reference for comparisons
+    pub fn with_local_db(mut self, db: Arc<Database>) -> Self {
+        self.local_db = Some(db);
+        self
+    }
+
+    /// Keys whose writes were rejected because the item changed in DynamoDB
+    /// after it was read
+    pub fn rejected(&self) -> &[Key] {
+        &self.rejected
+    }
+
+    fn client(&self) -> Result<&Client> {
+        self.client.as_ref().ok_or_else(|| anyhow!("DynamoDB client not connected"))
+    }
+
+    fn partition_key(&self) -> Result<&KeyAttribute> {
+        self.partition_key.as_ref().ok_or_else(|| anyhow!("DynamoDB client not connected"))
+    }
+
+    /// Read the table's key schema
+    async fn describe_table(&mut self) -> Result<()> {
+        let output = self
+            .client()?
+            .describe_table()
+            .table_name(&self.table_name)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Cannot describe DynamoDB table {}: {}", self.table_name, e))?;
+        let table = output
+            .table
+            .ok_or_else(|| anyhow!("DynamoDB table {} not found", self.table_name))?;
+
+        self.partition_key = None;
+        self.sort_key = None;
+        for element in table.key_schema() {
+            let name = element.attribute_name();
+            let kind = table
+                .attribute_definitions()
+                .iter()
+                .find(|definition| definition.attribute_name() == name)
+                .map(|definition| definition.attribute_type().clone())
+                .ok_or_else(|| anyhow!("Key attribute {} has no definition", name))?;
+            let attribute = KeyAttribute { name: name.to_string(), kind };
+
+            match element.key_type() {
+                KeyType::Hash => self.partition_key = Some(attribute),
+                KeyType::Range => self.sort_key = Some(attribute),
+                other => return Err(anyhow!("Unsupported key type {:?}", other)),
+            }
+        }
+
+        if self.partition_key.is_none() {
+            return Err(anyhow!("DynamoDB table {} has no partition key", self.table_name));
+        }
+        Ok(())
+    }
+
+    /// DynamoDB key of `key`
+    fn key_attributes(&self, key: &Key) -> Result<HashMap<String, AttributeValue>> {
+        let partition_key = self.partition_key()?;
+        let mut attributes = HashMap::new();
+        attributes.insert(partition_key.name.clone(), partition_key.to_attribute(&key.pk)?);
+
+        match (&self.sort_key, &key.sk) {
+            (Some(sort_key), Some(sk)) => {
+                attributes.insert(sort_key.name.clone(), sort_key.to_attribute(sk)?);
+            }
+            (None, None) => {}
+            (Some(sort_key), None) => {
+                return Err(anyhow!(
+                    "Table {} needs a sort key ({}), but key {} has none",
+                    self.table_name,
+                    sort_key.name,
+                    String::from_utf8_lossy(&key.pk)
+                ));
+            }
+            (None, Some(_)) => {
+                return Err(anyhow!(
+                    "Table {} has no sort key, but key {} has one",
+                    self.table_name,
+                    String::from_utf8_lossy(&key.pk)
+                ));
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Whether `name` is a key or sync attribute rather than item data
+    fn is_reserved(&self, name: &str) -> bool {
+        name == VERSION_ATTRIBUTE
+            || name == CLOCK_ATTRIBUTE
+            || self.partition_key.as_ref().is_some_and(|a| a.name == name)
+            || self.sort_key.as_ref().is_some_and(|a| a.name == name)
+    }
+
+    /// Item attributes to write, leaving out names the key and sync use
+    fn item_attributes(&self, item: &Item) -> HashMap<String, AttributeValue> {
+        item.iter()
+            .filter(|(name, _)| !self.is_reserved(name))
+            .map(|(name, value)| (name.clone(), value_to_attribute(value)))
+            .collect()
+    }
+
+    /// Split a DynamoDB item into its key, data and sync attributes
+    fn split_item(&self, mut attributes: HashMap<String, AttributeValue>) -> Result<RemoteItem> {
+        let partition_key = self.partition_key()?;
+        let pk = attributes
+            .remove(&partition_key.name)
+            .ok_or_else(|| anyhow!("Item has no partition key {}", partition_key.name))?;
+        let pk = partition_key.from_attribute(&pk)?;
+
+        let sk = match &self.sort_key {
+            Some(sort_key) => {
+                let sk = attributes
+                    .remove(&sort_key.name)
+                    .ok_or_else(|| anyhow!("Item has no sort key {}", sort_key.name))?;
+                Some(sort_key.from_attribute(&sk)?)
+            }
+            None => None,
+        };
+
+        let observed = match attributes.remove(VERSION_ATTRIBUTE) {
+            Some(AttributeValue::N(n)) => Observed::Version(n.parse().unwrap_or(0)),
+            _ => Observed::Unversioned,
+        };
+        let clock = match attributes.remove(CLOCK_ATTRIBUTE) {
+            Some(AttributeValue::S(json)) => serde_json::from_str(&json).unwrap_or_else(|_| VectorClock::new()),
+            _ => VectorClock::new(),
+        };
+
+        Ok(RemoteItem {
+            key: Key { pk, sk },
+            item: attributes_to_item(&attributes)?,
+            clock,
+            observed,
+        })
+    }
+
+    /// Bytes hashed for an item in the merkle trees
+    ///
+    /// Items are compared in DynamoDB form, so that values DynamoDB has no
+    /// type for (vectors, timestamps) don't count as changes after a round
+    /// trip.
+    fn item_bytes(&self, item: &Item) -> Result<Vec<u8>> {
+        let item = attributes_to_item(&self.item_attributes(item))?;
+        // serde_json::Value sorts map keys, unlike the HashMaps inside Item
+        Ok(serde_json::to_vec(&serde_json::to_value(&item)?)?)
+    }
+
+    /// Every item in the table
+    async fn scan_table(&self) -> Result<Vec<RemoteItem>> {
+        let client = self.client()?;
+        let mut items = Vec::new();
+        let mut start_key = None;
+
+        loop {
+            let output = client
+                .scan()
+                .table_name(&self.table_name)
+                .consistent_read(true)
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(|e| anyhow!("DynamoDB scan of {} failed: {}", self.table_name, e))?;
+
+            for attributes in output.items.unwrap_or_default() {
+                items.push(self.split_item(attributes)?);
+            }
+
+            start_key = output.last_evaluated_key;
+            if start_key.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Read `keys` with BatchGetItem, retrying keys DynamoDB leaves unprocessed
+    async fn batch_get(&self, keys: &[Key]) -> Result<Vec<RemoteItem>> {
+        let client = self.client()?;
+        let mut items = Vec::new();
+
+        for chunk in keys.chunks(BATCH_GET_SIZE) {
+            let request_keys = chunk
+                .iter()
+                .map(|key| self.key_attributes(key))
+                .collect::<Result<Vec<_>>>()?;
+            let mut request = Some(
+                KeysAndAttributes::builder()
+                    .set_keys(Some(request_keys))
+                    .consistent_read(true)
+                    .build()?,
+            );
+
+            let mut attempt = 0;
+            while let Some(keys_and_attributes) = request.take() {
+                attempt += 1;
+                if attempt > MAX_BATCH_GET_ATTEMPTS {
+                    return Err(anyhow!("DynamoDB kept throttling reads from {}", self.table_name));
+                }
+                if attempt > 1 {
+                    tokio::time::sleep(Duration::from_millis(50 << attempt.min(6))).await;
+                }
+
+                let output = client
+                    .batch_get_item()
+                    .request_items(&self.table_name, keys_and_attributes)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("DynamoDB batch get from {} failed: {}", self.table_name, e))?;
+
+                let responses = output
+                    .responses
+                    .and_then(|mut responses| responses.remove(&self.table_name))
+                    .unwrap_or_default();
+                for attributes in responses {
+                    items.push(self.split_item(attributes)?);
+                }
+
+                request = output
+                    .unprocessed_keys
+                    .and_then(|mut unprocessed| unprocessed.remove(&self.table_name))
+                    .filter(|unprocessed| !unprocessed.keys().is_empty());
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// Condition that a key still holds what was last read, so that concurrent
+/// changes in DynamoDB are not overwritten
+fn write_condition(partition_key: &str, observed: Option<Observed>) -> WriteCondition {
+    let names = |pairs: &[(&str, &str)]| {
+        Some(pairs.iter().map(|(alias, name)| (alias.to_string(), name.to_string())).collect())
+    };
+
+    match observed {
+        // Keys that were never read are written unconditionally
+        None => WriteCondition::default(),
+        Some(Observed::Absent) => WriteCondition {
+            expression: Some("attribute_not_exists(#pk)".to_string()),
+            names: names(&[("#pk", partition_key)]),
+            values: None,
+        },
+        Some(Observed::Unversioned) => WriteCondition {
+            expression: Some("attribute_exists(#pk) AND attribute_not_exists(#version)".to_string()),
+            names: names(&[("#pk", partition_key), ("#version", VERSION_ATTRIBUTE)]),
+            values: None,
+        },
+        Some(Observed::Version(version)) => WriteCondition {
+            expression: Some("#version = :version".to_string()),
+            names: names(&[("#version", VERSION_ATTRIBUTE)]),
+            values: Some(HashMap::from([(
+                ":version".to_string(),
+                AttributeValue::N(version.to_string()),
+            )])),
+        },
+    }
+}
+
+/// Every item in `db`, leaving out sync metadata
+fn local_items(db: &Database) -> Result<Vec<(Key, Item)>> {
+    let mut items = Vec::new();
+    let mut last_key: Option<(Bytes, Option<Bytes>)> = None;
+
+    loop {
+        let mut scan = Scan::new().select(Select::Keys).limit(KEYS_PAGE_SIZE);
+        if let Some((pk, sk)) = &last_key {
+            scan = scan.start_after(pk, sk.as_deref());
+        }
+        let page = db.scan(scan)?;
+
+        for (pk, sk) in page.keys {
+            if pk.starts_with(SYNC_METADATA_PREFIX) {
+                continue;
+            }
+            let item = match &sk {
+                Some(sk) => db.get_with_sk(&pk, sk)?,
+                None => db.get(&pk)?,
+            };
+            // Keys listed a moment ago may have been deleted since
+            if let Some(item) = item {
+                items.push((Key { pk, sk }, item));
+            }
+        }
+
+        last_key = page.last_key;
+        if last_key.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Convert a Value to a DynamoDB AttributeValue
+///
+/// Vectors become lists of numbers and timestamps numbers, since DynamoDB has
+/// no equivalent types.
+pub fn value_to_attribute(value: &Value) -> AttributeValue {
+    match value {
+        Value::S(s) => AttributeValue::S(s.clone()),
+        Value::N(n) => AttributeValue::N(n.clone()),
+        Value::B(b) => AttributeValue::B(Blob::new(b.to_vec())),
+        Value::Bool(b) => AttributeValue::Bool(*b),
+        Value::Null => AttributeValue::Null(true),
+        Value::L(list) => AttributeValue::L(list.iter().map(value_to_attribute).collect()),
+        Value::M(map) => AttributeValue::M(
+            map.iter()
+                .map(|(name, value)| (name.clone(), value_to_attribute(value)))
+                .collect(),
+        ),
+        Value::VecF32(v) => AttributeValue::L(v.iter().map(|f| AttributeValue::N(f.to_string())).collect()),
+        Value::Ts(ts) => AttributeValue::N(ts.to_string()),
+    }
+}
+
+/// Convert a DynamoDB AttributeValue to a Value
+///
+/// Sets become lists, since KeystoneDB has no set types.
+pub fn attribute_to_value(attribute: &AttributeValue) -> Result<Value> {
+    let bytes = |b: &Blob| Value::B(Bytes::copy_from_slice(b.as_ref()));
+
+    Ok(match attribute {
+        AttributeValue::S(s) => Value::S(s.clone()),
+        AttributeValue::N(n) => Value::N(n.clone()),
+        AttributeValue::B(b) => bytes(b),
+        AttributeValue::Bool(b) => Value::Bool(*b),
+        AttributeValue::Null(_) => Value::Null,
+        AttributeValue::L(list) => Value::L(list.iter().map(attribute_to_value).collect::<Result<_>>()?),
+        AttributeValue::M(map) => Value::M(attributes_to_item(map)?),
+        AttributeValue::Ss(set) => Value::L(set.iter().cloned().map(Value::S).collect()),
+        AttributeValue::Ns(set) => Value::L(set.iter().cloned().map(Value::N).collect()),
+        AttributeValue::Bs(set) => Value::L(set.iter().map(bytes).collect()),
+        other => return Err(anyhow!("Unsupported DynamoDB attribute value: {:?}", other)),
+    })
+}
+
+/// Convert a map of DynamoDB AttributeValues to an Item
+pub fn attributes_to_item(attributes: &HashMap<String, AttributeValue>) -> Result<Item> {
+    attributes
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), attribute_to_value(value)?)))
+        .collect()
+}
+
+#[async_trait]
+impl SyncProtocol for DynamoDBProtocol {
+    async fn connect(&mut self) -> Result<()> {
+        let mut loader = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(self.region.clone()));
+        if let Some(endpoint) = &self.endpoint_url {
+            loader = loader.endpoint_url(endpoint);
+        }
+        if let Some(credentials) = &self.credentials {
+            loader = loader.credentials_provider(Credentials::new(
+                credentials.access_key_id.clone(),
+                credentials.secret_access_key.clone(),
+                credentials.session_token.clone(),
+                None,
+                "kstone-sync",
+            ));
+        }
+
+        self.client = Some(Client::new(&loader.load().await));
+        self.observed.clear();
+        self.rejected.clear();
+
+        // Also verifies that the table exists and is readable
+        self.describe_table().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.client = None;
+        self.remote_endpoint_id = None;
+        self.remote_clock = None;
+        self.observed.clear();
+        Ok(())
+    }
+
+    async fn send(&mut self, _message: SyncMessage) -> Result<()> {
+        // DynamoDB protocol doesn't use message passing
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<SyncMessage> {
+        // Return completion message
+        Ok(SyncMessage::Complete {
+            stats: SyncSessionStats::default(),
+        })
+    }
+
+    async fn handshake(&mut self, _local_id: &EndpointId, local_clock: &VectorClock) -> Result<VectorClock> {
+        // The table has no clock of its own; items carry the clock of their last sync write
+        self.remote_endpoint_id = Some(EndpointId::from_str(&format!(
+            "dynamodb:{}:{}",
+            self.region, self.table_name
+        )));
+
+        let mut clock = VectorClock::new();
+        clock.merge(local_clock);
+        self.remote_clock = Some(clock.clone());
+
+        Ok(clock)
+    }
+
+    async fn exchange_merkle(&mut self, _local_tree: &MerkleNode) -> Result<Vec<(Key, DiffType)>> {
+        // The local tree is rebuilt with items hashed in DynamoDB form
+        let local_db = self
+            .local_db
+            .clone()
+            .ok_or_else(|| anyhow!("Local database not set"))?;
+
+        let mut local_keys = HashMap::new();
+        let mut local_leaves = Vec::new();
+        for (key, item) in local_items(&local_db)? {
+            let encoded = key.encode();
+            local_leaves.push((encoded.clone(), Bytes::from(self.item_bytes(&item)?)));
+            local_keys.insert(encoded, key);
+        }
+
+        let mut remote_keys = HashMap::new();
+        let mut remote_leaves = Vec::new();
+        self.observed.clear();
+        for remote in self.scan_table().await? {
+            let encoded = remote.key.encode();
+            remote_leaves.push((encoded.clone(), Bytes::from(self.item_bytes(&remote.item)?)));
+            self.observed.insert(remote.key.clone(), remote.observed);
+            remote_keys.insert(encoded, remote.key);
+        }
+        for key in local_keys.values() {
+            self.observed.entry(key.clone()).or_insert(Observed::Absent);
+        }
+
+        // Both trees must order their leaves the same way
+        local_leaves.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        remote_leaves.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let local_tree = MerkleTree::build(local_leaves, BRANCHING_FACTOR)?;
+        let remote_tree = MerkleTree::build(remote_leaves, BRANCHING_FACTOR)?;
+        let merkle_diff = local_tree.diff(&remote_tree);
+
+        let mut diffs = Vec::new();
+        for (key_bytes, _hash) in merkle_diff.only_in_left {
+            if let Some(key) = local_keys.get(&key_bytes) {
+                diffs.push((key.clone(), DiffType::LocalOnly));
+            }
+        }
+        for (key_bytes, _hash) in merkle_diff.only_in_right {
+            if let Some(key) = remote_keys.get(&key_bytes) {
+                diffs.push((key.clone(), DiffType::RemoteOnly));
+            }
+        }
+        for (key_bytes, _left_hash, _right_hash) in merkle_diff.modified {
+            if let Some(key) = local_keys.get(&key_bytes) {
+                diffs.push((key.clone(), DiffType::Modified));
+            }
+        }
+
+        Ok(diffs)
+    }
+
+    async fn pull_items(&mut self, keys: Vec<Key>) -> Result<Vec<(Key, Option<Item>, VectorClock)>> {
+        let mut found: HashMap<Key, RemoteItem> = self
+            .batch_get(&keys)
+            .await?
+            .into_iter()
+            .map(|remote| (remote.key.clone(), remote))
+            .collect();
+
+        let mut items = Vec::with_capacity(keys.len());
+        for key in keys {
+            match found.remove(&key) {
+                Some(remote) => {
+                    self.observed.insert(key.clone(), remote.observed);
+                    items.push((key, Some(remote.item), remote.clock));
+                }
+                None => {
+                    self.observed.insert(key.clone(), Observed::Absent);
+                    let clock = self.remote_clock.clone().unwrap_or_else(VectorClock::new);
+                    items.push((key, None, clock));
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    async fn push_items(&mut self, items: Vec<(Key, Option<Item>, VectorClock)>) -> Result<Vec<String>> {
+        let client = self.client()?.clone();
+        let partition_key = self.partition_key()?.name.clone();
+
+        // Conditional writes can't be batched, so they are sent concurrently
+        let mut writes = JoinSet::new();
+        for (key, item_opt, clock) in items {
+            let observed = self.observed.get(&key).copied();
+            let condition = write_condition(&partition_key, observed);
+            let key_attributes = self.key_attributes(&key)?;
+
+            match item_opt {
+                Some(item) => {
+                    let version = match observed {
+                        Some(Observed::Version(version)) => version + 1,
+                        _ => 1,
+                    };
+                    let mut attributes = self.item_attributes(&item);
+                    attributes.extend(key_attributes);
+                    attributes.insert(VERSION_ATTRIBUTE.to_string(), AttributeValue::N(version.to_string()));
+                    attributes.insert(CLOCK_ATTRIBUTE.to_string(), AttributeValue::S(serde_json::to_string(&clock)?));
+
+                    let request = client
+                        .put_item()
+                        .table_name(&self.table_name)
+                        .set_item(Some(attributes))
+                        .set_condition_expression(condition.expression)
+                        .set_expression_attribute_names(condition.names)
+                        .set_expression_attribute_values(condition.values);
+
+                    writes.spawn(async move {
+                        let accepted = match request.send().await {
+                            Ok(_) => Ok(true),
+                            Err(e) if e.as_service_error().is_some_and(|e| e.is_conditional_check_failed_exception()) => Ok(false),
+                            Err(e) => Err(anyhow!("DynamoDB put failed: {}", e)),
+                        };
+                        (key, accepted, Observed::Version(version))
+                    });
+                }
+                None => {
+                    let request = client
+                        .delete_item()
+                        .table_name(&self.table_name)
+                        .set_key(Some(key_attributes))
+                        .set_condition_expression(condition.expression)
+                        .set_expression_attribute_names(condition.names)
+                        .set_expression_attribute_values(condition.values);
+
+                    writes.spawn(async move {
+                        let accepted = match request.send().await {
+                            Ok(_) => Ok(true),
+                            Err(e) if e.as_service_error().is_some_and(|e| e.is_conditional_check_failed_exception()) => Ok(false),
+                            Err(e) => Err(anyhow!("DynamoDB delete failed: {}", e)),
+                        };
+                        (key, accepted, Observed::Absent)
+                    });
+                }
+            }
+        }
+
+        let mut ids = Vec::new();
+        while let Some(write) = writes.join_next().await {
+            let (key, accepted, observed) = write?;
+            if accepted? {
+                self.observed.insert(key, observed);
+                ids.push(uuid::Uuid::new_v4().to_string());
+            } else {
+                tracing::warn!(
+                    "Item {} changed in DynamoDB table {} since it was read; not overwritten",
+                    String::from_utf8_lossy(&key.pk),
+                    self.table_name
+                );
+                self.observed.remove(&key);
+                self.rejected.push(key);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+    use tempfile::TempDir;
+
+    fn protocol(sort_key: bool) -> DynamoDBProtocol {
+        let mut protocol = DynamoDBProtocol::new("us-east-1".to_string(), "users".to_string(), None);
+        protocol.partition_key = Some(KeyAttribute {
+            name: "id".to_string(),
+            kind: ScalarAttributeType::S,
+        });
+        if sort_key {
+            protocol.sort_key = Some(KeyAttribute {
+                name: "version".to_string(),
+                kind: ScalarAttributeType::N,
+            });
+        }
+        protocol
+    }
+
+    #[test]
+    fn test_value_conversion_round_trip() {
+        let item = ItemBuilder::new()
+            .string("name", "Alice")
+            .number("age", 30)
+            .bool("active", true)
+            .build();
+
+        let attributes: HashMap<_, _> = item
+            .iter()
+            .map(|(name, value)| (name.clone(), value_to_attribute(value)))
+            .collect();
+        assert_eq!(attributes["age"], AttributeValue::N("30".to_string()));
+        assert_eq!(attributes_to_item(&attributes).unwrap(), item);
+
+        // Types DynamoDB lacks come back as their DynamoDB equivalents
+        assert_eq!(
+            attribute_to_value(&value_to_attribute(&Value::Ts(5))).unwrap(),
+            Value::N("5".to_string())
+        );
+        assert_eq!(
+            attribute_to_value(&AttributeValue::Ss(vec!["a".to_string()])).unwrap(),
+            Value::L(vec![Value::S("a".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_split_item() {
+        let table = protocol(true);
+        let key = Key::with_sk(b"user#1".to_vec(), b"3".to_vec());
+
+        let mut attributes = table.key_attributes(&key).unwrap();
+        assert_eq!(attributes["version"], AttributeValue::N("3".to_string()));
+        attributes.insert("name".to_string(), AttributeValue::S("Alice".to_string()));
+        attributes.insert(VERSION_ATTRIBUTE.to_string(), AttributeValue::N("7".to_string()));
+
+        let remote = table.split_item(attributes).unwrap();
+        assert_eq!(remote.key, key);
+        assert_eq!(remote.observed, Observed::Version(7));
+        assert_eq!(remote.item.len(), 1);
+        assert_eq!(remote.item["name"], Value::S("Alice".to_string()));
+
+        // Keys must match the table's key schema
+        assert!(table.key_attributes(&Key::new(b"user#1".to_vec())).is_err());
+        assert!(protocol(false).key_attributes(&key).is_err());
+    }
+
+    #[test]
+    fn test_write_conditions() {
+        assert!(write_condition("id", None).expression.is_none());
+
+        let absent = write_condition("id", Some(Observed::Absent));
+        assert_eq!(absent.expression.as_deref(), Some("attribute_not_exists(#pk)"));
+        assert_eq!(absent.names.unwrap()["#pk"], "id");
+
+        let versioned = write_condition("id", Some(Observed::Version(4)));
+        assert_eq!(versioned.expression.as_deref(), Some("#version = :version"));
+        assert_eq!(versioned.values.unwrap()[":version"], AttributeValue::N("4".to_string()));
+    }
+
+    #[test]
+    fn test_local_items_skip_sync_metadata() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::create(dir.path()).unwrap();
+        db.put(b"user#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        db.put(b"_sync#metadata#local", ItemBuilder::new().string("x", "y").build()).unwrap();
+
+        let items = local_items(&db).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0, Key::new(b"user#1".to_vec()));
+    }
+}
@@ -250,8 +250,8 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let db_path = dir.path().join("test.keystone");
 
-        // Create a database
-        let _db = Database::create(&db_path).unwrap();
+        // Create a database; the protocol opens it itself
+        drop(Database::create(&db_path).unwrap());
 
         // Connect protocol
         let mut protocol = FilesystemProtocol::new(db_path.to_string_lossy().to_string());
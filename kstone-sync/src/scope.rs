@@ -0,0 +1,165 @@
+/// Sync scopes: which items a sync covers
+///
+/// By default every item is synced. A scope narrows that to partition keys
+/// with given prefixes, minus excluded prefixes, and optionally to items
+/// matching a filter expression, so a device can sync only its own tenant's
+/// data. The sync engine applies the scope to both pushed and pulled items.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use kstone_core::{
+    expression::{Expr, ExpressionContext, ExpressionEvaluator, ExpressionParser},
+    Item, Key, Value,
+};
+
+/// Which items a sync covers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncScope {
+    /// Partition key prefixes to sync; empty means every partition key
+    pub include_prefixes: Vec<Bytes>,
+    /// Partition key prefixes never to sync, even if included
+    pub exclude_prefixes: Vec<Bytes>,
+    /// Filter expression items must match (e.g. `tenant = :tenant`)
+    pub filter: Option<String>,
+    /// Expression attribute values for the filter
+    pub values: HashMap<String, Value>,
+    /// Expression attribute names for the filter
+    pub names: HashMap<String, String>,
+}
+
+impl SyncScope {
+    /// A scope covering every item
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Sync partition keys starting with `prefix` (may be given repeatedly)
+    pub fn with_include_prefix(mut self, prefix: impl AsRef<[u8]>) -> Self {
+        self.include_prefixes.push(Bytes::copy_from_slice(prefix.as_ref()));
+        self
+    }
+
+    /// Don't sync partition keys starting with `prefix`
+    pub fn with_exclude_prefix(mut self, prefix: impl AsRef<[u8]>) -> Self {
+        self.exclude_prefixes.push(Bytes::copy_from_slice(prefix.as_ref()));
+        self
+    }
+
+    /// Only sync items matching a filter expression
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Add an expression attribute value for the filter
+    pub fn with_value(mut self, placeholder: impl Into<String>, value: Value) -> Self {
+        self.values.insert(placeholder.into(), value);
+        self
+    }
+
+    /// Add an expression attribute name for the filter
+    pub fn with_name(mut self, placeholder: impl Into<String>, name: impl Into<String>) -> Self {
+        self.names.insert(placeholder.into(), name.into());
+        self
+    }
+
+    /// Whether the scope covers every item
+    pub fn is_all(&self) -> bool {
+        self.include_prefixes.is_empty() && self.exclude_prefixes.is_empty() && self.filter.is_none()
+    }
+
+    /// Parse the filter once, for checking many items
+    pub fn matcher(&self) -> Result<ScopeMatcher> {
+        let filter = self
+            .filter
+            .as_deref()
+            .map(ExpressionParser::parse)
+            .transpose()
+            .context("Invalid sync scope filter")?;
+
+        Ok(ScopeMatcher {
+            include_prefixes: self.include_prefixes.clone(),
+            exclude_prefixes: self.exclude_prefixes.clone(),
+            filter,
+            context: ExpressionContext {
+                values: self.values.clone(),
+                names: self.names.clone(),
+            },
+        })
+    }
+}
+
+/// A `SyncScope` ready to check keys and items
+#[derive(Debug, Clone)]
+pub struct ScopeMatcher {
+    include_prefixes: Vec<Bytes>,
+    exclude_prefixes: Vec<Bytes>,
+    filter: Option<Expr>,
+    context: ExpressionContext,
+}
+
+impl ScopeMatcher {
+    /// Whether the key's partition key is in scope
+    pub fn matches_key(&self, key: &Key) -> bool {
+        let included = self.include_prefixes.is_empty()
+            || self.include_prefixes.iter().any(|prefix| key.pk.starts_with(prefix));
+        included && !self.exclude_prefixes.iter().any(|prefix| key.pk.starts_with(prefix))
+    }
+
+    /// Whether an item, or the deletion of one (None), is in scope
+    ///
+    /// Deletions have no item to filter, so only their key is checked.
+    pub fn matches(&self, key: &Key, item: Option<&Item>) -> bool {
+        if !self.matches_key(key) {
+            return false;
+        }
+        match (&self.filter, item) {
+            (Some(filter), Some(item)) => ExpressionEvaluator::new(item, &self.context)
+                .evaluate(filter)
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kstone_api::ItemBuilder;
+
+    #[test]
+    fn test_prefix_scope() {
+        let matcher = SyncScope::all()
+            .with_include_prefix("tenant#a#")
+            .with_exclude_prefix("tenant#a#private#")
+            .matcher()
+            .unwrap();
+
+        assert!(matcher.matches_key(&Key::new(b"tenant#a#user#1".to_vec())));
+        assert!(!matcher.matches_key(&Key::new(b"tenant#b#user#1".to_vec())));
+        assert!(!matcher.matches_key(&Key::new(b"tenant#a#private#1".to_vec())));
+        assert!(SyncScope::all().matcher().unwrap().matches_key(&Key::new(b"anything".to_vec())));
+    }
+
+    #[test]
+    fn test_filter_scope() {
+        let scope = SyncScope::all()
+            .with_filter("#t = :tenant")
+            .with_name("#t", "tenant")
+            .with_value(":tenant", Value::string("a"));
+        assert!(!scope.is_all());
+        let matcher = scope.matcher().unwrap();
+
+        let key = Key::new(b"user#1".to_vec());
+        let ours = ItemBuilder::new().string("tenant", "a").build();
+        let theirs = ItemBuilder::new().string("tenant", "b").build();
+        assert!(matcher.matches(&key, Some(&ours)));
+        assert!(!matcher.matches(&key, Some(&theirs)));
+        assert!(matcher.matches(&key, None));
+
+        assert!(SyncScope::all().with_filter("tenant = ").matcher().is_err());
+    }
+}
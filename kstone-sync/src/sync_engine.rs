@@ -11,22 +11,36 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time;
 
 use kstone_api::Database;
-use kstone_core::{Item, Key, stream::StreamRecord};
+use kstone_core::{item_size, Item, Key, stream::{StreamEventType, StreamRecord}};
 
 use crate::{
     EndpointId, VectorClock, SyncOrigin, SyncStats,
     change_tracker::{ChangeTracker, SyncRecord},
-    conflict::{ConflictManager, ConflictStrategy, Conflict},
-    merkle::MerkleTree,
-    metadata::{SyncMetadata, SyncMetadataStore, SyncCheckpoint},
+    conflict::{ConflictManager, ConflictResolution, ConflictStrategy, Conflict},
+    inbox::{apply_resolution, ConflictChoice, ConflictInbox},
+    notify::{self, Notifications, SyncNotification, SyncNotifier},
+    merkle::MerkleNode,
+    merkle_store::MerkleStore,
+    metadata::{
+        SyncCheckpoint, SyncDirection, SyncMetadata, SyncMetadataStore, SyncRunRecord,
+        TransferCheckpoint,
+    },
     offline_queue::{OfflineQueue, PendingOperation, RetryPolicy},
     protocol::{SyncProtocol, SyncEndpoint, SyncMessage, SyncSessionStats, DiffType},
+    scope::{ScopeMatcher, SyncScope},
+    throttle::BandwidthLimiter,
 };
 
+/// Events buffered per subscriber; slower subscribers miss the oldest events
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Interrupted transfers older than this are discovered afresh (24 hours)
+const TRANSFER_CHECKPOINT_MAX_AGE_MS: i64 = 24 * 60 * 60 * 1000;
+
 /// Sync engine state
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SyncState {
@@ -68,11 +82,20 @@ pub enum SyncEvent {
         old_state: SyncState,
         new_state: SyncState,
     },
-    /// Progress update
+    /// Progress update, after each batch of a transfer
     Progress {
+        /// Changes transferred, including those of an interrupted run resumed
+        completed: usize,
+        /// Changes in the transfer
+        total: usize,
+        /// Items pushed
         sent: usize,
+        /// Items pulled
         received: usize,
-        total: usize,
+        /// Bytes pushed
+        bytes_sent: usize,
+        /// Bytes pulled
+        bytes_received: usize,
     },
     /// Conflict detected
     ConflictDetected {
@@ -83,6 +106,11 @@ pub enum SyncEvent {
     ConflictResolved {
         conflict_id: String,
     },
+    /// Conflict left in the conflict inbox for manual resolution
+    ConflictDeferred {
+        key: Key,
+        conflict_id: String,
+    },
     /// Sync completed
     Completed {
         stats: SyncSessionStats,
@@ -108,6 +136,12 @@ pub struct SyncConfig {
     pub max_retries: u32,
     /// Enable compression
     pub enable_compression: bool,
+    /// Items to sync; every item by default
+    #[serde(default)]
+    pub scope: SyncScope,
+    /// Maximum average transfer rate in bytes per second; unlimited if None
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
 }
 
 /// Main sync engine
@@ -126,12 +160,18 @@ pub struct SyncEngine {
     offline_queue: Arc<OfflineQueue>,
     /// Metadata store
     metadata_store: Arc<SyncMetadataStore>,
+    /// Conflicts awaiting manual resolution
+    inbox: Arc<ConflictInbox>,
+    /// Persisted merkle tree of the local items
+    merkle: Arc<MerkleStore>,
     /// Sync metadata
     metadata: Arc<RwLock<SyncMetadata>>,
-    /// Event channel sender
-    event_tx: mpsc::UnboundedSender<SyncEvent>,
-    /// Event channel receiver
-    event_rx: Option<mpsc::UnboundedReceiver<SyncEvent>>,
+    /// Checks items against the configured scope
+    scope: ScopeMatcher,
+    /// Event channel sender, shared with background sync tasks
+    event_tx: broadcast::Sender<SyncEvent>,
+    /// Webhooks and callbacks to notify
+    notifications: Arc<Notifications>,
     /// Shutdown signal
     shutdown_tx: Option<mpsc::Sender<()>>,
 }
@@ -139,7 +179,8 @@ pub struct SyncEngine {
 impl SyncEngine {
     /// Create a new sync engine
     pub fn new(db: Arc<Database>, config: SyncConfig) -> Result<Self> {
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let scope = config.scope.matcher()?;
 
         let local_endpoint = EndpointId::new();
 
@@ -148,18 +189,30 @@ impl SyncEngine {
         let offline_queue = Arc::new(OfflineQueue::new(RetryPolicy::default(), 1000));
 
         let metadata_store = Arc::new(SyncMetadataStore::new(db.clone()));
+        let inbox = Arc::new(ConflictInbox::new(db.clone()));
 
         // Initialize metadata if not exists
         metadata_store.initialize()?;
 
-        let metadata = match metadata_store.load_metadata()? {
-            Some(existing) => Arc::new(RwLock::new(existing)),
-            None => {
-                let new_metadata = SyncMetadata::new(local_endpoint.clone());
-                metadata_store.save_metadata(&new_metadata)?;
-                Arc::new(RwLock::new(new_metadata))
+        // Hash the database once; writes keep the tree up to date after that
+        let merkle = Arc::new(MerkleStore::new(db.clone()));
+        if !merkle.is_built()? {
+            merkle.rebuild()?;
+        }
+
+        let mut metadata = metadata_store
+            .load_metadata()?
+            .unwrap_or_else(|| SyncMetadata::new(local_endpoint.clone()));
+
+        // Record the scope, so it's visible what this database holds
+        if metadata.config.scope != config.scope {
+            if !metadata.config.scope.is_all() {
+                tracing::warn!("Sync scope changed; items outside the new scope stay in the database");
             }
-        };
+            metadata.config.scope = config.scope.clone();
+        }
+        metadata_store.save_metadata(&metadata)?;
+        let metadata = Arc::new(RwLock::new(metadata));
 
         Ok(Self {
             db,
@@ -169,9 +222,12 @@ impl SyncEngine {
             conflict_manager,
             offline_queue,
             metadata_store,
+            inbox,
+            merkle,
             metadata,
+            scope,
             event_tx,
-            event_rx: Some(event_rx),
+            notifications: Arc::new(Notifications::new()),
             shutdown_tx: None,
         })
     }
@@ -225,15 +281,77 @@ impl SyncEngine {
     ) -> Result<String> {
         use crate::protocol::s3::S3Protocol;
 
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let endpoint_id = s3_endpoint_id(&bucket, &prefix, &region);
         let mut protocol = S3Protocol::new(bucket, prefix, region, None, None)
             .with_local_db_path(db_path)
             .with_local_db(self.db.clone());
 
+        let result = async {
+            protocol.connect().await?;
+            let snapshot_id = protocol.upload_snapshot().await?;
+            protocol.disconnect().await?;
+            Ok::<_, anyhow::Error>(snapshot_id)
+        }
+        .await;
+
+        self.finish_run(endpoint_id, SyncDirection::Push, started_at, &SyncSessionStats::default(), &result).await;
+        result
+    }
+
+    /// Upload an incremental snapshot to S3 (Phase 8+)
+    ///
+    /// Only files no earlier snapshot uploaded are sent; the files come from
+    /// a point-in-time backup of the database.
+    #[cfg(feature = "s3-sync")]
+    pub async fn upload_incremental_to_s3(
+        &self,
+        bucket: String,
+        prefix: String,
+        region: String,
+    ) -> Result<crate::protocol::s3::SnapshotMetadata> {
+        use crate::protocol::s3::S3Protocol;
+
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let endpoint_id = s3_endpoint_id(&bucket, &prefix, &region);
+        let mut protocol = S3Protocol::new(bucket, prefix, region, None, None)
+            .with_local_db(self.db.clone());
+
+        let result = async {
+            protocol.connect().await?;
+            let snapshot = protocol.upload_incremental_snapshot().await?;
+            protocol.disconnect().await?;
+            Ok::<_, anyhow::Error>(snapshot)
+        }
+        .await;
+
+        let stats = SyncSessionStats {
+            bytes_sent: result.as_ref().map_or(0, |snapshot| snapshot.uploaded_size as usize),
+            ..Default::default()
+        };
+        self.finish_run(endpoint_id, SyncDirection::Push, started_at, &stats, &result).await;
+        result
+    }
+
+    /// Delete all but the newest `keep` S3 snapshots and the objects only
+    /// they used (Phase 8+)
+    #[cfg(feature = "s3-sync")]
+    pub async fn prune_s3_snapshots(
+        &self,
+        bucket: String,
+        prefix: String,
+        region: String,
+        keep: usize,
+    ) -> Result<crate::protocol::s3::SnapshotGcStats> {
+        use crate::protocol::s3::S3Protocol;
+
+        let mut protocol = S3Protocol::new(bucket, prefix, region, None, None);
+
         protocol.connect().await?;
-        let snapshot_id = protocol.upload_snapshot().await?;
+        let stats = protocol.collect_garbage(keep).await?;
         protocol.disconnect().await?;
 
-        Ok(snapshot_id)
+        Ok(stats)
     }
 
     /// Download a snapshot from S3
@@ -248,21 +366,38 @@ impl SyncEngine {
     ) -> Result<()> {
         use crate::protocol::s3::S3Protocol;
 
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let endpoint_id = s3_endpoint_id(&bucket, &prefix, &region);
         let mut protocol = S3Protocol::new(bucket, prefix, region, None, None)
             .with_local_db_path(db_path)
             .with_local_db(self.db.clone());
 
-        protocol.connect().await?;
-        protocol.download_snapshot(&snapshot_id).await?;
-        protocol.disconnect().await?;
+        let result = async {
+            protocol.connect().await?;
+            protocol.download_snapshot(&snapshot_id).await?;
+            protocol.disconnect().await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
 
-        Ok(())
+        self.finish_run(endpoint_id, SyncDirection::Pull, started_at, &SyncSessionStats::default(), &result).await;
+        result
     }
 
     /// Perform a single sync operation with a specific endpoint
     pub async fn sync(&self, endpoint: SyncEndpoint) -> Result<SyncSessionStats> {
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let mut stats = SyncSessionStats::default();
+        let result = self.sync_with(&endpoint, &mut stats).await;
+        self.finish_run(endpoint.endpoint_id(), SyncDirection::Bidirectional, started_at, &stats, &result).await;
+        self.report_failure(result).map(|()| stats)
+    }
+
+    async fn sync_with(&self, endpoint: &SyncEndpoint, stats: &mut SyncSessionStats) -> Result<()> {
+        let started = std::time::Instant::now();
+
         // Temporarily use the provided endpoint for this sync
-        let mut protocol = self.create_protocol_for_endpoint(&endpoint).await?;
+        let mut protocol = self.create_protocol_for_endpoint(endpoint).await?;
 
         self.set_state(SyncState::Connecting);
         self.emit_event(SyncEvent::Started {
@@ -283,40 +418,42 @@ impl SyncEngine {
         // Update vector clock
         self.change_tracker.update_vector_clock(&remote_clock);
 
-        // Discovery phase
+        // Discovery phase, unless an interrupted transfer can be resumed
         self.set_state(SyncState::Discovering);
-        let changes = self.discover_changes(protocol.as_mut()).await?;
+        let endpoint_id = endpoint.endpoint_id();
+        let mut transfer = self.changes_to_transfer(&endpoint_id, protocol.as_mut()).await?;
 
-        let total_changes = changes.len();
+        let total_changes = transfer.total;
 
         // Debug: Log discovered changes
         eprintln!("DEBUG: Discovered {} changes", total_changes);
-        for (key, diff_type) in &changes {
+        for (key, diff_type) in &transfer.changes {
             eprintln!("  Key: {:?}, Type: {:?}", String::from_utf8_lossy(&key.pk), diff_type);
         }
 
-        if total_changes == 0 {
+        if transfer.remaining() == 0 {
+            self.metadata_store.delete_transfer_checkpoint(&endpoint_id)?;
             self.set_state(SyncState::Completed);
-            let stats = SyncSessionStats::default();
             self.emit_event(SyncEvent::Completed {
                 stats: stats.clone(),
             });
-            return Ok(stats);
+            return Ok(());
         }
 
         // Transfer phase
         self.set_state(SyncState::Transferring {
-            sent: 0,
-            received: 0,
+            sent: transfer.items_sent,
+            received: transfer.items_received,
             total: total_changes,
         });
 
-        let stats = self.transfer_changes(protocol.as_mut(), changes).await?;
+        self.transfer_changes(protocol.as_mut(), &mut transfer, stats).await?;
+        self.metadata_store.delete_transfer_checkpoint(&endpoint_id)?;
 
         // Conflict resolution
         if self.conflict_manager.get_stats().pending_count > 0 {
             self.set_state(SyncState::ResolvingConflicts);
-            self.resolve_conflicts().await?;
+            self.resolve_conflicts(protocol.as_mut(), stats).await?;
         }
 
         // Commit phase
@@ -337,16 +474,28 @@ impl SyncEngine {
         self.metadata_store.save_metadata(&self.metadata.read())?;
         self.db.flush()?;
 
+        stats.duration_ms = started.elapsed().as_millis() as u64;
         self.set_state(SyncState::Completed);
         self.emit_event(SyncEvent::Completed {
             stats: stats.clone(),
         });
 
-        Ok(stats)
+        Ok(())
     }
 
     /// Perform a single sync operation
     pub async fn sync_once(&self) -> Result<()> {
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let mut stats = SyncSessionStats::default();
+        let result = self.sync_configured(&mut stats).await;
+        let endpoint_id = self.config.endpoint.endpoint_id();
+        self.finish_run(endpoint_id, SyncDirection::Bidirectional, started_at, &stats, &result).await;
+        self.report_failure(result)
+    }
+
+    async fn sync_configured(&self, stats: &mut SyncSessionStats) -> Result<()> {
+        let started = std::time::Instant::now();
+
         self.set_state(SyncState::Connecting);
         self.emit_event(SyncEvent::Started {
             endpoint_id: self.config.endpoint.endpoint_id(),
@@ -369,39 +518,42 @@ impl SyncEngine {
         // Update vector clock
         self.change_tracker.update_vector_clock(&remote_clock);
 
-        // Discovery phase
+        // Discovery phase, unless an interrupted transfer can be resumed
         self.set_state(SyncState::Discovering);
-        let changes = self.discover_changes(protocol.as_mut()).await?;
+        let endpoint_id = self.config.endpoint.endpoint_id();
+        let mut transfer = self.changes_to_transfer(&endpoint_id, protocol.as_mut()).await?;
 
-        let total_changes = changes.len();
+        let total_changes = transfer.total;
 
         // Debug: Log discovered changes
         eprintln!("DEBUG: Discovered {} changes", total_changes);
-        for (key, diff_type) in &changes {
+        for (key, diff_type) in &transfer.changes {
             eprintln!("  Key: {:?}, Type: {:?}", String::from_utf8_lossy(&key.pk), diff_type);
         }
 
-        if total_changes == 0 {
+        if transfer.remaining() == 0 {
+            self.metadata_store.delete_transfer_checkpoint(&endpoint_id)?;
             self.set_state(SyncState::Completed);
             self.emit_event(SyncEvent::Completed {
-                stats: SyncSessionStats::default(),
+                stats: stats.clone(),
             });
             return Ok(());
         }
 
         // Transfer phase
         self.set_state(SyncState::Transferring {
-            sent: 0,
-            received: 0,
+            sent: transfer.items_sent,
+            received: transfer.items_received,
             total: total_changes,
         });
 
-        let stats = self.transfer_changes(protocol.as_mut(), changes).await?;
+        self.transfer_changes(protocol.as_mut(), &mut transfer, stats).await?;
+        self.metadata_store.delete_transfer_checkpoint(&endpoint_id)?;
 
         // Conflict resolution
         if self.conflict_manager.get_stats().pending_count > 0 {
             self.set_state(SyncState::ResolvingConflicts);
-            self.resolve_conflicts().await?;
+            self.resolve_conflicts(protocol.as_mut(), stats).await?;
         }
 
         // Commit phase
@@ -409,8 +561,11 @@ impl SyncEngine {
         self.commit_changes().await?;
 
         // Complete
+        stats.duration_ms = started.elapsed().as_millis() as u64;
         self.set_state(SyncState::Completed);
-        self.emit_event(SyncEvent::Completed { stats });
+        self.emit_event(SyncEvent::Completed {
+            stats: stats.clone(),
+        });
 
         // Update metadata
         {
@@ -427,48 +582,80 @@ impl SyncEngine {
         Ok(())
     }
 
-    /// Discover changes using Merkle tree diff
-    async fn discover_changes(
+    /// Changes left by an interrupted transfer with the endpoint, or else
+    /// newly discovered changes, checkpointed so the transfer can be resumed
+    async fn changes_to_transfer(
         &self,
+        endpoint_id: &EndpointId,
         protocol: &mut dyn SyncProtocol,
-    ) -> Result<Vec<(Key, DiffType)>> {
-        // Build local Merkle tree by scanning the database with keys
-        let mut local_items = Vec::new();
+    ) -> Result<TransferCheckpoint> {
+        if let Some(checkpoint) = self.metadata_store.load_transfer_checkpoint(endpoint_id)? {
+            let age = chrono::Utc::now().timestamp_millis() - checkpoint.updated_at;
+            if age <= TRANSFER_CHECKPOINT_MAX_AGE_MS {
+                tracing::info!(
+                    "Resuming sync with {} after {} of {} changes",
+                    endpoint_id.0, checkpoint.completed, checkpoint.total
+                );
+                return Ok(checkpoint);
+            }
+            self.metadata_store.delete_transfer_checkpoint(endpoint_id)?;
+        }
 
-        // Scan the database to get items with their keys
-        let records = self.db.scan_with_keys(10000)?;
+        let changes: Vec<_> = self
+            .discover_changes(protocol)
+            .await?
+            .into_iter()
+            .filter(|(key, _)| self.scope.matches_key(key))
+            .collect();
 
-        for (key, item) in records {
-            let key_bytes = key.encode();
-            let value_bytes = serde_json::to_vec(&item)?;
-            local_items.push((key_bytes, Bytes::from(value_bytes)));
+        let checkpoint = TransferCheckpoint::new(endpoint_id.clone(), changes);
+        if checkpoint.total > 0 {
+            self.metadata_store.start_transfer(&checkpoint)?;
         }
+        Ok(checkpoint)
+    }
 
-        let local_tree = MerkleTree::build(local_items, 16)?;
+    /// Discover changes using Merkle tree diff
+    async fn discover_changes(
+        &self,
+        protocol: &mut dyn SyncProtocol,
+    ) -> Result<Vec<(Key, DiffType)>> {
+        // The persisted tree, instead of hashing every item again
+        let local_tree = self.merkle.tree()?;
+
+        // An empty database still has remote items to pull
+        let empty = MerkleNode {
+            hash: Bytes::new(),
+            level: 0,
+            key_range: None,
+            children: Vec::new(),
+        };
+        let root = local_tree.root.as_ref().unwrap_or(&empty);
+        protocol.exchange_merkle(root).await
 
-        if let Some(root) = local_tree.root.as_ref() {
-            let diffs = protocol.exchange_merkle(root).await?;
-            Ok(diffs)
-        } else {
-            Ok(Vec::new())
-        }
     }
 
-    /// Transfer changes between endpoints
+    /// Transfer the remaining changes between endpoints, a batch at a time
+    ///
+    /// Progress is checkpointed after every batch, and batches are paced to
+    /// the configured bandwidth limit.
     async fn transfer_changes(
         &self,
         protocol: &mut dyn SyncProtocol,
-        changes: Vec<(Key, DiffType)>,
-    ) -> Result<SyncSessionStats> {
-        let mut stats = SyncSessionStats::default();
-        let mut sent = 0;
-        let mut received = 0;
+        transfer: &mut TransferCheckpoint,
+        stats: &mut SyncSessionStats,
+    ) -> Result<()> {
+        let mut limiter = self.config.max_bytes_per_sec.map(BandwidthLimiter::new);
+        let batch_size = self.config.batch_size.max(1);
 
-        for chunk in changes.chunks(self.config.batch_size) {
+        while transfer.remaining() > 0 {
+            let end = (transfer.completed + batch_size).min(transfer.total);
+            let chunk = transfer.changes[transfer.completed..end].to_vec();
             let mut to_pull = Vec::new();
             let mut to_push = Vec::new();
+            let mut bytes = 0;
 
-            for (key, diff_type) in chunk {
+            for (key, diff_type) in &chunk {
                 match diff_type {
                     DiffType::LocalOnly => {
                         // We have it, they don't - push
@@ -478,7 +665,7 @@ impl SyncEngine {
                             self.db.get(&key.pk)?
                         };
 
-                        if let Some(item) = item {
+                        if let Some(item) = item.filter(|item| self.scope.matches(key, Some(item))) {
                             to_push.push((
                                 key.clone(),
                                 Some(item),
@@ -500,7 +687,7 @@ impl SyncEngine {
                             self.db.get(&key.pk)?
                         };
 
-                        if let Some(item) = item {
+                        if let Some(item) = item.filter(|item| self.scope.matches(key, Some(item))) {
                             to_push.push((
                                 key.clone(),
                                 Some(item),
@@ -514,46 +701,78 @@ impl SyncEngine {
             // Pull items from remote
             if !to_pull.is_empty() {
                 let pulled = protocol.pull_items(to_pull).await?;
-                received += pulled.len();
+                let pulled_bytes: usize = pulled
+                    .iter()
+                    .map(|(key, item, _)| change_size(key, item.as_ref()))
+                    .sum();
+                bytes += pulled_bytes;
                 stats.items_received += pulled.len();
+                stats.bytes_received += pulled_bytes;
+                transfer.items_received += pulled.len();
+                transfer.bytes_received += pulled_bytes;
 
-                // Process pulled items
+                // Process pulled items, leaving out those outside the scope.
+                // Conflicting items aren't pushed: the resolution decides
                 for (key, item, remote_clock) in pulled {
-                    self.process_remote_item(key, item, remote_clock).await?;
+                    if self.scope.matches(&key, item.as_ref())
+                        && self.process_remote_item(key.clone(), item, remote_clock).await?
+                    {
+                        stats.conflicts_detected += 1;
+                        to_push.retain(|(pushed, _, _)| *pushed != key);
+                    }
                 }
             }
 
             // Push items to remote
             if !to_push.is_empty() {
-                let pushed = protocol.push_items(to_push.clone()).await?;
-                sent += pushed.len();
+                let pushed_bytes: usize = to_push
+                    .iter()
+                    .map(|(key, item, _)| change_size(key, item.as_ref()))
+                    .sum();
+                let pushed = protocol.push_items(to_push).await?;
+                bytes += pushed_bytes;
                 stats.items_sent += pushed.len();
+                stats.bytes_sent += pushed_bytes;
+                transfer.items_sent += pushed.len();
+                transfer.bytes_sent += pushed_bytes;
             }
 
+            // Checkpoint, so an interruption resumes after this batch
+            transfer.completed = end;
+            transfer.updated_at = chrono::Utc::now().timestamp_millis();
+            self.metadata_store.save_transfer_progress(transfer)?;
+
             // Update progress
             self.set_state(SyncState::Transferring {
-                sent,
-                received,
-                total: changes.len(),
+                sent: transfer.items_sent,
+                received: transfer.items_received,
+                total: transfer.total,
             });
 
             self.emit_event(SyncEvent::Progress {
-                sent,
-                received,
-                total: changes.len(),
+                completed: transfer.completed,
+                total: transfer.total,
+                sent: transfer.items_sent,
+                received: transfer.items_received,
+                bytes_sent: transfer.bytes_sent,
+                bytes_received: transfer.bytes_received,
             });
+
+            if let Some(limiter) = limiter.as_mut() {
+                limiter.consume(bytes as u64).await;
+            }
         }
 
-        Ok(stats)
+        Ok(())
     }
 
-    /// Process an item received from remote
+    /// Process an item received from remote, returning whether it conflicted
     async fn process_remote_item(
         &self,
         key: Key,
         remote_item: Option<Item>,
         remote_clock: VectorClock,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         // Get the local item using the correct method
         let local_item = if let Some(ref sk) = key.sk {
             self.db.get_with_sk(&key.pk, sk)?
@@ -578,18 +797,20 @@ impl SyncEngine {
 
             let conflict_id = self.conflict_manager.add_conflict(conflict)?;
 
+            self.notifications.send(SyncNotification::conflict_detected(&conflict_id, &key));
             self.emit_event(SyncEvent::ConflictDetected {
                 key,
                 conflict_id,
             });
+            Ok(true)
         } else {
             // No conflict, apply remote change
-            match remote_item {
+            match &remote_item {
                 Some(item) => {
                     if let Some(ref sk) = key.sk {
-                        self.db.put_with_sk(&key.pk, sk, item)?
+                        self.db.put_with_sk(&key.pk, sk, item.clone())?
                     } else {
-                        self.db.put(&key.pk, item)?
+                        self.db.put(&key.pk, item.clone())?
                     }
                 }
                 None => {
@@ -600,29 +821,75 @@ impl SyncEngine {
                     }
                 }
             }
+            self.merkle.update(&key, remote_item.as_ref())?;
+            Ok(false)
         }
-
-        Ok(())
     }
 
     /// Resolve pending conflicts
-    async fn resolve_conflicts(&self) -> Result<()> {
-        let results = self.conflict_manager.resolve_all();
-
-        for (conflict_id, result) in results {
-            if let Ok(resolution) = result {
-                self.emit_event(SyncEvent::ConflictResolved {
-                    conflict_id: conflict_id.clone(),
+    ///
+    /// Resolutions are written locally and pushed to the remote; conflicts
+    /// the strategy defers go to the conflict inbox.
+    async fn resolve_conflicts(
+        &self,
+        protocol: &mut dyn SyncProtocol,
+        stats: &mut SyncSessionStats,
+    ) -> Result<()> {
+        let mut to_push = Vec::new();
+
+        for conflict in self.conflict_manager.get_pending() {
+            let resolution = match self.conflict_manager.resolve_conflict(&conflict.id) {
+                Ok(resolution) => resolution,
+                Err(e) => {
+                    tracing::warn!("Failed to resolve conflict {}: {}", conflict.id, e);
+                    ConflictResolution::Deferred
+                }
+            };
+
+            if resolution == ConflictResolution::Deferred {
+                let conflict = self.conflict_manager.take_pending(&conflict.id).unwrap_or(conflict);
+                self.inbox.add(&conflict)?;
+                self.emit_event(SyncEvent::ConflictDeferred {
+                    key: conflict.key,
+                    conflict_id: conflict.id,
                 });
-
-                // Apply resolution
-                // This would write to the database based on the resolution
+                continue;
             }
+
+            apply_resolution(&self.db, &conflict.key, &resolution)?;
+            to_push.push((
+                conflict.key.clone(),
+                resolution.get_item().cloned(),
+                self.change_tracker.get_vector_clock(),
+            ));
+            stats.conflicts_resolved += 1;
+            self.emit_event(SyncEvent::ConflictResolved {
+                conflict_id: conflict.id,
+            });
         }
 
+        if !to_push.is_empty() {
+            protocol.push_items(to_push).await?;
+        }
         Ok(())
     }
 
+    /// Conflicts waiting in the conflict inbox, oldest first
+    pub fn list_conflicts(&self) -> Result<Vec<Conflict>> {
+        self.inbox.list()
+    }
+
+    /// Resolve a conflict from the inbox by writing the chosen version
+    ///
+    /// The version is written locally; the next sync carries it to the remote.
+    pub fn resolve_conflict(&self, conflict_id: &str, choice: ConflictChoice) -> Result<ConflictResolution> {
+        let resolution = self.inbox.resolve(conflict_id, choice)?;
+        self.emit_event(SyncEvent::ConflictResolved {
+            conflict_id: conflict_id.to_string(),
+        });
+        Ok(resolution)
+    }
+
     /// Commit all changes
     async fn commit_changes(&self) -> Result<()> {
         // Flush database
@@ -645,16 +912,19 @@ impl SyncEngine {
     /// Create protocol handler for the configured endpoint
     async fn create_protocol_for_endpoint(&self, endpoint: &SyncEndpoint) -> Result<Box<dyn SyncProtocol>> {
         match endpoint {
+            #[cfg(feature = "dynamodb")]
+            SyncEndpoint::DynamoDB { region, table_name, credentials } => {
+                let protocol = crate::protocol::dynamodb::DynamoDBProtocol::new(
+                    region.clone(),
+                    table_name.clone(),
+                    credentials.clone(),
+                )
+                .with_local_db(self.db.clone());
+                Ok(Box::new(protocol))
+            }
+            #[cfg(not(feature = "dynamodb"))]
             SyncEndpoint::DynamoDB { .. } => {
-                #[cfg(feature = "dynamodb")]
-                {
-                    // Would create DynamoDB protocol
-                    Ok(Box::new(crate::protocol::MockSyncProtocol::new()))
-                }
-                #[cfg(not(feature = "dynamodb"))]
-                {
-                    Err(anyhow::anyhow!("DynamoDB support not enabled"))
-                }
+                Err(anyhow::anyhow!("DynamoDB support not enabled"))
             }
             SyncEndpoint::FileSystem { path } => {
                 // Use filesystem protocol for local database sync
@@ -690,16 +960,19 @@ impl SyncEngine {
 
     async fn create_protocol(&self) -> Result<Box<dyn SyncProtocol>> {
         match &self.config.endpoint {
+            #[cfg(feature = "dynamodb")]
+            SyncEndpoint::DynamoDB { region, table_name, credentials } => {
+                let protocol = crate::protocol::dynamodb::DynamoDBProtocol::new(
+                    region.clone(),
+                    table_name.clone(),
+                    credentials.clone(),
+                )
+                .with_local_db(self.db.clone());
+                Ok(Box::new(protocol))
+            }
+            #[cfg(not(feature = "dynamodb"))]
             SyncEndpoint::DynamoDB { .. } => {
-                #[cfg(feature = "dynamodb")]
-                {
-                    // Would create DynamoDB protocol
-                    Ok(Box::new(crate::protocol::MockSyncProtocol::new()))
-                }
-                #[cfg(not(feature = "dynamodb"))]
-                {
-                    Err(anyhow::anyhow!("DynamoDB support not enabled"))
-                }
+                Err(anyhow::anyhow!("DynamoDB support not enabled"))
             }
             SyncEndpoint::FileSystem { path } => {
                 // Use filesystem protocol for local database sync
@@ -734,6 +1007,54 @@ impl SyncEngine {
         }
     }
 
+    /// Add a finished run to the sync history and notify about it
+    ///
+    /// Failing to record it, or to deliver a notification, is logged rather
+    /// than failing the run.
+    async fn finish_run<T>(
+        &self,
+        endpoint_id: EndpointId,
+        direction: SyncDirection,
+        started_at: i64,
+        stats: &SyncSessionStats,
+        result: &Result<T>,
+    ) {
+        let mut run = SyncRunRecord::new(endpoint_id, direction, started_at);
+        run.items_sent = stats.items_sent;
+        run.items_received = stats.items_received;
+        run.bytes_sent = stats.bytes_sent;
+        run.bytes_received = stats.bytes_received;
+        run.conflicts = stats.conflicts_detected;
+        run.error = result.as_ref().err().map(|e| format!("{:#}", e));
+
+        if !run.succeeded() {
+            let mut metadata = self.metadata.write();
+            metadata.stats.total_syncs += 1;
+            metadata.stats.failed_syncs += 1;
+            if let Err(e) = self.metadata_store.save_metadata(&metadata) {
+                tracing::warn!("Failed to save sync statistics: {}", e);
+            }
+        }
+
+        if let Err(e) = self.metadata_store.record_sync_run(&run) {
+            tracing::warn!("Failed to record sync run: {}", e);
+        }
+
+        self.notifications.run_finished(&run);
+        self.notifications.flush().await;
+    }
+
+    /// Move to the error state and tell subscribers if a sync failed
+    fn report_failure<T>(&self, result: Result<T>) -> Result<T> {
+        if let Err(e) = &result {
+            self.set_state(SyncState::Error(e.to_string()));
+            self.emit_event(SyncEvent::Failed {
+                error: e.to_string(),
+            });
+        }
+        result
+    }
+
     /// Set the current state
     fn set_state(&self, new_state: SyncState) {
         let old_state = {
@@ -764,15 +1085,60 @@ impl SyncEngine {
         self.metadata.read().stats.clone()
     }
 
-    /// Subscribe to sync events
-    pub fn subscribe(&mut self) -> Option<mpsc::UnboundedReceiver<SyncEvent>> {
-        self.event_rx.take()
+    /// Subscribe to sync events: phase changes, transfer progress,
+    /// conflicts, and each sync's completion or failure
+    ///
+    /// Any number of subscribers may listen, including to the syncs `start`
+    /// runs in the background. A subscriber more than `EVENT_CHANNEL_CAPACITY`
+    /// events behind misses the oldest (`RecvError::Lagged`).
+    pub fn subscribe(&self) -> broadcast::Receiver<SyncEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Notify `notifier` of completed syncs, conflicts and repeated failures
+    pub fn add_notifier(&self, notifier: Arc<dyn SyncNotifier>) {
+        self.notifications.add(notifier);
+    }
+
+    /// Call `callback` with each notification (see `add_notifier`)
+    pub fn on_notification<F>(&self, callback: F)
+    where
+        F: Fn(&SyncNotification) + Send + Sync + 'static,
+    {
+        self.notifications.add(notify::callback(callback));
+    }
+
+    /// Send `RepeatedFailure` after this many failed runs in a row
+    /// (default `notify::DEFAULT_FAILURE_THRESHOLD`)
+    pub fn set_failure_alert_threshold(&self, failures: u32) {
+        self.notifications.set_failure_threshold(failures);
+    }
+
+    /// Track a local write: queue it for sync and update the merkle tree
+    ///
+    /// Feed every stream record of the database through here (see
+    /// `Database::subscribe_stream`) to keep the persisted tree current.
+    pub fn track_local_change(&self, record: StreamRecord) -> Result<SyncRecord> {
+        let item = match (&record.event_type, &record.new_image) {
+            (StreamEventType::Remove, _) => None,
+            (_, Some(item)) => Some(item.clone()),
+            // Keys-only streams don't carry the item written
+            (_, None) => match &record.key.sk {
+                Some(sk) => self.db.get_with_sk(&record.key.pk, sk)?,
+                None => self.db.get(&record.key.pk)?,
+            },
+        };
+        self.merkle.update(&record.key, item.as_ref())?;
+        self.change_tracker.track_local_change(record)
+    }
+
+    /// The persisted merkle tree of the local items
+    pub fn merkle(&self) -> &MerkleStore {
+        &self.merkle
     }
 
     /// Clone for spawning tasks
     fn clone_for_task(&self) -> Self {
-        let (event_tx, _) = mpsc::unbounded_channel();
-
         Self {
             db: self.db.clone(),
             config: self.config.clone(),
@@ -781,14 +1147,35 @@ impl SyncEngine {
             conflict_manager: self.conflict_manager.clone(),
             offline_queue: self.offline_queue.clone(),
             metadata_store: self.metadata_store.clone(),
+            inbox: self.inbox.clone(),
+            merkle: self.merkle.clone(),
             metadata: self.metadata.clone(),
-            event_tx,
-            event_rx: None,
+            scope: self.scope.clone(),
+            event_tx: self.event_tx.clone(),
+            notifications: self.notifications.clone(),
             shutdown_tx: None,
         }
     }
 }
 
+/// Endpoint ID of an S3 bucket and prefix, as `SyncEndpoint::S3` names it
+#[cfg(feature = "s3-sync")]
+fn s3_endpoint_id(bucket: &str, prefix: &str, region: &str) -> EndpointId {
+    SyncEndpoint::S3 {
+        bucket: bucket.to_string(),
+        prefix: prefix.to_string(),
+        region: region.to_string(),
+        endpoint_url: None,
+        credentials: None,
+    }
+    .endpoint_id()
+}
+
+/// Approximate bytes transferred for a change: its key plus its item
+fn change_size(key: &Key, item: Option<&Item>) -> usize {
+    key.pk.len() + key.sk.as_ref().map_or(0, |sk| sk.len()) + item.map_or(0, item_size)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -806,6 +1193,8 @@ mod tests {
             batch_size: 100,
             max_retries: 3,
             enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
         };
 
         let engine = SyncEngine::new(db, config).unwrap();
@@ -825,6 +1214,8 @@ mod tests {
             batch_size: 100,
             max_retries: 3,
             enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
         };
 
         let engine = SyncEngine::new(db, config).unwrap();
@@ -835,4 +1226,38 @@ mod tests {
         engine.set_state(SyncState::Handshaking);
         assert_eq!(engine.get_state(), SyncState::Handshaking);
     }
+
+    #[tokio::test]
+    async fn test_subscribe_to_events() {
+        let db = Arc::new(kstone_api::Database::create_in_memory().unwrap());
+
+        let config = SyncConfig {
+            endpoint: SyncEndpoint::FileSystem {
+                path: "/nonexistent/kstone-sync-test".to_string(),
+            },
+            conflict_strategy: ConflictStrategy::LastWriterWins,
+            sync_interval: None,
+            batch_size: 100,
+            max_retries: 3,
+            enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
+        };
+
+        let engine = SyncEngine::new(db, config).unwrap();
+        let mut first = engine.subscribe();
+        let mut second = engine.subscribe();
+
+        // Every subscriber sees the failed sync, ending in the error state
+        assert!(engine.sync_once().await.is_err());
+        for rx in [&mut first, &mut second] {
+            let mut events = Vec::new();
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+            assert!(matches!(events.first(), Some(SyncEvent::StateChanged { .. })));
+            assert!(matches!(events.last(), Some(SyncEvent::Failed { .. })));
+        }
+        assert!(matches!(engine.get_state(), SyncState::Error(_)));
+    }
 }
\ No newline at end of file
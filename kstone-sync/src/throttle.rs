@@ -0,0 +1,59 @@
+/// Bandwidth limiting for sync transfers (Phase 8+)
+///
+/// Transfers report the bytes of each batch they move and wait long enough
+/// that the average rate since the transfer started stays at the limit, so a
+/// large initial sync doesn't saturate the uplink.
+
+use std::time::{Duration, Instant};
+
+/// Paces a transfer to at most `bytes_per_sec` on average
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    started: Instant,
+    bytes: u64,
+}
+
+impl BandwidthLimiter {
+    /// Limit a transfer starting now; a limit of zero is treated as one byte per second
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            started: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Record `bytes` transferred and wait until the rate is back under the limit
+    pub async fn consume(&mut self, bytes: u64) {
+        let delay = self.delay(bytes, self.started.elapsed());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Record `bytes` transferred `elapsed` after the start, returning how
+    /// long to wait before transferring more
+    fn delay(&mut self, bytes: u64, elapsed: Duration) -> Duration {
+        self.bytes += bytes;
+        let allowed = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_sec as f64);
+        allowed.saturating_sub(elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bandwidth_limiter_delay() {
+        let mut limiter = BandwidthLimiter::new(1000);
+
+        // 500 bytes are allowed half a second
+        assert_eq!(limiter.delay(500, Duration::ZERO), Duration::from_millis(500));
+        // Batches slower than the limit don't wait
+        assert_eq!(limiter.delay(500, Duration::from_secs(2)), Duration::ZERO);
+        // 3000 bytes in total are allowed three seconds
+        assert_eq!(limiter.delay(2000, Duration::from_secs(2)), Duration::from_secs(1));
+    }
+}
@@ -3,7 +3,7 @@ mod s3_tests {
     use kstone_api::{Database, ItemBuilder};
     use kstone_sync::sync_engine::{SyncEngine, SyncConfig};
     use kstone_sync::protocol::s3::S3Protocol;
-    use kstone_sync::{ConflictStrategy, SyncEndpoint};
+    use kstone_sync::{ConflictStrategy, SyncEndpoint, SyncScope};
     use tempfile::TempDir;
     use std::sync::Arc;
 
@@ -40,7 +40,7 @@ mod s3_tests {
 
         // This test doesn't require actual S3 connection
         // Just tests the local file scanning functionality
-        let files = protocol.get_local_files(dir.path()).await.unwrap();
+        let files = protocol.get_local_files(&db_path).await.unwrap();
 
         // Should find wal.log and any SST files
         assert!(files.contains_key("wal.log"));
@@ -111,6 +111,8 @@ mod s3_tests {
             batch_size: 100,
             max_retries: 3,
             enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
         };
 
         let sync_engine = SyncEngine::new(db.clone(), config).unwrap();
@@ -145,6 +147,68 @@ mod s3_tests {
         assert_eq!(item.unwrap().get("data").unwrap().as_string().unwrap(), "test-value");
     }
 
+    /// Incremental snapshots against real S3 (skipped in CI)
+    #[tokio::test]
+    #[ignore] // Run with: cargo test --ignored
+    async fn test_s3_incremental_snapshots() {
+        use kstone_sync::protocol::s3::SnapshotKind;
+
+        let bucket = std::env::var("TEST_S3_BUCKET")
+            .expect("Set TEST_S3_BUCKET environment variable");
+        let prefix = format!("test-incremental-{}", uuid::Uuid::new_v4());
+
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::create(dir.path().join("test.keystone")).unwrap());
+        db.put(b"key1", ItemBuilder::new().string("data", "one").build()).unwrap();
+        db.flush().unwrap();
+
+        let config = SyncConfig {
+            endpoint: SyncEndpoint::FileSystem { path: "/tmp/dummy".to_string() },
+            conflict_strategy: ConflictStrategy::LastWriterWins,
+            sync_interval: None,
+            batch_size: 100,
+            max_retries: 3,
+            enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
+        };
+        let sync_engine = SyncEngine::new(db.clone(), config).unwrap();
+
+        let first = sync_engine
+            .upload_incremental_to_s3(bucket.clone(), prefix.clone(), "us-east-1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(first.kind, SnapshotKind::Full);
+
+        db.put(b"key2", ItemBuilder::new().string("data", "two").build()).unwrap();
+        db.flush().unwrap();
+        let second = sync_engine
+            .upload_incremental_to_s3(bucket.clone(), prefix.clone(), "us-east-1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(second.kind, SnapshotKind::Incremental);
+        assert_eq!(second.base.as_deref(), Some(first.id.as_str()));
+        assert!(second.uploaded_size < second.total_size);
+
+        // The second snapshot is the newest, and its base is kept with it
+        let stats = sync_engine
+            .prune_s3_snapshots(bucket.clone(), prefix.clone(), "us-east-1".to_string(), 1)
+            .await
+            .unwrap();
+        assert_eq!(stats.snapshots_deleted, 0);
+
+        let restore_dir = TempDir::new().unwrap();
+        let restore_path = restore_dir.path().join("restored.keystone");
+        sync_engine
+            .restore_from_s3(bucket, prefix, "us-east-1".to_string(), second.id, restore_path.clone())
+            .await
+            .unwrap();
+
+        let restored_db = Database::open(&restore_path).unwrap();
+        assert!(restored_db.get(b"key1").unwrap().is_some());
+        assert!(restored_db.get(b"key2").unwrap().is_some());
+    }
+
     /// Test with MinIO (local S3-compatible storage)
     #[tokio::test]
     #[ignore] // Run with: cargo test --ignored
@@ -1,6 +1,6 @@
 /// Edge cases and boundary condition tests for KeystoneDB
 
-use kstone_api::{Database, ItemBuilder};
+use kstone_api::{Database, DatabaseConfig, ItemBuilder};
 use kstone_core::Key;
 use tempfile::TempDir;
 
@@ -59,7 +59,8 @@ fn test_very_large_keys() {
 #[test]
 fn test_very_large_values() {
     let dir = TempDir::new().unwrap();
-    let db = Database::create(dir.path()).unwrap();
+    let config = DatabaseConfig::new().without_size_limits();
+    let db = Database::create_with_config(dir.path(), config).unwrap();
 
     // 1MB value
     let large_string = "x".repeat(1024 * 1024);
@@ -73,6 +74,22 @@ fn test_very_large_values() {
     assert_eq!(result, Some(item));
 }
 
+#[test]
+fn test_item_too_large_rejected_by_default() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+
+    // Just over the default 1MB item limit
+    let large_string = "x".repeat(1024 * 1024);
+    let item = ItemBuilder::new()
+        .string("large_data", &large_string)
+        .build();
+
+    let err = db.put(b"large_value", item).unwrap_err();
+    assert_eq!(err.code(), "ITEM_TOO_LARGE");
+    assert!(db.get(b"large_value").unwrap().is_none());
+}
+
 #[test]
 fn test_empty_string_key() {
     let dir = TempDir::new().unwrap();
@@ -1,4 +1,4 @@
-use kstone_core::{Error, Result, retry::{RetryPolicy, retry_with_policy}};
+use kstone_core::{Error, Result, ValidationError, retry::{RetryPolicy, retry_with_policy}};
 use std::sync::{Arc, Mutex};
 use std::io;
 
@@ -337,6 +337,11 @@ fn test_error_code_stability() {
         ("TRANSACTION_CANCELED", Error::TransactionCanceled("test".into())),
         ("INVALID_QUERY", Error::InvalidQuery("test".into())),
         ("RESOURCE_EXHAUSTED", Error::ResourceExhausted("test".into())),
+        ("DATABASE_LOCKED", Error::DatabaseLocked { path: "test".into(), pid: Some(1) }),
+        ("VERSION_CONFLICT", Error::VersionConflict { expected: Some(1), actual: Some(2) }),
+        ("ITEM_TOO_LARGE", Error::ItemTooLarge { size: 2, limit: 1 }),
+        ("DEADLINE_EXCEEDED", Error::DeadlineExceeded("test".into())),
+        ("VALIDATION_FAILED", Error::ValidationFailed(ValidationError { violations: vec![] })),
     ];
 
     for (expected_code, error) in expected_codes {
@@ -359,6 +364,7 @@ fn test_retryable_error_coverage() {
         Error::ResourceExhausted("test".into()),
         Error::CompactionError("test".into()),
         Error::StripeError("test".into()),
+        Error::DeadlineExceeded("test".into()),
     ];
 
     for error in retryable {
@@ -384,6 +390,10 @@ fn test_retryable_error_coverage() {
         Error::ConditionalCheckFailed("test".into()),
         Error::TransactionCanceled("test".into()),
         Error::InvalidQuery("test".into()),
+        Error::DatabaseLocked { path: "test".into(), pid: None },
+        Error::VersionConflict { expected: None, actual: Some(1) },
+        Error::ItemTooLarge { size: 2, limit: 1 },
+        Error::ValidationFailed(ValidationError { violations: vec![] }),
     ];
 
     for error in non_retryable {
@@ -30,8 +30,62 @@ fn test_database_stats() {
     // Flush to create SST
     db.flush().unwrap();
 
-    // Stats after flush - would show SST if we tracked it
-    let _stats = db.stats().unwrap();
+    // Stats after flush - keys spread over many stripes, one SST each
+    let stats = db.stats().unwrap();
+    assert!(stats.total_sst_files > 0);
+    assert_eq!(stats.memtable_size_bytes, Some(0));
+}
+
+#[test]
+fn test_stats_detailed() {
+    let dir = TempDir::new().unwrap();
+    let db = Database::create(dir.path()).unwrap();
+
+    for i in 0..100 {
+        let item = ItemBuilder::new().number("value", i).build();
+        db.put(format!("key{}", i).as_bytes(), item).unwrap();
+    }
+    db.flush().unwrap();
+    db.put(b"late", ItemBuilder::new().number("value", 0).build()).unwrap();
+
+    for i in 0..200 {
+        let found = db.get(format!("key{}", i).as_bytes()).unwrap().is_some();
+        assert_eq!(found, i < 100);
+    }
+
+    let detailed = db.stats_detailed().unwrap();
+    assert_eq!(detailed.stripes.len(), 256);
+
+    let sst_count: usize = detailed.stripes.iter().map(|stripe| stripe.sst_count()).sum();
+    assert_eq!(sst_count as u64, detailed.stats.total_sst_files);
+
+    let estimated_keys: u64 = detailed.stripes.iter().map(|stripe| stripe.estimated_keys).sum();
+    assert_eq!(estimated_keys, 101);
+
+    for stripe in detailed.non_empty_stripes() {
+        for sst in &stripe.ssts {
+            assert!(sst.size_bytes > 0);
+            assert!(sst.records > 0);
+            assert!(sst.age.is_some());
+            assert!(!sst.cold);
+        }
+    }
+
+    // Every present key was checked against its SST's bloom filter
+    assert!(detailed.bloom().checks >= 100);
+
+    let operations = &detailed.stats.operations;
+    assert_eq!((operations.reads, operations.writes), (200, 101));
+    assert_eq!(operations.last_sequence_number, 101);
+
+    // Only one record is left in memtables
+    let config = db.describe().unwrap().config.unwrap();
+    let fill = detailed.memtable_fill(&config);
+    assert!(fill > 0.0 && fill < 0.01);
+
+    // In-memory databases have no stripes to report
+    let memory = Database::create_in_memory().unwrap();
+    assert!(memory.stats_detailed().unwrap().stripes.is_empty());
 }
 
 #[test]
@@ -191,3 +245,37 @@ fn test_compaction_stats() {
     // Stats should still be valid
     let _stats = db.stats().unwrap();
 }
+
+#[test]
+fn test_slow_queries() {
+    use kstone_api::{DatabaseConfig, Query, SlowOperationKind};
+    use std::time::Duration;
+
+    let dir = TempDir::new().unwrap();
+    let config = DatabaseConfig::new().with_slow_operation_threshold(Duration::ZERO);
+    let db = Database::create_with_config(dir.path(), config).unwrap();
+
+    for i in 0..10 {
+        let item = ItemBuilder::new().number("value", i).build();
+        db.put_with_sk(b"user#1", format!("order#{}", i).as_bytes(), item).unwrap();
+    }
+    db.query(Query::new(b"user#1").limit(3)).unwrap();
+
+    let slow = db.slow_queries();
+    assert_eq!(slow.len(), 11);
+    assert!(slow[..10].iter().all(|op| op.kind == SlowOperationKind::Put));
+
+    let query = &slow[10];
+    assert_eq!(query.kind, SlowOperationKind::Query);
+    assert_eq!(query.key.as_ref().unwrap().pk.as_ref(), b"user#1");
+    assert_eq!(query.returned_count, 3);
+    assert!(query.scanned_count >= 3);
+
+    // A threshold nothing reaches stops new entries
+    db.set_slow_operation_threshold(Some(Duration::from_secs(3600))).unwrap();
+    db.get(b"user#1").unwrap();
+    assert_eq!(db.slow_queries().len(), 11);
+
+    db.clear_slow_queries();
+    assert!(db.slow_queries().is_empty());
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod sync_tests {
     use kstone_api::{Database, ItemBuilder};
-    use kstone_sync::{SyncEngine, SyncConfig, ConflictStrategy, SyncEndpoint};
+    use kstone_sync::{SyncEngine, SyncConfig, ConflictStrategy, SyncEndpoint, SyncScope};
     use tempfile::TempDir;
     use std::time::Duration;
 
@@ -35,6 +35,9 @@ mod sync_tests {
         eprintln!("DB2 has user1: {:?}", db2.get(b"user1").unwrap().is_some());
         eprintln!("DB2 has user2: {:?}", db2.get(b"user2").unwrap().is_some());
 
+        // FilesystemProtocol opens DB2 itself, which needs its lock
+        drop(db2);
+
         // Configure sync from DB1 to DB2
         let dummy_endpoint = SyncEndpoint::FileSystem {
             path: "dummy.keystone".to_string(),
@@ -47,6 +50,8 @@ mod sync_tests {
             batch_size: 100,
             max_retries: 3,
             enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
         };
 
         let sync_engine = SyncEngine::new(db1.clone(), config).unwrap();
@@ -78,7 +83,6 @@ mod sync_tests {
 
         // Flush to ensure writes are persisted
         db1.flush().unwrap();
-        db2.flush().unwrap();
 
         // Re-open DB2 since FilesystemProtocol opened its own instance
         let db2_reopened = std::sync::Arc::new(Database::open(&db2_path).unwrap());
@@ -142,6 +146,9 @@ mod sync_tests {
         eprintln!("DB1 user#shared location: New York");
         eprintln!("DB2 user#shared location: San Francisco");
 
+        // FilesystemProtocol opens DB2 itself, which needs its lock
+        drop(db2);
+
         // Configure sync with LastWriterWins strategy
         let dummy_endpoint = SyncEndpoint::FileSystem {
             path: "dummy.keystone".to_string(),
@@ -154,6 +161,8 @@ mod sync_tests {
             batch_size: 100,
             max_retries: 3,
             enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
         };
 
         let sync_engine = SyncEngine::new(db1.clone(), config).unwrap();
@@ -180,6 +189,7 @@ mod sync_tests {
 
         // With LocalWins strategy, DB1's version should win
         let db1_item = db1.get(b"user#shared").unwrap().expect("Item should exist in DB1");
+        let db2 = Database::open(&db2_path).unwrap();
         let db2_item = db2.get(b"user#shared").unwrap().expect("Item should exist in DB2");
 
         eprintln!("\n=== After sync ===");
@@ -194,4 +204,157 @@ mod sync_tests {
 
         eprintln!("\n✓ Conflict resolution test passed!");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_sync_with_prefix_scope() {
+        let dir1 = TempDir::new().unwrap();
+        let dir2 = TempDir::new().unwrap();
+        let db1_path = dir1.path().join("test1.keystone");
+        let db2_path = dir2.path().join("test2.keystone");
+
+        let db1 = std::sync::Arc::new(Database::create(&db1_path).unwrap());
+        let db2 = Database::create(&db2_path).unwrap();
+        let item = |name: &str| ItemBuilder::new().string("name", name).build();
+        db1.put(b"tenant#a#1", item("a1")).unwrap();
+        db1.put(b"tenant#b#1", item("b1")).unwrap();
+        db2.put(b"tenant#a#2", item("a2")).unwrap();
+        db2.put(b"tenant#b#2", item("b2")).unwrap();
+        drop(db2);
+
+        let endpoint = SyncEndpoint::FileSystem {
+            path: db2_path.to_string_lossy().to_string(),
+        };
+        let config = SyncConfig {
+            endpoint: endpoint.clone(),
+            conflict_strategy: ConflictStrategy::LastWriterWins,
+            sync_interval: None,
+            batch_size: 100,
+            max_retries: 3,
+            enable_compression: false,
+            scope: SyncScope::all().with_include_prefix("tenant#a#"),
+            max_bytes_per_sec: None,
+        };
+
+        let sync_engine = SyncEngine::new(db1.clone(), config).unwrap();
+        sync_engine.sync(endpoint).await.unwrap();
+
+        // Only tenant a's items moved, in both directions
+        assert!(db1.get(b"tenant#a#2").unwrap().is_some());
+        assert!(db1.get(b"tenant#b#2").unwrap().is_none());
+        let db2 = Database::open(&db2_path).unwrap();
+        assert!(db2.get(b"tenant#a#1").unwrap().is_some());
+        assert!(db2.get(b"tenant#b#1").unwrap().is_none());
+
+        // The scope is recorded in the sync metadata
+        let metadata = kstone_sync::SyncMetadataStore::new(db1.clone())
+            .load_metadata()
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.config.scope, SyncScope::all().with_include_prefix("tenant#a#"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_resumes_interrupted_transfer() {
+        use kstone_core::Key;
+        use kstone_sync::{protocol::DiffType, SyncMetadataStore, TransferCheckpoint};
+
+        let dir1 = TempDir::new().unwrap();
+        let dir2 = TempDir::new().unwrap();
+        let db1_path = dir1.path().join("local.keystone");
+        let db2_path = dir2.path().join("remote.keystone");
+
+        let db1 = std::sync::Arc::new(Database::create(&db1_path).unwrap());
+        let db2 = Database::create(&db2_path).unwrap();
+        db2.put(b"user#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        db2.put(b"user#2", ItemBuilder::new().string("name", "Bob").build()).unwrap();
+        drop(db2);
+
+        let endpoint = SyncEndpoint::FileSystem {
+            path: db2_path.to_string_lossy().to_string(),
+        };
+        let config = SyncConfig {
+            endpoint: endpoint.clone(),
+            conflict_strategy: ConflictStrategy::LastWriterWins,
+            sync_interval: None,
+            batch_size: 1,
+            max_retries: 3,
+            enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: Some(1024 * 1024),
+        };
+
+        // A transfer that was interrupted after pulling user#1
+        let store = SyncMetadataStore::new(db1.clone());
+        let mut checkpoint = TransferCheckpoint::new(
+            endpoint.endpoint_id(),
+            vec![
+                (Key::new(b"user#1".to_vec()), DiffType::RemoteOnly),
+                (Key::new(b"user#2".to_vec()), DiffType::RemoteOnly),
+            ],
+        );
+        store.start_transfer(&checkpoint).unwrap();
+        checkpoint.completed = 1;
+        store.save_transfer_progress(&checkpoint).unwrap();
+
+        let sync_engine = SyncEngine::new(db1.clone(), config).unwrap();
+        let stats = sync_engine.sync(endpoint.clone()).await.unwrap();
+
+        // Only the rest of the transfer ran, and the checkpoint is gone
+        assert_eq!(stats.items_received, 1);
+        assert!(db1.get(b"user#1").unwrap().is_none());
+        assert!(db1.get(b"user#2").unwrap().is_some());
+        assert!(store.load_transfer_checkpoint(&endpoint.endpoint_id()).unwrap().is_none());
+
+        // The next sync discovers what the checkpoint left out
+        sync_engine.sync(endpoint).await.unwrap();
+        assert!(db1.get(b"user#1").unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sync_history_records_runs() {
+        use kstone_sync::{SyncDirection, SyncMetadataStore};
+
+        let dir1 = TempDir::new().unwrap();
+        let dir2 = TempDir::new().unwrap();
+        let db1_path = dir1.path().join("local.keystone");
+        let db2_path = dir2.path().join("remote.keystone");
+
+        let db1 = std::sync::Arc::new(Database::create(&db1_path).unwrap());
+        db1.put(b"user#1", ItemBuilder::new().string("name", "Alice").build()).unwrap();
+        drop(Database::create(&db2_path).unwrap());
+
+        let endpoint = SyncEndpoint::FileSystem {
+            path: db2_path.to_string_lossy().to_string(),
+        };
+        let config = SyncConfig {
+            endpoint: endpoint.clone(),
+            conflict_strategy: ConflictStrategy::LastWriterWins,
+            sync_interval: None,
+            batch_size: 100,
+            max_retries: 3,
+            enable_compression: false,
+            scope: SyncScope::default(),
+            max_bytes_per_sec: None,
+        };
+        let sync_engine = SyncEngine::new(db1.clone(), config).unwrap();
+
+        sync_engine.sync(endpoint.clone()).await.unwrap();
+        let missing = SyncEndpoint::FileSystem {
+            path: dir2.path().join("missing.keystone").to_string_lossy().to_string(),
+        };
+        assert!(sync_engine.sync(missing.clone()).await.is_err());
+
+        // Newest first, with the failure's error
+        let runs = SyncMetadataStore::new(db1.clone()).load_sync_history(10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].endpoint_id, missing.endpoint_id());
+        assert!(runs[0].error.is_some());
+        assert_eq!(runs[1].endpoint_id, endpoint.endpoint_id());
+        assert_eq!(runs[1].direction, SyncDirection::Bidirectional);
+        assert!(runs[1].succeeded());
+        assert!(runs[1].items_sent >= 1);
+        assert!(runs[1].bytes_sent > 0);
+
+        assert_eq!(sync_engine.get_stats().failed_syncs, 1);
+    }
+}